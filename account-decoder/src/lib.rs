@@ -9,6 +9,7 @@ pub mod parse_bpf_loader;
 pub mod parse_config;
 pub mod parse_nonce;
 pub mod parse_stake;
+pub mod parse_stake_pool;
 pub mod parse_sysvar;
 pub mod parse_token;
 pub mod parse_vote;
@@ -16,6 +17,7 @@ pub mod validator_info;
 
 use {
     crate::parse_account_data::{parse_account_data, AccountAdditionalData, ParsedAccount},
+    flate2::{read::GzDecoder, write::GzEncoder, Compression},
     solana_sdk::{
         account::ReadableAccount, account::WritableAccount, clock::Epoch,
         fee_calculator::FeeCalculator, pubkey::Pubkey,
@@ -58,6 +60,29 @@ pub enum UiAccountEncoding {
     JsonParsed,
     #[serde(rename = "base64+zstd")]
     Base64Zstd,
+    #[serde(rename = "base64+gzip")]
+    Base64Gzip,
+}
+
+/// Compression of the account data, requested alongside the base64 encoding
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum UiAccountCompression {
+    Zstd,
+    Gzip,
+}
+
+impl UiAccountCompression {
+    /// All the compressions supported, as advertised by `getVersion`
+    pub const ALL: [Self; 2] = [Self::Zstd, Self::Gzip];
+
+    /// The base64 encoding of the data compressed this way
+    pub fn encoding(self) -> UiAccountEncoding {
+        match self {
+            Self::Zstd => UiAccountEncoding::Base64Zstd,
+            Self::Gzip => UiAccountEncoding::Base64Gzip,
+        }
+    }
 }
 
 impl UiAccount {
@@ -105,6 +130,19 @@ impl UiAccount {
                     ),
                 }
             }
+            UiAccountEncoding::Base64Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                match encoder
+                    .write_all(slice_data(account.data(), data_slice_config))
+                    .and_then(|()| encoder.finish())
+                {
+                    Ok(gzip_data) => UiAccountData::Binary(base64::encode(gzip_data), encoding),
+                    Err(_) => UiAccountData::Binary(
+                        base64::encode(slice_data(account.data(), data_slice_config)),
+                        UiAccountEncoding::Base64,
+                    ),
+                }
+            }
             UiAccountEncoding::JsonParsed => {
                 if let Ok(parsed_data) =
                     parse_account_data(pubkey, account.owner(), account.data(), additional_data)
@@ -144,6 +182,16 @@ impl UiAccount {
                             .ok()
                     })
                     .flatten(),
+                UiAccountEncoding::Base64Gzip => base64::decode(blob)
+                    .ok()
+                    .map(|gzip_data| {
+                        let mut data = vec![];
+                        GzDecoder::new(gzip_data.as_slice())
+                            .read_to_end(&mut data)
+                            .map(|_| data)
+                            .ok()
+                    })
+                    .flatten(),
                 UiAccountEncoding::Binary | UiAccountEncoding::JsonParsed => None,
             },
         }?;
@@ -255,4 +303,48 @@ mod test {
         let decoded_account = encoded_account.decode::<AccountSharedData>().unwrap();
         assert_eq!(decoded_account.data(), &vec![0; 1024]);
     }
+
+    #[test]
+    fn test_base64_gzip() {
+        let data: Vec<u8> = (0..1024).map(|i| (i % 7) as u8).collect();
+        let account = AccountSharedData::from(Account {
+            data: data.clone(),
+            ..Account::default()
+        });
+        let encoded_account = UiAccount::encode(
+            &Pubkey::default(),
+            &account,
+            UiAccountCompression::Gzip.encoding(),
+            None,
+            None,
+        );
+        match &encoded_account.data {
+            UiAccountData::Binary(blob, UiAccountEncoding::Base64Gzip) => {
+                assert!(blob.len() < base64::encode(&data).len());
+            }
+            data => panic!("unexpected data {:?}", data),
+        }
+
+        let decoded_account = encoded_account.decode::<AccountSharedData>().unwrap();
+        assert_eq!(decoded_account.data(), &data[..]);
+
+        // The data slice is compressed
+        let encoded_account = UiAccount::encode(
+            &Pubkey::default(),
+            &account,
+            UiAccountEncoding::Base64Gzip,
+            None,
+            Some(UiDataSliceConfig {
+                offset: 10,
+                length: 20,
+            }),
+        );
+        let decoded_account = encoded_account.decode::<Account>().unwrap();
+        assert_eq!(decoded_account.data(), &data[10..30]);
+
+        assert_eq!(
+            serde_json::to_string(&UiAccountCompression::ALL).unwrap(),
+            r#"["zstd","gzip"]"#
+        );
+    }
 }
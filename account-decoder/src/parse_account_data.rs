@@ -3,14 +3,18 @@ use crate::{
     parse_config::parse_config,
     parse_nonce::parse_nonce,
     parse_stake::parse_stake,
+    parse_stake_pool::{parse_stake_pool, spl_stake_pool_id},
     parse_sysvar::parse_sysvar,
-    parse_token::{parse_token, spl_token_id_v2_0},
+    parse_token::{parse_token, parse_token_2022, spl_token_2022_id, spl_token_id_v2_0},
     parse_vote::parse_vote,
 };
 use inflector::Inflector;
 use serde_json::Value;
 use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, stake, system_program, sysvar};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 use thiserror::Error;
 
 lazy_static! {
@@ -19,7 +23,9 @@ lazy_static! {
     static ref STAKE_PROGRAM_ID: Pubkey = stake::program::id();
     static ref SYSTEM_PROGRAM_ID: Pubkey = system_program::id();
     static ref SYSVAR_PROGRAM_ID: Pubkey = sysvar::id();
+    static ref STAKE_POOL_PROGRAM_ID: Pubkey = spl_stake_pool_id();
     static ref TOKEN_PROGRAM_ID: Pubkey = spl_token_id_v2_0();
+    static ref TOKEN_2022_PROGRAM_ID: Pubkey = spl_token_2022_id();
     static ref VOTE_PROGRAM_ID: Pubkey = solana_vote_program::id();
     pub static ref PARSABLE_PROGRAM_IDS: HashMap<Pubkey, ParsableAccount> = {
         let mut m = HashMap::new();
@@ -30,11 +36,64 @@ lazy_static! {
         m.insert(*CONFIG_PROGRAM_ID, ParsableAccount::Config);
         m.insert(*SYSTEM_PROGRAM_ID, ParsableAccount::Nonce);
         m.insert(*TOKEN_PROGRAM_ID, ParsableAccount::SplToken);
+        m.insert(*TOKEN_2022_PROGRAM_ID, ParsableAccount::SplToken2022);
+        m.insert(*STAKE_POOL_PROGRAM_ID, ParsableAccount::SplStakePool);
         m.insert(*STAKE_PROGRAM_ID, ParsableAccount::Stake);
         m.insert(*SYSVAR_PROGRAM_ID, ParsableAccount::Sysvar);
         m.insert(*VOTE_PROGRAM_ID, ParsableAccount::Vote);
         m
     };
+    static ref REGISTERED_ACCOUNT_PARSERS: RwLock<HashMap<Pubkey, Arc<dyn AccountParser>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Parser of the accounts owned by a program the crate doesn't know about, registered at runtime
+/// with `register_account_parser`
+pub trait AccountParser: Send + Sync {
+    /// Program owning the accounts parsed
+    fn program_id(&self) -> Pubkey;
+
+    /// Name reported as the `program` of the parsed accounts
+    fn program_name(&self) -> &str;
+
+    fn parse(
+        &self,
+        pubkey: &Pubkey,
+        data: &[u8],
+        additional_data: &AccountAdditionalData,
+    ) -> Result<Value, ParseAccountError>;
+}
+
+/// Registers `parser` for the accounts owned by its program, replacing the parser previously
+/// registered for it. The programs parsed by the crate itself can't be overridden.
+pub fn register_account_parser(parser: Arc<dyn AccountParser>) -> Result<(), ParseAccountError> {
+    let program_id = parser.program_id();
+    if PARSABLE_PROGRAM_IDS.contains_key(&program_id) {
+        return Err(ParseAccountError::ProgramAlreadyParsable(program_id));
+    }
+    REGISTERED_ACCOUNT_PARSERS
+        .write()
+        .unwrap()
+        .insert(program_id, parser);
+    Ok(())
+}
+
+/// Removes the parser registered for `program_id`, returning whether there was one
+pub fn unregister_account_parser(program_id: &Pubkey) -> bool {
+    REGISTERED_ACCOUNT_PARSERS
+        .write()
+        .unwrap()
+        .remove(program_id)
+        .is_some()
+}
+
+/// Whether the accounts owned by `program_id` can be parsed, by the crate or a registered parser
+pub fn is_parsable_program(program_id: &Pubkey) -> bool {
+    PARSABLE_PROGRAM_IDS.contains_key(program_id)
+        || REGISTERED_ACCOUNT_PARSERS
+            .read()
+            .unwrap()
+            .contains_key(program_id)
 }
 
 #[derive(Error, Debug)]
@@ -45,6 +104,9 @@ pub enum ParseAccountError {
     #[error("Program not parsable")]
     ProgramNotParsable,
 
+    #[error("Program {0} is already parsable")]
+    ProgramAlreadyParsable(Pubkey),
+
     #[error("Additional data required to parse: {0}")]
     AdditionalDataMissing(String),
 
@@ -69,10 +131,14 @@ pub enum ParsableAccount {
     BpfUpgradeableLoader,
     Config,
     Nonce,
+    SplStakePool,
     SplToken,
+    SplToken2022,
     Stake,
     Sysvar,
     Vote,
+    /// Owned by a program parsed by a registered `AccountParser`
+    Registered,
 }
 
 #[derive(Default)]
@@ -86,22 +152,39 @@ pub fn parse_account_data(
     data: &[u8],
     additional_data: Option<AccountAdditionalData>,
 ) -> Result<ParsedAccount, ParseAccountError> {
+    let additional_data = additional_data.unwrap_or_default();
     let program_name = PARSABLE_PROGRAM_IDS
         .get(program_id)
-        .ok_or(ParseAccountError::ProgramNotParsable)?;
-    let additional_data = additional_data.unwrap_or_default();
+        .unwrap_or(&ParsableAccount::Registered);
     let parsed_json = match program_name {
         ParsableAccount::BpfUpgradeableLoader => {
             serde_json::to_value(parse_bpf_upgradeable_loader(data)?)?
         }
         ParsableAccount::Config => serde_json::to_value(parse_config(data, pubkey)?)?,
         ParsableAccount::Nonce => serde_json::to_value(parse_nonce(data)?)?,
+        ParsableAccount::SplStakePool => serde_json::to_value(parse_stake_pool(data)?)?,
         ParsableAccount::SplToken => {
             serde_json::to_value(parse_token(data, additional_data.spl_token_decimals)?)?
         }
+        ParsableAccount::SplToken2022 => {
+            serde_json::to_value(parse_token_2022(data, additional_data.spl_token_decimals)?)?
+        }
         ParsableAccount::Stake => serde_json::to_value(parse_stake(data)?)?,
         ParsableAccount::Sysvar => serde_json::to_value(parse_sysvar(data, pubkey)?)?,
         ParsableAccount::Vote => serde_json::to_value(parse_vote(data)?)?,
+        ParsableAccount::Registered => {
+            let parser = REGISTERED_ACCOUNT_PARSERS
+                .read()
+                .unwrap()
+                .get(program_id)
+                .cloned()
+                .ok_or(ParseAccountError::ProgramNotParsable)?;
+            return Ok(ParsedAccount {
+                program: parser.program_name().to_string(),
+                parsed: parser.parse(pubkey, data, &additional_data)?,
+                space: data.len() as u64,
+            });
+        }
     };
     Ok(ParsedAccount {
         program: format!("{:?}", program_name).to_kebab_case(),
@@ -152,4 +235,60 @@ mod test {
         assert_eq!(parsed.program, "nonce".to_string());
         assert_eq!(parsed.space, State::size() as u64);
     }
+
+    struct TestParser(Pubkey);
+
+    impl AccountParser for TestParser {
+        fn program_id(&self) -> Pubkey {
+            self.0
+        }
+
+        fn program_name(&self) -> &str {
+            "test-program"
+        }
+
+        fn parse(
+            &self,
+            _pubkey: &Pubkey,
+            data: &[u8],
+            _additional_data: &AccountAdditionalData,
+        ) -> Result<Value, ParseAccountError> {
+            if data.is_empty() {
+                return Err(ParseAccountError::AccountNotParsable(
+                    ParsableAccount::Registered,
+                ));
+            }
+            Ok(serde_json::json!({ "firstByte": data[0] }))
+        }
+    }
+
+    #[test]
+    fn test_register_account_parser() {
+        let account_pubkey = solana_sdk::pubkey::new_rand();
+        let program_id = solana_sdk::pubkey::new_rand();
+        assert!(!is_parsable_program(&program_id));
+        assert!(parse_account_data(&account_pubkey, &program_id, &[7], None).is_err());
+
+        register_account_parser(Arc::new(TestParser(program_id))).unwrap();
+        assert!(is_parsable_program(&program_id));
+        let parsed = parse_account_data(&account_pubkey, &program_id, &[7, 0], None).unwrap();
+        assert_eq!(
+            parsed,
+            ParsedAccount {
+                program: "test-program".to_string(),
+                parsed: serde_json::json!({ "firstByte": 7 }),
+                space: 2,
+            }
+        );
+        assert!(parse_account_data(&account_pubkey, &program_id, &[], None).is_err());
+
+        assert!(unregister_account_parser(&program_id));
+        assert!(!unregister_account_parser(&program_id));
+        assert!(parse_account_data(&account_pubkey, &program_id, &[7], None).is_err());
+
+        assert!(matches!(
+            register_account_parser(Arc::new(TestParser(solana_vote_program::id()))),
+            Err(ParseAccountError::ProgramAlreadyParsable(_))
+        ));
+    }
 }
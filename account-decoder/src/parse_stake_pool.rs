@@ -0,0 +1,356 @@
+use crate::{
+    parse_account_data::{ParsableAccount, ParseAccountError},
+    StringAmount,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    clock::{Epoch, UnixTimestamp},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+// The spl-stake-pool program id
+pub fn spl_stake_pool_id() -> Pubkey {
+    Pubkey::from_str("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy").unwrap()
+}
+
+// Discriminants of spl_stake_pool::state::AccountType, the first byte of every pool account
+const ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+const ACCOUNT_TYPE_VALIDATOR_LIST: u8 = 2;
+
+pub fn parse_stake_pool(data: &[u8]) -> Result<StakePoolAccountType, ParseAccountError> {
+    let not_parsable = || ParseAccountError::AccountNotParsable(ParsableAccount::SplStakePool);
+    // Pool accounts are allocated larger than their contents, so trailing bytes are expected
+    let mut data = data;
+    match data.first() {
+        Some(&ACCOUNT_TYPE_STAKE_POOL) => {
+            let stake_pool = StakePool::deserialize(&mut data).map_err(|_| not_parsable())?;
+            Ok(StakePoolAccountType::StakePool(stake_pool.into()))
+        }
+        Some(&ACCOUNT_TYPE_VALIDATOR_LIST) => {
+            let validator_list =
+                ValidatorList::deserialize(&mut data).map_err(|_| not_parsable())?;
+            Ok(StakePoolAccountType::ValidatorList(validator_list.into()))
+        }
+        _ => Err(not_parsable()),
+    }
+}
+
+// Mirrors of the spl-stake-pool account layouts, to avoid depending on the program crate
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+struct Fee {
+    denominator: u64,
+    numerator: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+struct Lockup {
+    unix_timestamp: UnixTimestamp,
+    epoch: Epoch,
+    custodian: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+struct StakePool {
+    account_type: u8,
+    manager: Pubkey,
+    staker: Pubkey,
+    stake_deposit_authority: Pubkey,
+    stake_withdraw_bump_seed: u8,
+    validator_list: Pubkey,
+    reserve_stake: Pubkey,
+    pool_mint: Pubkey,
+    manager_fee_account: Pubkey,
+    token_program_id: Pubkey,
+    total_lamports: u64,
+    pool_token_supply: u64,
+    last_update_epoch: Epoch,
+    lockup: Lockup,
+    epoch_fee: Fee,
+    next_epoch_fee: Option<Fee>,
+    preferred_deposit_validator_vote_address: Option<Pubkey>,
+    preferred_withdraw_validator_vote_address: Option<Pubkey>,
+    stake_deposit_fee: Fee,
+    stake_withdrawal_fee: Fee,
+    next_stake_withdrawal_fee: Option<Fee>,
+    stake_referral_fee: u8,
+    sol_deposit_authority: Option<Pubkey>,
+    sol_deposit_fee: Fee,
+    sol_referral_fee: u8,
+    sol_withdraw_authority: Option<Pubkey>,
+    sol_withdrawal_fee: Fee,
+    next_sol_withdrawal_fee: Option<Fee>,
+    last_epoch_pool_token_supply: u64,
+    last_epoch_total_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+struct ValidatorStakeInfo {
+    active_stake_lamports: u64,
+    transient_stake_lamports: u64,
+    last_update_epoch: Epoch,
+    transient_seed_suffix_start: u64,
+    transient_seed_suffix_end: u64,
+    status: u8,
+    vote_account_address: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+struct ValidatorList {
+    account_type: u8,
+    max_validators: u32,
+    validators: Vec<ValidatorStakeInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type", content = "info")]
+pub enum StakePoolAccountType {
+    StakePool(UiStakePool),
+    ValidatorList(UiValidatorList),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiFee {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl From<Fee> for UiFee {
+    fn from(fee: Fee) -> Self {
+        Self {
+            numerator: fee.numerator,
+            denominator: fee.denominator,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakePoolLockup {
+    pub unix_timestamp: UnixTimestamp,
+    pub epoch: Epoch,
+    pub custodian: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiStakePool {
+    pub manager: String,
+    pub staker: String,
+    pub stake_deposit_authority: String,
+    pub stake_withdraw_bump_seed: u8,
+    pub validator_list: String,
+    pub reserve_stake: String,
+    pub pool_mint: String,
+    pub manager_fee_account: String,
+    pub token_program_id: String,
+    pub total_lamports: StringAmount,
+    pub pool_token_supply: StringAmount,
+    pub last_update_epoch: Epoch,
+    pub lockup: UiStakePoolLockup,
+    pub epoch_fee: UiFee,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_epoch_fee: Option<UiFee>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_deposit_validator_vote_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_withdraw_validator_vote_address: Option<String>,
+    pub stake_deposit_fee: UiFee,
+    pub stake_withdrawal_fee: UiFee,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_stake_withdrawal_fee: Option<UiFee>,
+    pub stake_referral_fee: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sol_deposit_authority: Option<String>,
+    pub sol_deposit_fee: UiFee,
+    pub sol_referral_fee: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sol_withdraw_authority: Option<String>,
+    pub sol_withdrawal_fee: UiFee,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_sol_withdrawal_fee: Option<UiFee>,
+    pub last_epoch_pool_token_supply: StringAmount,
+    pub last_epoch_total_lamports: StringAmount,
+}
+
+impl From<StakePool> for UiStakePool {
+    fn from(stake_pool: StakePool) -> Self {
+        Self {
+            manager: stake_pool.manager.to_string(),
+            staker: stake_pool.staker.to_string(),
+            stake_deposit_authority: stake_pool.stake_deposit_authority.to_string(),
+            stake_withdraw_bump_seed: stake_pool.stake_withdraw_bump_seed,
+            validator_list: stake_pool.validator_list.to_string(),
+            reserve_stake: stake_pool.reserve_stake.to_string(),
+            pool_mint: stake_pool.pool_mint.to_string(),
+            manager_fee_account: stake_pool.manager_fee_account.to_string(),
+            token_program_id: stake_pool.token_program_id.to_string(),
+            total_lamports: stake_pool.total_lamports.to_string(),
+            pool_token_supply: stake_pool.pool_token_supply.to_string(),
+            last_update_epoch: stake_pool.last_update_epoch,
+            lockup: UiStakePoolLockup {
+                unix_timestamp: stake_pool.lockup.unix_timestamp,
+                epoch: stake_pool.lockup.epoch,
+                custodian: stake_pool.lockup.custodian.to_string(),
+            },
+            epoch_fee: stake_pool.epoch_fee.into(),
+            next_epoch_fee: stake_pool.next_epoch_fee.map(UiFee::from),
+            preferred_deposit_validator_vote_address: stake_pool
+                .preferred_deposit_validator_vote_address
+                .map(|pubkey| pubkey.to_string()),
+            preferred_withdraw_validator_vote_address: stake_pool
+                .preferred_withdraw_validator_vote_address
+                .map(|pubkey| pubkey.to_string()),
+            stake_deposit_fee: stake_pool.stake_deposit_fee.into(),
+            stake_withdrawal_fee: stake_pool.stake_withdrawal_fee.into(),
+            next_stake_withdrawal_fee: stake_pool.next_stake_withdrawal_fee.map(UiFee::from),
+            stake_referral_fee: stake_pool.stake_referral_fee,
+            sol_deposit_authority: stake_pool
+                .sol_deposit_authority
+                .map(|pubkey| pubkey.to_string()),
+            sol_deposit_fee: stake_pool.sol_deposit_fee.into(),
+            sol_referral_fee: stake_pool.sol_referral_fee,
+            sol_withdraw_authority: stake_pool
+                .sol_withdraw_authority
+                .map(|pubkey| pubkey.to_string()),
+            sol_withdrawal_fee: stake_pool.sol_withdrawal_fee.into(),
+            next_sol_withdrawal_fee: stake_pool.next_sol_withdrawal_fee.map(UiFee::from),
+            last_epoch_pool_token_supply: stake_pool.last_epoch_pool_token_supply.to_string(),
+            last_epoch_total_lamports: stake_pool.last_epoch_total_lamports.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiValidatorStakeStatus {
+    Active,
+    DeactivatingTransient,
+    ReadyForRemoval,
+    Unknown,
+}
+
+impl From<u8> for UiValidatorStakeStatus {
+    fn from(status: u8) -> Self {
+        match status {
+            0 => Self::Active,
+            1 => Self::DeactivatingTransient,
+            2 => Self::ReadyForRemoval,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiValidatorStakeInfo {
+    pub vote_account_address: String,
+    pub active_stake_lamports: StringAmount,
+    pub transient_stake_lamports: StringAmount,
+    pub last_update_epoch: Epoch,
+    pub transient_seed_suffix_start: u64,
+    pub transient_seed_suffix_end: u64,
+    pub status: UiValidatorStakeStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiValidatorList {
+    pub max_validators: u32,
+    pub validators: Vec<UiValidatorStakeInfo>,
+}
+
+impl From<ValidatorList> for UiValidatorList {
+    fn from(validator_list: ValidatorList) -> Self {
+        Self {
+            max_validators: validator_list.max_validators,
+            validators: validator_list
+                .validators
+                .into_iter()
+                .map(|validator| UiValidatorStakeInfo {
+                    vote_account_address: validator.vote_account_address.to_string(),
+                    active_stake_lamports: validator.active_stake_lamports.to_string(),
+                    transient_stake_lamports: validator.transient_stake_lamports.to_string(),
+                    last_update_epoch: validator.last_update_epoch,
+                    transient_seed_suffix_start: validator.transient_seed_suffix_start,
+                    transient_seed_suffix_end: validator.transient_seed_suffix_end,
+                    status: validator.status.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_stake_pool() {
+        let stake_pool = StakePool {
+            account_type: ACCOUNT_TYPE_STAKE_POOL,
+            manager: solana_sdk::pubkey::new_rand(),
+            total_lamports: 42,
+            epoch_fee: Fee {
+                denominator: 100,
+                numerator: 3,
+            },
+            sol_deposit_authority: Some(solana_sdk::pubkey::new_rand()),
+            ..StakePool::default()
+        };
+        // Pool accounts are allocated with room to spare
+        let mut stake_pool_data = stake_pool.try_to_vec().unwrap();
+        stake_pool_data.resize(stake_pool_data.len() + 64, 0);
+        assert_eq!(
+            parse_stake_pool(&stake_pool_data).unwrap(),
+            StakePoolAccountType::StakePool(stake_pool.clone().into())
+        );
+        if let StakePoolAccountType::StakePool(ui_stake_pool) =
+            parse_stake_pool(&stake_pool_data).unwrap()
+        {
+            assert_eq!(ui_stake_pool.manager, stake_pool.manager.to_string());
+            assert_eq!(ui_stake_pool.total_lamports, "42".to_string());
+            assert_eq!(
+                ui_stake_pool.epoch_fee,
+                UiFee {
+                    numerator: 3,
+                    denominator: 100
+                }
+            );
+        }
+
+        let vote_account_address = solana_sdk::pubkey::new_rand();
+        let validator_list = ValidatorList {
+            account_type: ACCOUNT_TYPE_VALIDATOR_LIST,
+            max_validators: 10,
+            validators: vec![ValidatorStakeInfo {
+                active_stake_lamports: 1_000,
+                status: 1,
+                vote_account_address,
+                ..ValidatorStakeInfo::default()
+            }],
+        };
+        let mut validator_list_data = validator_list.try_to_vec().unwrap();
+        validator_list_data.resize(validator_list_data.len() + 9 * 73, 0);
+        assert_eq!(
+            parse_stake_pool(&validator_list_data).unwrap(),
+            StakePoolAccountType::ValidatorList(UiValidatorList {
+                max_validators: 10,
+                validators: vec![UiValidatorStakeInfo {
+                    vote_account_address: vote_account_address.to_string(),
+                    active_stake_lamports: "1000".to_string(),
+                    transient_stake_lamports: "0".to_string(),
+                    last_update_epoch: 0,
+                    transient_seed_suffix_start: 0,
+                    transient_seed_suffix_end: 0,
+                    status: UiValidatorStakeStatus::DeactivatingTransient,
+                }],
+            })
+        );
+
+        let bad_data = vec![0; 4];
+        assert!(parse_stake_pool(&bad_data).is_err());
+        assert!(parse_stake_pool(&stake_pool_data[..100]).is_err());
+    }
+}
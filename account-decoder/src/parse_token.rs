@@ -23,6 +23,12 @@ pub fn spl_token_v2_0_native_mint() -> Pubkey {
     Pubkey::new_from_array(spl_token_v2_0::native_mint::id().to_bytes())
 }
 
+// The spl-token-2022 program id, whose accounts share the spl-token layout and may carry
+// extensions after it
+pub fn spl_token_2022_id() -> Pubkey {
+    Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap()
+}
+
 // A helper function to convert a solana_sdk::pubkey::Pubkey to spl_sdk::pubkey::Pubkey
 pub fn spl_token_v2_0_pubkey(pubkey: &Pubkey) -> SplTokenPubkey {
     SplTokenPubkey::new_from_array(pubkey.to_bytes())
@@ -114,6 +120,83 @@ pub fn parse_token(
     }
 }
 
+// spl-token-2022 accounts with extensions append an account type byte to the spl-token account
+// layout (mints are zero-padded to that length), followed by type-length-value extension entries
+const TOKEN_2022_ACCOUNT_TYPE_MINT: u8 = 1;
+const TOKEN_2022_ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+pub fn parse_token_2022(
+    data: &[u8],
+    mint_decimals: Option<u8>,
+) -> Result<UiToken2022Account, ParseAccountError> {
+    let account_len = Account::get_packed_len();
+    if data.len() <= account_len || data.len() == Multisig::get_packed_len() {
+        return Ok(UiToken2022Account {
+            account_type: parse_token(data, mint_decimals)?,
+            extensions: vec![],
+        });
+    }
+    let base_data = match data[account_len] {
+        TOKEN_2022_ACCOUNT_TYPE_MINT => &data[..Mint::get_packed_len()],
+        TOKEN_2022_ACCOUNT_TYPE_ACCOUNT => &data[..account_len],
+        _ => {
+            return Err(ParseAccountError::AccountNotParsable(
+                ParsableAccount::SplToken2022,
+            ))
+        }
+    };
+    Ok(UiToken2022Account {
+        account_type: parse_token(base_data, mint_decimals)?,
+        extensions: parse_token_2022_extensions(&data[account_len + 1..]),
+    })
+}
+
+fn parse_token_2022_extensions(mut tlv_data: &[u8]) -> Vec<String> {
+    let mut extensions = vec![];
+    while tlv_data.len() >= 4 {
+        let extension_type = u16::from_le_bytes([tlv_data[0], tlv_data[1]]);
+        let length = u16::from_le_bytes([tlv_data[2], tlv_data[3]]) as usize;
+        // Unused space after the last extension is zeroed
+        if extension_type == 0 {
+            break;
+        }
+        extensions.push(token_2022_extension_name(extension_type));
+        tlv_data = tlv_data.get(4 + length..).unwrap_or_default();
+    }
+    extensions
+}
+
+fn token_2022_extension_name(extension_type: u16) -> String {
+    match extension_type {
+        1 => "transferFeeConfig",
+        2 => "transferFeeAmount",
+        3 => "mintCloseAuthority",
+        4 => "confidentialTransferMint",
+        5 => "confidentialTransferAccount",
+        6 => "defaultAccountState",
+        7 => "immutableOwner",
+        8 => "memoTransfer",
+        9 => "nonTransferable",
+        10 => "interestBearingConfig",
+        11 => "cpiGuard",
+        12 => "permanentDelegate",
+        13 => "nonTransferableAccount",
+        14 => "transferHook",
+        15 => "transferHookAccount",
+        _ => return format!("unknown({})", extension_type),
+    }
+    .to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiToken2022Account {
+    #[serde(flatten)]
+    pub account_type: TokenAccountType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase", tag = "type", content = "info")]
 #[allow(clippy::large_enum_variant)]
@@ -242,7 +325,11 @@ pub struct UiMultisig {
 }
 
 pub fn get_token_account_mint(data: &[u8]) -> Option<Pubkey> {
-    if data.len() == Account::get_packed_len() {
+    let account_len = Account::get_packed_len();
+    let is_token_2022_account = data.len() > account_len
+        && data.len() != Multisig::get_packed_len()
+        && data[account_len] == TOKEN_2022_ACCOUNT_TYPE_ACCOUNT;
+    if data.len() == account_len || is_token_2022_account {
         Some(Pubkey::new(&data[0..32]))
     } else {
         None
@@ -354,6 +441,71 @@ mod test {
             get_token_account_mint(&account_data),
             Some(expected_mint_pubkey)
         );
+
+        account_data.push(TOKEN_2022_ACCOUNT_TYPE_ACCOUNT);
+        assert_eq!(
+            get_token_account_mint(&account_data),
+            Some(expected_mint_pubkey)
+        );
+    }
+
+    #[test]
+    fn test_parse_token_2022() {
+        let mint_pubkey = SplTokenPubkey::new(&[2; 32]);
+        let owner_pubkey = SplTokenPubkey::new(&[3; 32]);
+        let mut account_data = vec![0; Account::get_packed_len()];
+        let mut account = Account::unpack_unchecked(&account_data).unwrap();
+        account.mint = mint_pubkey;
+        account.owner = owner_pubkey;
+        account.amount = 42;
+        account.state = AccountState::Initialized;
+        Account::pack(account, &mut account_data).unwrap();
+
+        // Without extensions, accounts are laid out like spl-token ones
+        let parsed_account = parse_token_2022(&account_data, Some(2)).unwrap();
+        assert_eq!(
+            parsed_account.account_type,
+            parse_token(&account_data, Some(2)).unwrap()
+        );
+        assert!(parsed_account.extensions.is_empty());
+
+        // immutableOwner, with no data, then memoTransfer, with a single byte
+        account_data.push(TOKEN_2022_ACCOUNT_TYPE_ACCOUNT);
+        account_data.extend_from_slice(&[7, 0, 0, 0, 8, 0, 1, 0, 1]);
+        let parsed_account = parse_token_2022(&account_data, Some(2)).unwrap();
+        assert_eq!(
+            parsed_account.account_type,
+            parse_token(&account_data[..Account::get_packed_len()], Some(2)).unwrap()
+        );
+        assert_eq!(
+            parsed_account.extensions,
+            vec!["immutableOwner".to_string(), "memoTransfer".to_string()]
+        );
+
+        let mut mint_data = vec![0; Mint::get_packed_len()];
+        let mut mint = Mint::unpack_unchecked(&mint_data).unwrap();
+        mint.supply = 42;
+        mint.decimals = 3;
+        mint.is_initialized = true;
+        Mint::pack(mint, &mut mint_data).unwrap();
+        let parsed_mint = parse_token(&mint_data, None).unwrap();
+
+        // mintCloseAuthority, followed by zeroed space
+        mint_data.resize(Account::get_packed_len(), 0);
+        mint_data.push(TOKEN_2022_ACCOUNT_TYPE_MINT);
+        mint_data.extend_from_slice(&[3, 0, 32, 0]);
+        mint_data.extend_from_slice(&[4; 32]);
+        mint_data.resize(mint_data.len() + 16, 0);
+        let parsed_account = parse_token_2022(&mint_data, None).unwrap();
+        assert_eq!(parsed_account.account_type, parsed_mint);
+        assert_eq!(
+            parsed_account.extensions,
+            vec!["mintCloseAuthority".to_string()]
+        );
+
+        let mut bad_data = vec![0; Account::get_packed_len() + 1];
+        bad_data[Account::get_packed_len()] = 3;
+        assert!(parse_token_2022(&bad_data, None).is_err());
     }
 
     #[test]
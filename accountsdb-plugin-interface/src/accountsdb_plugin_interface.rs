@@ -24,6 +24,64 @@ pub enum ReplicaAccountInfoVersions<'a> {
     V0_0_1(&'a ReplicaAccountInfo<'a>),
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplicaEntryInfo<'a> {
+    pub slot: u64,
+    /// Index of the entry in the slot
+    pub index: usize,
+    pub num_hashes: u64,
+    pub hash: &'a [u8],
+    pub num_transactions: u64,
+}
+
+pub enum ReplicaEntryInfoVersions<'a> {
+    V0_0_1(&'a ReplicaEntryInfo<'a>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplicaBlockInfo<'a> {
+    pub slot: u64,
+    pub parent_slot: u64,
+    pub blockhash: &'a [u8],
+    /// Pubkey of the leader which produced the block
+    pub leader: &'a [u8],
+    pub block_time: Option<i64>,
+    pub block_height: Option<u64>,
+}
+
+pub enum ReplicaBlockInfoVersions<'a> {
+    V0_0_1(&'a ReplicaBlockInfo<'a>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RewardType {
+    Fee,
+    Rent,
+    Staking,
+    Voting,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplicaRewardInfo<'a> {
+    pub pubkey: &'a [u8],
+    pub lamports: i64,
+    /// Balance of the account once the reward was credited
+    pub post_balance: u64,
+    pub reward_type: RewardType,
+    /// Commission of the vote account, for voting and staking rewards
+    pub commission: Option<u8>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplicaRewardsInfo<'a> {
+    pub slot: u64,
+    pub rewards: &'a [ReplicaRewardInfo<'a>],
+}
+
+pub enum ReplicaRewardsInfoVersions<'a> {
+    V0_0_1(&'a ReplicaRewardsInfo<'a>),
+}
+
 #[derive(Error, Debug)]
 pub enum AccountsDbPluginError {
     #[error("Error opening config file. Error detail: ({0}).")]
@@ -38,6 +96,15 @@ pub enum AccountsDbPluginError {
     #[error("Error updating slot status. Error message: ({msg})")]
     SlotStatusUpdateError { msg: String },
 
+    #[error("Error notifying entry. Error message: ({msg})")]
+    EntryNotifyError { msg: String },
+
+    #[error("Error notifying block metadata. Error message: ({msg})")]
+    BlockMetadataNotifyError { msg: String },
+
+    #[error("Error notifying rewards. Error message: ({msg})")]
+    RewardsNotifyError { msg: String },
+
     #[error("Plugin-defined custom error. Error message: ({0})")]
     Custom(Box<dyn error::Error + Send + Sync>),
 }
@@ -96,4 +163,33 @@ pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
         parent: Option<u64>,
         status: SlotStatus,
     ) -> Result<()>;
+
+    /// Called when an entry of a slot is deshredded, as soon as the data blocks up to it are
+    /// complete, before the slot is replayed. Only called if `entry_notifications_enabled`
+    /// returns true.
+    fn notify_entry(&mut self, _entry: ReplicaEntryInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the plugin is interested in the entries of the slots being received
+    fn entry_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    /// Called when a block is replayed and its bank frozen. Only called if
+    /// `block_metadata_notifications_enabled` returns true.
+    fn notify_block_metadata(&mut self, _block_info: ReplicaBlockInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the rewards credited in a block, possibly none, right after the metadata of
+    /// the block is notified. Only called if `block_metadata_notifications_enabled` returns true.
+    fn notify_rewards(&mut self, _rewards_info: ReplicaRewardsInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the plugin is interested in the metadata and the rewards of the blocks replayed
+    fn block_metadata_notifications_enabled(&self) -> bool {
+        false
+    }
 }
@@ -40,6 +40,20 @@ impl AccountsDbPluginManager {
         Ok(())
     }
 
+    /// Whether any plugin is interested in the entries of the slots being received
+    pub fn entry_notifications_enabled(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.entry_notifications_enabled())
+    }
+
+    /// Whether any plugin is interested in the metadata and the rewards of the blocks replayed
+    pub fn block_metadata_notifications_enabled(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.block_metadata_notifications_enabled())
+    }
+
     /// Unload all plugins and loaded plugin libraries, making sure to fire
     /// their `on_plugin_unload()` methods so they can do any necessary cleanup.
     pub fn unload(&mut self) {
@@ -2,11 +2,14 @@ use {
     crate::{
         accounts_update_notifier::AccountsUpdateNotifierImpl,
         accountsdb_plugin_manager::AccountsDbPluginManager,
-        slot_status_observer::SlotStatusObserver,
+        block_metadata_notifier::BlockMetadataNotifierImpl,
+        block_metadata_notifier_interface::BlockMetadataNotifier,
+        entry_notifier::EntryNotifierImpl, slot_status_observer::SlotStatusObserver,
     },
     crossbeam_channel::Receiver,
     log::*,
     serde_json,
+    solana_ledger::entry_notifier_interface::EntryNotifier,
     solana_rpc::optimistically_confirmed_bank_tracker::BankNotification,
     solana_runtime::accounts_update_notifier_interface::AccountsUpdateNotifier,
     std::{
@@ -45,6 +48,8 @@ pub struct AccountsDbPluginService {
     slot_status_observer: SlotStatusObserver,
     plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
     accounts_update_notifier: AccountsUpdateNotifier,
+    entry_notifier: Option<EntryNotifier>,
+    block_metadata_notifier: Option<BlockMetadataNotifier>,
 }
 
 impl AccountsDbPluginService {
@@ -75,18 +80,38 @@ impl AccountsDbPluginService {
             Self::load_plugin(&mut plugin_manager, accountsdb_plugin_config_file)?;
         }
 
+        let entry_notifications_enabled = plugin_manager.entry_notifications_enabled();
+        let block_metadata_notifications_enabled =
+            plugin_manager.block_metadata_notifications_enabled();
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
         let accounts_update_notifier = Arc::new(RwLock::new(AccountsUpdateNotifierImpl::new(
             plugin_manager.clone(),
         )));
         let slot_status_observer =
             SlotStatusObserver::new(confirmed_bank_receiver, accounts_update_notifier.clone());
+        let entry_notifier: Option<EntryNotifier> = if entry_notifications_enabled {
+            Some(Arc::new(RwLock::new(EntryNotifierImpl::new(
+                plugin_manager.clone(),
+            ))))
+        } else {
+            None
+        };
+        let block_metadata_notifier: Option<BlockMetadataNotifier> =
+            if block_metadata_notifications_enabled {
+                Some(Arc::new(RwLock::new(BlockMetadataNotifierImpl::new(
+                    plugin_manager.clone(),
+                ))))
+            } else {
+                None
+            };
 
         info!("Started AccountsDbPluginService");
         Ok(AccountsDbPluginService {
             slot_status_observer,
             plugin_manager,
             accounts_update_notifier,
+            entry_notifier,
+            block_metadata_notifier,
         })
     }
 
@@ -149,6 +174,18 @@ impl AccountsDbPluginService {
         self.accounts_update_notifier.clone()
     }
 
+    /// The notifier of the entries of the slots being received, if any plugin is interested in
+    /// them
+    pub fn get_entry_notifier(&self) -> Option<EntryNotifier> {
+        self.entry_notifier.clone()
+    }
+
+    /// The notifier of the metadata and the rewards of the blocks replayed, if any plugin is
+    /// interested in them
+    pub fn get_block_metadata_notifier(&self) -> Option<BlockMetadataNotifier> {
+        self.block_metadata_notifier.clone()
+    }
+
     pub fn join(mut self) -> thread::Result<()> {
         self.slot_status_observer.join()?;
         self.plugin_manager.write().unwrap().unload();
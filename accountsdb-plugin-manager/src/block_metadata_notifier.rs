@@ -0,0 +1,146 @@
+/// Module responsible for notifying plugins of the metadata and the rewards of the blocks replayed
+use {
+    crate::{
+        accountsdb_plugin_manager::AccountsDbPluginManager,
+        block_metadata_notifier_interface::BlockMetadataNotifierInterface,
+    },
+    log::*,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        ReplicaBlockInfo, ReplicaBlockInfoVersions, ReplicaRewardInfo, ReplicaRewardsInfo,
+        ReplicaRewardsInfoVersions, RewardType,
+    },
+    solana_measure::measure::Measure,
+    solana_metrics::*,
+    solana_runtime::bank::{self, RewardInfo},
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        hash::Hash,
+        pubkey::Pubkey,
+    },
+    std::sync::{Arc, RwLock},
+};
+
+#[derive(Debug)]
+pub(crate) struct BlockMetadataNotifierImpl {
+    plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+}
+
+impl BlockMetadataNotifierInterface for BlockMetadataNotifierImpl {
+    fn notify_block_metadata(
+        &self,
+        slot: Slot,
+        parent_slot: Slot,
+        blockhash: &Hash,
+        leader: &Pubkey,
+        block_time: Option<UnixTimestamp>,
+        block_height: Option<u64>,
+    ) {
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        if plugin_manager.plugins.is_empty() {
+            return;
+        }
+
+        let block_info = ReplicaBlockInfo {
+            slot,
+            parent_slot,
+            blockhash: blockhash.as_ref(),
+            leader: leader.as_ref(),
+            block_time,
+            block_height,
+        };
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.block_metadata_notifications_enabled() {
+                continue;
+            }
+            let mut measure = Measure::start("accountsdb-plugin-notify-block-metadata");
+            match plugin.notify_block_metadata(ReplicaBlockInfoVersions::V0_0_1(&block_info)) {
+                Err(err) => {
+                    error!(
+                        "Failed to notify block metadata of slot {}, error: {} to plugin {}",
+                        slot,
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully notified block metadata of slot {} to plugin {}",
+                        slot,
+                        plugin.name()
+                    );
+                }
+            }
+            measure.stop();
+            inc_new_counter_debug!(
+                "accountsdb-plugin-notify-block-metadata-us",
+                measure.as_us() as usize,
+                10000,
+                10000
+            );
+        }
+    }
+
+    fn notify_rewards(&self, slot: Slot, rewards: &[(Pubkey, RewardInfo)]) {
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        if plugin_manager.plugins.is_empty() {
+            return;
+        }
+
+        let rewards: Vec<_> = rewards
+            .iter()
+            .map(|(pubkey, reward_info)| ReplicaRewardInfo {
+                pubkey: pubkey.as_ref(),
+                lamports: reward_info.lamports,
+                post_balance: reward_info.post_balance,
+                reward_type: match reward_info.reward_type {
+                    bank::RewardType::Fee => RewardType::Fee,
+                    bank::RewardType::Rent => RewardType::Rent,
+                    bank::RewardType::Staking => RewardType::Staking,
+                    bank::RewardType::Voting => RewardType::Voting,
+                },
+                commission: reward_info.commission,
+            })
+            .collect();
+        let rewards_info = ReplicaRewardsInfo {
+            slot,
+            rewards: &rewards,
+        };
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.block_metadata_notifications_enabled() {
+                continue;
+            }
+            let mut measure = Measure::start("accountsdb-plugin-notify-rewards");
+            match plugin.notify_rewards(ReplicaRewardsInfoVersions::V0_0_1(&rewards_info)) {
+                Err(err) => {
+                    error!(
+                        "Failed to notify rewards of slot {}, error: {} to plugin {}",
+                        slot,
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully notified {} rewards of slot {} to plugin {}",
+                        rewards.len(),
+                        slot,
+                        plugin.name()
+                    );
+                }
+            }
+            measure.stop();
+            inc_new_counter_debug!(
+                "accountsdb-plugin-notify-rewards-us",
+                measure.as_us() as usize,
+                10000,
+                10000
+            );
+        }
+    }
+}
+
+impl BlockMetadataNotifierImpl {
+    pub fn new(plugin_manager: Arc<RwLock<AccountsDbPluginManager>>) -> Self {
+        BlockMetadataNotifierImpl { plugin_manager }
+    }
+}
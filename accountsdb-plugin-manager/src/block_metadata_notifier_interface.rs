@@ -0,0 +1,27 @@
+use {
+    solana_runtime::bank::RewardInfo,
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        hash::Hash,
+        pubkey::Pubkey,
+    },
+    std::sync::{Arc, RwLock},
+};
+
+pub trait BlockMetadataNotifierInterface: std::fmt::Debug {
+    /// Notified when a block is replayed and its bank frozen
+    fn notify_block_metadata(
+        &self,
+        slot: Slot,
+        parent_slot: Slot,
+        blockhash: &Hash,
+        leader: &Pubkey,
+        block_time: Option<UnixTimestamp>,
+        block_height: Option<u64>,
+    );
+
+    /// Notified with the rewards credited in a block, once its metadata is notified
+    fn notify_rewards(&self, slot: Slot, rewards: &[(Pubkey, RewardInfo)]);
+}
+
+pub type BlockMetadataNotifier = Arc<RwLock<dyn BlockMetadataNotifierInterface + Sync + Send>>;
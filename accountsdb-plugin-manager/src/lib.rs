@@ -1,4 +1,7 @@
 pub mod accounts_update_notifier;
 pub mod accountsdb_plugin_manager;
 pub mod accountsdb_plugin_service;
+pub(crate) mod block_metadata_notifier;
+pub mod block_metadata_notifier_interface;
+pub(crate) mod entry_notifier;
 pub mod slot_status_observer;
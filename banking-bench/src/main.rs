@@ -2,7 +2,7 @@
 use clap::{crate_description, crate_name, value_t, App, Arg};
 use crossbeam_channel::unbounded;
 use log::*;
-use rand::{thread_rng, Rng};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use rayon::prelude::*;
 use solana_core::banking_stage::BankingStage;
 use solana_gossip::{cluster_info::ClusterInfo, cluster_info::Node};
@@ -20,11 +20,14 @@ use solana_runtime::{
 };
 use solana_sdk::{
     hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
     signature::Keypair,
     signature::Signature,
-    system_transaction,
+    signature::Signer,
+    system_instruction, system_transaction,
     timing::{duration_as_us, timestamp},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 use solana_streamer::socket::SocketAddrSpace;
 use std::{
@@ -64,26 +67,79 @@ fn check_txs(
     no_bank
 }
 
+/// Shape of the generated workload
+struct ContentionProfile {
+    /// Accounts written by `hot_account_ratio` of the transfers, contending for their locks
+    hot_accounts: Vec<Pubkey>,
+    hot_account_ratio: f64,
+    /// Each transaction carries a uniformly distributed number of transfers, up to this many
+    max_transfers_per_tx: usize,
+    /// Seeds the generation of the workload, so that it can be reproduced
+    seed: u64,
+}
+
+impl ContentionProfile {
+    fn new(
+        num_hot_accounts: usize,
+        hot_account_ratio: f64,
+        max_transfers_per_tx: usize,
+        seed: u64,
+    ) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let hot_accounts = (0..num_hot_accounts)
+            .map(|_| Pubkey::new(&rng.gen::<[u8; 32]>()))
+            .collect();
+        Self {
+            hot_accounts,
+            hot_account_ratio,
+            max_transfers_per_tx,
+            seed,
+        }
+    }
+
+    fn make_transaction(
+        &self,
+        index: usize,
+        payer: &Pubkey,
+        same_payer: bool,
+        hash: Hash,
+    ) -> Transaction {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(index as u64 + 1));
+        let payer = if same_payer {
+            *payer
+        } else {
+            Pubkey::new(&rng.gen::<[u8; 32]>())
+        };
+        let num_transfers = rng.gen_range(1, self.max_transfers_per_tx + 1);
+        let instructions: Vec<_> = (0..num_transfers)
+            .map(|_| {
+                let to_pubkey =
+                    if !self.hot_accounts.is_empty() && rng.gen_bool(self.hot_account_ratio) {
+                        self.hot_accounts[rng.gen_range(0, self.hot_accounts.len())]
+                    } else {
+                        Pubkey::new(&rng.gen::<[u8; 32]>())
+                    };
+                system_instruction::transfer(&payer, &to_pubkey, 1)
+            })
+            .collect();
+        let message = Message::new_with_blockhash(&instructions, Some(&payer), &hash);
+        let mut tx = Transaction::new_unsigned(message);
+        let sig: Vec<u8> = (0..64).map(|_| rng.gen::<u8>()).collect();
+        tx.signatures = vec![Signature::new(&sig[0..64])];
+        tx
+    }
+}
+
 fn make_accounts_txs(
     total_num_transactions: usize,
     hash: Hash,
     same_payer: bool,
+    profile: &ContentionProfile,
 ) -> Vec<Transaction> {
-    let to_pubkey = solana_sdk::pubkey::new_rand();
-    let payer_key = Keypair::new();
-    let dummy = system_transaction::transfer(&payer_key, &to_pubkey, 1, hash);
+    let payer = Keypair::new().pubkey();
     (0..total_num_transactions)
         .into_par_iter()
-        .map(|_| {
-            let mut new = dummy.clone();
-            let sig: Vec<u8> = (0..64).map(|_| thread_rng().gen::<u8>()).collect();
-            if !same_payer {
-                new.message.account_keys[0] = solana_sdk::pubkey::new_rand();
-            }
-            new.message.account_keys[1] = solana_sdk::pubkey::new_rand();
-            new.signatures = vec![Signature::new(&sig[0..64])];
-            new
-        })
+        .map(|index| profile.make_transaction(index, &payer, same_payer, hash))
         .collect()
 }
 
@@ -148,6 +204,34 @@ fn main() {
                 .takes_value(true)
                 .help("Number of iterations"),
         )
+        .arg(
+            Arg::with_name("num_hot_accounts")
+                .long("num-hot-accounts")
+                .takes_value(true)
+                .value_name("NUM")
+                .help("Number of accounts contended for by the transactions [default: 0]"),
+        )
+        .arg(
+            Arg::with_name("hot_account_ratio")
+                .long("hot-account-ratio")
+                .takes_value(true)
+                .value_name("RATIO")
+                .help("Fraction of the transfers, between 0 and 1, that write to a hot account [default: 0.5]"),
+        )
+        .arg(
+            Arg::with_name("max_transfers_per_tx")
+                .long("max-transfers-per-tx")
+                .takes_value(true)
+                .value_name("NUM")
+                .help("Maximum number of transfers in each transaction, the number is uniformly distributed to spread the compute cost [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .value_name("SEED")
+                .help("Seed the workload is generated from [default: 0]"),
+        )
         .get_matches();
 
     let num_threads =
@@ -156,6 +240,18 @@ fn main() {
     let num_chunks = value_t!(matches, "num_chunks", usize).unwrap_or(16);
     let packets_per_chunk = value_t!(matches, "packets_per_chunk", usize).unwrap_or(192);
     let iterations = value_t!(matches, "iterations", usize).unwrap_or(1000);
+    let num_hot_accounts = value_t!(matches, "num_hot_accounts", usize).unwrap_or(0);
+    let hot_account_ratio = value_t!(matches, "hot_account_ratio", f64).unwrap_or(0.5);
+    let max_transfers_per_tx = value_t!(matches, "max_transfers_per_tx", usize).unwrap_or(1);
+    let seed = value_t!(matches, "seed", u64).unwrap_or(0);
+    assert!(
+        (0.0..=1.0).contains(&hot_account_ratio),
+        "--hot-account-ratio must be between 0 and 1"
+    );
+    assert!(
+        max_transfers_per_tx > 0,
+        "--max-transfers-per-tx must be at least 1"
+    );
 
     let total_num_transactions = num_chunks * num_threads * packets_per_chunk;
     let mint_total = 1_000_000_000_000;
@@ -174,10 +270,24 @@ fn main() {
     let mut bank = bank_forks.working_bank();
 
     info!("threads: {} txs: {}", num_threads, total_num_transactions);
+    info!(
+        "hot accounts: {} hot account ratio: {} max transfers per tx: {} seed: {}",
+        num_hot_accounts, hot_account_ratio, max_transfers_per_tx, seed
+    );
 
     let same_payer = matches.is_present("same_payer");
-    let mut transactions =
-        make_accounts_txs(total_num_transactions, genesis_config.hash(), same_payer);
+    let profile = ContentionProfile::new(
+        num_hot_accounts,
+        hot_account_ratio,
+        max_transfers_per_tx,
+        seed,
+    );
+    let mut transactions = make_accounts_txs(
+        total_num_transactions,
+        genesis_config.hash(),
+        same_payer,
+        &profile,
+    );
 
     // fund all the accounts
     transactions.iter().for_each(|tx| {
@@ -204,9 +314,14 @@ fn main() {
         bank.clear_signatures();
         //sanity check, make sure all the transactions can execute in parallel
 
+        // Transactions writing to the same hot account can't be executed in parallel
         let res = bank.process_transactions(transactions.iter());
         for r in res {
-            assert!(r.is_ok(), "sanity parallel execution error: {:?}", r);
+            assert!(
+                r.is_ok() || r == Err(TransactionError::AccountInUse),
+                "sanity parallel execution error: {:?}",
+                r
+            );
         }
         bank.clear_signatures();
     }
@@ -234,6 +349,8 @@ fn main() {
             None,
             replay_vote_sender,
             Arc::new(RwLock::new(CostModel::default())),
+            None,
+            None,
         );
         poh_recorder.lock().unwrap().set_bank(&bank);
 
@@ -247,6 +364,8 @@ fn main() {
         let mut total_us = 0;
         let mut tx_total_us = 0;
         let base_tx_count = bank.transaction_count();
+        let base_account_in_use_count = bank.account_in_use_count();
+        let mut account_in_use_count = 0;
         let mut txs_processed = 0;
         let mut root = 1;
         let collector = solana_sdk::pubkey::new_rand();
@@ -319,6 +438,7 @@ fn main() {
                     .reset(bank.clone(), Some((bank.slot(), bank.slot() + 1)));
                 poh_time.stop();
 
+                account_in_use_count += bank.account_in_use_count();
                 let mut new_bank_time = Measure::start("new_bank");
                 let new_bank = Bank::new_from_parent(&bank, &collector, bank.slot() + 1);
                 new_bank_time.stop();
@@ -371,6 +491,8 @@ fn main() {
         }
         let txs_processed = bank_forks.working_bank().transaction_count();
         debug!("processed: {} base: {}", txs_processed, base_tx_count);
+        account_in_use_count += bank_forks.working_bank().account_in_use_count();
+        account_in_use_count -= base_account_in_use_count;
         eprintln!(
             "{{'name': 'banking_bench_total', 'median': '{:.2}'}}",
             (1000.0 * 1000.0 * total_sent as f64) / (total_us as f64),
@@ -383,6 +505,11 @@ fn main() {
             "{{'name': 'banking_bench_success_tx_total', 'median': '{:.2}'}}",
             (1000.0 * 1000.0 * (txs_processed - base_tx_count) as f64) / (total_us as f64),
         );
+        // Every lock conflict stalls the transaction until it's retried by the banking stage
+        eprintln!(
+            "{{'name': 'banking_bench_account_in_use', 'median': '{}'}}",
+            account_in_use_count,
+        );
 
         drop(verified_sender);
         drop(tpu_vote_sender);
@@ -0,0 +1,483 @@
+//! Benchmarks BPF programs under the interpreter and the JIT, and reports their instruction
+//! counts, the compute units they consume and the share of each function and syscall, as JSON or
+//! CSV, so that the performance of programs can be tracked from one build to the next.
+
+use {
+    clap::{crate_description, crate_name, value_t_or_exit, values_t_or_exit, App, Arg},
+    log::*,
+    serde_derive::{Deserialize, Serialize},
+    solana_bpf_loader_program::{
+        create_vm,
+        profiler::{attribute_compute_units, SyscallCostRecorder},
+        serialization::serialize_parameters,
+        syscalls::register_syscalls,
+        BpfError, ThisInstructionMeter,
+    },
+    solana_measure::measure::Measure,
+    solana_program_runtime::invoke_context::{prepare_mock_invoke_context, ThisInvokeContext},
+    solana_rbpf::vm::{Config, Executable},
+    solana_sdk::{
+        account::AccountSharedData,
+        bpf_loader,
+        process_instruction::{ComputeMeter, InvokeContext},
+        pubkey::Pubkey,
+    },
+    std::{
+        fs::File,
+        io::{self, Write},
+        path::Path,
+        process::exit,
+    },
+};
+
+#[derive(Deserialize)]
+struct Account {
+    key: Pubkey,
+    owner: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+/// Accounts and instruction data the programs run on, in the format of `rbpf-cli`
+#[derive(Deserialize)]
+struct Input {
+    accounts: Vec<Account>,
+    instruction_data: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct ExecutionReport {
+    iterations: usize,
+    mean_us: f64,
+    min_us: f64,
+    max_us: f64,
+    /// Millions of instructions executed per second
+    mips: f64,
+}
+
+impl ExecutionReport {
+    fn new(durations_ns: &[u64], instruction_count: u64) -> Self {
+        let mean_us = durations_ns.iter().sum::<u64>() as f64 / durations_ns.len() as f64 / 1_000.0;
+        Self {
+            iterations: durations_ns.len(),
+            mean_us,
+            min_us: durations_ns.iter().min().copied().unwrap_or_default() as f64 / 1_000.0,
+            max_us: durations_ns.iter().max().copied().unwrap_or_default() as f64 / 1_000.0,
+            mips: if mean_us > 0.0 {
+                instruction_count as f64 / mean_us
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FunctionReport {
+    name: String,
+    /// Compute units consumed by the function, including those of the syscalls it made
+    compute_units: u64,
+}
+
+#[derive(Serialize)]
+struct SyscallReport {
+    name: String,
+    calls: u64,
+    compute_units: u64,
+}
+
+#[derive(Serialize)]
+struct ProgramReport {
+    program: String,
+    result: String,
+    instruction_count: u64,
+    compute_units: u64,
+    /// Compute units consumed by the syscalls, the others being consumed by the instructions
+    syscall_compute_units: u64,
+    interpreter: ExecutionReport,
+    jit: Option<ExecutionReport>,
+    functions: Vec<FunctionReport>,
+    syscalls: Vec<SyscallReport>,
+}
+
+struct Execution {
+    result: String,
+    instruction_count: u64,
+    compute_units: u64,
+    duration_ns: u64,
+    /// State of the VM at each instruction, if the execution was traced
+    trace: Vec<[u64; 12]>,
+}
+
+fn execute(
+    executable: &dyn Executable<BpfError, ThisInstructionMeter>,
+    invoke_context: &mut ThisInvokeContext,
+    instruction_data: &[u8],
+    instruction_meter: &mut ThisInstructionMeter,
+    use_jit: bool,
+) -> Execution {
+    // The input is serialized again for each execution, which may modify it
+    let keyed_accounts = invoke_context.get_keyed_accounts().unwrap();
+    let (mut parameter_bytes, account_lengths) = serialize_parameters(
+        keyed_accounts[0].unsigned_key(),
+        keyed_accounts[1].unsigned_key(),
+        &keyed_accounts[2..],
+        instruction_data,
+    )
+    .unwrap();
+    let compute_meter = invoke_context.get_compute_meter();
+    let initial_remaining = compute_meter.borrow().get_remaining();
+    let loader_id = bpf_loader::id();
+    let mut vm = create_vm(
+        &loader_id,
+        executable,
+        parameter_bytes.as_slice_mut(),
+        invoke_context,
+        &account_lengths,
+    )
+    .unwrap();
+
+    let mut measure = Measure::start("execute");
+    let result = if use_jit {
+        vm.execute_program_jit(instruction_meter)
+    } else {
+        vm.execute_program_interpreted(instruction_meter)
+    };
+    measure.stop();
+
+    let remaining = compute_meter.borrow().get_remaining();
+    Execution {
+        result: format!("{:?}", result),
+        instruction_count: vm.get_total_instruction_count(),
+        compute_units: initial_remaining.saturating_sub(remaining),
+        duration_ns: measure.as_ns(),
+        trace: vm.get_tracer().log.clone(),
+    }
+}
+
+fn load_executable(
+    elf: &[u8],
+    config: Config,
+    invoke_context: &mut ThisInvokeContext,
+) -> Result<Box<dyn Executable<BpfError, ThisInstructionMeter>>, String> {
+    let syscall_registry = register_syscalls(invoke_context)
+        .map_err(|err| format!("Unable to register syscalls: {:?}", err))?;
+    <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(elf, None, config, syscall_registry)
+        .map_err(|err| format!("Executable constructor failed: {:?}", err))
+}
+
+fn bench_program(
+    program: &str,
+    invoke_context: &mut ThisInvokeContext,
+    instruction_data: &[u8],
+    iterations: usize,
+    use_jit: bool,
+) -> Result<ProgramReport, String> {
+    let elf =
+        std::fs::read(program).map_err(|err| format!("Unable to read {}: {}", program, err))?;
+
+    // The compute units are attributed in a traced run, which is too slow to be timed
+    let traced_executable = load_executable(
+        &elf,
+        Config {
+            enable_instruction_tracing: true,
+            ..Config::default()
+        },
+        invoke_context,
+    )?;
+    let syscall_cost_recorder = SyscallCostRecorder::new_ref(invoke_context.get_compute_meter());
+    let mut instruction_meter = ThisInstructionMeter {
+        compute_meter: syscall_cost_recorder.clone(),
+    };
+    let profile = execute(
+        traced_executable.as_ref(),
+        invoke_context,
+        instruction_data,
+        &mut instruction_meter,
+        /*use_jit:*/ false,
+    );
+    let attribution = attribute_compute_units(
+        traced_executable.as_ref(),
+        &profile.trace,
+        &syscall_cost_recorder.borrow().syscall_costs(),
+    );
+
+    let instruction_count = profile.instruction_count;
+    let mut executable = load_executable(&elf, Config::default(), invoke_context)?;
+    let mut instruction_meter = ThisInstructionMeter {
+        compute_meter: invoke_context.get_compute_meter(),
+    };
+    let mut measure_executions = |executable: &dyn Executable<BpfError, ThisInstructionMeter>,
+                                  use_jit| {
+        let durations_ns: Vec<_> = (0..iterations)
+            .map(|_| {
+                execute(
+                    executable,
+                    invoke_context,
+                    instruction_data,
+                    &mut instruction_meter,
+                    use_jit,
+                )
+                .duration_ns
+            })
+            .collect();
+        ExecutionReport::new(&durations_ns, instruction_count)
+    };
+    let interpreter = measure_executions(executable.as_ref(), false);
+    let jit = if use_jit {
+        match executable.jit_compile() {
+            Ok(()) => Some(measure_executions(executable.as_ref(), true)),
+            Err(err) => {
+                warn!("Unable to JIT compile {}: {:?}", program, err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(ProgramReport {
+        program: program.to_string(),
+        result: profile.result,
+        instruction_count,
+        compute_units: profile.compute_units,
+        syscall_compute_units: profile.compute_units.saturating_sub(instruction_count),
+        interpreter,
+        jit,
+        functions: attribution
+            .functions
+            .into_iter()
+            .map(|(name, compute_units)| FunctionReport {
+                name,
+                compute_units,
+            })
+            .collect(),
+        syscalls: attribution
+            .syscalls
+            .into_iter()
+            .map(|(name, (calls, compute_units))| SyscallReport {
+                name,
+                calls,
+                compute_units,
+            })
+            .collect(),
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+fn write_csv(output: &mut dyn Write, reports: &[ProgramReport]) -> io::Result<()> {
+    writeln!(
+        output,
+        "program,result,instruction_count,compute_units,syscall_compute_units,\
+         interpreter_mean_us,interpreter_mips,jit_mean_us,jit_mips,functions,syscalls"
+    )?;
+    for report in reports {
+        let (jit_mean_us, jit_mips) = report
+            .jit
+            .as_ref()
+            .map(|jit| (jit.mean_us.to_string(), jit.mips.to_string()))
+            .unwrap_or_default();
+        // Each function is listed as name:compute_units, each syscall as name:calls:compute_units
+        let functions: Vec<_> = report
+            .functions
+            .iter()
+            .map(|function| format!("{}:{}", function.name, function.compute_units))
+            .collect();
+        let syscalls: Vec<_> = report
+            .syscalls
+            .iter()
+            .map(|syscall| {
+                format!(
+                    "{}:{}:{}",
+                    syscall.name, syscall.calls, syscall.compute_units
+                )
+            })
+            .collect();
+        writeln!(
+            output,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&report.program),
+            csv_field(&report.result),
+            report.instruction_count,
+            report.compute_units,
+            report.syscall_compute_units,
+            report.interpreter.mean_us,
+            report.interpreter.mips,
+            jit_mean_us,
+            jit_mips,
+            csv_field(&functions.join(" ")),
+            csv_field(&syscalls.join(" ")),
+        )?;
+    }
+    Ok(())
+}
+
+fn main() {
+    solana_logger::setup();
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("programs")
+                .index(1)
+                .value_name("PROGRAM_FILEPATH")
+                .takes_value(true)
+                .multiple(true)
+                .required(true)
+                .help("ELF shared objects of the programs to benchmark"),
+        )
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .value_name("FILE / BYTES")
+                .takes_value(true)
+                .default_value("0")
+                .help(
+                    "Input for the programs to run on, either a JSON file of accounts and \
+                     instruction data in the format of rbpf-cli, or the number of 0-valued \
+                     bytes to allocate for the program parameters",
+                ),
+        )
+        .arg(
+            Arg::with_name("iterations")
+                .long("iterations")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value("10")
+                .help("Number of timed executions of each program, per execution method"),
+        )
+        .arg(
+            Arg::with_name("no_jit")
+                .long("no-jit")
+                .takes_value(false)
+                .help("Only time the programs under the interpreter"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["json", "csv"])
+                .default_value("json")
+                .help("Format of the report"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILEPATH")
+                .takes_value(true)
+                .help("Write the report to this file [default: stdout]"),
+        )
+        .get_matches();
+
+    let programs = values_t_or_exit!(matches, "programs", String);
+    let iterations = value_t_or_exit!(matches, "iterations", usize).max(1);
+    let use_jit = !matches.is_present("no_jit");
+
+    let loader_id = bpf_loader::id();
+    let mut keyed_accounts = vec![
+        (
+            false,
+            false,
+            loader_id,
+            AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id()),
+        ),
+        (
+            false,
+            false,
+            Pubkey::new_unique(),
+            AccountSharedData::new_ref(0, 0, &loader_id),
+        ),
+    ];
+    let input = matches.value_of("input").unwrap();
+    let instruction_data = match input.parse::<usize>() {
+        Ok(allocation_size) => {
+            keyed_accounts.push((
+                false,
+                true,
+                Pubkey::new_unique(),
+                AccountSharedData::new_ref(0, allocation_size, &Pubkey::new_unique()),
+            ));
+            vec![]
+        }
+        Err(_) => {
+            let input: Input = File::open(Path::new(input))
+                .map_err(|err| err.to_string())
+                .and_then(|file| serde_json::from_reader(file).map_err(|err| err.to_string()))
+                .unwrap_or_else(|err| {
+                    eprintln!("Unable to load the input from {}: {}", input, err);
+                    exit(1);
+                });
+            for account in input.accounts {
+                let account_refcell = AccountSharedData::new_ref(
+                    account.lamports,
+                    account.data.len(),
+                    &account.owner,
+                );
+                account_refcell.borrow_mut().set_data(account.data);
+                keyed_accounts.push((
+                    account.is_signer,
+                    account.is_writable,
+                    account.key,
+                    account_refcell,
+                ));
+            }
+            input.instruction_data
+        }
+    };
+    let program_indices = [0, 1];
+    let preparation = prepare_mock_invoke_context(&program_indices, &[], &keyed_accounts);
+    let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+    invoke_context
+        .push(
+            &preparation.message,
+            &preparation.message.instructions[0],
+            &program_indices,
+            Some(&preparation.account_indices),
+        )
+        .unwrap();
+
+    let reports: Vec<_> = programs
+        .iter()
+        .map(|program| {
+            info!("Benchmarking {}", program);
+            bench_program(
+                program,
+                &mut invoke_context,
+                &instruction_data,
+                iterations,
+                use_jit,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            })
+        })
+        .collect();
+
+    let mut output: Box<dyn Write> = match matches.value_of("output") {
+        Some(path) => Box::new(File::create(path).unwrap_or_else(|err| {
+            eprintln!("Unable to create {}: {}", path, err);
+            exit(1);
+        })),
+        None => Box::new(io::stdout()),
+    };
+    let written = match matches.value_of("format").unwrap() {
+        "csv" => write_csv(&mut output, &reports),
+        _ => serde_json::to_writer_pretty(&mut output, &reports)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(output)),
+    };
+    if let Err(err) = written {
+        eprintln!("Unable to write the report: {}", err);
+        exit(1);
+    }
+}
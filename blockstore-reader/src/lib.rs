@@ -0,0 +1,241 @@
+//! Read-only access to the blockstore of a ledger directory, for tools that analyze blocks,
+//! transactions and shreds without copying the ledger.
+//!
+//! The blockstore is opened as a RocksDB secondary instance, so it can be read while a validator
+//! writes to it. The reader sees the blockstore as of when it was opened, until
+//! [`BlockstoreReader::catch_up`] is called. Each reader keeps the logs of its secondary instance
+//! in its own directory, so several readers can open the same blockstore at once.
+
+use {
+    solana_ledger::{
+        blockstore::{Blockstore, BLOCKSTORE_DIRECTORY},
+        blockstore_db::AccessType,
+    },
+    solana_sdk::{clock::Slot, signature::Signature},
+    std::{
+        io::{Error, ErrorKind},
+        path::{Path, PathBuf},
+    },
+    tempfile::TempDir,
+};
+pub use {
+    solana_ledger::{
+        blockstore_db::{BlockstoreError, Result},
+        shred::{Shred, ShredError},
+    },
+    solana_transaction_status::{ConfirmedBlock, ConfirmedTransaction, TransactionWithStatusMeta},
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct BlockstoreReaderOptions {
+    /// Directory keeping the logs of the secondary instance, which must not be used by another
+    /// reader. A temporary directory removed when the reader is dropped if unset
+    pub secondary_path: Option<PathBuf>,
+    /// Fail to open the blockstore if the open file descriptor limit can't be raised to what
+    /// RocksDB likes to have, instead of logging an error
+    pub enforce_ulimit_nofile: bool,
+}
+
+pub struct BlockstoreReader {
+    blockstore: Blockstore,
+    // Dropped after the blockstore, whose secondary instance logs to it
+    _secondary_dir: Option<TempDir>,
+}
+
+impl BlockstoreReader {
+    /// Opens the blockstore of the ledger at `ledger_path` read-only, with the default options
+    pub fn open(ledger_path: &Path) -> Result<Self> {
+        Self::open_with_options(ledger_path, BlockstoreReaderOptions::default())
+    }
+
+    /// Opens the blockstore of the ledger at `ledger_path` read-only
+    pub fn open_with_options(ledger_path: &Path, options: BlockstoreReaderOptions) -> Result<Self> {
+        // Opening the blockstore would create an empty one
+        let blockstore_path = ledger_path.join(BLOCKSTORE_DIRECTORY);
+        if !blockstore_path.exists() {
+            return Err(BlockstoreError::Io(Error::new(
+                ErrorKind::NotFound,
+                format!("No blockstore at {:?}", blockstore_path),
+            )));
+        }
+        let (secondary_path, secondary_dir) = match options.secondary_path {
+            Some(secondary_path) => (secondary_path, None),
+            None => {
+                let secondary_dir = tempfile::Builder::new()
+                    .prefix("solana-blockstore-reader")
+                    .tempdir()?;
+                (secondary_dir.path().to_path_buf(), Some(secondary_dir))
+            }
+        };
+        let blockstore = Blockstore::open_with_access_type(
+            ledger_path,
+            AccessType::Secondary(secondary_path),
+            None,
+            options.enforce_ulimit_nofile,
+        )?;
+        Ok(Self {
+            blockstore,
+            _secondary_dir: secondary_dir,
+        })
+    }
+
+    /// Makes what was written to the blockstore since it was opened or last caught up visible
+    pub fn catch_up(&self) -> Result<()> {
+        self.blockstore.try_catch_up_with_primary()
+    }
+
+    /// Highest rooted slot, or 0 if no slot is rooted
+    pub fn max_root(&self) -> Slot {
+        self.blockstore.max_root()
+    }
+
+    /// Lowest rooted slot whose block is available
+    pub fn first_available_block(&self) -> Result<Slot> {
+        self.blockstore.get_first_available_block()
+    }
+
+    /// Rooted slots, in order, from `start_slot`
+    pub fn rooted_slots(&self, start_slot: Slot) -> Result<impl Iterator<Item = Slot> + '_> {
+        self.blockstore.rooted_slot_iterator(start_slot)
+    }
+
+    /// Block of the rooted `slot`
+    pub fn block(&self, slot: Slot) -> Result<ConfirmedBlock> {
+        self.blockstore.get_rooted_block(slot, false)
+    }
+
+    /// Blocks of the rooted slots from `start_slot`. Rooted slots without a block, for example
+    /// those before the snapshot the validator started from, yield an error
+    pub fn blocks(
+        &self,
+        start_slot: Slot,
+    ) -> Result<impl Iterator<Item = Result<(Slot, ConfirmedBlock)>> + '_> {
+        Ok(self
+            .rooted_slots(start_slot)?
+            .map(move |slot| Ok((slot, self.block(slot)?))))
+    }
+
+    /// Transactions of the blocks from `start_slot`, with the slot of their block
+    pub fn transactions(
+        &self,
+        start_slot: Slot,
+    ) -> Result<impl Iterator<Item = Result<(Slot, TransactionWithStatusMeta)>> + '_> {
+        Ok(self.blocks(start_slot)?.flat_map(|block| match block {
+            Ok((slot, block)) => block
+                .transactions
+                .into_iter()
+                .map(|transaction| Ok((slot, transaction)))
+                .collect(),
+            Err(err) => vec![Err(err)],
+        }))
+    }
+
+    /// Rooted transaction with `signature`, if any
+    pub fn transaction(&self, signature: Signature) -> Result<Option<ConfirmedTransaction>> {
+        self.blockstore.get_rooted_transaction(signature)
+    }
+
+    /// Data shreds of `slot`, by index
+    pub fn data_shreds(
+        &self,
+        slot: Slot,
+    ) -> Result<impl Iterator<Item = std::result::Result<Shred, ShredError>> + '_> {
+        Ok(self
+            .blockstore
+            .slot_data_iterator(slot, 0)?
+            .map(|(_, shred)| Shred::new_from_serialized_shred(shred.to_vec())))
+    }
+
+    /// Coding shreds of `slot`, by index
+    pub fn coding_shreds(
+        &self,
+        slot: Slot,
+    ) -> Result<impl Iterator<Item = std::result::Result<Shred, ShredError>> + '_> {
+        Ok(self
+            .blockstore
+            .slot_coding_iterator(slot, 0)?
+            .map(|(_, shred)| Shred::new_from_serialized_shred(shred.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::{blockstore::make_slot_entries, get_tmp_ledger_path_auto_delete},
+    };
+
+    fn open_primary(ledger_path: &Path) -> Blockstore {
+        Blockstore::open_with_access_type(ledger_path, AccessType::PrimaryOnly, None, false)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_blockstore_reader() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        assert!(BlockstoreReader::open(ledger_path.path()).is_err());
+
+        let blockstore = open_primary(ledger_path.path());
+        let (shreds, _entries) = make_slot_entries(1, 0, 4);
+        let num_shreds = shreds.len();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(vec![0, 1].iter()).unwrap();
+
+        // The blockstore is read while the primary instance has it open
+        let reader = BlockstoreReader::open(ledger_path.path()).unwrap();
+        assert_eq!(reader.max_root(), 1);
+        assert_eq!(
+            reader.rooted_slots(0).unwrap().collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        let blocks = reader
+            .blocks(1)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].0, 1);
+        assert_eq!(blocks[0].1.parent_slot, 0);
+        assert_eq!(reader.transactions(1).unwrap().count(), 0);
+        assert!(reader.block(0).is_err());
+        assert_eq!(reader.data_shreds(1).unwrap().count(), num_shreds);
+        assert!(reader
+            .data_shreds(1)
+            .unwrap()
+            .all(|shred| shred.unwrap().slot() == 1));
+
+        let (shreds, _entries) = make_slot_entries(2, 1, 4);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(vec![2].iter()).unwrap();
+        reader.catch_up().unwrap();
+        assert_eq!(reader.max_root(), 2);
+        assert_eq!(reader.block(2).unwrap().parent_slot, 1);
+    }
+
+    #[test]
+    fn test_blockstore_readers_secondary_paths() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = open_primary(ledger_path.path());
+        blockstore.set_roots(vec![0, 1].iter()).unwrap();
+
+        // Each reader has its own secondary instance
+        let reader = BlockstoreReader::open(ledger_path.path()).unwrap();
+        let secondary_dir = tempfile::tempdir().unwrap();
+        let other_reader = BlockstoreReader::open_with_options(
+            ledger_path.path(),
+            BlockstoreReaderOptions {
+                secondary_path: Some(secondary_dir.path().join("reader")),
+                ..BlockstoreReaderOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(reader.max_root(), 1);
+        assert_eq!(other_reader.max_root(), 1);
+        assert!(secondary_dir.path().join("reader").exists());
+
+        let temp_secondary_path = reader._secondary_dir.as_ref().unwrap().path().to_path_buf();
+        assert!(temp_secondary_path.exists());
+        drop(reader);
+        assert!(!temp_secondary_path.exists());
+    }
+}
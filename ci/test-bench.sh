@@ -43,6 +43,7 @@ _ "$cargo" nightly bench --manifest-path sdk/Cargo.toml ${V:+--verbose} \
 
 # Run runtime benches
 _ "$cargo" nightly bench --manifest-path runtime/Cargo.toml ${V:+--verbose} \
+  --bench accounts_index --bench append_vec --bench bank --bench bloom --bench status_cache \
   -- -Z unstable-options --format=json | tee -a "$BENCH_FILE"
 
 # Run gossip benches
@@ -55,11 +56,19 @@ _ "$cargo" nightly bench --manifest-path poh/Cargo.toml ${V:+--verbose} \
 
 # Run core benches
 _ "$cargo" nightly bench --manifest-path core/Cargo.toml ${V:+--verbose} \
+  --bench cluster_info --bench consensus --bench gen_keys --bench retransmit_stage \
+  --bench shredder --bench sigverify_stage \
   -- -Z unstable-options --format=json | tee -a "$BENCH_FILE"
 
-# Run bpf benches
-_ "$cargo" nightly bench --manifest-path programs/bpf/Cargo.toml ${V:+--verbose} --features=bpf_c \
-  -- -Z unstable-options --format=json --nocapture | tee -a "$BENCH_FILE"
+# Run criterion benches, these don't require nightly
+export CRITERION_HOME="$PWD/target/criterion"
+rm -rf "$CRITERION_HOME"
+_ "$cargo" stable bench --manifest-path runtime/Cargo.toml ${V:+--verbose} --bench accounts
+_ "$cargo" stable bench --manifest-path core/Cargo.toml ${V:+--verbose} --bench banking_stage
+_ "$cargo" stable bench --manifest-path programs/bpf/Cargo.toml ${V:+--verbose} --features=bpf_c \
+  --bench bpf_loader
+_ "$cargo" stable run --release --package solana-upload-perf --bin solana-criterion-json \
+  -- "$CRITERION_HOME" | tee -a "$BENCH_FILE"
 
 # Run banking/accounts bench. Doesn't require nightly, but use since it is already built.
 _ "$cargo" nightly run --release --manifest-path banking-bench/Cargo.toml ${V:+--verbose} | tee -a "$BENCH_FILE"
@@ -0,0 +1,20 @@
+use {
+    crate::{input_validators, ArgConstant},
+    clap::Arg,
+};
+
+pub const COMPUTE_UNIT_PRICE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "compute_unit_price",
+    long: "with-compute-unit-price",
+    help: "Set a compute unit price for the transaction, in increments of 0.000001 lamports \n\
+           per compute unit, or `auto` to offer the median price of the recent transactions.",
+};
+
+pub fn compute_unit_price_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(COMPUTE_UNIT_PRICE_ARG.name)
+        .long(COMPUTE_UNIT_PRICE_ARG.long)
+        .takes_value(true)
+        .value_name("COMPUTE-UNIT-PRICE")
+        .validator(input_validators::is_compute_unit_price)
+        .help(COMPUTE_UNIT_PRICE_ARG.help)
+}
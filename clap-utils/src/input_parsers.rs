@@ -1,7 +1,10 @@
 use {
-    crate::keypair::{
-        keypair_from_seed_phrase, pubkey_from_path, resolve_signer_from_path, signer_from_path,
-        ASK_KEYWORD, SKIP_SEED_PHRASE_VALIDATION_ARG,
+    crate::{
+        keypair::{
+            keypair_from_seed_phrase, pubkey_from_path, resolve_signer_from_path, signer_from_path,
+            ASK_KEYWORD, SIGNER_SOURCE_FILEPATH, SKIP_SEED_PHRASE_VALIDATION_ARG,
+        },
+        offline::BLOCKHASH_ARG,
     },
     chrono::DateTime,
     clap::ArgMatches,
@@ -10,11 +13,14 @@ use {
         clock::UnixTimestamp,
         commitment_config::CommitmentConfig,
         genesis_config::ClusterType,
+        hash::Hash,
+        message::Message,
         native_token::sol_to_lamports,
+        program_utils::limited_deserialize,
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signature, Signer},
     },
-    std::{str::FromStr, sync::Arc},
+    std::{collections::HashSet, convert::TryFrom, str::FromStr, sync::Arc},
 };
 
 // Sentinel value used to indicate to write to screen instead of file
@@ -118,6 +124,143 @@ pub fn pubkeys_sigs_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<(Pubk
     })
 }
 
+// Split a signer bundle of the form `[file:]PATH[?threshold=M]` into its path and threshold.
+// Like a signer source, the bundle is parsed as a URI reference, so its query string starts at
+// the first '?'
+pub fn parse_signer_bundle(bundle: &str) -> Result<(String, Option<usize>), String> {
+    let uri = uriparse::URIReference::try_from(bundle)
+        .map_err(|_| format!("Unrecognized signer bundle: {}", bundle))?;
+    if let Some(scheme) = uri.scheme() {
+        if !scheme.as_str().eq_ignore_ascii_case(SIGNER_SOURCE_FILEPATH) {
+            return Err(format!("Unrecognized signer bundle: {}", bundle));
+        }
+    }
+    let path = uri.path().to_string();
+    let query_str = match uri.query() {
+        Some(query) if !query.as_str().is_empty() => query.as_str(),
+        _ => return Ok((path, None)),
+    };
+    let query = qstring::QString::from(query_str);
+    let threshold = match query.get("threshold") {
+        Some(threshold) if query.len() == 1 => threshold
+            .parse::<usize>()
+            .ok()
+            .filter(|threshold| *threshold > 0)
+            .ok_or_else(|| format!("Invalid signer bundle threshold: {}", threshold))?,
+        _ => {
+            return Err(format!(
+                "Invalid signer bundle query string `{}`, only `threshold` supported",
+                query_str
+            ))
+        }
+    };
+    Ok((path, Some(threshold)))
+}
+
+// Return the pubkey/signature pairs of the signer bundles at `name`, each a file saved from the
+// JSON output of `--sign-only --dump-transaction-message`. The bundles must have been signed with
+// the blockhash given on the command line, and hold at least their threshold of signatures. Each
+// signature is verified against the message of its bundle and must be of one of its signers.
+pub fn pubkeys_sigs_of_signer_bundles(
+    matches: &ArgMatches<'_>,
+    name: &str,
+) -> Result<Option<Vec<(Pubkey, Signature)>>, String> {
+    let bundles = match matches.values_of(name) {
+        Some(bundles) => bundles,
+        None => return Ok(None),
+    };
+    let blockhash = value_of::<Hash>(matches, BLOCKHASH_ARG.name);
+    let mut pubkeys_sigs = vec![];
+    for bundle in bundles {
+        let (path, threshold) = parse_signer_bundle(bundle)?;
+        let sign_only_data = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read signer bundle {}: {}", path, err))
+            .and_then(|data| {
+                serde_json::from_str::<serde_json::Value>(&data)
+                    .map_err(|err| format!("Invalid signer bundle {}: {}", path, err))
+            })?;
+
+        let message_data = sign_only_data["message"]
+            .as_str()
+            .and_then(|message| base64::decode(message).ok())
+            .ok_or_else(|| {
+                format!(
+                    "Signer bundle {} has no message, save it with --dump-transaction-message",
+                    path
+                )
+            })?;
+        let message: Message = limited_deserialize(&message_data)
+            .map_err(|_| format!("Signer bundle {} has an invalid message", path))?;
+        if let Some(blockhash) = blockhash {
+            if blockhash != message.recent_blockhash {
+                return Err(format!(
+                    "Signer bundle {} was signed with blockhash {}, not {}",
+                    path, message.recent_blockhash, blockhash
+                ));
+            }
+        }
+        let is_signer_of_message = |pubkey: &Pubkey| {
+            message
+                .account_keys
+                .iter()
+                .position(|key| key == pubkey)
+                .map_or(false, |index| message.is_signer(index))
+        };
+
+        let strings_of = |field: &str| -> Vec<String> {
+            sign_only_data[field]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let mut signed = HashSet::new();
+        for pubkey_signer_string in strings_of("signers") {
+            let (key, sig) = pubkey_signer_string
+                .split_once('=')
+                .and_then(|(key, sig)| {
+                    Some((Pubkey::from_str(key).ok()?, Signature::from_str(sig).ok()?))
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "Signer bundle {} has a malformed signer: {}",
+                        path, pubkey_signer_string
+                    )
+                })?;
+            if !is_signer_of_message(&key) {
+                return Err(format!(
+                    "Signer bundle {} has a signature of {}, which doesn't sign its message",
+                    path, key
+                ));
+            }
+            if !sig.verify(key.as_ref(), &message_data) {
+                return Err(format!(
+                    "Signer bundle {} has an invalid signature of {}",
+                    path, key
+                ));
+            }
+            signed.insert(key);
+            pubkeys_sigs.push((key, sig));
+        }
+        if let Some(threshold) = threshold {
+            if signed.len() < threshold {
+                return Err(format!(
+                    "Signer bundle {} has {} of the {} required signatures ({} signers)",
+                    path,
+                    signed.len(),
+                    threshold,
+                    signed.len() + strings_of("absent").len(),
+                ));
+            }
+        }
+    }
+    Ok(Some(pubkeys_sigs))
+}
+
 // Return a signer from matches at `name`
 #[allow(clippy::type_complexity)]
 pub fn signer_of(
@@ -198,7 +341,10 @@ pub fn commitment_of(matches: &ArgMatches<'_>, name: &str) -> Option<CommitmentC
 mod tests {
     use super::*;
     use clap::{App, Arg};
-    use solana_sdk::signature::write_keypair_file;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        signature::write_keypair_file,
+    };
     use std::fs;
 
     fn app<'ab, 'v>() -> App<'ab, 'v> {
@@ -351,6 +497,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pubkeys_sigs_of_signer_bundles() {
+        let app = || {
+            app().arg(
+                Arg::with_name(BLOCKHASH_ARG.name)
+                    .long(BLOCKHASH_ARG.long)
+                    .takes_value(true),
+            )
+        };
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        let blockhash = Hash::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            solana_sdk::pubkey::new_rand(),
+            &[],
+            vec![
+                AccountMeta::new(keypair1.pubkey(), true),
+                AccountMeta::new_readonly(keypair2.pubkey(), true),
+                AccountMeta::new_readonly(keypair3.pubkey(), true),
+            ],
+        );
+        let mut message = Message::new(&[instruction], Some(&keypair1.pubkey()));
+        message.recent_blockhash = blockhash;
+        let message_data = message.serialize();
+        let (key1, sig1) = (keypair1.pubkey(), keypair1.sign_message(&message_data));
+        let (key2, sig2) = (keypair2.pubkey(), keypair2.sign_message(&message_data));
+        let key3 = keypair3.pubkey();
+
+        let outfile = tmp_file_path("test_pubkeys_sigs_of_signer_bundles", &key1);
+        fs::create_dir_all(std::path::Path::new(&outfile).parent().unwrap()).unwrap();
+        let write_bundle = |message: Option<&[u8]>, signers: &[(Pubkey, Signature)]| {
+            let mut bundle = serde_json::json!({
+                "blockhash": blockhash.to_string(),
+                "signers": signers
+                    .iter()
+                    .map(|(key, sig)| format!("{}={}", key, sig))
+                    .collect::<Vec<_>>(),
+                "absent": [key3.to_string()],
+            });
+            if let Some(message) = message {
+                bundle["message"] = base64::encode(message).into();
+            }
+            fs::write(&outfile, bundle.to_string()).unwrap();
+        };
+        let bundles_of = |bundle: &str, blockhash: &Hash| {
+            let matches = app().get_matches_from(vec![
+                "test",
+                "--blockhash",
+                &blockhash.to_string(),
+                "--multiple",
+                bundle,
+            ]);
+            pubkeys_sigs_of_signer_bundles(&matches, "multiple")
+        };
+
+        write_bundle(Some(&message_data), &[(key1, sig1), (key2, sig2)]);
+        assert_eq!(
+            bundles_of(&outfile, &blockhash),
+            Ok(Some(vec![(key1, sig1), (key2, sig2)]))
+        );
+        assert_eq!(
+            bundles_of(&format!("{}?threshold=2", outfile), &blockhash),
+            Ok(Some(vec![(key1, sig1), (key2, sig2)]))
+        );
+        // Too few signatures for the threshold
+        assert!(bundles_of(&format!("{}?threshold=3", outfile), &blockhash).is_err());
+        // Signed with another blockhash
+        assert!(bundles_of(&outfile, &Hash::new_unique()).is_err());
+
+        // A forged signature of a signer of the message
+        let forged_sig = Keypair::new().sign_message(&message_data);
+        write_bundle(Some(&message_data), &[(key1, sig1), (key2, forged_sig)]);
+        assert!(bundles_of(&outfile, &blockhash).is_err());
+
+        // A valid signature, but of a pubkey that doesn't sign the message
+        let other_keypair = Keypair::new();
+        let other_sig = other_keypair.sign_message(&message_data);
+        write_bundle(
+            Some(&message_data),
+            &[(key1, sig1), (other_keypair.pubkey(), other_sig)],
+        );
+        assert!(bundles_of(&format!("{}?threshold=2", outfile), &blockhash).is_err());
+
+        // Without the message, the signatures can't be verified
+        write_bundle(None, &[(key1, sig1), (key2, sig2)]);
+        assert!(bundles_of(&outfile, &blockhash).is_err());
+
+        let matches = app().get_matches_from(vec!["test"]);
+        assert_eq!(
+            pubkeys_sigs_of_signer_bundles(&matches, "multiple"),
+            Ok(None)
+        );
+        fs::remove_file(&outfile).unwrap();
+    }
+
+    #[test]
+    fn test_parse_signer_bundle() {
+        assert_eq!(
+            parse_signer_bundle("bundle.json"),
+            Ok(("bundle.json".to_string(), None))
+        );
+        assert_eq!(
+            parse_signer_bundle("bundle.json?threshold=2"),
+            Ok(("bundle.json".to_string(), Some(2)))
+        );
+        assert_eq!(
+            parse_signer_bundle("file:/tmp/bundle.json?threshold=2"),
+            Ok(("/tmp/bundle.json".to_string(), Some(2)))
+        );
+        assert_eq!(
+            parse_signer_bundle("bundle.json?"),
+            Ok(("bundle.json".to_string(), None))
+        );
+        assert!(parse_signer_bundle("bundle.json?threshold=0").is_err());
+        assert!(parse_signer_bundle("bundle.json?key=0").is_err());
+        // The query string starts at the first '?', like that of a signer source
+        assert!(parse_signer_bundle("bundle?.json?threshold=2").is_err());
+        assert!(parse_signer_bundle("bundle.json?threshold=2&key=0").is_err());
+        assert!(parse_signer_bundle("usb://ledger?threshold=2").is_err());
+    }
+
     #[test]
     fn test_lamports_of_sol() {
         let matches = app()
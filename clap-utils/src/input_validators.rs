@@ -1,5 +1,8 @@
 use {
-    crate::keypair::{parse_signer_source, SignerSourceKind, ASK_KEYWORD},
+    crate::{
+        input_parsers::parse_signer_bundle,
+        keypair::{parse_signer_source, SignerSourceKind, ASK_KEYWORD},
+    },
     chrono::DateTime,
     solana_sdk::{
         clock::{Epoch, Slot},
@@ -124,6 +127,34 @@ where
     is_pubkey(string.as_ref()).or_else(|_| is_keypair(string))
 }
 
+// Return an error if string cannot be used as an address book alias: aliases can't be mistaken
+// for pubkeys, signer source URIs, amounts or existing files
+pub fn is_address_book_alias<T>(string: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    let alias = string.as_ref();
+    if alias.is_empty() {
+        Err("alias is empty".to_string())
+    } else if Pubkey::from_str(alias).is_ok() {
+        Err(format!("alias {} is a pubkey", alias))
+    } else if alias == ASK_KEYWORD
+        || alias == "-"
+        || alias.contains(|c: char| c == ':' || c == '/' || c == '\\' || c.is_whitespace())
+    {
+        Err(format!(
+            "alias {} could be mistaken for a signer source",
+            alias
+        ))
+    } else if alias.parse::<f64>().is_ok() || alias.eq_ignore_ascii_case("ALL") {
+        Err(format!("alias {} could be mistaken for an amount", alias))
+    } else if std::path::Path::new(alias).exists() {
+        Err(format!("alias {} is an existing file", alias))
+    } else {
+        Ok(())
+    }
+}
+
 // Return an error if string cannot be parsed as a pubkey string, or a valid Signer that can
 // produce a pubkey()
 pub fn is_valid_pubkey<T>(string: T) -> Result<(), String>
@@ -179,6 +210,14 @@ where
     }
 }
 
+// Return an error if a signer bundle is not of the form `PATH[?threshold=M]`
+pub fn is_signer_bundle<T>(string: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    parse_signer_bundle(string.as_ref()).map(|_| ())
+}
+
 // Return an error if a url cannot be parsed.
 pub fn is_url<T>(string: T) -> Result<(), String>
 where
@@ -316,6 +355,20 @@ where
     }
 }
 
+pub fn is_compute_unit_price<T>(price: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    if price.as_ref().parse::<u64>().is_ok() || price.as_ref() == "auto" {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unable to parse input compute unit price as integer or auto, provided: {}",
+            price
+        ))
+    }
+}
+
 pub fn is_rfc3339_datetime<T>(value: T) -> Result<(), String>
 where
     T: AsRef<str> + Display,
@@ -395,6 +448,24 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_address_book_alias() {
+        assert_eq!(is_address_book_alias("treasury"), Ok(()));
+        assert_eq!(is_address_book_alias("cold-wallet_2"), Ok(()));
+        assert!(is_address_book_alias("").is_err());
+        assert!(is_address_book_alias(Pubkey::new_unique().to_string()).is_err());
+        assert!(is_address_book_alias("ASK").is_err());
+        assert!(is_address_book_alias("-").is_err());
+        assert!(is_address_book_alias("usb://ledger").is_err());
+        assert!(is_address_book_alias("path/to/keypair.json").is_err());
+        assert!(is_address_book_alias("cold wallet").is_err());
+        assert!(is_address_book_alias("1").is_err());
+        assert!(is_address_book_alias("0.5").is_err());
+        assert!(is_address_book_alias("ALL").is_err());
+        assert!(is_address_book_alias("all").is_err());
+        assert!(is_address_book_alias("Cargo.toml").is_err());
+    }
+
     #[test]
     fn test_is_derivation() {
         assert_eq!(is_derivation("2"), Ok(()));
@@ -408,6 +479,16 @@ mod tests {
         assert!(is_derivation("0/4294967296").is_err());
     }
 
+    #[test]
+    fn test_is_compute_unit_price() {
+        assert_eq!(is_compute_unit_price("0"), Ok(()));
+        assert_eq!(is_compute_unit_price("1000"), Ok(()));
+        assert_eq!(is_compute_unit_price("auto"), Ok(()));
+        assert!(is_compute_unit_price("0.5").is_err());
+        assert!(is_compute_unit_price("-1").is_err());
+        assert!(is_compute_unit_price("ALL").is_err());
+    }
+
     #[test]
     fn test_is_niceness_adjustment_valid() {
         assert_eq!(is_niceness_adjustment_valid("0"), Ok(()));
@@ -11,8 +11,9 @@
 
 use {
     crate::{
-        input_parsers::{pubkeys_sigs_of, STDOUT_OUTFILE_TOKEN},
-        offline::{SIGNER_ARG, SIGN_ONLY_ARG},
+        input_parsers::{pubkeys_sigs_of, pubkeys_sigs_of_signer_bundles, STDOUT_OUTFILE_TOKEN},
+        input_validators::is_address_book_alias,
+        offline::{SIGNER_ARG, SIGNER_BUNDLE_ARG, SIGN_ONLY_ARG},
         ArgConstant,
     },
     bip39::{Language, Mnemonic, Seed},
@@ -36,8 +37,10 @@ use {
     },
     std::{
         cell::RefCell,
+        collections::HashMap,
         convert::TryFrom,
         error,
+        ffi::OsString,
         io::{stdin, stdout, Write},
         ops::Deref,
         process::exit,
@@ -177,7 +180,8 @@ impl DefaultSigner {
                     std::io::Error::new(
                         std::io::ErrorKind::Other,
                         format!(
-                        "No default signer found, run \"solana-keygen new -o {}\" to create a new one",
+                        "No default signer found, run \"solana-keygen new -o {}\" to create a new one, \
+                        or set a pubkey as the default signer to only run read-only commands",
                         self.path
                     ),
                     )
@@ -368,6 +372,77 @@ impl DefaultSigner {
             config,
         )
     }
+
+    /// Loads the default [Signer] of a read-only command, which only needs
+    /// its pubkey.
+    ///
+    /// This is the same as [`DefaultSigner::signer_from_path_with_config`]
+    /// with `allow_null_signer` set, so that a pubkey, or an alias of the
+    /// address book, is accepted as the default signer without a signature.
+    /// Read-only commands then run without any keypair, watching the address.
+    pub fn read_only_signer_from_path(
+        &self,
+        matches: &ArgMatches,
+        wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    ) -> Result<Box<dyn Signer>, Box<dyn std::error::Error>> {
+        let config = SignerFromPathConfig {
+            allow_null_signer: true,
+        };
+        self.signer_from_path_with_config(matches, wallet_manager, &config)
+    }
+}
+
+/// Pubkeys by alias, the aliases being accepted wherever a pubkey is.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddressBook {
+    aliases: HashMap<String, Pubkey>,
+}
+
+impl AddressBook {
+    /// Aliases that could be mistaken for anything else on the command line,
+    /// see `is_address_book_alias`, are left out.
+    pub fn new(aliases: HashMap<String, Pubkey>) -> Self {
+        let aliases = aliases
+            .into_iter()
+            .filter(|(alias, _)| is_address_book_alias(alias).is_ok())
+            .collect();
+        Self { aliases }
+    }
+
+    /// Looks up the pubkey of `alias`.
+    pub fn pubkey(&self, alias: &str) -> Option<Pubkey> {
+        self.aliases.get(alias).copied()
+    }
+
+    /// Replaces the aliases in the command line `args` by their pubkeys.
+    ///
+    /// The aliases are resolved before the arguments are matched, so that the
+    /// argument validators and parsers only ever see pubkeys. An argument is
+    /// replaced when it is an alias, or when it is a `--name=alias` option.
+    /// The first argument, the name of the binary, is left untouched.
+    pub fn resolve_args<I, T>(&self, args: I) -> Vec<OsString>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        args.next()
+            .into_iter()
+            .chain(args.map(|arg| {
+                let resolved = arg.to_str().and_then(|arg| self.resolve_arg(arg));
+                resolved.map(OsString::from).unwrap_or(arg)
+            }))
+            .collect()
+    }
+
+    fn resolve_arg(&self, arg: &str) -> Option<String> {
+        if let Some(pubkey) = self.pubkey(arg) {
+            return Some(pubkey.to_string());
+        }
+        let (name, alias) = arg.strip_prefix("--")?.split_once('=')?;
+        self.pubkey(alias)
+            .map(|pubkey| format!("--{}={}", name, pubkey))
+    }
 }
 
 pub(crate) struct SignerSource {
@@ -395,7 +470,7 @@ impl SignerSource {
 }
 
 const SIGNER_SOURCE_PROMPT: &str = "prompt";
-const SIGNER_SOURCE_FILEPATH: &str = "file";
+pub(crate) const SIGNER_SOURCE_FILEPATH: &str = "file";
 const SIGNER_SOURCE_USB: &str = "usb";
 const SIGNER_SOURCE_STDIN: &str = "stdin";
 const SIGNER_SOURCE_PUBKEY: &str = "pubkey";
@@ -521,6 +596,25 @@ pub fn presigner_from_pubkey_sigs(
     })
 }
 
+/// Returns the presigned pubkey/signature pairs of the command line, given one by one with the
+/// "signer" arguments or in files with the "signer_bundle" arguments
+fn presigners_of(
+    matches: &ArgMatches,
+) -> Result<Option<Vec<(Pubkey, Signature)>>, Box<dyn error::Error>> {
+    let presigners = pubkeys_sigs_of(matches, SIGNER_ARG.name);
+    let bundled_presigners = pubkeys_sigs_of_signer_bundles(matches, SIGNER_BUNDLE_ARG.name)?;
+    Ok(match (presigners, bundled_presigners) {
+        (None, None) => None,
+        (presigners, bundled_presigners) => Some(
+            presigners
+                .into_iter()
+                .chain(bundled_presigners)
+                .flatten()
+                .collect(),
+        ),
+    })
+}
+
 #[derive(Debug, Default)]
 pub struct SignerFromPathConfig {
     pub allow_null_signer: bool,
@@ -641,6 +735,17 @@ pub struct SignerFromPathConfig {
 /// `matches`, in which case the signer will be created with no associated
 /// signatures.
 ///
+/// The signatures may also be given in bundles, with clap arguments named
+/// "signer_bundle", as defined by [SIGNER_BUNDLE_ARG]. Each is the path to a
+/// file saved from the JSON output of a `--sign-only` run, optionally followed
+/// by `?threshold=M`, and all the pubkey/signature pairs of the file are used
+/// as if given with "signer" arguments. The bundle must have been signed with
+/// the blockhash of the "blockhash" argument, and if a threshold is given it
+/// must hold at least `M` signatures, which suits collecting the signatures of
+/// an m-of-n multisig. The bundle must include the transaction message, see
+/// `--dump-transaction-message`, and each signature must be a valid signature
+/// of that message by one of its signers.
+///
 /// Finally, if `path`, interpreted as a file path, represents a file on disk,
 /// then the signer is created by reading that file as a JSON-serialized
 /// keypair. This is the same as the `file:` URI scheme.
@@ -801,7 +906,7 @@ pub fn signer_from_path_with_config(
             }
         }
         SignerSourceKind::Pubkey(pubkey) => {
-            let presigner = pubkeys_sigs_of(matches, SIGNER_ARG.name)
+            let presigner = presigners_of(matches)?
                 .as_ref()
                 .and_then(|presigners| presigner_from_pubkey_sigs(&pubkey, presigners));
             if let Some(presigner) = presigner {
@@ -1290,6 +1395,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_address_book_resolve_args() {
+        let pubkey = Pubkey::new_unique();
+        let address_book = AddressBook::new(
+            vec!["treasury", "1", "ALL", "Cargo.toml"]
+                .into_iter()
+                .map(|alias| (alias.to_string(), pubkey))
+                .collect(),
+        );
+        assert_eq!(address_book.pubkey("treasury"), Some(pubkey));
+        assert_eq!(address_book.pubkey("unknown"), None);
+        // aliases that could be mistaken for amounts or files are left out
+        assert_eq!(address_book.pubkey("1"), None);
+        assert_eq!(address_book.pubkey("ALL"), None);
+        assert_eq!(address_book.pubkey("Cargo.toml"), None);
+
+        let resolved = address_book.resolve_args(vec![
+            "treasury",
+            "balance",
+            "treasury",
+            "--keypair=treasury",
+            "-k=treasury",
+            "--keypair",
+            "treasury",
+            "treasury.json",
+            "=treasury",
+            "1",
+            "ALL",
+        ]);
+        let pubkey = pubkey.to_string();
+        assert_eq!(
+            resolved,
+            vec![
+                "treasury".to_string(),
+                "balance".to_string(),
+                pubkey.clone(),
+                format!("--keypair={}", pubkey),
+                "-k=treasury".to_string(),
+                "--keypair".to_string(),
+                pubkey,
+                "treasury.json".to_string(),
+                "=treasury".to_string(),
+                "1".to_string(),
+                "ALL".to_string(),
+            ]
+            .into_iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn signer_from_path_with_file() -> Result<(), Box<dyn std::error::Error>> {
         let dir = TempDir::new()?;
@@ -21,6 +21,14 @@ pub const SIGNER_ARG: ArgConstant<'static> = ArgConstant {
     help: "Provide a public-key/signature pair for the transaction",
 };
 
+pub const SIGNER_BUNDLE_ARG: ArgConstant<'static> = ArgConstant {
+    name: "signer_bundle",
+    long: "signer-bundle",
+    help: "Provide the public-key/signature pairs of a file saved from the JSON output \
+           of --sign-only --dump-transaction-message. Append ?threshold=M to require at least \
+           M signatures from the file",
+};
+
 pub const DUMP_TRANSACTION_MESSAGE: ArgConstant<'static> = ArgConstant {
     name: "dump_transaction_message",
     long: "dump-transaction-message",
@@ -55,6 +63,17 @@ fn signer_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(SIGNER_ARG.help)
 }
 
+fn signer_bundle_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(SIGNER_BUNDLE_ARG.name)
+        .long(SIGNER_BUNDLE_ARG.long)
+        .takes_value(true)
+        .value_name("PATH[?threshold=M]")
+        .validator(is_signer_bundle)
+        .requires(BLOCKHASH_ARG.name)
+        .multiple(true)
+        .help(SIGNER_BUNDLE_ARG.help)
+}
+
 pub fn dump_transaction_message<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(DUMP_TRANSACTION_MESSAGE.name)
         .long(DUMP_TRANSACTION_MESSAGE.long)
@@ -73,6 +92,9 @@ pub trait ArgsConfig {
     fn signer_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
     }
+    fn signer_bundle_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
+        arg
+    }
     fn dump_transaction_message_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
     }
@@ -88,6 +110,7 @@ impl OfflineArgs for App<'_, '_> {
         self.arg(config.blockhash_arg(blockhash_arg()))
             .arg(config.sign_only_arg(sign_only_arg()))
             .arg(config.signer_arg(signer_arg()))
+            .arg(config.signer_bundle_arg(signer_bundle_arg()))
             .arg(config.dump_transaction_message_arg(dump_transaction_message()))
     }
     fn offline_args(self) -> Self {
@@ -21,6 +21,9 @@ pub struct Config {
     pub address_labels: HashMap<String, String>,
     #[serde(default)]
     pub commitment: String,
+    /// Pubkeys by alias, the aliases being accepted wherever a pubkey is
+    #[serde(default)]
+    pub address_book: HashMap<String, String>,
 }
 
 impl Default for Config {
@@ -50,6 +53,7 @@ impl Default for Config {
             keypair_path,
             address_labels,
             commitment,
+            address_book: HashMap::new(),
         }
     }
 }
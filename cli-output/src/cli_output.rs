@@ -13,7 +13,7 @@ use {
     inflector::cases::titlecase::to_title_case,
     serde::{Deserialize, Serialize},
     serde_json::{Map, Value},
-    solana_account_decoder::parse_token::UiTokenAccount,
+    solana_account_decoder::{parse_token::UiTokenAccount, UiAccountData},
     solana_clap_utils::keypair::SignOnly,
     solana_client::rpc_response::{
         RpcAccountBalance, RpcContactInfo, RpcInflationGovernor, RpcInflationRate, RpcKeyedAccount,
@@ -130,6 +130,14 @@ impl fmt::Display for CliAccount {
             "Rent Epoch:",
             &self.keyed_account.account.rent_epoch.to_string(),
         )?;
+        if let UiAccountData::Json(parsed_account) = &self.keyed_account.account.data {
+            writeln_name_value(f, "Parsed As:", &parsed_account.program)?;
+            writeln!(
+                f,
+                "{}",
+                serde_json::to_string_pretty(&parsed_account.parsed).map_err(|_| fmt::Error)?
+            )?;
+        }
         Ok(())
     }
 }
@@ -1962,6 +1970,73 @@ impl fmt::Display for CliUpgradeableProgram {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramSection {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub matched: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramHashVerification {
+    pub program_id: String,
+    pub program_location: String,
+    pub local_hash: String,
+    pub onchain_hash: String,
+    pub matched: bool,
+    pub sections: Vec<CliProgramSection>,
+}
+impl QuietDisplay for CliProgramHashVerification {}
+impl VerboseDisplay for CliProgramHashVerification {}
+impl fmt::Display for CliProgramHashVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Program Id:", &self.program_id)?;
+        writeln_name_value(f, "Local Program:", &self.program_location)?;
+        writeln_name_value(f, "Local Hash:", &self.local_hash)?;
+        writeln_name_value(f, "On-chain Hash:", &self.onchain_hash)?;
+        writeln_name_value(
+            f,
+            "Result:",
+            if self.matched {
+                "matched"
+            } else {
+                "MISMATCHED"
+            },
+        )?;
+        if !self.sections.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{}",
+                style(format!(
+                    "{:<24} {:>10} {:>10}  {}",
+                    "Section", "Offset", "Size", "Status"
+                ))
+                .bold()
+            )?;
+            for section in &self.sections {
+                writeln!(
+                    f,
+                    "{:<24} {:>#10x} {:>10}  {}",
+                    section.name,
+                    section.offset,
+                    section.size,
+                    if section.matched {
+                        "matched"
+                    } else {
+                        "MISMATCHED"
+                    }
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliUpgradeablePrograms {
@@ -2000,6 +2075,60 @@ impl fmt::Display for CliUpgradeablePrograms {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramAuthorityChange {
+    pub slot: Slot,
+    pub block_time: Option<UnixTimestamp>,
+    pub signature: String,
+    pub instruction: String,
+    /// Authority set by the instruction, "none" if the account was made immutable
+    pub authority: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramAuthorityHistory {
+    pub address: String,
+    pub authority_changes: Vec<CliProgramAuthorityChange>,
+}
+impl QuietDisplay for CliProgramAuthorityHistory {}
+impl VerboseDisplay for CliProgramAuthorityHistory {}
+impl fmt::Display for CliProgramAuthorityHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Authority History of:", &self.address)?;
+        if self.authority_changes.is_empty() {
+            writeln!(f, "No authority change found in the transaction history")?;
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "{}",
+            style(format!(
+                "{:<9} | {:<25} | {:<19} | {:<44} | {}",
+                "Slot", "Time", "Instruction", "Authority", "Signature"
+            ))
+            .bold()
+        )?;
+        for change in self.authority_changes.iter() {
+            writeln!(
+                f,
+                "{:<9} | {:<25} | {:<19} | {:<44} | {}",
+                change.slot,
+                change
+                    .block_time
+                    .map(unix_timestamp_to_string)
+                    .unwrap_or_else(|| "unknown".to_string()),
+                change.instruction,
+                change.authority,
+                change.signature,
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliUpgradeableProgramClosed {
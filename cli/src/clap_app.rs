@@ -54,7 +54,10 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
                 .value_name("KEYPAIR")
                 .global(true)
                 .takes_value(true)
-                .help("Filepath or URL to a keypair"),
+                .help(
+                    "Filepath or URL to a keypair. Read-only commands also accept a pubkey \
+                       or an address book alias",
+                ),
         )
         .arg(
             Arg::with_name("commitment")
@@ -187,6 +190,44 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
                         ),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("address-book")
+                .about("Manage the aliases accepted in place of pubkeys")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Add an alias, or replace the pubkey of an existing one")
+                        .arg(
+                            Arg::with_name("alias")
+                                .index(1)
+                                .value_name("ALIAS")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(is_address_book_alias)
+                                .help("Alias of the pubkey"),
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("pubkey")
+                                .index(2)
+                                .value_name("PUBKEY")
+                                .required(true),
+                                "Pubkey the alias stands for. "),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Remove an alias")
+                        .arg(
+                            Arg::with_name("alias")
+                                .index(1)
+                                .value_name("ALIAS")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Alias to remove"),
+                        ),
+                )
+                .subcommand(SubCommand::with_name("list").about("List the aliases")),
+        )
         .subcommand(
             SubCommand::with_name("completion")
             .about("Generate completion scripts for various shells")
@@ -1,6 +1,6 @@
 use crate::{
-    clap_app::*, cluster_query::*, feature::*, inflation::*, nonce::*, program::*, spend_utils::*,
-    stake::*, validator_info::*, vote::*, wallet::*,
+    clap_app::*, cluster_query::*, compute_unit_price::ComputeUnitPrice, feature::*, inflation::*,
+    nonce::*, program::*, spend_utils::*, stake::*, validator_info::*, vote::*, wallet::*,
 };
 use clap::{crate_description, crate_name, value_t_or_exit, ArgMatches, Shell};
 use log::*;
@@ -16,6 +16,8 @@ use solana_client::{
     nonce_utils,
     rpc_client::RpcClient,
     rpc_config::{RpcLargestAccountsFilter, RpcSendTransactionConfig, RpcTransactionLogsFilter},
+    rpc_request::RpcError,
+    transaction_confirmer::{ConfirmationOutcome, TransactionConfirmer},
 };
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
@@ -193,6 +195,7 @@ pub enum CliCommand {
     DelegateStake {
         stake_account_pubkey: Pubkey,
         vote_account_pubkey: Pubkey,
+        redelegation_stake_account: Option<SignerIndex>,
         stake_authority: SignerIndex,
         force: bool,
         sign_only: bool,
@@ -356,6 +359,14 @@ pub enum CliCommand {
         pubkey: Pubkey,
         output_file: Option<String>,
         use_lamports_unit: bool,
+        parse: bool,
+    },
+    TopUpRentExemption {
+        accounts: Vec<Pubkey>,
+        from: SignerIndex,
+        fee_payer: SignerIndex,
+        batch_size: usize,
+        dry_run: bool,
     },
     Transfer {
         amount: SpendAmount,
@@ -372,6 +383,7 @@ pub enum CliCommand {
         fee_payer: SignerIndex,
         derived_address_seed: Option<String>,
         derived_address_program_id: Option<Pubkey>,
+        compute_unit_price: Option<ComputeUnitPrice>,
     },
 }
 
@@ -737,7 +749,7 @@ pub fn parse_command(
         ("create-stake-account-checked", Some(matches)) => {
             parse_create_stake_account(matches, default_signer, wallet_manager, CHECKED)
         }
-        ("delegate-stake", Some(matches)) => {
+        ("delegate-stake", Some(matches)) | ("redelegate-stake", Some(matches)) => {
             parse_stake_delegate_stake(matches, default_signer, wallet_manager)
         }
         ("withdraw-stake", Some(matches)) => {
@@ -823,7 +835,7 @@ pub fn parse_command(
         ("account", Some(matches)) => parse_account(matches, wallet_manager),
         ("address", Some(matches)) => Ok(CliCommandInfo {
             command: CliCommand::Address,
-            signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+            signers: vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?],
         }),
         ("airdrop", Some(matches)) => parse_airdrop(matches, default_signer, wallet_manager),
         ("balance", Some(matches)) => parse_balance(matches, default_signer, wallet_manager),
@@ -845,6 +857,9 @@ pub fn parse_command(
                 signers: vec![],
             })
         }
+        ("top-up-rent-exemption", Some(matches)) => {
+            parse_top_up_rent_exemption(matches, default_signer, wallet_manager)
+        }
         ("transfer", Some(matches)) => parse_transfer(matches, default_signer, wallet_manager),
         //
         ("", None) => {
@@ -1172,6 +1187,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
         CliCommand::DelegateStake {
             stake_account_pubkey,
             vote_account_pubkey,
+            redelegation_stake_account,
             stake_authority,
             force,
             sign_only,
@@ -1186,6 +1202,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             config,
             stake_account_pubkey,
             vote_account_pubkey,
+            *redelegation_stake_account,
             *stake_authority,
             *force,
             *sign_only,
@@ -1502,7 +1519,30 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             pubkey,
             output_file,
             use_lamports_unit,
-        } => process_show_account(&rpc_client, config, pubkey, output_file, *use_lamports_unit),
+            parse,
+        } => process_show_account(
+            &rpc_client,
+            config,
+            pubkey,
+            output_file,
+            *use_lamports_unit,
+            *parse,
+        ),
+        CliCommand::TopUpRentExemption {
+            accounts,
+            from,
+            fee_payer,
+            batch_size,
+            dry_run,
+        } => process_top_up_rent_exemption(
+            &rpc_client,
+            config,
+            accounts,
+            *from,
+            *fee_payer,
+            *batch_size,
+            *dry_run,
+        ),
         CliCommand::Transfer {
             amount,
             to,
@@ -1518,6 +1558,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             fee_payer,
             derived_address_seed,
             ref derived_address_program_id,
+            compute_unit_price,
         } => process_transfer(
             &rpc_client,
             config,
@@ -1535,6 +1576,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *fee_payer,
             derived_address_seed.clone(),
             derived_address_program_id.as_ref(),
+            *compute_unit_price,
         ),
     }
 }
@@ -1545,15 +1587,22 @@ pub fn request_and_confirm_airdrop(
     to_pubkey: &Pubkey,
     lamports: u64,
 ) -> ClientResult<Signature> {
-    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let (recent_blockhash, last_valid_block_height) =
+        rpc_client.get_latest_blockhash_with_commitment(config.commitment)?;
     let signature =
         rpc_client.request_airdrop_with_blockhash(to_pubkey, lamports, &recent_blockhash)?;
-    rpc_client.confirm_transaction_with_spinner(
-        &signature,
-        &recent_blockhash,
-        config.commitment,
-    )?;
-    Ok(signature)
+    match TransactionConfirmer::new(rpc_client, config.commitment)
+        .with_websocket_url(config.websocket_url.clone())
+        .confirm(&signature, last_valid_block_height)?
+    {
+        ConfirmationOutcome::Confirmed { .. } => Ok(signature),
+        ConfirmationOutcome::Failed { err, .. } => Err(err.into()),
+        ConfirmationOutcome::Expired => Err(RpcError::ForUser(format!(
+            "Airdrop transaction {} expired before being confirmed",
+            signature
+        ))
+        .into()),
+    }
 }
 
 fn common_error_adapter<E>(ix_error: &InstructionError) -> Option<E>
@@ -1617,7 +1666,10 @@ mod tests {
     };
     use solana_sdk::{
         pubkey::Pubkey,
-        signature::{keypair_from_seed, read_keypair_file, write_keypair_file, Keypair, Presigner},
+        signature::{
+            keypair_from_seed, read_keypair_file, write_keypair_file, Keypair, NullSigner,
+            Presigner,
+        },
         stake, system_program,
         transaction::TransactionError,
     };
@@ -1787,6 +1839,19 @@ mod tests {
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
         );
+        // Watch-only, with a pubkey as the default signer
+        let watch_only_signer = DefaultSigner::new("", &pubkey_string);
+        let null_signer: Box<dyn Signer> = Box::new(NullSigner::new(&pubkey));
+        assert_eq!(
+            parse_command(&test_balance, &watch_only_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Balance {
+                    pubkey: None,
+                    use_lamports_unit: true,
+                },
+                signers: vec![null_signer],
+            }
+        );
 
         // Test Confirm Subcommand
         let signature = Signature::new(&[1; 64]);
@@ -2269,6 +2334,61 @@ mod tests {
         assert!(process_command(&config).is_err());
     }
 
+    #[test]
+    fn test_parse_top_up_rent_exemption_subcommand() {
+        let test_commands = get_clap_app("test", "desc", "version");
+
+        let default_keypair = Keypair::new();
+        let default_keypair_file = make_tmp_path("keypair_file");
+        write_keypair_file(&default_keypair, &default_keypair_file).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        // The list of `solana-ledger-tool accounts --rent-paying` is accepted as is
+        let account1 = solana_sdk::pubkey::new_rand();
+        let account2 = solana_sdk::pubkey::new_rand();
+        let accounts_file = make_tmp_path("accounts_file");
+        std::fs::write(
+            &accounts_file,
+            format!(
+                "# {}: 2 rent-paying accounts, 0.1 SOL needed for rent exemption\n{} 42\n\n{}\n",
+                Pubkey::default(),
+                account1,
+                account2
+            ),
+        )
+        .unwrap();
+        let test_top_up = test_commands.clone().get_matches_from(vec![
+            "test",
+            "top-up-rent-exemption",
+            &accounts_file,
+            "--batch-size",
+            "5",
+        ]);
+        assert_eq!(
+            parse_command(&test_top_up, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::TopUpRentExemption {
+                    accounts: vec![account1, account2],
+                    from: 0,
+                    fee_payer: 0,
+                    batch_size: 5,
+                    dry_run: false,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        // Invalid addresses are rejected
+        std::fs::write(&accounts_file, "not-an-address\n").unwrap();
+        let test_top_up = test_commands.clone().get_matches_from(vec![
+            "test",
+            "top-up-rent-exemption",
+            &accounts_file,
+            "--dry-run",
+        ]);
+        assert!(parse_command(&test_top_up, &default_signer, &mut None).is_err());
+    }
+
     #[test]
     fn test_parse_transfer_subcommand() {
         let test_commands = get_clap_app("test", "desc", "version");
@@ -2306,6 +2426,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -2333,10 +2454,56 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        // Test Transfer with a compute unit price
+        let test_transfer = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transfer",
+            "--with-compute-unit-price",
+            "auto",
+            &to_string,
+            "42",
+        ]);
+        assert_eq!(
+            parse_command(&test_transfer, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transfer {
+                    amount: SpendAmount::Some(42_000_000_000),
+                    to: to_pubkey,
+                    from: 0,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    allow_unfunded_recipient: false,
+                    no_wait: false,
+                    blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                    derived_address_seed: None,
+                    derived_address_program_id: None,
+                    compute_unit_price: Some(ComputeUnitPrice::Auto),
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
         );
+        let test_transfer = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transfer",
+            "--with-compute-unit-price",
+            "auto",
+            "--sign-only",
+            "--blockhash",
+            &Hash::default().to_string(),
+            &to_string,
+            "42",
+        ]);
+        assert!(parse_command(&test_transfer, &default_signer, &mut None).is_err());
 
         // Test Transfer no-wait and --allow-unfunded-recipient
         let test_transfer = test_commands.clone().get_matches_from(vec![
@@ -2365,6 +2532,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -2400,6 +2568,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -2443,6 +2612,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
                 },
                 signers: vec![Presigner::new(&from_pubkey, &from_sig).into()],
             }
@@ -2487,6 +2657,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: None,
                     derived_address_program_id: None,
+                    compute_unit_price: None,
                 },
                 signers: vec![
                     read_keypair_file(&default_keypair_file).unwrap().into(),
@@ -2526,6 +2697,7 @@ mod tests {
                     fee_payer: 0,
                     derived_address_seed: Some(derived_address_seed),
                     derived_address_program_id: Some(stake::program::id()),
+                    compute_unit_price: None,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
             }
@@ -532,7 +532,7 @@ pub fn parse_cluster_ping(
             blockhash,
             print_timestamp,
         },
-        signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        signers: vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?],
     })
 }
 
@@ -1560,6 +1560,8 @@ pub fn process_logs(config: &CliConfig, filter: &RpcTransactionLogsFilter) -> Pr
             RpcTransactionLogsFilter::AllWithVotes => " (including votes)".into(),
             RpcTransactionLogsFilter::Mentions(addresses) =>
                 format!(" mentioning {}", addresses.join(",")),
+            RpcTransactionLogsFilter::Programs(program_ids) =>
+                format!(" invoking {}", program_ids.join(",")),
         },
         config.commitment.commitment
     );
@@ -1575,6 +1577,12 @@ pub fn process_logs(config: &CliConfig, filter: &RpcTransactionLogsFilter) -> Pr
     loop {
         match receiver.recv() {
             Ok(logs) => {
+                if let Some(dropped_notifications) = logs.value.dropped_notifications {
+                    println!(
+                        "{} transactions dropped by the RPC node to cap the notification rate",
+                        dropped_notifications
+                    );
+                }
                 println!("Transaction executed in slot {}:", logs.context.slot);
                 println!("  Signature: {}", logs.value.signature);
                 println!(
@@ -2141,7 +2149,7 @@ pub fn process_calculate_rent(
 mod tests {
     use super::*;
     use crate::{clap_app::get_clap_app, cli::parse_command};
-    use solana_sdk::signature::{write_keypair, Keypair};
+    use solana_sdk::signature::{write_keypair, Keypair, NullSigner, Signer};
     use std::str::FromStr;
     use tempfile::NamedTempFile;
 
@@ -2312,5 +2320,27 @@ mod tests {
                 signers: vec![default_keypair.into()],
             }
         );
+
+        // Watch-only, with a pubkey as the default signer
+        let watch_only_pubkey = Pubkey::new_unique();
+        let watch_only_signer = DefaultSigner::new("", &watch_only_pubkey.to_string());
+        let test_ping = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "ping", "-c", "2"]);
+        let null_signer: Box<dyn Signer> = Box::new(NullSigner::new(&watch_only_pubkey));
+        assert_eq!(
+            parse_command(&test_ping, &watch_only_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Ping {
+                    lamports: 1,
+                    interval: Duration::from_secs(2),
+                    count: Some(2),
+                    timeout: Duration::from_secs(15),
+                    blockhash: None,
+                    print_timestamp: false,
+                },
+                signers: vec![null_signer],
+            }
+        );
     }
 }
@@ -0,0 +1,177 @@
+use {
+    clap::ArgMatches,
+    solana_clap_utils::compute_unit_price::COMPUTE_UNIT_PRICE_ARG,
+    solana_client::{
+        client_error::Result as ClientResult, rpc_client::RpcClient, rpc_config::RpcBlockConfig,
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        compute_budget::{get_compute_unit_price, ComputeBudgetInstruction},
+        instruction::Instruction,
+        transaction::Transaction,
+    },
+    solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
+};
+
+/// Number of recent blocks whose transactions are sampled to discover the compute unit price
+const RECENT_BLOCKS: usize = 4;
+/// Number of recent slots searched for these blocks, tolerating skipped slots
+const RECENT_SLOTS: u64 = 16;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ComputeUnitPrice {
+    /// Offer this many micro-lamports per compute unit
+    MicroLamports(u64),
+    /// Offer the median price of the recent non-vote transactions
+    Auto,
+}
+
+impl ComputeUnitPrice {
+    pub fn new_from_matches(matches: &ArgMatches<'_>) -> Option<Self> {
+        matches
+            .value_of(COMPUTE_UNIT_PRICE_ARG.name)
+            .and_then(|price| match price {
+                "auto" => Some(Self::Auto),
+                price => price.parse().ok().map(Self::MicroLamports),
+            })
+    }
+
+    /// Returns the price to offer, in micro-lamports per compute unit
+    pub fn resolve(self, rpc_client: &RpcClient) -> ClientResult<u64> {
+        match self {
+            Self::MicroLamports(micro_lamports) => Ok(micro_lamports),
+            Self::Auto => get_recent_compute_unit_price(rpc_client),
+        }
+    }
+}
+
+pub trait WithComputeUnitPrice {
+    fn with_compute_unit_price(self, micro_lamports: Option<u64>) -> Self;
+}
+
+impl WithComputeUnitPrice for Vec<Instruction> {
+    fn with_compute_unit_price(mut self, micro_lamports: Option<u64>) -> Self {
+        if let Some(micro_lamports) = micro_lamports {
+            // The runtime only honors compute budget instructions among the first instructions
+            self.insert(
+                0,
+                ComputeBudgetInstruction::set_compute_unit_price(micro_lamports),
+            );
+        }
+        self
+    }
+}
+
+/// Returns the price a transaction offers per compute unit, zero if it doesn't set one
+fn compute_unit_price_of(transaction: &Transaction) -> u64 {
+    let message = &transaction.message;
+    get_compute_unit_price(message.instructions.iter().filter_map(|instruction| {
+        message
+            .account_keys
+            .get(usize::from(instruction.program_id_index))
+            .map(|program_id| (program_id, instruction))
+    }))
+}
+
+fn is_vote_transaction(transaction: &Transaction) -> bool {
+    let message = &transaction.message;
+    message.instructions.iter().any(|instruction| {
+        message
+            .account_keys
+            .get(usize::from(instruction.program_id_index))
+            .map(solana_vote_program::check_id)
+            .unwrap_or(false)
+    })
+}
+
+fn median_compute_unit_price(mut prices: Vec<u64>) -> u64 {
+    prices.sort_unstable();
+    prices.get(prices.len() / 2).copied().unwrap_or_default()
+}
+
+/// Discovers the price to offer per compute unit, the median of the prices the non-vote
+/// transactions of the most recent confirmed blocks offered
+pub fn get_recent_compute_unit_price(rpc_client: &RpcClient) -> ClientResult<u64> {
+    let commitment = CommitmentConfig::confirmed();
+    let slot = rpc_client.get_slot_with_commitment(commitment)?;
+    let slots = rpc_client.get_blocks_with_commitment(
+        slot.saturating_sub(RECENT_SLOTS),
+        Some(slot),
+        commitment,
+    )?;
+
+    let mut prices = vec![];
+    for slot in slots.into_iter().rev().take(RECENT_BLOCKS) {
+        let block = rpc_client.get_block_with_config(
+            slot,
+            RpcBlockConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                transaction_details: Some(TransactionDetails::Full),
+                rewards: Some(false),
+                commitment: Some(commitment),
+            },
+        )?;
+        prices.extend(
+            block
+                .transactions
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|transaction| transaction.transaction.decode())
+                .filter(|transaction| !is_vote_transaction(transaction))
+                .map(|transaction| compute_unit_price_of(&transaction)),
+        );
+    }
+    Ok(median_compute_unit_price(prices))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{pubkey::Pubkey, system_instruction},
+    };
+
+    #[test]
+    fn test_with_compute_unit_price() {
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&from, &to, 42);
+
+        let instructions = vec![transfer.clone()].with_compute_unit_price(None);
+        assert_eq!(instructions, vec![transfer.clone()]);
+
+        let instructions = vec![transfer.clone()].with_compute_unit_price(Some(7));
+        assert_eq!(
+            instructions,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+                transfer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_unit_price_of() {
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&from, &to, 42);
+
+        let transaction = Transaction::new_with_payer(
+            &vec![transfer.clone()].with_compute_unit_price(Some(1_000)),
+            Some(&from),
+        );
+        assert_eq!(compute_unit_price_of(&transaction), 1_000);
+        assert!(!is_vote_transaction(&transaction));
+
+        let transaction = Transaction::new_with_payer(&[transfer], Some(&from));
+        assert_eq!(compute_unit_price_of(&transaction), 0);
+    }
+
+    #[test]
+    fn test_median_compute_unit_price() {
+        assert_eq!(median_compute_unit_price(vec![]), 0);
+        assert_eq!(median_compute_unit_price(vec![5]), 5);
+        assert_eq!(median_compute_unit_price(vec![9, 0, 3]), 3);
+        assert_eq!(median_compute_unit_price(vec![0, 0, 10, 20]), 10);
+    }
+}
@@ -1,21 +1,29 @@
 use crate::{
     cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
+    nonce::check_nonce_account,
     spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
 };
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use console::style;
 use serde::{Deserialize, Serialize};
-use solana_clap_utils::{input_parsers::*, input_validators::*, keypair::*};
-use solana_cli_output::{QuietDisplay, VerboseDisplay};
-use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_clap_utils::{
+    fee_payer::*, input_parsers::*, input_validators::*, keypair::*, nonce::*, offline::*,
+};
+use solana_cli_output::{
+    return_signers_with_config, QuietDisplay, ReturnSignersConfig, VerboseDisplay,
+};
+use solana_client::{
+    blockhash_query::BlockhashQuery, client_error::ClientError, nonce_utils, rpc_client::RpcClient,
+};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account,
-    clock::Slot,
+    clock::{Epoch, Slot, DEFAULT_MS_PER_SLOT},
     feature::{self, Feature},
     feature_set::FEATURE_NAMES,
     message::Message,
     pubkey::Pubkey,
+    rent::Rent,
     transaction::Transaction,
 };
 use std::{
@@ -23,6 +31,8 @@ use std::{
     collections::{HashMap, HashSet},
     fmt,
     sync::Arc,
+    thread::sleep,
+    time::Duration,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -40,6 +50,13 @@ pub enum FeatureCliCommand {
     Activate {
         feature: Pubkey,
         force: ForceActivation,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        fee_payer: SignerIndex,
+        at_epoch: Option<Epoch>,
     },
 }
 
@@ -151,7 +168,23 @@ impl FeatureSubCommands for App<'_, '_> {
                                 .hidden(true)
                                 .multiple(true)
                                 .help("Override activation sanity checks. Don't use this flag"),
-                        ),
+                        )
+                        .arg(
+                            Arg::with_name("at_epoch")
+                                .long("at-epoch")
+                                .value_name("EPOCH")
+                                .takes_value(true)
+                                .validator(is_parsable::<Epoch>)
+                                .conflicts_with(SIGN_ONLY_ARG.name)
+                                .help(
+                                    "Wait until EPOCH begins before submitting the activation. \
+                                     The feature becomes active at the following epoch boundary. \
+                                     Use a durable nonce to submit a transaction signed in advance",
+                                ),
+                        )
+                        .offline_args()
+                        .nonce_args(false)
+                        .arg(fee_payer_arg()),
                 ),
         )
     }
@@ -176,7 +209,15 @@ pub fn parse_feature_subcommand(
     let response = match matches.subcommand() {
         ("activate", Some(matches)) => {
             let (feature_signer, feature) = signer_of(matches, "feature", wallet_manager)?;
-            let mut signers = vec![default_signer.signer_from_path(matches, wallet_manager)?];
+            let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+            let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+            let blockhash_query = BlockhashQuery::new_from_matches(matches);
+            let nonce_account = pubkey_of_signer(matches, NONCE_ARG.name, wallet_manager)?;
+            let (nonce_authority, nonce_authority_pubkey) =
+                signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+            let (fee_payer, fee_payer_pubkey) =
+                signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+            let at_epoch = value_of(matches, "at_epoch");
 
             let force = match matches.occurrences_of("force") {
                 2 => ForceActivation::Yes,
@@ -184,14 +225,29 @@ pub fn parse_feature_subcommand(
                 _ => ForceActivation::No,
             };
 
-            signers.push(feature_signer.unwrap());
             let feature = feature.unwrap();
-
             known_feature(&feature)?;
 
+            let mut bulk_signers = vec![fee_payer, feature_signer];
+            if nonce_account.is_some() {
+                bulk_signers.push(nonce_authority);
+            }
+            let signer_info =
+                default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
             CliCommandInfo {
-                command: CliCommand::Feature(FeatureCliCommand::Activate { feature, force }),
-                signers,
+                command: CliCommand::Feature(FeatureCliCommand::Activate {
+                    feature,
+                    force,
+                    sign_only,
+                    dump_transaction_message,
+                    blockhash_query,
+                    nonce_account,
+                    nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+                    fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+                    at_epoch,
+                }),
+                signers: signer_info.signers,
             }
         }
         ("status", Some(matches)) => {
@@ -221,9 +277,29 @@ pub fn process_feature_subcommand(
 ) -> ProcessResult {
     match feature_subcommand {
         FeatureCliCommand::Status { features } => process_status(rpc_client, config, features),
-        FeatureCliCommand::Activate { feature, force } => {
-            process_activate(rpc_client, config, *feature, *force)
-        }
+        FeatureCliCommand::Activate {
+            feature,
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            fee_payer,
+            at_epoch,
+        } => process_activate(
+            rpc_client,
+            config,
+            *feature,
+            *force,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
+            *fee_payer,
+            *at_epoch,
+        ),
     }
 }
 
@@ -557,56 +633,128 @@ fn process_status(
     Ok(config.output_format.formatted_string(&feature_set))
 }
 
+/// Block until `epoch` has begun
+fn wait_for_epoch(rpc_client: &RpcClient, epoch: Epoch) -> Result<(), Box<dyn std::error::Error>> {
+    let epoch_info = rpc_client.get_epoch_info()?;
+    if epoch_info.epoch > epoch {
+        return Err(format!(
+            "Epoch {} has already passed, the current epoch is {}",
+            epoch, epoch_info.epoch
+        )
+        .into());
+    }
+    if epoch_info.epoch < epoch {
+        println!(
+            "Waiting for epoch {} to begin (current epoch {}, slot {})",
+            epoch, epoch_info.epoch, epoch_info.absolute_slot
+        );
+    }
+    loop {
+        let epoch_info = rpc_client.get_epoch_info()?;
+        if epoch_info.epoch >= epoch {
+            return Ok(());
+        }
+        let remaining_slots = (epoch - epoch_info.epoch - 1)
+            .saturating_mul(epoch_info.slots_in_epoch)
+            .saturating_add(epoch_info.slots_in_epoch - epoch_info.slot_index);
+        // Poll more often as the boundary approaches so the activation lands early in the epoch
+        let wait_ms = (remaining_slots.saturating_mul(DEFAULT_MS_PER_SLOT) / 2).clamp(400, 60_000);
+        sleep(Duration::from_millis(wait_ms));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn process_activate(
     rpc_client: &RpcClient,
     config: &CliConfig,
     feature_id: Pubkey,
     force: ForceActivation,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
+    fee_payer: SignerIndex,
+    at_epoch: Option<Epoch>,
 ) -> ProcessResult {
-    let account = rpc_client
-        .get_multiple_accounts(&[feature_id])?
-        .into_iter()
-        .next()
-        .unwrap();
+    if !sign_only {
+        let account = rpc_client
+            .get_multiple_accounts(&[feature_id])?
+            .into_iter()
+            .next()
+            .unwrap();
 
-    if let Some(account) = account {
-        if feature::from_account(&account).is_some() {
-            return Err(format!("{} has already been activated", feature_id).into());
+        if let Some(account) = account {
+            if feature::from_account(&account).is_some() {
+                return Err(format!("{} has already been activated", feature_id).into());
+            }
+        }
+
+        if !feature_activation_allowed(rpc_client, false)? {
+            match force {
+            ForceActivation::Almost =>
+                return Err("Add force argument once more to override the sanity check to force feature activation ".into()),
+            ForceActivation::Yes => println!("FEATURE ACTIVATION FORCED"),
+            ForceActivation::No =>
+                return Err("Feature activation is not allowed at this time".into()),
+            }
         }
-    }
 
-    if !feature_activation_allowed(rpc_client, false)? {
-        match force {
-        ForceActivation::Almost =>
-            return Err("Add force argument once more to override the sanity check to force feature activation ".into()),
-        ForceActivation::Yes => println!("FEATURE ACTIVATION FORCED"),
-        ForceActivation::No =>
-            return Err("Feature activation is not allowed at this time".into()),
+        if let Some(at_epoch) = at_epoch {
+            wait_for_epoch(rpc_client, at_epoch)?;
         }
     }
 
-    let rent = rpc_client.get_minimum_balance_for_rent_exemption(Feature::size_of())?;
+    // The cluster can't be queried when signing offline, so assume it uses the default rent
+    let rent = if sign_only {
+        Rent::default().minimum_balance(Feature::size_of())
+    } else {
+        rpc_client.get_minimum_balance_for_rent_exemption(Feature::size_of())?
+    };
 
-    let blockhash = rpc_client.get_latest_blockhash()?;
+    let fee_payer = config.signers[fee_payer];
+    let nonce_authority = config.signers[nonce_authority];
+    let blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
     let (message, _) = resolve_spend_tx_and_check_account_balance(
         rpc_client,
-        false,
+        sign_only,
         SpendAmount::Some(rent),
         &blockhash,
-        &config.signers[0].pubkey(),
+        &fee_payer.pubkey(),
         |lamports| {
-            Message::new(
-                &feature::activate_with_lamports(
-                    &feature_id,
-                    &config.signers[0].pubkey(),
-                    lamports,
-                ),
-                Some(&config.signers[0].pubkey()),
-            )
+            let instructions =
+                feature::activate_with_lamports(&feature_id, &fee_payer.pubkey(), lamports);
+            if let Some(nonce_account) = &nonce_account {
+                Message::new_with_nonce(
+                    instructions,
+                    Some(&fee_payer.pubkey()),
+                    nonce_account,
+                    &nonce_authority.pubkey(),
+                )
+            } else {
+                Message::new(&instructions, Some(&fee_payer.pubkey()))
+            }
         },
         config.commitment,
     )?;
     let mut transaction = Transaction::new_unsigned(message);
+
+    if sign_only {
+        transaction.try_partial_sign(&config.signers, blockhash)?;
+        return return_signers_with_config(
+            &transaction,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        );
+    }
+
+    if let Some(nonce_account) = &nonce_account {
+        let nonce_account =
+            nonce_utils::get_account_with_commitment(rpc_client, nonce_account, config.commitment)?;
+        check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &blockhash)?;
+    }
     transaction.try_sign(&config.signers, blockhash)?;
 
     println!(
@@ -3,6 +3,7 @@ macro_rules! ACCOUNT_STRING {
     () => {
         r#", one of:
   * a base58-encoded public key
+  * an alias of the address book
   * a path to a keypair file
   * a hyphen; signals a JSON-encoded keypair on stdin
   * the 'ASK' keyword; to recover a keypair via its seed phrase
@@ -27,6 +28,7 @@ pub mod checks;
 pub mod clap_app;
 pub mod cli;
 pub mod cluster_query;
+pub mod compute_unit_price;
 pub mod feature;
 pub mod inflation;
 pub mod memo;
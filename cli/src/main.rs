@@ -1,19 +1,27 @@
-use clap::{crate_description, crate_name, value_t_or_exit, ArgMatches};
+use clap::{crate_description, crate_name, value_t_or_exit, App, ArgMatches};
 use console::style;
 use solana_clap_utils::{
+    input_parsers::pubkey_of_signer,
     input_validators::normalize_to_url_if_moniker,
-    keypair::{CliSigners, DefaultSigner},
+    keypair::{AddressBook, CliSigners, DefaultSigner},
     DisplayError,
 };
 use solana_cli::{
     clap_app::get_clap_app,
     cli::{parse_command, process_command, CliCommandInfo, CliConfig, SettingType},
 };
-use solana_cli_config::Config;
+use solana_cli_config::{Config, CONFIG_FILE};
 use solana_cli_output::{display::println_name_value, OutputFormat};
 use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
-use std::{collections::HashMap, error, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    error,
+    ffi::OsString,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
 pub fn println_name_value_or(name: &str, value: &str, setting_type: SettingType) {
     let description = match setting_type {
@@ -142,11 +150,119 @@ fn parse_settings(matches: &ArgMatches<'_>) -> Result<bool, Box<dyn error::Error
             }
             false
         }
+        ("address-book", Some(matches)) => {
+            let config_file = match matches.value_of("config_file") {
+                None => {
+                    println!(
+                        "{} Either provide the `--config` arg or ensure home directory exists to use the default config location",
+                        style("No config file found.").bold()
+                    );
+                    return Ok(false);
+                }
+                Some(config_file) => config_file,
+            };
+            let mut config = Config::load(config_file).unwrap_or_default();
+
+            match matches.subcommand() {
+                ("add", Some(subcommand_matches)) => {
+                    let alias = subcommand_matches.value_of("alias").unwrap();
+                    if command_names().contains(alias) {
+                        return Err(format!("Alias {} is the name of a command", alias).into());
+                    }
+                    let pubkey =
+                        pubkey_of_signer(subcommand_matches, "pubkey", &mut None)?.unwrap();
+                    config
+                        .address_book
+                        .insert(alias.to_string(), pubkey.to_string());
+                    config.save(config_file)?;
+                    println_name_value(&format!("{}:", alias), &pubkey.to_string());
+                }
+                ("remove", Some(subcommand_matches)) => {
+                    let alias = subcommand_matches.value_of("alias").unwrap();
+                    if config.address_book.remove(alias).is_none() {
+                        return Err(format!("Alias {} is not in the address book", alias).into());
+                    }
+                    config.save(config_file)?;
+                    println!("Alias {} removed", alias);
+                }
+                ("list", Some(_subcommand_matches)) => {
+                    let mut address_book: Vec<_> = config.address_book.iter().collect();
+                    address_book.sort();
+                    for (alias, pubkey) in address_book {
+                        println_name_value(&format!("{}:", alias), pubkey);
+                    }
+                }
+                _ => unreachable!(),
+            }
+            false
+        }
         _ => true,
     };
     Ok(parse_args)
 }
 
+fn get_app<'ab, 'v>() -> App<'ab, 'v> {
+    get_clap_app(
+        crate_name!(),
+        crate_description!(),
+        solana_version::version!(),
+    )
+}
+
+// The names of the commands and of their aliases, at any depth, which address book aliases can't
+// shadow since they're resolved across the whole command line
+fn command_names() -> HashSet<String> {
+    fn add_command_names(app: &App, names: &mut HashSet<String>) {
+        for subcommand in &app.p.subcommands {
+            names.insert(subcommand.p.meta.name.clone());
+            names.extend(
+                subcommand
+                    .p
+                    .meta
+                    .aliases
+                    .iter()
+                    .flatten()
+                    .map(|(alias, _visible)| alias.to_string()),
+            );
+            add_command_names(subcommand, names);
+        }
+    }
+    let mut names = HashSet::new();
+    names.insert("help".to_string());
+    add_command_names(&get_app(), &mut names);
+    names
+}
+
+// The aliases of the address book are replaced by their pubkeys before the arguments are parsed,
+// so the address book is loaded from the config file the raw arguments point to
+fn load_address_book(args: &[OsString]) -> AddressBook {
+    let mut args = args.iter().skip(1).filter_map(|arg| arg.to_str());
+    let mut config_file = CONFIG_FILE.clone();
+    while let Some(arg) = args.next() {
+        if arg == "-C" || arg == "--config" {
+            config_file = args.next().map(|arg| arg.to_string());
+            break;
+        } else if let Some(value) = arg.strip_prefix("--config=") {
+            config_file = Some(value.to_string());
+            break;
+        }
+    }
+    config_file
+        .and_then(|config_file| Config::load(&config_file).ok())
+        .map(|config| {
+            let command_names = command_names();
+            AddressBook::new(
+                config
+                    .address_book
+                    .iter()
+                    .filter(|(alias, _pubkey)| !command_names.contains(*alias))
+                    .filter_map(|(alias, pubkey)| Some((alias.clone(), pubkey.parse().ok()?)))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
 pub fn parse_args<'a>(
     matches: &ArgMatches<'_>,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
@@ -189,10 +305,8 @@ pub fn parse_args<'a>(
     } = parse_command(matches, &default_signer, wallet_manager)?;
 
     if signers.is_empty() {
-        if let Ok(signer_info) =
-            default_signer.generate_unique_signers(vec![None], matches, wallet_manager)
-        {
-            signers.extend(signer_info.signers);
+        if let Ok(signer) = default_signer.read_only_signer_from_path(matches, wallet_manager) {
+            signers.push(signer);
         }
     }
 
@@ -235,12 +349,14 @@ pub fn parse_args<'a>(
 
 fn main() -> Result<(), Box<dyn error::Error>> {
     solana_logger::setup_with_default("off");
-    let matches = get_clap_app(
-        crate_name!(),
-        crate_description!(),
-        solana_version::version!(),
-    )
-    .get_matches();
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let address_book = load_address_book(&args);
+    // The aliases are kept as typed while the address book itself is edited
+    let args = match get_app().get_matches_from_safe(&args) {
+        Ok(matches) if matches.subcommand_name() == Some("address-book") => args,
+        _ => address_book.resolve_args(args),
+    };
+    let matches = get_app().get_matches_from(args);
 
     do_main(&matches).map_err(|err| DisplayError::new_as_boxed(err).into())
 }
@@ -4,28 +4,36 @@ use crate::{
         log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError,
         ProcessResult,
     },
+    compute_unit_price::{ComputeUnitPrice, WithComputeUnitPrice},
+    feature::get_feature_is_active,
 };
 use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use log::*;
 use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_bpf_loader_program::{syscalls::register_syscalls, BpfError, ThisInstructionMeter};
-use solana_clap_utils::{self, input_parsers::*, input_validators::*, keypair::*};
+use solana_clap_utils::{
+    self, compute_unit_price::compute_unit_price_arg, input_parsers::*, input_validators::*,
+    keypair::*,
+};
 use solana_cli_output::{
-    CliProgram, CliProgramAccountType, CliProgramAuthority, CliProgramBuffer, CliProgramId,
-    CliUpgradeableBuffer, CliUpgradeableBuffers, CliUpgradeableProgram,
+    CliProgram, CliProgramAccountType, CliProgramAuthority, CliProgramAuthorityChange,
+    CliProgramAuthorityHistory, CliProgramBuffer, CliProgramHashVerification, CliProgramId,
+    CliProgramSection, CliUpgradeableBuffer, CliUpgradeableBuffers, CliUpgradeableProgram,
     CliUpgradeableProgramClosed, CliUpgradeablePrograms,
 };
 use solana_client::{
     client_error::ClientErrorKind,
-    rpc_client::RpcClient,
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
     rpc_config::RpcSendTransactionConfig,
-    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+    rpc_request::MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
     tpu_client::{TpuClient, TpuClientConfig},
 };
 use solana_program_runtime::invoke_context::ThisInvokeContext;
 use solana_rbpf::{
+    static_analysis::Analysis,
     verifier,
     vm::{Config, Executable},
 };
@@ -35,12 +43,18 @@ use solana_sdk::{
     account_utils::StateMut,
     bpf_loader, bpf_loader_deprecated,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    commitment_config::CommitmentConfig,
+    feature_set,
+    hash::hash,
+    instruction::CompiledInstruction,
     instruction::Instruction,
     instruction::InstructionError,
     loader_instruction,
+    loader_upgradeable_instruction::UpgradeableLoaderInstruction,
     message::Message,
     native_token::Sol,
     packet::PACKET_DATA_SIZE,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     signature::{keypair_from_seed, read_keypair_file, Keypair, Signature, Signer},
     system_instruction::{self, SystemError},
@@ -48,7 +62,9 @@ use solana_sdk::{
     transaction::Transaction,
     transaction::TransactionError,
 };
+use solana_transaction_status::UiTransactionEncoding;
 use std::{
+    collections::BTreeSet,
     fs::File,
     io::{Read, Write},
     mem::size_of,
@@ -69,6 +85,7 @@ pub enum ProgramCliCommand {
         is_final: bool,
         max_len: Option<usize>,
         allow_excessive_balance: bool,
+        compute_unit_price: Option<ComputeUnitPrice>,
     },
     WriteBuffer {
         program_location: String,
@@ -76,6 +93,7 @@ pub enum ProgramCliCommand {
         buffer_pubkey: Option<Pubkey>,
         buffer_authority_signer_index: Option<SignerIndex>,
         max_len: Option<usize>,
+        compute_unit_price: Option<ComputeUnitPrice>,
     },
     SetBufferAuthority {
         buffer_pubkey: Pubkey,
@@ -87,6 +105,11 @@ pub enum ProgramCliCommand {
         upgrade_authority_index: Option<SignerIndex>,
         new_upgrade_authority: Option<Pubkey>,
     },
+    SetUpgradeAuthorityChecked {
+        program_pubkey: Pubkey,
+        upgrade_authority_index: Option<SignerIndex>,
+        new_upgrade_authority_index: SignerIndex,
+    },
     Show {
         account_pubkey: Option<Pubkey>,
         authority_pubkey: Pubkey,
@@ -94,10 +117,13 @@ pub enum ProgramCliCommand {
         get_buffers: bool,
         all: bool,
         use_lamports_unit: bool,
+        verify_program_location: Option<String>,
+        authority_history: bool,
     },
     Dump {
         account_pubkey: Option<Pubkey>,
         output_location: String,
+        disassemble: bool,
     },
     Close {
         account_pubkey: Option<Pubkey>,
@@ -170,7 +196,8 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .long("allow-excessive-deploy-account-balance")
                                 .takes_value(false)
                                 .help("Use the designated program id even if the account already holds a large balance of SOL")
-                        ),
+                        )
+                        .arg(compute_unit_price_arg()),
                 )
                 .subcommand(
                     SubCommand::with_name("write-buffer")
@@ -207,7 +234,8 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .required(false)
                                 .help("Maximum length of the upgradeable program \
                                       [default: twice the length of the original deployed program]")
-                        ),
+                        )
+                        .arg(compute_unit_price_arg()),
                 )
                 .subcommand(
                     SubCommand::with_name("set-buffer-authority")
@@ -256,11 +284,21 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .help("Upgrade authority [default: the default configured keypair]")
                         )
                         .arg(
-                            pubkey!(Arg::with_name("new_upgrade_authority")
+                            Arg::with_name("new_upgrade_authority")
                                 .long("new-upgrade-authority")
                                 .required_unless("final")
-                                .value_name("NEW_UPGRADE_AUTHORITY"),
-                                "Address of the new upgrade authority"),
+                                .value_name("NEW_UPGRADE_AUTHORITY")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("New upgrade authority, which must sign unless \
+                                       --skip-new-authority-signer is used")
+                        )
+                        .arg(
+                            Arg::with_name("skip_new_upgrade_authority_signer")
+                                .long("skip-new-authority-signer")
+                                .requires("new_upgrade_authority")
+                                .help("Don't require the new upgrade authority to sign, \
+                                       it may then be set to an address nobody controls")
                         )
                         .arg(
                             Arg::with_name("final")
@@ -314,6 +352,23 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .long("lamports")
                                 .takes_value(false)
                                 .help("Display balance in lamports instead of SOL"),
+                        )
+                        .arg(
+                            Arg::with_name("verify_hash")
+                                .long("verify-hash")
+                                .value_name("PROGRAM_FILEPATH")
+                                .takes_value(true)
+                                .requires("account")
+                                .help("Compare the deployed program against a local program file, \
+                                       e.g. the output of a verifiable build")
+                        )
+                        .arg(
+                            Arg::with_name("authority_history")
+                                .long("authority-history")
+                                .requires("account")
+                                .conflicts_with("verify_hash")
+                                .help("List the authority changes of the program or buffer, \
+                                       reconstructed from its transaction history")
                         ),
                 )
                 .subcommand(
@@ -334,6 +389,13 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .takes_value(true)
                                 .required(true)
                                 .help("/path/to/program.so"),
+                        )
+                        .arg(
+                            Arg::with_name("disassemble")
+                                .long("disassemble")
+                                .takes_value(false)
+                                .help("Write the program's sections, functions, syscalls and \
+                                       disassembled instructions instead of the raw program"),
                         ),
                 )
                 .subcommand(
@@ -479,6 +541,7 @@ pub fn parse_program_subcommand(
                     is_final: matches.is_present("final"),
                     max_len,
                     allow_excessive_balance: matches.is_present("allow_excessive_balance"),
+                    compute_unit_price: ComputeUnitPrice::new_from_matches(matches),
                 }),
                 signers: signer_info.signers,
             }
@@ -524,6 +587,7 @@ pub fn parse_program_subcommand(
                     buffer_authority_signer_index: signer_info
                         .index_of_or_none(buffer_authority_pubkey),
                     max_len,
+                    compute_unit_price: ComputeUnitPrice::new_from_matches(matches),
                 }),
                 signers: signer_info.signers,
             }
@@ -558,49 +622,86 @@ pub fn parse_program_subcommand(
             let (upgrade_authority_signer, upgrade_authority_pubkey) =
                 signer_of(matches, "upgrade_authority", wallet_manager)?;
             let program_pubkey = pubkey_of(matches, "program_id").unwrap();
-            let new_upgrade_authority = if matches.is_present("final") {
-                None
-            } else {
-                pubkey_of_signer(matches, "new_upgrade_authority", wallet_manager)?
-            };
-
-            let signer_info = default_signer.generate_unique_signers(
-                vec![
-                    Some(default_signer.signer_from_path(matches, wallet_manager)?),
-                    upgrade_authority_signer,
-                ],
-                matches,
-                wallet_manager,
-            )?;
+            if matches.is_present("final")
+                || matches.is_present("skip_new_upgrade_authority_signer")
+            {
+                let new_upgrade_authority = if matches.is_present("final") {
+                    None
+                } else {
+                    pubkey_of_signer(matches, "new_upgrade_authority", wallet_manager)?
+                };
 
-            CliCommandInfo {
-                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
-                    program_pubkey,
-                    upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
-                    new_upgrade_authority,
-                }),
-                signers: signer_info.signers,
+                let signer_info = default_signer.generate_unique_signers(
+                    vec![
+                        Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                        upgrade_authority_signer,
+                    ],
+                    matches,
+                    wallet_manager,
+                )?;
+
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                        program_pubkey,
+                        upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
+                        new_upgrade_authority,
+                    }),
+                    signers: signer_info.signers,
+                }
+            } else {
+                let (new_upgrade_authority_signer, new_upgrade_authority_pubkey) =
+                    signer_of(matches, "new_upgrade_authority", wallet_manager)?;
+
+                let signer_info = default_signer.generate_unique_signers(
+                    vec![
+                        Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                        upgrade_authority_signer,
+                        new_upgrade_authority_signer,
+                    ],
+                    matches,
+                    wallet_manager,
+                )?;
+
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthorityChecked {
+                        program_pubkey,
+                        upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
+                        new_upgrade_authority_index: signer_info
+                            .index_of(new_upgrade_authority_pubkey)
+                            .unwrap(),
+                    }),
+                    signers: signer_info.signers,
+                }
             }
         }
         ("show", Some(matches)) => {
+            let account_pubkey = pubkey_of(matches, "account");
             let authority_pubkey = if let Some(authority_pubkey) =
                 pubkey_of_signer(matches, "buffer_authority", wallet_manager)?
             {
                 authority_pubkey
             } else {
-                default_signer
-                    .signer_from_path(matches, wallet_manager)?
-                    .pubkey()
+                match default_signer.read_only_signer_from_path(matches, wallet_manager) {
+                    Ok(signer) => signer.pubkey(),
+                    // The authority only filters the listed programs and buffers, so an account
+                    // is shown even when no default signer is configured
+                    Err(_) if account_pubkey.is_some() || matches.is_present("all") => {
+                        Pubkey::default()
+                    }
+                    Err(err) => return Err(err.into()),
+                }
             };
 
             CliCommandInfo {
                 command: CliCommand::Program(ProgramCliCommand::Show {
-                    account_pubkey: pubkey_of(matches, "account"),
+                    account_pubkey,
                     authority_pubkey,
                     get_programs: matches.is_present("programs"),
                     get_buffers: matches.is_present("buffers"),
                     all: matches.is_present("all"),
                     use_lamports_unit: matches.is_present("lamports"),
+                    verify_program_location: matches.value_of("verify_hash").map(|s| s.to_string()),
+                    authority_history: matches.is_present("authority_history"),
                 }),
                 signers: vec![],
             }
@@ -609,6 +710,7 @@ pub fn parse_program_subcommand(
             command: CliCommand::Program(ProgramCliCommand::Dump {
                 account_pubkey: pubkey_of(matches, "account"),
                 output_location: matches.value_of("output_location").unwrap().to_string(),
+                disassemble: matches.is_present("disassemble"),
             }),
             signers: vec![],
         },
@@ -672,6 +774,7 @@ pub fn process_program_subcommand(
             is_final,
             max_len,
             allow_excessive_balance,
+            compute_unit_price,
         } => process_program_deploy(
             rpc_client,
             config,
@@ -684,6 +787,7 @@ pub fn process_program_subcommand(
             *is_final,
             *max_len,
             *allow_excessive_balance,
+            *compute_unit_price,
         ),
         ProgramCliCommand::WriteBuffer {
             program_location,
@@ -691,6 +795,7 @@ pub fn process_program_subcommand(
             buffer_pubkey,
             buffer_authority_signer_index,
             max_len,
+            compute_unit_price,
         } => process_write_buffer(
             rpc_client,
             config,
@@ -699,6 +804,7 @@ pub fn process_program_subcommand(
             *buffer_pubkey,
             *buffer_authority_signer_index,
             *max_len,
+            *compute_unit_price,
         ),
         ProgramCliCommand::SetBufferAuthority {
             buffer_pubkey,
@@ -724,6 +830,17 @@ pub fn process_program_subcommand(
             *upgrade_authority_index,
             *new_upgrade_authority,
         ),
+        ProgramCliCommand::SetUpgradeAuthorityChecked {
+            program_pubkey,
+            upgrade_authority_index,
+            new_upgrade_authority_index,
+        } => process_set_upgrade_authority_checked(
+            &rpc_client,
+            config,
+            program_pubkey,
+            *upgrade_authority_index,
+            *new_upgrade_authority_index,
+        ),
         ProgramCliCommand::Show {
             account_pubkey,
             authority_pubkey,
@@ -731,20 +848,37 @@ pub fn process_program_subcommand(
             get_buffers,
             all,
             use_lamports_unit,
-        } => process_show(
+            verify_program_location,
+            authority_history,
+        } => match (account_pubkey, verify_program_location) {
+            (Some(account_pubkey), Some(program_location)) => {
+                process_verify_hash(&rpc_client, config, account_pubkey, program_location)
+            }
+            (Some(account_pubkey), None) if *authority_history => {
+                process_show_authority_history(&rpc_client, config, account_pubkey)
+            }
+            _ => process_show(
+                &rpc_client,
+                config,
+                *account_pubkey,
+                *authority_pubkey,
+                *get_programs,
+                *get_buffers,
+                *all,
+                *use_lamports_unit,
+            ),
+        },
+        ProgramCliCommand::Dump {
+            account_pubkey,
+            output_location,
+            disassemble,
+        } => process_dump(
             &rpc_client,
             config,
             *account_pubkey,
-            *authority_pubkey,
-            *get_programs,
-            *get_buffers,
-            *all,
-            *use_lamports_unit,
-        ),
-        ProgramCliCommand::Dump {
-            account_pubkey,
             output_location,
-        } => process_dump(&rpc_client, config, *account_pubkey, output_location),
+            *disassemble,
+        ),
         ProgramCliCommand::Close {
             account_pubkey,
             recipient_pubkey,
@@ -796,7 +930,11 @@ fn process_program_deploy(
     is_final: bool,
     max_len: Option<usize>,
     allow_excessive_balance: bool,
+    compute_unit_price: Option<ComputeUnitPrice>,
 ) -> ProcessResult {
+    let compute_unit_price = compute_unit_price
+        .map(|price| price.resolve(&rpc_client))
+        .transpose()?;
     let (words, mnemonic, buffer_keypair) = create_ephemeral_keypair()?;
     let (buffer_provided, buffer_signer, buffer_pubkey) = if let Some(i) = buffer_signer_index {
         (true, Some(config.signers[i]), config.signers[i].pubkey())
@@ -950,6 +1088,7 @@ fn process_program_deploy(
             &buffer_pubkey,
             Some(upgrade_authority_signer),
             allow_excessive_balance,
+            compute_unit_price,
         )
     } else {
         do_process_program_upgrade(
@@ -960,6 +1099,7 @@ fn process_program_deploy(
             config.signers[upgrade_authority_signer_index],
             &buffer_pubkey,
             buffer_signer,
+            compute_unit_price,
         )
     };
     if result.is_ok() && is_final {
@@ -986,7 +1126,11 @@ fn process_write_buffer(
     buffer_pubkey: Option<Pubkey>,
     buffer_authority_signer_index: Option<SignerIndex>,
     max_len: Option<usize>,
+    compute_unit_price: Option<ComputeUnitPrice>,
 ) -> ProcessResult {
+    let compute_unit_price = compute_unit_price
+        .map(|price| price.resolve(&rpc_client))
+        .transpose()?;
     // Create ephemeral keypair to use for Buffer account, if not provided
     let (words, mnemonic, buffer_keypair) = create_ephemeral_keypair()?;
     let (buffer_signer, buffer_pubkey) = if let Some(i) = buffer_signer_index {
@@ -1053,6 +1197,7 @@ fn process_write_buffer(
         &buffer_pubkey,
         Some(buffer_authority),
         true,
+        compute_unit_price,
     );
 
     if result.is_err() && buffer_signer_index.is_none() && buffer_signer.is_some() {
@@ -1130,6 +1275,66 @@ fn process_set_authority(
     Ok(config.output_format.formatted_string(&authority))
 }
 
+fn process_set_upgrade_authority_checked(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    authority: Option<SignerIndex>,
+    new_authority: SignerIndex,
+) -> ProcessResult {
+    let authority_signer = if let Some(index) = authority {
+        config.signers[index]
+    } else {
+        return Err("Set authority requires the current authority".into());
+    };
+    let new_authority_signer = config.signers[new_authority];
+
+    if !get_feature_is_active(
+        rpc_client,
+        &feature_set::enable_bpf_loader_set_authority_checked_ix::id(),
+    )? {
+        return Err(
+            "The new upgrade authority can't co-sign on this cluster yet, \
+            use --skip-new-authority-signer to set it without its signature"
+                .into(),
+        );
+    }
+
+    trace!("Set a new authority, checked");
+    let blockhash = rpc_client.get_latest_blockhash()?;
+
+    let mut tx = Transaction::new_unsigned(Message::new(
+        &[bpf_loader_upgradeable::set_upgrade_authority_checked(
+            program_pubkey,
+            &authority_signer.pubkey(),
+            &new_authority_signer.pubkey(),
+        )],
+        Some(&config.signers[0].pubkey()),
+    ));
+
+    tx.try_sign(
+        &[config.signers[0], authority_signer, new_authority_signer],
+        blockhash,
+    )?;
+    rpc_client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            config.commitment,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                preflight_commitment: Some(config.commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .map_err(|e| format!("Setting authority failed: {}", e))?;
+
+    let authority = CliProgramAuthority {
+        authority: new_authority_signer.pubkey().to_string(),
+        account_type: CliProgramAccountType::Program,
+    };
+    Ok(config.output_format.formatted_string(&authority))
+}
+
 const ACCOUNT_TYPE_SIZE: usize = 4;
 const SLOT_SIZE: usize = size_of::<u64>();
 const OPTION_SIZE: usize = 1;
@@ -1389,62 +1594,340 @@ fn process_dump(
     config: &CliConfig,
     account_pubkey: Option<Pubkey>,
     output_location: &str,
+    disassemble: bool,
 ) -> ProcessResult {
     if let Some(account_pubkey) = account_pubkey {
-        if let Some(account) = rpc_client
-            .get_account_with_commitment(&account_pubkey, config.commitment)?
-            .value
-        {
-            if account.owner == bpf_loader::id() || account.owner == bpf_loader_deprecated::id() {
-                let mut f = File::create(output_location)?;
-                f.write_all(&account.data)?;
-                Ok(format!("Wrote program to {}", output_location))
-            } else if account.owner == bpf_loader_upgradeable::id() {
-                if let Ok(UpgradeableLoaderState::Program {
-                    programdata_address,
-                }) = account.state()
+        let program_data = get_program_data(rpc_client, config, &account_pubkey)?;
+        if disassemble {
+            // Disassemble before creating the file so a failure doesn't leave an empty one behind
+            let disassembly = disassemble_program(&program_data)?;
+            let mut f = File::create(output_location)?;
+            f.write_all(disassembly.as_bytes())?;
+            Ok(format!("Wrote disassembled program to {}", output_location))
+        } else {
+            let mut f = File::create(output_location)?;
+            f.write_all(&program_data)?;
+            Ok(format!("Wrote program to {}", output_location))
+        }
+    } else {
+        Err("No account specified".into())
+    }
+}
+
+/// Fetch the program bytes held by a program or buffer account, skipping the upgradeable loader's
+/// account metadata
+fn get_program_data(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if let Some(account) = rpc_client
+        .get_account_with_commitment(account_pubkey, config.commitment)?
+        .value
+    {
+        if account.owner == bpf_loader::id() || account.owner == bpf_loader_deprecated::id() {
+            Ok(account.data)
+        } else if account.owner == bpf_loader_upgradeable::id() {
+            if let Ok(UpgradeableLoaderState::Program {
+                programdata_address,
+            }) = account.state()
+            {
+                if let Some(programdata_account) = rpc_client
+                    .get_account_with_commitment(&programdata_address, config.commitment)?
+                    .value
                 {
-                    if let Some(programdata_account) = rpc_client
-                        .get_account_with_commitment(&programdata_address, config.commitment)?
-                        .value
+                    if let Ok(UpgradeableLoaderState::ProgramData { .. }) =
+                        programdata_account.state()
                     {
-                        if let Ok(UpgradeableLoaderState::ProgramData { .. }) =
-                            programdata_account.state()
-                        {
-                            let offset =
-                                UpgradeableLoaderState::programdata_data_offset().unwrap_or(0);
-                            let program_data = &programdata_account.data[offset..];
-                            let mut f = File::create(output_location)?;
-                            f.write_all(program_data)?;
-                            Ok(format!("Wrote program to {}", output_location))
-                        } else {
-                            Err(format!("Program {} has been closed", account_pubkey).into())
-                        }
+                        let offset = UpgradeableLoaderState::programdata_data_offset().unwrap_or(0);
+                        Ok(programdata_account.data[offset..].to_vec())
                     } else {
                         Err(format!("Program {} has been closed", account_pubkey).into())
                     }
-                } else if let Ok(UpgradeableLoaderState::Buffer { .. }) = account.state() {
-                    let offset = UpgradeableLoaderState::buffer_data_offset().unwrap_or(0);
-                    let program_data = &account.data[offset..];
-                    let mut f = File::create(output_location)?;
-                    f.write_all(program_data)?;
-                    Ok(format!("Wrote program to {}", output_location))
                 } else {
-                    Err(format!(
-                        "{} is not an upgradeable loader buffer or program account",
-                        account_pubkey
-                    )
-                    .into())
+                    Err(format!("Program {} has been closed", account_pubkey).into())
                 }
+            } else if let Ok(UpgradeableLoaderState::Buffer { .. }) = account.state() {
+                let offset = UpgradeableLoaderState::buffer_data_offset().unwrap_or(0);
+                Ok(account.data[offset..].to_vec())
             } else {
-                Err(format!("{} is not a BPF program", account_pubkey).into())
+                Err(format!(
+                    "{} is not an upgradeable loader buffer or program account",
+                    account_pubkey
+                )
+                .into())
             }
         } else {
-            Err(format!("Unable to find the account {}", account_pubkey).into())
+            Err(format!("{} is not a BPF program", account_pubkey).into())
         }
     } else {
-        Err("No account specified".into())
+        Err(format!("Unable to find the account {}", account_pubkey).into())
+    }
+}
+
+fn process_verify_hash(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+    program_location: &str,
+) -> ProcessResult {
+    let mut local_data = Vec::new();
+    File::open(program_location)
+        .and_then(|mut file| file.read_to_end(&mut local_data))
+        .map_err(|err| format!("Unable to read program file: {}", err))?;
+    let onchain_data = get_program_data(rpc_client, config, account_pubkey)?;
+    let onchain_data = trim_program_padding(&onchain_data, local_data.len());
+    let sections = compare_program_sections(&local_data, onchain_data)?;
+
+    let matched = local_data == onchain_data;
+    let verification = config
+        .output_format
+        .formatted_string(&CliProgramHashVerification {
+            program_id: account_pubkey.to_string(),
+            program_location: program_location.to_string(),
+            local_hash: hash(&local_data).to_string(),
+            onchain_hash: hash(onchain_data).to_string(),
+            matched,
+            sections,
+        });
+    if !matched {
+        // Still show which sections differ, the error alone doesn't say much
+        println!("{}", verification);
+        return Err(format!(
+            "{} does not match the program deployed at {}",
+            program_location, account_pubkey
+        )
+        .into());
     }
+    Ok(verification)
+}
+
+/// List the authority changes of a program or buffer, found in the top-level upgradeable loader
+/// instructions of the transactions which succeeded, oldest first
+fn process_show_authority_history(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+) -> ProcessResult {
+    let account = rpc_client
+        .get_account_with_commitment(account_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| format!("Unable to find the account {}", account_pubkey))?;
+    if account.owner != bpf_loader_upgradeable::id() {
+        return Err(format!("{} is not an upgradeable program or buffer", account_pubkey).into());
+    }
+    // The authority of a program is held by its program data account
+    let authority_address = match account.state() {
+        Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) => programdata_address,
+        Ok(UpgradeableLoaderState::Buffer { .. }) => *account_pubkey,
+        _ => {
+            return Err(
+                format!("{} is not an upgradeable program or buffer", account_pubkey).into(),
+            )
+        }
+    };
+
+    // The transaction history isn't available for processed transactions
+    let commitment = if config.commitment.is_at_least_confirmed() {
+        config.commitment
+    } else {
+        CommitmentConfig::confirmed()
+    };
+    let mut authority_changes = vec![];
+    let mut before = None;
+    loop {
+        let results = rpc_client.get_signatures_for_address_with_config(
+            &authority_address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT),
+                commitment: Some(commitment),
+            },
+        )?;
+        let last_signature = match results.last() {
+            Some(result) => Signature::from_str(&result.signature)?,
+            None => break,
+        };
+        for result in results.into_iter().filter(|result| result.err.is_none()) {
+            let signature = Signature::from_str(&result.signature)?;
+            let confirmed_transaction = rpc_client.get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(commitment),
+                },
+            )?;
+            let transaction = confirmed_transaction
+                .transaction
+                .transaction
+                .decode()
+                .ok_or_else(|| format!("Unable to decode transaction {}", signature))?;
+            // The signatures are listed newest first, so are the changes until reversed
+            for (instruction, authority) in find_authority_changes(&transaction, &authority_address)
+                .into_iter()
+                .rev()
+            {
+                authority_changes.push(CliProgramAuthorityChange {
+                    slot: confirmed_transaction.slot,
+                    block_time: confirmed_transaction.block_time,
+                    signature: result.signature.clone(),
+                    instruction: instruction.to_string(),
+                    authority: authority
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_else(|| "none".to_string()),
+                });
+            }
+        }
+        before = Some(last_signature);
+    }
+    authority_changes.reverse();
+
+    Ok(config
+        .output_format
+        .formatted_string(&CliProgramAuthorityHistory {
+            address: account_pubkey.to_string(),
+            authority_changes,
+        }))
+}
+
+/// Find the instructions of `transaction` which set the authority of `authority_address`, a
+/// buffer or program data account, along with the authority they set
+fn find_authority_changes(
+    transaction: &Transaction,
+    authority_address: &Pubkey,
+) -> Vec<(&'static str, Option<Pubkey>)> {
+    let account_keys = &transaction.message.account_keys;
+    let account_key = |instruction: &CompiledInstruction, position: usize| {
+        instruction
+            .accounts
+            .get(position)
+            .and_then(|index| account_keys.get(*index as usize))
+            .copied()
+    };
+    transaction
+        .message
+        .instructions
+        .iter()
+        .filter(|instruction| {
+            account_keys.get(instruction.program_id_index as usize)
+                == Some(&bpf_loader_upgradeable::id())
+        })
+        .filter_map(|instruction| {
+            let loader_instruction: UpgradeableLoaderInstruction =
+                limited_deserialize(&instruction.data).ok()?;
+            let (name, account, authority) = match loader_instruction {
+                UpgradeableLoaderInstruction::InitializeBuffer => {
+                    ("initializeBuffer", 0, account_key(instruction, 1))
+                }
+                UpgradeableLoaderInstruction::DeployWithMaxDataLen { .. } => {
+                    ("deployWithMaxDataLen", 1, account_key(instruction, 7))
+                }
+                UpgradeableLoaderInstruction::SetAuthority => {
+                    ("setAuthority", 0, account_key(instruction, 2))
+                }
+                UpgradeableLoaderInstruction::SetAuthorityChecked => {
+                    ("setAuthorityChecked", 0, account_key(instruction, 2))
+                }
+                _ => return None,
+            };
+            (account_key(instruction, account).as_ref() == Some(authority_address))
+                .then(|| (name, authority))
+        })
+        .collect()
+}
+
+/// Render a listing of the program's sections, function symbols and syscalls followed by its
+/// disassembly, with calls to syscalls annotated by name
+fn disassemble_program(program_data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let elf = goblin::elf::Elf::parse(program_data)
+        .map_err(|err| format!("Unable to parse program: {}", err))?;
+    let mut invoke_context = ThisInvokeContext::new_mock(&[], &[]);
+    let executable = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
+        program_data,
+        None,
+        Config::default(),
+        register_syscalls(&mut invoke_context).unwrap(),
+    )
+    .map_err(|err| format!("ELF error: {}", err))?;
+    let analysis = Analysis::from_executable(executable.as_ref());
+
+    let mut output = Vec::new();
+    writeln!(output, "Sections:")?;
+    for header in &elf.section_headers {
+        writeln!(
+            output,
+            "  {:<24} offset {:#010x} size {:#x}",
+            elf.shdr_strtab
+                .get_at(header.sh_name)
+                .unwrap_or("<unnamed>"),
+            header.sh_offset,
+            header.sh_size,
+        )?;
+    }
+    writeln!(output, "\nFunctions:")?;
+    for (pc, (_hash, name)) in executable.get_function_symbols() {
+        writeln!(output, "  {:>8} {}", pc, name)?;
+    }
+    writeln!(output, "\nSyscalls:")?;
+    for name in executable
+        .get_syscall_symbols()
+        .values()
+        .collect::<BTreeSet<_>>()
+    {
+        writeln!(output, "  {}", name)?;
+    }
+    writeln!(output, "\nDisassembly:")?;
+    analysis.disassemble(&mut output)?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Program and buffer accounts are usually allocated larger than the program they hold so that
+/// it can be upgraded in place, leaving zeros after the program. Strip that padding so the
+/// on-chain bytes can be compared against a local build of `local_len` bytes.
+fn trim_program_padding(onchain_data: &[u8], local_len: usize) -> &[u8] {
+    if onchain_data.len() >= local_len && onchain_data[local_len..].iter().all(|b| *b == 0) {
+        &onchain_data[..local_len]
+    } else {
+        let len = onchain_data
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |last| last + 1);
+        &onchain_data[..len]
+    }
+}
+
+/// Compare each section of the local ELF against the same file range of the on-chain program
+fn compare_program_sections(
+    local_data: &[u8],
+    onchain_data: &[u8],
+) -> Result<Vec<CliProgramSection>, Box<dyn std::error::Error>> {
+    let elf = goblin::elf::Elf::parse(local_data)
+        .map_err(|err| format!("Unable to parse local program: {}", err))?;
+    Ok(elf
+        .section_headers
+        .iter()
+        .filter(|header| header.sh_type != goblin::elf::section_header::SHT_NOBITS)
+        .filter(|header| header.sh_size > 0)
+        .map(|header| {
+            let start = header.sh_offset as usize;
+            let range = start..start.saturating_add(header.sh_size as usize);
+            CliProgramSection {
+                name: elf
+                    .shdr_strtab
+                    .get_at(header.sh_name)
+                    .unwrap_or("<unnamed>")
+                    .to_string(),
+                offset: header.sh_offset,
+                size: header.sh_size,
+                matched: local_data
+                    .get(range.clone())
+                    .map_or(false, |local| Some(local) == onchain_data.get(range)),
+            }
+        })
+        .collect())
 }
 
 fn close(
@@ -1669,6 +2152,7 @@ pub fn process_deploy(
         &buffer_signer.pubkey(),
         Some(buffer_signer),
         allow_excessive_balance,
+        None,
     );
     if result.is_err() && buffer_signer_index.is_none() {
         report_ephemeral_mnemonic(words, mnemonic);
@@ -1707,6 +2191,7 @@ fn do_process_program_write_and_deploy(
     buffer_pubkey: &Pubkey,
     buffer_authority_signer: Option<&dyn Signer>,
     allow_excessive_balance: bool,
+    compute_unit_price: Option<u64>,
 ) -> ProcessResult {
     // Build messages to calculate fees
     let mut messages: Vec<&Message> = Vec::new();
@@ -1757,7 +2242,7 @@ fn do_process_program_write_and_deploy(
             };
             let initial_message = if !initial_instructions.is_empty() {
                 Some(Message::new_with_blockhash(
-                    &initial_instructions,
+                    &initial_instructions.with_compute_unit_price(compute_unit_price),
                     Some(&config.signers[0].pubkey()),
                     &blockhash,
                 ))
@@ -1779,7 +2264,11 @@ fn do_process_program_write_and_deploy(
                 } else {
                     loader_instruction::write(buffer_pubkey, loader_id, offset, bytes)
                 };
-                Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash)
+                Message::new_with_blockhash(
+                    &vec![instruction].with_compute_unit_price(compute_unit_price),
+                    Some(&payer_pubkey),
+                    &blockhash,
+                )
             };
 
             let mut write_messages = vec![];
@@ -1818,13 +2307,15 @@ fn do_process_program_write_and_deploy(
                         UpgradeableLoaderState::program_len()?,
                     )?,
                     programdata_len,
-                )?,
+                )?
+                .with_compute_unit_price(compute_unit_price),
                 Some(&config.signers[0].pubkey()),
                 &blockhash,
             )
         } else {
             Message::new_with_blockhash(
-                &[loader_instruction::finalize(buffer_pubkey, loader_id)],
+                &vec![loader_instruction::finalize(buffer_pubkey, loader_id)]
+                    .with_compute_unit_price(compute_unit_price),
                 Some(&config.signers[0].pubkey()),
                 &blockhash,
             )
@@ -1871,6 +2362,7 @@ fn do_process_program_upgrade(
     upgrade_authority: &dyn Signer,
     buffer_pubkey: &Pubkey,
     buffer_signer: Option<&dyn Signer>,
+    compute_unit_price: Option<u64>,
 ) -> ProcessResult {
     let loader_id = bpf_loader_upgradeable::id();
     let data_len = program_data.len();
@@ -1913,7 +2405,7 @@ fn do_process_program_upgrade(
 
             let initial_message = if !initial_instructions.is_empty() {
                 Some(Message::new_with_blockhash(
-                    &initial_instructions,
+                    &initial_instructions.with_compute_unit_price(compute_unit_price),
                     Some(&config.signers[0].pubkey()),
                     &blockhash,
                 ))
@@ -1931,7 +2423,11 @@ fn do_process_program_upgrade(
                     offset,
                     bytes,
                 );
-                Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash)
+                Message::new_with_blockhash(
+                    &vec![instruction].with_compute_unit_price(compute_unit_price),
+                    Some(&payer_pubkey),
+                    &blockhash,
+                )
             };
 
             // Create and add write messages
@@ -1959,12 +2455,13 @@ fn do_process_program_upgrade(
 
     // Create and add final message
     let final_message = Message::new_with_blockhash(
-        &[bpf_loader_upgradeable::upgrade(
+        &vec![bpf_loader_upgradeable::upgrade(
             program_id,
             buffer_pubkey,
             &upgrade_authority.pubkey(),
             &config.signers[0].pubkey(),
-        )],
+        )]
+        .with_compute_unit_price(compute_unit_price),
         Some(&config.signers[0].pubkey()),
         &blockhash,
     );
@@ -2258,6 +2755,7 @@ mod tests {
                     is_final: false,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2284,6 +2782,34 @@ mod tests {
                     is_final: false,
                     max_len: Some(42),
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
+                }),
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+            "--with-compute-unit-price",
+            "1000",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: Some("/Users/test/program.so".to_string()),
+                    buffer_signer_index: None,
+                    buffer_pubkey: None,
+                    program_signer_index: None,
+                    program_pubkey: None,
+                    upgrade_authority_signer_index: 0,
+                    is_final: false,
+                    max_len: None,
+                    allow_excessive_balance: false,
+                    compute_unit_price: Some(ComputeUnitPrice::MicroLamports(1_000)),
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2312,6 +2838,7 @@ mod tests {
                     is_final: false,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2342,6 +2869,7 @@ mod tests {
                     is_final: false,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2371,6 +2899,7 @@ mod tests {
                     is_final: false,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2403,6 +2932,7 @@ mod tests {
                     is_final: false,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2431,6 +2961,7 @@ mod tests {
                     is_final: true,
                     max_len: None,
                     allow_excessive_balance: false,
+                    compute_unit_price: None,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2463,6 +2994,7 @@ mod tests {
                     buffer_pubkey: None,
                     buffer_authority_signer_index: Some(0),
                     max_len: None,
+                    compute_unit_price: None,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2486,6 +3018,7 @@ mod tests {
                     buffer_pubkey: None,
                     buffer_authority_signer_index: Some(0),
                     max_len: Some(42),
+                    compute_unit_price: None,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2512,6 +3045,7 @@ mod tests {
                     buffer_pubkey: Some(buffer_keypair.pubkey()),
                     buffer_authority_signer_index: Some(0),
                     max_len: None,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2541,6 +3075,7 @@ mod tests {
                     buffer_pubkey: None,
                     buffer_authority_signer_index: Some(1),
                     max_len: None,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2575,6 +3110,7 @@ mod tests {
                     buffer_pubkey: Some(buffer_keypair.pubkey()),
                     buffer_authority_signer_index: Some(2),
                     max_len: None,
+                    compute_unit_price: None,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2604,6 +3140,7 @@ mod tests {
             &program_pubkey.to_string(),
             "--new-upgrade-authority",
             &new_authority_pubkey.to_string(),
+            "--skip-new-authority-signer",
         ]);
         assert_eq!(
             parse_command(&test_command, &default_signer, &mut None).unwrap(),
@@ -2629,6 +3166,32 @@ mod tests {
             "--new-upgrade-authority",
             &new_authority_pubkey_file,
         ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthorityChecked {
+                    program_pubkey,
+                    upgrade_authority_index: Some(0),
+                    new_upgrade_authority_index: 1,
+                }),
+                signers: vec![
+                    read_keypair_file(&keypair_file).unwrap().into(),
+                    read_keypair_file(&new_authority_pubkey_file)
+                        .unwrap()
+                        .into(),
+                ],
+            }
+        );
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "set-upgrade-authority",
+            &program_pubkey.to_string(),
+            "--new-upgrade-authority",
+            &new_authority_pubkey_file,
+            "--skip-new-authority-signer",
+        ]);
         assert_eq!(
             parse_command(&test_command, &default_signer, &mut None).unwrap(),
             CliCommandInfo {
@@ -2782,6 +3345,8 @@ mod tests {
                     get_buffers: false,
                     all: false,
                     use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: false,
                 }),
                 signers: vec![],
             }
@@ -2805,6 +3370,8 @@ mod tests {
                     get_buffers: false,
                     all: true,
                     use_lamports_unit: true,
+                    verify_program_location: None,
+                    authority_history: false,
                 }),
                 signers: vec![],
             }
@@ -2828,6 +3395,8 @@ mod tests {
                     get_buffers: true,
                     all: true,
                     use_lamports_unit: true,
+                    verify_program_location: None,
+                    authority_history: false,
                 }),
                 signers: vec![],
             }
@@ -2851,6 +3420,8 @@ mod tests {
                     get_buffers: true,
                     all: false,
                     use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: false,
                 }),
                 signers: vec![],
             }
@@ -2874,10 +3445,171 @@ mod tests {
                     get_buffers: true,
                     all: false,
                     use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: false,
                 }),
                 signers: vec![],
             }
         );
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "show",
+            &buffer_pubkey.to_string(),
+            "--verify-hash",
+            "/Users/test/program.so",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: Some(buffer_pubkey),
+                    authority_pubkey: default_keypair.pubkey(),
+                    get_programs: false,
+                    get_buffers: false,
+                    all: false,
+                    use_lamports_unit: false,
+                    verify_program_location: Some("/Users/test/program.so".to_string()),
+                    authority_history: false,
+                }),
+                signers: vec![],
+            }
+        );
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "show",
+            &buffer_pubkey.to_string(),
+            "--authority-history",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: Some(buffer_pubkey),
+                    authority_pubkey: default_keypair.pubkey(),
+                    get_programs: false,
+                    get_buffers: false,
+                    all: false,
+                    use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: true,
+                }),
+                signers: vec![],
+            }
+        );
+
+        // Watch-only, with a pubkey as the default signer
+        let watch_only_pubkey = Pubkey::new_unique();
+        let watch_only_signer = DefaultSigner::new("", &watch_only_pubkey.to_string());
+        let test_command =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "program", "show", "--buffers"]);
+        assert_eq!(
+            parse_command(&test_command, &watch_only_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: None,
+                    authority_pubkey: watch_only_pubkey,
+                    get_programs: false,
+                    get_buffers: true,
+                    all: false,
+                    use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: false,
+                }),
+                signers: vec![],
+            }
+        );
+
+        // No default signer, the authority is only needed to filter the buffers
+        let missing_signer = DefaultSigner::new("", &make_tmp_path("missing_keypair_file"));
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "show",
+            &buffer_pubkey.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &missing_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: Some(buffer_pubkey),
+                    authority_pubkey: Pubkey::default(),
+                    get_programs: false,
+                    get_buffers: false,
+                    all: false,
+                    use_lamports_unit: false,
+                    verify_program_location: None,
+                    authority_history: false,
+                }),
+                signers: vec![],
+            }
+        );
+        let test_command =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "program", "show", "--buffers"]);
+        assert!(parse_command(&test_command, &missing_signer, &mut None).is_err());
+    }
+
+    #[test]
+    fn test_disassemble_program() {
+        let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        noop_path.push("tests/fixtures/noop.so");
+        let mut program_data = Vec::new();
+        File::open(noop_path)
+            .unwrap()
+            .read_to_end(&mut program_data)
+            .unwrap();
+
+        let listing = disassemble_program(&program_data).unwrap();
+        assert!(listing.contains(".text"));
+        assert!(listing.contains("entrypoint"));
+        assert!(listing.contains("exit"));
+        assert!(disassemble_program(&[0; 64]).is_err());
+    }
+
+    #[test]
+    fn test_trim_program_padding() {
+        assert_eq!(trim_program_padding(&[1, 2, 0, 0, 0], 3), &[1, 2, 0]);
+        assert_eq!(trim_program_padding(&[1, 2, 3], 3), &[1, 2, 3]);
+        // the on-chain program is longer than the local one
+        assert_eq!(trim_program_padding(&[1, 2, 3, 4, 0], 3), &[1, 2, 3, 4]);
+        // the on-chain program is shorter than the local one
+        assert_eq!(trim_program_padding(&[1, 2, 0], 4), &[1, 2]);
+    }
+
+    #[test]
+    fn test_compare_program_sections() {
+        let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        noop_path.push("tests/fixtures/noop.so");
+        let mut local_data = Vec::new();
+        File::open(noop_path)
+            .unwrap()
+            .read_to_end(&mut local_data)
+            .unwrap();
+
+        let mut onchain_data = local_data.clone();
+        onchain_data.extend_from_slice(&[0; 64]);
+        let onchain = trim_program_padding(&onchain_data, local_data.len());
+        assert_eq!(onchain, &local_data[..]);
+        let sections = compare_program_sections(&local_data, onchain).unwrap();
+        assert!(sections.iter().any(|section| section.name == ".text"));
+        assert!(sections.iter().all(|section| section.matched));
+
+        let text = sections
+            .iter()
+            .find(|section| section.name == ".text")
+            .unwrap();
+        onchain_data[text.offset as usize] ^= 0xff;
+        let sections = compare_program_sections(&local_data, &onchain_data).unwrap();
+        for section in sections {
+            assert_eq!(section.matched, section.name != ".text");
+        }
     }
 
     #[test]
@@ -3017,6 +3749,7 @@ mod tests {
                 is_final: false,
                 max_len: None,
                 allow_excessive_balance: false,
+                compute_unit_price: None,
             }),
             signers: vec![&default_keypair],
             output_format: OutputFormat::JsonCompact,
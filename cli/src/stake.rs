@@ -281,6 +281,48 @@ impl StakeSubCommands for App<'_, '_> {
                 .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
+        .subcommand(
+            SubCommand::with_name("redelegate-stake")
+                .about("Redelegate active stake to another vote account")
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .hidden(true) // Don't document this argument to discourage its use
+                        .help("Override vote account sanity checks (use carefully!)")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("stake_account_pubkey")
+                        .index(1)
+                        .value_name("STAKE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "Fully activated stake account to redelegate. \
+                        It is scheduled for deactivation, keeping only its rent-exempt reserve \
+                        and any undelegated lamports")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("vote_account_pubkey")
+                        .index(2)
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "The vote account to which the stake will be redelegated")
+                )
+                .arg(
+                    Arg::with_name("redelegation_stake_account")
+                        .index(3)
+                        .value_name("REDELEGATION_STAKE_ACCOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .help("Keypair of the new stake account that takes over the active stake \
+                            and starts activating it on the new vote account")
+                )
+                .arg(stake_authority_arg())
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+                .arg(memo_arg())
+        )
         .subcommand(
             SubCommand::with_name("stake-authorize")
                 .about("Authorize a new signing keypair for the given stake account")
@@ -743,11 +785,16 @@ pub fn parse_stake_delegate_stake(
     let (nonce_authority, nonce_authority_pubkey) =
         signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
     let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+    let (redelegation_stake_account, redelegation_stake_account_pubkey) =
+        signer_of(matches, "redelegation_stake_account", wallet_manager)?;
 
     let mut bulk_signers = vec![stake_authority, fee_payer];
     if nonce_account.is_some() {
         bulk_signers.push(nonce_authority);
     }
+    if redelegation_stake_account.is_some() {
+        bulk_signers.push(redelegation_stake_account);
+    }
     let signer_info =
         default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
 
@@ -755,6 +802,8 @@ pub fn parse_stake_delegate_stake(
         command: CliCommand::DelegateStake {
             stake_account_pubkey,
             vote_account_pubkey,
+            redelegation_stake_account: signer_info
+                .index_of_or_none(redelegation_stake_account_pubkey),
             stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
             force,
             sign_only,
@@ -2304,6 +2353,7 @@ pub fn process_delegate_stake(
     config: &CliConfig,
     stake_account_pubkey: &Pubkey,
     vote_account_pubkey: &Pubkey,
+    redelegation_stake_account: Option<SignerIndex>,
     stake_authority: SignerIndex,
     force: bool,
     sign_only: bool,
@@ -2318,6 +2368,16 @@ pub fn process_delegate_stake(
         (&config.signers[0].pubkey(), "cli keypair".to_string()),
         (stake_account_pubkey, "stake_account_pubkey".to_string()),
     )?;
+    let redelegation_stake_account = redelegation_stake_account.map(|index| config.signers[index]);
+    if let Some(redelegation_stake_account) = &redelegation_stake_account {
+        check_unique_pubkeys(
+            (stake_account_pubkey, "stake_account_pubkey".to_string()),
+            (
+                &redelegation_stake_account.pubkey(),
+                "redelegation_stake_account".to_string(),
+            ),
+        )?;
+    }
     let stake_authority = config.signers[stake_authority];
 
     if !sign_only {
@@ -2370,11 +2430,20 @@ pub fn process_delegate_stake(
 
     let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
 
-    let ixs = vec![stake_instruction::delegate_stake(
-        stake_account_pubkey,
-        &stake_authority.pubkey(),
-        vote_account_pubkey,
-    )]
+    let ixs = if let Some(redelegation_stake_account) = &redelegation_stake_account {
+        stake_instruction::redelegate(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+            &redelegation_stake_account.pubkey(),
+        )
+    } else {
+        vec![stake_instruction::delegate_stake(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+        )]
+    }
     .with_memo(memo);
     let nonce_authority = config.signers[nonce_authority];
     let fee_payer = config.signers[fee_payer];
@@ -3733,6 +3802,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: false,
@@ -3747,6 +3817,43 @@ mod tests {
             }
         );
 
+        // Test RedelegateStake Subcommand
+        let (redelegation_stake_account_keypair_file, mut tmp_file) = make_tmp_file();
+        let redelegation_stake_account_keypair = Keypair::new();
+        write_keypair(&redelegation_stake_account_keypair, tmp_file.as_file_mut()).unwrap();
+        let test_redelegate_stake = test_commands.clone().get_matches_from(vec![
+            "test",
+            "redelegate-stake",
+            &stake_account_string,
+            &vote_account_string,
+            &redelegation_stake_account_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_redelegate_stake, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::DelegateStake {
+                    stake_account_pubkey,
+                    vote_account_pubkey,
+                    redelegation_stake_account: Some(1),
+                    stake_authority: 0,
+                    force: false,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&redelegation_stake_account_keypair_file)
+                        .unwrap()
+                        .into()
+                ],
+            }
+        );
+
         // Test DelegateStake Subcommand w/ authority
         let vote_account_pubkey = solana_sdk::pubkey::new_rand();
         let vote_account_string = vote_account_pubkey.to_string();
@@ -3764,6 +3871,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 1,
                     force: false,
                     sign_only: false,
@@ -3797,6 +3905,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: true,
                     sign_only: false,
@@ -3828,6 +3937,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: false,
@@ -3860,6 +3970,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: true,
@@ -3896,6 +4007,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: false,
@@ -3944,6 +4056,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: false,
@@ -3983,6 +4096,7 @@ mod tests {
                 command: CliCommand::DelegateStake {
                     stake_account_pubkey,
                     vote_account_pubkey,
+                    redelegation_stake_account: None,
                     stake_authority: 0,
                     force: false,
                     sign_only: false,
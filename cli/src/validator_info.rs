@@ -223,7 +223,7 @@ pub fn parse_validator_info_command(
             force_keybase: matches.is_present("force"),
             info_pubkey,
         },
-        signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        signers: vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?],
     })
 }
 
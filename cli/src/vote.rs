@@ -4,6 +4,7 @@ use crate::{
         log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError,
         ProcessResult,
     },
+    feature::get_feature_is_active,
     memo::WithMemo,
     spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
     stake::check_current_authority,
@@ -19,13 +20,13 @@ use solana_cli_output::{CliEpochVotingHistory, CliLockout, CliVoteAccount};
 use solana_client::{rpc_client::RpcClient, rpc_config::RpcGetVoteAccountsConfig};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
-    account::Account, commitment_config::CommitmentConfig, message::Message,
-    native_token::lamports_to_sol, pubkey::Pubkey, system_instruction::SystemError,
-    transaction::Transaction,
+    account::Account, clock::Clock, commitment_config::CommitmentConfig, feature_set,
+    message::Message, native_token::lamports_to_sol, pubkey::Pubkey,
+    system_instruction::SystemError, transaction::Transaction,
 };
 use solana_vote_program::{
     vote_instruction::{self, withdraw, VoteError},
-    vote_state::{VoteAuthorize, VoteInit, VoteState},
+    vote_state::{check_commission_change, VoteAuthorize, VoteInit, VoteState},
 };
 use std::sync::Arc;
 
@@ -834,6 +835,7 @@ pub fn process_vote_update_commission(
     memo: Option<&String>,
 ) -> ProcessResult {
     let authorized_withdrawer = config.signers[withdraw_authority];
+    check_commission_update(rpc_client, config, vote_account_pubkey, commission)?;
     let latest_blockhash = rpc_client.get_latest_blockhash()?;
     let ixs = vec![vote_instruction::update_commission(
         vote_account_pubkey,
@@ -855,6 +857,56 @@ pub fn process_vote_update_commission(
     log_instruction_custom_error::<VoteError>(result, config)
 }
 
+// Catch commission increases the vote program would reject before sending them
+fn check_commission_update(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    vote_account_pubkey: &Pubkey,
+    commission: u8,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, vote_state) = get_vote_account(rpc_client, vote_account_pubkey, config.commitment)?;
+    if commission <= vote_state.commission
+        || !get_feature_is_active(rpc_client, &feature_set::vote_commission_increase_cap::id())?
+    {
+        return Ok(());
+    }
+
+    let vote_account_status =
+        rpc_client.get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
+            vote_pubkey: Some(vote_account_pubkey.to_string()),
+            commitment: Some(config.commitment),
+            ..RpcGetVoteAccountsConfig::default()
+        })?;
+    // Only reported when it differs from the current commission
+    let epoch_start_commission = vote_account_status
+        .current
+        .into_iter()
+        .chain(vote_account_status.delinquent.into_iter())
+        .find_map(|vote_account| vote_account.epoch_start_commission)
+        .unwrap_or(vote_state.commission);
+    let epoch_info = rpc_client.get_epoch_info()?;
+    let epoch_schedule = rpc_client.get_epoch_schedule()?;
+    let clock = Clock {
+        slot: epoch_info.absolute_slot,
+        epoch: epoch_info.epoch,
+        ..Clock::default()
+    };
+    check_commission_change(
+        vote_state.commission,
+        epoch_start_commission,
+        commission,
+        &clock,
+        &epoch_schedule,
+    )
+    .map_err(|err| {
+        CliError::BadParameter(format!(
+            "Unable to raise the commission of {} from {}% to {}%: {}",
+            vote_account_pubkey, vote_state.commission, commission, err
+        ))
+        .into()
+    })
+}
+
 fn get_vote_account(
     rpc_client: &RpcClient,
     vote_account_pubkey: &Pubkey,
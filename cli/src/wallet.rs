@@ -1,15 +1,28 @@
 use crate::{
+    checks::{
+        check_account_for_multiple_fees_with_commitment,
+        check_account_for_spend_multiple_fees_with_commitment,
+    },
     cli::{
         log_instruction_custom_error, request_and_confirm_airdrop, CliCommand, CliCommandInfo,
         CliConfig, CliError, ProcessResult,
     },
+    compute_unit_price::{ComputeUnitPrice, WithComputeUnitPrice},
     memo::WithMemo,
     nonce::check_nonce_account,
     spend_utils::{resolve_spend_tx_and_check_account_balances, SpendAmount},
 };
 use clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand};
-use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_account_decoder::{
+    parse_account_data::AccountAdditionalData,
+    parse_token::{
+        get_token_account_mint, parse_token_2022, spl_token_2022_id, spl_token_id_v2_0,
+        TokenAccountType,
+    },
+    UiAccount, UiAccountData, UiAccountEncoding,
+};
 use solana_clap_utils::{
+    compute_unit_price::*,
     fee_payer::*,
     input_parsers::*,
     input_validators::*,
@@ -25,10 +38,12 @@ use solana_cli_output::{
 };
 use solana_client::{
     blockhash_query::BlockhashQuery, nonce_utils, rpc_client::RpcClient,
-    rpc_config::RpcTransactionConfig, rpc_response::RpcKeyedAccount,
+    rpc_config::RpcTransactionConfig, rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    rpc_response::RpcKeyedAccount,
 };
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
+    account::Account,
     commitment_config::CommitmentConfig,
     message::Message,
     pubkey::Pubkey,
@@ -39,7 +54,7 @@ use solana_sdk::{
     transaction::Transaction,
 };
 use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding};
-use std::{fmt::Write as FmtWrite, fs::File, io::Write, sync::Arc};
+use std::{collections::HashMap, fmt::Write as FmtWrite, fs::File, io::Write, sync::Arc};
 
 pub trait WalletSubCommands {
     fn wallet_subcommands(self) -> Self;
@@ -71,6 +86,13 @@ impl WalletSubCommands for App<'_, '_> {
                         .long("lamports")
                         .takes_value(false)
                         .help("Display balance in lamports instead of SOL"),
+                )
+                .arg(
+                    Arg::with_name("parse")
+                        .long("parse")
+                        .takes_value(false)
+                        .help("Decode the account data of known programs, such as spl-token, \
+                            spl-token-2022 and spl-stake-pool, instead of dumping it"),
                 ),
         )
         .subcommand(
@@ -207,6 +229,42 @@ impl WalletSubCommands for App<'_, '_> {
                         .help("The signer path to resolve")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("top-up-rent-exemption")
+                .about("Transfer to each account of a list the lamports it lacks to be rent-exempt")
+                .arg(
+                    Arg::with_name("accounts_file")
+                        .index(1)
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("File with one account address per line, such as the list of \
+                               `solana-ledger-tool accounts --rent-paying`. Anything after \
+                               the address on a line, and lines starting with #, are ignored"),
+                )
+                .arg(
+                    pubkey!(Arg::with_name("from")
+                        .long("from")
+                        .value_name("FROM_ADDRESS"),
+                        "Source account of funds (if different from client local account). "),
+                )
+                .arg(
+                    Arg::with_name("batch_size")
+                        .long("batch-size")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .default_value("16")
+                        .validator(is_parsable::<usize>)
+                        .help("Number of accounts topped up by each transaction"),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .help("Only show the lamports each account lacks, without transferring them"),
+                )
+                .arg(fee_payer_arg()),
+        )
         .subcommand(
             SubCommand::with_name("transfer")
                 .about("Transfer funds between system accounts")
@@ -265,7 +323,8 @@ impl WalletSubCommands for App<'_, '_> {
                 .offline_args()
                 .nonce_args(false)
                 .arg(memo_arg())
-                .arg(fee_payer_arg()),
+                .arg(fee_payer_arg())
+                .arg(compute_unit_price_arg()),
         )
     }
 }
@@ -286,11 +345,13 @@ pub fn parse_account(
     let account_pubkey = pubkey_of_signer(matches, "account_pubkey", wallet_manager)?.unwrap();
     let output_file = matches.value_of("output_file");
     let use_lamports_unit = matches.is_present("lamports");
+    let parse = matches.is_present("parse");
     Ok(CliCommandInfo {
         command: CliCommand::ShowAccount {
             pubkey: account_pubkey,
             output_file: output_file.map(ToString::to_string),
             use_lamports_unit,
+            parse,
         },
         signers: vec![],
     })
@@ -305,7 +366,7 @@ pub fn parse_airdrop(
     let signers = if pubkey.is_some() {
         vec![]
     } else {
-        vec![default_signer.signer_from_path(matches, wallet_manager)?]
+        vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?]
     };
     let lamports = lamports_of_sol(matches, "amount").unwrap();
     Ok(CliCommandInfo {
@@ -323,7 +384,7 @@ pub fn parse_balance(
     let signers = if pubkey.is_some() {
         vec![]
     } else {
-        vec![default_signer.signer_from_path(matches, wallet_manager)?]
+        vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?]
     };
     Ok(CliCommandInfo {
         command: CliCommand::Balance {
@@ -364,7 +425,7 @@ pub fn parse_create_address_with_seed(
     let signers = if from_pubkey.is_some() {
         vec![]
     } else {
-        vec![default_signer.signer_from_path(matches, wallet_manager)?]
+        vec![default_signer.read_only_signer_from_path(matches, wallet_manager)?]
     };
 
     let program_id = resolve_derived_address_program_id(matches, "program_id").unwrap();
@@ -381,6 +442,54 @@ pub fn parse_create_address_with_seed(
     })
 }
 
+pub fn parse_top_up_rent_exemption(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let accounts_file = matches.value_of("accounts_file").unwrap();
+    let accounts = std::fs::read_to_string(accounts_file)
+        .map_err(|err| {
+            CliError::BadParameter(format!("Unable to read {}: {}", accounts_file, err))
+        })?
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                None
+            } else {
+                line.split_whitespace().next()
+            }
+        })
+        .map(|address| {
+            address.parse::<Pubkey>().map_err(|_| {
+                CliError::BadParameter(format!("Invalid account address: {}", address))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let batch_size = value_t_or_exit!(matches, "batch_size", usize);
+    if batch_size == 0 {
+        return Err(CliError::BadParameter(
+            "Batch size must be at least 1".to_string(),
+        ));
+    }
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+    let (from, from_pubkey) = signer_of(matches, "from", wallet_manager)?;
+    let signer_info =
+        default_signer.generate_unique_signers(vec![fee_payer, from], matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::TopUpRentExemption {
+            accounts,
+            from: signer_info.index_of(from_pubkey).unwrap(),
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+            batch_size,
+            dry_run: matches.is_present("dry_run"),
+        },
+        signers: signer_info.signers,
+    })
+}
+
 pub fn parse_transfer(
     matches: &ArgMatches<'_>,
     default_signer: &DefaultSigner,
@@ -399,6 +508,12 @@ pub fn parse_transfer(
     let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
     let (from, from_pubkey) = signer_of(matches, "from", wallet_manager)?;
     let allow_unfunded_recipient = matches.is_present("allow_unfunded_recipient");
+    let compute_unit_price = ComputeUnitPrice::new_from_matches(matches);
+    if sign_only && compute_unit_price == Some(ComputeUnitPrice::Auto) {
+        return Err(CliError::BadParameter(
+            "An automatic compute unit price can't be discovered offline".to_string(),
+        ));
+    }
 
     let mut bulk_signers = vec![fee_payer, from];
     if nonce_account.is_some() {
@@ -430,6 +545,7 @@ pub fn parse_transfer(
             from: signer_info.index_of(from_pubkey).unwrap(),
             derived_address_seed,
             derived_address_program_id,
+            compute_unit_price,
         },
         signers: signer_info.signers,
     })
@@ -441,22 +557,29 @@ pub fn process_show_account(
     account_pubkey: &Pubkey,
     output_file: &Option<String>,
     use_lamports_unit: bool,
+    parse: bool,
 ) -> ProcessResult {
     let account = rpc_client.get_account(account_pubkey)?;
     let data = account.data.clone();
+    let (encoding, additional_data) = if parse {
+        (
+            UiAccountEncoding::JsonParsed,
+            get_account_additional_data(rpc_client, &account),
+        )
+    } else {
+        (UiAccountEncoding::Base64, None)
+    };
     let cli_account = CliAccount {
         keyed_account: RpcKeyedAccount {
             pubkey: account_pubkey.to_string(),
-            account: UiAccount::encode(
-                account_pubkey,
-                &account,
-                UiAccountEncoding::Base64,
-                None,
-                None,
-            ),
+            account: UiAccount::encode(account_pubkey, &account, encoding, additional_data, None),
         },
         use_lamports_unit,
     };
+    let parsed = matches!(
+        cli_account.keyed_account.account.data,
+        UiAccountData::Json(_)
+    );
 
     let mut account_string = config.output_format.formatted_string(&cli_account);
 
@@ -468,7 +591,7 @@ pub fn process_show_account(
             f.write_all(&data)?;
             writeln!(&mut account_string)?;
             writeln!(&mut account_string, "Wrote account data to {}", output_file)?;
-        } else if !data.is_empty() {
+        } else if !data.is_empty() && !parsed {
             use pretty_hex::*;
             writeln!(&mut account_string, "{:?}", data.hex_dump())?;
         }
@@ -477,6 +600,27 @@ pub fn process_show_account(
     Ok(account_string)
 }
 
+// Token accounts need the decimals of their mint to be parsed
+fn get_account_additional_data(
+    rpc_client: &RpcClient,
+    account: &Account,
+) -> Option<AccountAdditionalData> {
+    if account.owner != spl_token_id_v2_0() && account.owner != spl_token_2022_id() {
+        return None;
+    }
+    let mint_pubkey = get_token_account_mint(&account.data)?;
+    let mint_account = rpc_client.get_account(&mint_pubkey).ok()?;
+    match parse_token_2022(&mint_account.data, None)
+        .ok()?
+        .account_type
+    {
+        TokenAccountType::Mint(mint) => Some(AccountAdditionalData {
+            spl_token_decimals: Some(mint.decimals),
+        }),
+        _ => None,
+    }
+}
+
 pub fn process_airdrop(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -623,6 +767,117 @@ pub fn process_create_address_with_seed(
     Ok(address.to_string())
 }
 
+pub fn process_top_up_rent_exemption(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    accounts: &[Pubkey],
+    from: SignerIndex,
+    fee_payer: SignerIndex,
+    batch_size: usize,
+    dry_run: bool,
+) -> ProcessResult {
+    let from = config.signers[from];
+    let fee_payer = config.signers[fee_payer];
+
+    let mut minimum_balances = HashMap::new();
+    let mut top_ups = vec![];
+    for addresses in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client
+            .get_multiple_accounts_with_commitment(addresses, config.commitment)?
+            .value;
+        for (address, account) in addresses.iter().zip(accounts) {
+            let account = match account {
+                Some(account) => account,
+                None => {
+                    println!("Skipping {}, the account doesn't exist", address);
+                    continue;
+                }
+            };
+            let data_len = account.data.len();
+            let minimum_balance = match minimum_balances.get(&data_len) {
+                Some(minimum_balance) => *minimum_balance,
+                None => {
+                    let minimum_balance =
+                        rpc_client.get_minimum_balance_for_rent_exemption(data_len)?;
+                    minimum_balances.insert(data_len, minimum_balance);
+                    minimum_balance
+                }
+            };
+            if account.lamports < minimum_balance {
+                top_ups.push((*address, minimum_balance - account.lamports));
+            }
+        }
+    }
+
+    let total_lamports: u64 = top_ups.iter().map(|(_, lamports)| lamports).sum();
+    for (address, lamports) in &top_ups {
+        println!(
+            "{}: {}",
+            address,
+            build_balance_message(*lamports, false, true)
+        );
+    }
+    let summary = format!(
+        "{} accounts need {} to be rent-exempt",
+        top_ups.len(),
+        build_balance_message(total_lamports, false, true)
+    );
+    if dry_run || top_ups.is_empty() {
+        return Ok(summary);
+    }
+    println!("{}", summary);
+
+    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let build_message = |top_ups: &[(Pubkey, u64)], recent_blockhash| {
+        let instructions: Vec<_> = top_ups
+            .iter()
+            .map(|(to, lamports)| system_instruction::transfer(&from.pubkey(), to, *lamports))
+            .collect();
+        Message::new_with_blockhash(&instructions, Some(&fee_payer.pubkey()), &recent_blockhash)
+    };
+    let messages: Vec<_> = top_ups
+        .chunks(batch_size)
+        .map(|top_ups| build_message(top_ups, recent_blockhash))
+        .collect();
+    let messages: Vec<_> = messages.iter().collect();
+    if from.pubkey() == fee_payer.pubkey() {
+        check_account_for_spend_multiple_fees_with_commitment(
+            rpc_client,
+            &from.pubkey(),
+            total_lamports,
+            &messages,
+            config.commitment,
+        )?;
+    } else {
+        check_account_for_spend_multiple_fees_with_commitment(
+            rpc_client,
+            &from.pubkey(),
+            total_lamports,
+            &[],
+            config.commitment,
+        )?;
+        check_account_for_multiple_fees_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            &messages,
+            config.commitment,
+        )?;
+    }
+
+    // Each batch gets a fresh blockhash, so that a long list doesn't outlive the first one
+    for top_ups in top_ups.chunks(batch_size) {
+        let recent_blockhash = rpc_client.get_latest_blockhash()?;
+        let mut tx = Transaction::new_unsigned(build_message(top_ups, recent_blockhash));
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+        println!(
+            "{}",
+            log_instruction_custom_error::<SystemError>(result, config)?
+        );
+    }
+    Ok(format!("Topped up {} accounts", top_ups.len()))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn process_transfer(
     rpc_client: &RpcClient,
@@ -641,11 +896,15 @@ pub fn process_transfer(
     fee_payer: SignerIndex,
     derived_address_seed: Option<String>,
     derived_address_program_id: Option<&Pubkey>,
+    compute_unit_price: Option<ComputeUnitPrice>,
 ) -> ProcessResult {
     let from = config.signers[from];
     let mut from_pubkey = from.pubkey();
 
     let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
+    let compute_unit_price = compute_unit_price
+        .map(|price| price.resolve(rpc_client))
+        .transpose()?;
 
     if !sign_only && !allow_unfunded_recipient {
         let recipient_balance = rpc_client
@@ -687,7 +946,8 @@ pub fn process_transfer(
             .with_memo(memo)
         } else {
             vec![system_instruction::transfer(&from_pubkey, to, lamports)].with_memo(memo)
-        };
+        }
+        .with_compute_unit_price(compute_unit_price);
 
         if let Some(nonce_account) = &nonce_account {
             Message::new_with_nonce(
@@ -311,6 +311,7 @@ fn test_create_account_with_seed() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     authority_config.output_format = OutputFormat::JsonCompact;
     let sign_only_reply = process_command(&authority_config).unwrap();
@@ -340,6 +341,7 @@ fn test_create_account_with_seed() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&submit_config).unwrap();
     check_recent_balance(241, &rpc_client, &nonce_address);
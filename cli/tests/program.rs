@@ -187,6 +187,7 @@ fn test_cli_program_deploy_no_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: true,
         max_len: None,
@@ -212,6 +213,7 @@ fn test_cli_program_deploy_no_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: None,
@@ -272,6 +274,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: Some(max_len),
@@ -319,6 +322,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: Some(max_len),
@@ -360,6 +364,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: Some(max_len),
@@ -414,6 +419,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: None,
@@ -446,6 +452,8 @@ fn test_cli_program_deploy_with_authority() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -488,6 +496,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: None,
@@ -503,6 +512,7 @@ fn test_cli_program_deploy_with_authority() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: true,
         max_len: None,
@@ -539,6 +549,8 @@ fn test_cli_program_deploy_with_authority() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -605,6 +617,7 @@ fn test_cli_program_close_program() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: Some(max_len),
@@ -692,6 +705,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: None,
         buffer_authority_signer_index: None,
         max_len: None,
+        compute_unit_price: None,
     });
     config.output_format = OutputFormat::JsonCompact;
     let response = process_command(&config);
@@ -726,6 +740,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: None,
         max_len: Some(max_len),
+        compute_unit_price: None,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -762,6 +777,8 @@ fn test_cli_program_write_buffer() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -787,6 +804,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: Some(2),
         max_len: None,
+        compute_unit_price: None,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -824,6 +842,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: None,
         buffer_authority_signer_index: Some(2),
         max_len: None,
+        compute_unit_price: None,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -857,6 +876,8 @@ fn test_cli_program_write_buffer() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -896,6 +917,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: None,
         buffer_authority_signer_index: None,
         max_len: None,
+        compute_unit_price: None,
     });
     config.output_format = OutputFormat::JsonCompact;
     let response = process_command(&config);
@@ -935,6 +957,7 @@ fn test_cli_program_write_buffer() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: None,
         max_len: None, //Some(max_len),
+        compute_unit_price: None,
     });
     process_command(&config).unwrap();
     config.signers = vec![&keypair, &buffer_keypair];
@@ -945,6 +968,7 @@ fn test_cli_program_write_buffer() {
         buffer_signer_index: Some(1),
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: true,
         max_len: None,
@@ -1005,6 +1029,7 @@ fn test_cli_program_set_buffer_authority() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: None,
         max_len: None,
+        compute_unit_price: None,
     });
     process_command(&config).unwrap();
     let buffer_account = rpc_client.get_account(&buffer_keypair.pubkey()).unwrap();
@@ -1120,6 +1145,7 @@ fn test_cli_program_mismatch_buffer_authority() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: Some(2),
         max_len: None,
+        compute_unit_price: None,
     });
     process_command(&config).unwrap();
     let buffer_account = rpc_client.get_account(&buffer_keypair.pubkey()).unwrap();
@@ -1139,6 +1165,7 @@ fn test_cli_program_mismatch_buffer_authority() {
         buffer_signer_index: None,
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: true,
         max_len: None,
@@ -1154,6 +1181,7 @@ fn test_cli_program_mismatch_buffer_authority() {
         buffer_signer_index: None,
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: true,
         max_len: None,
@@ -1213,6 +1241,7 @@ fn test_cli_program_show() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: Some(2),
         max_len: None,
+        compute_unit_price: None,
     });
     process_command(&config).unwrap();
 
@@ -1225,6 +1254,8 @@ fn test_cli_program_show() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -1269,6 +1300,7 @@ fn test_cli_program_show() {
         buffer_signer_index: None,
         buffer_pubkey: None,
         allow_excessive_balance: false,
+        compute_unit_price: None,
         upgrade_authority_signer_index: 1,
         is_final: false,
         max_len: Some(max_len),
@@ -1287,6 +1319,8 @@ fn test_cli_program_show() {
         get_buffers: false,
         all: false,
         use_lamports_unit: false,
+        verify_program_location: None,
+        authority_history: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -1398,6 +1432,7 @@ fn test_cli_program_dump() {
         buffer_pubkey: Some(buffer_keypair.pubkey()),
         buffer_authority_signer_index: Some(2),
         max_len: None,
+        compute_unit_price: None,
     });
     process_command(&config).unwrap();
 
@@ -1411,6 +1446,7 @@ fn test_cli_program_dump() {
     config.command = CliCommand::Program(ProgramCliCommand::Dump {
         account_pubkey: Some(buffer_keypair.pubkey()),
         output_location: out_file.clone().into_os_string().into_string().unwrap(),
+        disassemble: false,
     });
     process_command(&config).unwrap();
 
@@ -88,6 +88,7 @@ fn test_stake_delegation_force() {
     config.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: vote_keypair.pubkey(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: false,
         sign_only: false,
@@ -104,6 +105,7 @@ fn test_stake_delegation_force() {
     config.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: vote_keypair.pubkey(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: false,
@@ -176,6 +178,7 @@ fn test_seed_stake_delegation_and_deactivation() {
     config_validator.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_address,
         vote_account_pubkey: test_validator.vote_account_address(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: false,
@@ -259,6 +262,7 @@ fn test_stake_delegation_and_deactivation() {
     config_validator.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: test_validator.vote_account_address(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: false,
@@ -362,6 +366,7 @@ fn test_offline_stake_delegation_and_deactivation() {
     config_offline.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: test_validator.vote_account_address(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: true,
@@ -383,6 +388,7 @@ fn test_offline_stake_delegation_and_deactivation() {
     config_payer.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: test_validator.vote_account_address(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: false,
@@ -505,6 +511,7 @@ fn test_nonced_stake_delegation_and_deactivation() {
     config.command = CliCommand::DelegateStake {
         stake_account_pubkey: stake_keypair.pubkey(),
         vote_account_pubkey: test_validator.vote_account_address(),
+        redelegation_stake_account: None,
         stake_authority: 0,
         force: true,
         sign_only: false,
@@ -69,6 +69,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(49_989, &rpc_client, &sender_pubkey);
@@ -90,6 +91,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     assert!(process_command(&config).is_err());
     check_recent_balance(49_989, &rpc_client, &sender_pubkey);
@@ -123,6 +125,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     offline.output_format = OutputFormat::JsonCompact;
     let sign_only_reply = process_command(&offline).unwrap();
@@ -145,6 +148,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(39, &rpc_client, &offline_pubkey);
@@ -196,6 +200,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(49_976 - minimum_nonce_balance, &rpc_client, &sender_pubkey);
@@ -248,6 +253,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     let sign_only_reply = process_command(&offline).unwrap();
     let sign_only = parse_sign_only_reply_string(&sign_only_reply);
@@ -272,6 +278,7 @@ fn test_transfer() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(28, &rpc_client, &offline_pubkey);
@@ -343,6 +350,7 @@ fn test_transfer_multisession_signing() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     fee_payer_config.output_format = OutputFormat::JsonCompact;
     let sign_only_reply = process_command(&fee_payer_config).unwrap();
@@ -374,6 +382,7 @@ fn test_transfer_multisession_signing() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     from_config.output_format = OutputFormat::JsonCompact;
     let sign_only_reply = process_command(&from_config).unwrap();
@@ -402,6 +411,7 @@ fn test_transfer_multisession_signing() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
 
@@ -457,6 +467,7 @@ fn test_transfer_all() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(0, &rpc_client, &sender_pubkey);
@@ -510,6 +521,7 @@ fn test_transfer_unfunded_recipient() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
 
     // Expect failure due to unfunded recipient and the lack of the `allow_unfunded_recipient` flag
@@ -573,6 +585,7 @@ fn test_transfer_with_seed() {
         fee_payer: 0,
         derived_address_seed: Some(derived_address_seed),
         derived_address_program_id: Some(derived_address_program_id),
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     check_recent_balance(0, &rpc_client, &sender_pubkey);
@@ -79,6 +79,7 @@ fn test_vote_authorize_and_withdraw() {
         fee_payer: 0,
         derived_address_seed: None,
         derived_address_program_id: None,
+        compute_unit_price: None,
     };
     process_command(&config).unwrap();
     let expected_balance = expected_balance + 1_000;
@@ -5,6 +5,7 @@ use solana_client::{
     rpc_client::RpcClient,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_response::SlotInfo,
+    transaction_confirmer::{ConfirmationOutcome, TransactionConfirmer},
 };
 use solana_rpc::{
     optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
@@ -96,6 +97,51 @@ fn test_rpc_client() {
     );
 }
 
+#[test]
+fn test_transaction_confirmer() {
+    solana_logger::setup();
+
+    let alice = Keypair::new();
+    let test_validator =
+        TestValidator::with_no_fees(alice.pubkey(), None, SocketAddrSpace::Unspecified);
+    let client =
+        RpcClient::new_with_commitment(test_validator.rpc_url(), CommitmentConfig::confirmed());
+    let confirmer = TransactionConfirmer::new(&client, CommitmentConfig::confirmed())
+        .with_websocket_url(test_validator.rpc_pubsub_url());
+
+    let (blockhash, last_valid_block_height) = client
+        .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+        .unwrap();
+    let bob_pubkey = solana_sdk::pubkey::new_rand();
+    let tx = system_transaction::transfer(&alice, &bob_pubkey, sol_to_lamports(1.0), blockhash);
+    let signature = client.send_transaction(&tx).unwrap();
+    assert!(matches!(
+        confirmer
+            .confirm(&signature, last_valid_block_height)
+            .unwrap(),
+        ConfirmationOutcome::Confirmed { .. }
+    ));
+    assert_eq!(
+        client
+            .get_balance_with_commitment(&bob_pubkey, CommitmentConfig::confirmed())
+            .unwrap()
+            .value,
+        sol_to_lamports(1.0)
+    );
+
+    // A transaction which was never sent expires, here a couple of blocks from now
+    let tx = system_transaction::transfer(&alice, &bob_pubkey, 1, blockhash);
+    let block_height = client
+        .get_block_height_with_commitment(CommitmentConfig::confirmed())
+        .unwrap();
+    assert_eq!(
+        confirmer
+            .confirm(&tx.signatures[0], block_height + 2)
+            .unwrap(),
+        ConfirmationOutcome::Expired
+    );
+}
+
 #[test]
 #[serial]
 fn test_account_subscription() {
@@ -131,6 +177,7 @@ fn test_account_subscription() {
         commitment: Some(CommitmentConfig::finalized()),
         encoding: None,
         data_slice: None,
+        min_context_slot: None,
     });
     let (mut client, receiver) = PubsubClient::account_subscribe(
         &format!("ws://0.0.0.0:{}/", pubsub_addr.port()),
@@ -0,0 +1,531 @@
+//! An asynchronous RPC client over HTTP.
+//!
+//! [`AsyncRpcClient`] keeps a pool of connections to the RPC node, times requests out,
+//! retries those that are rate limited or time out with an exponential backoff, and runs
+//! [`RpcMiddleware`] hooks around the requests of all or some RPC methods.
+//!
+//! Share one client, for example in an `Arc`, so that its requests reuse pooled connections.
+//! Unlike [`RpcClient`], it doesn't adapt its requests to nodes older than 1.8.
+//!
+//! [`RpcClient`]: crate::rpc_client::RpcClient
+
+use {
+    crate::{
+        client_error::{ClientError, Result as ClientResult},
+        http_sender::{retry_after, rpc_result, StatsUpdater},
+        rpc_config::{RpcAccountInfoConfig, RpcSendTransactionConfig},
+        rpc_request::{RpcError, RpcRequest},
+        rpc_response::{Response, RpcBlockhash, RpcResult, RpcVersionInfo},
+        rpc_sender::RpcTransportStats,
+    },
+    bincode::serialize,
+    log::*,
+    reqwest::{header::CONTENT_TYPE, StatusCode},
+    serde_json::{json, Value},
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_sdk::{
+        account::Account, clock::Slot, commitment_config::CommitmentConfig, hash::Hash,
+        pubkey::Pubkey, signature::Signature, transaction::Transaction,
+    },
+    solana_transaction_status::{TransactionStatus, UiTransactionEncoding},
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+        time::{Duration, Instant},
+    },
+    tokio::time::sleep,
+};
+
+/// How requests that are rate limited or time out are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of times a request is retried before its error is returned
+    pub max_retries: u32,
+    /// Pause before the first retry, doubled for each following one
+    pub initial_backoff: Duration,
+    /// Longest pause between retries, unless the node asks for a longer one with `Retry-After`
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retries requests
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Pause before retrying a request that failed `retries + 1` times
+    pub fn backoff(&self, retries: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retries))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AsyncRpcClientConfig {
+    pub commitment_config: CommitmentConfig,
+    /// Time after which a request, including reading its response, times out
+    pub timeout: Duration,
+    /// Number of idle connections to the node kept open for later requests
+    pub pool_max_idle_per_host: usize,
+    /// Time after which an idle connection is closed, unset to keep idle connections open
+    pub pool_idle_timeout: Option<Duration>,
+    pub retry_policy: RetryPolicy,
+}
+
+impl Default for AsyncRpcClientConfig {
+    fn default() -> Self {
+        Self {
+            commitment_config: CommitmentConfig::default(),
+            timeout: Duration::from_secs(30),
+            pool_max_idle_per_host: usize::MAX,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Hooks run around the requests of an [`AsyncRpcClient`], for example to log, meter or
+/// rewrite them.
+pub trait RpcMiddleware: Send + Sync {
+    /// Called before `request` is first sent, with its parameters
+    fn before_request(&self, _request: RpcRequest, _params: &mut Value) {}
+
+    /// Called once `request` succeeded or failed for good, with the time it took including
+    /// retries
+    fn after_request(
+        &self,
+        _request: RpcRequest,
+        _result: &ClientResult<Value>,
+        _elapsed: Duration,
+    ) {
+    }
+}
+
+pub struct AsyncRpcClient {
+    client: reqwest::Client,
+    url: String,
+    config: AsyncRpcClientConfig,
+    /// Middleware with the method it's restricted to, if any
+    middleware: Vec<(Option<RpcRequest>, Arc<dyn RpcMiddleware>)>,
+    request_id: AtomicU64,
+    stats: RwLock<RpcTransportStats>,
+}
+
+impl AsyncRpcClient {
+    /// Create an asynchronous HTTP RPC client with the default configuration.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899, as in "http://localhost:8899".
+    pub fn new(url: String) -> Self {
+        Self::new_with_config(url, AsyncRpcClientConfig::default())
+    }
+
+    /// Create an asynchronous HTTP RPC client.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899.
+    pub fn new_with_config(url: String, config: AsyncRpcClientConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .build()
+            .expect("build rpc client");
+
+        Self {
+            client,
+            url,
+            config,
+            middleware: vec![],
+            request_id: AtomicU64::new(0),
+            stats: RwLock::new(RpcTransportStats::default()),
+        }
+    }
+
+    /// Run `middleware` around the requests of every method
+    pub fn add_middleware(&mut self, middleware: Arc<dyn RpcMiddleware>) {
+        self.middleware.push((None, middleware));
+    }
+
+    /// Run `middleware` around the requests of the `request` method only
+    pub fn add_method_middleware(
+        &mut self,
+        request: RpcRequest,
+        middleware: Arc<dyn RpcMiddleware>,
+    ) {
+        self.middleware.push((Some(request), middleware));
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.config.commitment_config
+    }
+
+    pub fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.read().unwrap().clone()
+    }
+
+    /// Send `request` with `params`, and deserialize its result
+    pub async fn send<T>(&self, request: RpcRequest, mut params: Value) -> ClientResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        assert!(params.is_array() || params.is_null());
+
+        let middleware: Vec<_> = self
+            .middleware
+            .iter()
+            .filter(|(method, _)| method.map_or(true, |method| method == request))
+            .map(|(_, middleware)| middleware)
+            .collect();
+        middleware
+            .iter()
+            .for_each(|middleware| middleware.before_request(request, &mut params));
+
+        let start = Instant::now();
+        let result = self.send_with_retries(request, params).await;
+        let elapsed = start.elapsed();
+        middleware
+            .iter()
+            .for_each(|middleware| middleware.after_request(request, &result, elapsed));
+
+        let response = result.map_err(|err| err.into_with_request(request))?;
+        serde_json::from_value(response)
+            .map_err(|err| ClientError::new_with_request(err.into(), request))
+    }
+
+    async fn send_with_retries(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let mut stats_updater = StatsUpdater::new(&self.stats);
+
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+
+        let retry_policy = &self.config.retry_policy;
+        let mut retries = 0;
+        loop {
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(request_json.clone())
+                .send()
+                .await;
+
+            let backoff = match &response {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => Some(
+                    retry_after(response.headers())
+                        .unwrap_or_else(|| retry_policy.backoff(retries)),
+                ),
+                Err(err) if err.is_timeout() => Some(retry_policy.backoff(retries)),
+                _ => None,
+            };
+            if let Some(backoff) = backoff {
+                if retries < retry_policy.max_retries {
+                    retries += 1;
+                    debug!(
+                        "{} request failed with {:?}, retry {} of {} in {:?}",
+                        request, response, retries, retry_policy.max_retries, backoff
+                    );
+                    sleep(backoff).await;
+                    if response.is_ok() {
+                        stats_updater.add_rate_limited_time(backoff);
+                    }
+                    continue;
+                }
+            }
+
+            let response = response?;
+            if !response.status().is_success() {
+                return Err(response.error_for_status().unwrap_err().into());
+            }
+            return rpc_result(response.json::<Value>().await?);
+        }
+    }
+
+    pub async fn get_version(&self) -> ClientResult<RpcVersionInfo> {
+        self.send(RpcRequest::GetVersion, Value::Null).await
+    }
+
+    pub async fn get_slot(&self) -> ClientResult<Slot> {
+        self.get_slot_with_commitment(self.commitment()).await
+    }
+
+    pub async fn get_slot_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetSlot, json!([commitment_config]))
+            .await
+    }
+
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        Ok(self
+            .get_balance_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<u64> {
+        self.send(
+            RpcRequest::GetBalance,
+            json!([pubkey.to_string(), commitment_config]),
+        )
+        .await
+    }
+
+    /// Returns the account at `pubkey`, an error if there is none
+    pub async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value
+            .ok_or_else(|| RpcError::ForUser(format!("AccountNotFound: pubkey={}", pubkey)).into())
+    }
+
+    pub async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Option<Account>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            commitment: Some(commitment_config),
+            data_slice: None,
+            min_context_slot: None,
+        };
+        let Response { context, value } = self
+            .send::<Response<Option<UiAccount>>>(
+                RpcRequest::GetAccountInfo,
+                json!([pubkey.to_string(), config]),
+            )
+            .await?;
+        Ok(Response {
+            context,
+            value: value.and_then(|account| account.decode()),
+        })
+    }
+
+    /// Returns the latest blockhash and the last block height at which it's valid
+    pub async fn get_latest_blockhash(&self) -> ClientResult<(Hash, u64)> {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .await
+    }
+
+    pub async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        let RpcBlockhash {
+            blockhash,
+            last_valid_block_height,
+        } = self
+            .send::<Response<RpcBlockhash>>(
+                RpcRequest::GetLatestBlockhash,
+                json!([commitment_config]),
+            )
+            .await?
+            .value;
+        let blockhash = blockhash.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Hash".to_string()).into(),
+                RpcRequest::GetLatestBlockhash,
+            )
+        })?;
+        Ok((blockhash, last_valid_block_height))
+    }
+
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        let signatures: Vec<_> = signatures.iter().map(|s| s.to_string()).collect();
+        self.send(RpcRequest::GetSignatureStatuses, json!([signatures]))
+            .await
+    }
+
+    /// Submits `transaction` to the node, which forwards it to the leaders, without waiting for
+    /// it to be confirmed
+    pub async fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        self.send_transaction_with_config(
+            transaction,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(self.commitment().commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let config = RpcSendTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..config
+        };
+        let serialized = base64::encode(serialize(transaction).map_err(|err| {
+            ClientError::new_with_request(
+                RpcError::ParseError(format!("Transaction: {}", err)).into(),
+                RpcRequest::SendTransaction,
+            )
+        })?);
+        let signature: String = self
+            .send(RpcRequest::SendTransaction, json!([serialized, config]))
+            .await?;
+        signature.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Signature".to_string()).into(),
+                RpcRequest::SendTransaction,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::sync::atomic::AtomicUsize,
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        },
+    };
+
+    /// Serves HTTP requests with `responses`, one per request, returning the URL to serve at
+    async fn serve(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut responses = responses.into_iter();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![];
+                let mut buffer = [0; 4096];
+                for response in responses.by_ref() {
+                    // Read up to the end of the request body, whose length the client sets
+                    loop {
+                        let request_len = String::from_utf8_lossy(&request)
+                            .split_once("\r\n\r\n")
+                            .and_then(|(headers, _)| {
+                                let content_length = headers
+                                    .lines()
+                                    .find_map(|line| line.strip_prefix("content-length: "))?;
+                                Some(headers.len() + 4 + content_length.parse::<usize>().ok()?)
+                            });
+                        if request_len.map_or(false, |len| request.len() >= len) {
+                            request.drain(..request_len.unwrap());
+                            break;
+                        }
+                        let len = stream.read(&mut buffer).await.unwrap();
+                        if len == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..len]);
+                    }
+                    let response = format!(
+                        "HTTP/1.1 {}\r\ncontent-type: application/json\r\n\
+                         content-length: {}\r\n\r\n{}",
+                        if response.is_empty() {
+                            "429 Too Many Requests\r\nretry-after: 0"
+                        } else {
+                            "200 OK"
+                        },
+                        response.len(),
+                        response
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        url
+    }
+
+    #[derive(Default)]
+    struct CountingMiddleware {
+        requests: AtomicUsize,
+        errors: AtomicUsize,
+    }
+
+    impl RpcMiddleware for CountingMiddleware {
+        fn before_request(&self, _request: RpcRequest, _params: &mut Value) {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn after_request(
+            &self,
+            _request: RpcRequest,
+            result: &ClientResult<Value>,
+            _elapsed: Duration,
+        ) {
+            if result.is_err() {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let retry_policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        assert_eq!(retry_policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(retry_policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(retry_policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(retry_policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(retry_policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_async_rpc_client_retries_rate_limited_requests() {
+        // An empty response stands for a rate limited request
+        let url = serve(vec![
+            "",
+            "",
+            r#"{"jsonrpc":"2.0","result":42,"id":0}"#,
+            "",
+            r#"{"jsonrpc":"2.0","result":43,"id":1}"#,
+        ])
+        .await;
+        let mut rpc_client = AsyncRpcClient::new(url);
+        let middleware = Arc::new(CountingMiddleware::default());
+        rpc_client.add_method_middleware(RpcRequest::GetSlot, middleware.clone());
+        let version_middleware = Arc::new(CountingMiddleware::default());
+        rpc_client.add_method_middleware(RpcRequest::GetVersion, version_middleware.clone());
+
+        assert_eq!(rpc_client.get_slot().await.unwrap(), 42);
+        assert_eq!(middleware.requests.load(Ordering::Relaxed), 1);
+        assert_eq!(rpc_client.get_transport_stats().request_count, 1);
+
+        rpc_client.config.retry_policy = RetryPolicy::none();
+        assert!(rpc_client.get_slot().await.is_err());
+        assert_eq!(middleware.requests.load(Ordering::Relaxed), 2);
+        assert_eq!(middleware.errors.load(Ordering::Relaxed), 1);
+        assert_eq!(rpc_client.get_slot().await.unwrap(), 43);
+        assert_eq!(version_middleware.requests.load(Ordering::Relaxed), 0);
+    }
+}
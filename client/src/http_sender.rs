@@ -11,7 +11,7 @@ use {
     log::*,
     reqwest::{
         self,
-        header::{CONTENT_TYPE, RETRY_AFTER},
+        header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER},
         StatusCode,
     },
     std::{
@@ -71,14 +71,14 @@ struct RpcErrorObject {
     message: String,
 }
 
-struct StatsUpdater<'a> {
+pub(crate) struct StatsUpdater<'a> {
     stats: &'a RwLock<RpcTransportStats>,
     request_start_time: Instant,
     rate_limited_time: Duration,
 }
 
 impl<'a> StatsUpdater<'a> {
-    fn new(stats: &'a RwLock<RpcTransportStats>) -> Self {
+    pub(crate) fn new(stats: &'a RwLock<RpcTransportStats>) -> Self {
         Self {
             stats,
             request_start_time: Instant::now(),
@@ -86,7 +86,7 @@ impl<'a> StatsUpdater<'a> {
         }
     }
 
-    fn add_rate_limited_time(&mut self, duration: Duration) {
+    pub(crate) fn add_rate_limited_time(&mut self, duration: Duration) {
         self.rate_limited_time += duration;
     }
 }
@@ -100,6 +100,68 @@ impl<'a> Drop for StatsUpdater<'a> {
     }
 }
 
+/// Pause requested by the `Retry-After` header of a rate limited response, unless unreasonable
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let retry_after = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()?;
+    (retry_after < 120).then(|| Duration::from_secs(retry_after))
+}
+
+/// Returns the result of a JSON RPC response, or its error
+pub(crate) fn rpc_result(mut json: serde_json::Value) -> Result<serde_json::Value> {
+    if json["error"].is_object() {
+        return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
+            Ok(rpc_error_object) => {
+                let data = match rpc_error_object.code {
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                        match serde_json::from_value::<RpcSimulateTransactionResult>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
+                            Err(err) => {
+                                debug!(
+                                    "Failed to deserialize RpcSimulateTransactionResult: {:?}",
+                                    err
+                                );
+                                RpcResponseErrorData::Empty
+                            }
+                        }
+                    }
+                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(
+                            json["error"]["data"].clone(),
+                        ) {
+                            Ok(rpc_custom_error::NodeUnhealthyErrorData { num_slots_behind }) => {
+                                RpcResponseErrorData::NodeUnhealthy { num_slots_behind }
+                            }
+                            Err(_err) => RpcResponseErrorData::Empty,
+                        }
+                    }
+                    _ => RpcResponseErrorData::Empty,
+                };
+
+                Err(RpcError::RpcResponseError {
+                    code: rpc_error_object.code,
+                    message: rpc_error_object.message,
+                    data,
+                }
+                .into())
+            }
+            Err(err) => Err(RpcError::RpcRequestError(format!(
+                "Failed to deserialize RPC error response: {} [{}]",
+                serde_json::to_string(&json["error"]).unwrap(),
+                err
+            ))
+            .into()),
+        };
+    }
+    Ok(json["result"].take())
+}
+
 impl RpcSender for HttpSender {
     fn get_transport_stats(&self) -> RpcTransportStats {
         self.stats.read().unwrap().clone()
@@ -131,16 +193,8 @@ impl RpcSender for HttpSender {
                 if response.status() == StatusCode::TOO_MANY_REQUESTS
                     && too_many_requests_retries > 0
                 {
-                    let mut duration = Duration::from_millis(500);
-                    if let Some(retry_after) = response.headers().get(RETRY_AFTER) {
-                        if let Ok(retry_after) = retry_after.to_str() {
-                            if let Ok(retry_after) = retry_after.parse::<u64>() {
-                                if retry_after < 120 {
-                                    duration = Duration::from_secs(retry_after);
-                                }
-                            }
-                        }
-                    }
+                    let duration = retry_after(response.headers())
+                        .unwrap_or_else(|| Duration::from_millis(500));
 
                     too_many_requests_retries -= 1;
                     debug!(
@@ -155,48 +209,8 @@ impl RpcSender for HttpSender {
                 return Err(response.error_for_status().unwrap_err().into());
             }
 
-            let mut json =
-                tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
-            if json["error"].is_object() {
-                return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
-                    Ok(rpc_error_object) => {
-                        let data = match rpc_error_object.code {
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
-                                        match serde_json::from_value::<RpcSimulateTransactionResult>(json["error"]["data"].clone()) {
-                                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
-                                            Err(err) => {
-                                                debug!("Failed to deserialize RpcSimulateTransactionResult: {:?}", err);
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
-                                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(json["error"]["data"].clone()) {
-                                            Ok(rpc_custom_error::NodeUnhealthyErrorData {num_slots_behind}) => RpcResponseErrorData::NodeUnhealthy {num_slots_behind},
-                                            Err(_err) => {
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    _ => RpcResponseErrorData::Empty
-                                };
-
-                        Err(RpcError::RpcResponseError {
-                            code: rpc_error_object.code,
-                            message: rpc_error_object.message,
-                            data,
-                        }
-                        .into())
-                    }
-                    Err(err) => Err(RpcError::RpcRequestError(format!(
-                        "Failed to deserialize RPC error response: {} [{}]",
-                        serde_json::to_string(&json["error"]).unwrap(),
-                        err
-                    ))
-                    .into()),
-                };
-            }
-            return Ok(json["result"].take());
+            let json = tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
+            return rpc_result(json);
         }
     }
 }
@@ -2,6 +2,7 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod async_rpc_client;
 pub mod blockhash_query;
 pub mod client_error;
 pub mod http_sender;
@@ -21,4 +22,5 @@ pub mod rpc_sender;
 pub mod spinner;
 pub mod thin_client;
 pub mod tpu_client;
+pub mod transaction_confirmer;
 pub mod transaction_executor;
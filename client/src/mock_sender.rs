@@ -6,17 +6,19 @@ use {
         rpc_config::RpcBlockProductionConfig,
         rpc_request::RpcRequest,
         rpc_response::{
-            Response, RpcAccountBalance, RpcBlockProduction, RpcBlockProductionRange, RpcBlockhash,
-            RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcFees, RpcIdentity,
+            Response, RpcAccountBalance, RpcAccountStatus, RpcBlockProduction,
+            RpcBlockProductionRange, RpcBlockhash, RpcConfirmedTransactionStatusWithSignature,
+            RpcContactInfo, RpcErrorCodes, RpcFees, RpcHealthDetail, RpcIdentity,
             RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcKeyedAccount,
-            RpcPerfSample, RpcResponseContext, RpcSimulateTransactionResult, RpcSnapshotSlotInfo,
+            RpcMultipleAccounts, RpcMultipleAccountsEntry, RpcPerfSample, RpcResponseContext,
+            RpcSimulateTransactionResult, RpcSlotTimeEstimate, RpcSnapshotSlotInfo,
             RpcStakeActivation, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
             RpcVoteAccountStatus, StakeActivationState,
         },
         rpc_sender::*,
     },
     serde_json::{json, Number, Value},
-    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_account_decoder::{UiAccount, UiAccountCompression, UiAccountEncoding},
     solana_sdk::{
         account::Account,
         clock::{Slot, UnixTimestamp},
@@ -222,6 +224,11 @@ impl RpcSender for MockSender {
                             pre_token_balances: None,
                             post_token_balances: None,
                             rewards: None,
+                            loaded_addresses: None,
+                            compute_budget_exceeded: None,
+                            compute_units_consumed: None,
+                            fee_details: None,
+                            loaded_accounts_data_size: None,
                         }),
                 },
                 block_time: Some(1628633791),
@@ -308,6 +315,7 @@ impl RpcSender for MockSender {
                         node_pubkey: PUBKEY.to_string(),
                         activated_stake: 0,
                         commission: 0,
+                        epoch_start_commission: None,
                         epoch_vote_account: false,
                         epoch_credits: vec![],
                         last_vote: 0,
@@ -333,6 +341,7 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    compute_budget_exceeded: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -341,6 +350,7 @@ impl RpcSender for MockSender {
                 json!(RpcVersionInfo {
                     solana_core: version.to_string(),
                     feature_set: Some(version.feature_set),
+                    account_compression: Some(UiAccountCompression::ALL.to_vec()),
                 })
             }
             "getLatestBlockhash" => serde_json::to_value(Response {
@@ -358,7 +368,11 @@ impl RpcSender for MockSender {
                 pubkey: PUBKEY.to_string(),
                 gossip: Some(SocketAddr::from(([10, 239, 6, 48], 8899))),
                 tpu: Some(SocketAddr::from(([10, 239, 6, 48], 8856))),
+                tpu_forwards: Some(SocketAddr::from(([10, 239, 6, 48], 8857))),
+                tpu_vote: Some(SocketAddr::from(([10, 239, 6, 48], 8858))),
+                serve_repair: Some(SocketAddr::from(([10, 239, 6, 48], 8859))),
                 rpc: Some(SocketAddr::from(([10, 239, 6, 48], 8899))),
+                pubsub: Some(SocketAddr::from(([10, 239, 6, 48], 8900))),
                 version: Some("1.0.0 c375ce1f".to_string()),
                 feature_set: None,
                 shred_version: None,
@@ -396,12 +410,26 @@ impl RpcSender for MockSender {
             }
             "getBlockTime" => serde_json::to_value(UnixTimestamp::default())?,
             "getEpochSchedule" => serde_json::to_value(EpochSchedule::default())?,
+            "getErrorCodes" => serde_json::to_value(RpcErrorCodes::default())?,
             "getRecentPerformanceSamples" => serde_json::to_value(vec![RpcPerfSample {
                 slot: 347873,
                 num_transactions: 125,
                 num_slots: 123,
                 sample_period_secs: 60,
             }])?,
+            "getSlotTimeEstimate" => serde_json::to_value(RpcSlotTimeEstimate {
+                current_slot: 34,
+                current_time: 1_628_633_791,
+                ms_per_slot: 487.8,
+                slot: 64,
+                estimated_time: 1_628_633_806,
+            })?,
+            "getHealthDetail" => serde_json::to_value(RpcHealthDetail {
+                status: "ok".to_string(),
+                num_slots_behind: None,
+                identity: PUBKEY.to_string(),
+                duplicate_instances: vec![],
+            })?,
             "getIdentity" => serde_json::to_value(RpcIdentity {
                 identity: PUBKEY.to_string(),
             })?,
@@ -430,10 +458,30 @@ impl RpcSender for MockSender {
                 })])?,
             "minimumLedgerSlot" => json![123],
             "getMaxRetransmitSlot" => json![123],
-            "getMultipleAccounts" => serde_json::to_value(Response {
-                context: RpcResponseContext { slot: 1 },
-                value: vec![Value::Null, Value::Null]
-            })?,
+            "getMultipleAccounts" => {
+                let with_status = params.as_array().unwrap().get(1).map(|config| {
+                    config["withStatus"].as_bool().unwrap_or_default()
+                        || config["allowPartial"].as_bool().unwrap_or_default()
+                }).unwrap_or_default();
+                if with_status {
+                    let num_pubkeys = params.as_array().unwrap()[0].as_array().unwrap().len();
+                    serde_json::to_value(Response {
+                        context: RpcResponseContext { slot: 1 },
+                        value: RpcMultipleAccounts {
+                            accounts: vec![RpcMultipleAccountsEntry {
+                                account: None,
+                                status: RpcAccountStatus::DoesNotExist,
+                            }; num_pubkeys],
+                            remaining: 0,
+                        },
+                    })?
+                } else {
+                    serde_json::to_value(Response {
+                        context: RpcResponseContext { slot: 1 },
+                        value: vec![Value::Null, Value::Null]
+                    })?
+                }
+            }
             "getProgramAccounts" => {
                 let pubkey = Pubkey::from_str(&PUBKEY.to_string()).unwrap();
                 let account = Account {
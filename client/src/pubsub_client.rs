@@ -1,8 +1,8 @@
 use {
     crate::{
         rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         rpc_response::{
             Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult,
@@ -19,6 +19,7 @@ use {
     solana_account_decoder::UiAccount,
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
     std::{
+        io::ErrorKind,
         marker::PhantomData,
         net::TcpStream,
         sync::{
@@ -49,6 +50,10 @@ pub enum PubsubClientError {
     UnexpectedMessageError(String),
 }
 
+/// Longest the receive loop of a subscription holds the socket waiting for a message, before
+/// checking whether to exit
+const SOCKET_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
 pub struct PubsubClientSubscription<T>
 where
     T: DeserializeOwned,
@@ -66,6 +71,13 @@ where
     T: DeserializeOwned,
 {
     fn drop(&mut self) {
+        // The receive loop is joined first, as it holds the socket until its read times out
+        self.exit.store(true, Ordering::Relaxed);
+        if let Some(t_cleanup) = self.t_cleanup.take() {
+            t_cleanup
+                .join()
+                .unwrap_or_else(|_| warn!("websocket thread panicked"));
+        }
         self.send_unsubscribe()
             .unwrap_or_else(|_| warn!("unable to unsubscribe from websocket"));
         self.socket
@@ -224,11 +236,36 @@ fn connect_with_retry(
     }
 }
 
+fn set_read_timeout(
+    socket: &WebSocket<MaybeTlsStream<TcpStream>>,
+    timeout: Option<Duration>,
+) -> std::io::Result<()> {
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(timeout),
+        MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(timeout),
+        _ => Ok(()),
+    }
+}
+
 impl PubsubClient {
     pub fn account_subscribe(
         url: &str,
         pubkey: &Pubkey,
         config: Option<RpcAccountInfoConfig>,
+    ) -> Result<AccountSubscription, PubsubClientError> {
+        Self::account_subscribe_with_config(
+            url,
+            pubkey,
+            config.map(RpcAccountSubscribeConfig::from),
+        )
+    }
+
+    /// Subscribes to the account of `pubkey`, with the subscription options beyond those of
+    /// `RpcAccountInfoConfig`, such as skipping the notifications of unchanged accounts
+    pub fn account_subscribe_with_config(
+        url: &str,
+        pubkey: &Pubkey,
+        config: Option<RpcAccountSubscribeConfig>,
     ) -> Result<AccountSubscription, PubsubClientError> {
         let url = Url::parse(url)?;
         let socket = connect_with_retry(url)?;
@@ -513,6 +550,9 @@ impl PubsubClient {
         T: DeserializeOwned,
         F: Fn(T) + Send + 'static,
     {
+        if let Err(err) = set_read_timeout(&socket.read().unwrap(), Some(SOCKET_READ_TIMEOUT)) {
+            warn!("unable to set the websocket read timeout: {:?}", err);
+        }
         loop {
             if exit.load(Ordering::Relaxed) {
                 break;
@@ -520,6 +560,8 @@ impl PubsubClient {
 
             match PubsubClientSubscription::read_message(socket) {
                 Ok(message) => handler(message),
+                Err(PubsubClientError::ConnectionError(tungstenite::Error::Io(err)))
+                    if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
                 Err(err) => {
                     info!("receive error: {:?}", err);
                     break;
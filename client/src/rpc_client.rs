@@ -3240,6 +3240,7 @@ impl RpcClient {
     /// let config = RpcLeaderScheduleConfig {
     ///     identity: Some(validator_pubkey_str),
     ///     commitment: Some(CommitmentConfig::processed()),
+    ///     ..RpcLeaderScheduleConfig::default()
     /// };
     /// let leader_schedule = rpc_client.get_leader_schedule_with_config(
     ///     Some(slot),
@@ -3278,6 +3279,30 @@ impl RpcClient {
         self.send(RpcRequest::GetEpochSchedule, Value::Null)
     }
 
+    /// Returns the stable numeric codes of the transaction and instruction errors known to the
+    /// node.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getErrorCodes`] RPC method.
+    ///
+    /// [`getErrorCodes`]: https://docs.solana.com/developing/clients/jsonrpc-api#geterrorcodes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let error_codes = rpc_client.get_error_codes()?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_error_codes(&self) -> ClientResult<RpcErrorCodes> {
+        self.send(RpcRequest::GetErrorCodes, Value::Null)
+    }
+
     /// Returns a list of recent performance samples, in reverse slot order.
     ///
     /// Performance samples are taken every 60 seconds and include the number of
@@ -3310,6 +3335,42 @@ impl RpcClient {
         self.send(RpcRequest::GetRecentPerformanceSamples, json!([limit]))
     }
 
+    /// Estimates the wall-clock time of a slot, or of the start of an epoch.
+    ///
+    /// The estimate extrapolates the time of the current slot with a moving
+    /// average of the slot times of the recent performance samples, which is
+    /// returned as well. The start of the next epoch is estimated when neither
+    /// a slot nor an epoch are given.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getSlotTimeEstimate`] RPC method.
+    ///
+    /// [`getSlotTimeEstimate`]: https://docs.solana.com/developing/clients/jsonrpc-api#getslottimeestimate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcSlotTimeEstimateConfig,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let config = RpcSlotTimeEstimateConfig {
+    ///     slot: Some(64),
+    ///     ..RpcSlotTimeEstimateConfig::default()
+    /// };
+    /// let estimate = rpc_client.get_slot_time_estimate(config)?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_slot_time_estimate(
+        &self,
+        config: RpcSlotTimeEstimateConfig,
+    ) -> ClientResult<RpcSlotTimeEstimate> {
+        self.send(RpcRequest::GetSlotTimeEstimate, json!([config]))
+    }
+
     /// Returns the identity pubkey for the current node.
     ///
     /// # RPC Reference
@@ -3596,6 +3657,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::Base64Zstd),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            min_context_slot: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -3629,6 +3691,78 @@ impl RpcClient {
             })?
     }
 
+    /// Returns all information associated with the account of the provided pubkey, requested
+    /// with the given configuration.
+    ///
+    /// The account data is requested with the `base64+zstd` encoding unless another encoding
+    /// or a compression is given, and the commitment of the client unless another is given.
+    /// If the account does not exist, this method returns `Ok(None)`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getAccountInfo`] RPC method.
+    ///
+    /// [`getAccountInfo`]: https://docs.solana.com/developing/clients/jsonrpc-api#getaccountinfo
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_account_decoder::UiAccountCompression;
+    /// # use solana_client::{
+    /// #     rpc_client::{self, RpcClient},
+    /// #     rpc_config::RpcCompressedAccountInfoConfig,
+    /// #     client_error::ClientError,
+    /// # };
+    /// # use solana_sdk::pubkey::Pubkey;
+    /// # use std::str::FromStr;
+    /// # let mocks = rpc_client::create_rpc_client_mocks();
+    /// # let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+    /// let alice_pubkey = Pubkey::from_str("BgvYtJEfmZYdVKiptmMjxGzv8iQoo4MWjsP3QsTkhhxa").unwrap();
+    /// let config = RpcCompressedAccountInfoConfig {
+    ///     compression: Some(UiAccountCompression::Gzip),
+    ///     ..RpcCompressedAccountInfoConfig::default()
+    /// };
+    /// let account = rpc_client.get_account_with_config(&alice_pubkey, config)?;
+    /// assert!(account.value.is_some());
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcCompressedAccountInfoConfig,
+    ) -> RpcResult<Option<Account>> {
+        let RpcCompressedAccountInfoConfig {
+            account_config,
+            compression,
+        } = config;
+        // The compressed data is encoded in base64, which is then requested by default
+        let default_encoding = compression.is_none().then(|| UiAccountEncoding::Base64Zstd);
+        let commitment = match account_config.commitment {
+            Some(commitment) => commitment,
+            None => self.commitment(),
+        };
+        let config = RpcCompressedAccountInfoConfig {
+            account_config: RpcAccountInfoConfig {
+                encoding: account_config.encoding.or(default_encoding),
+                commitment: Some(self.maybe_map_commitment(commitment)?),
+                ..account_config
+            },
+            compression,
+        };
+        let Response {
+            context,
+            value: rpc_account,
+        } = self.send::<Response<Option<UiAccount>>>(
+            RpcRequest::GetAccountInfo,
+            json!([pubkey.to_string(), config]),
+        )?;
+        trace!("Response account {:?} {:?}", pubkey, rpc_account);
+        Ok(Response {
+            context,
+            value: rpc_account.and_then(|rpc_account| rpc_account.decode()),
+        })
+    }
+
     /// Get the max slot seen from retransmit stage.
     ///
     /// # RPC Reference
@@ -3753,6 +3887,7 @@ impl RpcClient {
                 encoding: Some(UiAccountEncoding::Base64Zstd),
                 commitment: Some(self.maybe_map_commitment(commitment_config)?),
                 data_slice: None,
+                min_context_slot: None,
             },
         )
     }
@@ -3820,6 +3955,113 @@ impl RpcClient {
         })
     }
 
+    /// Returns the account information for a list of pubkeys, along with the
+    /// reason each missing account wasn't found.
+    ///
+    /// Any number of pubkeys can be requested: when the node serves only part
+    /// of them, the remaining ones are requested again at the slot of the
+    /// first response, or a later one.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getMultipleAccounts`] RPC method.
+    ///
+    /// [`getMultipleAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getmultipleaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcAccountInfoConfig,
+    /// #     rpc_response::RpcAccountStatus,
+    /// #     client_error::ClientError,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// let pubkeys = vec![alice.pubkey(), bob.pubkey()];
+    /// let accounts = rpc_client
+    ///     .get_multiple_accounts_with_status(&pubkeys, RpcAccountInfoConfig::default())?
+    ///     .value;
+    /// for (pubkey, (_account, status)) in pubkeys.iter().zip(accounts) {
+    ///     if status == RpcAccountStatus::NotFoundAtCommitment {
+    ///         println!("{} isn't confirmed yet", pubkey);
+    ///     }
+    /// }
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_multiple_accounts_with_status(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<(Option<Account>, RpcAccountStatus)>> {
+        let mut config = RpcMultipleAccountsConfig {
+            account_config: RpcAccountInfoConfig {
+                encoding: config.encoding.or(Some(UiAccountEncoding::Base64Zstd)),
+                commitment: config.commitment.or_else(|| Some(self.commitment())),
+                ..config
+            },
+            with_status: Some(true),
+            allow_partial: Some(true),
+            compression: None,
+        };
+        let mut context = None;
+        let mut accounts = Vec::with_capacity(pubkeys.len());
+        let mut pubkeys: Vec<_> = pubkeys.iter().map(|pubkey| pubkey.to_string()).collect();
+        loop {
+            let response = self.send(
+                RpcRequest::GetMultipleAccounts,
+                json!([pubkeys, config.clone()]),
+            )?;
+            let Response {
+                context: response_context,
+                value:
+                    RpcMultipleAccounts {
+                        accounts: response_accounts,
+                        remaining,
+                    },
+            } = serde_json::from_value::<Response<RpcMultipleAccounts>>(response)?;
+            let num_served = pubkeys.len().saturating_sub(remaining);
+            if response_accounts.len() != num_served || (remaining > 0 && num_served == 0) {
+                return Err(RpcError::ForUser(format!(
+                    "unexpected getMultipleAccounts response: {} accounts served and {} remaining \
+                     for {} pubkeys",
+                    response_accounts.len(),
+                    remaining,
+                    pubkeys.len()
+                ))
+                .into());
+            }
+            accounts.extend(response_accounts.into_iter().map(|entry| {
+                (
+                    entry.account.and_then(|account| account.decode()),
+                    entry.status,
+                )
+            }));
+            // The remaining accounts must be read at the slot of the first response or a later one
+            let context = context.get_or_insert(response_context);
+            if remaining == 0 {
+                return Ok(Response {
+                    context: context.clone(),
+                    value: accounts,
+                });
+            }
+            pubkeys.drain(..num_served);
+            config.account_config.min_context_slot = Some(
+                config
+                    .account_config
+                    .min_context_slot
+                    .unwrap_or_default()
+                    .max(context.slot),
+            );
+        }
+    }
+
     /// Gets the raw data associated with an account.
     ///
     /// This is equivalent to calling [`get_account`] and then accessing the
@@ -4060,6 +4302,7 @@ impl RpcClient {
     ///             length: 5,
     ///         }),
     ///         commitment: Some(CommitmentConfig::processed()),
+    ///         min_context_slot: None,
     ///     },
     ///     with_context: Some(false),
     /// };
@@ -4330,6 +4573,12 @@ impl RpcClient {
             .map(|_| ())
     }
 
+    /// Returns the node's health along with any duplicate running instances of validators that
+    /// it has seen in gossip.
+    pub fn get_health_detail(&self) -> ClientResult<RpcHealthDetail> {
+        self.send(RpcRequest::GetHealthDetail, Value::Null)
+    }
+
     pub fn get_token_account(&self, pubkey: &Pubkey) -> ClientResult<Option<UiTokenAccount>> {
         Ok(self
             .get_token_account_with_commitment(pubkey, self.commitment())?
@@ -4345,6 +4594,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            min_context_slot: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -4442,6 +4692,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            min_context_slot: None,
         };
 
         self.send(
@@ -4481,6 +4732,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            min_context_slot: None,
         };
 
         self.send(
@@ -4943,6 +5195,7 @@ mod tests {
     use jsonrpc_core::{futures::prelude::*, Error, IoHandler, Params};
     use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
     use serde_json::Number;
+    use solana_account_decoder::UiAccountCompression;
     use solana_sdk::{
         instruction::InstructionError,
         signature::{Keypair, Signer},
@@ -5164,4 +5417,86 @@ mod tests {
         let is_err = rpc_client.get_latest_blockhash().is_err();
         assert!(is_err);
     }
+
+    #[test]
+    fn test_get_account_with_config() {
+        let account = Account {
+            lamports: 42,
+            data: vec![7; 100],
+            ..Account::default()
+        };
+        let (sender, receiver) = channel();
+        let (params_sender, params_receiver) = channel();
+        let ui_account = account.clone();
+        thread::spawn(move || {
+            let rpc_addr = "0.0.0.0:0".parse().unwrap();
+            let mut io = IoHandler::default();
+            io.add_method("getVersion", |_params: Params| {
+                future::ok(json!({"solana-core": "1.9.0"}))
+            });
+            // Serves the account with the encoding requested, after reporting the config
+            io.add_method("getAccountInfo", move |params: Params| {
+                let (_pubkey, config): (String, RpcCompressedAccountInfoConfig) =
+                    params.parse().unwrap();
+                params_sender.send(config.clone()).unwrap();
+                let encoding = match config.compression {
+                    Some(compression) => compression.encoding(),
+                    None => config.account_config.encoding.unwrap(),
+                };
+                future::ok(json!(Response {
+                    context: RpcResponseContext { slot: 1 },
+                    value: Some(UiAccount::encode(
+                        &Pubkey::default(),
+                        &ui_account,
+                        encoding,
+                        None,
+                        None
+                    )),
+                }))
+            });
+            let server = ServerBuilder::new(io)
+                .threads(1)
+                .start_http(&rpc_addr)
+                .expect("Unable to start RPC server");
+            sender.send(*server.address()).unwrap();
+            server.wait();
+        });
+        let rpc_client = RpcClient::new_socket_with_commitment(
+            receiver.recv().unwrap(),
+            CommitmentConfig::confirmed(),
+        );
+
+        // The data is encoded in base64+zstd by default
+        let response = rpc_client
+            .get_account_with_config(
+                &Pubkey::default(),
+                RpcCompressedAccountInfoConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(response.value, Some(account.clone()));
+        let config = params_receiver.recv().unwrap();
+        assert_eq!(
+            config.account_config.encoding,
+            Some(UiAccountEncoding::Base64Zstd)
+        );
+        assert_eq!(
+            config.account_config.commitment,
+            Some(CommitmentConfig::confirmed())
+        );
+
+        // The encoding is left to the compression requested
+        let response = rpc_client
+            .get_account_with_config(
+                &Pubkey::default(),
+                RpcCompressedAccountInfoConfig {
+                    compression: Some(UiAccountCompression::Gzip),
+                    ..RpcCompressedAccountInfoConfig::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(response.value, Some(account));
+        let config = params_receiver.recv().unwrap();
+        assert_eq!(config.account_config.encoding, None);
+        assert_eq!(config.compression, Some(UiAccountCompression::Gzip));
+    }
 }
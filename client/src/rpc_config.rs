@@ -1,6 +1,6 @@
 use {
     crate::rpc_filter::RpcFilterType,
-    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_account_decoder::{UiAccountCompression, UiAccountEncoding, UiDataSliceConfig},
     solana_sdk::{
         clock::{Epoch, Slot},
         commitment_config::{CommitmentConfig, CommitmentLevel},
@@ -8,6 +8,14 @@ use {
     solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
 };
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContextConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSignatureStatusConfig {
@@ -44,6 +52,20 @@ pub struct RpcSimulateTransactionConfig {
     pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateBundleConfig {
+    #[serde(default)]
+    pub sig_verify: bool,
+    #[serde(default)]
+    pub replace_recent_blockhash: bool,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    /// Accounts returned after each transaction, unset for the transactions not loading them
+    pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcRequestAirdropConfig {
@@ -56,10 +78,28 @@ pub struct RpcRequestAirdropConfig {
 #[serde(rename_all = "camelCase")]
 pub struct RpcLeaderScheduleConfig {
     pub identity: Option<String>, // validator identity, as a base-58 encoded string
+    pub epoch: Option<Epoch>,     // instead of the epoch of a slot
+    pub encoding: Option<RpcLeaderScheduleEncoding>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcLeaderScheduleEncoding {
+    /// Slot indices relative to the first slot of the epoch
+    Absolute,
+    /// Differences between consecutive slot indices, the first one being absolute. Leaders get a
+    /// few consecutive slots, so most of the values are 1.
+    Delta,
+}
+
+impl Default for RpcLeaderScheduleEncoding {
+    fn default() -> Self {
+        Self::Absolute
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcBlockProductionConfigRange {
@@ -134,6 +174,17 @@ pub struct RpcEpochConfig {
     pub commitment: Option<CommitmentConfig>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSlotTimeEstimateConfig {
+    /// Slot to estimate the time of
+    pub slot: Option<Slot>,
+    /// Epoch to estimate the start time of, the next epoch when neither it nor `slot` are set
+    pub epoch: Option<Epoch>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcAccountInfoConfig {
@@ -141,6 +192,59 @@ pub struct RpcAccountInfoConfig {
     pub data_slice: Option<UiDataSliceConfig>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCompressedAccountInfoConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Compresses the account data server-side, the data is then encoded in base64. Only valid
+    /// with the base64 encoding or no encoding.
+    pub compression: Option<UiAccountCompression>,
+}
+
+impl From<RpcAccountInfoConfig> for RpcCompressedAccountInfoConfig {
+    fn from(account_config: RpcAccountInfoConfig) -> Self {
+        Self {
+            account_config,
+            compression: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountSubscribeConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Don't notify when the account was written to but its lamports, owner, executable flag
+    /// and data are the same as when it was last notified
+    pub skip_unchanged: Option<bool>,
+}
+
+impl From<RpcAccountInfoConfig> for RpcAccountSubscribeConfig {
+    fn from(account_config: RpcAccountInfoConfig) -> Self {
+        Self {
+            account_config,
+            skip_unchanged: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultipleAccountsConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Report why each missing account wasn't found, in an `RpcMultipleAccounts`
+    pub with_status: Option<bool>,
+    /// Serve the first pubkeys up to the limit of the node instead of failing when more are
+    /// requested, reporting how many were left out in an `RpcMultipleAccounts`
+    pub allow_partial: Option<bool>,
+    /// Compresses the account data server-side, as with `RpcCompressedAccountInfoConfig`
+    pub compression: Option<UiAccountCompression>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -158,6 +262,9 @@ pub enum RpcTransactionLogsFilter {
     All,
     AllWithVotes,
     Mentions(Vec<String>), // base58-encoded list of addresses
+    /// Transactions invoking any of the programs, at the top level or through cross-program
+    /// invocations
+    Programs(Vec<String>), // base58-encoded list of program ids
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -20,6 +20,10 @@ pub const JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE: i64 = -32011;
 pub const JSON_RPC_SCAN_ERROR: i64 = -32012;
 pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013;
 pub const JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET: i64 = -32014;
+pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32015;
+pub const JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_SEARCH_RATE_LIMITED: i64 = -32016;
+pub const JSON_RPC_SERVER_ERROR_ACCOUNTS_SCAN_QUEUE_TIMEOUT: i64 = -32017;
+pub const JSON_RPC_SERVER_ERROR_TRANSACTION_NONCE_VERIFICATION_FAILURE: i64 = -32018;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -57,6 +61,14 @@ pub enum RpcCustomError {
     TransactionSignatureLenMismatch,
     #[error("BlockStatusNotAvailableYet")]
     BlockStatusNotAvailableYet { slot: Slot },
+    #[error("MinContextSlotNotReached")]
+    MinContextSlotNotReached { context_slot: Slot },
+    #[error("TransactionHistorySearchRateLimited")]
+    TransactionHistorySearchRateLimited,
+    #[error("AccountsScanQueueTimeout")]
+    AccountsScanQueueTimeout,
+    #[error("TransactionNonceVerificationFailure")]
+    TransactionNonceVerificationFailure { message: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -65,6 +77,12 @@ pub struct NodeUnhealthyErrorData {
     pub num_slots_behind: Option<Slot>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinContextSlotNotReachedErrorData {
+    pub context_slot: Slot,
+}
+
 impl From<RpcCustomError> for Error {
     fn from(e: RpcCustomError) -> Self {
         match e {
@@ -169,6 +187,32 @@ impl From<RpcCustomError> for Error {
                 message: format!("Block status not yet available for slot {}", slot),
                 data: None,
             },
+            RpcCustomError::MinContextSlotNotReached { context_slot } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED),
+                message: "Minimum context slot has not been reached".to_string(),
+                data: Some(serde_json::json!(MinContextSlotNotReachedErrorData {
+                    context_slot
+                })),
+            },
+            RpcCustomError::TransactionHistorySearchRateLimited => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_SEARCH_RATE_LIMITED,
+                ),
+                message: "Too many transaction history searches, retry later".to_string(),
+                data: None,
+            },
+            RpcCustomError::AccountsScanQueueTimeout => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_ACCOUNTS_SCAN_QUEUE_TIMEOUT),
+                message: "Too many accounts scans in progress, retry later".to_string(),
+                data: None,
+            },
+            RpcCustomError::TransactionNonceVerificationFailure { message } => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_TRANSACTION_NONCE_VERIFICATION_FAILURE,
+                ),
+                message: format!("Transaction nonce verification failed: {}", message),
+                data: None,
+            },
         }
     }
 }
@@ -42,6 +42,7 @@ pub enum RpcRequest {
     GetConfirmedTransaction,
     GetEpochInfo,
     GetEpochSchedule,
+    GetErrorCodes,
     #[deprecated(
         since = "1.9.0",
         note = "Please use RpcRequest::GetFeeForMessage instead"
@@ -61,6 +62,7 @@ pub enum RpcRequest {
     GetFirstAvailableBlock,
     GetGenesisHash,
     GetHealth,
+    GetHealthDetail,
     GetIdentity,
     GetInflationGovernor,
     GetInflationRate,
@@ -90,6 +92,7 @@ pub enum RpcRequest {
     GetSlot,
     GetSlotLeader,
     GetSlotLeaders,
+    GetSlotTimeEstimate,
     GetStorageTurn,
     GetStorageTurnRate,
     GetSlotsPerSegment,
@@ -109,6 +112,7 @@ pub enum RpcRequest {
     RegisterNode,
     RequestAirdrop,
     SendTransaction,
+    SimulateBundle,
     SimulateTransaction,
     SignVote,
 }
@@ -135,6 +139,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetConfirmedTransaction => "getConfirmedTransaction",
             RpcRequest::GetEpochInfo => "getEpochInfo",
             RpcRequest::GetEpochSchedule => "getEpochSchedule",
+            RpcRequest::GetErrorCodes => "getErrorCodes",
             RpcRequest::GetFeeCalculatorForBlockhash => "getFeeCalculatorForBlockhash",
             RpcRequest::GetFeeForMessage => "getFeeForMessage",
             RpcRequest::GetFeeRateGovernor => "getFeeRateGovernor",
@@ -142,6 +147,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetFirstAvailableBlock => "getFirstAvailableBlock",
             RpcRequest::GetGenesisHash => "getGenesisHash",
             RpcRequest::GetHealth => "getHealth",
+            RpcRequest::GetHealthDetail => "getHealthDetail",
             RpcRequest::GetIdentity => "getIdentity",
             RpcRequest::GetInflationGovernor => "getInflationGovernor",
             RpcRequest::GetInflationRate => "getInflationRate",
@@ -163,6 +169,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetSlot => "getSlot",
             RpcRequest::GetSlotLeader => "getSlotLeader",
             RpcRequest::GetSlotLeaders => "getSlotLeaders",
+            RpcRequest::GetSlotTimeEstimate => "getSlotTimeEstimate",
             RpcRequest::GetStakeActivation => "getStakeActivation",
             RpcRequest::GetStorageTurn => "getStorageTurn",
             RpcRequest::GetStorageTurnRate => "getStorageTurnRate",
@@ -182,6 +189,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
             RpcRequest::SendTransaction => "sendTransaction",
+            RpcRequest::SimulateBundle => "simulateBundle",
             RpcRequest::SimulateTransaction => "simulateTransaction",
             RpcRequest::SignVote => "signVote",
         };
@@ -191,10 +199,15 @@ impl fmt::Display for RpcRequest {
 }
 
 pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
+/// Maximum number of signatures of a `getSignatureStatuses` request that doesn't search the
+/// transaction history
+pub const MAX_GET_SIGNATURE_STATUSES_RECENT_QUERY_ITEMS: usize = 1_024;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE: u64 = 10_000;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+/// Maximum number of transactions of a `simulateBundle` request
+pub const MAX_SIMULATE_BUNDLE_TRANSACTIONS: usize = 16;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
@@ -1,15 +1,17 @@
 use {
     crate::client_error,
-    solana_account_decoder::{parse_token::UiTokenAmount, UiAccount},
+    solana_account_decoder::{parse_token::UiTokenAmount, UiAccount, UiAccountCompression},
     solana_sdk::{
         clock::{Epoch, Slot, UnixTimestamp},
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         hash::Hash,
         inflation::Inflation,
-        transaction::{Result, TransactionError},
+        instruction::INSTRUCTION_ERROR_CODES,
+        transaction::{Result, TransactionError, TRANSACTION_ERROR_CODES},
     },
     solana_transaction_status::{
         ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus,
+        UiComputeBudgetExceeded,
     },
     std::{collections::HashMap, fmt, net::SocketAddr},
 };
@@ -123,6 +125,32 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcAccountStatus {
+    Found,
+    /// The account doesn't exist, not even in the most recent bank of the node
+    DoesNotExist,
+    /// The account doesn't exist at the requested commitment, but does in a more recent bank
+    NotFoundAtCommitment,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultipleAccountsEntry {
+    pub account: Option<UiAccount>,
+    pub status: RpcAccountStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultipleAccounts {
+    /// In the order of the requested pubkeys
+    pub accounts: Vec<RpcMultipleAccountsEntry>,
+    /// Number of trailing pubkeys left out of a partial response, to be requested again
+    pub remaining: usize,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -202,6 +230,10 @@ pub struct RpcLogsResponse {
     pub signature: String, // Signature as base58 string
     pub err: Option<TransactionError>,
     pub logs: Vec<String>,
+    /// Notifications of the subscription the RPC node dropped to cap their rate, since the
+    /// previous one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped_notifications: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -225,8 +257,16 @@ pub struct RpcContactInfo {
     pub gossip: Option<SocketAddr>,
     /// Tpu port
     pub tpu: Option<SocketAddr>,
+    /// Tpu forwards port
+    pub tpu_forwards: Option<SocketAddr>,
+    /// Tpu vote port
+    pub tpu_vote: Option<SocketAddr>,
+    /// Serve repair port
+    pub serve_repair: Option<SocketAddr>,
     /// JSON RPC port
     pub rpc: Option<SocketAddr>,
+    /// JSON RPC PubSub port
+    pub pubsub: Option<SocketAddr>,
     /// Software version
     pub version: Option<String>,
     /// First 4 bytes of the FeatureSet identifier
@@ -238,6 +278,30 @@ pub struct RpcContactInfo {
 /// Map of leader base58 identity pubkeys to the slot indices relative to the first epoch slot
 pub type RpcLeaderSchedule = HashMap<String, Vec<usize>>;
 
+/// Encodes the ascending slot indices of a leader as `RpcLeaderScheduleEncoding::Delta`
+pub fn delta_encode_leader_slot_indices(slot_indices: &[usize]) -> Vec<usize> {
+    let mut previous = 0;
+    slot_indices
+        .iter()
+        .map(|slot_index| {
+            let delta = slot_index.saturating_sub(previous);
+            previous = *slot_index;
+            delta
+        })
+        .collect()
+}
+
+/// Decodes the slot indices of a leader received as `RpcLeaderScheduleEncoding::Delta`
+pub fn delta_decode_leader_slot_indices(deltas: &[usize]) -> Vec<usize> {
+    deltas
+        .iter()
+        .scan(0, |slot_index, delta| {
+            *slot_index += delta;
+            Some(*slot_index)
+        })
+        .collect()
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcBlockProductionRange {
@@ -260,6 +324,10 @@ pub struct RpcVersionInfo {
     pub solana_core: String,
     /// first 4 bytes of the FeatureSet identifier
     pub feature_set: Option<u32>,
+    /// Compressions of the account data supported by the node, unset by the nodes that
+    /// predate them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_compression: Option<Vec<UiAccountCompression>>,
 }
 
 impl fmt::Debug for RpcVersionInfo {
@@ -286,6 +354,64 @@ pub struct RpcIdentity {
     pub identity: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcErrorCode {
+    pub code: u32,
+    pub name: String,
+}
+
+/// Stable numeric codes of the `TransactionError` and `InstructionError` variants
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcErrorCodes {
+    pub transaction_errors: Vec<RpcErrorCode>,
+    pub instruction_errors: Vec<RpcErrorCode>,
+}
+
+impl Default for RpcErrorCodes {
+    /// The error codes known to this release
+    fn default() -> Self {
+        let to_rpc_error_codes = |codes: &[(u32, &str)]| {
+            codes
+                .iter()
+                .map(|(code, name)| RpcErrorCode {
+                    code: *code,
+                    name: name.to_string(),
+                })
+                .collect()
+        };
+        Self {
+            transaction_errors: to_rpc_error_codes(TRANSACTION_ERROR_CODES),
+            instruction_errors: to_rpc_error_codes(INSTRUCTION_ERROR_CODES),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHealthDetail {
+    /// Node health as reported by `getHealth`: "ok", "behind" or "unknown"
+    pub status: String,
+    pub num_slots_behind: Option<Slot>,
+    /// The current node identity pubkey
+    pub identity: String,
+    /// Nodes seen running more than one instance of the same identity
+    pub duplicate_instances: Vec<RpcDuplicateInstance>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDuplicateInstance {
+    /// Identity pubkey of the duplicated node
+    pub identity: String,
+    pub num_instances: usize,
+    /// Milliseconds since the Unix epoch at which the duplicate was first detected
+    pub first_detected: u64,
+    /// Milliseconds since the Unix epoch at which any of the instances was last seen
+    pub last_seen: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcVote {
@@ -316,6 +442,10 @@ pub struct RpcVoteAccountInfo {
     /// An 8-bit integer used as a fraction (commission/MAX_U8) for rewards payout
     pub commission: u8,
 
+    /// The commission at the start of the current epoch, if it has changed since
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch_start_commission: Option<u8>,
+
     /// Whether this account is staked for the current epoch
     pub epoch_vote_account: bool,
 
@@ -344,6 +474,8 @@ pub struct RpcSimulateTransactionResult {
     pub logs: Option<Vec<String>>,
     pub accounts: Option<Vec<Option<UiAccount>>>,
     pub units_consumed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_budget_exceeded: Option<UiComputeBudgetExceeded>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -414,6 +546,17 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSlotTimeEstimate {
+    pub current_slot: Slot,
+    pub current_time: UnixTimestamp,
+    /// Moving average of the recent slot times, in milliseconds
+    pub ms_per_slot: f64,
+    pub slot: Slot,
+    pub estimated_time: UnixTimestamp,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
@@ -0,0 +1,230 @@
+//! The `transaction_confirmer` module waits for a sent transaction to reach a commitment level,
+//! until the last block height at which its blockhash is valid. Signature notifications of the
+//! websocket of the node are used when available, and the status of the transaction is polled
+//! otherwise. It's meant for callers which know the last valid block height of the transaction,
+//! `RpcClient::send_and_confirm_transaction_with_spinner` keeps confirming with its own timeouts.
+
+use {
+    crate::{
+        client_error::Result as ClientResult,
+        pubsub_client::{PubsubClient, SignatureSubscription},
+        rpc_client::RpcClient,
+        rpc_config::RpcSignatureSubscribeConfig,
+        rpc_response::RpcSignatureResult,
+    },
+    log::*,
+    solana_sdk::{
+        clock::Slot, commitment_config::CommitmentConfig, signature::Signature,
+        transaction::TransactionError,
+    },
+    std::{sync::mpsc::RecvTimeoutError, thread::sleep, time::Duration},
+};
+
+/// Interval between two polls of the status of a transaction, by default
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmationOutcome {
+    /// The transaction succeeded in `slot`, which reached the commitment level
+    Confirmed { slot: Slot },
+    /// The transaction failed in `slot`, which reached the commitment level
+    Failed { slot: Slot, err: TransactionError },
+    /// The block height went past the last valid block height of the transaction before it was
+    /// processed, it can't be anymore
+    Expired,
+}
+
+pub struct TransactionConfirmer<'a> {
+    rpc_client: &'a RpcClient,
+    websocket_url: Option<String>,
+    commitment: CommitmentConfig,
+    poll_interval: Duration,
+}
+
+impl<'a> TransactionConfirmer<'a> {
+    pub fn new(rpc_client: &'a RpcClient, commitment: CommitmentConfig) -> Self {
+        Self {
+            rpc_client,
+            websocket_url: None,
+            commitment,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Waits for the signature notifications of the websocket at `websocket_url`, instead of only
+    /// polling the status of the transaction
+    pub fn with_websocket_url(mut self, websocket_url: String) -> Self {
+        self.websocket_url = Some(websocket_url);
+        self
+    }
+
+    /// Interval between two polls of the status of the transaction, or only of the block height
+    /// while waiting for a signature notification
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Waits until the transaction of `signature` reaches the commitment level, or until it
+    /// expires once the block height is past `last_valid_block_height`
+    pub fn confirm(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> ClientResult<ConfirmationOutcome> {
+        if let Some(websocket_url) = &self.websocket_url {
+            match PubsubClient::signature_subscribe(
+                websocket_url,
+                signature,
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(self.commitment),
+                    enable_received_notification: Some(false),
+                }),
+            ) {
+                Ok(subscription) => {
+                    return self.confirm_with_subscription(
+                        signature,
+                        last_valid_block_height,
+                        subscription,
+                    )
+                }
+                Err(err) => debug!(
+                    "Unable to subscribe to {}, polling its status instead: {}",
+                    signature, err
+                ),
+            }
+        }
+        self.poll(signature, last_valid_block_height)
+    }
+
+    fn poll(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> ClientResult<ConfirmationOutcome> {
+        loop {
+            if let Some(outcome) = self.check(signature, last_valid_block_height)? {
+                return Ok(outcome);
+            }
+            sleep(self.poll_interval);
+        }
+    }
+
+    fn confirm_with_subscription(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+        subscription: SignatureSubscription,
+    ) -> ClientResult<ConfirmationOutcome> {
+        // Dropping the subscription on any return unsubscribes, closes the websocket and joins
+        // its receive thread
+        let (_client_subscription, receiver) = subscription;
+        // The transaction may have reached the commitment level before the subscription
+        if let Some(outcome) = self.check(signature, last_valid_block_height)? {
+            return Ok(outcome);
+        }
+        loop {
+            match receiver.recv_timeout(self.poll_interval) {
+                Ok(response) => {
+                    if let RpcSignatureResult::ProcessedSignature(result) = response.value {
+                        let slot = response.context.slot;
+                        return Ok(match result.err {
+                            Some(err) => ConfirmationOutcome::Failed { slot, err },
+                            None => ConfirmationOutcome::Confirmed { slot },
+                        });
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // Only the block height is polled, for the expiration to be noticed
+                    let block_height = self
+                        .rpc_client
+                        .get_block_height_with_commitment(self.commitment)?;
+                    if block_height > last_valid_block_height {
+                        if let Some(outcome) = self.check(signature, last_valid_block_height)? {
+                            return Ok(outcome);
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    debug!("Subscription to {} closed, polling its status", signature);
+                    return self.poll(signature, last_valid_block_height);
+                }
+            }
+        }
+    }
+
+    /// Checks the status of the transaction once, `None` if it's still pending
+    fn check(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> ClientResult<Option<ConfirmationOutcome>> {
+        // The block height is fetched first, so that a transaction processed in between isn't
+        // reported expired
+        let block_height = self
+            .rpc_client
+            .get_block_height_with_commitment(self.commitment)?;
+        let status = self
+            .rpc_client
+            .get_signature_statuses(&[*signature])?
+            .value
+            .pop()
+            .flatten();
+        Ok(match status {
+            Some(status) => {
+                status
+                    .satisfies_commitment(self.commitment)
+                    .then(|| match status.err {
+                        Some(err) => ConfirmationOutcome::Failed {
+                            slot: status.slot,
+                            err,
+                        },
+                        None => ConfirmationOutcome::Confirmed { slot: status.slot },
+                    })
+            }
+            None => (block_height > last_valid_block_height).then(|| ConfirmationOutcome::Expired),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::instruction::InstructionError};
+
+    #[test]
+    fn test_confirm_by_polling() {
+        let signature = Signature::default();
+        // The mock block height is 1234
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let confirmer = TransactionConfirmer::new(&rpc_client, CommitmentConfig::finalized());
+        assert_eq!(
+            confirmer.confirm(&signature, 1_000).unwrap(),
+            ConfirmationOutcome::Confirmed { slot: 1 }
+        );
+
+        let rpc_client = RpcClient::new_mock("instruction_error".to_string());
+        let confirmer = TransactionConfirmer::new(&rpc_client, CommitmentConfig::finalized());
+        assert_eq!(
+            confirmer.confirm(&signature, 1_000).unwrap(),
+            ConfirmationOutcome::Failed {
+                slot: 1,
+                err: TransactionError::InstructionError(0, InstructionError::UninitializedAccount),
+            }
+        );
+
+        let rpc_client = RpcClient::new_mock("sig_not_found".to_string());
+        let confirmer = TransactionConfirmer::new(&rpc_client, CommitmentConfig::finalized());
+        assert_eq!(
+            confirmer.confirm(&signature, 1_000).unwrap(),
+            ConfirmationOutcome::Expired
+        );
+
+        // Unreachable websockets fall back to polling
+        let confirmer = TransactionConfirmer::new(&rpc_client, CommitmentConfig::finalized())
+            .with_websocket_url("ws://127.0.0.1:1".to_string());
+        assert_eq!(
+            confirmer.confirm(&signature, 1_000).unwrap(),
+            ConfirmationOutcome::Expired
+        );
+    }
+}
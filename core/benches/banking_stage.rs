@@ -1,8 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
-#![feature(test)]
-
-extern crate test;
 
+use criterion::{criterion_group, criterion_main, Criterion};
 use crossbeam_channel::unbounded;
 use log::*;
 use rand::{thread_rng, Rng};
@@ -36,7 +34,6 @@ use std::sync::atomic::Ordering;
 use std::sync::mpsc::Receiver;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
-use test::Bencher;
 
 fn check_txs(receiver: &Arc<Receiver<WorkingBankEntry>>, ref_tx_count: usize) {
     let mut total = 0;
@@ -55,8 +52,7 @@ fn check_txs(receiver: &Arc<Receiver<WorkingBankEntry>>, ref_tx_count: usize) {
     assert_eq!(total, ref_tx_count);
 }
 
-#[bench]
-fn bench_consume_buffered(bencher: &mut Bencher) {
+fn bench_consume_buffered(c: &mut Criterion) {
     let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100_000);
     let bank = Arc::new(Bank::new_for_benches(&genesis_config));
     let ledger_path = get_tmp_ledger_path!();
@@ -77,24 +73,26 @@ fn bench_consume_buffered(bencher: &mut Bencher) {
         let mut packets = VecDeque::new();
         for batch in batches {
             let batch_len = batch.packets.len();
-            packets.push_back((batch, vec![0usize; batch_len], false));
+            packets.push_back((batch, vec![0usize; batch_len], false, 0));
         }
         let (s, _r) = unbounded();
         // This tests the performance of buffering packets.
         // If the packet buffers are copied, performance will be poor.
-        bencher.iter(move || {
-            let _ignored = BankingStage::consume_buffered_packets(
-                &my_pubkey,
-                std::u128::MAX,
-                &poh_recorder,
-                &mut packets,
-                None,
-                &s,
-                None::<Box<dyn Fn()>>,
-                &BankingStageStats::default(),
-                &recorder,
-                &Arc::new(RwLock::new(CostModel::default())),
-            );
+        c.bench_function("bench_consume_buffered", |bencher| {
+            bencher.iter(|| {
+                let _ignored = BankingStage::consume_buffered_packets(
+                    &my_pubkey,
+                    std::u128::MAX,
+                    &poh_recorder,
+                    &mut packets,
+                    None,
+                    &s,
+                    None::<Box<dyn Fn()>>,
+                    &BankingStageStats::default(),
+                    &recorder,
+                    &Arc::new(RwLock::new(CostModel::default())),
+                );
+            });
         });
 
         exit.store(true, Ordering::Relaxed);
@@ -141,7 +139,7 @@ enum TransactionType {
     Programs,
 }
 
-fn bench_banking(bencher: &mut Bencher, tx_type: TransactionType) {
+fn bench_banking(c: &mut Criterion, name: &str, tx_type: TransactionType) {
     solana_logger::setup();
     let num_threads = BankingStage::num_threads() as usize;
     //   a multiple of packet chunk duplicates to avoid races
@@ -226,48 +224,48 @@ fn bench_banking(bencher: &mut Bencher, tx_type: TransactionType) {
             None,
             s,
             Arc::new(RwLock::new(CostModel::default())),
+            None,
+            None,
         );
         poh_recorder.lock().unwrap().set_bank(&bank);
 
         let chunk_len = verified.len() / CHUNKS;
         let mut start = 0;
 
-        // This is so that the signal_receiver does not go out of scope after the closure.
-        // If it is dropped before poh_service, then poh_service will error when
-        // calling send() on the channel.
         let signal_receiver = Arc::new(signal_receiver);
-        let signal_receiver2 = signal_receiver;
-        bencher.iter(move || {
-            let now = Instant::now();
-            let mut sent = 0;
-
-            for v in verified[start..start + chunk_len].chunks(chunk_len / num_threads) {
-                debug!(
-                    "sending... {}..{} {} v.len: {}",
-                    start,
-                    start + chunk_len,
-                    timestamp(),
-                    v.len(),
-                );
-                for xv in v {
-                    sent += xv.packets.len();
+        c.bench_function(name, |bencher| {
+            bencher.iter(|| {
+                let now = Instant::now();
+                let mut sent = 0;
+
+                for v in verified[start..start + chunk_len].chunks(chunk_len / num_threads) {
+                    debug!(
+                        "sending... {}..{} {} v.len: {}",
+                        start,
+                        start + chunk_len,
+                        timestamp(),
+                        v.len(),
+                    );
+                    for xv in v {
+                        sent += xv.packets.len();
+                    }
+                    verified_sender.send(v.to_vec()).unwrap();
                 }
-                verified_sender.send(v.to_vec()).unwrap();
-            }
-            check_txs(&signal_receiver2, txes / CHUNKS);
-
-            // This signature clear may not actually clear the signatures
-            // in this chunk, but since we rotate between CHUNKS then
-            // we should clear them by the time we come around again to re-use that chunk.
-            bank.clear_signatures();
-            trace!(
-                "time: {} checked: {} sent: {}",
-                duration_as_us(&now.elapsed()),
-                txes / CHUNKS,
-                sent,
-            );
-            start += chunk_len;
-            start %= verified.len();
+                check_txs(&signal_receiver, txes / CHUNKS);
+
+                // This signature clear may not actually clear the signatures
+                // in this chunk, but since we rotate between CHUNKS then
+                // we should clear them by the time we come around again to re-use that chunk.
+                bank.clear_signatures();
+                trace!(
+                    "time: {} checked: {} sent: {}",
+                    duration_as_us(&now.elapsed()),
+                    txes / CHUNKS,
+                    sent,
+                );
+                start += chunk_len;
+                start %= verified.len();
+            });
         });
         drop(tpu_vote_sender);
         drop(vote_sender);
@@ -277,14 +275,20 @@ fn bench_banking(bencher: &mut Bencher, tx_type: TransactionType) {
     let _unused = Blockstore::destroy(&ledger_path);
 }
 
-#[bench]
-fn bench_banking_stage_multi_accounts(bencher: &mut Bencher) {
-    bench_banking(bencher, TransactionType::Accounts);
+fn bench_banking_stage_multi_accounts(c: &mut Criterion) {
+    bench_banking(
+        c,
+        "bench_banking_stage_multi_accounts",
+        TransactionType::Accounts,
+    );
 }
 
-#[bench]
-fn bench_banking_stage_multi_programs(bencher: &mut Bencher) {
-    bench_banking(bencher, TransactionType::Programs);
+fn bench_banking_stage_multi_programs(c: &mut Criterion) {
+    bench_banking(
+        c,
+        "bench_banking_stage_multi_programs",
+        TransactionType::Programs,
+    );
 }
 
 fn simulate_process_entries(
@@ -325,7 +329,7 @@ fn simulate_process_entries(
 }
 
 #[allow(clippy::same_item_push)]
-fn bench_process_entries(randomize_txs: bool, bencher: &mut Bencher) {
+fn bench_process_entries(c: &mut Criterion, name: &str, randomize_txs: bool) {
     // entropy multiplier should be big enough to provide sufficient entropy
     // but small enough to not take too much time while executing the test.
     let entropy_multiplier: usize = 25;
@@ -348,25 +352,35 @@ fn bench_process_entries(randomize_txs: bool, bencher: &mut Bencher) {
         keypairs.push(keypair);
     }
 
-    bencher.iter(|| {
-        simulate_process_entries(
-            randomize_txs,
-            &mint_keypair,
-            tx_vector.clone(),
-            &genesis_config,
-            &keypairs,
-            initial_lamports,
-            num_accounts,
-        );
+    c.bench_function(name, |bencher| {
+        bencher.iter(|| {
+            simulate_process_entries(
+                randomize_txs,
+                &mint_keypair,
+                tx_vector.clone(),
+                &genesis_config,
+                &keypairs,
+                initial_lamports,
+                num_accounts,
+            );
+        });
     });
 }
 
-#[bench]
-fn bench_process_entries_without_order_shuffeling(bencher: &mut Bencher) {
-    bench_process_entries(false, bencher);
+fn bench_process_entries_without_order_shuffeling(c: &mut Criterion) {
+    bench_process_entries(c, "bench_process_entries_without_order_shuffeling", false);
 }
 
-#[bench]
-fn bench_process_entries_with_order_shuffeling(bencher: &mut Bencher) {
-    bench_process_entries(true, bencher);
+fn bench_process_entries_with_order_shuffeling(c: &mut Criterion) {
+    bench_process_entries(c, "bench_process_entries_with_order_shuffeling", true);
 }
+
+criterion_group!(
+    benches,
+    bench_consume_buffered,
+    bench_banking_stage_multi_accounts,
+    bench_banking_stage_multi_programs,
+    bench_process_entries_without_order_shuffeling,
+    bench_process_entries_with_order_shuffeling
+);
+criterion_main!(benches);
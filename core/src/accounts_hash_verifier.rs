@@ -4,9 +4,9 @@
 // hash on gossip. Monitor gossip for messages from validators in the `--known-validator`s
 // set and halt the node if a mismatch is detected.
 
-use rayon::ThreadPool;
 use solana_gossip::cluster_info::{ClusterInfo, MAX_SNAPSHOT_HASHES};
 use solana_measure::measure::Measure;
+use solana_rayon_threadlimit::thread_pools::ManagedThreadPool;
 use solana_runtime::{
     accounts_db::{self, AccountsDb},
     accounts_hash::HashStats,
@@ -75,7 +75,7 @@ impl AccountsHashVerifier {
                                 &exit,
                                 fault_injection_rate_slots,
                                 snapshot_config.as_ref(),
-                                thread_pool.as_ref(),
+                                thread_pool.as_deref(),
                                 &ledger_path,
                             );
                         }
@@ -101,7 +101,7 @@ impl AccountsHashVerifier {
         exit: &Arc<AtomicBool>,
         fault_injection_rate_slots: u64,
         snapshot_config: Option<&SnapshotConfig>,
-        thread_pool: Option<&ThreadPool>,
+        thread_pool: Option<&ManagedThreadPool>,
         ledger_path: &Path,
     ) {
         Self::verify_accounts_package_hash(&accounts_package, thread_pool, ledger_path);
@@ -121,7 +121,7 @@ impl AccountsHashVerifier {
 
     fn verify_accounts_package_hash(
         accounts_package: &AccountsPackage,
-        thread_pool: Option<&ThreadPool>,
+        thread_pool: Option<&ManagedThreadPool>,
         ledger_path: &Path,
     ) {
         let mut measure_hash = Measure::start("hash");
@@ -19,6 +19,7 @@ use solana_perf::{
 use solana_runtime::bank::Bank;
 use solana_sdk::{
     clock::{Slot, SLOT_MS},
+    feature_set,
     pubkey::Pubkey,
     timing::timestamp,
 };
@@ -568,6 +569,9 @@ impl AncestorHashesService {
                     slot,
                     repair_stats,
                     outstanding_requests,
+                    root_bank
+                        .feature_set
+                        .is_active(&feature_set::sign_repair_requests::id()),
                 ) {
                     request_throttle.push(timestamp());
                     repairable_dead_slot_pool.take(&slot).unwrap();
@@ -632,6 +636,7 @@ impl AncestorHashesService {
 
     /// Returns true if a request was successfully made and the status
     /// added to `ancestor_hashes_request_statuses`
+    #[allow(clippy::too_many_arguments)]
     fn initiate_ancestor_hashes_requests_for_duplicate_slot(
         ancestor_hashes_request_statuses: &DashMap<Slot, DeadSlotAncestorRequestStatus>,
         ancestor_hashes_request_socket: &UdpSocket,
@@ -641,6 +646,7 @@ impl AncestorHashesService {
         duplicate_slot: Slot,
         repair_stats: &mut AncestorRepairRequestsStats,
         outstanding_requests: &RwLock<OutstandingAncestorHashesRepairs>,
+        sign_requests: bool,
     ) -> bool {
         let sampled_validators = serve_repair.repair_request_ancestor_hashes_sample_peers(
             duplicate_slot,
@@ -657,8 +663,12 @@ impl AncestorHashesService {
                     .write()
                     .unwrap()
                     .add_request(AncestorHashesRepairType(duplicate_slot), timestamp());
-                let request_bytes =
-                    serve_repair.ancestor_repair_request_bytes(duplicate_slot, nonce);
+                let request_bytes = serve_repair.ancestor_repair_request_bytes(
+                    duplicate_slot,
+                    pubkey,
+                    nonce,
+                    sign_requests,
+                );
                 if let Ok(request_bytes) = request_bytes {
                     let _ = ancestor_hashes_request_socket.send_to(&request_bytes, socket_addr);
                 }
@@ -925,6 +935,7 @@ mod test {
             let t_listen = ServeRepair::listen(
                 responder_serve_repair,
                 Some(blockstore),
+                None,
                 requests_receiver,
                 response_sender,
                 &exit,
@@ -1098,6 +1109,7 @@ mod test {
             dead_slot,
             &mut repair_stats,
             &outstanding_requests,
+            false,
         );
         assert!(ancestor_hashes_request_statuses.is_empty());
 
@@ -1115,6 +1127,7 @@ mod test {
             dead_slot,
             &mut repair_stats,
             &outstanding_requests,
+            false,
         );
 
         assert_eq!(ancestor_hashes_request_statuses.len(), 1);
@@ -1433,6 +1446,8 @@ mod test {
             &bank_forks,
             &requester_blockstore,
             None,
+            false,
+            None,
         );
 
         // Simulate making a request
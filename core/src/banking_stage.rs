@@ -1,7 +1,11 @@
 //! The `banking_stage` processes Transaction messages. It is intended to be used
 //! to contruct a software pipeline. The stage uses all available CPU cores and
 //! can do its processing in parallel with signature verification on the GPU.
-use crate::packet_hasher::PacketHasher;
+use crate::{
+    leader_slot_reports::{LeaderSlotCounters, LeaderSlotReports},
+    packet_hasher::PacketHasher,
+    transaction_priority::{packet_compute_unit_price, prioritize_transactions},
+};
 use crossbeam_channel::{Receiver as CrossbeamReceiver, RecvTimeoutError};
 use itertools::Itertools;
 use lru::LruCache;
@@ -10,12 +14,13 @@ use solana_entry::entry::hash_transactions;
 use solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo};
 use solana_ledger::blockstore_processor::TransactionStatusSender;
 use solana_measure::measure::Measure;
-use solana_metrics::{inc_new_counter_debug, inc_new_counter_info};
+use solana_metrics::{inc_new_counter_debug, inc_new_counter_info, transaction_tracing};
 use solana_perf::{
     cuda_runtime::PinnedVec,
     data_budget::DataBudget,
     packet::{limited_deserialize, Packet, Packets, PACKETS_PER_BATCH},
     perf_libs,
+    thread_affinity::{self, ThreadRole},
 };
 use solana_poh::poh_recorder::{BankStart, PohRecorder, PohRecorderError, TransactionRecorder};
 use solana_runtime::{
@@ -25,7 +30,7 @@ use solana_runtime::{
         TransactionExecutionResult,
     },
     bank_utils,
-    cost_model::CostModel,
+    cost_model::{CostModel, TransactionCost},
     cost_tracker::CostTracker,
     transaction_batch::TransactionBatch,
     vote_sender_types::ReplayVoteSender,
@@ -41,7 +46,9 @@ use solana_sdk::{
     short_vec::decode_shortu16_len,
     signature::Signature,
     timing::{duration_as_ms, timestamp, AtomicInterval},
-    transaction::{self, SanitizedTransaction, TransactionError, VersionedTransaction},
+    transaction::{
+        self, SanitizedTransaction, Transaction, TransactionError, VersionedTransaction,
+    },
 };
 use solana_streamer::sendmmsg::{batch_send, SendPktsError};
 use solana_transaction_status::token_balances::{
@@ -60,10 +67,12 @@ use std::{
     time::Duration,
     time::Instant,
 };
+use thiserror::Error;
 
-/// (packets, valid_indexes, forwarded)
-/// Set of packets with a list of which are valid and if this batch has been forwarded.
-type PacketsAndOffsets = (Packets, Vec<usize>, bool);
+/// (packets, valid_indexes, forwarded, priority)
+/// Set of packets with a list of which are valid, if this batch has been forwarded and the
+/// lowest compute unit price offered by its packets.
+type PacketsAndOffsets = (Packets, Vec<usize>, bool, u64);
 
 pub type UnprocessedPackets = VecDeque<PacketsAndOffsets>;
 
@@ -99,6 +108,9 @@ pub struct BankingStageStats {
     consumed_buffered_packets_count: AtomicUsize,
     cost_tracker_check_count: AtomicUsize,
     cost_forced_retry_transactions_count: AtomicUsize,
+    prioritized_packets_count: AtomicUsize,
+    dropped_low_priority_packets_count: AtomicUsize,
+    write_lock_deferred_transactions_count: AtomicUsize,
 
     // Timing
     consume_buffered_packets_elapsed: AtomicU64,
@@ -112,6 +124,8 @@ pub struct BankingStageStats {
     cost_tracker_update_elapsed: AtomicU64,
     cost_tracker_clone_elapsed: AtomicU64,
     cost_tracker_check_elapsed: AtomicU64,
+
+    leader_slot_reports: Option<Arc<LeaderSlotReports>>,
 }
 
 impl BankingStageStats {
@@ -122,6 +136,10 @@ impl BankingStageStats {
         }
     }
 
+    fn leader_slot_counters(&self, slot: Slot) -> Option<Arc<LeaderSlotCounters>> {
+        self.leader_slot_reports.as_ref()?.slot_counters(slot)
+    }
+
     fn report(&self, report_interval_ms: u64) {
         if self.last_report.should_update(report_interval_ms) {
             datapoint_info!(
@@ -192,6 +210,23 @@ impl BankingStageStats {
                         .swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "prioritized_packets_count",
+                    self.prioritized_packets_count.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
+                (
+                    "dropped_low_priority_packets_count",
+                    self.dropped_low_priority_packets_count
+                        .swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
+                (
+                    "write_lock_deferred_transactions_count",
+                    self.write_lock_deferred_transactions_count
+                        .swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "consume_buffered_packets_elapsed",
                     self.consume_buffered_packets_elapsed
@@ -271,6 +306,23 @@ pub enum BufferedPacketsDecision {
     Hold,
 }
 
+/// Why a bundle of an external block producer was dropped
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("the working bank only accepts votes")]
+    VoteOnlyBank,
+    #[error("transaction {0} failed sanitization: {1}")]
+    Sanitize(usize, TransactionError),
+    #[error("bundle exceeds the block cost limits")]
+    CostLimit,
+    #[error("transaction {0} could not lock its accounts: {1}")]
+    Lock(usize, TransactionError),
+    #[error("a transaction failed")]
+    ExecutionFailure,
+    #[error("bundle could not be recorded: {0}")]
+    Record(PohRecorderError),
+}
+
 #[derive(Debug, Clone)]
 pub enum ForwardOption {
     NotForward,
@@ -290,6 +342,8 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: ReplayVoteSender,
         cost_model: Arc<RwLock<CostModel>>,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+        bundle_receiver: Option<CrossbeamReceiver<Vec<Transaction>>>,
     ) -> Self {
         Self::new_num_threads(
             cluster_info,
@@ -301,9 +355,12 @@ impl BankingStage {
             transaction_status_sender,
             gossip_vote_sender,
             cost_model,
+            leader_slot_reports,
+            bundle_receiver,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new_num_threads(
         cluster_info: &Arc<ClusterInfo>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
@@ -314,6 +371,8 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: ReplayVoteSender,
         cost_model: Arc<RwLock<CostModel>>,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+        bundle_receiver: Option<CrossbeamReceiver<Vec<Transaction>>>,
     ) -> Self {
         let batch_limit = TOTAL_BUFFERED_PACKETS / ((num_threads - 1) as usize * PACKETS_PER_BATCH);
         // Single thread to generate entries from many banks.
@@ -326,7 +385,7 @@ impl BankingStage {
         let data_budget = Arc::new(DataBudget::default());
         // Many banks that process transactions in parallel.
         assert!(num_threads >= NUM_VOTE_PROCESSING_THREADS + MIN_THREADS_BANKING);
-        let bank_thread_hdls: Vec<JoinHandle<()>> = (0..num_threads)
+        let mut bank_thread_hdls: Vec<JoinHandle<()>> = (0..num_threads)
             .map(|i| {
                 let (verified_receiver, forward_option) = match i {
                     0 => {
@@ -349,9 +408,11 @@ impl BankingStage {
                 let duplicates = duplicates.clone();
                 let data_budget = data_budget.clone();
                 let cost_model = cost_model.clone();
+                let leader_slot_reports = leader_slot_reports.clone();
                 Builder::new()
                     .name("solana-banking-stage-tx".to_string())
                     .spawn(move || {
+                        thread_affinity::pin_current_thread(ThreadRole::Banking, i as usize);
                         Self::process_loop(
                             &verified_receiver,
                             &poh_recorder,
@@ -365,11 +426,33 @@ impl BankingStage {
                             &duplicates,
                             &data_budget,
                             cost_model,
+                            leader_slot_reports,
                         );
                     })
                     .unwrap()
             })
             .collect();
+        if let Some(bundle_receiver) = bundle_receiver {
+            let poh_recorder = poh_recorder.clone();
+            bank_thread_hdls.push(
+                Builder::new()
+                    .name("solana-banking-stage-bundle".to_string())
+                    .spawn(move || {
+                        thread_affinity::pin_current_thread(
+                            ThreadRole::Banking,
+                            num_threads as usize,
+                        );
+                        Self::bundle_process_loop(
+                            &bundle_receiver,
+                            &poh_recorder,
+                            transaction_status_sender,
+                            gossip_vote_sender,
+                            cost_model,
+                        );
+                    })
+                    .unwrap(),
+            );
+        }
         Self { bank_thread_hdls }
     }
 
@@ -377,8 +460,8 @@ impl BankingStage {
         all_packets: impl Iterator<Item = &'a PacketsAndOffsets>,
     ) -> Vec<&'a Packet> {
         all_packets
-            .filter(|(_p, _indexes, forwarded)| !forwarded)
-            .flat_map(|(p, valid_indexes, _forwarded)| {
+            .filter(|(_p, _indexes, forwarded, _priority)| !forwarded)
+            .flat_map(|(p, valid_indexes, _forwarded, _priority)| {
                 valid_indexes.iter().map(move |x| &p.packets[*x])
             })
             .collect()
@@ -446,7 +529,7 @@ impl BankingStage {
         let mut proc_start = Measure::start("consume_buffered_process");
         let mut reached_end_of_slot = None;
 
-        buffered_packets.retain_mut(|(msgs, ref mut original_unprocessed_indexes, _forwarded)| {
+        buffered_packets.retain_mut(|(msgs, ref mut original_unprocessed_indexes, _, _)| {
             if let Some((next_leader, bank)) = &reached_end_of_slot {
                 // We've hit the end of this slot, no need to perform more processing,
                 // just filter the remaining packets for the invalid (e.g. too old) ones
@@ -684,8 +767,8 @@ impl BankingStage {
         };
         let _ = Self::forward_buffered_packets(socket, &addr, buffered_packets, data_budget);
         if hold {
-            buffered_packets.retain(|(_, index, _)| !index.is_empty());
-            for (_, _, forwarded) in buffered_packets.iter_mut() {
+            buffered_packets.retain(|(_, index, _, _)| !index.is_empty());
+            for (_, _, forwarded, _) in buffered_packets.iter_mut() {
                 *forwarded = true;
             }
         } else {
@@ -707,11 +790,15 @@ impl BankingStage {
         duplicates: &Arc<Mutex<(LruCache<u64, ()>, PacketHasher)>>,
         data_budget: &DataBudget,
         cost_model: Arc<RwLock<CostModel>>,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
     ) {
         let recorder = poh_recorder.lock().unwrap().recorder();
         let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
         let mut buffered_packets = VecDeque::with_capacity(batch_limit);
-        let banking_stage_stats = BankingStageStats::new(id);
+        let banking_stage_stats = BankingStageStats {
+            leader_slot_reports,
+            ..BankingStageStats::new(id)
+        };
         loop {
             let my_pubkey = cluster_info.id();
             while !buffered_packets.is_empty() {
@@ -772,6 +859,136 @@ impl BankingStage {
         }
     }
 
+    /// Processes the bundles of an external block producer one at a time, in the order they are
+    /// received. Bundles arriving without a working bank are dropped.
+    fn bundle_process_loop(
+        bundle_receiver: &CrossbeamReceiver<Vec<Transaction>>,
+        poh_recorder: &Arc<Mutex<PohRecorder>>,
+        transaction_status_sender: Option<TransactionStatusSender>,
+        gossip_vote_sender: ReplayVoteSender,
+        cost_model: Arc<RwLock<CostModel>>,
+    ) {
+        let recorder = poh_recorder.lock().unwrap().recorder();
+        while let Ok(bundle) = bundle_receiver.recv() {
+            let bank_start = poh_recorder.lock().unwrap().bank_start();
+            let working_bank =
+                match PohRecorder::get_working_bank_if_not_expired(&bank_start.as_ref()) {
+                    Some(working_bank) => working_bank,
+                    None => {
+                        inc_new_counter_info!("banking_stage-bundles_not_leader", 1);
+                        continue;
+                    }
+                };
+            match Self::process_bundle(
+                working_bank,
+                bundle,
+                &recorder,
+                transaction_status_sender.clone(),
+                &gossip_vote_sender,
+                &cost_model,
+            ) {
+                Ok(num_committed) => {
+                    inc_new_counter_info!("banking_stage-bundles_committed", 1);
+                    inc_new_counter_info!(
+                        "banking_stage-bundle_transactions_committed",
+                        num_committed
+                    );
+                }
+                Err(err) => {
+                    inc_new_counter_info!("banking_stage-bundles_dropped", 1);
+                    debug!("bank: {} dropped bundle: {}", working_bank.slot(), err);
+                }
+            }
+        }
+    }
+
+    /// Executes the transactions of `bundle` in order and records them in a single entry, but
+    /// only if all of them succeed; otherwise nothing of the bundle is committed. As the
+    /// transactions share an entry, they must not lock conflicting accounts.
+    fn process_bundle(
+        bank: &Arc<Bank>,
+        bundle: Vec<Transaction>,
+        poh: &TransactionRecorder,
+        transaction_status_sender: Option<TransactionStatusSender>,
+        gossip_vote_sender: &ReplayVoteSender,
+        cost_model: &Arc<RwLock<CostModel>>,
+    ) -> Result<usize, BundleError> {
+        if bank.vote_only_bank() {
+            return Err(BundleError::VoteOnlyBank);
+        }
+        let transactions = bundle
+            .into_iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                let message_hash = tx.message.hash();
+                let tx = SanitizedTransaction::try_create(tx.into(), message_hash, None, |_| {
+                    Err(TransactionError::UnsupportedVersion)
+                })
+                .map_err(|err| BundleError::Sanitize(i, err))?;
+                tx.verify_precompiles(&bank.feature_set)
+                    .map_err(|err| BundleError::Sanitize(i, err))?;
+                Ok(tx)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let demote_program_write_locks = bank.demote_program_write_locks();
+        let tx_costs: Vec<_> = {
+            let cost_model = cost_model.read().unwrap();
+            transactions
+                .iter()
+                .map(|tx| cost_model.calculate_cost(tx, demote_program_write_locks))
+                .collect()
+        };
+        {
+            let mut cost_tracker = bank.write_cost_tracker().unwrap();
+            for (num_reserved, (tx, tx_cost)) in transactions.iter().zip(&tx_costs).enumerate() {
+                if cost_tracker
+                    .try_reserve_transaction_cost(tx, tx_cost)
+                    .is_err()
+                {
+                    for (tx, tx_cost) in transactions.iter().zip(&tx_costs).take(num_reserved) {
+                        cost_tracker.release_reserved_transaction_cost(tx, tx_cost);
+                    }
+                    return Err(BundleError::CostLimit);
+                }
+            }
+        }
+
+        let batch = bank.prepare_sanitized_batch(&transactions);
+        let result = match batch.lock_results().iter().position(Result::is_err) {
+            Some(i) => Err(BundleError::Lock(
+                i,
+                batch.lock_results()[i].clone().unwrap_err(),
+            )),
+            None => {
+                let (result, _retryable_txs) = Self::process_and_record_transactions_locked(
+                    bank,
+                    poh,
+                    &batch,
+                    transaction_status_sender,
+                    gossip_vote_sender,
+                    true,
+                );
+                match result {
+                    Ok(num_committed) if num_committed == transactions.len() => Ok(num_committed),
+                    Ok(_) => Err(BundleError::ExecutionFailure),
+                    Err(err) => Err(BundleError::Record(err)),
+                }
+            }
+        };
+        drop(batch);
+
+        let mut cost_tracker = bank.write_cost_tracker().unwrap();
+        for (tx, tx_cost) in transactions.iter().zip(&tx_costs) {
+            if result.is_ok() {
+                cost_tracker.commit_reserved_transaction_cost(tx, tx_cost);
+            } else {
+                cost_tracker.release_reserved_transaction_cost(tx, tx_cost);
+            }
+        }
+        result
+    }
+
     pub fn num_threads() -> u32 {
         cmp::max(
             env::var("SOLANA_BANKING_THREADS")
@@ -845,7 +1062,9 @@ impl BankingStage {
         batch: &TransactionBatch,
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
+        all_or_nothing: bool,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
+        let execute_start = Instant::now();
         let mut load_execute_time = Measure::start("load_execute_time");
         // Use a shorter maximum age when adding transactions into the pipeline.  This will reduce
         // the likelihood of any single thread getting starved and processing old ids.
@@ -871,6 +1090,8 @@ impl BankingStage {
             results,
             inner_instructions,
             transaction_logs,
+            compute_budget_exceeded,
+            execution_details,
             mut retryable_txs,
             tx_count,
             signature_count,
@@ -882,6 +1103,19 @@ impl BankingStage {
             &mut execute_timings,
         );
         load_execute_time.stop();
+        let execute_end = Instant::now();
+        let signatures = || {
+            batch
+                .sanitized_transactions()
+                .iter()
+                .map(|tx| tx.signature())
+        };
+        transaction_tracing::record_spans("execute", signatures(), execute_start, execute_end);
+
+        // None of the batch is recorded, not even the fees, unless every transaction succeeded
+        if all_or_nothing && results.iter().any(|(result, _)| result.is_err()) {
+            return (Ok(0), retryable_txs);
+        }
 
         let freeze_lock = bank.freeze_lock();
 
@@ -901,6 +1135,8 @@ impl BankingStage {
             return (num_to_commit, retryable_txs);
         }
         record_time.stop();
+        let record_end = Instant::now();
+        transaction_tracing::record_spans("record", signatures(), execute_end, record_end);
 
         let mut commit_time = Measure::start("commit_time");
         let sanitized_txs = batch.sanitized_transactions();
@@ -910,6 +1146,7 @@ impl BankingStage {
                 sanitized_txs,
                 &mut loaded_accounts,
                 &results,
+                execution_details,
                 tx_count,
                 signature_count,
                 &mut execute_timings,
@@ -924,15 +1161,18 @@ impl BankingStage {
                     bank.clone(),
                     txs,
                     tx_results.execution_results,
+                    tx_results.execution_details,
                     TransactionBalancesSet::new(pre_balances, post_balances),
                     TransactionTokenBalancesSet::new(pre_token_balances, post_token_balances),
                     inner_instructions,
                     transaction_logs,
+                    compute_budget_exceeded,
                     tx_results.rent_debits,
                 );
             }
         }
         commit_time.stop();
+        transaction_tracing::record_spans("commit", signatures(), record_end, Instant::now());
 
         drop(freeze_lock);
 
@@ -961,31 +1201,74 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
-        let mut lock_time = Measure::start("lock_time");
-        // Once accounts are locked, other threads cannot encode transactions that will modify the
-        // same account state
-        let batch = bank.prepare_sanitized_batch(txs);
-        lock_time.stop();
+        let banking_start = Instant::now();
+        let mut lock_us = 0;
+        let mut unlock_us = 0;
+        let mut num_batches = 0;
+        let mut num_to_commit = 0;
+        let mut error = None;
+        let mut retryable_txs = vec![];
+        // If the bank queues conflicting transactions, those conflicting with an earlier one of
+        // `txs` are left to the next batch, which is locked once the earlier one is committed
+        let mut batch_start = 0;
+        while batch_start < txs.len() {
+            let mut lock_time = Measure::start("lock_time");
+            // Once accounts are locked, other threads cannot encode transactions that will modify
+            // the same account state
+            let batch = bank.prepare_queued_sanitized_batch(&txs[batch_start..]);
+            lock_time.stop();
+            lock_us += lock_time.as_us();
+            num_batches += 1;
+            let batch_end = batch_start + batch.sanitized_transactions().len();
+
+            let (result, batch_retryable_txs) = Self::process_and_record_transactions_locked(
+                bank,
+                poh,
+                &batch,
+                transaction_status_sender.clone(),
+                gossip_vote_sender,
+                false,
+            );
+            retryable_txs.extend(
+                batch_retryable_txs
+                    .into_iter()
+                    .map(|x| x + chunk_offset + batch_start),
+            );
 
-        let (result, mut retryable_txs) = Self::process_and_record_transactions_locked(
-            bank,
-            poh,
-            &batch,
-            transaction_status_sender,
-            gossip_vote_sender,
+            let mut unlock_time = Measure::start("unlock_time");
+            // Once the accounts are new transactions can enter the pipeline to process them
+            drop(batch);
+            unlock_time.stop();
+            unlock_us += unlock_time.as_us();
+
+            match result {
+                Ok(batch_num_to_commit) => num_to_commit += batch_num_to_commit,
+                Err(err) => {
+                    // The queued transactions are retried along with those of the batch
+                    retryable_txs.extend(chunk_offset + batch_end..chunk_offset + txs.len());
+                    error = Some(err);
+                    break;
+                }
+            }
+            batch_start = batch_end;
+        }
+        if num_batches > 1 {
+            inc_new_counter_info!("banking_stage-queued_batches", num_batches - 1);
+        }
+        let result = error.map_or(Ok(num_to_commit), Err);
+        transaction_tracing::record_spans(
+            "banking",
+            txs.iter().map(|tx| tx.signature()),
+            banking_start,
+            Instant::now(),
         );
-        retryable_txs.iter_mut().for_each(|x| *x += chunk_offset);
-
-        let mut unlock_time = Measure::start("unlock_time");
-        // Once the accounts are new transactions can enter the pipeline to process them
-        drop(batch);
-        unlock_time.stop();
 
         debug!(
-            "bank: {} lock: {}us unlock: {}us txs_len: {}",
+            "bank: {} lock: {}us unlock: {}us batches: {} txs_len: {}",
             bank.slot(),
-            lock_time.as_us(),
-            unlock_time.as_us(),
+            lock_us,
+            unlock_us,
+            num_batches,
             txs.len(),
         );
 
@@ -1172,6 +1455,65 @@ impl BankingStage {
         )
     }
 
+    /// Reserves the cost of each transaction in the bank's cost tracker before it's executed, so
+    /// that concurrent banking threads don't execute transactions the block has no room left
+    /// for. Returns the transactions which could reserve their cost, their packet indexes and
+    /// costs, and the packet indexes of the ones to retry.
+    fn reserve_transaction_costs(
+        bank: &Arc<Bank>,
+        msgs: &Packets,
+        transactions: Vec<SanitizedTransaction>,
+        transaction_to_packet_indexes: Vec<usize>,
+        cost_model: &Arc<RwLock<CostModel>>,
+    ) -> (
+        Vec<SanitizedTransaction>,
+        Vec<usize>,
+        Vec<TransactionCost>,
+        Vec<usize>,
+    ) {
+        let demote_program_write_locks = bank.demote_program_write_locks();
+        let tx_costs: Vec<_> = {
+            let cost_model = cost_model.read().unwrap();
+            transactions
+                .iter()
+                .map(|tx| cost_model.calculate_cost(tx, demote_program_write_locks))
+                .collect()
+        };
+
+        let mut cost_tracker = bank.write_cost_tracker().unwrap();
+        let mut reserved_transactions = Vec::with_capacity(transactions.len());
+        let mut reserved_packet_indexes = Vec::with_capacity(transactions.len());
+        let mut reserved_tx_costs = Vec::with_capacity(transactions.len());
+        let mut retryable_packet_indexes = vec![];
+        for ((tx, packet_index), tx_cost) in transactions
+            .into_iter()
+            .zip(transaction_to_packet_indexes)
+            .zip(tx_costs)
+        {
+            // excluding vote TX from the limits, for now
+            if msgs.packets[packet_index].meta.is_simple_vote_tx {
+                cost_tracker.reserve_transaction_cost(&tx, &tx_cost);
+            } else if cost_tracker
+                .try_reserve_transaction_cost(&tx, &tx_cost)
+                .is_err()
+            {
+                debug!("transaction {:?} would exceed limit", tx);
+                retryable_packet_indexes.push(packet_index);
+                continue;
+            }
+            reserved_transactions.push(tx);
+            reserved_packet_indexes.push(packet_index);
+            reserved_tx_costs.push(tx_cost);
+        }
+
+        (
+            reserved_transactions,
+            reserved_packet_indexes,
+            reserved_tx_costs,
+            retryable_packet_indexes,
+        )
+    }
+
     /// This function filters pending packets that are still valid
     /// # Arguments
     /// * `transactions` - a batch of transactions deserialized from packets
@@ -1224,7 +1566,7 @@ impl BankingStage {
         cost_model: &Arc<RwLock<CostModel>>,
     ) -> (usize, usize, Vec<usize>) {
         let mut packet_conversion_time = Measure::start("packet_conversion");
-        let (transactions, transaction_to_packet_indexes, retryable_packet_indexes) =
+        let (transactions, transaction_to_packet_indexes, mut retryable_packet_indexes) =
             Self::transactions_from_packets(
                 msgs,
                 &packet_indexes,
@@ -1236,6 +1578,46 @@ impl BankingStage {
                 cost_model,
             );
         packet_conversion_time.stop();
+
+        // Schedule the highest priced transactions first, deferring those which would contend
+        // for the write lock of an account to the next pass over the buffered packets, unless
+        // the bank queues them behind the conflicting transactions of their batch
+        let (scheduled_tx_indexes, deferred_tx_indexes) = prioritize_transactions(
+            &transactions,
+            bank.demote_program_write_locks(),
+            bank.feature_set
+                .is_active(&feature_set::compute_unit_price_instruction::id()),
+            !bank.queue_conflicting_transactions(),
+        );
+        let deferred_packet_indexes: Vec<_> = deferred_tx_indexes
+            .iter()
+            .map(|tx_index| transaction_to_packet_indexes[*tx_index])
+            .collect();
+        let mut transactions: Vec<_> = transactions.into_iter().map(Some).collect();
+        let (transactions, transaction_to_packet_indexes): (Vec<_>, Vec<_>) = scheduled_tx_indexes
+            .iter()
+            .map(|tx_index| {
+                (
+                    transactions[*tx_index].take().unwrap(),
+                    transaction_to_packet_indexes[*tx_index],
+                )
+            })
+            .unzip();
+        banking_stage_stats
+            .write_lock_deferred_transactions_count
+            .fetch_add(deferred_packet_indexes.len(), Ordering::Relaxed);
+
+        let mut cost_reservation_time = Measure::start("cost_reservation_time");
+        let (transactions, transaction_to_packet_indexes, tx_costs, over_limit_packet_indexes) =
+            Self::reserve_transaction_costs(
+                bank,
+                msgs,
+                transactions,
+                transaction_to_packet_indexes,
+                cost_model,
+            );
+        retryable_packet_indexes.extend(over_limit_packet_indexes);
+        cost_reservation_time.stop();
         inc_new_counter_info!("banking_stage-packet_conversion", 1);
 
         banking_stage_stats
@@ -1249,6 +1631,7 @@ impl BankingStage {
         );
 
         let tx_len = transactions.len();
+        let retryable_packets_count = retryable_packet_indexes.len();
 
         let mut process_tx_time = Measure::start("process_tx_time");
         let (processed, unprocessed_tx_indexes) = Self::process_transactions(
@@ -1266,19 +1649,18 @@ impl BankingStage {
             unprocessed_tx_count
         );
 
-        // applying cost of processed transactions to shared cost_tracker
+        // settling the costs reserved by the transactions in the shared cost_tracker
         let mut cost_tracking_time = Measure::start("cost_tracking_time");
-        transactions.iter().enumerate().for_each(|(index, tx)| {
-            if unprocessed_tx_indexes.iter().all(|&i| i != index) {
-                bank.write_cost_tracker().unwrap().add_transaction_cost(
-                    tx,
-                    &cost_model
-                        .read()
-                        .unwrap()
-                        .calculate_cost(tx, bank.demote_program_write_locks()),
-                );
+        {
+            let mut cost_tracker = bank.write_cost_tracker().unwrap();
+            for (index, (tx, tx_cost)) in transactions.iter().zip(&tx_costs).enumerate() {
+                if unprocessed_tx_indexes.contains(&index) {
+                    cost_tracker.release_reserved_transaction_cost(tx, tx_cost);
+                } else {
+                    cost_tracker.commit_reserved_transaction_cost(tx, tx_cost);
+                }
             }
-        });
+        }
         cost_tracking_time.stop();
 
         let mut filter_pending_packets_time = Measure::start("filter_pending_packets_time");
@@ -1295,9 +1677,35 @@ impl BankingStage {
             unprocessed_tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len())
         );
 
+        if let Some(counters) = banking_stage_stats.leader_slot_counters(bank.slot()) {
+            LeaderSlotCounters::add(
+                &counters.transactions_executed,
+                tx_len.saturating_sub(unprocessed_tx_count),
+            );
+            LeaderSlotCounters::add(
+                &counters.packets_dropped_invalid,
+                packet_indexes.len().saturating_sub(
+                    tx_len + retryable_packets_count + deferred_packet_indexes.len(),
+                ),
+            );
+            LeaderSlotCounters::add(
+                &counters.packets_dropped_expired_or_processed,
+                unprocessed_tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len()),
+            );
+            LeaderSlotCounters::add(
+                &counters.packets_retried_cost_limit,
+                retryable_packets_count,
+            );
+            LeaderSlotCounters::add(
+                &counters.packets_retried_retryable,
+                filtered_unprocessed_packet_indexes.len(),
+            );
+        }
+
         // combine cost-related unprocessed transactions with bank determined unprocessed for
         // buffering
         filtered_unprocessed_packet_indexes.extend(retryable_packet_indexes);
+        filtered_unprocessed_packet_indexes.extend(deferred_packet_indexes);
 
         banking_stage_stats
             .packet_conversion_elapsed
@@ -1305,9 +1713,10 @@ impl BankingStage {
         banking_stage_stats
             .transaction_processing_elapsed
             .fetch_add(process_tx_time.as_us(), Ordering::Relaxed);
-        banking_stage_stats
-            .cost_tracker_update_elapsed
-            .fetch_add(cost_tracking_time.as_us(), Ordering::Relaxed);
+        banking_stage_stats.cost_tracker_update_elapsed.fetch_add(
+            cost_reservation_time.as_us() + cost_tracking_time.as_us(),
+            Ordering::Relaxed,
+        );
         banking_stage_stats
             .filter_pending_packets_elapsed
             .fetch_add(filter_pending_packets_time.as_us(), Ordering::Relaxed);
@@ -1424,6 +1833,14 @@ impl BankingStage {
         let mut proc_start = Measure::start("process_packets_transactions_process");
         let mut new_tx_count = 0;
 
+        // Buffered batches are ordered by the price their packets offer once the
+        // SetComputeUnitPrice instruction is enabled
+        let prioritize = poh
+            .lock()
+            .unwrap()
+            .start_bank()
+            .feature_set
+            .is_active(&feature_set::compute_unit_price_instruction::id());
         let mut mms_iter = mms.into_iter();
         let mut dropped_packets_count = 0;
         let mut dropped_packet_batches_count = 0;
@@ -1443,6 +1860,7 @@ impl BankingStage {
                     batch_limit,
                     duplicates,
                     banking_stage_stats,
+                    prioritize,
                 );
                 continue;
             }
@@ -1452,6 +1870,11 @@ impl BankingStage {
                 working_bank,
                 bank_creation_time,
             } = &*working_bank_start.unwrap();
+            let leader_slot_counters =
+                banking_stage_stats.leader_slot_counters(working_bank.slot());
+            if let Some(counters) = &leader_slot_counters {
+                LeaderSlotCounters::add(&counters.packets_received, packet_indexes.len());
+            }
 
             let (processed, verified_txs_len, unprocessed_indexes) =
                 Self::process_packets_transactions(
@@ -1469,6 +1892,8 @@ impl BankingStage {
             new_tx_count += processed;
 
             // Collect any unprocessed transactions in this batch for forwarding
+            let (prev_dropped_packets_count, prev_newly_buffered_packets_count) =
+                (dropped_packets_count, newly_buffered_packets_count);
             Self::push_unprocessed(
                 buffered_packets,
                 msgs,
@@ -1479,7 +1904,18 @@ impl BankingStage {
                 batch_limit,
                 duplicates,
                 banking_stage_stats,
+                prioritize,
             );
+            if let Some(counters) = &leader_slot_counters {
+                LeaderSlotCounters::add(
+                    &counters.packets_dropped_buffer_full,
+                    dropped_packets_count - prev_dropped_packets_count,
+                );
+                LeaderSlotCounters::add(
+                    &counters.packets_buffered,
+                    newly_buffered_packets_count - prev_newly_buffered_packets_count,
+                );
+            }
 
             // If there were retryable transactions, add the unexpired ones to the buffered queue
             if processed < verified_txs_len {
@@ -1508,6 +1944,7 @@ impl BankingStage {
                         batch_limit,
                         duplicates,
                         banking_stage_stats,
+                        prioritize,
                     );
                 }
                 handle_retryable_packets_time.stop();
@@ -1557,6 +1994,7 @@ impl BankingStage {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn push_unprocessed(
         unprocessed_packets: &mut UnprocessedPackets,
         packets: Packets,
@@ -1567,6 +2005,7 @@ impl BankingStage {
         batch_limit: usize,
         duplicates: &Arc<Mutex<(LruCache<u64, ()>, PacketHasher)>>,
         banking_stage_stats: &BankingStageStats,
+        prioritize: bool,
     ) {
         {
             let original_packets_count = packet_indexes.len();
@@ -1595,14 +2034,59 @@ impl BankingStage {
                 );
         }
         if Self::packet_has_more_unprocessed_transactions(&packet_indexes) {
+            // A batch ranks at the lowest price offered by its packets, so the packets offering
+            // nothing can't be buffered ahead of others by sharing a batch with a priced one
+            let priority = if prioritize {
+                let compute_unit_prices: Vec<_> = packet_indexes
+                    .iter()
+                    .map(|i| packet_compute_unit_price(&packets.packets[*i]))
+                    .collect();
+                banking_stage_stats.prioritized_packets_count.fetch_add(
+                    compute_unit_prices
+                        .iter()
+                        .filter(|price| **price > 0)
+                        .count(),
+                    Ordering::Relaxed,
+                );
+                compute_unit_prices.into_iter().min().unwrap_or_default()
+            } else {
+                0
+            };
             if unprocessed_packets.len() >= batch_limit {
                 *dropped_packet_batches_count += 1;
-                if let Some(dropped_batch) = unprocessed_packets.pop_front() {
-                    *dropped_packets_count += dropped_batch.1.len();
+                // The buffer is ordered by descending priority, so the lowest priced batches are
+                // at the back. The oldest of them is dropped, unless the new batch is cheaper.
+                let lowest_priority = unprocessed_packets
+                    .back()
+                    .map(|(_, _, _, priority)| *priority)
+                    .unwrap_or_default();
+                if priority < lowest_priority {
+                    *dropped_packets_count += packet_indexes.len();
+                    banking_stage_stats
+                        .dropped_low_priority_packets_count
+                        .fetch_add(packet_indexes.len(), Ordering::Relaxed);
+                    return;
+                }
+                let dropped_index = unprocessed_packets
+                    .iter()
+                    .position(|(_, _, _, priority)| *priority == lowest_priority);
+                let dropped_batch =
+                    dropped_index.and_then(|index| unprocessed_packets.remove(index));
+                if let Some((_, dropped_indexes, _, dropped_priority)) = dropped_batch {
+                    *dropped_packets_count += dropped_indexes.len();
+                    if dropped_priority < priority {
+                        banking_stage_stats
+                            .dropped_low_priority_packets_count
+                            .fetch_add(dropped_indexes.len(), Ordering::Relaxed);
+                    }
                 }
             }
             *newly_buffered_packets_count += packet_indexes.len();
-            unprocessed_packets.push_back((packets, packet_indexes, false));
+            let index = unprocessed_packets
+                .iter()
+                .position(|(_, _, _, buffered_priority)| *buffered_priority < priority)
+                .unwrap_or_else(|| unprocessed_packets.len());
+            unprocessed_packets.insert(index, (packets, packet_indexes, false, priority));
         }
     }
 
@@ -1618,13 +2102,6 @@ impl BankingStage {
     }
 }
 
-pub(crate) fn next_leader_tpu(
-    cluster_info: &ClusterInfo,
-    poh_recorder: &Mutex<PohRecorder>,
-) -> Option<std::net::SocketAddr> {
-    next_leader_x(cluster_info, poh_recorder, |leader| leader.tpu)
-}
-
 fn next_leader_tpu_forwards(
     cluster_info: &ClusterInfo,
     poh_recorder: &Mutex<PohRecorder>,
@@ -1632,7 +2109,7 @@ fn next_leader_tpu_forwards(
     next_leader_x(cluster_info, poh_recorder, |leader| leader.tpu_forwards)
 }
 
-pub(crate) fn next_leader_tpu_vote(
+fn next_leader_tpu_vote(
     cluster_info: &ClusterInfo,
     poh_recorder: &Mutex<PohRecorder>,
 ) -> Option<std::net::SocketAddr> {
@@ -1679,11 +2156,12 @@ mod tests {
     use solana_rpc::transaction_status_service::TransactionStatusService;
     use solana_runtime::cost_model::CostModel;
     use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
         hash::Hash,
         instruction::InstructionError,
         poh_config::PohConfig,
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
+        system_instruction::{self, SystemError},
         system_transaction,
         transaction::{Transaction, TransactionError},
     };
@@ -1736,6 +2214,8 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                None,
+                None,
             );
             drop(verified_sender);
             drop(gossip_verified_vote_sender);
@@ -1785,6 +2265,8 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                None,
+                None,
             );
             trace!("sending bank");
             drop(verified_sender);
@@ -1858,6 +2340,8 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                None,
+                None,
             );
 
             // fund another account so we can send 2 good transactions in a single batch.
@@ -2009,6 +2493,8 @@ mod tests {
                     None,
                     gossip_vote_sender,
                     Arc::new(RwLock::new(CostModel::default())),
+                    None,
+                    None,
                 );
 
                 // wait for banking_stage to eat the packets
@@ -2400,6 +2886,123 @@ mod tests {
         Blockstore::destroy(&ledger_path).unwrap();
     }
 
+    #[test]
+    fn test_process_bundle() {
+        solana_logger::setup();
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_slow_genesis_config(10_000);
+        let bank = Arc::new(Bank::new_no_wallclock_throttle_for_tests(&genesis_config));
+        let alice = Keypair::new();
+        let bob = solana_sdk::pubkey::new_rand();
+        let carol = solana_sdk::pubkey::new_rand();
+        let dave = solana_sdk::pubkey::new_rand();
+        let blockhash = genesis_config.hash();
+
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&ledger_path)
+                .expect("Expected to be able to open database ledger");
+            let (poh_recorder, entry_receiver, record_receiver) = PohRecorder::new(
+                bank.tick_height(),
+                bank.last_blockhash(),
+                bank.clone(),
+                Some((4, 4)),
+                bank.ticks_per_slot(),
+                &bob,
+                &Arc::new(blockstore),
+                &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+                &Arc::new(PohConfig::default()),
+                Arc::new(AtomicBool::default()),
+            );
+            let recorder = poh_recorder.recorder();
+            let poh_recorder = Arc::new(Mutex::new(poh_recorder));
+            let poh_simulator = simulate_poh(record_receiver, &poh_recorder);
+            poh_recorder.lock().unwrap().set_bank(&bank);
+            let (gossip_vote_sender, _gossip_vote_receiver) = unbounded();
+            let cost_model = Arc::new(RwLock::new(CostModel::default()));
+            let process_bundle = |bundle| {
+                BankingStage::process_bundle(
+                    &bank,
+                    bundle,
+                    &recorder,
+                    None,
+                    &gossip_vote_sender,
+                    &cost_model,
+                )
+            };
+
+            let bundle = vec![system_transaction::transfer(
+                &mint_keypair,
+                &alice.pubkey(),
+                1_000,
+                blockhash,
+            )];
+            assert_eq!(process_bundle(bundle).unwrap(), 1);
+            let bundle = vec![
+                system_transaction::transfer(&mint_keypair, &bob, 10, blockhash),
+                system_transaction::transfer(&alice, &carol, 10, blockhash),
+            ];
+            assert_eq!(process_bundle(bundle).unwrap(), 2);
+            assert_eq!(bank.get_balance(&bob), 10);
+            assert_eq!(bank.get_balance(&carol), 10);
+            let mint_balance = bank.get_balance(&mint_keypair.pubkey());
+
+            // the failing transfer takes the successful one down with it
+            let bundle = vec![
+                system_transaction::transfer(&mint_keypair, &dave, 10, blockhash),
+                system_transaction::transfer(&alice, &carol, 1_000_000, blockhash),
+            ];
+            assert_matches!(process_bundle(bundle), Err(BundleError::ExecutionFailure));
+            assert_eq!(bank.get_balance(&dave), 0);
+            assert_eq!(bank.get_balance(&mint_keypair.pubkey()), mint_balance);
+
+            // transactions of a bundle share an entry, so they can't conflict
+            let bundle = vec![
+                system_transaction::transfer(&mint_keypair, &dave, 10, blockhash),
+                system_transaction::transfer(&mint_keypair, &carol, 10, blockhash),
+            ];
+            assert_matches!(
+                process_bundle(bundle),
+                Err(BundleError::Lock(1, TransactionError::AccountInUse))
+            );
+            assert_eq!(bank.get_balance(&dave), 0);
+
+            let mut bundle = vec![system_transaction::transfer(
+                &mint_keypair,
+                &dave,
+                10,
+                blockhash,
+            )];
+            bundle[0].message.instructions[0].program_id_index = 42;
+            assert_matches!(process_bundle(bundle), Err(BundleError::Sanitize(0, _)));
+
+            // each committed bundle was recorded as one entry, in order
+            let entries: Vec<_> = entry_receiver
+                .try_iter()
+                .map(|(_bank, (entry, _tick_height))| entry)
+                .filter(|entry| !entry.is_tick())
+                .collect();
+            assert_eq!(
+                entries
+                    .iter()
+                    .map(|entry| entry.transactions.len())
+                    .collect::<Vec<_>>(),
+                vec![1, 2]
+            );
+
+            poh_recorder
+                .lock()
+                .unwrap()
+                .is_exited
+                .store(true, Ordering::Relaxed);
+            let _ = poh_simulator.join();
+        }
+        Blockstore::destroy(&ledger_path).unwrap();
+    }
+
     fn simulate_poh(
         record_receiver: CrossbeamReceiver<Record>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
@@ -2421,25 +3024,30 @@ mod tests {
         tick_producer.unwrap()
     }
 
-    #[test]
-    fn test_bank_process_and_record_transactions_account_in_use() {
+    /// Runs `process` on two conflicting transfers in the leader slot of a bank
+    fn with_conflicting_transactions<T, F>(queue_conflicting_transactions: bool, process: F) -> T
+    where
+        F: FnOnce(&Arc<Bank>, &TransactionRecorder, &ReplayVoteSender, Vec<Transaction>) -> T,
+    {
         solana_logger::setup();
         let GenesisConfigInfo {
             genesis_config,
             mint_keypair,
             ..
         } = create_slow_genesis_config(10_000);
-        let bank = Arc::new(Bank::new_no_wallclock_throttle_for_tests(&genesis_config));
+        let mut bank = Bank::new_no_wallclock_throttle_for_tests(&genesis_config);
+        bank.set_queue_conflicting_transactions(queue_conflicting_transactions);
+        let bank = Arc::new(bank);
         let pubkey = solana_sdk::pubkey::new_rand();
         let pubkey1 = solana_sdk::pubkey::new_rand();
 
-        let transactions = sanitize_transactions(vec![
+        let transactions = vec![
             system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
             system_transaction::transfer(&mint_keypair, &pubkey1, 1, genesis_config.hash()),
-        ]);
+        ];
 
         let ledger_path = get_tmp_ledger_path!();
-        {
+        let result = {
             let blockstore = Blockstore::open(&ledger_path)
                 .expect("Expected to be able to open database ledger");
             let (poh_recorder, _entry_receiver, record_receiver) = PohRecorder::new(
@@ -2463,14 +3071,7 @@ mod tests {
 
             let (gossip_vote_sender, _gossip_vote_receiver) = unbounded();
 
-            let (result, unprocessed) = BankingStage::process_and_record_transactions(
-                &bank,
-                &transactions,
-                &recorder,
-                0,
-                None,
-                &gossip_vote_sender,
-            );
+            let result = process(&bank, &recorder, &gossip_vote_sender, transactions);
 
             poh_recorder
                 .lock()
@@ -2478,11 +3079,80 @@ mod tests {
                 .is_exited
                 .store(true, Ordering::Relaxed);
             let _ = poh_simulator.join();
-
-            assert!(result.is_ok());
-            assert_eq!(unprocessed.len(), 1);
-        }
+            result
+        };
         Blockstore::destroy(&ledger_path).unwrap();
+        result
+    }
+
+    fn process_and_record_conflicting_transactions(
+        queue_conflicting_transactions: bool,
+    ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
+        with_conflicting_transactions(
+            queue_conflicting_transactions,
+            |bank, recorder, gossip_vote_sender, transactions| {
+                BankingStage::process_and_record_transactions(
+                    bank,
+                    &sanitize_transactions(transactions),
+                    recorder,
+                    0,
+                    None,
+                    gossip_vote_sender,
+                )
+            },
+        )
+    }
+
+    fn process_conflicting_packets(
+        queue_conflicting_transactions: bool,
+    ) -> (usize, usize, Vec<usize>) {
+        with_conflicting_transactions(
+            queue_conflicting_transactions,
+            |bank, recorder, gossip_vote_sender, transactions| {
+                let packets = to_packets_chunked(&transactions, transactions.len())
+                    .pop()
+                    .unwrap();
+                BankingStage::process_packets_transactions(
+                    bank,
+                    &Instant::now(),
+                    recorder,
+                    &packets,
+                    (0..transactions.len()).collect(),
+                    None,
+                    gossip_vote_sender,
+                    &BankingStageStats::default(),
+                    &Arc::new(RwLock::new(CostModel::default())),
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn test_bank_process_and_record_transactions_account_in_use() {
+        let (result, unprocessed) = process_and_record_conflicting_transactions(false);
+        assert!(result.is_ok());
+        assert_eq!(unprocessed.len(), 1);
+    }
+
+    #[test]
+    fn test_bank_process_and_record_transactions_queued() {
+        // The second transfer waits for the first one instead of being retried
+        let (result, unprocessed) = process_and_record_conflicting_transactions(true);
+        assert_eq!(result.unwrap(), 2);
+        assert!(unprocessed.is_empty());
+    }
+
+    #[test]
+    fn test_process_packets_transactions_queued() {
+        // The second transfer is deferred to the next pass over the buffered packets
+        let (processed, _, unprocessed) = process_conflicting_packets(false);
+        assert_eq!(processed, 1);
+        assert_eq!(unprocessed, vec![1]);
+
+        // unless it can wait for the first one within the batch
+        let (processed, _, unprocessed) = process_conflicting_packets(true);
+        assert_eq!(processed, 2);
+        assert!(unprocessed.is_empty());
     }
 
     #[test]
@@ -2503,7 +3173,7 @@ mod tests {
                 let valid_indexes = (0..32)
                     .filter_map(|x| if x % 2 != 0 { Some(x as usize) } else { None })
                     .collect_vec();
-                (packets, valid_indexes, false)
+                (packets, valid_indexes, false, 0)
             })
             .collect_vec();
 
@@ -2655,6 +3325,7 @@ mod tests {
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
                 blockstore.clone(),
+                None,
                 &Arc::new(AtomicBool::new(false)),
             );
 
@@ -2778,6 +3449,7 @@ mod tests {
                 all_packets,
                 (0..num_conflicting_transactions).into_iter().collect(),
                 false,
+                0,
             )]
             .into_iter()
             .collect();
@@ -2847,7 +3519,7 @@ mod tests {
             let mut buffered_packets: UnprocessedPackets = packets_vec
                 .clone()
                 .into_iter()
-                .map(|single_packets| (single_packets, vec![0], false))
+                .map(|single_packets| (single_packets, vec![0], false, 0))
                 .collect();
 
             let (continue_sender, continue_receiver) = unbounded();
@@ -2889,10 +3561,12 @@ mod tests {
                         buffered_packets.len(),
                         packets_vec[interrupted_iteration + 1..].len()
                     );
-                    for ((remaining_unprocessed_packet, _, _forwarded), original_packet) in
-                        buffered_packets
-                            .iter()
-                            .zip(&packets_vec[interrupted_iteration + 1..])
+                    for (
+                        (remaining_unprocessed_packet, _, _forwarded, _priority),
+                        original_packet,
+                    ) in buffered_packets
+                        .iter()
+                        .zip(&packets_vec[interrupted_iteration + 1..])
                     {
                         assert_eq!(
                             remaining_unprocessed_packet.packets[0],
@@ -2930,7 +3604,7 @@ mod tests {
         // Create `Packets` with 1 unprocessed element
         let single_element_packets = Packets::new(vec![Packet::default()]);
         let mut unprocessed_packets: UnprocessedPackets =
-            vec![(single_element_packets, vec![0], false)]
+            vec![(single_element_packets, vec![0], false, 0)]
                 .into_iter()
                 .collect();
 
@@ -2978,8 +3652,9 @@ mod tests {
         solana_logger::setup();
         // Create `Packets` with 2 unprocessed elements
         let new_packets = Packets::new(vec![Packet::default(); 2]);
-        let mut unprocessed_packets: UnprocessedPackets =
-            vec![(new_packets, vec![0, 1], false)].into_iter().collect();
+        let mut unprocessed_packets: UnprocessedPackets = vec![(new_packets, vec![0, 1], false, 0)]
+            .into_iter()
+            .collect();
         // Set the limit to 2
         let batch_limit = 2;
         // Create some new unprocessed packets
@@ -3006,6 +3681,7 @@ mod tests {
             batch_limit,
             &duplicates,
             &banking_stage_stats,
+            false,
         );
         assert_eq!(unprocessed_packets.len(), 1);
         assert_eq!(dropped_packet_batches_count, 0);
@@ -3025,6 +3701,7 @@ mod tests {
             batch_limit,
             &duplicates,
             &banking_stage_stats,
+            false,
         );
         assert_eq!(unprocessed_packets.len(), 2);
         assert_eq!(dropped_packet_batches_count, 0);
@@ -3049,6 +3726,7 @@ mod tests {
             batch_limit,
             &duplicates,
             &banking_stage_stats,
+            false,
         );
         assert_eq!(unprocessed_packets.len(), 2);
         assert_eq!(unprocessed_packets[1].0.packets[0], new_packets.packets[0]);
@@ -3067,6 +3745,7 @@ mod tests {
             3,
             &duplicates,
             &banking_stage_stats,
+            false,
         );
         assert_eq!(unprocessed_packets.len(), 2);
         assert_eq!(unprocessed_packets[1].0.packets[0], new_packets.packets[0]);
@@ -3075,6 +3754,96 @@ mod tests {
         assert_eq!(newly_buffered_packets_count, 2);
     }
 
+    #[test]
+    fn test_push_unprocessed_priority() {
+        solana_logger::setup();
+        let priced_packets = |compute_unit_prices: &[u64]| {
+            let transactions: Vec<_> = compute_unit_prices
+                .iter()
+                .map(|compute_unit_price| {
+                    let keypair = Keypair::new();
+                    let instructions = [
+                        ComputeBudgetInstruction::set_compute_unit_price(*compute_unit_price),
+                        system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1),
+                    ];
+                    Transaction::new(
+                        &[&keypair],
+                        Message::new(&instructions, Some(&keypair.pubkey())),
+                        Hash::default(),
+                    )
+                })
+                .collect();
+            to_packets_chunked(&transactions, transactions.len())
+                .pop()
+                .unwrap()
+        };
+        let duplicates = Arc::new(Mutex::new((
+            LruCache::new(DEFAULT_LRU_SIZE),
+            PacketHasher::default(),
+        )));
+        let mut unprocessed_packets = UnprocessedPackets::default();
+        let mut dropped_packet_batches_count = 0;
+        let mut dropped_packets_count = 0;
+        let mut newly_buffered_packets_count = 0;
+        let banking_stage_stats = BankingStageStats::default();
+        let batch_limit = 2;
+        let mut push_unprocessed = |unprocessed_packets: &mut UnprocessedPackets,
+                                    packets: Packets| {
+            let packet_indexes = (0..packets.packets.len()).collect();
+            BankingStage::push_unprocessed(
+                unprocessed_packets,
+                packets,
+                packet_indexes,
+                &mut dropped_packet_batches_count,
+                &mut dropped_packets_count,
+                &mut newly_buffered_packets_count,
+                batch_limit,
+                &duplicates,
+                &banking_stage_stats,
+                true,
+            );
+        };
+        let priorities = |unprocessed_packets: &UnprocessedPackets| {
+            unprocessed_packets
+                .iter()
+                .map(|(_, _, _, priority)| *priority)
+                .collect::<Vec<_>>()
+        };
+
+        // The buffered batches are ordered by descending priority
+        push_unprocessed(&mut unprocessed_packets, priced_packets(&[0]));
+        push_unprocessed(&mut unprocessed_packets, priced_packets(&[10]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 0]);
+
+        // Once the limit is reached, the lowest priced batch makes room for a higher priced one
+        push_unprocessed(&mut unprocessed_packets, priced_packets(&[5]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 5]);
+
+        // and a lower priced one is dropped
+        push_unprocessed(&mut unprocessed_packets, priced_packets(&[1]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 5]);
+
+        // A batch ranks at its lowest price, whatever its other packets offer
+        push_unprocessed(&mut unprocessed_packets, priced_packets(&[1, 50]));
+        assert_eq!(priorities(&unprocessed_packets), vec![10, 5]);
+
+        assert_eq!(dropped_packet_batches_count, 3);
+        assert_eq!(dropped_packets_count, 4);
+        assert_eq!(newly_buffered_packets_count, 3);
+        assert_eq!(
+            banking_stage_stats
+                .prioritized_packets_count
+                .load(Ordering::Relaxed),
+            5
+        );
+        assert_eq!(
+            banking_stage_stats
+                .dropped_low_priority_packets_count
+                .load(Ordering::Relaxed),
+            4
+        );
+    }
+
     #[test]
     fn test_packet_message() {
         let keypair = Keypair::new();
@@ -10,6 +10,8 @@ use {
     },
     crate::{
         cluster_nodes::{ClusterNodes, ClusterNodesCache},
+        completed_data_sets_service::CompletedDataSetsSender,
+        leader_slot_reports::LeaderSlotReports,
         result::{Error, Result},
     },
     crossbeam_channel::{
@@ -86,6 +88,8 @@ impl BroadcastStageType {
         blockstore: &Arc<Blockstore>,
         bank_forks: &Arc<RwLock<BankForks>>,
         shred_version: u16,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+        completed_data_sets_sender: Option<CompletedDataSetsSender>,
     ) -> BroadcastStage {
         match self {
             BroadcastStageType::Standard => BroadcastStage::new(
@@ -96,7 +100,11 @@ impl BroadcastStageType {
                 exit_sender,
                 blockstore,
                 bank_forks,
-                StandardBroadcastRun::new(shred_version),
+                StandardBroadcastRun::new(
+                    shred_version,
+                    leader_slot_reports,
+                    completed_data_sets_sender,
+                ),
             ),
 
             BroadcastStageType::FailEntryVerification => BroadcastStage::new(
@@ -630,7 +638,7 @@ pub mod test {
             &exit_sender,
             &blockstore,
             &bank_forks,
-            StandardBroadcastRun::new(0),
+            StandardBroadcastRun::new(0, None, None),
         );
 
         MockBroadcastStage {
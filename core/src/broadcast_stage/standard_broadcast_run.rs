@@ -7,11 +7,16 @@ use {
     },
     crate::{
         broadcast_stage::broadcast_utils::UnfinishedSlotInfo, cluster_nodes::ClusterNodesCache,
+        completed_data_sets_service::CompletedDataSetsSender,
+        leader_slot_reports::LeaderSlotReports,
     },
     solana_entry::entry::Entry,
-    solana_ledger::shred::{
-        ProcessShredsStats, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
-        SHRED_TICK_REFERENCE_MASK,
+    solana_ledger::{
+        blockstore::CompletedDataSetInfo,
+        shred::{
+            ProcessShredsStats, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
+            SHRED_TICK_REFERENCE_MASK,
+        },
     },
     solana_sdk::{
         signature::Keypair,
@@ -32,10 +37,18 @@ pub struct StandardBroadcastRun {
     last_datapoint_submit: Arc<AtomicInterval>,
     num_batches: usize,
     cluster_nodes_cache: Arc<ClusterNodesCache<BroadcastStage>>,
+    leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+    // Data sets completed by the shreds of this leader, so that received signature
+    // notifications are sent for its own blocks as well
+    completed_data_sets_sender: Option<CompletedDataSetsSender>,
 }
 
 impl StandardBroadcastRun {
-    pub(super) fn new(shred_version: u16) -> Self {
+    pub(super) fn new(
+        shred_version: u16,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+        completed_data_sets_sender: Option<CompletedDataSetsSender>,
+    ) -> Self {
         let cluster_nodes_cache = Arc::new(ClusterNodesCache::<BroadcastStage>::new(
             CLUSTER_NODES_CACHE_NUM_EPOCH_CAP,
             CLUSTER_NODES_CACHE_TTL,
@@ -51,6 +64,16 @@ impl StandardBroadcastRun {
             last_datapoint_submit: Arc::default(),
             num_batches: 0,
             cluster_nodes_cache,
+            leader_slot_reports,
+            completed_data_sets_sender,
+        }
+    }
+
+    fn send_completed_data_sets(&self, completed_data_sets: Vec<CompletedDataSetInfo>) {
+        if let Some(completed_data_sets_sender) = &self.completed_data_sets_sender {
+            if !completed_data_sets.is_empty() {
+                let _ = completed_data_sets_sender.try_send(completed_data_sets);
+            }
         }
     }
 
@@ -223,9 +246,13 @@ impl StandardBroadcastRun {
         // This must be done before the blocks are sent out over the wire.
         if !data_shreds.is_empty() && data_shreds[0].index() == 0 {
             let first = vec![data_shreds[0].clone()];
-            blockstore
+            let (completed_data_sets, _) = blockstore
                 .insert_shreds(first, None, true)
                 .expect("Failed to insert shreds in blockstore");
+            self.send_completed_data_sets(completed_data_sets);
+            if let Some(leader_slot_reports) = &self.leader_slot_reports {
+                leader_slot_reports.record_shred(data_shreds[0].slot());
+            }
         }
         to_shreds_time.stop();
 
@@ -320,9 +347,10 @@ impl StandardBroadcastRun {
         } else {
             shreds.to_vec()
         };
-        blockstore
+        let (completed_data_sets, _) = blockstore
             .insert_shreds(data_shreds, None, true)
             .expect("Failed to insert shreds in blockstore");
+        self.send_completed_data_sets(completed_data_sets);
         let insert_shreds_elapsed = insert_shreds_start.elapsed();
         let new_insert_shreds_stats = InsertShredsStats {
             insert_shreds_elapsed: duration_as_us(&insert_shreds_elapsed),
@@ -571,7 +599,7 @@ mod test {
     #[test]
     fn test_interrupted_slot_last_shred() {
         let keypair = Arc::new(Keypair::new());
-        let mut run = StandardBroadcastRun::new(0);
+        let mut run = StandardBroadcastRun::new(0, None, None);
 
         // Set up the slot to be interrupted
         let next_shred_index = 10;
@@ -620,7 +648,7 @@ mod test {
         };
 
         // Step 1: Make an incomplete transmission for slot 0
-        let mut standard_broadcast_run = StandardBroadcastRun::new(0);
+        let mut standard_broadcast_run = StandardBroadcastRun::new(0, None, None);
         standard_broadcast_run
             .test_process_receive_results(
                 &leader_keypair,
@@ -740,7 +768,7 @@ mod test {
         let (bsend, brecv) = channel();
         let (ssend, _srecv) = channel();
         let mut last_tick_height = 0;
-        let mut standard_broadcast_run = StandardBroadcastRun::new(0);
+        let mut standard_broadcast_run = StandardBroadcastRun::new(0, None, None);
         let mut process_ticks = |num_ticks| {
             let ticks = create_ticks(num_ticks, 0, genesis_config.hash());
             last_tick_height += (ticks.len() - 1) as u64;
@@ -798,7 +826,7 @@ mod test {
             last_tick_height: ticks.len() as u64,
         };
 
-        let mut standard_broadcast_run = StandardBroadcastRun::new(0);
+        let mut standard_broadcast_run = StandardBroadcastRun::new(0, None, None);
         standard_broadcast_run
             .test_process_receive_results(
                 &leader_keypair,
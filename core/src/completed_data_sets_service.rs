@@ -1,9 +1,14 @@
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use solana_entry::entry::Entry;
-use solana_ledger::blockstore::{Blockstore, CompletedDataSetInfo};
+use solana_ledger::{
+    blockstore::{Blockstore, CompletedDataSetInfo},
+    entry_notifier_interface::EntryNotifier,
+    streaming_deshredder::StreamingDeshredder,
+};
 use solana_rpc::{max_slots::MaxSlots, rpc_subscriptions::RpcSubscriptions};
-use solana_sdk::signature::Signature;
+use solana_sdk::{clock::Slot, signature::Signature};
 use std::{
+    collections::BTreeSet,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -26,8 +31,11 @@ impl CompletedDataSetsService {
         rpc_subscriptions: Arc<RpcSubscriptions>,
         exit: &Arc<AtomicBool>,
         max_slots: Arc<MaxSlots>,
+        entry_notifier: Option<EntryNotifier>,
     ) -> Self {
         let exit = exit.clone();
+        // The entries are only deshredded a second time when some plugin streams them
+        let mut deshredder = entry_notifier.is_some().then(StreamingDeshredder::default);
         let thread_hdl = Builder::new()
             .name("completed-data-set-service".to_string())
             .spawn(move || loop {
@@ -39,6 +47,7 @@ impl CompletedDataSetsService {
                     &blockstore,
                     &rpc_subscriptions,
                     &max_slots,
+                    entry_notifier.as_ref().zip(deshredder.as_mut()),
                 ) {
                     break;
                 }
@@ -52,9 +61,11 @@ impl CompletedDataSetsService {
         blockstore: &Blockstore,
         rpc_subscriptions: &RpcSubscriptions,
         max_slots: &Arc<MaxSlots>,
+        entry_streaming: Option<(&EntryNotifier, &mut StreamingDeshredder)>,
     ) -> Result<(), RecvTimeoutError> {
         let completed_data_sets = completed_sets_receiver.recv_timeout(Duration::from_secs(1))?;
         let mut max_slot = 0;
+        let mut updated_slots = BTreeSet::new();
         for completed_set_info in std::iter::once(completed_data_sets)
             .chain(completed_sets_receiver.try_iter())
             .flatten()
@@ -65,6 +76,7 @@ impl CompletedDataSetsService {
                 end_index,
             } = completed_set_info;
             max_slot = max_slot.max(slot);
+            updated_slots.insert(slot);
             match blockstore.get_entries_in_data_block(slot, start_index, end_index, None) {
                 Ok(entries) => {
                     let transactions = Self::get_transaction_signatures(entries);
@@ -78,10 +90,35 @@ impl CompletedDataSetsService {
         max_slots
             .shred_insert
             .fetch_max(max_slot, Ordering::Relaxed);
+        if let Some((entry_notifier, deshredder)) = entry_streaming {
+            Self::notify_entries(blockstore, entry_notifier, deshredder, updated_slots);
+        }
 
         Ok(())
     }
 
+    /// Notifies the entries of the updated slots whose data blocks before them are all complete
+    fn notify_entries(
+        blockstore: &Blockstore,
+        entry_notifier: &EntryNotifier,
+        deshredder: &mut StreamingDeshredder,
+        updated_slots: BTreeSet<Slot>,
+    ) {
+        deshredder.set_root(blockstore.max_root());
+        let entry_notifier = entry_notifier.read().unwrap();
+        for slot in updated_slots {
+            match deshredder.on_slot_updated(blockstore, slot) {
+                Ok(Some(batch)) => {
+                    for (i, entry) in batch.entries.iter().enumerate() {
+                        entry_notifier.notify_entry(slot, batch.start_entry_index + i, entry);
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => warn!("completed-data-set-service deshred error: {:?}", e),
+            }
+        }
+    }
+
     fn get_transaction_signatures(entries: Vec<Entry>) -> Vec<Signature> {
         entries
             .into_iter()
@@ -0,0 +1,110 @@
+//! The `dumped_slots` module keeps track of the slots replay dumped from the blockstore because
+//! the cluster confirmed another version of them, so that the most recent ones, and why they were
+//! dumped, can be listed through the admin RPC.
+
+use {
+    solana_sdk::{clock::Slot, hash::Hash, timing::timestamp},
+    std::{collections::VecDeque, sync::Mutex},
+};
+
+/// Number of dumped slots kept by default
+pub const DEFAULT_DUMPED_SLOTS_CAPACITY: usize = 1_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DumpedSlotReason {
+    /// Our version of the slot failed to replay
+    Dead,
+    /// Our version of the slot replayed to a different bank hash than the cluster's
+    HashMismatch,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpedSlot {
+    pub slot: Slot,
+    pub reason: DumpedSlotReason,
+    /// Bank hash of our version of the slot, unset if it is dead
+    pub dumped_hash: Option<String>,
+    /// Bank hash of the version of the slot the cluster confirmed
+    pub correct_hash: String,
+    /// Whether the slot was purged to repair the correct version, false if dumping is disabled
+    /// and the slot is left to be recovered by hand
+    pub purged: bool,
+    /// Milliseconds since the UNIX epoch
+    pub timestamp: u64,
+}
+
+#[derive(Debug)]
+pub struct DumpedSlots {
+    capacity: usize,
+    slots: Mutex<VecDeque<DumpedSlot>>,
+}
+
+impl DumpedSlots {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            slots: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(
+        &self,
+        slot: Slot,
+        reason: DumpedSlotReason,
+        dumped_hash: Option<Hash>,
+        correct_hash: Hash,
+        purged: bool,
+    ) {
+        let mut slots = self.slots.lock().unwrap();
+        if slots.len() >= self.capacity {
+            slots.pop_front();
+        }
+        slots.push_back(DumpedSlot {
+            slot,
+            reason,
+            dumped_hash: dumped_hash.map(|hash| hash.to_string()),
+            correct_hash: correct_hash.to_string(),
+            purged,
+            timestamp: timestamp(),
+        });
+    }
+
+    /// Returns the most recently dumped slots, oldest first
+    pub fn recent(&self) -> Vec<DumpedSlot> {
+        self.slots.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dumped_slots() {
+        let dumped_slots = DumpedSlots::new(2);
+        let correct_hash = Hash::new_unique();
+        dumped_slots.record(1, DumpedSlotReason::Dead, None, correct_hash, true);
+        dumped_slots.record(
+            2,
+            DumpedSlotReason::HashMismatch,
+            Some(Hash::new_unique()),
+            correct_hash,
+            false,
+        );
+        dumped_slots.record(3, DumpedSlotReason::Dead, None, correct_hash, true);
+
+        // The oldest slots are dropped beyond the capacity
+        let recent = dumped_slots.recent();
+        assert_eq!(
+            recent.iter().map(|dumped| dumped.slot).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(recent[0].reason, DumpedSlotReason::HashMismatch);
+        assert!(recent[0].dumped_hash.is_some());
+        assert!(!recent[0].purged);
+        assert_eq!(recent[1].dumped_hash, None);
+        assert_eq!(recent[1].correct_hash, correct_hash.to_string());
+    }
+}
@@ -0,0 +1,435 @@
+//! The `external_block_producer` module lets a local relayer feed pre-built transaction bundles
+//! into the banking stage while this node is leader.
+//!
+//! The relayer connects to a Unix domain socket and writes frames consisting of a little-endian
+//! `u32` length followed by a bincode-serialized `Vec<Transaction>`. Each bundle is validated,
+//! including its signatures, and then handed to the banking stage as a single unit, which
+//! executes it in order and records it in one entry only if every transaction succeeds. Bundles
+//! received outside of this node's leader slots are dropped. The regular TPU keeps running
+//! regardless of whether a relayer is connected, so a missing or misbehaving relayer only means
+//! falling back to normal operation. Unix domain sockets are only available on unix targets;
+//! elsewhere the producer fails to start.
+#![cfg_attr(not(unix), allow(dead_code, unused_imports))]
+
+#[cfg(unix)]
+use std::os::unix::{
+    fs::FileTypeExt,
+    net::{UnixListener, UnixStream},
+};
+use {
+    crossbeam_channel::Sender as CrossbeamSender,
+    solana_metrics::datapoint_info,
+    solana_poh::poh_recorder::PohRecorder,
+    solana_sdk::{packet::PACKET_DATA_SIZE, sanitize::Sanitize, transaction::Transaction},
+    std::{
+        fs,
+        io::{self, Read},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
+};
+
+pub const DEFAULT_MAX_BATCH_TRANSACTIONS: usize = 256;
+
+/// Sends the validated bundles of the relayer to the banking stage
+pub type BundleSender = CrossbeamSender<Vec<Transaction>>;
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+const STATS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug)]
+pub struct ExternalBlockProducerConfig {
+    pub socket_path: PathBuf,
+    pub max_batch_transactions: usize,
+}
+
+impl ExternalBlockProducerConfig {
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self {
+            socket_path,
+            max_batch_transactions: DEFAULT_MAX_BATCH_TRANSACTIONS,
+        }
+    }
+
+    /// Largest frame accepted from the relayer; every transaction must fit in a packet
+    fn max_frame_size(&self) -> usize {
+        std::mem::size_of::<u64>() + self.max_batch_transactions * PACKET_DATA_SIZE
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BatchError {
+    #[error("batch is empty")]
+    Empty,
+    #[error("batch contains {0} transactions")]
+    TooManyTransactions(usize),
+    #[error("transaction {0} does not fit in a packet")]
+    TransactionTooLarge(usize),
+    #[error("transaction {0} failed sanitization")]
+    SanitizeFailure(usize),
+    #[error("transaction {0} failed signature verification")]
+    SignatureFailure(usize),
+    #[error("batch could not be deserialized")]
+    Deserialize,
+}
+
+#[derive(Default)]
+struct ExternalBlockProducerStats {
+    connections: u64,
+    batches_received: u64,
+    batches_forwarded: u64,
+    batches_not_leader: u64,
+    batches_invalid: u64,
+    transactions_forwarded: u64,
+}
+
+impl ExternalBlockProducerStats {
+    fn report(&mut self) {
+        datapoint_info!(
+            "external_block_producer",
+            ("connections", self.connections, i64),
+            ("batches_received", self.batches_received, i64),
+            ("batches_forwarded", self.batches_forwarded, i64),
+            ("batches_not_leader", self.batches_not_leader, i64),
+            ("batches_invalid", self.batches_invalid, i64),
+            ("transactions_forwarded", self.transactions_forwarded, i64),
+        );
+        *self = Self::default();
+    }
+}
+
+pub struct ExternalBlockProducer {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl ExternalBlockProducer {
+    #[cfg(not(unix))]
+    pub fn new(
+        _config: ExternalBlockProducerConfig,
+        _poh_recorder: &Arc<Mutex<PohRecorder>>,
+        _bundle_sender: BundleSender,
+        _exit: &Arc<AtomicBool>,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "external block producer requires unix domain sockets",
+        ))
+    }
+
+    #[cfg(unix)]
+    pub fn new(
+        config: ExternalBlockProducerConfig,
+        poh_recorder: &Arc<Mutex<PohRecorder>>,
+        bundle_sender: BundleSender,
+        exit: &Arc<AtomicBool>,
+    ) -> io::Result<Self> {
+        remove_stale_socket(&config.socket_path)?;
+        let listener = UnixListener::bind(&config.socket_path)?;
+        listener.set_nonblocking(true)?;
+        info!(
+            "external block producer listening on {:?}",
+            config.socket_path
+        );
+
+        let poh_recorder = poh_recorder.clone();
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solExtBlockProd".to_string())
+            .spawn(move || {
+                Self::run(&config, &listener, &poh_recorder, &bundle_sender, &exit);
+                let _ = fs::remove_file(&config.socket_path);
+            })
+            .unwrap();
+        Ok(Self { thread_hdl })
+    }
+
+    #[cfg(unix)]
+    fn run(
+        config: &ExternalBlockProducerConfig,
+        listener: &UnixListener,
+        poh_recorder: &Mutex<PohRecorder>,
+        bundle_sender: &BundleSender,
+        exit: &AtomicBool,
+    ) {
+        let mut stats = ExternalBlockProducerStats::default();
+        let mut last_report = Instant::now();
+        while !exit.load(Ordering::Relaxed) {
+            if last_report.elapsed() > STATS_REPORT_INTERVAL {
+                stats.report();
+                last_report = Instant::now();
+            }
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    sleep(ACCEPT_POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => {
+                    warn!("external block producer accept failed: {}", err);
+                    sleep(ACCEPT_POLL_INTERVAL);
+                    continue;
+                }
+            };
+            stats.connections += 1;
+            info!("external block producer connected");
+            if let Err(err) = Self::serve_connection(
+                config,
+                stream,
+                poh_recorder,
+                bundle_sender,
+                exit,
+                &mut stats,
+                &mut last_report,
+            ) {
+                warn!("external block producer disconnected: {}", err);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn serve_connection(
+        config: &ExternalBlockProducerConfig,
+        mut stream: UnixStream,
+        poh_recorder: &Mutex<PohRecorder>,
+        bundle_sender: &BundleSender,
+        exit: &AtomicBool,
+        stats: &mut ExternalBlockProducerStats,
+        last_report: &mut Instant,
+    ) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let max_frame_size = config.max_frame_size();
+        loop {
+            let frame = match read_frame(&mut stream, max_frame_size, exit)? {
+                Some(frame) => frame,
+                None => return Ok(()),
+            };
+            stats.batches_received += 1;
+            match validate_batch(&frame, config.max_batch_transactions) {
+                Ok(transactions) => {
+                    if !poh_recorder.lock().unwrap().has_bank() {
+                        stats.batches_not_leader += 1;
+                    } else {
+                        stats.batches_forwarded += 1;
+                        stats.transactions_forwarded += transactions.len() as u64;
+                        if bundle_sender.send(transactions).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+                Err(err) => {
+                    stats.batches_invalid += 1;
+                    debug!("external block producer sent invalid batch: {}", err);
+                }
+            }
+            if last_report.elapsed() > STATS_REPORT_INTERVAL {
+                stats.report();
+                *last_report = Instant::now();
+            }
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+/// A socket left behind by a previous run would make the bind fail. Anything else at `path` is
+/// not ours to remove.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{:?} exists and is not a socket", path),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Read one length-prefixed frame. Returns None once `exit` is set.
+fn read_frame<R: Read>(
+    reader: &mut R,
+    max_frame_size: usize,
+    exit: &AtomicBool,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    if !read_full(reader, &mut len, exit)? {
+        return Ok(None);
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > max_frame_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds limit of {}", len, max_frame_size),
+        ));
+    }
+    let mut frame = vec![0u8; len];
+    if !read_full(reader, &mut frame, exit)? {
+        return Ok(None);
+    }
+    Ok(Some(frame))
+}
+
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8], exit: &AtomicBool) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        if exit.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock
+                        | io::ErrorKind::TimedOut
+                        | io::ErrorKind::Interrupted
+                ) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+fn validate_batch(
+    frame: &[u8],
+    max_batch_transactions: usize,
+) -> std::result::Result<Vec<Transaction>, BatchError> {
+    let transactions: Vec<Transaction> =
+        bincode::deserialize(frame).map_err(|_| BatchError::Deserialize)?;
+    if transactions.is_empty() {
+        return Err(BatchError::Empty);
+    }
+    if transactions.len() > max_batch_transactions {
+        return Err(BatchError::TooManyTransactions(transactions.len()));
+    }
+    for (i, transaction) in transactions.iter().enumerate() {
+        let size = bincode::serialized_size(transaction).map_err(|_| BatchError::Deserialize)?;
+        if size as usize > PACKET_DATA_SIZE {
+            return Err(BatchError::TransactionTooLarge(i));
+        }
+        if transaction.sanitize().is_err() {
+            return Err(BatchError::SanitizeFailure(i));
+        }
+        // Bundles bypass the sigverify stage
+        if transaction.verify().is_err() {
+            return Err(BatchError::SignatureFailure(i));
+        }
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash,
+            instruction::CompiledInstruction,
+            signature::{Keypair, Signature, Signer},
+            system_transaction,
+        },
+        std::io::Write,
+    };
+
+    #[test]
+    fn test_validate_batch() {
+        let keypair = Keypair::new();
+        let tx = system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default());
+        let frame = bincode::serialize(&vec![tx.clone(), tx.clone()]).unwrap();
+        assert_eq!(validate_batch(&frame, 2).unwrap().len(), 2);
+        assert_eq!(
+            validate_batch(&frame, 1).unwrap_err(),
+            BatchError::TooManyTransactions(2)
+        );
+
+        let empty = bincode::serialize(&Vec::<Transaction>::new()).unwrap();
+        assert_eq!(validate_batch(&empty, 2).unwrap_err(), BatchError::Empty);
+        assert_eq!(
+            validate_batch(&frame[..frame.len() - 1], 2).unwrap_err(),
+            BatchError::Deserialize
+        );
+
+        let mut unsanitary = tx.clone();
+        unsanitary.message.instructions[0] = CompiledInstruction::new(42, &(), vec![]);
+        let frame = bincode::serialize(&vec![tx.clone(), unsanitary]).unwrap();
+        assert_eq!(
+            validate_batch(&frame, 2).unwrap_err(),
+            BatchError::SanitizeFailure(1)
+        );
+
+        let mut forged = tx.clone();
+        forged.signatures[0] = Signature::default();
+        let frame = bincode::serialize(&vec![tx.clone(), forged]).unwrap();
+        assert_eq!(
+            validate_batch(&frame, 2).unwrap_err(),
+            BatchError::SignatureFailure(1)
+        );
+
+        let mut oversized = tx;
+        oversized.message.instructions[0].data = vec![0; PACKET_DATA_SIZE];
+        let frame = bincode::serialize(&vec![oversized]).unwrap();
+        assert_eq!(
+            validate_batch(&frame, 2).unwrap_err(),
+            BatchError::TransactionTooLarge(0)
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_read_frame() {
+        let exit = AtomicBool::new(false);
+        let (mut writer, mut reader) = UnixStream::pair().unwrap();
+        reader.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+
+        writer.write_all(&3u32.to_le_bytes()).unwrap();
+        writer.write_all(&[1, 2, 3]).unwrap();
+        assert_eq!(
+            read_frame(&mut reader, 16, &exit).unwrap(),
+            Some(vec![1, 2, 3])
+        );
+
+        writer.write_all(&17u32.to_le_bytes()).unwrap();
+        assert_eq!(
+            read_frame(&mut reader, 16, &exit).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // nothing pending and exit set
+        exit.store(true, Ordering::Relaxed);
+        assert_eq!(read_frame(&mut reader, 16, &exit).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remove_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relayer.sock");
+        // nothing to remove
+        remove_stale_socket(&path).unwrap();
+
+        let listener = UnixListener::bind(&path).unwrap();
+        drop(listener);
+        assert!(path.exists());
+        remove_stale_socket(&path).unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, b"not a socket").unwrap();
+        assert_eq!(
+            remove_stale_socket(&path).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert!(path.exists());
+    }
+}
@@ -7,7 +7,7 @@ use solana_perf::packet::PacketsRecycler;
 use solana_perf::recycler::Recycler;
 use solana_poh::poh_recorder::PohRecorder;
 use solana_sdk::clock::DEFAULT_TICKS_PER_SLOT;
-use solana_streamer::streamer::{self, PacketReceiver, PacketSender};
+use solana_streamer::streamer::{self, PacketReceiver, PacketSender, ReceiverConfig};
 use std::net::UdpSocket;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::{channel, RecvTimeoutError};
@@ -26,7 +26,7 @@ impl FetchStage {
         tpu_vote_sockets: Vec<UdpSocket>,
         exit: &Arc<AtomicBool>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
-        coalesce_ms: u64,
+        receiver_config: ReceiverConfig,
     ) -> (Self, PacketReceiver, PacketReceiver) {
         let (sender, receiver) = channel();
         let (vote_sender, vote_receiver) = channel();
@@ -39,7 +39,7 @@ impl FetchStage {
                 &sender,
                 &vote_sender,
                 poh_recorder,
-                coalesce_ms,
+                receiver_config,
             ),
             receiver,
             vote_receiver,
@@ -54,7 +54,7 @@ impl FetchStage {
         sender: &PacketSender,
         vote_sender: &PacketSender,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
-        coalesce_ms: u64,
+        receiver_config: ReceiverConfig,
     ) -> Self {
         let tx_sockets = sockets.into_iter().map(Arc::new).collect();
         let tpu_forwards_sockets = tpu_forwards_sockets.into_iter().map(Arc::new).collect();
@@ -67,7 +67,7 @@ impl FetchStage {
             sender,
             vote_sender,
             poh_recorder,
-            coalesce_ms,
+            receiver_config,
         )
     }
 
@@ -115,43 +115,49 @@ impl FetchStage {
         sender: &PacketSender,
         vote_sender: &PacketSender,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
-        coalesce_ms: u64,
+        receiver_config: ReceiverConfig,
     ) -> Self {
         let recycler: PacketsRecycler = Recycler::warmed(1000, 1024);
 
         let tpu_threads = sockets.into_iter().map(|socket| {
-            streamer::receiver(
+            streamer::receiver_with_config(
                 socket,
                 exit,
                 sender.clone(),
                 recycler.clone(),
                 "fetch_stage",
-                coalesce_ms,
+                receiver_config,
                 true,
             )
         });
 
+        // The requested receive buffer size is for the TPU sockets only; the forwards and vote
+        // sockets keep the system default
+        let other_receiver_config = ReceiverConfig {
+            recv_buffer_size: None,
+            ..receiver_config
+        };
         let (forward_sender, forward_receiver) = channel();
         let tpu_forwards_threads = tpu_forwards_sockets.into_iter().map(|socket| {
-            streamer::receiver(
+            streamer::receiver_with_config(
                 socket,
                 exit,
                 forward_sender.clone(),
                 recycler.clone(),
                 "fetch_forward_stage",
-                coalesce_ms,
+                other_receiver_config,
                 true,
             )
         });
 
         let tpu_vote_threads = tpu_vote_sockets.into_iter().map(|socket| {
-            streamer::receiver(
+            streamer::receiver_with_config(
                 socket,
                 exit,
                 vote_sender.clone(),
                 recycler.clone(),
                 "fetch_vote_stage",
-                coalesce_ms,
+                other_receiver_config,
                 true,
             )
         });
@@ -0,0 +1,311 @@
+//! The `leader_slot_reports` module gathers what happened during each leader slot of the
+//! validator: what the banking stage did with the packets it received, what the block ended up
+//! holding and how long it took to start broadcasting it. A report is emitted to metrics once the
+//! slot is frozen, and the most recent ones are kept for the admin RPC.
+
+use {
+    solana_runtime::bank::{Bank, RewardType},
+    solana_sdk::clock::Slot,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        time::Instant,
+    },
+};
+
+/// Number of leader slot reports kept by default
+pub const DEFAULT_LEADER_SLOT_REPORTS_CAPACITY: usize = 1_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderSlotDroppedPackets {
+    /// Packets that couldn't be turned into valid transactions
+    pub invalid: u64,
+    /// Transactions with an expired blockhash, or already processed
+    pub expired_or_processed: u64,
+    /// Packets that didn't fit in the buffer of the banking stage
+    pub buffer_full: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderSlotRetriedPackets {
+    /// Transactions that would have exceeded the block or account cost limits
+    pub cost_limit: u64,
+    /// Transactions that couldn't be executed yet, mostly because of account lock contention
+    pub retryable: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderSlotReport {
+    pub slot: Slot,
+    /// Packets received from sigverify during the slot
+    pub packets_received: u64,
+    /// Packets buffered during the slot, to be retried or forwarded
+    pub packets_buffered: u64,
+    pub transactions_executed: u64,
+    pub packets_dropped: LeaderSlotDroppedPackets,
+    pub packets_retried: LeaderSlotRetriedPackets,
+    /// Compute units of the transactions packed in the block
+    pub compute_units: u64,
+    /// Fees credited to the validator for the block, in lamports
+    pub fees_earned: u64,
+    /// Time between the creation of the bank and the first shred of the block, unset if no shred
+    /// was made
+    pub time_to_first_shred_us: Option<u64>,
+}
+
+/// Counters of a leader slot in progress, shared by the banking stage threads
+#[derive(Debug)]
+pub struct LeaderSlotCounters {
+    start: Instant,
+    first_shred_us: AtomicU64,
+    pub packets_received: AtomicU64,
+    pub packets_buffered: AtomicU64,
+    pub transactions_executed: AtomicU64,
+    pub packets_dropped_invalid: AtomicU64,
+    pub packets_dropped_expired_or_processed: AtomicU64,
+    pub packets_dropped_buffer_full: AtomicU64,
+    pub packets_retried_cost_limit: AtomicU64,
+    pub packets_retried_retryable: AtomicU64,
+}
+
+impl LeaderSlotCounters {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            first_shred_us: AtomicU64::new(u64::MAX),
+            packets_received: AtomicU64::default(),
+            packets_buffered: AtomicU64::default(),
+            transactions_executed: AtomicU64::default(),
+            packets_dropped_invalid: AtomicU64::default(),
+            packets_dropped_expired_or_processed: AtomicU64::default(),
+            packets_dropped_buffer_full: AtomicU64::default(),
+            packets_retried_cost_limit: AtomicU64::default(),
+            packets_retried_retryable: AtomicU64::default(),
+        }
+    }
+
+    pub fn add(counter: &AtomicU64, count: usize) {
+        counter.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    fn report(&self, bank: &Bank) -> LeaderSlotReport {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let fees_earned = bank
+            .rewards
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(pubkey, reward)| {
+                pubkey == bank.collector_id() && reward.reward_type == RewardType::Fee
+            })
+            .map(|(_, reward)| reward.lamports.max(0) as u64)
+            .sum();
+        let first_shred_us = load(&self.first_shred_us);
+        LeaderSlotReport {
+            slot: bank.slot(),
+            packets_received: load(&self.packets_received),
+            packets_buffered: load(&self.packets_buffered),
+            transactions_executed: load(&self.transactions_executed),
+            packets_dropped: LeaderSlotDroppedPackets {
+                invalid: load(&self.packets_dropped_invalid),
+                expired_or_processed: load(&self.packets_dropped_expired_or_processed),
+                buffer_full: load(&self.packets_dropped_buffer_full),
+            },
+            packets_retried: LeaderSlotRetriedPackets {
+                cost_limit: load(&self.packets_retried_cost_limit),
+                retryable: load(&self.packets_retried_retryable),
+            },
+            compute_units: bank.read_cost_tracker().unwrap().block_cost(),
+            fees_earned,
+            time_to_first_shred_us: (first_shred_us != u64::MAX).then(|| first_shred_us),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LeaderSlotReports {
+    capacity: usize,
+    slots_in_progress: Mutex<HashMap<Slot, Arc<LeaderSlotCounters>>>,
+    reports: Mutex<VecDeque<LeaderSlotReport>>,
+}
+
+impl LeaderSlotReports {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            slots_in_progress: Mutex::default(),
+            reports: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Starts counting for `slot`, when its bank is created
+    pub fn start_slot(&self, slot: Slot) {
+        self.slots_in_progress
+            .lock()
+            .unwrap()
+            .insert(slot, Arc::new(LeaderSlotCounters::new()));
+    }
+
+    /// Counters of `slot`, if it is a leader slot in progress
+    pub fn slot_counters(&self, slot: Slot) -> Option<Arc<LeaderSlotCounters>> {
+        self.slots_in_progress.lock().unwrap().get(&slot).cloned()
+    }
+
+    /// Records that a shred of `slot` was made, only the first one counts
+    pub fn record_shred(&self, slot: Slot) {
+        if let Some(counters) = self.slot_counters(slot) {
+            let elapsed_us = counters.start.elapsed().as_micros() as u64;
+            counters
+                .first_shred_us
+                .fetch_min(elapsed_us, Ordering::Relaxed);
+        }
+    }
+
+    /// Reports the leader slot of `bank` once it is frozen. The slots in progress before it were
+    /// abandoned and are dropped.
+    pub fn complete_slot(&self, bank: &Bank) {
+        let counters = {
+            let mut slots_in_progress = self.slots_in_progress.lock().unwrap();
+            let counters = slots_in_progress.remove(&bank.slot());
+            slots_in_progress.retain(|slot, _| *slot > bank.slot());
+            counters
+        };
+        let report = match counters {
+            Some(counters) => counters.report(bank),
+            None => return,
+        };
+        datapoint_info!(
+            "leader-slot-report",
+            ("slot", report.slot, i64),
+            ("packets_received", report.packets_received, i64),
+            ("packets_buffered", report.packets_buffered, i64),
+            ("transactions_executed", report.transactions_executed, i64),
+            (
+                "packets_dropped_invalid",
+                report.packets_dropped.invalid,
+                i64
+            ),
+            (
+                "packets_dropped_expired_or_processed",
+                report.packets_dropped.expired_or_processed,
+                i64
+            ),
+            (
+                "packets_dropped_buffer_full",
+                report.packets_dropped.buffer_full,
+                i64
+            ),
+            (
+                "packets_retried_cost_limit",
+                report.packets_retried.cost_limit,
+                i64
+            ),
+            (
+                "packets_retried_retryable",
+                report.packets_retried.retryable,
+                i64
+            ),
+            ("compute_units", report.compute_units, i64),
+            ("fees_earned", report.fees_earned, i64),
+            (
+                "time_to_first_shred_us",
+                report.time_to_first_shred_us.unwrap_or_default(),
+                i64
+            ),
+        );
+        let mut reports = self.reports.lock().unwrap();
+        if reports.len() >= self.capacity {
+            reports.pop_front();
+        }
+        reports.push_back(report);
+    }
+
+    /// Returns the most recent reports, oldest first
+    pub fn recent(&self) -> Vec<LeaderSlotReport> {
+        self.reports.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_runtime::{bank::RewardInfo, genesis_utils::create_genesis_config},
+        solana_sdk::pubkey::Pubkey,
+    };
+
+    #[test]
+    fn test_leader_slot_reports() {
+        let genesis_config = create_genesis_config(100).genesis_config;
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let bank2 = Bank::new_from_parent(&bank0, &Pubkey::default(), 2);
+        let leader_slot_reports = LeaderSlotReports::new(1);
+
+        // Only the slots started as leader are counted
+        assert!(leader_slot_reports.slot_counters(1).is_none());
+        leader_slot_reports.start_slot(1);
+        leader_slot_reports.start_slot(2);
+        let counters = leader_slot_reports.slot_counters(2).unwrap();
+        LeaderSlotCounters::add(&counters.packets_received, 10);
+        LeaderSlotCounters::add(&counters.transactions_executed, 7);
+        LeaderSlotCounters::add(&counters.packets_dropped_invalid, 1);
+        LeaderSlotCounters::add(&counters.packets_retried_retryable, 2);
+        leader_slot_reports.record_shred(2);
+        let first_shred_us = counters.first_shred_us.load(Ordering::Relaxed);
+        leader_slot_reports.record_shred(2);
+        assert_eq!(
+            counters.first_shred_us.load(Ordering::Relaxed),
+            first_shred_us
+        );
+        bank2.rewards.write().unwrap().push((
+            *bank2.collector_id(),
+            RewardInfo {
+                reward_type: RewardType::Fee,
+                lamports: 5_000,
+                post_balance: 5_000,
+                commission: None,
+            },
+        ));
+
+        // Completing slot 2 drops the abandoned slot 1
+        leader_slot_reports.complete_slot(&bank2);
+        assert!(leader_slot_reports.slot_counters(1).is_none());
+        assert!(leader_slot_reports.slot_counters(2).is_none());
+        leader_slot_reports.complete_slot(&bank1);
+        assert_eq!(
+            leader_slot_reports.recent(),
+            vec![LeaderSlotReport {
+                slot: 2,
+                packets_received: 10,
+                transactions_executed: 7,
+                packets_dropped: LeaderSlotDroppedPackets {
+                    invalid: 1,
+                    ..LeaderSlotDroppedPackets::default()
+                },
+                packets_retried: LeaderSlotRetriedPackets {
+                    retryable: 2,
+                    ..LeaderSlotRetriedPackets::default()
+                },
+                fees_earned: 5_000,
+                time_to_first_shred_us: Some(first_shred_us),
+                ..LeaderSlotReport::default()
+            }]
+        );
+
+        // Only the most recent reports are kept
+        leader_slot_reports.start_slot(3);
+        let bank3 = Bank::new_from_parent(&Arc::new(bank2), &Pubkey::default(), 3);
+        leader_slot_reports.complete_slot(&bank3);
+        let reports = leader_slot_reports.recent();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].slot, 3);
+        assert_eq!(reports[0].time_to_first_shred_us, None);
+    }
+}
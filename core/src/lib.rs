@@ -21,12 +21,15 @@ pub mod commitment_service;
 pub mod completed_data_sets_service;
 pub mod consensus;
 pub mod cost_update_service;
+pub mod dumped_slots;
 pub mod duplicate_repair_status;
+pub mod external_block_producer;
 pub mod fetch_stage;
 pub mod fork_choice;
 pub mod gen_keys;
 pub mod heaviest_subtree_fork_choice;
 pub mod latest_validator_votes_for_frozen_banks;
+pub mod leader_slot_reports;
 pub mod ledger_cleanup_service;
 pub mod optimistic_confirmation_verifier;
 pub mod outstanding_requests;
@@ -52,6 +55,7 @@ pub mod snapshot_packager_service;
 pub mod system_monitor_service;
 pub mod tower_storage;
 pub mod tpu;
+pub mod transaction_priority;
 pub mod tree_diff;
 pub mod tvu;
 pub mod unfrozen_gossip_verified_vote_hashes;
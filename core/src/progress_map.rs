@@ -11,6 +11,7 @@ use {
     std::{
         collections::{BTreeMap, HashMap, HashSet},
         sync::{Arc, RwLock},
+        time::Instant,
     },
 };
 
@@ -200,6 +201,9 @@ pub struct ForkProgress {
     // so these stats do not span all of time
     pub num_blocks_on_fork: u64,
     pub num_dropped_blocks_on_fork: u64,
+    // When replay started waiting for broadcast to insert the shreds of a
+    // block this node produced, so that it can be verified
+    pub produced_block_wait_start: Option<Instant>,
 }
 
 impl ForkProgress {
@@ -242,6 +246,7 @@ impl ForkProgress {
             replay_progress: ConfirmationProgress::new(last_entry),
             num_blocks_on_fork,
             num_dropped_blocks_on_fork,
+            produced_block_wait_start: None,
             propagated_stats: PropagatedStats {
                 propagated_validators,
                 propagated_validators_stake,
@@ -20,7 +20,8 @@ use solana_ledger::{
 use solana_measure::measure::Measure;
 use solana_runtime::{bank_forks::BankForks, contains::Contains};
 use solana_sdk::{
-    clock::Slot, epoch_schedule::EpochSchedule, hash::Hash, pubkey::Pubkey, timing::timestamp,
+    clock::Slot, epoch_schedule::EpochSchedule, feature_set, hash::Hash, pubkey::Pubkey,
+    timing::timestamp,
 };
 use solana_streamer::sendmmsg::{batch_send, SendPktsError};
 use std::{
@@ -223,10 +224,14 @@ impl RepairService {
             let mut set_root_elapsed;
             let mut get_votes_elapsed;
             let mut add_votes_elapsed;
+            let sign_repair_requests;
 
             let repairs = {
                 let root_bank = repair_info.bank_forks.read().unwrap().root_bank().clone();
                 let new_root = root_bank.slot();
+                sign_repair_requests = root_bank
+                    .feature_set
+                    .is_active(&feature_set::sign_repair_requests::id());
 
                 // Purge outdated slots from the weighting heuristic
                 set_root_elapsed = Measure::start("set_root_elapsed");
@@ -282,6 +287,7 @@ impl RepairService {
                                 &mut repair_stats,
                                 &repair_info.repair_validators,
                                 &mut outstanding_requests,
+                                sign_repair_requests,
                             )
                             .ok()?;
                         Some((req, to))
@@ -500,6 +506,7 @@ impl RepairService {
     }
 
     #[allow(dead_code)]
+    #[allow(clippy::too_many_arguments)]
     fn generate_and_send_duplicate_repairs(
         duplicate_slot_repair_statuses: &mut HashMap<Slot, DuplicateSlotRepairStatus>,
         cluster_slots: &ClusterSlots,
@@ -509,6 +516,7 @@ impl RepairService {
         repair_socket: &UdpSocket,
         repair_validators: &Option<HashSet<Pubkey>>,
         outstanding_requests: &RwLock<OutstandingShredRepairs>,
+        sign_repair_requests: bool,
     ) {
         duplicate_slot_repair_statuses.retain(|slot, status| {
             Self::update_duplicate_slot_repair_addr(
@@ -533,6 +541,7 @@ impl RepairService {
                             serve_repair,
                             repair_stats,
                             nonce,
+                            sign_repair_requests,
                         ) {
                             info!(
                                 "repair req send_to {} ({}) error {:?}",
@@ -551,6 +560,7 @@ impl RepairService {
     }
 
     #[allow(dead_code)]
+    #[allow(clippy::too_many_arguments)]
     fn serialize_and_send_request(
         repair_type: &ShredRepairType,
         repair_socket: &UdpSocket,
@@ -559,9 +569,15 @@ impl RepairService {
         serve_repair: &ServeRepair,
         repair_stats: &mut RepairStats,
         nonce: Nonce,
+        sign_request: bool,
     ) -> Result<()> {
-        let req =
-            serve_repair.map_repair_request(repair_type, repair_pubkey, repair_stats, nonce)?;
+        let req = serve_repair.map_repair_request(
+            repair_type,
+            repair_pubkey,
+            repair_stats,
+            nonce,
+            sign_request,
+        )?;
         repair_socket.send_to(&req, to)?;
         Ok(())
     }
@@ -965,6 +981,7 @@ mod test {
             &UdpSocket::bind("0.0.0.0:0").unwrap(),
             &None,
             &RwLock::new(OutstandingRequests::default()),
+            false,
         );
         assert!(duplicate_slot_repair_statuses
             .get(&dead_slot)
@@ -990,6 +1007,7 @@ mod test {
             &UdpSocket::bind("0.0.0.0:0").unwrap(),
             &None,
             &RwLock::new(OutstandingRequests::default()),
+            false,
         );
         assert_eq!(duplicate_slot_repair_statuses.len(), 1);
         assert!(duplicate_slot_repair_statuses.get(&dead_slot).is_some());
@@ -1008,6 +1026,7 @@ mod test {
             &UdpSocket::bind("0.0.0.0:0").unwrap(),
             &None,
             &RwLock::new(OutstandingRequests::default()),
+            false,
         );
         assert!(duplicate_slot_repair_statuses.is_empty());
     }
@@ -15,9 +15,11 @@ use {
             ComputedBankState, Stake, SwitchForkDecision, Tower, VotedStakes, SWITCH_FORK_THRESHOLD,
         },
         cost_update_service::CostUpdate,
+        dumped_slots::{DumpedSlotReason, DumpedSlots},
         fork_choice::{ForkChoice, SelectVoteAndResetForkResult},
         heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
         latest_validator_votes_for_frozen_banks::LatestValidatorVotesForFrozenBanks,
+        leader_slot_reports::LeaderSlotReports,
         progress_map::{ForkProgress, ProgressMap, PropagatedStats},
         repair_service::DuplicateSlotsResetReceiver,
         rewards_recorder_service::RewardsRecorderSender,
@@ -26,13 +28,17 @@ use {
         voting_service::VoteOp,
         window_service::DuplicateSlotReceiver,
     },
+    solana_accountsdb_plugin_manager::block_metadata_notifier_interface::BlockMetadataNotifier,
     solana_client::rpc_response::SlotUpdate,
     solana_entry::entry::VerifyRecyclers,
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         block_error::BlockError,
         blockstore::Blockstore,
-        blockstore_processor::{self, BlockstoreProcessorError, TransactionStatusSender},
+        blockstore_processor::{
+            self, BlockstoreProcessorError, ConfirmationProgress, ConfirmationTiming,
+            TransactionStatusSender,
+        },
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_measure::measure::Measure,
@@ -61,6 +67,7 @@ use {
     std::{
         collections::{HashMap, HashSet},
         result,
+        str::FromStr,
         sync::{
             atomic::{AtomicBool, Ordering},
             mpsc::{Receiver, RecvTimeoutError, Sender},
@@ -78,6 +85,65 @@ pub const DUPLICATE_LIVENESS_THRESHOLD: f64 = 0.1;
 pub const DUPLICATE_THRESHOLD: f64 = 1.0 - SWITCH_FORK_THRESHOLD - DUPLICATE_LIVENESS_THRESHOLD;
 const MAX_VOTE_SIGNATURES: usize = 200;
 const MAX_VOTE_REFRESH_INTERVAL_MILLIS: usize = 5000;
+// Number of slots after which a vote which hasn't landed is refreshed, even though its
+// blockhash is still valid, so that it doesn't miss credits while the cluster is congested
+const VOTE_REFRESH_SLOTS: Slot = 8;
+// How long to wait for broadcast to insert all the shreds of a block this
+// node produced before giving up on verifying it.
+const PRODUCED_BLOCK_SHREDS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What to do when a block this node produced doesn't replay to the bank hash it was produced
+/// with, which points at nondeterminism or a bug in banking stage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProducedBlockVerification {
+    /// Report the mismatch and carry on with the replayed bank, which matches what the rest of
+    /// the cluster computes for the block
+    Warn,
+    /// Abort the validator before it votes on the block
+    Abort,
+}
+
+impl FromStr for ProducedBlockVerification {
+    type Err = String;
+
+    fn from_str(string: &str) -> result::Result<Self, Self::Err> {
+        match string {
+            "warn" => Ok(ProducedBlockVerification::Warn),
+            "abort" => Ok(ProducedBlockVerification::Abort),
+            bad_policy => Err(format!(
+                "Invalid produced block verification policy: {}",
+                bad_policy
+            )),
+        }
+    }
+}
+
+/// Request to warp the validator ahead to `slot`. The slot warped to, or the reason the warp
+/// was refused, is sent back on `response_sender`.
+pub struct WarpSlotRequest {
+    pub slot: Slot,
+    pub response_sender: Sender<result::Result<Slot, String>>,
+}
+pub type WarpSlotSender = Sender<WarpSlotRequest>;
+pub type WarpSlotReceiver = Receiver<WarpSlotRequest>;
+
+/// Ask ReplayStage to warp to `slot` and wait up to `timeout` for the outcome
+pub fn request_warp_slot(
+    warp_slot_sender: &WarpSlotSender,
+    slot: Slot,
+    timeout: Duration,
+) -> result::Result<Slot, String> {
+    let (response_sender, response_receiver) = std::sync::mpsc::channel();
+    warp_slot_sender
+        .send(WarpSlotRequest {
+            slot,
+            response_sender,
+        })
+        .map_err(|_| "Validator is shutting down".to_string())?;
+    response_receiver
+        .recv_timeout(timeout)
+        .map_err(|err| format!("Warp to slot {} did not complete: {}", slot, err))?
+}
 
 #[derive(PartialEq, Debug)]
 pub enum HeaviestForkFailures {
@@ -134,6 +200,12 @@ pub struct ReplayStageConfig {
     pub ancestor_hashes_replay_update_sender: AncestorHashesReplayUpdateSender,
     pub tower_storage: Arc<dyn TowerStorage>,
     pub disable_epoch_boundary_optimization: bool,
+    pub warp_slot_receiver: WarpSlotReceiver,
+    pub verify_produced_blocks: Option<ProducedBlockVerification>,
+    pub leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+    pub disable_duplicate_slot_dumping: bool,
+    pub dumped_slots: Option<Arc<DumpedSlots>>,
+    pub block_metadata_notifier: Option<BlockMetadataNotifier>,
 }
 
 #[derive(Default)]
@@ -344,6 +416,12 @@ impl ReplayStage {
             ancestor_hashes_replay_update_sender,
             tower_storage,
             disable_epoch_boundary_optimization,
+            warp_slot_receiver,
+            verify_produced_blocks,
+            leader_slot_reports,
+            disable_duplicate_slot_dumping,
+            dumped_slots,
+            block_metadata_notifier,
         } = config;
 
         trace!("replay stage");
@@ -432,9 +510,36 @@ impl ReplayStage {
                         &cost_update_sender,
                         &mut duplicate_slots_to_repair,
                         &ancestor_hashes_replay_update_sender,
+                        verify_produced_blocks,
+                        leader_slot_reports.as_deref(),
+                        block_metadata_notifier.as_ref(),
                     );
                     replay_active_banks_time.stop();
 
+                    // Only warp between leader slots so that no transactions recorded by the TPU
+                    // are dropped with the working bank
+                    if !tpu_has_bank
+                        && Self::process_warp_slot_requests(
+                            &warp_slot_receiver,
+                            &my_pubkey,
+                            &vote_account,
+                            &blockstore,
+                            &bank_forks,
+                            &leader_schedule_cache,
+                            &poh_recorder,
+                            &accounts_background_request_sender,
+                            &rpc_subscriptions,
+                            &bank_notification_sender,
+                            &mut progress,
+                            &mut heaviest_subtree_fork_choice,
+                            &mut tower,
+                            &mut last_reset,
+                        )
+                    {
+                        // Everything computed so far this iteration refers to the pruned forks
+                        continue;
+                    }
+
                     let forks_root = bank_forks.read().unwrap().root();
 
                     // Reset any dead slots that have been frozen by a sufficient portion of
@@ -754,7 +859,7 @@ impl ReplayStage {
                     //
                     // Has to be before `maybe_start_leader()`. Otherwise, `ancestors` and `descendants`
                     // will be outdated, and we cannot assume `poh_bank` will be in either of these maps.
-                    Self::dump_then_repair_correct_slots(&mut duplicate_slots_to_repair, &mut ancestors, &mut descendants, &mut progress, &bank_forks, &blockstore, poh_bank.map(|bank| bank.slot()));
+                    Self::dump_then_repair_correct_slots(&mut duplicate_slots_to_repair, &mut ancestors, &mut descendants, &mut progress, &bank_forks, &blockstore, poh_bank.map(|bank| bank.slot()), disable_duplicate_slot_dumping, dumped_slots.as_deref());
                     dump_then_repair_correct_slots_time.stop();
 
                     // From this point on, its not safe to use ancestors/descendants since maybe_start_leader
@@ -773,6 +878,7 @@ impl ReplayStage {
                             &mut skipped_slots_info,
                             has_new_vote_been_rooted,
                             disable_epoch_boundary_optimization,
+                            leader_slot_reports.as_deref(),
                         );
 
                         let poh_bank = poh_recorder.lock().unwrap().bank();
@@ -885,6 +991,7 @@ impl ReplayStage {
         (progress, heaviest_subtree_fork_choice)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn dump_then_repair_correct_slots(
         duplicate_slots_to_repair: &mut DuplicateSlotsToRepair,
         ancestors: &mut HashMap<Slot, HashSet<Slot>>,
@@ -893,6 +1000,8 @@ impl ReplayStage {
         bank_forks: &RwLock<BankForks>,
         blockstore: &Blockstore,
         poh_bank_slot: Option<Slot>,
+        disable_duplicate_slot_dumping: bool,
+        dumped_slots: Option<&DumpedSlots>,
     ) {
         if duplicate_slots_to_repair.is_empty() {
             return;
@@ -926,23 +1035,26 @@ impl ReplayStage {
             let did_purge_repair = {
                 if !is_poh_building_on_duplicate_fork {
                     let frozen_hash = bank_forks.read().unwrap().bank_hash(*duplicate_slot);
-                    if let Some(frozen_hash) = frozen_hash {
+                    let (reason, dumped_hash) = if let Some(frozen_hash) = frozen_hash {
+                        let is_dead = progress
+                            .is_dead(*duplicate_slot)
+                            .expect("If slot exists in BankForks must exist in the progress map");
                         if frozen_hash == *correct_hash {
                             warn!(
                                 "Trying to purge slot {} with correct_hash {}",
                                 *duplicate_slot, *correct_hash
                             );
                             return false;
-                        } else if frozen_hash == Hash::default()
-                            && !progress.is_dead(*duplicate_slot).expect(
-                                "If slot exists in BankForks must exist in the progress map",
-                            )
-                        {
+                        } else if frozen_hash == Hash::default() && !is_dead {
                             warn!(
                                 "Trying to purge unfrozen slot {} that is not dead",
                                 *duplicate_slot
                             );
                             return false;
+                        } else if is_dead {
+                            (DumpedSlotReason::Dead, None)
+                        } else {
+                            (DumpedSlotReason::HashMismatch, Some(frozen_hash))
                         }
                     } else {
                         warn!(
@@ -950,6 +1062,24 @@ impl ReplayStage {
                             *duplicate_slot
                         );
                         return false;
+                    };
+
+                    if let Some(dumped_slots) = dumped_slots {
+                        dumped_slots.record(
+                            *duplicate_slot,
+                            reason,
+                            dumped_hash,
+                            *correct_hash,
+                            !disable_duplicate_slot_dumping,
+                        );
+                    }
+                    if disable_duplicate_slot_dumping {
+                        warn!(
+                            "Not dumping slot {} ({:?}), the cluster confirmed version {} of it",
+                            *duplicate_slot, reason, *correct_hash
+                        );
+                        // Leave the slot marked invalid in fork choice for the operator to recover
+                        return false;
                     }
 
                     Self::purge_unconfirmed_duplicate_slot(
@@ -1357,6 +1487,7 @@ impl ReplayStage {
         skipped_slots_info: &mut SkippedSlotsInfo,
         has_new_vote_been_rooted: bool,
         disable_epoch_boundary_optimization: bool,
+        leader_slot_reports: Option<&LeaderSlotReports>,
     ) {
         // all the individual calls to poh_recorder.lock() are designed to
         // increase granularity, decrease contention
@@ -1481,6 +1612,9 @@ impl ReplayStage {
             );
 
             let tpu_bank = bank_forks.write().unwrap().insert(tpu_bank);
+            if let Some(leader_slot_reports) = leader_slot_reports {
+                leader_slot_reports.start_slot(poh_slot);
+            }
             poh_recorder.lock().unwrap().set_bank(&tpu_bank);
         } else {
             error!("{} No next leader found", my_pubkey);
@@ -1520,6 +1654,93 @@ impl ReplayStage {
         Ok(tx_count)
     }
 
+    /// Replays a block this node produced into a new bank, the same way the rest of the cluster
+    /// does, and swaps it in for the frozen bank built by banking stage before this node votes on
+    /// it. Returns the replayed bank, along with the error if the block failed to replay.
+    fn replay_produced_bank(
+        produced_bank: &Arc<Bank>,
+        blockstore: &Blockstore,
+        bank_forks: &RwLock<BankForks>,
+        verify_recyclers: &VerifyRecyclers,
+        policy: ProducedBlockVerification,
+    ) -> result::Result<Arc<Bank>, (Arc<Bank>, BlockstoreProcessorError)> {
+        let slot = produced_bank.slot();
+        if !blockstore.is_full(slot) {
+            warn!(
+                "unable to verify produced block {}: its shreds were not inserted in time",
+                slot
+            );
+            datapoint_warn!(
+                "replay_stage-produced_block_unverified",
+                ("slot", slot, i64)
+            );
+            return Ok(produced_bank.clone());
+        }
+
+        let mut replay_time = Measure::start("replay_produced_bank");
+        let parent = produced_bank
+            .parent()
+            .expect("produced bank must have a parent");
+        // Clear the produced bank's accounts and signatures, so that the
+        // replayed bank starts from the parent's state.
+        let removed_bank = bank_forks
+            .write()
+            .unwrap()
+            .remove(slot)
+            .expect("produced bank must exist in bank forks");
+        removed_bank.remove_unrooted_slots(&[(slot, removed_bank.bank_id())]);
+        removed_bank.clear_slot_signatures(slot);
+        drop(removed_bank);
+        let replayed_bank = bank_forks.write().unwrap().insert(Bank::new_from_parent(
+            &parent,
+            produced_bank.collector_id(),
+            slot,
+        ));
+        blockstore_processor::confirm_slot(
+            blockstore,
+            &replayed_bank,
+            &mut ConfirmationTiming::default(),
+            &mut ConfirmationProgress::new(parent.last_blockhash()),
+            false,
+            None,
+            None,
+            None,
+            verify_recyclers,
+            false,
+        )
+        .map_err(|err| (replayed_bank.clone(), err))?;
+        replayed_bank.freeze();
+        replay_time.stop();
+
+        if replayed_bank.hash() == produced_bank.hash() {
+            datapoint_info!(
+                "replay_stage-produced_block_verified",
+                ("slot", slot, i64),
+                ("replay_us", replay_time.as_us(), i64),
+            );
+        } else {
+            error!(
+                "produced block {} replayed to bank hash {} instead of {}",
+                slot,
+                replayed_bank.hash(),
+                produced_bank.hash()
+            );
+            datapoint_error!(
+                "replay_stage-produced_block_mismatch",
+                ("slot", slot, i64),
+                ("produced_hash", produced_bank.hash().to_string(), String),
+                ("replayed_hash", replayed_bank.hash().to_string(), String),
+            );
+            if policy == ProducedBlockVerification::Abort {
+                panic!(
+                    "Produced block {} does not replay to the same bank hash, aborting",
+                    slot
+                );
+            }
+        }
+        Ok(replayed_bank)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn mark_dead_slot(
         blockstore: &Blockstore,
@@ -1818,9 +2039,11 @@ impl ReplayStage {
             return;
         }
 
-        // Refresh the vote if our latest vote hasn't landed, and the recent blockhash of the
-        // last attempt at a vote transaction has expired
+        // Refresh the vote if our latest vote hasn't landed, and either the recent blockhash of
+        // the last attempt at a vote transaction has expired or the vote is stale
         let last_voted_slot = last_voted_slot.unwrap();
+        let is_vote_stale =
+            heaviest_bank_on_same_fork.slot() >= last_voted_slot.saturating_add(VOTE_REFRESH_SLOTS);
         if my_latest_landed_vote > last_voted_slot
             && last_vote_refresh_time.last_print_time.elapsed().as_secs() >= 1
         {
@@ -1833,9 +2056,10 @@ impl ReplayStage {
             );
         }
         if my_latest_landed_vote >= last_voted_slot
-            || heaviest_bank_on_same_fork
-                .check_hash_age(&tower.last_vote_tx_blockhash(), MAX_PROCESSING_AGE)
-                .unwrap_or(false)
+            || (!is_vote_stale
+                && heaviest_bank_on_same_fork
+                    .check_hash_age(&tower.last_vote_tx_blockhash(), MAX_PROCESSING_AGE)
+                    .unwrap_or(false))
             // In order to avoid voting on multiple forks all past MAX_PROCESSING_AGE that don't
             // include the last voted blockhash
             || last_vote_refresh_time.last_refresh_time.elapsed().as_millis() < MAX_VOTE_REFRESH_INTERVAL_MILLIS as u128
@@ -1867,6 +2091,7 @@ impl ReplayStage {
                 ("last_voted_slot", last_voted_slot, i64),
                 ("target_bank_slot", heaviest_bank_on_same_fork.slot(), i64),
                 ("target_bank_hash", hash_string, String),
+                ("is_vote_stale", is_vote_stale, bool),
             );
             voting_sender
                 .send(VoteOp::RefreshVote {
@@ -1990,6 +2215,9 @@ impl ReplayStage {
         cost_update_sender: &Sender<CostUpdate>,
         duplicate_slots_to_repair: &mut DuplicateSlotsToRepair,
         ancestor_hashes_replay_update_sender: &AncestorHashesReplayUpdateSender,
+        verify_produced_blocks: Option<ProducedBlockVerification>,
+        leader_slot_reports: Option<&LeaderSlotReports>,
+        block_metadata_notifier: Option<&BlockMetadataNotifier>,
     ) -> bool {
         let mut did_complete_bank = false;
         let mut tx_count = 0;
@@ -2066,6 +2294,20 @@ impl ReplayStage {
                 }
             }
             assert_eq!(*bank_slot, bank.slot());
+            if bank.is_complete()
+                && verify_produced_blocks.is_some()
+                && bank.collector_id() == my_pubkey
+                && !blockstore.is_full(bank.slot())
+            {
+                // Leave the bank unfrozen and check back on the next pass, rather than stalling
+                // replay of the other forks until broadcast has inserted all the shreds
+                let wait_start = bank_progress
+                    .produced_block_wait_start
+                    .get_or_insert_with(Instant::now);
+                if wait_start.elapsed() < PRODUCED_BLOCK_SHREDS_TIMEOUT {
+                    continue;
+                }
+            }
             if bank.is_complete() {
                 execute_timings.accumulate(&bank_progress.replay_stats.execute_timings);
                 debug!("bank {} is completed replay from blockstore, contribute to update cost with {:?}",
@@ -2085,6 +2327,43 @@ impl ReplayStage {
                     transaction_status_sender.send_transaction_status_freeze_message(&bank);
                 }
                 bank.freeze();
+                if let Some(leader_slot_reports) = leader_slot_reports {
+                    if bank.collector_id() == my_pubkey {
+                        leader_slot_reports.complete_slot(&bank);
+                    }
+                }
+                let bank = match verify_produced_blocks {
+                    Some(policy) if bank.collector_id() == my_pubkey => {
+                        match Self::replay_produced_bank(
+                            &bank,
+                            blockstore,
+                            bank_forks,
+                            verify_recyclers,
+                            policy,
+                        ) {
+                            Ok(bank) => bank,
+                            Err((replayed_bank, err)) => {
+                                let root_slot = bank_forks.read().unwrap().root();
+                                Self::mark_dead_slot(
+                                    blockstore,
+                                    &replayed_bank,
+                                    root_slot,
+                                    &err,
+                                    rpc_subscriptions,
+                                    duplicate_slots_tracker,
+                                    gossip_duplicate_confirmed_slots,
+                                    epoch_slots_frozen_slots,
+                                    progress,
+                                    heaviest_subtree_fork_choice,
+                                    duplicate_slots_to_repair,
+                                    ancestor_hashes_replay_update_sender,
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                    _ => bank,
+                };
                 // report cost tracker stats
                 cost_update_sender
                     .send(CostUpdate::FrozenBank { bank: bank.clone() })
@@ -2145,6 +2424,7 @@ impl ReplayStage {
                     }
                 }
                 Self::record_rewards(&bank, rewards_recorder_sender);
+                Self::notify_block_metadata(&bank, block_metadata_notifier);
             } else {
                 trace!(
                     "bank {} not completed tick_height: {}, max_tick_height: {}",
@@ -2709,6 +2989,130 @@ impl ReplayStage {
         confirmed_forks
     }
 
+    /// Service any pending warp requests. Returns true if the validator warped, in which case the
+    /// root, progress map, fork choice and tower have all been replaced.
+    #[allow(clippy::too_many_arguments)]
+    fn process_warp_slot_requests(
+        warp_slot_receiver: &WarpSlotReceiver,
+        my_pubkey: &Pubkey,
+        vote_account: &Pubkey,
+        blockstore: &Blockstore,
+        bank_forks: &RwLock<BankForks>,
+        leader_schedule_cache: &LeaderScheduleCache,
+        poh_recorder: &Mutex<PohRecorder>,
+        accounts_background_request_sender: &AbsRequestSender,
+        rpc_subscriptions: &RpcSubscriptions,
+        bank_notification_sender: &Option<BankNotificationSender>,
+        progress: &mut ProgressMap,
+        heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
+        tower: &mut Tower,
+        last_reset: &mut Hash,
+    ) -> bool {
+        let mut warped = false;
+        for WarpSlotRequest {
+            slot,
+            response_sender,
+        } in warp_slot_receiver.try_iter()
+        {
+            let result = Self::warp_to_slot(
+                slot,
+                blockstore,
+                bank_forks,
+                leader_schedule_cache,
+                accounts_background_request_sender,
+                rpc_subscriptions,
+                bank_notification_sender,
+            );
+            if result.is_ok() {
+                warped = true;
+            }
+            let _ = response_sender.send(result);
+        }
+        if !warped {
+            return false;
+        }
+
+        // The warped bank doesn't descend from anything the fork choice or tower know about, so
+        // rebuild them the same way as at startup
+        let (new_progress, new_heaviest_subtree_fork_choice) =
+            Self::initialize_progress_and_fork_choice_with_locked_bank_forks(
+                bank_forks,
+                my_pubkey,
+                vote_account,
+            );
+        *progress = new_progress;
+        *heaviest_subtree_fork_choice = new_heaviest_subtree_fork_choice;
+        *tower = Tower::new_from_bankforks(&bank_forks.read().unwrap(), my_pubkey, vote_account);
+
+        let root_bank = bank_forks.read().unwrap().root_bank();
+        Self::reset_poh_recorder(
+            my_pubkey,
+            blockstore,
+            &root_bank,
+            poh_recorder,
+            leader_schedule_cache,
+        );
+        *last_reset = root_bank.last_blockhash();
+        true
+    }
+
+    /// Root a bank at `warp_slot` on top of the highest frozen bank, as if every slot in between
+    /// had been skipped. Epoch boundaries, stake activation and rent collection are all caught up
+    /// by `Bank::warp_from_parent`.
+    fn warp_to_slot(
+        warp_slot: Slot,
+        blockstore: &Blockstore,
+        bank_forks: &RwLock<BankForks>,
+        leader_schedule_cache: &LeaderScheduleCache,
+        accounts_background_request_sender: &AbsRequestSender,
+        rpc_subscriptions: &RpcSubscriptions,
+        bank_notification_sender: &Option<BankNotificationSender>,
+    ) -> result::Result<Slot, String> {
+        let parent = {
+            let r_bank_forks = bank_forks.read().unwrap();
+            let highest_slot = r_bank_forks.highest_slot();
+            if warp_slot <= highest_slot {
+                return Err(format!(
+                    "warp slot ({}) must be greater than the highest bank slot ({})",
+                    warp_slot, highest_slot
+                ));
+            }
+            r_bank_forks
+                .frozen_banks()
+                .into_iter()
+                .max_by_key(|(slot, _)| *slot)
+                .map(|(_, bank)| bank)
+                .expect("root bank is always frozen")
+        };
+        info!("warping from slot {} to slot {}", parent.slot(), warp_slot);
+
+        let warp_bank = bank_forks.write().unwrap().insert(Bank::warp_from_parent(
+            &parent,
+            &Pubkey::default(),
+            warp_slot,
+        ));
+        let mut rooted_banks = warp_bank.parents();
+        rooted_banks.push(warp_bank.clone());
+        let rooted_slots: Vec<_> = rooted_banks.iter().map(|bank| bank.slot()).collect();
+        leader_schedule_cache.set_root(&warp_bank);
+        blockstore
+            .set_roots(rooted_slots.iter())
+            .map_err(|err| format!("Ledger set roots failed: {}", err))?;
+        bank_forks.write().unwrap().set_root(
+            warp_slot,
+            accounts_background_request_sender,
+            Some(warp_slot),
+        );
+        rpc_subscriptions.notify_roots(rooted_slots);
+        if let Some(sender) = bank_notification_sender {
+            sender
+                .send(BankNotification::Root(warp_bank))
+                .unwrap_or_else(|err| warn!("bank_notification_sender failed: {:?}", err));
+        }
+        datapoint_info!("replay_stage-warp_slot", ("slot", warp_slot, i64));
+        Ok(warp_slot)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn handle_new_root(
         new_root: Slot,
@@ -2854,6 +3258,21 @@ impl ReplayStage {
         }
     }
 
+    fn notify_block_metadata(bank: &Bank, block_metadata_notifier: Option<&BlockMetadataNotifier>) {
+        if let Some(block_metadata_notifier) = block_metadata_notifier {
+            let block_metadata_notifier = block_metadata_notifier.read().unwrap();
+            block_metadata_notifier.notify_block_metadata(
+                bank.slot(),
+                bank.parent_slot(),
+                &bank.last_blockhash(),
+                bank.collector_id(),
+                Some(bank.clock().unix_timestamp),
+                Some(bank.block_height()),
+            );
+            block_metadata_notifier.notify_rewards(bank.slot(), &bank.rewards.read().unwrap());
+        }
+    }
+
     pub fn get_unlock_switch_vote_slot(cluster_type: ClusterType) -> Slot {
         match cluster_type {
             ClusterType::Development => 0,
@@ -2876,6 +3295,7 @@ pub mod tests {
     use super::*;
     use crate::{
         consensus::Tower,
+        dumped_slots::DEFAULT_DUMPED_SLOTS_CAPACITY,
         progress_map::ValidatorStakeInfo,
         replay_stage::ReplayStage,
         tree_diff::TreeDiff,
@@ -2925,6 +3345,7 @@ pub mod tests {
     use std::{
         fs::remove_dir_all,
         iter,
+        net::UdpSocket,
         sync::{atomic::AtomicU64, Arc, RwLock},
     };
     use trees::{tr, Tree};
@@ -3515,6 +3936,87 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_replay_produced_bank() {
+        let ReplayBlockstoreComponents {
+            blockstore,
+            vote_simulator,
+            ..
+        } = replay_blockstore_components(Some(tr(0)), 1, None);
+        let bank_forks = vote_simulator.bank_forks;
+        let bank0 = bank_forks.read().unwrap().get(0).cloned().unwrap();
+        let my_pubkey = Pubkey::new_unique();
+        let ticks = entry::create_ticks(
+            bank0.ticks_per_slot(),
+            bank0.hashes_per_tick().unwrap_or(0),
+            bank0.last_blockhash(),
+        );
+        // Produce a block of ticks, with banking stage optionally diverging
+        // from what the block replays to.
+        let produce_bank = |slot, diverge| {
+            let bank = bank_forks
+                .write()
+                .unwrap()
+                .insert(Bank::new_from_parent(&bank0, &my_pubkey, slot));
+            if diverge {
+                bank.deposit(&my_pubkey, 1).unwrap();
+            }
+            for tick in &ticks {
+                bank.register_tick(&tick.hash);
+            }
+            bank.freeze();
+            let shreds = entries_to_test_shreds(ticks.clone(), slot, 0, true, 0);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+            bank
+        };
+
+        let produced_bank = produce_bank(1, false);
+        let replayed_bank = ReplayStage::replay_produced_bank(
+            &produced_bank,
+            &blockstore,
+            &bank_forks,
+            &VerifyRecyclers::default(),
+            ProducedBlockVerification::Abort,
+        )
+        .unwrap();
+        assert!(!Arc::ptr_eq(&produced_bank, &replayed_bank));
+        assert_eq!(replayed_bank.hash(), produced_bank.hash());
+        assert!(Arc::ptr_eq(
+            bank_forks.read().unwrap().get(1).unwrap(),
+            &replayed_bank
+        ));
+
+        let produced_bank = produce_bank(2, true);
+        let replayed_bank = ReplayStage::replay_produced_bank(
+            &produced_bank,
+            &blockstore,
+            &bank_forks,
+            &VerifyRecyclers::default(),
+            ProducedBlockVerification::Warn,
+        )
+        .unwrap();
+        assert!(replayed_bank.is_frozen());
+        assert_ne!(replayed_bank.hash(), produced_bank.hash());
+        assert_eq!(replayed_bank.get_balance(&my_pubkey), 0);
+        assert!(Arc::ptr_eq(
+            bank_forks.read().unwrap().get(2).unwrap(),
+            &replayed_bank
+        ));
+    }
+
+    #[test]
+    fn test_produced_block_verification_from_str() {
+        assert_eq!(
+            "warn".parse::<ProducedBlockVerification>(),
+            Ok(ProducedBlockVerification::Warn)
+        );
+        assert_eq!(
+            "abort".parse::<ProducedBlockVerification>(),
+            Ok(ProducedBlockVerification::Abort)
+        );
+        assert!("ignore".parse::<ProducedBlockVerification>().is_err());
+    }
+
     // Given a shred and a fatal expected error, check that replaying that shred causes causes the fork to be
     // marked as dead. Returns the error for caller to verify.
     fn check_dead_fork<F>(shred_to_insert: F) -> result::Result<(), BlockstoreProcessorError>
@@ -5146,6 +5648,25 @@ pub mod tests {
         let mut duplicate_slots_to_repair = DuplicateSlotsToRepair::default();
         duplicate_slots_to_repair.insert(1, Hash::new_unique());
         duplicate_slots_to_repair.insert(2, Hash::new_unique());
+        let dumped_slots = DumpedSlots::new(DEFAULT_DUMPED_SLOTS_CAPACITY);
+
+        // Nothing is purged while dumping is disabled, the slots are only recorded
+        ReplayStage::dump_then_repair_correct_slots(
+            &mut duplicate_slots_to_repair.clone(),
+            &mut ancestors,
+            &mut descendants,
+            progress,
+            bank_forks,
+            blockstore,
+            None,
+            true,
+            Some(&dumped_slots),
+        );
+        assert!(bank_forks.read().unwrap().get(2).is_some());
+        let recent = dumped_slots.recent();
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().all(|dumped_slot| !dumped_slot.purged
+            && dumped_slot.reason == DumpedSlotReason::HashMismatch));
 
         ReplayStage::dump_then_repair_correct_slots(
             &mut duplicate_slots_to_repair,
@@ -5155,7 +5676,12 @@ pub mod tests {
             bank_forks,
             blockstore,
             None,
+            false,
+            Some(&dumped_slots),
         );
+        assert!(dumped_slots.recent()[2..]
+            .iter()
+            .all(|dumped_slot| dumped_slot.purged));
 
         let r_bank_forks = bank_forks.read().unwrap();
         for slot in 0..=2 {
@@ -5266,6 +5792,8 @@ pub mod tests {
             bank_forks,
             blockstore,
             None,
+            false,
+            None,
         );
 
         // Check everything was purged properly
@@ -5581,6 +6109,7 @@ pub mod tests {
             ..
         } = replay_blockstore_components(None, 10, None::<GenerateVotes>);
         let tower_storage = crate::tower_storage::NullTowerStorage::default();
+        let vote_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
 
         let VoteSimulator {
             mut validator_keypairs,
@@ -5624,11 +6153,12 @@ pub mod tests {
         let vote_info = voting_receiver
             .recv_timeout(Duration::from_secs(1))
             .unwrap();
-        crate::voting_service::VotingService::handle_vote(
+        crate::voting_service::VotingService::handle_votes(
             &cluster_info,
             &poh_recorder,
             &tower_storage,
-            vote_info,
+            &vote_socket,
+            vec![vote_info],
             false,
         );
 
@@ -5688,11 +6218,12 @@ pub mod tests {
         let vote_info = voting_receiver
             .recv_timeout(Duration::from_secs(1))
             .unwrap();
-        crate::voting_service::VotingService::handle_vote(
+        crate::voting_service::VotingService::handle_votes(
             &cluster_info,
             &poh_recorder,
             &tower_storage,
-            vote_info,
+            &vote_socket,
+            vec![vote_info],
             false,
         );
         let (_, votes) = cluster_info.get_votes(&mut cursor);
@@ -5756,11 +6287,12 @@ pub mod tests {
         let vote_info = voting_receiver
             .recv_timeout(Duration::from_secs(1))
             .unwrap();
-        crate::voting_service::VotingService::handle_vote(
+        crate::voting_service::VotingService::handle_votes(
             &cluster_info,
             &poh_recorder,
             &tower_storage,
-            vote_info,
+            &vote_socket,
+            vec![vote_info],
             false,
         );
 
@@ -5834,6 +6366,98 @@ pub mod tests {
         assert_eq!(tower.last_voted_slot().unwrap(), 1);
     }
 
+    #[test]
+    fn test_replay_stage_refresh_stale_vote() {
+        let ReplayBlockstoreComponents {
+            cluster_info,
+            mut tower,
+            my_pubkey,
+            vote_simulator,
+            ..
+        } = replay_blockstore_components(None, 10, None::<GenerateVotes>);
+        let VoteSimulator {
+            mut validator_keypairs,
+            bank_forks,
+            ..
+        } = vote_simulator;
+        let mut last_vote_refresh_time = LastVoteRefreshTime {
+            last_refresh_time: Instant::now()
+                .checked_sub(Duration::from_millis(
+                    MAX_VOTE_REFRESH_INTERVAL_MILLIS as u64 + 1,
+                ))
+                .unwrap(),
+            last_print_time: Instant::now(),
+        };
+        let mut voted_signatures = vec![];
+        let identity_keypair = cluster_info.keypair().clone();
+        let my_vote_keypair = vec![Arc::new(
+            validator_keypairs.remove(&my_pubkey).unwrap().vote_keypair,
+        )];
+        let my_vote_pubkey = my_vote_keypair[0].pubkey();
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let (voting_sender, voting_receiver) = channel();
+
+        // The vote for slot 0 lands in slot 1, the vote for slot 1 never lands
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1.fill_bank_with_ticks();
+        for (bank, lands) in [(&bank0, true), (&bank1, false)] {
+            tower.record_bank_vote(bank, &my_vote_pubkey);
+            ReplayStage::push_vote(
+                bank,
+                &my_vote_pubkey,
+                &identity_keypair,
+                &my_vote_keypair,
+                &mut tower,
+                SavedTower::default(),
+                &SwitchForkDecision::SameFork,
+                &mut voted_signatures,
+                false,
+                &mut ReplayTiming::default(),
+                &voting_sender,
+            );
+            match voting_receiver.try_recv() {
+                Ok(VoteOp::PushVote { tx, .. }) if lands => {
+                    bank1.process_transaction(&tx).unwrap();
+                    bank1.freeze();
+                }
+                Ok(VoteOp::PushVote { .. }) => (),
+                _ => panic!("expected a vote"),
+            }
+        }
+
+        // The blockhash of the vote for slot 1 is still valid in both banks, but the vote is
+        // only stale in the second one
+        for (slot, is_vote_stale) in [(VOTE_REFRESH_SLOTS, false), (VOTE_REFRESH_SLOTS + 1, true)] {
+            let bank = Bank::new_from_parent(&bank1, &Pubkey::default(), slot);
+            bank.fill_bank_with_ticks();
+            bank.freeze();
+            ReplayStage::refresh_last_vote(
+                &mut tower,
+                &bank,
+                Tower::last_voted_slot_in_bank(&bank, &my_vote_pubkey).unwrap(),
+                &my_vote_pubkey,
+                &identity_keypair,
+                &my_vote_keypair,
+                &mut voted_signatures,
+                false,
+                &mut last_vote_refresh_time,
+                &voting_sender,
+            );
+            match voting_receiver.try_recv() {
+                Ok(VoteOp::RefreshVote {
+                    tx,
+                    last_voted_slot,
+                }) => {
+                    assert!(is_vote_stale);
+                    assert_eq!(last_voted_slot, 1);
+                    assert_eq!(tx.message.recent_blockhash, bank.last_blockhash());
+                }
+                Ok(_) => panic!("unexpected vote op"),
+                Err(_) => assert!(!is_vote_stale),
+            }
+        }
+    }
+
     fn run_compute_and_select_forks(
         bank_forks: &RwLock<BankForks>,
         progress: &mut ProgressMap,
@@ -5931,6 +6555,85 @@ pub mod tests {
         setup_forks_from_tree(tree, num_keys, generate_votes)
     }
 
+    #[test]
+    fn test_process_warp_slot_requests() {
+        let forks = tr(0) / (tr(1) / (tr(2)));
+        let ReplayBlockstoreComponents {
+            blockstore,
+            validator_node_to_vote_keys,
+            my_pubkey,
+            leader_schedule_cache,
+            poh_recorder,
+            mut tower,
+            rpc_subscriptions,
+            mut vote_simulator,
+            ..
+        } = replay_blockstore_components(Some(forks), 1, None);
+        let VoteSimulator {
+            ref bank_forks,
+            ref mut progress,
+            ref mut heaviest_subtree_fork_choice,
+            ..
+        } = vote_simulator;
+        let vote_account = validator_node_to_vote_keys[&my_pubkey];
+        let warp_slot = bank_forks
+            .read()
+            .unwrap()
+            .root_bank()
+            .epoch_schedule()
+            .get_first_slot_in_epoch(2);
+
+        let (warp_slot_sender, warp_slot_receiver) = std::sync::mpsc::channel();
+        let mut last_reset = Hash::default();
+        let mut request_warp = |slot| {
+            let (response_sender, response_receiver) = std::sync::mpsc::channel();
+            warp_slot_sender
+                .send(WarpSlotRequest {
+                    slot,
+                    response_sender,
+                })
+                .unwrap();
+            let warped = ReplayStage::process_warp_slot_requests(
+                &warp_slot_receiver,
+                &my_pubkey,
+                &vote_account,
+                &blockstore,
+                bank_forks,
+                &leader_schedule_cache,
+                &poh_recorder,
+                &AbsRequestSender::default(),
+                &rpc_subscriptions,
+                &None,
+                progress,
+                heaviest_subtree_fork_choice,
+                &mut tower,
+                &mut last_reset,
+            );
+            (warped, response_receiver.recv().unwrap())
+        };
+
+        // Can't warp to a slot that already has a bank
+        let (warped, response) = request_warp(2);
+        assert!(!warped);
+        assert!(response.is_err());
+
+        assert_eq!(request_warp(warp_slot), (true, Ok(warp_slot)));
+        let root_bank = bank_forks.read().unwrap().root_bank();
+        assert_eq!(root_bank.slot(), warp_slot);
+        assert_eq!(root_bank.epoch(), 2);
+        assert_eq!(root_bank.parent_slot(), 2);
+        assert!(blockstore.is_root(2));
+        assert_eq!(last_reset, root_bank.last_blockhash());
+        assert_eq!(poh_recorder.lock().unwrap().start_slot(), warp_slot);
+        assert_eq!(tower.root(), warp_slot);
+        assert!(progress.get(&warp_slot).is_some());
+        assert!(progress.get(&2).is_none());
+        assert_eq!(
+            heaviest_subtree_fork_choice.best_overall_slot(),
+            (warp_slot, root_bank.hash())
+        );
+    }
+
     fn check_map_eq<K: Eq + std::hash::Hash + std::fmt::Debug, T: PartialEq + std::fmt::Debug>(
         map1: &HashMap<K, T>,
         map2: &HashMap<K, T>,
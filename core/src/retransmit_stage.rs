@@ -15,7 +15,7 @@ use {
     },
     crossbeam_channel::{Receiver, Sender},
     lru::LruCache,
-    rayon::{prelude::*, ThreadPool, ThreadPoolBuilder},
+    rayon::{prelude::*, ThreadPoolBuilder},
     solana_client::rpc_response::SlotUpdate,
     solana_gossip::{
         cluster_info::{ClusterInfo, DATA_PLANE_FANOUT},
@@ -27,7 +27,10 @@ use {
     },
     solana_measure::measure::Measure,
     solana_perf::packet::Packets,
-    solana_rayon_threadlimit::get_thread_count,
+    solana_rayon_threadlimit::{
+        get_thread_count,
+        thread_pools::{ManagedThreadPool, RETRANSMIT_THREAD_POOL},
+    },
     solana_rpc::{max_slots::MaxSlots, rpc_subscriptions::RpcSubscriptions},
     solana_runtime::{bank::Bank, bank_forks::BankForks},
     solana_sdk::{clock::Slot, epoch_schedule::EpochSchedule, pubkey::Pubkey, timing::timestamp},
@@ -211,7 +214,7 @@ fn maybe_reset_shreds_received_cache(
 
 #[allow(clippy::too_many_arguments)]
 fn retransmit(
-    thread_pool: &ThreadPool,
+    thread_pool: &ManagedThreadPool,
     bank_forks: &RwLock<BankForks>,
     leader_schedule_cache: &LeaderScheduleCache,
     cluster_info: &ClusterInfo,
@@ -332,7 +335,7 @@ fn retransmit(
             .into_par_iter()
             .with_min_len(4)
             .map(|shred| {
-                let index = thread_pool.current_thread_index().unwrap();
+                let index = rayon::current_thread_index().unwrap();
                 let socket = &sockets[index % sockets.len()];
                 let num_nodes = retransmit_shred(&shred, socket);
                 (shred.slot(), num_nodes)
@@ -381,11 +384,13 @@ pub fn retransmitter(
     let shreds_received = Mutex::new((LruCache::new(DEFAULT_LRU_SIZE), PacketHasher::default()));
     let first_shreds_received = Mutex::<BTreeSet<Slot>>::default();
     let num_threads = get_thread_count().min(8).max(sockets.len());
-    let thread_pool = ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .thread_name(|i| format!("retransmit-{}", i))
-        .build()
-        .unwrap();
+    let thread_pool = ManagedThreadPool::new(RETRANSMIT_THREAD_POOL, num_threads, |num_threads| {
+        ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("retransmit-{}", i))
+            .build()
+            .unwrap()
+    });
     Builder::new()
         .name("solana-retransmitter".to_string())
         .spawn(move || {
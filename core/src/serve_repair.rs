@@ -25,13 +25,20 @@ use solana_ledger::{
 use solana_measure::measure::Measure;
 use solana_metrics::inc_new_counter_debug;
 use solana_perf::packet::{limited_deserialize, Packets, PacketsRecycler};
+use solana_runtime::{bank::Bank, bank_forks::BankForks};
 use solana_sdk::{
-    clock::Slot, hash::Hash, packet::PACKET_DATA_SIZE, pubkey::Pubkey, timing::duration_as_ms,
+    clock::Slot,
+    feature_set,
+    hash::Hash,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Signature, Signer, SIGNATURE_BYTES},
+    timing::{duration_as_ms, timestamp},
 };
 use solana_streamer::streamer::{PacketReceiver, PacketSender};
 use std::{
     collections::HashSet,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::atomic::{AtomicBool, Ordering},
     sync::{Arc, RwLock},
     thread::{Builder, JoinHandle},
@@ -55,6 +62,15 @@ pub const MAX_ANCESTOR_RESPONSES: usize =
     MAX_ANCESTOR_BYTES_IN_PACKET / std::mem::size_of::<SlotHash>();
 #[cfg(test)]
 static_assertions::const_assert_eq!(MAX_ANCESTOR_RESPONSES, 30);
+// The signed bytes of a request follow its enum discriminator and the signature of its header
+const SIGNED_REPAIR_DATA_OFFSET: usize = 4 + SIGNATURE_BYTES;
+// How far the timestamp of a signed request may be from the local clock, in milliseconds
+const SIGNED_REPAIR_TIME_WINDOW_MS: u64 = 10 * 60 * 1000;
+/// the number of requests served per second to a requester, identified by the signer of its
+/// requests, or by its IP address for the unsigned requests
+pub const MAX_REPAIR_REQUESTS_PER_SECOND: u64 = 2048;
+// Number of requesters whose request rates are tracked, of each kind.
+const REPAIR_REQUESTERS_CACHE_CAPACITY: usize = 8192;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum ShredRepairType {
@@ -146,6 +162,35 @@ pub struct ServeRepairStats {
     pub highest_window_index: usize,
     pub orphan: usize,
     pub ancestor_hashes: usize,
+    pub signed_requests: usize,
+    pub unsigned_requests: usize,
+    pub err_unsigned: usize,
+    pub err_sig_verify: usize,
+    pub err_id_mismatch: usize,
+    pub err_time_skew: usize,
+    pub rate_limited: usize,
+}
+
+/// Identifies the requester and the recipient of a signed repair request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepairRequestHeader {
+    signature: Signature,
+    sender: Pubkey,
+    recipient: Pubkey,
+    timestamp: u64,
+    nonce: Nonce,
+}
+
+impl RepairRequestHeader {
+    pub fn new(sender: Pubkey, recipient: Pubkey, timestamp: u64, nonce: Nonce) -> Self {
+        Self {
+            signature: Signature::default(),
+            sender,
+            recipient,
+            timestamp,
+            nonce,
+        }
+    }
 }
 
 /// Window protocol messages
@@ -158,6 +203,99 @@ pub enum RepairProtocol {
     HighestWindowIndexWithNonce(ContactInfo, Slot, u64, Nonce),
     OrphanWithNonce(ContactInfo, Slot, Nonce),
     AncestorHashes(ContactInfo, Slot, Nonce),
+    // The header of the signed requests must stay their first field, see
+    // SIGNED_REPAIR_DATA_OFFSET
+    SignedWindowIndex(RepairRequestHeader, Slot, u64),
+    SignedHighestWindowIndex(RepairRequestHeader, Slot, u64),
+    SignedOrphan(RepairRequestHeader, Slot),
+    SignedAncestorHashes(RepairRequestHeader, Slot),
+}
+
+impl RepairProtocol {
+    fn header(&self) -> Option<&RepairRequestHeader> {
+        match self {
+            RepairProtocol::SignedWindowIndex(header, _, _)
+            | RepairProtocol::SignedHighestWindowIndex(header, _, _)
+            | RepairProtocol::SignedOrphan(header, _)
+            | RepairProtocol::SignedAncestorHashes(header, _) => Some(header),
+            _ => None,
+        }
+    }
+
+    fn sender(&self) -> &Pubkey {
+        match self {
+            RepairProtocol::WindowIndex(from, _, _) => &from.id,
+            RepairProtocol::HighestWindowIndex(from, _, _) => &from.id,
+            RepairProtocol::Orphan(from, _) => &from.id,
+            RepairProtocol::WindowIndexWithNonce(from, _, _, _) => &from.id,
+            RepairProtocol::HighestWindowIndexWithNonce(from, _, _, _) => &from.id,
+            RepairProtocol::OrphanWithNonce(from, _, _) => &from.id,
+            RepairProtocol::AncestorHashes(from, _, _) => &from.id,
+            RepairProtocol::SignedWindowIndex(header, _, _) => &header.sender,
+            RepairProtocol::SignedHighestWindowIndex(header, _, _) => &header.sender,
+            RepairProtocol::SignedOrphan(header, _) => &header.sender,
+            RepairProtocol::SignedAncestorHashes(header, _) => &header.sender,
+        }
+    }
+}
+
+/// Limits the rate of the requests served to each requester
+struct RepairRequestsLimiter {
+    // Signers of the signed requests
+    identities: LruCache<Pubkey, (/*window start:*/ Instant, /*requests:*/ u64)>,
+    // Source addresses of the unsigned requests
+    addresses: LruCache<IpAddr, (/*window start:*/ Instant, /*requests:*/ u64)>,
+    max_requests_per_second: u64,
+}
+
+impl RepairRequestsLimiter {
+    fn new(capacity: usize, max_requests_per_second: u64) -> Self {
+        Self {
+            identities: LruCache::new(capacity),
+            addresses: LruCache::new(capacity),
+            max_requests_per_second,
+        }
+    }
+
+    fn allow_identity(&mut self, identity: Pubkey, now: Instant) -> bool {
+        Self::allow(
+            &mut self.identities,
+            identity,
+            now,
+            self.max_requests_per_second,
+        )
+    }
+
+    fn allow_address(&mut self, address: IpAddr, now: Instant) -> bool {
+        Self::allow(
+            &mut self.addresses,
+            address,
+            now,
+            self.max_requests_per_second,
+        )
+    }
+
+    fn allow<K: std::hash::Hash + Eq>(
+        requesters: &mut LruCache<K, (Instant, u64)>,
+        requester: K,
+        now: Instant,
+        max_requests_per_second: u64,
+    ) -> bool {
+        match requesters.get_mut(&requester) {
+            Some((window_start, requests))
+                if now.duration_since(*window_start) < Duration::from_secs(1) =>
+            {
+                if *requests >= max_requests_per_second {
+                    return false;
+                }
+                *requests += 1;
+            }
+            _ => {
+                requesters.put(requester, (now, 1));
+            }
+        }
+        true
+    }
 }
 
 #[derive(Clone)]
@@ -211,18 +349,6 @@ impl ServeRepair {
         self.cluster_info.id()
     }
 
-    fn get_repair_sender(request: &RepairProtocol) -> &ContactInfo {
-        match request {
-            RepairProtocol::WindowIndex(ref from, _, _) => from,
-            RepairProtocol::HighestWindowIndex(ref from, _, _) => from,
-            RepairProtocol::Orphan(ref from, _) => from,
-            RepairProtocol::WindowIndexWithNonce(ref from, _, _, _) => from,
-            RepairProtocol::HighestWindowIndexWithNonce(ref from, _, _, _) => from,
-            RepairProtocol::OrphanWithNonce(ref from, _, _) => from,
-            RepairProtocol::AncestorHashes(ref from, _, _) => from,
-        }
-    }
-
     fn handle_repair(
         me: &Arc<RwLock<Self>>,
         recycler: &PacketsRecycler,
@@ -234,16 +360,21 @@ impl ServeRepair {
         let now = Instant::now();
 
         let my_id = me.read().unwrap().my_id();
-        //TODO: verify `from` is signed
-        let from = Self::get_repair_sender(&request);
-        if from.id == my_id {
+        // The sender of the signed requests was verified by `check_request`
+        let from = request.sender();
+        if *from == my_id {
             stats.self_repair += 1;
             return None;
         }
 
         let (res, label) = {
             match &request {
-                RepairProtocol::WindowIndexWithNonce(_, slot, shred_index, nonce) => {
+                RepairProtocol::WindowIndexWithNonce(_, slot, shred_index, nonce)
+                | RepairProtocol::SignedWindowIndex(
+                    RepairRequestHeader { nonce, .. },
+                    slot,
+                    shred_index,
+                ) => {
                     stats.window_index += 1;
                     (
                         Self::run_window_request(
@@ -259,7 +390,12 @@ impl ServeRepair {
                         "WindowIndexWithNonce",
                     )
                 }
-                RepairProtocol::HighestWindowIndexWithNonce(_, slot, highest_index, nonce) => {
+                RepairProtocol::HighestWindowIndexWithNonce(_, slot, highest_index, nonce)
+                | RepairProtocol::SignedHighestWindowIndex(
+                    RepairRequestHeader { nonce, .. },
+                    slot,
+                    highest_index,
+                ) => {
                     stats.highest_window_index += 1;
                     (
                         Self::run_highest_window_request(
@@ -273,7 +409,8 @@ impl ServeRepair {
                         "HighestWindowIndexWithNonce",
                     )
                 }
-                RepairProtocol::OrphanWithNonce(_, slot, nonce) => {
+                RepairProtocol::OrphanWithNonce(_, slot, nonce)
+                | RepairProtocol::SignedOrphan(RepairRequestHeader { nonce, .. }, slot) => {
                     stats.orphan += 1;
                     (
                         Self::run_orphan(
@@ -287,7 +424,8 @@ impl ServeRepair {
                         "OrphanWithNonce",
                     )
                 }
-                RepairProtocol::AncestorHashes(_, slot, nonce) => {
+                RepairProtocol::AncestorHashes(_, slot, nonce)
+                | RepairProtocol::SignedAncestorHashes(RepairRequestHeader { nonce, .. }, slot) => {
                     stats.ancestor_hashes += 1;
                     (
                         Self::run_ancestor_hashes(recycler, from_addr, blockstore, *slot, *nonce),
@@ -311,12 +449,15 @@ impl ServeRepair {
     }
 
     /// Process messages from the network
+    #[allow(clippy::too_many_arguments)]
     fn run_listen(
         obj: &Arc<RwLock<Self>>,
         recycler: &PacketsRecycler,
         blockstore: Option<&Arc<Blockstore>>,
+        bank_forks: Option<&Arc<RwLock<BankForks>>>,
         requests_receiver: &PacketReceiver,
         response_sender: &PacketSender,
+        requests_limiter: &mut RepairRequestsLimiter,
         stats: &mut ServeRepairStats,
         max_packets: &mut usize,
     ) -> Result<()> {
@@ -339,9 +480,23 @@ impl ServeRepair {
         stats.dropped_packets += dropped_packets;
         stats.total_packets += total_packets;
 
+        let reject_unsigned_requests = bank_forks
+            .map(|bank_forks| {
+                Self::reject_unsigned_requests(&bank_forks.read().unwrap().root_bank())
+            })
+            .unwrap_or(false);
         let mut time = Measure::start("repair::handle_packets");
         for reqs in reqs_v {
-            Self::handle_packets(obj, recycler, blockstore, reqs, response_sender, stats);
+            Self::handle_packets(
+                obj,
+                recycler,
+                blockstore,
+                reject_unsigned_requests,
+                reqs,
+                response_sender,
+                requests_limiter,
+                stats,
+            );
         }
         time.stop();
         if total_packets >= *max_packets {
@@ -354,6 +509,17 @@ impl ServeRepair {
         Ok(())
     }
 
+    /// Whether the unsigned requests are rejected. Nodes sign their requests once the
+    /// sign_repair_requests feature is active, the unsigned ones are served until the epoch after
+    /// its activation so that every node has switched by then
+    fn reject_unsigned_requests(root_bank: &Bank) -> bool {
+        root_bank
+            .feature_set
+            .activated_slot(&feature_set::sign_repair_requests::id())
+            .map(|slot| root_bank.epoch_schedule().get_epoch(slot) < root_bank.epoch())
+            .unwrap_or(false)
+    }
+
     fn report_reset_stats(me: &Arc<RwLock<Self>>, stats: &mut ServeRepairStats) {
         if stats.self_repair > 0 {
             let my_id = me.read().unwrap().cluster_info.id();
@@ -382,12 +548,20 @@ impl ServeRepair {
             "serve_repair-request-ancestor-hashes",
             stats.ancestor_hashes
         );
+        inc_new_counter_debug!("serve_repair-request-signed", stats.signed_requests);
+        inc_new_counter_debug!("serve_repair-request-unsigned", stats.unsigned_requests);
+        inc_new_counter_info!("serve_repair-err-unsigned", stats.err_unsigned);
+        inc_new_counter_info!("serve_repair-err-sig-verify", stats.err_sig_verify);
+        inc_new_counter_info!("serve_repair-err-id-mismatch", stats.err_id_mismatch);
+        inc_new_counter_info!("serve_repair-err-time-skew", stats.err_time_skew);
+        inc_new_counter_info!("serve_repair-rate-limited", stats.rate_limited);
         *stats = ServeRepairStats::default();
     }
 
     pub fn listen(
         me: Arc<RwLock<Self>>,
         blockstore: Option<Arc<Blockstore>>,
+        bank_forks: Option<Arc<RwLock<BankForks>>>,
         requests_receiver: PacketReceiver,
         response_sender: PacketSender,
         exit: &Arc<AtomicBool>,
@@ -399,14 +573,20 @@ impl ServeRepair {
             .spawn(move || {
                 let mut last_print = Instant::now();
                 let mut stats = ServeRepairStats::default();
+                let mut requests_limiter = RepairRequestsLimiter::new(
+                    REPAIR_REQUESTERS_CACHE_CAPACITY,
+                    MAX_REPAIR_REQUESTS_PER_SECOND,
+                );
                 let mut max_packets = 1024;
                 loop {
                     let result = Self::run_listen(
                         &me,
                         &recycler,
                         blockstore.as_ref(),
+                        bank_forks.as_ref(),
                         &requests_receiver,
                         &response_sender,
+                        &mut requests_limiter,
                         &mut stats,
                         &mut max_packets,
                     );
@@ -430,63 +610,173 @@ impl ServeRepair {
         me: &Arc<RwLock<Self>>,
         recycler: &PacketsRecycler,
         blockstore: Option<&Arc<Blockstore>>,
+        reject_unsigned_requests: bool,
         packets: Packets,
         response_sender: &PacketSender,
+        requests_limiter: &mut RepairRequestsLimiter,
         stats: &mut ServeRepairStats,
     ) {
+        let my_id = me.read().unwrap().my_id();
         // iter over the packets
         packets.packets.iter().for_each(|packet| {
             let from_addr = packet.meta.addr();
-            limited_deserialize(&packet.data[..packet.meta.size])
-                .into_iter()
-                .for_each(|request| {
-                    stats.processed += 1;
-                    let rsp =
-                        Self::handle_repair(me, recycler, &from_addr, blockstore, request, stats);
-                    if let Some(rsp) = rsp {
-                        let _ignore_disconnect = response_sender.send(rsp);
-                    }
-                });
+            let data = &packet.data[..packet.meta.size];
+            let request = match limited_deserialize(data) {
+                Ok(request) => request,
+                Err(_) => return,
+            };
+            if !Self::check_request(
+                &my_id,
+                &request,
+                data,
+                &from_addr,
+                reject_unsigned_requests,
+                requests_limiter,
+                stats,
+            ) {
+                return;
+            }
+            stats.processed += 1;
+            let rsp = Self::handle_repair(me, recycler, &from_addr, blockstore, request, stats);
+            if let Some(rsp) = rsp {
+                let _ignore_disconnect = response_sender.send(rsp);
+            }
         });
     }
 
+    /// Verifies the signed requests were signed by their sender for this node, recently, and
+    /// rate limits the requests of each requester
+    fn check_request(
+        my_id: &Pubkey,
+        request: &RepairProtocol,
+        data: &[u8],
+        from_addr: &SocketAddr,
+        reject_unsigned_requests: bool,
+        requests_limiter: &mut RepairRequestsLimiter,
+        stats: &mut ServeRepairStats,
+    ) -> bool {
+        let now = Instant::now();
+        match request.header() {
+            Some(header) => {
+                stats.signed_requests += 1;
+                if header.recipient != *my_id {
+                    stats.err_id_mismatch += 1;
+                    return false;
+                }
+                let now_ms = timestamp();
+                let time_diff = now_ms.max(header.timestamp) - now_ms.min(header.timestamp);
+                if time_diff > SIGNED_REPAIR_TIME_WINDOW_MS {
+                    stats.err_time_skew += 1;
+                    return false;
+                }
+                if !header
+                    .signature
+                    .verify(header.sender.as_ref(), &data[SIGNED_REPAIR_DATA_OFFSET..])
+                {
+                    stats.err_sig_verify += 1;
+                    return false;
+                }
+                if !requests_limiter.allow_identity(header.sender, now) {
+                    stats.rate_limited += 1;
+                    return false;
+                }
+            }
+            None => {
+                stats.unsigned_requests += 1;
+                if reject_unsigned_requests {
+                    stats.err_unsigned += 1;
+                    return false;
+                }
+                if !requests_limiter.allow_address(from_addr.ip(), now) {
+                    stats.rate_limited += 1;
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn request_header(&self, recipient: &Pubkey, nonce: Nonce) -> RepairRequestHeader {
+        RepairRequestHeader::new(self.my_id(), *recipient, timestamp(), nonce)
+    }
+
+    /// Serializes the request, signing it with the node identity if it has a header
+    fn request_bytes(&self, request: &RepairProtocol) -> Result<Vec<u8>> {
+        let mut out = serialize(request)?;
+        if request.header().is_some() {
+            let signature = self
+                .cluster_info
+                .keypair()
+                .sign_message(&out[SIGNED_REPAIR_DATA_OFFSET..]);
+            out[SIGNED_REPAIR_DATA_OFFSET - SIGNATURE_BYTES..SIGNED_REPAIR_DATA_OFFSET]
+                .copy_from_slice(signature.as_ref());
+        }
+        Ok(out)
+    }
+
     fn window_index_request_bytes(
         &self,
         slot: Slot,
         shred_index: u64,
+        recipient: &Pubkey,
         nonce: Nonce,
+        sign_request: bool,
     ) -> Result<Vec<u8>> {
-        let req = RepairProtocol::WindowIndexWithNonce(self.my_info(), slot, shred_index, nonce);
-        let out = serialize(&req)?;
-        Ok(out)
+        let req = if sign_request {
+            let header = self.request_header(recipient, nonce);
+            RepairProtocol::SignedWindowIndex(header, slot, shred_index)
+        } else {
+            RepairProtocol::WindowIndexWithNonce(self.my_info(), slot, shred_index, nonce)
+        };
+        self.request_bytes(&req)
     }
 
     fn window_highest_index_request_bytes(
         &self,
         slot: Slot,
         shred_index: u64,
+        recipient: &Pubkey,
         nonce: Nonce,
+        sign_request: bool,
     ) -> Result<Vec<u8>> {
-        let req =
-            RepairProtocol::HighestWindowIndexWithNonce(self.my_info(), slot, shred_index, nonce);
-        let out = serialize(&req)?;
-        Ok(out)
+        let req = if sign_request {
+            let header = self.request_header(recipient, nonce);
+            RepairProtocol::SignedHighestWindowIndex(header, slot, shred_index)
+        } else {
+            RepairProtocol::HighestWindowIndexWithNonce(self.my_info(), slot, shred_index, nonce)
+        };
+        self.request_bytes(&req)
     }
 
-    fn orphan_bytes(&self, slot: Slot, nonce: Nonce) -> Result<Vec<u8>> {
-        let req = RepairProtocol::OrphanWithNonce(self.my_info(), slot, nonce);
-        let out = serialize(&req)?;
-        Ok(out)
+    fn orphan_bytes(
+        &self,
+        slot: Slot,
+        recipient: &Pubkey,
+        nonce: Nonce,
+        sign_request: bool,
+    ) -> Result<Vec<u8>> {
+        let req = if sign_request {
+            RepairProtocol::SignedOrphan(self.request_header(recipient, nonce), slot)
+        } else {
+            RepairProtocol::OrphanWithNonce(self.my_info(), slot, nonce)
+        };
+        self.request_bytes(&req)
     }
 
     pub fn ancestor_repair_request_bytes(
         &self,
         request_slot: Slot,
+        recipient: &Pubkey,
         nonce: Nonce,
+        sign_request: bool,
     ) -> Result<Vec<u8>> {
-        let repair_request = RepairProtocol::AncestorHashes(self.my_info(), request_slot, nonce);
-        let out = serialize(&repair_request)?;
-        Ok(out)
+        let repair_request = if sign_request {
+            let header = self.request_header(recipient, nonce);
+            RepairProtocol::SignedAncestorHashes(header, request_slot)
+        } else {
+            RepairProtocol::AncestorHashes(self.my_info(), request_slot, nonce)
+        };
+        self.request_bytes(&repair_request)
     }
 
     pub(crate) fn repair_request(
@@ -497,6 +787,7 @@ impl ServeRepair {
         repair_stats: &mut RepairStats,
         repair_validators: &Option<HashSet<Pubkey>>,
         outstanding_requests: &mut OutstandingShredRepairs,
+        sign_request: bool,
     ) -> Result<(SocketAddr, Vec<u8>)> {
         // find a peer that appears to be accepting replication and has the desired slot, as indicated
         // by a valid tvu port location
@@ -515,7 +806,8 @@ impl ServeRepair {
         let (peer, addr) = repair_peers.sample(&mut rand::thread_rng());
         let nonce =
             outstanding_requests.add_request(repair_request, solana_sdk::timing::timestamp());
-        let out = self.map_repair_request(&repair_request, &peer, repair_stats, nonce)?;
+        let out =
+            self.map_repair_request(&repair_request, &peer, repair_stats, nonce, sign_request)?;
         Ok((addr, out))
     }
 
@@ -562,23 +854,36 @@ impl ServeRepair {
         repair_peer_id: &Pubkey,
         repair_stats: &mut RepairStats,
         nonce: Nonce,
+        sign_request: bool,
     ) -> Result<Vec<u8>> {
         match repair_request {
             ShredRepairType::Shred(slot, shred_index) => {
                 repair_stats
                     .shred
                     .update(repair_peer_id, *slot, *shred_index);
-                Ok(self.window_index_request_bytes(*slot, *shred_index, nonce)?)
+                Ok(self.window_index_request_bytes(
+                    *slot,
+                    *shred_index,
+                    repair_peer_id,
+                    nonce,
+                    sign_request,
+                )?)
             }
             ShredRepairType::HighestShred(slot, shred_index) => {
                 repair_stats
                     .highest_shred
                     .update(repair_peer_id, *slot, *shred_index);
-                Ok(self.window_highest_index_request_bytes(*slot, *shred_index, nonce)?)
+                Ok(self.window_highest_index_request_bytes(
+                    *slot,
+                    *shred_index,
+                    repair_peer_id,
+                    nonce,
+                    sign_request,
+                )?)
             }
             ShredRepairType::Orphan(slot) => {
                 repair_stats.orphan.update(repair_peer_id, *slot, 0);
-                Ok(self.orphan_bytes(*slot, nonce)?)
+                Ok(self.orphan_bytes(*slot, repair_peer_id, nonce, sign_request)?)
             }
         }
     }
@@ -606,7 +911,7 @@ impl ServeRepair {
 
     fn run_window_request(
         recycler: &PacketsRecycler,
-        from: &ContactInfo,
+        from: &Pubkey,
         from_addr: &SocketAddr,
         blockstore: Option<&Arc<Blockstore>>,
         my_id: &Pubkey,
@@ -638,7 +943,7 @@ impl ServeRepair {
         trace!(
             "{}: failed WindowIndex {} {} {}",
             my_id,
-            from.id,
+            from,
             slot,
             shred_index,
         );
@@ -762,6 +1067,9 @@ mod tests {
         shred::{max_ticks_per_n_shreds, Shred},
     };
     use solana_perf::packet::Packet;
+    use solana_runtime::genesis_utils::{
+        activate_all_features, create_genesis_config, GenesisConfigInfo,
+    };
     use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, timing::timestamp};
     use solana_streamer::socket::SocketAddrSpace;
 
@@ -864,7 +1172,7 @@ mod tests {
             };
             let rv = ServeRepair::run_window_request(
                 &recycler,
-                &me,
+                &me.id,
                 &socketaddr_any!(),
                 Some(&blockstore),
                 &me.id,
@@ -882,7 +1190,7 @@ mod tests {
             let index = 1;
             let rv = ServeRepair::run_window_request(
                 &recycler,
-                &me,
+                &me.id,
                 &socketaddr_any!(),
                 Some(&blockstore),
                 &me.id,
@@ -930,6 +1238,7 @@ mod tests {
             &mut RepairStats::default(),
             &None,
             &mut outstanding_requests,
+            false,
         );
         assert_matches!(rv, Err(Error::ClusterInfo(ClusterInfoError::NoPeers)));
 
@@ -958,6 +1267,7 @@ mod tests {
                 &mut RepairStats::default(),
                 &None,
                 &mut outstanding_requests,
+                false,
             )
             .unwrap();
         assert_eq!(nxt.serve_repair, serve_repair_addr);
@@ -992,6 +1302,7 @@ mod tests {
                     &mut RepairStats::default(),
                     &None,
                     &mut outstanding_requests,
+                    false,
                 )
                 .unwrap();
             if rv.0 == serve_repair_addr {
@@ -1004,6 +1315,107 @@ mod tests {
         assert!(one && two);
     }
 
+    #[test]
+    fn test_check_request() {
+        let keypair = Arc::new(Keypair::new());
+        let me = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+        let cluster_info = ClusterInfo::new(me, keypair, SocketAddrSpace::Unspecified);
+        let serve_repair = ServeRepair::new(Arc::new(cluster_info));
+        let recipient = solana_sdk::pubkey::new_rand();
+        let from_addr = socketaddr!([127, 0, 0, 1], 1234);
+        let mut requests_limiter = RepairRequestsLimiter::new(16, 2);
+        let mut stats = ServeRepairStats::default();
+        let mut check_request = |my_id: &Pubkey, request: &RepairProtocol, data: &[u8]| {
+            ServeRepair::check_request(
+                my_id,
+                request,
+                data,
+                &from_addr,
+                false,
+                &mut requests_limiter,
+                &mut stats,
+            )
+        };
+
+        let bytes = serve_repair
+            .window_index_request_bytes(5, 3, &recipient, 7, true)
+            .unwrap();
+        let request: RepairProtocol = limited_deserialize(&bytes).unwrap();
+        assert_matches!(request, RepairProtocol::SignedWindowIndex(_, 5, 3));
+        assert!(check_request(&recipient, &request, &bytes));
+
+        // Requests for other nodes and tampered requests are rejected
+        assert!(!check_request(
+            &solana_sdk::pubkey::new_rand(),
+            &request,
+            &bytes
+        ));
+        let mut tampered_bytes = bytes.clone();
+        *tampered_bytes.last_mut().unwrap() ^= 1;
+        assert!(!check_request(&recipient, &request, &tampered_bytes));
+
+        // So are stale requests
+        let stale_header = RepairRequestHeader::new(serve_repair.my_id(), recipient, 0, 7);
+        let stale_request = RepairProtocol::SignedOrphan(stale_header, 5);
+        let stale_bytes = serve_repair.request_bytes(&stale_request).unwrap();
+        assert!(!check_request(&recipient, &stale_request, &stale_bytes));
+
+        // The signed requests are limited by sender
+        assert!(check_request(&recipient, &request, &bytes));
+        assert!(!check_request(&recipient, &request, &bytes));
+
+        // The unsigned requests by source address
+        let bytes = serve_repair
+            .window_index_request_bytes(5, 3, &recipient, 7, false)
+            .unwrap();
+        let request: RepairProtocol = limited_deserialize(&bytes).unwrap();
+        assert!(request.header().is_none());
+        assert!(check_request(&recipient, &request, &bytes));
+        assert!(check_request(&recipient, &request, &bytes));
+        assert!(!check_request(&recipient, &request, &bytes));
+
+        assert_eq!(stats.signed_requests, 6);
+        assert_eq!(stats.unsigned_requests, 3);
+        assert_eq!(stats.err_id_mismatch, 1);
+        assert_eq!(stats.err_sig_verify, 1);
+        assert_eq!(stats.err_time_skew, 1);
+        assert_eq!(stats.rate_limited, 2);
+
+        // Unless unsigned requests are rejected
+        let mut requests_limiter = RepairRequestsLimiter::new(16, 2);
+        let mut stats = ServeRepairStats::default();
+        assert!(!ServeRepair::check_request(
+            &recipient,
+            &request,
+            &bytes,
+            &from_addr,
+            true,
+            &mut requests_limiter,
+            &mut stats,
+        ));
+        assert_eq!(stats.err_unsigned, 1);
+    }
+
+    #[test]
+    fn test_reject_unsigned_requests() {
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config(10_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        assert!(!ServeRepair::reject_unsigned_requests(&bank0));
+
+        // Unsigned requests are still served during the epoch the feature is activated in
+        activate_all_features(&mut genesis_config);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        assert!(!ServeRepair::reject_unsigned_requests(&bank0));
+        let bank1 = Bank::new_from_parent(
+            &bank0,
+            &Pubkey::default(),
+            genesis_config.epoch_schedule.get_first_slot_in_epoch(1),
+        );
+        assert!(ServeRepair::reject_unsigned_requests(&bank1));
+    }
+
     #[test]
     fn test_run_orphan() {
         run_orphan(2, 3, 9);
@@ -1256,6 +1668,7 @@ mod tests {
                     &mut RepairStats::default(),
                     &trusted_validators,
                     &mut OutstandingShredRepairs::default(),
+                    false,
                 )
                 .is_err());
         }
@@ -1273,6 +1686,7 @@ mod tests {
                 &mut RepairStats::default(),
                 &trusted_validators,
                 &mut OutstandingShredRepairs::default(),
+                false,
             )
             .is_ok());
 
@@ -1294,6 +1708,7 @@ mod tests {
                 &mut RepairStats::default(),
                 &None,
                 &mut OutstandingShredRepairs::default(),
+                false,
             )
             .is_ok());
     }
@@ -1,6 +1,7 @@
 use crate::serve_repair::ServeRepair;
 use solana_ledger::blockstore::Blockstore;
 use solana_perf::recycler::Recycler;
+use solana_runtime::bank_forks::BankForks;
 use solana_streamer::{socket::SocketAddrSpace, streamer};
 use std::net::UdpSocket;
 use std::sync::atomic::AtomicBool;
@@ -16,6 +17,7 @@ impl ServeRepairService {
     pub fn new(
         serve_repair: &Arc<RwLock<ServeRepair>>,
         blockstore: Option<Arc<Blockstore>>,
+        bank_forks: Option<Arc<RwLock<BankForks>>>,
         serve_repair_socket: UdpSocket,
         socket_addr_space: SocketAddrSpace,
         exit: &Arc<AtomicBool>,
@@ -46,6 +48,7 @@ impl ServeRepairService {
         let t_listen = ServeRepair::listen(
             serve_repair.clone(),
             blockstore,
+            bank_forks,
             request_receiver,
             response_sender,
             exit,
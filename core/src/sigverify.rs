@@ -5,13 +5,16 @@
 //!
 
 use crate::sigverify_stage::SigVerifier;
+use solana_metrics::transaction_tracing;
 use solana_perf::cuda_runtime::PinnedVec;
-use solana_perf::packet::Packets;
+use solana_perf::packet::{Packet, Packets};
 use solana_perf::recycler::Recycler;
 use solana_perf::sigverify;
 pub use solana_perf::sigverify::{
     batch_size, ed25519_verify_cpu, ed25519_verify_disabled, init, TxOffset,
 };
+use solana_sdk::{short_vec::decode_shortu16_len, signature::Signature};
+use std::time::Instant;
 
 #[derive(Clone)]
 pub struct TransactionSigVerifier {
@@ -42,12 +45,40 @@ impl Default for TransactionSigVerifier {
 
 impl SigVerifier for TransactionSigVerifier {
     fn verify_batch(&self, mut batch: Vec<Packets>) -> Vec<Packets> {
+        let verify_start = Instant::now();
         sigverify::ed25519_verify(
             &mut batch,
             &self.recycler,
             &self.recycler_out,
             self.reject_non_vote,
         );
+        if transaction_tracing::is_enabled() {
+            let signatures: Vec<_> = batch
+                .iter()
+                .flat_map(|packets| packets.packets.iter())
+                .filter(|packet| !packet.meta.discard)
+                .filter_map(packet_signature)
+                .collect();
+            transaction_tracing::record_spans(
+                "sigverify",
+                &signatures,
+                verify_start,
+                Instant::now(),
+            );
+        }
         batch
     }
 }
+
+// Returns the first signature of the transaction in the packet, which identifies it
+fn packet_signature(packet: &Packet) -> Option<Signature> {
+    let (num_signatures, sig_start) = decode_shortu16_len(&packet.data).ok()?;
+    if num_signatures == 0 {
+        return None;
+    }
+    let sig_end = sig_start.checked_add(std::mem::size_of::<Signature>())?;
+    if sig_end > packet.meta.size {
+        return None;
+    }
+    Some(Signature::new(&packet.data[sig_start..sig_end]))
+}
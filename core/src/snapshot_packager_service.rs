@@ -167,6 +167,17 @@ impl SnapshotGossipManager {
         }
         self.cluster_info
             .push_snapshot_hashes(self.full_snapshot_hashes.hashes.clone());
+
+        // The incremental snapshots based on the previous full snapshot are purged once a new full
+        // snapshot is archived, so stop advertising them, lest bootstrapping nodes which have the
+        // previous full snapshot try to download them.
+        if !self.incremental_snapshot_hashes.hashes.is_empty() {
+            self.incremental_snapshot_hashes.base = full_snapshot_hash.hash;
+            self.incremental_snapshot_hashes.hashes.clear();
+            self.cluster_info
+                .push_incremental_snapshot_hashes(self.incremental_snapshot_hashes.base, vec![])
+                .expect("pushing no incremental snapshot hashes cannot fail");
+        }
     }
 
     /// Add `incremental_snapshot_hash` to the vector of incremental snapshot hashes, then push
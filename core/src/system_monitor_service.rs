@@ -1,3 +1,4 @@
+use solana_rayon_threadlimit::thread_pools::{self, ThreadPoolStats};
 use std::{
     collections::HashMap,
     io::BufRead,
@@ -173,8 +174,41 @@ impl SystemMonitorService {
         );
     }
 
+    fn process_thread_pool_stats(thread_pool_stats: &mut HashMap<&'static str, ThreadPoolStats>) {
+        for new_stats in thread_pools::thread_pool_stats() {
+            if let Some(old_stats) = thread_pool_stats.get(new_stats.name) {
+                SystemMonitorService::report_thread_pool_stats(old_stats, &new_stats);
+            }
+            thread_pool_stats.insert(new_stats.name, new_stats);
+        }
+    }
+
+    fn report_thread_pool_stats(old_stats: &ThreadPoolStats, new_stats: &ThreadPoolStats) {
+        let busy_us = new_stats.busy_us.saturating_sub(old_stats.busy_us);
+        datapoint_info!(
+            "thread-pool-stats",
+            ("name", new_stats.name.to_string(), String),
+            ("num_pools", new_stats.num_pools, i64),
+            ("num_threads", new_stats.num_threads, i64),
+            (
+                "installs_delta",
+                new_stats.installs.saturating_sub(old_stats.installs),
+                i64
+            ),
+            ("busy_us_delta", busy_us, i64),
+            // Share of the sample interval the pool spent running operations, above 100 when
+            // several operations run in the pool at once
+            (
+                "busy_pct",
+                busy_us * 100 / SAMPLE_INTERVAL.as_micros() as u64,
+                i64
+            ),
+        );
+    }
+
     pub fn run(exit: Arc<AtomicBool>) {
         let mut udp_stats = None;
+        let mut thread_pool_stats = HashMap::new();
 
         let mut now = Instant::now();
         loop {
@@ -186,6 +220,7 @@ impl SystemMonitorService {
                 now = Instant::now();
 
                 SystemMonitorService::process_udp_stats(&mut udp_stats);
+                SystemMonitorService::process_thread_pool_stats(&mut thread_pool_stats);
             }
 
             sleep(SLEEP_INTERVAL);
@@ -8,7 +8,10 @@ use crate::{
         ClusterInfoVoteListener, GossipDuplicateConfirmedSlotsSender, GossipVerifiedVoteHashSender,
         VerifiedVoteSender, VoteTracker,
     },
+    completed_data_sets_service::CompletedDataSetsSender,
+    external_block_producer::{ExternalBlockProducer, ExternalBlockProducerConfig},
     fetch_stage::FetchStage,
+    leader_slot_reports::LeaderSlotReports,
     sigverify::TransactionSigVerifier,
     sigverify_stage::SigVerifyStage,
 };
@@ -25,6 +28,7 @@ use solana_runtime::{
     cost_model::CostModel,
     vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
 };
+use solana_streamer::streamer::ReceiverConfig;
 use std::{
     net::UdpSocket,
     sync::{
@@ -44,6 +48,7 @@ pub struct Tpu {
     banking_stage: BankingStage,
     cluster_info_vote_listener: ClusterInfoVoteListener,
     broadcast_stage: BroadcastStage,
+    external_block_producer: Option<ExternalBlockProducer>,
 }
 
 impl Tpu {
@@ -70,9 +75,12 @@ impl Tpu {
         replay_vote_receiver: ReplayVoteReceiver,
         replay_vote_sender: ReplayVoteSender,
         bank_notification_sender: Option<BankNotificationSender>,
-        tpu_coalesce_ms: u64,
+        tpu_receiver_config: ReceiverConfig,
         cluster_confirmed_slot_sender: GossipDuplicateConfirmedSlotsSender,
         cost_model: &Arc<RwLock<CostModel>>,
+        external_block_producer_config: Option<ExternalBlockProducerConfig>,
+        leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+        completed_data_sets_sender: Option<CompletedDataSetsSender>,
     ) -> Self {
         let (packet_sender, packet_receiver) = channel();
         let (vote_packet_sender, vote_packet_receiver) = channel();
@@ -84,8 +92,27 @@ impl Tpu {
             &packet_sender,
             &vote_packet_sender,
             poh_recorder,
-            tpu_coalesce_ms,
+            tpu_receiver_config,
         );
+        let (external_block_producer, bundle_receiver) = match external_block_producer_config {
+            Some(config) => {
+                let socket_path = config.socket_path.clone();
+                let (bundle_sender, bundle_receiver) = unbounded();
+                match ExternalBlockProducer::new(config, poh_recorder, bundle_sender, exit) {
+                    Ok(external_block_producer) => {
+                        (Some(external_block_producer), Some(bundle_receiver))
+                    }
+                    Err(err) => {
+                        error!(
+                            "Failed to start external block producer on {:?}: {}",
+                            socket_path, err
+                        );
+                        (None, None)
+                    }
+                }
+            }
+            None => (None, None),
+        };
         let (verified_sender, verified_receiver) = unbounded();
 
         let sigverify_stage = {
@@ -131,6 +158,8 @@ impl Tpu {
             transaction_status_sender,
             replay_vote_sender,
             cost_model.clone(),
+            leader_slot_reports.clone(),
+            bundle_receiver,
         );
 
         let broadcast_stage = broadcast_type.new_broadcast_stage(
@@ -142,6 +171,8 @@ impl Tpu {
             blockstore,
             &bank_forks,
             shred_version,
+            leader_slot_reports,
+            completed_data_sets_sender,
         );
 
         Self {
@@ -151,6 +182,7 @@ impl Tpu {
             banking_stage,
             cluster_info_vote_listener,
             broadcast_stage,
+            external_block_producer,
         }
     }
 
@@ -162,6 +194,9 @@ impl Tpu {
             self.cluster_info_vote_listener.join(),
             self.banking_stage.join(),
         ];
+        if let Some(external_block_producer) = self.external_block_producer {
+            external_block_producer.join()?;
+        }
         let broadcast_result = self.broadcast_stage.join();
         for result in results {
             result?;
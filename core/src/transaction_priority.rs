@@ -0,0 +1,211 @@
+//! Prioritization of the transactions scheduled by the banking stage
+//!
+//! Once the `SetComputeUnitPrice` compute budget instruction is enabled, transactions are ordered
+//! by the price per compute unit they offer with it, highest first, and are charged that price
+//! for the compute units they request. The transactions write-locking an account then queue
+//! behind each other: only the first transaction write-locking an account is scheduled at once,
+//! the others wait for a later pass instead of failing to lock the account.
+
+use {
+    solana_perf::packet::Packet,
+    solana_sdk::{
+        borsh::try_from_slice_unchecked,
+        compute_budget::{
+            self, get_compute_unit_price, ComputeBudgetInstruction, MAX_COMPUTE_BUDGET_INSTRUCTIONS,
+        },
+        hash::Hash,
+        message::{MESSAGE_HEADER_LENGTH, MESSAGE_VERSION_PREFIX},
+        pubkey::Pubkey,
+        short_vec::decode_shortu16_len,
+        signature::Signature,
+        transaction::SanitizedTransaction,
+    },
+    std::{cmp::Reverse, collections::HashSet, mem::size_of},
+};
+
+/// Reads the fields of a serialized transaction in place
+struct PacketReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (field, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(field)
+    }
+
+    fn take_short_vec_len(&mut self) -> Option<usize> {
+        let (len, size) = decode_shortu16_len(self.bytes).ok()?;
+        self.take(size)?;
+        Some(len)
+    }
+}
+
+/// Price per compute unit offered by the transaction of `packet`, zero if it offers none or
+/// can't be parsed. Only the start of the message up to the compute budget instructions is read,
+/// the transaction isn't deserialized.
+pub fn packet_compute_unit_price(packet: &Packet) -> u64 {
+    parse_compute_unit_price(&packet.data[0..packet.meta.size]).unwrap_or_default()
+}
+
+fn parse_compute_unit_price(bytes: &[u8]) -> Option<u64> {
+    let mut reader = PacketReader { bytes };
+    let num_signatures = reader.take_short_vec_len()?;
+    reader.take(num_signatures.checked_mul(size_of::<Signature>())?)?;
+    // Versioned messages lay out their instructions like legacy ones, after a version prefix
+    if reader.bytes.first()? & MESSAGE_VERSION_PREFIX != 0 {
+        reader.take(1)?;
+    }
+    reader.take(MESSAGE_HEADER_LENGTH)?;
+    let num_account_keys = reader.take_short_vec_len()?;
+    let account_keys = reader.take(num_account_keys.checked_mul(size_of::<Pubkey>())?)?;
+    reader.take(size_of::<Hash>())?;
+    let num_instructions = reader.take_short_vec_len()?;
+    // As with `get_compute_unit_price`, the last price set wins
+    let mut compute_unit_price = None;
+    for _ in 0..num_instructions.min(MAX_COMPUTE_BUDGET_INSTRUCTIONS) {
+        let program_id_index = usize::from(*reader.take(1)?.first()?);
+        let num_accounts = reader.take_short_vec_len()?;
+        reader.take(num_accounts)?;
+        let data_len = reader.take_short_vec_len()?;
+        let data = reader.take(data_len)?;
+        let program_id = account_keys
+            .chunks_exact(size_of::<Pubkey>())
+            .nth(program_id_index);
+        if program_id == Some(compute_budget::id().as_ref()) {
+            if let Ok(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports)) =
+                try_from_slice_unchecked(data)
+            {
+                compute_unit_price = Some(micro_lamports);
+            }
+        }
+    }
+    compute_unit_price
+}
+
+/// Orders `transactions` by descending compute unit price if `prioritize` is set, FIFO among
+/// equal prices, and if `defer_write_conflicts` is set, defers the transactions write-locking an
+/// account already write-locked by a scheduled transaction. Returns the indexes of the scheduled
+/// transactions, in order, and of the deferred ones.
+pub fn prioritize_transactions(
+    transactions: &[SanitizedTransaction],
+    demote_program_write_locks: bool,
+    prioritize: bool,
+    defer_write_conflicts: bool,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut indexes: Vec<_> = (0..transactions.len()).collect();
+    if prioritize {
+        indexes.sort_by_cached_key(|index| {
+            Reverse(get_compute_unit_price(
+                transactions[*index].message().program_instructions_iter(),
+            ))
+        });
+    }
+
+    if !defer_write_conflicts {
+        return (indexes, vec![]);
+    }
+    let mut write_locked_accounts: HashSet<&Pubkey> = HashSet::new();
+    indexes.into_iter().partition(|index| {
+        let account_locks = transactions[*index].get_account_locks(demote_program_write_locks);
+        if account_locks
+            .writable
+            .iter()
+            .any(|pubkey| write_locked_accounts.contains(*pubkey))
+        {
+            false
+        } else {
+            write_locked_accounts.extend(account_locks.writable);
+            true
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_perf::packet::to_packets_chunked,
+        solana_sdk::{
+            hash::Hash, message::Message, signature::Keypair, signer::Signer, system_instruction,
+            transaction::Transaction,
+        },
+    };
+
+    fn transfer(from: &Keypair, to: &Pubkey, compute_unit_price: Option<u64>) -> Transaction {
+        let mut instructions = vec![];
+        if let Some(compute_unit_price) = compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                compute_unit_price,
+            ));
+        }
+        instructions.push(system_instruction::transfer(&from.pubkey(), to, 1));
+        Transaction::new(
+            &[from],
+            Message::new(&instructions, Some(&from.pubkey())),
+            Hash::default(),
+        )
+    }
+
+    #[test]
+    fn test_packet_compute_unit_price() {
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let packets = to_packets_chunked(
+            &[
+                transfer(&keypair, &to, None),
+                transfer(&keypair, &to, Some(42)),
+            ],
+            2,
+        );
+        assert_eq!(packet_compute_unit_price(&packets[0].packets[0]), 0);
+        assert_eq!(packet_compute_unit_price(&packets[0].packets[1]), 42);
+        assert_eq!(packet_compute_unit_price(&Packet::default()), 0);
+
+        // Packets truncated before the compute budget instructions offer nothing
+        let mut packet = packets[0].packets[1].clone();
+        packet.meta.size = 100;
+        assert_eq!(packet_compute_unit_price(&packet), 0);
+    }
+
+    #[test]
+    fn test_prioritize_transactions() {
+        let hot_account = Pubkey::new_unique();
+        let transactions: Vec<_> = [
+            (Keypair::new(), hot_account, None),
+            (Keypair::new(), hot_account, Some(10)),
+            (Keypair::new(), Pubkey::new_unique(), Some(5)),
+            (Keypair::new(), hot_account, Some(20)),
+            (Keypair::new(), Pubkey::new_unique(), None),
+        ]
+        .iter()
+        .map(|(from, to, compute_unit_price)| {
+            SanitizedTransaction::from_transaction_for_tests(transfer(
+                from,
+                to,
+                *compute_unit_price,
+            ))
+        })
+        .collect();
+
+        // Only the highest priced transfer to the hot account is scheduled
+        assert_eq!(
+            prioritize_transactions(&transactions, true, true, true),
+            (vec![3, 2, 4], vec![1, 0])
+        );
+        // Without prioritization, the first transfer to the hot account is
+        assert_eq!(
+            prioritize_transactions(&transactions, true, false, true),
+            (vec![0, 2, 4], vec![1, 3])
+        );
+        // Without deferral, all of them are, in price order
+        assert_eq!(
+            prioritize_transactions(&transactions, true, true, false),
+            (vec![3, 1, 2, 0, 4], vec![])
+        );
+    }
+}
@@ -13,8 +13,10 @@ use crate::{
     completed_data_sets_service::CompletedDataSetsSender,
     consensus::Tower,
     cost_update_service::CostUpdateService,
+    dumped_slots::DumpedSlots,
+    leader_slot_reports::LeaderSlotReports,
     ledger_cleanup_service::LedgerCleanupService,
-    replay_stage::{ReplayStage, ReplayStageConfig},
+    replay_stage::{ProducedBlockVerification, ReplayStage, ReplayStageConfig, WarpSlotReceiver},
     retransmit_stage::RetransmitStage,
     rewards_recorder_service::RewardsRecorderSender,
     shred_fetch_stage::ShredFetchStage,
@@ -24,6 +26,7 @@ use crate::{
     voting_service::VotingService,
 };
 use crossbeam_channel::unbounded;
+use solana_accountsdb_plugin_manager::block_metadata_notifier_interface::BlockMetadataNotifier;
 use solana_gossip::cluster_info::ClusterInfo;
 use solana_ledger::{
     blockstore::Blockstore, blockstore_processor::TransactionStatusSender,
@@ -39,6 +42,7 @@ use solana_runtime::{
         AbsRequestHandler, AbsRequestSender, AccountsBackgroundService, SnapshotRequestHandler,
     },
     accounts_db::AccountShrinkThreshold,
+    accounts_hash_audit::AccountsHashAudit,
     bank_forks::BankForks,
     commitment::BlockCommitmentCache,
     cost_model::CostModel,
@@ -94,6 +98,12 @@ pub struct TvuConfig {
     pub wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
     pub disable_epoch_boundary_optimization: bool,
+    pub verify_produced_blocks: Option<ProducedBlockVerification>,
+    pub leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+    pub disable_duplicate_slot_dumping: bool,
+    pub dumped_slots: Option<Arc<DumpedSlots>>,
+    pub block_metadata_notifier: Option<BlockMetadataNotifier>,
+    pub accounts_hash_audit: Option<Arc<AccountsHashAudit>>,
 }
 
 impl Tvu {
@@ -137,6 +147,7 @@ impl Tvu {
         cost_model: &Arc<RwLock<CostModel>>,
         accounts_package_channel: (AccountsPackageSender, AccountsPackageReceiver),
         last_full_snapshot_slot: Option<Slot>,
+        warp_slot_receiver: WarpSlotReceiver,
     ) -> Self {
         let Sockets {
             repair: repair_socket,
@@ -264,6 +275,7 @@ impl Tvu {
         let accounts_background_request_handler = AbsRequestHandler {
             snapshot_request_handler,
             pruned_banks_receiver,
+            accounts_hash_audit: tvu_config.accounts_hash_audit,
         };
 
         let replay_stage_config = ReplayStageConfig {
@@ -283,6 +295,12 @@ impl Tvu {
             ancestor_hashes_replay_update_sender,
             tower_storage: tower_storage.clone(),
             disable_epoch_boundary_optimization: tvu_config.disable_epoch_boundary_optimization,
+            warp_slot_receiver,
+            verify_produced_blocks: tvu_config.verify_produced_blocks,
+            leader_slot_reports: tvu_config.leader_slot_reports,
+            disable_duplicate_slot_dumping: tvu_config.disable_duplicate_slot_dumping,
+            dumped_slots: tvu_config.dumped_slots,
+            block_metadata_notifier: tvu_config.block_metadata_notifier,
         };
 
         let (voting_sender, voting_receiver) = channel();
@@ -481,6 +499,7 @@ pub mod tests {
             &Arc::new(RwLock::new(CostModel::default())),
             accounts_package_channel,
             None,
+            channel().1,
         );
         exit.store(true, Ordering::Relaxed);
         tvu.join().unwrap();
@@ -8,6 +8,10 @@ use {
         cluster_info_vote_listener::VoteTracker,
         completed_data_sets_service::CompletedDataSetsService,
         consensus::{reconcile_blockstore_roots_with_tower, Tower},
+        dumped_slots::DumpedSlots,
+        external_block_producer::ExternalBlockProducerConfig,
+        leader_slot_reports::LeaderSlotReports,
+        replay_stage::{ProducedBlockVerification, WarpSlotSender},
         rewards_recorder_service::{RewardsRecorderSender, RewardsRecorderService},
         sample_performance_service::SamplePerformanceService,
         serve_repair::ServeRepair,
@@ -22,6 +26,7 @@ use {
     crossbeam_channel::{bounded, unbounded},
     rand::{thread_rng, Rng},
     solana_accountsdb_plugin_manager::accountsdb_plugin_service::AccountsDbPluginService,
+    solana_bpf_loader_program::syscalls::{register_custom_syscall, CustomSyscall},
     solana_entry::poh::compute_hash_time_ns,
     solana_gossip::{
         cluster_info::{
@@ -44,11 +49,12 @@ use {
     solana_metrics::datapoint_info,
     solana_poh::{
         poh_recorder::{PohRecorder, GRACE_TICKS_FACTOR, MAX_GRACE_SLOTS},
-        poh_service::{self, PohService},
+        poh_service::{self, ManualTicks, PohService},
     },
     solana_replica_lib::{
         accountsdb_repl_server::{AccountsDbReplService, AccountsDbReplServiceConfig},
         accountsdb_repl_server_factory,
+        rpc_reads_server::{RpcReadsService, RpcReadsServiceConfig},
     },
     solana_rpc::{
         max_slots::MaxSlots,
@@ -64,9 +70,10 @@ use {
     },
     solana_runtime::{
         accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
+        accounts_hash_audit::AccountsHashAudit,
         accounts_index::AccountSecondaryIndexes,
         accounts_update_notifier_interface::AccountsUpdateNotifier,
-        bank::Bank,
+        bank::{Bank, MAX_CACHED_EXECUTORS},
         bank_forks::BankForks,
         commitment::BlockCommitmentCache,
         cost_model::CostModel,
@@ -79,6 +86,7 @@ use {
     },
     solana_sdk::{
         clock::Slot,
+        compute_budget::ComputeBudget,
         epoch_schedule::MAX_LEADER_SCHEDULE_EPOCH_OFFSET,
         exit::Exit,
         genesis_config::GenesisConfig,
@@ -89,7 +97,10 @@ use {
         timing::timestamp,
     },
     solana_send_transaction_service::send_transaction_service,
-    solana_streamer::socket::SocketAddrSpace,
+    solana_streamer::{
+        packet::PACKETS_PER_BATCH, recv_backend::RecvBackendKind, socket::SocketAddrSpace,
+        streamer::ReceiverConfig,
+    },
     solana_vote_program::vote_state::VoteState,
     std::{
         collections::{HashMap, HashSet},
@@ -119,6 +130,7 @@ pub struct ValidatorConfig {
     pub account_shrink_paths: Option<Vec<PathBuf>>,
     pub rpc_config: JsonRpcConfig,
     pub accountsdb_repl_service_config: Option<AccountsDbReplServiceConfig>,
+    pub rpc_reads_service_config: Option<RpcReadsServiceConfig>,
     pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
@@ -137,6 +149,7 @@ pub struct ValidatorConfig {
     pub accounts_hash_fault_injection_slots: u64, // 0 = no fault injection
     pub frozen_accounts: Vec<Pubkey>,
     pub no_rocksdb_compaction: bool,
+    pub blockstore_versioned_protobuf: bool,
     pub rocksdb_compaction_interval: Option<u64>,
     pub rocksdb_max_compaction_jitter: Option<u64>,
     pub accounts_hash_interval_slots: u64,
@@ -161,10 +174,29 @@ pub struct ValidatorConfig {
     pub accounts_db_skip_shrink: bool,
     pub accounts_db_use_index_hash_calculation: bool,
     pub tpu_coalesce_ms: u64,
+    pub tpu_coalesce_max_packets: usize,
+    pub tpu_recv_buffer_size: Option<usize>,
+    pub tpu_recv_backend: RecvBackendKind,
+    pub tpu_autotune_recv_batch_size: bool,
     pub validator_exit: Arc<RwLock<Exit>>,
     pub no_wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
     pub disable_epoch_boundary_optimization: bool,
+    pub external_block_producer_config: Option<ExternalBlockProducerConfig>,
+    pub executor_cache_capacity: usize,
+    pub poh_manual_ticks: Option<Arc<ManualTicks>>,
+    pub verify_produced_blocks: Option<ProducedBlockVerification>,
+    pub leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+    /// Leave the slots the cluster confirmed another version of to be recovered by hand, instead
+    /// of purging them and repairing that version
+    pub disable_duplicate_slot_dumping: bool,
+    pub dumped_slots: Option<Arc<DumpedSlots>>,
+    pub accounts_hash_audit: Option<Arc<AccountsHashAudit>>,
+    pub compute_budget: Option<ComputeBudget>,
+    pub queue_conflicting_transactions: bool,
+    /// Syscalls made available to the programs in addition to the built-in ones, once their
+    /// feature is active
+    pub custom_syscalls: Vec<CustomSyscall>,
 }
 
 impl Default for ValidatorConfig {
@@ -180,6 +212,7 @@ impl Default for ValidatorConfig {
             account_shrink_paths: None,
             rpc_config: JsonRpcConfig::default(),
             accountsdb_repl_service_config: None,
+            rpc_reads_service_config: None,
             accountsdb_plugin_config_files: None,
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
@@ -197,6 +230,7 @@ impl Default for ValidatorConfig {
             accounts_hash_fault_injection_slots: 0,
             frozen_accounts: vec![],
             no_rocksdb_compaction: false,
+            blockstore_versioned_protobuf: false,
             rocksdb_compaction_interval: None,
             rocksdb_max_compaction_jitter: None,
             accounts_hash_interval_slots: std::u64::MAX,
@@ -220,11 +254,26 @@ impl Default for ValidatorConfig {
             accounts_db_skip_shrink: false,
             accounts_db_use_index_hash_calculation: true,
             tpu_coalesce_ms: DEFAULT_TPU_COALESCE_MS,
+            tpu_coalesce_max_packets: PACKETS_PER_BATCH,
+            tpu_recv_buffer_size: None,
+            tpu_recv_backend: RecvBackendKind::default(),
+            tpu_autotune_recv_batch_size: false,
             validator_exit: Arc::new(RwLock::new(Exit::default())),
             no_wait_for_vote_to_start_leader: true,
             accounts_shrink_ratio: AccountShrinkThreshold::default(),
             accounts_db_config: None,
             disable_epoch_boundary_optimization: false,
+            external_block_producer_config: None,
+            executor_cache_capacity: MAX_CACHED_EXECUTORS,
+            poh_manual_ticks: None,
+            verify_produced_blocks: None,
+            leader_slot_reports: None,
+            disable_duplicate_slot_dumping: false,
+            dumped_slots: None,
+            accounts_hash_audit: None,
+            compute_budget: None,
+            queue_conflicting_transactions: false,
+            custom_syscalls: Vec::new(),
         }
     }
 }
@@ -287,7 +336,10 @@ pub struct Validator {
     tvu: Tvu,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
+    pub warp_slot_sender: WarpSlotSender,
+    pub bank_forks: Arc<RwLock<BankForks>>,
     accountsdb_repl_service: Option<AccountsDbReplService>,
+    rpc_reads_service: Option<RpcReadsService>,
     accountsdb_plugin_service: Option<AccountsDbPluginService>,
 }
 
@@ -325,6 +377,11 @@ impl Validator {
         warn!("identity: {}", id);
         warn!("vote account: {}", vote_account);
 
+        // Registered before any program is loaded, as the executables created earlier lack them
+        for custom_syscall in &config.custom_syscalls {
+            register_custom_syscall(*custom_syscall);
+        }
+
         let mut bank_notification_senders = Vec::new();
 
         let accountsdb_plugin_service =
@@ -529,6 +586,9 @@ impl Validator {
             rpc_subscriptions.clone(),
             &exit,
             max_slots.clone(),
+            accountsdb_plugin_service
+                .as_ref()
+                .and_then(|service| service.get_entry_notifier()),
         );
 
         info!(
@@ -573,6 +633,7 @@ impl Validator {
             optimistically_confirmed_bank_tracker,
             bank_notification_sender,
             accountsdb_repl_service,
+            rpc_reads_service,
         ) = if let Some((rpc_addr, rpc_pubsub_addr)) = config.rpc_addrs {
             if ContactInfo::is_valid_address(&node.info.rpc, &socket_addr_space) {
                 assert!(ContactInfo::is_valid_address(
@@ -593,6 +654,23 @@ impl Validator {
                     accountsdb_repl_service_config.clone(), bank_notification_receiver, bank_forks.clone())
             });
 
+            let rpc_reads_service =
+                config
+                    .rpc_reads_service_config
+                    .as_ref()
+                    .map(|rpc_reads_service_config| {
+                        let (bank_notification_sender, bank_notification_receiver) = unbounded();
+                        bank_notification_senders.push(bank_notification_sender);
+                        RpcReadsService::new(
+                            rpc_reads_service_config.clone(),
+                            bank_notification_receiver,
+                            bank_forks.clone(),
+                            block_commitment_cache.clone(),
+                            optimistically_confirmed_bank.clone(),
+                            blockstore.clone(),
+                        )
+                    });
+
             let (bank_notification_sender, bank_notification_receiver) = unbounded();
             let confirmed_bank_subscribers = if !bank_notification_senders.is_empty() {
                 Some(Arc::new(RwLock::new(bank_notification_senders)))
@@ -620,7 +698,7 @@ impl Validator {
                     leader_schedule_cache.clone(),
                     max_complete_transaction_status_slot,
                 )),
-                if config.rpc_config.minimal_api {
+                if config.rpc_config.minimal_api || config.rpc_config.snapshot_only {
                     None
                 } else {
                     let (trigger, pubsub_service) = PubSubService::new(
@@ -646,9 +724,10 @@ impl Validator {
                 )),
                 Some(bank_notification_sender),
                 accountsdb_repl_service,
+                rpc_reads_service,
             )
         } else {
-            (None, None, None, None, None)
+            (None, None, None, None, None, None)
         };
 
         if config.dev_halt_at_slot.is_some() {
@@ -683,6 +762,7 @@ impl Validator {
         let serve_repair_service = ServeRepairService::new(
             &serve_repair,
             Some(blockstore.clone()),
+            Some(bank_forks.clone()),
             node.sockets.serve_repair,
             socket_addr_space,
             &exit,
@@ -749,6 +829,7 @@ impl Validator {
             config.poh_pinned_cpu_core,
             config.poh_hashes_per_batch,
             record_receiver,
+            config.poh_manual_ticks.clone(),
         );
         assert_eq!(
             blockstore.new_shreds_signals.len(),
@@ -773,6 +854,7 @@ impl Validator {
             RpcCompletedSlotsService::spawn(completed_slots_receiver, rpc_subscriptions.clone());
 
         let (replay_vote_sender, replay_vote_receiver) = unbounded();
+        let (warp_slot_sender, warp_slot_receiver) = channel();
         let tvu = Tvu::new(
             vote_account,
             authorized_voter_keypairs,
@@ -822,7 +904,7 @@ impl Validator {
             gossip_verified_vote_hash_receiver,
             verified_vote_receiver,
             replay_vote_sender.clone(),
-            completed_data_sets_sender,
+            completed_data_sets_sender.clone(),
             bank_notification_sender.clone(),
             cluster_confirmed_slot_receiver,
             TvuConfig {
@@ -841,11 +923,20 @@ impl Validator {
                 wait_for_vote_to_start_leader,
                 accounts_shrink_ratio: config.accounts_shrink_ratio,
                 disable_epoch_boundary_optimization: config.disable_epoch_boundary_optimization,
+                verify_produced_blocks: config.verify_produced_blocks,
+                leader_slot_reports: config.leader_slot_reports.clone(),
+                disable_duplicate_slot_dumping: config.disable_duplicate_slot_dumping,
+                dumped_slots: config.dumped_slots.clone(),
+                block_metadata_notifier: accountsdb_plugin_service
+                    .as_ref()
+                    .and_then(|service| service.get_block_metadata_notifier()),
+                accounts_hash_audit: config.accounts_hash_audit.clone(),
             },
             &max_slots,
             &cost_model,
             accounts_package_channel,
             last_full_snapshot_slot,
+            warp_slot_receiver,
         );
 
         let tpu = Tpu::new(
@@ -864,15 +955,24 @@ impl Validator {
             &exit,
             node.info.shred_version,
             vote_tracker,
-            bank_forks,
+            bank_forks.clone(),
             verified_vote_sender,
             gossip_verified_vote_hash_sender,
             replay_vote_receiver,
             replay_vote_sender,
             bank_notification_sender,
-            config.tpu_coalesce_ms,
+            ReceiverConfig {
+                coalesce_ms: config.tpu_coalesce_ms,
+                max_coalesce_packets: config.tpu_coalesce_max_packets,
+                recv_buffer_size: config.tpu_recv_buffer_size,
+                recv_backend: config.tpu_recv_backend,
+                autotune_batch_size: config.tpu_autotune_recv_batch_size,
+            },
             cluster_confirmed_slot_sender,
             &cost_model,
+            config.external_block_producer_config.clone(),
+            config.leader_slot_reports.clone(),
+            Some(completed_data_sets_sender),
         );
 
         datapoint_info!("validator-new", ("id", id.to_string(), String));
@@ -899,7 +999,10 @@ impl Validator {
             ip_echo_server,
             validator_exit: config.validator_exit.clone(),
             cluster_info,
+            warp_slot_sender,
+            bank_forks,
             accountsdb_repl_service,
+            rpc_reads_service,
             accountsdb_plugin_service,
         }
     }
@@ -1018,6 +1121,10 @@ impl Validator {
                 .expect("accountsdb_repl_service");
         }
 
+        if let Some(rpc_reads_service) = self.rpc_reads_service {
+            rpc_reads_service.join().expect("rpc_reads_service");
+        }
+
         if let Some(accountsdb_plugin_service) = self.accountsdb_plugin_service {
             accountsdb_plugin_service
                 .join()
@@ -1216,6 +1323,7 @@ fn new_banks_from_ledger(
     )
     .expect("Failed to open ledger database");
     blockstore.set_no_compaction(config.no_rocksdb_compaction);
+    blockstore.set_write_versioned_protobuf(config.blockstore_versioned_protobuf);
 
     let restored_tower = Tower::restore(config.tower_storage.as_ref(), validator_identity);
     if let Ok(tower) = &restored_tower {
@@ -1255,6 +1363,9 @@ fn new_banks_from_ledger(
         shrink_ratio: config.accounts_shrink_ratio,
         accounts_db_test_hash_calculation: config.accounts_db_test_hash_calculation,
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
+        executor_cache_capacity: Some(config.executor_cache_capacity),
+        compute_budget: config.compute_budget,
+        queue_conflicting_transactions: config.queue_conflicting_transactions,
         ..blockstore_processor::ProcessOptions::default()
     };
 
@@ -1264,6 +1375,7 @@ fn new_banks_from_ledger(
                 blockstore.clone(),
                 exit,
                 config.rpc_config.enable_cpi_and_log_storage,
+                config.rpc_config.max_stored_log_messages_bytes,
             )
         } else {
             TransactionHistoryServices::default()
@@ -1455,6 +1567,7 @@ fn initialize_rpc_transaction_history_services(
     blockstore: Arc<Blockstore>,
     exit: &Arc<AtomicBool>,
     enable_cpi_and_log_storage: bool,
+    max_stored_log_messages_bytes: Option<usize>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
     let (transaction_status_sender, transaction_status_receiver) = unbounded();
@@ -1466,6 +1579,7 @@ fn initialize_rpc_transaction_history_services(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
         blockstore.clone(),
+        max_stored_log_messages_bytes,
         exit,
     ));
 
@@ -1,14 +1,26 @@
-use crate::tower_storage::{SavedTower, TowerStorage};
-use solana_gossip::cluster_info::ClusterInfo;
+use crate::{
+    banking_stage::FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET,
+    tower_storage::{SavedTower, TowerStorage},
+};
+use bincode::serialize;
+use solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo};
 use solana_measure::measure::Measure;
 use solana_poh::poh_recorder::PohRecorder;
 use solana_runtime::bank_forks::BankForks;
-use solana_sdk::{clock::Slot, transaction::Transaction};
+use solana_sdk::{
+    clock::{Slot, NUM_CONSECUTIVE_LEADER_SLOTS},
+    transaction::Transaction,
+};
+use solana_streamer::sendmmsg::batch_send;
 use std::{
+    net::{SocketAddr, UdpSocket},
     sync::{mpsc::Receiver, Arc, Mutex, RwLock},
     thread::{self, Builder, JoinHandle},
 };
 
+// Number of upcoming leaders the votes are sent to, on top of the local TPU
+const NUM_UPCOMING_LEADERS_TO_SEND_VOTES: u64 = 2;
+
 pub enum VoteOp {
     PushVote {
         tx: Transaction,
@@ -45,14 +57,20 @@ impl VotingService {
         let thread_hdl = Builder::new()
             .name("sol-vote-service".to_string())
             .spawn(move || {
+                let vote_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
                 for vote_op in vote_receiver.iter() {
+                    // The votes queued up while the previous ones were sent go out together
+                    let vote_ops: Vec<_> = std::iter::once(vote_op)
+                        .chain(vote_receiver.try_iter())
+                        .collect();
                     let rooted_bank = bank_forks.read().unwrap().root_bank().clone();
                     let send_to_tpu_vote_port = rooted_bank.send_to_tpu_vote_port_enabled();
-                    Self::handle_vote(
+                    Self::handle_votes(
                         &cluster_info,
                         &poh_recorder,
                         tower_storage.as_ref(),
-                        vote_op,
+                        &vote_socket,
+                        vote_ops,
                         send_to_tpu_vote_port,
                     );
                 }
@@ -61,14 +79,22 @@ impl VotingService {
         Self { thread_hdl }
     }
 
-    pub fn handle_vote(
+    /// Saves the tower of the latest vote, then sends the vote transactions in a batch to the
+    /// local TPU and the upcoming leaders, and pushes them to gossip
+    pub fn handle_votes(
         cluster_info: &ClusterInfo,
         poh_recorder: &Mutex<PohRecorder>,
         tower_storage: &dyn TowerStorage,
-        vote_op: VoteOp,
+        vote_socket: &UdpSocket,
+        vote_ops: Vec<VoteOp>,
         send_to_tpu_vote_port: bool,
     ) {
-        if let VoteOp::PushVote { saved_tower, .. } = &vote_op {
+        // Each saved tower supersedes the previous ones, only the latest needs to be stored
+        let saved_tower = vote_ops.iter().rev().find_map(|vote_op| match vote_op {
+            VoteOp::PushVote { saved_tower, .. } => Some(saved_tower),
+            VoteOp::RefreshVote { .. } => None,
+        });
+        if let Some(saved_tower) = saved_tower {
             let mut measure = Measure::start("tower_save-ms");
             if let Err(err) = tower_storage.store(saved_tower) {
                 error!("Unable to save tower to storage: {:?}", err);
@@ -78,26 +104,72 @@ impl VotingService {
             inc_new_counter_info!("tower_save-ms", measure.as_ms() as usize);
         }
 
-        let target_address = if send_to_tpu_vote_port {
-            crate::banking_stage::next_leader_tpu_vote(cluster_info, poh_recorder)
-        } else {
-            crate::banking_stage::next_leader_tpu(cluster_info, poh_recorder)
-        };
-        let _ = cluster_info.send_transaction(vote_op.tx(), target_address);
+        let target_addresses =
+            Self::vote_target_addresses(cluster_info, poh_recorder, send_to_tpu_vote_port);
+        let packets: Vec<_> = vote_ops
+            .iter()
+            .filter_map(|vote_op| serialize(vote_op.tx()).ok())
+            .flat_map(|buf| {
+                target_addresses
+                    .iter()
+                    .map(move |address| (buf.clone(), *address))
+            })
+            .collect();
+        inc_new_counter_info!("voting_service-batch_votes", vote_ops.len());
+        if let Err(err) = batch_send(vote_socket, &packets) {
+            warn!("Failed to send vote transactions: {:?}", err);
+        }
+
+        for vote_op in vote_ops {
+            match vote_op {
+                VoteOp::PushVote {
+                    tx, tower_slots, ..
+                } => {
+                    cluster_info.push_vote(&tower_slots, tx);
+                }
+                VoteOp::RefreshVote {
+                    tx,
+                    last_voted_slot,
+                } => {
+                    cluster_info.refresh_vote(tx, last_voted_slot);
+                }
+            }
+        }
+    }
 
-        match vote_op {
-            VoteOp::PushVote {
-                tx, tower_slots, ..
-            } => {
-                cluster_info.push_vote(&tower_slots, tx);
+    /// The local TPU and the TPUs of the upcoming leaders, without duplicates
+    fn vote_target_addresses(
+        cluster_info: &ClusterInfo,
+        poh_recorder: &Mutex<PohRecorder>,
+        send_to_tpu_vote_port: bool,
+    ) -> Vec<SocketAddr> {
+        let tpu = |contact_info: &ContactInfo| {
+            if send_to_tpu_vote_port {
+                contact_info.tpu_vote
+            } else {
+                contact_info.tpu
             }
-            VoteOp::RefreshVote {
-                tx,
-                last_voted_slot,
-            } => {
-                cluster_info.refresh_vote(tx, last_voted_slot);
+        };
+        let mut target_addresses = vec![tpu(&cluster_info.my_contact_info())];
+        let upcoming_leaders: Vec<_> = {
+            let poh_recorder = poh_recorder.lock().unwrap();
+            (0..NUM_UPCOMING_LEADERS_TO_SEND_VOTES)
+                .filter_map(|i| {
+                    poh_recorder.leader_after_n_slots(
+                        FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET
+                            + i * NUM_CONSECUTIVE_LEADER_SLOTS,
+                    )
+                })
+                .collect()
+        };
+        for leader in upcoming_leaders {
+            if let Some(address) = cluster_info.lookup_contact_info(&leader, tpu) {
+                if !target_addresses.contains(&address) {
+                    target_addresses.push(address);
+                }
             }
         }
+        target_addresses
     }
 
     pub fn join(self) -> thread::Result<()> {
@@ -13,7 +13,7 @@ use {
     crossbeam_channel::{
         unbounded, Receiver as CrossbeamReceiver, RecvTimeoutError, Sender as CrossbeamSender,
     },
-    rayon::{prelude::*, ThreadPool},
+    rayon::prelude::*,
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         blockstore::{self, Blockstore, BlockstoreInsertionMetrics, MAX_DATA_SHREDS_PER_SLOT},
@@ -23,7 +23,10 @@ use {
     solana_measure::measure::Measure,
     solana_metrics::{inc_new_counter_debug, inc_new_counter_error},
     solana_perf::packet::{Packet, Packets},
-    solana_rayon_threadlimit::get_thread_count,
+    solana_rayon_threadlimit::{
+        get_thread_count,
+        thread_pools::{ManagedThreadPool, WINDOW_THREAD_POOL},
+    },
     solana_runtime::{bank::Bank, bank_forks::BankForks},
     solana_sdk::{clock::Slot, packet::PACKET_DATA_SIZE, pubkey::Pubkey},
     std::collections::HashSet,
@@ -355,7 +358,7 @@ fn recv_window<F>(
     verified_receiver: &CrossbeamReceiver<Vec<Packets>>,
     retransmit_sender: &Sender<Vec<Shred>>,
     shred_filter: F,
-    thread_pool: &ThreadPool,
+    thread_pool: &ManagedThreadPool,
     stats: &mut ReceiveWindowStats,
 ) -> Result<()>
 where
@@ -645,10 +648,14 @@ impl WindowService {
             .spawn(move || {
                 let _exit = Finalizer::new(exit.clone());
                 trace!("{}: RECV_WINDOW started", id);
-                let thread_pool = rayon::ThreadPoolBuilder::new()
-                    .num_threads(get_thread_count())
-                    .build()
-                    .unwrap();
+                let thread_pool =
+                    ManagedThreadPool::new(WINDOW_THREAD_POOL, get_thread_count(), |num_threads| {
+                        rayon::ThreadPoolBuilder::new()
+                            .num_threads(num_threads)
+                            .thread_name(|i| format!("solana-window-{}", i))
+                            .build()
+                            .unwrap()
+                    });
                 let mut now = Instant::now();
                 let handle_error = || {
                     inc_new_counter_error!("solana-window-error", 1, 1);
@@ -920,6 +920,7 @@ mod tests {
         let abs_request_handler = AbsRequestHandler {
             snapshot_request_handler,
             pruned_banks_receiver,
+            accounts_hash_audit: None,
         };
 
         let exit = Arc::new(AtomicBool::new(false));
@@ -6,17 +6,35 @@ use solana_runtime::{
     snapshot_package::SnapshotType,
     snapshot_utils::{self, ArchiveFormat},
 };
-use solana_sdk::{clock::Slot, genesis_config::DEFAULT_GENESIS_ARCHIVE, hash::Hash};
-use std::fs::{self, File};
+use solana_sdk::{
+    clock::Slot,
+    genesis_config::DEFAULT_GENESIS_ARCHIVE,
+    hash::{hash, hashv, Hash},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
 use std::io;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    mpsc::{channel, RecvTimeoutError},
+    Arc, Mutex,
+};
+use std::thread::{self, sleep};
 use std::time::{Duration, Instant};
 
 static TRUCK: Emoji = Emoji("🚚 ", "");
 static SPARKLE: Emoji = Emoji("✨ ", "");
 
+/// Default size of the chunks of a file downloaded from several peers
+pub const DEFAULT_DOWNLOAD_CHUNK_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Consecutive chunk failures after which a peer is no longer downloaded from
+const MAX_PEER_CHUNK_FAILURES: usize = 3;
+
 /// Creates a new process bar for processing that will take an unknown amount of time
 fn new_spinner_progress_bar() -> ProgressBar {
     let progress_bar = ProgressBar::new(42);
@@ -49,8 +67,23 @@ pub struct DownloadProgressRecord {
     pub notification_count: u64,
 }
 
-type DownloadProgressCallback<'a> = Box<dyn FnMut(&DownloadProgressRecord) -> bool + 'a>;
-type DownloadProgressCallbackOption<'a> = Option<DownloadProgressCallback<'a>>;
+pub type DownloadProgressCallback<'a> = Box<dyn FnMut(&DownloadProgressRecord) -> bool + 'a>;
+pub type DownloadProgressCallbackOption<'a> = Option<DownloadProgressCallback<'a>>;
+
+/// Returns the path `destination_file` is downloaded to before it is complete
+fn temp_download_path(destination_file: &Path, extension: &str) -> PathBuf {
+    let mut temp_destination_file = destination_file.to_path_buf();
+    temp_destination_file.set_file_name(format!(
+        "tmp-{}{}",
+        destination_file
+            .file_name()
+            .expect("file_name")
+            .to_str()
+            .expect("to_str"),
+        extension,
+    ));
+    temp_destination_file
+}
 
 /// This callback allows the caller to get notified of the download progress modelled by DownloadProgressRecord
 /// Return "true" to continue the download
@@ -69,15 +102,7 @@ pub fn download_file<'a, 'b>(
     fs::create_dir_all(destination_file.parent().expect("parent"))
         .map_err(|err| err.to_string())?;
 
-    let mut temp_destination_file = destination_file.to_path_buf();
-    temp_destination_file.set_file_name(format!(
-        "tmp-{}",
-        destination_file
-            .file_name()
-            .expect("file_name")
-            .to_str()
-            .expect("to_str")
-    ));
+    let temp_destination_file = temp_download_path(destination_file, "");
 
     let progress_bar = new_spinner_progress_bar();
     if use_progress_bar {
@@ -247,6 +272,31 @@ pub fn download_genesis_if_missing(
     }
 }
 
+fn snapshot_archive_path(
+    snapshot_archives_dir: &Path,
+    desired_snapshot_hash: (Slot, Hash),
+    snapshot_type: SnapshotType,
+    archive_format: ArchiveFormat,
+) -> PathBuf {
+    match snapshot_type {
+        SnapshotType::FullSnapshot => snapshot_utils::build_full_snapshot_archive_path(
+            snapshot_archives_dir.to_path_buf(),
+            desired_snapshot_hash.0,
+            &desired_snapshot_hash.1,
+            archive_format,
+        ),
+        SnapshotType::IncrementalSnapshot(base_slot) => {
+            snapshot_utils::build_incremental_snapshot_archive_path(
+                snapshot_archives_dir.to_path_buf(),
+                base_slot,
+                desired_snapshot_hash.0,
+                &desired_snapshot_hash.1,
+                archive_format,
+            )
+        }
+    }
+}
+
 /// Download a snapshot archive from `rpc_addr`.  Use `snapshot_type` to specify downloading either
 /// a full snapshot or an incremental snapshot.
 pub fn download_snapshot_archive<'a, 'b>(
@@ -271,23 +321,12 @@ pub fn download_snapshot_archive<'a, 'b>(
         ArchiveFormat::TarBzip2,
         ArchiveFormat::Tar, // `solana-test-validator` creates uncompressed snapshots
     ] {
-        let destination_path = match snapshot_type {
-            SnapshotType::FullSnapshot => snapshot_utils::build_full_snapshot_archive_path(
-                snapshot_archives_dir.to_path_buf(),
-                desired_snapshot_hash.0,
-                &desired_snapshot_hash.1,
-                archive_format,
-            ),
-            SnapshotType::IncrementalSnapshot(base_slot) => {
-                snapshot_utils::build_incremental_snapshot_archive_path(
-                    snapshot_archives_dir.to_path_buf(),
-                    base_slot,
-                    desired_snapshot_hash.0,
-                    &desired_snapshot_hash.1,
-                    archive_format,
-                )
-            }
-        };
+        let destination_path = snapshot_archive_path(
+            snapshot_archives_dir,
+            desired_snapshot_hash,
+            snapshot_type,
+            archive_format,
+        );
 
         if destination_path.is_file() {
             return Ok(());
@@ -312,3 +351,672 @@ pub fn download_snapshot_archive<'a, 'b>(
         desired_snapshot_hash.0, rpc_addr
     ))
 }
+
+/// Download a snapshot archive from all the `rpc_addrs` in parallel, in chunks of `chunk_size`
+/// bytes.  An interrupted download is resumed from the chunks already downloaded.  See
+/// `download_file_from_peers()`.
+#[allow(clippy::too_many_arguments)]
+pub fn download_snapshot_archive_from_peers<'a, 'b>(
+    rpc_addrs: &[SocketAddr],
+    snapshot_archives_dir: &Path,
+    desired_snapshot_hash: (Slot, Hash),
+    snapshot_type: SnapshotType,
+    maximum_full_snapshot_archives_to_retain: usize,
+    maximum_incremental_snapshot_archives_to_retain: usize,
+    chunk_size: u64,
+    use_progress_bar: bool,
+    progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
+) -> Result<(), String> {
+    snapshot_utils::purge_old_snapshot_archives(
+        snapshot_archives_dir,
+        maximum_full_snapshot_archives_to_retain,
+        maximum_incremental_snapshot_archives_to_retain,
+    );
+
+    for archive_format in [
+        ArchiveFormat::TarZstd,
+        ArchiveFormat::TarGzip,
+        ArchiveFormat::TarBzip2,
+        ArchiveFormat::Tar, // `solana-test-validator` creates uncompressed snapshots
+    ] {
+        let destination_path = snapshot_archive_path(
+            snapshot_archives_dir,
+            desired_snapshot_hash,
+            snapshot_type,
+            archive_format,
+        );
+
+        if destination_path.is_file() {
+            return Ok(());
+        }
+
+        let file_name = destination_path.file_name().unwrap().to_str().unwrap();
+        let urls: Vec<_> = rpc_addrs
+            .iter()
+            .map(|rpc_addr| format!("http://{}/{}", rpc_addr, file_name))
+            .collect();
+        match download_file_from_peers(
+            &urls,
+            &destination_path,
+            chunk_size,
+            use_progress_bar,
+            progress_notify_callback,
+        ) {
+            Ok(()) => return Ok(()),
+            Err(err) => info!("{}", err),
+        }
+    }
+    Err(format!(
+        "Failed to download a snapshot archive for slot {} from {:?}",
+        desired_snapshot_hash.0, rpc_addrs
+    ))
+}
+
+/// Length of the file a peer serves and the hashes of its chunks, peers serving the same bytes
+/// have the same probe
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FileProbe {
+    file_length: u64,
+    chunk_hashes: Vec<Hash>,
+}
+
+impl FileProbe {
+    fn fingerprint(&self) -> Hash {
+        let chunk_hashes: Vec<_> = self.chunk_hashes.iter().map(Hash::as_ref).collect();
+        hashv(&chunk_hashes)
+    }
+}
+
+fn num_chunks(file_length: u64, chunk_size: u64) -> u64 {
+    (file_length + chunk_size - 1) / chunk_size
+}
+
+/// Requests `range` of the file at `url`, returns the length of the whole file along with the
+/// response streaming the bytes of the range
+fn get_range(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    range: &str,
+) -> Result<(u64, reqwest::blocking::Response), String> {
+    let response = client
+        .get(url)
+        .header(reqwest::header::RANGE, format!("bytes={}", range))
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(format!("{} does not serve byte ranges", url));
+    }
+    // `Content-Range: bytes <first>-<last>/<file length>`
+    let file_length = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|content_range| content_range.to_str().ok())
+        .and_then(|content_range| content_range.rsplit('/').next())
+        .and_then(|file_length| u64::from_str(file_length).ok())
+        .ok_or_else(|| format!("{} returned an invalid Content-Range", url))?;
+    Ok((file_length, response))
+}
+
+/// Requests the hashes of the `chunk_size` bytes chunks of the file at `url`, which the RPC file
+/// server returns one per line for a `chunk-hashes` query
+fn get_chunk_hashes(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    chunk_size: u64,
+) -> Result<Vec<Hash>, String> {
+    client
+        .get(&format!("{}?chunk-hashes={}", url, chunk_size))
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|err| err.to_string())?
+        .lines()
+        .map(|chunk_hash| {
+            Hash::from_str(chunk_hash).map_err(|_| format!("{} returned an invalid hash", url))
+        })
+        .collect()
+}
+
+/// Reads the length of the file a peer serves and the hashes of its chunks
+fn probe_peer(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    chunk_size: u64,
+) -> Result<FileProbe, String> {
+    let (file_length, _response) = get_range(client, url, "0-0")?;
+    let chunk_hashes = get_chunk_hashes(client, url, chunk_size)?;
+    if chunk_hashes.len() as u64 != num_chunks(file_length, chunk_size) {
+        return Err(format!(
+            "{} returned {} chunk hashes for {} bytes",
+            url,
+            chunk_hashes.len(),
+            file_length
+        ));
+    }
+    Ok(FileProbe {
+        file_length,
+        chunk_hashes,
+    })
+}
+
+/// Returns the chunks of a partial download that already match their hash
+fn verified_chunks(temp_file: &mut File, probe: &FileProbe, chunk_size: u64) -> HashSet<u64> {
+    let mut chunks = HashSet::new();
+    for (index, chunk_hash) in (0..).zip(probe.chunk_hashes.iter()) {
+        let offset = index * chunk_size;
+        let mut bytes = vec![0; chunk_size.min(probe.file_length - offset) as usize];
+        if temp_file.seek(SeekFrom::Start(offset)).is_ok()
+            && temp_file.read_exact(&mut bytes).is_ok()
+            && hash(&bytes) == *chunk_hash
+        {
+            chunks.insert(index);
+        }
+    }
+    chunks
+}
+
+/// Downloads chunk `index` of the file at `url`, and writes it to `temp_file` if it matches its
+/// hash.  `downloaded_bytes` counts the bytes of the chunk as they are received.
+#[allow(clippy::too_many_arguments)]
+fn download_chunk(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    index: u64,
+    chunk_size: u64,
+    probe: &FileProbe,
+    temp_file: &Mutex<File>,
+    downloaded_bytes: &AtomicU64,
+    abort: &AtomicBool,
+) -> Result<(), String> {
+    let offset = index * chunk_size;
+    let length = chunk_size.min(probe.file_length - offset);
+    let (file_length, mut response) =
+        get_range(client, url, &format!("{}-{}", offset, offset + length - 1))?;
+    if file_length != probe.file_length {
+        return Err(format!("{} no longer serves the same file", url));
+    }
+
+    let mut bytes = Vec::with_capacity(length as usize);
+    let mut buffer = [0; 64 * 1024];
+    let result = loop {
+        if abort.load(Ordering::Relaxed) {
+            break Err("Download is aborted by the caller".to_string());
+        }
+        match response.read(&mut buffer) {
+            Ok(0) => break Ok(()),
+            Ok(n) if (bytes.len() + n) as u64 <= length => {
+                bytes.extend_from_slice(&buffer[..n]);
+                downloaded_bytes.fetch_add(n as u64, Ordering::Relaxed);
+            }
+            Ok(_) => break Err(format!("{} returned an oversized chunk", url)),
+            Err(err) => break Err(err.to_string()),
+        }
+    }
+    .and_then(|()| {
+        if bytes.len() as u64 != length {
+            return Err(format!("{} returned a truncated chunk", url));
+        }
+        if hash(&bytes) != probe.chunk_hashes[index as usize] {
+            return Err(format!("{} returned a chunk not matching its hash", url));
+        }
+        let mut temp_file = temp_file.lock().unwrap();
+        temp_file
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| temp_file.write_all(&bytes))
+            .map_err(|err| err.to_string())
+    });
+    if result.is_err() {
+        downloaded_bytes.fetch_sub(bytes.len() as u64, Ordering::Relaxed);
+    }
+    result
+}
+
+/// Downloads the file at `urls` in chunks of `chunk_size` bytes, fetching the chunks from all the
+/// peers serving the same file in parallel.  The chunks are HTTP range requests to the RPC file
+/// server of the peers.
+///
+/// Every chunk is verified against the hash the peers publish for it.  Downloading the same file
+/// again after an interruption only fetches the chunks that don't match their hash yet.  Falls
+/// back to downloading from the first peer if no peer serves byte ranges and chunk hashes.
+///
+/// Like `download_file()`, `progress_notify_callback` is notified of the download progress and
+/// aborts the download by returning "false".
+pub fn download_file_from_peers<'a, 'b>(
+    urls: &[String],
+    destination_file: &Path,
+    chunk_size: u64,
+    use_progress_bar: bool,
+    progress_notify_callback: &'a mut DownloadProgressCallbackOption<'b>,
+) -> Result<(), String> {
+    if destination_file.is_file() {
+        return Err(format!("{:?} already exists", destination_file));
+    }
+    if urls.is_empty() || chunk_size == 0 {
+        return Err("No peers to download from".to_string());
+    }
+    let download_start = Instant::now();
+
+    fs::create_dir_all(destination_file.parent().expect("parent"))
+        .map_err(|err| err.to_string())?;
+
+    // Only peers serving the very same bytes can be downloaded from together, pick the file most
+    // peers serve
+    let client = reqwest::blocking::Client::new();
+    let mut peers_by_probe: HashMap<FileProbe, Vec<String>> = HashMap::new();
+    for url in urls {
+        match probe_peer(&client, url, chunk_size) {
+            Ok(probe) => peers_by_probe.entry(probe).or_default().push(url.clone()),
+            Err(err) => info!("Unable to probe {}: {}", url, err),
+        }
+    }
+    let (probe, peers) = match peers_by_probe
+        .into_iter()
+        .max_by_key(|(_probe, peers)| peers.len())
+    {
+        Some(peers) => peers,
+        None => {
+            return download_file(
+                &urls[0],
+                destination_file,
+                use_progress_bar,
+                progress_notify_callback,
+            )
+        }
+    };
+    let file_length = probe.file_length;
+    info!(
+        "Downloading {} bytes to {:?} from {} peer{}: {:?}",
+        file_length,
+        destination_file,
+        peers.len(),
+        if peers.len() > 1 { "s" } else { "" },
+        peers,
+    );
+
+    // The probe is part of the temporary file name so that a partial download of another
+    // version of the file is not resumed
+    let temp_destination_file =
+        temp_download_path(destination_file, &format!("-{}", probe.fingerprint()));
+    let resuming = temp_destination_file.is_file();
+    let mut temp_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(&temp_destination_file)
+        .and_then(|file| file.set_len(file_length).map(|()| file))
+        .map_err(|err| format!("Unable to open {:?}: {:?}", temp_destination_file, err))?;
+
+    let num_chunks = num_chunks(file_length, chunk_size);
+    let done_chunks = if resuming {
+        verified_chunks(&mut temp_file, &probe, chunk_size)
+    } else {
+        HashSet::new()
+    };
+    let pending_chunks: VecDeque<_> = (0..num_chunks)
+        .filter(|index| !done_chunks.contains(index))
+        .collect();
+    let done_bytes: u64 = done_chunks
+        .iter()
+        .map(|index| chunk_size.min(file_length - index * chunk_size))
+        .sum();
+    if !done_chunks.is_empty() {
+        info!(
+            "Resuming download of {:?}, {} of {} chunks are downloaded",
+            destination_file,
+            done_chunks.len(),
+            num_chunks
+        );
+    }
+
+    let progress_bar = if use_progress_bar {
+        let progress_bar = ProgressBar::new(file_length);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green}{msg_wide}[{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+                )
+                .progress_chars("=> "),
+        );
+        progress_bar.set_message(format!("{}Downloading~ {:?}", TRUCK, destination_file));
+        progress_bar.set_position(done_bytes);
+        progress_bar
+    } else {
+        ProgressBar::hidden()
+    };
+
+    let probe = Arc::new(probe);
+    let pending_chunks = Arc::new(Mutex::new(pending_chunks));
+    let in_flight_chunks = Arc::new(AtomicUsize::new(0));
+    let temp_file = Arc::new(Mutex::new(temp_file));
+    let downloaded_bytes = Arc::new(AtomicU64::new(0));
+    let abort = Arc::new(AtomicBool::new(false));
+    // Disconnected once all the workers returned
+    let (worker_exit_sender, worker_exit_receiver) = channel::<()>();
+    let workers: Vec<_> = peers
+        .into_iter()
+        .enumerate()
+        .map(|(i, url)| {
+            let client = client.clone();
+            let probe = probe.clone();
+            let pending_chunks = pending_chunks.clone();
+            let in_flight_chunks = in_flight_chunks.clone();
+            let temp_file = temp_file.clone();
+            let downloaded_bytes = downloaded_bytes.clone();
+            let abort = abort.clone();
+            let worker_exit_sender = worker_exit_sender.clone();
+            thread::Builder::new()
+                .name(format!("solDownload{:02}", i))
+                .spawn(move || {
+                    let _worker_exit_sender = worker_exit_sender;
+                    let mut failures = 0;
+                    while !abort.load(Ordering::Relaxed) {
+                        // Wait for the chunks being downloaded by the other peers to complete,
+                        // they are queued again if they fail
+                        let index = {
+                            let mut pending_chunks = pending_chunks.lock().unwrap();
+                            let index = pending_chunks.pop_front();
+                            if index.is_some() {
+                                in_flight_chunks.fetch_add(1, Ordering::Relaxed);
+                            }
+                            index
+                        };
+                        let index = match index {
+                            Some(index) => index,
+                            None if in_flight_chunks.load(Ordering::Relaxed) > 0 => {
+                                sleep(Duration::from_millis(100));
+                                continue;
+                            }
+                            None => return,
+                        };
+
+                        let result = download_chunk(
+                            &client,
+                            &url,
+                            index,
+                            chunk_size,
+                            &probe,
+                            &temp_file,
+                            &downloaded_bytes,
+                            &abort,
+                        );
+                        if let Err(err) = result {
+                            pending_chunks.lock().unwrap().push_back(index);
+                            in_flight_chunks.fetch_sub(1, Ordering::Relaxed);
+                            if abort.load(Ordering::Relaxed) {
+                                return;
+                            }
+                            warn!("Failed to download chunk {} from {}: {}", index, url, err);
+                            failures += 1;
+                            if failures >= MAX_PEER_CHUNK_FAILURES {
+                                warn!("No longer downloading from {}", url);
+                                return;
+                            }
+                        } else {
+                            failures = 0;
+                            in_flight_chunks.fetch_sub(1, Ordering::Relaxed);
+                        }
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    drop(worker_exit_sender);
+
+    // Notify the progress like `download_file()` does, until all the workers returned
+    let mut last_print = Instant::now();
+    let mut last_print_bytes = 0;
+    let mut notification_count = 0;
+    while let Err(RecvTimeoutError::Timeout) =
+        worker_exit_receiver.recv_timeout(Duration::from_millis(100))
+    {
+        let current_bytes = done_bytes + downloaded_bytes.load(Ordering::Relaxed);
+        progress_bar.set_position(current_bytes);
+        if last_print.elapsed().as_secs() <= 5 {
+            continue;
+        }
+        let last_throughput = current_bytes.saturating_sub(last_print_bytes) as f32
+            / last_print.elapsed().as_secs_f32();
+        notification_count += 1;
+        let progress_record = DownloadProgressRecord {
+            elapsed_time: download_start.elapsed(),
+            last_elapsed_time: last_print.elapsed(),
+            last_throughput,
+            total_throughput: current_bytes.saturating_sub(done_bytes) as f32
+                / download_start.elapsed().as_secs_f32(),
+            total_bytes: file_length as usize,
+            current_bytes: current_bytes as usize,
+            percentage_done: 100f32 * (current_bytes as f32 / file_length as f32),
+            estimated_remaining_time: if last_throughput > 0_f32 {
+                file_length.saturating_sub(current_bytes) as f32 / last_throughput
+            } else {
+                f32::MAX
+            },
+            notification_count,
+        };
+        last_print = Instant::now();
+        last_print_bytes = current_bytes;
+        if !use_progress_bar {
+            info!(
+                "downloaded {} bytes {:.1}% {:.1} bytes/s",
+                current_bytes, progress_record.percentage_done, progress_record.last_throughput,
+            );
+        }
+        if let Some(callback) = progress_notify_callback {
+            if !callback(&progress_record) {
+                info!("Download is aborted by the caller");
+                abort.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    progress_bar.finish_and_clear();
+
+    if abort.load(Ordering::Relaxed) {
+        return Err(format!(
+            "Download of {:?} is aborted by the caller, the download can be resumed",
+            destination_file
+        ));
+    }
+    let missing_chunks = pending_chunks.lock().unwrap().len();
+    if missing_chunks > 0 {
+        return Err(format!(
+            "Failed to download {} of {} chunks of {:?}, the download can be resumed",
+            missing_chunks, num_chunks, destination_file
+        ));
+    }
+    temp_file
+        .lock()
+        .unwrap()
+        .sync_all()
+        .map_err(|err| format!("Unable to write {:?}: {:?}", temp_destination_file, err))?;
+    info!(
+        "  {}{}",
+        SPARKLE,
+        format!(
+            "Downloaded {:?} ({} bytes) in {:?}",
+            destination_file,
+            file_length,
+            Instant::now().duration_since(download_start),
+        )
+    );
+
+    std::fs::rename(temp_destination_file, destination_file)
+        .map_err(|err| format!("Unable to rename: {:?}", err))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            io::{BufRead, BufReader},
+            net::TcpListener,
+        },
+    };
+
+    /// Serves `file` over HTTP like the RPC file server does, with the byte at `corrupt_byte`
+    /// altered but not its chunk hash, delaying each byte range by `delay`.  Returns the URL of
+    /// the file and a counter of the byte ranges served.
+    fn serve_file(
+        file: &[u8],
+        chunk_size: u64,
+        corrupt_byte: Option<usize>,
+        delay: Duration,
+    ) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let chunk_hashes: String = file
+            .chunks(chunk_size as usize)
+            .map(|chunk| format!("{}\n", hash(chunk)))
+            .collect();
+        let mut served_file = file.to_vec();
+        if let Some(corrupt_byte) = corrupt_byte {
+            served_file[corrupt_byte] ^= 0xff;
+        }
+        let served_ranges = Arc::new(AtomicUsize::new(0));
+        let served_ranges_ = served_ranges.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut range = None;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let header = header.trim().to_ascii_lowercase();
+                    if let Some(bytes) = header.strip_prefix("range: bytes=") {
+                        let (first, last) = bytes.split_once('-').unwrap();
+                        range = Some((
+                            first.parse::<usize>().unwrap(),
+                            last.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+                let (status, content_range, body) = if request_line.contains("?chunk-hashes=") {
+                    ("200 OK", String::new(), chunk_hashes.as_bytes())
+                } else {
+                    let (first, last) = range.unwrap();
+                    served_ranges_.fetch_add(1, Ordering::Relaxed);
+                    sleep(delay);
+                    (
+                        "206 Partial Content",
+                        format!(
+                            "Content-Range: bytes {}-{}/{}\r\n",
+                            first,
+                            last,
+                            served_file.len()
+                        ),
+                        &served_file[first..=last],
+                    )
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    content_range,
+                    body.len()
+                )
+                .and_then(|()| stream.write_all(body));
+            }
+        });
+        (url, served_ranges)
+    }
+
+    #[test]
+    fn test_download_file_from_peers() {
+        let file: Vec<u8> = (0..40).collect();
+        let chunk_size = 4;
+        let (good_url, _) = serve_file(&file, chunk_size, None, Duration::default());
+        let (corrupt_url, _) = serve_file(&file, chunk_size, Some(5), Duration::default());
+        let (other_file_url, _) = serve_file(&[0; 40], chunk_size, None, Duration::default());
+        let download_dir = tempfile::TempDir::new().unwrap();
+
+        // The chunks the corrupt peer alters are downloaded from the other peer serving the
+        // same chunk hashes, the peer serving another file is not downloaded from
+        let destination_file = download_dir.path().join("file");
+        download_file_from_peers(
+            &[corrupt_url.clone(), other_file_url, good_url],
+            &destination_file,
+            chunk_size,
+            false,
+            &mut None,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), file);
+
+        // No chunk is written unless it matches its hash
+        let destination_file = download_dir.path().join("corrupt_file");
+        assert!(download_file_from_peers(
+            &[corrupt_url],
+            &destination_file,
+            chunk_size,
+            false,
+            &mut None,
+        )
+        .is_err());
+        assert!(!destination_file.exists());
+    }
+
+    #[test]
+    fn test_download_file_from_peers_resume() {
+        let file: Vec<u8> = (0..40).collect();
+        let chunk_size = 4;
+        let (url, served_ranges) = serve_file(&file, chunk_size, None, Duration::default());
+        let download_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = download_dir.path().join("file");
+
+        // A partial download missing chunks 2 and 7
+        let probe = FileProbe {
+            file_length: file.len() as u64,
+            chunk_hashes: file.chunks(chunk_size as usize).map(hash).collect(),
+        };
+        let mut partial_file = file.clone();
+        partial_file[8..12].fill(0);
+        partial_file[28..32].fill(0);
+        fs::write(
+            temp_download_path(&destination_file, &format!("-{}", probe.fingerprint())),
+            partial_file,
+        )
+        .unwrap();
+
+        download_file_from_peers(&[url], &destination_file, chunk_size, false, &mut None).unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), file);
+        // The probe and the two missing chunks
+        assert_eq!(served_ranges.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_download_file_from_peers_abort() {
+        let file: Vec<u8> = (0..40).collect();
+        let chunk_size = 4;
+        let (url, _) = serve_file(&file, chunk_size, None, Duration::from_secs(1));
+        let download_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = download_dir.path().join("file");
+
+        let mut notification_count = 0;
+        let result = download_file_from_peers(
+            &[url],
+            &destination_file,
+            chunk_size,
+            false,
+            &mut Some(Box::new(|download_progress: &DownloadProgressRecord| {
+                notification_count = download_progress.notification_count;
+                assert!(download_progress.current_bytes < file.len());
+                false
+            })),
+        );
+        assert!(result.is_err());
+        assert_eq!(notification_count, 1);
+        assert!(!destination_file.exists());
+    }
+}
@@ -3,3 +3,5 @@ pub mod entry;
 pub mod poh;
 
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
@@ -429,6 +429,12 @@ impl AbiExample for std::path::PathBuf {
 }
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+impl AbiExample for IpAddr {
+    fn example() -> Self {
+        IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0))
+    }
+}
+
 impl AbiExample for SocketAddr {
     fn example() -> Self {
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0)
@@ -1,13 +1,14 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod address_generator;
 pub mod genesis_accounts;
+pub mod manifest;
 pub mod stakes;
 pub mod unlocks;
 
 use serde::{Deserialize, Serialize};
 
 /// An account where the data is encoded as a Base64 string.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Base64Account {
     pub balance: u64,
     pub owner: String,
@@ -7,7 +7,9 @@ use solana_clap_utils::{
     input_validators::{is_pubkey_or_keypair, is_rfc3339_datetime, is_slot, is_valid_percentage},
 };
 use solana_entry::poh::compute_hashes_per_tick;
-use solana_genesis::{genesis_accounts::add_genesis_accounts, Base64Account};
+use solana_genesis::{
+    genesis_accounts::add_genesis_accounts, manifest::GenesisManifest, Base64Account,
+};
 use solana_ledger::{blockstore::create_new_ledger, blockstore_db::AccessType};
 use solana_runtime::hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE;
 use solana_sdk::{
@@ -32,7 +34,7 @@ use std::{
     error,
     fs::File,
     io::{self, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process,
     str::FromStr,
     time::Duration,
@@ -158,7 +160,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .validator(is_pubkey_or_keypair)
                 .number_of_values(3)
                 .multiple(true)
-                .required(true)
+                .required_unless("manifest")
                 .help("The bootstrap validator's identity, vote and stake pubkeys"),
         )
         .arg(
@@ -378,8 +380,24 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .possible_values(&["pico", "full", "none"])
                 .help("Selects inflation"),
         )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .value_name("FILENAME")
+                .takes_value(true)
+                .help(
+                    "YAML or JSON manifest describing the cluster's settings, validators, \
+                     accounts, programs, builtins and features. Settings in the manifest \
+                     override the corresponding arguments",
+                ),
+        )
         .get_matches();
 
+    let manifest = matches
+        .value_of("manifest")
+        .map(|path| GenesisManifest::load(Path::new(path)))
+        .transpose()?;
+
     let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
 
     let rent = Rent {
@@ -404,7 +422,8 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
-    let bootstrap_validator_pubkeys = pubkeys_of(&matches, "bootstrap_validator").unwrap();
+    let bootstrap_validator_pubkeys =
+        pubkeys_of(&matches, "bootstrap_validator").unwrap_or_default();
     assert_eq!(bootstrap_validator_pubkeys.len() % 3, 0);
 
     // Ensure there are no duplicated pubkeys in the --bootstrap-validator list
@@ -421,12 +440,6 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let bootstrap_validator_lamports =
         value_t_or_exit!(matches, "bootstrap_validator_lamports", u64);
 
-    let bootstrap_validator_stake_lamports = rent_exempt_check(
-        &matches,
-        "bootstrap_validator_stake_lamports",
-        StakeState::get_rent_exempt_reserve(&rent),
-    )?;
-
     let bootstrap_stake_authorized_pubkey =
         pubkey_of(&matches, "bootstrap_stake_authorized_pubkey");
     let faucet_lamports = value_t!(matches, "faucet_lamports", u64).unwrap_or(0);
@@ -449,7 +462,11 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         ..PohConfig::default()
     };
 
-    let cluster_type = cluster_type_of(&matches, "cluster_type").unwrap();
+    let cluster_type = match &manifest {
+        Some(manifest) => manifest.cluster_type()?,
+        None => None,
+    }
+    .unwrap_or_else(|| cluster_type_of(&matches, "cluster_type").unwrap());
 
     match matches.value_of("hashes_per_tick").unwrap() {
         "auto" => match cluster_type {
@@ -507,6 +524,17 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         genesis_config.inflation = inflation;
     }
 
+    if let Some(manifest) = &manifest {
+        manifest.apply_settings(&mut genesis_config)?;
+    }
+    let rent = genesis_config.rent;
+
+    let bootstrap_validator_stake_lamports = rent_exempt_check(
+        &matches,
+        "bootstrap_validator_stake_lamports",
+        StakeState::get_rent_exempt_reserve(&rent),
+    )?;
+
     let commission = value_t_or_exit!(matches, "vote_commission_percentage", u8);
 
     let mut bootstrap_validator_pubkeys_iter = bootstrap_validator_pubkeys.iter();
@@ -569,6 +597,10 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
+    if let Some(manifest) = &manifest {
+        manifest.add_accounts(&mut genesis_config)?;
+    }
+
     let max_genesis_archive_unpacked_size =
         value_t_or_exit!(matches, "max_genesis_archive_unpacked_size", u64);
 
@@ -0,0 +1,444 @@
+//! A declarative description of a cluster's genesis.
+//!
+//! A manifest is a YAML (or JSON) file that can stand in for most of the `solana-genesis` command
+//! line, so that complex test clusters can be bootstrapped reproducibly from a single checked-in
+//! file. Settings in the manifest take precedence over the corresponding command line arguments,
+//! and accounts, validators and programs are added alongside any given on the command line.
+use {
+    crate::Base64Account,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
+        epoch_schedule::EpochSchedule,
+        feature::{self, Feature},
+        feature_set::FEATURE_NAMES,
+        fee_calculator::FeeRateGovernor,
+        genesis_config::{ClusterType, GenesisConfig},
+        inflation::Inflation,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_program,
+    },
+    solana_stake_program::stake_state,
+    solana_vote_program::vote_state::{self, VoteState},
+    std::{
+        collections::HashMap,
+        fs::{self, File},
+        io,
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GenesisManifest {
+    pub cluster_type: Option<String>,
+    pub creation_time: Option<i64>,
+    pub ticks_per_slot: Option<u64>,
+    pub hashes_per_tick: Option<u64>,
+    pub slots_per_epoch: Option<u64>,
+    pub enable_warmup_epochs: Option<bool>,
+    pub inflation: Option<String>,
+    pub rent: Option<ManifestRent>,
+    pub fees: Option<ManifestFees>,
+    #[serde(default)]
+    pub features: ManifestFeatures,
+    #[serde(default)]
+    pub validators: Vec<ManifestValidator>,
+    #[serde(default)]
+    pub accounts: HashMap<String, Base64Account>,
+    #[serde(default)]
+    pub programs: Vec<ManifestProgram>,
+    #[serde(default)]
+    pub builtins: Vec<ManifestBuiltin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestRent {
+    pub lamports_per_byte_year: u64,
+    pub exemption_threshold: f64,
+    pub burn_percent: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestFees {
+    pub target_lamports_per_signature: u64,
+    pub target_signatures_per_slot: u64,
+    pub burn_percent: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestFeatures {
+    /// Activate every known feature at genesis, like a development cluster
+    #[serde(default)]
+    pub activate_all: bool,
+    #[serde(default)]
+    pub activate: Vec<String>,
+    /// Features to leave inactive even if `activate-all` is set or the cluster type activates them
+    #[serde(default)]
+    pub deactivate: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestValidator {
+    pub identity: String,
+    pub vote: String,
+    pub stake: String,
+    pub identity_lamports: u64,
+    pub stake_lamports: u64,
+    #[serde(default)]
+    pub commission: u8,
+    /// Stake authority, the identity if unset
+    pub stake_authority: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestProgram {
+    pub address: String,
+    pub loader: String,
+    /// Path to the program's shared object, relative to the manifest
+    pub path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ManifestBuiltin {
+    pub name: String,
+    pub program_id: String,
+}
+
+fn invalid_manifest(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, message)
+}
+
+fn parse_pubkey(field: &str, value: &str) -> io::Result<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|err| invalid_manifest(format!("Invalid {}: {}: {:?}", field, value, err)))
+}
+
+fn parse_feature(value: &str) -> io::Result<Pubkey> {
+    let feature_id = parse_pubkey("feature", value)?;
+    if !FEATURE_NAMES.contains_key(&feature_id) {
+        return Err(invalid_manifest(format!("Unknown feature: {}", value)));
+    }
+    Ok(feature_id)
+}
+
+impl GenesisManifest {
+    /// Read a manifest. JSON manifests are accepted as well, since JSON is a subset of YAML.
+    /// Program paths are resolved relative to the manifest's directory.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mut manifest: Self = serde_yaml::from_reader(file).map_err(|err| {
+            invalid_manifest(format!("Invalid manifest {}: {}", path.display(), err))
+        })?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for program in manifest.programs.iter_mut() {
+            program.path = base_dir.join(&program.path);
+        }
+        Ok(manifest)
+    }
+
+    pub fn cluster_type(&self) -> io::Result<Option<ClusterType>> {
+        self.cluster_type
+            .as_ref()
+            .map(|cluster_type| ClusterType::from_str(cluster_type).map_err(invalid_manifest))
+            .transpose()
+    }
+
+    /// Override the cluster-wide settings of `genesis_config` with those given in the manifest
+    pub fn apply_settings(&self, genesis_config: &mut GenesisConfig) -> io::Result<()> {
+        if let Some(cluster_type) = self.cluster_type()? {
+            genesis_config.cluster_type = cluster_type;
+        }
+        if let Some(creation_time) = self.creation_time {
+            genesis_config.creation_time = creation_time;
+        }
+        if let Some(ticks_per_slot) = self.ticks_per_slot {
+            genesis_config.ticks_per_slot = ticks_per_slot;
+        }
+        if let Some(hashes_per_tick) = self.hashes_per_tick {
+            genesis_config.poh_config.hashes_per_tick = Some(hashes_per_tick);
+        }
+        if self.slots_per_epoch.is_some() || self.enable_warmup_epochs.is_some() {
+            let slots_per_epoch = self
+                .slots_per_epoch
+                .unwrap_or(genesis_config.epoch_schedule.slots_per_epoch);
+            genesis_config.epoch_schedule = EpochSchedule::custom(
+                slots_per_epoch,
+                slots_per_epoch,
+                self.enable_warmup_epochs
+                    .unwrap_or(genesis_config.epoch_schedule.warmup),
+            );
+        }
+        if let Some(inflation) = &self.inflation {
+            genesis_config.inflation = match inflation.as_str() {
+                "pico" => Inflation::pico(),
+                "full" => Inflation::full(),
+                "none" => Inflation::new_disabled(),
+                _ => {
+                    return Err(invalid_manifest(format!(
+                        "Invalid inflation: {}, expected pico, full or none",
+                        inflation
+                    )))
+                }
+            };
+        }
+        if let Some(rent) = &self.rent {
+            genesis_config.rent = Rent {
+                lamports_per_byte_year: rent.lamports_per_byte_year,
+                exemption_threshold: rent.exemption_threshold,
+                burn_percent: rent.burn_percent,
+            };
+        }
+        if let Some(fees) = &self.fees {
+            let mut fee_rate_governor = FeeRateGovernor::new(
+                fees.target_lamports_per_signature,
+                fees.target_signatures_per_slot,
+            );
+            fee_rate_governor.burn_percent = fees.burn_percent;
+            genesis_config.fee_rate_governor = fee_rate_governor;
+        }
+        Ok(())
+    }
+
+    /// Add the manifest's validators, accounts, programs and builtins to `genesis_config`, and
+    /// apply its feature selection. Returns the lamports added.
+    pub fn add_accounts(&self, genesis_config: &mut GenesisConfig) -> io::Result<u64> {
+        let mut lamports = 0;
+        let rent = genesis_config.rent;
+
+        for validator in &self.validators {
+            let identity = parse_pubkey("validator identity", &validator.identity)?;
+            let vote = parse_pubkey("validator vote account", &validator.vote)?;
+            let stake = parse_pubkey("validator stake account", &validator.stake)?;
+            let stake_authority = validator
+                .stake_authority
+                .as_ref()
+                .map(|stake_authority| parse_pubkey("stake authority", stake_authority))
+                .transpose()?
+                .unwrap_or(identity);
+            let stake_rent_exempt_reserve =
+                solana_sdk::stake::state::StakeState::get_rent_exempt_reserve(&rent);
+            if validator.stake_lamports < stake_rent_exempt_reserve {
+                return Err(invalid_manifest(format!(
+                    "Insufficient stake lamports for {}: {} for rent exemption, requires {}",
+                    validator.identity, validator.stake_lamports, stake_rent_exempt_reserve
+                )));
+            }
+
+            let vote_account = vote_state::create_account_with_authorized(
+                &identity,
+                &identity,
+                &identity,
+                validator.commission,
+                VoteState::get_rent_exempt_reserve(&rent).max(1),
+            );
+            let stake_account = stake_state::create_account(
+                &stake_authority,
+                &vote,
+                &vote_account,
+                &rent,
+                validator.stake_lamports,
+            );
+            lamports +=
+                validator.identity_lamports + vote_account.lamports() + stake_account.lamports();
+            genesis_config.add_account(
+                identity,
+                AccountSharedData::new(validator.identity_lamports, 0, &system_program::id()),
+            );
+            genesis_config.add_account(stake, stake_account);
+            genesis_config.add_account(vote, vote_account);
+        }
+
+        for (key, account_details) in &self.accounts {
+            let pubkey = parse_pubkey("account", key)?;
+            let owner = parse_pubkey("owner", &account_details.owner)?;
+            let mut account = AccountSharedData::new(account_details.balance, 0, &owner);
+            if account_details.data != "~" {
+                account.set_data(
+                    base64::decode(account_details.data.as_str()).map_err(|err| {
+                        invalid_manifest(format!(
+                            "Invalid account data: {}: {:?}",
+                            account_details.data, err
+                        ))
+                    })?,
+                );
+            }
+            account.set_executable(account_details.executable);
+            lamports += account_details.balance;
+            genesis_config.add_account(pubkey, account);
+        }
+
+        for program in &self.programs {
+            let address = parse_pubkey("program address", &program.address)?;
+            let loader = parse_pubkey("program loader", &program.loader)?;
+            let data = fs::read(&program.path).map_err(|err| {
+                invalid_manifest(format!(
+                    "Failed to read {}: {}",
+                    program.path.display(),
+                    err
+                ))
+            })?;
+            let program_lamports = rent.minimum_balance(data.len());
+            lamports += program_lamports;
+            genesis_config.add_account(
+                address,
+                AccountSharedData::from(Account {
+                    lamports: program_lamports,
+                    data,
+                    executable: true,
+                    owner: loader,
+                    rent_epoch: 0,
+                }),
+            );
+        }
+
+        for builtin in &self.builtins {
+            let program_id = parse_pubkey("builtin program id", &builtin.program_id)?;
+            genesis_config.add_native_instruction_processor(builtin.name.clone(), program_id);
+        }
+
+        if self.features.activate_all {
+            solana_runtime::genesis_utils::activate_all_features(genesis_config);
+        }
+        let feature_lamports = rent.minimum_balance(Feature::size_of()).max(1);
+        for feature_id in &self.features.activate {
+            let feature_id = parse_feature(feature_id)?;
+            genesis_config.add_account(
+                feature_id,
+                AccountSharedData::from(feature::create_account(
+                    &Feature {
+                        activated_at: Some(0),
+                    },
+                    feature_lamports,
+                )),
+            );
+        }
+        for feature_id in &self.features.deactivate {
+            let feature_id = parse_feature(feature_id)?;
+            genesis_config.accounts.remove(&feature_id);
+        }
+
+        Ok(lamports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{bpf_loader, feature_set},
+        std::io::Write,
+    };
+
+    #[test]
+    fn test_manifest_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("noop.so"), b"program").unwrap();
+
+        let identity = solana_sdk::pubkey::new_rand();
+        let vote = solana_sdk::pubkey::new_rand();
+        let stake = solana_sdk::pubkey::new_rand();
+        let account = solana_sdk::pubkey::new_rand();
+        let program = solana_sdk::pubkey::new_rand();
+        let manifest_path = dir.path().join("genesis.yml");
+        let mut manifest_file = File::create(&manifest_path).unwrap();
+        write!(
+            manifest_file,
+            r#"
+cluster-type: development
+ticks-per-slot: 8
+slots-per-epoch: 64
+inflation: none
+features:
+  activate-all: true
+  deactivate:
+    - {deactivated}
+validators:
+  - identity: {identity}
+    vote: {vote}
+    stake: {stake}
+    identity-lamports: 500000000000
+    stake-lamports: 1000000000
+accounts:
+  {account}:
+    balance: 42
+    owner: {owner}
+    data: aGVsbG8=
+    executable: false
+programs:
+  - address: {program}
+    loader: {loader}
+    path: noop.so
+builtins:
+  - name: test_builtin
+    program-id: {program}
+"#,
+            deactivated = feature_set::spl_token_v2_multisig_fix::id(),
+            identity = identity,
+            vote = vote,
+            stake = stake,
+            account = account,
+            owner = system_program::id(),
+            program = program,
+            loader = bpf_loader::id(),
+        )
+        .unwrap();
+
+        let manifest = GenesisManifest::load(&manifest_path).unwrap();
+        assert_eq!(
+            manifest.cluster_type().unwrap(),
+            Some(ClusterType::Development)
+        );
+
+        let mut genesis_config = GenesisConfig::default();
+        manifest.apply_settings(&mut genesis_config).unwrap();
+        assert_eq!(genesis_config.ticks_per_slot, 8);
+        assert_eq!(genesis_config.epoch_schedule.slots_per_epoch, 64);
+        assert_eq!(genesis_config.inflation, Inflation::new_disabled());
+
+        let lamports = manifest.add_accounts(&mut genesis_config).unwrap();
+        assert!(lamports > 500_000_000_000);
+        assert!(genesis_config.accounts.contains_key(&identity));
+        assert!(genesis_config.accounts.contains_key(&vote));
+        assert!(genesis_config.accounts.contains_key(&stake));
+        assert_eq!(genesis_config.accounts[&account].data, b"hello");
+        assert_eq!(genesis_config.accounts[&program].data, b"program");
+        assert!(genesis_config.accounts[&program].executable);
+        assert_eq!(
+            genesis_config.native_instruction_processors,
+            vec![("test_builtin".to_string(), program)]
+        );
+        assert!(genesis_config
+            .accounts
+            .contains_key(&feature_set::pico_inflation::id()));
+        assert!(!genesis_config
+            .accounts
+            .contains_key(&feature_set::spl_token_v2_multisig_fix::id()));
+    }
+
+    #[test]
+    fn test_manifest_rejects_unknown_fields() {
+        assert!(serde_yaml::from_str::<GenesisManifest>("ticks-per-slot: 8").is_ok());
+        assert!(serde_yaml::from_str::<GenesisManifest>("tick-per-slot: 8").is_err());
+        assert!(serde_yaml::from_str::<GenesisManifest>(r#"{"ticks-per-slot": 8}"#).is_ok());
+
+        let manifest = GenesisManifest {
+            features: ManifestFeatures {
+                activate: vec![solana_sdk::pubkey::new_rand().to_string()],
+                ..ManifestFeatures::default()
+            },
+            ..GenesisManifest::default()
+        };
+        assert!(manifest
+            .add_accounts(&mut GenesisConfig::default())
+            .is_err());
+    }
+}
@@ -27,7 +27,9 @@ use {
             LowestSlot, NodeInstance, SnapshotHashes, Version, Vote, MAX_WALLCLOCK,
         },
         epoch_slots::EpochSlots,
+        extended_contact_info::ExtendedContactInfo,
         gossip_error::GossipError,
+        node_instance_tracker::{DuplicateInstance, NodeInstanceEvent, NodeInstanceTracker},
         ping_pong::{self, PingCache, Pong},
         socketaddr, socketaddr_any,
         weighted_shuffle::WeightedShuffle,
@@ -39,7 +41,9 @@ use {
     serde::ser::Serialize,
     solana_ledger::shred::Shred,
     solana_measure::measure::Measure,
-    solana_metrics::{inc_new_counter_debug, inc_new_counter_error},
+    solana_metrics::{
+        datapoint_info, datapoint_warn, inc_new_counter_debug, inc_new_counter_error,
+    },
     solana_net_utils::{
         bind_common, bind_common_in_range, bind_in_range, find_available_port_in_range,
         multi_bind_in_range, PortRange,
@@ -49,11 +53,17 @@ use {
         limited_deserialize, to_packets_with_destination, Packet, Packets, PacketsRecycler,
         PACKET_DATA_SIZE,
     },
-    solana_rayon_threadlimit::get_thread_count,
+    solana_rayon_threadlimit::{
+        get_thread_count,
+        thread_pools::{
+            ManagedThreadPool, GOSSIP_CONSUME_THREAD_POOL, GOSSIP_LISTEN_THREAD_POOL,
+            GOSSIP_THREAD_POOL,
+        },
+    },
     solana_runtime::bank_forks::BankForks,
     solana_sdk::{
         clock::{Slot, DEFAULT_MS_PER_SLOT, DEFAULT_SLOTS_PER_EPOCH},
-        feature_set::FeatureSet,
+        feature_set::{self, FeatureSet},
         hash::Hash,
         pubkey::Pubkey,
         sanitize::{Sanitize, SanitizeError},
@@ -73,6 +83,7 @@ use {
     std::{
         borrow::Cow,
         collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+        convert::TryFrom,
         fmt::Debug,
         fs::{self, File},
         io::BufReader,
@@ -96,6 +107,8 @@ pub const MINIMUM_VALIDATOR_PORT_RANGE_WIDTH: u16 = 10; // VALIDATOR_PORT_RANGE
 
 /// The Data plane fanout size, also used as the neighborhood size
 pub const DATA_PLANE_FANOUT: usize = 200;
+/// Number of sockets bound to the TPU port unless configured otherwise
+pub const DEFAULT_NUM_TPU_SOCKETS: usize = 32;
 /// milliseconds we sleep for between gossip requests
 pub const GOSSIP_SLEEP_MILLIS: u64 = 100;
 /// The maximum size of a bloom filter
@@ -163,6 +176,7 @@ pub struct ClusterInfo {
     contact_debug_interval: u64, // milliseconds, 0 = disabled
     contact_save_interval: u64,  // milliseconds, 0 = disabled
     instance: RwLock<NodeInstance>,
+    node_instance_tracker: Mutex<NodeInstanceTracker>,
     contact_info_path: PathBuf,
     socket_addr_space: SocketAddrSpace,
 }
@@ -259,7 +273,7 @@ pub fn make_accounts_hashes_message(
 pub(crate) type Ping = ping_pong::Ping<[u8; GOSSIP_PING_TOKEN_SIZE]>;
 
 // TODO These messages should go through the gpu pipeline for spam filtering
-#[frozen_abi(digest = "4VqzaZbxQkeTgo916HVoLtaWoM8bbGaQZy6Qgw7K9kLf")]
+#[frozen_abi(digest = "GiG6ykqnwcsNajefHQn5rqV7p9QtVi6Dv1jN1xDhh8iN")]
 #[derive(Serialize, Deserialize, Debug, AbiEnumVisitor, AbiExample)]
 #[allow(clippy::large_enum_variant)]
 pub(crate) enum Protocol {
@@ -381,6 +395,7 @@ fn retain_staked(values: &mut Vec<CrdsValue>, stakes: &HashMap<Pubkey, u64>) {
             // the various dashboards.
             CrdsData::Version(_) => true,
             CrdsData::NodeInstance(_) => true,
+            CrdsData::ExtendedContactInfo(_) => true,
             CrdsData::LowestSlot(_, _)
             | CrdsData::AccountsHashes(_)
             | CrdsData::LegacyVersion(_)
@@ -414,12 +429,13 @@ impl ClusterInfo {
             local_message_pending_push_queue: Mutex::default(),
             contact_debug_interval: DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
             instance: RwLock::new(NodeInstance::new(&mut thread_rng(), id, timestamp())),
+            node_instance_tracker: Mutex::default(),
             contact_info_path: PathBuf::default(),
             contact_save_interval: 0, // disabled
             socket_addr_space,
         };
         me.insert_self();
-        me.push_self(&HashMap::new(), None);
+        me.push_self(&HashMap::new(), None, None);
         me
     }
 
@@ -444,6 +460,7 @@ impl ClusterInfo {
             ),
             contact_debug_interval: self.contact_debug_interval,
             instance: RwLock::new(NodeInstance::new(&mut thread_rng(), *new_id, timestamp())),
+            node_instance_tracker: Mutex::default(),
             contact_info_path: PathBuf::default(),
             contact_save_interval: 0, // disabled
             ..*self
@@ -462,16 +479,37 @@ impl ClusterInfo {
         &self,
         stakes: &HashMap<Pubkey, u64>,
         gossip_validators: Option<&HashSet<Pubkey>>,
+        feature_set: Option<&FeatureSet>,
     ) {
         let now = timestamp();
         self.my_contact_info.write().unwrap().wallclock = now;
-        let entries: Vec<_> = vec![
-            CrdsData::ContactInfo(self.my_contact_info()),
-            CrdsData::NodeInstance(self.instance.read().unwrap().with_wallclock(now)),
-        ]
-        .into_iter()
-        .map(|v| CrdsValue::new_signed(v, &self.keypair()))
-        .collect();
+        let contact_info = self.my_contact_info();
+        let instance = self.instance.read().unwrap().with_wallclock(now);
+        {
+            let mut node_instance_tracker = self.node_instance_tracker.lock().unwrap();
+            node_instance_tracker.prune(now);
+            node_instance_tracker.record(&instance);
+        }
+        // Nodes that predate ExtendedContactInfo fail to deserialize the messages carrying it
+        let extended_contact_info = feature_set
+            .filter(|feature_set| {
+                feature_set.is_active(&feature_set::gossip_extended_contact_info::id())
+            })
+            .and_then(|_| match ExtendedContactInfo::try_from(&contact_info) {
+                Ok(node) => Some(CrdsData::ExtendedContactInfo(node)),
+                Err(err) => {
+                    warn!("Not pushing extended contact info: {}", err);
+                    None
+                }
+            });
+        let entries: Vec<_> = extended_contact_info
+            .into_iter()
+            .chain(vec![
+                CrdsData::ContactInfo(contact_info),
+                CrdsData::NodeInstance(instance),
+            ])
+            .map(|v| CrdsValue::new_signed(v, &self.keypair()))
+            .collect();
         self.local_message_pending_push_queue
             .lock()
             .unwrap()
@@ -625,15 +663,70 @@ impl ClusterInfo {
 
     pub fn set_keypair(&self, new_keypair: Arc<Keypair>) {
         let id = new_keypair.pubkey();
-        {
-            let mut instance = self.instance.write().unwrap();
-            *instance = NodeInstance::new(&mut thread_rng(), id, timestamp());
-        }
         *self.keypair.write().unwrap() = new_keypair;
         self.my_contact_info.write().unwrap().id = id;
 
         self.insert_self();
-        self.push_self(&HashMap::new(), None);
+        self.rotate_instance_token();
+    }
+
+    /// Starts a new instance of this node, with a fresh token and creation timestamp. The rest
+    /// of the cluster sees this as a restart of the node, so a node taking over an identity from
+    /// another one that has stopped should rotate its token to supersede the old instance.
+    pub fn rotate_instance_token(&self) {
+        let id = self.id();
+        *self.instance.write().unwrap() = NodeInstance::new(&mut thread_rng(), id, timestamp());
+        self.push_self(&HashMap::new(), None, None);
+    }
+
+    /// Returns the node's duplicate instance, if more than one instance of the node was seen
+    /// running at the same time.
+    pub fn get_duplicate_instance(&self, pubkey: &Pubkey) -> Option<DuplicateInstance> {
+        self.node_instance_tracker
+            .lock()
+            .unwrap()
+            .get_duplicate(pubkey)
+    }
+
+    /// Returns all the nodes seen running more than one instance at the same time.
+    pub fn duplicate_instances(&self) -> Vec<DuplicateInstance> {
+        self.node_instance_tracker.lock().unwrap().duplicates()
+    }
+
+    fn record_node_instances(&self, values: &[CrdsValue]) {
+        let mut instances = values
+            .iter()
+            .filter_map(|value| match &value.data {
+                CrdsData::NodeInstance(instance) => Some(instance),
+                _ => None,
+            })
+            .peekable();
+        if instances.peek().is_none() {
+            return;
+        }
+        let mut node_instance_tracker = self.node_instance_tracker.lock().unwrap();
+        for instance in instances {
+            match node_instance_tracker.record(instance) {
+                None => (),
+                Some(NodeInstanceEvent::Restart) => {
+                    debug!("node instance restarted: {}", instance.from());
+                    datapoint_info!(
+                        "cluster_info-node_instance_restart",
+                        ("pubkey", instance.from().to_string(), String),
+                    );
+                }
+                Some(NodeInstanceEvent::Duplicate) => {
+                    warn!(
+                        "duplicate running instances of node detected: {}",
+                        instance.from()
+                    );
+                    datapoint_warn!(
+                        "cluster_info-duplicate_node_instance",
+                        ("pubkey", instance.from().to_string(), String),
+                    );
+                }
+            }
+        }
     }
 
     pub fn lookup_contact_info<F, Y>(&self, id: &Pubkey, map: F) -> Option<Y>
@@ -644,6 +737,20 @@ impl ClusterInfo {
         gossip_crds.get(*id).map(map)
     }
 
+    /// Returns the node's extended contact info, falling back on its legacy contact info if the
+    /// node doesn't publish one
+    pub fn lookup_extended_contact_info(&self, id: &Pubkey) -> Option<ExtendedContactInfo> {
+        let gossip_crds = self.gossip.crds.read().unwrap();
+        gossip_crds
+            .get::<&ExtendedContactInfo>(*id)
+            .cloned()
+            .or_else(|| {
+                gossip_crds
+                    .get::<&ContactInfo>(*id)
+                    .and_then(|node| ExtendedContactInfo::try_from(node).ok())
+            })
+    }
+
     pub fn lookup_contact_info_by_gossip_addr(
         &self,
         gossip_addr: &SocketAddr,
@@ -1424,6 +1531,20 @@ impl ClusterInfo {
         })
     }
 
+    /// Like `split_gossip_messages()` for the values of push messages, except that each
+    /// ExtendedContactInfo goes in a push message of its own. Nodes that predate it fail to
+    /// deserialize the whole message carrying it, and only drop that value this way.
+    fn split_push_messages(values: Vec<CrdsValue>) -> impl Iterator<Item = Vec<CrdsValue>> {
+        let (extended_contact_infos, values): (Vec<_>, Vec<_>) = values
+            .into_iter()
+            .partition(|value| matches!(value.data, CrdsData::ExtendedContactInfo(_)));
+        Self::split_gossip_messages(PUSH_MESSAGE_MAX_PAYLOAD_SIZE, values).chain(
+            Self::split_gossip_messages(PUSH_MESSAGE_MAX_PAYLOAD_SIZE, extended_contact_infos)
+                .flatten()
+                .map(|value| vec![value]),
+        )
+    }
+
     #[allow(clippy::type_complexity)]
     fn new_pull_requests(
         &self,
@@ -1518,7 +1639,7 @@ impl ClusterInfo {
         let messages: Vec<_> = push_messages
             .into_iter()
             .flat_map(|(peer, msgs)| {
-                Self::split_gossip_messages(PUSH_MESSAGE_MAX_PAYLOAD_SIZE, msgs)
+                Self::split_push_messages(msgs)
                     .map(move |payload| (peer, Protocol::PushMessage(self_id, payload)))
             })
             .collect();
@@ -1680,11 +1801,17 @@ impl ClusterInfo {
         gossip_validators: Option<HashSet<Pubkey>>,
         exit: Arc<AtomicBool>,
     ) -> JoinHandle<()> {
-        let thread_pool = ThreadPoolBuilder::new()
-            .num_threads(std::cmp::min(get_thread_count(), 8))
-            .thread_name(|i| format!("ClusterInfo::gossip-{}", i))
-            .build()
-            .unwrap();
+        let gossip_thread_pool = ManagedThreadPool::new(
+            GOSSIP_THREAD_POOL,
+            std::cmp::min(get_thread_count(), 8),
+            |num_threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|i| format!("ClusterInfo::gossip-{}", i))
+                    .build()
+                    .unwrap()
+            },
+        );
         Builder::new()
             .name("solana-gossip".to_string())
             .spawn(move || {
@@ -1706,6 +1833,7 @@ impl ClusterInfo {
                 let mut generate_pull_requests = true;
                 loop {
                     let start = timestamp();
+                    let thread_pool = gossip_thread_pool.current();
                     if self.contact_debug_interval != 0
                         && start - last_contact_info_trace > self.contact_debug_interval
                     {
@@ -1725,7 +1853,7 @@ impl ClusterInfo {
                         last_contact_info_save = start;
                     }
 
-                    let (stakes, _feature_set) = match bank_forks {
+                    let (stakes, feature_set) = match bank_forks {
                         Some(ref bank_forks) => {
                             let root_bank = bank_forks.read().unwrap().root_bank();
                             (
@@ -1751,7 +1879,7 @@ impl ClusterInfo {
                     //TODO: possibly tune this parameter
                     //we saw a deadlock passing an self.read().unwrap().timeout into sleep
                     if start - last_push > CRDS_GOSSIP_PULL_CRDS_TIMEOUT_MS / 2 {
-                        self.push_self(&stakes, gossip_validators.as_ref());
+                        self.push_self(&stakes, gossip_validators.as_ref(), feature_set.as_deref());
                         last_push = timestamp();
                     }
                     let elapsed = timestamp() - start;
@@ -2377,10 +2505,12 @@ impl ClusterInfo {
                 }
                 Protocol::PullResponse(from, data) => {
                     check_duplicate_instance(&data)?;
+                    self.record_node_instances(&data);
                     pull_responses.push((from, data));
                 }
                 Protocol::PushMessage(from, data) => {
                     check_duplicate_instance(&data)?;
+                    self.record_node_instances(&data);
                     push_messages.push((from, data));
                 }
                 Protocol::PruneMessage(from, data) => prune_messages.push((from, data)),
@@ -2532,14 +2662,20 @@ impl ClusterInfo {
         sender: Sender<Vec<(/*from:*/ SocketAddr, Protocol)>>,
         exit: Arc<AtomicBool>,
     ) -> JoinHandle<()> {
-        let thread_pool = ThreadPoolBuilder::new()
-            .num_threads(get_thread_count().min(8))
-            .thread_name(|i| format!("gossip-consume-{}", i))
-            .build()
-            .unwrap();
+        let thread_pool = ManagedThreadPool::new(
+            GOSSIP_CONSUME_THREAD_POOL,
+            get_thread_count().min(8),
+            |num_threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|i| format!("gossip-consume-{}", i))
+                    .build()
+                    .unwrap()
+            },
+        );
         let run_consume = move || {
             while !exit.load(Ordering::Relaxed) {
-                match self.run_socket_consume(&receiver, &sender, &thread_pool) {
+                match self.run_socket_consume(&receiver, &sender, &thread_pool.current()) {
                     Err(GossipError::RecvTimeoutError(RecvTimeoutError::Disconnected)) => break,
                     Err(GossipError::RecvTimeoutError(RecvTimeoutError::Timeout)) => (),
                     // A send operation can only fail if the receiving end of a
@@ -2564,11 +2700,17 @@ impl ClusterInfo {
     ) -> JoinHandle<()> {
         let mut last_print = Instant::now();
         let recycler = PacketsRecycler::default();
-        let thread_pool = ThreadPoolBuilder::new()
-            .num_threads(get_thread_count().min(8))
-            .thread_name(|i| format!("sol-gossip-work-{}", i))
-            .build()
-            .unwrap();
+        let thread_pool = ManagedThreadPool::new(
+            GOSSIP_LISTEN_THREAD_POOL,
+            get_thread_count().min(8),
+            |num_threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|i| format!("sol-gossip-work-{}", i))
+                    .build()
+                    .unwrap()
+            },
+        );
         Builder::new()
             .name("solana-listen".to_string())
             .spawn(move || {
@@ -2578,7 +2720,7 @@ impl ClusterInfo {
                         bank_forks.as_deref(),
                         &requests_receiver,
                         &response_sender,
-                        &thread_pool,
+                        &thread_pool.current(),
                         &mut last_print,
                         should_check_duplicate_instance,
                     ) {
@@ -2859,6 +3001,24 @@ impl Node {
         gossip_addr: &SocketAddr,
         port_range: PortRange,
         bind_ip_addr: IpAddr,
+    ) -> Node {
+        Self::new_with_external_ip_and_tpu_sockets(
+            pubkey,
+            gossip_addr,
+            port_range,
+            bind_ip_addr,
+            DEFAULT_NUM_TPU_SOCKETS,
+        )
+    }
+
+    /// Like `new_with_external_ip`, but binds `num_tpu_sockets` TPU sockets to the same port
+    /// with SO_REUSEPORT, so the kernel shards incoming transactions across that many receivers
+    pub fn new_with_external_ip_and_tpu_sockets(
+        pubkey: &Pubkey,
+        gossip_addr: &SocketAddr,
+        port_range: PortRange,
+        bind_ip_addr: IpAddr,
+        num_tpu_sockets: usize,
     ) -> Node {
         let (gossip_port, (gossip, ip_echo)) =
             Self::get_gossip_port(gossip_addr, port_range, bind_ip_addr);
@@ -2870,7 +3030,7 @@ impl Node {
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu_forwards multi_bind");
 
         let (tpu_port, tpu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
+            multi_bind_in_range(bind_ip_addr, port_range, num_tpu_sockets).expect("tpu multi_bind");
 
         let (tpu_forwards_port, tpu_forwards_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tpu_forwards multi_bind");
@@ -2929,7 +3089,7 @@ pub fn push_messages_to_peer(
     peer_gossip: SocketAddr,
     socket_addr_space: &SocketAddrSpace,
 ) -> Result<(), GossipError> {
-    let reqs: Vec<_> = ClusterInfo::split_gossip_messages(PUSH_MESSAGE_MAX_PAYLOAD_SIZE, messages)
+    let reqs: Vec<_> = ClusterInfo::split_push_messages(messages)
         .map(move |payload| (peer_gossip, Protocol::PushMessage(self_id, payload)))
         .collect();
     let packets = to_packets_with_destination(PacketsRecycler::default(), &reqs);
@@ -3792,6 +3952,15 @@ mod tests {
         let slots = cluster_info.get_epoch_slots(&mut Cursor::default());
         assert_eq!(slots.len(), 1);
         assert_eq!(slots[0].from, cluster_info.id());
+        // Extended contact info isn't pushed until the feature is active. The queued values are
+        // dropped, or they could shadow the ones pushed within the same millisecond below.
+        cluster_info.push_self(&HashMap::default(), None, None);
+        assert!(!cluster_info
+            .local_message_pending_push_queue
+            .lock()
+            .unwrap()
+            .drain(..)
+            .any(|value| matches!(value.data, CrdsData::ExtendedContactInfo(_))));
         // Match shred versions.
         {
             let mut node = cluster_info.my_contact_info.write().unwrap();
@@ -3800,8 +3969,18 @@ mod tests {
         cluster_info.push_self(
             &HashMap::default(), // stakes
             None,                // gossip validators
+            Some(&FeatureSet::all_enabled()),
         );
         cluster_info.flush_push_queue();
+        let extended = cluster_info
+            .gossip
+            .crds
+            .read()
+            .unwrap()
+            .get::<&ExtendedContactInfo>(cluster_info.id())
+            .cloned()
+            .unwrap();
+        assert_eq!(ContactInfo::from(&extended), cluster_info.my_contact_info());
         // Should now include both epoch slots.
         let slots = cluster_info.get_epoch_slots(&mut Cursor::default());
         assert_eq!(slots.len(), 2);
@@ -3809,6 +3988,36 @@ mod tests {
         assert_eq!(slots[1].from, node_pubkey);
     }
 
+    #[test]
+    fn test_rotate_instance_token() {
+        let keypair = Arc::new(Keypair::new());
+        let contact_info = ContactInfo::new_localhost(&keypair.pubkey(), 0);
+        let cluster_info = ClusterInfo::new(contact_info, keypair, SocketAddrSpace::Unspecified);
+        let instance = cluster_info.instance.read().unwrap().clone();
+        cluster_info.rotate_instance_token();
+        let rotated = cluster_info.instance.read().unwrap().clone();
+        assert_ne!(instance.token(), rotated.token());
+        assert!(instance.timestamp() <= rotated.timestamp());
+        assert_eq!(
+            cluster_info.get_duplicate_instance(&cluster_info.id()),
+            None
+        );
+        // Another instance, created earlier, keeps running alongside this one.
+        let other = NodeInstance::new(
+            &mut rand::thread_rng(),
+            cluster_info.id(),
+            rotated.timestamp().saturating_sub(1_000),
+        );
+        cluster_info.record_node_instances(&[CrdsValue::new_unsigned(CrdsData::NodeInstance(
+            other.with_wallclock(rotated.timestamp() + 1),
+        ))]);
+        let duplicate = cluster_info
+            .get_duplicate_instance(&cluster_info.id())
+            .unwrap();
+        assert_eq!(duplicate.pubkey, cluster_info.id());
+        assert_eq!(cluster_info.duplicate_instances(), vec![duplicate]);
+    }
+
     #[test]
     fn test_append_entrypoint_to_pulls() {
         let thread_pool = ThreadPoolBuilder::new().build().unwrap();
@@ -3908,6 +4117,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_split_push_messages() {
+        let mut rng = rand::thread_rng();
+        let mut values: Vec<_> = repeat_with(|| CrdsValue::new_rand(&mut rng, None))
+            .take(64)
+            .collect();
+        values.extend(
+            repeat_with(|| {
+                CrdsValue::new_unsigned(CrdsData::ExtendedContactInfo(
+                    ExtendedContactInfo::new_rand(&mut rng, None),
+                ))
+            })
+            .take(16),
+        );
+        let splits: Vec<_> = ClusterInfo::split_push_messages(values.clone()).collect();
+        assert_eq!(splits.iter().map(Vec::len).sum::<usize>(), values.len());
+        let extended_contact_info_splits: Vec<_> = splits
+            .iter()
+            .filter(|split| {
+                split
+                    .iter()
+                    .any(|value| matches!(value.data, CrdsData::ExtendedContactInfo(_)))
+            })
+            .collect();
+        assert_eq!(extended_contact_info_splits.len(), 16);
+        assert!(extended_contact_info_splits
+            .iter()
+            .all(|split| split.len() == 1));
+    }
+
+    #[test]
+    fn test_unknown_crds_data_fails_push_message() {
+        // A node that predates a CrdsData variant fails to deserialize the whole push message
+        // carrying it, including the values it does know
+        let mut rng = rand::thread_rng();
+        let unknown = CrdsValue::new_rand(&mut rng, None);
+        let values = vec![CrdsValue::new_rand(&mut rng, None), unknown.clone()];
+        let message = Protocol::PushMessage(Pubkey::new_unique(), values);
+        let mut bytes = serialize(&message).unwrap();
+        assert!(bincode::deserialize::<Protocol>(&bytes).is_ok());
+        // The data of the last value ends the message, and starts with its variant index
+        let offset = bytes.len() - serialized_size(&unknown.data).unwrap() as usize;
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(bincode::deserialize::<Protocol>(&bytes).is_err());
+    }
+
     #[test]
     #[allow(clippy::needless_collect)]
     fn test_split_messages_packet_size() {
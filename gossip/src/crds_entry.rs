@@ -6,6 +6,7 @@ use {
             CrdsData, CrdsValue, CrdsValueLabel, IncrementalSnapshotHashes, LegacyVersion,
             LowestSlot, SnapshotHashes, Version,
         },
+        extended_contact_info::ExtendedContactInfo,
     },
     indexmap::IndexMap,
     solana_sdk::pubkey::Pubkey,
@@ -54,6 +55,11 @@ impl_crds_entry!(VersionedCrdsValue, |entry| entry);
 
 // Lookup by Pubkey.
 impl_crds_entry!(ContactInfo, CrdsData::ContactInfo(node), node);
+impl_crds_entry!(
+    ExtendedContactInfo,
+    CrdsData::ExtendedContactInfo(node),
+    node
+);
 impl_crds_entry!(LegacyVersion, CrdsData::LegacyVersion(version), version);
 impl_crds_entry!(LowestSlot, CrdsData::LowestSlot(_, slot), slot);
 impl_crds_entry!(Version, CrdsData::Version(version), version);
@@ -117,6 +123,9 @@ mod tests {
                 CrdsData::ContactInfo(node) => {
                     assert_eq!(crds.get::<&ContactInfo>(key), Some(node))
                 }
+                CrdsData::ExtendedContactInfo(node) => {
+                    assert_eq!(crds.get::<&ExtendedContactInfo>(key), Some(node))
+                }
                 CrdsData::LowestSlot(_, slot) => {
                     assert_eq!(crds.get::<&LowestSlot>(key), Some(slot))
                 }
@@ -5,6 +5,7 @@ use {
         deprecated,
         duplicate_shred::{DuplicateShred, DuplicateShredIndex, MAX_DUPLICATE_SHREDS},
         epoch_slots::EpochSlots,
+        extended_contact_info::ExtendedContactInfo,
     },
     bincode::{serialize, serialized_size},
     rand::{CryptoRng, Rng},
@@ -92,6 +93,7 @@ pub enum CrdsData {
     NodeInstance(NodeInstance),
     DuplicateShred(DuplicateShredIndex, DuplicateShred),
     IncrementalSnapshotHashes(IncrementalSnapshotHashes),
+    ExtendedContactInfo(ExtendedContactInfo),
 }
 
 impl Sanitize for CrdsData {
@@ -129,6 +131,7 @@ impl Sanitize for CrdsData {
                 }
             }
             CrdsData::IncrementalSnapshotHashes(val) => val.sanitize(),
+            CrdsData::ExtendedContactInfo(node) => node.sanitize(),
         }
     }
 }
@@ -441,6 +444,22 @@ impl NodeInstance {
         Self { wallclock, ..*self }
     }
 
+    pub fn from(&self) -> Pubkey {
+        self.from
+    }
+
+    pub fn wallclock(&self) -> u64 {
+        self.wallclock
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
     // Returns true if the crds-value is a duplicate instance
     // of this node, with a more recent timestamp.
     pub(crate) fn check_duplicate(&self, other: &CrdsValue) -> bool {
@@ -497,6 +516,7 @@ pub enum CrdsValueLabel {
     NodeInstance(Pubkey),
     DuplicateShred(DuplicateShredIndex, Pubkey),
     IncrementalSnapshotHashes(Pubkey),
+    ExtendedContactInfo(Pubkey),
 }
 
 impl fmt::Display for CrdsValueLabel {
@@ -515,6 +535,9 @@ impl fmt::Display for CrdsValueLabel {
             CrdsValueLabel::IncrementalSnapshotHashes(_) => {
                 write!(f, "IncrementalSnapshotHashes({})", self.pubkey())
             }
+            CrdsValueLabel::ExtendedContactInfo(_) => {
+                write!(f, "ExtendedContactInfo({})", self.pubkey())
+            }
         }
     }
 }
@@ -533,6 +556,7 @@ impl CrdsValueLabel {
             CrdsValueLabel::NodeInstance(p) => *p,
             CrdsValueLabel::DuplicateShred(_, p) => *p,
             CrdsValueLabel::IncrementalSnapshotHashes(p) => *p,
+            CrdsValueLabel::ExtendedContactInfo(p) => *p,
         }
     }
 }
@@ -582,6 +606,7 @@ impl CrdsValue {
             CrdsData::NodeInstance(node) => node.wallclock,
             CrdsData::DuplicateShred(_, shred) => shred.wallclock,
             CrdsData::IncrementalSnapshotHashes(hash) => hash.wallclock,
+            CrdsData::ExtendedContactInfo(node) => node.wallclock,
         }
     }
     pub fn pubkey(&self) -> Pubkey {
@@ -597,6 +622,7 @@ impl CrdsValue {
             CrdsData::NodeInstance(node) => node.from,
             CrdsData::DuplicateShred(_, shred) => shred.from,
             CrdsData::IncrementalSnapshotHashes(hash) => hash.from,
+            CrdsData::ExtendedContactInfo(node) => node.id,
         }
     }
     pub fn label(&self) -> CrdsValueLabel {
@@ -614,6 +640,7 @@ impl CrdsValue {
             CrdsData::IncrementalSnapshotHashes(_) => {
                 CrdsValueLabel::IncrementalSnapshotHashes(self.pubkey())
             }
+            CrdsData::ExtendedContactInfo(node) => CrdsValueLabel::ExtendedContactInfo(node.id),
         }
     }
     pub fn contact_info(&self) -> Option<&ContactInfo> {
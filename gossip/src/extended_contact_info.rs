@@ -0,0 +1,393 @@
+//! Variable-length node contact information.
+//!
+//! `ContactInfo` carries one field per service socket, so advertising a new port or protocol
+//! means a breaking change to the gossip wire format. `ExtendedContactInfo` instead holds a
+//! deduplicated list of IP addresses and a list of tagged socket entries referencing them.
+//! Receivers skip tags they don't recognize, so new sockets can be advertised without
+//! coordinating a cluster-wide upgrade, and values convert to and from `ContactInfo` for nodes
+//! that only understand the legacy format.
+use {
+    crate::{
+        contact_info::ContactInfo,
+        crds_value::{new_rand_timestamp, sanitize_wallclock},
+    },
+    rand::Rng,
+    solana_sdk::{
+        pubkey::Pubkey,
+        sanitize::{Sanitize, SanitizeError},
+        short_vec,
+    },
+    std::{
+        convert::TryFrom,
+        net::{IpAddr, SocketAddr},
+    },
+    thiserror::Error,
+};
+
+/// Version of the socket tag and protocol flag semantics understood by this node
+pub const EXTENDED_CONTACT_INFO_VERSION: u16 = 1;
+
+pub const SOCKET_TAG_GOSSIP: u8 = 0;
+pub const SOCKET_TAG_TVU: u8 = 1;
+pub const SOCKET_TAG_TVU_FORWARDS: u8 = 2;
+pub const SOCKET_TAG_REPAIR: u8 = 3;
+pub const SOCKET_TAG_TPU: u8 = 4;
+pub const SOCKET_TAG_TPU_FORWARDS: u8 = 5;
+pub const SOCKET_TAG_TPU_VOTE: u8 = 6;
+pub const SOCKET_TAG_RPC: u8 = 7;
+pub const SOCKET_TAG_RPC_PUBSUB: u8 = 8;
+pub const SOCKET_TAG_SERVE_REPAIR: u8 = 9;
+
+pub const PROTOCOL_UDP: u8 = 1 << 0;
+pub const PROTOCOL_TCP: u8 = 1 << 1;
+pub const PROTOCOL_QUIC: u8 = 1 << 2;
+
+const MAX_ADDRS: usize = 8;
+const MAX_SOCKETS: usize = 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExtendedContactInfoError {
+    #[error("too many addresses: {0}")]
+    TooManyAddrs(usize),
+    #[error("too many sockets: {0}")]
+    TooManySockets(usize),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, AbiExample)]
+struct SocketEntry {
+    tag: u8,
+    /// Bit set of the PROTOCOL_* flags served on this socket
+    protocols: u8,
+    /// Index into `ExtendedContactInfo::addrs`
+    addr_index: u8,
+    port: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, AbiExample)]
+pub struct ExtendedContactInfo {
+    pub id: Pubkey,
+    pub wallclock: u64,
+    pub shred_version: u16,
+    /// Version of the tag and flag semantics the node follows; see EXTENDED_CONTACT_INFO_VERSION
+    pub version: u16,
+    #[serde(with = "short_vec")]
+    addrs: Vec<IpAddr>,
+    #[serde(with = "short_vec")]
+    sockets: Vec<SocketEntry>,
+}
+
+impl Sanitize for ExtendedContactInfo {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        sanitize_wallclock(self.wallclock)?;
+        if self.addrs.len() > MAX_ADDRS || self.sockets.len() > MAX_SOCKETS {
+            return Err(SanitizeError::ValueOutOfBounds);
+        }
+        if self
+            .sockets
+            .iter()
+            .any(|entry| usize::from(entry.addr_index) >= self.addrs.len())
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        // Every address must be referenced by at least one socket
+        if (0..self.addrs.len()).any(|index| {
+            !self
+                .sockets
+                .iter()
+                .any(|entry| usize::from(entry.addr_index) == index)
+        }) {
+            return Err(SanitizeError::InvalidValue);
+        }
+        self.id.sanitize()
+    }
+}
+
+impl ExtendedContactInfo {
+    pub fn new(id: Pubkey, wallclock: u64, shred_version: u16) -> Self {
+        Self {
+            id,
+            wallclock,
+            shred_version,
+            version: EXTENDED_CONTACT_INFO_VERSION,
+            addrs: Vec::default(),
+            sockets: Vec::default(),
+        }
+    }
+
+    /// New random ExtendedContactInfo for tests and simulations.
+    pub fn new_rand<R: Rng>(rng: &mut R, pubkey: Option<Pubkey>) -> Self {
+        let node = ContactInfo::new_rand(rng, pubkey);
+        let mut node = Self::try_from(&node).unwrap();
+        node.wallclock = new_rand_timestamp(rng);
+        node
+    }
+
+    /// Returns the first socket advertised with `tag` that serves any of `protocols`
+    pub fn socket(&self, tag: u8, protocols: u8) -> Option<SocketAddr> {
+        self.sockets
+            .iter()
+            .find(|entry| entry.tag == tag && entry.protocols & protocols != 0)
+            .map(|entry| self.socket_addr(entry))
+    }
+
+    /// Returns all advertised sockets as `(tag, protocols, address)`, including tags this node
+    /// doesn't recognize
+    pub fn sockets(&self) -> impl Iterator<Item = (u8, u8, SocketAddr)> + '_ {
+        self.sockets
+            .iter()
+            .map(move |entry| (entry.tag, entry.protocols, self.socket_addr(entry)))
+    }
+
+    /// Advertise `addr` for `tag`, replacing any socket with the same tag that serves one of
+    /// `protocols`. Unspecified addresses are not advertised. Fails, leaving the sockets
+    /// unchanged, if the address or the socket would exceed the caps enforced by `sanitize`.
+    pub fn set_socket(
+        &mut self,
+        tag: u8,
+        protocols: u8,
+        addr: SocketAddr,
+    ) -> Result<(), ExtendedContactInfoError> {
+        let mut node = self.clone();
+        node.remove_socket(tag, protocols);
+        if !addr.ip().is_unspecified() && addr.port() != 0 {
+            let addr_index = match node.addrs.iter().position(|ip| *ip == addr.ip()) {
+                Some(index) => index,
+                None => {
+                    node.addrs.push(addr.ip());
+                    node.addrs.len() - 1
+                }
+            };
+            if node.addrs.len() > MAX_ADDRS {
+                return Err(ExtendedContactInfoError::TooManyAddrs(node.addrs.len()));
+            }
+            if node.sockets.len() >= MAX_SOCKETS {
+                return Err(ExtendedContactInfoError::TooManySockets(
+                    node.sockets.len() + 1,
+                ));
+            }
+            node.sockets.push(SocketEntry {
+                tag,
+                protocols,
+                addr_index: u8::try_from(addr_index)
+                    .map_err(|_| ExtendedContactInfoError::TooManyAddrs(node.addrs.len()))?,
+                port: addr.port(),
+            });
+        }
+        *self = node;
+        Ok(())
+    }
+
+    /// Stop advertising sockets with `tag` that serve any of `protocols`
+    pub fn remove_socket(&mut self, tag: u8, protocols: u8) {
+        self.sockets
+            .retain(|entry| entry.tag != tag || entry.protocols & protocols == 0);
+        // Drop addresses no longer referenced and reindex the remaining sockets
+        let mut index = 0;
+        while index < self.addrs.len() {
+            if self
+                .sockets
+                .iter()
+                .any(|entry| usize::from(entry.addr_index) == index)
+            {
+                index += 1;
+                continue;
+            }
+            self.addrs.remove(index);
+            for entry in self.sockets.iter_mut() {
+                if usize::from(entry.addr_index) > index {
+                    entry.addr_index -= 1;
+                }
+            }
+        }
+    }
+
+    fn socket_addr(&self, entry: &SocketEntry) -> SocketAddr {
+        SocketAddr::new(self.addrs[usize::from(entry.addr_index)], entry.port)
+    }
+}
+
+const LEGACY_SOCKETS: [(u8, u8); 10] = [
+    (SOCKET_TAG_GOSSIP, PROTOCOL_UDP),
+    (SOCKET_TAG_TVU, PROTOCOL_UDP),
+    (SOCKET_TAG_TVU_FORWARDS, PROTOCOL_UDP),
+    (SOCKET_TAG_REPAIR, PROTOCOL_UDP),
+    (SOCKET_TAG_TPU, PROTOCOL_UDP),
+    (SOCKET_TAG_TPU_FORWARDS, PROTOCOL_UDP),
+    (SOCKET_TAG_TPU_VOTE, PROTOCOL_UDP),
+    (SOCKET_TAG_RPC, PROTOCOL_TCP),
+    (SOCKET_TAG_RPC_PUBSUB, PROTOCOL_TCP),
+    (SOCKET_TAG_SERVE_REPAIR, PROTOCOL_UDP),
+];
+
+fn legacy_socket_mut(node: &mut ContactInfo, tag: u8) -> &mut SocketAddr {
+    match tag {
+        SOCKET_TAG_GOSSIP => &mut node.gossip,
+        SOCKET_TAG_TVU => &mut node.tvu,
+        SOCKET_TAG_TVU_FORWARDS => &mut node.tvu_forwards,
+        SOCKET_TAG_REPAIR => &mut node.repair,
+        SOCKET_TAG_TPU => &mut node.tpu,
+        SOCKET_TAG_TPU_FORWARDS => &mut node.tpu_forwards,
+        SOCKET_TAG_TPU_VOTE => &mut node.tpu_vote,
+        SOCKET_TAG_RPC => &mut node.rpc,
+        SOCKET_TAG_RPC_PUBSUB => &mut node.rpc_pubsub,
+        SOCKET_TAG_SERVE_REPAIR => &mut node.serve_repair,
+        _ => unreachable!("not a legacy socket tag: {}", tag),
+    }
+}
+
+impl TryFrom<&ContactInfo> for ExtendedContactInfo {
+    type Error = ExtendedContactInfoError;
+
+    /// Fails if the legacy sockets are spread over more addresses than can be advertised
+    fn try_from(node: &ContactInfo) -> Result<Self, Self::Error> {
+        let mut extended = Self::new(node.id, node.wallclock, node.shred_version);
+        let mut node = node.clone();
+        for (tag, protocols) in LEGACY_SOCKETS {
+            extended.set_socket(tag, protocols, *legacy_socket_mut(&mut node, tag))?;
+        }
+        Ok(extended)
+    }
+}
+
+impl From<&ExtendedContactInfo> for ContactInfo {
+    /// Sockets not advertised over the legacy protocols are left unspecified
+    fn from(extended: &ExtendedContactInfo) -> Self {
+        let mut node = ContactInfo {
+            id: extended.id,
+            wallclock: extended.wallclock,
+            shred_version: extended.shred_version,
+            ..ContactInfo::default()
+        };
+        for (tag, protocols) in LEGACY_SOCKETS {
+            if let Some(addr) = extended.socket(tag, protocols) {
+                *legacy_socket_mut(&mut node, tag) = addr;
+            }
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        bincode::{deserialize, serialize, serialized_size},
+        solana_sdk::timing::timestamp,
+    };
+
+    #[test]
+    fn test_legacy_round_trip() {
+        let node = ContactInfo::new_localhost(&solana_sdk::pubkey::new_rand(), timestamp());
+        let extended = ExtendedContactInfo::try_from(&node).unwrap();
+        assert_eq!(extended.sanitize(), Ok(()));
+        assert_eq!(extended.addrs.len(), 1);
+        assert_eq!(
+            extended.socket(SOCKET_TAG_TPU, PROTOCOL_UDP),
+            Some(node.tpu)
+        );
+        assert_eq!(extended.socket(SOCKET_TAG_TPU, PROTOCOL_QUIC), None);
+        assert_eq!(ContactInfo::from(&extended), node);
+        assert!(serialized_size(&extended).unwrap() < serialized_size(&node).unwrap());
+
+        // Unspecified sockets aren't advertised
+        let extended = ExtendedContactInfo::try_from(&ContactInfo::default()).unwrap();
+        assert_eq!(extended.sockets().count(), 0);
+        assert_eq!(ContactInfo::from(&extended), ContactInfo::default());
+    }
+
+    #[test]
+    fn test_unknown_sockets() {
+        const SOCKET_TAG_UNKNOWN: u8 = 200;
+        let node = ContactInfo::new_localhost(&solana_sdk::pubkey::new_rand(), timestamp());
+        let mut extended = ExtendedContactInfo::try_from(&node).unwrap();
+        let quic_tpu = "10.0.0.1:8009".parse().unwrap();
+        let unknown = "10.0.0.2:9000".parse().unwrap();
+        extended
+            .set_socket(SOCKET_TAG_TPU, PROTOCOL_QUIC, quic_tpu)
+            .unwrap();
+        extended
+            .set_socket(SOCKET_TAG_UNKNOWN, PROTOCOL_UDP | PROTOCOL_TCP, unknown)
+            .unwrap();
+
+        let extended: ExtendedContactInfo = deserialize(&serialize(&extended).unwrap()).unwrap();
+        assert_eq!(extended.sanitize(), Ok(()));
+        assert_eq!(
+            extended.socket(SOCKET_TAG_TPU, PROTOCOL_QUIC),
+            Some(quic_tpu)
+        );
+        assert_eq!(
+            extended.socket(SOCKET_TAG_TPU, PROTOCOL_UDP),
+            Some(node.tpu)
+        );
+        assert!(extended
+            .sockets()
+            .any(|socket| socket == (SOCKET_TAG_UNKNOWN, PROTOCOL_UDP | PROTOCOL_TCP, unknown)));
+        assert_eq!(ContactInfo::from(&extended), node);
+    }
+
+    #[test]
+    fn test_remove_socket() {
+        let mut extended = ExtendedContactInfo::new(Pubkey::default(), timestamp(), 0);
+        let gossip = "10.0.0.1:8001".parse().unwrap();
+        let tpu = "10.0.0.2:8003".parse().unwrap();
+        extended
+            .set_socket(SOCKET_TAG_GOSSIP, PROTOCOL_UDP, gossip)
+            .unwrap();
+        extended
+            .set_socket(SOCKET_TAG_TPU, PROTOCOL_UDP, tpu)
+            .unwrap();
+        assert_eq!(extended.addrs.len(), 2);
+
+        extended.remove_socket(SOCKET_TAG_GOSSIP, PROTOCOL_UDP);
+        assert_eq!(extended.addrs, vec![tpu.ip()]);
+        assert_eq!(extended.socket(SOCKET_TAG_GOSSIP, PROTOCOL_UDP), None);
+        assert_eq!(extended.socket(SOCKET_TAG_TPU, PROTOCOL_UDP), Some(tpu));
+        assert_eq!(extended.sanitize(), Ok(()));
+
+        extended.sockets[0].addr_index = 1;
+        assert_eq!(extended.sanitize(), Err(SanitizeError::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_set_socket_caps() {
+        let mut extended = ExtendedContactInfo::new(Pubkey::default(), timestamp(), 0);
+        for i in 0..MAX_ADDRS {
+            let addr = SocketAddr::new(IpAddr::from([10, 0, 0, i as u8 + 1]), 8000);
+            extended.set_socket(i as u8, PROTOCOL_UDP, addr).unwrap();
+        }
+        let before = extended.clone();
+        let addr = "10.0.1.1:8000".parse().unwrap();
+        assert_eq!(
+            extended.set_socket(200, PROTOCOL_UDP, addr),
+            Err(ExtendedContactInfoError::TooManyAddrs(MAX_ADDRS + 1))
+        );
+        // The sockets are left unchanged on failure
+        assert_eq!(extended, before);
+        // Replacing a socket whose address is no longer referenced frees the address
+        extended.set_socket(0, PROTOCOL_UDP, addr).unwrap();
+        assert_eq!(extended.socket(0, PROTOCOL_UDP), Some(addr));
+        assert_eq!(extended.sanitize(), Ok(()));
+
+        let mut extended = ExtendedContactInfo::new(Pubkey::default(), timestamp(), 0);
+        let addr = "10.0.0.1:8000".parse().unwrap();
+        for tag in 0..MAX_SOCKETS {
+            extended.set_socket(tag as u8, PROTOCOL_UDP, addr).unwrap();
+        }
+        assert_eq!(
+            extended.set_socket(200, PROTOCOL_UDP, addr),
+            Err(ExtendedContactInfoError::TooManySockets(MAX_SOCKETS + 1))
+        );
+        assert_eq!(extended.sanitize(), Ok(()));
+
+        // Legacy sockets spread over too many addresses can't be converted
+        let mut node = ContactInfo::new_localhost(&solana_sdk::pubkey::new_rand(), timestamp());
+        for (i, (tag, _)) in LEGACY_SOCKETS.iter().enumerate() {
+            *legacy_socket_mut(&mut node, *tag) =
+                SocketAddr::new(IpAddr::from([10, 0, 0, i as u8 + 1]), 8000);
+        }
+        assert_eq!(
+            ExtendedContactInfo::try_from(&node),
+            Err(ExtendedContactInfoError::TooManyAddrs(MAX_ADDRS + 1))
+        );
+    }
+}
@@ -16,8 +16,10 @@ pub mod crds_value;
 pub mod deprecated;
 pub mod duplicate_shred;
 pub mod epoch_slots;
+pub mod extended_contact_info;
 pub mod gossip_error;
 pub mod gossip_service;
+pub mod node_instance_tracker;
 pub mod ping_pong;
 pub mod weighted_shuffle;
 
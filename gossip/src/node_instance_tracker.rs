@@ -0,0 +1,186 @@
+//! Detects restarts and duplicate running instances of nodes across the cluster.
+//!
+//! Every node pushes a `NodeInstance` value, carrying a random token and the time the instance
+//! was created, and refreshes its wallclock periodically. When a node restarts, its new instance
+//! is created after the old one stopped, so the old token is never seen again with a wallclock
+//! past the new instance's creation time. Two instances of the same identity running at once
+//! both keep refreshing their wallclocks, which distinguishes a duplicate from a restart.
+use {
+    crate::crds_value::NodeInstance,
+    solana_sdk::pubkey::Pubkey,
+    std::collections::{hash_map::Entry, HashMap},
+};
+
+/// How long an instance is remembered after its last update, and so how long a duplicate keeps
+/// being reported after one of the instances stops
+pub const NODE_INSTANCE_RETENTION_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateInstance {
+    pub pubkey: Pubkey,
+    /// Number of distinct instances seen running concurrently
+    pub num_instances: usize,
+    /// Wallclock at which the duplicate was first detected
+    pub first_detected: u64,
+    /// Latest wallclock of the instances involved
+    pub last_seen: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NodeInstanceEvent {
+    /// The node is running a new instance, created after the previous one stopped
+    Restart,
+    /// The node is running more than one instance concurrently
+    Duplicate,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct InstanceSighting {
+    // Timestamp when the instance was created.
+    timestamp: u64,
+    // Latest wallclock seen for the instance.
+    wallclock: u64,
+}
+
+#[derive(Debug, Default)]
+struct NodeInstances {
+    instances: HashMap</*token:*/ u64, InstanceSighting>,
+    // Wallclock at which concurrent instances were first detected.
+    duplicate_since: Option<u64>,
+}
+
+impl NodeInstances {
+    // Returns true if an instance was seen updating after another instance
+    // of the same node had been created.
+    fn has_concurrent_instances(&self) -> bool {
+        let newest = self.instances.values().map(|i| i.timestamp).max();
+        let newest = match newest {
+            None => return false,
+            Some(newest) => newest,
+        };
+        self.instances
+            .values()
+            .any(|i| i.timestamp < newest && i.wallclock > newest)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct NodeInstanceTracker {
+    nodes: HashMap<Pubkey, NodeInstances>,
+}
+
+impl NodeInstanceTracker {
+    /// Records an update of a node-instance, returning an event if it reveals a restart or a
+    /// newly detected duplicate instance.
+    pub(crate) fn record(&mut self, instance: &NodeInstance) -> Option<NodeInstanceEvent> {
+        let node = self.nodes.entry(instance.from()).or_default();
+        let is_new_instance = match node.instances.entry(instance.token()) {
+            Entry::Occupied(mut entry) => {
+                let sighting = entry.get_mut();
+                sighting.wallclock = sighting.wallclock.max(instance.wallclock());
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(InstanceSighting {
+                    timestamp: instance.timestamp(),
+                    wallclock: instance.wallclock(),
+                });
+                true
+            }
+        };
+        if node.duplicate_since.is_none() && node.has_concurrent_instances() {
+            node.duplicate_since = Some(instance.wallclock());
+            Some(NodeInstanceEvent::Duplicate)
+        } else if is_new_instance && node.instances.len() > 1 && node.duplicate_since.is_none() {
+            Some(NodeInstanceEvent::Restart)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the duplicate instance of the node, if it's running more than one.
+    pub(crate) fn get_duplicate(&self, pubkey: &Pubkey) -> Option<DuplicateInstance> {
+        let node = self.nodes.get(pubkey)?;
+        Some(DuplicateInstance {
+            pubkey: *pubkey,
+            num_instances: node.instances.len(),
+            first_detected: node.duplicate_since?,
+            last_seen: node.instances.values().map(|i| i.wallclock).max()?,
+        })
+    }
+
+    pub(crate) fn duplicates(&self) -> Vec<DuplicateInstance> {
+        self.nodes
+            .keys()
+            .filter_map(|pubkey| self.get_duplicate(pubkey))
+            .collect()
+    }
+
+    /// Forgets instances that haven't been updated within the retention period.
+    pub(crate) fn prune(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(NODE_INSTANCE_RETENTION_MS);
+        self.nodes.retain(|_, node| {
+            node.instances.retain(|_, i| i.wallclock >= cutoff);
+            if !node.has_concurrent_instances() {
+                node.duplicate_since = None;
+            }
+            !node.instances.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, rand::thread_rng};
+
+    #[test]
+    fn test_node_instance_restart() {
+        let mut rng = thread_rng();
+        let pubkey = Pubkey::new_unique();
+        let mut tracker = NodeInstanceTracker::default();
+        let instance = NodeInstance::new(&mut rng, pubkey, 1_000);
+        assert_eq!(tracker.record(&instance), None);
+        assert_eq!(tracker.record(&instance.with_wallclock(2_000)), None);
+        // The new instance is created after the old one's last update.
+        let restarted = NodeInstance::new(&mut rng, pubkey, 3_000);
+        assert_eq!(tracker.record(&restarted), Some(NodeInstanceEvent::Restart));
+        assert_eq!(tracker.record(&restarted.with_wallclock(4_000)), None);
+        // Stale values of the old instance are still propagating.
+        assert_eq!(tracker.record(&instance.with_wallclock(2_500)), None);
+        assert_eq!(tracker.get_duplicate(&pubkey), None);
+        assert!(tracker.duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_node_instance_duplicate() {
+        let mut rng = thread_rng();
+        let pubkey = Pubkey::new_unique();
+        let mut tracker = NodeInstanceTracker::default();
+        let instance = NodeInstance::new(&mut rng, pubkey, 1_000);
+        let other = NodeInstance::new(&mut rng, pubkey, 2_000);
+        assert_eq!(tracker.record(&instance), None);
+        assert_eq!(tracker.record(&other), Some(NodeInstanceEvent::Restart));
+        // The old instance keeps running after the other one was created.
+        assert_eq!(
+            tracker.record(&instance.with_wallclock(3_000)),
+            Some(NodeInstanceEvent::Duplicate)
+        );
+        assert_eq!(tracker.record(&other.with_wallclock(4_000)), None);
+        assert_eq!(
+            tracker.get_duplicate(&pubkey),
+            Some(DuplicateInstance {
+                pubkey,
+                num_instances: 2,
+                first_detected: 3_000,
+                last_seen: 4_000,
+            })
+        );
+        assert_eq!(tracker.duplicates().len(), 1);
+
+        // Once the old instance stops, the duplicate ages out.
+        tracker.prune(3_000 + NODE_INSTANCE_RETENTION_MS + 1);
+        assert_eq!(tracker.get_duplicate(&pubkey), None);
+        tracker.prune(4_000 + NODE_INSTANCE_RETENTION_MS + 1);
+        assert!(tracker.nodes.is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+/// Backfill of the transaction statuses, rewards and block metadata of a ledger from a remote
+/// source, for RPC nodes with gaps in their history
+use log::*;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcBlockConfig};
+use solana_ledger::blockstore::Blockstore;
+use solana_sdk::{
+    clock::Slot, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    transaction::Transaction,
+};
+use solana_transaction_status::{
+    extract_and_fmt_memos, ConfirmedBlock, TransactionDetails, TransactionStatusMeta,
+    UiTransactionEncoding,
+};
+
+pub enum BlockSource {
+    Rpc(RpcClient),
+    Bigtable {
+        runtime: tokio::runtime::Runtime,
+        bigtable: solana_storage_bigtable::LedgerStorage,
+    },
+}
+
+impl BlockSource {
+    pub fn rpc(url: String) -> Self {
+        Self::Rpc(RpcClient::new_with_commitment(
+            url,
+            CommitmentConfig::finalized(),
+        ))
+    }
+
+    pub fn bigtable() -> Result<Self, String> {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let bigtable = runtime
+            .block_on(solana_storage_bigtable::LedgerStorage::new(true, None))
+            .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
+        Ok(Self::Bigtable { runtime, bigtable })
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<ConfirmedBlock, String> {
+        match self {
+            Self::Rpc(rpc_client) => rpc_client
+                .get_block_with_config(
+                    slot,
+                    RpcBlockConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        transaction_details: Some(TransactionDetails::Full),
+                        rewards: Some(true),
+                        commitment: Some(CommitmentConfig::finalized()),
+                    },
+                )
+                .map_err(|err| err.to_string())
+                .and_then(|block| {
+                    solana_transaction_status::EncodedConfirmedBlock::from(block)
+                        .decode()
+                        .ok_or_else(|| "undecodable block".to_string())
+                }),
+            Self::Bigtable { runtime, bigtable } => runtime
+                .block_on(bigtable.get_confirmed_block(slot))
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BackfillStats {
+    complete_slots: usize,
+    backfilled_slots: usize,
+    failed_slots: usize,
+    transaction_statuses: usize,
+}
+
+/// Checks that the remote block is the block stored locally, down to its transactions
+fn verify_block(local: &ConfirmedBlock, remote: &ConfirmedBlock) -> Result<(), String> {
+    if local.blockhash != remote.blockhash {
+        return Err(format!(
+            "blockhash mismatch, {} locally and {} remotely",
+            local.blockhash, remote.blockhash
+        ));
+    }
+    if local.parent_slot != remote.parent_slot {
+        return Err(format!(
+            "parent slot mismatch, {} locally and {} remotely",
+            local.parent_slot, remote.parent_slot
+        ));
+    }
+    // The previous blockhash is unknown when the parent isn't in the ledger
+    if local.previous_blockhash != Hash::default().to_string()
+        && local.previous_blockhash != remote.previous_blockhash
+    {
+        return Err(format!(
+            "previous blockhash mismatch, {} locally and {} remotely",
+            local.previous_blockhash, remote.previous_blockhash
+        ));
+    }
+    if local.transactions.len() != remote.transactions.len()
+        || local
+            .transactions
+            .iter()
+            .zip(&remote.transactions)
+            .any(|(local, remote)| local.transaction.signatures != remote.transaction.signatures)
+    {
+        return Err("transactions mismatch".to_string());
+    }
+    Ok(())
+}
+
+fn write_transaction_status(
+    blockstore: &Blockstore,
+    slot: Slot,
+    transaction: &Transaction,
+    meta: TransactionStatusMeta,
+) -> Result<(), String> {
+    let message = &transaction.message;
+    let (mut writable_keys, mut readonly_keys): (Vec<&Pubkey>, Vec<&Pubkey>) = (vec![], vec![]);
+    for (i, key) in message.account_keys.iter().enumerate() {
+        if message.is_writable(i, /*demote_program_write_locks=*/ true) {
+            writable_keys.push(key);
+        } else {
+            readonly_keys.push(key);
+        }
+    }
+    let loaded_addresses = meta.loaded_addresses.clone();
+    writable_keys.extend(&loaded_addresses.writable);
+    readonly_keys.extend(&loaded_addresses.readonly);
+
+    let signature = transaction.signatures[0];
+    if let Some(memos) = extract_and_fmt_memos(message) {
+        blockstore
+            .write_transaction_memos(&signature, memos)
+            .map_err(|err| format!("Failed to write memos of {}: {:?}", signature, err))?;
+    }
+    blockstore
+        .write_transaction_status(slot, signature, writable_keys, readonly_keys, meta)
+        .map_err(|err| format!("Failed to write status of {}: {:?}", signature, err))
+}
+
+/// Fills in the missing transaction statuses, rewards, block time and block height of a complete
+/// slot, once its block is verified against the remote one. Returns the number of transaction
+/// statuses written, or `None` if nothing was missing.
+fn backfill_slot(
+    blockstore: &Blockstore,
+    source: &BlockSource,
+    slot: Slot,
+) -> Result<Option<usize>, String> {
+    let local = blockstore
+        .get_complete_block(slot, false)
+        .map_err(|err| format!("Failed to read block: {:?}", err))?;
+    let missing_rewards = blockstore
+        .read_rewards(slot)
+        .map_err(|err| format!("Failed to read rewards: {:?}", err))?
+        .is_none();
+    let missing_statuses = local
+        .transactions
+        .iter()
+        .filter(|transaction| transaction.meta.is_none())
+        .count();
+    if missing_statuses == 0
+        && !missing_rewards
+        && local.block_time.is_some()
+        && local.block_height.is_some()
+    {
+        return Ok(None);
+    }
+
+    let remote = source.get_block(slot)?;
+    verify_block(&local, &remote)?;
+
+    let mut transaction_statuses = 0;
+    for (local_transaction, remote_transaction) in
+        local.transactions.iter().zip(remote.transactions)
+    {
+        if let (None, Some(meta)) = (&local_transaction.meta, remote_transaction.meta) {
+            write_transaction_status(blockstore, slot, &local_transaction.transaction, meta)?;
+            transaction_statuses += 1;
+        }
+    }
+    if missing_rewards {
+        blockstore
+            .write_rewards(slot, remote.rewards)
+            .map_err(|err| format!("Failed to write rewards: {:?}", err))?;
+    }
+    if let (None, Some(block_time)) = (local.block_time, remote.block_time) {
+        blockstore
+            .cache_block_time(slot, block_time)
+            .map_err(|err| format!("Failed to write block time: {:?}", err))?;
+    }
+    if let (None, Some(block_height)) = (local.block_height, remote.block_height) {
+        blockstore
+            .cache_block_height(slot, block_height)
+            .map_err(|err| format!("Failed to write block height: {:?}", err))?;
+    }
+    Ok(Some(transaction_statuses))
+}
+
+pub fn backfill(
+    blockstore: &Blockstore,
+    source: &BlockSource,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) {
+    let mut stats = BackfillStats::default();
+    for (slot, meta) in blockstore.slot_meta_iterator(starting_slot).unwrap() {
+        if slot > ending_slot {
+            break;
+        }
+        if !meta.is_full() {
+            // The entries of a block can't be backfilled, only what was derived from them
+            warn!(
+                "Slot {} is incomplete, its shreds must be repaired first",
+                slot
+            );
+            stats.failed_slots += 1;
+            continue;
+        }
+        match backfill_slot(blockstore, source, slot) {
+            Ok(None) => stats.complete_slots += 1,
+            Ok(Some(transaction_statuses)) => {
+                info!(
+                    "Slot {}: backfilled {} transaction statuses",
+                    slot, transaction_statuses
+                );
+                stats.backfilled_slots += 1;
+                stats.transaction_statuses += transaction_statuses;
+            }
+            Err(err) => {
+                warn!("Slot {}: {}", slot, err);
+                stats.failed_slots += 1;
+            }
+        }
+    }
+    println!(
+        "{} slots backfilled with {} transaction statuses, {} slots already complete, {} slots failed",
+        stats.backfilled_slots,
+        stats.transaction_statuses,
+        stats.complete_slots,
+        stats.failed_slots,
+    );
+}
@@ -26,10 +26,12 @@ async fn upload(
     ending_slot: Option<Slot>,
     allow_missing_metadata: bool,
     force_reupload: bool,
+    versioned_protobuf: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+    let mut bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
+    bigtable.set_write_versioned_protobuf(versioned_protobuf);
 
     solana_ledger::bigtable_upload::upload_confirmed_blocks(
         Arc::new(blockstore),
@@ -272,6 +274,15 @@ impl BigTableSubCommand for App<'_, '_> {
                                     Note: reupload will *not* delete any data from the tx-by-addr table;\
                                     Use with care.",
                                 ),
+                        )
+                        .arg(
+                            Arg::with_name("versioned_protobuf")
+                                .long("versioned-protobuf")
+                                .takes_value(false)
+                                .help(
+                                    "Upload protobuf cells with the versioned framing. \
+                                    Readers predating the framing can't decode them",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -426,6 +437,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
             let allow_missing_metadata = arg_matches.is_present("allow_missing_metadata");
             let force_reupload = arg_matches.is_present("force_reupload");
+            let versioned_protobuf = arg_matches.is_present("versioned_protobuf");
             let blockstore =
                 crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
 
@@ -435,6 +447,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 ending_slot,
                 allow_missing_metadata,
                 force_reupload,
+                versioned_protobuf,
             ))
         }
         ("delete-slots", Some(arg_matches)) => {
@@ -3,6 +3,7 @@ use clap::{
     crate_description, crate_name, value_t, value_t_or_exit, values_t_or_exit, App, AppSettings,
     Arg, ArgMatches, SubCommand,
 };
+use crossbeam_channel::unbounded;
 use dashmap::DashMap;
 use itertools::Itertools;
 use log::*;
@@ -12,21 +13,22 @@ use serde_json::json;
 use solana_clap_utils::{
     input_parsers::{cluster_type_of, pubkey_of, pubkeys_of},
     input_validators::{
-        is_parsable, is_pow2, is_pubkey, is_pubkey_or_keypair, is_slot, is_valid_percentage,
+        is_parsable, is_pow2, is_pubkey, is_pubkey_or_keypair, is_slot, is_url, is_valid_percentage,
     },
 };
-use solana_entry::entry::Entry;
+use solana_entry::entry::{Entry, EntrySlice};
 use solana_ledger::{
     ancestor_iterator::AncestorIterator,
     bank_forks_utils,
     blockstore::{create_new_ledger, Blockstore, PurgeType},
     blockstore_db::{self, AccessType, BlockstoreRecoveryMode, Column, Database},
-    blockstore_processor::ProcessOptions,
+    blockstore_processor::{ProcessOptions, TransactionStatusMessage, TransactionStatusSender},
+    leader_schedule_cache::LeaderScheduleCache,
     shred::Shred,
 };
 use solana_runtime::{
     accounts_db::AccountsDbConfig,
-    accounts_index::AccountsIndexConfig,
+    accounts_index::{AccountsIndexConfig, ScanConfig},
     bank::{Bank, RewardCalculationEvent},
     bank_forks::BankForks,
     cost_model::CostModel,
@@ -68,9 +70,12 @@ use std::{
     process::{exit, Command, Stdio},
     str::FromStr,
     sync::{mpsc::channel, Arc, RwLock},
+    thread,
 };
 
+mod backfill;
 mod bigtable;
+use backfill::*;
 use bigtable::*;
 
 #[derive(PartialEq)]
@@ -700,6 +705,7 @@ fn load_bank_forks(
     blockstore: &Blockstore,
     process_options: ProcessOptions,
     snapshot_archive_path: Option<PathBuf>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
 ) -> bank_forks_utils::LoadResult {
     let bank_snapshots_dir = blockstore
         .ledger_path()
@@ -748,13 +754,183 @@ fn load_bank_forks(
         None,
         snapshot_config.as_ref(),
         process_options,
-        None,
+        transaction_status_sender,
         None,
         accounts_package_sender,
         None,
     )
 }
 
+#[derive(Default)]
+struct BlockhashChainStats {
+    verified_slots: usize,
+    unchained_slots: usize,
+    incomplete_slots: usize,
+    unknown_leader_slots: usize,
+    failed_slots: usize,
+}
+
+/// Checks the entries of a slot against the last entry hash of its parent, and the signatures of
+/// its shreds against its leader. Returns the last entry hash of the slot.
+fn verify_slot_blockhash_chain(
+    blockstore: &Blockstore,
+    slot: Slot,
+    parent_last_hash: Option<Hash>,
+    leader: Pubkey,
+    genesis_config: &GenesisConfig,
+    allow_dead_slots: bool,
+) -> Result<Hash, String> {
+    let shreds = blockstore
+        .get_data_shreds_for_slot(slot, 0)
+        .and_then(|mut shreds| {
+            shreds.extend(blockstore.get_coding_shreds_for_slot(slot, 0)?);
+            Ok(shreds)
+        })
+        .map_err(|err| format!("Failed to load shreds: {:?}", err))?;
+    if let Some(shred) = shreds.iter().find(|shred| !shred.verify(&leader)) {
+        return Err(format!(
+            "Shred {} is not signed by the leader {}",
+            shred.index(),
+            leader
+        ));
+    }
+
+    let (entries, _num_shreds, _is_full) = blockstore
+        .get_slot_entries_with_shred_info(slot, 0, allow_dead_slots)
+        .map_err(|err| format!("Failed to load entries: {:?}", err))?;
+    let last_hash = entries
+        .last()
+        .map(|entry| entry.hash)
+        .ok_or_else(|| "No entries".to_string())?;
+    if let Some(parent_last_hash) = parent_last_hash {
+        if !entries.verify(&parent_last_hash) {
+            return Err("Entries don't chain from the last entry of the parent".to_string());
+        }
+    }
+    if let Some(hashes_per_tick) = genesis_config.poh_config.hashes_per_tick {
+        let mut tick_hash_count = 0;
+        if !entries.verify_tick_hash_count(&mut tick_hash_count, hashes_per_tick) {
+            return Err("Invalid tick hash count".to_string());
+        }
+    }
+    Ok(last_hash)
+}
+
+/// Verifies the PoH chaining of the entries and the signatures of the shreds of every complete
+/// slot in the range, without executing their transactions
+fn verify_blockhash_chain(
+    blockstore: &Blockstore,
+    genesis_config: &GenesisConfig,
+    bank: &Bank,
+    leader_schedule_cache: &LeaderScheduleCache,
+    starting_slot: Slot,
+    ending_slot: Option<Slot>,
+    allow_dead_slots: bool,
+) -> BlockhashChainStats {
+    const MAX_CACHED_LAST_HASHES: u64 = 1024;
+
+    let mut stats = BlockhashChainStats::default();
+    let mut last_hashes = BTreeMap::new();
+    for (slot, meta) in blockstore.slot_meta_iterator(starting_slot).unwrap() {
+        if ending_slot
+            .map(|ending_slot| slot > ending_slot)
+            .unwrap_or(false)
+        {
+            break;
+        }
+        if !meta.is_full() || (!allow_dead_slots && blockstore.is_dead(slot)) {
+            stats.incomplete_slots += 1;
+            continue;
+        }
+
+        let parent_last_hash = if slot == 0 {
+            Some(genesis_config.hash())
+        } else if let Some(last_hash) = last_hashes.get(&meta.parent_slot) {
+            Some(*last_hash)
+        } else {
+            blockstore
+                .get_slot_entries(meta.parent_slot, 0)
+                .ok()
+                .and_then(|entries| entries.last().map(|entry| entry.hash))
+        };
+        let leader = match leader_schedule_cache.slot_leader_at(slot, Some(bank)) {
+            Some(leader) => leader,
+            None => {
+                // The shreds can't be checked against a leader the bank doesn't know of
+                eprintln!("Slot {}: skipped, unknown leader", slot);
+                stats.unknown_leader_slots += 1;
+                continue;
+            }
+        };
+        if parent_last_hash.is_none() {
+            stats.unchained_slots += 1;
+        }
+
+        match verify_slot_blockhash_chain(
+            blockstore,
+            slot,
+            parent_last_hash,
+            leader,
+            genesis_config,
+            allow_dead_slots,
+        ) {
+            Ok(last_hash) => {
+                stats.verified_slots += 1;
+                last_hashes.insert(slot, last_hash);
+                last_hashes = last_hashes.split_off(&slot.saturating_sub(MAX_CACHED_LAST_HASHES));
+            }
+            Err(err) => {
+                eprintln!("Slot {}: {}", slot, err);
+                stats.failed_slots += 1;
+            }
+        }
+    }
+    stats
+}
+
+/// Lists the accounts which aren't rent-exempt grouped by owner, one `ADDRESS LAMPORTS` line per
+/// account with the lamports it lacks to be rent-exempt, the other lines being `#` comments
+fn print_rent_paying_accounts(
+    rent: &Rent,
+    accounts: impl Iterator<Item = (Pubkey, AccountSharedData)>,
+) {
+    let mut rent_paying_accounts_by_owner = BTreeMap::<Pubkey, Vec<(Pubkey, u64)>>::new();
+    for (pubkey, account) in accounts {
+        let minimum_balance = rent.minimum_balance(account.data().len());
+        if account.lamports() > 0 && account.lamports() < minimum_balance {
+            rent_paying_accounts_by_owner
+                .entry(*account.owner())
+                .or_default()
+                .push((pubkey, minimum_balance - account.lamports()));
+        }
+    }
+
+    let mut total_accounts = 0;
+    let mut total_lamports = 0;
+    for (owner, rent_paying_accounts) in rent_paying_accounts_by_owner {
+        let lamports: u64 = rent_paying_accounts
+            .iter()
+            .map(|(_, lamports)| lamports)
+            .sum();
+        println!(
+            "# {}: {} rent-paying accounts, {} SOL needed for rent exemption",
+            owner,
+            rent_paying_accounts.len(),
+            lamports_to_sol(lamports)
+        );
+        for (pubkey, lamports) in &rent_paying_accounts {
+            println!("{} {}", pubkey, lamports);
+        }
+        total_accounts += rent_paying_accounts.len();
+        total_lamports += lamports;
+    }
+    println!(
+        "# Total: {} rent-paying accounts, {} SOL needed for rent exemption",
+        total_accounts,
+        lamports_to_sol(total_lamports)
+    );
+}
+
 fn compute_slot_cost(blockstore: &Blockstore, slot: Slot) -> Result<(), String> {
     if blockstore.is_dead(slot) {
         return Err("Dead slot".to_string());
@@ -1077,8 +1253,32 @@ fn main() {
                     .long("target-db")
                     .value_name("PATH")
                     .takes_value(true)
+                    .required_unless_one(&["from_rpc", "from_bigtable"])
                     .help("Target db"),
             )
+            .arg(
+                Arg::with_name("from_rpc")
+                    .long("from-rpc")
+                    .value_name("URL")
+                    .takes_value(true)
+                    .validator(is_url)
+                    .conflicts_with_all(&["target_db", "from_bigtable"])
+                    .help("Instead of copying the ledger, backfill its missing transaction \
+                           statuses, rewards, block times and block heights from the blocks \
+                           served by this RPC node"),
+            )
+            .arg(
+                Arg::with_name("from_bigtable")
+                    .long("from-bigtable")
+                    .takes_value(false)
+                    .conflicts_with("target_db")
+                    .help("Instead of copying the ledger, backfill its missing transaction \
+                           statuses, rewards, block times and block heights from the blocks \
+                           stored in bigtable"),
+            )
+            .after_help("Note: a block is only backfilled when its shreds are complete in the \
+                         ledger, and its blockhash, parent and transactions match the remote \
+                         block")
         )
         .subcommand(
             SubCommand::with_name("slot")
@@ -1237,12 +1437,47 @@ fn main() {
                     .takes_value(false)
                     .help("Skip ledger PoH verification"),
             )
+            .arg(
+                Arg::with_name("check_blockhash_chain_only")
+                    .long("check-blockhash-chain-only")
+                    .takes_value(false)
+                    .conflicts_with("skip_poh_verify")
+                    .help("Only check that the entries of each complete slot chain from its \
+                           parent and that its shreds are signed by its leader, without \
+                           executing transactions. Slots are checked from --starting-slot up \
+                           to --halt-at-slot. The ledger is only replayed up to --starting-slot, \
+                           to find the leader schedules, if no snapshot reaches it. Slots whose \
+                           leader is still unknown are skipped"),
+            )
+            .arg(&starting_slot_arg)
             .arg(
                 Arg::with_name("print_accounts_stats")
                     .long("print-accounts-stats")
                     .takes_value(false)
                     .help("After verifying the ledger, print some information about the account stores"),
             )
+        ).subcommand(
+            SubCommand::with_name("replay-range")
+            .about("Replay a range of slots and print the execution time, compute units and \
+                    loaded account bytes of each of their transactions as CSV")
+            .after_help("Note: the slots before --starting-slot are replayed without being \
+                         reported, use a snapshot close to it to avoid replaying them")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&hard_forks_arg)
+            .arg(&no_bpf_jit_arg)
+            .arg(&allow_dead_slots_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&starting_slot_arg)
+            .arg(
+                Arg::with_name("ending_slot")
+                    .long("ending-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Last slot to replay"),
+            )
         ).subcommand(
             SubCommand::with_name("graph")
             .about("Create a Graphviz rendering of the ledger")
@@ -1436,6 +1671,15 @@ fn main() {
                     .takes_value(false)
                     .help("Do not print account data when printing account contents."),
             )
+            .arg(
+                Arg::with_name("rent_paying")
+                    .long("rent-paying")
+                    .takes_value(false)
+                    .conflicts_with_all(&["no_account_contents", "no_account_data"])
+                    .help("Only list the accounts which aren't rent-exempt, grouped by owner, \
+                           with the lamports each one needs to become rent-exempt. The list \
+                           can be passed to `solana top-up-rent-exemption`"),
+            )
             .arg(&max_genesis_archive_unpacked_size_arg)
         ).subcommand(
             SubCommand::with_name("capitalization")
@@ -1588,6 +1832,12 @@ fn main() {
                 .about("Output statistics in JSON format about \
                         all column families in the ledger rocksdb")
         )
+        .subcommand(
+            SubCommand::with_name("migrate-status-columns")
+                .about("Rewrite transaction status and rewards entries still stored \
+                        as bincode or unversioned protobuf as versioned protobuf. \
+                        Releases predating the versioned framing can't read them back")
+        )
         .subcommand(
             SubCommand::with_name("compute-slot-cost")
             .about("runs cost_model over the block at the given slots, \
@@ -1661,16 +1911,32 @@ fn main() {
         ("copy", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
             let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
-            let target_db = PathBuf::from(value_t_or_exit!(arg_matches, "target_db", String));
-            let source = open_blockstore(&ledger_path, AccessType::TryPrimaryThenSecondary, None);
-            let target = open_blockstore(&target_db, AccessType::PrimaryOnly, None);
-            for (slot, _meta) in source.slot_meta_iterator(starting_slot).unwrap() {
-                if slot > ending_slot {
-                    break;
-                }
-                if let Ok(shreds) = source.get_data_shreds_for_slot(slot, 0) {
-                    if target.insert_shreds(shreds, None, true).is_err() {
-                        warn!("error inserting shreds for slot {}", slot);
+            let block_source = if let Some(url) = arg_matches.value_of("from_rpc") {
+                Some(BlockSource::rpc(url.to_string()))
+            } else if arg_matches.is_present("from_bigtable") {
+                Some(BlockSource::bigtable().unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    exit(1);
+                }))
+            } else {
+                None
+            };
+            if let Some(block_source) = block_source {
+                let blockstore = open_blockstore(&ledger_path, AccessType::PrimaryOnly, None);
+                backfill(&blockstore, &block_source, starting_slot, ending_slot);
+            } else {
+                let target_db = PathBuf::from(value_t_or_exit!(arg_matches, "target_db", String));
+                let source =
+                    open_blockstore(&ledger_path, AccessType::TryPrimaryThenSecondary, None);
+                let target = open_blockstore(&target_db, AccessType::PrimaryOnly, None);
+                for (slot, _meta) in source.slot_meta_iterator(starting_slot).unwrap() {
+                    if slot > ending_slot {
+                        break;
+                    }
+                    if let Ok(shreds) = source.get_data_shreds_for_slot(slot, 0) {
+                        if target.insert_shreds(shreds, None, true).is_err() {
+                            warn!("error inserting shreds for slot {}", slot);
+                        }
                     }
                 }
             }
@@ -1732,6 +1998,7 @@ fn main() {
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             ) {
                 Ok((bank_forks, ..)) => {
                     println!(
@@ -1808,6 +2075,7 @@ fn main() {
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             ) {
                 Ok((bank_forks, ..)) => {
                     println!("{}", &bank_forks.working_bank().hash());
@@ -2035,12 +2303,58 @@ fn main() {
                 AccessType::TryPrimaryThenSecondary,
                 wal_recovery_mode,
             );
+            if arg_matches.is_present("check_blockhash_chain_only") {
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+                // The leader schedules are computed from the bank at the starting slot, which only
+                // needs to be replayed when no snapshot already reaches it
+                let (bank_forks, leader_schedule_cache, ..) = load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    ProcessOptions {
+                        dev_halt_at_slot: Some(starting_slot),
+                        poh_verify: false,
+                        ..process_options
+                    },
+                    snapshot_archive_path,
+                    None,
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to load the root bank: {:?}", err);
+                    exit(1);
+                });
+                let stats = verify_blockhash_chain(
+                    &blockstore,
+                    &genesis_config,
+                    &bank_forks.working_bank(),
+                    &leader_schedule_cache,
+                    starting_slot,
+                    value_t!(arg_matches, "halt_at_slot", Slot).ok(),
+                    arg_matches.is_present("allow_dead_slots"),
+                );
+                println!(
+                    "{} slots verified, {} without their parent, {} slots with an unknown \
+                     leader and {} incomplete slots skipped, {} slots failed",
+                    stats.verified_slots,
+                    stats.unchained_slots,
+                    stats.unknown_leader_slots,
+                    stats.incomplete_slots,
+                    stats.failed_slots,
+                );
+                if stats.failed_slots > 0 {
+                    exit(1);
+                }
+                println!("Ok");
+                return;
+            }
             let (bank_forks, ..) = load_bank_forks(
                 arg_matches,
                 &open_genesis_config_by(&ledger_path, arg_matches),
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             )
             .unwrap_or_else(|err| {
                 eprintln!("Ledger verification failed: {:?}", err);
@@ -2052,6 +2366,86 @@ fn main() {
             }
             println!("Ok");
         }
+        ("replay-range", Some(arg_matches)) => {
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
+            if ending_slot < starting_slot {
+                eprintln!(
+                    "Ending slot {} is before starting slot {}",
+                    ending_slot, starting_slot
+                );
+                exit(1);
+            }
+            let process_options = ProcessOptions {
+                dev_halt_at_slot: Some(ending_slot),
+                new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                poh_verify: false,
+                bpf_jit: !matches.is_present("no_bpf_jit"),
+                allow_dead_slots: arg_matches.is_present("allow_dead_slots"),
+                ..ProcessOptions::default()
+            };
+            let blockstore = open_blockstore(
+                &ledger_path,
+                AccessType::TryPrimaryThenSecondary,
+                wal_recovery_mode,
+            );
+
+            let (sender, receiver) = unbounded();
+            let transaction_status_sender = TransactionStatusSender {
+                sender,
+                enable_cpi_and_log_storage: false,
+            };
+            let printer = thread::spawn(move || {
+                println!(
+                    "slot,signature,status,execute_us,compute_units,loaded_accounts_data_size"
+                );
+                for message in receiver.iter() {
+                    let batch = match message {
+                        TransactionStatusMessage::Batch(batch) => batch,
+                        TransactionStatusMessage::Freeze(_) => continue,
+                    };
+                    let slot = batch.bank.slot();
+                    if slot < starting_slot {
+                        continue;
+                    }
+                    for ((transaction, (status, _)), details) in batch
+                        .transactions
+                        .iter()
+                        .zip(&batch.statuses)
+                        .zip(&batch.execution_details)
+                    {
+                        let status = match status {
+                            Ok(()) => "ok".to_string(),
+                            Err(err) => format!("\"{}\"", err.to_string().replace('"', "\"\"")),
+                        };
+                        println!(
+                            "{},{},{},{},{},{}",
+                            slot,
+                            transaction.signature(),
+                            status,
+                            details.execute_us,
+                            details.executed_units,
+                            details.loaded_accounts_data_size,
+                        );
+                    }
+                }
+            });
+
+            let result = load_bank_forks(
+                arg_matches,
+                &open_genesis_config_by(&ledger_path, arg_matches),
+                &blockstore,
+                process_options,
+                snapshot_archive_path,
+                Some(&transaction_status_sender),
+            );
+            drop(transaction_status_sender);
+            printer.join().unwrap();
+            if let Err(err) = result {
+                eprintln!("Ledger replay failed: {:?}", err);
+                exit(1);
+            }
+        }
         ("graph", Some(arg_matches)) => {
             let output_file = value_t_or_exit!(arg_matches, "graph_filename", String);
 
@@ -2073,6 +2467,7 @@ fn main() {
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             ) {
                 Ok((bank_forks, ..)) => {
                     let dot = graph_forks(&bank_forks, arg_matches.is_present("include_all_votes"));
@@ -2187,6 +2582,7 @@ fn main() {
                     ..ProcessOptions::default()
                 },
                 snapshot_archive_path,
+                None,
             ) {
                 Ok((bank_forks, .., starting_snapshot_hashes)) => {
                     let mut bank = bank_forks
@@ -2232,7 +2628,7 @@ fn main() {
 
                     if remove_stake_accounts {
                         for (address, mut account) in bank
-                            .get_program_accounts(&stake::program::id())
+                            .get_program_accounts(&stake::program::id(), &ScanConfig::default())
                             .unwrap()
                             .into_iter()
                         {
@@ -2256,7 +2652,7 @@ fn main() {
 
                     if !vote_accounts_to_destake.is_empty() {
                         for (address, mut account) in bank
-                            .get_program_accounts(&stake::program::id())
+                            .get_program_accounts(&stake::program::id(), &ScanConfig::default())
                             .unwrap()
                             .into_iter()
                         {
@@ -2294,7 +2690,10 @@ fn main() {
 
                         // Delete existing vote accounts
                         for (address, mut account) in bank
-                            .get_program_accounts(&solana_vote_program::id())
+                            .get_program_accounts(
+                                &solana_vote_program::id(),
+                                &ScanConfig::default(),
+                            )
                             .unwrap()
                             .into_iter()
                         {
@@ -2488,6 +2887,7 @@ fn main() {
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             )
             .unwrap_or_else(|err| {
                 eprintln!("Failed to load ledger: {:?}", err);
@@ -2505,6 +2905,16 @@ fn main() {
                 .map(|(pubkey, account, slot)| (pubkey, (account, slot)))
                 .collect();
 
+            if arg_matches.is_present("rent_paying") {
+                print_rent_paying_accounts(
+                    &bank.rent_collector().rent,
+                    accounts
+                        .into_iter()
+                        .map(|(pubkey, (account, _slot))| (pubkey, account)),
+                );
+                return;
+            }
+
             let print_account_contents = !arg_matches.is_present("no_account_contents");
             if print_account_contents {
                 println!("Getting accounts contents...");
@@ -2544,6 +2954,7 @@ fn main() {
                 &blockstore,
                 process_options,
                 snapshot_archive_path,
+                None,
             ) {
                 Ok((bank_forks, ..)) => {
                     let slot = bank_forks.working_bank().slot();
@@ -3242,6 +3653,23 @@ fn main() {
                 }
             };
         }
+        ("migrate-status-columns", _) => {
+            let blockstore =
+                open_blockstore(&ledger_path, AccessType::PrimaryOnly, wal_recovery_mode);
+            match blockstore.migrate_status_columns() {
+                Ok(stats) => println!(
+                    "Migrated {} of {} transaction status entries and {} of {} rewards entries",
+                    stats.transaction_statuses_migrated,
+                    stats.transaction_statuses_scanned,
+                    stats.rewards_migrated,
+                    stats.rewards_scanned,
+                ),
+                Err(err) => {
+                    eprintln!("Failed to migrate status columns: {:?}", err);
+                    exit(1);
+                }
+            }
+        }
         ("analyze-storage", _) => {
             analyze_storage(&open_database(
                 &ledger_path,
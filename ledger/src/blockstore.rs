@@ -17,15 +17,15 @@ use {
     },
     bincode::deserialize,
     log::*,
-    rayon::{
-        iter::{IntoParallelRefIterator, ParallelIterator},
-        ThreadPool,
-    },
+    rayon::iter::{IntoParallelRefIterator, ParallelIterator},
     rocksdb::DBRawIterator,
     solana_entry::entry::{create_ticks, Entry},
     solana_measure::measure::Measure,
     solana_metrics::{datapoint_debug, datapoint_error},
-    solana_rayon_threadlimit::get_thread_count,
+    solana_rayon_threadlimit::{
+        get_thread_count,
+        thread_pools::{ManagedThreadPool, BLOCKSTORE_PURGE_THREAD_POOL, BLOCKSTORE_THREAD_POOL},
+    },
     solana_runtime::hardened_unpack::{unpack_genesis_archive, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
     solana_sdk::{
         clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
@@ -68,17 +68,27 @@ pub mod blockstore_purge;
 
 pub const BLOCKSTORE_DIRECTORY: &str = "rocksdb";
 
-thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::ThreadPoolBuilder::new()
-                    .num_threads(get_thread_count())
-                    .thread_name(|ix| format!("blockstore_{}", ix))
-                    .build()
-                    .unwrap()));
-
-thread_local!(static PAR_THREAD_POOL_ALL_CPUS: RefCell<ThreadPool> = RefCell::new(rayon::ThreadPoolBuilder::new()
-                    .num_threads(num_cpus::get())
-                    .thread_name(|ix| format!("blockstore_{}", ix))
-                    .build()
-                    .unwrap()));
+lazy_static! {
+    static ref PAR_THREAD_POOL: Arc<ManagedThreadPool> =
+        ManagedThreadPool::new(BLOCKSTORE_THREAD_POOL, get_thread_count(), |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("blockstore_{}", ix))
+                .build()
+                .unwrap()
+        });
+    static ref PAR_THREAD_POOL_ALL_CPUS: Arc<ManagedThreadPool> = ManagedThreadPool::new(
+        BLOCKSTORE_PURGE_THREAD_POOL,
+        num_cpus::get(),
+        |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("blockstore_{}", ix))
+                .build()
+                .unwrap()
+        }
+    );
+}
 
 pub const MAX_COMPLETED_SLOTS_IN_CHANNEL: usize = 100_000;
 pub const MAX_TURBINE_PROPAGATION_IN_MS: u64 = 100;
@@ -89,6 +99,9 @@ pub const MAX_TURBINE_DELAY_IN_TICKS: u64 = MAX_TURBINE_PROPAGATION_IN_MS / MS_P
 // (32K shreds per slot * 4 TX per shred * 2.5 slots per sec)
 pub const MAX_DATA_SHREDS_PER_SLOT: usize = 32_768;
 
+// Upper bound on the number of slots quarantined for shreds not signed by their leader
+const MAX_LEADER_MISMATCH_QUARANTINED_SLOTS: usize = 1_024;
+
 pub type CompletedSlotsSender = SyncSender<Vec<Slot>>;
 pub type CompletedSlotsReceiver = Receiver<Vec<Slot>>;
 type CompletedRanges = Vec<(u32, u32)>;
@@ -113,6 +126,14 @@ impl std::fmt::Display for InsertDataShredError {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusColumnsMigrationStats {
+    pub transaction_statuses_migrated: usize,
+    pub transaction_statuses_scanned: usize,
+    pub rewards_migrated: usize,
+    pub rewards_scanned: usize,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CompletedDataSetInfo {
     pub slot: Slot,
@@ -156,6 +177,9 @@ pub struct Blockstore {
     pub lowest_cleanup_slot: Arc<RwLock<Slot>>,
     no_compaction: bool,
     slots_stats: Arc<Mutex<SlotsStats>>,
+    // Slots which received shreds not signed by their leader, with the number of such shreds.
+    // All the untrusted shreds of a quarantined slot have their signature checked on insert.
+    leader_mismatch_quarantine: Mutex<BTreeMap<Slot, usize>>,
 }
 
 struct SlotsStats {
@@ -226,6 +250,9 @@ pub struct BlockstoreInsertionMetrics {
     num_coding_shreds_invalid: usize,
     num_coding_shreds_invalid_erasure_config: usize,
     num_coding_shreds_inserted: usize,
+    num_shreds_duplicate_in_batch: usize,
+    num_shreds_leader_mismatch: usize,
+    num_slots_quarantined: usize,
 }
 
 impl SlotMetaWorkingSetEntry {
@@ -317,6 +344,17 @@ impl BlockstoreInsertionMetrics {
                 self.num_coding_shreds_inserted,
                 i64
             ),
+            (
+                "num_shreds_duplicate_in_batch",
+                self.num_shreds_duplicate_in_batch,
+                i64
+            ),
+            (
+                "num_shreds_leader_mismatch",
+                self.num_shreds_leader_mismatch,
+                i64
+            ),
+            ("num_slots_quarantined", self.num_slots_quarantined, i64),
         );
     }
 }
@@ -449,8 +487,10 @@ impl Blockstore {
             lowest_cleanup_slot: Arc::new(RwLock::new(0)),
             no_compaction: false,
             slots_stats: Arc::new(Mutex::new(SlotsStats::default())),
+            leader_mismatch_quarantine: Mutex::default(),
         };
-        if initialize_transaction_status_index {
+        // A secondary instance can't write, the primary one initializes the index instead
+        if initialize_transaction_status_index && blockstore.is_primary_access() {
             blockstore.initialize_transaction_status_index()?;
         }
         Ok(blockstore)
@@ -531,6 +571,15 @@ impl Blockstore {
         self.no_compaction = no_compaction;
     }
 
+    /// Write TransactionStatus and Rewards entries with the versioned protobuf framing. Leave it
+    /// unset while the ledger may still be read by a release that predates the framing.
+    pub fn set_write_versioned_protobuf(&mut self, write_versioned_protobuf: bool) {
+        self.transaction_status_cf
+            .set_write_versioned_protobuf(write_versioned_protobuf);
+        self.rewards_cf
+            .set_write_versioned_protobuf(write_versioned_protobuf);
+    }
+
     pub fn destroy(ledger_path: &Path) -> Result<()> {
         // Database::destroy() fails if the path doesn't exist
         fs::create_dir_all(ledger_path)?;
@@ -871,7 +920,32 @@ impl Blockstore {
         let mut index_meta_time = 0;
         let mut newly_completed_data_sets: Vec<CompletedDataSetInfo> = vec![];
         let mut inserted_indices = Vec::new();
-        for (i, (shred, is_repaired)) in shreds.into_iter().zip(is_repaired).enumerate() {
+        let is_duplicate_in_batch = if is_trusted {
+            vec![false; shreds.len()]
+        } else {
+            Self::find_duplicates_in_batch(&shreds)
+        };
+        let mut slot_leaders = HashMap::new();
+        for (i, ((shred, is_repaired), is_duplicate_in_batch)) in shreds
+            .into_iter()
+            .zip(is_repaired)
+            .zip(is_duplicate_in_batch)
+            .enumerate()
+        {
+            if !is_trusted {
+                if is_duplicate_in_batch {
+                    metrics.num_shreds_duplicate_in_batch += 1;
+                    continue;
+                }
+                let leader = *slot_leaders.entry(shred.slot()).or_insert_with(|| {
+                    leader_schedule.and_then(|leader_schedule| {
+                        leader_schedule.slot_leader_at(shred.slot(), /*bank=*/ None)
+                    })
+                });
+                if !self.check_shred_leader(&shred, is_repaired, leader, metrics) {
+                    continue;
+                }
+            }
             if shred.is_data() {
                 let shred_source = if is_repaired {
                     ShredSource::Repaired
@@ -1043,6 +1117,86 @@ impl Blockstore {
         Ok((newly_completed_data_sets, inserted_indices))
     }
 
+    /// Flags the shreds which are exact copies of an earlier shred of the batch. Copies with a
+    /// different payload are left to the duplicate shred handling.
+    fn find_duplicates_in_batch(shreds: &[Shred]) -> Vec<bool> {
+        let mut payloads = HashMap::<(Slot, u32, bool), &[u8]>::new();
+        shreds
+            .iter()
+            .map(
+                |shred| match payloads.entry((shred.slot(), shred.index(), shred.is_data())) {
+                    HashMapEntry::Vacant(entry) => {
+                        entry.insert(&shred.payload);
+                        false
+                    }
+                    HashMapEntry::Occupied(entry) => *entry.get() == &shred.payload[..],
+                },
+            )
+            .collect()
+    }
+
+    /// Checks the signature of an untrusted shred against the leader of its slot, when known.
+    /// Repaired shreds are always checked since any peer can answer a repair request, while
+    /// turbine shreds were checked by sigverify and are only checked again once their slot is
+    /// quarantined.
+    fn check_shred_leader(
+        &self,
+        shred: &Shred,
+        is_repaired: bool,
+        leader: Option<Pubkey>,
+        metrics: &mut BlockstoreInsertionMetrics,
+    ) -> bool {
+        let leader = match leader {
+            Some(leader) => leader,
+            None => return true,
+        };
+        let slot = shred.slot();
+        if !is_repaired
+            && !self
+                .leader_mismatch_quarantine
+                .lock()
+                .unwrap()
+                .contains_key(&slot)
+        {
+            return true;
+        }
+        if shred.verify(&leader) {
+            return true;
+        }
+
+        metrics.num_shreds_leader_mismatch += 1;
+        let mut quarantine = self.leader_mismatch_quarantine.lock().unwrap();
+        let num_mismatches = quarantine.entry(slot).or_default();
+        if *num_mismatches == 0 {
+            warn!(
+                "Quarantining slot {}, received shred {} not signed by leader {}",
+                slot,
+                shred.index(),
+                leader
+            );
+            metrics.num_slots_quarantined += 1;
+        }
+        *num_mismatches += 1;
+        if quarantine.len() > MAX_LEADER_MISMATCH_QUARANTINED_SLOTS {
+            *quarantine = quarantine.split_off(&self.last_root());
+            while quarantine.len() > MAX_LEADER_MISMATCH_QUARANTINED_SLOTS {
+                let oldest_slot = *quarantine.keys().next().unwrap();
+                quarantine.remove(&oldest_slot);
+            }
+        }
+        false
+    }
+
+    /// Number of shreds of `slot` rejected on insert for not being signed by its leader
+    pub fn leader_mismatch_count(&self, slot: Slot) -> usize {
+        self.leader_mismatch_quarantine
+            .lock()
+            .unwrap()
+            .get(&slot)
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub fn clear_unconfirmed_slot(&self, slot: Slot) {
         let _lock = self.insert_shreds_lock.lock().unwrap();
         if let Some(mut slot_meta) = self
@@ -2721,6 +2875,23 @@ impl Blockstore {
         self.rewards_cf.put_protobuf(index, &rewards)
     }
 
+    /// Rewrite TransactionStatus and Rewards entries still stored as bincode or unversioned
+    /// protobuf as versioned protobuf, so that they no longer need the legacy decoding path
+    pub fn migrate_status_columns(&self) -> Result<StatusColumnsMigrationStats> {
+        let (transaction_statuses_migrated, transaction_statuses_scanned) = self
+            .transaction_status_cf
+            .migrate_to_versioned_protobuf::<StoredTransactionStatusMeta>()?;
+        let (rewards_migrated, rewards_scanned) = self
+            .rewards_cf
+            .migrate_to_versioned_protobuf::<StoredExtendedRewards>()?;
+        Ok(StatusColumnsMigrationStats {
+            transaction_statuses_migrated,
+            transaction_statuses_scanned,
+            rewards_migrated,
+            rewards_scanned,
+        })
+    }
+
     pub fn get_recent_perf_samples(&self, num: usize) -> Result<Vec<(Slot, PerfSample)>> {
         Ok(self
             .db
@@ -2789,20 +2960,13 @@ impl Blockstore {
             .map(|(_, end_index)| u64::from(*end_index) - start_index + 1)
             .unwrap_or(0);
 
-        let entries: Result<Vec<Vec<Entry>>> = PAR_THREAD_POOL.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                completed_ranges
-                    .par_iter()
-                    .map(|(start_index, end_index)| {
-                        self.get_entries_in_data_block(
-                            slot,
-                            *start_index,
-                            *end_index,
-                            Some(&slot_meta),
-                        )
-                    })
-                    .collect()
-            })
+        let entries: Result<Vec<Vec<Entry>>> = PAR_THREAD_POOL.install(|| {
+            completed_ranges
+                .par_iter()
+                .map(|(start_index, end_index)| {
+                    self.get_entries_in_data_block(slot, *start_index, *end_index, Some(&slot_meta))
+                })
+                .collect()
         });
 
         let entries: Vec<Entry> = entries?.into_iter().flatten().collect();
@@ -2934,21 +3098,14 @@ impl Blockstore {
         }
         let slot_meta = slot_meta.unwrap();
 
-        let entries: Vec<Vec<Entry>> = PAR_THREAD_POOL_ALL_CPUS.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                completed_ranges
-                    .par_iter()
-                    .map(|(start_index, end_index)| {
-                        self.get_entries_in_data_block(
-                            slot,
-                            *start_index,
-                            *end_index,
-                            Some(&slot_meta),
-                        )
+        let entries: Vec<Vec<Entry>> = PAR_THREAD_POOL_ALL_CPUS.install(|| {
+            completed_ranges
+                .par_iter()
+                .map(|(start_index, end_index)| {
+                    self.get_entries_in_data_block(slot, *start_index, *end_index, Some(&slot_meta))
                         .unwrap_or_default()
-                    })
-                    .collect()
-            })
+                })
+                .collect()
         });
 
         entries.into_iter().flatten().collect()
@@ -3190,6 +3347,11 @@ impl Blockstore {
         self.db.is_primary_access()
     }
 
+    /// Makes the writes of the primary instance visible to this secondary instance
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()
+    }
+
     pub fn scan_and_fix_roots(&self, exit: &Arc<AtomicBool>) -> Result<()> {
         let ancestor_iterator = AncestorIterator::new(self.last_root(), self)
             .take_while(|&slot| slot >= self.lowest_cleanup_slot());
@@ -4034,15 +4196,22 @@ pub mod tests {
     use solana_entry::entry::{next_entry, next_entry_mut};
     use solana_runtime::bank::{Bank, RewardType};
     use solana_sdk::{
+        compute_budget::ComputeBudgetExceeded,
         hash::{self, hash, Hash},
         instruction::CompiledInstruction,
+        message::MappedAddresses,
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
         signature::Signature,
         transaction::{Transaction, TransactionError},
     };
-    use solana_storage_proto::convert::generated;
-    use solana_transaction_status::{InnerInstructions, Reward, Rewards, TransactionTokenBalance};
+    use solana_storage_proto::{
+        convert::generated,
+        versioned::{self, StoredFormat},
+    };
+    use solana_transaction_status::{
+        FeeDetails, InnerInstructions, Reward, Rewards, TransactionTokenBalance,
+    };
     use std::{sync::mpsc::channel, thread::Builder, time::Duration};
 
     // used for tests only
@@ -6144,6 +6313,11 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: MappedAddresses::default(),
+                    compute_budget_exceeded: None,
+                    compute_units_consumed: None,
+                    fee_details: None,
+                    loaded_accounts_data_size: None,
                 }
                 .into();
                 blockstore
@@ -6160,6 +6334,11 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: MappedAddresses::default(),
+                    compute_budget_exceeded: None,
+                    compute_units_consumed: None,
+                    fee_details: None,
+                    loaded_accounts_data_size: None,
                 }
                 .into();
                 blockstore
@@ -6176,6 +6355,11 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    loaded_addresses: MappedAddresses::default(),
+                    compute_budget_exceeded: None,
+                    compute_units_consumed: None,
+                    fee_details: None,
+                    loaded_accounts_data_size: None,
                 }
                 .into();
                 blockstore
@@ -6194,6 +6378,11 @@ pub mod tests {
                         pre_token_balances: Some(vec![]),
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
+                        loaded_addresses: MappedAddresses::default(),
+                        compute_budget_exceeded: None,
+                        compute_units_consumed: None,
+                        fee_details: None,
+                        loaded_accounts_data_size: None,
                     }),
                 }
             })
@@ -6301,6 +6490,21 @@ pub mod tests {
         let pre_token_balances_vec = vec![];
         let post_token_balances_vec = vec![];
         let rewards_vec = vec![];
+        let test_loaded_addresses = MappedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
+        let test_compute_budget_exceeded = ComputeBudgetExceeded {
+            instruction_index: 1,
+            program_id: Pubkey::new_unique(),
+            units_consumed: vec![100, 400],
+            units_limit: 500,
+        };
+        let test_fee_details = FeeDetails {
+            signature_fee: 5,
+            precompile_signature_fee: 0,
+            prioritization_fee: 0,
+        };
 
         // result not found
         assert!(transaction_status_cf
@@ -6319,6 +6523,11 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            loaded_addresses: test_loaded_addresses.clone(),
+            compute_budget_exceeded: Some(test_compute_budget_exceeded.clone()),
+            compute_units_consumed: Some(500),
+            fee_details: Some(test_fee_details),
+            loaded_accounts_data_size: Some(1_000),
         }
         .into();
         assert!(transaction_status_cf
@@ -6336,6 +6545,11 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((0, Signature::default(), 0))
             .unwrap()
@@ -6351,6 +6565,11 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert_eq!(loaded_addresses, test_loaded_addresses);
+        assert_eq!(compute_budget_exceeded, Some(test_compute_budget_exceeded));
+        assert_eq!(compute_units_consumed, Some(500));
+        assert_eq!(fee_details, Some(test_fee_details));
+        assert_eq!(loaded_accounts_data_size, Some(1_000));
 
         // insert value
         let status = TransactionStatusMeta {
@@ -6363,6 +6582,11 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         }
         .into();
         assert!(transaction_status_cf
@@ -6380,6 +6604,11 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                 0,
@@ -6401,6 +6630,11 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert_eq!(loaded_addresses, MappedAddresses::default());
+        assert_eq!(compute_budget_exceeded, None);
+        assert_eq!(compute_units_consumed, None);
+        assert_eq!(fee_details, None);
+        assert_eq!(loaded_accounts_data_size, None);
     }
 
     #[test]
@@ -6628,6 +6862,11 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         }
         .into();
 
@@ -6822,6 +7061,11 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         }
         .into();
 
@@ -7007,6 +7251,11 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    loaded_addresses: MappedAddresses::default(),
+                    compute_budget_exceeded: None,
+                    compute_units_consumed: None,
+                    fee_details: None,
+                    loaded_accounts_data_size: None,
                 }
                 .into();
                 blockstore
@@ -7025,6 +7274,11 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        loaded_addresses: MappedAddresses::default(),
+                        compute_budget_exceeded: None,
+                        compute_units_consumed: None,
+                        fee_details: None,
+                        loaded_accounts_data_size: None,
                     }),
                 }
             })
@@ -7111,6 +7365,11 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    loaded_addresses: MappedAddresses::default(),
+                    compute_budget_exceeded: None,
+                    compute_units_consumed: None,
+                    fee_details: None,
+                    loaded_accounts_data_size: None,
                 }
                 .into();
                 blockstore
@@ -7129,6 +7388,11 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        loaded_addresses: MappedAddresses::default(),
+                        compute_budget_exceeded: None,
+                        compute_units_consumed: None,
+                        fee_details: None,
+                        loaded_accounts_data_size: None,
                     }),
                 }
             })
@@ -7858,6 +8122,11 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                loaded_addresses: MappedAddresses::default(),
+                compute_budget_exceeded: None,
+                compute_units_consumed: None,
+                fee_details: None,
+                loaded_accounts_data_size: None,
             }
             .into();
             transaction_status_cf
@@ -8100,6 +8369,74 @@ pub mod tests {
         blockstore.purge_and_compact_slots(0, slot);
     }
 
+    #[test]
+    fn test_insert_shreds_leader_mismatch() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let slot = 1;
+        let (data_shreds, _, leader_schedule_cache) = setup_erasure_shreds(slot, 0, 10);
+        let entries = make_slot_entries_with_transactions(10);
+        let (forged_shreds, _, _) = Shredder::new(slot, 0, 0, 0).unwrap().entries_to_shreds(
+            &Keypair::new(),
+            &entries,
+            true,
+            0,
+        );
+        let insert = |shreds: Vec<Shred>, is_repaired: bool| {
+            let mut metrics = BlockstoreInsertionMetrics::default();
+            let num_shreds = shreds.len();
+            let (_, inserted_indices) = blockstore
+                .insert_shreds_handle_duplicate(
+                    shreds,
+                    vec![is_repaired; num_shreds],
+                    Some(&leader_schedule_cache),
+                    false, // is_trusted
+                    None,  // retransmit-sender
+                    &|_| {},
+                    &mut metrics,
+                )
+                .unwrap();
+            (inserted_indices, metrics)
+        };
+
+        // Turbine shreds were verified by sigverify, and aren't checked again
+        let (inserted_indices, _) = insert(vec![forged_shreds[0].clone()], false);
+        assert_eq!(inserted_indices, vec![0]);
+        assert_eq!(blockstore.leader_mismatch_count(slot), 0);
+
+        // Repaired shreds are checked, and the slot is quarantined
+        let (inserted_indices, metrics) = insert(vec![forged_shreds[1].clone()], true);
+        assert!(inserted_indices.is_empty());
+        assert_eq!(metrics.num_shreds_leader_mismatch, 1);
+        assert_eq!(metrics.num_slots_quarantined, 1);
+        assert_eq!(blockstore.leader_mismatch_count(slot), 1);
+        assert!(blockstore.get_data_shred(slot, 1).unwrap().is_none());
+
+        // Once quarantined, turbine shreds of the slot are checked as well
+        let (inserted_indices, metrics) = insert(vec![forged_shreds[2].clone()], false);
+        assert!(inserted_indices.is_empty());
+        assert_eq!(metrics.num_slots_quarantined, 0);
+        assert_eq!(blockstore.leader_mismatch_count(slot), 2);
+
+        // Copies within a batch are dropped before insert
+        let (inserted_indices, metrics) = insert(
+            vec![
+                data_shreds[1].clone(),
+                data_shreds[1].clone(),
+                data_shreds[2].clone(),
+            ],
+            true,
+        );
+        assert_eq!(inserted_indices, vec![0, 2]);
+        assert_eq!(metrics.num_shreds_duplicate_in_batch, 1);
+        assert_eq!(metrics.num_shreds_leader_mismatch, 0);
+        assert_eq!(
+            blockstore.get_data_shred(slot, 1).unwrap().unwrap(),
+            data_shreds[1].payload
+        );
+    }
+
     fn setup_erasure_shreds(
         slot: u64,
         parent_slot: u64,
@@ -8356,6 +8693,132 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_migrate_status_columns() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let meta = TransactionStatusMeta {
+            fee: 42,
+            pre_balances: vec![1, 2, 3],
+            post_balances: vec![3, 2, 1],
+            ..TransactionStatusMeta::default()
+        };
+        let deprecated_status: StoredTransactionStatusMeta = meta.clone().into();
+        let status: generated::TransactionStatusMeta = meta.into();
+        let mut unversioned_status = Vec::with_capacity(prost::Message::encoded_len(&status));
+        prost::Message::encode(&status, &mut unversioned_status).unwrap();
+        let rewards: generated::Rewards = vec![Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 42,
+            post_balance: 42,
+            reward_type: Some(RewardType::Fee),
+            commission: None,
+        }]
+        .into();
+        let deprecated_rewards: StoredExtendedRewards = rewards.clone().into();
+
+        // bincode, unversioned protobuf, and versioned protobuf entries
+        blockstore
+            .transaction_status_cf
+            .put_bytes(
+                (0, Signature::default(), 0),
+                &serialize(&deprecated_status).unwrap(),
+            )
+            .unwrap();
+        blockstore
+            .transaction_status_cf
+            .put_bytes((0, Signature::default(), 1), &unversioned_status)
+            .unwrap();
+        blockstore
+            .transaction_status_cf
+            .put_bytes((0, Signature::default(), 2), &versioned::encode(&status))
+            .unwrap();
+        blockstore
+            .rewards_cf
+            .put_bytes(0, &serialize(&deprecated_rewards).unwrap())
+            .unwrap();
+        blockstore.rewards_cf.put_protobuf(1, &rewards).unwrap();
+
+        assert_eq!(
+            blockstore.migrate_status_columns().unwrap(),
+            StatusColumnsMigrationStats {
+                transaction_statuses_migrated: 2,
+                transaction_statuses_scanned: 3,
+                rewards_migrated: 2,
+                rewards_scanned: 2,
+            }
+        );
+        // every legacy entry is rewritten as versioned protobuf
+        for slot in 0..3 {
+            let data = blockstore
+                .transaction_status_cf
+                .get_bytes((0, Signature::default(), slot))
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                versioned::stored_format(&data),
+                StoredFormat::Versioned(versioned::CURRENT_VERSION)
+            );
+            assert_eq!(
+                blockstore
+                    .transaction_status_cf
+                    .get_protobuf((0, Signature::default(), slot))
+                    .unwrap()
+                    .unwrap(),
+                status
+            );
+        }
+        for slot in 0..2 {
+            let data = blockstore.rewards_cf.get_bytes(slot).unwrap().unwrap();
+            assert_eq!(
+                versioned::stored_format(&data),
+                StoredFormat::Versioned(versioned::CURRENT_VERSION)
+            );
+            assert_eq!(
+                blockstore.rewards_cf.get_protobuf(slot).unwrap().unwrap(),
+                rewards
+            );
+        }
+
+        // a second pass has nothing left to do
+        assert_eq!(
+            blockstore.migrate_status_columns().unwrap(),
+            StatusColumnsMigrationStats {
+                transaction_statuses_migrated: 0,
+                transaction_statuses_scanned: 3,
+                rewards_migrated: 0,
+                rewards_scanned: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_versioned_protobuf() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let mut blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let rewards: Rewards = vec![Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 42,
+            post_balance: 42,
+            reward_type: Some(RewardType::Fee),
+            commission: None,
+        }];
+
+        blockstore.write_rewards(0, rewards.clone()).unwrap();
+        blockstore.set_write_versioned_protobuf(true);
+        blockstore.write_rewards(1, rewards.clone()).unwrap();
+
+        for (slot, expected_format) in [
+            (0, StoredFormat::Legacy),
+            (1, StoredFormat::Versioned(versioned::CURRENT_VERSION)),
+        ] {
+            let data = blockstore.rewards_cf.get_bytes(slot).unwrap().unwrap();
+            assert_eq!(versioned::stored_format(&data), expected_format);
+            assert_eq!(blockstore.read_rewards(slot).unwrap().unwrap(), rewards);
+        }
+    }
+
     #[test]
     fn test_transaction_status_protobuf_backward_compatability() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -8397,6 +8860,11 @@ pub mod tests {
                 reward_type: Some(RewardType::Rent),
                 commission: None,
             }]),
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         };
         let deprecated_status: StoredTransactionStatusMeta = status.clone().into();
         let protobuf_status: generated::TransactionStatusMeta = status.into();
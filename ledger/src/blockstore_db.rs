@@ -14,19 +14,24 @@ use rocksdb::{
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use solana_metrics::datapoint::DataPoint;
 use solana_runtime::hardened_unpack::UnpackError;
 use solana_sdk::{
     clock::{Slot, UnixTimestamp},
     pubkey::Pubkey,
     signature::Signature,
+    timing::AtomicInterval,
+};
+use solana_storage_proto::{
+    convert::generated,
+    versioned::{self, StoredFormat},
 };
-use solana_storage_proto::convert::generated;
 use std::{
     collections::{HashMap, HashSet},
     ffi::{CStr, CString},
     fs,
     marker::PhantomData,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
@@ -102,6 +107,7 @@ pub enum BlockstoreError {
     ParentEntriesUnavailable,
     SlotUnavailable,
     UnsupportedTransactionVersion,
+    UnsupportedProtobufVersion(u8),
 }
 pub type Result<T> = std::result::Result<T, BlockstoreError>;
 
@@ -199,6 +205,10 @@ pub enum AccessType {
     PrimaryOnly,
     PrimaryOnlyForMaintenance, // this indicates no compaction
     TryPrimaryThenSecondary,
+    // this indicates read-only access, alongside a primary such as a running validator. The
+    // secondary instance keeps its logs in the given directory, which must not be shared with
+    // another secondary instance
+    Secondary(PathBuf),
 }
 
 #[derive(Debug, PartialEq)]
@@ -266,8 +276,46 @@ impl OldestSlot {
     }
 }
 
+const BYTES_WRITTEN_REPORT_INTERVAL_MS: u64 = 10_000;
+
+/// Bytes of keys and values written to each column, reported periodically to tell which columns
+/// drive the growth of the blockstore
+#[derive(Debug)]
+struct BytesWritten {
+    by_column: HashMap<&'static str, AtomicU64>,
+    report_interval: AtomicInterval,
+}
+
+impl BytesWritten {
+    fn new(columns: &[&'static str]) -> Self {
+        Self {
+            by_column: columns
+                .iter()
+                .map(|column| (*column, AtomicU64::default()))
+                .collect(),
+            report_interval: AtomicInterval::default(),
+        }
+    }
+
+    fn add(&self, column: &'static str, bytes: usize) {
+        if let Some(bytes_written) = self.by_column.get(column) {
+            bytes_written.fetch_add(bytes as u64, Ordering::Relaxed);
+        }
+        if self
+            .report_interval
+            .should_update(BYTES_WRITTEN_REPORT_INTERVAL_MS)
+        {
+            let mut point = DataPoint::new("blockstore-bytes-written");
+            for (column, bytes_written) in self.by_column.iter() {
+                point.add_field_i64(*column, bytes_written.swap(0, Ordering::Relaxed) as i64);
+            }
+            solana_metrics::submit(point, Level::Info);
+        }
+    }
+}
+
 #[derive(Debug)]
-struct Rocks(rocksdb::DB, ActualAccessType, OldestSlot);
+struct Rocks(rocksdb::DB, ActualAccessType, OldestSlot, BytesWritten);
 
 impl Rocks {
     fn open(
@@ -395,17 +443,19 @@ impl Rocks {
             (ProgramCosts::NAME, program_costs_cf_descriptor),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
+        let bytes_written = BytesWritten::new(&cf_names);
 
         // Open the database
-        let db = match access_type {
+        let db = match &access_type {
             AccessType::PrimaryOnly | AccessType::PrimaryOnlyForMaintenance => Rocks(
                 DB::open_cf_descriptors(&db_options, path, cfs.into_iter().map(|c| c.1))?,
                 ActualAccessType::Primary,
                 oldest_slot,
+                bytes_written,
             ),
             AccessType::TryPrimaryThenSecondary => {
                 match DB::open_cf_descriptors(&db_options, path, cfs.into_iter().map(|c| c.1)) {
-                    Ok(db) => Rocks(db, ActualAccessType::Primary, oldest_slot),
+                    Ok(db) => Rocks(db, ActualAccessType::Primary, oldest_slot, bytes_written),
                     Err(err) => {
                         let secondary_path = path.join("solana-secondary");
 
@@ -425,10 +475,22 @@ impl Rocks {
                             )?,
                             ActualAccessType::Secondary,
                             oldest_slot,
+                            bytes_written,
                         )
                     }
                 }
             }
+            AccessType::Secondary(secondary_path) => {
+                // This is needed according to https://github.com/facebook/rocksdb/wiki/Secondary-instance
+                db_options.set_max_open_files(-1);
+
+                Rocks(
+                    DB::open_cf_as_secondary(&db_options, path, secondary_path, cf_names.clone())?,
+                    ActualAccessType::Secondary,
+                    oldest_slot,
+                    bytes_written,
+                )
+            }
         };
         // this is only needed for LedgerCleanupService. so guard with PrimaryOnly (i.e. running solana-validator)
         if matches!(access_type, AccessType::PrimaryOnly) {
@@ -575,6 +637,11 @@ impl Rocks {
     fn is_primary_access(&self) -> bool {
         self.1 == ActualAccessType::Primary
     }
+
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.0.try_catch_up_with_primary()?;
+        Ok(())
+    }
 }
 
 pub trait Column {
@@ -1009,11 +1076,14 @@ where
 {
     backend: Arc<Rocks>,
     column: PhantomData<C>,
+    // Write protobuf values with the versioned framing, which releases predating it can't read
+    write_versioned_protobuf: bool,
 }
 
 pub struct WriteBatch<'a> {
     write_batch: RWriteBatch,
     map: HashMap<&'static str, &'a ColumnFamily>,
+    bytes_written: HashMap<&'static str, usize>,
 }
 
 impl Database {
@@ -1076,6 +1146,7 @@ impl Database {
         LedgerColumn {
             backend: Arc::clone(&self.backend),
             column: PhantomData,
+            write_versioned_protobuf: false,
         }
     }
 
@@ -1093,11 +1164,19 @@ impl Database {
             .map(|desc| (desc, self.backend.cf_handle(desc)))
             .collect();
 
-        Ok(WriteBatch { write_batch, map })
+        Ok(WriteBatch {
+            write_batch,
+            map,
+            bytes_written: HashMap::new(),
+        })
     }
 
     pub fn write(&self, batch: WriteBatch) -> Result<()> {
-        self.backend.write(batch.write_batch)
+        self.backend.write(batch.write_batch)?;
+        for (column, bytes) in batch.bytes_written {
+            self.backend.3.add(column, bytes);
+        }
+        Ok(())
     }
 
     pub fn storage_size(&self) -> Result<u64> {
@@ -1119,6 +1198,12 @@ impl Database {
         self.backend.is_primary_access()
     }
 
+    /// Reads the writes of the primary instance since the secondary one was opened or last
+    /// caught up
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.backend.try_catch_up_with_primary()
+    }
+
     pub fn set_oldest_slot(&self, oldest_slot: Slot) {
         self.backend.2.set(oldest_slot);
     }
@@ -1199,7 +1284,13 @@ where
     }
 
     pub fn put_bytes(&self, key: C::Index, value: &[u8]) -> Result<()> {
-        self.backend.put_cf(self.handle(), &C::key(key), value)
+        self.put_cf(&C::key(key), value)
+    }
+
+    fn put_cf(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.backend.put_cf(self.handle(), key, value)?;
+        self.backend.3.add(C::NAME, key.len() + value.len());
+        Ok(())
     }
 }
 
@@ -1220,8 +1311,7 @@ where
     pub fn put(&self, key: C::Index, value: &C::Type) -> Result<()> {
         let serialized_value = serialize(value)?;
 
-        self.backend
-            .put_cf(self.handle(), &C::key(key), &serialized_value)
+        self.put_cf(&C::key(key), &serialized_value)
     }
 
     pub fn delete(&self, key: C::Index) -> Result<()> {
@@ -1233,14 +1323,20 @@ impl<C> LedgerColumn<C>
 where
     C: ProtobufColumn + ColumnName,
 {
+    pub fn set_write_versioned_protobuf(&mut self, write_versioned_protobuf: bool) {
+        self.write_versioned_protobuf = write_versioned_protobuf;
+    }
+
+    /// Decode a value that may predate explicit versioning, in which case it was written either
+    /// as bincode `T` or as unversioned protobuf. Versioned values never fall back to bincode.
     pub fn get_protobuf_or_bincode<T: DeserializeOwned + Into<C::Type>>(
         &self,
         key: C::Index,
     ) -> Result<Option<C::Type>> {
         if let Some(serialized_value) = self.backend.get_cf(self.handle(), &C::key(key))? {
-            let value = match C::Type::decode(&serialized_value[..]) {
-                Ok(value) => value,
-                Err(_) => deserialize::<T>(&serialized_value)?.into(),
+            let value = match versioned::stored_format(&serialized_value) {
+                StoredFormat::Versioned(_) => Self::decode_versioned(&serialized_value)?,
+                StoredFormat::Legacy => Self::decode_legacy::<T>(&serialized_value)?,
             };
             Ok(Some(value))
         } else {
@@ -1248,25 +1344,90 @@ where
         }
     }
 
+    /// Like `get_protobuf_or_bincode()`, for values that were never written as bincode
     pub fn get_protobuf(&self, key: C::Index) -> Result<Option<C::Type>> {
         if let Some(serialized_value) = self.backend.get_cf(self.handle(), &C::key(key))? {
-            Ok(Some(C::Type::decode(&serialized_value[..])?))
+            let value = match versioned::stored_format(&serialized_value) {
+                StoredFormat::Versioned(_) => Self::decode_versioned(&serialized_value)?,
+                StoredFormat::Legacy => {
+                    inc_new_counter_info!("blockstore-legacy_protobuf_reads", 1);
+                    C::Type::decode(&serialized_value[..])?
+                }
+            };
+            Ok(Some(value))
         } else {
             Ok(None)
         }
     }
 
+    /// Values are written as unversioned protobuf unless `set_write_versioned_protobuf()` was
+    /// set, so that releases which can't read the versioned framing keep reading them until it's
+    /// rolled out.
     pub fn put_protobuf(&self, key: C::Index, value: &C::Type) -> Result<()> {
-        let mut buf = Vec::with_capacity(value.encoded_len());
-        value.encode(&mut buf)?;
-        self.backend.put_cf(self.handle(), &C::key(key), &buf)
+        let buf = if self.write_versioned_protobuf {
+            versioned::encode(value)
+        } else {
+            let mut buf = Vec::with_capacity(value.encoded_len());
+            value.encode(&mut buf)?;
+            buf
+        };
+        self.put_cf(&C::key(key), &buf)
+    }
+
+    /// Rewrite every entry that predates explicit versioning, bincode or unversioned protobuf, as
+    /// versioned protobuf, so that no read has to guess its format anymore.
+    /// Returns the number of entries rewritten and the number of entries scanned.
+    pub fn migrate_to_versioned_protobuf<T: DeserializeOwned + Into<C::Type>>(
+        &self,
+    ) -> Result<(usize, usize)> {
+        let mut migrated = 0;
+        let mut scanned = 0;
+        for (index, serialized_value) in self.iter(IteratorMode::Start)? {
+            scanned += 1;
+            if versioned::stored_format(&serialized_value) == StoredFormat::Legacy {
+                let value = Self::decode_legacy::<T>(&serialized_value)?;
+                self.put_cf(&C::key(index), &versioned::encode(&value))?;
+                migrated += 1;
+            }
+        }
+        Ok((migrated, scanned))
+    }
+
+    fn decode_versioned(serialized_value: &[u8]) -> Result<C::Type> {
+        versioned::decode(serialized_value).map_err(|err| match err {
+            versioned::DecodeError::UnsupportedVersion(version) => {
+                BlockstoreError::UnsupportedProtobufVersion(version)
+            }
+            versioned::DecodeError::Protobuf(err) => BlockstoreError::ProtobufDecodeError(err),
+            versioned::DecodeError::Unversioned => {
+                unreachable!("only called on versioned values")
+            }
+        })
+    }
+
+    /// The only decoding that guesses the format of a value, for the values written before the
+    /// versioned framing: unversioned protobuf, or else bincode `T`
+    fn decode_legacy<T: DeserializeOwned + Into<C::Type>>(
+        serialized_value: &[u8],
+    ) -> Result<C::Type> {
+        match C::Type::decode(serialized_value) {
+            Ok(value) => {
+                inc_new_counter_info!("blockstore-legacy_protobuf_reads", 1);
+                Ok(value)
+            }
+            Err(_) => {
+                inc_new_counter_info!("blockstore-legacy_bincode_reads", 1);
+                Ok(deserialize::<T>(serialized_value)?.into())
+            }
+        }
     }
 }
 
 impl<'a> WriteBatch<'a> {
     pub fn put_bytes<C: Column + ColumnName>(&mut self, key: C::Index, bytes: &[u8]) -> Result<()> {
-        self.write_batch
-            .put_cf(self.get_cf::<C>(), &C::key(key), bytes);
+        let key = C::key(key);
+        self.write_batch.put_cf(self.get_cf::<C>(), &key, bytes);
+        self.add_bytes_written::<C>(key.len() + bytes.len());
         Ok(())
     }
 
@@ -1281,8 +1442,10 @@ impl<'a> WriteBatch<'a> {
         value: &C::Type,
     ) -> Result<()> {
         let serialized_value = serialize(&value)?;
+        let key = C::key(key);
         self.write_batch
-            .put_cf(self.get_cf::<C>(), &C::key(key), &serialized_value);
+            .put_cf(self.get_cf::<C>(), &key, &serialized_value);
+        self.add_bytes_written::<C>(key.len() + serialized_value.len());
         Ok(())
     }
 
@@ -1291,6 +1454,10 @@ impl<'a> WriteBatch<'a> {
         self.map[C::NAME]
     }
 
+    fn add_bytes_written<C: Column + ColumnName>(&mut self, bytes: usize) {
+        *self.bytes_written.entry(C::NAME).or_default() += bytes;
+    }
+
     pub fn delete_range_cf<C: Column>(
         &mut self,
         cf: &ColumnFamily,
@@ -1492,4 +1659,20 @@ pub mod tests {
         assert!(excludes_from_compaction(columns::TransactionMemos::NAME));
         assert!(!excludes_from_compaction("something else"));
     }
+
+    #[test]
+    fn test_bytes_written() {
+        let bytes_written = BytesWritten::new(&[ShredData::NAME, columns::Root::NAME]);
+        bytes_written.add(ShredData::NAME, 10);
+        bytes_written.add(ShredData::NAME, 5);
+        bytes_written.add("unknown column", 7);
+        assert_eq!(
+            bytes_written.by_column[ShredData::NAME].load(Ordering::Relaxed),
+            15
+        );
+        assert_eq!(
+            bytes_written.by_column[columns::Root::NAME].load(Ordering::Relaxed),
+            0
+        );
+    }
 }
@@ -13,14 +13,18 @@ use solana_entry::entry::{
 };
 use solana_measure::measure::Measure;
 use solana_metrics::{datapoint_error, inc_new_counter_debug};
-use solana_rayon_threadlimit::get_thread_count;
+use solana_rayon_threadlimit::{
+    get_thread_count,
+    thread_pools::{ManagedThreadPool, REPLAY_THREAD_POOL},
+};
 use solana_runtime::{
     accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
     accounts_index::AccountSecondaryIndexes,
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     bank::{
         Bank, ExecuteTimings, InnerInstructionsList, RentDebits, TransactionBalancesSet,
-        TransactionExecutionResult, TransactionLogMessages, TransactionResults,
+        TransactionExecutionDetails, TransactionExecutionResult, TransactionLogMessages,
+        TransactionResults,
     },
     bank_forks::BankForks,
     bank_utils,
@@ -35,6 +39,7 @@ use solana_runtime::{
 };
 use solana_sdk::{
     clock::{Slot, MAX_PROCESSING_AGE},
+    compute_budget::{ComputeBudget, ComputeBudgetExceeded},
     feature_set,
     genesis_config::GenesisConfig,
     hash::Hash,
@@ -90,12 +95,20 @@ pub type BlockstoreProcessorInner = (BankForks, LeaderScheduleCache, Option<Slot
 pub type BlockstoreProcessorResult =
     result::Result<BlockstoreProcessorInner, BlockstoreProcessorError>;
 
-thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::ThreadPoolBuilder::new()
-                    .num_threads(get_thread_count())
-                    .thread_name(|ix| format!("blockstore_processor_{}", ix))
-                    .build()
-                    .unwrap())
-);
+lazy_static! {
+    static ref PAR_THREAD_POOL: Arc<ManagedThreadPool> =
+        ManagedThreadPool::new(REPLAY_THREAD_POOL, get_thread_count(), |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("blockstore_processor_{}", ix))
+                .build()
+                .unwrap()
+        });
+}
+
+// Replaces the replay pool on the threads processing the blockstore with
+// `ProcessOptions::override_num_threads`, leaving the pool shared by the other threads unchanged
+thread_local!(static PAR_THREAD_POOL_OVERRIDE: RefCell<Option<ThreadPool>> = RefCell::new(None));
 
 fn first_err(results: &[Result<()>]) -> Result<()> {
     for r in results {
@@ -170,7 +183,7 @@ fn execute_batch(
 
     let pre_process_units: u64 = aggregate_total_execution_units(timings);
 
-    let (tx_results, balances, inner_instructions, transaction_logs) =
+    let (tx_results, balances, inner_instructions, transaction_logs, compute_budget_exceeded) =
         batch.bank().load_execute_and_commit_transactions(
             batch,
             MAX_PROCESSING_AGE,
@@ -212,8 +225,8 @@ fn execute_batch(
     let TransactionResults {
         fee_collection_results,
         execution_results,
+        execution_details,
         rent_debits,
-        ..
     } = tx_results;
 
     if let Some(transaction_status_sender) = transaction_status_sender {
@@ -231,10 +244,12 @@ fn execute_batch(
             bank.clone(),
             transactions,
             execution_results,
+            execution_details,
             balances,
             token_balances,
             inner_instructions,
             transaction_logs,
+            compute_budget_exceeded,
             rent_debits,
         );
     }
@@ -253,28 +268,30 @@ fn execute_batches(
     cost_capacity_meter: Arc<RwLock<BlockCostCapacityMeter>>,
 ) -> Result<()> {
     inc_new_counter_debug!("bank-par_execute_entries-count", batches.len());
-    let (results, new_timings): (Vec<Result<()>>, Vec<ExecuteTimings>) =
-        PAR_THREAD_POOL.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                batches
-                    .into_par_iter()
-                    .map(|batch| {
-                        let mut timings = ExecuteTimings::default();
-                        let result = execute_batch(
-                            batch,
-                            bank,
-                            transaction_status_sender,
-                            replay_vote_sender,
-                            &mut timings,
-                            cost_capacity_meter.clone(),
-                        );
-                        if let Some(entry_callback) = entry_callback {
-                            entry_callback(bank);
-                        }
-                        (result, timings)
-                    })
-                    .unzip()
+    let execute = || {
+        batches
+            .into_par_iter()
+            .map(|batch| {
+                let mut timings = ExecuteTimings::default();
+                let result = execute_batch(
+                    batch,
+                    bank,
+                    transaction_status_sender,
+                    replay_vote_sender,
+                    &mut timings,
+                    cost_capacity_meter.clone(),
+                );
+                if let Some(entry_callback) = entry_callback {
+                    entry_callback(bank);
+                }
+                (result, timings)
             })
+            .unzip()
+    };
+    let (results, new_timings): (Vec<Result<()>>, Vec<ExecuteTimings>) = PAR_THREAD_POOL_OVERRIDE
+        .with(|thread_pool| match &*thread_pool.borrow() {
+            Some(thread_pool) => thread_pool.install(execute),
+            None => PAR_THREAD_POOL.install(execute),
         });
 
     timings.total_batches_len += batches.len();
@@ -475,6 +492,11 @@ pub struct ProcessOptions {
     pub accounts_db_config: Option<AccountsDbConfig>,
     pub verify_index: bool,
     pub shrink_ratio: AccountShrinkThreshold,
+    pub executor_cache_capacity: Option<usize>,
+    /// Compute budget overriding the default one, to benchmark programs under another cost model
+    pub compute_budget: Option<ComputeBudget>,
+    /// Whether banking stage queues transactions behind conflicting ones of their batch
+    pub queue_conflicting_transactions: bool,
 }
 
 pub fn process_blockstore(
@@ -488,16 +510,19 @@ pub fn process_blockstore(
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
 ) -> BlockstoreProcessorResult {
     if let Some(num_threads) = opts.override_num_threads {
-        PAR_THREAD_POOL.with(|pool| {
-            *pool.borrow_mut() = rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .build()
-                .unwrap()
+        PAR_THREAD_POOL_OVERRIDE.with(|thread_pool| {
+            *thread_pool.borrow_mut() = Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|ix| format!("blockstore_processor_{}", ix))
+                    .build()
+                    .unwrap(),
+            )
         });
     }
 
     // Setup bank for slot 0
-    let bank0 = Bank::new_with_paths(
+    let mut bank0 = Bank::new_with_paths(
         genesis_config,
         account_paths,
         &opts.frozen_accounts,
@@ -510,6 +535,10 @@ pub fn process_blockstore(
         opts.accounts_db_config.clone(),
         accounts_update_notifier,
     );
+    if opts.compute_budget.is_some() {
+        bank0.set_compute_budget(opts.compute_budget);
+    }
+    bank0.set_queue_conflicting_transactions(opts.queue_conflicting_transactions);
     let bank0 = Arc::new(bank0);
     info!("processing ledger for slot 0...");
     let recyclers = VerifyRecyclers::default();
@@ -538,7 +567,7 @@ pub fn process_blockstore(
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn process_blockstore_from_root(
     blockstore: &Blockstore,
-    bank: Bank,
+    mut bank: Bank,
     opts: &ProcessOptions,
     recyclers: &VerifyRecyclers,
     transaction_status_sender: Option<&TransactionStatusSender>,
@@ -548,6 +577,11 @@ pub(crate) fn process_blockstore_from_root(
     timings: BankFromArchiveTimings,
     last_full_snapshot_slot: Slot,
 ) -> BlockstoreProcessorResult {
+    // The compute budget isn't part of snapshots
+    if opts.compute_budget.is_some() {
+        bank.set_compute_budget(opts.compute_budget);
+    }
+    bank.set_queue_conflicting_transactions(opts.queue_conflicting_transactions);
     do_process_blockstore_from_root(
         blockstore,
         Arc::new(bank),
@@ -579,6 +613,9 @@ fn do_process_blockstore_from_root(
 
     // Starting slot must be a root, and thus has no parents
     assert!(bank.parent().is_none());
+    if let Some(capacity) = opts.executor_cache_capacity {
+        bank.set_executor_cache_capacity(capacity);
+    }
     let start_slot = bank.slot();
     let now = Instant::now();
     let mut root = start_slot;
@@ -1365,10 +1402,12 @@ pub struct TransactionStatusBatch {
     pub bank: Arc<Bank>,
     pub transactions: Vec<SanitizedTransaction>,
     pub statuses: Vec<TransactionExecutionResult>,
+    pub execution_details: Vec<TransactionExecutionDetails>,
     pub balances: TransactionBalancesSet,
     pub token_balances: TransactionTokenBalancesSet,
     pub inner_instructions: Option<Vec<Option<InnerInstructionsList>>>,
     pub transaction_logs: Option<Vec<Option<TransactionLogMessages>>>,
+    pub compute_budget_exceeded: Vec<Option<ComputeBudgetExceeded>>,
     pub rent_debits: Vec<RentDebits>,
 }
 
@@ -1379,15 +1418,18 @@ pub struct TransactionStatusSender {
 }
 
 impl TransactionStatusSender {
+    #[allow(clippy::too_many_arguments)]
     pub fn send_transaction_status_batch(
         &self,
         bank: Arc<Bank>,
         transactions: Vec<SanitizedTransaction>,
         statuses: Vec<TransactionExecutionResult>,
+        execution_details: Vec<TransactionExecutionDetails>,
         balances: TransactionBalancesSet,
         token_balances: TransactionTokenBalancesSet,
         inner_instructions: Vec<Option<InnerInstructionsList>>,
         transaction_logs: Vec<Option<TransactionLogMessages>>,
+        compute_budget_exceeded: Vec<Option<ComputeBudgetExceeded>>,
         rent_debits: Vec<RentDebits>,
     ) {
         let slot = bank.slot();
@@ -1402,10 +1444,12 @@ impl TransactionStatusSender {
                 bank,
                 transactions,
                 statuses,
+                execution_details,
                 balances,
                 token_balances,
                 inner_instructions,
                 transaction_logs,
+                compute_budget_exceeded,
                 rent_debits,
             }))
         {
@@ -2288,9 +2332,14 @@ pub mod tests {
             ..ProcessOptions::default()
         };
         test_process_blockstore(&genesis_config, &blockstore, opts);
-        PAR_THREAD_POOL.with(|pool| {
-            assert_eq!(pool.borrow().current_num_threads(), 1);
+        PAR_THREAD_POOL_OVERRIDE.with(|thread_pool| {
+            assert_eq!(
+                thread_pool.borrow().as_ref().unwrap().current_num_threads(),
+                1
+            );
         });
+        // The pool replaying on the other threads keeps its size
+        assert_eq!(PAR_THREAD_POOL.current_num_threads(), get_thread_count());
     }
 
     #[test]
@@ -3443,6 +3492,7 @@ pub mod tests {
             _balances,
             _inner_instructions,
             _log_messages,
+            _compute_budget_exceeded,
         ) = batch.bank().load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
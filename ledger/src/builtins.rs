@@ -1,4 +1,4 @@
-use solana_runtime::builtins::{ActivationType, Builtin, Builtins};
+use solana_runtime::builtins::{ActivationType, Builtin, BuiltinMigration, Builtins};
 use solana_sdk::pubkey::Pubkey;
 
 macro_rules! to_builtin {
@@ -29,9 +29,15 @@ fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
     vec![]
 }
 
+/// Builtin programs replaced by a BPF implementation by feature
+fn builtin_migrations() -> Vec<BuiltinMigration> {
+    vec![]
+}
+
 pub(crate) fn get(bpf_jit: bool) -> Builtins {
     Builtins {
         genesis_builtins: genesis_builtins(bpf_jit),
         feature_builtins: feature_builtins(),
+        builtin_migrations: builtin_migrations(),
     }
 }
@@ -0,0 +1,13 @@
+use {
+    solana_entry::entry::Entry,
+    solana_sdk::clock::Slot,
+    std::sync::{Arc, RwLock},
+};
+
+pub trait EntryNotifierInterface: std::fmt::Debug {
+    /// Notified when an entry of a slot is deshredded, in the order of the entries of the slot,
+    /// before the slot is full
+    fn notify_entry(&self, slot: Slot, index: usize, entry: &Entry);
+}
+
+pub type EntryNotifier = Arc<RwLock<dyn EntryNotifierInterface + Sync + Send>>;
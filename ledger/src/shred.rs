@@ -52,12 +52,15 @@
 use {
     crate::{blockstore::MAX_DATA_SHREDS_PER_SLOT, erasure::Session},
     bincode::config::Options,
-    rayon::{prelude::*, ThreadPool},
+    rayon::prelude::*,
     serde::{Deserialize, Serialize},
     solana_entry::entry::{create_ticks, Entry},
     solana_measure::measure::Measure,
     solana_perf::packet::{limited_deserialize, Packet},
-    solana_rayon_threadlimit::get_thread_count,
+    solana_rayon_threadlimit::{
+        get_thread_count,
+        thread_pools::{ManagedThreadPool, SHRED_THREAD_POOL},
+    },
     solana_runtime::bank::Bank,
     solana_sdk::{
         clock::Slot,
@@ -67,7 +70,7 @@ use {
         pubkey::Pubkey,
         signature::{Keypair, Signature, Signer},
     },
-    std::{cell::RefCell, convert::TryInto, mem::size_of},
+    std::{convert::TryInto, mem::size_of, sync::Arc},
     thiserror::Error,
 };
 
@@ -125,11 +128,16 @@ pub const OFFSET_OF_SHRED_SLOT: usize = SIZE_OF_SIGNATURE + SIZE_OF_SHRED_TYPE;
 pub const OFFSET_OF_SHRED_INDEX: usize = OFFSET_OF_SHRED_SLOT + SIZE_OF_SHRED_SLOT;
 pub const SHRED_PAYLOAD_SIZE: usize = PACKET_DATA_SIZE - SIZE_OF_NONCE;
 
-thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::ThreadPoolBuilder::new()
-                    .num_threads(get_thread_count())
-                    .thread_name(|ix| format!("shredder_{}", ix))
-                    .build()
-                    .unwrap()));
+lazy_static! {
+    static ref PAR_THREAD_POOL: Arc<ManagedThreadPool> =
+        ManagedThreadPool::new(SHRED_THREAD_POOL, get_thread_count(), |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("shredder_{}", ix))
+                .build()
+                .unwrap()
+        });
+}
 
 /// The constants that define if a shred is data or coding
 pub const DATA_SHRED: u8 = 0b1010_0101;
@@ -670,17 +678,15 @@ impl Shredder {
             Shredder::sign_shred(keypair, &mut shred);
             shred
         };
-        let data_shreds: Vec<Shred> = PAR_THREAD_POOL.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                serialized_shreds
-                    .par_chunks(payload_capacity)
-                    .enumerate()
-                    .map(|(i, shred_data)| {
-                        let shred_index = next_shred_index + i as u32;
-                        make_data_shred(shred_index, shred_data)
-                    })
-                    .collect()
-            })
+        let data_shreds: Vec<Shred> = PAR_THREAD_POOL.install(|| {
+            serialized_shreds
+                .par_chunks(payload_capacity)
+                .enumerate()
+                .map(|(i, shred_data)| {
+                    let shred_index = next_shred_index + i as u32;
+                    make_data_shred(shred_index, shred_data)
+                })
+                .collect()
         });
         gen_data_time.stop();
 
@@ -701,25 +707,21 @@ impl Shredder {
         }
         let mut gen_coding_time = Measure::start("gen_coding_shreds");
         // 1) Generate coding shreds
-        let mut coding_shreds: Vec<_> = PAR_THREAD_POOL.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                data_shreds
-                    .par_chunks(MAX_DATA_SHREDS_PER_FEC_BLOCK as usize)
-                    .flat_map(|shred_data_batch| {
-                        Shredder::generate_coding_shreds(shred_data_batch, is_last_in_slot)
-                    })
-                    .collect()
-            })
+        let mut coding_shreds: Vec<_> = PAR_THREAD_POOL.install(|| {
+            data_shreds
+                .par_chunks(MAX_DATA_SHREDS_PER_FEC_BLOCK as usize)
+                .flat_map(|shred_data_batch| {
+                    Shredder::generate_coding_shreds(shred_data_batch, is_last_in_slot)
+                })
+                .collect()
         });
         gen_coding_time.stop();
 
         let mut sign_coding_time = Measure::start("sign_coding_shreds");
         // 2) Sign coding shreds
-        PAR_THREAD_POOL.with(|thread_pool| {
-            thread_pool.borrow().install(|| {
-                coding_shreds.par_iter_mut().for_each(|coding_shred| {
-                    Shredder::sign_shred(keypair, coding_shred);
-                })
+        PAR_THREAD_POOL.install(|| {
+            coding_shreds.par_iter_mut().for_each(|coding_shred| {
+                Shredder::sign_shred(keypair, coding_shred);
             })
         });
         sign_coding_time.stop();
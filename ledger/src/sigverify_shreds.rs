@@ -1,10 +1,7 @@
 #![allow(clippy::implicit_hasher)]
 use crate::shred::{ShredType, SIZE_OF_NONCE};
-use rayon::{
-    iter::{
-        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
-    },
-    ThreadPool,
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
 };
 use sha2::{Digest, Sha512};
 use solana_metrics::inc_new_counter_debug;
@@ -15,7 +12,10 @@ use solana_perf::{
     recycler_cache::RecyclerCache,
     sigverify::{self, batch_size, TxOffset},
 };
-use solana_rayon_threadlimit::get_thread_count;
+use solana_rayon_threadlimit::{
+    get_thread_count,
+    thread_pools::{ManagedThreadPool, SIGVERIFY_SHREDS_THREAD_POOL},
+};
 use solana_sdk::{
     clock::Slot,
     pubkey::Pubkey,
@@ -28,11 +28,17 @@ use std::{collections::HashMap, mem::size_of};
 pub const SIGN_SHRED_GPU_MIN: usize = 256;
 
 lazy_static! {
-    pub static ref SIGVERIFY_THREAD_POOL: ThreadPool = rayon::ThreadPoolBuilder::new()
-        .num_threads(get_thread_count())
-        .thread_name(|ix| format!("sigverify_shreds_{}", ix))
-        .build()
-        .unwrap();
+    pub static ref SIGVERIFY_THREAD_POOL: Arc<ManagedThreadPool> = ManagedThreadPool::new(
+        SIGVERIFY_SHREDS_THREAD_POOL,
+        get_thread_count(),
+        |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("sigverify_shreds_{}", ix))
+                .build()
+                .unwrap()
+        }
+    );
 }
 
 /// Assuming layout is
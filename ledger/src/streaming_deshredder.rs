@@ -0,0 +1,112 @@
+//! The `streaming_deshredder` module yields the entries of slots while their shreds arrive. The
+//! entries of a data block are returned as soon as it and all the blocks before it in the slot
+//! are complete, instead of once the slot is full, and the blocks completed between two calls are
+//! coalesced into a single batch.
+
+use {
+    crate::{blockstore::Blockstore, blockstore_db::Result},
+    solana_entry::entry::Entry,
+    solana_sdk::clock::Slot,
+    std::collections::HashMap,
+};
+
+/// Entries of a slot which were completed together
+#[derive(Debug, Default, PartialEq)]
+pub struct SlotEntryBatch {
+    pub slot: Slot,
+    /// Index in the slot of the first entry of the batch
+    pub start_entry_index: usize,
+    pub entries: Vec<Entry>,
+    /// Number of shreds the entries were deshredded from
+    pub num_shreds: u64,
+    /// Whether the batch holds the last entries of the slot
+    pub is_full: bool,
+}
+
+/// Position of the stream of the entries of a slot
+#[derive(Debug, Default)]
+struct SlotEntryStream {
+    next_shred_index: u64,
+    next_entry_index: usize,
+    is_full: bool,
+}
+
+/// Streams the entries of all the slots being received, for the consumers of blocks which don't
+/// replay them
+#[derive(Debug, Default)]
+pub struct StreamingDeshredder {
+    streams: HashMap<Slot, SlotEntryStream>,
+}
+
+impl StreamingDeshredder {
+    /// Returns the new entries of `slot`, whose shreds were updated, if any
+    pub fn on_slot_updated(
+        &mut self,
+        blockstore: &Blockstore,
+        slot: Slot,
+    ) -> Result<Option<SlotEntryBatch>> {
+        let stream = self.streams.entry(slot).or_default();
+        // Full slots are kept until rooted, so that they aren't streamed again
+        if stream.is_full {
+            return Ok(None);
+        }
+        let (entries, num_shreds, is_full) = blockstore.get_slot_entries_with_shred_info(
+            slot,
+            stream.next_shred_index,
+            /*allow_dead_slots:*/ false,
+        )?;
+        let start_entry_index = stream.next_entry_index;
+        stream.next_shred_index += num_shreds;
+        stream.next_entry_index += entries.len();
+        stream.is_full = is_full;
+        Ok((!entries.is_empty()).then(|| SlotEntryBatch {
+            slot,
+            start_entry_index,
+            entries,
+            num_shreds,
+            is_full,
+        }))
+    }
+
+    /// Forgets the slots older than `root`
+    pub fn set_root(&mut self, root: Slot) {
+        self.streams.retain(|slot, _| *slot >= root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::blockstore::make_slot_entries};
+
+    #[test]
+    fn test_streaming_deshredder() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, entries) = make_slot_entries(1, 0, 200);
+        let num_shreds = shreds.len();
+        assert!(num_shreds > 1);
+
+        // The shreds of the second half don't make a block the first half completes
+        let mut first_half = shreds;
+        let second_half = first_half.split_off(num_shreds / 2);
+        let mut deshredder = StreamingDeshredder::default();
+        blockstore.insert_shreds(second_half, None, false).unwrap();
+        assert_eq!(deshredder.on_slot_updated(&blockstore, 1).unwrap(), None);
+
+        blockstore.insert_shreds(first_half, None, false).unwrap();
+        assert_eq!(
+            deshredder.on_slot_updated(&blockstore, 1).unwrap(),
+            Some(SlotEntryBatch {
+                slot: 1,
+                start_entry_index: 0,
+                entries,
+                num_shreds: num_shreds as u64,
+                is_full: true,
+            })
+        );
+        // Full slots aren't streamed again until rooted
+        assert_eq!(deshredder.on_slot_updated(&blockstore, 1).unwrap(), None);
+        deshredder.set_root(2);
+        assert!(deshredder.streams.is_empty());
+    }
+}
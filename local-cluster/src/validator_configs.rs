@@ -13,6 +13,7 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         account_shrink_paths: config.account_shrink_paths.clone(),
         rpc_config: config.rpc_config.clone(),
         accountsdb_repl_service_config: config.accountsdb_repl_service_config.clone(),
+        rpc_reads_service_config: config.rpc_reads_service_config.clone(),
         accountsdb_plugin_config_files: config.accountsdb_plugin_config_files.clone(),
         rpc_addrs: config.rpc_addrs,
         pubsub_config: config.pubsub_config.clone(),
@@ -32,6 +33,7 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accounts_hash_fault_injection_slots: config.accounts_hash_fault_injection_slots,
         frozen_accounts: config.frozen_accounts.clone(),
         no_rocksdb_compaction: config.no_rocksdb_compaction,
+        blockstore_versioned_protobuf: config.blockstore_versioned_protobuf,
         rocksdb_compaction_interval: config.rocksdb_compaction_interval,
         rocksdb_max_compaction_jitter: config.rocksdb_max_compaction_jitter,
         accounts_hash_interval_slots: config.accounts_hash_interval_slots,
@@ -54,12 +56,27 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
         accounts_db_use_index_hash_calculation: config.accounts_db_use_index_hash_calculation,
         tpu_coalesce_ms: config.tpu_coalesce_ms,
+        tpu_coalesce_max_packets: config.tpu_coalesce_max_packets,
+        tpu_recv_buffer_size: config.tpu_recv_buffer_size,
+        tpu_recv_backend: config.tpu_recv_backend,
+        tpu_autotune_recv_batch_size: config.tpu_autotune_recv_batch_size,
         validator_exit: Arc::new(RwLock::new(Exit::default())),
         poh_hashes_per_batch: config.poh_hashes_per_batch,
         no_wait_for_vote_to_start_leader: config.no_wait_for_vote_to_start_leader,
         accounts_shrink_ratio: config.accounts_shrink_ratio,
         accounts_db_config: config.accounts_db_config.clone(),
         disable_epoch_boundary_optimization: config.disable_epoch_boundary_optimization,
+        external_block_producer_config: config.external_block_producer_config.clone(),
+        executor_cache_capacity: config.executor_cache_capacity,
+        poh_manual_ticks: config.poh_manual_ticks.clone(),
+        verify_produced_blocks: config.verify_produced_blocks,
+        leader_slot_reports: config.leader_slot_reports.clone(),
+        disable_duplicate_slot_dumping: config.disable_duplicate_slot_dumping,
+        dumped_slots: config.dumped_slots.clone(),
+        accounts_hash_audit: config.accounts_hash_audit.clone(),
+        compute_budget: config.compute_budget,
+        queue_conflicting_transactions: config.queue_conflicting_transactions,
+        custom_syscalls: config.custom_syscalls.clone(),
     }
 }
 
@@ -1,11 +1,45 @@
 //! The `logger` module configures `env_logger`
 
 use lazy_static::lazy_static;
-use std::sync::{Arc, RwLock};
+use std::{
+    fmt::Write as _,
+    io::Write,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    thread,
+};
 
 lazy_static! {
     static ref LOGGER: Arc<RwLock<env_logger::Logger>> =
         Arc::new(RwLock::new(env_logger::Logger::from_default_env()));
+    static ref LOG_FORMAT: RwLock<LogFormat> = RwLock::new(LogFormat::Text);
+    static ref LOG_FILE: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// Format of the log records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line of text per record, the default
+    Text,
+    /// One JSON object per line, with the timestamp, level, target and fields of the record
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format {}, expected text or json", s)),
+        }
+    }
+}
+
+// Sets the format of the records logged by the loggers configured from now on
+pub fn set_log_format(format: LogFormat) {
+    *LOG_FORMAT.write().unwrap() = format;
 }
 
 struct LoggerShim {}
@@ -22,6 +56,77 @@ impl log::Log for LoggerShim {
     fn flush(&self) {}
 }
 
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn format_json_record(timestamp: &str, record: &log::Record) -> String {
+    let mut line = String::from("{\"timestamp\":");
+    write_json_string(&mut line, timestamp);
+    line.push_str(",\"level\":");
+    write_json_string(&mut line, record.level().as_str());
+    line.push_str(",\"target\":");
+    write_json_string(&mut line, record.target());
+    line.push_str(",\"fields\":{\"message\":");
+    write_json_string(&mut line, &record.args().to_string());
+    line.push_str("},\"threadName\":");
+    write_json_string(&mut line, thread::current().name().unwrap_or_default());
+    if let Some(file) = record.file() {
+        line.push_str(",\"file\":");
+        write_json_string(&mut line, file);
+    }
+    if let Some(line_number) = record.line() {
+        let _ = write!(line, ",\"line\":{}", line_number);
+    }
+    line.push('}');
+    line
+}
+
+fn build_logger(mut builder: env_logger::Builder) -> env_logger::Logger {
+    match *LOG_FORMAT.read().unwrap() {
+        LogFormat::Text => builder.format_timestamp_nanos(),
+        LogFormat::Json => builder.format(|buf, record| {
+            let timestamp = buf.timestamp_nanos().to_string();
+            writeln!(buf, "{}", format_json_record(&timestamp, record))
+        }),
+    };
+    builder.build()
+}
+
+// Sends the records to the file set by `setup_file_with_default`, if any, so re-configuring the
+// filter does not silently switch the logger back to stderr
+fn set_log_target(builder: &mut env_logger::Builder) {
+    if let Some(logfile) = LOG_FILE.read().unwrap().as_ref() {
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(logfile)
+        {
+            Ok(file) => {
+                builder.target(env_logger::Target::Pipe(Box::new(file)));
+            }
+            Err(err) => eprintln!(
+                "Unable to open log file {}, logging to stderr instead: {}",
+                logfile, err
+            ),
+        }
+    }
+}
+
 fn replace_logger(logger: env_logger::Logger) {
     log::set_max_level(logger.filter());
     *LOGGER.write().unwrap() = logger;
@@ -32,18 +137,17 @@ fn replace_logger(logger: env_logger::Logger) {
 // so if set it takes precedence.
 // May be called at any time to re-configure the log filter
 pub fn setup_with(filter: &str) {
-    let logger =
-        env_logger::Builder::from_env(env_logger::Env::new().filter_or("_RUST_LOG", filter))
-            .format_timestamp_nanos()
-            .build();
-    replace_logger(logger);
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::new().filter_or("_RUST_LOG", filter));
+    set_log_target(&mut builder);
+    replace_logger(build_logger(builder));
 }
 
 // Configures logging with a default filter if RUST_LOG is not set
 pub fn setup_with_default(filter: &str) {
-    let logger = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter))
-        .format_timestamp_nanos()
-        .build();
+    let logger = build_logger(env_logger::Builder::from_env(
+        env_logger::Env::new().default_filter_or(filter),
+    ));
     replace_logger(logger);
 }
 
@@ -56,16 +160,39 @@ pub fn setup() {
 //
 // NOTE: This does not work at the moment, pending the resolution of https://github.com/env-logger-rs/env_logger/issues/208
 pub fn setup_file_with_default(logfile: &str, filter: &str) {
-    use std::fs::OpenOptions;
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(logfile)
-        .unwrap();
-    let logger = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter))
-        .format_timestamp_nanos()
-        .target(env_logger::Target::Pipe(Box::new(file)))
-        .build();
-    replace_logger(logger);
+    *LOG_FILE.write().unwrap() = Some(logfile.to_string());
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter));
+    set_log_target(&mut builder);
+    replace_logger(build_logger(builder));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json_record() {
+        let line = format_json_record(
+            "2021-11-30T12:00:00.000000000Z",
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target("solana_core::replay_stage")
+                .args(format_args!("slot \"{}\"\tfailed\n", 42))
+                .line(Some(7))
+                .build(),
+        );
+        assert_eq!(
+            line,
+            format!(
+                "{{\"timestamp\":\"2021-11-30T12:00:00.000000000Z\",\"level\":\"WARN\",\
+                 \"target\":\"solana_core::replay_stage\",\
+                 \"fields\":{{\"message\":\"slot \\\"42\\\"\\tfailed\\n\"}},\
+                 \"threadName\":\"{}\",\"line\":7}}",
+                thread::current().name().unwrap()
+            )
+        );
+        assert_eq!(LogFormat::from_str("json"), Ok(LogFormat::Json));
+        assert!(LogFormat::from_str("yaml").is_err());
+    }
 }
@@ -0,0 +1,245 @@
+//! Per-transaction tracing with OpenTelemetry
+//!
+//! Each stage a transaction goes through (sigverify, banking, execution, commit and RPC
+//! preflight) records a span for it. The trace id is derived from the transaction's signature,
+//! so the spans recorded by different stages for the same transaction are grouped into a single
+//! trace, which can be looked up by signature.
+//!
+//! Spans are exported to an OTLP collector once `init()` has been called, and are only recorded
+//! while tracing is enabled. Stages time their spans with `Instant`, which is only converted to
+//! wall clock time when a span is actually recorded.
+//!
+//! The exporter is only built with the `tracing` feature. Without it `init()` fails and every
+//! span is dropped, so stages record their spans unconditionally.
+#[cfg(feature = "tracing")]
+use {
+    lazy_static::lazy_static,
+    opentelemetry::{
+        global,
+        sdk::{trace, Resource},
+        trace::{
+            Span, SpanContext, SpanId, TraceContextExt, TraceError, TraceFlags, TraceId,
+            TraceState, Tracer,
+        },
+        Context, KeyValue,
+    },
+    opentelemetry_otlp::WithExportConfig,
+    std::{convert::TryInto, sync::Mutex, time::SystemTime},
+    tokio::runtime::Runtime,
+};
+use {
+    solana_sdk::signature::Signature,
+    std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Instant,
+    },
+};
+
+/// Name of the tracer recording the transaction spans
+#[cfg(feature = "tracing")]
+const TRACER_NAME: &str = "solana-transaction";
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "tracing")]
+lazy_static! {
+    // Runs the batch span processor of the exporter
+    static ref EXPORT_RUNTIME: Mutex<Option<Runtime>> = Mutex::new(None);
+}
+
+/// Installs an exporter sending the spans to the OTLP collector at `endpoint`, and enables
+/// tracing.
+#[cfg(feature = "tracing")]
+pub fn init(endpoint: &str, service_name: &str) -> Result<(), String> {
+    init_exporter(endpoint, service_name).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "tracing"))]
+pub fn init(_endpoint: &str, _service_name: &str) -> Result<(), String> {
+    Err("built without the `tracing` feature".to_string())
+}
+
+#[cfg(feature = "tracing")]
+fn init_exporter(endpoint: &str, service_name: &str) -> Result<(), TraceError> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("solana-otlp-export")
+        .enable_all()
+        .build()
+        .map_err(|err| TraceError::Other(Box::new(err)))?;
+    {
+        let _guard = runtime.enter();
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(
+                trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    service_name.to_string(),
+                )])),
+            )
+            .install_batch(opentelemetry::runtime::Tokio)?;
+    }
+    *EXPORT_RUNTIME.lock().unwrap() = Some(runtime);
+    INITIALIZED.store(true, Ordering::Relaxed);
+    set_enabled(true);
+    Ok(())
+}
+
+/// Flushes the spans that haven't been exported yet and stops the exporter.
+pub fn shutdown() {
+    if INITIALIZED.swap(false, Ordering::Relaxed) {
+        #[cfg(feature = "tracing")]
+        {
+            global::shutdown_tracer_provider();
+            EXPORT_RUNTIME.lock().unwrap().take();
+        }
+    }
+}
+
+/// Starts or stops recording spans. Has no effect on what's exported until `init()` is called.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) && INITIALIZED.load(Ordering::Relaxed)
+}
+
+// The trace of a transaction hangs off a parent span derived from its signature, which is never
+// recorded itself.
+#[cfg(feature = "tracing")]
+fn transaction_context(signature: &Signature) -> Context {
+    let bytes = signature.as_ref();
+    let trace_id = TraceId::from_bytes(bytes[..16].try_into().unwrap());
+    let span_id = SpanId::from_bytes(bytes[16..24].try_into().unwrap());
+    Context::new().with_remote_span_context(SpanContext::new(
+        trace_id,
+        span_id,
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ))
+}
+
+// Maps instants to wall clock time, sampling the clocks once for a whole batch of spans
+#[cfg(feature = "tracing")]
+fn to_system_times(start: Instant, end: Instant) -> (SystemTime, SystemTime) {
+    let now = Instant::now();
+    let system_now = SystemTime::now();
+    (
+        system_now - now.saturating_duration_since(start),
+        system_now - now.saturating_duration_since(end),
+    )
+}
+
+/// Records the span of a stage of a transaction.
+pub fn record_span(stage: &'static str, signature: &Signature, start: Instant, end: Instant) {
+    record_spans(stage, std::iter::once(signature), start, end)
+}
+
+/// Records the span of a stage processing a batch of transactions, for each of them.
+pub fn record_spans<'a>(
+    stage: &'static str,
+    signatures: impl IntoIterator<Item = &'a Signature>,
+    start: Instant,
+    end: Instant,
+) {
+    if !is_enabled() {
+        return;
+    }
+    export_spans(stage, signatures, start, end);
+}
+
+// Never reached, tracing can't be initialized without the exporter
+#[cfg(not(feature = "tracing"))]
+fn export_spans<'a>(
+    _stage: &'static str,
+    _signatures: impl IntoIterator<Item = &'a Signature>,
+    _start: Instant,
+    _end: Instant,
+) {
+}
+
+#[cfg(feature = "tracing")]
+fn export_spans<'a>(
+    stage: &'static str,
+    signatures: impl IntoIterator<Item = &'a Signature>,
+    start: Instant,
+    end: Instant,
+) {
+    let (start, end) = to_system_times(start, end);
+    let tracer = global::tracer(TRACER_NAME);
+    for signature in signatures {
+        let mut span = tracer
+            .span_builder(stage)
+            .with_start_time(start)
+            .with_attributes(vec![KeyValue::new("signature", signature.to_string())])
+            .start_with_context(&tracer, &transaction_context(signature));
+        span.end_with_timestamp(end);
+    }
+}
+
+/// Records the span of a stage of a transaction, from its creation until it's dropped
+pub struct TransactionSpan {
+    stage: &'static str,
+    // None if tracing was disabled when the span was started
+    start: Option<(Signature, Instant)>,
+}
+
+impl TransactionSpan {
+    pub fn start(stage: &'static str, signature: &Signature) -> Self {
+        let start = is_enabled().then(|| (*signature, Instant::now()));
+        Self { stage, start }
+    }
+}
+
+impl Drop for TransactionSpan {
+    fn drop(&mut self) {
+        if let Some((signature, start)) = self.start.take() {
+            record_span(self.stage, &signature, start, Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn test_transaction_context() {
+        let signature = Signature::new(&[7; 64]);
+        let context = transaction_context(&signature);
+        let span_context = context.span().span_context().clone();
+        assert_eq!(span_context.trace_id(), TraceId::from_bytes([7; 16]));
+        assert_eq!(span_context.span_id(), SpanId::from_bytes([7; 8]));
+        assert!(span_context.is_valid());
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn test_to_system_times() {
+        let end = Instant::now();
+        let start = end - std::time::Duration::from_millis(5);
+        let (system_start, system_end) = to_system_times(start, end);
+        assert_eq!(
+            system_end.duration_since(system_start).unwrap(),
+            std::time::Duration::from_millis(5)
+        );
+    }
+
+    #[test]
+    fn test_disabled_without_init() {
+        set_enabled(true);
+        assert!(!is_enabled());
+        set_enabled(false);
+    }
+}
@@ -5,7 +5,8 @@ use {
     rand::{thread_rng, Rng},
     socket2::{Domain, SockAddr, Socket, Type},
     std::{
-        collections::{BTreeMap, HashSet},
+        collections::{BTreeMap, BTreeSet, HashSet},
+        fmt,
         io::{self, Read, Write},
         net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket},
         sync::{mpsc::channel, Arc, RwLock},
@@ -15,6 +16,7 @@ use {
 };
 
 mod ip_echo_server;
+pub mod nat_pmp;
 pub use ip_echo_server::{ip_echo_server, IpEchoServer, MAX_PORT_COUNT_PER_MESSAGE};
 use ip_echo_server::{IpEchoServerMessage, IpEchoServerResponse};
 
@@ -114,13 +116,55 @@ pub fn get_cluster_shred_version(ip_echo_server_addr: &SocketAddr) -> Result<u16
 const DEFAULT_TIMEOUT_SECS: u64 = 5;
 const DEFAULT_RETRY_COUNT: usize = 5;
 
-fn do_verify_reachable_ports(
+/// The ports which could be reached from an ip echo server, and those which could not
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PortReachabilityReport {
+    pub reachable_tcp_ports: BTreeSet<u16>,
+    pub unreachable_tcp_ports: BTreeSet<u16>,
+    pub reachable_udp_ports: BTreeSet<u16>,
+    pub unreachable_udp_ports: BTreeSet<u16>,
+}
+
+impl PortReachabilityReport {
+    pub fn all_reachable(&self) -> bool {
+        self.unreachable_tcp_ports.is_empty() && self.unreachable_udp_ports.is_empty()
+    }
+}
+
+impl fmt::Display for PortReachabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (protocol, reachable_ports, unreachable_ports) in [
+            (
+                "tcp",
+                &self.reachable_tcp_ports,
+                &self.unreachable_tcp_ports,
+            ),
+            (
+                "udp",
+                &self.reachable_udp_ports,
+                &self.unreachable_udp_ports,
+            ),
+        ] {
+            for port in reachable_ports {
+                writeln!(f, "{}/{}: reachable", protocol, port)?;
+            }
+            for port in unreachable_ports {
+                writeln!(f, "{}/{}: UNREACHABLE", protocol, port)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn do_check_reachable_ports(
     ip_echo_server_addr: &SocketAddr,
     tcp_listeners: Vec<(u16, TcpListener)>,
     udp_sockets: &[&UdpSocket],
     timeout: u64,
     udp_retry_count: usize,
-) -> bool {
+    stop_at_first_failure: bool,
+) -> PortReachabilityReport {
+    let mut report = PortReachabilityReport::default();
     info!(
         "Checking that tcp ports {:?} are reachable from {:?}",
         tcp_listeners, ip_echo_server_addr
@@ -133,7 +177,6 @@ fn do_verify_reachable_ports(
     )
     .map_err(|err| warn!("ip_echo_server request failed: {}", err));
 
-    let mut ok = true;
     let timeout = Duration::from_secs(timeout);
 
     // Wait for a connection to open on each TCP port
@@ -152,6 +195,7 @@ fn do_verify_reachable_ports(
         match receiver.recv_timeout(timeout) {
             Ok(_) => {
                 info!("tcp/{} is reachable", port);
+                report.reachable_tcp_ports.insert(port);
             }
             Err(err) => {
                 error!(
@@ -163,16 +207,16 @@ fn do_verify_reachable_ports(
                 // So, to close the thread cleanly, just connect from here.
                 // ref: https://github.com/rust-lang/rust/issues/31615
                 TcpStream::connect_timeout(&listening_addr, timeout).unwrap();
-                ok = false;
+                report.unreachable_tcp_ports.insert(port);
             }
         }
         // ensure to reap the thread
         thread_handle.join().unwrap();
     }
 
-    if !ok {
+    if stop_at_first_failure && !report.all_reachable() {
         // No retries for TCP, abort on the first failure
-        return ok;
+        return report;
     }
 
     let mut udp_ports: BTreeMap<_, _> = BTreeMap::new();
@@ -192,8 +236,6 @@ fn do_verify_reachable_ports(
     );
 
     'outer: for checked_ports_and_sockets in udp_ports.chunks(MAX_PORT_COUNT_PER_MESSAGE) {
-        ok = false;
-
         for udp_remaining_retry in (0_usize..udp_retry_count).rev() {
             let (checked_ports, checked_socket_iter) = (
                 checked_ports_and_sockets
@@ -262,7 +304,7 @@ fn do_verify_reachable_ports(
                     "checked udp ports: {:?}, reachable udp ports: {:?}",
                     checked_ports, reachable_ports
                 );
-                ok = true;
+                report.reachable_udp_ports.extend(reachable_ports);
                 break;
             } else if udp_remaining_retry > 0 {
                 // Might have lost a UDP packet, retry a couple times
@@ -273,12 +315,21 @@ fn do_verify_reachable_ports(
                 error!("There are some udp ports with no response!! Retrying...");
             } else {
                 error!("Maximum retry count is reached....");
-                break 'outer;
+                for port in checked_ports {
+                    if reachable_ports.contains(&port) {
+                        report.reachable_udp_ports.insert(port);
+                    } else {
+                        report.unreachable_udp_ports.insert(port);
+                    }
+                }
+                if stop_at_first_failure {
+                    break 'outer;
+                }
             }
         }
     }
 
-    ok
+    report
 }
 
 pub fn verify_reachable_ports(
@@ -286,12 +337,31 @@ pub fn verify_reachable_ports(
     tcp_listeners: Vec<(u16, TcpListener)>,
     udp_sockets: &[&UdpSocket],
 ) -> bool {
-    do_verify_reachable_ports(
+    do_check_reachable_ports(
         ip_echo_server_addr,
         tcp_listeners,
         udp_sockets,
         DEFAULT_TIMEOUT_SECS,
         DEFAULT_RETRY_COUNT,
+        /*stop_at_first_failure:*/ true,
+    )
+    .all_reachable()
+}
+
+/// Checks all the provided TCP/UDP ports, even after some are found unreachable, and reports
+/// which of them are reachable by the machine at `ip_echo_server_addr`
+pub fn check_reachable_ports(
+    ip_echo_server_addr: &SocketAddr,
+    tcp_listeners: Vec<(u16, TcpListener)>,
+    udp_sockets: &[&UdpSocket],
+) -> PortReachabilityReport {
+    do_check_reachable_ports(
+        ip_echo_server_addr,
+        tcp_listeners,
+        udp_sockets,
+        DEFAULT_TIMEOUT_SECS,
+        DEFAULT_RETRY_COUNT,
+        /*stop_at_first_failure:*/ false,
     )
 }
 
@@ -727,13 +797,15 @@ mod tests {
         let (correct_client_port, (_client_udp_socket, client_tcp_listener)) =
             bind_common_in_range(ip_addr, (3200, 3250)).unwrap();
 
-        assert!(!do_verify_reachable_ports(
+        assert!(!do_check_reachable_ports(
             &server_ip_echo_addr,
             vec![(correct_client_port, client_tcp_listener)],
             &[],
             2,
             3,
-        ));
+            true,
+        )
+        .all_reachable());
     }
 
     #[test]
@@ -747,15 +819,21 @@ mod tests {
 
         let server_ip_echo_addr = server_udp_socket.local_addr().unwrap();
 
-        let (_correct_client_port, (client_udp_socket, _client_tcp_listener)) =
+        let (correct_client_port, (client_udp_socket, _client_tcp_listener)) =
             bind_common_in_range(ip_addr, (3200, 3250)).unwrap();
 
-        assert!(!do_verify_reachable_ports(
+        let report = do_check_reachable_ports(
             &server_ip_echo_addr,
             vec![],
             &[&client_udp_socket],
             2,
             3,
-        ));
+            false,
+        );
+        assert!(!report.all_reachable());
+        assert!(report.unreachable_udp_ports.contains(&correct_client_port));
+        assert!(report
+            .to_string()
+            .contains(&format!("udp/{}: UNREACHABLE", correct_client_port)));
     }
 }
@@ -0,0 +1,219 @@
+//! The `nat_pmp` module requests port mappings from the NAT gateway of the local network with
+//! the NAT Port Mapping Protocol (RFC 6886), so that the ports of a node behind a NAT router are
+//! reachable from the outside without forwarding them by hand. Gateways which only speak UPnP or
+//! PCP aren't supported.
+
+use {
+    log::*,
+    std::{
+        convert::TryInto,
+        fmt,
+        net::{Ipv4Addr, SocketAddr, UdpSocket},
+        time::Duration,
+    },
+};
+
+pub const NAT_PMP_PORT: u16 = 5351;
+/// Lifetime of the mappings recommended by RFC 6886, they are to be renewed halfway through
+pub const DEFAULT_NAT_MAPPING_LIFETIME_SECS: u32 = 7200;
+/// Shortest lifetime assumed for a granted mapping, so that a gateway granting very short or zero
+/// lifetimes doesn't make the node renew its mappings in a tight loop
+pub const MIN_NAT_MAPPING_LIFETIME_SECS: u32 = 120;
+
+const NAT_PMP_VERSION: u8 = 0;
+const MAPPING_REQUEST_LENGTH: usize = 12;
+const MAPPING_RESPONSE_LENGTH: usize = 16;
+// The first request times out after 250ms, then the timeout doubles at each retry
+const INITIAL_REQUEST_TIMEOUT_MS: u64 = 250;
+const REQUEST_RETRY_COUNT: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NatProtocol {
+    Udp,
+    Tcp,
+}
+
+impl NatProtocol {
+    fn opcode(&self) -> u8 {
+        match self {
+            Self::Udp => 1,
+            Self::Tcp => 2,
+        }
+    }
+}
+
+impl fmt::Display for NatProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Udp => write!(f, "udp"),
+            Self::Tcp => write!(f, "tcp"),
+        }
+    }
+}
+
+/// A port mapping granted by the gateway
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NatMapping {
+    pub protocol: NatProtocol,
+    pub internal_port: u16,
+    pub external_port: u16,
+    pub lifetime_secs: u32,
+}
+
+/// The default IPv4 gateway of the machine, read from the routing table
+#[cfg(target_os = "linux")]
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    parse_default_gateway(&std::fs::read_to_string("/proc/net/route").ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    None
+}
+
+// The addresses of /proc/net/route are in network order, printed as native-endian integers
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(route_table: &str) -> Option<Ipv4Addr> {
+    const RTF_GATEWAY: u16 = 0x2;
+    route_table.lines().skip(1).find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let (destination, gateway, flags) = (fields.get(1)?, fields.get(2)?, fields.get(3)?);
+        let flags = u16::from_str_radix(flags, 16).ok()?;
+        if *destination != "00000000" || flags & RTF_GATEWAY == 0 {
+            return None;
+        }
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+/// Requests the gateway to map `internal_port` to the same external port for `lifetime_secs`,
+/// or to remove the mapping if `lifetime_secs` is 0. The gateway may grant another external
+/// port, or a shorter lifetime.
+pub fn request_nat_pmp_mapping(
+    gateway: Ipv4Addr,
+    protocol: NatProtocol,
+    internal_port: u16,
+    lifetime_secs: u32,
+) -> Result<NatMapping, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|err| err.to_string())?;
+    socket
+        .connect(SocketAddr::from((gateway, NAT_PMP_PORT)))
+        .map_err(|err| err.to_string())?;
+    let request = mapping_request(protocol, internal_port, internal_port, lifetime_secs);
+    let mut response = [0u8; MAPPING_RESPONSE_LENGTH];
+    for retry in 0..REQUEST_RETRY_COUNT {
+        let timeout = Duration::from_millis(INITIAL_REQUEST_TIMEOUT_MS << retry);
+        socket
+            .set_read_timeout(Some(timeout))
+            .map_err(|err| err.to_string())?;
+        socket.send(&request).map_err(|err| err.to_string())?;
+        match socket.recv(&mut response) {
+            Ok(size) => return parse_mapping_response(protocol, &response[..size]),
+            Err(err) => debug!("No NAT-PMP response from {}: {}", gateway, err),
+        }
+    }
+    Err(format!("No NAT-PMP response from gateway {}", gateway))
+}
+
+fn mapping_request(
+    protocol: NatProtocol,
+    internal_port: u16,
+    external_port: u16,
+    lifetime_secs: u32,
+) -> [u8; MAPPING_REQUEST_LENGTH] {
+    let mut request = [0u8; MAPPING_REQUEST_LENGTH];
+    request[0] = NAT_PMP_VERSION;
+    request[1] = protocol.opcode();
+    // Bytes 2 and 3 are reserved
+    request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    request[6..8].copy_from_slice(&external_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime_secs.to_be_bytes());
+    request
+}
+
+fn parse_mapping_response(protocol: NatProtocol, response: &[u8]) -> Result<NatMapping, String> {
+    if response.len() < MAPPING_RESPONSE_LENGTH {
+        return Err(format!(
+            "NAT-PMP response too short, received {} bytes",
+            response.len()
+        ));
+    }
+    if response[0] != NAT_PMP_VERSION || response[1] != 128 + protocol.opcode() {
+        return Err(format!(
+            "Unexpected NAT-PMP response, version {} opcode {}",
+            response[0], response[1]
+        ));
+    }
+    let result_code = u16::from_be_bytes([response[2], response[3]]);
+    if result_code != 0 {
+        let reason = match result_code {
+            1 => "unsupported version",
+            2 => "not authorized or refused",
+            3 => "network failure",
+            4 => "out of resources",
+            5 => "unsupported opcode",
+            _ => "unknown error",
+        };
+        return Err(format!(
+            "NAT-PMP mapping refused by the gateway: {} ({})",
+            reason, result_code
+        ));
+    }
+    Ok(NatMapping {
+        protocol,
+        internal_port: u16::from_be_bytes([response[8], response[9]]),
+        external_port: u16::from_be_bytes([response[10], response[11]]),
+        lifetime_secs: u32::from_be_bytes(response[12..16].try_into().unwrap()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_gateway() {
+        let route_table = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+eth0\t00000000\t0100A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0
+";
+        let expected = Ipv4Addr::from(0x0100_A8C0u32.to_ne_bytes());
+        assert_eq!(parse_default_gateway(route_table), Some(expected));
+        if cfg!(target_endian = "little") {
+            assert_eq!(expected, Ipv4Addr::new(192, 168, 0, 1));
+        }
+        assert_eq!(parse_default_gateway(""), None);
+    }
+
+    #[test]
+    fn test_mapping_request_and_response() {
+        assert_eq!(
+            mapping_request(NatProtocol::Tcp, 8001, 8001, 7200),
+            [0, 2, 0, 0, 0x1f, 0x41, 0x1f, 0x41, 0, 0, 0x1c, 0x20]
+        );
+
+        let response = [
+            0, 129, 0, 0, 0, 0, 0, 42, 0x1f, 0x41, 0x1f, 0x42, 0, 0, 0x0e, 0x10,
+        ];
+        assert_eq!(
+            parse_mapping_response(NatProtocol::Udp, &response),
+            Ok(NatMapping {
+                protocol: NatProtocol::Udp,
+                internal_port: 8001,
+                external_port: 8002,
+                lifetime_secs: 3600,
+            })
+        );
+        // A response to another opcode
+        assert!(parse_mapping_response(NatProtocol::Tcp, &response).is_err());
+        assert!(parse_mapping_response(NatProtocol::Udp, &response[..8]).is_err());
+
+        let mut refused = response;
+        refused[3] = 2;
+        assert!(parse_mapping_response(NatProtocol::Udp, &refused)
+            .unwrap_err()
+            .contains("refused"));
+    }
+}
@@ -23,6 +23,19 @@ fn bench_sigverify(bencher: &mut Bencher) {
     })
 }
 
+#[bench]
+fn bench_sigverify_cpu(bencher: &mut Bencher) {
+    let tx = test_tx();
+
+    // generate packet vector
+    let mut batches = to_packets_chunked(&std::iter::repeat(tx).take(128).collect::<Vec<_>>(), 128);
+
+    // verify packets, bypassing the GPU even when perf-libs are available
+    bencher.iter(|| {
+        sigverify::ed25519_verify_cpu(&mut batches, false);
+    })
+}
+
 #[bench]
 fn bench_get_offsets(bencher: &mut Bencher) {
     let tx = test_tx();
@@ -7,6 +7,7 @@ pub mod recycler_cache;
 pub mod sigverify;
 pub mod test_tx;
 pub mod thread;
+pub mod thread_affinity;
 
 #[macro_use]
 extern crate lazy_static;
@@ -14,6 +15,9 @@ extern crate lazy_static;
 #[macro_use]
 extern crate log;
 
+#[macro_use]
+extern crate serde_derive;
+
 #[cfg(test)]
 #[macro_use]
 extern crate matches;
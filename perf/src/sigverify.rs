@@ -3,14 +3,22 @@
 //! cores.  When perf-libs are available signature verification is offloaded
 //! to the GPU.
 //!
+//! The CPU backend deliberately verifies each signature individually with
+//! `verify_strict`. Random-linear-combination batch verification is faster, but
+//! it can accept signatures with small torsion components that `verify_strict`
+//! rejects, and every node must agree exactly on which transactions are valid.
+//!
 
 use crate::cuda_runtime::PinnedVec;
 use crate::packet::{Packet, Packets};
 use crate::perf_libs;
 use crate::recycler::Recycler;
-use rayon::ThreadPool;
+use crate::thread_affinity::{self, ThreadRole};
 use solana_metrics::inc_new_counter_debug;
-use solana_rayon_threadlimit::get_thread_count;
+use solana_rayon_threadlimit::{
+    get_thread_count,
+    thread_pools::{ManagedThreadPool, SIGVERIFY_THREAD_POOL},
+};
 use solana_sdk::hash::Hash;
 use solana_sdk::message::{MESSAGE_HEADER_LENGTH, MESSAGE_VERSION_PREFIX};
 use solana_sdk::pubkey::Pubkey;
@@ -20,6 +28,7 @@ use solana_sdk::signature::Signature;
 use solana_sdk::transaction::Transaction;
 use std::convert::TryFrom;
 use std::mem::size_of;
+use std::sync::Arc;
 
 // Representing key tKeYE4wtowRb8yRroZShTipE18YVnqwXjsSAoNsFU6g
 const TRACER_KEY_BYTES: [u8; 32] = [
@@ -29,11 +38,17 @@ const TRACER_KEY_BYTES: [u8; 32] = [
 const TRACER_KEY: Pubkey = Pubkey::new_from_array(TRACER_KEY_BYTES);
 
 lazy_static! {
-    static ref PAR_THREAD_POOL: ThreadPool = rayon::ThreadPoolBuilder::new()
-        .num_threads(get_thread_count())
-        .thread_name(|ix| format!("sigverify_{}", ix))
-        .build()
-        .unwrap();
+    static ref PAR_THREAD_POOL: Arc<ManagedThreadPool> =
+        ManagedThreadPool::new(SIGVERIFY_THREAD_POOL, get_thread_count(), |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|ix| format!("sigverify_{}", ix))
+                .start_handler(|ix| {
+                    thread_affinity::pin_current_thread(ThreadRole::SigVerify, ix);
+                })
+                .build()
+                .unwrap()
+        });
 }
 
 pub type TxOffset = PinnedVec<u32>;
@@ -0,0 +1,278 @@
+//! The `thread_affinity` module pins the critical threads of the validator to CPU cores. The cores
+//! of each role of thread are configured once, at startup, and each thread pins itself to one of
+//! them when it starts. Unlike with `taskset`, threads which are restarted are pinned again, and
+//! the cores the threads are pinned to can be inspected at runtime.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+    sync::RwLock,
+    thread,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThreadRole {
+    /// The thread of the PoH service generating the ticks
+    PohTick,
+    /// The threads of the pool verifying the signatures of the packets
+    SigVerify,
+    /// The threads of the banking stage
+    Banking,
+}
+
+impl ThreadRole {
+    pub const ALL: [ThreadRole; 3] = [Self::PohTick, Self::SigVerify, Self::Banking];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::PohTick => "poh",
+            Self::SigVerify => "sigverify",
+            Self::Banking => "banking",
+        }
+    }
+}
+
+impl fmt::Display for ThreadRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ThreadRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|role| role.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "unknown thread role {}, expected one of {}",
+                    s,
+                    Self::ALL
+                        .iter()
+                        .map(ThreadRole::name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// Cores of each role of thread, parsed from a spec of comma separated `<ROLE>=<CORES>` entries
+/// such as `poh=2,sigverify=4-7,banking=8-11`. The threads of a role are pinned to its cores in
+/// turn, and the roles left out aren't pinned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadAffinityConfig {
+    cores: HashMap<ThreadRole, Vec<usize>>,
+}
+
+impl ThreadAffinityConfig {
+    pub fn cores(&self, role: ThreadRole) -> &[usize] {
+        self.cores.get(&role).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Highest core of the config, to check that the machine has it
+    pub fn max_core(&self) -> Option<usize> {
+        self.cores.values().flatten().max().copied()
+    }
+
+    /// Core of the `index`th thread of `role`
+    pub fn core(&self, role: ThreadRole, index: usize) -> Option<usize> {
+        let cores = self.cores(role);
+        (!cores.is_empty()).then(|| cores[index % cores.len()])
+    }
+}
+
+impl FromStr for ThreadAffinityConfig {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parse_core = |s: &str| {
+            s.trim()
+                .parse::<usize>()
+                .map_err(|err| format!("invalid core {}: {}", s, err))
+        };
+        let mut cores: HashMap<ThreadRole, Vec<usize>> = HashMap::new();
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (role, range) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected <ROLE>=<CORES>, found {}", entry))?;
+            let role = role.trim().parse::<ThreadRole>()?;
+            let (first, last) = match range.split_once('-') {
+                Some((first, last)) => (parse_core(first)?, parse_core(last)?),
+                None => {
+                    let core = parse_core(range)?;
+                    (core, core)
+                }
+            };
+            if first > last {
+                return Err(format!("invalid core range {}", range));
+            }
+            cores.entry(role).or_default().extend(first..=last);
+        }
+        Ok(Self { cores })
+    }
+}
+
+/// A thread pinned to a core
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedThread {
+    pub role: ThreadRole,
+    pub index: usize,
+    pub thread_name: String,
+    pub core: usize,
+}
+
+#[derive(Default)]
+struct ThreadManager {
+    config: ThreadAffinityConfig,
+    /// Last thread pinned for each role and index, restarted threads replace their predecessor
+    pinned: BTreeMap<(ThreadRole, usize), PinnedThread>,
+}
+
+lazy_static! {
+    static ref THREAD_MANAGER: RwLock<ThreadManager> = RwLock::default();
+}
+
+/// Sets the cores of the threads starting from now on
+pub fn set_thread_affinity_config(config: ThreadAffinityConfig) {
+    THREAD_MANAGER.write().unwrap().config = config;
+}
+
+/// Core configured for the `index`th thread of `role`
+pub fn configured_core(role: ThreadRole, index: usize) -> Option<usize> {
+    THREAD_MANAGER.read().unwrap().config.core(role, index)
+}
+
+/// Pins the current thread, the `index`th one of `role`, to its configured core. Returns the core,
+/// or `None` if the role isn't configured or the thread couldn't be pinned.
+pub fn pin_current_thread(role: ThreadRole, index: usize) -> Option<usize> {
+    let core = configured_core(role, index)?;
+    match pin_current_thread_to_core(role, index, core) {
+        Ok(()) => Some(core),
+        Err(err) => {
+            warn!("{}", err);
+            None
+        }
+    }
+}
+
+/// Pins the current thread, the `index`th one of `role`, to `core`
+pub fn pin_current_thread_to_core(
+    role: ThreadRole,
+    index: usize,
+    core: usize,
+) -> Result<(), String> {
+    set_current_thread_affinity(core)?;
+    let thread_name = thread::current().name().unwrap_or_default().to_string();
+    info!(
+        "Pinned thread {} ({} {}) to core {}",
+        thread_name, role, index, core
+    );
+    THREAD_MANAGER.write().unwrap().pinned.insert(
+        (role, index),
+        PinnedThread {
+            role,
+            index,
+            thread_name,
+            core,
+        },
+    );
+    Ok(())
+}
+
+/// Threads pinned to cores, by role and index
+pub fn pinned_threads() -> Vec<PinnedThread> {
+    THREAD_MANAGER
+        .read()
+        .unwrap()
+        .pinned
+        .values()
+        .cloned()
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn set_current_thread_affinity(core: usize) -> Result<(), String> {
+    if core >= libc::CPU_SETSIZE as usize {
+        return Err(format!(
+            "Failed to pin thread to core {}: out of range",
+            core
+        ));
+    }
+    // On Linux, the affinity is a per-thread attribute, 0 being the calling thread
+    let result = unsafe {
+        let mut cpu_set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut cpu_set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &cpu_set)
+    };
+    if result != 0 {
+        return Err(format!(
+            "Failed to pin thread to core {}: {}",
+            core,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_current_thread_affinity(core: usize) -> Result<(), String> {
+    Err(format!(
+        "Failed to pin thread to core {}: only supported on Linux",
+        core
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_affinity_config() {
+        let config = ThreadAffinityConfig::from_str("poh=2, sigverify=4-6,sigverify=9").unwrap();
+        assert_eq!(config.cores(ThreadRole::PohTick), &[2]);
+        assert_eq!(config.cores(ThreadRole::SigVerify), &[4, 5, 6, 9]);
+        assert!(config.cores(ThreadRole::Banking).is_empty());
+        assert_eq!(config.max_core(), Some(9));
+        assert_eq!(config.core(ThreadRole::PohTick, 3), Some(2));
+        assert_eq!(config.core(ThreadRole::SigVerify, 5), Some(5));
+        assert_eq!(config.core(ThreadRole::Banking, 0), None);
+
+        assert_eq!(
+            ThreadAffinityConfig::from_str("").unwrap(),
+            ThreadAffinityConfig::default()
+        );
+        assert!(ThreadAffinityConfig::from_str("poh").is_err());
+        assert!(ThreadAffinityConfig::from_str("replay=1").is_err());
+        assert!(ThreadAffinityConfig::from_str("banking=8-4").is_err());
+        assert!(ThreadAffinityConfig::from_str("banking=x").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin_current_thread() {
+        let pinned = thread::Builder::new()
+            .name("test-pinned".to_string())
+            .spawn(|| pin_current_thread_to_core(ThreadRole::Banking, 7, 0))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(pinned, Ok(()));
+        assert!(pinned_threads().contains(&PinnedThread {
+            role: ThreadRole::Banking,
+            index: 7,
+            thread_name: "test-pinned".to_string(),
+            core: 0,
+        }));
+    }
+}
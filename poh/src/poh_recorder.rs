@@ -343,6 +343,10 @@ impl PohRecorder {
         self.start_bank.slot()
     }
 
+    pub fn start_bank(&self) -> Arc<Bank> {
+        self.start_bank.clone()
+    }
+
     /// returns if leader slot has been reached, how many grace ticks were afforded,
     ///   imputed leader_slot and self.start_slot()
     /// reached_leader_slot() == true means "ready for a bank"
@@ -815,6 +819,7 @@ pub fn create_test_recorder(
         crate::poh_service::DEFAULT_PINNED_CPU_CORE,
         crate::poh_service::DEFAULT_HASHES_PER_BATCH,
         record_receiver,
+        None,
     );
 
     (exit, poh_recorder, poh_service, entry_receiver)
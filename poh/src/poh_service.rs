@@ -6,10 +6,11 @@ use {
     log::*,
     solana_entry::poh::Poh,
     solana_measure::measure::Measure,
-    solana_sdk::poh_config::PohConfig,
+    solana_perf::thread_affinity::{self, ThreadRole},
+    solana_sdk::{clock::Slot, poh_config::PohConfig},
     std::{
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, Mutex,
         },
         thread::{self, sleep, Builder, JoinHandle},
@@ -33,6 +34,86 @@ pub const DEFAULT_PINNED_CPU_CORE: usize = 0;
 
 const TARGET_SLOT_ADJUSTMENT_NS: u64 = 50_000_000;
 
+/// Puts a `PohService` in manual mode, where it only ticks up to a tick height requested through
+/// `advance`. Slots then advance deterministically, which makes tests of slot and epoch dependent
+/// logic fast and repeatable.
+#[derive(Debug, Default)]
+pub struct ManualTicks {
+    ticks_per_slot: AtomicU64,
+    tick_height: AtomicU64,
+    target_tick_height: AtomicU64,
+}
+
+impl ManualTicks {
+    fn start(&self, tick_height: u64, ticks_per_slot: u64) {
+        self.tick_height.store(tick_height, Ordering::Relaxed);
+        self.target_tick_height
+            .store(tick_height, Ordering::Relaxed);
+        self.ticks_per_slot.store(ticks_per_slot, Ordering::Release);
+    }
+
+    /// Allow PoH to advance `slots` slot boundaries past the last requested one. Returns the tick
+    /// height PoH will stop at, or None if the `PohService` hasn't started yet.
+    pub fn advance(&self, slots: u64) -> Option<u64> {
+        let ticks_per_slot = self.ticks_per_slot()?;
+        let advance = |target: u64| (target / ticks_per_slot + slots) * ticks_per_slot;
+        let previous = self
+            .target_tick_height
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |target| {
+                Some(advance(target))
+            })
+            .unwrap();
+        Some(advance(previous))
+    }
+
+    pub fn ticks_per_slot(&self) -> Option<u64> {
+        match self.ticks_per_slot.load(Ordering::Acquire) {
+            0 => None,
+            ticks_per_slot => Some(ticks_per_slot),
+        }
+    }
+
+    pub fn tick_height(&self) -> u64 {
+        self.tick_height.load(Ordering::Relaxed)
+    }
+
+    /// Advance `slots` slots and wait for PoH to get there. Returns the slot PoH advanced to, or
+    /// None if the `PohService` hasn't started or `timeout` elapses first.
+    pub fn advance_and_wait(&self, slots: u64, timeout: Duration) -> Option<Slot> {
+        let ticks_per_slot = self.ticks_per_slot()?;
+        let tick_height = self.advance(slots)?;
+        if self.wait_for_tick_height(tick_height, timeout) {
+            Some(tick_height / ticks_per_slot)
+        } else {
+            None
+        }
+    }
+
+    /// Block until PoH reaches `tick_height`. Returns false if `timeout` elapses first.
+    pub fn wait_for_tick_height(&self, tick_height: u64, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while self.tick_height() < tick_height {
+            if start.elapsed() > timeout {
+                return false;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        true
+    }
+
+    /// Carry on from `tick_height` after PoH is reset past the last manual tick, as happens when
+    /// the validator warps ahead
+    fn rebase(&self, tick_height: u64) {
+        self.tick_height.store(tick_height, Ordering::Relaxed);
+        self.target_tick_height
+            .fetch_max(tick_height, Ordering::Relaxed);
+    }
+
+    fn should_tick(&self) -> bool {
+        self.tick_height() < self.target_tick_height.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Debug)]
 struct PohTiming {
     num_ticks: u64,
@@ -94,6 +175,7 @@ impl PohService {
         pinned_cpu_core: usize,
         hashes_per_batch: u64,
         record_receiver: Receiver<Record>,
+        manual_ticks: Option<Arc<ManualTicks>>,
     ) -> Self {
         let poh_exit_ = poh_exit.clone();
         let poh_config = poh_config.clone();
@@ -101,7 +183,17 @@ impl PohService {
             .name("solana-poh-service-tick_producer".to_string())
             .spawn(move || {
                 solana_sys_tuner::request_realtime_poh();
-                if poh_config.hashes_per_tick.is_none() {
+                if let Some(manual_ticks) = manual_ticks {
+                    Self::manual_tick_producer(
+                        poh_recorder,
+                        &poh_config,
+                        &poh_exit_,
+                        ticks_per_slot,
+                        hashes_per_batch,
+                        record_receiver,
+                        &manual_ticks,
+                    );
+                } else if poh_config.hashes_per_tick.is_none() {
                     if poh_config.target_tick_count.is_none() {
                         Self::sleepy_tick_producer(
                             poh_recorder,
@@ -121,8 +213,15 @@ impl PohService {
                     // PoH service runs in a tight loop, generating hashes as fast as possible.
                     // Let's dedicate one of the CPU cores to this thread so that it can gain
                     // from cache performance.
-                    if let Some(cores) = core_affinity::get_core_ids() {
-                        core_affinity::set_for_current(cores[pinned_cpu_core]);
+                    let core = thread_affinity::configured_core(ThreadRole::PohTick, 0)
+                        .unwrap_or(pinned_cpu_core);
+                    if let Err(err) =
+                        thread_affinity::pin_current_thread_to_core(ThreadRole::PohTick, 0, core)
+                    {
+                        debug!("{}", err);
+                        if let Some(cores) = core_affinity::get_core_ids() {
+                            core_affinity::set_for_current(cores[core]);
+                        }
                     }
                     Self::tick_producer(
                         poh_recorder,
@@ -171,6 +270,53 @@ impl PohService {
         }
     }
 
+    fn manual_tick_producer(
+        poh_recorder: Arc<Mutex<PohRecorder>>,
+        poh_config: &PohConfig,
+        poh_exit: &AtomicBool,
+        ticks_per_slot: u64,
+        hashes_per_batch: u64,
+        record_receiver: Receiver<Record>,
+        manual_ticks: &ManualTicks,
+    ) {
+        let poh = poh_recorder.lock().unwrap().poh.clone();
+        manual_ticks.start(poh_recorder.lock().unwrap().tick_height(), ticks_per_slot);
+        // Ticks are produced as fast as they're requested, so give ReplayStage a chance to start
+        // each leader slot instead of ticking past it. Only wait for about a slot's worth of time
+        // in case the bank never shows up.
+        let max_bank_wait = poh_config.target_tick_duration * ticks_per_slot as u32;
+        let mut waiting_for_bank_since = None;
+        while !poh_exit.load(Ordering::Relaxed) {
+            Self::read_record_receiver_and_process(
+                &poh_recorder,
+                &record_receiver,
+                Duration::from_millis(1),
+            );
+            let tick_height = poh_recorder.lock().unwrap().tick_height();
+            if tick_height > manual_ticks.tick_height() {
+                manual_ticks.rebase(tick_height);
+            }
+            if !manual_ticks.should_tick() {
+                continue;
+            }
+            let mut poh_recorder_l = poh_recorder.lock().unwrap();
+            if poh_recorder_l.reached_leader_slot().0 && !poh_recorder_l.has_bank() {
+                let since = *waiting_for_bank_since.get_or_insert_with(Instant::now);
+                if since.elapsed() < max_bank_wait {
+                    continue;
+                }
+            }
+            waiting_for_bank_since = None;
+            if poh_config.hashes_per_tick.is_some() {
+                while !poh.lock().unwrap().hash(hashes_per_batch) {}
+            }
+            poh_recorder_l.tick();
+            manual_ticks
+                .tick_height
+                .store(poh_recorder_l.tick_height(), Ordering::Relaxed);
+        }
+    }
+
     pub fn read_record_receiver_and_process(
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         record_receiver: &Receiver<Record>,
@@ -473,6 +619,7 @@ mod tests {
                 DEFAULT_PINNED_CPU_CORE,
                 hashes_per_batch,
                 record_receiver,
+                None,
             );
             poh_recorder.lock().unwrap().set_bank(&bank);
 
@@ -547,4 +694,63 @@ mod tests {
         }
         Blockstore::destroy(&ledger_path).unwrap();
     }
+
+    #[test]
+    fn test_poh_service_manual_ticks() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+        let bank = Arc::new(Bank::new_no_wallclock_throttle_for_tests(&genesis_config));
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Arc::new(
+                Blockstore::open(&ledger_path)
+                    .expect("Expected to be able to open database ledger"),
+            );
+            let poh_config = Arc::new(PohConfig::default());
+            let exit = Arc::new(AtomicBool::new(false));
+            let ticks_per_slot = bank.ticks_per_slot();
+            let (poh_recorder, _entry_receiver, record_receiver) = PohRecorder::new(
+                bank.tick_height(),
+                bank.last_blockhash(),
+                bank.clone(),
+                None,
+                ticks_per_slot,
+                &Pubkey::default(),
+                &blockstore,
+                &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+                &poh_config,
+                exit.clone(),
+            );
+            let poh_recorder = Arc::new(Mutex::new(poh_recorder));
+            let manual_ticks = Arc::new(ManualTicks::default());
+            assert_eq!(manual_ticks.advance(1), None);
+
+            let poh_service = PohService::new(
+                poh_recorder.clone(),
+                &poh_config,
+                &exit,
+                ticks_per_slot,
+                DEFAULT_PINNED_CPU_CORE,
+                DEFAULT_HASHES_PER_BATCH,
+                record_receiver,
+                Some(manual_ticks.clone()),
+            );
+            while manual_ticks.ticks_per_slot().is_none() {
+                sleep(Duration::from_millis(10));
+            }
+
+            // no ticks are produced until requested
+            sleep(poh_config.target_tick_duration * 4);
+            assert_eq!(poh_recorder.lock().unwrap().tick_height(), 0);
+
+            let target = manual_ticks.advance(2).unwrap();
+            assert_eq!(target, 2 * ticks_per_slot);
+            assert!(manual_ticks.wait_for_tick_height(target, Duration::from_secs(10)));
+            sleep(poh_config.target_tick_duration * 4);
+            assert_eq!(poh_recorder.lock().unwrap().tick_height(), target);
+
+            exit.store(true, Ordering::Relaxed);
+            poh_service.join().unwrap();
+        }
+        Blockstore::destroy(&ledger_path).unwrap();
+    }
 }
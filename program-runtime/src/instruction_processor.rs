@@ -83,6 +83,22 @@ impl ExecuteDetailsTimings {
         program_timing.accumulated_units = program_timing.accumulated_units.saturating_add(units);
         program_timing.count = program_timing.count.saturating_add(1);
     }
+    /// Total compute units consumed by the instructions executed so far
+    pub fn accumulated_units(&self) -> u64 {
+        self.per_program_timings
+            .values()
+            .fold(0, |acc, program_timing| {
+                acc.saturating_add(program_timing.accumulated_units)
+            })
+    }
+    /// Total time spent executing the instructions executed so far
+    pub fn accumulated_us(&self) -> u64 {
+        self.per_program_timings
+            .values()
+            .fold(0, |acc, program_timing| {
+                acc.saturating_add(program_timing.accumulated_us)
+            })
+    }
 }
 
 // The relevant state of an account before an Instruction executes, used
@@ -17,7 +17,8 @@ use solana_sdk::{
     keyed_account::{create_keyed_accounts_unified, KeyedAccount},
     message::Message,
     process_instruction::{
-        ComputeMeter, Executor, InvokeContext, Logger, ProcessInstructionWithContext,
+        ComputeMeter, ComputeUnitProfile, Executor, InvokeContext, Logger,
+        ProcessInstructionWithContext,
     },
     pubkey::Pubkey,
     rent::Rent,
@@ -93,6 +94,16 @@ impl<'a> InvokeContextStackFrame<'a> {
     }
 }
 
+/// Source of the stakes of the current epoch, queried by programs through the
+/// `sol_get_epoch_stake` syscall
+pub trait EpochStakeCallback {
+    fn get_epoch_total_stake(&self) -> u64;
+    /// Returns zero if the vote account has no stake delegated to it
+    fn get_epoch_vote_account_stake(&self, vote_address: &Pubkey) -> u64;
+    /// Returns None if the vote account didn't exist when the epoch started
+    fn get_epoch_start_commission(&self, vote_address: &Pubkey) -> Option<u8>;
+}
+
 pub struct ThisInvokeContext<'a> {
     instruction_index: usize,
     invoke_stack: Vec<InvokeContextStackFrame<'a>>,
@@ -101,6 +112,7 @@ pub struct ThisInvokeContext<'a> {
     accounts: &'a [(Pubkey, Rc<RefCell<AccountSharedData>>)],
     programs: &'a [(Pubkey, ProcessInstructionWithContext)],
     sysvars: &'a [(Pubkey, Vec<u8>)],
+    log_collector: Option<Rc<LogCollector>>,
     logger: Rc<RefCell<dyn Logger>>,
     compute_budget: ComputeBudget,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
@@ -111,6 +123,8 @@ pub struct ThisInvokeContext<'a> {
     blockhash: Hash,
     lamports_per_signature: u64,
     return_data: (Pubkey, Vec<u8>),
+    epoch_stakes: Option<&'a dyn EpochStakeCallback>,
+    compute_unit_profile: Option<Rc<RefCell<ComputeUnitProfile>>>,
 }
 impl<'a> ThisInvokeContext<'a> {
     #[allow(clippy::too_many_arguments)]
@@ -127,6 +141,7 @@ impl<'a> ThisInvokeContext<'a> {
         feature_set: Arc<FeatureSet>,
         blockhash: Hash,
         lamports_per_signature: u64,
+        epoch_stakes: Option<&'a dyn EpochStakeCallback>,
     ) -> Self {
         Self {
             instruction_index: 0,
@@ -136,7 +151,8 @@ impl<'a> ThisInvokeContext<'a> {
             accounts,
             programs,
             sysvars,
-            logger: ThisLogger::new_ref(log_collector),
+            logger: ThisLogger::new_ref(log_collector.clone()),
+            log_collector,
             compute_budget,
             compute_meter,
             executors,
@@ -146,9 +162,20 @@ impl<'a> ThisInvokeContext<'a> {
             blockhash,
             lamports_per_signature,
             return_data: (Pubkey::default(), Vec::new()),
+            epoch_stakes,
+            compute_unit_profile: None,
         }
     }
 
+    /// Profile the compute units consumed by the functions of the BPF programs into
+    /// `compute_unit_profile`, which slows their execution down
+    pub fn set_compute_unit_profile(
+        &mut self,
+        compute_unit_profile: Option<Rc<RefCell<ComputeUnitProfile>>>,
+    ) {
+        self.compute_unit_profile = compute_unit_profile;
+    }
+
     pub fn new_mock_with_sysvars_and_features(
         accounts: &'a [(Pubkey, Rc<RefCell<AccountSharedData>>)],
         programs: &'a [(Pubkey, ProcessInstructionWithContext)],
@@ -168,6 +195,7 @@ impl<'a> ThisInvokeContext<'a> {
             feature_set,
             Hash::default(),
             0,
+            None,
         )
     }
 
@@ -264,6 +292,11 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             program_indices.len(),
             create_keyed_accounts_unified(keyed_accounts.as_slice()),
         ));
+        if let (Some(log_collector), Some(index_of_program_id)) =
+            (&self.log_collector, program_indices.last())
+        {
+            log_collector.record_invoke(&self.accounts[*index_of_program_id].0);
+        }
         Ok(())
     }
     fn pop(&mut self) {
@@ -496,6 +529,23 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
     fn get_return_data(&self) -> (Pubkey, &[u8]) {
         (self.return_data.0, &self.return_data.1)
     }
+    fn get_epoch_total_stake(&self) -> u64 {
+        self.epoch_stakes
+            .map(|epoch_stakes| epoch_stakes.get_epoch_total_stake())
+            .unwrap_or(0)
+    }
+    fn get_epoch_vote_account_stake(&self, vote_address: &Pubkey) -> u64 {
+        self.epoch_stakes
+            .map(|epoch_stakes| epoch_stakes.get_epoch_vote_account_stake(vote_address))
+            .unwrap_or(0)
+    }
+    fn get_epoch_start_commission(&self, vote_address: &Pubkey) -> Option<u8> {
+        self.epoch_stakes
+            .and_then(|epoch_stakes| epoch_stakes.get_epoch_start_commission(vote_address))
+    }
+    fn get_compute_unit_profile(&self) -> Option<Rc<RefCell<ComputeUnitProfile>>> {
+        self.compute_unit_profile.clone()
+    }
 }
 
 // This method which has a generic parameter is outside of the InvokeContext,
@@ -621,10 +671,28 @@ pub fn with_mock_invoke_context<R, F: FnMut(&mut ThisInvokeContext) -> R>(
 }
 
 pub fn mock_process_instruction(
+    loader_id: &Pubkey,
+    program_indices: Vec<usize>,
+    instruction_data: &[u8],
+    keyed_accounts: &[(bool, bool, Pubkey, Rc<RefCell<AccountSharedData>>)],
+    process_instruction: ProcessInstructionWithContext,
+) -> Result<(), InstructionError> {
+    mock_process_instruction_with_sysvars(
+        loader_id,
+        program_indices,
+        instruction_data,
+        keyed_accounts,
+        &[],
+        process_instruction,
+    )
+}
+
+pub fn mock_process_instruction_with_sysvars(
     loader_id: &Pubkey,
     mut program_indices: Vec<usize>,
     instruction_data: &[u8],
     keyed_accounts: &[(bool, bool, Pubkey, Rc<RefCell<AccountSharedData>>)],
+    sysvars: &[(Pubkey, Vec<u8>)],
     process_instruction: ProcessInstructionWithContext,
 ) -> Result<(), InstructionError> {
     let mut preparation =
@@ -632,7 +700,12 @@ pub fn mock_process_instruction(
     let processor_account = AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id());
     program_indices.insert(0, preparation.accounts.len());
     preparation.accounts.push((*loader_id, processor_account));
-    let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+    let mut invoke_context = ThisInvokeContext::new_mock_with_sysvars_and_features(
+        &preparation.accounts,
+        &[],
+        sysvars,
+        Arc::new(FeatureSet::all_enabled()),
+    );
     invoke_context.push(
         &preparation.message,
         &preparation.message.instructions[0],
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use {solana_sdk::pubkey::Pubkey, std::cell::RefCell};
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
 
@@ -7,6 +7,7 @@ struct LogCollectorInner {
     messages: Vec<String>,
     bytes_written: usize,
     limit_warning: bool,
+    invoked_programs: Vec<Pubkey>,
 }
 
 #[derive(Default)]
@@ -28,6 +29,20 @@ impl LogCollector {
             inner.messages.push(message.to_string());
         }
     }
+
+    /// Records a program invoked by the transaction, whether or not its invoke message fits in
+    /// the logs
+    pub fn record_invoke(&self, program_id: &Pubkey) {
+        let mut inner = self.inner.borrow_mut();
+        if !inner.invoked_programs.contains(program_id) {
+            inner.invoked_programs.push(*program_id);
+        }
+    }
+
+    /// Programs invoked by the transaction, in order of first invocation
+    pub fn invoked_programs(&self) -> Vec<Pubkey> {
+        self.inner.borrow().invoked_programs.clone()
+    }
 }
 
 impl From<LogCollector> for Vec<String> {
@@ -55,4 +70,20 @@ pub(crate) mod tests {
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_invoked_programs_past_bytes_limit() {
+        let lc = LogCollector::default();
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+
+        lc.log(&"x".repeat(LOG_MESSAGES_BYTES_LIMIT));
+        lc.record_invoke(&program_id);
+        lc.record_invoke(&other_program_id);
+        lc.record_invoke(&program_id);
+
+        assert_eq!(lc.invoked_programs(), vec![program_id, other_program_id]);
+        let logs: Vec<_> = lc.into();
+        assert_eq!(logs, vec!["Log truncated".to_string()]);
+    }
 }
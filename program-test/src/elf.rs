@@ -0,0 +1,191 @@
+//! Executes the ELF of a BPF program as is, without deploying it to a bank, so that a freshly
+//! built program is unit tested without starting a `ProgramTest`
+
+use {
+    solana_program_runtime::{
+        instruction_processor::Executors,
+        invoke_context::{ThisComputeMeter, ThisInvokeContext},
+        log_collector::LogCollector,
+    },
+    solana_sdk::{
+        account::{AccountSharedData, WritableAccount},
+        bpf_loader,
+        clock::Clock,
+        compute_budget::ComputeBudget,
+        epoch_schedule::EpochSchedule,
+        feature_set::FeatureSet,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        message::Message,
+        native_loader,
+        process_instruction::InvokeContext,
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar,
+    },
+    std::{cell::RefCell, rc::Rc, sync::Arc},
+};
+
+/// Outcome of the execution of an ELF
+#[derive(Debug, PartialEq)]
+pub struct ElfExecution {
+    pub result: Result<(), InstructionError>,
+    /// The instruction accounts once executed, in the order they were given
+    pub accounts: Vec<(Pubkey, AccountSharedData)>,
+    pub logs: Vec<String>,
+    pub compute_units_consumed: u64,
+    /// The program which set the return data, and the data
+    pub return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+/// Environment an ELF is executed in, in place of the one of a bank
+pub struct ElfEnvironment {
+    pub rent: Rent,
+    pub sysvars: Vec<(Pubkey, Vec<u8>)>,
+    pub feature_set: Arc<FeatureSet>,
+    pub compute_budget: ComputeBudget,
+}
+
+impl Default for ElfEnvironment {
+    /// The default rent, clock and epoch schedule, with all the features enabled
+    fn default() -> Self {
+        let rent = Rent::default();
+        Self {
+            sysvars: vec![
+                (
+                    sysvar::clock::id(),
+                    bincode::serialize(&Clock::default()).unwrap(),
+                ),
+                (
+                    sysvar::epoch_schedule::id(),
+                    bincode::serialize(&EpochSchedule::default()).unwrap(),
+                ),
+                (sysvar::rent::id(), bincode::serialize(&rent).unwrap()),
+            ],
+            rent,
+            feature_set: Arc::new(FeatureSet::all_enabled()),
+            compute_budget: ComputeBudget::default(),
+        }
+    }
+}
+
+impl ElfEnvironment {
+    /// Executes `elf` as the program `program_id` with the bpf loader, which serializes the
+    /// instruction accounts, runs the program in the interpreter and deserializes the accounts
+    /// back. The program can't invoke other programs.
+    pub fn execute_elf(
+        &self,
+        program_id: &Pubkey,
+        elf: &[u8],
+        instruction_data: &[u8],
+        accounts: &[(AccountMeta, AccountSharedData)],
+    ) -> ElfExecution {
+        let metas = accounts.iter().map(|(meta, _)| meta.clone()).collect();
+        let message = Message::new(
+            &[Instruction::new_with_bytes(
+                *program_id,
+                instruction_data,
+                metas,
+            )],
+            None,
+        );
+        let mut program_account =
+            AccountSharedData::new(self.rent.minimum_balance(elf.len()), 0, &bpf_loader::id());
+        program_account.set_data(elf.to_vec());
+        program_account.set_executable(true);
+        let mut message_accounts: Vec<_> = message
+            .account_keys
+            .iter()
+            .map(|pubkey| {
+                let account = if pubkey == program_id {
+                    program_account.clone()
+                } else {
+                    accounts
+                        .iter()
+                        .find(|(meta, _)| meta.pubkey == *pubkey)
+                        .map(|(_, account)| account.clone())
+                        .unwrap_or_default()
+                };
+                (*pubkey, Rc::new(RefCell::new(account)))
+            })
+            .collect();
+        let program_index = message
+            .account_keys
+            .iter()
+            .position(|pubkey| pubkey == program_id)
+            .unwrap();
+        let program_indices = [message_accounts.len(), program_index];
+        message_accounts.push((
+            bpf_loader::id(),
+            AccountSharedData::new_ref(0, 0, &native_loader::id()),
+        ));
+
+        let log_collector = Rc::new(LogCollector::default());
+        let (result, compute_units_consumed, return_data) = {
+            let mut invoke_context = ThisInvokeContext::new(
+                self.rent,
+                &message_accounts,
+                &[],
+                &self.sysvars,
+                Some(log_collector.clone()),
+                self.compute_budget,
+                ThisComputeMeter::new_ref(self.compute_budget.max_units),
+                Rc::new(RefCell::new(Executors::default())),
+                None,
+                self.feature_set.clone(),
+                Hash::default(),
+                0,
+                None,
+            );
+            let instruction = &message.instructions[0];
+            let result = invoke_context
+                .push(&message, instruction, &program_indices, None)
+                .and_then(|_| {
+                    solana_bpf_loader_program::process_instruction(
+                        1,
+                        instruction_data,
+                        &mut invoke_context,
+                    )
+                })
+                .and_then(|_| invoke_context.verify(&message, instruction, &program_indices));
+            invoke_context.pop();
+            let compute_units_consumed = self
+                .compute_budget
+                .max_units
+                .saturating_sub(invoke_context.get_compute_meter().borrow().get_remaining());
+            let (return_data_program_id, return_data) = invoke_context.get_return_data();
+            let return_data =
+                (!return_data.is_empty()).then(|| (return_data_program_id, return_data.to_vec()));
+            (result, compute_units_consumed, return_data)
+        };
+
+        ElfExecution {
+            result,
+            accounts: accounts
+                .iter()
+                .map(|(meta, _)| {
+                    let (_, account) = message_accounts
+                        .iter()
+                        .find(|(pubkey, _)| *pubkey == meta.pubkey)
+                        .unwrap();
+                    (meta.pubkey, account.borrow().clone())
+                })
+                .collect(),
+            logs: Rc::try_unwrap(log_collector)
+                .map(Into::into)
+                .unwrap_or_default(),
+            compute_units_consumed,
+            return_data,
+        }
+    }
+}
+
+/// Executes `elf` as the program `program_id` in the default environment
+pub fn execute_elf(
+    program_id: &Pubkey,
+    elf: &[u8],
+    instruction_data: &[u8],
+    accounts: &[(AccountMeta, AccountSharedData)],
+) -> ElfExecution {
+    ElfEnvironment::default().execute_elf(program_id, elf, instruction_data, accounts)
+}
@@ -28,8 +28,7 @@ use {
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         genesis_config::{ClusterType, GenesisConfig},
         hash::Hash,
-        instruction::Instruction,
-        instruction::InstructionError,
+        instruction::{AccountMeta, Instruction, InstructionError},
         message::Message,
         native_token::sol_to_lamports,
         poh_config::PohConfig,
@@ -70,8 +69,11 @@ pub use solana_banks_client::BanksClient;
 // Export tokio for test clients
 pub use tokio;
 
+pub mod elf;
 pub mod programs;
 
+pub use elf::{execute_elf, ElfEnvironment, ElfExecution};
+
 #[macro_use]
 extern crate solana_bpf_loader_program;
 
@@ -235,6 +237,57 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
         }
     }
 
+    fn sol_remaining_compute_units(&self) -> u64 {
+        let invoke_context = get_invoke_context();
+        let compute_meter = invoke_context.get_compute_meter();
+        let mut compute_meter = compute_meter
+            .try_borrow_mut()
+            .map_err(|_| ACCOUNT_BORROW_FAILED)
+            .unwrap();
+        if compute_meter
+            .consume(invoke_context.get_compute_budget().syscall_base_cost)
+            .is_err()
+        {
+            panic!("Exceeded compute budget");
+        }
+        compute_meter.get_remaining()
+    }
+
+    fn sol_get_epoch_stake(&self, vote_address: *const u8) -> u64 {
+        let invoke_context = get_invoke_context();
+        if invoke_context
+            .get_compute_meter()
+            .try_borrow_mut()
+            .map_err(|_| ACCOUNT_BORROW_FAILED)
+            .unwrap()
+            .consume(invoke_context.get_compute_budget().syscall_base_cost)
+            .is_err()
+        {
+            panic!("Exceeded compute budget");
+        }
+        if vote_address.is_null() {
+            invoke_context.get_epoch_total_stake()
+        } else {
+            let vote_address = unsafe { &*(vote_address as *const Pubkey) };
+            invoke_context.get_epoch_vote_account_stake(vote_address)
+        }
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        let invoke_context = get_invoke_context();
+        if invoke_context
+            .get_compute_meter()
+            .try_borrow_mut()
+            .map_err(|_| ACCOUNT_BORROW_FAILED)
+            .unwrap()
+            .consume(invoke_context.get_compute_budget().syscall_base_cost)
+            .is_err()
+        {
+            panic!("Exceeded compute budget");
+        }
+        invoke_context.invoke_depth() as u64
+    }
+
     fn sol_invoke_signed(
         &self,
         instruction: &Instruction,
@@ -425,6 +478,7 @@ fn setup_fees(bank: Bank) -> Bank {
         &[],     // transactions
         &mut [], // loaded accounts
         &[],     // transaction execution results
+        vec![],  // transaction execution details
         0,       // tx count
         1,       // signature count
         &mut ExecuteTimings::default(),
@@ -453,6 +507,7 @@ fn setup_fees(bank: Bank) -> Bank {
 pub struct ProgramTest {
     accounts: Vec<(Pubkey, AccountSharedData)>,
     builtins: Vec<Builtin>,
+    compute_budget: Option<ComputeBudget>,
     compute_max_units: Option<u64>,
     prefer_bpf: bool,
     use_bpf_jit: bool,
@@ -466,6 +521,10 @@ impl Default for ProgramTest {
     /// used to override this preference at runtime.  `cargo test-bpf` will set `BPF_OUT_DIR`
     /// automatically.
     ///
+    /// If the `COMPUTE_BUDGET_CONFIG` environment variable is defined, transactions are executed
+    /// with the compute budget in that TOML file.  `cargo test-bpf --compute-budget-config` will
+    /// set `COMPUTE_BUDGET_CONFIG`.
+    ///
     /// BPF program shared objects and account data files are searched for in
     /// * the value of the `BPF_OUT_DIR` environment variable
     /// * the `tests/fixtures` sub-directory
@@ -479,10 +538,14 @@ impl Default for ProgramTest {
              solana_program_test=info",
         );
         let prefer_bpf = std::env::var("BPF_OUT_DIR").is_ok();
+        let compute_budget = std::env::var("COMPUTE_BUDGET_CONFIG").ok().map(|path| {
+            ComputeBudget::from_toml_file(path).unwrap_or_else(|err| panic!("{}", err))
+        });
 
         Self {
             accounts: vec![],
             builtins: vec![],
+            compute_budget,
             compute_max_units: None,
             prefer_bpf,
             use_bpf_jit: false,
@@ -513,6 +576,11 @@ impl ProgramTest {
         self.prefer_bpf = prefer_bpf;
     }
 
+    /// Override the default compute budget
+    pub fn set_compute_budget(&mut self, compute_budget: ComputeBudget) {
+        self.compute_budget = Some(compute_budget);
+    }
+
     /// Override the default maximum compute units
     pub fn set_compute_max_units(&mut self, compute_max_units: u64) {
         self.compute_max_units = Some(compute_max_units);
@@ -797,10 +865,11 @@ impl ProgramTest {
             bank.store_account(address, account);
         }
         bank.set_capitalization();
-        if let Some(max_units) = self.compute_max_units {
+        if self.compute_budget.is_some() || self.compute_max_units.is_some() {
+            let compute_budget = self.compute_budget.unwrap_or_default();
             bank.set_compute_budget(Some(ComputeBudget {
-                max_units,
-                ..ComputeBudget::default()
+                max_units: self.compute_max_units.unwrap_or(compute_budget.max_units),
+                ..compute_budget
             }));
         }
         let bank = setup_fees(bank);
@@ -1089,4 +1158,27 @@ impl ProgramTestContext {
         self.last_blockhash = bank.last_blockhash();
         Ok(())
     }
+
+    /// Executes `elf` as the program `program_id` with the rent, sysvars and features of the
+    /// working bank, without deploying it
+    pub fn execute_elf(
+        &self,
+        program_id: &Pubkey,
+        elf: &[u8],
+        instruction_data: &[u8],
+        accounts: &[(AccountMeta, AccountSharedData)],
+    ) -> ElfExecution {
+        let bank = self.bank_forks.read().unwrap().working_bank();
+        let environment = ElfEnvironment {
+            rent: bank.rent_collector().rent,
+            sysvars: [clock::id(), epoch_schedule::id(), rent::id()]
+                .iter()
+                .filter_map(|id| Some((*id, bank.get_account(id)?.data().to_vec())))
+                .collect(),
+            feature_set: bank.feature_set.clone(),
+            compute_budget: bank.compute_budget().unwrap_or_default(),
+            ..ElfEnvironment::default()
+        };
+        environment.execute_elf(program_id, elf, instruction_data, accounts)
+    }
 }
@@ -0,0 +1,60 @@
+use {
+    solana_program_test::{execute_elf, read_file, ProgramTest},
+    solana_sdk::{
+        account::AccountSharedData, instruction::AccountMeta, pubkey::Pubkey, system_program,
+    },
+    std::path::PathBuf,
+};
+
+fn noop_elf() -> Vec<u8> {
+    read_file(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../programs/bpf_loader/test_elfs/noop_aligned.so"),
+    )
+}
+
+#[test]
+fn test_execute_elf() {
+    let program_id = Pubkey::new_unique();
+    let account_address = Pubkey::new_unique();
+    let account = AccountSharedData::new(42, 8, &program_id);
+    let accounts = vec![(AccountMeta::new(account_address, false), account.clone())];
+
+    let execution = execute_elf(&program_id, &noop_elf(), &[1, 2, 3], &accounts);
+    assert_eq!(execution.result, Ok(()));
+    assert_eq!(execution.accounts, vec![(account_address, account)]);
+    assert!(execution.compute_units_consumed > 0);
+    assert_eq!(
+        execution.logs.first(),
+        Some(&format!("Program {} invoke [1]", program_id))
+    );
+    assert_eq!(
+        execution.logs.last(),
+        Some(&format!("Program {} success", program_id))
+    );
+    assert_eq!(execution.return_data, None);
+}
+
+#[tokio::test]
+async fn test_program_test_context_execute_elf() {
+    let context = ProgramTest::default().start_with_context().await;
+    let program_id = Pubkey::new_unique();
+    let account_address = Pubkey::new_unique();
+    let account = AccountSharedData::new(42, 0, &system_program::id());
+    let accounts = vec![(
+        AccountMeta::new_readonly(account_address, false),
+        account.clone(),
+    )];
+
+    // The program isn't deployed to the bank it's executed against
+    let execution = context.execute_elf(&program_id, &noop_elf(), &[], &accounts);
+    assert_eq!(execution.result, Ok(()));
+    assert_eq!(execution.accounts, vec![(account_address, account)]);
+    assert!(context
+        .banks_client
+        .clone()
+        .get_account(program_id)
+        .await
+        .unwrap()
+        .is_none());
+}
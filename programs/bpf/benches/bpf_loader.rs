@@ -1,16 +1,14 @@
-#![feature(test)]
-#![cfg(feature = "bpf_c")]
-
-extern crate test;
 #[macro_use]
 extern crate solana_bpf_loader_program;
 
 use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use solana_bpf_loader_program::{
     create_vm, serialization::serialize_parameters, syscalls::register_syscalls, BpfError,
     ThisInstructionMeter,
 };
 use solana_measure::measure::Measure;
+use solana_program_runtime::invoke_context::with_mock_invoke_context;
 use solana_rbpf::vm::{Config, Executable, InstructionMeter, SyscallRegistry};
 use solana_runtime::{
     bank::Bank,
@@ -18,7 +16,6 @@ use solana_runtime::{
     genesis_utils::{create_genesis_config, GenesisConfigInfo},
     loader_utils::load_program,
 };
-use solana_program_runtime::invoke_context::with_mock_invoke_context;
 use solana_sdk::{
     bpf_loader,
     client::SyncClient,
@@ -30,7 +27,6 @@ use solana_sdk::{
     signature::{Keypair, Signer},
 };
 use std::{env, fs::File, io::Read, mem, path::PathBuf, sync::Arc};
-use test::Bencher;
 
 /// BPF program file extension
 const PLATFORM_FILE_EXTENSION_BPF: &str = "so";
@@ -70,25 +66,23 @@ fn load_bpf_program(
 const ARMSTRONG_LIMIT: u64 = 500;
 const ARMSTRONG_EXPECTED: u64 = 5;
 
-#[bench]
-fn bench_program_create_executable(bencher: &mut Bencher) {
+fn bench_program_create_executable(c: &mut Criterion) {
     let elf = load_elf("bench_alu").unwrap();
 
-    bencher.iter(|| {
-        let _ = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
-            &elf,
-            None,
-            Config::default(),
-            SyscallRegistry::default(),
-        )
-        .unwrap();
+    c.bench_function("bench_program_create_executable", |bencher| {
+        bencher.iter(|| {
+            let _ = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
+                &elf,
+                None,
+                Config::default(),
+                SyscallRegistry::default(),
+            )
+            .unwrap();
+        });
     });
 }
 
-#[bench]
-fn bench_program_alu(bencher: &mut Bencher) {
-    let ns_per_s = 1000000000;
-    let one_million = 1000000;
+fn bench_program_alu(c: &mut Criterion) {
     let mut inner_iter = vec![];
     inner_iter
         .write_u64::<LittleEndian>(ARMSTRONG_LIMIT)
@@ -116,7 +110,6 @@ fn bench_program_alu(bencher: &mut Bencher) {
         )
         .unwrap();
 
-        println!("Interpreted:");
         assert_eq!(
             SUCCESS,
             vm.execute_program_interpreted(&mut instruction_meter)
@@ -127,21 +120,9 @@ fn bench_program_alu(bencher: &mut Bencher) {
             ARMSTRONG_EXPECTED,
             LittleEndian::read_u64(&inner_iter[mem::size_of::<u64>()..])
         );
-
-        bencher.iter(|| {
-            vm.execute_program_interpreted(&mut instruction_meter)
-                .unwrap();
-        });
         let instructions = vm.get_total_instruction_count();
-        let summary = bencher.bench(|_bencher| {}).unwrap();
         println!("  {:?} instructions", instructions);
-        println!("  {:?} ns/iter median", summary.median as u64);
-        assert!(0f64 != summary.median);
-        let mips = (instructions * (ns_per_s / summary.median as u64)) / one_million;
-        println!("  {:?} MIPS", mips);
-        println!("{{ \"type\": \"bench\", \"name\": \"bench_program_alu_interpreted_mips\", \"median\": {:?}, \"deviation\": 0 }}", mips);
 
-        println!("JIT to native:");
         assert_eq!(
             SUCCESS,
             vm.execute_program_jit(&mut instruction_meter).unwrap()
@@ -152,19 +133,23 @@ fn bench_program_alu(bencher: &mut Bencher) {
             LittleEndian::read_u64(&inner_iter[mem::size_of::<u64>()..])
         );
 
-        bencher.iter(|| vm.execute_program_jit(&mut instruction_meter).unwrap());
-        let summary = bencher.bench(|_bencher| {}).unwrap();
-        println!("  {:?} instructions", instructions);
-        println!("  {:?} ns/iter median", summary.median as u64);
-        assert!(0f64 != summary.median);
-        let mips = (instructions * (ns_per_s / summary.median as u64)) / one_million;
-        println!("  {:?} MIPS", mips);
-        println!("{{ \"type\": \"bench\", \"name\": \"bench_program_alu_jit_to_native_mips\", \"median\": {:?}, \"deviation\": 0 }}", mips);
+        // Reporting the throughput in instructions lets the results be converted to MIPS
+        let mut group = c.benchmark_group("bench_program_alu");
+        group.throughput(Throughput::Elements(instructions));
+        group.bench_function("interpreted", |bencher| {
+            bencher.iter(|| {
+                vm.execute_program_interpreted(&mut instruction_meter)
+                    .unwrap();
+            });
+        });
+        group.bench_function("jit_to_native", |bencher| {
+            bencher.iter(|| vm.execute_program_jit(&mut instruction_meter).unwrap());
+        });
+        group.finish();
     });
 }
 
-#[bench]
-fn bench_program_execute_noop(bencher: &mut Bencher) {
+fn bench_program_execute_noop(c: &mut Criterion) {
     let GenesisConfigInfo {
         genesis_config,
         mint_keypair,
@@ -190,16 +175,17 @@ fn bench_program_execute_noop(bencher: &mut Bencher) {
         .send_and_confirm_message(&[&mint_keypair], message.clone())
         .unwrap();
 
-    bencher.iter(|| {
-        bank.clear_signatures();
-        bank_client
-            .send_and_confirm_message(&[&mint_keypair], message.clone())
-            .unwrap();
+    c.bench_function("bench_program_execute_noop", |bencher| {
+        bencher.iter(|| {
+            bank.clear_signatures();
+            bank_client
+                .send_and_confirm_message(&[&mint_keypair], message.clone())
+                .unwrap();
+        });
     });
 }
 
-#[bench]
-fn bench_create_vm(bencher: &mut Bencher) {
+fn bench_create_vm(c: &mut Criterion) {
     let elf = load_elf("noop").unwrap();
     let loader_id = bpf_loader::id();
     with_mock_invoke_context(loader_id, 10000001, |invoke_context| {
@@ -229,21 +215,22 @@ fn bench_create_vm(bencher: &mut Bencher) {
         )
         .unwrap();
 
-        bencher.iter(|| {
-            let _ = create_vm(
-                &loader_id,
-                executable.as_ref(),
-                serialized.as_slice_mut(),
-                invoke_context,
-                &account_lengths,
-            )
-            .unwrap();
+        c.bench_function("bench_create_vm", |bencher| {
+            bencher.iter(|| {
+                let _ = create_vm(
+                    &loader_id,
+                    executable.as_ref(),
+                    serialized.as_slice_mut(),
+                    invoke_context,
+                    &account_lengths,
+                )
+                .unwrap();
+            });
         });
     });
 }
 
-#[bench]
-fn bench_instruction_count_tuner(_bencher: &mut Bencher) {
+fn bench_instruction_count_tuner(_c: &mut Criterion) {
     let elf = load_elf("tuner").unwrap();
     let loader_id = bpf_loader::id();
     with_mock_invoke_context(loader_id, 10000001, |invoke_context| {
@@ -299,3 +286,13 @@ fn bench_instruction_count_tuner(_bencher: &mut Bencher) {
         );
     });
 }
+
+criterion_group!(
+    benches,
+    bench_program_create_executable,
+    bench_program_alu,
+    bench_program_execute_noop,
+    bench_create_vm,
+    bench_instruction_count_tuner
+);
+criterion_main!(benches);
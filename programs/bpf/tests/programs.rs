@@ -18,6 +18,9 @@ use solana_bpf_rust_invoke::instructions::*;
 use solana_bpf_rust_realloc::instructions::*;
 use solana_bpf_rust_realloc_invoke::instructions::*;
 use solana_cli_output::display::println_transaction;
+use solana_program_runtime::invoke_context::{
+    prepare_mock_invoke_context, with_mock_invoke_context, ThisInvokeContext,
+};
 use solana_rbpf::{
     static_analysis::Analysis,
     vm::{Config, Executable, Tracer},
@@ -31,7 +34,6 @@ use solana_runtime::{
         upgrade_program,
     },
 };
-use solana_program_runtime::invoke_context::with_mock_invoke_context;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
     account_utils::StateMut,
@@ -42,7 +44,7 @@ use solana_sdk::{
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
     loader_instruction,
-    message::{Message, SanitizedMessage},
+    message::{MappedAddresses, Message, SanitizedMessage},
     process_instruction::InvokeContext,
     pubkey::Pubkey,
     signature::{keypair_from_seed, Keypair, Signer},
@@ -56,7 +58,13 @@ use solana_transaction_status::{
     TransactionStatusMeta, TransactionWithStatusMeta, UiTransactionEncoding,
 };
 use std::{
-    collections::HashMap, convert::TryFrom, env, fs::File, io::Read, path::PathBuf, str::FromStr,
+    collections::HashMap,
+    convert::TryFrom,
+    env,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 
@@ -289,6 +297,117 @@ fn run_program(name: &str) -> u64 {
     })
 }
 
+/// Directory holding the golden instruction traces
+const GOLDEN_TRACE_DIR: &str = "tests/traces";
+
+/// Set to re-record the golden instruction traces instead of comparing against them
+const UPDATE_GOLDEN_TRACES_ENV: &str = "BPF_UPDATE_GOLDEN_TRACES";
+
+/// Runs a program in the interpreter and returns its instruction trace, listing the
+/// registers and the disassembled instruction at every step.
+///
+/// Unlike `run_program()` the accounts are created with fixed keys, so that the trace
+/// only depends on the program and the VM, not on the order the tests ran in.
+fn trace_program(name: &str) -> String {
+    let data = read_bpf_program(name);
+    let loader_id = bpf_loader::id();
+    let program_id = Pubkey::new_from_array([1; 32]);
+    let program_indices = vec![0, 1];
+    let keyed_accounts = [
+        (
+            false,
+            false,
+            loader_id,
+            AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id()),
+        ),
+        (
+            false,
+            false,
+            program_id,
+            AccountSharedData::new_ref(1, 0, &loader_id),
+        ),
+        (
+            false,
+            false,
+            Pubkey::new_from_array([2; 32]),
+            AccountSharedData::new_ref(2, 0, &Pubkey::new_from_array([3; 32])),
+        ),
+    ];
+    let preparation = prepare_mock_invoke_context(&program_indices, &[], &keyed_accounts);
+    let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+    invoke_context
+        .push(
+            &preparation.message,
+            &preparation.message.instructions[0],
+            &program_indices,
+            Some(&preparation.account_indices),
+        )
+        .unwrap();
+
+    let keyed_accounts = invoke_context.get_keyed_accounts().unwrap();
+    let (mut parameter_bytes, account_lengths) =
+        serialize_parameters(&loader_id, &program_id, &keyed_accounts[2..], &[]).unwrap();
+    let compute_meter = invoke_context.get_compute_meter();
+    let mut instruction_meter = ThisInstructionMeter { compute_meter };
+    let config = Config {
+        enable_instruction_tracing: true,
+        ..Config::default()
+    };
+    let executable = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
+        &data,
+        None,
+        config,
+        register_syscalls(&mut invoke_context).unwrap(),
+    )
+    .unwrap();
+    let mut vm = create_vm(
+        &loader_id,
+        executable.as_ref(),
+        parameter_bytes.as_slice_mut(),
+        &mut invoke_context,
+        &account_lengths,
+    )
+    .unwrap();
+    // The trace is recorded for failing programs as well
+    let _ = vm.execute_program_interpreted(&mut instruction_meter);
+
+    let analysis = Analysis::from_executable(executable.as_ref());
+    let mut trace_buffer = Vec::<u8>::new();
+    vm.get_tracer().write(&mut trace_buffer, &analysis).unwrap();
+    String::from_utf8(trace_buffer).unwrap()
+}
+
+/// Compares a trace against its golden file, returning a description of the first
+/// difference.  The actual trace is written next to the golden file for inspection.
+fn compare_golden_trace(golden_path: &Path, trace: &str) -> Result<(), String> {
+    let golden = fs::read_to_string(golden_path)
+        .map_err(|err| format!("failed to read {}: {}", golden_path.display(), err))?;
+    if golden == trace {
+        return Ok(());
+    }
+    let actual_path = golden_path.with_extension("trace.actual");
+    fs::write(&actual_path, trace).unwrap();
+
+    let mut golden_lines = golden.lines();
+    let mut trace_lines = trace.lines();
+    let mut line = 1;
+    loop {
+        match (golden_lines.next(), trace_lines.next()) {
+            (Some(expected), Some(actual)) if expected == actual => line += 1,
+            (expected, actual) => {
+                return Err(format!(
+                    "trace differs from {} at line {}, actual trace written to {}\n  expected: {}\n  actual:   {}",
+                    golden_path.display(),
+                    line,
+                    actual_path.display(),
+                    expected.unwrap_or("<end of trace>"),
+                    actual.unwrap_or("<end of trace>"),
+                ));
+            }
+        }
+    }
+}
+
 fn process_transaction_and_record_inner(
     bank: &Bank,
     tx: Transaction,
@@ -296,8 +415,8 @@ fn process_transaction_and_record_inner(
     let signature = tx.signatures.get(0).unwrap().clone();
     let txs = vec![tx];
     let tx_batch = bank.prepare_batch_for_tests(txs);
-    let (mut results, _, mut inner_instructions, _transaction_logs) = bank
-        .load_execute_and_commit_transactions(
+    let (mut results, _, mut inner_instructions, _transaction_logs, _compute_budget_exceeded) =
+        bank.load_execute_and_commit_transactions(
             &tx_batch,
             MAX_PROCESSING_AGE,
             false,
@@ -324,7 +443,9 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
     let tx_pre_token_balances = collect_token_balances(&bank, &batch, &mut mint_decimals);
     let (
         TransactionResults {
-            execution_results, ..
+            execution_results,
+            execution_details,
+            ..
         },
         TransactionBalancesSet {
             pre_balances,
@@ -333,6 +454,7 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
         },
         inner_instructions,
         transaction_logs,
+        compute_budget_exceeded,
     ) = bank.load_execute_and_commit_transactions(
         &batch,
         std::usize::MAX,
@@ -346,23 +468,27 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
     izip!(
         txs.iter(),
         execution_results.into_iter(),
+        execution_details.into_iter(),
         inner_instructions.into_iter(),
         pre_balances.into_iter(),
         post_balances.into_iter(),
         tx_pre_token_balances.into_iter(),
         tx_post_token_balances.into_iter(),
         transaction_logs.into_iter(),
+        compute_budget_exceeded.into_iter(),
     )
     .map(
         |(
             tx,
             (execute_result, nonce_rollback),
+            execution_details,
             inner_instructions,
             pre_balances,
             post_balances,
             pre_token_balances,
             post_token_balances,
             log_messages,
+            compute_budget_exceeded,
         )| {
             let lamports_per_signature = nonce_rollback
                 .map(|nonce_rollback| nonce_rollback.lamports_per_signature())
@@ -370,9 +496,10 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
                     bank.get_lamports_per_signature_for_blockhash(&tx.message().recent_blockhash)
                 })
                 .expect("lamports_per_signature must exist");
-            let fee = Bank::get_fee_for_message_with_lamports_per_signature(
+            let fee = Bank::calculate_fee_with_feature_set(
                 &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
                 lamports_per_signature,
+                &bank.feature_set,
             );
 
             let inner_instructions = inner_instructions.map(|inner_instructions| {
@@ -397,6 +524,11 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
                 inner_instructions,
                 log_messages,
                 rewards: None,
+                loaded_addresses: MappedAddresses::default(),
+                compute_budget_exceeded,
+                compute_units_consumed: Some(execution_details.executed_units),
+                fee_details: execution_details.fee_details,
+                loaded_accounts_data_size: Some(execution_details.loaded_accounts_data_size as u64),
             };
 
             ConfirmedTransaction {
@@ -1335,7 +1467,8 @@ fn test_program_bpf_compute_budget() {
     let mut bank = Bank::new_for_tests(&genesis_config);
     let (name, id, entrypoint) = solana_bpf_loader_program!();
     bank.add_builtin(&name, &id, entrypoint);
-    let bank_client = BankClient::new(bank);
+    let bank = Arc::new(bank);
+    let bank_client = BankClient::new_shared(&bank);
     let program_id = load_bpf_program(
         &bank_client,
         &bpf_loader::id(),
@@ -1349,11 +1482,26 @@ fn test_program_bpf_compute_budget() {
         ],
         Some(&mint_keypair.pubkey()),
     );
-    let result = bank_client.send_and_confirm_message(&[&mint_keypair], message);
+    let result = bank_client.send_and_confirm_message(&[&mint_keypair], message.clone());
     assert_eq!(
         result.unwrap_err().unwrap(),
         TransactionError::InstructionError(1, InstructionError::ProgramFailedToComplete),
     );
+
+    // The program fails to complete rather than reporting the exceeded budget, the exhausted
+    // compute meter is what identifies it
+    let tx = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+    let simulation =
+        bank.simulate_transaction(SanitizedTransaction::from_transaction_for_tests(tx));
+    let compute_budget_exceeded = simulation.compute_budget_exceeded.unwrap();
+    assert_eq!(compute_budget_exceeded.instruction_index, 1);
+    assert_eq!(compute_budget_exceeded.program_id, program_id);
+    assert_eq!(compute_budget_exceeded.units_limit, 1);
+    assert_eq!(compute_budget_exceeded.units_consumed.len(), 2);
+    assert!(simulation.logs.contains(&format!(
+        "Program {} exceeded the compute budget of 1 units in instruction 1",
+        program_id
+    )));
 }
 
 #[test]
@@ -1420,6 +1568,71 @@ fn assert_instruction_count() {
     assert!(passed);
 }
 
+#[test]
+fn assert_golden_traces() {
+    solana_logger::setup();
+
+    let mut programs = Vec::new();
+    #[cfg(feature = "bpf_c")]
+    {
+        programs.extend_from_slice(&[
+            "bpf_to_bpf",
+            "multiple_static",
+            "noop",
+            "noop++",
+            "relative_call",
+            "sanity",
+            "sanity++",
+            "struct_pass",
+            "struct_ret",
+        ]);
+    }
+    #[cfg(feature = "bpf_rust")]
+    {
+        programs.extend_from_slice(&[
+            "solana_bpf_rust_128bit",
+            "solana_bpf_rust_dep_crate",
+            "solana_bpf_rust_iter",
+            "solana_bpf_rust_many_args",
+            "solana_bpf_rust_noop",
+            "solana_bpf_rust_param_passing",
+        ]);
+    }
+
+    let update = env::var_os(UPDATE_GOLDEN_TRACES_ENV).is_some();
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_TRACE_DIR);
+    if update {
+        fs::create_dir_all(&golden_dir).unwrap();
+    }
+
+    let mut failures = Vec::new();
+    for program in programs.iter() {
+        let trace = trace_program(program);
+        let golden_path = golden_dir.join(format!("{}.trace", program));
+        if update {
+            println!("Recording golden trace: {}", golden_path.display());
+            fs::write(&golden_path, trace).unwrap();
+        } else if !golden_path.exists() {
+            failures.push(format!(
+                "no golden trace for {:?} at {}, run with {}=1 to record one",
+                program,
+                golden_path.display(),
+                UPDATE_GOLDEN_TRACES_ENV
+            ));
+        } else if let Err(err) = compare_golden_trace(&golden_path, &trace) {
+            failures.push(err);
+        }
+    }
+    for failure in failures.iter() {
+        println!("{}", failure);
+    }
+    assert!(
+        failures.is_empty(),
+        "instruction traces changed or are missing, if intended re-record them with {}=1",
+        UPDATE_GOLDEN_TRACES_ENV
+    );
+}
+
 #[cfg(any(feature = "bpf_rust"))]
 #[test]
 fn test_program_bpf_instruction_introspection() {
@@ -0,0 +1,145 @@
+//! Process-wide cache of the verified executables of BPF programs
+//!
+//! The executors are shared by all the banks, keyed by the hash of their program data and
+//! whether they are JIT compiled, so that loading a program again skips its verification and
+//! compilation. The executables are JIT compiled on a thread pool in the background, the
+//! executors run in the interpreter until their executable is compiled. Deployments always
+//! compile synchronously, so that whether a deployment succeeds doesn't depend on the node.
+
+use crate::{BpfError, BpfExecutor, ThisInstructionMeter};
+use lazy_static::lazy_static;
+use log::warn;
+use lru::LruCache;
+use solana_rbpf::vm::{Config, Executable, SyscallRegistry};
+use solana_sdk::hash::Hash;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Default number of executables cached
+pub const DEFAULT_EXECUTABLE_CACHE_CAPACITY: usize = 512;
+/// Default number of threads JIT compiling the executables
+pub const DEFAULT_JIT_COMPILER_THREADS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutableCacheConfig {
+    /// Executables cached, the least recently used ones are evicted first, none if zero
+    pub capacity: usize,
+    /// Threads JIT compiling the executables in the background, the executables are compiled
+    /// before their first use if zero
+    pub jit_compiler_threads: usize,
+}
+
+impl Default for ExecutableCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_EXECUTABLE_CACHE_CAPACITY,
+            jit_compiler_threads: DEFAULT_JIT_COMPILER_THREADS,
+        }
+    }
+}
+
+lazy_static! {
+    static ref EXECUTABLE_CACHE: RwLock<Arc<ExecutableCache>> = RwLock::new(Arc::new(
+        ExecutableCache::new(ExecutableCacheConfig::default())
+    ));
+}
+
+/// Replaces the process-wide cache with an empty one of this configuration
+pub fn configure(config: ExecutableCacheConfig) {
+    *EXECUTABLE_CACHE.write().unwrap() = Arc::new(ExecutableCache::new(config));
+}
+
+pub(crate) fn get() -> Arc<ExecutableCache> {
+    EXECUTABLE_CACHE.read().unwrap().clone()
+}
+
+pub(crate) struct ExecutableCache {
+    config: ExecutableCacheConfig,
+    executors: Mutex<LruCache<(Hash, bool), Arc<BpfExecutor>>>,
+    jit_compiler: Option<rayon::ThreadPool>,
+}
+
+impl ExecutableCache {
+    fn new(config: ExecutableCacheConfig) -> Self {
+        let jit_compiler = (config.jit_compiler_threads > 0).then(|| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(config.jit_compiler_threads)
+                .thread_name(|i| format!("solana-bpf-jit-{:02}", i))
+                .build()
+                .unwrap()
+        });
+        Self {
+            config,
+            executors: Mutex::new(LruCache::new(config.capacity)),
+            jit_compiler,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config.capacity > 0
+    }
+
+    /// Returns the executor of the program data, if it was loaded with the same JIT setting,
+    /// configuration and syscalls
+    pub(crate) fn get(
+        &self,
+        program_data_hash: &Hash,
+        use_jit: bool,
+        config: &Config,
+        syscall_registry: &SyscallRegistry,
+    ) -> Option<Arc<BpfExecutor>> {
+        let executor = self
+            .executors
+            .lock()
+            .unwrap()
+            .get(&(*program_data_hash, use_jit))?
+            .clone();
+        let executable = executor.executable();
+        (executable.get_config() == config && executable.get_syscall_registry() == syscall_registry)
+            .then(|| executor)
+    }
+
+    pub(crate) fn insert(
+        &self,
+        program_data_hash: Hash,
+        use_jit: bool,
+        executor: Arc<BpfExecutor>,
+    ) {
+        if self.is_enabled() {
+            self.executors
+                .lock()
+                .unwrap()
+                .put((program_data_hash, use_jit), executor);
+        }
+    }
+
+    pub(crate) fn compiles_in_background(&self) -> bool {
+        self.jit_compiler.is_some()
+    }
+
+    /// Loads the program again and JIT compiles it in the background, then hands the compiled
+    /// executable to the executor
+    pub(crate) fn jit_compile_in_background(
+        &self,
+        executor: Arc<BpfExecutor>,
+        elf_bytes: Vec<u8>,
+        syscall_registry: SyscallRegistry,
+    ) {
+        if let Some(jit_compiler) = &self.jit_compiler {
+            jit_compiler.spawn(move || {
+                let config = *executor.executable().get_config();
+                // The executor's executable was verified already
+                let executable = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
+                    &elf_bytes,
+                    None,
+                    config,
+                    syscall_registry,
+                )
+                .and_then(|mut executable| executable.jit_compile().map(|()| executable));
+                match executable {
+                    Ok(executable) => executor.set_executable(executable),
+                    Err(err) => warn!("Failed to compile program in the background: {:?}", err),
+                }
+            });
+        }
+    }
+}
@@ -2,50 +2,69 @@
 pub mod alloc;
 pub mod allocator_bump;
 pub mod deprecated;
+pub mod executable_cache;
+pub mod profiler;
 pub mod serialization;
+pub mod stack_metadata;
 pub mod syscalls;
 pub mod upgradeable;
 pub mod upgradeable_with_jit;
 pub mod with_jit;
 
 use crate::{
-    serialization::{deserialize_parameters, serialize_parameters},
+    profiler::{attribute_compute_units, SyscallCostRecorder},
+    serialization::{describe_serialized_offset, deserialize_parameters, serialize_parameters},
+    stack_metadata::StackMetadata,
     syscalls::SyscallError,
 };
 use log::{log_enabled, trace, Level::Trace};
 use solana_measure::measure::Measure;
-use solana_program_runtime::instruction_processor::InstructionProcessor;
+use solana_program_runtime::{
+    instruction_processor::{Executors, InstructionProcessor},
+    invoke_context::{ThisComputeMeter, ThisInvokeContext},
+};
 use solana_rbpf::{
     aligned_memory::AlignedMemory,
-    ebpf::HOST_ALIGN,
+    ebpf::{HOST_ALIGN, MM_HEAP_START, MM_INPUT_START, MM_PROGRAM_START, MM_STACK_START},
     error::{EbpfError, UserDefinedError},
+    memory_region::AccessType,
     static_analysis::Analysis,
     verifier::{self, VerifierError},
     vm::{Config, EbpfVm, Executable, InstructionMeter},
 };
 use solana_sdk::{
-    account::{ReadableAccount, WritableAccount},
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
     account_utils::State,
     bpf_loader, bpf_loader_deprecated,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::Clock,
+    compute_budget::ComputeBudget,
     entrypoint::{HEAP_LENGTH, SUCCESS},
     feature_set::{
-        do_support_realloc, reduce_required_deploy_balance, requestable_heap_size,
-        stop_verify_mul64_imm_nonzero,
+        do_support_realloc, enable_bpf_loader_set_authority_checked_ix,
+        program_stack_metadata_enabled, reduce_required_deploy_balance, requestable_heap_size,
+        stop_verify_mul64_imm_nonzero, FeatureSet,
     },
+    hash::{hash, Hash},
     ic_logger_msg, ic_msg,
-    instruction::{AccountMeta, InstructionError},
+    instruction::{AccountMeta, Instruction, InstructionError},
     keyed_account::{from_keyed_account, keyed_account_at_index, KeyedAccount},
     loader_instruction::LoaderInstruction,
     loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    message::Message,
+    native_loader,
     process_instruction::{stable_log, ComputeMeter, Executor, InvokeContext, Logger},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction::{self, MAX_PERMITTED_DATA_LENGTH},
 };
-use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    rc::Rc,
+    sync::{Arc, RwLock},
+};
 use thiserror::Error;
 
 solana_sdk::declare_builtin!(
@@ -69,46 +88,169 @@ fn map_ebpf_error(invoke_context: &dyn InvokeContext, e: EbpfError<BpfError>) ->
     InstructionError::InvalidAccountData
 }
 
+/// Resolves the address of an access violation to the memory region it falls in and, for the
+/// input region, to the serialized account field or instruction data
+fn describe_access_violation(
+    invoke_context: &dyn InvokeContext,
+    loader_id: &Pubkey,
+    first_instruction_account: usize,
+    instruction_data: &[u8],
+    config: &Config,
+    vm_addr: u64,
+) -> Option<String> {
+    let region_start = vm_addr & !(MM_PROGRAM_START - 1);
+    let offset = (vm_addr - region_start) as usize;
+    match region_start {
+        MM_PROGRAM_START => Some(format!("program at offset {}", offset)),
+        MM_STACK_START => Some(format!(
+            "stack frame {} at offset {} of {} bytes",
+            offset / config.stack_frame_size,
+            offset % config.stack_frame_size,
+            config.stack_frame_size,
+        )),
+        MM_HEAP_START => Some(format!(
+            "heap at offset {} of {} bytes",
+            offset,
+            invoke_context
+                .get_compute_budget()
+                .heap_size
+                .unwrap_or(HEAP_LENGTH),
+        )),
+        MM_INPUT_START => {
+            let keyed_accounts = invoke_context.get_keyed_accounts().ok()?;
+            describe_serialized_offset(
+                loader_id,
+                keyed_accounts.get(first_instruction_account + 1..)?,
+                instruction_data.len(),
+                offset,
+            )
+            .ok()
+        }
+        _ => None,
+    }
+}
+
+/// Loads and verifies the program, deployments JIT compile it before returning so that a
+/// program failing to compile is rejected regardless of the background compiler
 pub fn create_executor(
     programdata_account_index: usize,
     programdata_offset: usize,
     invoke_context: &mut dyn InvokeContext,
     use_jit: bool,
+    deploying: bool,
 ) -> Result<Arc<BpfExecutor>, InstructionError> {
     let syscall_registry = syscalls::register_syscalls(invoke_context).map_err(|e| {
         ic_msg!(invoke_context, "Failed to register syscalls: {}", e);
         InstructionError::ProgramEnvironmentSetupFailure
     })?;
     let compute_budget = invoke_context.get_compute_budget();
-    let config = Config {
+    let mut config = Config {
         max_call_depth: compute_budget.max_call_depth,
         stack_frame_size: compute_budget.stack_frame_size,
-        enable_instruction_tracing: log_enabled!(Trace),
+        enable_instruction_tracing: log_enabled!(Trace)
+            || invoke_context.get_compute_unit_profile().is_some(),
         verify_mul64_imm_nonzero: !invoke_context
             .is_feature_active(&stop_verify_mul64_imm_nonzero::id()), // TODO: Feature gate and then remove me
         ..Config::default()
     };
-    let mut executable = {
+    let is_program_stack_metadata_enabled =
+        invoke_context.is_feature_active(&program_stack_metadata_enabled::id());
+    let elf_bytes = {
         let keyed_accounts = invoke_context.get_keyed_accounts()?;
         let programdata = keyed_account_at_index(keyed_accounts, programdata_account_index)?;
-        <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
-            &programdata.try_account_ref()?.data()[programdata_offset..],
-            None,
-            config,
-            syscall_registry,
-        )
+        let programdata = programdata.try_account_ref()?;
+        programdata.data()[programdata_offset..].to_vec()
+    };
+    if is_program_stack_metadata_enabled {
+        let stack_metadata =
+            StackMetadata::from_elf(&elf_bytes, config.stack_frame_size).map_err(|err| {
+                ic_msg!(invoke_context, "Invalid stack metadata: {}", err);
+                InstructionError::InvalidAccountData
+            })?;
+        if let Some(stack_metadata) = stack_metadata {
+            config.stack_frame_size = stack_metadata.stack_frame_size;
+            config.max_call_depth = stack_metadata.max_call_depth;
+        }
+    }
+    let executable_cache = executable_cache::get();
+    let elf_hash = hash(&elf_bytes);
+    if let Some(executor) = executable_cache
+        .get(&elf_hash, use_jit, &config, &syscall_registry)
+        .filter(|executor| !deploying || !use_jit || executor.is_jit_compiled())
+    {
+        return Ok(executor);
     }
+    let jit_in_background = use_jit && !deploying && executable_cache.compiles_in_background();
+    // The background compiler loads its own copy of the executable
+    let background_syscall_registry = if jit_in_background {
+        Some(syscalls::register_syscalls(invoke_context).map_err(|e| {
+            ic_msg!(invoke_context, "Failed to register syscalls: {}", e);
+            InstructionError::ProgramEnvironmentSetupFailure
+        })?)
+    } else {
+        None
+    };
+    let mut executable = <dyn Executable<BpfError, ThisInstructionMeter>>::from_elf(
+        &elf_bytes,
+        None,
+        config,
+        syscall_registry,
+    )
     .map_err(|e| map_ebpf_error(invoke_context, e))?;
     let text_bytes = executable.get_text_bytes().1;
     verifier::check(text_bytes, &config)
         .map_err(|e| map_ebpf_error(invoke_context, EbpfError::UserError(e.into())))?;
-    if use_jit {
+    if use_jit && !jit_in_background {
         if let Err(err) = executable.jit_compile() {
             ic_msg!(invoke_context, "Failed to compile program {:?}", err);
             return Err(InstructionError::ProgramFailedToCompile);
         }
     }
-    Ok(Arc::new(BpfExecutor { executable }))
+    let executor = Arc::new(BpfExecutor::new(executable));
+    if let Some(syscall_registry) = background_syscall_registry {
+        executable_cache.jit_compile_in_background(executor.clone(), elf_bytes, syscall_registry);
+    }
+    // Traced executors would evict the untraced ones of the same program
+    if !config.enable_instruction_tracing {
+        executable_cache.insert(elf_hash, use_jit, executor.clone());
+    }
+    Ok(executor)
+}
+
+/// Loads and verifies the program `elf` as its deployment under `feature_set` would, for programs
+/// installed without going through the loader instructions
+pub fn verify_program(elf: &[u8], feature_set: Arc<FeatureSet>) -> Result<(), InstructionError> {
+    let program_id = Pubkey::new_unique();
+    let mut programdata = AccountSharedData::new(0, elf.len(), &bpf_loader_upgradeable::id());
+    programdata.data_as_mut_slice().copy_from_slice(elf);
+    let accounts = [
+        (
+            bpf_loader_upgradeable::id(),
+            AccountSharedData::new_ref(0, 0, &native_loader::id()),
+        ),
+        (program_id, Rc::new(RefCell::new(programdata))),
+    ];
+    let message = Message::new(
+        &[Instruction::new_with_bytes(program_id, &[], vec![])],
+        None,
+    );
+    let mut invoke_context = ThisInvokeContext::new(
+        Rent::default(),
+        &accounts,
+        &[],
+        &[],
+        None,
+        ComputeBudget::default(),
+        ThisComputeMeter::new_ref(std::i64::MAX as u64),
+        Rc::new(RefCell::new(Executors::default())),
+        None,
+        feature_set,
+        Hash::default(),
+        0,
+        None,
+    );
+    invoke_context.push(&message, &message.instructions[0], &[0, 1], Some(&[1]))?;
+    create_executor(1, 0, &mut invoke_context, false, true).map(|_| ())
 }
 
 fn write_program_data(
@@ -157,6 +299,16 @@ pub fn create_vm<'a>(
             .borrow_mut()
             .consume((heap_size as u64 / (32 * 1024)).saturating_sub(1) * compute_budget.heap_cost);
     }
+    // Programs that declared a larger stack pay for the memory above the default
+    let config = program.get_config();
+    let stack_size = config.stack_frame_size * config.max_call_depth;
+    let default_stack_size = compute_budget.stack_frame_size * compute_budget.max_call_depth;
+    if stack_size > default_stack_size {
+        let _ = invoke_context.get_compute_meter().borrow_mut().consume(
+            ((stack_size - default_stack_size) as u64 + 32 * 1024 - 1) / (32 * 1024)
+                * compute_budget.stack_cost,
+        );
+    }
     let mut heap =
         AlignedMemory::new_with_size(compute_budget.heap_size.unwrap_or(HEAP_LENGTH), HOST_ALIGN);
     let mut vm = EbpfVm::new(program, heap.as_slice_mut(), parameter_bytes)?;
@@ -263,7 +415,13 @@ fn process_instruction_common(
             0
         };
 
-        let executor = match invoke_context.get_executor(program_id) {
+        // Profiled programs are traced by the interpreter, which the cached executors don't do
+        let is_profiling = invoke_context.get_compute_unit_profile().is_some();
+        let use_jit = use_jit && !is_profiling;
+        let executor = match invoke_context
+            .get_executor(program_id)
+            .filter(|_| !is_profiling)
+        {
             Some(executor) => executor,
             None => {
                 let executor = create_executor(
@@ -271,9 +429,12 @@ fn process_instruction_common(
                     program_data_offset,
                     invoke_context,
                     use_jit,
+                    false,
                 )?;
-                let program_id = invoke_context.get_caller()?;
-                invoke_context.add_executor(program_id, executor.clone());
+                if !is_profiling {
+                    let program_id = invoke_context.get_caller()?;
+                    invoke_context.add_executor(program_id, executor.clone());
+                }
                 executor
             }
         };
@@ -475,6 +636,7 @@ fn process_loader_upgradeable_instruction(
                 buffer_data_offset,
                 invoke_context,
                 use_jit,
+                true,
             )?;
             invoke_context.add_executor(&new_program_id, executor);
 
@@ -619,6 +781,7 @@ fn process_loader_upgradeable_instruction(
                 buffer_data_offset,
                 invoke_context,
                 use_jit,
+                true,
             )?;
             invoke_context.add_executor(&new_program_id, executor);
 
@@ -714,6 +877,73 @@ fn process_loader_upgradeable_instruction(
 
             ic_logger_msg!(logger, "New authority {:?}", new_authority);
         }
+        UpgradeableLoaderInstruction::SetAuthorityChecked => {
+            if !invoke_context.is_feature_active(&enable_bpf_loader_set_authority_checked_ix::id())
+            {
+                return Err(InstructionError::InvalidInstructionData);
+            }
+
+            let account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+            let present_authority =
+                keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+            let new_authority =
+                keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+
+            match account.state()? {
+                UpgradeableLoaderState::Buffer { authority_address } => {
+                    if authority_address.is_none() {
+                        ic_logger_msg!(logger, "Buffer is immutable");
+                        return Err(InstructionError::Immutable);
+                    }
+                    if authority_address != Some(*present_authority.unsigned_key()) {
+                        ic_logger_msg!(logger, "Incorrect buffer authority provided");
+                        return Err(InstructionError::IncorrectAuthority);
+                    }
+                    if present_authority.signer_key().is_none() {
+                        ic_logger_msg!(logger, "Buffer authority did not sign");
+                        return Err(InstructionError::MissingRequiredSignature);
+                    }
+                    if new_authority.signer_key().is_none() {
+                        ic_logger_msg!(logger, "New authority did not sign");
+                        return Err(InstructionError::MissingRequiredSignature);
+                    }
+                    account.set_state(&UpgradeableLoaderState::Buffer {
+                        authority_address: Some(*new_authority.unsigned_key()),
+                    })?;
+                }
+                UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address,
+                } => {
+                    if upgrade_authority_address.is_none() {
+                        ic_logger_msg!(logger, "Program not upgradeable");
+                        return Err(InstructionError::Immutable);
+                    }
+                    if upgrade_authority_address != Some(*present_authority.unsigned_key()) {
+                        ic_logger_msg!(logger, "Incorrect upgrade authority provided");
+                        return Err(InstructionError::IncorrectAuthority);
+                    }
+                    if present_authority.signer_key().is_none() {
+                        ic_logger_msg!(logger, "Upgrade authority did not sign");
+                        return Err(InstructionError::MissingRequiredSignature);
+                    }
+                    if new_authority.signer_key().is_none() {
+                        ic_logger_msg!(logger, "New authority did not sign");
+                        return Err(InstructionError::MissingRequiredSignature);
+                    }
+                    account.set_state(&UpgradeableLoaderState::ProgramData {
+                        slot,
+                        upgrade_authority_address: Some(*new_authority.unsigned_key()),
+                    })?;
+                }
+                _ => {
+                    ic_logger_msg!(logger, "Account does not support authorities");
+                    return Err(InstructionError::InvalidArgument);
+                }
+            }
+
+            ic_logger_msg!(logger, "New authority {:?}", new_authority.unsigned_key());
+        }
         UpgradeableLoaderInstruction::Close => {
             let close_account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
             let recipient_account =
@@ -872,7 +1102,8 @@ fn process_loader_instruction(
                 return Err(InstructionError::MissingRequiredSignature);
             }
 
-            let executor = create_executor(first_instruction_account, 0, invoke_context, use_jit)?;
+            let executor =
+                create_executor(first_instruction_account, 0, invoke_context, use_jit, true)?;
             let keyed_accounts = invoke_context.get_keyed_accounts()?;
             let program = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
             invoke_context.add_executor(program.unsigned_key(), executor);
@@ -910,7 +1141,29 @@ impl InstructionMeter for ThisInstructionMeter {
 
 /// BPF Loader's Executor implementation
 pub struct BpfExecutor {
-    executable: Box<dyn Executable<BpfError, ThisInstructionMeter>>,
+    /// Replaced by its JIT compiled counterpart once compiled in the background
+    executable: RwLock<Arc<dyn Executable<BpfError, ThisInstructionMeter>>>,
+}
+
+impl BpfExecutor {
+    fn new(executable: Box<dyn Executable<BpfError, ThisInstructionMeter>>) -> Self {
+        Self {
+            executable: RwLock::new(Arc::from(executable)),
+        }
+    }
+
+    fn executable(&self) -> Arc<dyn Executable<BpfError, ThisInstructionMeter>> {
+        self.executable.read().unwrap().clone()
+    }
+
+    fn set_executable(&self, executable: Box<dyn Executable<BpfError, ThisInstructionMeter>>) {
+        *self.executable.write().unwrap() = Arc::from(executable);
+    }
+
+    /// Whether the executable is JIT compiled
+    pub fn is_jit_compiled(&self) -> bool {
+        self.executable().get_compiled_program().is_some()
+    }
 }
 
 // Well, implement Debug for solana_rbpf::vm::Executable in solana-rbpf...
@@ -936,6 +1189,7 @@ impl Executor for BpfExecutor {
         let program = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
         let loader_id = &program.owner()?;
         let program_id = invoke_context.get_caller()?;
+        let executable = self.executable();
         let (mut parameter_bytes, account_lengths) = serialize_parameters(
             loader_id,
             program_id,
@@ -948,9 +1202,12 @@ impl Executor for BpfExecutor {
         {
             let program_id = &invoke_context.get_caller()?.clone();
             let compute_meter = invoke_context.get_compute_meter();
+            let compute_unit_profile = invoke_context
+                .get_compute_unit_profile()
+                .filter(|_| executable.get_config().enable_instruction_tracing);
             let mut vm = match create_vm(
                 loader_id,
-                self.executable.as_ref(),
+                executable.as_ref(),
                 parameter_bytes.as_slice_mut(),
                 invoke_context,
                 &account_lengths,
@@ -965,14 +1222,39 @@ impl Executor for BpfExecutor {
 
             execute_time = Measure::start("execute");
             stable_log::program_invoke(&logger, program_id, invoke_depth);
-            let mut instruction_meter = ThisInstructionMeter::new(compute_meter.clone());
+            let syscall_cost_recorder = compute_unit_profile
+                .as_ref()
+                .map(|_| SyscallCostRecorder::new_ref(compute_meter.clone()));
+            let mut instruction_meter = match &syscall_cost_recorder {
+                Some(syscall_cost_recorder) => {
+                    ThisInstructionMeter::new(syscall_cost_recorder.clone())
+                }
+                None => ThisInstructionMeter::new(compute_meter.clone()),
+            };
             let before = compute_meter.borrow().get_remaining();
-            let result = if use_jit {
+            // Executables still compiling in the background are interpreted meanwhile
+            let result = if use_jit
+                && compute_unit_profile.is_none()
+                && executable.get_compiled_program().is_some()
+            {
                 vm.execute_program_jit(&mut instruction_meter)
             } else {
                 vm.execute_program_interpreted(&mut instruction_meter)
             };
             let after = compute_meter.borrow().get_remaining();
+            if let (Some(compute_unit_profile), Some(syscall_cost_recorder)) =
+                (&compute_unit_profile, &syscall_cost_recorder)
+            {
+                let attribution = attribute_compute_units(
+                    executable.as_ref(),
+                    &vm.get_tracer().log,
+                    &syscall_cost_recorder.borrow().syscall_costs(),
+                );
+                let mut compute_unit_profile = compute_unit_profile.borrow_mut();
+                for (function, units) in attribution.functions {
+                    compute_unit_profile.record(program_id, &function, units);
+                }
+            }
             ic_logger_msg!(
                 logger,
                 "Program {} consumed {} of {} compute units",
@@ -982,7 +1264,7 @@ impl Executor for BpfExecutor {
             );
             if log_enabled!(Trace) {
                 let mut trace_buffer = Vec::<u8>::new();
-                let analysis = Analysis::from_executable(self.executable.as_ref());
+                let analysis = Analysis::from_executable(executable.as_ref());
                 vm.get_tracer().write(&mut trace_buffer, &analysis).unwrap();
                 let trace_string = String::from_utf8(trace_buffer).unwrap();
                 trace!("BPF Program Instruction Trace:\n{}", trace_string);
@@ -1007,6 +1289,48 @@ impl Executor for BpfExecutor {
                         )) => error,
                         err => {
                             ic_logger_msg!(logger, "Program failed to complete: {}", err);
+                            if let EbpfError::AccessViolation(_, access_type, vm_addr, len, _)
+                            | EbpfError::StackAccessViolation(
+                                _,
+                                access_type,
+                                vm_addr,
+                                len,
+                                _,
+                            ) = err
+                            {
+                                if let Some(location) = describe_access_violation(
+                                    invoke_context,
+                                    loader_id,
+                                    first_instruction_account,
+                                    instruction_data,
+                                    executable.get_config(),
+                                    vm_addr,
+                                ) {
+                                    ic_logger_msg!(
+                                        logger,
+                                        "Program attempted to {} {} bytes in the {}",
+                                        match access_type {
+                                            AccessType::Load => "load",
+                                            AccessType::Store => "store",
+                                        },
+                                        len,
+                                        location,
+                                    );
+                                }
+                            }
+                            if matches!(
+                                err,
+                                EbpfError::StackAccessViolation(..)
+                                    | EbpfError::CallDepthExceeded(..)
+                            ) {
+                                let config = executable.get_config();
+                                ic_logger_msg!(
+                                    logger,
+                                    "Program ran out of stack, its stack frames are {} bytes and its max call depth is {}",
+                                    config.stack_frame_size,
+                                    config.max_call_depth,
+                                );
+                            }
                             InstructionError::ProgramFailedToComplete
                         }
                     };
@@ -1042,7 +1366,9 @@ impl Executor for BpfExecutor {
 mod tests {
     use super::*;
     use rand::Rng;
-    use solana_program_runtime::invoke_context::mock_process_instruction;
+    use solana_program_runtime::invoke_context::{
+        mock_process_instruction, prepare_mock_invoke_context, ThisInvokeContext,
+    };
     use solana_rbpf::vm::SyscallRegistry;
     use solana_runtime::{bank::Bank, bank_client::BankClient};
     use solana_sdk::{
@@ -1057,6 +1383,7 @@ mod tests {
         instruction::{AccountMeta, Instruction, InstructionError},
         message::Message,
         native_token::LAMPORTS_PER_SOL,
+        process_instruction::ComputeUnitProfile,
         pubkey::Pubkey,
         rent::Rent,
         signature::{Keypair, Signer},
@@ -1298,6 +1625,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bpf_loader_compute_unit_profile() {
+        let loader_id = bpf_loader::id();
+        let program_id = Pubkey::new_unique();
+        let program_account =
+            load_program_account_from_elf(&loader_id, "test_elfs/noop_aligned.so");
+        let keyed_accounts = vec![(false, false, program_id, program_account)];
+        let mut program_indices = vec![0];
+        let mut preparation = prepare_mock_invoke_context(&program_indices, &[], &keyed_accounts);
+        program_indices.insert(0, preparation.accounts.len());
+        preparation.accounts.push((
+            loader_id,
+            AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id()),
+        ));
+        let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+        let compute_unit_profile = Rc::new(RefCell::new(ComputeUnitProfile::default()));
+        invoke_context.set_compute_unit_profile(Some(compute_unit_profile.clone()));
+        invoke_context
+            .push(
+                &preparation.message,
+                &preparation.message.instructions[0],
+                &program_indices,
+                Some(&preparation.account_indices),
+            )
+            .unwrap();
+        let compute_meter = invoke_context.get_compute_meter();
+        let before = compute_meter.borrow().get_remaining();
+        assert_eq!(
+            Ok(()),
+            super::process_instruction(1, &[], &mut invoke_context)
+        );
+        let consumed = before - compute_meter.borrow().get_remaining();
+
+        // All the compute units consumed are attributed to the functions of the program
+        let compute_unit_profile = compute_unit_profile.borrow();
+        let program_profile = compute_unit_profile
+            .get_program_profile(&program_id)
+            .unwrap();
+        assert!(!program_profile.is_empty());
+        assert_eq!(program_profile.values().sum::<u64>(), consumed);
+
+        // The traced executors of the profiled programs aren't cached
+        assert!(invoke_context.get_executor(&program_id).is_none());
+    }
+
+    #[test]
+    fn test_bpf_loader_custom_stack() {
+        let loader_id = bpf_loader::id();
+        let consumed_compute_units = |elf_path| {
+            let program_id = Pubkey::new_unique();
+            let program_account = load_program_account_from_elf(&loader_id, elf_path);
+            let keyed_accounts = vec![(false, false, program_id, program_account)];
+            let mut program_indices = vec![0];
+            let mut preparation =
+                prepare_mock_invoke_context(&program_indices, &[], &keyed_accounts);
+            program_indices.insert(0, preparation.accounts.len());
+            preparation.accounts.push((
+                loader_id,
+                AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id()),
+            ));
+            let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+            invoke_context
+                .push(
+                    &preparation.message,
+                    &preparation.message.instructions[0],
+                    &program_indices,
+                    Some(&preparation.account_indices),
+                )
+                .unwrap();
+            let compute_meter = invoke_context.get_compute_meter();
+            let before = compute_meter.borrow().get_remaining();
+            assert_eq!(
+                Ok(()),
+                super::process_instruction(1, &[], &mut invoke_context)
+            );
+            let after = compute_meter.borrow().get_remaining();
+            before - after
+        };
+
+        // The same program, declaring 8KB stack frames and a call depth of 128 in its stack
+        // note, is charged for the 768KB of stack above the default 256KB
+        let compute_budget = ComputeBudget::default();
+        assert_eq!(
+            consumed_compute_units("test_elfs/noop_stack_note.so"),
+            consumed_compute_units("test_elfs/noop_aligned.so") + 24 * compute_budget.stack_cost
+        );
+    }
+
+    #[test]
+    fn test_bpf_loader_verify_program() {
+        let mut file = File::open("test_elfs/noop_aligned.so").expect("file open failed");
+        let mut elf = Vec::new();
+        file.read_to_end(&mut elf).unwrap();
+        let feature_set = Arc::new(FeatureSet::all_enabled());
+
+        assert_eq!(verify_program(&elf, feature_set.clone()), Ok(()));
+        assert_eq!(
+            verify_program(&elf[..elf.len() / 2], feature_set.clone()),
+            Err(InstructionError::InvalidAccountData)
+        );
+
+        // A program declaring its stack requirements in a note loads as well
+        let mut file = File::open("test_elfs/noop_stack_note.so").expect("file open failed");
+        let mut elf = Vec::new();
+        file.read_to_end(&mut elf).unwrap();
+        assert_eq!(verify_program(&elf, feature_set), Ok(()));
+    }
+
+    #[test]
+    fn test_bpf_loader_executable_cache() {
+        let loader_id = bpf_loader::id();
+        let program_id = Pubkey::new_unique();
+        let program_account =
+            load_program_account_from_elf(&loader_id, "test_elfs/noop_aligned.so");
+        let keyed_accounts = vec![(false, false, program_id, program_account)];
+        let mut program_indices = vec![0];
+        let mut preparation = prepare_mock_invoke_context(&program_indices, &[], &keyed_accounts);
+        program_indices.insert(0, preparation.accounts.len());
+        preparation.accounts.push((
+            loader_id,
+            AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id()),
+        ));
+        let mut invoke_context = ThisInvokeContext::new_mock(&preparation.accounts, &[]);
+        invoke_context
+            .push(
+                &preparation.message,
+                &preparation.message.instructions[0],
+                &program_indices,
+                Some(&preparation.account_indices),
+            )
+            .unwrap();
+
+        // Loading the same program data again reuses the verified executable
+        let executor = create_executor(1, 0, &mut invoke_context, false, false).unwrap();
+        assert!(Arc::ptr_eq(
+            &executor,
+            &create_executor(1, 0, &mut invoke_context, false, false).unwrap()
+        ));
+        assert_eq!(Ok(()), executor.execute(1, &[], &mut invoke_context, false));
+
+        // Deployments compile before returning and don't reuse the interpreted executors
+        #[cfg(all(not(target_os = "windows"), target_arch = "x86_64"))]
+        {
+            let jit_executor = create_executor(1, 0, &mut invoke_context, true, true).unwrap();
+            assert!(!Arc::ptr_eq(&executor, &jit_executor));
+            assert!(jit_executor.is_jit_compiled());
+            assert!(!executor.is_jit_compiled());
+            assert!(Arc::ptr_eq(
+                &jit_executor,
+                &create_executor(1, 0, &mut invoke_context, true, false).unwrap()
+            ));
+        }
+    }
+
     #[test]
     fn test_bpf_loader_serialize_unaligned() {
         let loader_id = bpf_loader_deprecated::id();
@@ -3135,6 +3616,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bpf_loader_upgradeable_set_upgrade_authority_checked() {
+        let instruction =
+            bincode::serialize(&UpgradeableLoaderInstruction::SetAuthorityChecked).unwrap();
+        let loader_id = bpf_loader_upgradeable::id();
+        let slot = 0;
+        let upgrade_authority_address = Pubkey::new_unique();
+        let upgrade_authority_account = AccountSharedData::new_ref(1, 0, &Pubkey::new_unique());
+        let new_upgrade_authority_address = Pubkey::new_unique();
+        let new_upgrade_authority_account = AccountSharedData::new_ref(1, 0, &Pubkey::new_unique());
+        let programdata_address = Pubkey::new_unique();
+        let programdata_account = AccountSharedData::new_ref(
+            1,
+            UpgradeableLoaderState::programdata_len(0).unwrap(),
+            &bpf_loader_upgradeable::id(),
+        );
+        programdata_account
+            .borrow_mut()
+            .set_state(&UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address: Some(upgrade_authority_address),
+            })
+            .unwrap();
+
+        // Case: New authority did not sign
+        let keyed_accounts = vec![
+            (
+                false,
+                false,
+                programdata_address,
+                programdata_account.clone(),
+            ),
+            (
+                true,
+                false,
+                upgrade_authority_address,
+                upgrade_authority_account.clone(),
+            ),
+            (
+                false,
+                false,
+                new_upgrade_authority_address,
+                new_upgrade_authority_account.clone(),
+            ),
+        ];
+        assert_eq!(
+            Err(InstructionError::MissingRequiredSignature),
+            process_instruction(&loader_id, &[], &instruction, &keyed_accounts),
+        );
+
+        // Case: No new authority, the program can't be made immutable
+        assert_eq!(
+            Err(InstructionError::NotEnoughAccountKeys),
+            process_instruction(&loader_id, &[], &instruction, &keyed_accounts[..2]),
+        );
+
+        // Case: Set to new authority
+        let keyed_accounts = vec![
+            (
+                false,
+                false,
+                programdata_address,
+                programdata_account.clone(),
+            ),
+            (
+                true,
+                false,
+                upgrade_authority_address,
+                upgrade_authority_account,
+            ),
+            (
+                true,
+                false,
+                new_upgrade_authority_address,
+                new_upgrade_authority_account,
+            ),
+        ];
+        assert_eq!(
+            Ok(()),
+            process_instruction(&loader_id, &[], &instruction, &keyed_accounts),
+        );
+        let state: UpgradeableLoaderState = programdata_account.borrow().state().unwrap();
+        assert_eq!(
+            state,
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address: Some(new_upgrade_authority_address),
+            }
+        );
+
+        // Case: The former authority no longer has authority
+        assert_eq!(
+            Err(InstructionError::IncorrectAuthority),
+            process_instruction(&loader_id, &[], &instruction, &keyed_accounts),
+        );
+    }
+
     #[test]
     fn test_bpf_loader_upgradeable_set_buffer_authority() {
         let instruction = bincode::serialize(&UpgradeableLoaderInstruction::SetAuthority).unwrap();
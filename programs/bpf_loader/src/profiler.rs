@@ -0,0 +1,116 @@
+//! Compute unit profiler
+//!
+//! Attributes the compute units consumed by a traced execution of a program to its functions and
+//! to the syscalls it made. Each instruction costs one compute unit, and the cost of each syscall
+//! is told apart by a `SyscallCostRecorder`, which the interpreter updates right before and right
+//! after each syscall.
+
+use {
+    crate::{BpfError, ThisInstructionMeter},
+    solana_rbpf::{ebpf, vm::Executable},
+    solana_sdk::{instruction::InstructionError, process_instruction::ComputeMeter},
+    std::{
+        cell::{Cell, RefCell},
+        collections::BTreeMap,
+        rc::Rc,
+    },
+};
+
+/// Compute meter of a profiled execution, forwarding to the compute meter of the invocation
+pub struct SyscallCostRecorder {
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    remaining_before_syscall: Cell<Option<u64>>,
+    syscall_costs: RefCell<Vec<u64>>,
+}
+
+impl SyscallCostRecorder {
+    pub fn new_ref(compute_meter: Rc<RefCell<dyn ComputeMeter>>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            compute_meter,
+            remaining_before_syscall: Cell::new(None),
+            syscall_costs: RefCell::new(vec![]),
+        }))
+    }
+
+    /// Compute units consumed by each syscall made by the interpreter, in order. The last ones
+    /// may be zeros, recorded once the program exited.
+    pub fn syscall_costs(&self) -> Vec<u64> {
+        self.syscall_costs.borrow().clone()
+    }
+}
+
+impl ComputeMeter for SyscallCostRecorder {
+    fn consume(&mut self, amount: u64) -> Result<(), InstructionError> {
+        let result = self.compute_meter.borrow_mut().consume(amount);
+        self.remaining_before_syscall
+            .set(Some(self.compute_meter.borrow().get_remaining()));
+        result
+    }
+
+    fn get_remaining(&self) -> u64 {
+        let remaining = self.compute_meter.borrow().get_remaining();
+        if let Some(remaining_before_syscall) = self.remaining_before_syscall.take() {
+            self.syscall_costs
+                .borrow_mut()
+                .push(remaining_before_syscall.saturating_sub(remaining));
+        }
+        remaining
+    }
+}
+
+/// Compute units consumed by a traced execution
+#[derive(Debug, Default, PartialEq)]
+pub struct ComputeUnitAttribution {
+    /// Compute units consumed by each function, including those of the syscalls it made
+    pub functions: BTreeMap<String, u64>,
+    /// Number of calls and compute units consumed by each syscall
+    pub syscalls: BTreeMap<String, (u64, u64)>,
+}
+
+/// Attributes the compute units consumed by the execution traced in `trace` to the functions of
+/// the program. The instructions without a symbol of their own are attributed to the function
+/// preceding them.
+pub fn attribute_compute_units(
+    executable: &dyn Executable<BpfError, ThisInstructionMeter>,
+    trace: &[[u64; 12]],
+    syscall_costs: &[u64],
+) -> ComputeUnitAttribution {
+    let (_, text_bytes) = executable.get_text_bytes();
+    let function_symbols = executable.get_function_symbols();
+    let syscall_registry = executable.get_syscall_registry();
+    let syscall_symbols = executable.get_syscall_symbols();
+    let mut syscall_costs = syscall_costs.iter();
+    let mut units_by_function_pc = BTreeMap::<usize, u64>::new();
+    let mut attribution = ComputeUnitAttribution::default();
+    for state in trace {
+        let pc = state[11] as usize;
+        let mut units = 1;
+        let insn = ebpf::get_insn(text_bytes, pc);
+        if insn.opc == ebpf::CALL_IMM && syscall_registry.lookup_syscall(insn.imm as u32).is_some()
+        {
+            let syscall_units = syscall_costs.next().copied().unwrap_or_default();
+            let name = syscall_symbols
+                .get(&(insn.imm as u32))
+                .cloned()
+                .unwrap_or_else(|| format!("syscall {:#x}", insn.imm as u32));
+            let (calls, total_units) = attribution.syscalls.entry(name).or_default();
+            *calls += 1;
+            *total_units += syscall_units;
+            units += syscall_units;
+        }
+        let function_pc = function_symbols
+            .range(..=pc)
+            .next_back()
+            .map(|(function_pc, _)| *function_pc)
+            .unwrap_or_default();
+        *units_by_function_pc.entry(function_pc).or_default() += units;
+    }
+    for (function_pc, units) in units_by_function_pc {
+        let name = function_symbols
+            .get(&function_pc)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| format!("function {:#x}", function_pc));
+        *attribution.functions.entry(name).or_default() += units;
+    }
+    attribution
+}
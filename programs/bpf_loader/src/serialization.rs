@@ -58,6 +58,117 @@ pub fn deserialize_parameters(
     }
 }
 
+/// Describes the field of the serialized input parameters an offset falls in, so that access
+/// violations in the input region can be reported in terms of the accounts passed to the program
+pub fn describe_serialized_offset(
+    loader_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    instruction_data_len: usize,
+    offset: usize,
+) -> Result<String, InstructionError> {
+    let fields = serialized_fields(loader_id, keyed_accounts, instruction_data_len)?;
+    Ok(fields
+        .iter()
+        .find(|(start, len, _)| (*start..start + len).contains(&offset))
+        .map(|(start, len, name)| format!("{} at offset {} of {} bytes", name, offset - start, len))
+        .unwrap_or_else(|| format!("past the end of the {} byte input", serialized_len(&fields))))
+}
+
+/// Returns the start offset, length and name of each field of the serialized input parameters,
+/// following the layouts written by `serialize_parameters_unaligned` and
+/// `serialize_parameters_aligned`
+fn serialized_fields(
+    loader_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    instruction_data_len: usize,
+) -> Result<Vec<(usize, usize, String)>, InstructionError> {
+    let is_aligned = *loader_id != bpf_loader_deprecated::id();
+    let mut fields = Vec::new();
+    push_field(
+        &mut fields,
+        size_of::<u64>(),
+        "number of accounts".to_string(),
+    );
+    for (i, keyed_account) in keyed_accounts.iter().enumerate() {
+        let account_field = |field: &str| {
+            format!(
+                "{} of account #{} ({})",
+                field,
+                i,
+                keyed_account.unsigned_key()
+            )
+        };
+        let (is_dup, _) = is_dup(&keyed_accounts[..i], keyed_account);
+        if is_dup {
+            push_field(
+                &mut fields,
+                size_of::<u8>(),
+                account_field("duplicate index"),
+            );
+            if is_aligned {
+                push_field(&mut fields, 7, account_field("padding"));
+            }
+            continue;
+        }
+        let data_len = keyed_account.data_len()?;
+        push_field(
+            &mut fields,
+            size_of::<u8>(),
+            account_field("duplicate marker"),
+        );
+        push_field(&mut fields, size_of::<u8>(), account_field("is_signer"));
+        push_field(&mut fields, size_of::<u8>(), account_field("is_writable"));
+        if is_aligned {
+            push_field(&mut fields, size_of::<u8>(), account_field("executable"));
+            push_field(&mut fields, 4, account_field("padding"));
+            push_field(&mut fields, size_of::<Pubkey>(), account_field("key"));
+            push_field(&mut fields, size_of::<Pubkey>(), account_field("owner"));
+            push_field(&mut fields, size_of::<u64>(), account_field("lamports"));
+            push_field(&mut fields, size_of::<u64>(), account_field("data length"));
+            push_field(&mut fields, data_len, account_field("data"));
+            let data_end = serialized_len(&fields);
+            push_field(
+                &mut fields,
+                MAX_PERMITTED_DATA_INCREASE
+                    + (data_end as *const u8).align_offset(align_of::<u128>()),
+                account_field("realloc region"),
+            );
+            push_field(&mut fields, size_of::<u64>(), account_field("rent_epoch"));
+        } else {
+            push_field(&mut fields, size_of::<Pubkey>(), account_field("key"));
+            push_field(&mut fields, size_of::<u64>(), account_field("lamports"));
+            push_field(&mut fields, size_of::<u64>(), account_field("data length"));
+            push_field(&mut fields, data_len, account_field("data"));
+            push_field(&mut fields, size_of::<Pubkey>(), account_field("owner"));
+            push_field(&mut fields, size_of::<u8>(), account_field("executable"));
+            push_field(&mut fields, size_of::<u64>(), account_field("rent_epoch"));
+        }
+    }
+    push_field(
+        &mut fields,
+        size_of::<u64>(),
+        "instruction data length".to_string(),
+    );
+    push_field(
+        &mut fields,
+        instruction_data_len,
+        "instruction data".to_string(),
+    );
+    push_field(&mut fields, size_of::<Pubkey>(), "program id".to_string());
+    Ok(fields)
+}
+
+fn push_field(fields: &mut Vec<(usize, usize, String)>, len: usize, name: String) {
+    fields.push((serialized_len(fields), len, name));
+}
+
+fn serialized_len(fields: &[(usize, usize, String)]) -> usize {
+    fields
+        .last()
+        .map(|(start, len, _)| start + len)
+        .unwrap_or(0)
+}
+
 pub fn get_serialized_account_size_unaligned(
     keyed_account: &KeyedAccount,
 ) -> Result<usize, InstructionError> {
@@ -329,6 +440,103 @@ mod tests {
         slice::{from_raw_parts, from_raw_parts_mut},
     };
 
+    #[test]
+    fn test_describe_serialized_offset() {
+        let program_id = solana_sdk::pubkey::new_rand();
+        let key = solana_sdk::pubkey::new_rand();
+        let other_key = solana_sdk::pubkey::new_rand();
+        let account = RefCell::new(AccountSharedData::from(Account {
+            lamports: 1,
+            data: vec![1u8, 2, 3],
+            owner: bpf_loader::id(),
+            executable: false,
+            rent_epoch: 100,
+        }));
+        let other_account = RefCell::new(AccountSharedData::from(Account {
+            lamports: 2,
+            data: vec![0u8; 32],
+            owner: bpf_loader::id(),
+            executable: false,
+            rent_epoch: 100,
+        }));
+        let keyed_accounts = vec![
+            KeyedAccount::new(&key, false, &account),
+            KeyedAccount::new(&key, false, &account),
+            KeyedAccount::new(&other_key, false, &other_account),
+        ];
+        let instruction_data = vec![1u8, 2, 3, 4, 5];
+
+        for loader_id in [bpf_loader::id(), bpf_loader_deprecated::id()].iter() {
+            let (serialized, _account_lengths) =
+                serialize_parameters(loader_id, &program_id, &keyed_accounts, &instruction_data)
+                    .unwrap();
+            let fields =
+                serialized_fields(loader_id, &keyed_accounts, instruction_data.len()).unwrap();
+            assert_eq!(serialized_len(&fields), serialized.len());
+
+            // The data field of each account points at its serialized data
+            for (i, data) in [(0, vec![1u8, 2, 3]), (2, vec![0u8; 32])].iter() {
+                let name = format!(
+                    "data of account #{} ({})",
+                    i,
+                    keyed_accounts[*i].unsigned_key()
+                );
+                let (start, len, _) = fields.iter().find(|field| field.2 == name).unwrap();
+                assert_eq!(&serialized.as_slice()[*start..start + len], &data[..]);
+            }
+
+            let (start, _, _) = fields
+                .iter()
+                .find(|field| field.2 == "instruction data")
+                .unwrap();
+            assert_eq!(
+                describe_serialized_offset(
+                    loader_id,
+                    &keyed_accounts,
+                    instruction_data.len(),
+                    start + 2
+                )
+                .unwrap(),
+                "instruction data at offset 2 of 5 bytes"
+            );
+            assert_eq!(
+                describe_serialized_offset(
+                    loader_id,
+                    &keyed_accounts,
+                    instruction_data.len(),
+                    serialized.len()
+                )
+                .unwrap(),
+                format!("past the end of the {} byte input", serialized.len())
+            );
+        }
+
+        assert_eq!(
+            describe_serialized_offset(
+                &bpf_loader::id(),
+                &keyed_accounts,
+                instruction_data.len(),
+                8 + 8 + 32 + 32 + 8 + 8 + 3 + 1
+            )
+            .unwrap(),
+            format!(
+                "realloc region of account #0 ({}) at offset 1 of {} bytes",
+                key,
+                MAX_PERMITTED_DATA_INCREASE + 13
+            )
+        );
+        assert_eq!(
+            describe_serialized_offset(
+                &bpf_loader::id(),
+                &keyed_accounts,
+                instruction_data.len(),
+                0
+            )
+            .unwrap(),
+            "number of accounts at offset 0 of 8 bytes"
+        );
+    }
+
     #[test]
     fn test_serialize_parameters() {
         let program_id = solana_sdk::pubkey::new_rand();
@@ -0,0 +1,235 @@
+//! Stack requirements declared by programs in an ELF note
+//!
+//! Programs that need larger stack frames or a deeper call stack than the defaults declare
+//! them with `solana_program::custom_stack!`, which emits a note into the
+//! `.note.solana.stack` section of the program's ELF.
+
+use {
+    goblin::elf::Elf,
+    solana_sdk::entrypoint::{
+        MAX_CALL_DEPTH, MAX_STACK_FRAME_SIZE, MAX_STACK_SIZE, STACK_NOTE_NAME, STACK_NOTE_SECTION,
+        STACK_NOTE_TYPE,
+    },
+    std::convert::TryInto,
+    thiserror::Error,
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StackMetadataError {
+    #[error("Failed to parse ELF: {0}")]
+    InvalidElf(String),
+    #[error("Malformed stack note")]
+    MalformedNote,
+    #[error("Stack frame size {0} must be a power of two multiple of {1} and at most {2}")]
+    InvalidStackFrameSize(usize, usize, usize),
+    #[error("Max call depth {0} must be between 1 and {1}")]
+    InvalidCallDepth(usize, usize),
+    #[error("Stack size {0} exceeds the maximum of {1}")]
+    StackTooLarge(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackMetadata {
+    pub stack_frame_size: usize,
+    pub max_call_depth: usize,
+}
+
+impl StackMetadata {
+    /// Returns the stack requirements declared by the program, `None` if it doesn't declare any
+    pub fn from_elf(
+        elf_bytes: &[u8],
+        default_stack_frame_size: usize,
+    ) -> Result<Option<Self>, StackMetadataError> {
+        let elf =
+            Elf::parse(elf_bytes).map_err(|err| StackMetadataError::InvalidElf(err.to_string()))?;
+        let notes = match elf.iter_note_sections(elf_bytes, Some(STACK_NOTE_SECTION)) {
+            None => return Ok(None),
+            Some(notes) => notes,
+        };
+        for note in notes {
+            let note = note.map_err(|_| StackMetadataError::MalformedNote)?;
+            if note.name == STACK_NOTE_NAME && note.n_type == STACK_NOTE_TYPE {
+                let stack_metadata = Self::from_note_desc(note.desc)?;
+                stack_metadata.validate(default_stack_frame_size)?;
+                return Ok(Some(stack_metadata));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Total size of the stack, in bytes
+    pub fn stack_size(&self) -> usize {
+        self.stack_frame_size.saturating_mul(self.max_call_depth)
+    }
+
+    // The descriptor holds the stack frame size and the max call depth as little-endian u32s.
+    fn from_note_desc(desc: &[u8]) -> Result<Self, StackMetadataError> {
+        if desc.len() != 8 {
+            return Err(StackMetadataError::MalformedNote);
+        }
+        let stack_frame_size = u32::from_le_bytes(desc[..4].try_into().unwrap());
+        let max_call_depth = u32::from_le_bytes(desc[4..].try_into().unwrap());
+        Ok(Self {
+            stack_frame_size: stack_frame_size as usize,
+            max_call_depth: max_call_depth as usize,
+        })
+    }
+
+    fn validate(&self, default_stack_frame_size: usize) -> Result<(), StackMetadataError> {
+        if !self.stack_frame_size.is_power_of_two()
+            || self.stack_frame_size % default_stack_frame_size != 0
+            || self.stack_frame_size > MAX_STACK_FRAME_SIZE
+        {
+            return Err(StackMetadataError::InvalidStackFrameSize(
+                self.stack_frame_size,
+                default_stack_frame_size,
+                MAX_STACK_FRAME_SIZE,
+            ));
+        }
+        if self.max_call_depth == 0 || self.max_call_depth > MAX_CALL_DEPTH {
+            return Err(StackMetadataError::InvalidCallDepth(
+                self.max_call_depth,
+                MAX_CALL_DEPTH,
+            ));
+        }
+        if self.stack_size() > MAX_STACK_SIZE {
+            return Err(StackMetadataError::StackTooLarge(
+                self.stack_size(),
+                MAX_STACK_SIZE,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::fs::File, std::io::Read};
+
+    fn note_desc(stack_frame_size: u32, max_call_depth: u32) -> Vec<u8> {
+        [stack_frame_size.to_le_bytes(), max_call_depth.to_le_bytes()].concat()
+    }
+
+    #[test]
+    fn test_stack_metadata_from_elf_without_note() {
+        let mut file = File::open("test_elfs/noop_aligned.so").unwrap();
+        let mut elf = Vec::new();
+        file.read_to_end(&mut elf).unwrap();
+        assert_eq!(StackMetadata::from_elf(&elf, 4_096), Ok(None));
+
+        assert!(matches!(
+            StackMetadata::from_elf(&elf[..16], 4_096),
+            Err(StackMetadataError::InvalidElf(_))
+        ));
+    }
+
+    #[test]
+    fn test_stack_metadata_from_elf_with_note() {
+        // noop_aligned.so with a `.note.solana.stack` section added, as emitted by
+        // `custom_stack!(8 * 1024, 128)`
+        let mut file = File::open("test_elfs/noop_stack_note.so").unwrap();
+        let mut elf = Vec::new();
+        file.read_to_end(&mut elf).unwrap();
+        assert_eq!(
+            StackMetadata::from_elf(&elf, 4_096),
+            Ok(Some(StackMetadata {
+                stack_frame_size: 8_192,
+                max_call_depth: 128,
+            }))
+        );
+
+        // Frames that aren't a multiple of the default size are rejected
+        assert_eq!(
+            StackMetadata::from_elf(&elf, 3_000),
+            Err(StackMetadataError::InvalidStackFrameSize(
+                8_192,
+                3_000,
+                MAX_STACK_FRAME_SIZE
+            ))
+        );
+    }
+
+    #[test]
+    fn test_stack_metadata_from_note_desc() {
+        let stack_metadata = StackMetadata::from_note_desc(&note_desc(8_192, 128)).unwrap();
+        assert_eq!(
+            stack_metadata,
+            StackMetadata {
+                stack_frame_size: 8_192,
+                max_call_depth: 128,
+            }
+        );
+        assert_eq!(stack_metadata.stack_size(), 1024 * 1024);
+        assert_eq!(stack_metadata.validate(4_096), Ok(()));
+
+        assert_eq!(
+            StackMetadata::from_note_desc(&note_desc(8_192, 128)[..7]),
+            Err(StackMetadataError::MalformedNote)
+        );
+    }
+
+    #[test]
+    fn test_stack_metadata_validate() {
+        let validate = |stack_frame_size, max_call_depth| {
+            StackMetadata {
+                stack_frame_size,
+                max_call_depth,
+            }
+            .validate(4_096)
+        };
+        assert_eq!(validate(4_096, 64), Ok(()));
+        assert_eq!(validate(4_096, 1), Ok(()));
+        assert_eq!(validate(MAX_STACK_FRAME_SIZE, 16), Ok(()));
+        assert_eq!(
+            validate(6_000, 64),
+            Err(StackMetadataError::InvalidStackFrameSize(
+                6_000,
+                4_096,
+                MAX_STACK_FRAME_SIZE
+            ))
+        );
+        // A multiple of the default frame size that isn't a power of two
+        assert_eq!(
+            validate(12_288, 64),
+            Err(StackMetadataError::InvalidStackFrameSize(
+                12_288,
+                4_096,
+                MAX_STACK_FRAME_SIZE
+            ))
+        );
+        assert_eq!(
+            validate(0, 64),
+            Err(StackMetadataError::InvalidStackFrameSize(
+                0,
+                4_096,
+                MAX_STACK_FRAME_SIZE
+            ))
+        );
+        assert_eq!(
+            validate(MAX_STACK_FRAME_SIZE * 2, 1),
+            Err(StackMetadataError::InvalidStackFrameSize(
+                MAX_STACK_FRAME_SIZE * 2,
+                4_096,
+                MAX_STACK_FRAME_SIZE
+            ))
+        );
+        assert_eq!(
+            validate(4_096, 0),
+            Err(StackMetadataError::InvalidCallDepth(0, MAX_CALL_DEPTH))
+        );
+        assert_eq!(
+            validate(4_096, MAX_CALL_DEPTH + 1),
+            Err(StackMetadataError::InvalidCallDepth(
+                MAX_CALL_DEPTH + 1,
+                MAX_CALL_DEPTH
+            ))
+        );
+        assert_eq!(
+            validate(MAX_STACK_FRAME_SIZE, MAX_CALL_DEPTH),
+            Err(StackMetadataError::StackTooLarge(
+                MAX_STACK_FRAME_SIZE * MAX_CALL_DEPTH,
+                MAX_STACK_SIZE
+            ))
+        );
+    }
+}
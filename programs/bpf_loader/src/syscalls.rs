@@ -1,5 +1,6 @@
 use crate::{alloc, BpfError};
 use alloc::Alloc;
+use lazy_static::lazy_static;
 use solana_program_runtime::instruction_processor::InstructionProcessor;
 use solana_rbpf::{
     aligned_memory::AlignedMemory,
@@ -14,15 +15,23 @@ use solana_sdk::sysvar::fees::Fees;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
     account_info::AccountInfo,
+    alt_bn128::{
+        alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing, AltBn128Error,
+        ALT_BN128_ADD, ALT_BN128_ADDITION_OUTPUT_LEN, ALT_BN128_MUL,
+        ALT_BN128_MULTIPLICATION_OUTPUT_LEN, ALT_BN128_PAIRING, ALT_BN128_PAIRING_ELEMENT_LEN,
+        ALT_BN128_PAIRING_OUTPUT_LEN,
+    },
     blake3, bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable,
     clock::Clock,
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     epoch_schedule::EpochSchedule,
     feature_set::{
         blake3_syscall_enabled, demote_program_write_locks, disable_fees_sysvar,
-        do_support_realloc, libsecp256k1_0_5_upgrade_enabled,
-        prevent_calling_precompiles_as_programs, return_data_syscall_enabled,
-        secp256k1_recover_syscall_enabled, sol_log_data_syscall_enabled,
+        do_support_realloc, enable_alt_bn128_syscall, enable_poseidon_syscall,
+        get_epoch_stake_syscall_enabled, libsecp256k1_0_5_upgrade_enabled,
+        prevent_calling_precompiles_as_programs, remaining_compute_units_syscall_enabled,
+        return_data_syscall_enabled, secp256k1_recover_syscall_enabled,
+        sol_log_data_syscall_enabled, stack_height_syscall_enabled,
     },
     hash::{Hasher, HASH_BYTES},
     ic_msg,
@@ -30,6 +39,7 @@ use solana_sdk::{
     keccak,
     message::Message,
     native_loader,
+    poseidon::{self, PoseidonSyscallError},
     precompiles::is_precompile,
     process_instruction::{stable_log, ComputeMeter, InvokeContext, Logger},
     program::MAX_RETURN_DATA,
@@ -43,10 +53,12 @@ use solana_sdk::{
 use std::{
     alloc::Layout,
     cell::{Ref, RefCell, RefMut},
+    convert::TryFrom,
     mem::{align_of, size_of},
     rc::Rc,
     slice::from_raw_parts_mut,
     str::{from_utf8, Utf8Error},
+    sync::RwLock,
 };
 use thiserror::Error as ThisError;
 
@@ -84,6 +96,8 @@ pub enum SyscallError {
     CopyOverlapping,
     #[error("Return data too large ({0} > {1})")]
     ReturnDataTooLarge(u64, u64),
+    #[error("Invalid attribute")]
+    InvalidAttribute,
 }
 impl From<SyscallError> for EbpfError<BpfError> {
     fn from(error: SyscallError) -> Self {
@@ -112,6 +126,49 @@ impl SyscallConsume for Rc<RefCell<dyn ComputeMeter>> {
 /// Simple bump allocator, never frees
 use crate::allocator_bump::BpfAllocator;
 
+/// A syscall registered at runtime in addition to the ones built into the loader, available to
+/// programs once its feature is active
+#[derive(Clone, Copy)]
+pub struct CustomSyscall {
+    pub feature_id: Pubkey,
+    /// Registers the function of the syscall, `SyscallObject::call` of its context object
+    pub register: fn(&mut SyscallRegistry) -> Result<(), EbpfError<BpfError>>,
+    /// Creates the context object of the syscall for an invocation
+    pub new_context_object: fn(&dyn InvokeContext) -> Box<dyn SyscallObject<BpfError>>,
+}
+
+lazy_static! {
+    static ref CUSTOM_SYSCALLS: RwLock<Vec<CustomSyscall>> = RwLock::new(vec![CustomSyscall {
+        feature_id: stack_height_syscall_enabled::id(),
+        register: |syscall_registry| {
+            syscall_registry
+                .register_syscall_by_name(b"sol_get_stack_height", SyscallGetStackHeight::call)
+        },
+        new_context_object: |invoke_context| {
+            Box::new(SyscallGetStackHeight {
+                cost: invoke_context.get_compute_budget().syscall_base_cost,
+                compute_meter: invoke_context.get_compute_meter(),
+                stack_height: invoke_context.invoke_depth() as u64,
+            })
+        },
+    }]);
+}
+
+/// Registers a custom syscall with every program executable created from now on. Meant to be
+/// called at validator startup, before any program is loaded, so that all the validators of a
+/// cluster agree on the syscalls a program may use once the syscall's feature is active. A
+/// syscall already registered is not registered again.
+pub fn register_custom_syscall(custom_syscall: CustomSyscall) {
+    let mut custom_syscalls = CUSTOM_SYSCALLS.write().unwrap();
+    let is_registered = custom_syscalls.iter().any(|registered| {
+        registered.feature_id == custom_syscall.feature_id
+            && registered.register as usize == custom_syscall.register as usize
+    });
+    if !is_registered {
+        custom_syscalls.push(custom_syscall);
+    }
+}
+
 pub fn register_syscalls(
     invoke_context: &mut dyn InvokeContext,
 ) -> Result<SyscallRegistry, EbpfError<BpfError>> {
@@ -125,6 +182,13 @@ pub fn register_syscalls(
     syscall_registry
         .register_syscall_by_name(b"sol_log_compute_units_", SyscallLogBpfComputeUnits::call)?;
 
+    if invoke_context.is_feature_active(&remaining_compute_units_syscall_enabled::id()) {
+        syscall_registry.register_syscall_by_name(
+            b"sol_remaining_compute_units",
+            SyscallRemainingComputeUnits::call,
+        )?;
+    }
+
     syscall_registry.register_syscall_by_name(b"sol_log_pubkey", SyscallLogPubkey::call)?;
 
     syscall_registry.register_syscall_by_name(
@@ -148,6 +212,15 @@ pub fn register_syscalls(
         syscall_registry.register_syscall_by_name(b"sol_blake3", SyscallBlake3::call)?;
     }
 
+    if invoke_context.is_feature_active(&enable_alt_bn128_syscall::id()) {
+        syscall_registry
+            .register_syscall_by_name(b"sol_alt_bn128_group_op", SyscallAltBn128::call)?;
+    }
+
+    if invoke_context.is_feature_active(&enable_poseidon_syscall::id()) {
+        syscall_registry.register_syscall_by_name(b"sol_poseidon", SyscallPoseidon::call)?;
+    }
+
     syscall_registry
         .register_syscall_by_name(b"sol_get_clock_sysvar", SyscallGetClockSysvar::call)?;
     syscall_registry.register_syscall_by_name(
@@ -188,6 +261,18 @@ pub fn register_syscalls(
         syscall_registry.register_syscall_by_name(b"sol_log_data", SyscallLogData::call)?;
     }
 
+    // Epoch stake
+    if invoke_context.is_feature_active(&get_epoch_stake_syscall_enabled::id()) {
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_epoch_stake", SyscallGetEpochStake::call)?;
+    }
+
+    for custom_syscall in CUSTOM_SYSCALLS.read().unwrap().iter() {
+        if invoke_context.is_feature_active(&custom_syscall.feature_id) {
+            (custom_syscall.register)(&mut syscall_registry)?;
+        }
+    }
+
     Ok(syscall_registry)
 }
 
@@ -249,6 +334,15 @@ pub fn bind_syscall_context_objects<'a>(
         None,
     )?;
 
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        invoke_context.is_feature_active(&remaining_compute_units_syscall_enabled::id()),
+        Box::new(SyscallRemainingComputeUnits {
+            cost: compute_budget.syscall_base_cost,
+            compute_meter: invoke_context.get_compute_meter(),
+        }),
+    );
+
     vm.bind_syscall_context_object(
         Box::new(SyscallLogPubkey {
             cost: compute_budget.log_pubkey_units,
@@ -352,12 +446,48 @@ pub fn bind_syscall_context_objects<'a>(
         }),
     );
 
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        invoke_context.is_feature_active(&enable_alt_bn128_syscall::id()),
+        Box::new(SyscallAltBn128 {
+            addition_cost: compute_budget.alt_bn128_addition_cost,
+            multiplication_cost: compute_budget.alt_bn128_multiplication_cost,
+            pairing_one_pair_cost_first: compute_budget.alt_bn128_pairing_one_pair_cost_first,
+            pairing_one_pair_cost_other: compute_budget.alt_bn128_pairing_one_pair_cost_other,
+            sha256_base_cost: compute_budget.sha256_base_cost,
+            compute_meter: invoke_context.get_compute_meter(),
+            loader_id,
+        }),
+    );
+
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        invoke_context.is_feature_active(&enable_poseidon_syscall::id()),
+        Box::new(SyscallPoseidon {
+            cost_coefficient_a: compute_budget.poseidon_cost_coefficient_a,
+            cost_coefficient_c: compute_budget.poseidon_cost_coefficient_c,
+            compute_meter: invoke_context.get_compute_meter(),
+            loader_id,
+        }),
+    );
+
+    // Custom syscalls
+    for custom_syscall in CUSTOM_SYSCALLS.read().unwrap().iter() {
+        bind_feature_gated_syscall_context_object!(
+            vm,
+            invoke_context.is_feature_active(&custom_syscall.feature_id),
+            (custom_syscall.new_context_object)(&*invoke_context),
+        );
+    }
+
     let is_fee_sysvar_via_syscall_active =
         !invoke_context.is_feature_active(&disable_fees_sysvar::id());
     let is_return_data_syscall_active =
         invoke_context.is_feature_active(&return_data_syscall_enabled::id());
     let is_sol_log_data_syscall_active =
         invoke_context.is_feature_active(&sol_log_data_syscall_enabled::id());
+    let is_get_epoch_stake_syscall_active =
+        invoke_context.is_feature_active(&get_epoch_stake_syscall_enabled::id());
 
     let invoke_context = Rc::new(RefCell::new(invoke_context));
 
@@ -420,6 +550,16 @@ pub fn bind_syscall_context_objects<'a>(
         }),
     );
 
+    // Epoch stake
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_get_epoch_stake_syscall_active,
+        Box::new(SyscallGetEpochStake {
+            invoke_context: invoke_context.clone(),
+            loader_id,
+        }),
+    );
+
     // Cross-program invocation syscalls
     vm.bind_syscall_context_object(
         Box::new(SyscallInvokeSignedC {
@@ -700,6 +840,56 @@ impl SyscallObject<BpfError> for SyscallLogBpfComputeUnits {
     }
 }
 
+/// Return the number of compute units remaining
+pub struct SyscallRemainingComputeUnits {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+}
+impl SyscallObject<BpfError> for SyscallRemainingComputeUnits {
+    fn call(
+        &mut self,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        let compute_meter = question_mark!(
+            self.compute_meter
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        *result = Ok(compute_meter.get_remaining());
+    }
+}
+
+/// Return the height of the invocation stack, 1 for the instructions of the transaction and
+/// one more for each level of cross-program invocation
+pub struct SyscallGetStackHeight {
+    cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    stack_height: u64,
+}
+impl SyscallObject<BpfError> for SyscallGetStackHeight {
+    fn call(
+        &mut self,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        question_mark!(self.compute_meter.consume(self.cost), result);
+        *result = Ok(self.stack_height);
+    }
+}
+
 /// Log 5 64-bit values
 pub struct SyscallLogPubkey<'a> {
     cost: u64,
@@ -1442,6 +1632,168 @@ impl<'a> SyscallObject<BpfError> for SyscallBlake3<'a> {
     }
 }
 
+/// alt_bn128 group operations
+pub struct SyscallAltBn128<'a> {
+    addition_cost: u64,
+    multiplication_cost: u64,
+    pairing_one_pair_cost_first: u64,
+    pairing_one_pair_cost_other: u64,
+    sha256_base_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallAltBn128<'a> {
+    fn call(
+        &mut self,
+        group_op: u64,
+        input_addr: u64,
+        input_size: u64,
+        result_addr: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let (cost, output_len, calculation): (_, _, fn(&[u8]) -> Result<Vec<u8>, AltBn128Error>) =
+            match group_op {
+                ALT_BN128_ADD => (
+                    self.addition_cost,
+                    ALT_BN128_ADDITION_OUTPUT_LEN,
+                    alt_bn128_addition,
+                ),
+                ALT_BN128_MUL => (
+                    self.multiplication_cost,
+                    ALT_BN128_MULTIPLICATION_OUTPUT_LEN,
+                    alt_bn128_multiplication,
+                ),
+                ALT_BN128_PAIRING => {
+                    let pairs = input_size / ALT_BN128_PAIRING_ELEMENT_LEN as u64;
+                    let cost = self
+                        .pairing_one_pair_cost_first
+                        .saturating_add(
+                            self.pairing_one_pair_cost_other
+                                .saturating_mul(pairs.saturating_sub(1)),
+                        )
+                        .saturating_add(self.sha256_base_cost)
+                        .saturating_add(input_size)
+                        .saturating_add(ALT_BN128_PAIRING_OUTPUT_LEN as u64);
+                    (cost, ALT_BN128_PAIRING_OUTPUT_LEN, alt_bn128_pairing)
+                }
+                _ => {
+                    *result = Err(SyscallError::InvalidAttribute.into());
+                    return;
+                }
+            };
+        question_mark!(self.compute_meter.consume(cost), result);
+
+        let input = question_mark!(
+            translate_slice::<u8>(memory_mapping, input_addr, input_size, self.loader_id),
+            result
+        );
+        let call_result = question_mark!(
+            translate_slice_mut::<u8>(
+                memory_mapping,
+                result_addr,
+                output_len as u64,
+                self.loader_id,
+            ),
+            result
+        );
+
+        match calculation(input) {
+            Ok(value) => {
+                call_result.copy_from_slice(&value);
+                *result = Ok(SUCCESS);
+            }
+            Err(err) => {
+                *result = Ok(err.into());
+            }
+        }
+    }
+}
+
+/// Poseidon hash
+pub struct SyscallPoseidon<'a> {
+    cost_coefficient_a: u64,
+    cost_coefficient_c: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallPoseidon<'a> {
+    fn call(
+        &mut self,
+        parameters: u64,
+        endianness: u64,
+        vals_addr: u64,
+        vals_len: u64,
+        result_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let parameters = match poseidon::Parameters::try_from(parameters) {
+            Ok(parameters) => parameters,
+            Err(err) => {
+                *result = Ok(err.into());
+                return;
+            }
+        };
+        let endianness = match poseidon::Endianness::try_from(endianness) {
+            Ok(endianness) => endianness,
+            Err(err) => {
+                *result = Ok(err.into());
+                return;
+            }
+        };
+        if vals_len > poseidon::MAX_INPUTS as u64 {
+            *result = Ok(PoseidonSyscallError::InvalidNumberOfInputs.into());
+            return;
+        }
+
+        // The cost grows quadratically with the number of inputs, as the width of the
+        // permutation grows with it
+        let cost = self
+            .cost_coefficient_a
+            .saturating_mul(vals_len.saturating_mul(vals_len))
+            .saturating_add(self.cost_coefficient_c);
+        question_mark!(self.compute_meter.consume(cost), result);
+
+        let hash_result = question_mark!(
+            translate_slice_mut::<u8>(
+                memory_mapping,
+                result_addr,
+                poseidon::HASH_BYTES as u64,
+                self.loader_id,
+            ),
+            result
+        );
+        let vals = question_mark!(
+            translate_slice::<&[u8]>(memory_mapping, vals_addr, vals_len, self.loader_id),
+            result
+        );
+        let mut inputs = Vec::with_capacity(vals.len());
+        for val in vals.iter() {
+            inputs.push(question_mark!(
+                translate_slice::<u8>(
+                    memory_mapping,
+                    val.as_ptr() as u64,
+                    val.len() as u64,
+                    self.loader_id,
+                ),
+                result
+            ));
+        }
+
+        match poseidon::hashv(parameters, endianness, &inputs) {
+            Ok(hash) => {
+                hash_result.copy_from_slice(&hash.to_bytes());
+                *result = Ok(SUCCESS);
+            }
+            Err(err) => {
+                *result = Ok(err.into());
+            }
+        }
+    }
+}
+
 // Cross-program invocation syscalls
 
 struct CallerAccount<'a> {
@@ -2358,6 +2710,55 @@ impl<'a> SyscallObject<BpfError> for SyscallGetReturnData<'a> {
     }
 }
 
+/// Stake of the current epoch
+///
+/// Returns the total stake of the epoch if the vote address is null, and the stake delegated to
+/// the vote account otherwise.
+pub struct SyscallGetEpochStake<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallGetEpochStake<'a> {
+    fn call(
+        &mut self,
+        vote_address_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        let budget = invoke_context.get_compute_budget();
+
+        question_mark!(
+            invoke_context
+                .get_compute_meter()
+                .consume(budget.syscall_base_cost),
+            result
+        );
+
+        if vote_address_addr == 0 {
+            *result = Ok(invoke_context.get_epoch_total_stake());
+            return;
+        }
+
+        let vote_address = question_mark!(
+            translate_type::<Pubkey>(memory_mapping, vote_address_addr, self.loader_id),
+            result
+        );
+
+        *result = Ok(invoke_context.get_epoch_vote_account_stake(vote_address));
+    }
+}
+
 // Log data handling
 pub struct SyscallLogData<'a> {
     invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
@@ -2428,14 +2829,19 @@ impl<'a> SyscallObject<BpfError> for SyscallLogData<'a> {
 mod tests {
     use super::*;
     use solana_program_runtime::{
-        invoke_context::{ThisComputeMeter, ThisInvokeContext, ThisLogger},
+        instruction_processor::Executors,
+        invoke_context::{EpochStakeCallback, ThisComputeMeter, ThisInvokeContext, ThisLogger},
         log_collector::LogCollector,
     };
     use solana_rbpf::{
         ebpf::HOST_ALIGN, memory_region::MemoryRegion, user_error::UserError, vm::Config,
     };
     use solana_sdk::{
-        bpf_loader, feature_set::FeatureSet, fee_calculator::FeeCalculator, hash::hashv,
+        bpf_loader,
+        compute_budget::ComputeBudget,
+        feature_set::FeatureSet,
+        fee_calculator::FeeCalculator,
+        hash::{hashv, Hash},
     };
     use std::{str::FromStr, sync::Arc};
 
@@ -2947,6 +3353,68 @@ mod tests {
         assert_eq!(log[0], "Program log: 0x1, 0x2, 0x3, 0x4, 0x5");
     }
 
+    #[test]
+    fn test_syscall_sol_remaining_compute_units() {
+        let compute_meter = ThisComputeMeter::new_ref(1_000);
+        let mut syscall_sol_remaining_compute_units = SyscallRemainingComputeUnits {
+            cost: 100,
+            compute_meter: compute_meter.clone(),
+        };
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(vec![], &config).unwrap();
+
+        // The cost of the call is charged before the remaining units are read
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall_sol_remaining_compute_units.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 900);
+
+        compute_meter.borrow_mut().consume(850).unwrap();
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall_sol_remaining_compute_units.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_get_stack_height() {
+        let compute_meter = ThisComputeMeter::new_ref(150);
+        let mut syscall_sol_get_stack_height = SyscallGetStackHeight {
+            cost: 100,
+            compute_meter,
+            stack_height: 2,
+        };
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(vec![], &config).unwrap();
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall_sol_get_stack_height.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 2);
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall_sol_get_stack_height.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_register_custom_syscall() {
+        // Registering the syscalls again, as a validator restarted in the same process does,
+        // doesn't duplicate them
+        let custom_syscalls = CUSTOM_SYSCALLS.read().unwrap().clone();
+        for custom_syscall in &custom_syscalls {
+            register_custom_syscall(*custom_syscall);
+        }
+        assert_eq!(CUSTOM_SYSCALLS.read().unwrap().len(), custom_syscalls.len());
+    }
+
     #[test]
     fn test_syscall_sol_pubkey() {
         let pubkey = Pubkey::from_str("MoqiU1vryuCGQSxFKA1SZ316JdLEFFhoAu6cKUNk7dN").unwrap();
@@ -3254,6 +3722,225 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_syscall_alt_bn128() {
+        let input = hex::decode(
+            "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+             063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+             07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+             06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+        )
+        .unwrap();
+        let expected = hex::decode(
+            "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+             301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+        )
+        .unwrap();
+        let output = [0u8; ALT_BN128_ADDITION_OUTPUT_LEN];
+        let ro_va = 0x100000000;
+        let rw_va = 0x200000000;
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(
+            vec![
+                MemoryRegion::default(),
+                MemoryRegion {
+                    host_addr: input.as_ptr() as *const _ as u64,
+                    vm_addr: ro_va,
+                    len: input.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: output.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: output.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &config,
+        )
+        .unwrap();
+        let compute_budget = ComputeBudget::default();
+        let compute_meter = ThisComputeMeter::new_ref(compute_budget.alt_bn128_addition_cost * 2);
+        let mut syscall = SyscallAltBn128 {
+            addition_cost: compute_budget.alt_bn128_addition_cost,
+            multiplication_cost: compute_budget.alt_bn128_multiplication_cost,
+            pairing_one_pair_cost_first: compute_budget.alt_bn128_pairing_one_pair_cost_first,
+            pairing_one_pair_cost_other: compute_budget.alt_bn128_pairing_one_pair_cost_other,
+            sha256_base_cost: compute_budget.sha256_base_cost,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            ALT_BN128_ADD,
+            ro_va,
+            input.len() as u64,
+            rw_va,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), SUCCESS);
+        assert_eq!(output.to_vec(), expected);
+        assert_eq!(
+            compute_meter.borrow().get_remaining(),
+            compute_budget.alt_bn128_addition_cost
+        );
+
+        // A point that is not on the curve is reported to the program
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            ALT_BN128_ADD,
+            ro_va + 1,
+            input.len() as u64 - 1,
+            rw_va,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), u64::from(AltBn128Error::GroupError));
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            ALT_BN128_MUL,
+            ro_va,
+            input.len() as u64,
+            rw_va,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            1,
+            ro_va,
+            input.len() as u64,
+            rw_va,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InvalidAttribute
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_poseidon() {
+        let mut input = [0u8; 32];
+        input[31] = 1;
+        let mock_slice = MockSlice {
+            vm_addr: 0x300000000,
+            len: input.len(),
+        };
+        let vals = [mock_slice];
+        let hash_result = [0u8; poseidon::HASH_BYTES];
+        let ro_va = 0x100000000;
+        let rw_va = 0x200000000;
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(
+            vec![
+                MemoryRegion::default(),
+                MemoryRegion {
+                    host_addr: vals.as_ptr() as *const _ as u64,
+                    vm_addr: ro_va,
+                    len: 16,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: hash_result.as_ptr() as *const _ as u64,
+                    vm_addr: rw_va,
+                    len: poseidon::HASH_BYTES as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+                MemoryRegion {
+                    host_addr: input.as_ptr() as *const _ as u64,
+                    vm_addr: vals[0].vm_addr,
+                    len: input.len() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+            ],
+            &config,
+        )
+        .unwrap();
+        let compute_budget = ComputeBudget::default();
+        let cost =
+            compute_budget.poseidon_cost_coefficient_a + compute_budget.poseidon_cost_coefficient_c;
+        let compute_meter = ThisComputeMeter::new_ref(cost);
+        let mut syscall = SyscallPoseidon {
+            cost_coefficient_a: compute_budget.poseidon_cost_coefficient_a,
+            cost_coefficient_c: compute_budget.poseidon_cost_coefficient_c,
+            compute_meter: compute_meter.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            poseidon::Parameters::Bn254X5 as u64,
+            poseidon::Endianness::BigEndian as u64,
+            ro_va,
+            1,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), SUCCESS);
+        assert_eq!(
+            hex::encode(hash_result),
+            "29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133"
+        );
+        assert_eq!(compute_meter.borrow().get_remaining(), 0);
+
+        // Invalid parameters are reported to the program without charging for the hash
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            poseidon::Parameters::Bn254X5 as u64,
+            2,
+            ro_va,
+            1,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            result.unwrap(),
+            u64::from(PoseidonSyscallError::InvalidEndianness)
+        );
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            poseidon::Parameters::Bn254X5 as u64,
+            poseidon::Endianness::BigEndian as u64,
+            ro_va,
+            1,
+            rw_va,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
     #[test]
     fn test_syscall_get_sysvar() {
         let config = Config::default();
@@ -3455,6 +4142,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_syscall_get_epoch_stake() {
+        struct MockEpochStakes {
+            vote_address: Pubkey,
+        }
+        impl EpochStakeCallback for MockEpochStakes {
+            fn get_epoch_total_stake(&self) -> u64 {
+                1_000
+            }
+            fn get_epoch_vote_account_stake(&self, vote_address: &Pubkey) -> u64 {
+                if *vote_address == self.vote_address {
+                    400
+                } else {
+                    0
+                }
+            }
+            fn get_epoch_start_commission(&self, _vote_address: &Pubkey) -> Option<u8> {
+                None
+            }
+        }
+
+        let vote_address = Pubkey::new_unique();
+        let other_vote_address = Pubkey::new_unique();
+        let epoch_stakes = MockEpochStakes { vote_address };
+        let vote_address_va = 0x100000000;
+        let other_vote_address_va = 0x200000000;
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(
+            vec![
+                MemoryRegion::default(),
+                MemoryRegion {
+                    host_addr: &vote_address as *const _ as u64,
+                    vm_addr: vote_address_va,
+                    len: size_of::<Pubkey>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+                MemoryRegion {
+                    host_addr: &other_vote_address as *const _ as u64,
+                    vm_addr: other_vote_address_va,
+                    len: size_of::<Pubkey>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: false,
+                },
+            ],
+            &config,
+        )
+        .unwrap();
+
+        let compute_budget = ComputeBudget::default();
+        let mut invoke_context = ThisInvokeContext::new(
+            Rent::default(),
+            &[],
+            &[],
+            &[],
+            None,
+            compute_budget,
+            ThisComputeMeter::new_ref(compute_budget.syscall_base_cost * 3),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+            Hash::default(),
+            0,
+            Some(&epoch_stakes),
+        );
+        let mut syscall = SyscallGetEpochStake {
+            invoke_context: Rc::new(RefCell::new(&mut invoke_context)),
+            loader_id: &bpf_loader::id(),
+        };
+
+        // A null vote address returns the total stake of the epoch
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 1_000);
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(vote_address_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(result.unwrap(), 400);
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(
+            other_vote_address_va,
+            0,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
     #[test]
     fn test_overlapping() {
         assert!(!check_overlapping(10, 7, 3));
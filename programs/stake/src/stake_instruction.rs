@@ -319,6 +319,29 @@ pub fn process_instruction(
                 Err(InstructionError::InvalidInstructionData)
             }
         }
+        StakeInstruction::Redelegate => {
+            if invoke_context.is_feature_active(&feature_set::stake_redelegate_instruction::id()) {
+                let uninitialized_stake =
+                    &keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+                let vote = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+
+                me.redelegate(
+                    invoke_context,
+                    uninitialized_stake,
+                    vote,
+                    &get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?,
+                    &get_sysvar::<StakeHistory>(invoke_context, &sysvar::stake_history::id())?,
+                    &config::from_keyed_account(keyed_account_at_index(
+                        keyed_accounts,
+                        first_instruction_account + 3,
+                    )?)?,
+                    &get_sysvar::<Rent>(invoke_context, &sysvar::rent::id())?,
+                    &signers,
+                )
+            } else {
+                Err(InstructionError::InvalidInstructionData)
+            }
+        }
     }
 }
 
@@ -88,7 +88,7 @@ pub fn meta_from(account: &AccountSharedData) -> Option<Meta> {
     from(account).and_then(|state: StakeState| state.meta())
 }
 
-fn redelegate(
+fn redelegate_stake(
     stake: &mut Stake,
     stake_lamports: u64,
     voter_pubkey: &Pubkey,
@@ -382,6 +382,18 @@ pub trait StakeAccount {
         can_reverse_deactivation: bool,
     ) -> Result<(), InstructionError>;
     fn deactivate(&self, clock: &Clock, signers: &HashSet<Pubkey>) -> Result<(), InstructionError>;
+    #[allow(clippy::too_many_arguments)]
+    fn redelegate(
+        &self,
+        invoke_context: &dyn InvokeContext,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        rent: &Rent,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError>;
     fn set_lockup(
         &self,
         lockup: &LockupArgs,
@@ -539,7 +551,7 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             }
             StakeState::Stake(meta, mut stake) => {
                 meta.authorized.check(signers, StakeAuthorize::Staker)?;
-                redelegate(
+                redelegate_stake(
                     &mut stake,
                     self.lamports()?.saturating_sub(meta.rent_exempt_reserve), // can't stake the rent ;)
                     vote_account.unsigned_key(),
@@ -564,6 +576,109 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             Err(InstructionError::InvalidAccountData)
         }
     }
+    fn redelegate(
+        &self,
+        invoke_context: &dyn InvokeContext,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        rent: &Rent,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError> {
+        if uninitialized_stake.owner()? != id() {
+            ic_msg!(
+                invoke_context,
+                "expected uninitialized stake account owner to be {}, not {}",
+                id(),
+                uninitialized_stake.owner()?
+            );
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if uninitialized_stake.data_len()? != std::mem::size_of::<StakeState>() {
+            ic_msg!(
+                invoke_context,
+                "expected uninitialized stake account data len to be {}, not {}",
+                std::mem::size_of::<StakeState>(),
+                uninitialized_stake.data_len()?
+            );
+            return Err(InstructionError::InvalidAccountData);
+        }
+        if !matches!(uninitialized_stake.state()?, StakeState::Uninitialized) {
+            ic_msg!(
+                invoke_context,
+                "expected uninitialized stake account to be uninitialized"
+            );
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+
+        if vote_account.owner()? != solana_vote_program::id() {
+            ic_msg!(
+                invoke_context,
+                "expected vote account owner to be {}, not {}",
+                solana_vote_program::id(),
+                vote_account.owner()?
+            );
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        let vote_pubkey = vote_account.unsigned_key();
+        let vote_state = State::<VoteStateVersions>::state(vote_account)?.convert_to_current();
+
+        let (meta, effective_stake) = if let StakeState::Stake(meta, stake) = self.state()? {
+            let status = stake
+                .delegation
+                .stake_activating_and_deactivating(clock.epoch, Some(stake_history));
+            if status.effective == 0 || status.activating != 0 || status.deactivating != 0 {
+                ic_msg!(invoke_context, "stake is not fully active");
+                return Err(StakeError::RedelegateTransientOrInactiveStake.into());
+            }
+
+            // Redelegating to the same vote account is pointless, and would needlessly take up
+            // the cluster-wide warmup/cooldown allowance
+            if stake.delegation.voter_pubkey == *vote_pubkey {
+                ic_msg!(
+                    invoke_context,
+                    "stake is already delegated to {}",
+                    vote_pubkey
+                );
+                return Err(StakeError::RedelegateToSameVoteAccount.into());
+            }
+            (meta, status.effective)
+        } else {
+            ic_msg!(invoke_context, "stake account is not delegated");
+            return Err(InstructionError::InvalidAccountData);
+        };
+
+        // Also checks the stake authority signed
+        self.deactivate(clock, signers)?;
+
+        self.try_account_ref_mut()?
+            .checked_sub_lamports(effective_stake)?;
+        uninitialized_stake
+            .try_account_ref_mut()?
+            .checked_add_lamports(effective_stake)?;
+
+        let mut redelegated_meta = meta;
+        redelegated_meta.rent_exempt_reserve =
+            rent.minimum_balance(uninitialized_stake.data_len()?);
+        let redelegated_stake_amount = uninitialized_stake
+            .lamports()?
+            .saturating_sub(redelegated_meta.rent_exempt_reserve);
+        if redelegated_stake_amount == 0 {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        uninitialized_stake.set_state(&StakeState::Stake(
+            redelegated_meta,
+            new_stake(
+                redelegated_stake_amount,
+                vote_pubkey,
+                &vote_state,
+                clock.epoch,
+                config,
+            ),
+        ))
+    }
     fn set_lockup(
         &self,
         lockup: &LockupArgs,
@@ -2527,6 +2642,183 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_redelegate() {
+        let invoke_context = ThisInvokeContext::new_mock(&[], &[]);
+        let rent = Rent::default();
+        let rent_exempt_reserve = rent.minimum_balance(std::mem::size_of::<StakeState>());
+        let clock = Clock {
+            epoch: 2,
+            ..Clock::default()
+        };
+        let stake_history = StakeHistory::default();
+        let config = Config::default();
+
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let stake_lamports = rent_exempt_reserve + 1_000_000;
+        let meta = Meta {
+            rent_exempt_reserve,
+            ..Meta::auto(&stake_pubkey)
+        };
+        let vote_pubkey = solana_sdk::pubkey::new_rand();
+        let stake = Stake {
+            delegation: Delegation::new(
+                &vote_pubkey,
+                stake_lamports - rent_exempt_reserve,
+                0,
+                config.warmup_cooldown_rate,
+            ),
+            credits_observed: 0,
+        };
+        let stake_account = AccountSharedData::new_ref_data_with_space(
+            stake_lamports,
+            &StakeState::Stake(meta, stake),
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("stake_account");
+        let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
+        let signers = vec![stake_pubkey].into_iter().collect();
+
+        let vote_account = RefCell::new(vote_state::create_account(
+            &vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        ));
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &vote_account);
+        let new_vote_pubkey = solana_sdk::pubkey::new_rand();
+        let new_vote_account = RefCell::new(vote_state::create_account(
+            &new_vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        ));
+        let new_vote_keyed_account = KeyedAccount::new(&new_vote_pubkey, false, &new_vote_account);
+
+        let redelegated_stake_pubkey = solana_sdk::pubkey::new_rand();
+        let redelegated_stake_account = AccountSharedData::new_ref_data_with_space(
+            0,
+            &StakeState::Uninitialized,
+            std::mem::size_of::<StakeState>(),
+            &solana_sdk::pubkey::new_rand(),
+        )
+        .expect("redelegated_stake_account");
+        let redelegated_stake_keyed_account =
+            KeyedAccount::new(&redelegated_stake_pubkey, false, &redelegated_stake_account);
+
+        // the redelegated stake account must be owned by the stake program
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &invoke_context,
+                &redelegated_stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &rent,
+                &signers,
+            ),
+            Err(InstructionError::IncorrectProgramId)
+        );
+        redelegated_stake_account.borrow_mut().set_owner(id());
+
+        // redelegating to the same vote account fails
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &invoke_context,
+                &redelegated_stake_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &rent,
+                &signers,
+            ),
+            Err(StakeError::RedelegateToSameVoteAccount.into())
+        );
+
+        // no signers fails
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &invoke_context,
+                &redelegated_stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &rent,
+                &HashSet::default(),
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &invoke_context,
+                &redelegated_stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &rent,
+                &signers,
+            ),
+            Ok(())
+        );
+
+        // the original stake account keeps its rent exempt reserve and starts deactivating
+        assert_eq!(stake_keyed_account.lamports().unwrap(), rent_exempt_reserve);
+        let stake = stake_from(&stake_keyed_account.account.borrow()).unwrap();
+        assert_eq!(stake.delegation.deactivation_epoch, clock.epoch);
+
+        // the redelegated stake account takes over the effective stake and starts activating
+        assert_eq!(
+            redelegated_stake_keyed_account.lamports().unwrap(),
+            stake_lamports - rent_exempt_reserve
+        );
+        if let StakeState::Stake(redelegated_meta, redelegated_stake) =
+            redelegated_stake_keyed_account.state().unwrap()
+        {
+            assert_eq!(redelegated_meta.authorized, meta.authorized);
+            assert_eq!(redelegated_meta.rent_exempt_reserve, rent_exempt_reserve);
+            assert_eq!(redelegated_stake.delegation.voter_pubkey, new_vote_pubkey);
+            assert_eq!(redelegated_stake.delegation.activation_epoch, clock.epoch);
+            assert_eq!(
+                redelegated_stake.delegation.stake,
+                stake_lamports - 2 * rent_exempt_reserve
+            );
+        } else {
+            panic!("redelegated stake account is not delegated");
+        }
+
+        // deactivating stake can't be redelegated again
+        let another_stake_account = AccountSharedData::new_ref_data_with_space(
+            0,
+            &StakeState::Uninitialized,
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("another_stake_account");
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &invoke_context,
+                &KeyedAccount::new(
+                    &solana_sdk::pubkey::new_rand(),
+                    false,
+                    &another_stake_account
+                ),
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &rent,
+                &signers,
+            ),
+            Err(StakeError::RedelegateTransientOrInactiveStake.into())
+        );
+    }
+
     #[test]
     fn test_set_lockup() {
         let stake_pubkey = solana_sdk::pubkey::new_rand();
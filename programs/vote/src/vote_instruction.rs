@@ -9,8 +9,10 @@ use log::*;
 use num_derive::{FromPrimitive, ToPrimitive};
 use serde_derive::{Deserialize, Serialize};
 use solana_metrics::inc_new_counter_info;
+use solana_program_runtime::invoke_context::get_sysvar;
 use solana_sdk::{
     decode_error::DecodeError,
+    epoch_schedule::EpochSchedule,
     feature_set,
     hash::Hash,
     instruction::{AccountMeta, Instruction, InstructionError},
@@ -44,6 +46,12 @@ pub enum VoteError {
 
     #[error("authorized voter has already been changed this epoch")]
     TooSoonToReauthorize,
+
+    #[error("commission increase exceeds the per-epoch cap")]
+    CommissionIncreaseTooLarge,
+
+    #[error("commission can only be increased during the first half of an epoch")]
+    CommissionUpdateTooLate,
 }
 
 impl<E> DecodeError<E> for VoteError {
@@ -355,7 +363,27 @@ pub fn process_instruction(
             &signers,
         ),
         VoteInstruction::UpdateCommission(commission) => {
-            vote_state::update_commission(me, commission, &signers)
+            let commission_bounds = if invoke_context
+                .is_feature_active(&feature_set::vote_commission_increase_cap::id())
+            {
+                Some((
+                    get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?,
+                    get_sysvar::<EpochSchedule>(invoke_context, &sysvar::epoch_schedule::id())?,
+                    invoke_context.get_epoch_start_commission(me.unsigned_key()),
+                ))
+            } else {
+                None
+            };
+            vote_state::update_commission(
+                me,
+                commission,
+                &signers,
+                commission_bounds.as_ref().map(
+                    |(clock, epoch_schedule, epoch_start_commission)| {
+                        (clock, epoch_schedule, *epoch_start_commission)
+                    },
+                ),
+            )
         }
         VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => {
             inc_new_counter_info!("vote-native", 1);
@@ -405,7 +433,9 @@ pub fn process_instruction(
 mod tests {
     use super::*;
     use bincode::serialize;
-    use solana_program_runtime::invoke_context::mock_process_instruction;
+    use solana_program_runtime::invoke_context::{
+        mock_process_instruction, mock_process_instruction_with_sysvars,
+    };
     use solana_sdk::{
         account::{self, Account, AccountSharedData},
         rent::Rent,
@@ -464,11 +494,19 @@ mod tests {
             .zip(accounts.into_iter())
             .map(|(meta, account)| (meta.is_signer, meta.is_writable, meta.pubkey, account))
             .collect();
-        solana_program_runtime::invoke_context::mock_process_instruction(
+        let sysvars = vec![
+            (sysvar::clock::id(), serialize(&Clock::default()).unwrap()),
+            (
+                sysvar::epoch_schedule::id(),
+                serialize(&EpochSchedule::default()).unwrap(),
+            ),
+        ];
+        mock_process_instruction_with_sysvars(
             &id(),
             Vec::new(),
             &instruction.data,
             &keyed_accounts,
+            &sysvars,
             super::process_instruction,
         )
     }
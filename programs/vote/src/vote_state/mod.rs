@@ -9,7 +9,7 @@ use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
     account_utils::State,
     clock::{Epoch, Slot, UnixTimestamp},
-    epoch_schedule::MAX_LEADER_SCHEDULE_EPOCH_OFFSET,
+    epoch_schedule::{EpochSchedule, MAX_LEADER_SCHEDULE_EPOCH_OFFSET},
     hash::Hash,
     instruction::InstructionError,
     keyed_account::KeyedAccount,
@@ -33,6 +33,10 @@ pub const INITIAL_LOCKOUT: usize = 2;
 // Maximum number of credits history to keep around
 pub const MAX_EPOCH_CREDITS_HISTORY: usize = 64;
 
+// Maximum number of percentage points the commission may be raised by within an epoch, once the
+// vote_commission_increase_cap feature is active
+pub const MAX_COMMISSION_INCREASE: u8 = 5;
+
 // Offset of VoteState::prior_voters, for determining initialization status without deserialization
 const DEFAULT_PRIOR_VOTERS_OFFSET: usize = 82;
 
@@ -645,11 +649,41 @@ pub fn update_validator_identity<S: std::hash::BuildHasher>(
     vote_account.set_state(&VoteStateVersions::new_current(vote_state))
 }
 
-/// Update the vote account's commission
+/// Check that a commission change from `current_commission` to `new_commission` at the clock's
+/// slot may be applied. Decreases are always allowed; increases are limited to
+/// MAX_COMMISSION_INCREASE points above `epoch_start_commission` and to the first half of the
+/// epoch, so delegators see them coming well before the epoch's rewards are paid out at the new
+/// rate
+pub fn check_commission_change(
+    current_commission: u8,
+    epoch_start_commission: u8,
+    new_commission: u8,
+    clock: &Clock,
+    epoch_schedule: &EpochSchedule,
+) -> Result<(), VoteError> {
+    if new_commission <= current_commission {
+        return Ok(());
+    }
+    if new_commission.saturating_sub(epoch_start_commission) > MAX_COMMISSION_INCREASE {
+        return Err(VoteError::CommissionIncreaseTooLarge);
+    }
+    let (_epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(clock.slot);
+    let slots_in_epoch = epoch_schedule.get_slots_in_epoch(clock.epoch);
+    if slot_index >= slots_in_epoch / 2 {
+        return Err(VoteError::CommissionUpdateTooLate);
+    }
+    Ok(())
+}
+
+/// Update the vote account's commission. Commission increases are bounded by
+/// `check_commission_change` when `commission_bounds` is provided, along with the commission the
+/// vote account had when the epoch started. Vote accounts created during the epoch have none, and
+/// may only lower their commission until the next epoch.
 pub fn update_commission<S: std::hash::BuildHasher>(
     vote_account: &KeyedAccount,
     commission: u8,
     signers: &HashSet<Pubkey, S>,
+    commission_bounds: Option<(&Clock, &EpochSchedule, Option<u8>)>,
 ) -> Result<(), InstructionError> {
     let mut vote_state: VoteState =
         State::<VoteStateVersions>::state(vote_account)?.convert_to_current();
@@ -657,6 +691,22 @@ pub fn update_commission<S: std::hash::BuildHasher>(
     // current authorized withdrawer must say "yay"
     verify_authorized_signer(&vote_state.authorized_withdrawer, signers)?;
 
+    if let Some((clock, epoch_schedule, epoch_start_commission)) = commission_bounds {
+        match epoch_start_commission {
+            Some(epoch_start_commission) => check_commission_change(
+                vote_state.commission,
+                epoch_start_commission,
+                commission,
+                clock,
+                epoch_schedule,
+            )?,
+            None if commission > vote_state.commission => {
+                return Err(VoteError::CommissionIncreaseTooLarge.into());
+            }
+            None => {}
+        }
+    }
+
     vote_state.commission = commission;
 
     vote_account.set_state(&VoteStateVersions::new_current(vote_state))
@@ -1106,7 +1156,7 @@ mod tests {
             ),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], 42, &signers);
+        let res = update_commission(&keyed_accounts[0], 42, &signers, None);
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
         let keyed_accounts = &[
@@ -1114,7 +1164,7 @@ mod tests {
             KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], 42, &signers);
+        let res = update_commission(&keyed_accounts[0], 42, &signers, None);
         assert_eq!(res, Ok(()));
         let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
             .unwrap()
@@ -1126,7 +1176,7 @@ mod tests {
             KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], u8::MAX, &signers);
+        let res = update_commission(&keyed_accounts[0], u8::MAX, &signers, None);
         assert_eq!(res, Ok(()));
         let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
             .unwrap()
@@ -1134,6 +1184,137 @@ mod tests {
         assert_eq!(vote_state.commission, u8::MAX);
     }
 
+    #[test]
+    fn test_vote_update_commission_bounded() {
+        let (vote_pubkey, _authorized_voter, authorized_withdrawer, vote_account) =
+            create_test_account_with_authorized();
+        let authorized_withdrawer_account = RefCell::new(AccountSharedData::default());
+        let keyed_accounts = &[
+            KeyedAccount::new(&vote_pubkey, true, &vote_account),
+            KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
+        ];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let commission = |vote_account: &RefCell<AccountSharedData>| {
+            StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+                .unwrap()
+                .convert_to_current()
+                .commission
+        };
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let mut clock = Clock {
+            slot: epoch_schedule.get_first_slot_in_epoch(1),
+            epoch: 1,
+            ..Clock::default()
+        };
+        let initial_commission = commission(&vote_account);
+        let bounds = Some((&clock, &epoch_schedule, Some(initial_commission)));
+
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE + 1,
+                &signers,
+                bounds,
+            ),
+            Err(VoteError::CommissionIncreaseTooLarge.into())
+        );
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE,
+                &signers,
+                bounds,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            commission(&vote_account),
+            initial_commission + MAX_COMMISSION_INCREASE
+        );
+
+        // The increases of an epoch add up, relative to the commission it started with
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE - 1,
+                &signers,
+                bounds,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE + 1,
+                &signers,
+                bounds,
+            ),
+            Err(VoteError::CommissionIncreaseTooLarge.into())
+        );
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE,
+                &signers,
+                bounds,
+            ),
+            Ok(())
+        );
+
+        // Without an epoch start commission, the vote account was created during the epoch, and
+        // can't raise its commission at all until the next one
+        let bounds = Some((&clock, &epoch_schedule, None));
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE + 1,
+                &signers,
+                bounds,
+            ),
+            Err(VoteError::CommissionIncreaseTooLarge.into())
+        );
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE - 1,
+                &signers,
+                bounds,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE,
+                &signers,
+                bounds,
+            ),
+            Err(VoteError::CommissionIncreaseTooLarge.into())
+        );
+        assert_eq!(
+            commission(&vote_account),
+            initial_commission + MAX_COMMISSION_INCREASE - 1
+        );
+
+        // Past the middle of the epoch, only decreases go through
+        clock.slot += epoch_schedule.get_slots_in_epoch(1) / 2;
+        let bounds = Some((&clock, &epoch_schedule, Some(initial_commission)));
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                initial_commission + MAX_COMMISSION_INCREASE,
+                &signers,
+                bounds,
+            ),
+            Err(VoteError::CommissionUpdateTooLate.into())
+        );
+        assert_eq!(
+            update_commission(&keyed_accounts[0], initial_commission, &signers, bounds),
+            Ok(())
+        );
+        assert_eq!(commission(&vote_account), initial_commission);
+    }
+
     #[test]
     fn test_vote_signature() {
         let (vote_pubkey, vote_account) = create_test_account();
@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod thread_pools;
+
 use std::env;
 //TODO remove this hack when rayon fixes itself
 
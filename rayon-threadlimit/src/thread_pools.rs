@@ -0,0 +1,326 @@
+//! The `thread_pools` module is the registry of the rayon thread pools whose size can be set on
+//! the command line and changed at runtime. Each pool is owned by the component that builds it,
+//! with the size set for its name or its default size, and registers a handle here so that
+//! setting the size of a name resizes all the live pools of that name. Resizing a pool swaps in a
+//! new pool of the new size: the work already running completes on the old pool, which is dropped
+//! once it is idle.
+
+use {
+    rayon::ThreadPool,
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex, RwLock, Weak,
+        },
+        time::Instant,
+    },
+};
+
+/// Pool executing the transactions of the entries being replayed
+pub const REPLAY_THREAD_POOL: &str = "replay";
+/// Pool verifying the signatures of the packets received by the TPU
+pub const SIGVERIFY_THREAD_POOL: &str = "sigverify";
+/// Pool calculating the accounts hash in the background
+pub const ACCOUNTS_HASH_THREAD_POOL: &str = "accounts-hash";
+/// Pool scanning and shrinking the account storages
+pub const ACCOUNTS_DB_THREAD_POOL: &str = "accounts-db";
+/// Pool cleaning the accounts, with fewer threads to lower its priority
+pub const ACCOUNTS_CLEAN_THREAD_POOL: &str = "accounts-clean";
+/// Pool verifying the hashes of the entries
+pub const ENTRY_THREAD_POOL: &str = "entry";
+/// Pool making and signing the shreds of the entries broadcast
+pub const SHRED_THREAD_POOL: &str = "shred";
+/// Pool verifying the signatures of the shreds received
+pub const SIGVERIFY_SHREDS_THREAD_POOL: &str = "sigverify-shreds";
+/// Pool reading the entries of the slots from the blockstore
+pub const BLOCKSTORE_THREAD_POOL: &str = "blockstore";
+/// Pool reading the entries of the slots purged from the blockstore
+pub const BLOCKSTORE_PURGE_THREAD_POOL: &str = "blockstore-purge";
+/// Pool deserializing the shreds received by the window service
+pub const WINDOW_THREAD_POOL: &str = "window";
+/// Pool retransmitting the shreds received
+pub const RETRANSMIT_THREAD_POOL: &str = "retransmit";
+/// Pool building the gossip requests and purging the gossip table
+pub const GOSSIP_THREAD_POOL: &str = "gossip";
+/// Pool verifying the gossip packets received
+pub const GOSSIP_CONSUME_THREAD_POOL: &str = "gossip-consume";
+/// Pool processing the gossip packets verified
+pub const GOSSIP_LISTEN_THREAD_POOL: &str = "gossip-listen";
+/// Pool activating the stakes and calculating the rewards at the epoch boundaries
+pub const EPOCH_BOUNDARY_THREAD_POOL: &str = "epoch-boundary";
+
+pub const THREAD_POOL_NAMES: [&str; 16] = [
+    REPLAY_THREAD_POOL,
+    SIGVERIFY_THREAD_POOL,
+    ACCOUNTS_HASH_THREAD_POOL,
+    ACCOUNTS_DB_THREAD_POOL,
+    ACCOUNTS_CLEAN_THREAD_POOL,
+    ENTRY_THREAD_POOL,
+    SHRED_THREAD_POOL,
+    SIGVERIFY_SHREDS_THREAD_POOL,
+    BLOCKSTORE_THREAD_POOL,
+    BLOCKSTORE_PURGE_THREAD_POOL,
+    WINDOW_THREAD_POOL,
+    RETRANSMIT_THREAD_POOL,
+    GOSSIP_THREAD_POOL,
+    GOSSIP_CONSUME_THREAD_POOL,
+    GOSSIP_LISTEN_THREAD_POOL,
+    EPOCH_BOUNDARY_THREAD_POOL,
+];
+
+type BuildThreadPool = Box<dyn Fn(usize) -> ThreadPool + Send + Sync>;
+
+lazy_static! {
+    static ref THREAD_POOL_SIZES: Mutex<HashMap<&'static str, usize>> = Mutex::new(HashMap::new());
+    static ref THREAD_POOLS: Mutex<Vec<Weak<ManagedThreadPool>>> = Mutex::new(Vec::new());
+    // Installs and busy time of the pools dropped, so that the usage of a name only ever grows
+    static ref DROPPED_THREAD_POOLS_USAGE: Mutex<HashMap<&'static str, (u64, u64)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Usage of the pools of a name since the first of them was built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadPoolStats {
+    pub name: &'static str,
+    /// Live pools of that name
+    pub num_pools: usize,
+    /// Threads of the live pools
+    pub num_threads: usize,
+    /// Operations installed in the pools
+    pub installs: u64,
+    /// Time spent in the installed operations, which exceeds the elapsed time when several
+    /// operations run in the pools at once
+    pub busy_us: u64,
+}
+
+pub struct ManagedThreadPool {
+    name: &'static str,
+    build: BuildThreadPool,
+    pool: RwLock<Arc<ThreadPool>>,
+    installs: AtomicU64,
+    busy_us: AtomicU64,
+}
+
+impl fmt::Debug for ManagedThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManagedThreadPool")
+            .field("name", &self.name)
+            .field("pool", &self.pool)
+            .finish()
+    }
+}
+
+impl ManagedThreadPool {
+    /// Builds a pool `name` with `build`, with the size set for that name or
+    /// `default_num_threads`, and registers it to be resized along with the other pools of that
+    /// name
+    pub fn new<F>(name: &'static str, default_num_threads: usize, build: F) -> Arc<Self>
+    where
+        F: Fn(usize) -> ThreadPool + Send + Sync + 'static,
+    {
+        let mut thread_pools = THREAD_POOLS.lock().unwrap();
+        let num_threads = THREAD_POOL_SIZES
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or(default_num_threads);
+        let thread_pool = Arc::new(Self {
+            name,
+            pool: RwLock::new(Arc::new(build(num_threads))),
+            build: Box::new(build),
+            installs: AtomicU64::default(),
+            busy_us: AtomicU64::default(),
+        });
+        thread_pools.retain(|thread_pool| thread_pool.strong_count() > 0);
+        thread_pools.push(Arc::downgrade(&thread_pool));
+        thread_pool
+    }
+
+    /// Executes `op` in the pool, see `rayon::ThreadPool::install()`
+    pub fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        let pool = self.current();
+        let start = Instant::now();
+        let result = pool.install(op);
+        self.installs.fetch_add(1, Ordering::Relaxed);
+        self.busy_us
+            .fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+        result
+    }
+
+    /// Returns the pool the operations are installed in, for the callers that need a
+    /// `rayon::ThreadPool`. The work run on it isn't counted in the stats, and it keeps its size
+    /// when the pool is resized, so long-running callers should get it again now and then
+    pub fn current(&self) -> Arc<ThreadPool> {
+        self.pool.read().unwrap().clone()
+    }
+
+    pub fn current_num_threads(&self) -> usize {
+        self.pool.read().unwrap().current_num_threads()
+    }
+
+    fn resize(&self, num_threads: usize) {
+        let pool = Arc::new((self.build)(num_threads));
+        *self.pool.write().unwrap() = pool;
+    }
+}
+
+impl Drop for ManagedThreadPool {
+    fn drop(&mut self) {
+        let mut usage = DROPPED_THREAD_POOLS_USAGE.lock().unwrap();
+        let (installs, busy_us) = usage.entry(self.name).or_default();
+        *installs += self.installs.load(Ordering::Relaxed);
+        *busy_us += self.busy_us.load(Ordering::Relaxed);
+    }
+}
+
+fn thread_pool_name(name: &str) -> Result<&'static str, String> {
+    THREAD_POOL_NAMES
+        .iter()
+        .find(|pool_name| **pool_name == name)
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "unknown thread pool {}, expected one of {}",
+                name,
+                THREAD_POOL_NAMES.join(", ")
+            )
+        })
+}
+
+fn live_thread_pools() -> Vec<Arc<ManagedThreadPool>> {
+    THREAD_POOLS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/// Sets the size of the pools `name`, and resizes the ones built already
+pub fn set_thread_pool_size(name: &str, num_threads: usize) -> Result<(), String> {
+    let name = thread_pool_name(name)?;
+    if num_threads == 0 {
+        return Err(format!("thread pool {} needs at least one thread", name));
+    }
+    THREAD_POOL_SIZES.lock().unwrap().insert(name, num_threads);
+    live_thread_pools()
+        .into_iter()
+        .filter(|thread_pool| thread_pool.name == name)
+        .for_each(|thread_pool| thread_pool.resize(num_threads));
+    Ok(())
+}
+
+/// Parses a `<NAME>=<THREADS>` pool size, as given on the command line
+pub fn parse_thread_pool_size(pool_size: &str) -> Result<(&'static str, usize), String> {
+    let (name, num_threads) = pool_size
+        .split_once('=')
+        .ok_or_else(|| format!("expected <NAME>=<THREADS>, got {}", pool_size))?;
+    let name = thread_pool_name(name)?;
+    let num_threads = num_threads
+        .parse::<usize>()
+        .ok()
+        .filter(|num_threads| *num_threads > 0)
+        .ok_or_else(|| format!("invalid number of threads: {}", num_threads))?;
+    Ok((name, num_threads))
+}
+
+/// Returns the usage of the pools built so far, by name
+pub fn thread_pool_stats() -> Vec<ThreadPoolStats> {
+    // The live pools are held until the usage of the dropped ones is read, so that a pool
+    // dropped meanwhile is counted once
+    let thread_pools = live_thread_pools();
+    let mut stats = BTreeMap::<&'static str, ThreadPoolStats>::new();
+    for (&name, &(installs, busy_us)) in DROPPED_THREAD_POOLS_USAGE.lock().unwrap().iter() {
+        let stats = stats
+            .entry(name)
+            .or_insert_with(|| ThreadPoolStats::new(name));
+        stats.installs += installs;
+        stats.busy_us += busy_us;
+    }
+    for thread_pool in &thread_pools {
+        let stats = stats
+            .entry(thread_pool.name)
+            .or_insert_with(|| ThreadPoolStats::new(thread_pool.name));
+        stats.num_pools += 1;
+        stats.num_threads += thread_pool.current_num_threads();
+        stats.installs += thread_pool.installs.load(Ordering::Relaxed);
+        stats.busy_us += thread_pool.busy_us.load(Ordering::Relaxed);
+    }
+    stats.into_values().collect()
+}
+
+impl ThreadPoolStats {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            num_pools: 0,
+            num_threads: 0,
+            installs: 0,
+            busy_us: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(name: &str) -> ThreadPoolStats {
+        thread_pool_stats()
+            .into_iter()
+            .find(|stats| stats.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_thread_pools() {
+        assert!(parse_thread_pool_size("replay").is_err());
+        assert!(parse_thread_pool_size("replay=0").is_err());
+        assert!(parse_thread_pool_size("unknown=2").is_err());
+        assert_eq!(
+            parse_thread_pool_size("replay=2"),
+            Ok((REPLAY_THREAD_POOL, 2))
+        );
+        assert!(set_thread_pool_size("unknown", 2).is_err());
+
+        // The size set before the pool is built overrides the default size
+        set_thread_pool_size(ACCOUNTS_HASH_THREAD_POOL, 2).unwrap();
+        let build = |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+        };
+        let thread_pool = ManagedThreadPool::new(ACCOUNTS_HASH_THREAD_POOL, 1, build);
+        assert_eq!(thread_pool.install(rayon::current_num_threads), 2);
+
+        // Each owner gets its own pool, and resizing swaps in a new pool in all of them
+        let other_thread_pool = ManagedThreadPool::new(ACCOUNTS_HASH_THREAD_POOL, 1, build);
+        assert!(!Arc::ptr_eq(&thread_pool, &other_thread_pool));
+        set_thread_pool_size(ACCOUNTS_HASH_THREAD_POOL, 3).unwrap();
+        assert_eq!(thread_pool.install(rayon::current_num_threads), 3);
+        assert_eq!(other_thread_pool.install(rayon::current_num_threads), 3);
+
+        let stats_before_drop = stats(ACCOUNTS_HASH_THREAD_POOL);
+        assert_eq!(stats_before_drop.num_pools, 2);
+        assert_eq!(stats_before_drop.num_threads, 6);
+        assert_eq!(stats_before_drop.installs, 3);
+
+        // A dropped pool isn't resized anymore, and its usage is kept
+        drop(other_thread_pool);
+        let stats_after_drop = stats(ACCOUNTS_HASH_THREAD_POOL);
+        assert_eq!(stats_after_drop.num_pools, 1);
+        assert_eq!(stats_after_drop.num_threads, 3);
+        assert_eq!(stats_after_drop.installs, 3);
+        assert!(stats_after_drop.busy_us >= stats_before_drop.busy_us);
+        set_thread_pool_size(ACCOUNTS_HASH_THREAD_POOL, 1).unwrap();
+        assert_eq!(stats(ACCOUNTS_HASH_THREAD_POOL).num_threads, 1);
+    }
+}
@@ -1,5 +1,23 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // compiling protos using path on build time
     tonic_build::configure().compile(&["proto/accountsdb_repl.proto"], &["proto"])?;
+
+    // The reads served to indexers share the account and block messages of the replication and
+    // the bigtable storage. Their code is generated in its own directory, generating it also
+    // writes the modules of the imported packages, which would replace the replication's
+    println!("cargo:rerun-if-changed=../storage-proto/proto/confirmed_block.proto");
+    let rpc_reads_out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?).join("rpc_reads");
+    std::fs::create_dir_all(&rpc_reads_out_dir)?;
+    tonic_build::configure()
+        .out_dir(rpc_reads_out_dir)
+        .extern_path(".accountsdb_repl", "crate::accountsdb_repl_server")
+        .extern_path(
+            ".solana.storage.ConfirmedBlock",
+            "::solana_storage_proto::convert::generated",
+        )
+        .compile(
+            &["proto/rpc_reads.proto"],
+            &["proto", "../storage-proto/proto"],
+        )?;
     Ok(())
 }
@@ -5,3 +5,4 @@ pub mod accountsdb_repl_server;
 pub mod accountsdb_repl_server_factory;
 pub mod replica_accounts_server;
 pub mod replica_confirmed_slots_server;
+pub mod rpc_reads_server;
@@ -0,0 +1,614 @@
+//! A gRPC interface to the most bandwidth-heavy RPC reads: accounts, blocks, transaction
+//! statuses and a stream of slot updates. Accounts and blocks are encoded with the messages of the
+//! AccountsDb replication and of the bigtable storage, so indexers consuming those don't need to
+//! decode the JSON RPC encodings.
+use {
+    crate::accountsdb_repl_server::{ReplicaAccountData, ReplicaAccountInfo, ReplicaAccountMeta},
+    crossbeam_channel::{Receiver, RecvTimeoutError},
+    futures_util::{stream, FutureExt, Stream},
+    log::*,
+    solana_ledger::blockstore::Blockstore,
+    solana_rpc::optimistically_confirmed_bank_tracker::{
+        BankNotification, OptimisticallyConfirmedBank,
+    },
+    solana_runtime::{bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache},
+    solana_sdk::{
+        account::ReadableAccount, clock::Slot, commitment_config, pubkey::Pubkey,
+        signature::Signature,
+    },
+    solana_storage_proto::convert::generated,
+    std::{
+        convert::TryFrom,
+        net::SocketAddr,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+    tokio::{
+        runtime::Runtime,
+        sync::{
+            broadcast::{self, error::RecvError},
+            oneshot::{self, Sender},
+        },
+    },
+    tonic::{self, transport},
+};
+
+include!(concat!(env!("OUT_DIR"), "/rpc_reads/rpc_reads.rs"));
+
+/// Maximum number of accounts or signatures in a single request
+pub const MAX_RPC_READS_REQUEST_ITEMS: usize = 256;
+
+/// Number of slot updates buffered for each subscriber
+const SLOT_UPDATES_CAPACITY: usize = 1024;
+
+impl From<CommitmentLevel> for commitment_config::CommitmentLevel {
+    fn from(commitment: CommitmentLevel) -> Self {
+        match commitment {
+            CommitmentLevel::Processed => Self::Processed,
+            CommitmentLevel::Confirmed => Self::Confirmed,
+            CommitmentLevel::Finalized => Self::Finalized,
+        }
+    }
+}
+
+fn commitment_level(commitment: i32) -> Result<CommitmentLevel, tonic::Status> {
+    CommitmentLevel::from_i32(commitment)
+        .ok_or_else(|| tonic::Status::invalid_argument("Invalid commitment level"))
+}
+
+fn check_request_items(count: usize) -> Result<(), tonic::Status> {
+    if count > MAX_RPC_READS_REQUEST_ITEMS {
+        Err(tonic::Status::invalid_argument(format!(
+            "Too many items requested, the maximum is {}",
+            MAX_RPC_READS_REQUEST_ITEMS
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct RpcReadsServiceConfig {
+    pub worker_threads: usize,
+    pub server_addr: SocketAddr,
+}
+
+#[derive(Clone)]
+struct RpcReadsServer {
+    bank_forks: Arc<RwLock<BankForks>>,
+    block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+    optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+    blockstore: Arc<Blockstore>,
+    slot_updates: broadcast::Sender<SlotUpdate>,
+}
+
+impl RpcReadsServer {
+    fn bank(&self, commitment: CommitmentLevel) -> Arc<Bank> {
+        match commitment {
+            CommitmentLevel::Confirmed => self.optimistically_confirmed_bank.read().unwrap().bank(),
+            // The working bank may still be replaying, the processed bank is the frozen bank the
+            // node last voted on
+            CommitmentLevel::Processed | CommitmentLevel::Finalized => {
+                let slot = self
+                    .block_commitment_cache
+                    .read()
+                    .unwrap()
+                    .slot_with_commitment(commitment.into());
+                let bank_forks = self.bank_forks.read().unwrap();
+                bank_forks
+                    .get(slot)
+                    .cloned()
+                    .unwrap_or_else(|| bank_forks.root_bank())
+            }
+        }
+    }
+
+    fn account(bank: &Bank, pubkey: &Pubkey) -> MaybeAccount {
+        MaybeAccount {
+            account: bank.get_account(pubkey).map(|account| ReplicaAccountInfo {
+                account_meta: Some(ReplicaAccountMeta {
+                    pubkey: pubkey.to_bytes().to_vec(),
+                    lamports: account.lamports(),
+                    owner: account.owner().to_bytes().to_vec(),
+                    executable: account.executable(),
+                    rent_epoch: account.rent_epoch(),
+                }),
+                hash: vec![],
+                data: Some(ReplicaAccountData {
+                    data: account.data().to_vec(),
+                }),
+            }),
+        }
+    }
+
+    fn transaction_status(
+        &self,
+        bank: &Bank,
+        confirmed_bank: &Bank,
+        signature: &Signature,
+        search_transaction_history: bool,
+    ) -> Result<MaybeTransactionStatus, tonic::Status> {
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let confirmation_status = |slot: Slot| {
+            if slot <= highest_confirmed_root {
+                CommitmentLevel::Finalized
+            } else if confirmed_bank.ancestors.contains_key(&slot) {
+                CommitmentLevel::Confirmed
+            } else {
+                CommitmentLevel::Processed
+            }
+        };
+        let err = |result: Result<(), solana_sdk::transaction::TransactionError>| {
+            result.err().map(|err| generated::TransactionError {
+                err: bincode::serialize(&err).expect("transaction error to serialize to bytes"),
+            })
+        };
+
+        if let Some((slot, result)) = bank.get_signature_status_slot(signature) {
+            return Ok(MaybeTransactionStatus {
+                status: Some(TransactionStatus {
+                    slot,
+                    err: err(result),
+                    confirmation_status: confirmation_status(slot) as i32,
+                }),
+            });
+        }
+        if !search_transaction_history {
+            return Ok(MaybeTransactionStatus { status: None });
+        }
+        let status = self
+            .blockstore
+            .get_rooted_transaction_status(*signature)
+            .map_err(|err| tonic::Status::internal(err.to_string()))?
+            .filter(|(slot, _)| *slot <= highest_confirmed_root)
+            .map(|(slot, meta)| TransactionStatus {
+                slot,
+                err: err(meta.status),
+                confirmation_status: CommitmentLevel::Finalized as i32,
+            });
+        Ok(MaybeTransactionStatus { status })
+    }
+
+    /// Runs a request reading the banks or the blockstore on the blocking threads of the runtime,
+    /// so it doesn't hold up the runtime workers serving the other requests
+    async fn run_blocking<T, F>(&self, f: F) -> Result<tonic::Response<T>, tonic::Status>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Self) -> Result<T, tonic::Status> + Send + 'static,
+    {
+        let server = self.clone();
+        tokio::task::spawn_blocking(move || f(&server))
+            .await
+            .map_err(|err| tonic::Status::internal(err.to_string()))?
+            .map(tonic::Response::new)
+    }
+
+    fn read_accounts(
+        &self,
+        request: GetAccountsRequest,
+    ) -> Result<GetAccountsResponse, tonic::Status> {
+        check_request_items(request.pubkeys.len())?;
+        let pubkeys = request
+            .pubkeys
+            .iter()
+            .map(|pubkey| <[u8; 32]>::try_from(pubkey.as_slice()).map(Pubkey::new_from_array))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| tonic::Status::invalid_argument("Invalid pubkey"))?;
+
+        let bank = self.bank(commitment_level(request.commitment)?);
+        let accounts = pubkeys
+            .iter()
+            .map(|pubkey| Self::account(&bank, pubkey))
+            .collect();
+        Ok(GetAccountsResponse {
+            slot: bank.slot(),
+            accounts,
+        })
+    }
+
+    fn read_block(&self, request: GetBlockRequest) -> Result<GetBlockResponse, tonic::Status> {
+        let slot = request.slot;
+        let commitment = commitment_level(request.commitment)?;
+
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let block = if slot <= highest_confirmed_root {
+            self.blockstore.get_rooted_block(slot, true)
+        } else if commitment != CommitmentLevel::Finalized {
+            let confirmed_bank = self.bank(CommitmentLevel::Confirmed);
+            if slot == confirmed_bank.slot() || confirmed_bank.ancestors.contains_key(&slot) {
+                self.blockstore.get_complete_block(slot, true)
+            } else {
+                return Err(tonic::Status::not_found("The block is not confirmed"));
+            }
+        } else {
+            return Err(tonic::Status::not_found("The block is not finalized"));
+        };
+        let block = block.map_err(|err| tonic::Status::not_found(err.to_string()))?;
+        Ok(GetBlockResponse {
+            block: Some(block.into()),
+        })
+    }
+
+    fn read_transaction_statuses(
+        &self,
+        request: GetTransactionStatusesRequest,
+    ) -> Result<GetTransactionStatusesResponse, tonic::Status> {
+        check_request_items(request.signatures.len())?;
+        let signatures = request
+            .signatures
+            .iter()
+            .map(|signature| {
+                <[u8; 64]>::try_from(signature.as_slice())
+                    .map(|signature| Signature::new(&signature))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| tonic::Status::invalid_argument("Invalid signature"))?;
+
+        let bank = self.bank(commitment_level(request.commitment)?);
+        let confirmed_bank = self.bank(CommitmentLevel::Confirmed);
+        let statuses = signatures
+            .iter()
+            .map(|signature| {
+                self.transaction_status(
+                    &bank,
+                    &confirmed_bank,
+                    signature,
+                    request.search_transaction_history,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(GetTransactionStatusesResponse {
+            slot: bank.slot(),
+            statuses,
+        })
+    }
+}
+
+/// Implementing the RpcReads interface declared by the protocol
+#[tonic::async_trait]
+impl rpc_reads_server::RpcReads for RpcReadsServer {
+    async fn get_accounts(
+        &self,
+        request: tonic::Request<GetAccountsRequest>,
+    ) -> Result<tonic::Response<GetAccountsResponse>, tonic::Status> {
+        let request = request.into_inner();
+        self.run_blocking(move |server| server.read_accounts(request))
+            .await
+    }
+
+    async fn get_block(
+        &self,
+        request: tonic::Request<GetBlockRequest>,
+    ) -> Result<tonic::Response<GetBlockResponse>, tonic::Status> {
+        let request = request.into_inner();
+        self.run_blocking(move |server| server.read_block(request))
+            .await
+    }
+
+    async fn get_transaction_statuses(
+        &self,
+        request: tonic::Request<GetTransactionStatusesRequest>,
+    ) -> Result<tonic::Response<GetTransactionStatusesResponse>, tonic::Status> {
+        let request = request.into_inner();
+        self.run_blocking(move |server| server.read_transaction_statuses(request))
+            .await
+    }
+
+    type subscribe_slotsStream =
+        Pin<Box<dyn Stream<Item = Result<SlotUpdate, tonic::Status>> + Send + 'static>>;
+
+    async fn subscribe_slots(
+        &self,
+        _request: tonic::Request<SubscribeSlotsRequest>,
+    ) -> Result<tonic::Response<Self::subscribe_slotsStream>, tonic::Status> {
+        let slot_updates =
+            stream::unfold(Some(self.slot_updates.subscribe()), |receiver| async move {
+                let mut receiver = receiver?;
+                match receiver.recv().await {
+                    Ok(slot_update) => Some((Ok(slot_update), Some(receiver))),
+                    // The subscriber is too slow, end the stream with an error
+                    Err(RecvError::Lagged(skipped)) => Some((
+                        Err(tonic::Status::data_loss(format!(
+                            "Skipped {} slot updates",
+                            skipped
+                        ))),
+                        None,
+                    )),
+                    Err(RecvError::Closed) => None,
+                }
+            });
+        Ok(tonic::Response::new(Box::pin(slot_updates)))
+    }
+}
+
+/// The service running the RpcReadsServer in the tokio runtime, and publishing the slot updates
+/// received from the OptimisticallyConfirmedBankTracker
+pub struct RpcReadsService {
+    thread: JoinHandle<()>,
+    slot_updates_thread: JoinHandle<()>,
+    exit: Arc<AtomicBool>,
+    exit_signal_sender: Sender<()>,
+}
+
+impl RpcReadsService {
+    pub fn new(
+        config: RpcReadsServiceConfig,
+        bank_notification_receiver: Receiver<BankNotification>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+        optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+        blockstore: Arc<Blockstore>,
+    ) -> Self {
+        let (slot_updates, _) = broadcast::channel(SLOT_UPDATES_CAPACITY);
+        let exit = Arc::new(AtomicBool::new(false));
+        let slot_updates_thread = Self::run_slot_updates(
+            bank_notification_receiver,
+            bank_forks.clone(),
+            slot_updates.clone(),
+            exit.clone(),
+        );
+
+        let server = RpcReadsServer {
+            bank_forks,
+            block_commitment_cache,
+            optimistically_confirmed_bank,
+            blockstore,
+            slot_updates,
+        };
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(config.worker_threads)
+            .thread_name("sol-rpc-reads-wrk")
+            .enable_all()
+            .build()
+            .expect("Runtime");
+        let (exit_signal_sender, exit_signal_receiver) = oneshot::channel::<()>();
+
+        let thread = Builder::new()
+            .name("sol-rpc-reads-rt".to_string())
+            .spawn(move || {
+                Self::run_rpc_reads_server_in_runtime(config, runtime, server, exit_signal_receiver)
+            })
+            .unwrap();
+
+        Self {
+            thread,
+            slot_updates_thread,
+            exit,
+            exit_signal_sender,
+        }
+    }
+
+    fn run_slot_updates(
+        bank_notification_receiver: Receiver<BankNotification>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        slot_updates: broadcast::Sender<SlotUpdate>,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("sol-rpc-reads-slots".to_string())
+            .spawn(move || {
+                let mut last_root = bank_forks.read().unwrap().root();
+                while !exit.load(Ordering::Relaxed) {
+                    let slot_update =
+                        match bank_notification_receiver.recv_timeout(Duration::from_secs(1)) {
+                            Ok(BankNotification::OptimisticallyConfirmed(slot)) => {
+                                let parent = bank_forks
+                                    .read()
+                                    .unwrap()
+                                    .get(slot)
+                                    .map(|bank| bank.parent_slot());
+                                SlotUpdate {
+                                    slot,
+                                    parent: parent.unwrap_or_default(),
+                                    status: CommitmentLevel::Confirmed as i32,
+                                }
+                            }
+                            Ok(BankNotification::Root(bank)) => {
+                                if bank.slot() <= last_root {
+                                    continue;
+                                }
+                                last_root = bank.slot();
+                                SlotUpdate {
+                                    slot: bank.slot(),
+                                    parent: bank.parent_slot(),
+                                    status: CommitmentLevel::Finalized as i32,
+                                }
+                            }
+                            Ok(BankNotification::Frozen(_)) | Err(RecvTimeoutError::Timeout) => {
+                                continue
+                            }
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        };
+                    // Sending fails when there are no subscribers
+                    let _ = slot_updates.send(slot_update);
+                }
+            })
+            .unwrap()
+    }
+
+    async fn run_rpc_reads_server(
+        config: RpcReadsServiceConfig,
+        server: RpcReadsServer,
+        exit_signal: oneshot::Receiver<()>,
+    ) -> Result<(), tonic::transport::Error> {
+        info!(
+            "Running RpcReadsServer at the endpoint: {:?}",
+            config.server_addr
+        );
+        transport::Server::builder()
+            .add_service(rpc_reads_server::RpcReadsServer::new(server))
+            .serve_with_shutdown(config.server_addr, exit_signal.map(drop))
+            .await
+    }
+
+    fn run_rpc_reads_server_in_runtime(
+        config: RpcReadsServiceConfig,
+        runtime: Runtime,
+        server: RpcReadsServer,
+        exit_signal: oneshot::Receiver<()>,
+    ) {
+        let result = runtime.block_on(Self::run_rpc_reads_server(config, server, exit_signal));
+        match result {
+            Ok(_) => {
+                info!("RpcReadsServer finished");
+            }
+            Err(err) => {
+                error!("RpcReadsServer finished in error: {:?}", err);
+            }
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.exit.store(true, Ordering::Relaxed);
+        let _ = self.exit_signal_sender.send(());
+        self.slot_updates_thread.join()?;
+        self.thread.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crossbeam_channel::unbounded,
+        rpc_reads_server::RpcReads,
+        solana_ledger::get_tmp_ledger_path,
+        solana_runtime::commitment::{BlockCommitmentCache, CommitmentSlots},
+        solana_sdk::{genesis_config::create_genesis_config, system_transaction},
+        std::collections::HashMap,
+    };
+
+    fn new_server(bank: Arc<Bank>, blockstore: Arc<Blockstore>) -> RpcReadsServer {
+        let bank_forks = Arc::new(RwLock::new(BankForks::new_from_banks(
+            &[bank.clone()],
+            bank.slot(),
+        )));
+        RpcReadsServer {
+            bank_forks,
+            block_commitment_cache: Arc::new(RwLock::new(BlockCommitmentCache::new(
+                HashMap::new(),
+                0,
+                CommitmentSlots::new_from_slot(bank.slot()),
+            ))),
+            optimistically_confirmed_bank: Arc::new(RwLock::new(OptimisticallyConfirmedBank::new(
+                bank,
+            ))),
+            blockstore,
+            slot_updates: broadcast::channel(SLOT_UPDATES_CAPACITY).0,
+        }
+    }
+
+    #[test]
+    fn test_get_accounts_and_transaction_statuses() {
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let recipient = solana_sdk::pubkey::new_rand();
+        let tx = system_transaction::transfer(&mint_keypair, &recipient, 42, bank.last_blockhash());
+        bank.process_transaction(&tx).unwrap();
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let server = new_server(bank.clone(), blockstore);
+        let runtime = Runtime::new().unwrap();
+
+        let response = runtime
+            .block_on(server.get_accounts(tonic::Request::new(GetAccountsRequest {
+                pubkeys: vec![
+                    recipient.to_bytes().to_vec(),
+                    solana_sdk::pubkey::new_rand().to_bytes().to_vec(),
+                ],
+                commitment: CommitmentLevel::Processed as i32,
+            })))
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.slot, bank.slot());
+        let account = response.accounts[0].account.as_ref().unwrap();
+        assert_eq!(account.account_meta.as_ref().unwrap().lamports, 42);
+        assert!(response.accounts[1].account.is_none());
+
+        let response =
+            runtime.block_on(server.get_accounts(tonic::Request::new(GetAccountsRequest {
+                pubkeys: vec![vec![1, 2, 3]],
+                commitment: CommitmentLevel::Processed as i32,
+            })));
+        assert_eq!(response.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let response = runtime
+            .block_on(server.get_transaction_statuses(tonic::Request::new(
+                GetTransactionStatusesRequest {
+                    signatures: vec![
+                        tx.signatures[0].as_ref().to_vec(),
+                        Signature::new(&[1; 64]).as_ref().to_vec(),
+                    ],
+                    commitment: CommitmentLevel::Processed as i32,
+                    search_transaction_history: true,
+                },
+            )))
+            .unwrap()
+            .into_inner();
+        let status = response.statuses[0].status.as_ref().unwrap();
+        assert_eq!(status.slot, bank.slot());
+        assert!(status.err.is_none());
+        assert_eq!(
+            status.confirmation_status,
+            CommitmentLevel::Finalized as i32
+        );
+        assert!(response.statuses[1].status.is_none());
+    }
+
+    #[test]
+    fn test_slot_updates() {
+        let (genesis_config, _) = create_genesis_config(1_000_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new_from_banks(&[bank.clone()], 0)));
+        let bank1 = bank_forks.write().unwrap().insert(bank1);
+
+        let (sender, receiver) = unbounded();
+        let (slot_updates, mut slot_updates_receiver) = broadcast::channel(SLOT_UPDATES_CAPACITY);
+        let exit = Arc::new(AtomicBool::new(false));
+        let thread =
+            RpcReadsService::run_slot_updates(receiver, bank_forks, slot_updates, exit.clone());
+        sender
+            .send(BankNotification::OptimisticallyConfirmed(1))
+            .unwrap();
+
+        let runtime = Runtime::new().unwrap();
+        let slot_update = runtime.block_on(slot_updates_receiver.recv()).unwrap();
+        assert_eq!(
+            slot_update,
+            SlotUpdate {
+                slot: 1,
+                parent: 0,
+                status: CommitmentLevel::Confirmed as i32,
+            }
+        );
+
+        // Roots are notified once, in order
+        sender.send(BankNotification::Root(bank)).unwrap();
+        sender.send(BankNotification::Root(bank1)).unwrap();
+        let slot_update = runtime.block_on(slot_updates_receiver.recv()).unwrap();
+        assert_eq!(
+            slot_update,
+            SlotUpdate {
+                slot: 1,
+                parent: 0,
+                status: CommitmentLevel::Finalized as i32,
+            }
+        );
+        exit.store(true, Ordering::Relaxed);
+        thread.join().unwrap();
+    }
+}
@@ -114,6 +114,7 @@ fn test_rpc_send_tx() {
         encoding: Some(UiAccountEncoding::Base64),
         commitment: None,
         data_slice: None,
+        min_context_slot: None,
     };
     let req = json_req!(
         "getAccountInfo",
@@ -297,10 +298,13 @@ fn test_rpc_subscriptions() {
             let mut client_sub = client
                 .account_subscribe(
                     pubkey,
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
-                    }),
+                    Some(
+                        RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        }
+                        .into(),
+                    ),
                 )
                 .unwrap_or_else(|err| panic!("acct sub err: {:#?}", err));
             tokio::spawn(async move {
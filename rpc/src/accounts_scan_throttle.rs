@@ -0,0 +1,144 @@
+//! The `accounts_scan_throttle` module runs the scans of the accounts database made for RPC
+//! requests, such as getProgramAccounts and getLargestAccounts, on a thread pool of their own. Only
+//! a few scans run at once, the others wait in a queue for a limited time, and a scan is aborted
+//! once it exceeds its time budget or its budget of bytes read from the account storages, or once
+//! its request is dropped because the client disconnected. Otherwise, RPC load could starve replay of the accounts index and storage.
+
+use {
+    rayon::{ThreadPool, ThreadPoolBuilder},
+    solana_runtime::accounts_index::ScanConfig,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    },
+    tokio::sync::oneshot,
+};
+
+/// Number of accounts scans running at once by default
+pub const DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS: usize = 4;
+/// Time an accounts scan waits for a thread by default, before it's rejected
+pub const DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct AccountsScanThrottle {
+    thread_pool: ThreadPool,
+    queue_timeout: Duration,
+    max_scan_duration: Option<Duration>,
+    max_scan_bytes: Option<usize>,
+}
+
+// Aborts the scan of a request once the request is dropped, completed or not
+struct AbortOnDrop(Arc<AtomicBool>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Default for AccountsScanThrottle {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS,
+            DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT,
+            None,
+            None,
+        )
+    }
+}
+
+impl AccountsScanThrottle {
+    /// Runs up to `max_concurrent_scans` scans at once, aborting those running for longer than
+    /// `max_scan_duration` or reading more than `max_scan_bytes` from the account storages, if any
+    pub fn new(
+        max_concurrent_scans: usize,
+        queue_timeout: Duration,
+        max_scan_duration: Option<Duration>,
+        max_scan_bytes: Option<usize>,
+    ) -> Self {
+        Self {
+            thread_pool: ThreadPoolBuilder::new()
+                .num_threads(max_concurrent_scans.max(1))
+                .thread_name(|i| format!("sol-rpc-scan-{}", i))
+                .build()
+                .unwrap(),
+            queue_timeout,
+            max_scan_duration,
+            max_scan_bytes,
+        }
+    }
+
+    /// Runs `scan` once a scan thread is free, passing it the config to scan the accounts with.
+    /// Returns `None` if the scan waited for a thread past the queue timeout.
+    pub async fn run<T: Send + 'static>(
+        &self,
+        scan: impl FnOnce(&ScanConfig) -> T + Send + 'static,
+    ) -> Option<T> {
+        let abort = Arc::new(AtomicBool::new(false));
+        let _abort_on_drop = AbortOnDrop(abort.clone());
+        let (sender, receiver) = oneshot::channel();
+        let queued = Instant::now();
+        let queue_timeout = self.queue_timeout;
+        let max_scan_duration = self.max_scan_duration;
+        let max_scan_bytes = self.max_scan_bytes;
+        self.thread_pool.spawn(move || {
+            // The request may have been dropped while the scan was queued
+            if abort.load(Ordering::Relaxed) {
+                return;
+            }
+            if queued.elapsed() > queue_timeout {
+                inc_new_counter_info!("rpc-accounts-scan-queue-timeout", 1);
+                return;
+            }
+            let mut config = ScanConfig::new_with_abort(abort);
+            config.deadline = max_scan_duration.map(|duration| Instant::now() + duration);
+            config.max_bytes_loaded = max_scan_bytes;
+            let _ = sender.send(scan(&config));
+        });
+        receiver.await.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::runtime::Runtime};
+
+    #[test]
+    fn test_accounts_scan_throttle() {
+        let runtime = Runtime::new().unwrap();
+        let throttle = AccountsScanThrottle::new(1, Duration::from_secs(10), None, None);
+        assert_eq!(
+            runtime.block_on(throttle.run(|config| config.is_aborted())),
+            Some(false)
+        );
+
+        // The scans exceeding their time budget are aborted
+        let throttle =
+            AccountsScanThrottle::new(1, Duration::from_secs(10), Some(Duration::ZERO), None);
+        assert_eq!(
+            runtime.block_on(throttle.run(|config| config.is_aborted())),
+            Some(true)
+        );
+
+        // The scans exceeding their budget of bytes read are aborted
+        let throttle = AccountsScanThrottle::new(1, Duration::from_secs(10), None, Some(100));
+        assert_eq!(
+            runtime.block_on(throttle.run(|config| {
+                config.add_bytes_loaded(100);
+                let within_budget = !config.is_aborted();
+                config.add_bytes_loaded(1);
+                (within_budget, config.is_aborted())
+            })),
+            Some((true, true))
+        );
+
+        // The scans queued for longer than the timeout are rejected
+        let throttle = AccountsScanThrottle::new(1, Duration::ZERO, None, None);
+        throttle
+            .thread_pool
+            .spawn(|| std::thread::sleep(Duration::from_millis(10)));
+        assert_eq!(runtime.block_on(throttle.run(|_| ())), None);
+    }
+}
@@ -1,16 +1,21 @@
 #![allow(clippy::integer_arithmetic)]
+pub mod accounts_scan_throttle;
 mod cluster_tpu_info;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
 pub mod rpc;
 pub mod rpc_completed_slots_service;
+pub mod rpc_compression;
 pub mod rpc_health;
 pub mod rpc_pubsub;
 pub mod rpc_pubsub_service;
 pub mod rpc_service;
 pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
+pub mod slow_query_log;
+pub mod transaction_history_search;
+pub mod transaction_logs_bus;
 pub mod transaction_status_service;
 
 #[macro_use]
@@ -19,15 +19,42 @@ use {
     },
 };
 
+/// The most recent optimistically confirmed bank, which the reads at `confirmed` commitment are
+/// served from. Past the initial bank, it only ever moves forward to frozen banks, so a snapshot
+/// taken with `bank()` never changes under a reader, whatever replay does meanwhile.
 pub struct OptimisticallyConfirmedBank {
-    pub bank: Arc<Bank>,
+    bank: Arc<Bank>,
 }
 
 impl OptimisticallyConfirmedBank {
+    pub fn new(bank: Arc<Bank>) -> Self {
+        Self { bank }
+    }
+
     pub fn locked_from_bank_forks_root(bank_forks: &Arc<RwLock<BankForks>>) -> Arc<RwLock<Self>> {
-        Arc::new(RwLock::new(Self {
-            bank: bank_forks.read().unwrap().root_bank(),
-        }))
+        Arc::new(RwLock::new(Self::new(
+            bank_forks.read().unwrap().root_bank(),
+        )))
+    }
+
+    /// Immutable snapshot of the optimistically confirmed state, to serve all the reads of a
+    /// request from
+    pub fn bank(&self) -> Arc<Bank> {
+        self.bank.clone()
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.bank.slot()
+    }
+
+    /// Moves to `bank` if it is frozen and more recent, returning whether it did
+    pub fn update(&mut self, bank: Arc<Bank>) -> bool {
+        if bank.is_frozen() && bank.slot() > self.bank.slot() {
+            self.bank = bank;
+            true
+        } else {
+            false
+        }
     }
 }
 
@@ -208,10 +235,7 @@ impl OptimisticallyConfirmedBankTracker {
                     let mut w_optimistically_confirmed_bank =
                         optimistically_confirmed_bank.write().unwrap();
 
-                    if bank.slot() > w_optimistically_confirmed_bank.bank.slot() && bank.is_frozen()
-                    {
-                        w_optimistically_confirmed_bank.bank = bank.clone();
-                    }
+                    w_optimistically_confirmed_bank.update(bank.clone());
 
                     if slot > *highest_confirmed_slot {
                         Self::notify_or_defer_confirmed_banks(
@@ -241,6 +265,7 @@ impl OptimisticallyConfirmedBankTracker {
             }
             BankNotification::Frozen(bank) => {
                 let frozen_slot = bank.slot();
+                subscriptions.notify_frozen(frozen_slot);
                 if let Some(parent) = bank.parent() {
                     let num_successful_transactions = bank
                         .transaction_count()
@@ -278,12 +303,7 @@ impl OptimisticallyConfirmedBankTracker {
                         bank_notification_subscribers,
                     );
 
-                    let mut w_optimistically_confirmed_bank =
-                        optimistically_confirmed_bank.write().unwrap();
-                    if frozen_slot > w_optimistically_confirmed_bank.bank.slot() {
-                        w_optimistically_confirmed_bank.bank = bank;
-                    }
-                    drop(w_optimistically_confirmed_bank);
+                    optimistically_confirmed_bank.write().unwrap().update(bank);
                 }
             }
             BankNotification::Root(bank) => {
@@ -292,12 +312,7 @@ impl OptimisticallyConfirmedBankTracker {
                     BankNotification::Root(bank.clone()),
                 );
                 let root_slot = bank.slot();
-                let mut w_optimistically_confirmed_bank =
-                    optimistically_confirmed_bank.write().unwrap();
-                if root_slot > w_optimistically_confirmed_bank.bank.slot() {
-                    w_optimistically_confirmed_bank.bank = bank;
-                }
-                drop(w_optimistically_confirmed_bank);
+                optimistically_confirmed_bank.write().unwrap().update(bank);
                 pending_optimistically_confirmed_banks.retain(|&s| s > root_slot);
             }
         }
@@ -323,6 +338,28 @@ mod tests {
         solana_sdk::pubkey::Pubkey,
     };
 
+    #[test]
+    fn test_optimistically_confirmed_bank_update() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let mut optimistically_confirmed_bank = OptimisticallyConfirmedBank::new(bank0.clone());
+
+        // Banks still being replayed are never served
+        assert!(!optimistically_confirmed_bank.update(bank1.clone()));
+        assert_eq!(optimistically_confirmed_bank.slot(), 0);
+
+        bank1.freeze();
+        let snapshot = optimistically_confirmed_bank.bank();
+        assert!(optimistically_confirmed_bank.update(bank1));
+        assert_eq!(optimistically_confirmed_bank.slot(), 1);
+        assert_eq!(snapshot.slot(), 0);
+
+        // Nor older banks
+        assert!(!optimistically_confirmed_bank.update(bank0));
+        assert_eq!(optimistically_confirmed_bank.slot(), 1);
+    }
+
     #[test]
     fn test_process_notification() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -351,7 +388,7 @@ mod tests {
         ));
         let mut pending_optimistically_confirmed_banks = HashSet::new();
 
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 0);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 0);
 
         let mut highest_confirmed_slot: Slot = 0;
         let mut last_notified_confirmed_slot: Slot = 0;
@@ -365,7 +402,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 2);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 2);
         assert_eq!(highest_confirmed_slot, 2);
 
         // Test max optimistically confirmed bank remains in the cache
@@ -379,7 +416,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 2);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 2);
         assert_eq!(highest_confirmed_slot, 2);
 
         // Test bank will only be cached when frozen
@@ -393,7 +430,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 2);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 2);
         assert_eq!(pending_optimistically_confirmed_banks.len(), 1);
         assert!(pending_optimistically_confirmed_banks.contains(&3));
         assert_eq!(highest_confirmed_slot, 3);
@@ -412,7 +449,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 3);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 3);
         assert_eq!(highest_confirmed_slot, 3);
         assert_eq!(pending_optimistically_confirmed_banks.len(), 0);
 
@@ -430,7 +467,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 3);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 3);
         assert_eq!(pending_optimistically_confirmed_banks.len(), 1);
         assert!(pending_optimistically_confirmed_banks.contains(&4));
         assert_eq!(highest_confirmed_slot, 4);
@@ -439,6 +476,8 @@ mod tests {
         let bank5 = Bank::new_from_parent(&bank4, &Pubkey::default(), 5);
         bank_forks.write().unwrap().insert(bank5);
         let bank5 = bank_forks.read().unwrap().get(5).unwrap().clone();
+        // Roots are always frozen
+        bank5.freeze();
         OptimisticallyConfirmedBankTracker::process_notification(
             BankNotification::Root(bank5),
             &bank_forks,
@@ -449,7 +488,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 5);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 5);
         assert_eq!(pending_optimistically_confirmed_banks.len(), 0);
         assert!(!pending_optimistically_confirmed_banks.contains(&4));
         assert_eq!(highest_confirmed_slot, 4);
@@ -475,7 +514,7 @@ mod tests {
             &mut highest_confirmed_slot,
             &None,
         );
-        assert_eq!(optimistically_confirmed_bank.read().unwrap().bank.slot(), 5);
+        assert_eq!(optimistically_confirmed_bank.read().unwrap().slot(), 5);
         assert_eq!(pending_optimistically_confirmed_banks.len(), 0);
         assert!(!pending_optimistically_confirmed_banks.contains(&6));
         assert_eq!(highest_confirmed_slot, 4);
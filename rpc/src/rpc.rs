@@ -2,16 +2,27 @@
 
 use {
     crate::{
-        max_slots::MaxSlots, optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_health::*,
+        accounts_scan_throttle::{
+            AccountsScanThrottle, DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT,
+            DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS,
+        },
+        max_slots::MaxSlots,
+        optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        parsed_token_accounts::*,
+        rpc_health::*,
+        slow_query_log::SlowQueryLog,
+        transaction_history_search::{
+            TransactionHistorySearch, DEFAULT_TRANSACTION_HISTORY_SEARCH_THREADS,
+        },
     },
     bincode::{config::Options, serialize},
     jsonrpc_core::{futures::future, types::error, BoxFuture, Error, Metadata, Result},
     jsonrpc_derive::rpc,
+    rayon::prelude::*,
     serde::{Deserialize, Serialize},
     solana_account_decoder::{
         parse_token::{spl_token_id_v2_0, token_amount_to_ui_amount, UiTokenAmount},
-        UiAccount, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
+        UiAccount, UiAccountCompression, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
     },
     solana_client::{
         rpc_cache::LargestAccountsCache,
@@ -23,7 +34,8 @@ use {
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
             MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
-            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SIGNATURE_STATUSES_RECENT_QUERY_ITEMS,
+            MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS, MAX_SIMULATE_BUNDLE_TRANSACTIONS,
             NUM_LARGEST_ACCOUNTS,
         },
         rpc_response::Response as RpcResponse,
@@ -35,11 +47,11 @@ use {
         blockstore::Blockstore, blockstore_db::BlockstoreError, get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
     },
-    solana_metrics::inc_new_counter_info,
+    solana_metrics::{inc_new_counter_info, transaction_tracing::TransactionSpan},
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_runtime::{
         accounts::AccountAddressFilter,
-        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey},
+        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult},
         bank::{Bank, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
@@ -51,7 +63,10 @@ use {
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         account_utils::StateMut,
-        clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
+        clock::{
+            Slot, UnixTimestamp, MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES,
+            MAX_TRANSACTION_FORWARDING_DELAY,
+        },
         commitment_config::{CommitmentConfig, CommitmentLevel},
         epoch_info::EpochInfo,
         epoch_schedule::EpochSchedule,
@@ -60,11 +75,13 @@ use {
         fee_calculator::FeeCalculator,
         hash::Hash,
         message::{Message, SanitizedMessage},
+        nonce_account,
         pubkey::{Pubkey, PUBKEY_BYTES},
         signature::{Keypair, Signature, Signer},
+        slot_time::SlotTimeEstimator,
         stake::state::{StakeActivationStatus, StakeState},
         stake_history::StakeHistory,
-        system_instruction,
+        system_instruction, system_program,
         sysvar::stake_history,
         transaction::{self, SanitizedTransaction, TransactionError, VersionedTransaction},
     },
@@ -94,7 +111,7 @@ use {
             mpsc::{channel, Receiver, Sender},
             Arc, Mutex, RwLock,
         },
-        time::Duration,
+        time::{Duration, Instant},
     },
 };
 
@@ -102,11 +119,16 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_PAYLOAD_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+/// Number of the recent performance samples, one a minute, averaged into the slot time estimates
+const SLOT_TIME_ESTIMATE_SAMPLES: usize = 60;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
 const MAX_RPC_EPOCH_CREDITS_HISTORY: usize = 5;
 
+// Signature statuses are looked up in parallel from this many signatures
+const MIN_PARALLEL_SIGNATURE_STATUSES: usize = 64;
+
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     let context = RpcResponseContext { slot: bank.slot() };
     Response { context, value }
@@ -122,6 +144,15 @@ pub enum OptionalContext<T> {
     NoContext(T),
 }
 
+/// Value of the `getMultipleAccounts` responses, which only report the status of the accounts when
+/// the request opts into it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcMultipleAccountsResult {
+    Accounts(Vec<Option<UiAccount>>),
+    WithStatus(RpcMultipleAccounts),
+}
+
 fn is_finalized(
     block_commitment_cache: &BlockCommitmentCache,
     bank: &Bank,
@@ -136,18 +167,42 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_cpi_and_log_storage: bool,
+    /// Log messages stored per transaction are truncated past this many bytes, unlimited if unset
+    pub max_stored_log_messages_bytes: Option<usize>,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub enable_bigtable_ledger_storage: bool,
     pub enable_bigtable_ledger_upload: bool,
+    /// Upload protobuf cells to BigTable with the versioned framing
+    pub bigtable_versioned_protobuf: bool,
     pub max_multiple_accounts: Option<usize>,
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
     pub rpc_niceness_adj: i8,
     pub rpc_bigtable_timeout: Option<Duration>,
     pub minimal_api: bool,
+    /// Only expose the minimal API and serve the snapshots, without sending transactions nor
+    /// serving the supply REST endpoints
+    pub snapshot_only: bool,
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
+    pub slow_query_log: Option<Arc<SlowQueryLog>>,
+    /// Compress the responses of at least this many bytes for the clients accepting it
+    pub response_compression_min_bytes: Option<usize>,
+    /// Threads searching the transaction history for signature statuses
+    pub transaction_history_search_threads: Option<usize>,
+    /// Signature statuses that can be searched in the transaction history per second, unlimited
+    /// if unset
+    pub max_transaction_history_searches_per_second: Option<u64>,
+    /// Accounts scans of getProgramAccounts and getLargestAccounts running at once
+    pub max_concurrent_accounts_scans: Option<usize>,
+    /// Time an accounts scan waits for the scans running before it's rejected
+    pub accounts_scan_queue_timeout: Option<Duration>,
+    /// Time after which an accounts scan is aborted, unlimited if unset
+    pub max_accounts_scan_duration: Option<Duration>,
+    /// Bytes read from the account storages after which an accounts scan is aborted, unlimited
+    /// if unset
+    pub max_accounts_scan_bytes: Option<usize>,
 }
 
 #[derive(Clone)]
@@ -169,6 +224,10 @@ pub struct JsonRpcRequestProcessor {
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    transaction_history_search: Arc<TransactionHistorySearch>,
+    accounts_scan_throttle: Arc<AccountsScanThrottle>,
+    /// When the HTTP request of the current call was received, to measure its time in queue
+    request_received: Option<Instant>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -176,21 +235,16 @@ impl JsonRpcRequestProcessor {
     #[allow(deprecated)]
     fn bank(&self, commitment: Option<CommitmentConfig>) -> Arc<Bank> {
         debug!("RPC commitment_config: {:?}", commitment);
-        let r_bank_forks = self.bank_forks.read().unwrap();
-
         let commitment = commitment.unwrap_or_default();
 
         if commitment.is_confirmed() {
-            let bank = self
-                .optimistically_confirmed_bank
-                .read()
-                .unwrap()
-                .bank
-                .clone();
+            let bank = self.optimistically_confirmed_bank.read().unwrap().bank();
             debug!("RPC using optimistically confirmed slot: {:?}", bank.slot());
             return bank;
         }
 
+        let r_bank_forks = self.bank_forks.read().unwrap();
+
         let slot = self
             .block_commitment_cache
             .read()
@@ -260,6 +314,22 @@ impl JsonRpcRequestProcessor {
         max_complete_transaction_status_slot: Arc<AtomicU64>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = channel();
+        let transaction_history_search = Arc::new(TransactionHistorySearch::new(
+            config
+                .transaction_history_search_threads
+                .unwrap_or(DEFAULT_TRANSACTION_HISTORY_SEARCH_THREADS),
+            config.max_transaction_history_searches_per_second,
+        ));
+        let accounts_scan_throttle = Arc::new(AccountsScanThrottle::new(
+            config
+                .max_concurrent_accounts_scans
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS),
+            config
+                .accounts_scan_queue_timeout
+                .unwrap_or(DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT),
+            config.max_accounts_scan_duration,
+            config.max_accounts_scan_bytes,
+        ));
         (
             Self {
                 config,
@@ -278,6 +348,9 @@ impl JsonRpcRequestProcessor {
                 max_slots,
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
+                transaction_history_search,
+                accounts_scan_throttle,
+                request_received: None,
             },
             receiver,
         )
@@ -324,24 +397,64 @@ impl JsonRpcRequestProcessor {
             genesis_hash,
             transaction_sender: Arc::new(Mutex::new(sender)),
             bigtable_ledger_storage: None,
-            optimistically_confirmed_bank: Arc::new(RwLock::new(OptimisticallyConfirmedBank {
-                bank: bank.clone(),
-            })),
+            optimistically_confirmed_bank: Arc::new(RwLock::new(OptimisticallyConfirmedBank::new(
+                bank.clone(),
+            ))),
             largest_accounts_cache: Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
+            transaction_history_search: Arc::new(TransactionHistorySearch::new(1, None)),
+            accounts_scan_throttle: Arc::new(AccountsScanThrottle::default()),
+            request_received: None,
         }
     }
 
+    /// Returns a copy of this processor for a request received at `request_received`
+    pub(crate) fn with_request_received(&self, request_received: Instant) -> Self {
+        Self {
+            request_received: Some(request_received),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn request_received(&self) -> Option<Instant> {
+        self.request_received
+    }
+
+    /// Like `bank()`, but fails with a retriable error if the selected bank has not yet reached
+    /// the requested minimum context slot
+    fn get_bank_with_config(&self, config: RpcContextConfig) -> Result<Arc<Bank>> {
+        let RpcContextConfig {
+            commitment,
+            min_context_slot,
+        } = config;
+        let bank = self.bank(commitment);
+        if let Some(min_context_slot) = min_context_slot {
+            if bank.slot() < min_context_slot {
+                return Err(RpcCustomError::MinContextSlotNotReached {
+                    context_slot: bank.slot(),
+                }
+                .into());
+            }
+        }
+        Ok(bank)
+    }
+
     pub fn get_account_info(
         &self,
         pubkey: &Pubkey,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcCompressedAccountInfoConfig>,
     ) -> Result<RpcResponse<Option<UiAccount>>> {
-        let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
-        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let RpcCompressedAccountInfoConfig {
+            account_config: config,
+            compression,
+        } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
+        let encoding = account_encoding(&config, compression, UiAccountEncoding::Binary)?;
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
         let response = get_encoded_account(&bank, pubkey, encoding, config.data_slice)?;
@@ -351,11 +464,17 @@ impl JsonRpcRequestProcessor {
     pub fn get_multiple_accounts(
         &self,
         pubkeys: Vec<Pubkey>,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcCompressedAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
-        let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
-        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
+        let RpcCompressedAccountInfoConfig {
+            account_config: config,
+            compression,
+        } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
+        let encoding = account_encoding(&config, compression, UiAccountEncoding::Base64)?;
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
         let accounts = pubkeys
@@ -365,6 +484,57 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, accounts))
     }
 
+    pub fn get_multiple_accounts_with_status(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        remaining: usize,
+        config: Option<RpcCompressedAccountInfoConfig>,
+    ) -> Result<RpcResponse<RpcMultipleAccounts>> {
+        let RpcCompressedAccountInfoConfig {
+            account_config: config,
+            compression,
+        } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
+        let encoding = account_encoding(&config, compression, UiAccountEncoding::Base64)?;
+        check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
+
+        // The missing accounts are looked up in the most recent bank to tell the ones that don't
+        // exist from the ones that haven't reached the requested commitment yet
+        let processed_bank = self.bank(Some(CommitmentConfig::processed()));
+        let mut num_not_found_at_commitment = 0;
+        let accounts = pubkeys
+            .into_iter()
+            .map(|pubkey| {
+                let account = get_encoded_account(&bank, &pubkey, encoding, config.data_slice)?;
+                let status = if account.is_some() {
+                    RpcAccountStatus::Found
+                } else if processed_bank.slot() != bank.slot()
+                    && processed_bank.get_account(&pubkey).is_some()
+                {
+                    num_not_found_at_commitment += 1;
+                    RpcAccountStatus::NotFoundAtCommitment
+                } else {
+                    RpcAccountStatus::DoesNotExist
+                };
+                Ok(RpcMultipleAccountsEntry { account, status })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        inc_new_counter_info!(
+            "rpc-get-multiple-accounts_not-found-at-commitment",
+            num_not_found_at_commitment
+        );
+        Ok(new_response(
+            &bank,
+            RpcMultipleAccounts {
+                accounts,
+                remaining,
+            },
+        ))
+    }
+
     pub fn get_minimum_balance_for_rent_exemption(
         &self,
         data_len: usize,
@@ -374,26 +544,46 @@ impl JsonRpcRequestProcessor {
             .get_minimum_balance_for_rent_exemption(data_len)
     }
 
-    pub fn get_program_accounts(
+    pub async fn get_program_accounts(
+        &self,
+        program_id: Pubkey,
+        config: Option<RpcAccountInfoConfig>,
+        filters: Vec<RpcFilterType>,
+        with_context: bool,
+    ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+        let meta = self.clone();
+        self.accounts_scan_throttle
+            .run(move |scan_config| {
+                meta.scan_program_accounts(&program_id, config, filters, with_context, scan_config)
+            })
+            .await
+            .ok_or(RpcCustomError::AccountsScanQueueTimeout)?
+    }
+
+    fn scan_program_accounts(
         &self,
         program_id: &Pubkey,
         config: Option<RpcAccountInfoConfig>,
         mut filters: Vec<RpcFilterType>,
         with_context: bool,
+        scan_config: &ScanConfig,
     ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
         optimize_filters(&mut filters);
         let keyed_accounts = {
             if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters)?
+                self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters, scan_config)?
             } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+                self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters, scan_config)?
             } else {
-                self.get_filtered_program_accounts(&bank, program_id, filters)?
+                self.get_filtered_program_accounts(&bank, program_id, filters, scan_config)?
             }
         };
         let result = if program_id == &spl_token_id_v2_0()
@@ -535,6 +725,54 @@ impl JsonRpcRequestProcessor {
         *bank.epoch_schedule()
     }
 
+    pub fn get_slot_time_estimate(
+        &self,
+        config: Option<RpcSlotTimeEstimateConfig>,
+    ) -> Result<RpcSlotTimeEstimate> {
+        let config = config.unwrap_or_default();
+        let bank = self.bank(config.commitment);
+        let current_slot = bank.slot();
+        let current_time = bank.clock().unix_timestamp;
+        let slot = match (config.slot, config.epoch) {
+            (Some(_), Some(_)) => {
+                return Err(Error::invalid_params(
+                    "Only one of slot or epoch may be specified",
+                ))
+            }
+            (Some(slot), None) => slot,
+            (None, epoch) => {
+                let epoch = epoch.unwrap_or_else(|| bank.epoch().saturating_add(1));
+                bank.epoch_schedule().get_first_slot_in_epoch(epoch)
+            }
+        };
+
+        // The samples are returned newest first, and fed oldest first
+        let mut samples = self
+            .blockstore
+            .get_recent_perf_samples(SLOT_TIME_ESTIMATE_SAMPLES)
+            .map_err(|err| {
+                warn!("get_slot_time_estimate failed: {:?}", err);
+                Error::invalid_request()
+            })?;
+        samples.reverse();
+        let estimator = SlotTimeEstimator::from_samples(
+            bank.ns_per_slot() as f64 / 1_000_000.0,
+            samples.into_iter().map(|(_slot, sample)| {
+                (
+                    sample.num_slots,
+                    u64::from(sample.sample_period_secs).saturating_mul(1000),
+                )
+            }),
+        );
+        Ok(RpcSlotTimeEstimate {
+            current_slot,
+            current_time,
+            ms_per_slot: estimator.ms_per_slot(),
+            slot,
+            estimated_time: estimator.estimate_slot_time(current_slot, current_time, slot),
+        })
+    }
+
     pub fn get_balance(
         &self,
         pubkey: &Pubkey,
@@ -732,50 +970,107 @@ impl JsonRpcRequestProcessor {
         largest_accounts_cache.set_largest_accounts(filter, slot, accounts)
     }
 
-    fn get_largest_accounts(
+    async fn get_largest_accounts(
         &self,
         config: Option<RpcLargestAccountsConfig>,
     ) -> RpcCustomResult<RpcResponse<Vec<RpcAccountBalance>>> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
-
         if let Some((slot, accounts)) = self.get_cached_largest_accounts(&config.filter) {
-            Ok(Response {
+            return Ok(Response {
                 context: RpcResponseContext { slot },
                 value: accounts,
-            })
-        } else {
-            let (addresses, address_filter) = if let Some(filter) = config.clone().filter {
-                let non_circulating_supply =
-                    calculate_non_circulating_supply(&bank).map_err(|e| {
-                        RpcCustomError::ScanError {
-                            message: e.to_string(),
-                        }
-                    })?;
-                let addresses = non_circulating_supply.accounts.into_iter().collect();
-                let address_filter = match filter {
-                    RpcLargestAccountsFilter::Circulating => AccountAddressFilter::Exclude,
-                    RpcLargestAccountsFilter::NonCirculating => AccountAddressFilter::Include,
-                };
-                (addresses, address_filter)
-            } else {
-                (HashSet::new(), AccountAddressFilter::Exclude)
+            });
+        }
+        let meta = self.clone();
+        self.accounts_scan_throttle
+            .run(move |scan_config| meta.scan_largest_accounts(config, scan_config))
+            .await
+            .ok_or(RpcCustomError::AccountsScanQueueTimeout)?
+    }
+
+    fn scan_largest_accounts(
+        &self,
+        config: RpcLargestAccountsConfig,
+        scan_config: &ScanConfig,
+    ) -> RpcCustomResult<RpcResponse<Vec<RpcAccountBalance>>> {
+        let bank = self.bank(config.commitment);
+        let (addresses, address_filter) = if let Some(filter) = config.clone().filter {
+            let non_circulating_supply =
+                calculate_non_circulating_supply(&bank).map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?;
+            let addresses = non_circulating_supply.accounts.into_iter().collect();
+            let address_filter = match filter {
+                RpcLargestAccountsFilter::Circulating => AccountAddressFilter::Exclude,
+                RpcLargestAccountsFilter::NonCirculating => AccountAddressFilter::Include,
             };
-            let accounts = bank
-                .get_largest_accounts(NUM_LARGEST_ACCOUNTS, &addresses, address_filter)
+            (addresses, address_filter)
+        } else {
+            (HashSet::new(), AccountAddressFilter::Exclude)
+        };
+        let accounts = match address_filter {
+            // The non circulating accounts are few, their balances are loaded directly
+            AccountAddressFilter::Include => {
+                let mut accounts: Vec<_> = addresses
+                    .iter()
+                    .map(|address| (*address, bank.get_balance(address)))
+                    .filter(|(_, lamports)| *lamports > 0)
+                    .collect();
+                accounts.sort_unstable_by(|(address_a, lamports_a), (address_b, lamports_b)| {
+                    (lamports_b, address_b).cmp(&(lamports_a, address_a))
+                });
+                accounts.truncate(NUM_LARGEST_ACCOUNTS);
+                accounts
+            }
+            AccountAddressFilter::Exclude => self
+                .load_largest_accounts(&bank, &addresses, scan_config)
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
-                })?
-                .into_iter()
-                .map(|(address, lamports)| RpcAccountBalance {
-                    address: address.to_string(),
-                    lamports,
-                })
-                .collect::<Vec<RpcAccountBalance>>();
+                })?,
+        }
+        .into_iter()
+        .map(|(address, lamports)| RpcAccountBalance {
+            address: address.to_string(),
+            lamports,
+        })
+        .collect::<Vec<RpcAccountBalance>>();
+
+        self.set_cached_largest_accounts(&config.filter, bank.slot(), &accounts);
+        Ok(new_response(&bank, accounts))
+    }
 
-            self.set_cached_largest_accounts(&config.filter, bank.slot(), &accounts);
-            Ok(new_response(&bank, accounts))
+    // Lists the largest accounts from those tracked by the runtime, rebuilding them with a scan of
+    // the root bank if they were never built, or too few of them are left
+    fn load_largest_accounts(
+        &self,
+        bank: &Bank,
+        excluded_addresses: &HashSet<Pubkey>,
+        scan_config: &ScanConfig,
+    ) -> ScanResult<Vec<(Pubkey, u64)>> {
+        let tracked_largest_accounts = |excluded_addresses: &HashSet<Pubkey>| {
+            bank.get_tracked_largest_accounts(
+                NUM_LARGEST_ACCOUNTS,
+                excluded_addresses,
+                AccountAddressFilter::Exclude,
+            )
+        };
+        if let Some(accounts) = tracked_largest_accounts(excluded_addresses) {
+            return Ok(accounts);
+        }
+        // Rebuilding doesn't help if only the filter leaves too few tracked accounts
+        if tracked_largest_accounts(&HashSet::new()).is_none() {
+            let root_bank = self.bank_forks.read().unwrap().root_bank();
+            root_bank.rebuild_tracked_largest_accounts(scan_config)?;
+            if let Some(accounts) = tracked_largest_accounts(excluded_addresses) {
+                return Ok(accounts);
+            }
         }
+        bank.get_largest_accounts(
+            NUM_LARGEST_ACCOUNTS,
+            excluded_addresses,
+            AccountAddressFilter::Exclude,
+            scan_config,
+        )
     }
 
     fn get_supply(
@@ -827,6 +1122,9 @@ impl JsonRpcRequestProcessor {
         let epoch_vote_accounts = bank
             .epoch_vote_accounts(bank.get_epoch_and_slot_index(bank.slot()).0)
             .ok_or_else(Error::invalid_request)?;
+        // Snapshotted when the current epoch started, to surface commission changes made since
+        let epoch_start_vote_accounts =
+            bank.epoch_vote_accounts(bank.get_leader_schedule_epoch(bank.slot()));
         let default_vote_state = VoteState::default();
         let delinquent_validator_slot_distance = config
             .delinquent_slot_distance
@@ -862,11 +1160,23 @@ impl JsonRpcRequestProcessor {
                     epoch_credits.clone()
                 };
 
+                let epoch_start_commission = epoch_start_vote_accounts
+                    .and_then(|vote_accounts| vote_accounts.get(vote_pubkey))
+                    .and_then(|(_stake, account)| {
+                        account
+                            .vote_state()
+                            .as_ref()
+                            .ok()
+                            .map(|state| state.commission)
+                    })
+                    .filter(|commission| *commission != vote_state.commission);
+
                 Some(RpcVoteAccountInfo {
                     vote_pubkey: vote_pubkey.to_string(),
                     node_pubkey: vote_state.node_pubkey.to_string(),
                     activated_stake: *activated_stake,
                     commission: vote_state.commission,
+                    epoch_start_commission,
                     root_slot: vote_state.root_slot.unwrap_or(0),
                     epoch_credits,
                     epoch_vote_account: epoch_vote_accounts.contains_key(vote_pubkey),
@@ -1236,7 +1546,8 @@ impl JsonRpcRequestProcessor {
         commitment: Option<CommitmentConfig>,
     ) -> Option<RpcSignatureConfirmation> {
         let bank = self.bank(commitment);
-        let transaction_status = self.get_transaction_status(signature, &bank)?;
+        let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
+        let transaction_status = self.get_transaction_status(signature, &bank, &confirmed_bank)?;
         let confirmations = transaction_status
             .confirmations
             .unwrap_or(MAX_LOCKOUT_HISTORY + 1);
@@ -1261,72 +1572,120 @@ impl JsonRpcRequestProcessor {
         signatures: Vec<Signature>,
         config: Option<RpcSignatureStatusConfig>,
     ) -> Result<RpcResponse<Vec<Option<TransactionStatus>>>> {
-        let mut statuses: Vec<Option<TransactionStatus>> = vec![];
-
         let search_transaction_history = config
             .map(|x| x.search_transaction_history)
             .unwrap_or(false);
-        let bank = self.bank(Some(CommitmentConfig::processed()));
-
         if search_transaction_history && !self.config.enable_rpc_transaction_history {
             return Err(RpcCustomError::TransactionHistoryNotAvailable.into());
         }
 
-        for signature in signatures {
-            let status = if let Some(status) = self.get_transaction_status(signature, &bank) {
-                Some(status)
-            } else if self.config.enable_rpc_transaction_history && search_transaction_history {
-                if let Some(status) = self
-                    .blockstore
-                    .get_rooted_transaction_status(signature)
-                    .map_err(|_| Error::internal_error())?
-                    .filter(|(slot, _status_meta)| {
-                        slot <= &self
-                            .block_commitment_cache
-                            .read()
-                            .unwrap()
-                            .highest_confirmed_root()
-                    })
-                    .map(|(slot, status_meta)| {
-                        let err = status_meta.status.clone().err();
-                        TransactionStatus {
-                            slot,
-                            status: status_meta.status,
-                            confirmations: None,
-                            err,
-                            confirmation_status: Some(TransactionConfirmationStatus::Finalized),
-                        }
-                    })
-                {
-                    Some(status)
-                } else if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                    bigtable_ledger_storage
-                        .get_signature_status(&signature)
-                        .await
-                        .map(Some)
-                        .unwrap_or(None)
-                } else {
-                    None
-                }
+        // All the statuses are reported against the same snapshots, even if the confirmed bank
+        // moves on while they are looked up
+        let bank = self.bank(Some(CommitmentConfig::processed()));
+        let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
+        let get_transaction_status =
+            |signature: &Signature| self.get_transaction_status(*signature, &bank, &confirmed_bank);
+        let mut statuses: Vec<Option<TransactionStatus>> =
+            if signatures.len() >= MIN_PARALLEL_SIGNATURE_STATUSES {
+                signatures.par_iter().map(get_transaction_status).collect()
             } else {
-                None
+                signatures.iter().map(get_transaction_status).collect()
             };
-            statuses.push(status);
+
+        if self.config.enable_rpc_transaction_history && search_transaction_history {
+            let missing: Vec<_> = statuses
+                .iter()
+                .enumerate()
+                .filter(|(_, status)| status.is_none())
+                .map(|(i, _)| i)
+                .collect();
+            if !missing.is_empty() {
+                if !self.transaction_history_search.try_reserve(missing.len()) {
+                    return Err(RpcCustomError::TransactionHistorySearchRateLimited.into());
+                }
+                let missing_signatures = missing.iter().map(|i| signatures[*i]).collect();
+                let historical_statuses = self
+                    .get_historical_transaction_statuses(missing_signatures)
+                    .await?;
+                for (i, status) in missing.into_iter().zip(historical_statuses) {
+                    statuses[i] = status;
+                }
+            }
         }
         Ok(new_response(&bank, statuses))
     }
 
+    /// Looks up the statuses of rooted transactions in the blockstore, then in BigTable for those
+    /// not found. The blockstore lookups run on the transaction history search thread pool.
+    async fn get_historical_transaction_statuses(
+        &self,
+        signatures: Vec<Signature>,
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        let blockstore = self.blockstore.clone();
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let mut statuses = self
+            .transaction_history_search
+            .run(move || {
+                signatures
+                    .into_par_iter()
+                    .map(|signature| {
+                        let status = blockstore
+                            .get_rooted_transaction_status(signature)
+                            .map_err(|_| Error::internal_error())?
+                            .filter(|(slot, _status_meta)| *slot <= highest_confirmed_root)
+                            .map(|(slot, status_meta)| {
+                                let err = status_meta.status.clone().err();
+                                TransactionStatus {
+                                    slot,
+                                    status: status_meta.status,
+                                    confirmations: None,
+                                    err,
+                                    confirmation_status: Some(
+                                        TransactionConfirmationStatus::Finalized,
+                                    ),
+                                }
+                            });
+                        Ok((signature, status))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .await?;
+
+        if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
+            let bigtable_statuses =
+                future::join_all(statuses.iter().map(|(signature, status)| async move {
+                    match status {
+                        Some(_) => None,
+                        None => bigtable_ledger_storage
+                            .get_signature_status(signature)
+                            .await
+                            .ok(),
+                    }
+                }))
+                .await;
+            for ((_, status), bigtable_status) in statuses.iter_mut().zip(bigtable_statuses) {
+                if status.is_none() {
+                    *status = bigtable_status;
+                }
+            }
+        }
+        Ok(statuses.into_iter().map(|(_, status)| status).collect())
+    }
+
     fn get_transaction_status(
         &self,
         signature: Signature,
         bank: &Arc<Bank>,
+        confirmed_bank: &Arc<Bank>,
     ) -> Option<TransactionStatus> {
         let (slot, status) = bank.get_signature_status_slot(&signature)?;
         let r_block_commitment_cache = self.block_commitment_cache.read().unwrap();
 
-        let optimistically_confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
-        let optimistically_confirmed =
-            optimistically_confirmed_bank.get_signature_status_slot(&signature);
+        let optimistically_confirmed = confirmed_bank.get_signature_status_slot(&signature);
 
         let confirmations = if r_block_commitment_cache.root() >= slot
             && is_finalized(&r_block_commitment_cache, bank, &self.blockstore, slot)
@@ -1678,7 +2037,7 @@ impl JsonRpcRequestProcessor {
             ));
         }
         let mut token_balances: Vec<RpcTokenAccountBalance> = self
-            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![])?
+            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![], &ScanConfig::default())?
             .into_iter()
             .map(|(address, account)| {
                 let amount = TokenAccount::unpack(account.data())
@@ -1710,7 +2069,10 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
@@ -1726,8 +2088,12 @@ impl JsonRpcRequestProcessor {
             }));
         }
 
-        let keyed_accounts =
-            self.get_filtered_spl_token_accounts_by_owner(&bank, owner, filters)?;
+        let keyed_accounts = self.get_filtered_spl_token_accounts_by_owner(
+            &bank,
+            owner,
+            filters,
+            &ScanConfig::default(),
+        )?;
         let accounts = if encoding == UiAccountEncoding::JsonParsed {
             get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
         } else {
@@ -1755,7 +2121,10 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<RpcKeyedAccount>>> {
         let config = config.unwrap_or_default();
-        let bank = self.bank(config.commitment);
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment: config.commitment,
+            min_context_slot: config.min_context_slot,
+        })?;
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
@@ -1777,13 +2146,23 @@ impl JsonRpcRequestProcessor {
         ];
         // Optional filter on Mint address, uses mint account index for scan
         let keyed_accounts = if let Some(mint) = mint {
-            self.get_filtered_spl_token_accounts_by_mint(&bank, &mint, filters)?
+            self.get_filtered_spl_token_accounts_by_mint(
+                &bank,
+                &mint,
+                filters,
+                &ScanConfig::default(),
+            )?
         } else {
             // Filter on Token Account state
             filters.push(RpcFilterType::DataSize(
                 TokenAccount::get_packed_len() as u64
             ));
-            self.get_filtered_program_accounts(&bank, &token_program_id, filters)?
+            self.get_filtered_program_accounts(
+                &bank,
+                &token_program_id,
+                filters,
+                &ScanConfig::default(),
+            )?
         };
         let accounts = if encoding == UiAccountEncoding::JsonParsed {
             get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
@@ -1811,6 +2190,7 @@ impl JsonRpcRequestProcessor {
         bank: &Arc<Bank>,
         program_id: &Pubkey,
         mut filters: Vec<RpcFilterType>,
+        scan_config: &ScanConfig,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         optimize_filters(&mut filters);
         let filter_closure = |account: &AccountSharedData| {
@@ -1830,20 +2210,24 @@ impl JsonRpcRequestProcessor {
                 });
             }
             Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::ProgramId(*program_id), |account| {
-                    // The program-id account index checks for Account owner on inclusion. However, due
-                    // to the current AccountsDb implementation, an account may remain in storage as a
-                    // zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
-                    // updates. We include the redundant filters here to avoid returning these
-                    // accounts.
-                    account.owner() == program_id && filter_closure(account)
-                })
+                .get_filtered_indexed_accounts(
+                    &IndexKey::ProgramId(*program_id),
+                    |account| {
+                        // The program-id account index checks for Account owner on inclusion.
+                        // However, due to the current AccountsDb implementation, an account may
+                        // remain in storage as a zero-lamport AccountSharedData::Default() after
+                        // being wiped and reinitialized in later updates. We include the redundant
+                        // filters here to avoid returning these accounts.
+                        account.owner() == program_id && filter_closure(account)
+                    },
+                    scan_config,
+                )
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
             Ok(bank
-                .get_filtered_program_accounts(program_id, filter_closure)
+                .get_filtered_program_accounts(program_id, filter_closure, scan_config)
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
@@ -1856,6 +2240,7 @@ impl JsonRpcRequestProcessor {
         bank: &Arc<Bank>,
         owner_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
+        scan_config: &ScanConfig,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-owner accounts index checks for Token Account state and Owner address on
         // inclusion. However, due to the current AccountsDb implementation, an account may remain
@@ -1884,18 +2269,26 @@ impl JsonRpcRequestProcessor {
                 });
             }
             Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenOwner(*owner_key), |account| {
-                    account.owner() == &spl_token_id_v2_0()
-                        && filters.iter().all(|filter_type| match filter_type {
-                            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-                            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-                        })
-                })
+                .get_filtered_indexed_accounts(
+                    &IndexKey::SplTokenOwner(*owner_key),
+                    |account| {
+                        account.owner() == &spl_token_id_v2_0()
+                            && filters.iter().all(|filter_type| match filter_type {
+                                RpcFilterType::DataSize(size) => {
+                                    account.data().len() as u64 == *size
+                                }
+                                RpcFilterType::Memcmp(compare) => {
+                                    compare.bytes_match(account.data())
+                                }
+                            })
+                    },
+                    scan_config,
+                )
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters, scan_config)
         }
     }
 
@@ -1905,6 +2298,7 @@ impl JsonRpcRequestProcessor {
         bank: &Arc<Bank>,
         mint_key: &Pubkey,
         mut filters: Vec<RpcFilterType>,
+        scan_config: &ScanConfig,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-mint accounts index checks for Token Account state and Mint address on inclusion.
         // However, due to the current AccountsDb implementation, an account may remain in storage
@@ -1932,18 +2326,26 @@ impl JsonRpcRequestProcessor {
                 });
             }
             Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::SplTokenMint(*mint_key), |account| {
-                    account.owner() == &spl_token_id_v2_0()
-                        && filters.iter().all(|filter_type| match filter_type {
-                            RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-                            RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-                        })
-                })
+                .get_filtered_indexed_accounts(
+                    &IndexKey::SplTokenMint(*mint_key),
+                    |account| {
+                        account.owner() == &spl_token_id_v2_0()
+                            && filters.iter().all(|filter_type| match filter_type {
+                                RpcFilterType::DataSize(size) => {
+                                    account.data().len() as u64 == *size
+                                }
+                                RpcFilterType::Memcmp(compare) => {
+                                    compare.bytes_match(account.data())
+                                }
+                            })
+                    },
+                    scan_config,
+                )
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
         } else {
-            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters)
+            self.get_filtered_program_accounts(bank, &spl_token_id_v2_0(), filters, scan_config)
         }
     }
 
@@ -2024,6 +2426,130 @@ fn verify_transaction(
     Ok(())
 }
 
+// The blockhash of a durable nonce transaction is its nonce, which the bank only accepts if it's
+// the one stored by the nonce account, once it's no longer a recent blockhash
+fn verify_durable_nonce(transaction: &SanitizedTransaction, bank: &Bank) -> Result<()> {
+    let nonce_pubkey = match transaction.get_durable_nonce() {
+        Some(nonce_pubkey) => nonce_pubkey,
+        None => return Ok(()),
+    };
+    let nonce = transaction.message().recent_blockhash();
+    let max_age = MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY;
+    if bank.check_hash_age(nonce, max_age) == Some(true)
+        || bank.check_tx_durable_nonce(transaction).is_some()
+    {
+        return Ok(());
+    }
+    let message = match durable_nonce_blockhash(nonce_pubkey, bank) {
+        None => format!("{} is not an initialized nonce account", nonce_pubkey),
+        Some(stored_nonce) => format!(
+            "nonce {} doesn't match nonce {} stored by account {}",
+            nonce, stored_nonce, nonce_pubkey
+        ),
+    };
+    Err(RpcCustomError::TransactionNonceVerificationFailure { message }.into())
+}
+
+fn durable_nonce_blockhash(nonce_pubkey: &Pubkey, bank: &Bank) -> Option<Hash> {
+    bank.get_account(nonce_pubkey)
+        .filter(|account| system_program::check_id(account.owner()))
+        .and_then(|account| nonce_account::blockhash_of(&account))
+}
+
+// Decodes a transaction to simulate, replacing its recent blockhash and verifying its signatures
+// as requested
+fn sanitize_simulated_transaction(
+    data: String,
+    encoding: UiTransactionEncoding,
+    bank: &Bank,
+    sig_verify: bool,
+    replace_recent_blockhash: bool,
+) -> Result<SanitizedTransaction> {
+    let (_, mut unsanitized_tx) = decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
+    if replace_recent_blockhash {
+        if sig_verify {
+            return Err(Error::invalid_params(
+                "sigVerify may not be used with replaceRecentBlockhash",
+            ));
+        }
+        // Durable nonce transactions are simulated with the nonce currently stored by their
+        // nonce account instead, which they must advance
+        let recent_blockhash = sanitize_transaction(unsanitized_tx.clone())?
+            .get_durable_nonce()
+            .and_then(|nonce_pubkey| durable_nonce_blockhash(nonce_pubkey, bank))
+            .unwrap_or_else(|| bank.last_blockhash());
+        unsanitized_tx
+            .message
+            .set_recent_blockhash(recent_blockhash);
+    }
+
+    let transaction = sanitize_transaction(unsanitized_tx)?;
+    if sig_verify {
+        verify_transaction(&transaction, &bank.feature_set)?;
+    }
+    verify_durable_nonce(&transaction, bank)?;
+    Ok(transaction)
+}
+
+fn encode_simulation_result(
+    simulation_result: TransactionSimulationResult,
+    config_accounts: Option<&RpcSimulateTransactionAccountsConfig>,
+    max_addresses: usize,
+) -> Result<RpcSimulateTransactionResult> {
+    let TransactionSimulationResult {
+        result,
+        logs,
+        post_simulation_accounts,
+        units_consumed,
+        compute_budget_exceeded,
+    } = simulation_result;
+
+    let accounts = if let Some(config_accounts) = config_accounts {
+        let accounts_encoding = config_accounts
+            .encoding
+            .unwrap_or(UiAccountEncoding::Base64);
+
+        if accounts_encoding == UiAccountEncoding::Binary
+            || accounts_encoding == UiAccountEncoding::Base58
+        {
+            return Err(Error::invalid_params("base58 encoding not supported"));
+        }
+
+        if config_accounts.addresses.len() > max_addresses {
+            return Err(Error::invalid_params(format!(
+                "Too many accounts provided; max {}",
+                max_addresses
+            )));
+        }
+
+        let mut accounts = vec![];
+        for address_str in &config_accounts.addresses {
+            let address = verify_pubkey(address_str)?;
+            accounts.push(if result.is_err() {
+                None
+            } else {
+                post_simulation_accounts
+                    .iter()
+                    .find(|(key, _account)| key == &address)
+                    .map(|(pubkey, account)| {
+                        UiAccount::encode(pubkey, account, accounts_encoding, None, None)
+                    })
+            });
+        }
+        Some(accounts)
+    } else {
+        None
+    };
+
+    Ok(RpcSimulateTransactionResult {
+        err: result.err(),
+        logs: Some(logs),
+        accounts,
+        units_consumed: Some(units_consumed),
+        compute_budget_exceeded: compute_budget_exceeded.map(Into::into),
+    })
+}
+
 fn verify_filter(input: &RpcFilterType) -> Result<()> {
     input
         .verify()
@@ -2113,7 +2639,25 @@ fn check_slice_and_encoding(encoding: &UiAccountEncoding, data_slice_is_some: bo
         UiAccountEncoding::Binary
         | UiAccountEncoding::Base58
         | UiAccountEncoding::Base64
-        | UiAccountEncoding::Base64Zstd => Ok(()),
+        | UiAccountEncoding::Base64Zstd
+        | UiAccountEncoding::Base64Gzip => Ok(()),
+    }
+}
+
+/// Encoding of the accounts requested, the base64 encoding of the compressed data if a
+/// compression is requested
+fn account_encoding(
+    config: &RpcAccountInfoConfig,
+    compression: Option<UiAccountCompression>,
+    default_encoding: UiAccountEncoding,
+) -> Result<UiAccountEncoding> {
+    match (config.encoding, compression) {
+        (encoding, None) => Ok(encoding.unwrap_or(default_encoding)),
+        (None | Some(UiAccountEncoding::Base64), Some(compression)) => Ok(compression.encoding()),
+        (Some(encoding), Some(_)) => Err(Error::invalid_params(format!(
+            "Compressed account data can only be encoded using base64 encoding, not {:?}",
+            encoding
+        ))),
     }
 }
 
@@ -2318,9 +2862,15 @@ pub mod rpc_minimal {
             commitment: Option<CommitmentConfig>,
         ) -> Result<EpochInfo>;
 
+        #[rpc(meta, name = "getErrorCodes")]
+        fn get_error_codes(&self, meta: Self::Metadata) -> Result<RpcErrorCodes>;
+
         #[rpc(meta, name = "getHealth")]
         fn get_health(&self, meta: Self::Metadata) -> Result<String>;
 
+        #[rpc(meta, name = "getHealthDetail")]
+        fn get_health_detail(&self, meta: Self::Metadata) -> Result<RpcHealthDetail>;
+
         #[rpc(meta, name = "getIdentity")]
         fn get_identity(&self, meta: Self::Metadata) -> Result<RpcIdentity>;
 
@@ -2396,6 +2946,11 @@ pub mod rpc_minimal {
             Ok(bank.get_epoch_info())
         }
 
+        fn get_error_codes(&self, _meta: Self::Metadata) -> Result<RpcErrorCodes> {
+            debug!("get_error_codes rpc request received");
+            Ok(RpcErrorCodes::default())
+        }
+
         fn get_health(&self, meta: Self::Metadata) -> Result<String> {
             match meta.health.check() {
                 RpcHealthStatus::Ok => Ok("ok".to_string()),
@@ -2410,6 +2965,32 @@ pub mod rpc_minimal {
             }
         }
 
+        fn get_health_detail(&self, meta: Self::Metadata) -> Result<RpcHealthDetail> {
+            debug!("get_health_detail rpc request received");
+            let (status, num_slots_behind) = match meta.health.check() {
+                RpcHealthStatus::Ok => ("ok", None),
+                RpcHealthStatus::Unknown => ("unknown", None),
+                RpcHealthStatus::Behind { num_slots } => ("behind", Some(num_slots)),
+            };
+            let duplicate_instances = meta
+                .cluster_info
+                .duplicate_instances()
+                .into_iter()
+                .map(|duplicate| RpcDuplicateInstance {
+                    identity: duplicate.pubkey.to_string(),
+                    num_instances: duplicate.num_instances,
+                    first_detected: duplicate.first_detected,
+                    last_seen: duplicate.last_seen,
+                })
+                .collect();
+            Ok(RpcHealthDetail {
+                status: status.to_string(),
+                num_slots_behind,
+                identity: meta.cluster_info.id().to_string(),
+                duplicate_instances,
+            })
+        }
+
         fn get_identity(&self, meta: Self::Metadata) -> Result<RpcIdentity> {
             debug!("get_identity rpc request received");
             Ok(RpcIdentity {
@@ -2477,6 +3058,7 @@ pub mod rpc_minimal {
             Ok(RpcVersionInfo {
                 solana_core: version.to_string(),
                 feature_set: Some(version.feature_set),
+                account_compression: Some(UiAccountCompression::ALL.to_vec()),
             })
         }
 
@@ -2507,10 +3089,19 @@ pub mod rpc_minimal {
             }
 
             let bank = meta.bank(config.commitment);
-            let slot = slot.unwrap_or_else(|| bank.slot());
-            let epoch = bank.epoch_schedule().get_epoch(slot);
+            let epoch = match (slot, config.epoch) {
+                (Some(_), Some(_)) => {
+                    return Err(Error::invalid_params(
+                        "Only one of the slot and the epoch can be provided",
+                    ));
+                }
+                (None, Some(epoch)) => epoch,
+                (slot, None) => bank
+                    .epoch_schedule()
+                    .get_epoch(slot.unwrap_or_else(|| bank.slot())),
+            };
 
-            debug!("get_leader_schedule rpc request received: {:?}", slot);
+            debug!("get_leader_schedule rpc request received: {:?}", epoch);
 
             Ok(meta
                 .leader_schedule_cache
@@ -2523,6 +3114,11 @@ pub mod rpc_minimal {
                     if let Some(identity) = config.identity {
                         schedule_by_identity.retain(|k, _| *k == identity);
                     }
+                    if config.encoding.unwrap_or_default() == RpcLeaderScheduleEncoding::Delta {
+                        for slot_indices in schedule_by_identity.values_mut() {
+                            *slot_indices = delta_encode_leader_slot_indices(slot_indices);
+                        }
+                    }
                     schedule_by_identity
                 }))
         }
@@ -2760,7 +3356,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcCompressedAccountInfoConfig>,
         ) -> Result<RpcResponse<Option<UiAccount>>>;
 
         #[rpc(meta, name = "getMultipleAccounts")]
@@ -2768,8 +3364,8 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
-        ) -> Result<RpcResponse<Vec<Option<UiAccount>>>>;
+            config: Option<RpcMultipleAccountsConfig>,
+        ) -> Result<RpcResponse<RpcMultipleAccountsResult>>;
 
         #[rpc(meta, name = "getProgramAccounts")]
         fn get_program_accounts(
@@ -2777,7 +3373,7 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             program_id_str: String,
             config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
+        ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>>;
 
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
@@ -2791,7 +3387,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             config: Option<RpcLargestAccountsConfig>,
-        ) -> Result<RpcResponse<Vec<RpcAccountBalance>>>;
+        ) -> BoxFuture<Result<RpcResponse<Vec<RpcAccountBalance>>>>;
 
         #[rpc(meta, name = "getSupply")]
         fn get_supply(
@@ -2863,7 +3459,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcCompressedAccountInfoConfig>,
         ) -> Result<RpcResponse<Option<UiAccount>>> {
             debug!("get_account_info rpc request received: {:?}", pubkey_str);
             let pubkey = verify_pubkey(&pubkey_str)?;
@@ -2874,28 +3470,66 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
-        ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
+            config: Option<RpcMultipleAccountsConfig>,
+        ) -> Result<RpcResponse<RpcMultipleAccountsResult>> {
             debug!(
                 "get_multiple_accounts rpc request received: {:?}",
                 pubkey_strs.len()
             );
+            let RpcMultipleAccountsConfig {
+                account_config,
+                with_status,
+                allow_partial,
+                compression,
+            } = config.unwrap_or_default();
+            let account_config = RpcCompressedAccountInfoConfig {
+                account_config,
+                compression,
+            };
+            let allow_partial = allow_partial.unwrap_or_default();
+            let with_status = with_status.unwrap_or_default() || allow_partial;
 
             let max_multiple_accounts = meta
                 .config
                 .max_multiple_accounts
                 .unwrap_or(MAX_MULTIPLE_ACCOUNTS);
+            let mut pubkey_strs = pubkey_strs;
+            let mut remaining = 0;
             if pubkey_strs.len() > max_multiple_accounts {
-                return Err(Error::invalid_params(format!(
-                    "Too many inputs provided; max {}",
-                    max_multiple_accounts
-                )));
+                if !allow_partial {
+                    return Err(Error::invalid_params(format!(
+                        "Too many inputs provided; max {}",
+                        max_multiple_accounts
+                    )));
+                }
+                remaining = pubkey_strs.len() - max_multiple_accounts;
+                pubkey_strs.truncate(max_multiple_accounts);
+                inc_new_counter_info!("rpc-get-multiple-accounts_partial", 1);
             }
+            inc_new_counter_info!("rpc-get-multiple-accounts_requests", 1);
+            inc_new_counter_info!("rpc-get-multiple-accounts_pubkeys", pubkey_strs.len());
+
             let pubkeys = pubkey_strs
                 .into_iter()
                 .map(|pubkey_str| verify_pubkey(&pubkey_str))
                 .collect::<Result<Vec<_>>>()?;
-            meta.get_multiple_accounts(pubkeys, config)
+            if with_status {
+                let response = meta.get_multiple_accounts_with_status(
+                    pubkeys,
+                    remaining,
+                    Some(account_config),
+                )?;
+                Ok(RpcResponse {
+                    context: response.context,
+                    value: RpcMultipleAccountsResult::WithStatus(response.value),
+                })
+            } else {
+                let response = meta.get_multiple_accounts(pubkeys, Some(account_config))?;
+                Ok(RpcResponse {
+                    context: response.context,
+                    value: RpcMultipleAccountsResult::Accounts(response.value),
+                })
+            }
         }
 
         fn get_program_accounts(
@@ -2903,12 +3537,15 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             program_id_str: String,
             config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+        ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>> {
             debug!(
                 "get_program_accounts rpc request received: {:?}",
                 program_id_str
             );
-            let program_id = verify_pubkey(&program_id_str)?;
+            let program_id = match verify_pubkey(&program_id_str) {
+                Ok(program_id) => program_id,
+                Err(err) => return Box::pin(future::err(err)),
+            };
             let (config, filters, with_context) = if let Some(config) = config {
                 (
                     Some(config.account_config),
@@ -2919,15 +3556,18 @@ pub mod rpc_accounts {
                 (None, vec![], false)
             };
             if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
-                return Err(Error::invalid_params(format!(
+                return Box::pin(future::err(Error::invalid_params(format!(
                     "Too many filters provided; max {}",
                     MAX_GET_PROGRAM_ACCOUNT_FILTERS
-                )));
+                ))));
             }
-            for filter in &filters {
-                verify_filter(filter)?;
+            if let Err(err) = filters.iter().try_for_each(verify_filter) {
+                return Box::pin(future::err(err));
             }
-            meta.get_program_accounts(&program_id, config, filters, with_context)
+            Box::pin(async move {
+                meta.get_program_accounts(program_id, config, filters, with_context)
+                    .await
+            })
         }
 
         fn get_block_commitment(
@@ -2943,9 +3583,9 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             config: Option<RpcLargestAccountsConfig>,
-        ) -> Result<RpcResponse<Vec<RpcAccountBalance>>> {
+        ) -> BoxFuture<Result<RpcResponse<Vec<RpcAccountBalance>>>> {
             debug!("get_largest_accounts rpc request received");
-            Ok(meta.get_largest_accounts(config)?)
+            Box::pin(async move { Ok(meta.get_largest_accounts(config).await?) })
         }
 
         fn get_supply(
@@ -3070,6 +3710,13 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getSlotTimeEstimate")]
+        fn get_slot_time_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcSlotTimeEstimateConfig>,
+        ) -> Result<RpcSlotTimeEstimate>;
+
         #[rpc(meta, name = "getGenesisHash")]
         fn get_genesis_hash(&self, meta: Self::Metadata) -> Result<String>;
 
@@ -3112,6 +3759,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<Vec<Option<RpcSimulateTransactionResult>>>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3228,6 +3883,15 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_slot_time_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcSlotTimeEstimateConfig>,
+        ) -> Result<RpcSlotTimeEstimate> {
+            debug!("get_slot_time_estimate rpc request received");
+            meta.get_slot_time_estimate(config)
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -3258,7 +3922,11 @@ pub mod rpc_full {
                             pubkey: contact_info.id.to_string(),
                             gossip: Some(contact_info.gossip),
                             tpu: valid_address_or_none(&contact_info.tpu),
+                            tpu_forwards: valid_address_or_none(&contact_info.tpu_forwards),
+                            tpu_vote: valid_address_or_none(&contact_info.tpu_vote),
+                            serve_repair: valid_address_or_none(&contact_info.serve_repair),
                             rpc: valid_address_or_none(&contact_info.rpc),
+                            pubsub: valid_address_or_none(&contact_info.rpc_pubsub),
                             version,
                             feature_set,
                             shred_version: Some(my_shred_version),
@@ -3285,10 +3953,21 @@ pub mod rpc_full {
                 "get_signature_statuses rpc request received: {:?}",
                 signature_strs.len()
             );
-            if signature_strs.len() > MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS {
+            // Searching the transaction history is much more expensive, so fewer signatures are
+            // accepted then
+            let max_query_items = if config
+                .as_ref()
+                .map(|config| config.search_transaction_history)
+                .unwrap_or(false)
+            {
+                MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS
+            } else {
+                MAX_GET_SIGNATURE_STATUSES_RECENT_QUERY_ITEMS
+            };
+            if signature_strs.len() > max_query_items {
                 return Box::pin(future::err(Error::invalid_params(format!(
                     "Too many inputs provided; max {}",
-                    MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS
+                    max_query_items
                 ))));
             }
             let mut signatures: Vec<Signature> = vec![];
@@ -3408,9 +4087,11 @@ pub mod rpc_full {
             }
 
             if !config.skip_preflight {
+                let _preflight_span = TransactionSpan::start("rpc_preflight", &signature);
                 if let Err(e) = verify_transaction(&transaction, &preflight_bank.feature_set) {
                     return Err(e);
                 }
+                verify_durable_nonce(&transaction, preflight_bank)?;
 
                 match meta.health.check() {
                     RpcHealthStatus::Ok => (),
@@ -3435,6 +4116,7 @@ pub mod rpc_full {
                     logs,
                     post_simulation_accounts: _,
                     units_consumed,
+                    compute_budget_exceeded,
                 } = preflight_bank.simulate_transaction(transaction)
                 {
                     match err {
@@ -3452,6 +4134,7 @@ pub mod rpc_full {
                             logs: Some(logs),
                             accounts: None,
                             units_consumed: Some(units_consumed),
+                            compute_budget_exceeded: compute_budget_exceeded.map(Into::into),
                         },
                     }
                     .into());
@@ -3477,81 +4160,74 @@ pub mod rpc_full {
             debug!("simulate_transaction rpc request received");
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (_, mut unsanitized_tx) =
-                decode_and_deserialize::<VersionedTransaction>(data, encoding)?;
-
             let bank = &*meta.bank(config.commitment);
-            if config.replace_recent_blockhash {
-                if config.sig_verify {
-                    return Err(Error::invalid_params(
-                        "sigVerify may not be used with replaceRecentBlockhash",
-                    ));
-                }
-                unsanitized_tx
-                    .message
-                    .set_recent_blockhash(bank.last_blockhash());
-            }
-
-            let transaction = sanitize_transaction(unsanitized_tx)?;
-            if config.sig_verify {
-                verify_transaction(&transaction, &bank.feature_set)?;
-            }
-
-            let TransactionSimulationResult {
-                result,
-                logs,
-                post_simulation_accounts,
-                units_consumed,
-            } = bank.simulate_transaction(transaction);
-
-            let accounts = if let Some(config_accounts) = config.accounts {
-                let accounts_encoding = config_accounts
-                    .encoding
-                    .unwrap_or(UiAccountEncoding::Base64);
-
-                if accounts_encoding == UiAccountEncoding::Binary
-                    || accounts_encoding == UiAccountEncoding::Base58
-                {
-                    return Err(Error::invalid_params("base58 encoding not supported"));
-                }
-
-                if config_accounts.addresses.len() > post_simulation_accounts.len() {
-                    return Err(Error::invalid_params(format!(
-                        "Too many accounts provided; max {}",
-                        post_simulation_accounts.len()
-                    )));
-                }
-
-                let mut accounts = vec![];
-                for address_str in config_accounts.addresses {
-                    let address = verify_pubkey(&address_str)?;
-                    accounts.push(if result.is_err() {
-                        None
-                    } else {
-                        post_simulation_accounts
-                            .iter()
-                            .find(|(key, _account)| key == &address)
-                            .map(|(pubkey, account)| {
-                                UiAccount::encode(pubkey, account, accounts_encoding, None, None)
-                            })
-                    });
-                }
-                Some(accounts)
-            } else {
-                None
-            };
+            let transaction = sanitize_simulated_transaction(
+                data,
+                encoding,
+                bank,
+                config.sig_verify,
+                config.replace_recent_blockhash,
+            )?;
 
+            let simulation_result = bank.simulate_transaction(transaction);
+            let max_addresses = simulation_result.post_simulation_accounts.len();
             Ok(new_response(
                 bank,
-                RpcSimulateTransactionResult {
-                    err: result.err(),
-                    logs: Some(logs),
-                    accounts,
-                    units_consumed: Some(units_consumed),
-                },
+                encode_simulation_result(
+                    simulation_result,
+                    config.accounts.as_ref(),
+                    max_addresses,
+                )?,
             ))
         }
 
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateBundleConfig>,
+        ) -> Result<RpcResponse<Vec<Option<RpcSimulateTransactionResult>>>> {
+            debug!("simulate_bundle rpc request received: {:?}", data.len());
+            if data.is_empty() || data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Bundles must have between 1 and {} transactions",
+                    MAX_SIMULATE_BUNDLE_TRANSACTIONS
+                )));
+            }
+            let config = config.unwrap_or_default();
+            let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let bank = &*meta.bank(config.commitment);
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    sanitize_simulated_transaction(
+                        data,
+                        encoding,
+                        bank,
+                        config.sig_verify,
+                        config.replace_recent_blockhash,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let results = bank
+                .simulate_bundle(transactions)
+                .into_iter()
+                .map(|simulation_result| {
+                    simulation_result
+                        .map(|simulation_result| {
+                            encode_simulation_result(
+                                simulation_result,
+                                config.accounts.as_ref(),
+                                MAX_MULTIPLE_ACCOUNTS,
+                            )
+                        })
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(new_response(bank, results))
+        }
+
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("minimum_ledger_slot rpc request received");
             meta.minimum_ledger_slot()
@@ -4239,6 +4915,7 @@ pub fn create_test_transactions_and_populate_blockstore(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
             blockstore,
+            None,
             &Arc::new(AtomicBool::new(false)),
         );
 
@@ -4277,7 +4954,13 @@ pub mod tests {
         bincode::deserialize,
         jsonrpc_core::{futures, ErrorCode, MetaIoHandler, Output, Response, Value},
         jsonrpc_core_client::transports::local,
-        solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        solana_client::{
+            rpc_custom_error::{
+                JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+                JSON_RPC_SERVER_ERROR_TRANSACTION_NONCE_VERIFICATION_FAILURE,
+            },
+            rpc_filter::{Memcmp, MemcmpEncodedBytes},
+        },
         solana_gossip::{contact_info::ContactInfo, socketaddr},
         solana_ledger::{
             blockstore_meta::PerfSample,
@@ -4610,9 +5293,10 @@ pub mod tests {
             .expect("actual response deserialization");
 
         let expected = format!(
-            r#"{{"jsonrpc":"2.0","result":[{{"pubkey": "{}", "gossip": "127.0.0.1:1235", "shredVersion": 0, "tpu": "127.0.0.1:1234", "rpc": "127.0.0.1:{}", "version": null, "featureSet": null}}],"id":1}}"#,
+            r#"{{"jsonrpc":"2.0","result":[{{"pubkey": "{}", "gossip": "127.0.0.1:1235", "shredVersion": 0, "tpu": "127.0.0.1:1234", "tpuForwards": "127.0.0.1:1237", "tpuVote": "127.0.0.1:1241", "serveRepair": "127.0.0.1:1240", "rpc": "127.0.0.1:{}", "pubsub": "127.0.0.1:{}", "version": null, "featureSet": null}}],"id":1}}"#,
             leader_pubkey,
-            rpc_port::DEFAULT_RPC_PORT
+            rpc_port::DEFAULT_RPC_PORT,
+            rpc_port::DEFAULT_RPC_PUBSUB_PORT
         );
 
         let expected: Response =
@@ -4675,6 +5359,70 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_slot_time_estimate() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let commitment = Some(CommitmentConfig::processed());
+        let bank = meta.bank(commitment);
+        let current_time = bank.clock().unix_timestamp;
+        // The single performance sample of the blockstore is of 1 slot in 60 seconds
+        let estimator = SlotTimeEstimator::from_samples(
+            bank.ns_per_slot() as f64 / 1_000_000.0,
+            vec![(1, 60_000)],
+        );
+        let estimate = meta
+            .get_slot_time_estimate(Some(RpcSlotTimeEstimateConfig {
+                slot: Some(bank.slot() + 10),
+                commitment,
+                ..RpcSlotTimeEstimateConfig::default()
+            }))
+            .unwrap();
+        assert_eq!(
+            estimate,
+            RpcSlotTimeEstimate {
+                current_slot: bank.slot(),
+                current_time,
+                ms_per_slot: estimator.ms_per_slot(),
+                slot: bank.slot() + 10,
+                estimated_time: estimator.estimate_slot_time(
+                    bank.slot(),
+                    current_time,
+                    bank.slot() + 10
+                ),
+            }
+        );
+
+        let estimate = meta
+            .get_slot_time_estimate(Some(RpcSlotTimeEstimateConfig {
+                commitment,
+                ..RpcSlotTimeEstimateConfig::default()
+            }))
+            .unwrap();
+        assert_eq!(
+            estimate.slot,
+            bank.epoch_schedule()
+                .get_first_slot_in_epoch(bank.epoch() + 1)
+        );
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getSlotTimeEstimate","params":[{"slot":10,"epoch":1}]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "error": {
+                "code": -32602,
+                "message": "Only one of slot or epoch may be specified"
+            },
+            "id": 1
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_get_slot_leader() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5008,7 +5756,7 @@ pub mod tests {
             bob_pubkey
         );
 
-        let rep = io.handle_request_sync(&req, meta);
+        let rep = io.handle_request_sync(&req, meta.clone());
         let res: Response = serde_json::from_str(&rep.expect("actual response"))
             .expect("actual response deserialization");
 
@@ -5022,6 +5770,35 @@ pub mod tests {
             panic!("Expected single response");
         };
         assert_eq!(schedule, Some(HashMap::default()));
+
+        // Select the epoch directly, with the slot indices delta encoded
+        let full_schedule =
+            solana_ledger::leader_schedule_utils::leader_schedule(bank.epoch(), &bank).unwrap();
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getLeaderSchedule", "params": [null, {{ "identity": "{}", "epoch": {}, "encoding": "delta" }}]}}"#,
+            bank.collector_id(),
+            bank.epoch(),
+        );
+        let rep = io.handle_request_sync(&req, meta.clone());
+        let res: Value = serde_json::from_str(&rep.expect("actual response"))
+            .expect("actual response deserialization");
+        let schedule: RpcLeaderSchedule = serde_json::from_value(res["result"].clone()).unwrap();
+        let deltas = &schedule[&bank.collector_id().to_string()];
+        assert_eq!(deltas[0], 0);
+        assert!(deltas[1..].iter().all(|delta| *delta == 1));
+        assert_eq!(
+            delta_decode_leader_slot_indices(deltas),
+            (0..full_schedule.get_slot_leaders().len()).collect::<Vec<_>>()
+        );
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getLeaderSchedule", "params": [0, {{ "epoch": {} }}]}}"#,
+            bank.epoch(),
+        );
+        let rep = io.handle_request_sync(&req, meta);
+        let res: Value = serde_json::from_str(&rep.expect("actual response"))
+            .expect("actual response deserialization");
+        res["error"].as_object().unwrap();
     }
 
     #[test]
@@ -5152,6 +5929,68 @@ pub mod tests {
             bs58::encode(&data[1..3]).into_string(),
         );
 
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"base64+zstd", "dataSlice": {{"length": 2, "offset": 1}}}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let ui_account: UiAccount =
+            serde_json::from_value(result["result"]["value"].clone()).unwrap();
+        let decoded: AccountSharedData = ui_account.decode().unwrap();
+        assert_eq!(decoded.data(), &data[1..3]);
+
+        for (compression, encoding) in [("zstd", "base64+zstd"), ("gzip", "base64+gzip")] {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"base64", "compression":"{}"}}]}}"#,
+                address, compression
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert_eq!(result["result"]["value"]["data"][1], encoding);
+            let ui_account: UiAccount =
+                serde_json::from_value(result["result"]["value"].clone()).unwrap();
+            let decoded: AccountSharedData = ui_account.decode().unwrap();
+            assert_eq!(decoded.data(), &data[..]);
+        }
+
+        // Compression requires the base64 encoding
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"jsonParsed", "compression":"gzip"}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["code"],
+            json!(ErrorCode::InvalidParams.code())
+        );
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"minContextSlot": 0}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"]["context"]["slot"], 0);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"minContextSlot": 1}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["code"],
+            JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+        );
+        assert_eq!(result["error"]["data"]["contextSlot"], 0);
+
         let req = format!(
             r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"jsonParsed", "dataSlice": {{"length": 2, "offset": 1}}}}]}}"#,
             address
@@ -5273,10 +6112,80 @@ pub mod tests {
             }}"#,
             bob_pubkey, non_existent_address, address,
         );
-        let res = io.handle_request_sync(&req, meta);
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        result["error"].as_object().unwrap();
+
+        // Test the status of the accounts
+        let req = format!(
+            r#"{{
+                "jsonrpc":"2.0","id":1,"method":"getMultipleAccounts","params":[
+                ["{}", "{}", "{}"],
+                {{"withStatus": true}}
+                ]
+            }}"#,
+            bob_pubkey, non_existent_address, address,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let value = &result["result"]["value"];
+        assert_eq!(value["remaining"], 0);
+        assert_eq!(value["accounts"].as_array().unwrap().len(), 3);
+        assert_eq!(value["accounts"][0]["status"], "found");
+        assert_eq!(value["accounts"][0]["account"]["lamports"], 20);
+        assert_eq!(value["accounts"][1]["status"], "doesNotExist");
+        assert_eq!(value["accounts"][1]["account"], Value::Null);
+        assert_eq!(value["accounts"][2]["status"], "found");
+
+        // Test compressed account data, in base64 by default
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getMultipleAccounts","params":[["{}", "{}"], {{"compression":"gzip"}}]}}"#,
+            non_existent_address, address,
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let value = &result["result"]["value"];
+        assert_eq!(value[0], Value::Null);
+        assert_eq!(value[1]["data"][1], "base64+gzip");
+        let ui_account: UiAccount = serde_json::from_value(value[1].clone()).unwrap();
+        let decoded: AccountSharedData = ui_account.decode().unwrap();
+        assert_eq!(decoded.data(), &data[..]);
+
+        // Test too many inputs, with and without partial results
+        let pubkey_strs: Vec<_> = (0..MAX_MULTIPLE_ACCOUNTS + 2)
+            .map(|_| solana_sdk::pubkey::new_rand().to_string())
+            .collect();
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [pubkey_strs],
+        })
+        .to_string();
+        let res = io.handle_request_sync(&req, meta.clone());
         let result: Value = serde_json::from_str(&res.expect("actual response"))
             .expect("actual response deserialization");
         result["error"].as_object().unwrap();
+
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [pubkey_strs, {"allowPartial": true}],
+        })
+        .to_string();
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let value = &result["result"]["value"];
+        assert_eq!(value["remaining"], 2);
+        assert_eq!(
+            value["accounts"].as_array().unwrap().len(),
+            MAX_MULTIPLE_ACCOUNTS
+        );
     }
 
     #[test]
@@ -5766,6 +6675,137 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_simulate_durable_nonce_transaction() {
+        let RpcHandler {
+            io,
+            meta,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+
+        // The nonce isn't a recent blockhash of the bank
+        let nonce_pubkey = solana_sdk::pubkey::new_rand();
+        let stored_nonce = Hash::new_unique();
+        let nonce_account = AccountSharedData::new_data(
+            bank.get_minimum_balance_for_rent_exemption(nonce::State::size()),
+            &nonce::state::Versions::new_current(nonce::State::Initialized(
+                nonce::state::Data::new(alice.pubkey(), stored_nonce, 5000),
+            )),
+            &system_program::id(),
+        )
+        .unwrap();
+        bank.store_account(&nonce_pubkey, &nonce_account);
+        bank.freeze();
+
+        let message = Message::new_with_nonce(
+            vec![system_instruction::transfer(
+                &alice.pubkey(),
+                &solana_sdk::pubkey::new_rand(),
+                1234,
+            )],
+            Some(&alice.pubkey()),
+            &nonce_pubkey,
+            &alice.pubkey(),
+        );
+        let simulate_transaction = |nonce: Hash, config: Value| {
+            let tx = Transaction::new(&[&alice], message.clone(), nonce);
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"simulateTransaction","params":["{}", {}]}}"#,
+                bs58::encode(serialize(&tx).unwrap()).into_string(),
+                config,
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            serde_json::from_str::<Value>(&res.unwrap()).unwrap()
+        };
+
+        let json = simulate_transaction(stored_nonce, json!({"sigVerify": true}));
+        assert_eq!(json["result"]["value"]["err"], Value::Null);
+
+        // Other nonces are rejected, unless replaced by the stored one
+        let json = simulate_transaction(Hash::new_unique(), json!({}));
+        assert_eq!(
+            json["error"]["code"],
+            JSON_RPC_SERVER_ERROR_TRANSACTION_NONCE_VERIFICATION_FAILURE
+        );
+        let json =
+            simulate_transaction(Hash::new_unique(), json!({"replaceRecentBlockhash": true}));
+        assert_eq!(json["result"]["value"]["err"], Value::Null);
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let RpcHandler {
+            io,
+            meta,
+            blockhash,
+            alice,
+            bank,
+            ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+        bank.freeze();
+
+        let bob = Keypair::new();
+        let carol_pubkey = solana_sdk::pubkey::new_rand();
+        let fund = system_transaction::transfer(&alice, &bob.pubkey(), 1234, blockhash);
+        // Alice pays the fee, so that Bob's balance only depends on the transfers
+        let spend = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &bob.pubkey(),
+                &carol_pubkey,
+                1000,
+            )],
+            Some(&alice.pubkey()),
+            &[&alice, &bob],
+            blockhash,
+        );
+        let encode = |tx: &Transaction| bs58::encode(serialize(tx).unwrap()).into_string();
+        let simulate_bundle = |txs: &[&Transaction]| {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "simulateBundle",
+                "params": [
+                    txs.iter().map(|tx| encode(tx)).collect::<Vec<_>>(),
+                    {
+                        "sigVerify": true,
+                        "accounts": {
+                            "encoding": "base64",
+                            "addresses": [bob.pubkey().to_string(), carol_pubkey.to_string()]
+                        }
+                    }
+                ]
+            });
+            let res = io.handle_request_sync(&req.to_string(), meta.clone());
+            serde_json::from_str::<Value>(&res.expect("actual response"))
+                .expect("actual response deserialization")
+        };
+
+        // Each transaction sees the accounts written by the previous ones
+        let result = simulate_bundle(&[&fund, &spend]);
+        let value = &result["result"]["value"];
+        assert_eq!(value[0]["err"], Value::Null);
+        assert_eq!(value[0]["accounts"][0]["lamports"], 1234);
+        assert_eq!(value[0]["accounts"][1], Value::Null);
+        assert_eq!(value[1]["err"], Value::Null);
+        assert_eq!(value[1]["accounts"][0]["lamports"], 234);
+        assert_eq!(value[1]["accounts"][1]["lamports"], 1000);
+
+        // The bundle stops at the first failed transaction
+        let result = simulate_bundle(&[&spend, &fund]);
+        let value = &result["result"]["value"];
+        assert_ne!(value[0]["err"], Value::Null);
+        assert_eq!(value[0]["accounts"], json!([null, null]));
+        assert_eq!(value[1], Value::Null);
+
+        // Nothing was committed
+        assert_eq!(bank.get_balance(&bob.pubkey()), 0);
+
+        let result = simulate_bundle(&[]);
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     #[should_panic]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -6283,6 +7323,66 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_error_codes() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getErrorCodes"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let error_codes: RpcErrorCodes = if let Response::Single(res) = result {
+            if let Output::Success(res) = res {
+                serde_json::from_value(res.result).unwrap()
+            } else {
+                panic!("Expected success");
+            }
+        } else {
+            panic!("Expected single response");
+        };
+        let code_of = |codes: &[RpcErrorCode], name: &str| {
+            codes
+                .iter()
+                .find(|error_code| error_code.name == name)
+                .map(|error_code| error_code.code)
+        };
+        assert_eq!(
+            code_of(&error_codes.transaction_errors, "InstructionError"),
+            Some(TransactionError::InstructionError(0, InstructionError::GenericError).code())
+        );
+        assert_eq!(
+            code_of(&error_codes.instruction_errors, "Custom"),
+            Some(InstructionError::Custom(42).code())
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_health_detail() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io, meta, alice, ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getHealthDetail"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "result": {
+                "status": "ok",
+                "numSlotsBehind": null,
+                "identity": alice.pubkey().to_string(),
+                "duplicateInstances": [],
+            },
+            "id": 1
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     fn test_basic_slot(method: &str, expected: Slot) {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
         let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
@@ -6314,6 +7414,7 @@ pub mod tests {
             "result": {
                 "solana-core": version.to_string(),
                 "feature-set": version.feature_set,
+                "account-compression": ["zstd", "gzip"],
             },
             "id": 1
         });
@@ -0,0 +1,373 @@
+//! The `rpc_compression` module compresses the JSON RPC responses of clients accepting a gzip or
+//! zstd `Content-Encoding`, which shrinks the largest responses (`getProgramAccounts`,
+//! `getBlock`, ...) by an order of magnitude.
+//!
+//! `jsonrpc_http_server` can't post-process its responses, so the requests of these clients are
+//! handled by the `RequestMiddleware` of the service instead.
+
+use {
+    crate::{rpc::JsonRpcRequestProcessor, slow_query_log::SlowQueryMiddleware},
+    jsonrpc_core::MetaIoHandler,
+    jsonrpc_http_server::{hyper, RequestMiddlewareAction},
+    std::{
+        io::{self, Write},
+        sync::Arc,
+        time::Instant,
+    },
+};
+
+pub type RpcIoHandler = MetaIoHandler<JsonRpcRequestProcessor, SlowQueryMiddleware>;
+
+/// Responses smaller than this aren't compressed by default
+pub const DEFAULT_RPC_COMPRESSION_MIN_BYTES: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContentEncoding {
+    Gzip,
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Picks the encoding of the response from the `Accept-Encoding` header of the request,
+    /// preferring zstd
+    pub fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let mut gzip = false;
+        let mut zstd = false;
+        for coding in accept_encoding.split(',') {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .map(|q| q <= 0.0)
+                    .unwrap_or(false)
+            });
+            if refused {
+                continue;
+            }
+            match name.to_ascii_lowercase().as_str() {
+                "gzip" | "x-gzip" => gzip = true,
+                "zstd" => zstd = true,
+                _ => {}
+            }
+        }
+        if zstd {
+            Some(Self::Zstd)
+        } else if gzip {
+            Some(Self::Gzip)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip => {
+                let mut e =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                e.write_all(data)?;
+                e.finish()
+            }
+            Self::Zstd => {
+                let mut e = zstd::stream::write::Encoder::new(Vec::new(), 0)?;
+                e.write_all(data)?;
+                e.finish()
+            }
+        }
+    }
+}
+
+pub struct RpcCompression {
+    min_bytes: usize,
+    max_request_body_size: usize,
+    io: Arc<RpcIoHandler>,
+    request_processor: JsonRpcRequestProcessor,
+}
+
+impl RpcCompression {
+    pub fn new(
+        min_bytes: usize,
+        max_request_body_size: usize,
+        io: Arc<RpcIoHandler>,
+        request_processor: JsonRpcRequestProcessor,
+    ) -> Self {
+        Self {
+            min_bytes,
+            max_request_body_size,
+            io,
+            request_processor,
+        }
+    }
+
+    /// Returns the encoding of the response to `request` if it's a JSON RPC call from a client
+    /// accepting a compressed response
+    pub fn encoding(&self, request: &hyper::Request<hyper::Body>) -> Option<ContentEncoding> {
+        if request.method() != hyper::Method::POST {
+            return None;
+        }
+        let is_json = request
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| {
+                content_type
+                    .to_ascii_lowercase()
+                    .starts_with("application/json")
+            })
+            .unwrap_or(false);
+        if !is_json {
+            return None;
+        }
+        request
+            .headers()
+            .get(hyper::header::ACCEPT_ENCODING)
+            .and_then(|accept_encoding| accept_encoding.to_str().ok())
+            .and_then(ContentEncoding::negotiate)
+    }
+
+    fn response(
+        status: hyper::StatusCode,
+        encoding: Option<ContentEncoding>,
+        body: Vec<u8>,
+    ) -> hyper::Response<hyper::Body> {
+        let mut builder = hyper::Response::builder()
+            .status(status)
+            .header(
+                hyper::header::CONTENT_TYPE,
+                "application/json; charset=utf-8",
+            )
+            .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(hyper::header::VARY, "Accept-Encoding");
+        if let Some(encoding) = encoding {
+            builder = builder.header(hyper::header::CONTENT_ENCODING, encoding.as_str());
+        }
+        builder.body(hyper::Body::from(body)).unwrap()
+    }
+
+    fn error_response(status: hyper::StatusCode, message: &str) -> hyper::Response<hyper::Body> {
+        hyper::Response::builder()
+            .status(status)
+            .header(hyper::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(hyper::Body::from(format!("{}\n", message)))
+            .unwrap()
+    }
+
+    async fn read_body(
+        mut body: hyper::Body,
+        max_request_body_size: usize,
+    ) -> Result<Vec<u8>, hyper::Response<hyper::Body>> {
+        use hyper::body::HttpBody;
+
+        let mut request = vec![];
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(|err| {
+                Self::error_response(hyper::StatusCode::BAD_REQUEST, &err.to_string())
+            })?;
+            if request.len() + chunk.len() > max_request_body_size {
+                return Err(Self::error_response(
+                    hyper::StatusCode::PAYLOAD_TOO_LARGE,
+                    "request body size exceeds allowed maximum",
+                ));
+            }
+            request.extend_from_slice(&chunk);
+        }
+        Ok(request)
+    }
+
+    pub fn process(
+        &self,
+        request: hyper::Request<hyper::Body>,
+        encoding: ContentEncoding,
+    ) -> RequestMiddlewareAction {
+        let meta = self.request_processor.with_request_received(Instant::now());
+        let io = self.io.clone();
+        let min_bytes = self.min_bytes;
+        let max_request_body_size = self.max_request_body_size;
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                let request = match Self::read_body(request.into_body(), max_request_body_size)
+                    .await
+                    .and_then(|request| {
+                        String::from_utf8(request).map_err(|_| {
+                            Self::error_response(
+                                hyper::StatusCode::BAD_REQUEST,
+                                "request body is not valid UTF-8",
+                            )
+                        })
+                    }) {
+                    Ok(request) => request,
+                    Err(response) => return Ok(response),
+                };
+
+                let response = match io.handle_request(&request, meta).await {
+                    Some(response) => format!("{}\n", response).into_bytes(),
+                    None => vec![],
+                };
+                if response.len() < min_bytes {
+                    return Ok(Self::response(hyper::StatusCode::OK, None, response));
+                }
+
+                let uncompressed_bytes = response.len();
+                let compressed = tokio::task::spawn_blocking(move || encoding.compress(&response))
+                    .await
+                    .unwrap_or_else(|err| Err(io::Error::new(io::ErrorKind::Other, err)));
+                Ok(match compressed {
+                    Ok(compressed) => {
+                        datapoint_info!(
+                            "rpc-response-compression",
+                            ("encoding", encoding.as_str().to_string(), String),
+                            ("uncompressed_bytes", uncompressed_bytes, i64),
+                            ("compressed_bytes", compressed.len(), i64),
+                        );
+                        Self::response(hyper::StatusCode::OK, Some(encoding), compressed)
+                    }
+                    Err(err) => {
+                        warn!("failed to compress rpc response: {}", err);
+                        Self::error_response(
+                            hyper::StatusCode::INTERNAL_SERVER_ERROR,
+                            "failed to compress response",
+                        )
+                    }
+                })
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, jsonrpc_core::Value, solana_runtime::bank::Bank,
+        solana_sdk::genesis_config::create_genesis_config,
+        solana_streamer::socket::SocketAddrSpace, std::io::Read,
+    };
+
+    #[test]
+    fn test_negotiate_content_encoding() {
+        assert_eq!(ContentEncoding::negotiate(""), None);
+        assert_eq!(ContentEncoding::negotiate("identity, br"), None);
+        assert_eq!(
+            ContentEncoding::negotiate("gzip, deflate"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::negotiate("gzip, ZSTD"),
+            Some(ContentEncoding::Zstd)
+        );
+        assert_eq!(
+            ContentEncoding::negotiate("zstd;q=0, gzip;q=0.5"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(ContentEncoding::negotiate("gzip; q=0.0"), None);
+    }
+
+    fn decompress(encoding: ContentEncoding, data: &[u8]) -> Vec<u8> {
+        let mut decompressed = vec![];
+        match encoding {
+            ContentEncoding::Gzip => flate2::read::GzDecoder::new(data)
+                .read_to_end(&mut decompressed)
+                .unwrap(),
+            ContentEncoding::Zstd => zstd::stream::read::Decoder::new(data)
+                .unwrap()
+                .read_to_end(&mut decompressed)
+                .unwrap(),
+        };
+        decompressed
+    }
+
+    fn call(compression: &RpcCompression, method: &str, encoding: ContentEncoding) -> Vec<u8> {
+        let request = hyper::Request::builder()
+            .method(hyper::Method::POST)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .header(hyper::header::ACCEPT_ENCODING, encoding.as_str())
+            .body(hyper::Body::from(format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"{}"}}"#,
+                method
+            )))
+            .unwrap();
+        assert_eq!(compression.encoding(&request), Some(encoding));
+        let response = match compression.process(request, encoding) {
+            RequestMiddlewareAction::Respond { response, .. } => response,
+            RequestMiddlewareAction::Proceed { .. } => panic!("request not handled"),
+        };
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let response = response.await.unwrap();
+            assert_eq!(response.status(), hyper::StatusCode::OK);
+            let content_encoding = response
+                .headers()
+                .get(hyper::header::CONTENT_ENCODING)
+                .map(|content_encoding| content_encoding.to_str().unwrap().to_string());
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            match content_encoding {
+                Some(content_encoding) => {
+                    assert_eq!(content_encoding, encoding.as_str());
+                    decompress(encoding, &body)
+                }
+                None => body.to_vec(),
+            }
+        })
+    }
+
+    #[test]
+    fn test_compressed_response() {
+        let (genesis_config, _) = create_genesis_config(1);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let request_processor =
+            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let mut io = MetaIoHandler::with_middleware(SlowQueryMiddleware::new(None));
+        io.add_method_with_meta("small", |_params, _meta: JsonRpcRequestProcessor| async {
+            Ok(Value::Bool(true))
+        });
+        io.add_method_with_meta("large", |_params, _meta: JsonRpcRequestProcessor| async {
+            Ok(Value::String("a".repeat(10_000)))
+        });
+        let compression = RpcCompression::new(1_000, 1_000, Arc::new(io), request_processor);
+
+        for encoding in [ContentEncoding::Gzip, ContentEncoding::Zstd] {
+            let response = call(&compression, "small", encoding);
+            assert_eq!(
+                response,
+                b"{\"jsonrpc\":\"2.0\",\"result\":true,\"id\":1}\n"
+            );
+
+            let response: Value =
+                serde_json::from_slice(&call(&compression, "large", encoding)).unwrap();
+            assert_eq!(response["result"], Value::String("a".repeat(10_000)));
+        }
+    }
+
+    #[test]
+    fn test_not_json_rpc_request() {
+        let (genesis_config, _) = create_genesis_config(1);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let request_processor =
+            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let io = MetaIoHandler::with_middleware(SlowQueryMiddleware::new(None));
+        let compression = RpcCompression::new(0, 1_000, Arc::new(io), request_processor);
+
+        let request = hyper::Request::builder()
+            .method(hyper::Method::GET)
+            .header(hyper::header::ACCEPT_ENCODING, "gzip")
+            .body(hyper::Body::empty())
+            .unwrap();
+        assert_eq!(compression.encoding(&request), None);
+
+        let request = hyper::Request::builder()
+            .method(hyper::Method::POST)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(hyper::Body::empty())
+            .unwrap();
+        assert_eq!(compression.encoding(&request), None);
+    }
+}
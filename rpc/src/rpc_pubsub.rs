@@ -16,8 +16,8 @@ use {
     solana_account_decoder::{UiAccount, UiAccountEncoding},
     solana_client::{
         rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         rpc_response::{
             Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult, RpcVote,
@@ -56,7 +56,7 @@ pub trait RpcSolPubSub {
         meta: Self::Metadata,
         subscriber: Subscriber<RpcResponse<UiAccount>>,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     );
 
     // Unsubscribe from account notification subscription.
@@ -120,6 +120,33 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Get logs for all transactions invoking the specified program, including through
+    // cross-program invocations
+    #[pubsub(
+        subscription = "programChainNotification",
+        subscribe,
+        name = "programChainSubscribe"
+    )]
+    fn program_chain_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<RpcResponse<RpcLogsResponse>>,
+        pubkey_str: String,
+        config: Option<RpcTransactionLogsConfig>,
+    );
+
+    // Unsubscribe from program chain notification subscription.
+    #[pubsub(
+        subscription = "programChainNotification",
+        unsubscribe,
+        name = "programChainUnsubscribe"
+    )]
+    fn program_chain_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when signature is verified
     // Accepts signature parameter as base-58 encoded string
     #[pubsub(
@@ -234,7 +261,7 @@ mod internal {
         fn account_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -266,6 +293,19 @@ mod internal {
         #[rpc(name = "logsUnsubscribe")]
         fn logs_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Get logs for all transactions invoking the specified program, including through
+        // cross-program invocations
+        #[rpc(name = "programChainSubscribe")]
+        fn program_chain_subscribe(
+            &self,
+            pubkey_str: String,
+            config: Option<RpcTransactionLogsConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from program chain notification subscription.
+        #[rpc(name = "programChainUnsubscribe")]
+        fn program_chain_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when signature is verified
         // Accepts signature parameter as base-58 encoded string
         #[rpc(name = "signatureSubscribe")]
@@ -360,6 +400,18 @@ impl RpcSolPubSubImpl {
     }
 }
 
+/// Subscriptions are notified of the slots to come, so there's no context slot to hold back
+fn reject_min_context_slot(config: &RpcAccountInfoConfig) -> Result<()> {
+    if config.min_context_slot.is_some() {
+        return Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: "Invalid Request: minContextSlot is not supported by subscriptions".into(),
+            data: None,
+        });
+    }
+    Ok(())
+}
+
 fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     param_str.parse::<T>().map_err(|_e| Error {
         code: ErrorCode::InvalidParams,
@@ -372,14 +424,19 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let config = config.unwrap_or_default();
+        let RpcAccountSubscribeConfig {
+            account_config: config,
+            skip_unchanged,
+        } = config.unwrap_or_default();
+        reject_min_context_slot(&config)?;
         let params = AccountSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             commitment: config.commitment.unwrap_or_default(),
             data_slice: config.data_slice,
             encoding: config.encoding.unwrap_or(UiAccountEncoding::Binary),
+            skip_unchanged: skip_unchanged.unwrap_or_default(),
         };
         self.subscribe(SubscriptionParams::Account(params))
     }
@@ -394,6 +451,7 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         config: Option<RpcProgramAccountsConfig>,
     ) -> Result<SubscriptionId> {
         let config = config.unwrap_or_default();
+        reject_min_context_slot(&config.account_config)?;
         let params = ProgramSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             filters: config.filters.unwrap_or_default(),
@@ -417,19 +475,35 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         filter: RpcTransactionLogsFilter,
         config: Option<RpcTransactionLogsConfig>,
     ) -> Result<SubscriptionId> {
+        let max_addresses = self.config.max_logs_subscription_addresses;
+        let addresses = |keys: &[String], param_name: &str| -> Result<Vec<Pubkey>> {
+            if keys.is_empty() || keys.len() > max_addresses {
+                return Err(Error {
+                    code: ErrorCode::InvalidParams,
+                    message: format!(
+                        "Invalid Request: Between 1 and {} addresses supported",
+                        max_addresses
+                    ),
+                    data: None,
+                });
+            }
+            let mut addresses = keys
+                .iter()
+                .map(|key| param::<Pubkey>(key, param_name))
+                .collect::<Result<Vec<_>>>()?;
+            addresses.sort_unstable();
+            addresses.dedup();
+            Ok(addresses)
+        };
         let params = LogsSubscriptionParams {
             kind: match filter {
                 RpcTransactionLogsFilter::All => LogsSubscriptionKind::All,
                 RpcTransactionLogsFilter::AllWithVotes => LogsSubscriptionKind::AllWithVotes,
                 RpcTransactionLogsFilter::Mentions(keys) => {
-                    if keys.len() != 1 {
-                        return Err(Error {
-                            code: ErrorCode::InvalidParams,
-                            message: "Invalid Request: Only 1 address supported".into(),
-                            data: None,
-                        });
-                    }
-                    LogsSubscriptionKind::Single(param::<Pubkey>(&keys[0], "mentions")?)
+                    LogsSubscriptionKind::Mentions(addresses(&keys, "mentions")?)
+                }
+                RpcTransactionLogsFilter::Programs(keys) => {
+                    LogsSubscriptionKind::Programs(addresses(&keys, "programs")?)
                 }
             },
             commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
@@ -441,6 +515,22 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn program_chain_subscribe(
+        &self,
+        pubkey_str: String,
+        config: Option<RpcTransactionLogsConfig>,
+    ) -> Result<SubscriptionId> {
+        let params = LogsSubscriptionParams {
+            kind: LogsSubscriptionKind::ProgramChain(param::<Pubkey>(&pubkey_str, "pubkey")?),
+            commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
+        };
+        self.subscribe(SubscriptionParams::Logs(params))
+    }
+
+    fn program_chain_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        self.unsubscribe(id)
+    }
+
     fn signature_subscribe(
         &self,
         signature_str: String,
@@ -772,11 +862,15 @@ mod tests {
 
         rpc.account_subscribe(
             stake_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
@@ -887,11 +981,15 @@ mod tests {
 
         rpc.account_subscribe(
             nonce_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(UiAccountEncoding::JsonParsed),
-                data_slice: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
@@ -991,6 +1089,18 @@ mod tests {
 
         let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
         assert_eq!(expected, result);
+
+        // minContextSlot is rejected
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"accountSubscribe","params":["{}",{{"minContextSlot":1}}]}}"#,
+            bob_pubkey
+        );
+        let res = io.handle_request_sync(&req);
+        let expected = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid Request: minContextSlot is not supported by subscriptions"},"id":1}"#;
+        let expected: Response = serde_json::from_str(expected).unwrap();
+
+        let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(expected, result);
     }
 
     #[test]
@@ -1018,11 +1128,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
@@ -1069,11 +1183,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-            }),
+            Some(
+                RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                }
+                .into(),
+            ),
         )
         .unwrap();
 
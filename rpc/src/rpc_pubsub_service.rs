@@ -11,18 +11,24 @@ use {
     dashmap::{mapref::entry::Entry, DashMap},
     jsonrpc_core::IoHandler,
     soketto::handshake::{server, Server},
+    soketto::Sender,
     solana_metrics::TokenCounter,
     std::{
+        collections::VecDeque,
         io,
         net::SocketAddr,
         str,
-        sync::Arc,
+        sync::{Arc, Mutex},
         thread::{self, Builder, JoinHandle},
     },
     stream_cancel::{Trigger, Tripwire},
     thiserror::Error,
-    tokio::{net::TcpStream, pin, select, sync::broadcast},
-    tokio_util::compat::TokioAsyncReadCompatExt,
+    tokio::{
+        net::TcpStream,
+        pin, select,
+        sync::{broadcast, Notify},
+    },
+    tokio_util::compat::{Compat, TokioAsyncReadCompatExt},
 };
 
 pub const MAX_ACTIVE_SUBSCRIPTIONS: usize = 1_000_000;
@@ -30,6 +36,33 @@ pub const DEFAULT_QUEUE_CAPACITY_ITEMS: usize = 10_000_000;
 pub const DEFAULT_TEST_QUEUE_CAPACITY_ITEMS: usize = 100;
 pub const DEFAULT_QUEUE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
 pub const DEFAULT_WORKER_THREADS: usize = 1;
+pub const DEFAULT_CONNECTION_QUEUE_CAPACITY_ITEMS: usize = 100_000;
+pub const DEFAULT_CONNECTION_QUEUE_CAPACITY_BYTES: usize = 16 * 1024 * 1024;
+/// Default maximum number of addresses of a logs subscription
+pub const DEFAULT_MAX_LOGS_SUBSCRIPTION_ADDRESSES: usize = 32;
+
+/// What happens to the notifications of a client which doesn't read them as fast as they are
+/// made, once its connection queue is full
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlowConsumerPolicy {
+    /// Disconnect the client
+    Disconnect,
+    /// Drop the oldest queued notifications to make room for the new ones
+    DropOldest,
+    /// Drop the new notifications until the queue has room again
+    DropNewest,
+}
+
+impl From<&str> for SlowConsumerPolicy {
+    fn from(string: &str) -> Self {
+        match string {
+            "disconnect" => SlowConsumerPolicy::Disconnect,
+            "drop-oldest" => SlowConsumerPolicy::DropOldest,
+            "drop-newest" => SlowConsumerPolicy::DropNewest,
+            bad_policy => panic!("Invalid slow consumer policy: {}", bad_policy),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
@@ -39,6 +72,18 @@ pub struct PubSubConfig {
     pub queue_capacity_bytes: usize,
     pub worker_threads: usize,
     pub notification_threads: Option<usize>,
+    /// Capacity of the notification queue of each connection
+    pub connection_queue_capacity_items: usize,
+    pub connection_queue_capacity_bytes: usize,
+    pub slow_consumer_policy: SlowConsumerPolicy,
+    /// Addresses a logs subscription may mention or invoke
+    pub max_logs_subscription_addresses: usize,
+    /// Log messages of a transaction notified to the logs subscriptions, the following ones are
+    /// replaced by a marker, unlimited if zero
+    pub max_logs_notification_messages: usize,
+    /// Notifications sent to a logs subscription per second, the following ones are dropped and
+    /// counted in the next one sent, unlimited if zero
+    pub max_logs_notifications_per_second: usize,
 }
 
 impl Default for PubSubConfig {
@@ -50,6 +95,12 @@ impl Default for PubSubConfig {
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: None,
+            connection_queue_capacity_items: DEFAULT_CONNECTION_QUEUE_CAPACITY_ITEMS,
+            connection_queue_capacity_bytes: DEFAULT_CONNECTION_QUEUE_CAPACITY_BYTES,
+            slow_consumer_policy: SlowConsumerPolicy::Disconnect,
+            max_logs_subscription_addresses: DEFAULT_MAX_LOGS_SUBSCRIPTION_ADDRESSES,
+            max_logs_notification_messages: 0,
+            max_logs_notifications_per_second: 0,
         }
     }
 }
@@ -63,6 +114,12 @@ impl PubSubConfig {
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: Some(2),
+            connection_queue_capacity_items: DEFAULT_CONNECTION_QUEUE_CAPACITY_ITEMS,
+            connection_queue_capacity_bytes: DEFAULT_CONNECTION_QUEUE_CAPACITY_BYTES,
+            slow_consumer_policy: SlowConsumerPolicy::Disconnect,
+            max_logs_subscription_addresses: DEFAULT_MAX_LOGS_SUBSCRIPTION_ADDRESSES,
+            max_logs_notification_messages: 0,
+            max_logs_notifications_per_second: 0,
         }
     }
 }
@@ -178,6 +235,145 @@ impl BroadcastHandler {
     }
 }
 
+#[derive(Default)]
+struct ConnectionQueueItems {
+    // Messages, flagged if they are a response to a request
+    items: VecDeque<(Arc<String>, bool)>,
+    total_bytes: usize,
+}
+
+impl ConnectionQueueItems {
+    fn push(&mut self, item: Arc<String>, is_response: bool) {
+        self.total_bytes += item.len();
+        self.items.push_back((item, is_response));
+    }
+
+    fn pop(&mut self) -> Option<Arc<String>> {
+        let (item, _) = self.items.pop_front()?;
+        self.total_bytes -= item.len();
+        Some(item)
+    }
+
+    fn drop_oldest_notification(&mut self) -> bool {
+        let position = self.items.iter().position(|(_, is_response)| !is_response);
+        match position.and_then(|position| self.items.remove(position)) {
+            Some((item, _)) => {
+                self.total_bytes -= item.len();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Outgoing messages of a connection, written to the socket by their own task so that a slow
+/// client only holds up its own notifications. The notifications are shared with the other
+/// connections subscribed to the same subscription, they are only serialized once.
+struct ConnectionQueue {
+    items: Mutex<ConnectionQueueItems>,
+    notify: Notify,
+    capacity_items: usize,
+    capacity_bytes: usize,
+    slow_consumer_policy: SlowConsumerPolicy,
+}
+
+impl ConnectionQueue {
+    fn new(config: &PubSubConfig) -> Self {
+        Self {
+            items: Mutex::default(),
+            notify: Notify::new(),
+            capacity_items: config.connection_queue_capacity_items,
+            capacity_bytes: config.connection_queue_capacity_bytes,
+            slow_consumer_policy: config.slow_consumer_policy,
+        }
+    }
+
+    fn is_full(&self, items: &ConnectionQueueItems, item: &str) -> bool {
+        items.items.len() >= self.capacity_items
+            || items.total_bytes + item.len() > self.capacity_bytes
+    }
+
+    /// Queues a notification, applying the slow consumer policy if the queue is full
+    fn push_notification(&self, notification: Arc<String>) -> Result<(), Error> {
+        let mut items = self.items.lock().unwrap();
+        if self.is_full(&items, &notification) {
+            match self.slow_consumer_policy {
+                SlowConsumerPolicy::Disconnect => {
+                    inc_new_counter_info!("rpc-pubsub-slow-consumer-disconnects", 1);
+                    return Err(Error::SlowConsumer);
+                }
+                SlowConsumerPolicy::DropNewest => {
+                    inc_new_counter_info!("rpc-pubsub-dropped-notifications", 1);
+                    return Ok(());
+                }
+                SlowConsumerPolicy::DropOldest => {
+                    self.drop_oldest_notifications(&mut items, &notification);
+                }
+            }
+        }
+        items.push(notification, false);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Queues a response to a request of the client. Responses are never dropped: if the queue
+    /// is full, queued notifications make room for it unless the policy is to disconnect, and
+    /// the client is disconnected if it isn't even reading its responses.
+    fn push_response(&self, response: String) -> Result<(), Error> {
+        let mut items = self.items.lock().unwrap();
+        if self.is_full(&items, &response) {
+            if self.slow_consumer_policy != SlowConsumerPolicy::Disconnect {
+                self.drop_oldest_notifications(&mut items, &response);
+            }
+            if self.is_full(&items, &response) {
+                inc_new_counter_info!("rpc-pubsub-slow-consumer-disconnects", 1);
+                return Err(Error::SlowConsumer);
+            }
+        }
+        items.push(Arc::new(response), true);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Drops the oldest queued notifications until `item` fits in the queue or only responses
+    /// are left
+    fn drop_oldest_notifications(&self, items: &mut ConnectionQueueItems, item: &str) {
+        let mut num_dropped = 0;
+        while self.is_full(items, item) && items.drop_oldest_notification() {
+            num_dropped += 1;
+        }
+        inc_new_counter_info!("rpc-pubsub-dropped-notifications", num_dropped);
+    }
+
+    async fn pop(&self) -> Arc<String> {
+        loop {
+            if let Some(item) = self.items.lock().unwrap().pop() {
+                return item;
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+async fn write_queued_messages(
+    mut sender: Sender<Compat<TcpStream>>,
+    queue: Arc<ConnectionQueue>,
+) -> Result<(), Error> {
+    loop {
+        let json = queue.pop().await;
+        sender.send_text(&*json).await?;
+    }
+}
+
+/// Aborts the writer task of a connection once the connection handler returns
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 pub struct TestBroadcastReceiver {
     handler: BroadcastHandler,
@@ -253,6 +449,10 @@ enum Error {
     Broadcast(#[from] broadcast::error::RecvError),
     #[error("client has lagged behind (notification is gone)")]
     NotificationIsGone,
+    #[error("client has lagged behind (connection queue is full)")]
+    SlowConsumer,
+    #[error("connection writer failed: {0}")]
+    Writer(#[from] tokio::task::JoinError),
 }
 
 async fn handle_connection(
@@ -268,8 +468,13 @@ async fn handle_connection(
         protocol: None,
     };
     server.send_response(&accept).await?;
-    let (mut sender, mut receiver) = server.into_builder().finish();
+    let (sender, mut receiver) = server.into_builder().finish();
 
+    let queue = Arc::new(ConnectionQueue::new(&config));
+    let mut writer = AbortOnDrop(tokio::spawn(write_queued_messages(
+        sender,
+        Arc::clone(&queue),
+    )));
     let mut broadcast_receiver = subscription_control.broadcast_receiver();
     let mut data = Vec::new();
     let current_subscriptions = Arc::new(DashMap::new());
@@ -302,9 +507,10 @@ async fn handle_connection(
 
                         // In both possible error cases (closed or lagged) we disconnect the client.
                         if let Some(json) = broadcast_handler.handle(result?)? {
-                            sender.send_text(&*json).await?;
+                            queue.push_notification(json)?;
                         }
                     },
+                    result = &mut writer.0 => return result?,
                     _ = &mut tripwire => {
                         warn!("disconnecting websocket client: shutting down");
                         return Ok(())
@@ -323,7 +529,7 @@ async fn handle_connection(
         };
 
         if let Some(response) = json_rpc_handler.handle_request(data_str).await {
-            sender.send_text(&response).await?;
+            queue.push_response(response)?;
         }
         data.clear();
     }
@@ -403,4 +609,73 @@ mod tests {
         let thread = pubsub_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solana-pubsub");
     }
+
+    #[test]
+    fn test_connection_queue_slow_consumer_policies() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let new_queue = |slow_consumer_policy| {
+            ConnectionQueue::new(&PubSubConfig {
+                connection_queue_capacity_items: 2,
+                slow_consumer_policy,
+                ..PubSubConfig::default()
+            })
+        };
+        let notification = |json: &str| Arc::new(json.to_string());
+
+        let queue = new_queue(SlowConsumerPolicy::Disconnect);
+        queue.push_notification(notification("1")).unwrap();
+        queue.push_notification(notification("2")).unwrap();
+        assert!(matches!(
+            queue.push_notification(notification("3")),
+            Err(Error::SlowConsumer)
+        ));
+
+        let queue = new_queue(SlowConsumerPolicy::DropNewest);
+        queue.push_notification(notification("1")).unwrap();
+        queue.push_notification(notification("2")).unwrap();
+        queue.push_notification(notification("3")).unwrap();
+        assert_eq!(*runtime.block_on(queue.pop()), "1");
+        assert_eq!(*runtime.block_on(queue.pop()), "2");
+
+        // Responses are never dropped, notifications are dropped to make room for them
+        let queue = new_queue(SlowConsumerPolicy::DropOldest);
+        queue.push_response("response".to_string()).unwrap();
+        queue.push_notification(notification("1")).unwrap();
+        queue.push_notification(notification("2")).unwrap();
+        queue.push_notification(notification("3")).unwrap();
+        assert_eq!(*runtime.block_on(queue.pop()), "response");
+        assert_eq!(*runtime.block_on(queue.pop()), "3");
+        assert!(queue.items.lock().unwrap().items.is_empty());
+
+        // Responses make room by dropping notifications, but are bounded as well
+        let queue = new_queue(SlowConsumerPolicy::DropNewest);
+        queue.push_notification(notification("1")).unwrap();
+        queue.push_notification(notification("2")).unwrap();
+        queue.push_response("response 1".to_string()).unwrap();
+        queue.push_response("response 2".to_string()).unwrap();
+        assert!(matches!(
+            queue.push_response("response 3".to_string()),
+            Err(Error::SlowConsumer)
+        ));
+        assert_eq!(*runtime.block_on(queue.pop()), "response 1");
+        assert_eq!(*runtime.block_on(queue.pop()), "response 2");
+
+        let queue = new_queue(SlowConsumerPolicy::Disconnect);
+        queue.push_notification(notification("1")).unwrap();
+        queue.push_notification(notification("2")).unwrap();
+        assert!(matches!(
+            queue.push_response("response".to_string()),
+            Err(Error::SlowConsumer)
+        ));
+
+        // The byte capacity applies to responses too
+        let queue = ConnectionQueue::new(&PubSubConfig {
+            connection_queue_capacity_bytes: 4,
+            ..PubSubConfig::default()
+        });
+        assert!(matches!(
+            queue.push_response("response".to_string()),
+            Err(Error::SlowConsumer)
+        ));
+    }
 }
@@ -9,7 +9,9 @@ use {
             rpc_accounts::*, rpc_bank::*, rpc_deprecated_v1_7::*, rpc_deprecated_v1_9::*,
             rpc_full::*, rpc_minimal::*, rpc_obsolete_v1_7::*, *,
         },
+        rpc_compression::RpcCompression,
         rpc_health::*,
+        slow_query_log::SlowQueryMiddleware,
     },
     jsonrpc_core::{futures::prelude::*, MetaIoHandler},
     jsonrpc_http_server::{
@@ -32,23 +34,33 @@ use {
         snapshot_utils,
     },
     solana_sdk::{
-        exit::Exit, genesis_config::DEFAULT_GENESIS_DOWNLOAD_PATH, hash::Hash,
-        native_token::lamports_to_sol, pubkey::Pubkey,
+        exit::Exit,
+        genesis_config::DEFAULT_GENESIS_DOWNLOAD_PATH,
+        hash::{Hash, Hasher},
+        native_token::lamports_to_sol,
+        pubkey::Pubkey,
     },
     solana_send_transaction_service::send_transaction_service::{self, SendTransactionService},
     std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
+        io::{Read, SeekFrom},
         net::SocketAddr,
         path::{Path, PathBuf},
         sync::atomic::{AtomicBool, AtomicU64, Ordering},
         sync::{mpsc::channel, Arc, Mutex, RwLock},
         thread::{self, Builder, JoinHandle},
+        time::Instant,
     },
+    tokio::io::{AsyncReadExt, AsyncSeekExt},
     tokio_util::codec::{BytesCodec, FramedRead},
 };
 
 const LARGEST_ACCOUNTS_CACHE_DURATION: u64 = 60 * 60 * 2;
 
+/// Smallest chunk size the hashes of the chunks of a file can be requested for, which bounds the
+/// size of the response
+const MIN_CHUNK_HASHES_CHUNK_SIZE: u64 = 1024 * 1024;
+
 pub struct JsonRpcService {
     thread_hdl: JoinHandle<()>,
 
@@ -65,6 +77,11 @@ struct RpcRequestMiddleware {
     snapshot_config: Option<SnapshotConfig>,
     bank_forks: Arc<RwLock<BankForks>>,
     health: Arc<RpcHealth>,
+    response_compression: Option<RpcCompression>,
+    /// Serve the supply REST endpoints, which scan the accounts
+    rest_api: bool,
+    /// Hashes of the chunks of the served files, by file and chunk size
+    chunk_hashes_cache: Arc<Mutex<HashMap<(PathBuf, u64), Arc<String>>>>,
 }
 
 impl RpcRequestMiddleware {
@@ -87,9 +104,22 @@ impl RpcRequestMiddleware {
             snapshot_config,
             bank_forks,
             health,
+            response_compression: None,
+            rest_api: true,
+            chunk_hashes_cache: Arc::default(),
         }
     }
 
+    pub fn response_compression(mut self, response_compression: Option<RpcCompression>) -> Self {
+        self.response_compression = response_compression;
+        self
+    }
+
+    pub fn rest_api(mut self, rest_api: bool) -> Self {
+        self.rest_api = rest_api;
+        self
+    }
+
     fn redirect(location: &str) -> hyper::Response<hyper::Body> {
         hyper::Response::builder()
             .status(hyper::StatusCode::SEE_OTHER)
@@ -150,54 +180,203 @@ impl RpcRequestMiddleware {
         tokio::fs::File::open(path).await
     }
 
-    fn process_file_get(&self, path: &str) -> RequestMiddlewareAction {
-        let stem = path.split_at(1).1; // Drop leading '/' from path
-        let filename = {
-            match path {
-                DEFAULT_GENESIS_DOWNLOAD_PATH => {
-                    inc_new_counter_info!("rpc-get_genesis", 1);
-                    self.ledger_path.join(stem)
-                }
-                _ => {
-                    inc_new_counter_info!("rpc-get_snapshot", 1);
-                    self.snapshot_config
-                        .as_ref()
-                        .unwrap()
-                        .snapshot_archives_dir
-                        .join(stem)
-                }
+    /// Parses a `Range: bytes=<first>-<last>` header of a request for a file of `file_length`
+    /// bytes into the offsets of the first and last bytes requested, `Err` if the range is not
+    /// satisfiable.  Returns `None` to serve the whole file if the header is not a single byte
+    /// range.
+    fn parse_byte_range(range: &str, file_length: u64) -> Option<Result<(u64, u64), ()>> {
+        let range = range.trim().strip_prefix("bytes=")?;
+        if range.contains(',') {
+            return None;
+        }
+        let (first, last) = range.split_once('-')?;
+        let (first, last) = (first.trim(), last.trim());
+        let (first, last) = if first.is_empty() {
+            // The last `<last>` bytes of the file
+            let suffix_length = last.parse::<u64>().ok()?;
+            if suffix_length == 0 {
+                return Some(Err(()));
             }
+            (
+                file_length.saturating_sub(suffix_length),
+                file_length.checked_sub(1)?,
+            )
+        } else {
+            let first = first.parse::<u64>().ok()?;
+            let last = if last.is_empty() {
+                file_length.saturating_sub(1)
+            } else {
+                last.parse::<u64>().ok()?.min(file_length.saturating_sub(1))
+            };
+            (first, last)
         };
+        Some(if first < file_length && first <= last {
+            Ok((first, last))
+        } else {
+            Err(())
+        })
+    }
+
+    fn file_get_filename(&self, path: &str) -> PathBuf {
+        let stem = path.split_at(1).1; // Drop leading '/' from path
+        match path {
+            DEFAULT_GENESIS_DOWNLOAD_PATH => self.ledger_path.join(stem),
+            _ => self
+                .snapshot_config
+                .as_ref()
+                .unwrap()
+                .snapshot_archives_dir
+                .join(stem),
+        }
+    }
+
+    fn process_file_get(&self, path: &str, range: Option<&str>) -> RequestMiddlewareAction {
+        if path == DEFAULT_GENESIS_DOWNLOAD_PATH {
+            inc_new_counter_info!("rpc-get_genesis", 1);
+        } else {
+            inc_new_counter_info!("rpc-get_snapshot", 1);
+        }
+        let filename = self.file_get_filename(path);
 
-        let file_length = std::fs::metadata(&filename)
-            .map(|m| m.len())
-            .unwrap_or(0)
-            .to_string();
-        info!("get {} -> {:?} ({} bytes)", path, filename, file_length);
+        let file_length = std::fs::metadata(&filename).map(|m| m.len()).unwrap_or(0);
+        let range = range.and_then(|range| Self::parse_byte_range(range, file_length));
+        info!(
+            "get {} -> {:?} ({} bytes, range {:?})",
+            path, filename, file_length, range
+        );
         RequestMiddlewareAction::Respond {
             should_validate_hosts: true,
-            response: Box::pin(async {
+            response: Box::pin(async move {
                 match Self::open_no_follow(filename).await {
                     Err(err) => Ok(if err.kind() == std::io::ErrorKind::NotFound {
                         Self::not_found()
                     } else {
                         Self::internal_server_error()
                     }),
-                    Ok(file) => {
-                        let stream =
-                            FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze());
-                        let body = hyper::Body::wrap_stream(stream);
-
-                        Ok(hyper::Response::builder()
-                            .header(hyper::header::CONTENT_LENGTH, file_length)
-                            .body(body)
-                            .unwrap())
-                    }
+                    Ok(mut file) => match range {
+                        None => {
+                            let stream =
+                                FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze());
+                            let body = hyper::Body::wrap_stream(stream);
+
+                            Ok(hyper::Response::builder()
+                                .header(hyper::header::CONTENT_LENGTH, file_length)
+                                .header(hyper::header::ACCEPT_RANGES, "bytes")
+                                .body(body)
+                                .unwrap())
+                        }
+                        Some(Ok((first, last))) => {
+                            if file.seek(SeekFrom::Start(first)).await.is_err() {
+                                return Ok(Self::internal_server_error());
+                            }
+                            let length = last - first + 1;
+                            let stream = FramedRead::new(file.take(length), BytesCodec::new())
+                                .map_ok(|b| b.freeze());
+                            let body = hyper::Body::wrap_stream(stream);
+
+                            Ok(hyper::Response::builder()
+                                .status(hyper::StatusCode::PARTIAL_CONTENT)
+                                .header(hyper::header::CONTENT_LENGTH, length)
+                                .header(
+                                    hyper::header::CONTENT_RANGE,
+                                    format!("bytes {}-{}/{}", first, last, file_length),
+                                )
+                                .body(body)
+                                .unwrap())
+                        }
+                        Some(Err(())) => Ok(hyper::Response::builder()
+                            .status(hyper::StatusCode::RANGE_NOT_SATISFIABLE)
+                            .header(
+                                hyper::header::CONTENT_RANGE,
+                                format!("bytes */{}", file_length),
+                            )
+                            .body(hyper::Body::empty())
+                            .unwrap()),
+                    },
                 }
             }),
         }
     }
 
+    /// Hashes the `chunk_size` bytes chunks of `file`, one hash per line
+    fn hash_chunks(mut file: std::fs::File, chunk_size: u64) -> std::io::Result<String> {
+        let mut chunk_hashes = String::new();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let mut chunk = (&mut file).take(chunk_size);
+            let mut hasher = Hasher::default();
+            let mut chunk_length = 0;
+            loop {
+                let n = chunk.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.hash(&buffer[..n]);
+                chunk_length += n;
+            }
+            if chunk_length == 0 {
+                return Ok(chunk_hashes);
+            }
+            chunk_hashes.push_str(&format!("{}\n", hasher.result()));
+        }
+    }
+
+    /// Serves the hashes of the `chunk_size` bytes chunks of a file, for the downloads of the
+    /// file from several peers to verify the chunks against
+    fn process_chunk_hashes_get(&self, path: &str, chunk_size: u64) -> RequestMiddlewareAction {
+        inc_new_counter_info!("rpc-get_chunk_hashes", 1);
+        let filename = self.file_get_filename(path);
+        info!(
+            "get chunk hashes {} -> {:?} ({} bytes chunks)",
+            path, filename, chunk_size
+        );
+        let chunk_hashes_cache = self.chunk_hashes_cache.clone();
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                if chunk_size < MIN_CHUNK_HASHES_CHUNK_SIZE {
+                    return Ok(hyper::Response::builder()
+                        .status(hyper::StatusCode::BAD_REQUEST)
+                        .body(hyper::Body::empty())
+                        .unwrap());
+                }
+                let key = (filename, chunk_size);
+                let cached_chunk_hashes = chunk_hashes_cache.lock().unwrap().get(&key).cloned();
+                let chunk_hashes = match cached_chunk_hashes {
+                    Some(chunk_hashes) => chunk_hashes,
+                    None => {
+                        let file = match Self::open_no_follow(&key.0).await {
+                            Err(err) => {
+                                return Ok(if err.kind() == std::io::ErrorKind::NotFound {
+                                    Self::not_found()
+                                } else {
+                                    Self::internal_server_error()
+                                })
+                            }
+                            Ok(file) => file.into_std().await,
+                        };
+                        let chunk_hashes = match tokio::task::spawn_blocking(move || {
+                            Self::hash_chunks(file, chunk_size)
+                        })
+                        .await
+                        {
+                            Ok(Ok(chunk_hashes)) => Arc::new(chunk_hashes),
+                            _ => return Ok(Self::internal_server_error()),
+                        };
+                        let mut chunk_hashes_cache = chunk_hashes_cache.lock().unwrap();
+                        // Forget the files that were purged since
+                        chunk_hashes_cache.retain(|(filename, _), _| filename.is_file());
+                        chunk_hashes_cache.insert(key, chunk_hashes.clone());
+                        chunk_hashes
+                    }
+                };
+                Ok(hyper::Response::builder()
+                    .header(hyper::header::CONTENT_LENGTH, chunk_hashes.len())
+                    .body(hyper::Body::from(chunk_hashes.to_string()))
+                    .unwrap())
+            }),
+        }
+    }
     fn health_check(&self) -> &'static str {
         let response = match self.health.check() {
             RpcHealthStatus::Ok => "ok",
@@ -236,20 +415,47 @@ impl RequestMiddleware for RpcRequestMiddleware {
             }
         }
 
-        if let Some(result) = process_rest(&self.bank_forks, request.uri().path()) {
+        if let Some(result) = Some(request.uri().path())
+            .filter(|_| self.rest_api)
+            .and_then(|path| process_rest(&self.bank_forks, path))
+        {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
                 .body(hyper::Body::from(result))
                 .unwrap()
                 .into()
+        } else if let Some(chunk_size) = request
+            .uri()
+            .query()
+            .and_then(|query| query.strip_prefix("chunk-hashes="))
+            .and_then(|chunk_size| chunk_size.parse::<u64>().ok())
+            .filter(|_| self.is_file_get_path(request.uri().path()))
+        {
+            self.process_chunk_hashes_get(request.uri().path(), chunk_size)
         } else if self.is_file_get_path(request.uri().path()) {
-            self.process_file_get(request.uri().path())
+            self.process_file_get(
+                request.uri().path(),
+                request
+                    .headers()
+                    .get(hyper::header::RANGE)
+                    .and_then(|range| range.to_str().ok()),
+            )
         } else if request.uri().path() == "/health" {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
                 .body(hyper::Body::from(self.health_check()))
                 .unwrap()
                 .into()
+        } else if let Some((response_compression, encoding)) = self
+            .response_compression
+            .as_ref()
+            .and_then(|response_compression| {
+                response_compression
+                    .encoding(&request)
+                    .map(|encoding| (response_compression, encoding))
+            })
+        {
+            response_compression.process(request, encoding)
         } else {
             request.into()
         }
@@ -346,8 +552,10 @@ impl JsonRpcService {
                         !config.enable_bigtable_ledger_upload,
                         config.rpc_bigtable_timeout,
                     ))
-                    .map(|bigtable_ledger_storage| {
+                    .map(|mut bigtable_ledger_storage| {
                         info!("BigTable ledger storage initialized");
+                        bigtable_ledger_storage
+                            .set_write_versioned_protobuf(config.bigtable_versioned_protobuf);
 
                         let bigtable_ledger_upload_service = if config.enable_bigtable_ledger_upload
                         {
@@ -375,8 +583,11 @@ impl JsonRpcService {
                 (None, None)
             };
 
-        let minimal_api = config.minimal_api;
+        let minimal_api = config.minimal_api || config.snapshot_only;
+        let snapshot_only = config.snapshot_only;
         let obsolete_v1_7_api = config.obsolete_v1_7_api;
+        let slow_query_log = config.slow_query_log.clone();
+        let response_compression_min_bytes = config.response_compression_min_bytes;
         let (request_processor, receiver) = JsonRpcRequestProcessor::new(
             config,
             snapshot_config.clone(),
@@ -397,13 +608,16 @@ impl JsonRpcService {
 
         let leader_info =
             poh_recorder.map(|recorder| ClusterTpuInfo::new(cluster_info.clone(), recorder));
-        let _send_transaction_service = Arc::new(SendTransactionService::new_with_config(
-            tpu_address,
-            &bank_forks,
-            leader_info,
-            receiver,
-            send_transaction_service_config,
-        ));
+        // A snapshot-only node doesn't expose sendTransaction, so has no transaction to send
+        let _send_transaction_service = (!snapshot_only).then(|| {
+            Arc::new(SendTransactionService::new_with_config(
+                tpu_address,
+                &bank_forks,
+                leader_info,
+                receiver,
+                send_transaction_service_config,
+            ))
+        });
 
         #[cfg(test)]
         let test_request_processor = request_processor.clone();
@@ -416,7 +630,8 @@ impl JsonRpcService {
             .spawn(move || {
                 renice_this_thread(rpc_niceness_adj).unwrap();
 
-                let mut io = MetaIoHandler::default();
+                let mut io =
+                    MetaIoHandler::with_middleware(SlowQueryMiddleware::new(slow_query_log));
 
                 io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
                 if !minimal_api {
@@ -430,15 +645,27 @@ impl JsonRpcService {
                     io.extend_with(rpc_obsolete_v1_7::ObsoleteV1_7Impl.to_delegate());
                 }
 
+                let response_compression = response_compression_min_bytes.map(|min_bytes| {
+                    RpcCompression::new(
+                        min_bytes,
+                        MAX_REQUEST_PAYLOAD_SIZE,
+                        Arc::new(io.clone()),
+                        request_processor.clone(),
+                    )
+                });
                 let request_middleware = RpcRequestMiddleware::new(
                     ledger_path,
                     snapshot_config,
                     bank_forks.clone(),
                     health.clone(),
-                );
+                )
+                .response_compression(response_compression)
+                .rest_api(!snapshot_only);
                 let server = ServerBuilder::with_meta_extractor(
                     io,
-                    move |_req: &hyper::Request<hyper::Body>| request_processor.clone(),
+                    move |_req: &hyper::Request<hyper::Body>| {
+                        request_processor.with_request_received(Instant::now())
+                    },
                 )
                 .event_loop_executor(runtime.handle().clone())
                 .threads(1)
@@ -604,6 +831,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rest_api_disabled() {
+        let total_supply_request = || {
+            hyper::Request::get("/v0/total-supply")
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+        let rrm = RpcRequestMiddleware::new(
+            PathBuf::from("/"),
+            None,
+            create_bank_forks(),
+            RpcHealth::stub(),
+        );
+        assert!(matches!(
+            rrm.on_request(total_supply_request()),
+            RequestMiddlewareAction::Respond { .. }
+        ));
+
+        // The request goes through to the JSON RPC handler, which rejects it
+        let rrm = rrm.rest_api(false);
+        assert!(matches!(
+            rrm.on_request(total_supply_request()),
+            RequestMiddlewareAction::Proceed { .. }
+        ));
+    }
+
     #[test]
     fn test_is_file_get_path() {
         let bank_forks = create_bank_forks();
@@ -691,7 +944,7 @@ mod tests {
         );
 
         // File does not exist => request should fail.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -706,11 +959,77 @@ mod tests {
         }
 
         // Normal file exist => request should succeed.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 200);
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
+        // Byte range of the file => request should return the range only.
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some("bytes=3-4"));
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 206);
+            assert_eq!(
+                response.headers()[hyper::header::CONTENT_RANGE],
+                "bytes 3-4/12"
+            );
+            let body = runtime
+                .block_on(hyper::body::to_bytes(response.into_body()))
+                .unwrap();
+            assert_eq!(&body[..], b"ul");
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
+        // Chunk hashes of the file => request should return one hash per chunk.
+        let action = rrm
+            .process_chunk_hashes_get(DEFAULT_GENESIS_DOWNLOAD_PATH, MIN_CHUNK_HASHES_CHUNK_SIZE);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
             assert_eq!(response.status(), 200);
+            let body = runtime
+                .block_on(hyper::body::to_bytes(response.into_body()))
+                .unwrap();
+            assert_eq!(
+                &body[..],
+                format!("{}\n", solana_sdk::hash::hash(b"should be ok")).as_bytes()
+            );
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+        assert_eq!(
+            RpcRequestMiddleware::hash_chunks(std::fs::File::open(&genesis_path).unwrap(), 5)
+                .unwrap(),
+            format!(
+                "{}\n{}\n{}\n",
+                solana_sdk::hash::hash(b"shoul"),
+                solana_sdk::hash::hash(b"d be "),
+                solana_sdk::hash::hash(b"ok")
+            )
+        );
+
+        // Chunks too small => request should fail.
+        let action = rrm.process_chunk_hashes_get(DEFAULT_GENESIS_DOWNLOAD_PATH, 5);
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 400);
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
+        // Byte range past the end of the file => request should fail.
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some("bytes=12-"));
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response);
+            let response = response.unwrap();
+            assert_eq!(response.status(), 416);
         } else {
             panic!("Unexpected RequestMiddlewareAction variant");
         }
@@ -725,7 +1044,7 @@ mod tests {
             symlink::symlink_file("wrong", &genesis_path).unwrap();
 
             // File is a symbolic link => request should fail.
-            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
             if let RequestMiddlewareAction::Respond { response, .. } = action {
                 let response = runtime.block_on(response);
                 let response = response.unwrap();
@@ -736,6 +1055,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_byte_range() {
+        let parse = RpcRequestMiddleware::parse_byte_range;
+        assert_eq!(parse("bytes=0-9", 100), Some(Ok((0, 9))));
+        assert_eq!(parse("bytes=90-", 100), Some(Ok((90, 99))));
+        assert_eq!(parse("bytes=90-200", 100), Some(Ok((90, 99))));
+        assert_eq!(parse("bytes=-10", 100), Some(Ok((90, 99))));
+        assert_eq!(parse("bytes=-200", 100), Some(Ok((0, 99))));
+        assert_eq!(parse("bytes=100-", 100), Some(Err(())));
+        assert_eq!(parse("bytes=9-0", 100), Some(Err(())));
+        assert_eq!(parse("bytes=-0", 100), Some(Err(())));
+
+        // Anything else serves the whole file
+        assert_eq!(parse("bytes=0-9,20-29", 100), None);
+        assert_eq!(parse("lines=0-9", 100), None);
+        assert_eq!(parse("bytes=a-b", 100), None);
+    }
+
     #[test]
     fn test_health_check_with_no_trusted_validators() {
         let rm = RpcRequestMiddleware::new(
@@ -1,5 +1,8 @@
 use {
-    crate::rpc_subscriptions::{NotificationEntry, RpcNotification, TimestampedNotificationEntry},
+    crate::{
+        rpc_subscriptions::{NotificationEntry, RpcNotification, TimestampedNotificationEntry},
+        transaction_logs_bus::LogsNotificationRate,
+    },
     dashmap::{mapref::entry::Entry as DashEntry, DashMap},
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_client::rpc_filter::RpcFilterType,
@@ -9,17 +12,15 @@ use {
         bank_forks::BankForks,
     },
     solana_sdk::{
-        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+        clock::Slot, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+        signature::Signature,
     },
     std::{
-        collections::{
-            hash_map::{Entry, HashMap},
-            HashSet,
-        },
+        collections::hash_map::{Entry, HashMap},
         fmt,
         sync::{
             atomic::{AtomicU64, Ordering},
-            Arc, RwLock, Weak,
+            Arc, Mutex, RwLock, Weak,
         },
     },
     thiserror::Error,
@@ -57,7 +58,10 @@ impl SubscriptionParams {
     fn method(&self) -> &'static str {
         match self {
             SubscriptionParams::Account(_) => "accountNotification",
-            SubscriptionParams::Logs(_) => "logsNotification",
+            SubscriptionParams::Logs(params) => match params.kind {
+                LogsSubscriptionKind::ProgramChain(_) => "programChainNotification",
+                _ => "logsNotification",
+            },
             SubscriptionParams::Program(_) => "programNotification",
             SubscriptionParams::Signature(_) => "signatureNotification",
             SubscriptionParams::Slot => "slotNotification",
@@ -125,6 +129,8 @@ pub struct AccountSubscriptionParams {
     pub encoding: UiAccountEncoding,
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    /// Notify only the writes changing the account
+    pub skip_unchanged: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -137,7 +143,25 @@ pub struct LogsSubscriptionParams {
 pub enum LogsSubscriptionKind {
     All,
     AllWithVotes,
-    Single(Pubkey),
+    /// Transactions mentioning any of the addresses
+    Mentions(Vec<Pubkey>),
+    /// Transactions invoking any of the programs, at the top level or through cross-program
+    /// invocations
+    Programs(Vec<Pubkey>),
+    /// Transactions invoking the program, at the top level or through cross-program invocations
+    ProgramChain(Pubkey),
+}
+
+impl LogsSubscriptionKind {
+    /// Addresses the transactions must mention for their logs to be collected
+    fn mentioned_addresses(&self) -> &[Pubkey] {
+        match self {
+            LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => &[],
+            LogsSubscriptionKind::Mentions(addresses)
+            | LogsSubscriptionKind::Programs(addresses) => addresses,
+            LogsSubscriptionKind::ProgramChain(program_id) => std::slice::from_ref(program_id),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -274,6 +298,11 @@ pub struct SubscriptionInfo {
     params: SubscriptionParams,
     method: &'static str,
     pub last_notified_slot: RwLock<Slot>,
+    /// Slot the account of an account subscription skipping unchanged accounts was last seen
+    /// modified at, and the hash of its state then
+    pub last_seen_account_hash: Mutex<Option<(Slot, Hash)>>,
+    /// Rate of the notifications of a logs subscription
+    pub logs_notification_rate: Mutex<LogsNotificationRate>,
     commitment: Option<CommitmentConfig>,
 }
 
@@ -314,9 +343,10 @@ impl LogsSubscriptionsIndex {
         match params.kind {
             LogsSubscriptionKind::All => self.all_count += 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count += 1,
-            LogsSubscriptionKind::Single(key) => {
-                *self.single_count.entry(key).or_default() += 1;
-            }
+            _ => {}
+        }
+        for key in params.kind.mentioned_addresses() {
+            *self.single_count.entry(*key).or_default() += 1;
         }
         self.update_config();
     }
@@ -325,7 +355,10 @@ impl LogsSubscriptionsIndex {
         match params.kind {
             LogsSubscriptionKind::All => self.all_count -= 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count -= 1,
-            LogsSubscriptionKind::Single(key) => match self.single_count.entry(key) {
+            _ => {}
+        }
+        for key in params.kind.mentioned_addresses() {
+            match self.single_count.entry(*key) {
                 Entry::Occupied(mut entry) => {
                     *entry.get_mut() -= 1;
                     if *entry.get() == 0 {
@@ -333,26 +366,29 @@ impl LogsSubscriptionsIndex {
                     }
                 }
                 Entry::Vacant(_) => error!("missing entry in single_count"),
-            },
+            }
         }
         self.update_config();
     }
 
     fn update_config(&self) {
+        // The mentioned addresses are indexed whatever the filter, for the subscriptions to
+        // them to be notified while others subscribe to all the transactions
+        let mentioned_addresses = self.single_count.keys().copied().collect();
         let config = if self.all_with_votes_count > 0 {
             TransactionLogCollectorConfig {
                 filter: TransactionLogCollectorFilter::AllWithVotes,
-                mentioned_addresses: HashSet::new(),
+                mentioned_addresses,
             }
         } else if self.all_count > 0 {
             TransactionLogCollectorConfig {
                 filter: TransactionLogCollectorFilter::All,
-                mentioned_addresses: HashSet::new(),
+                mentioned_addresses,
             }
         } else {
             TransactionLogCollectorConfig {
                 filter: TransactionLogCollectorFilter::OnlyMentionedAddresses,
-                mentioned_addresses: self.single_count.keys().copied().collect(),
+                mentioned_addresses,
             }
         };
 
@@ -402,6 +438,8 @@ impl SubscriptionsTracker {
     ) {
         let info = Arc::new(SubscriptionInfo {
             last_notified_slot: RwLock::new(last_notified_slot()),
+            last_seen_account_hash: Mutex::default(),
+            logs_notification_rate: Mutex::default(),
             id,
             commitment: params.commitment(),
             method: params.method(),
@@ -662,6 +700,7 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            skip_unchanged: false,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 42);
 
@@ -702,6 +741,7 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            skip_unchanged: false,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 0);
         assert_eq!(counts(&tracker), (0, 1, 0, 0));
@@ -713,6 +753,7 @@ mod tests {
             commitment: CommitmentConfig::confirmed(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            skip_unchanged: false,
         });
         tracker.subscribe(account_params2.clone(), 2.into(), || 0);
         assert_eq!(counts(&tracker), (0, 0, 1, 0));
@@ -6,10 +6,11 @@ use {
         parsed_token_accounts::{get_parsed_token_account, get_parsed_token_accounts},
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
-            AccountSubscriptionParams, LogsSubscriptionKind, LogsSubscriptionParams,
-            ProgramSubscriptionParams, SignatureSubscriptionParams, SubscriptionControl,
-            SubscriptionId, SubscriptionInfo, SubscriptionParams, SubscriptionsTracker,
+            AccountSubscriptionParams, LogsSubscriptionParams, ProgramSubscriptionParams,
+            SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionInfo,
+            SubscriptionParams, SubscriptionsTracker,
         },
+        transaction_logs_bus::{LogsNotificationLimits, TransactionLogsBus},
     },
     crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
     rayon::prelude::*,
@@ -19,19 +20,20 @@ use {
         rpc_filter::RpcFilterType,
         rpc_response::{
             ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount,
-            RpcLogsResponse, RpcResponseContext, RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+            RpcResponseContext, RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_measure::measure::Measure,
     solana_rayon_threadlimit::get_thread_count,
     solana_runtime::{
-        bank::{Bank, TransactionLogInfo},
+        bank::Bank,
         bank_forks::BankForks,
         commitment::{BlockCommitmentCache, CommitmentSlots},
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
+        hash::{hashv, Hash},
         pubkey::Pubkey,
         signature::Signature,
         timing::timestamp,
@@ -56,23 +58,6 @@ use {
 
 const RECEIVE_DELAY_MILLIS: u64 = 100;
 
-fn get_transaction_logs(
-    bank: &Bank,
-    params: &LogsSubscriptionParams,
-) -> Option<Vec<TransactionLogInfo>> {
-    let pubkey = match &params.kind {
-        LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => None,
-        LogsSubscriptionKind::Single(pubkey) => Some(pubkey),
-    };
-    let mut logs = bank.get_transaction_logs(pubkey);
-    if matches!(params.kind, LogsSubscriptionKind::All) {
-        // Filter out votes if the subscriber doesn't want them
-        if let Some(logs) = &mut logs {
-            logs.retain(|log| !log.is_vote);
-        }
-    }
-    logs
-}
 #[derive(Debug)]
 pub struct TimestampedNotificationEntry {
     pub entry: NotificationEntry,
@@ -96,6 +81,7 @@ pub enum NotificationEntry {
     Bank(CommitmentSlots),
     Gossip(Slot),
     SignaturesReceived((Slot, Vec<Signature>)),
+    Frozen(Slot),
     Subscribed(SubscriptionParams, SubscriptionId),
     Unsubscribed(SubscriptionParams, SubscriptionId),
 }
@@ -116,6 +102,7 @@ impl std::fmt::Debug for NotificationEntry {
                 write!(f, "SignaturesReceived({:?})", slot_signatures)
             }
             NotificationEntry::Gossip(slot) => write!(f, "Gossip({:?})", slot),
+            NotificationEntry::Frozen(slot) => write!(f, "Frozen({:?})", slot),
             NotificationEntry::Subscribed(params, id) => {
                 write!(f, "Subscribed({:?}, {:?})", params, id)
             }
@@ -143,19 +130,12 @@ where
     F: Fn(X, &P, Slot, Arc<Bank>) -> (Box<dyn Iterator<Item = S>>, Slot),
     X: Clone + Default,
 {
-    let commitment = if let Some(commitment) = subscription.commitment() {
-        commitment
+    let slot = if let Some(slot) = commitment_slot(subscription, commitment_slots) {
+        slot
     } else {
         error!("missing commitment in check_commitment_and_notify");
         return false;
     };
-    let slot = if commitment.is_finalized() {
-        commitment_slots.highest_confirmed_root
-    } else if commitment.is_confirmed() {
-        commitment_slots.highest_confirmed_slot
-    } else {
-        commitment_slots.slot
-    };
 
     let mut notified = false;
     if let Some(bank) = bank_forks.read().unwrap().get(slot).cloned() {
@@ -179,6 +159,62 @@ where
     notified
 }
 
+/// Slot of the bank the subscription is notified of, going by its commitment
+fn commitment_slot(
+    subscription: &SubscriptionInfo,
+    commitment_slots: &CommitmentSlots,
+) -> Option<Slot> {
+    let commitment = subscription.commitment()?;
+    Some(if commitment.is_finalized() {
+        commitment_slots.highest_confirmed_root
+    } else if commitment.is_confirmed() {
+        commitment_slots.highest_confirmed_slot
+    } else {
+        commitment_slots.slot
+    })
+}
+
+/// Notifies the logs subscription of the transactions of the bank at its commitment, the
+/// notifications are encoded once for all the subscriptions unless some were dropped before
+fn check_commitment_and_notify_logs(
+    params: &LogsSubscriptionParams,
+    subscription: &SubscriptionInfo,
+    bank_forks: &RwLock<BankForks>,
+    commitment_slots: &CommitmentSlots,
+    logs_bus: &TransactionLogsBus,
+    notifier: &RpcNotifier,
+) -> bool {
+    let slot = if let Some(slot) = commitment_slot(subscription, commitment_slots) {
+        slot
+    } else {
+        error!("missing commitment in check_commitment_and_notify_logs");
+        return false;
+    };
+    let bank = if let Some(bank) = bank_forks.read().unwrap().get(slot).cloned() {
+        bank
+    } else {
+        return false;
+    };
+
+    let bank_logs = logs_bus.bank_logs(&bank);
+    let max_notifications_per_second = logs_bus.limits().max_notifications_per_second;
+    let mut notification_rate = subscription.logs_notification_rate.lock().unwrap();
+    let mut notified = false;
+    for entry in bank_logs.filter(&params.kind) {
+        match notification_rate.check(max_notifications_per_second) {
+            None => continue,
+            Some(0) => notifier.notify_encoded(entry.encoded(), subscription, false),
+            Some(dropped_notifications) => notifier.notify(
+                entry.response_with_dropped_notifications(dropped_notifications),
+                subscription,
+                false,
+            ),
+        }
+        notified = true;
+    }
+    notified
+}
+
 #[derive(Debug, Clone)]
 pub struct RpcNotification {
     pub subscription_id: SubscriptionId,
@@ -270,7 +306,28 @@ impl RpcNotifier {
             let buf_str = str::from_utf8(&buf).expect("json is always utf-8");
             Arc::new(String::from(buf_str))
         });
+        self.send(buf_arc, subscription, is_final);
+    }
 
+    /// Notifies the subscription of a result encoded as JSON already, so that results sent to
+    /// many subscriptions are only encoded once
+    fn notify_encoded(
+        &self,
+        encoded_result: &str,
+        subscription: &SubscriptionInfo,
+        is_final: bool,
+    ) {
+        // Same layout as `Notification`
+        let buf_arc = Arc::new(format!(
+            r#"{{"jsonrpc":"2.0","method":"{}","params":{{"result":{},"subscription":{}}}}}"#,
+            subscription.method(),
+            encoded_result,
+            u64::from(subscription.id()),
+        ));
+        self.send(buf_arc, subscription, is_final);
+    }
+
+    fn send(&self, buf_arc: Arc<String>, subscription: &SubscriptionInfo, is_final: bool) {
         let notification = RpcNotification {
             subscription_id: subscription.id(),
             json: Arc::downgrade(&buf_arc),
@@ -288,19 +345,52 @@ impl RpcNotifier {
     }
 }
 
+fn hash_account_state(account: &AccountSharedData) -> Hash {
+    hashv(&[
+        &account.lamports().to_le_bytes(),
+        account.owner().as_ref(),
+        &[account.executable() as u8],
+        account.data(),
+    ])
+}
+
+/// Whether the account modified at `last_modified_slot` is the same as when it was last seen,
+/// remembering its state otherwise
+fn is_account_unchanged(
+    account: &AccountSharedData,
+    last_modified_slot: Slot,
+    last_seen_account_hash: &Mutex<Option<(Slot, Hash)>>,
+) -> bool {
+    let mut last_seen_account_hash = last_seen_account_hash.lock().unwrap();
+    match *last_seen_account_hash {
+        // Seen unchanged already, without notifying it
+        Some((last_seen_slot, _)) if last_seen_slot == last_modified_slot => true,
+        _ => {
+            let hash = hash_account_state(account);
+            let last_seen_hash = last_seen_account_hash.replace((last_modified_slot, hash));
+            last_seen_hash.map(|(_, last_seen_hash)| last_seen_hash) == Some(hash)
+        }
+    }
+}
+
 fn filter_account_result(
     result: Option<(AccountSharedData, Slot)>,
     params: &AccountSubscriptionParams,
     last_notified_slot: Slot,
     bank: Arc<Bank>,
+    last_seen_account_hash: &Mutex<Option<(Slot, Hash)>>,
 ) -> (Box<dyn Iterator<Item = UiAccount>>, Slot) {
     // If the account is not found, `last_modified_slot` will default to zero and
     // we will notify clients that the account no longer exists if we haven't already
     let (account, last_modified_slot) = result.unwrap_or_default();
 
+    let is_notified = last_modified_slot != last_notified_slot
+        && !(params.skip_unchanged
+            && is_account_unchanged(&account, last_modified_slot, last_seen_account_hash));
+
     // If last_modified_slot < last_notified_slot this means that we last notified for a fork
     // and should notify that the account state has been reverted.
-    let results: Box<dyn Iterator<Item = UiAccount>> = if last_modified_slot != last_notified_slot {
+    let results: Box<dyn Iterator<Item = UiAccount>> = if is_notified {
         if account.owner() == &spl_token_id_v2_0()
             && params.encoding == UiAccountEncoding::JsonParsed
         {
@@ -371,25 +461,6 @@ fn filter_program_results(
     (accounts, last_notified_slot)
 }
 
-fn filter_logs_results(
-    logs: Option<Vec<TransactionLogInfo>>,
-    _params: &LogsSubscriptionParams,
-    last_notified_slot: Slot,
-    _bank: Arc<Bank>,
-) -> (Box<dyn Iterator<Item = RpcLogsResponse>>, Slot) {
-    match logs {
-        None => (Box::new(iter::empty()), last_notified_slot),
-        Some(logs) => (
-            Box::new(logs.into_iter().map(|log| RpcLogsResponse {
-                signature: log.signature.to_string(),
-                err: log.result.err(),
-                logs: log.log_messages,
-            })),
-            last_notified_slot,
-        ),
-    }
-}
-
 fn initial_last_notified_slot(
     params: &SubscriptionParams,
     bank_forks: &RwLock<BankForks>,
@@ -404,7 +475,7 @@ fn initial_last_notified_slot(
                     .unwrap()
                     .highest_confirmed_root()
             } else if params.commitment.is_confirmed() {
-                optimistically_confirmed_bank.read().unwrap().bank.slot()
+                optimistically_confirmed_bank.read().unwrap().slot()
             } else {
                 block_commitment_cache.read().unwrap().slot()
             };
@@ -533,6 +604,10 @@ impl RpcSubscriptions {
                 config.queue_capacity_bytes,
             )),
         };
+        let logs_bus = TransactionLogsBus::new(LogsNotificationLimits {
+            max_messages: config.max_logs_notification_messages,
+            max_notifications_per_second: config.max_logs_notifications_per_second,
+        });
         let notification_threads = config.notification_threads;
         let t_cleanup = Builder::new()
             .name("solana-rpc-notifications".to_string())
@@ -546,6 +621,7 @@ impl RpcSubscriptions {
                     Self::process_notifications(
                         exit_clone,
                         notifier,
+                        logs_bus,
                         notification_receiver,
                         subscriptions,
                         bank_forks,
@@ -616,6 +692,12 @@ impl RpcSubscriptions {
         self.enqueue_notification(NotificationEntry::SignaturesReceived(slot_signatures));
     }
 
+    /// Notify Processed commitment-level signature subscribers as soon as the bank of `slot` is
+    /// frozen, rather than once it is voted on.
+    pub fn notify_frozen(&self, slot: Slot) {
+        self.enqueue_notification(NotificationEntry::Frozen(slot));
+    }
+
     pub fn notify_vote(&self, vote: &Vote) {
         self.enqueue_notification(NotificationEntry::Vote(vote.clone()));
     }
@@ -643,9 +725,11 @@ impl RpcSubscriptions {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_notifications(
         exit: Arc<AtomicBool>,
         notifier: RpcNotifier,
+        logs_bus: TransactionLogsBus,
         notification_receiver: Receiver<TimestampedNotificationEntry>,
         mut subscriptions: SubscriptionsTracker,
         bank_forks: Arc<RwLock<BankForks>>,
@@ -728,6 +812,7 @@ impl RpcSubscriptions {
                                 subscriptions.commitment_watchers(),
                                 &bank_forks,
                                 &commitment_slots,
+                                &logs_bus,
                                 &notifier,
                                 "bank",
                             )
@@ -742,10 +827,40 @@ impl RpcSubscriptions {
                                 subscriptions.gossip_watchers(),
                                 &bank_forks,
                                 &commitment_slots,
+                                &logs_bus,
                                 &notifier,
                                 "gossip",
                             )
                         }
+                        NotificationEntry::Frozen(slot) => {
+                            let commitment_slots = CommitmentSlots {
+                                slot,
+                                ..CommitmentSlots::default()
+                            };
+                            let processed_signature_watchers: HashMap<_, _> = subscriptions
+                                .commitment_watchers()
+                                .iter()
+                                .filter(|(_, subscription)| {
+                                    matches!(
+                                        subscription.params(),
+                                        SubscriptionParams::Signature(_)
+                                    ) && subscription
+                                        .commitment()
+                                        .map(|commitment| commitment.is_processed())
+                                        .unwrap_or(false)
+                                })
+                                .map(|(id, subscription)| (*id, Arc::clone(subscription)))
+                                .collect();
+
+                            RpcSubscriptions::notify_accounts_logs_programs_signatures(
+                                &processed_signature_watchers,
+                                &bank_forks,
+                                &commitment_slots,
+                                &logs_bus,
+                                &notifier,
+                                "frozen",
+                            )
+                        }
                         NotificationEntry::SignaturesReceived((slot, slot_signatures)) => {
                             for slot_signature in &slot_signatures {
                                 if let Some(subs) = subscriptions.by_signature().get(slot_signature)
@@ -794,6 +909,7 @@ impl RpcSubscriptions {
         subscriptions: &HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
         bank_forks: &Arc<RwLock<BankForks>>,
         commitment_slots: &CommitmentSlots,
+        logs_bus: &TransactionLogsBus,
         notifier: &RpcNotifier,
         source: &'static str,
     ) {
@@ -820,7 +936,15 @@ impl RpcSubscriptions {
                         bank_forks,
                         commitment_slots,
                         |bank, params| bank.get_account_modified_slot(&params.pubkey),
-                        filter_account_result,
+                        |result, params, last_notified_slot, bank| {
+                            filter_account_result(
+                                result,
+                                params,
+                                last_notified_slot,
+                                bank,
+                                &subscription.last_seen_account_hash,
+                            )
+                        },
                         notifier,
                         false,
                     );
@@ -832,15 +956,13 @@ impl RpcSubscriptions {
                     }
                 }
                 SubscriptionParams::Logs(params) => {
-                    let notified = check_commitment_and_notify(
+                    let notified = check_commitment_and_notify_logs(
                         params,
                         subscription,
                         bank_forks,
                         commitment_slots,
-                        get_transaction_logs,
-                        filter_logs_results,
+                        logs_bus,
                         notifier,
-                        false,
                     );
                     num_logs_found.fetch_add(1, Ordering::Relaxed);
 
@@ -1008,20 +1130,22 @@ pub(crate) mod tests {
         serial_test::serial,
         solana_client::rpc_config::{
             RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsFilter,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         solana_runtime::{
+            bank::ExecuteTimings,
             commitment::BlockCommitment,
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
         },
         solana_sdk::{
+            clock::MAX_PROCESSING_AGE,
             commitment_config::CommitmentConfig,
             message::Message,
             signature::{Keypair, Signer},
             stake, system_instruction, system_program, system_transaction,
             transaction::Transaction,
         },
-        std::{collections::HashSet, sync::atomic::Ordering::Relaxed},
+        std::{collections::HashSet, sync::atomic::Ordering::Relaxed, thread::sleep},
     };
 
     fn make_account_result(lamports: u64, subscription: u64, data: &str) -> serde_json::Value {
@@ -1044,6 +1168,27 @@ pub(crate) mod tests {
         })
     }
 
+    #[test]
+    fn test_is_account_unchanged() {
+        let last_seen_account_hash = Mutex::default();
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 8, &owner);
+
+        assert!(!is_account_unchanged(&account, 1, &last_seen_account_hash));
+        // Written to again, with the same state
+        assert!(is_account_unchanged(&account, 2, &last_seen_account_hash));
+        assert!(is_account_unchanged(&account, 2, &last_seen_account_hash));
+
+        let mut changed_account = account.clone();
+        changed_account.set_data(vec![1; 8]);
+        assert!(!is_account_unchanged(
+            &changed_account,
+            3,
+            &last_seen_account_hash
+        ));
+        assert!(!is_account_unchanged(&account, 4, &last_seen_account_hash));
+    }
+
     #[test]
     #[serial]
     fn test_check_account_subscribe() {
@@ -1110,11 +1255,15 @@ pub(crate) mod tests {
             let sub_id = rpc
                 .account_subscribe(
                     pubkey.to_string(),
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        encoding: None,
-                        data_slice: None,
-                    }),
+                    Some(
+                        RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            encoding: None,
+                            data_slice: None,
+                            min_context_slot: None,
+                        }
+                        .into(),
+                    ),
                 )
                 .unwrap();
 
@@ -1125,6 +1274,7 @@ pub(crate) mod tests {
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
+                    skip_unchanged: false,
                 }));
 
             bank_forks
@@ -1154,10 +1304,113 @@ pub(crate) mod tests {
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
+                    skip_unchanged: false,
                 }));
         }
     }
 
+    #[test]
+    #[serial]
+    fn test_check_logs_subscribe() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests_with_slots(
+                1, 1,
+            ))),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+
+        let sub_id = rpc
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![alice.to_string(), bob.to_string()]),
+                Some(RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                }),
+            )
+            .unwrap();
+        let bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
+        // The logs of the mentioned addresses are collected once the subscription is processed
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !bank1
+            .transaction_log_collector_config
+            .read()
+            .unwrap()
+            .mentioned_addresses
+            .contains(&bob)
+        {
+            assert!(
+                Instant::now() < deadline,
+                "mentioned addresses not collected"
+            );
+            sleep(Duration::from_millis(10));
+        }
+
+        let tx = system_transaction::transfer(&mint_keypair, &bob, 1, blockhash);
+        let batch = bank1.prepare_batch_for_tests(vec![tx.clone()]);
+        let (results, ..) = bank1.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            false,
+            false,
+            true,
+            &mut ExecuteTimings::default(),
+        );
+        assert!(results.fee_collection_results[0].is_ok());
+        drop(batch);
+        subscriptions.notify_subscribers(CommitmentSlots {
+            slot: 1,
+            ..CommitmentSlots::default()
+        });
+        let response = receiver.recv();
+        let expected = json!({
+           "jsonrpc": "2.0",
+           "method": "logsNotification",
+           "params": {
+               "result": {
+                   "context": { "slot": 1 },
+                   "value": {
+                       "signature": tx.signatures[0].to_string(),
+                       "err": null,
+                       "logs": [
+                           "Program 11111111111111111111111111111111 invoke [1]",
+                           "Program 11111111111111111111111111111111 success"
+                       ],
+                   },
+               },
+               "subscription": sub_id,
+           }
+        });
+        assert_eq!(
+            expected,
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+        );
+
+        // Subscriptions to too many addresses are rejected
+        let addresses = (0..=rpc_pubsub_service::DEFAULT_MAX_LOGS_SUBSCRIPTION_ADDRESSES)
+            .map(|_| Pubkey::new_unique().to_string())
+            .collect();
+        assert!(rpc
+            .logs_subscribe(RpcTransactionLogsFilter::Programs(addresses), None)
+            .is_err());
+    }
+
     #[test]
     #[serial]
     fn test_check_program_subscribe() {
@@ -1962,6 +2215,84 @@ pub(crate) mod tests {
             .signature_subscribed(&unprocessed_tx.signatures[0]));
     }
 
+    #[test]
+    #[serial]
+    fn test_check_signature_subscribe_frozen() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let mut bank_forks = BankForks::new(bank);
+        let alice = Keypair::new();
+        let tx = system_transaction::transfer(&mint_keypair, &alice.pubkey(), 1, blockhash);
+        let bank1 = Bank::new_from_parent(&bank_forks[0], &solana_sdk::pubkey::new_rand(), 1);
+        bank1.process_transaction(&tx).unwrap();
+        bank1.freeze();
+        bank_forks.insert(bank1);
+        let bank_forks = Arc::new(RwLock::new(bank_forks));
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            &exit,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (processed_rpc, mut processed_receiver) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let (confirmed_rpc, _confirmed_receiver) =
+            rpc_pubsub_service::test_connection(&subscriptions);
+        let processed_sub_id = processed_rpc
+            .signature_subscribe(
+                tx.signatures[0].to_string(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    enable_received_notification: Some(false),
+                }),
+            )
+            .unwrap();
+        confirmed_rpc
+            .signature_subscribe(
+                tx.signatures[0].to_string(),
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    enable_received_notification: Some(false),
+                }),
+            )
+            .unwrap();
+
+        // Processed subscriptions are notified once the bank is frozen, without waiting for the
+        // commitment of the bank
+        subscriptions.notify_frozen(1);
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "method": "signatureNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 1 },
+                    "value": RpcSignatureResult::ProcessedSignature(
+                        ProcessedSignatureResult { err: None }
+                    ),
+                },
+                "subscription": u64::from(processed_sub_id),
+            }
+        });
+        assert_eq!(
+            serde_json::to_string(&expected).unwrap(),
+            processed_receiver.recv()
+        );
+
+        // The confirmed subscription is still waiting for its notification
+        assert!(subscriptions
+            .control
+            .signature_subscribed(&tx.signatures[0]));
+    }
+
     #[test]
     #[serial]
     fn test_check_slot_subscribe() {
@@ -2084,11 +2415,15 @@ pub(crate) mod tests {
         let sub_id0 = rpc0
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                }),
+                Some(
+                    RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    }
+                    .into(),
+                ),
             )
             .unwrap();
 
@@ -2171,11 +2506,15 @@ pub(crate) mod tests {
         let sub_id1 = rpc1
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                }),
+                Some(
+                    RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    }
+                    .into(),
+                ),
             )
             .unwrap();
 
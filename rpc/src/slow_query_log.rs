@@ -0,0 +1,235 @@
+//! The `slow_query_log` module times each RPC call and keeps the most recent ones that took
+//! longer than a threshold, so that operators can find abusive or pathological calls.
+
+use {
+    crate::rpc::JsonRpcRequestProcessor,
+    jsonrpc_core::{
+        futures::{future::Either, FutureExt},
+        BoxFuture, Call, Middleware, Output,
+    },
+    solana_sdk::timing::timestamp,
+    std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+/// Number of slow queries kept by default
+pub const DEFAULT_SLOW_QUERY_LOG_CAPACITY: usize = 1_000;
+
+/// Maximum length of the summary of the parameters of a query
+const MAX_PARAMS_SUMMARY_LEN: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQuery {
+    pub method: String,
+    /// The parameters of the call, truncated
+    pub params: String,
+    /// When the call completed, in milliseconds since the UNIX epoch
+    pub timestamp: u64,
+    /// Time between the receipt of the HTTP request and the start of the call
+    pub queue_us: u64,
+    /// Time spent processing the call
+    pub processing_us: u64,
+    /// Size of the serialized response
+    pub response_bytes: usize,
+}
+
+#[derive(Debug)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    capacity: usize,
+    queries: Mutex<VecDeque<SlowQuery>>,
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold: Duration, capacity: usize) -> Self {
+        Self {
+            threshold,
+            capacity,
+            queries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    fn is_slow(&self, queue_time: Duration, processing_time: Duration) -> bool {
+        queue_time + processing_time >= self.threshold
+    }
+
+    fn push(&self, query: SlowQuery) {
+        warn!(
+            "slow rpc query: {} {} queue: {}us processing: {}us response: {} bytes",
+            query.method, query.params, query.queue_us, query.processing_us, query.response_bytes,
+        );
+        datapoint_info!(
+            "rpc-slow-query",
+            ("method", query.method.clone(), String),
+            ("queue_us", query.queue_us, i64),
+            ("processing_us", query.processing_us, i64),
+            ("response_bytes", query.response_bytes, i64),
+        );
+        let mut queries = self.queries.lock().unwrap();
+        if queries.len() >= self.capacity {
+            queries.pop_front();
+        }
+        queries.push_back(query);
+    }
+
+    /// Returns the most recent slow queries, oldest first
+    pub fn recent(&self) -> Vec<SlowQuery> {
+        self.queries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+fn summarize_params(params: &jsonrpc_core::Params) -> String {
+    let mut summary = serde_json::to_string(params).unwrap_or_default();
+    if summary.len() > MAX_PARAMS_SUMMARY_LEN {
+        let mut end = MAX_PARAMS_SUMMARY_LEN;
+        while !summary.is_char_boundary(end) {
+            end -= 1;
+        }
+        summary.truncate(end);
+        summary.push_str("...");
+    }
+    summary
+}
+
+/// Times the calls handled by the RPC service, recording the slow ones in a `SlowQueryLog`
+#[derive(Clone)]
+pub struct SlowQueryMiddleware {
+    log: Option<Arc<SlowQueryLog>>,
+}
+
+impl SlowQueryMiddleware {
+    pub fn new(log: Option<Arc<SlowQueryLog>>) -> Self {
+        Self { log }
+    }
+}
+
+impl Middleware<JsonRpcRequestProcessor> for SlowQueryMiddleware {
+    type Future = BoxFuture<Option<jsonrpc_core::Response>>;
+    type CallFuture = BoxFuture<Option<Output>>;
+
+    fn on_call<F, X>(
+        &self,
+        call: Call,
+        meta: JsonRpcRequestProcessor,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, JsonRpcRequestProcessor) -> X + Send + Sync,
+        X: std::future::Future<Output = Option<Output>> + Send + 'static,
+    {
+        let (log, method, params) = match (&self.log, &call) {
+            (Some(log), Call::MethodCall(method_call)) => (
+                log.clone(),
+                method_call.method.clone(),
+                method_call.params.clone(),
+            ),
+            _ => return Either::Right(next(call, meta)),
+        };
+        let start = Instant::now();
+        let queue_time = meta
+            .request_received()
+            .map(|received| start.saturating_duration_since(received))
+            .unwrap_or_default();
+        Either::Left(Box::pin(next(call, meta).map(move |output| {
+            let processing_time = start.elapsed();
+            if log.is_slow(queue_time, processing_time) {
+                log.push(SlowQuery {
+                    method,
+                    params: summarize_params(&params),
+                    timestamp: timestamp(),
+                    queue_us: queue_time.as_micros() as u64,
+                    processing_us: processing_time.as_micros() as u64,
+                    response_bytes: output
+                        .as_ref()
+                        .and_then(|output| serde_json::to_vec(output).ok())
+                        .map(|response| response.len())
+                        .unwrap_or_default(),
+                });
+            }
+            output
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        jsonrpc_core::{MetaIoHandler, Params, Value},
+        solana_runtime::bank::Bank,
+        solana_sdk::genesis_config::create_genesis_config,
+        solana_streamer::socket::SocketAddrSpace,
+        std::thread::sleep,
+    };
+
+    fn slow_query(method: &str) -> SlowQuery {
+        SlowQuery {
+            method: method.to_string(),
+            params: String::new(),
+            timestamp: 0,
+            queue_us: 0,
+            processing_us: 0,
+            response_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_slow_query_log_capacity() {
+        let log = SlowQueryLog::new(Duration::from_millis(10), 2);
+        assert!(!log.is_slow(Duration::from_millis(4), Duration::from_millis(5)));
+        assert!(log.is_slow(Duration::from_millis(5), Duration::from_millis(5)));
+
+        log.push(slow_query("a"));
+        log.push(slow_query("b"));
+        log.push(slow_query("c"));
+        let methods: Vec<_> = log.recent().into_iter().map(|query| query.method).collect();
+        assert_eq!(methods, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_summarize_params() {
+        let params = Params::Array(vec![Value::String("é".repeat(MAX_PARAMS_SUMMARY_LEN))]);
+        let summary = summarize_params(&params);
+        assert!(summary.ends_with("..."));
+        assert!(summary.len() <= MAX_PARAMS_SUMMARY_LEN + 3);
+        assert_eq!(summarize_params(&Params::None), "null");
+    }
+
+    #[test]
+    fn test_slow_query_middleware() {
+        let (genesis_config, _) = create_genesis_config(1);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified)
+            .with_request_received(Instant::now());
+        let log = Arc::new(SlowQueryLog::new(Duration::from_millis(50), 10));
+        let mut io = MetaIoHandler::with_middleware(SlowQueryMiddleware::new(Some(log.clone())));
+        io.add_method_with_meta("fast", |_params, _meta: JsonRpcRequestProcessor| async {
+            Ok(Value::Bool(true))
+        });
+        io.add_method_with_meta("slow", |_params, _meta: JsonRpcRequestProcessor| async {
+            sleep(Duration::from_millis(60));
+            Ok(Value::String("done".to_string()))
+        });
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"fast","params":[]}"#;
+        io.handle_request_sync(request, meta.clone()).unwrap();
+        assert!(log.recent().is_empty());
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"slow","params":[42]}"#;
+        let response = io.handle_request_sync(request, meta).unwrap();
+        let queries = log.recent();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].method, "slow");
+        assert_eq!(queries[0].params, "[42]");
+        assert!(queries[0].processing_us >= 60_000);
+        assert_eq!(queries[0].response_bytes, response.len());
+    }
+}
@@ -0,0 +1,89 @@
+//! The `transaction_history_search` module runs the lookups of transaction statuses in the ledger
+//! history on a thread pool of its own, and limits their rate. These lookups hit the blockstore or
+//! BigTable, which is much more expensive than looking in the status caches of the recent banks,
+//! and would otherwise stall the RPC threads serving those.
+
+use {
+    rayon::{ThreadPool, ThreadPoolBuilder},
+    std::{
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+    tokio::sync::oneshot,
+};
+
+/// Number of threads searching the transaction history by default
+pub const DEFAULT_TRANSACTION_HISTORY_SEARCH_THREADS: usize = 4;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+pub struct TransactionHistorySearch {
+    thread_pool: ThreadPool,
+    max_searches_per_second: Option<u64>,
+    /// Start of the current rate limit window, and number of searches made in it
+    window: Mutex<(Instant, u64)>,
+}
+
+impl TransactionHistorySearch {
+    pub fn new(num_threads: usize, max_searches_per_second: Option<u64>) -> Self {
+        Self {
+            thread_pool: ThreadPoolBuilder::new()
+                .num_threads(num_threads.max(1))
+                .thread_name(|i| format!("sol-rpc-history-{}", i))
+                .build()
+                .unwrap(),
+            max_searches_per_second,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Reserves `count` searches, unless they would exceed the rate limit
+    pub fn try_reserve(&self, count: usize) -> bool {
+        let max_searches_per_second = match self.max_searches_per_second {
+            Some(max_searches_per_second) => max_searches_per_second,
+            None => return true,
+        };
+        let mut window = self.window.lock().unwrap();
+        let (start, searches) = &mut *window;
+        if start.elapsed() >= RATE_LIMIT_WINDOW {
+            *start = Instant::now();
+            *searches = 0;
+        }
+        if *searches + count as u64 > max_searches_per_second {
+            inc_new_counter_info!("rpc-transaction-history-search-rate-limited", 1);
+            return false;
+        }
+        *searches += count as u64;
+        true
+    }
+
+    /// Runs `search` on the thread pool, parallel iterators in it included
+    pub async fn run<T: Send + 'static>(&self, search: impl FnOnce() -> T + Send + 'static) -> T {
+        let (sender, receiver) = oneshot::channel();
+        self.thread_pool.spawn(move || {
+            let _ = sender.send(search());
+        });
+        receiver.await.expect("transaction history search panicked")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_history_search_rate_limit() {
+        let unlimited = TransactionHistorySearch::new(1, None);
+        assert!(unlimited.try_reserve(usize::MAX / 2));
+
+        let search = TransactionHistorySearch::new(1, Some(10));
+        assert!(search.try_reserve(4));
+        assert!(search.try_reserve(6));
+        assert!(!search.try_reserve(1));
+
+        // The searches made are forgotten in the next window
+        search.window.lock().unwrap().0 -= RATE_LIMIT_WINDOW;
+        assert!(!search.try_reserve(11));
+        assert!(search.try_reserve(10));
+    }
+}
@@ -0,0 +1,358 @@
+//! The `transaction_logs_bus` module shares the transaction logs of the banks between the logs
+//! subscriptions. The logs of a bank are collected from it once per notified state, and the
+//! notification of each transaction is encoded once, however many subscriptions it is sent to.
+
+use {
+    crate::rpc_subscription_tracker::LogsSubscriptionKind,
+    solana_client::rpc_response::{Response, RpcLogsResponse, RpcResponseContext},
+    solana_runtime::bank::Bank,
+    solana_sdk::{
+        clock::{BankId, Slot},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+/// Number of banks whose logs are kept, the logs of the lowest slots are dropped first
+const MAX_BANKS: usize = 64;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LogsNotificationLimits {
+    /// Log messages notified per transaction, the following ones are replaced by a marker,
+    /// unlimited if zero
+    pub max_messages: usize,
+    /// Notifications sent to a subscription per second, the following ones are dropped and
+    /// counted in the next notification sent, unlimited if zero
+    pub max_notifications_per_second: usize,
+}
+
+/// Logs of a transaction, as notified to the subscriptions
+#[derive(Debug)]
+pub struct TransactionLogsEntry {
+    is_vote: bool,
+    /// Programs invoked by the transaction, as recorded when it was processed
+    invoked_programs: Vec<Pubkey>,
+    response: Response<RpcLogsResponse>,
+    /// The response, encoded as JSON
+    encoded: String,
+}
+
+impl TransactionLogsEntry {
+    pub fn encoded(&self) -> &str {
+        &self.encoded
+    }
+
+    /// Returns the response, counting the notifications of the subscription dropped before it
+    pub fn response_with_dropped_notifications(
+        &self,
+        dropped_notifications: u64,
+    ) -> Response<RpcLogsResponse> {
+        let mut response = self.response.clone();
+        response.value.dropped_notifications = Some(dropped_notifications);
+        response
+    }
+
+    fn invokes_any(&self, program_ids: &[Pubkey]) -> bool {
+        self.invoked_programs
+            .iter()
+            .any(|program_id| program_ids.contains(program_id))
+    }
+}
+
+/// Logs of the transactions of a bank
+#[derive(Debug)]
+pub struct BankTransactionLogs {
+    transactions: Vec<TransactionLogsEntry>,
+    /// Indices of the transactions mentioning each of the addresses subscribed to
+    mentioned_address_map: HashMap<Pubkey, Vec<usize>>,
+}
+
+impl BankTransactionLogs {
+    fn new(bank: &Bank, max_messages: usize) -> Self {
+        let transaction_log_collector = bank.transaction_log_collector.read().unwrap();
+        let transactions = transaction_log_collector
+            .logs
+            .iter()
+            .map(|log| {
+                let mut logs = log.log_messages.clone();
+                if max_messages > 0 && logs.len() > max_messages {
+                    let omitted = logs.len() - max_messages;
+                    logs.truncate(max_messages);
+                    logs.push(format!(
+                        "Log truncated by the RPC node, {} messages omitted",
+                        omitted
+                    ));
+                }
+                let response = Response {
+                    context: RpcResponseContext { slot: bank.slot() },
+                    value: RpcLogsResponse {
+                        signature: log.signature.to_string(),
+                        err: log.result.clone().err(),
+                        logs,
+                        dropped_notifications: None,
+                    },
+                };
+                let encoded = serde_json::to_string(&response).expect("serialization never fails");
+                TransactionLogsEntry {
+                    is_vote: log.is_vote,
+                    invoked_programs: log.invoked_programs.clone(),
+                    response,
+                    encoded,
+                }
+            })
+            .collect();
+        Self {
+            transactions,
+            mentioned_address_map: transaction_log_collector.mentioned_address_map.clone(),
+        }
+    }
+
+    /// Returns the logs of the transactions the subscription is notified of, in order
+    pub fn filter(&self, kind: &LogsSubscriptionKind) -> Vec<&TransactionLogsEntry> {
+        match kind {
+            LogsSubscriptionKind::All => self
+                .transactions
+                .iter()
+                .filter(|entry| !entry.is_vote)
+                .collect(),
+            LogsSubscriptionKind::AllWithVotes => self.transactions.iter().collect(),
+            LogsSubscriptionKind::Mentions(addresses) => self.mentioning(addresses).collect(),
+            // The invoked programs are mentioned by the transactions, their logs are collected
+            // only for the mentioned addresses
+            LogsSubscriptionKind::Programs(program_ids) => self
+                .mentioning(program_ids)
+                .filter(|entry| entry.invokes_any(program_ids))
+                .collect(),
+            LogsSubscriptionKind::ProgramChain(program_id) => {
+                let program_ids = std::slice::from_ref(program_id);
+                self.mentioning(program_ids)
+                    .filter(|entry| entry.invokes_any(program_ids))
+                    .collect()
+            }
+        }
+    }
+
+    fn mentioning<'a>(
+        &'a self,
+        addresses: &[Pubkey],
+    ) -> impl Iterator<Item = &'a TransactionLogsEntry> {
+        let mut indices: Vec<usize> = addresses
+            .iter()
+            .filter_map(|address| self.mentioned_address_map.get(address))
+            .flatten()
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+            .into_iter()
+            .filter_map(move |i| self.transactions.get(i))
+    }
+}
+
+/// Transaction logs of the recently notified banks, shared by all the logs subscriptions
+#[derive(Debug, Default)]
+pub struct TransactionLogsBus {
+    limits: LogsNotificationLimits,
+    banks: Mutex<HashMap<BankId, (Slot, Arc<BankTransactionLogs>)>>,
+}
+
+impl TransactionLogsBus {
+    pub fn new(limits: LogsNotificationLimits) -> Self {
+        Self {
+            limits,
+            banks: Mutex::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &LogsNotificationLimits {
+        &self.limits
+    }
+
+    /// Returns the logs of the bank, collecting them from it again only once it has processed
+    /// more transactions, as an unfrozen bank may
+    pub fn bank_logs(&self, bank: &Bank) -> Arc<BankTransactionLogs> {
+        let logs_len = bank.transaction_log_collector.read().unwrap().logs.len();
+        let mut banks = self.banks.lock().unwrap();
+        if let Some((_slot, logs)) = banks.get(&bank.bank_id()) {
+            if logs.transactions.len() == logs_len {
+                return logs.clone();
+            }
+        } else if banks.len() >= MAX_BANKS {
+            let lowest_bank_id = banks
+                .iter()
+                .min_by_key(|(_bank_id, (slot, _logs))| *slot)
+                .map(|(bank_id, _)| *bank_id);
+            if let Some(bank_id) = lowest_bank_id {
+                banks.remove(&bank_id);
+            }
+        }
+        let logs = Arc::new(BankTransactionLogs::new(bank, self.limits.max_messages));
+        banks.insert(bank.bank_id(), (bank.slot(), logs.clone()));
+        logs
+    }
+}
+
+/// Caps the rate of the notifications of a logs subscription, counting those dropped
+#[derive(Debug, Default)]
+pub struct LogsNotificationRate {
+    window_start: Option<Instant>,
+    sent_in_window: usize,
+    dropped: u64,
+}
+
+impl LogsNotificationRate {
+    /// Returns whether the next notification may be sent, and if so the number of
+    /// notifications dropped since the previous one sent
+    pub fn check(&mut self, max_notifications_per_second: usize) -> Option<u64> {
+        if max_notifications_per_second == 0 {
+            return Some(0);
+        }
+        let now = Instant::now();
+        match self.window_start {
+            Some(window_start) if now.duration_since(window_start) < Duration::from_secs(1) => {}
+            _ => {
+                self.window_start = Some(now);
+                self.sent_in_window = 0;
+            }
+        }
+        if self.sent_in_window < max_notifications_per_second {
+            self.sent_in_window += 1;
+            Some(std::mem::take(&mut self.dropped))
+        } else {
+            self.dropped += 1;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_runtime::{
+            bank::{ExecuteTimings, TransactionLogCollectorConfig, TransactionLogCollectorFilter},
+            genesis_utils::create_genesis_config,
+        },
+        solana_sdk::{
+            clock::MAX_PROCESSING_AGE, system_program, system_transaction, transaction::Transaction,
+        },
+        std::collections::HashSet,
+    };
+
+    fn process_transaction_with_logs(bank: &Bank, tx: Transaction) {
+        let batch = bank.prepare_batch_for_tests(vec![tx]);
+        let (results, ..) = bank.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            false,
+            false,
+            true,
+            &mut ExecuteTimings::default(),
+        );
+        assert!(results.fee_collection_results[0].is_ok());
+    }
+
+    #[test]
+    fn test_bank_transaction_logs() {
+        let genesis = create_genesis_config(1_000_000);
+        let bank = Bank::new_for_tests(&genesis.genesis_config);
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        *bank.transaction_log_collector_config.write().unwrap() = TransactionLogCollectorConfig {
+            filter: TransactionLogCollectorFilter::All,
+            mentioned_addresses: vec![alice, bob, system_program::id()]
+                .into_iter()
+                .collect::<HashSet<_>>(),
+        };
+        let blockhash = bank.last_blockhash();
+        let mint_keypair = &genesis.mint_keypair;
+        let transfer = |lamports, to| {
+            process_transaction_with_logs(
+                &bank,
+                system_transaction::transfer(mint_keypair, to, lamports, blockhash),
+            )
+        };
+        transfer(1, &alice);
+        transfer(2, &bob);
+
+        let limited_bus = TransactionLogsBus::new(LogsNotificationLimits {
+            max_messages: 1,
+            max_notifications_per_second: 0,
+        });
+        // The logs of an unfrozen bank are shared until it processes more transactions
+        let unfrozen_logs = limited_bus.bank_logs(&bank);
+        assert_eq!(unfrozen_logs.filter(&LogsSubscriptionKind::All).len(), 2);
+        assert!(Arc::ptr_eq(&unfrozen_logs, &limited_bus.bank_logs(&bank)));
+        transfer(3, &alice);
+        bank.freeze();
+
+        let logs = limited_bus.bank_logs(&bank);
+        assert!(!Arc::ptr_eq(&unfrozen_logs, &logs));
+        assert_eq!(logs.filter(&LogsSubscriptionKind::All).len(), 3);
+        assert_eq!(
+            logs.filter(&LogsSubscriptionKind::Mentions(vec![alice]))
+                .len(),
+            2
+        );
+        // Transactions mentioning any of the addresses
+        assert_eq!(
+            logs.filter(&LogsSubscriptionKind::Mentions(vec![alice, bob]))
+                .len(),
+            3
+        );
+        assert_eq!(
+            logs.filter(&LogsSubscriptionKind::Programs(vec![system_program::id()]))
+                .len(),
+            3
+        );
+        // Mentioned, but not invoked
+        assert!(logs
+            .filter(&LogsSubscriptionKind::Programs(vec![alice]))
+            .is_empty());
+
+        // The logs past the limit are replaced by a marker
+        let entry = logs.filter(&LogsSubscriptionKind::All)[0];
+        assert_eq!(
+            entry.response.value.logs,
+            vec![
+                format!("Program {} invoke [1]", system_program::id()),
+                "Log truncated by the RPC node, 1 messages omitted".to_string(),
+            ]
+        );
+        assert_eq!(
+            entry.encoded(),
+            serde_json::to_string(&entry.response).unwrap()
+        );
+        assert_eq!(
+            entry.response.value.signature,
+            bank.transaction_log_collector.read().unwrap().logs[0]
+                .signature
+                .to_string()
+        );
+        // The invoked programs are recorded by the runtime rather than parsed from the logs
+        assert_eq!(entry.invoked_programs, vec![system_program::id()]);
+
+        // The logs of a bank are collected once
+        assert!(Arc::ptr_eq(&logs, &limited_bus.bank_logs(&bank)));
+    }
+
+    #[test]
+    fn test_logs_notification_rate() {
+        let mut rate = LogsNotificationRate::default();
+        assert_eq!(rate.check(0), Some(0));
+
+        assert_eq!(rate.check(2), Some(0));
+        assert_eq!(rate.check(2), Some(0));
+        assert_eq!(rate.check(2), None);
+        assert_eq!(rate.check(2), None);
+        // The dropped notifications are counted in the next one sent
+        rate.window_start = Some(Instant::now() - Duration::from_secs(1));
+        assert_eq!(rate.check(2), Some(2));
+        assert_eq!(rate.check(2), Some(0));
+    }
+}
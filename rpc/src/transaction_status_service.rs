@@ -6,6 +6,7 @@ use {
         blockstore_processor::{TransactionStatusBatch, TransactionStatusMessage},
     },
     solana_runtime::bank::{Bank, InnerInstructionsList, TransactionLogMessages},
+    solana_sdk::message::{MappedAddresses, SanitizedMessage},
     solana_transaction_status::{
         extract_and_fmt_memos, InnerInstructions, Reward, TransactionStatusMeta,
     },
@@ -19,6 +20,10 @@ use {
     },
 };
 
+/// Last log message of a transaction whose logs were truncated, as the runtime does past its own
+/// limit
+const LOG_TRUNCATED_MESSAGE: &str = "Log truncated";
+
 pub struct TransactionStatusService {
     thread_hdl: JoinHandle<()>,
 }
@@ -29,6 +34,7 @@ impl TransactionStatusService {
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         blockstore: Arc<Blockstore>,
+        max_stored_log_messages_bytes: Option<usize>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let exit = exit.clone();
@@ -42,6 +48,7 @@ impl TransactionStatusService {
                     &write_transaction_status_receiver,
                     &max_complete_transaction_status_slot,
                     &blockstore,
+                    max_stored_log_messages_bytes,
                 ) {
                     break;
                 }
@@ -54,16 +61,19 @@ impl TransactionStatusService {
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
         blockstore: &Arc<Blockstore>,
+        max_stored_log_messages_bytes: Option<usize>,
     ) -> Result<(), RecvTimeoutError> {
         match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
             TransactionStatusMessage::Batch(TransactionStatusBatch {
                 bank,
                 transactions,
                 statuses,
+                execution_details,
                 balances,
                 token_balances,
                 inner_instructions,
                 transaction_logs,
+                compute_budget_exceeded,
                 rent_debits,
             }) => {
                 let slot = bank.slot();
@@ -84,22 +94,26 @@ impl TransactionStatusService {
                 for (
                     transaction,
                     (status, nonce_rollback),
+                    execution_details,
                     pre_balances,
                     post_balances,
                     pre_token_balances,
                     post_token_balances,
                     inner_instructions,
                     log_messages,
+                    compute_budget_exceeded,
                     rent_debits,
                 ) in izip!(
                     transactions,
                     statuses,
+                    execution_details,
                     balances.pre_balances,
                     balances.post_balances,
                     token_balances.pre_token_balances,
                     token_balances.post_token_balances,
                     inner_instructions_iter,
                     transaction_logs_iter,
+                    compute_budget_exceeded,
                     rent_debits,
                 ) {
                     if Bank::can_commit(&status) {
@@ -111,9 +125,10 @@ impl TransactionStatusService {
                                 )
                             })
                             .expect("lamports_per_signature must be available");
-                        let fee = Bank::get_fee_for_message_with_lamports_per_signature(
+                        let fee = Bank::calculate_fee_with_feature_set(
                             transaction.message(),
                             lamports_per_signature,
+                            &bank.feature_set,
                         );
                         let tx_account_locks =
                             transaction.get_account_locks(bank.demote_program_write_locks());
@@ -130,6 +145,13 @@ impl TransactionStatusService {
                                 .collect()
                         });
 
+                        let log_messages = match (log_messages, max_stored_log_messages_bytes) {
+                            (Some(log_messages), Some(max_bytes)) => {
+                                Some(Self::truncate_log_messages(log_messages, max_bytes))
+                            }
+                            (log_messages, _) => log_messages,
+                        };
+
                         let pre_token_balances = Some(pre_token_balances);
                         let post_token_balances = Some(post_token_balances);
                         let rewards = Some(
@@ -145,6 +167,11 @@ impl TransactionStatusService {
                                 .collect(),
                         );
 
+                        let loaded_addresses = match transaction.message() {
+                            SanitizedMessage::Legacy(_) => MappedAddresses::default(),
+                            SanitizedMessage::V0(message) => message.mapped_addresses.clone(),
+                        };
+
                         if let Some(memos) = extract_and_fmt_memos(transaction.message()) {
                             blockstore
                                 .write_transaction_memos(transaction.signature(), memos)
@@ -167,6 +194,13 @@ impl TransactionStatusService {
                                     pre_token_balances,
                                     post_token_balances,
                                     rewards,
+                                    loaded_addresses,
+                                    compute_budget_exceeded,
+                                    compute_units_consumed: Some(execution_details.executed_units),
+                                    fee_details: execution_details.fee_details,
+                                    loaded_accounts_data_size: Some(
+                                        execution_details.loaded_accounts_data_size as u64,
+                                    ),
                                 },
                             )
                             .expect("Expect database write to succeed: TransactionStatus");
@@ -180,7 +214,50 @@ impl TransactionStatusService {
         Ok(())
     }
 
+    /// Drops the log messages past `max_bytes`, ending the ones kept with a message telling they
+    /// were truncated. A limit of 0 stores no logs at all.
+    fn truncate_log_messages(mut log_messages: Vec<String>, max_bytes: usize) -> Vec<String> {
+        if max_bytes == 0 {
+            return vec![];
+        }
+        let mut bytes = 0;
+        let truncate_at = log_messages.iter().position(|message| {
+            bytes += message.len();
+            bytes > max_bytes
+        });
+        if let Some(truncate_at) = truncate_at {
+            inc_new_counter_info!("transaction-status-truncated-logs", 1);
+            log_messages.truncate(truncate_at);
+            log_messages.push(LOG_TRUNCATED_MESSAGE.to_string());
+        }
+        log_messages
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.thread_hdl.join()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_log_messages() {
+        let log_messages = vec!["abc".to_string(), "de".to_string(), "f".to_string()];
+        assert_eq!(
+            TransactionStatusService::truncate_log_messages(log_messages.clone(), 6),
+            log_messages
+        );
+        assert_eq!(
+            TransactionStatusService::truncate_log_messages(log_messages.clone(), 5),
+            vec!["abc", "de", LOG_TRUNCATED_MESSAGE]
+        );
+        assert_eq!(
+            TransactionStatusService::truncate_log_messages(log_messages.clone(), 3),
+            vec!["abc", LOG_TRUNCATED_MESSAGE]
+        );
+        assert!(TransactionStatusService::truncate_log_messages(log_messages, 0).is_empty());
+        assert!(TransactionStatusService::truncate_log_messages(vec![], 0).is_empty());
+    }
+}
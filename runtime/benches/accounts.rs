@@ -1,15 +1,13 @@
-#![feature(test)]
 #![allow(clippy::integer_arithmetic)]
 
-extern crate test;
-
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use dashmap::DashMap;
 use rand::Rng;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use solana_runtime::{
     accounts::{create_test_accounts, AccountAddressFilter, Accounts},
     accounts_db::AccountShrinkThreshold,
-    accounts_index::AccountSecondaryIndexes,
+    accounts_index::{AccountSecondaryIndexes, ScanConfig},
     ancestors::Ancestors,
     bank::*,
 };
@@ -23,10 +21,20 @@ use solana_sdk::{
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
-    sync::{Arc, RwLock},
-    thread::Builder,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{Builder, JoinHandle},
 };
-use test::Bencher;
+
+/// Stops and joins the threads contending with a bench, so they don't skew the benches after it
+fn stop_contending_threads(exit: &AtomicBool, threads: Vec<JoinHandle<()>>) {
+    exit.store(true, Ordering::Relaxed);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
 
 fn deposit_many(bank: &Bank, pubkeys: &mut Vec<Pubkey>, num: usize) -> Result<(), LamportsError> {
     for t in 0..num {
@@ -41,8 +49,7 @@ fn deposit_many(bank: &Bank, pubkeys: &mut Vec<Pubkey>, num: usize) -> Result<()
     Ok(())
 }
 
-#[bench]
-fn test_accounts_create(bencher: &mut Bencher) {
+fn test_accounts_create(c: &mut Criterion) {
     let (genesis_config, _) = create_genesis_config(10_000);
     let bank0 = Bank::new_with_paths_for_benches(
         &genesis_config,
@@ -55,14 +62,15 @@ fn test_accounts_create(bencher: &mut Bencher) {
         AccountShrinkThreshold::default(),
         false,
     );
-    bencher.iter(|| {
-        let mut pubkeys: Vec<Pubkey> = vec![];
-        deposit_many(&bank0, &mut pubkeys, 1000).unwrap();
+    c.bench_function("test_accounts_create", |bencher| {
+        bencher.iter(|| {
+            let mut pubkeys: Vec<Pubkey> = vec![];
+            deposit_many(&bank0, &mut pubkeys, 1000).unwrap();
+        });
     });
 }
 
-#[bench]
-fn test_accounts_squash(bencher: &mut Bencher) {
+fn test_accounts_squash(c: &mut Criterion) {
     let (mut genesis_config, _) = create_genesis_config(100_000);
     genesis_config.rent.burn_percent = 100; // Avoid triggering an assert in Bank::distribute_rent_to_validators()
     let mut prev_bank = Arc::new(Bank::new_with_paths_for_benches(
@@ -84,17 +92,18 @@ fn test_accounts_squash(bencher: &mut Bencher) {
     // This mainly consists of the freeze operation which calculates the
     // merkle hash of the account state and distribution of fees and rent
     let mut slot = 1u64;
-    bencher.iter(|| {
-        let next_bank = Arc::new(Bank::new_from_parent(&prev_bank, &Pubkey::default(), slot));
-        next_bank.deposit(&pubkeys[0], 1).unwrap();
-        next_bank.squash();
-        slot += 1;
-        prev_bank = next_bank;
+    c.bench_function("test_accounts_squash", |bencher| {
+        bencher.iter(|| {
+            let next_bank = Arc::new(Bank::new_from_parent(&prev_bank, &Pubkey::default(), slot));
+            next_bank.deposit(&pubkeys[0], 1).unwrap();
+            next_bank.squash();
+            slot += 1;
+            prev_bank = next_bank;
+        });
     });
 }
 
-#[bench]
-fn test_accounts_hash_bank_hash(bencher: &mut Bencher) {
+fn test_accounts_hash_bank_hash(c: &mut Criterion) {
     let accounts = Accounts::new_with_config_for_benches(
         vec![PathBuf::from("bench_accounts_hash_internal")],
         &ClusterType::Development,
@@ -109,18 +118,19 @@ fn test_accounts_hash_bank_hash(bencher: &mut Bencher) {
     let ancestors = Ancestors::from(vec![0]);
     let (_, total_lamports) = accounts.accounts_db.update_accounts_hash(0, &ancestors);
     let test_hash_calculation = false;
-    bencher.iter(|| {
-        assert!(accounts.verify_bank_hash_and_lamports(
-            0,
-            &ancestors,
-            total_lamports,
-            test_hash_calculation
-        ))
+    c.bench_function("test_accounts_hash_bank_hash", |bencher| {
+        bencher.iter(|| {
+            assert!(accounts.verify_bank_hash_and_lamports(
+                0,
+                &ancestors,
+                total_lamports,
+                test_hash_calculation
+            ))
+        });
     });
 }
 
-#[bench]
-fn test_update_accounts_hash(bencher: &mut Bencher) {
+fn test_update_accounts_hash(c: &mut Criterion) {
     solana_logger::setup();
     let accounts = Accounts::new_with_config_for_benches(
         vec![PathBuf::from("update_accounts_hash")],
@@ -132,13 +142,14 @@ fn test_update_accounts_hash(bencher: &mut Bencher) {
     let mut pubkeys: Vec<Pubkey> = vec![];
     create_test_accounts(&accounts, &mut pubkeys, 50_000, 0);
     let ancestors = Ancestors::from(vec![0]);
-    bencher.iter(|| {
-        accounts.accounts_db.update_accounts_hash(0, &ancestors);
+    c.bench_function("test_update_accounts_hash", |bencher| {
+        bencher.iter(|| {
+            accounts.accounts_db.update_accounts_hash(0, &ancestors);
+        });
     });
 }
 
-#[bench]
-fn test_accounts_delta_hash(bencher: &mut Bencher) {
+fn test_accounts_delta_hash(c: &mut Criterion) {
     solana_logger::setup();
     let accounts = Accounts::new_with_config_for_benches(
         vec![PathBuf::from("accounts_delta_hash")],
@@ -149,13 +160,14 @@ fn test_accounts_delta_hash(bencher: &mut Bencher) {
     );
     let mut pubkeys: Vec<Pubkey> = vec![];
     create_test_accounts(&accounts, &mut pubkeys, 100_000, 0);
-    bencher.iter(|| {
-        accounts.accounts_db.get_accounts_delta_hash(0);
+    c.bench_function("test_accounts_delta_hash", |bencher| {
+        bencher.iter(|| {
+            accounts.accounts_db.get_accounts_delta_hash(0);
+        });
     });
 }
 
-#[bench]
-fn bench_delete_dependencies(bencher: &mut Bencher) {
+fn bench_delete_dependencies(c: &mut Criterion) {
     solana_logger::setup();
     let accounts = Accounts::new_with_config_for_benches(
         vec![PathBuf::from("accounts_delete_deps")],
@@ -175,17 +187,19 @@ fn bench_delete_dependencies(bencher: &mut Bencher) {
         old_pubkey = pubkey;
         accounts.add_root(i);
     }
-    bencher.iter(|| {
-        accounts.accounts_db.clean_accounts(None, false, None);
+    c.bench_function("bench_delete_dependencies", |bencher| {
+        bencher.iter(|| {
+            accounts.accounts_db.clean_accounts(None, false, None);
+        });
     });
 }
 
 fn store_accounts_with_possible_contention<F: 'static>(
     bench_name: &str,
-    bencher: &mut Bencher,
+    c: &mut Criterion,
     reader_f: F,
 ) where
-    F: Fn(&Accounts, &[Pubkey]) + Send + Copy,
+    F: Fn(&Accounts, &[Pubkey], &AtomicBool) + Send + Copy,
 {
     let num_readers = 5;
     let accounts = Arc::new(Accounts::new_with_config_for_benches(
@@ -212,42 +226,47 @@ fn store_accounts_with_possible_contention<F: 'static>(
             .collect(),
     );
 
-    for _ in 0..num_readers {
-        let accounts = accounts.clone();
-        let pubkeys = pubkeys.clone();
-        Builder::new()
-            .name("readers".to_string())
-            .spawn(move || {
-                reader_f(&accounts, &pubkeys);
-            })
-            .unwrap();
-    }
+    let exit = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> = (0..num_readers)
+        .map(|_| {
+            let accounts = accounts.clone();
+            let pubkeys = pubkeys.clone();
+            let exit = exit.clone();
+            Builder::new()
+                .name("readers".to_string())
+                .spawn(move || {
+                    reader_f(&accounts, &pubkeys, &exit);
+                })
+                .unwrap()
+        })
+        .collect();
 
     let num_new_keys = 1000;
     let new_accounts: Vec<_> = (0..num_new_keys)
         .map(|_| AccountSharedData::new(1, 0, AccountSharedData::default().owner()))
         .collect();
-    bencher.iter(|| {
-        for account in &new_accounts {
-            // Write to a different slot than the one being read from. Because
-            // there's a new account pubkey being written to every time, will
-            // compete for the accounts index lock on every store
-            accounts.store_slow_uncached(slot + 1, &solana_sdk::pubkey::new_rand(), account);
-        }
-    })
+    c.bench_function(bench_name, |bencher| {
+        bencher.iter(|| {
+            for account in &new_accounts {
+                // Write to a different slot than the one being read from. Because
+                // there's a new account pubkey being written to every time, will
+                // compete for the accounts index lock on every store
+                accounts.store_slow_uncached(slot + 1, &solana_sdk::pubkey::new_rand(), account);
+            }
+        });
+    });
+    stop_contending_threads(&exit, readers);
 }
 
-#[bench]
-#[ignore]
-fn bench_concurrent_read_write(bencher: &mut Bencher) {
+fn bench_concurrent_read_write(c: &mut Criterion) {
     store_accounts_with_possible_contention(
         "concurrent_read_write",
-        bencher,
-        |accounts, pubkeys| {
+        c,
+        |accounts, pubkeys, exit| {
             let mut rng = rand::thread_rng();
-            loop {
+            while !exit.load(Ordering::Relaxed) {
                 let i = rng.gen_range(0, pubkeys.len());
-                test::black_box(
+                black_box(
                     accounts
                         .load_without_fixed_root(&Ancestors::default(), &pubkeys[i])
                         .unwrap(),
@@ -257,70 +276,88 @@ fn bench_concurrent_read_write(bencher: &mut Bencher) {
     )
 }
 
-#[bench]
-#[ignore]
-fn bench_concurrent_scan_write(bencher: &mut Bencher) {
-    store_accounts_with_possible_contention("concurrent_scan_write", bencher, |accounts, _| loop {
-        test::black_box(
-            accounts
-                .load_by_program(
-                    &Ancestors::default(),
-                    0,
-                    AccountSharedData::default().owner(),
-                )
-                .unwrap(),
-        );
+fn bench_concurrent_scan_write(c: &mut Criterion) {
+    store_accounts_with_possible_contention("concurrent_scan_write", c, |accounts, _, exit| {
+        while !exit.load(Ordering::Relaxed) {
+            black_box(
+                accounts
+                    .load_by_program(
+                        &Ancestors::default(),
+                        0,
+                        AccountSharedData::default().owner(),
+                        &ScanConfig::default(),
+                    )
+                    .unwrap(),
+            );
+        }
     })
 }
 
-#[bench]
-#[ignore]
-fn bench_dashmap_single_reader_with_n_writers(bencher: &mut Bencher) {
+fn bench_dashmap_single_reader_with_n_writers(c: &mut Criterion) {
     let num_readers = 5;
     let num_keys = 10000;
     let map = Arc::new(DashMap::new());
     for i in 0..num_keys {
         map.insert(i, i);
     }
-    for _ in 0..num_readers {
-        let map = map.clone();
-        Builder::new()
-            .name("readers".to_string())
-            .spawn(move || loop {
-                test::black_box(map.entry(5).or_insert(2));
-            })
-            .unwrap();
-    }
-    bencher.iter(|| {
-        for _ in 0..num_keys {
-            test::black_box(map.get(&5).unwrap().value());
-        }
-    })
+    let exit = Arc::new(AtomicBool::new(false));
+    let writers: Vec<_> = (0..num_readers)
+        .map(|_| {
+            let map = map.clone();
+            let exit = exit.clone();
+            Builder::new()
+                .name("readers".to_string())
+                .spawn(move || {
+                    while !exit.load(Ordering::Relaxed) {
+                        black_box(map.entry(5).or_insert(2));
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    c.bench_function("bench_dashmap_single_reader_with_n_writers", |bencher| {
+        bencher.iter(|| {
+            for _ in 0..num_keys {
+                black_box(map.get(&5).unwrap().value());
+            }
+        });
+    });
+    stop_contending_threads(&exit, writers);
 }
 
-#[bench]
-#[ignore]
-fn bench_rwlock_hashmap_single_reader_with_n_writers(bencher: &mut Bencher) {
+fn bench_rwlock_hashmap_single_reader_with_n_writers(c: &mut Criterion) {
     let num_readers = 5;
     let num_keys = 10000;
     let map = Arc::new(RwLock::new(HashMap::new()));
     for i in 0..num_keys {
         map.write().unwrap().insert(i, i);
     }
-    for _ in 0..num_readers {
-        let map = map.clone();
-        Builder::new()
-            .name("readers".to_string())
-            .spawn(move || loop {
-                test::black_box(map.write().unwrap().get(&5));
-            })
-            .unwrap();
-    }
-    bencher.iter(|| {
-        for _ in 0..num_keys {
-            test::black_box(map.read().unwrap().get(&5));
-        }
-    })
+    let exit = Arc::new(AtomicBool::new(false));
+    let writers: Vec<_> = (0..num_readers)
+        .map(|_| {
+            let map = map.clone();
+            let exit = exit.clone();
+            Builder::new()
+                .name("readers".to_string())
+                .spawn(move || {
+                    while !exit.load(Ordering::Relaxed) {
+                        black_box(map.write().unwrap().get(&5));
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    c.bench_function(
+        "bench_rwlock_hashmap_single_reader_with_n_writers",
+        |bencher| {
+            bencher.iter(|| {
+                for _ in 0..num_keys {
+                    black_box(map.read().unwrap().get(&5));
+                }
+            });
+        },
+    );
+    stop_contending_threads(&exit, writers);
 }
 
 fn setup_bench_dashmap_iter() -> (Arc<Accounts>, DashMap<Pubkey, (AccountSharedData, Hash)>) {
@@ -352,36 +389,37 @@ fn setup_bench_dashmap_iter() -> (Arc<Accounts>, DashMap<Pubkey, (AccountSharedD
     (accounts, dashmap)
 }
 
-#[bench]
-fn bench_dashmap_par_iter(bencher: &mut Bencher) {
+fn bench_dashmap_par_iter(c: &mut Criterion) {
     let (accounts, dashmap) = setup_bench_dashmap_iter();
 
-    bencher.iter(|| {
-        test::black_box(accounts.accounts_db.thread_pool.install(|| {
-            dashmap
-                .par_iter()
-                .map(|cached_account| (*cached_account.key(), cached_account.value().1))
-                .collect::<Vec<(Pubkey, Hash)>>()
-        }));
+    c.bench_function("bench_dashmap_par_iter", |bencher| {
+        bencher.iter(|| {
+            black_box(accounts.accounts_db.thread_pool.install(|| {
+                dashmap
+                    .par_iter()
+                    .map(|cached_account| (*cached_account.key(), cached_account.value().1))
+                    .collect::<Vec<(Pubkey, Hash)>>()
+            }));
+        });
     });
 }
 
-#[bench]
-fn bench_dashmap_iter(bencher: &mut Bencher) {
+fn bench_dashmap_iter(c: &mut Criterion) {
     let (_accounts, dashmap) = setup_bench_dashmap_iter();
 
-    bencher.iter(|| {
-        test::black_box(
-            dashmap
-                .iter()
-                .map(|cached_account| (*cached_account.key(), cached_account.value().1))
-                .collect::<Vec<(Pubkey, Hash)>>(),
-        );
+    c.bench_function("bench_dashmap_iter", |bencher| {
+        bencher.iter(|| {
+            black_box(
+                dashmap
+                    .iter()
+                    .map(|cached_account| (*cached_account.key(), cached_account.value().1))
+                    .collect::<Vec<(Pubkey, Hash)>>(),
+            );
+        });
     });
 }
 
-#[bench]
-fn bench_load_largest_accounts(b: &mut Bencher) {
+fn bench_load_largest_accounts(c: &mut Criterion) {
     let accounts = Accounts::new_with_config_for_benches(
         Vec::new(),
         &ClusterType::Development,
@@ -398,13 +436,35 @@ fn bench_load_largest_accounts(b: &mut Bencher) {
     }
     let ancestors = Ancestors::from(vec![0]);
     let bank_id = 0;
-    b.iter(|| {
-        accounts.load_largest_accounts(
-            &ancestors,
-            bank_id,
-            20,
-            &HashSet::new(),
-            AccountAddressFilter::Exclude,
-        )
+    c.bench_function("bench_load_largest_accounts", |bencher| {
+        bencher.iter(|| {
+            accounts.load_largest_accounts(
+                &ancestors,
+                bank_id,
+                20,
+                &HashSet::new(),
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default(),
+            )
+        });
     });
 }
+
+criterion_group!(
+    benches,
+    test_accounts_create,
+    test_accounts_squash,
+    test_accounts_hash_bank_hash,
+    test_update_accounts_hash,
+    test_accounts_delta_hash,
+    bench_delete_dependencies,
+    bench_dashmap_par_iter,
+    bench_dashmap_iter,
+    bench_load_largest_accounts,
+    bench_concurrent_read_write,
+    bench_concurrent_scan_write,
+    bench_dashmap_single_reader_with_n_writers,
+    bench_rwlock_hashmap_single_reader_with_n_writers
+);
+
+criterion_main!(benches);
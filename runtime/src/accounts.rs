@@ -4,7 +4,7 @@ use crate::{
         LoadHint, LoadedAccount, ScanStorageResult, ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS,
         ACCOUNTS_DB_CONFIG_FOR_TESTING,
     },
-    accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
+    accounts_index::{AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult},
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::Ancestors,
     bank::{
@@ -26,6 +26,7 @@ use solana_sdk::{
     account_utils::StateMut,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::{BankId, Slot, INITIAL_RENT_EPOCH},
+    compute_budget::ComputeBudget,
     feature_set::{self, FeatureSet},
     genesis_config::ClusterType,
     hash::Hash,
@@ -103,6 +104,9 @@ pub struct Accounts {
 pub type TransactionAccounts = Vec<(Pubkey, AccountSharedData)>;
 pub type TransactionRent = u64;
 pub type TransactionProgramIndices = Vec<Vec<usize>>;
+/// Accounts loaded for the transactions in place of those stored, such as the accounts written
+/// by the previous transactions of a simulated bundle
+pub type AccountOverrides = HashMap<Pubkey, AccountSharedData>;
 #[derive(PartialEq, Debug, Clone)]
 pub struct LoadedTransaction {
     pub accounts: TransactionAccounts,
@@ -118,6 +122,43 @@ pub enum AccountAddressFilter {
     Include, // only include addresses matching the filter
 }
 
+/// Running total of the data size of the accounts loaded by a transaction
+struct LoadedAccountsDataSize {
+    limit: Option<usize>,
+    size: usize,
+    counted: HashSet<Pubkey>,
+}
+
+impl LoadedAccountsDataSize {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            size: 0,
+            counted: HashSet::new(),
+        }
+    }
+
+    /// Adds the data size of `account`, counting each account once even if it is loaded for
+    /// several instructions, and fails as soon as the total crosses the limit
+    fn add(
+        &mut self,
+        key: &Pubkey,
+        account: &AccountSharedData,
+        error_counters: &mut ErrorCounters,
+    ) -> Result<()> {
+        if let Some(limit) = self.limit {
+            if self.counted.insert(*key) {
+                self.size = self.size.saturating_add(account.data().len());
+                if self.size > limit {
+                    error_counters.max_loaded_accounts_data_size_exceeded += 1;
+                    return Err(TransactionError::MaxLoadedAccountsDataSizeExceeded);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Accounts {
     pub fn default_for_tests() -> Self {
         Self {
@@ -219,6 +260,7 @@ impl Accounts {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn load_transaction(
         &self,
         ancestors: &Ancestors,
@@ -227,6 +269,7 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        account_overrides: Option<&AccountOverrides>,
     ) -> Result<LoadedTransaction> {
         // Copy all the accounts
         let message = tx.message();
@@ -244,6 +287,17 @@ impl Accounts {
             let rent_for_sysvars = feature_set.is_active(&feature_set::rent_for_sysvars::id());
             let demote_program_write_locks =
                 feature_set.is_active(&feature_set::demote_program_write_locks::id());
+            let mut loaded_accounts_data_size = LoadedAccountsDataSize::new(
+                feature_set
+                    .is_active(&feature_set::cap_transaction_accounts_data_size::id())
+                    .then(|| {
+                        let mut compute_budget = ComputeBudget::new();
+                        // Malformed compute budget requests fail the transaction during
+                        // execution, so only the limit matters here
+                        let _ = compute_budget.process_message(message, feature_set);
+                        compute_budget.loaded_accounts_data_size_limit
+                    }),
+            );
 
             for (i, key) in message.account_keys_iter().enumerate() {
                 let account = if !message.is_non_loader_key(i) {
@@ -262,23 +316,30 @@ impl Accounts {
                             demote_program_write_locks,
                         )
                     } else {
-                        let (account, rent) = self
-                            .accounts_db
-                            .load_with_fixed_root(ancestors, key)
-                            .map(|(mut account, _)| {
-                                if message.is_writable(i, demote_program_write_locks) {
-                                    let rent_due = rent_collector.collect_from_existing_account(
-                                        key,
-                                        &mut account,
-                                        rent_for_sysvars,
-                                        self.accounts_db.filler_account_suffix.as_ref(),
-                                    );
-                                    (account, rent_due)
-                                } else {
-                                    (account, 0)
-                                }
-                            })
-                            .unwrap_or_default();
+                        let account_override =
+                            account_overrides.and_then(|overrides| overrides.get(key));
+                        let (account, rent) = match account_override {
+                            Some(account) => Some(account.clone()),
+                            None => self
+                                .accounts_db
+                                .load_with_fixed_root(ancestors, key)
+                                .map(|(account, _)| account),
+                        }
+                        .map(|mut account| {
+                            if message.is_writable(i, demote_program_write_locks) {
+                                let rent_due = rent_collector.collect_from_existing_account(
+                                    key,
+                                    &mut account,
+                                    rent_for_sysvars,
+                                    self.accounts_db.filler_account_suffix.as_ref(),
+                                );
+                                (account, rent_due)
+                            } else {
+                                (account, 0)
+                            }
+                        })
+                        .unwrap_or_default();
+                        loaded_accounts_data_size.add(key, &account, error_counters)?;
 
                         if bpf_loader_upgradeable::check_id(account.owner()) {
                             if demote_program_write_locks
@@ -299,6 +360,11 @@ impl Accounts {
                                         .accounts_db
                                         .load_with_fixed_root(ancestors, &programdata_address)
                                     {
+                                        loaded_accounts_data_size.add(
+                                            &programdata_address,
+                                            &programdata_account,
+                                            error_counters,
+                                        )?;
                                         account_deps
                                             .push((programdata_address, programdata_account));
                                     } else {
@@ -372,6 +438,7 @@ impl Accounts {
                             &mut accounts,
                             instruction.program_id_index as usize,
                             error_counters,
+                            &mut loaded_accounts_data_size,
                         )
                     })
                     .collect::<Result<Vec<Vec<usize>>>>()?;
@@ -394,6 +461,7 @@ impl Accounts {
         accounts: &mut Vec<(Pubkey, AccountSharedData)>,
         mut program_account_index: usize,
         error_counters: &mut ErrorCounters,
+        loaded_accounts_data_size: &mut LoadedAccountsDataSize,
     ) -> Result<Vec<usize>> {
         let mut account_indices = Vec::new();
         let mut program_id = accounts[program_account_index].0;
@@ -410,6 +478,7 @@ impl Accounts {
                 .load_with_fixed_root(ancestors, &program_id)
             {
                 Some((program_account, _)) => {
+                    loaded_accounts_data_size.add(&program_id, &program_account, error_counters)?;
                     let account_index = accounts.len();
                     accounts.push((program_id, program_account));
                     account_index
@@ -439,6 +508,11 @@ impl Accounts {
                         .load_with_fixed_root(ancestors, &programdata_address)
                     {
                         Some((programdata_account, _)) => {
+                            loaded_accounts_data_size.add(
+                                &programdata_address,
+                                &programdata_account,
+                                error_counters,
+                            )?;
                             let account_index = accounts.len();
                             accounts.push((programdata_address, programdata_account));
                             account_index
@@ -460,6 +534,7 @@ impl Accounts {
         Ok(account_indices)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn load_accounts(
         &self,
         ancestors: &Ancestors,
@@ -469,6 +544,7 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        account_overrides: Option<&AccountOverrides>,
     ) -> Vec<TransactionLoadResult> {
         txs.iter()
             .zip(lock_results)
@@ -482,7 +558,11 @@ impl Accounts {
                             hash_queue.get_lamports_per_signature(tx.message().recent_blockhash())
                         });
                     let fee = if let Some(lamports_per_signature) = lamports_per_signature {
-                        Bank::calculate_fee(tx.message(), lamports_per_signature)
+                        Bank::calculate_fee_with_feature_set(
+                            tx.message(),
+                            lamports_per_signature,
+                            feature_set,
+                        )
                     } else {
                         return (Err(TransactionError::BlockhashNotFound), None);
                     };
@@ -494,6 +574,7 @@ impl Accounts {
                         error_counters,
                         rent_collector,
                         feature_set,
+                        account_overrides,
                     ) {
                         Ok(loaded_transaction) => loaded_transaction,
                         Err(e) => return (Err(e), None),
@@ -635,6 +716,7 @@ impl Accounts {
         num: usize,
         filter_by_address: &HashSet<Pubkey>,
         filter: AccountAddressFilter,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, u64)>> {
         if num == 0 {
             return Ok(vec![]);
@@ -667,6 +749,7 @@ impl Accounts {
                     collector.push(Reverse((account.lamports(), *pubkey)));
                 }
             },
+            config,
         )?;
         Ok(account_balances
             .into_sorted_vec()
@@ -675,6 +758,131 @@ impl Accounts {
             .collect())
     }
 
+    /// Returns the `num` largest accounts as of `ancestors`, from the largest accounts tracked
+    /// as of the latest root and the accounts stored in the slots of `ancestors` since. Returns
+    /// `None` if the tracked accounts were never rebuilt, or may leave some of them out.
+    pub fn tracked_largest_accounts(
+        &self,
+        ancestors: &Ancestors,
+        num: usize,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: AccountAddressFilter,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        self.update_tracked_largest_accounts();
+        let root = self.accounts_db.largest_accounts.read().unwrap().root()?;
+        // The root is scanned again, as it may still be stored to if it has no child yet
+        let mut slots: Vec<_> = ancestors
+            .keys()
+            .into_iter()
+            .filter(|slot| *slot >= root)
+            .collect();
+        slots.sort_unstable();
+        let mut unrooted = HashMap::new();
+        for slot in slots {
+            unrooted.extend(self.scan_slot_balances(slot));
+        }
+        self.accounts_db.largest_accounts.read().unwrap().largest(
+            num,
+            &unrooted,
+            filter_by_address,
+            &filter,
+        )
+    }
+
+    /// Accounts for the slots rooted since the tracked largest accounts were last updated,
+    /// reloading the rooted balances of the tracked accounts and of those stored in these slots
+    fn update_tracked_largest_accounts(&self) {
+        let _update_lock = self
+            .accounts_db
+            .largest_accounts_update_lock
+            .lock()
+            .unwrap();
+        let (pending_roots, tracked) = {
+            let mut largest_accounts = self.accounts_db.largest_accounts.write().unwrap();
+            (
+                largest_accounts.take_pending_roots(),
+                largest_accounts.tracked(),
+            )
+        };
+        let root = match pending_roots.iter().max() {
+            Some(root) => *root,
+            None => return,
+        };
+        // Loading the latest rooted balances covers the accounts cleaned out of these slots since
+        let mut pubkeys: HashSet<_> = tracked.into_iter().collect();
+        for slot in pending_roots {
+            pubkeys.extend(self.scan_slot(slot, |account| Some(*account.pubkey())));
+        }
+        let ancestors = Ancestors::default();
+        let balances: Vec<_> = pubkeys
+            .into_iter()
+            .map(|pubkey| {
+                let lamports = self
+                    .load_with_fixed_root(&ancestors, &pubkey)
+                    .map(|(account, _slot)| account.lamports())
+                    .unwrap_or_default();
+                (pubkey, lamports)
+            })
+            .collect();
+        self.accounts_db
+            .largest_accounts
+            .write()
+            .unwrap()
+            .update(root, balances);
+    }
+
+    /// Rebuilds the tracked largest accounts with a scan of all the accounts as of `ancestors`,
+    /// those of the rooted slot `root`. Does nothing if they were already updated up to `root`.
+    pub fn rebuild_tracked_largest_accounts(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        root: Slot,
+        config: &ScanConfig,
+    ) -> ScanResult<()> {
+        let _update_lock = self
+            .accounts_db
+            .largest_accounts_update_lock
+            .lock()
+            .unwrap();
+        let capacity = {
+            let mut largest_accounts = self.accounts_db.largest_accounts.write().unwrap();
+            if largest_accounts
+                .root()
+                .map_or(false, |tracked_root| tracked_root >= root)
+            {
+                return Ok(());
+            }
+            largest_accounts.begin_rebuild();
+            largest_accounts.capacity()
+        };
+        let largest = self.load_largest_accounts(
+            ancestors,
+            bank_id,
+            capacity,
+            &HashSet::new(),
+            AccountAddressFilter::Exclude,
+            config,
+        );
+        let mut largest_accounts = self.accounts_db.largest_accounts.write().unwrap();
+        match largest {
+            Ok(largest) => {
+                largest_accounts.finish_rebuild(root, largest);
+                Ok(())
+            }
+            Err(err) => {
+                largest_accounts.abort_rebuild();
+                Err(err)
+            }
+        }
+    }
+
+    fn scan_slot_balances(&self, slot: Slot) -> Vec<(Pubkey, u64)> {
+        self.scan_slot(slot, |account| {
+            Some((*account.pubkey(), account.lamports()))
+        })
+    }
+
     pub fn calculate_capitalization(
         &self,
         ancestors: &Ancestors,
@@ -744,6 +952,7 @@ impl Accounts {
         ancestors: &Ancestors,
         bank_id: BankId,
         program_id: &Pubkey,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.accounts_db.scan_accounts(
             ancestors,
@@ -753,6 +962,7 @@ impl Accounts {
                     account.owner() == program_id
                 })
             },
+            config,
         )
     }
 
@@ -762,6 +972,7 @@ impl Accounts {
         bank_id: BankId,
         program_id: &Pubkey,
         filter: F,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.accounts_db.scan_accounts(
             ancestors,
@@ -771,6 +982,7 @@ impl Accounts {
                     account.owner() == program_id && filter(account)
                 })
             },
+            config,
         )
     }
 
@@ -780,6 +992,7 @@ impl Accounts {
         bank_id: BankId,
         index_key: &IndexKey,
         filter: F,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.accounts_db
             .index_scan_accounts(
@@ -791,6 +1004,7 @@ impl Accounts {
                         filter(account)
                     })
                 },
+                config,
             )
             .map(|result| result.0)
     }
@@ -814,6 +1028,7 @@ impl Accounts {
                     collector.push((*pubkey, account, slot))
                 }
             },
+            &ScanConfig::default(),
         )
     }
 
@@ -932,6 +1147,35 @@ impl Accounts {
             .collect()
     }
 
+    /// Like `lock_accounts`, except that it stops at the first transaction conflicting with an
+    /// earlier one of `txs` instead of failing it with `AccountInUse`, so that it queues behind
+    /// the earlier one. Returns the lock results of the transactions before the conflict
+    #[must_use]
+    pub fn lock_accounts_until_conflict<'a>(
+        &self,
+        txs: impl Iterator<Item = &'a SanitizedTransaction>,
+        demote_program_write_locks: bool,
+    ) -> Vec<Result<()>> {
+        let account_locks = &mut self.account_locks.lock().unwrap();
+        let mut batch_locks = AccountLocks::default();
+        let mut lock_results = vec![];
+        for tx in txs {
+            let keys = tx.get_account_locks(demote_program_write_locks);
+            if self
+                .lock_account(
+                    &mut batch_locks,
+                    keys.writable.clone(),
+                    keys.readonly.clone(),
+                )
+                .is_err()
+            {
+                break;
+            }
+            lock_results.push(self.lock_account(account_locks, keys.writable, keys.readonly));
+        }
+        lock_results
+    }
+
     /// Once accounts are unlocked, new transactions that modify that state can enter the pipeline
     #[allow(clippy::needless_collect)]
     pub fn unlock_accounts<'a>(
@@ -995,7 +1239,21 @@ impl Accounts {
 
     /// Add a slot to root.  Root slots cannot be purged
     pub fn add_root(&self, slot: Slot) {
-        self.accounts_db.add_root(slot)
+        self.accounts_db.add_root(slot);
+        if self
+            .accounts_db
+            .largest_accounts
+            .read()
+            .unwrap()
+            .is_tracking()
+        {
+            // The accounts stored in the slot are only accounted for once asked for
+            self.accounts_db
+                .largest_accounts
+                .write()
+                .unwrap()
+                .add_root(slot);
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1170,10 +1428,11 @@ mod tests {
     use crate::rent_collector::RentCollector;
     use solana_sdk::{
         account::{AccountSharedData, WritableAccount},
+        compute_budget::{self, ComputeBudgetInstruction},
         epoch_schedule::EpochSchedule,
         genesis_config::ClusterType,
         hash::Hash,
-        instruction::{CompiledInstruction, InstructionError},
+        instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
         message::Message,
         nonce, nonce_account,
         rent::Rent,
@@ -1229,6 +1488,7 @@ mod tests {
             error_counters,
             rent_collector,
             &FeatureSet::all_enabled(),
+            None,
         )
     }
 
@@ -1388,9 +1648,10 @@ mod tests {
             instructions,
         );
 
-        let fee = Bank::calculate_fee(
+        let fee = Bank::calculate_fee_with_feature_set(
             &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
             10,
+            &FeatureSet::default(),
         );
         assert_eq!(fee, 10);
 
@@ -1549,6 +1810,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_accounts_data_size_limit() {
+        let mut accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
+
+        let keypair = Keypair::new();
+        let key0 = keypair.pubkey();
+        let key1 = Pubkey::new(&[5u8; 32]);
+        let program_id = Pubkey::new(&[6u8; 32]);
+
+        accounts.push((key0, AccountSharedData::new(1, 0, &Pubkey::default())));
+        accounts.push((key1, AccountSharedData::new(2, 1024, &Pubkey::default())));
+        for key in [program_id, compute_budget::id()] {
+            let mut account = AccountSharedData::new(40, 1, &native_loader::id());
+            account.set_executable(true);
+            accounts.push((key, account));
+        }
+
+        let tx_with_limit = |limit| {
+            let instructions = [
+                ComputeBudgetInstruction::request_loaded_accounts_data_size_limit(limit),
+                Instruction::new_with_bytes(
+                    program_id,
+                    &[],
+                    vec![AccountMeta::new_readonly(key1, false)],
+                ),
+            ];
+            let message = Message::new(&instructions, Some(&key0));
+            Transaction::new(&[&keypair], message, Hash::default())
+        };
+
+        // The program accounts loaded for the instructions count towards the limit
+        let mut error_counters = ErrorCounters::default();
+        let loaded_accounts = load_accounts(tx_with_limit(1025), &accounts, &mut error_counters);
+        assert_eq!(error_counters.max_loaded_accounts_data_size_exceeded, 1);
+        assert_eq!(
+            loaded_accounts[0],
+            (
+                Err(TransactionError::MaxLoadedAccountsDataSizeExceeded),
+                None
+            )
+        );
+
+        let mut error_counters = ErrorCounters::default();
+        let loaded_accounts = load_accounts(tx_with_limit(1026), &accounts, &mut error_counters);
+        assert_eq!(error_counters.max_loaded_accounts_data_size_exceeded, 0);
+        assert!(loaded_accounts[0].0.is_ok());
+    }
+
     #[test]
     fn test_load_accounts_max_call_depth() {
         let mut accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
@@ -2039,6 +2348,7 @@ mod tests {
                 &mut vec![(keypair.pubkey(), account)],
                 0,
                 &mut error_counters,
+                &mut LoadedAccountsDataSize::new(None),
             ),
             Err(TransactionError::ProgramAccountNotFound)
         );
@@ -2479,6 +2789,7 @@ mod tests {
             &mut error_counters,
             &rent_collector,
             &FeatureSet::all_enabled(),
+            None,
         )
     }
 
@@ -2949,7 +3260,8 @@ mod tests {
                     bank_id,
                     0,
                     &HashSet::new(),
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![]
@@ -2961,7 +3273,8 @@ mod tests {
                     bank_id,
                     0,
                     &all_pubkeys,
-                    AccountAddressFilter::Include
+                    AccountAddressFilter::Include,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![]
@@ -2976,7 +3289,8 @@ mod tests {
                     bank_id,
                     1,
                     &HashSet::new(),
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42)]
@@ -2988,7 +3302,8 @@ mod tests {
                     bank_id,
                     2,
                     &HashSet::new(),
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42), (pubkey0, 42)]
@@ -3000,7 +3315,8 @@ mod tests {
                     bank_id,
                     3,
                     &HashSet::new(),
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42), (pubkey0, 42), (pubkey2, 41)]
@@ -3014,7 +3330,8 @@ mod tests {
                     bank_id,
                     6,
                     &HashSet::new(),
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42), (pubkey0, 42), (pubkey2, 41)]
@@ -3029,7 +3346,8 @@ mod tests {
                     bank_id,
                     1,
                     &exclude1,
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey0, 42)]
@@ -3041,7 +3359,8 @@ mod tests {
                     bank_id,
                     2,
                     &exclude1,
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey0, 42), (pubkey2, 41)]
@@ -3053,7 +3372,8 @@ mod tests {
                     bank_id,
                     3,
                     &exclude1,
-                    AccountAddressFilter::Exclude
+                    AccountAddressFilter::Exclude,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey0, 42), (pubkey2, 41)]
@@ -3068,7 +3388,8 @@ mod tests {
                     bank_id,
                     1,
                     &include1_2,
-                    AccountAddressFilter::Include
+                    AccountAddressFilter::Include,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42)]
@@ -3080,7 +3401,8 @@ mod tests {
                     bank_id,
                     2,
                     &include1_2,
-                    AccountAddressFilter::Include
+                    AccountAddressFilter::Include,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42), (pubkey2, 41)]
@@ -3092,7 +3414,8 @@ mod tests {
                     bank_id,
                     3,
                     &include1_2,
-                    AccountAddressFilter::Include
+                    AccountAddressFilter::Include,
+                    &ScanConfig::default(),
                 )
                 .unwrap(),
             vec![(pubkey1, 42), (pubkey2, 41)]
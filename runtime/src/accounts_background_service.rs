@@ -3,6 +3,7 @@
 // This can be expensive since we have to walk the append vecs being cleaned up.
 
 use crate::{
+    accounts_hash_audit::AccountsHashAudit,
     bank::{Bank, BankSlotDelta, DropCallback},
     bank_forks::BankForks,
     snapshot_config::SnapshotConfig,
@@ -311,6 +312,7 @@ impl AbsRequestSender {
 pub struct AbsRequestHandler {
     pub snapshot_request_handler: Option<SnapshotRequestHandler>,
     pub pruned_banks_receiver: DroppedSlotsReceiver,
+    pub accounts_hash_audit: Option<Arc<AccountsHashAudit>>,
 }
 
 impl AbsRequestHandler {
@@ -348,6 +350,13 @@ impl AbsRequestHandler {
 
         count
     }
+
+    /// Audits the accounts hash of the root `bank` if an audit was requested or is due
+    pub fn handle_accounts_hash_audit(&self, bank: &Bank) {
+        if let Some(accounts_hash_audit) = &self.accounts_hash_audit {
+            accounts_hash_audit.audit_if_due(bank);
+        }
+    }
 }
 
 pub struct AccountsBackgroundService {
@@ -447,6 +456,9 @@ impl AccountsBackgroundService {
                             return;
                         }
                     } else {
+                        // Audits run in between clean and shrink, which they must not race with
+                        request_handler.handle_accounts_hash_audit(&bank);
+
                         if accounts_db_caching_enabled {
                             bank.shrink_candidate_slots();
                         } else {
@@ -533,6 +545,7 @@ mod test {
         let request_handler = AbsRequestHandler {
             snapshot_request_handler: None,
             pruned_banks_receiver,
+            accounts_hash_audit: None,
         };
 
         // Store an account in slot 0
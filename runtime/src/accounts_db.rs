@@ -24,15 +24,19 @@ use crate::{
     accounts_hash::{AccountsHash, CalculateHashIntermediate, HashStats, PreviousPass},
     accounts_index::{
         AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexConfig,
-        AccountsIndexRootsStats, IndexKey, IndexValue, IsCached, RefCount, ScanResult, SlotList,
-        SlotSlice, ZeroLamport, ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS,
+        AccountsIndexRootsStats, IndexKey, IndexValue, IsCached, RefCount, ScanConfig, ScanResult,
+        SlotList, SlotSlice, ZeroLamport, ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS,
         ACCOUNTS_INDEX_CONFIG_FOR_TESTING,
     },
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::Ancestors,
-    append_vec::{AppendVec, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
+    append_vec::{
+        AppendVec, AppendVecFormat, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion,
+        MAXIMUM_APPEND_VEC_FILE_SIZE,
+    },
     cache_hash_data::CacheHashData,
     contains::Contains,
+    largest_accounts::LargestAccounts,
     pubkey_bins::PubkeyBinCalculator24,
     read_only_accounts_cache::ReadOnlyAccountsCache,
     rent_collector::RentCollector,
@@ -47,10 +51,16 @@ use dashmap::{
 use lazy_static::lazy_static;
 use log::*;
 use rand::{prelude::SliceRandom, thread_rng, Rng};
-use rayon::{prelude::*, ThreadPool};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use solana_measure::measure::Measure;
-use solana_rayon_threadlimit::get_thread_count;
+use solana_rayon_threadlimit::{
+    get_thread_count,
+    thread_pools::{
+        ManagedThreadPool, ACCOUNTS_CLEAN_THREAD_POOL, ACCOUNTS_DB_THREAD_POOL,
+        ACCOUNTS_HASH_THREAD_POOL,
+    },
+};
 use solana_sdk::genesis_config::GenesisConfig;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
@@ -74,22 +84,27 @@ use std::{
     str::FromStr,
     sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     sync::{Arc, Condvar, Mutex, MutexGuard, RwLock},
-    thread::Builder,
-    time::Instant,
+    thread::{sleep, Builder},
+    time::{Duration, Instant},
 };
 use tempfile::TempDir;
-
-#[cfg(test)]
-use std::{thread::sleep, time::Duration};
+use thiserror::Error;
 
 const PAGE_SIZE: u64 = 4 * 1024;
-const MAX_RECYCLE_STORES: usize = 1000;
 const STORE_META_OVERHEAD: usize = 256;
 const MAX_CACHE_SLOTS: usize = 200;
 const FLUSH_CACHE_RANDOM_THRESHOLD: usize = MAX_LOCKOUT_HISTORY;
 const SCAN_SLOT_PAR_ITER_THRESHOLD: usize = 4000;
+/// Failures to create a storage that don't quarantine the path, like running out of file
+/// descriptors, tolerated before giving up
+const MAX_TRANSIENT_STORE_CREATE_FAILURES: usize = 3;
+/// Delay before retrying to create a storage after a failure, doubled on each retry
+const STORE_CREATE_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// Longest delay between retries of the writes that can't go on without a new storage
+const MAX_STORE_CREATE_RETRY_DELAY: Duration = Duration::from_secs(10);
 
 pub const DEFAULT_FILE_SIZE: u64 = PAGE_SIZE * 1024;
+pub const DEFAULT_MAX_RECYCLE_STORES: usize = 1000;
 pub const DEFAULT_NUM_THREADS: u32 = 8;
 pub const DEFAULT_NUM_DIRS: u32 = 4;
 
@@ -130,12 +145,20 @@ pub const ACCOUNTS_DB_CONFIG_FOR_TESTING: AccountsDbConfig = AccountsDbConfig {
     accounts_hash_cache_path: None,
     filler_account_count: None,
     hash_calc_num_passes: None,
+    append_vec_format: None,
+    storages_in_memory: false,
+    file_size: None,
+    max_recycle_stores: None,
 };
 pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig {
     index: Some(ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS),
     accounts_hash_cache_path: None,
     filler_account_count: None,
     hash_calc_num_passes: None,
+    append_vec_format: None,
+    storages_in_memory: false,
+    file_size: None,
+    max_recycle_stores: None,
 };
 
 pub type BinnedHashData = Vec<Vec<CalculateHashIntermediate>>;
@@ -146,6 +169,19 @@ pub struct AccountsDbConfig {
     pub accounts_hash_cache_path: Option<PathBuf>,
     pub filler_account_count: Option<usize>,
     pub hash_calc_num_passes: Option<usize>,
+    /// Layout of newly created append vecs. Existing storages are read in whichever format they
+    /// were written and are migrated as they get shrunk or recycled.
+    pub append_vec_format: Option<AppendVecFormat>,
+    /// Keep account storages in anonymous memory instead of files under the accounts paths.
+    /// Only suitable for tests and benchmarks: the accounts are lost when the process exits
+    /// unless they're captured in a snapshot.
+    pub storages_in_memory: bool,
+    /// Size preallocated for newly created append vecs. Larger files mean fewer of them to create
+    /// and delete, at the cost of more disk space held by partially filled storages.
+    pub file_size: Option<u64>,
+    /// Number of dropped storages kept around to be reused as new append vecs, instead of deleting
+    /// their files and creating new ones
+    pub max_recycle_stores: Option<usize>,
 }
 
 struct FoundStoredAccount<'a> {
@@ -163,6 +199,20 @@ lazy_static! {
     // FROZEN_ACCOUNT_PANIC is used to signal local_cluster that an AccountsDb panic has occurred,
     // as |cargo test| cannot observe panics in other threads
     pub static ref FROZEN_ACCOUNT_PANIC: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+
+    // Calculates the accounts hash in the background, with as few threads as the cleaning by
+    // default
+    static ref ACCOUNTS_HASH_POOL: Arc<ManagedThreadPool> = ManagedThreadPool::new(
+        ACCOUNTS_HASH_THREAD_POOL,
+        quarter_thread_count(),
+        |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .thread_name(|i| format!("solana-accounts-hash-{}", i))
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+        }
+    );
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -212,6 +262,7 @@ pub struct ErrorCounters {
     pub invalid_program_for_execution: usize,
     pub not_allowed_during_cluster_maintenance: usize,
     pub invalid_writable_account: usize,
+    pub max_loaded_accounts_data_size_exceeded: usize,
 }
 
 #[derive(Default, Debug)]
@@ -414,6 +465,8 @@ pub enum LoadedAccountAccessor<'a> {
 }
 
 mod accountsdb_plugin_utils;
+mod storage_paths;
+use storage_paths::StoragePaths;
 
 impl<'a> LoadedAccountAccessor<'a> {
     fn check_and_get_loaded_account(&mut self) -> LoadedAccount {
@@ -604,6 +657,19 @@ pub enum BankHashVerificationError {
     MismatchedTotalLamports(u64, u64),
 }
 
+#[derive(Error, Debug)]
+pub enum CreateStoreError {
+    #[error("all accounts paths {0:?} are quarantined after IO failures")]
+    AllPathsQuarantined(Vec<PathBuf>),
+
+    #[error("failed to create an account storage in {path:?}: {err}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        err: IoError,
+    },
+}
+
 #[derive(Default)]
 struct CleanKeyTimings {
     collect_delta_keys_us: u64,
@@ -644,10 +710,52 @@ pub struct AccountStorageEntry {
 
 impl AccountStorageEntry {
     pub fn new(path: &Path, slot: Slot, id: usize, file_size: u64) -> Self {
+        Self::new_with_format(path, slot, id, file_size, AppendVecFormat::V1)
+    }
+
+    pub fn new_with_format(
+        path: &Path,
+        slot: Slot,
+        id: usize,
+        file_size: u64,
+        format: AppendVecFormat,
+    ) -> Self {
+        let tail = AppendVec::file_name(slot, id);
+        let path = Path::new(path).join(tail);
+        let accounts = AppendVec::new_with_format(&path, true, file_size as usize, format);
+        Self::new_from_append_vec(slot, id, accounts)
+    }
+
+    /// Like `new_with_format()`, but returns an error instead of panicking if the backing file
+    /// can't be created.
+    pub fn try_new_with_format(
+        path: &Path,
+        slot: Slot,
+        id: usize,
+        file_size: u64,
+        format: AppendVecFormat,
+    ) -> IoResult<Self> {
+        let tail = AppendVec::file_name(slot, id);
+        let path = Path::new(path).join(tail);
+        let accounts = AppendVec::try_new_with_format(&path, true, file_size as usize, format)?;
+        Ok(Self::new_from_append_vec(slot, id, accounts))
+    }
+
+    /// Create a storage backed by anonymous memory. `path` is only used to name the storage.
+    pub fn new_in_memory(
+        path: &Path,
+        slot: Slot,
+        id: usize,
+        file_size: u64,
+        format: AppendVecFormat,
+    ) -> Self {
         let tail = AppendVec::file_name(slot, id);
         let path = Path::new(path).join(tail);
-        let accounts = AppendVec::new(&path, true, file_size as usize);
+        let accounts = AppendVec::new_in_memory(&path, file_size as usize, format);
+        Self::new_from_append_vec(slot, id, accounts)
+    }
 
+    fn new_from_append_vec(slot: Slot, id: usize, accounts: AppendVec) -> Self {
         Self {
             id: AtomicUsize::new(id),
             slot: AtomicU64::new(slot),
@@ -769,7 +877,7 @@ impl AccountStorageEntry {
     }
 
     pub fn all_accounts(&self) -> Vec<StoredAccountMeta> {
-        self.accounts.accounts(0)
+        self.accounts.accounts(self.accounts.first_account_offset())
     }
 
     fn remove_account(&self, num_bytes: usize, reset_accounts: bool) -> usize {
@@ -810,6 +918,10 @@ impl AccountStorageEntry {
     pub fn get_path(&self) -> PathBuf {
         self.accounts.get_path()
     }
+
+    pub fn is_in_memory(&self) -> bool {
+        self.accounts.is_in_memory()
+    }
 }
 
 pub fn get_temp_accounts_paths(count: u32) -> IoResult<(Vec<TempDir>, Vec<PathBuf>)> {
@@ -1002,13 +1114,16 @@ pub struct AccountsDb {
     /// Starting file size of appendvecs
     file_size: u64,
 
+    /// Max number of dropped storages held in `recycle_stores`, beyond which their files are deleted
+    max_recycle_stores: usize,
+
     /// Accounts that will cause a panic! if data modified or lamports decrease
     frozen_accounts: HashMap<Pubkey, FrozenAccountInfo>,
 
     /// Thread pool used for par_iter
-    pub thread_pool: ThreadPool,
+    pub thread_pool: Arc<ManagedThreadPool>,
 
-    pub thread_pool_clean: ThreadPool,
+    pub thread_pool_clean: Arc<ManagedThreadPool>,
 
     /// Number of append vecs to create to maximize parallelism when scanning
     /// the accounts
@@ -1071,6 +1186,22 @@ pub struct AccountsDb {
     // lower passes = faster total time, higher dynamic memory usage
     // passes=2 cuts dynamic memory usage in approximately half.
     pub num_hash_scan_passes: Option<usize>,
+
+    /// Layout used when creating new append vecs
+    append_vec_format: AppendVecFormat,
+
+    /// Free space balancing, failure quarantine and metrics for `paths` and `shrink_paths`
+    storage_paths: StoragePaths,
+
+    /// Create new storages in anonymous memory instead of files
+    storages_in_memory: bool,
+
+    /// Accounts with the most lamports as of the latest root
+    pub largest_accounts: RwLock<LargestAccounts>,
+
+    /// Serializes the updates and rebuilds of `largest_accounts`, which load the accounts
+    /// without holding it
+    pub largest_accounts_update_lock: Mutex<()>,
 }
 
 #[derive(Debug, Default)]
@@ -1094,6 +1225,7 @@ struct AccountsStats {
     store_get_slot_store: AtomicU64,
     store_find_existing: AtomicU64,
     dropped_stores: AtomicU64,
+    expired_stores: AtomicU64,
     store_uncleaned_update: AtomicU64,
 }
 
@@ -1456,14 +1588,16 @@ fn quarter_thread_count() -> usize {
     std::cmp::max(2, num_cpus::get() / 4)
 }
 
-pub fn make_min_priority_thread_pool() -> ThreadPool {
+pub fn make_min_priority_thread_pool() -> Arc<ManagedThreadPool> {
     // Use lower thread count to reduce priority.
     let num_threads = quarter_thread_count();
-    rayon::ThreadPoolBuilder::new()
-        .thread_name(|i| format!("solana-cleanup-accounts-{}", i))
-        .num_threads(num_threads)
-        .build()
-        .unwrap()
+    ManagedThreadPool::new(ACCOUNTS_CLEAN_THREAD_POOL, num_threads, |num_threads| {
+        rayon::ThreadPoolBuilder::new()
+            .thread_name(|i| format!("solana-cleanup-accounts-{}", i))
+            .num_threads(num_threads)
+            .build()
+            .unwrap()
+    })
 }
 
 #[cfg(all(test, RUSTC_WITH_SPECIALIZATION))]
@@ -1486,7 +1620,7 @@ impl<'a> ReadableAccount for StoredAccountMeta<'a> {
         self.account_meta.lamports
     }
     fn data(&self) -> &[u8] {
-        self.data
+        &self.data
     }
     fn owner(&self) -> &Pubkey {
         &self.account_meta.owner
@@ -1571,11 +1705,18 @@ impl AccountsDb {
             shrink_paths: RwLock::new(None),
             temp_paths: None,
             file_size: DEFAULT_FILE_SIZE,
-            thread_pool: rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .thread_name(|i| format!("solana-db-accounts-{}", i))
-                .build()
-                .unwrap(),
+            max_recycle_stores: DEFAULT_MAX_RECYCLE_STORES,
+            thread_pool: ManagedThreadPool::new(
+                ACCOUNTS_DB_THREAD_POOL,
+                num_threads,
+                |num_threads| {
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(num_threads)
+                        .thread_name(|i| format!("solana-db-accounts-{}", i))
+                        .build()
+                        .unwrap()
+                },
+            ),
             thread_pool_clean: make_min_priority_thread_pool(),
             min_num_stores: num_threads,
             bank_hashes: RwLock::new(bank_hashes),
@@ -1600,6 +1741,11 @@ impl AccountsDb {
             filler_account_count: 0,
             filler_account_suffix: None,
             num_hash_scan_passes,
+            append_vec_format: AppendVecFormat::default(),
+            storage_paths: StoragePaths::default(),
+            storages_in_memory: false,
+            largest_accounts: RwLock::default(),
+            largest_accounts_update_lock: Mutex::default(),
         }
     }
 
@@ -1650,6 +1796,28 @@ impl AccountsDb {
         } else {
             None
         };
+        let append_vec_format = accounts_db_config
+            .as_ref()
+            .and_then(|cfg| cfg.append_vec_format)
+            .unwrap_or_default();
+        let storages_in_memory = accounts_db_config
+            .as_ref()
+            .map(|cfg| cfg.storages_in_memory)
+            .unwrap_or_default();
+        let file_size = accounts_db_config
+            .as_ref()
+            .and_then(|cfg| cfg.file_size)
+            .map(Self::page_align)
+            .unwrap_or(DEFAULT_FILE_SIZE);
+        assert!(
+            (1..=MAXIMUM_APPEND_VEC_FILE_SIZE as u64).contains(&file_size),
+            "invalid append vec file size {}",
+            file_size
+        );
+        let max_recycle_stores = accounts_db_config
+            .as_ref()
+            .and_then(|cfg| cfg.max_recycle_stores)
+            .unwrap_or(DEFAULT_MAX_RECYCLE_STORES);
         let paths_is_empty = paths.is_empty();
         let mut new = Self {
             paths,
@@ -1660,6 +1828,10 @@ impl AccountsDb {
             accounts_update_notifier,
             filler_account_count,
             filler_account_suffix,
+            append_vec_format,
+            storages_in_memory,
+            file_size,
+            max_recycle_stores,
             ..Self::default_with_accounts_index(
                 accounts_index,
                 accounts_hash_cache_path,
@@ -1713,12 +1885,27 @@ impl AccountsDb {
         }
     }
 
-    fn new_storage_entry(&self, slot: Slot, path: &Path, size: u64) -> AccountStorageEntry {
-        AccountStorageEntry::new(
+    fn try_new_storage_entry(
+        &self,
+        slot: Slot,
+        path: &Path,
+        size: u64,
+    ) -> IoResult<AccountStorageEntry> {
+        if self.storages_in_memory {
+            return Ok(AccountStorageEntry::new_in_memory(
+                path,
+                slot,
+                self.next_id.fetch_add(1, Ordering::Relaxed),
+                size,
+                self.append_vec_format,
+            ));
+        }
+        AccountStorageEntry::try_new_with_format(
             path,
             slot,
             self.next_id.fetch_add(1, Ordering::Relaxed),
             size,
+            self.append_vec_format,
         )
     }
 
@@ -1989,7 +2176,10 @@ impl AccountsDb {
         let dirty_stores_len = dirty_stores.len();
         let pubkeys = DashSet::new();
         for (_slot, store) in dirty_stores {
-            for account in store.accounts.accounts(0) {
+            for account in store
+                .accounts
+                .accounts(store.accounts.first_account_offset())
+            {
                 pubkeys.insert(account.meta.pubkey);
             }
         }
@@ -2603,6 +2793,18 @@ impl AccountsDb {
         alive_total
     }
 
+    /// Restore the references to the alive accounts unrefed for a shrink that isn't done
+    fn skip_shrink<'a>(&self, unrefed_pubkeys: impl IntoIterator<Item = &'a Pubkey>) {
+        self.shrink_stats
+            .skipped_shrink
+            .fetch_add(1, Ordering::Relaxed);
+        for pubkey in unrefed_pubkeys {
+            if let Some(locked_entry) = self.accounts_index.get_account_read_entry(pubkey) {
+                locked_entry.addref();
+            }
+        }
+    }
+
     fn do_shrink_slot_stores<'a, I>(&'a self, slot: Slot, stores: I) -> usize
     where
         I: Iterator<Item = &'a Arc<AccountStorageEntry>>,
@@ -2612,7 +2814,7 @@ impl AccountsDb {
         let mut original_bytes = 0;
         let mut num_stores = 0;
         for store in stores {
-            let mut start = 0;
+            let mut start = store.accounts.first_account_offset();
             original_bytes += store.total_bytes();
             let store_id = store.append_vec_id();
             while let Some((account, next)) = store.accounts.get_account(start) {
@@ -2688,14 +2890,7 @@ impl AccountsDb {
 
         // This shouldn't happen if alive_bytes/approx_stored_count are accurate
         if Self::should_not_shrink(aligned_total, original_bytes, num_stores) {
-            self.shrink_stats
-                .skipped_shrink
-                .fetch_add(1, Ordering::Relaxed);
-            for pubkey in unrefed_pubkeys {
-                if let Some(locked_entry) = self.accounts_index.get_account_read_entry(pubkey) {
-                    locked_entry.addref();
-                }
-            }
+            self.skip_shrink(unrefed_pubkeys);
             return 0;
         }
 
@@ -2738,15 +2933,21 @@ impl AccountsDb {
                 new_store
             } else {
                 let maybe_shrink_paths = self.shrink_paths.read().unwrap();
-                if let Some(ref shrink_paths) = *maybe_shrink_paths {
-                    self.create_and_insert_store_with_paths(
-                        slot,
-                        aligned_total,
-                        "shrink-w-path",
-                        shrink_paths,
-                    )
-                } else {
-                    self.create_and_insert_store(slot, aligned_total, "shrink")
+                let (paths, from) = match *maybe_shrink_paths {
+                    Some(ref shrink_paths) => (shrink_paths, "shrink-w-path"),
+                    None => (&self.paths, "shrink"),
+                };
+                match self.try_create_store(slot, aligned_total, from, paths) {
+                    Ok(store) => {
+                        self.insert_store(slot, store.clone());
+                        store
+                    }
+                    Err(err) => {
+                        // unlike storing new accounts, shrinking can wait for the paths to recover
+                        warn!("Unable to shrink slot {}: {}", slot, err);
+                        self.skip_shrink(unrefed_pubkeys);
+                        return 0;
+                    }
                 }
             };
             start.stop();
@@ -2794,7 +2995,7 @@ impl AccountsDb {
         recycle_stores_write_elapsed.stop();
 
         let mut drop_storage_entries_elapsed = Measure::start("drop_storage_entries_elapsed");
-        if recycle_stores.entry_count() < MAX_RECYCLE_STORES {
+        if recycle_stores.entry_count() < self.max_recycle_stores {
             recycle_stores.add_entries(dead_storages);
             drop(recycle_stores);
         } else {
@@ -3060,6 +3261,7 @@ impl AccountsDb {
         ancestors: &Ancestors,
         bank_id: BankId,
         scan_func: F,
+        config: &ScanConfig,
     ) -> ScanResult<A>
     where
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
@@ -3068,14 +3270,21 @@ impl AccountsDb {
         let mut collector = A::default();
 
         // This can error out if the slots being scanned over are aborted
-        self.accounts_index
-            .scan_accounts(ancestors, bank_id, |pubkey, (account_info, slot)| {
+        self.accounts_index.scan_accounts(
+            ancestors,
+            bank_id,
+            |pubkey, (account_info, slot)| {
                 let account_slot = self
                     .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
                     .get_loaded_account()
-                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                    .map(|loaded_account| {
+                        config.add_bytes_loaded(loaded_account.stored_size());
+                        (pubkey, loaded_account.take_account(), slot)
+                    });
                 scan_func(&mut collector, account_slot)
-            })?;
+            },
+            config,
+        )?;
 
         Ok(collector)
     }
@@ -3154,6 +3363,7 @@ impl AccountsDb {
         bank_id: BankId,
         index_key: IndexKey,
         scan_func: F,
+        config: &ScanConfig,
     ) -> ScanResult<(A, bool)>
     where
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
@@ -3167,7 +3377,7 @@ impl AccountsDb {
         if !self.account_indexes.include_key(key) {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
-            let scan_result = self.scan_accounts(ancestors, bank_id, scan_func)?;
+            let scan_result = self.scan_accounts(ancestors, bank_id, scan_func, config)?;
             return Ok((scan_result, used_index));
         }
 
@@ -3180,9 +3390,13 @@ impl AccountsDb {
                 let account_slot = self
                     .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
                     .get_loaded_account()
-                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                    .map(|loaded_account| {
+                        config.add_bytes_loaded(loaded_account.stored_size());
+                        (pubkey, loaded_account.take_account(), slot)
+                    });
                 scan_func(&mut collector, account_slot)
             },
+            config,
         )?;
         let used_index = true;
         Ok((collector, used_index))
@@ -3784,7 +3998,20 @@ impl AccountsDb {
                                 self.stats
                                     .create_store_count
                                     .fetch_add(1, Ordering::Relaxed);
-                                self.create_and_insert_store(slot, self.file_size, "store extra");
+                                match self.try_create_store(
+                                    slot,
+                                    self.file_size,
+                                    "store extra",
+                                    &self.paths,
+                                ) {
+                                    Ok(store) => self.insert_store(slot, store),
+                                    // the extra store only spreads the scans of the slot over
+                                    // more threads, the available store is used meanwhile
+                                    Err(err) => warn!(
+                                        "Unable to create an extra store for slot {}: {}",
+                                        slot, err
+                                    ),
+                                }
                             } else {
                                 self.stats
                                     .recycle_store_count
@@ -3847,19 +4074,43 @@ impl AccountsDb {
         false
     }
 
-    fn create_store(
+    /// Create a storage on one of `paths`, moving on to the other paths when one fails with an
+    /// error that would recur, and retrying after a delay when the failure is transient
+    fn try_create_store(
         &self,
         slot: Slot,
         size: u64,
         from: &str,
         paths: &[PathBuf],
-    ) -> Arc<AccountStorageEntry> {
-        let path_index = thread_rng().gen_range(0, paths.len());
-        let store = Arc::new(self.new_storage_entry(
-            slot,
-            Path::new(&paths[path_index]),
-            Self::page_align(size),
-        ));
+    ) -> Result<Arc<AccountStorageEntry>, CreateStoreError> {
+        let size = Self::page_align(size + self.append_vec_format.header_len() as u64);
+        let mut transient_failures = 0;
+        let mut retry_delay = STORE_CREATE_RETRY_DELAY;
+        let store = loop {
+            let path = self
+                .storage_paths
+                .select_path(paths, size)
+                .ok_or_else(|| CreateStoreError::AllPathsQuarantined(paths.to_vec()))?;
+            match self.try_new_storage_entry(slot, path, size) {
+                Ok(store) => {
+                    self.storage_paths.record_store_created(path, size);
+                    break Arc::new(store);
+                }
+                Err(err) => {
+                    if !self.storage_paths.record_create_failure(path, &err) {
+                        transient_failures += 1;
+                        if transient_failures >= MAX_TRANSIENT_STORE_CREATE_FAILURES {
+                            return Err(CreateStoreError::Io {
+                                path: path.clone(),
+                                err,
+                            });
+                        }
+                        sleep(retry_delay);
+                        retry_delay *= 2;
+                    }
+                }
+            }
+        };
 
         assert!(
             store.append_vec_id() != CACHE_VIRTUAL_STORAGE_ID,
@@ -3875,7 +4126,38 @@ impl AccountsDb {
             store.accounts.get_path()
         );
 
-        store
+        Ok(store)
+    }
+
+    /// Like `try_create_store()`, for the writes that can't go on without the storage: rather than
+    /// failing, waits for a path to come out of quarantine or for the transient failures to clear
+    fn create_store(
+        &self,
+        slot: Slot,
+        size: u64,
+        from: &str,
+        paths: &[PathBuf],
+    ) -> Arc<AccountStorageEntry> {
+        let mut retry_delay = STORE_CREATE_RETRY_DELAY;
+        loop {
+            match self.try_create_store(slot, size, from, paths) {
+                Ok(store) => return store,
+                Err(err) => {
+                    error!(
+                        "Unable to create account storage for slot {} ({}): {}. Retrying in {:?}",
+                        slot, from, err, retry_delay
+                    );
+                    datapoint_error!(
+                        "accounts_db-create_store_failed",
+                        ("slot", slot, i64),
+                        ("from", from.to_string(), String),
+                        ("error", err.to_string(), String),
+                    );
+                    sleep(retry_delay);
+                    retry_delay = (retry_delay * 2).min(MAX_STORE_CREATE_RETRY_DELAY);
+                }
+            }
+        }
     }
 
     fn create_and_insert_store(
@@ -3968,7 +4250,7 @@ impl AccountsDb {
         for slot_entries in slot_stores {
             let entry = slot_entries.read().unwrap();
             for (_store_id, stores) in entry.iter() {
-                if recycle_stores.entry_count() > MAX_RECYCLE_STORES {
+                if recycle_stores.entry_count() > self.max_recycle_stores {
                     let dropped_count = total_removed_storage_entries - recycled_count;
                     self.stats
                         .dropped_stores
@@ -4321,7 +4603,7 @@ impl AccountsDb {
             &account.account_meta.owner,
             account.account_meta.executable,
             account.account_meta.rent_epoch,
-            account.data,
+            &account.data,
             &account.meta.pubkey,
         )
     }
@@ -4490,6 +4772,10 @@ impl AccountsDb {
         let recycle_stores = self.recycle_stores.write().unwrap().expire_old_entries();
         recycle_stores_write_elapsed.stop();
 
+        self.stats
+            .expired_stores
+            .fetch_add(recycle_stores.len() as u64, Ordering::Relaxed);
+
         let mut drop_storage_entries_elapsed = Measure::start("drop_storage_entries_elapsed");
         drop(recycle_stores);
         drop_storage_entries_elapsed.stop();
@@ -4961,6 +5247,7 @@ impl AccountsDb {
         let mut oldest_slot = std::u64::MAX;
         let mut total_bytes = 0;
         let mut total_alive_bytes = 0;
+        let mut path_usage: HashMap<PathBuf, (usize, u64)> = HashMap::new();
         for iter_item in self.storage.0.iter() {
             let slot = iter_item.key();
             let slot_stores = iter_item.value().read().unwrap();
@@ -4985,8 +5272,14 @@ impl AccountsDb {
             for store in slot_stores.values() {
                 total_alive_bytes += Self::page_align(store.alive_bytes() as u64);
                 total_bytes += store.total_bytes();
+                if let Some(dir) = store.get_path().parent() {
+                    let usage = path_usage.entry(dir.to_path_buf()).or_default();
+                    usage.0 += 1;
+                    usage.1 += store.total_bytes();
+                }
             }
         }
+        self.storage_paths.report(&path_usage);
         info!("total_stores: {}, newest_slot: {}, oldest_slot: {}, max_slot: {} (num={}), min_slot: {} (num={})",
               total_count, newest_slot, oldest_slot, max_slot, max, min_slot, min);
 
@@ -5194,7 +5487,9 @@ impl AccountsDb {
         let mut progress = Vec::with_capacity(len);
         let mut current = Vec::with_capacity(len);
         for storage in storages {
-            let accounts = storage.accounts.accounts(0);
+            let accounts = storage
+                .accounts
+                .accounts(storage.accounts.first_account_offset());
             let mut iterator: std::vec::IntoIter<StoredAccountMeta<'_>> = accounts.into_iter();
             if let Some(item) = iterator
                 .next()
@@ -5453,25 +5748,27 @@ impl AccountsDb {
                 ..HashStats::default()
             };
 
-            let thread_pool = if is_startup {
-                None
-            } else {
-                Some(&self.thread_pool_clean)
+            let calculate_accounts_hash = || {
+                Self::calculate_accounts_hash_without_index(
+                    &self.accounts_hash_cache_path,
+                    &storages,
+                    None,
+                    timings,
+                    check_hash,
+                    accounts_cache_and_ancestors,
+                    if self.filler_account_count > 0 {
+                        self.filler_account_suffix.as_ref()
+                    } else {
+                        None
+                    },
+                    self.num_hash_scan_passes,
+                )
             };
-            Self::calculate_accounts_hash_without_index(
-                &self.accounts_hash_cache_path,
-                &storages,
-                thread_pool,
-                timings,
-                check_hash,
-                accounts_cache_and_ancestors,
-                if self.filler_account_count > 0 {
-                    self.filler_account_suffix.as_ref()
-                } else {
-                    None
-                },
-                self.num_hash_scan_passes,
-            )
+            if is_startup {
+                calculate_accounts_hash()
+            } else {
+                ACCOUNTS_HASH_POOL.install(calculate_accounts_hash)
+            }
         } else {
             self.calculate_accounts_hash(slot, ancestors, check_hash)
         }
@@ -5663,7 +5960,7 @@ impl AccountsDb {
     pub fn calculate_accounts_hash_without_index(
         accounts_hash_cache_path: &Path,
         storages: &SortedStorages,
-        thread_pool: Option<&ThreadPool>,
+        thread_pool: Option<&ManagedThreadPool>,
         mut stats: HashStats,
         check_hash: bool,
         accounts_cache_and_ancestors: Option<(
@@ -5772,6 +6069,57 @@ impl AccountsDb {
         }
     }
 
+    /// Recalculates the accounts hash of `slot` from the account storages, checking the hash of
+    /// each account, and verifies it against `total_lamports` and against the accounts hash
+    /// recorded for the slot, if any.
+    /// Must not run concurrently with clean and shrink.
+    pub fn verify_accounts_hash(
+        &self,
+        slot: Slot,
+        ancestors: &Ancestors,
+        total_lamports: u64,
+    ) -> Result<Hash, BankHashVerificationError> {
+        use BankHashVerificationError::*;
+
+        let check_hash = true;
+        let can_cached_slot_be_unflushed = true;
+        let is_startup = false;
+        let (calculated_hash, calculated_lamports) = self.calculate_accounts_hash_helper(
+            false,
+            slot,
+            ancestors,
+            check_hash,
+            can_cached_slot_be_unflushed,
+            None,
+            is_startup,
+        )?;
+        if calculated_lamports != total_lamports {
+            warn!(
+                "Mismatched total lamports: {} calculated: {}",
+                total_lamports, calculated_lamports
+            );
+            return Err(MismatchedTotalLamports(calculated_lamports, total_lamports));
+        }
+
+        let recorded_hash = self
+            .bank_hashes
+            .read()
+            .unwrap()
+            .get(&slot)
+            .map(|bank_hash_info| bank_hash_info.snapshot_hash)
+            .filter(|snapshot_hash| *snapshot_hash != Hash::default());
+        match recorded_hash {
+            Some(recorded_hash) if recorded_hash != calculated_hash => {
+                warn!(
+                    "mismatched accounts hash for slot {}: {} (calculated) != {} (recorded)",
+                    slot, calculated_hash, recorded_hash
+                );
+                Err(MismatchedBankHash)
+            }
+            _ => Ok(calculated_hash),
+        }
+    }
+
     /// Perform the scan for pubkeys that were written to in a slot
     fn do_scan_slot_for_dirty_pubkeys(
         &self,
@@ -6393,6 +6741,11 @@ impl AccountsDb {
                     self.stats.dropped_stores.swap(0, Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "expired_stores",
+                    self.stats.expired_stores.swap(0, Ordering::Relaxed),
+                    i64
+                ),
             );
         }
     }
@@ -6701,7 +7054,7 @@ impl AccountsDb {
                     self.accounts_index.update_secondary_indexes(
                         &pubkey,
                         &stored_account.account_meta.owner,
-                        stored_account.data,
+                        &stored_account.data,
                         &self.account_indexes,
                     );
                 }
@@ -7365,7 +7718,7 @@ pub mod tests {
     use crate::{
         accounts_hash::MERKLE_FANOUT,
         accounts_index::RefCount,
-        accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude},
+        accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude, ScanError},
         append_vec::{test_utils::TempFile, AccountMeta},
         inline_spl_token_v2_0,
     };
@@ -8947,9 +9300,15 @@ pub mod tests {
         let bank_id = 0;
         accounts
             .accounts_index
-            .index_scan_accounts(&Ancestors::default(), bank_id, index_key, |key, _| {
-                found_accounts.insert(*key);
-            })
+            .index_scan_accounts(
+                &Ancestors::default(),
+                bank_id,
+                index_key,
+                |key, _| {
+                    found_accounts.insert(*key);
+                },
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(found_accounts.len(), 2);
         assert!(found_accounts.contains(&pubkey1));
@@ -8969,6 +9328,7 @@ pub mod tests {
                     |collection: &mut HashSet<Pubkey>, account| {
                         collection.insert(*account.unwrap().0);
                     },
+                    &ScanConfig::default(),
                 )
                 .unwrap();
             assert!(!found_accounts.1);
@@ -8987,6 +9347,7 @@ pub mod tests {
                     |collection: &mut HashSet<Pubkey>, account| {
                         collection.insert(*account.unwrap().0);
                     },
+                    &ScanConfig::default(),
                 )
                 .unwrap();
             assert!(found_accounts.1);
@@ -9020,11 +9381,56 @@ pub mod tests {
                 bank_id,
                 IndexKey::SplTokenMint(mint_key),
                 |key, _| found_accounts.push(*key),
+                &ScanConfig::default(),
             )
             .unwrap();
         assert_eq!(found_accounts, vec![pubkey2]);
     }
 
+    #[test]
+    fn test_scan_accounts_aborted() {
+        let db = AccountsDb::new_single_for_tests();
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let account = AccountSharedData::new(1, 0, AccountSharedData::default().owner());
+        db.store_uncached(0, &[(&pubkey, &account)]);
+        db.add_root(0);
+
+        let abort = Arc::new(AtomicBool::new(false));
+        let config = ScanConfig::new_with_abort(abort.clone());
+        let scan_func = |collector: &mut Vec<Pubkey>, maybe_account: Option<(&Pubkey, _, _)>| {
+            if let Some((pubkey, _, _)) = maybe_account {
+                collector.push(*pubkey);
+            }
+        };
+        assert_eq!(
+            db.scan_accounts(&Ancestors::default(), 0, scan_func, &config),
+            Ok(vec![pubkey])
+        );
+        abort.store(true, Ordering::Relaxed);
+        assert_eq!(
+            db.scan_accounts(&Ancestors::default(), 0, scan_func, &config),
+            Err(ScanError::Aborted)
+        );
+
+        // The scans reading more bytes from the storages than their budget are aborted
+        let scan_with_max_bytes_loaded = |max_bytes_loaded| {
+            let mut config = ScanConfig::default();
+            config.max_bytes_loaded = Some(max_bytes_loaded);
+            let result = db.scan_accounts(&Ancestors::default(), 0, scan_func, &config);
+            (result, config.bytes_loaded())
+        };
+        let (result, bytes_loaded) = scan_with_max_bytes_loaded(usize::MAX);
+        assert_eq!(result, Ok(vec![pubkey]));
+        assert!(bytes_loaded > 0);
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        let pubkey3 = solana_sdk::pubkey::new_rand();
+        db.store_uncached(0, &[(&pubkey2, &account), (&pubkey3, &account)]);
+        assert_eq!(
+            scan_with_max_bytes_loaded(bytes_loaded).0,
+            Err(ScanError::Aborted)
+        );
+    }
+
     #[test]
     fn test_clean_max_slot_zero_lamport_account() {
         solana_logger::setup();
@@ -9806,7 +10212,7 @@ pub mod tests {
         let stored_account = StoredAccountMeta {
             meta: &meta,
             account_meta: &account_meta,
-            data: &data,
+            data: Cow::Borrowed(&data),
             offset,
             stored_size,
             hash: &hash,
@@ -9846,7 +10252,7 @@ pub mod tests {
         let stored_account = StoredAccountMeta {
             meta: &meta,
             account_meta: &account_meta,
-            data: &data,
+            data: Cow::Borrowed(&data),
             offset,
             stored_size: CACHE_VIRTUAL_STORED_SIZE,
             hash: &hash,
@@ -10100,6 +10506,19 @@ pub mod tests {
         db.store_uncached(1, &[(&key, &account)]);
     }
 
+    #[test]
+    fn test_try_create_store_all_paths_quarantined() {
+        let db = AccountsDb::new_sized(Vec::new(), 16 * 1024);
+        let err = IoError::new(std::io::ErrorKind::PermissionDenied, "read-only mount");
+        for path in &db.paths {
+            assert!(db.storage_paths.record_create_failure(path, &err));
+        }
+        assert_matches!(
+            db.try_create_store(1, 8192, "test", &db.paths),
+            Err(CreateStoreError::AllPathsQuarantined(paths)) if paths == db.paths
+        );
+    }
+
     #[test]
     fn test_get_snapshot_storages_empty() {
         let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
@@ -11337,6 +11756,49 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_store_no_recycle() {
+        solana_logger::setup();
+        let accounts = AccountsDb::new_with_config(
+            vec![],
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+            Some(AccountsDbConfig {
+                file_size: Some(4000),
+                max_recycle_stores: Some(0),
+                ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+            }),
+            None,
+        );
+        assert_eq!(accounts.file_size(), 4096);
+
+        let size = 100;
+        let num_accounts: usize = 100;
+        let mut keys = Vec::new();
+        for i in 0..num_accounts {
+            let account = AccountSharedData::new((i + 1) as u64, size, &Pubkey::default());
+            let pubkey = solana_sdk::pubkey::new_rand();
+            accounts.store_uncached(0, &[(&pubkey, &account)]);
+            keys.push(pubkey);
+        }
+        accounts.add_root(0);
+        for (i, key) in keys[1..].iter().enumerate() {
+            let account =
+                AccountSharedData::new((1 + i + num_accounts) as u64, size, &Pubkey::default());
+            accounts.store_uncached(1, &[(key, &account)]);
+        }
+        accounts.add_root(1);
+        accounts.clean_accounts(None, false, None);
+        accounts.shrink_all_slots(false, None);
+        accounts.clean_accounts(None, false, None);
+
+        // The dead storages are dropped along with their files instead of being kept for reuse
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 0);
+        assert!(accounts.stats.dropped_stores.load(Ordering::Relaxed) > 0);
+    }
+
     #[test]
     fn test_zero_lamport_new_root_not_cleaned() {
         let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
@@ -11760,6 +12222,7 @@ pub mod tests {
                             }
                         }
                     },
+                    &ScanConfig::default(),
                 )
                 .unwrap();
             })
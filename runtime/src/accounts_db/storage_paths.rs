@@ -0,0 +1,363 @@
+//! Tracks the health and usage of the directories backing account storages.
+//!
+//! New storages are allocated across the configured paths proportionally to their free space,
+//! and a path that fails to create a storage with an error that would recur, like a read-only or
+//! failing disk, is quarantined for a while so that no further storages are allocated on it while
+//! the remaining paths keep working. A path that keeps failing once its quarantine expires is
+//! quarantined for longer each time.
+use {
+    log::*,
+    rand::{thread_rng, Rng},
+    solana_metrics::*,
+    std::{
+        collections::HashMap,
+        io::{self, ErrorKind},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU32, AtomicU64, Ordering},
+            RwLock,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// How long a free space reading for a path is considered fresh
+const FREE_SPACE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long no storages are allocated on a path after it failed to create one, doubled for each
+/// further quarantine until a storage is created on the path again
+const QUARANTINE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Longest quarantine of a path that keeps failing
+const MAX_QUARANTINE_DURATION: Duration = Duration::from_secs(4 * 60 * 60);
+
+#[derive(Debug, Default)]
+struct StoragePathStats {
+    stores_created: AtomicU64,
+    bytes_allocated: AtomicU64,
+    create_failures: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct StoragePathState {
+    /// when the quarantine of the path ends; None if it isn't quarantined
+    quarantined_until: RwLock<Option<Instant>>,
+    /// quarantines since a storage was last created on the path
+    consecutive_quarantines: AtomicU32,
+    /// last free space reading and when it was taken; None if the platform can't report it
+    free_space: RwLock<Option<(u64, Instant)>>,
+    stats: StoragePathStats,
+}
+
+impl StoragePathState {
+    fn is_quarantined(&self, path: &Path) -> bool {
+        match *self.quarantined_until.read().unwrap() {
+            Some(until) if Instant::now() < until => return true,
+            Some(_) => (),
+            None => return false,
+        }
+        if self.quarantined_until.write().unwrap().take().is_some() {
+            info!(
+                "Quarantine of accounts path {:?} expired, allocating account storages on it again",
+                path
+            );
+        }
+        false
+    }
+
+    fn next_quarantine_duration(&self) -> Duration {
+        let quarantines = self.consecutive_quarantines.fetch_add(1, Ordering::Relaxed);
+        QUARANTINE_DURATION
+            .checked_mul(1 << quarantines.min(16))
+            .unwrap_or(MAX_QUARANTINE_DURATION)
+            .min(MAX_QUARANTINE_DURATION)
+    }
+
+    fn free_space(&self, path: &Path) -> Option<u64> {
+        if let Some((free, when)) = *self.free_space.read().unwrap() {
+            if when.elapsed() < FREE_SPACE_REFRESH_INTERVAL {
+                return Some(free);
+            }
+        }
+        let free = available_space(path)?;
+        *self.free_space.write().unwrap() = Some((free, Instant::now()));
+        Some(free)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StoragePaths {
+    states: RwLock<HashMap<PathBuf, StoragePathState>>,
+}
+
+impl StoragePaths {
+    fn with_state<R>(&self, path: &Path, f: impl FnOnce(&StoragePathState) -> R) -> R {
+        if let Some(state) = self.states.read().unwrap().get(path) {
+            return f(state);
+        }
+        let mut states = self.states.write().unwrap();
+        f(states.entry(path.to_path_buf()).or_default())
+    }
+
+    pub fn is_quarantined(&self, path: &Path) -> bool {
+        self.with_state(path, |state| state.is_quarantined(path))
+    }
+
+    /// Pick a non-quarantined path for a new storage of `size` bytes, weighting each path by its
+    /// free space. Paths without room for the storage are only used if no other path has room.
+    /// Returns None if every path is quarantined.
+    pub fn select_path<'a>(&self, paths: &'a [PathBuf], size: u64) -> Option<&'a PathBuf> {
+        let candidates: Vec<(&PathBuf, Option<u64>)> = paths
+            .iter()
+            .filter(|path| !self.is_quarantined(path))
+            .map(|path| (path, self.with_state(path, |state| state.free_space(path))))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let with_room: Vec<(&PathBuf, u64)> = candidates
+            .iter()
+            .filter_map(|(path, free)| free.filter(|free| *free > size).map(|free| (*path, free)))
+            .collect();
+        if with_room.is_empty() {
+            // free space is unknown or exhausted everywhere; fall back to a uniform choice
+            return Some(candidates[thread_rng().gen_range(0, candidates.len())].0);
+        }
+
+        let total_free: u64 = with_room.iter().map(|(_, free)| *free).sum();
+        let mut pick = thread_rng().gen_range(0, total_free);
+        for (path, free) in &with_room {
+            if pick < *free {
+                return Some(*path);
+            }
+            pick -= free;
+        }
+        with_room.last().map(|(path, _)| *path)
+    }
+
+    pub fn record_store_created(&self, path: &Path, size: u64) {
+        self.with_state(path, |state| {
+            state.stats.stores_created.fetch_add(1, Ordering::Relaxed);
+            state.consecutive_quarantines.store(0, Ordering::Relaxed);
+            state
+                .stats
+                .bytes_allocated
+                .fetch_add(size, Ordering::Relaxed);
+            if let Some((free, _)) = state.free_space.write().unwrap().as_mut() {
+                *free = free.saturating_sub(size);
+            }
+        });
+    }
+
+    /// Record a failure to create a storage on `path`, quarantining the path if the failure would
+    /// recur. Returns whether the path was quarantined; transient failures, like running out of
+    /// file descriptors or an interrupted call, are left to the caller to retry.
+    pub fn record_create_failure(&self, path: &Path, err: &io::Error) -> bool {
+        self.with_state(path, |state| {
+            state.stats.create_failures.fetch_add(1, Ordering::Relaxed);
+        });
+        if is_deterministic_failure(err) {
+            self.quarantine(path, err);
+            true
+        } else {
+            warn!("Failed to create account storage in {:?}: {}", path, err);
+            false
+        }
+    }
+
+    /// Stop allocating storages on `path` for a while, see `QUARANTINE_DURATION`. Storages already
+    /// on the path are left untouched.
+    fn quarantine(&self, path: &Path, err: &io::Error) {
+        self.with_state(path, |state| {
+            if state.is_quarantined(path) {
+                return;
+            }
+            let duration = state.next_quarantine_duration();
+            *state.quarantined_until.write().unwrap() = Some(Instant::now() + duration);
+            error!(
+                "Failed to create account storage in {:?}: {}. \
+                 No new account storages will be allocated on this path for {:?}.",
+                path, err, duration
+            );
+            datapoint_error!(
+                "accounts_db-storage_path_quarantined",
+                ("path", path.display().to_string(), String),
+                ("error", err.to_string(), String),
+                ("duration_secs", duration.as_secs(), i64),
+            );
+        });
+    }
+
+    /// Report per-path metrics. `store_usage` maps each path to the number of storages and bytes
+    /// currently held on it.
+    pub fn report(&self, store_usage: &HashMap<PathBuf, (usize, u64)>) {
+        let states = self.states.read().unwrap();
+        for (path, state) in states.iter() {
+            let (store_count, store_bytes) = store_usage.get(path).cloned().unwrap_or_default();
+            datapoint_info!(
+                "accounts_db-storage_path",
+                ("path", path.display().to_string(), String),
+                ("quarantined", state.is_quarantined(path), bool),
+                (
+                    "free_bytes",
+                    state.free_space(path).unwrap_or_default(),
+                    i64
+                ),
+                ("store_count", store_count, i64),
+                ("store_bytes", store_bytes, i64),
+                (
+                    "stores_created",
+                    state.stats.stores_created.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "bytes_allocated",
+                    state.stats.bytes_allocated.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "create_failures",
+                    state.stats.create_failures.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+            );
+        }
+    }
+}
+
+/// Whether creating a storage on the path would fail the same way if retried
+fn is_deterministic_failure(err: &io::Error) -> bool {
+    match err.kind() {
+        ErrorKind::NotFound | ErrorKind::PermissionDenied => true,
+        _ => err.raw_os_error().map_or(false, is_path_failure_code),
+    }
+}
+
+#[cfg(unix)]
+fn is_path_failure_code(code: i32) -> bool {
+    matches!(
+        code,
+        libc::EIO | libc::EROFS | libc::ENOSPC | libc::EDQUOT | libc::ENOTDIR
+    )
+}
+
+#[cfg(not(unix))]
+fn is_path_failure_code(_code: i32) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // UNSAFE: `path` is a valid nul-terminated string and `stat` is a properly sized out param
+    let result = unsafe { libc::statvfs(path.as_ptr(), &mut stat) };
+    if result == 0 {
+        Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_path_skips_quarantined() {
+        let storage_paths = StoragePaths::default();
+        let paths = vec![PathBuf::from("a"), PathBuf::from("b")];
+        let err = io::Error::new(ErrorKind::PermissionDenied, "read-only mount");
+        assert!(storage_paths.record_create_failure(&paths[0], &err));
+        assert!(storage_paths.is_quarantined(&paths[0]));
+        for _ in 0..10 {
+            assert_eq!(storage_paths.select_path(&paths, 1024), Some(&paths[1]));
+        }
+        assert!(storage_paths.record_create_failure(&paths[1], &err));
+        assert_eq!(storage_paths.select_path(&paths, 1024), None);
+
+        // The quarantine expires
+        storage_paths.with_state(&paths[0], |state| {
+            *state.quarantined_until.write().unwrap() = Some(Instant::now())
+        });
+        assert!(!storage_paths.is_quarantined(&paths[0]));
+        assert_eq!(storage_paths.select_path(&paths, 1024), Some(&paths[0]));
+    }
+
+    #[test]
+    fn test_quarantine_backoff() {
+        let storage_paths = StoragePaths::default();
+        let path = PathBuf::from("a");
+        let err = io::Error::new(ErrorKind::PermissionDenied, "read-only mount");
+        let quarantine_duration = |storage_paths: &StoragePaths| {
+            storage_paths.with_state(&path, |state| {
+                let until = state.quarantined_until.write().unwrap().take().unwrap();
+                until.duration_since(Instant::now())
+            })
+        };
+
+        // Each quarantine following an expired one is longer, up to a limit
+        let mut previous = Duration::default();
+        for _ in 0..10 {
+            assert!(storage_paths.record_create_failure(&path, &err));
+            let duration = quarantine_duration(&storage_paths);
+            assert!(duration > previous || duration > MAX_QUARANTINE_DURATION / 2);
+            assert!(duration <= MAX_QUARANTINE_DURATION);
+            previous = duration;
+        }
+
+        // A failure while quarantined doesn't extend the quarantine
+        let quarantined_until = |storage_paths: &StoragePaths| {
+            storage_paths.with_state(&path, |state| *state.quarantined_until.read().unwrap())
+        };
+        assert!(storage_paths.record_create_failure(&path, &err));
+        let until = quarantined_until(&storage_paths);
+        assert!(storage_paths.record_create_failure(&path, &err));
+        assert_eq!(quarantined_until(&storage_paths), until);
+        quarantine_duration(&storage_paths);
+
+        // Creating a storage on the path resets the backoff
+        storage_paths.record_store_created(&path, 1024);
+        assert!(storage_paths.record_create_failure(&path, &err));
+        assert!(quarantine_duration(&storage_paths) <= QUARANTINE_DURATION);
+    }
+
+    #[test]
+    fn test_transient_failure_not_quarantined() {
+        let storage_paths = StoragePaths::default();
+        let path = PathBuf::from("a");
+        let err = io::Error::new(ErrorKind::Interrupted, "interrupted");
+        assert!(!storage_paths.record_create_failure(&path, &err));
+        assert!(!storage_paths.is_quarantined(&path));
+    }
+
+    #[test]
+    fn test_select_path_prefers_free_space() {
+        let storage_paths = StoragePaths::default();
+        let paths = vec![PathBuf::from("full"), PathBuf::from("empty")];
+        let now = Instant::now();
+        storage_paths.with_state(&paths[0], |state| {
+            *state.free_space.write().unwrap() = Some((100, now))
+        });
+        storage_paths.with_state(&paths[1], |state| {
+            *state.free_space.write().unwrap() = Some((1_000_000, now))
+        });
+        // the first path has no room for the storage, so it's never picked
+        for _ in 0..10 {
+            assert_eq!(storage_paths.select_path(&paths, 1024), Some(&paths[1]));
+        }
+        storage_paths.record_store_created(&paths[1], 1024);
+        assert_eq!(
+            storage_paths.with_state(&paths[1], |state| state.free_space(&paths[1])),
+            Some(1_000_000 - 1024)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Audits of the accounts hash, requested by the operator or scheduled at a fixed interval, to
+//! detect accounts-db corruption before a snapshot or a restart does. An audit recalculates the
+//! accounts hash of the root bank in the accounts background service, so that it doesn't race
+//! with clean and shrink.
+
+use {
+    crate::bank::Bank,
+    log::*,
+    solana_measure::measure::Measure,
+    solana_sdk::{clock::Slot, timing::timestamp},
+    std::{
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AccountsHashAuditState {
+    /// Waiting for the accounts background service to pick the audit up
+    Requested,
+    Running,
+    Passed,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsHashAuditStatus {
+    pub state: AccountsHashAuditState,
+    /// Whether the audit was scheduled rather than requested
+    pub scheduled: bool,
+    /// Time the audit was requested or scheduled, in milliseconds since the UNIX epoch
+    pub requested_at: u64,
+    /// Root the accounts hash is verified at, once the audit is running
+    pub slot: Option<Slot>,
+    /// Time the verification took, once the audit is finished
+    pub elapsed_ms: Option<u64>,
+    pub accounts_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+impl AccountsHashAuditStatus {
+    fn new(scheduled: bool) -> Self {
+        Self {
+            state: AccountsHashAuditState::Requested,
+            scheduled,
+            requested_at: timestamp(),
+            slot: None,
+            elapsed_ms: None,
+            accounts_hash: None,
+            error: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.state,
+            AccountsHashAuditState::Passed | AccountsHashAuditState::Failed
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AccountsHashAudit {
+    /// Interval between scheduled audits, unset if the accounts hash is only audited on request
+    interval: Option<Duration>,
+    next_scheduled: Mutex<Option<Instant>>,
+    status: Mutex<Option<AccountsHashAuditStatus>>,
+}
+
+impl AccountsHashAudit {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            next_scheduled: Mutex::new(interval.map(|interval| Instant::now() + interval)),
+            status: Mutex::default(),
+        }
+    }
+
+    /// Requests an audit of the next root bank, unless one is already pending, and returns the
+    /// status of the pending audit
+    pub fn request(&self) -> AccountsHashAuditStatus {
+        let mut status = self.status.lock().unwrap();
+        match status.as_ref() {
+            Some(pending_status) if !pending_status.is_finished() => pending_status.clone(),
+            _ => status.insert(AccountsHashAuditStatus::new(false)).clone(),
+        }
+    }
+
+    /// Status of the last audit, unset if the accounts hash was never audited
+    pub fn status(&self) -> Option<AccountsHashAuditStatus> {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Audits the accounts hash of the root `bank` if an audit was requested or is due
+    pub fn audit_if_due(&self, bank: &Bank) {
+        {
+            let mut status = self.status.lock().unwrap();
+            let requested = matches!(
+                status.as_ref().map(|status| status.state),
+                Some(AccountsHashAuditState::Requested)
+            );
+            let mut next_scheduled = self.next_scheduled.lock().unwrap();
+            let scheduled = next_scheduled.map_or(false, |next| next <= Instant::now());
+            if !requested && !scheduled {
+                return;
+            }
+            if scheduled {
+                *next_scheduled = self.interval.map(|interval| Instant::now() + interval);
+            }
+            let status = if requested {
+                status.as_mut().unwrap()
+            } else {
+                status.insert(AccountsHashAuditStatus::new(true))
+            };
+            status.state = AccountsHashAuditState::Running;
+            status.slot = Some(bank.slot());
+        }
+
+        info!("Auditing the accounts hash of slot {}", bank.slot());
+        let mut verify_time = Measure::start("verify_accounts_hash");
+        let result = bank.verify_accounts_hash();
+        verify_time.stop();
+        match &result {
+            Ok(accounts_hash) => info!(
+                "Accounts hash audit of slot {} passed: {}, {}",
+                bank.slot(),
+                accounts_hash,
+                verify_time
+            ),
+            Err(err) => error!(
+                "Accounts hash audit of slot {} failed: {:?}, {}",
+                bank.slot(),
+                err,
+                verify_time
+            ),
+        }
+        datapoint_info!(
+            "accounts_hash_audit",
+            ("slot", bank.slot(), i64),
+            ("passed", result.is_ok(), bool),
+            ("verify_ms", verify_time.as_ms(), i64),
+        );
+
+        let mut status = self.status.lock().unwrap();
+        if let Some(status) = status.as_mut() {
+            status.elapsed_ms = Some(verify_time.as_ms());
+            match result {
+                Ok(accounts_hash) => {
+                    status.state = AccountsHashAuditState::Passed;
+                    status.accounts_hash = Some(accounts_hash.to_string());
+                }
+                Err(err) => {
+                    status.state = AccountsHashAuditState::Failed;
+                    status.error = Some(format!("{:?}", err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::genesis_utils::create_genesis_config,
+        solana_sdk::{account::AccountSharedData, pubkey::Pubkey},
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn test_accounts_hash_audit() {
+        let genesis_config = create_genesis_config(1_000_000).genesis_config;
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank1.freeze();
+        bank1.squash();
+
+        let audit = AccountsHashAudit::new(None);
+        assert_eq!(audit.status(), None);
+        audit.audit_if_due(&bank1);
+        assert_eq!(audit.status(), None);
+
+        let status = audit.request();
+        assert_eq!(status.state, AccountsHashAuditState::Requested);
+        assert!(!status.scheduled);
+        // A pending audit isn't requested again
+        assert_eq!(audit.request(), status);
+
+        audit.audit_if_due(&bank1);
+        let status = audit.status().unwrap();
+        assert_eq!(status.state, AccountsHashAuditState::Passed);
+        assert_eq!(status.slot, Some(1));
+        assert!(status.accounts_hash.is_some());
+
+        // Lamports appearing out of thin air fail the audit
+        let bank2 = Bank::new_from_parent(&Arc::new(bank1), &Pubkey::default(), 2);
+        bank2.store_account(
+            &Pubkey::new_unique(),
+            &AccountSharedData::new(42, 0, &Pubkey::default()),
+        );
+        bank2.freeze();
+        bank2.squash();
+        let capitalization = bank2.capitalization();
+        audit.request();
+        audit.audit_if_due(&bank2);
+        let status = audit.status().unwrap();
+        assert_eq!(status.state, AccountsHashAuditState::Failed);
+        assert_eq!(
+            status.error,
+            Some(format!(
+                "MismatchedTotalLamports({}, {})",
+                capitalization + 42,
+                capitalization
+            ))
+        );
+    }
+
+    #[test]
+    fn test_accounts_hash_audit_scheduled() {
+        let genesis_config = create_genesis_config(1_000_000).genesis_config;
+        let bank = Bank::new_for_tests(&genesis_config);
+        bank.freeze();
+        bank.squash();
+
+        let audit = AccountsHashAudit::new(Some(Duration::default()));
+        audit.audit_if_due(&bank);
+        let status = audit.status().unwrap();
+        assert_eq!(status.state, AccountsHashAuditState::Passed);
+        assert!(status.scheduled);
+    }
+}
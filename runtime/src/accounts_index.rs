@@ -28,9 +28,10 @@ use std::{
     },
     path::PathBuf,
     sync::{
-        atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
         Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
     },
+    time::Instant,
 };
 use thiserror::Error;
 
@@ -76,6 +77,54 @@ pub trait IndexValue:
 pub enum ScanError {
     #[error("Node detected it replayed bad version of slot {slot:?} with id {bank_id:?}, thus the scan on said slot was aborted")]
     SlotRemoved { slot: Slot, bank_id: BankId },
+    #[error("The scan was aborted")]
+    Aborted,
+}
+
+/// Options of the scans checked against the removal of their bank
+#[derive(Debug, Default, Clone)]
+pub struct ScanConfig {
+    /// Setting it stops the scan, which then fails with `ScanError::Aborted`
+    pub abort: Option<Arc<AtomicBool>>,
+    /// The scan stops and fails with `ScanError::Aborted` once past this instant
+    pub deadline: Option<Instant>,
+    /// The scan stops and fails with `ScanError::Aborted` once it read more than this many bytes
+    /// of accounts from the storages, the accounts loaded from the cache being free
+    pub max_bytes_loaded: Option<usize>,
+    bytes_loaded: Arc<AtomicUsize>,
+}
+
+impl ScanConfig {
+    pub fn new_with_abort(abort: Arc<AtomicBool>) -> Self {
+        Self {
+            abort: Some(abort),
+            ..Self::default()
+        }
+    }
+
+    /// Accounts the bytes of an account loaded by the scan against `max_bytes_loaded`
+    pub fn add_bytes_loaded(&self, bytes: usize) {
+        self.bytes_loaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn bytes_loaded(&self) -> usize {
+        self.bytes_loaded.load(Ordering::Relaxed)
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.abort
+            .as_ref()
+            .map(|abort| abort.load(Ordering::Relaxed))
+            .unwrap_or(false)
+            || self
+                .deadline
+                .map(|deadline| Instant::now() >= deadline)
+                .unwrap_or(false)
+            || self
+                .max_bytes_loaded
+                .map(|max_bytes_loaded| self.bytes_loaded() > max_bytes_loaded)
+                .unwrap_or(false)
+    }
 }
 
 enum ScanTypes<R: RangeBounds<Pubkey>> {
@@ -838,6 +887,7 @@ impl<T: IndexValue> AccountsIndex<T> {
         func: F,
         scan_type: ScanTypes<R>,
         collect_all_unsorted: bool,
+        config: &ScanConfig,
     ) -> Result<(), ScanError>
     where
         F: FnMut(&Pubkey, (&T, Slot)),
@@ -987,7 +1037,7 @@ impl<T: IndexValue> AccountsIndex<T> {
         will not be cleaned in the middle of the scan either. (NOTE similar reasoning is employed for
         assert!() justification in AccountsDb::retry_to_get_account_accessor)
         */
-        match scan_type {
+        let completed = match scan_type {
             ScanTypes::Unindexed(range) => {
                 // Pass "" not to log metrics, so RPC doesn't get spammy
                 self.do_scan_accounts(
@@ -997,36 +1047,34 @@ impl<T: IndexValue> AccountsIndex<T> {
                     range,
                     Some(max_root),
                     collect_all_unsorted,
-                );
-            }
-            ScanTypes::Indexed(IndexKey::ProgramId(program_id)) => {
-                self.do_scan_secondary_index(
-                    ancestors,
-                    func,
-                    &self.program_id_index,
-                    &program_id,
-                    Some(max_root),
-                );
-            }
-            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key)) => {
-                self.do_scan_secondary_index(
-                    ancestors,
-                    func,
-                    &self.spl_token_mint_index,
-                    &mint_key,
-                    Some(max_root),
-                );
+                    config,
+                )
             }
-            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key)) => {
-                self.do_scan_secondary_index(
-                    ancestors,
-                    func,
-                    &self.spl_token_owner_index,
-                    &owner_key,
-                    Some(max_root),
-                );
-            }
-        }
+            ScanTypes::Indexed(IndexKey::ProgramId(program_id)) => self.do_scan_secondary_index(
+                ancestors,
+                func,
+                &self.program_id_index,
+                &program_id,
+                Some(max_root),
+                config,
+            ),
+            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key)) => self.do_scan_secondary_index(
+                ancestors,
+                func,
+                &self.spl_token_mint_index,
+                &mint_key,
+                Some(max_root),
+                config,
+            ),
+            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key)) => self.do_scan_secondary_index(
+                ancestors,
+                func,
+                &self.spl_token_owner_index,
+                &owner_key,
+                Some(max_root),
+                config,
+            ),
+        };
 
         {
             let mut ongoing_scan_roots = self.ongoing_scan_roots.write().unwrap();
@@ -1050,6 +1098,8 @@ impl<T: IndexValue> AccountsIndex<T> {
                 slot: ancestors.max_slot(),
                 bank_id: scan_bank_id,
             })
+        } else if !completed {
+            Err(ScanError::Aborted)
         } else {
             Ok(())
         }
@@ -1073,12 +1123,14 @@ impl<T: IndexValue> AccountsIndex<T> {
             range,
             None,
             collect_all_unsorted,
+            &ScanConfig::default(),
         );
     }
 
     // Scan accounts and return latest version of each account that is either:
     // 1) rooted or
     // 2) present in ancestors
+    // Returns false if the scan was aborted before completion
     fn do_scan_accounts<F, R>(
         &self,
         metric_name: &'static str,
@@ -1087,7 +1139,9 @@ impl<T: IndexValue> AccountsIndex<T> {
         range: Option<R>,
         max_root: Option<Slot>,
         collect_all_unsorted: bool,
-    ) where
+        config: &ScanConfig,
+    ) -> bool
+    where
         F: FnMut(&Pubkey, (&T, Slot)),
         R: RangeBounds<Pubkey> + std::fmt::Debug,
     {
@@ -1100,10 +1154,16 @@ impl<T: IndexValue> AccountsIndex<T> {
         let mut read_lock_elapsed = 0;
         let mut iterator_elapsed = 0;
         let mut iterator_timer = Measure::start("iterator_elapsed");
-        for pubkey_list in self.iter(range.as_ref(), collect_all_unsorted) {
+        let mut completed = true;
+        'scan: for pubkey_list in self.iter(range.as_ref(), collect_all_unsorted) {
             iterator_timer.stop();
             iterator_elapsed += iterator_timer.as_us();
             for (pubkey, list) in pubkey_list {
+                // Checked for each account, as a single bin may hold a lot of them
+                if config.is_aborted() {
+                    completed = false;
+                    break 'scan;
+                }
                 num_keys_iterated += 1;
                 let mut read_lock_timer = Measure::start("read_lock");
                 let list_r = &list.slot_list.read().unwrap();
@@ -1134,6 +1194,7 @@ impl<T: IndexValue> AccountsIndex<T> {
                 ("num_keys_iterated", num_keys_iterated, i64),
             )
         }
+        completed
     }
 
     fn do_scan_secondary_index<
@@ -1146,10 +1207,15 @@ impl<T: IndexValue> AccountsIndex<T> {
         index: &SecondaryIndex<SecondaryIndexEntryType>,
         index_key: &Pubkey,
         max_root: Option<Slot>,
-    ) where
+        config: &ScanConfig,
+    ) -> bool
+    where
         F: FnMut(&Pubkey, (&T, Slot)),
     {
         for pubkey in index.get(index_key) {
+            if config.is_aborted() {
+                return false;
+            }
             // Maybe these reads from the AccountsIndex can be batched every time it
             // grabs the read lock as well...
             if let AccountIndexGetResult::Found(list_r, index) =
@@ -1161,6 +1227,7 @@ impl<T: IndexValue> AccountsIndex<T> {
                 );
             }
         }
+        true
     }
 
     pub fn get_account_read_entry(&self, pubkey: &Pubkey) -> Option<ReadAccountMapEntry<T>> {
@@ -1212,6 +1279,7 @@ impl<T: IndexValue> AccountsIndex<T> {
         ancestors: &Ancestors,
         scan_bank_id: BankId,
         func: F,
+        config: &ScanConfig,
     ) -> Result<(), ScanError>
     where
         F: FnMut(&Pubkey, (&T, Slot)),
@@ -1225,6 +1293,7 @@ impl<T: IndexValue> AccountsIndex<T> {
             func,
             ScanTypes::Unindexed(None::<Range<Pubkey>>),
             collect_all_unsorted,
+            config,
         )
     }
 
@@ -1275,6 +1344,7 @@ impl<T: IndexValue> AccountsIndex<T> {
         scan_bank_id: BankId,
         index_key: IndexKey,
         func: F,
+        config: &ScanConfig,
     ) -> Result<(), ScanError>
     where
         F: FnMut(&Pubkey, (&T, Slot)),
@@ -1289,6 +1359,7 @@ impl<T: IndexValue> AccountsIndex<T> {
             func,
             ScanTypes::<Range<Pubkey>>::Indexed(index_key),
             collect_all_unsorted,
+            config,
         )
     }
 
@@ -14,8 +14,8 @@ use solana_sdk::{
     pubkey::Pubkey,
 };
 use std::{
-    borrow::Borrow,
-    fs::{remove_file, OpenOptions},
+    borrow::{Borrow, Cow},
+    fs::{remove_file, File, OpenOptions},
     io,
     io::{Seek, SeekFrom, Write},
     mem,
@@ -33,10 +33,110 @@ macro_rules! u64_align {
     };
 }
 
-const MAXIMUM_APPEND_VEC_FILE_SIZE: usize = 16 * 1024 * 1024 * 1024; // 16 GiB
+pub const MAXIMUM_APPEND_VEC_FILE_SIZE: usize = 16 * 1024 * 1024 * 1024; // 16 GiB
 
 pub type StoredMetaWriteVersion = u64;
 
+/// Marks the first bytes of an `AppendVec` written in a versioned layout.
+/// V1 files have no header and start directly with a `StoredMeta`, whose `write_version` is a
+/// global counter that can never realistically reach this value.
+const APPEND_VEC_MAGIC: u64 = u64::from_le_bytes(*b"SOLAVEC\0");
+
+/// Default zstd level used for compressing account data in V2 append vecs
+const APPEND_VEC_COMPRESSION_LEVEL: i32 = 1;
+
+/// The entry's data is stored zstd compressed
+const ENTRY_FLAG_ZSTD_COMPRESSED: u64 = 1;
+
+/// On-disk layout of an `AppendVec`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AbiExample)]
+pub enum AppendVecFormat {
+    /// `StoredMeta | AccountMeta | Hash | data` with no file header
+    V1,
+    /// An `AppendVecHeader` followed by
+    /// `StoredMeta | AccountMeta | Hash | StoredEntryInfo | data`, where every entry carries a
+    /// checksum and data of at least `compression_threshold` bytes may be stored zstd compressed.
+    V2 {
+        /// Only used when appending; `None` disables compression of newly written entries
+        compression_threshold: Option<usize>,
+    },
+}
+
+impl Default for AppendVecFormat {
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl AppendVecFormat {
+    /// Number of bytes preceding the first entry
+    pub(crate) fn header_len(&self) -> usize {
+        match self {
+            Self::V1 => 0,
+            Self::V2 { .. } => mem::size_of::<AppendVecHeader>(),
+        }
+    }
+
+    fn version(&self) -> u64 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 { .. } => 2,
+        }
+    }
+}
+
+/// Header written at the start of versioned (V2 and later) append vecs
+/// This struct will be backed by mmaped and snapshotted data files.
+/// So the data layout must be stable and consistent across the entire cluster!
+#[derive(Clone, PartialEq, Debug)]
+#[repr(C)]
+struct AppendVecHeader {
+    magic: u64,
+    version: u64,
+}
+
+/// Per-entry integrity and encoding information for V2 append vecs
+/// This struct will be backed by mmaped and snapshotted data files.
+/// So the data layout must be stable and consistent across the entire cluster!
+#[derive(Clone, PartialEq, Debug)]
+#[repr(C)]
+struct StoredEntryInfo {
+    /// checksum over the meta, account meta, hash and stored data of the entry
+    checksum: u64,
+    /// number of data bytes following this struct, which differs from `StoredMeta::data_len`
+    /// when the data is compressed
+    stored_data_len: u64,
+    flags: u64,
+}
+
+impl StoredEntryInfo {
+    fn compute_checksum(
+        meta: &StoredMeta,
+        account_meta: &AccountMeta,
+        hash: &Hash,
+        stored_data: &[u8],
+    ) -> u64 {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&meta.write_version.to_le_bytes());
+        hasher.update(meta.pubkey.as_ref());
+        hasher.update(&meta.data_len.to_le_bytes());
+        hasher.update(&account_meta.lamports.to_le_bytes());
+        hasher.update(account_meta.owner.as_ref());
+        hasher.update(&[account_meta.executable as u8]);
+        hasher.update(&account_meta.rent_epoch.to_le_bytes());
+        hasher.update(hash.as_ref());
+        hasher.update(stored_data);
+        let digest = hasher.finalize();
+        let mut checksum = [0u8; 8];
+        checksum.copy_from_slice(&digest.as_bytes()[..8]);
+        u64::from_le_bytes(checksum)
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.flags & ENTRY_FLAG_ZSTD_COMPRESSED != 0
+    }
+}
+
 /// Meta contains enough context to recover the index from storage itself
 /// This struct will be backed by mmaped and snapshotted data files.
 /// So the data layout must be stable and consistent across the entire cluster!
@@ -90,7 +190,8 @@ pub struct StoredAccountMeta<'a> {
     pub meta: &'a StoredMeta,
     /// account data
     pub account_meta: &'a AccountMeta,
-    pub data: &'a [u8],
+    /// Borrowed from the storage, or owned when the entry was stored compressed
+    pub data: Cow<'a, [u8]>,
     pub offset: usize,
     pub stored_size: usize,
     pub hash: &'a Hash,
@@ -155,6 +256,12 @@ pub struct AppendVec {
 
     /// True if the file should automatically be deleted when this AppendVec is dropped.
     remove_on_drop: bool,
+
+    /// The layout of the entries in this file.
+    format: AppendVecFormat,
+
+    /// True if the contents are held in anonymous memory and `path` is never created on disk.
+    in_memory: bool,
 }
 
 impl Drop for AppendVec {
@@ -172,7 +279,35 @@ impl Drop for AppendVec {
 
 impl AppendVec {
     pub fn new(file: &Path, create: bool, size: usize) -> Self {
-        let initial_len = 0;
+        Self::new_with_format(file, create, size, AppendVecFormat::V1)
+    }
+
+    pub fn new_with_format(
+        file: &Path,
+        create: bool,
+        size: usize,
+        format: AppendVecFormat,
+    ) -> Self {
+        Self::try_new_with_format(file, create, size, format).unwrap_or_else(|e| {
+            panic!(
+                "Unable to {} data file {} in current dir({:?}): {:?}",
+                if create { "create" } else { "open" },
+                file.display(),
+                std::env::current_dir(),
+                e
+            );
+        })
+    }
+
+    /// Like `new_with_format()`, but returns IO errors from creating the backing file instead of
+    /// panicking, so callers can isolate a failing storage path.
+    pub fn try_new_with_format(
+        file: &Path,
+        create: bool,
+        size: usize,
+        format: AppendVecFormat,
+    ) -> io::Result<Self> {
+        let initial_len = format.header_len();
         AppendVec::sanitize_len_and_size(initial_len, size).unwrap();
 
         if create {
@@ -183,25 +318,15 @@ impl AppendVec {
             .read(true)
             .write(true)
             .create(create)
-            .open(file)
-            .map_err(|e| {
-                panic!(
-                    "Unable to {} data file {} in current dir({:?}): {:?}",
-                    if create { "create" } else { "open" },
-                    file.display(),
-                    std::env::current_dir(),
-                    e
-                );
-            })
-            .unwrap();
+            .open(file)?;
 
-        // Theoretical performance optimization: write a zero to the end of
-        // the file so that we won't have to resize it later, which may be
-        // expensive.
-        data.seek(SeekFrom::Start((size - 1) as u64)).unwrap();
-        data.write_all(&[0]).unwrap();
-        data.seek(SeekFrom::Start(0)).unwrap();
-        data.flush().unwrap();
+        if let Err(err) = Self::extend_to_size(&mut data, size) {
+            if create {
+                // don't leave a partially created file behind
+                let _ignored = remove_file(file);
+            }
+            return Err(err);
+        }
 
         //UNSAFE: Required to create a Mmap
         let map = unsafe { MmapMut::map_mut(&data) };
@@ -214,7 +339,7 @@ impl AppendVec {
             std::process::exit(1);
         });
 
-        AppendVec {
+        let new = AppendVec {
             path: file.to_path_buf(),
             map,
             // This mutex forces append to be single threaded, but concurrent with reads
@@ -223,7 +348,111 @@ impl AppendVec {
             current_len: AtomicUsize::new(initial_len),
             file_size: size as u64,
             remove_on_drop: true,
+            format,
+            in_memory: false,
+        };
+        new.write_header();
+        Ok(new)
+    }
+
+    fn extend_to_size(data: &mut File, size: usize) -> io::Result<()> {
+        // Theoretical performance optimization: write a zero to the end of
+        // the file so that we won't have to resize it later, which may be
+        // expensive.
+        data.seek(SeekFrom::Start((size - 1) as u64))?;
+        data.write_all(&[0])?;
+        data.seek(SeekFrom::Start(0))?;
+        data.flush()
+    }
+
+    /// Create an `AppendVec` whose contents are kept in anonymous memory rather than a file.
+    /// `file` is only used to name the storage; nothing is written to disk until the contents
+    /// are explicitly persisted with `write_to_file()`.
+    pub fn new_in_memory(file: &Path, size: usize, format: AppendVecFormat) -> Self {
+        let initial_len = format.header_len();
+        AppendVec::sanitize_len_and_size(initial_len, size).unwrap();
+
+        let map = MmapMut::map_anon(size).unwrap_or_else(|e| {
+            error!(
+                "Failed to create in-memory map (size: {}): {}.\n
+                    Please increase sysctl vm.max_map_count or equivalent for your platform.",
+                size, e
+            );
+            std::process::exit(1);
+        });
+
+        let new = AppendVec {
+            path: file.to_path_buf(),
+            map,
+            // This mutex forces append to be single threaded, but concurrent with reads
+            // See UNSAFE usage in `append_ptr`
+            append_lock: Mutex::new(()),
+            current_len: AtomicUsize::new(initial_len),
+            file_size: size as u64,
+            // there's no file to remove
+            remove_on_drop: false,
+            format,
+            in_memory: true,
+        };
+        new.write_header();
+        new
+    }
+
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    /// Write the whole backing memory to `path`, producing a file that can be loaded with
+    /// `new_from_file()`.
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, &self.map[..])
+    }
+
+    /// Write the file header for versioned formats. V1 files have no header.
+    fn write_header(&self) {
+        if self.format == AppendVecFormat::V1 {
+            return;
         }
+        // The header is written little endian whatever the platform, as it is read back
+        let header = AppendVecHeader {
+            magic: APPEND_VEC_MAGIC.to_le(),
+            version: self.format.version().to_le(),
+        };
+        let _lock = self.append_lock.lock().unwrap();
+        let mut offset = 0;
+        self.append_ptr(
+            &mut offset,
+            &header as *const AppendVecHeader as *const u8,
+            mem::size_of::<AppendVecHeader>(),
+        );
+    }
+
+    /// Determine the format of an existing file by looking for a versioned header
+    fn detect_format(map: &MmapMut, current_len: usize) -> io::Result<AppendVecFormat> {
+        let header_len = mem::size_of::<AppendVecHeader>();
+        if current_len < header_len || map.len() < header_len {
+            return Ok(AppendVecFormat::V1);
+        }
+        let mut magic = [0u8; 8];
+        magic.copy_from_slice(&map[0..8]);
+        if u64::from_le_bytes(magic) != APPEND_VEC_MAGIC {
+            return Ok(AppendVecFormat::V1);
+        }
+        let mut version = [0u8; 8];
+        version.copy_from_slice(&map[8..16]);
+        match u64::from_le_bytes(version) {
+            2 => Ok(AppendVecFormat::V2 {
+                compression_threshold: None,
+            }),
+            version => Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("unsupported AppendVec version {}", version),
+            )),
+        }
+    }
+
+    pub fn format(&self) -> AppendVecFormat {
+        self.format
     }
 
     pub fn set_no_remove_on_drop(&mut self) {
@@ -247,6 +476,8 @@ impl AppendVec {
             current_len: AtomicUsize::new(current_len),
             file_size: 0, // will be filled by set_file()
             remove_on_drop: true,
+            format: AppendVecFormat::V1,
+            in_memory: false,
         }
     }
 
@@ -272,6 +503,9 @@ impl AppendVec {
     }
 
     pub fn flush(&self) -> io::Result<()> {
+        if self.in_memory {
+            return Ok(());
+        }
         self.map.flush()
     }
 
@@ -279,7 +513,8 @@ impl AppendVec {
         // This mutex forces append to be single threaded, but concurrent with reads
         // See UNSAFE usage in `append_ptr`
         let _lock = self.append_lock.lock().unwrap();
-        self.current_len.store(0, Ordering::Relaxed);
+        self.current_len
+            .store(self.format.header_len(), Ordering::Relaxed);
     }
 
     pub fn len(&self) -> usize {
@@ -287,13 +522,18 @@ impl AppendVec {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.len() <= self.format.header_len()
     }
 
     pub fn capacity(&self) -> u64 {
         self.file_size
     }
 
+    /// Offset of the first entry, which follows the header in versioned formats
+    pub fn first_account_offset(&self) -> usize {
+        self.format.header_len()
+    }
+
     pub fn file_name(slot: Slot, id: usize) -> String {
         format!("{}.{}", slot, id)
     }
@@ -317,6 +557,9 @@ impl AppendVec {
             result?
         };
 
+        // Files without a versioned header are read in the original V1 layout
+        let format = Self::detect_format(&map, current_len)?;
+
         let new = AppendVec {
             path: path.as_ref().to_path_buf(),
             map,
@@ -324,6 +567,8 @@ impl AppendVec {
             current_len: AtomicUsize::new(current_len),
             file_size,
             remove_on_drop: true,
+            format,
+            in_memory: false,
         };
 
         let (sanitized, num_accounts) = new.sanitize_layout_and_length();
@@ -338,16 +583,18 @@ impl AppendVec {
     }
 
     fn sanitize_layout_and_length(&self) -> (bool, usize) {
-        let mut offset = 0;
+        let mut offset = self.format.header_len();
 
         // This discards allocated accounts immediately after check at each loop iteration.
         //
         // This code should not reuse AppendVec.accounts() method as the current form or
         // extend it to be reused here because it would allow attackers to accumulate
         // some measurable amount of memory needlessly.
+        //
+        // Compressed entries are checked as stored, without decompressing them.
         let mut num_accounts = 0;
-        while let Some((account, next_offset)) = self.get_account(offset) {
-            if !account.sanitize() {
+        while let Some((account, info, next_offset)) = self.get_stored_entry(offset) {
+            if !account.sanitize() || !Self::verify_checksum(&account, info) {
                 return (false, num_accounts);
             }
             offset = next_offset;
@@ -358,6 +605,22 @@ impl AppendVec {
         (offset == aligned_current_len, num_accounts)
     }
 
+    /// Verify the checksum of an entry as returned by `get_stored_entry()`. Always true for
+    /// formats without checksums. Torn writes and on-disk corruption are detected here when the
+    /// file is loaded.
+    fn verify_checksum(account: &StoredAccountMeta, info: Option<&StoredEntryInfo>) -> bool {
+        info.map(|info| {
+            info.checksum
+                == StoredEntryInfo::compute_checksum(
+                    account.meta,
+                    account.account_meta,
+                    account.hash,
+                    &account.data,
+                )
+        })
+        .unwrap_or(true)
+    }
+
     /// Get a reference to the data at `offset` of `size` bytes if that slice
     /// doesn't overrun the internal buffer. Otherwise return None.
     /// Also return the offset of the first byte after the requested data that
@@ -431,23 +694,54 @@ impl AppendVec {
     /// the internal buffer. Otherwise return None. Also return the offset of the first byte
     /// after the requested data that falls on a 64-byte boundary.
     pub fn get_account<'a>(&'a self, offset: usize) -> Option<(StoredAccountMeta<'a>, usize)> {
+        let (mut account, info, next) = self.get_stored_entry(offset)?;
+        if info.map_or(false, |info| info.is_compressed()) {
+            // decompressed into a buffer owned by the returned account, so that reading
+            // doesn't grow the memory held by the storage
+            let data = zstd::block::decompress(&account.data, account.meta.data_len as usize)
+                .ok()
+                .filter(|data| data.len() as u64 == account.meta.data_len)?;
+            account.data = Cow::Owned(data);
+        }
+        Some((account, next))
+    }
+
+    /// Like `get_account()`, but the data is returned as stored, compressed or not, along with
+    /// the entry info of versioned formats.
+    fn get_stored_entry<'a>(
+        &'a self,
+        offset: usize,
+    ) -> Option<(StoredAccountMeta<'a>, Option<&'a StoredEntryInfo>, usize)> {
+        // the header isn't an entry, see `first_account_offset()`
+        if offset < self.format.header_len() {
+            return None;
+        }
         let (meta, next): (&'a StoredMeta, _) = self.get_type(offset)?;
         let (account_meta, next): (&'a AccountMeta, _) = self.get_type(next)?;
         let (hash, next): (&'a Hash, _) = self.get_type(next)?;
-        let (data, next) = self.get_slice(next, meta.data_len as usize)?;
+        let (info, data_len, next) = match self.format {
+            AppendVecFormat::V1 => (None, meta.data_len, next),
+            AppendVecFormat::V2 { .. } => {
+                let (info, next): (&'a StoredEntryInfo, _) = self.get_type(next)?;
+                (Some(info), info.stored_data_len, next)
+            }
+        };
+        let (data, next) = self.get_slice(next, data_len as usize)?;
         let stored_size = next - offset;
         Some((
             StoredAccountMeta {
                 meta,
                 account_meta,
-                data,
+                data: Cow::Borrowed(data),
                 offset,
                 stored_size,
                 hash,
             },
+            info,
             next,
         ))
     }
+
     pub fn get_account_test(&self, offset: usize) -> Option<(StoredMeta, AccountSharedData)> {
         let (stored_account, _) = self.get_account(offset)?;
         let meta = stored_account.meta.clone();
@@ -490,12 +784,47 @@ impl AppendVec {
                 .unwrap_or_default()
                 .as_ptr();
             let hash_ptr = hash.borrow().as_ref().as_ptr();
-            let ptrs = [
+            let mut ptrs = vec![
                 (meta_ptr as *const u8, mem::size_of::<StoredMeta>()),
                 (account_meta_ptr as *const u8, mem::size_of::<AccountMeta>()),
                 (hash_ptr as *const u8, mem::size_of::<Hash>()),
-                (data_ptr, data_len),
             ];
+            // keep the compressed data and the entry info alive until they're copied
+            let compressed_data;
+            let entry_info;
+            match self.format {
+                AppendVecFormat::V1 => ptrs.push((data_ptr, data_len)),
+                AppendVecFormat::V2 {
+                    compression_threshold,
+                } => {
+                    let data = account.map(|account| account.data()).unwrap_or_default();
+                    compressed_data = compression_threshold
+                        .filter(|threshold| data_len >= *threshold)
+                        .and_then(|_| {
+                            zstd::block::compress(data, APPEND_VEC_COMPRESSION_LEVEL).ok()
+                        })
+                        .filter(|compressed| compressed.len() < data_len);
+                    let (stored_data, flags) = match &compressed_data {
+                        Some(compressed) => (&compressed[..], ENTRY_FLAG_ZSTD_COMPRESSED),
+                        None => (data, 0),
+                    };
+                    entry_info = StoredEntryInfo {
+                        checksum: StoredEntryInfo::compute_checksum(
+                            stored_meta,
+                            &account_meta,
+                            hash.borrow(),
+                            stored_data,
+                        ),
+                        stored_data_len: stored_data.len() as u64,
+                        flags,
+                    };
+                    ptrs.push((
+                        &entry_info as *const StoredEntryInfo as *const u8,
+                        mem::size_of::<StoredEntryInfo>(),
+                    ));
+                    ptrs.push((stored_data.as_ptr(), stored_data.len()));
+                }
+            }
             if let Some(res) = self.append_ptrs_locked(&mut offset, &ptrs) {
                 rv.push(res)
             } else {
@@ -585,6 +914,11 @@ pub mod tests {
     use solana_sdk::{account::WritableAccount, timing::duration_as_ms};
     use std::time::Instant;
 
+    const STORE_META_OVERHEAD_V2: usize = mem::size_of::<StoredMeta>()
+        + mem::size_of::<AccountMeta>()
+        + mem::size_of::<Hash>()
+        + mem::size_of::<StoredEntryInfo>();
+
     impl AppendVec {
         fn append_account_test(&self, data: &(StoredMeta, AccountSharedData)) -> Option<usize> {
             self.append_account(data.0.clone(), &data.1, Hash::default())
@@ -777,6 +1111,113 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_append_vec_v2_round_trip() {
+        let file = get_append_vec_path("test_append_vec_v2_round_trip");
+        let path = &file.path;
+        let format = AppendVecFormat::V2 {
+            compression_threshold: Some(64),
+        };
+        let mut av = AppendVec::new_with_format(path, true, 1024 * 1024, format);
+        av.set_no_remove_on_drop();
+        assert!(av.is_empty());
+        assert_eq!(av.format(), format);
+
+        let mut indexes = vec![];
+        for sample in 0..300 {
+            let account = create_test_account(sample);
+            let index = av.append_account_test(&account).unwrap();
+            assert_eq!(av.get_account_test(index).unwrap(), account);
+            indexes.push(index);
+        }
+        // the first entry follows the file header
+        assert_eq!(indexes[0], mem::size_of::<AppendVecHeader>());
+
+        // data above the threshold is compressed and decompressed transparently
+        let (stored_account, _) = av.get_account(indexes[255]).unwrap();
+        assert!(
+            stored_account.stored_size
+                < stored_account.meta.data_len as usize + STORE_META_OVERHEAD_V2
+        );
+
+        // the header isn't an entry
+        assert!(av.get_account(0).is_none());
+        assert_eq!(av.first_account_offset(), indexes[0]);
+
+        av.flush().unwrap();
+        let accounts_len = av.len();
+        drop(av);
+        let (av, num_accounts) = AppendVec::new_from_file(path, accounts_len).unwrap();
+        assert_eq!(num_accounts, indexes.len());
+        assert_eq!(
+            av.format(),
+            AppendVecFormat::V2 {
+                compression_threshold: None
+            }
+        );
+        for (sample, v) in av.accounts(av.first_account_offset()).iter().enumerate() {
+            assert_eq!(v.clone_account(), create_test_account(sample).1);
+        }
+    }
+
+    #[test]
+    fn test_append_vec_v1_read_compat() {
+        let file = get_append_vec_path("test_append_vec_v1_read_compat");
+        let path = &file.path;
+        let mut av = AppendVec::new(path, true, 1024 * 1024);
+        av.set_no_remove_on_drop();
+        av.append_account_test(&create_test_account(10)).unwrap();
+        av.flush().unwrap();
+        let accounts_len = av.len();
+        drop(av);
+        let (av, num_accounts) = AppendVec::new_from_file(path, accounts_len).unwrap();
+        assert_eq!(num_accounts, 1);
+        assert_eq!(av.format(), AppendVecFormat::V1);
+    }
+
+    #[test]
+    fn test_append_vec_in_memory() {
+        let file = get_append_vec_path("test_append_vec_in_memory");
+        let path = &file.path;
+        let av = AppendVec::new_in_memory(path, 1024 * 1024, AppendVecFormat::V1);
+        assert!(av.is_in_memory());
+        let account = create_test_account(5);
+        let index = av.append_account_test(&account).unwrap();
+        assert_eq!(av.get_account_test(index).unwrap(), account);
+        av.flush().unwrap();
+        assert!(!path.exists());
+
+        // persisted contents load like any file-backed storage
+        av.write_to_file(path).unwrap();
+        let accounts_len = av.len();
+        drop(av);
+        let (av, num_accounts) = AppendVec::new_from_file(path, accounts_len).unwrap();
+        assert!(!av.is_in_memory());
+        assert_eq!(num_accounts, 1);
+        assert_eq!(av.get_account_test(index).unwrap(), account);
+    }
+
+    #[test]
+    fn test_new_from_file_v2_corrupted_data() {
+        let file = get_append_vec_path("test_new_from_file_v2_corrupted_data");
+        let path = &file.path;
+        let format = AppendVecFormat::V2 {
+            compression_threshold: None,
+        };
+        let mut av = AppendVec::new_with_format(path, true, 1024 * 1024, format);
+        av.set_no_remove_on_drop();
+        av.append_account_test(&create_test_account(10)).unwrap();
+        let accounts_len = av.len();
+
+        // flip the last data byte, as a torn write would leave it
+        av.map[accounts_len - 1] ^= 0xff;
+
+        av.flush().unwrap();
+        drop(av);
+        let result = AppendVec::new_from_file(path, accounts_len);
+        assert_matches!(result, Err(ref message) if message.to_string() == *"incorrect layout/length/data");
+    }
+
     #[test]
     fn test_new_from_file_crafted_zero_lamport_account() {
         let file = get_append_vec_path("test_append");
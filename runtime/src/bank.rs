@@ -34,18 +34,21 @@
 //! on behalf of the caller, and a low-level API for when they have
 //! already been signed and verified.
 use crate::{
-    accounts::{AccountAddressFilter, Accounts, TransactionAccounts, TransactionLoadResult},
+    accounts::{
+        prepare_if_nonce_account, AccountAddressFilter, AccountOverrides, Accounts,
+        TransactionAccounts, TransactionLoadResult,
+    },
     accounts_db::{
-        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, SnapshotStorages,
-        ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS, ACCOUNTS_DB_CONFIG_FOR_TESTING,
+        AccountShrinkThreshold, AccountsDbConfig, BankHashVerificationError, ErrorCounters,
+        SnapshotStorages, ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS, ACCOUNTS_DB_CONFIG_FOR_TESTING,
     },
-    accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
+    accounts_index::{AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult},
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     ancestors::{Ancestors, AncestorsForSerialization},
     blockhash_queue::BlockhashQueue,
-    builtins::{self, ActivationType, Builtin, Builtins},
+    builtins::{self, ActivationType, Builtin, BuiltinMigration, Builtins},
     cost_tracker::CostTracker,
-    epoch_stakes::{EpochStakes, NodeVoteAccounts},
+    epoch_stakes::{BankEpochStakes, EpochStakes, NodeVoteAccounts},
     inline_spl_token_v2_0,
     message_processor::MessageProcessor,
     rent_collector::RentCollector,
@@ -74,6 +77,7 @@ use solana_program_runtime::{
     instruction_recorder::InstructionRecorder,
     log_collector::LogCollector,
 };
+use solana_rayon_threadlimit::thread_pools::{ManagedThreadPool, EPOCH_BOUNDARY_THREAD_POOL};
 #[allow(deprecated)]
 use solana_sdk::recent_blockhashes_account;
 use solana_sdk::{
@@ -82,21 +86,24 @@ use solana_sdk::{
         AccountSharedData, InheritableAccountFields, ReadableAccount, WritableAccount,
     },
     account_utils::StateMut,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::{
         BankId, Epoch, Slot, SlotCount, SlotIndex, UnixTimestamp, DEFAULT_TICKS_PER_SECOND,
         INITIAL_RENT_EPOCH, MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES,
         MAX_TRANSACTION_FORWARDING_DELAY, SECONDS_PER_DAY,
     },
-    compute_budget::ComputeBudget,
+    compute_budget::{ComputeBudget, ComputeBudgetExceeded},
     ed25519_program,
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
-    feature_set::{self, disable_fee_calculator, tx_wide_compute_cap, FeatureSet},
+    feature_set::{
+        self, charge_compute_unit_price, disable_fee_calculator, tx_wide_compute_cap, FeatureSet,
+    },
     fee_calculator::{FeeCalculator, FeeRateGovernor},
     genesis_config::{ClusterType, GenesisConfig},
     hard_forks::HardForks,
-    hash::{extend_and_hash, hashv, Hash},
+    hash::{extend_and_hash, hash, hashv, Hash},
     incinerator,
     inflation::Inflation,
     instruction::{CompiledInstruction, InstructionError},
@@ -230,7 +237,7 @@ impl ExecuteTimings {
 }
 
 type BankStatusCache = StatusCache<Result<()>>;
-#[frozen_abi(digest = "5Br3PNyyX1L7XoS4jYLt5JTeMXowLSsu7v9LhokC8vnq")]
+#[frozen_abi(digest = "9rjNMoUcRdPW8BwSSEbWuzowrQ1oHiYeFAWkNGnh7sYm")]
 pub type BankSlotDelta = SlotDelta<Result<()>>;
 type TransactionAccountRefCells = Vec<(Pubkey, Rc<RefCell<AccountSharedData>>)>;
 
@@ -301,30 +308,69 @@ impl CowCachedExecutors {
     }
 }
 
-const MAX_CACHED_EXECUTORS: usize = 100; // 10 MB assuming programs are around 100k
+pub const MAX_CACHED_EXECUTORS: usize = 100; // 10 MB assuming programs are around 100k
 #[derive(Debug)]
 struct CachedExecutorsEntry {
+    /// Cache age at the time the entry was inserted
+    base_count: u64,
     prev_epoch_count: u64,
     epoch_count: AtomicU64,
     executor: Arc<dyn Executor>,
 }
-/// LFU Cache of executors with single-epoch memory of usage counts
+impl CachedExecutorsEntry {
+    fn score(&self) -> u64 {
+        self.base_count
+            .saturating_add(self.prev_epoch_count)
+            .saturating_add(self.epoch_count.load(Relaxed))
+    }
+}
+/// LFU Cache of executors with single-epoch memory of usage counts.
+///
+/// Eviction uses dynamic aging: the cache age is raised to the score of every evicted entry and
+/// new entries start from the current age. A flood of freshly deployed programs then only
+/// displaces each other instead of pushing out programs that are still in use.
 #[derive(Debug)]
 struct CachedExecutors {
     max: usize,
     current_epoch: Epoch,
+    age: u64,
     executors: HashMap<Pubkey, CachedExecutorsEntry>,
+    /// Programs removed from this cache since it was cloned; never reinstated by `prewarm`
+    invalidated: HashSet<Pubkey>,
 }
 impl Default for CachedExecutors {
     fn default() -> Self {
         Self {
             max: MAX_CACHED_EXECUTORS,
             current_epoch: 0,
+            age: 0,
             executors: HashMap::new(),
+            invalidated: HashSet::new(),
         }
     }
 }
 
+/// Per-bank executor cache activity, reported when the bank is frozen
+#[derive(AbiExample, Debug, Default)]
+struct CachedExecutorsStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    prewarmed: AtomicU64,
+}
+impl CachedExecutorsStats {
+    fn report(&self, slot: Slot) {
+        datapoint_info!(
+            "bank-executor-cache-stats",
+            ("slot", slot, i64),
+            ("hits", self.hits.load(Relaxed), i64),
+            ("misses", self.misses.load(Relaxed), i64),
+            ("evictions", self.evictions.load(Relaxed), i64),
+            ("prewarmed", self.prewarmed.load(Relaxed), i64),
+        );
+    }
+}
+
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
 impl AbiExample for CachedExecutors {
     fn example() -> Self {
@@ -354,6 +400,7 @@ impl CachedExecutors {
                 executors.insert(
                     *key,
                     CachedExecutorsEntry {
+                        base_count: entry.base_count,
                         prev_epoch_count: entry.epoch_count.load(Relaxed),
                         epoch_count: AtomicU64::new(0),
                         executor: entry.executor.clone(),
@@ -363,6 +410,7 @@ impl CachedExecutors {
                 executors.insert(
                     *key,
                     CachedExecutorsEntry {
+                        base_count: entry.base_count,
                         prev_epoch_count: entry.prev_epoch_count,
                         epoch_count: AtomicU64::new(entry.epoch_count.load(Relaxed)),
                         executor: entry.executor.clone(),
@@ -373,7 +421,9 @@ impl CachedExecutors {
         Self {
             max: self.max,
             current_epoch: epoch,
+            age: self.age,
             executors,
+            invalidated: HashSet::new(),
         }
     }
 
@@ -381,7 +431,9 @@ impl CachedExecutors {
         Self {
             max,
             current_epoch,
+            age: 0,
             executors: HashMap::new(),
+            invalidated: HashSet::new(),
         }
     }
     fn get(&self, pubkey: &Pubkey) -> Option<Arc<dyn Executor>> {
@@ -390,33 +442,85 @@ impl CachedExecutors {
             entry.executor.clone()
         })
     }
-    fn put(&mut self, pubkey: &Pubkey, executor: Arc<dyn Executor>) {
-        if !self.executors.contains_key(pubkey) && self.executors.len() >= self.max {
-            let mut least = u64::MAX;
-            let default_key = Pubkey::default();
-            let mut least_key = &default_key;
-
-            for (key, entry) in self.executors.iter() {
-                let count = entry.prev_epoch_count + entry.epoch_count.load(Relaxed);
-                if count < least {
-                    least = count;
-                    least_key = key;
-                }
+    /// Evict the entry with the lowest score, returning its score
+    fn evict_one(&mut self) -> Option<u64> {
+        let (least_key, least) = self
+            .executors
+            .iter()
+            .map(|(key, entry)| (*key, entry.score()))
+            .min_by_key(|(_, score)| *score)?;
+        let _ = self.executors.remove(&least_key);
+        self.age = self.age.max(least);
+        Some(least)
+    }
+    /// Insert an executor, returning the number of entries evicted to make room for it
+    fn put(&mut self, pubkey: &Pubkey, executor: Arc<dyn Executor>) -> usize {
+        let mut evicted = 0;
+        if !self.executors.contains_key(pubkey) {
+            while self.executors.len() >= self.max && self.evict_one().is_some() {
+                evicted += 1;
             }
-            let least_key = *least_key;
-            let _ = self.executors.remove(&least_key);
         }
         let _ = self.executors.insert(
             *pubkey,
             CachedExecutorsEntry {
+                base_count: self.age,
                 prev_epoch_count: 0,
                 epoch_count: AtomicU64::new(0),
                 executor,
             },
         );
+        evicted
     }
     fn remove(&mut self, pubkey: &Pubkey) {
         let _ = self.executors.remove(pubkey);
+        self.invalidated.insert(*pubkey);
+    }
+    /// Change the capacity, evicting entries if the cache is over the new limit. Returns the
+    /// number of entries evicted.
+    fn set_max(&mut self, max: usize) -> usize {
+        self.max = max;
+        let mut evicted = 0;
+        while self.executors.len() > self.max && self.evict_one().is_some() {
+            evicted += 1;
+        }
+        evicted
+    }
+    /// Copy entries that `parent` holds but this cache doesn't, as long as they either fit or
+    /// score higher than the entry they would displace. Entries this cache invalidated are
+    /// skipped since the parent's copy may be stale. Returns the number of entries copied.
+    fn prewarm(&mut self, parent: &CachedExecutors) -> usize {
+        let mut candidates: Vec<(&Pubkey, &CachedExecutorsEntry)> = parent
+            .executors
+            .iter()
+            .filter(|(key, _)| !self.executors.contains_key(key) && !self.invalidated.contains(key))
+            .collect();
+        candidates.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.score()));
+
+        let mut prewarmed = 0;
+        for (key, entry) in candidates {
+            let score = entry.score();
+            if self.executors.len() >= self.max {
+                let least = self.executors.values().map(|entry| entry.score()).min();
+                match least {
+                    Some(least) if least < score => {
+                        self.evict_one();
+                    }
+                    _ => break,
+                }
+            }
+            self.executors.insert(
+                *key,
+                CachedExecutorsEntry {
+                    base_count: entry.base_count,
+                    prev_epoch_count: entry.prev_epoch_count,
+                    epoch_count: AtomicU64::new(entry.epoch_count.load(Relaxed)),
+                    executor: entry.executor.clone(),
+                },
+            );
+            prewarmed += 1;
+        }
+        prewarmed
     }
 }
 
@@ -519,13 +623,47 @@ pub type TransactionExecutionResult = (Result<()>, Option<NonceRollbackFull>);
 pub struct TransactionResults {
     pub fee_collection_results: Vec<Result<()>>,
     pub execution_results: Vec<TransactionExecutionResult>,
+    pub execution_details: Vec<TransactionExecutionDetails>,
     pub rent_debits: Vec<RentDebits>,
 }
+
+/// Breakdown of the fee of a transaction
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeDetails {
+    /// Fee for the signatures of the transaction
+    pub signature_fee: u64,
+    /// Fee for the signatures verified by the secp256k1 and ed25519 programs
+    pub precompile_signature_fee: u64,
+    /// Price of the requested compute units, offered with the SetComputeUnitPrice instruction
+    pub prioritization_fee: u64,
+}
+
+impl FeeDetails {
+    pub fn total_fee(&self) -> u64 {
+        self.signature_fee
+            .saturating_add(self.precompile_signature_fee)
+            .saturating_add(self.prioritization_fee)
+    }
+}
+
+/// Details of the execution of a transaction, for the consumers of the transaction results
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransactionExecutionDetails {
+    /// Compute units consumed by the instructions of the transaction
+    pub executed_units: u64,
+    /// Time spent executing the instructions of the transaction
+    pub execute_us: u64,
+    /// Total size of the data of the accounts loaded for the transaction, programs included
+    pub loaded_accounts_data_size: usize,
+    /// Fee charged for the transaction, if one was
+    pub fee_details: Option<FeeDetails>,
+}
 pub struct TransactionSimulationResult {
     pub result: Result<()>,
     pub logs: TransactionLogMessages,
     pub post_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
     pub units_consumed: u64,
+    pub compute_budget_exceeded: Option<ComputeBudgetExceeded>,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -578,6 +716,9 @@ pub struct TransactionLogInfo {
     pub result: Result<()>,
     pub is_vote: bool,
     pub log_messages: TransactionLogMessages,
+    // Programs invoked by the transaction, including those whose invoke messages were dropped
+    // from the truncated logs
+    pub invoked_programs: Vec<Pubkey>,
 }
 
 #[derive(AbiExample, Default, Debug)]
@@ -921,6 +1062,10 @@ pub struct Bank {
     /// The max number of transaction in an entry in this slot
     transactions_per_entry_max: AtomicU64,
 
+    /// The number of transactions that couldn't be locked because an account they use was
+    /// locked by another transaction, in this slot
+    account_in_use_count: AtomicU64,
+
     /// Bank tick height
     tick_height: AtomicU64,
 
@@ -1003,10 +1148,18 @@ pub struct Bank {
 
     compute_budget: Option<ComputeBudget>,
 
+    /// Whether transactions conflicting with an earlier transaction of their batch queue behind
+    /// it instead of failing with `AccountInUse`
+    queue_conflicting_transactions: bool,
+
     /// Builtin programs activated dynamically by feature
     #[allow(clippy::rc_buffer)]
     feature_builtins: Arc<Vec<(Builtin, Pubkey, ActivationType)>>,
 
+    /// Builtin programs replaced by a BPF implementation by feature
+    #[allow(clippy::rc_buffer)]
+    builtin_migrations: Arc<Vec<BuiltinMigration>>,
+
     /// Last time when the cluster info vote listener has synced with this bank
     pub last_vote_sync: AtomicU64,
 
@@ -1023,6 +1176,8 @@ pub struct Bank {
     /// Cached executors
     cached_executors: RwLock<CowCachedExecutors>,
 
+    cached_executors_stats: CachedExecutorsStats,
+
     transaction_debug_keys: Option<Arc<HashSet<Pubkey>>>,
 
     // Global configuration for how transaction logs should be collected across all banks
@@ -1069,8 +1224,24 @@ impl Bank {
     }
 
     pub fn new_for_benches(genesis_config: &GenesisConfig) -> Self {
-        // this will diverge
-        Self::new_for_tests(genesis_config)
+        // Benches don't need durable storages, so keep them in memory to avoid thrashing the
+        // filesystem
+        Self::new_with_paths(
+            genesis_config,
+            Vec::new(),
+            &[],
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+            false,
+            Some(AccountsDbConfig {
+                storages_in_memory: true,
+                ..ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS
+            }),
+            None,
+        )
     }
 
     pub fn new_for_tests(genesis_config: &GenesisConfig) -> Self {
@@ -1139,6 +1310,7 @@ impl Bank {
             transaction_error_count: AtomicU64::default(),
             transaction_entries_count: AtomicU64::default(),
             transactions_per_entry_max: AtomicU64::default(),
+            account_in_use_count: AtomicU64::default(),
             tick_height: AtomicU64::default(),
             signature_count: AtomicU64::default(),
             capitalization: AtomicU64::default(),
@@ -1166,13 +1338,16 @@ impl Bank {
             is_delta: AtomicBool::default(),
             instruction_processor: InstructionProcessor::default(),
             compute_budget: Option::<ComputeBudget>::default(),
+            queue_conflicting_transactions: bool::default(),
             feature_builtins: Arc::<Vec<(Builtin, Pubkey, ActivationType)>>::default(),
+            builtin_migrations: Arc::<Vec<BuiltinMigration>>::default(),
             last_vote_sync: AtomicU64::default(),
             rewards: RwLock::<Vec<(Pubkey, RewardInfo)>>::default(),
             cluster_type: Option::<ClusterType>::default(),
             lazy_rent_collection: AtomicBool::default(),
             rewards_pool_pubkeys: Arc::<HashSet<Pubkey>>::default(),
             cached_executors: RwLock::<CowCachedExecutors>::default(),
+            cached_executors_stats: CachedExecutorsStats::default(),
             transaction_debug_keys: Option::<Arc<HashSet<Pubkey>>>::default(),
             transaction_log_collector_config: Arc::<RwLock<TransactionLogCollectorConfig>>::default(
             ),
@@ -1402,6 +1577,7 @@ impl Bank {
             transaction_error_count: AtomicU64::new(0),
             transaction_entries_count: AtomicU64::new(0),
             transactions_per_entry_max: AtomicU64::new(0),
+            account_in_use_count: AtomicU64::new(0),
             // we will .clone_with_epoch() this soon after stake data update; so just .clone() for now
             stakes: RwLock::new(parent.stakes.read().unwrap().clone()),
             epoch_stakes: parent.epoch_stakes.clone(),
@@ -1416,7 +1592,9 @@ impl Bank {
             signature_count: AtomicU64::new(0),
             instruction_processor: parent.instruction_processor.clone(),
             compute_budget: parent.compute_budget,
+            queue_conflicting_transactions: parent.queue_conflicting_transactions,
             feature_builtins: parent.feature_builtins.clone(),
+            builtin_migrations: parent.builtin_migrations.clone(),
             hard_forks: parent.hard_forks.clone(),
             last_vote_sync: AtomicU64::new(parent.last_vote_sync.load(Relaxed)),
             rewards: RwLock::new(vec![]),
@@ -1426,6 +1604,7 @@ impl Bank {
             cached_executors: RwLock::new(
                 (*parent.cached_executors.read().unwrap()).clone_with_epoch(epoch),
             ),
+            cached_executors_stats: CachedExecutorsStats::default(),
             transaction_debug_keys: parent.transaction_debug_keys.clone(),
             transaction_log_collector_config: parent.transaction_log_collector_config.clone(),
             transaction_log_collector: Arc::new(RwLock::new(TransactionLogCollector::default())),
@@ -1471,7 +1650,18 @@ impl Bank {
 
         if optimize_epoch_boundary_updates {
             if parent_epoch < new.epoch() {
-                let thread_pool = ThreadPoolBuilder::new().build().unwrap();
+                let thread_pool = ManagedThreadPool::new(
+                    EPOCH_BOUNDARY_THREAD_POOL,
+                    num_cpus::get(),
+                    |num_threads| {
+                        ThreadPoolBuilder::new()
+                            .num_threads(num_threads)
+                            .thread_name(|i| format!("solana-epoch-boundary-{}", i))
+                            .build()
+                            .unwrap()
+                    },
+                )
+                .current();
 
                 // Add new entry to stakes.stake_history, set appropriate epoch and
                 //   update vote accounts with warmed up stakes before saving a
@@ -1586,6 +1776,7 @@ impl Bank {
             transaction_error_count: new(),
             transaction_entries_count: new(),
             transactions_per_entry_max: new(),
+            account_in_use_count: new(),
             tick_height: AtomicU64::new(fields.tick_height),
             signature_count: AtomicU64::new(fields.signature_count),
             capitalization: AtomicU64::new(fields.capitalization),
@@ -1614,7 +1805,9 @@ impl Bank {
             is_delta: AtomicBool::new(fields.is_delta),
             instruction_processor: new(),
             compute_budget: None,
+            queue_conflicting_transactions: false,
             feature_builtins: new(),
+            builtin_migrations: new(),
             last_vote_sync: new(),
             rewards: new(),
             cluster_type: Some(genesis_config.cluster_type),
@@ -1623,6 +1816,7 @@ impl Bank {
             cached_executors: RwLock::new(CowCachedExecutors::new(Arc::new(RwLock::new(
                 CachedExecutors::new(MAX_CACHED_EXECUTORS, fields.epoch),
             )))),
+            cached_executors_stats: CachedExecutorsStats::default(),
             transaction_debug_keys: debug_keys,
             transaction_log_collector_config: new(),
             transaction_log_collector: new(),
@@ -2904,6 +3098,7 @@ impl Bank {
             self.freeze_started.store(true, Relaxed);
             *hash = self.hash_internal_state();
             self.rc.accounts.accounts_db.mark_slot_frozen(self.slot());
+            self.cached_executors_stats.report(self.slot());
         }
     }
 
@@ -2955,6 +3150,9 @@ impl Bank {
         }
         squash_accounts_time.stop();
 
+        if let Some(parent) = self.parent() {
+            self.prewarm_executors(&parent);
+        }
         *self.rc.parent.write().unwrap() = None;
 
         let mut squash_cache_time = Measure::start("squash_cache_time");
@@ -3182,14 +3380,26 @@ impl Bank {
         let blockhash_queue = self.blockhash_queue.read().unwrap();
         let lamports_per_signature =
             blockhash_queue.get_lamports_per_signature(message.recent_blockhash())?;
-        Some(Self::calculate_fee(message, lamports_per_signature))
+        Some(Self::calculate_fee_with_feature_set(
+            message,
+            lamports_per_signature,
+            &self.feature_set,
+        ))
     }
 
+    #[deprecated(
+        since = "1.9.0",
+        note = "Please use `calculate_fee_with_feature_set` instead"
+    )]
     pub fn get_fee_for_message_with_lamports_per_signature(
         message: &SanitizedMessage,
         lamports_per_signature: u64,
     ) -> u64 {
-        Self::calculate_fee(message, lamports_per_signature)
+        Self::calculate_fee_with_feature_set(
+            message,
+            lamports_per_signature,
+            &FeatureSet::default(),
+        )
     }
 
     #[deprecated(
@@ -3357,6 +3567,25 @@ impl Bank {
         TransactionBatch::new(lock_results, self, Cow::Borrowed(txs))
     }
 
+    /// Prepare a locked transaction batch from the longest prefix of `txs` without conflicting
+    /// transactions, if conflicting transactions are queued. The rest of `txs` is left to
+    /// later batches, so that each transaction executes after those it conflicts with rather
+    /// than failing with `AccountInUse`.
+    pub fn prepare_queued_sanitized_batch<'a, 'b>(
+        &'a self,
+        txs: &'b [SanitizedTransaction],
+    ) -> TransactionBatch<'a, 'b> {
+        if !self.queue_conflicting_transactions {
+            return self.prepare_sanitized_batch(txs);
+        }
+        let lock_results = self
+            .rc
+            .accounts
+            .lock_accounts_until_conflict(txs.iter(), self.demote_program_write_locks());
+        let txs = &txs[..lock_results.len()];
+        TransactionBatch::new(lock_results, self, Cow::Borrowed(txs))
+    }
+
     /// Prepare a transaction batch without locking accounts for transaction simulation.
     pub(crate) fn prepare_simulation_batch<'a>(
         &'a self,
@@ -3372,6 +3601,134 @@ impl Bank {
         &self,
         transaction: SanitizedTransaction,
     ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_account_overrides(transaction, None)
+            .0
+    }
+
+    /// Run the transactions of a bundle one after the other against a frozen bank without
+    /// committing the results, each transaction loading the accounts written by the previous
+    /// ones, including the withdrawn fees and advanced nonces. The bundle is atomic: the
+    /// simulation stops at the first transaction that fails, and the transactions after it have
+    /// no result. A transaction signed like an earlier one of the bundle fails as already
+    /// processed.
+    pub fn simulate_bundle(
+        &self,
+        transactions: Vec<SanitizedTransaction>,
+    ) -> Vec<Option<TransactionSimulationResult>> {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        let demote_program_write_locks = self.demote_program_write_locks();
+        let mut account_overrides = AccountOverrides::default();
+        let mut signatures = HashSet::new();
+        let mut failed = false;
+        transactions
+            .into_iter()
+            .map(|transaction| {
+                if failed {
+                    return None;
+                }
+                if let Err(err) =
+                    self.check_bundle_transaction(&transaction, &mut signatures, &account_overrides)
+                {
+                    failed = true;
+                    return Some(TransactionSimulationResult {
+                        result: Err(err),
+                        logs: vec![],
+                        post_simulation_accounts: vec![],
+                        units_consumed: 0,
+                        compute_budget_exceeded: None,
+                    });
+                }
+                let message = transaction.message();
+                let written_accounts: Vec<bool> = (0..message.account_keys_len())
+                    .map(|i| {
+                        message.is_non_loader_key(i)
+                            && message.is_writable(i, demote_program_write_locks)
+                    })
+                    .collect();
+                let (simulation_result, nonce_rollback) = self
+                    .simulate_transaction_with_account_overrides(
+                        transaction,
+                        Some(&account_overrides),
+                    );
+                if simulation_result.result.is_ok() {
+                    for ((pubkey, account), _) in simulation_result
+                        .post_simulation_accounts
+                        .iter()
+                        .zip(written_accounts)
+                        .filter(|(_, written)| *written)
+                    {
+                        // Closed accounts load as if they were never stored
+                        let account = if account.lamports() == 0 {
+                            AccountSharedData::default()
+                        } else {
+                            account.clone()
+                        };
+                        account_overrides.insert(*pubkey, account);
+                    }
+                    // Advance the nonce like committing the transaction would
+                    if let Some(nonce_rollback) = nonce_rollback {
+                        let nonce_address = nonce_rollback.nonce_address();
+                        if let Some(nonce_account) = account_overrides.get_mut(nonce_address) {
+                            prepare_if_nonce_account(
+                                nonce_account,
+                                nonce_address,
+                                &Ok(()),
+                                Some((
+                                    nonce_address,
+                                    nonce_rollback.nonce_account(),
+                                    nonce_rollback.fee_account(),
+                                    true,
+                                )),
+                                &self.last_blockhash(),
+                                self.get_lamports_per_signature(),
+                            );
+                        }
+                    }
+                } else {
+                    failed = true;
+                }
+                Some(simulation_result)
+            })
+            .collect()
+    }
+
+    /// Checks what the simulation of a bundle transaction can't see on its own: whether an
+    /// earlier transaction of the bundle had the same signature, and whether its durable nonce
+    /// was already advanced by an earlier transaction
+    fn check_bundle_transaction(
+        &self,
+        transaction: &SanitizedTransaction,
+        signatures: &mut HashSet<Signature>,
+        account_overrides: &AccountOverrides,
+    ) -> Result<()> {
+        if !signatures.insert(*transaction.signature()) {
+            return Err(TransactionError::AlreadyProcessed);
+        }
+        let recent_blockhash = transaction.message().recent_blockhash();
+        let advanced_nonce = transaction
+            .get_durable_nonce()
+            .and_then(|nonce_pubkey| account_overrides.get(nonce_pubkey))
+            .map(|nonce_account| {
+                !nonce_account::verify_nonce_account(nonce_account, recent_blockhash)
+            })
+            .unwrap_or(false);
+        if advanced_nonce
+            && self.check_hash_age(
+                recent_blockhash,
+                MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
+            ) != Some(true)
+        {
+            return Err(TransactionError::BlockhashNotFound);
+        }
+        Ok(())
+    }
+
+    fn simulate_transaction_with_account_overrides(
+        &self,
+        transaction: SanitizedTransaction,
+        account_overrides: Option<&AccountOverrides>,
+    ) -> (TransactionSimulationResult, Option<NonceRollbackFull>) {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
         let number_of_accounts = transaction.message().account_keys_len();
@@ -3383,10 +3740,12 @@ impl Bank {
             executed,
             _inner_instructions,
             logs,
+            compute_budget_exceeded,
+            execution_details,
             _retryable_transactions,
             _transaction_count,
             _signature_count,
-        ) = self.load_and_execute_transactions(
+        ) = self.load_and_execute_transactions_with_account_overrides(
             &batch,
             // After simulation, transactions will need to be forwarded to the leader
             // for processing. During forwarding, the transaction could expire if the
@@ -3395,10 +3754,13 @@ impl Bank {
             false,
             true,
             &mut timings,
+            account_overrides,
         );
 
-        let result = executed[0].0.clone().map(|_| ());
+        let (result, nonce_rollback) = executed.into_iter().next().unwrap();
+        let units_consumed = execution_details[0].executed_units;
         let logs = logs.get(0).cloned().flatten().unwrap_or_default();
+        let compute_budget_exceeded = compute_budget_exceeded.into_iter().next().flatten();
         let post_simulation_accounts = loaded_transactions
             .into_iter()
             .next()
@@ -3414,22 +3776,18 @@ impl Bank {
             })
             .unwrap_or_default();
 
-        let units_consumed = timings
-            .details
-            .per_program_timings
-            .iter()
-            .fold(0, |acc: u64, (_, program_timing)| {
-                acc.saturating_add(program_timing.accumulated_units)
-            });
-
         debug!("simulate_transaction: {:?}", timings);
 
-        TransactionSimulationResult {
-            result,
-            logs,
-            post_simulation_accounts,
-            units_consumed,
-        }
+        (
+            TransactionSimulationResult {
+                result,
+                logs,
+                post_simulation_accounts,
+                units_consumed,
+                compute_budget_exceeded,
+            },
+            nonce_rollback,
+        )
     }
 
     pub fn unlock_accounts(&self, batch: &mut TransactionBatch) {
@@ -3639,6 +3997,12 @@ impl Bank {
                 error_counters.invalid_writable_account
             );
         }
+        if 0 != error_counters.max_loaded_accounts_data_size_exceeded {
+            inc_new_counter_info!(
+                "bank-process_transactions-error-max_loaded_accounts_data_size_exceeded",
+                error_counters.max_loaded_accounts_data_size_exceeded
+            );
+        }
     }
 
     /// Converts Accounts into RefCell<AccountSharedData>, this involves moving
@@ -3715,6 +4079,9 @@ impl Bank {
                 }
             }
         }
+        self.cached_executors_stats
+            .hits
+            .fetch_add(executors.len() as u64, Relaxed);
 
         Rc::new(RefCell::new(Executors {
             executors,
@@ -3728,9 +4095,20 @@ impl Bank {
         if executors.is_dirty {
             let mut cow_cache = self.cached_executors.write().unwrap();
             let mut cache = cow_cache.write().unwrap();
+            let mut misses = 0;
+            let mut evictions = 0;
             for (key, executor) in executors.executors.iter() {
-                cache.put(key, (*executor).clone());
+                if !cache.executors.contains_key(key) {
+                    misses += 1;
+                }
+                evictions += cache.put(key, (*executor).clone());
             }
+            self.cached_executors_stats
+                .misses
+                .fetch_add(misses, Relaxed);
+            self.cached_executors_stats
+                .evictions
+                .fetch_add(evictions as u64, Relaxed);
         }
     }
 
@@ -3741,6 +4119,31 @@ impl Bank {
         cache.remove(pubkey);
     }
 
+    /// Set how many executors the cache holds. Banks derived from this one inherit the capacity.
+    pub fn set_executor_cache_capacity(&self, capacity: usize) {
+        let mut cow_cache = self.cached_executors.write().unwrap();
+        let mut cache = cow_cache.write().unwrap();
+        let evicted = cache.set_max(capacity);
+        self.cached_executors_stats
+            .evictions
+            .fetch_add(evicted as u64, Relaxed);
+    }
+
+    /// Carry executors cached by `parent` that this bank's cache no longer holds, so that
+    /// programs evicted on this fork don't all need to be recompiled once it is rooted
+    fn prewarm_executors(&self, parent: &Bank) {
+        let parent_cow_cache = parent.cached_executors.read().unwrap();
+        let mut cow_cache = self.cached_executors.write().unwrap();
+        if Arc::ptr_eq(&parent_cow_cache.executors, &cow_cache.executors) {
+            return;
+        }
+        let parent_cache = parent_cow_cache.read().unwrap();
+        let prewarmed = cow_cache.write().unwrap().prewarm(&parent_cache);
+        self.cached_executors_stats
+            .prewarmed
+            .fetch_add(prewarmed as u64, Relaxed);
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn load_and_execute_transactions(
         &self,
@@ -3754,6 +4157,38 @@ impl Bank {
         Vec<TransactionExecutionResult>,
         Vec<Option<InnerInstructionsList>>,
         Vec<Option<TransactionLogMessages>>,
+        Vec<Option<ComputeBudgetExceeded>>,
+        Vec<TransactionExecutionDetails>,
+        Vec<usize>,
+        u64,
+        u64,
+    ) {
+        self.load_and_execute_transactions_with_account_overrides(
+            batch,
+            max_age,
+            enable_cpi_recording,
+            enable_log_recording,
+            timings,
+            None,
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn load_and_execute_transactions_with_account_overrides(
+        &self,
+        batch: &TransactionBatch,
+        max_age: usize,
+        enable_cpi_recording: bool,
+        enable_log_recording: bool,
+        timings: &mut ExecuteTimings,
+        account_overrides: Option<&AccountOverrides>,
+    ) -> (
+        Vec<TransactionLoadResult>,
+        Vec<TransactionExecutionResult>,
+        Vec<Option<InnerInstructionsList>>,
+        Vec<Option<TransactionLogMessages>>,
+        Vec<Option<ComputeBudgetExceeded>>,
+        Vec<TransactionExecutionDetails>,
         Vec<usize>,
         u64,
         u64,
@@ -3776,6 +4211,8 @@ impl Bank {
                 Ok(_) => None,
             })
             .collect();
+        self.account_in_use_count
+            .fetch_add(error_counters.account_in_use as u64, Relaxed);
 
         let mut check_time = Measure::start("check_transactions");
         let check_results = self.check_transactions(
@@ -3795,6 +4232,7 @@ impl Bank {
             &mut error_counters,
             &self.rent_collector,
             &self.feature_set,
+            account_overrides,
         );
         load_time.stop();
 
@@ -3804,6 +4242,16 @@ impl Bank {
             Vec::with_capacity(sanitized_txs.len());
         let mut transaction_log_messages: Vec<Option<Vec<String>>> =
             Vec::with_capacity(sanitized_txs.len());
+        let mut transaction_invoked_programs: Vec<Vec<Pubkey>> =
+            Vec::with_capacity(sanitized_txs.len());
+        let mut compute_budget_exceeded: Vec<Option<ComputeBudgetExceeded>> =
+            Vec::with_capacity(sanitized_txs.len());
+        let mut execution_details: Vec<TransactionExecutionDetails> =
+            Vec::with_capacity(sanitized_txs.len());
+        let epoch_stakes = BankEpochStakes {
+            epoch_stakes: self.epoch_stakes(self.epoch()),
+            epoch_start_stakes: self.epoch_stakes(self.get_leader_schedule_epoch(self.slot())),
+        };
 
         let executed: Vec<TransactionExecutionResult> = loaded_txs
             .iter_mut()
@@ -3811,11 +4259,22 @@ impl Bank {
             .map(|(accs, tx)| match accs {
                 (Err(e), _nonce_rollback) => {
                     transaction_log_messages.push(None);
+                    transaction_invoked_programs.push(Vec::new());
                     inner_instructions.push(None);
+                    compute_budget_exceeded.push(None);
+                    execution_details.push(TransactionExecutionDetails::default());
                     (Err(e.clone()), None)
                 }
                 (Ok(loaded_transaction), nonce_rollback) => {
                     let feature_set = self.feature_set.clone();
+                    let mut tx_execution_details = TransactionExecutionDetails {
+                        loaded_accounts_data_size: loaded_transaction
+                            .accounts
+                            .iter()
+                            .map(|(_, account)| account.data().len())
+                            .sum(),
+                        ..TransactionExecutionDetails::default()
+                    };
                     signature_count += u64::from(tx.message().header().num_required_signatures);
 
                     let mut compute_budget = self.compute_budget.unwrap_or_else(ComputeBudget::new);
@@ -3866,6 +4325,11 @@ impl Bank {
                             )
                         };
 
+                        let mut tx_compute_budget_exceeded = None;
+                        // The instructions are timed as they execute, the time and units of the
+                        // transaction are those accumulated while processing its message
+                        let pre_executed_units = timings.details.accumulated_units();
+                        let pre_execute_us = timings.details.accumulated_us();
                         if let Some(legacy_message) = tx.message().legacy_message() {
                             process_result = MessageProcessor::process_message(
                                 &self.instruction_processor,
@@ -3883,13 +4347,30 @@ impl Bank {
                                 &*self.sysvar_cache.read().unwrap(),
                                 blockhash,
                                 lamports_per_signature,
+                                Some(&epoch_stakes),
+                                &mut tx_compute_budget_exceeded,
                             );
                         } else {
                             // TODO: support versioned messages
                             process_result = Err(TransactionError::UnsupportedVersion);
                         }
-
+                        tx_execution_details.execute_us = timings
+                            .details
+                            .accumulated_us()
+                            .saturating_sub(pre_execute_us);
+                        tx_execution_details.executed_units = timings
+                            .details
+                            .accumulated_units()
+                            .saturating_sub(pre_executed_units);
+
+                        transaction_invoked_programs.push(
+                            log_collector
+                                .as_ref()
+                                .map(|log_collector| log_collector.invoked_programs())
+                                .unwrap_or_default(),
+                        );
                         transaction_log_messages.push(Self::collect_log_messages(log_collector));
+                        compute_budget_exceeded.push(tx_compute_budget_exceeded);
                         inner_instructions.push(Self::compile_recorded_instructions(
                             instruction_recorders,
                             tx.message(),
@@ -3908,8 +4389,11 @@ impl Bank {
                         }
                     } else {
                         transaction_log_messages.push(None);
+                        transaction_invoked_programs.push(Vec::new());
                         inner_instructions.push(None);
+                        compute_budget_exceeded.push(None);
                     }
+                    execution_details.push(tx_execution_details);
 
                     let nonce_rollback =
                         if let Err(TransactionError::InstructionError(_, _)) = &process_result {
@@ -3994,6 +4478,7 @@ impl Bank {
                             result: r.clone(),
                             is_vote,
                             log_messages,
+                            invoked_programs: transaction_invoked_programs[i].clone(),
                         });
                     }
                 }
@@ -4021,6 +4506,8 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_log_messages,
+            compute_budget_exceeded,
+            execution_details,
             retryable_txs,
             tx_count,
             signature_count,
@@ -4028,31 +4515,96 @@ impl Bank {
     }
 
     /// Calculate fee for `SanitizedMessage`
+    #[deprecated(
+        since = "1.9.0",
+        note = "Please use `calculate_fee_with_feature_set` instead"
+    )]
     pub fn calculate_fee(message: &SanitizedMessage, lamports_per_signature: u64) -> u64 {
-        let mut num_signatures = u64::from(message.header().num_required_signatures);
+        Self::calculate_fee_with_feature_set(
+            message,
+            lamports_per_signature,
+            &FeatureSet::default(),
+        )
+    }
+
+    /// Calculate fee for `SanitizedMessage` under the given feature set
+    pub fn calculate_fee_with_feature_set(
+        message: &SanitizedMessage,
+        lamports_per_signature: u64,
+        feature_set: &FeatureSet,
+    ) -> u64 {
+        Self::calculate_fee_details_with_feature_set(message, lamports_per_signature, feature_set)
+            .total_fee()
+    }
+
+    /// Calculate the breakdown of the fee for `SanitizedMessage`
+    #[deprecated(
+        since = "1.9.0",
+        note = "Please use `calculate_fee_details_with_feature_set` instead"
+    )]
+    pub fn calculate_fee_details(
+        message: &SanitizedMessage,
+        lamports_per_signature: u64,
+    ) -> FeeDetails {
+        Self::calculate_fee_details_with_feature_set(
+            message,
+            lamports_per_signature,
+            &FeatureSet::default(),
+        )
+    }
+
+    /// Calculate the breakdown of the fee for `SanitizedMessage` under the given feature set
+    pub fn calculate_fee_details_with_feature_set(
+        message: &SanitizedMessage,
+        lamports_per_signature: u64,
+        feature_set: &FeatureSet,
+    ) -> FeeDetails {
+        let num_signatures = u64::from(message.header().num_required_signatures);
+        let mut num_precompile_signatures = 0u64;
         for (program_id, instruction) in message.program_instructions_iter() {
             if secp256k1_program::check_id(program_id) || ed25519_program::check_id(program_id) {
                 if let Some(num_verifies) = instruction.data.get(0) {
-                    num_signatures = num_signatures.saturating_add(u64::from(*num_verifies));
+                    num_precompile_signatures =
+                        num_precompile_signatures.saturating_add(u64::from(*num_verifies));
                 }
             }
         }
 
-        lamports_per_signature.saturating_mul(num_signatures)
+        // Invalid compute budget instructions fail the transaction, which then only pays for its
+        // signatures
+        let prioritization_fee = if feature_set.is_active(&charge_compute_unit_price::id()) {
+            let mut compute_budget = ComputeBudget::new();
+            compute_budget
+                .process_message(message, feature_set)
+                .map(|_| compute_budget.prioritization_fee())
+                .unwrap_or_default()
+        } else {
+            0
+        };
+
+        FeeDetails {
+            signature_fee: lamports_per_signature.saturating_mul(num_signatures),
+            precompile_signature_fee: lamports_per_signature
+                .saturating_mul(num_precompile_signatures),
+            prioritization_fee,
+        }
     }
 
     fn filter_program_errors_and_collect_fee(
         &self,
         txs: &[SanitizedTransaction],
         executed: &[TransactionExecutionResult],
+        execution_details: &mut [TransactionExecutionDetails],
     ) -> Vec<Result<()>> {
+        assert_eq!(txs.len(), execution_details.len());
         let hash_queue = self.blockhash_queue.read().unwrap();
         let mut fees = 0;
 
         let results = txs
             .iter()
             .zip(executed)
-            .map(|(tx, (res, nonce_rollback))| {
+            .zip(execution_details.iter_mut())
+            .map(|((tx, (res, nonce_rollback)), execution_details)| {
                 let (lamports_per_signature, is_durable_nonce) = nonce_rollback
                     .as_ref()
                     .map(|nonce_rollback| nonce_rollback.lamports_per_signature())
@@ -4066,7 +4618,12 @@ impl Bank {
 
                 let lamports_per_signature =
                     lamports_per_signature.ok_or(TransactionError::BlockhashNotFound)?;
-                let fee = Self::calculate_fee(tx.message(), lamports_per_signature);
+                let fee_details = Self::calculate_fee_details_with_feature_set(
+                    tx.message(),
+                    lamports_per_signature,
+                    &self.feature_set,
+                );
+                let fee = fee_details.total_fee();
 
                 match *res {
                     Err(TransactionError::InstructionError(_, _)) => {
@@ -4080,10 +4637,12 @@ impl Bank {
                             self.withdraw(tx.message().fee_payer(), fee)?;
                         }
                         fees += fee;
+                        execution_details.fee_details = Some(fee_details);
                         Ok(())
                     }
                     Ok(()) => {
                         fees += fee;
+                        execution_details.fee_details = Some(fee_details);
                         Ok(())
                     }
                     _ => res.clone(),
@@ -4100,6 +4659,7 @@ impl Bank {
         sanitized_txs: &[SanitizedTransaction],
         loaded_txs: &mut [TransactionLoadResult],
         executed: &[TransactionExecutionResult],
+        mut execution_details: Vec<TransactionExecutionDetails>,
         tx_count: u64,
         signature_count: u64,
         timings: &mut ExecuteTimings,
@@ -4163,12 +4723,16 @@ impl Bank {
             .update_stakes_cache_us
             .saturating_add(update_stakes_cache_time.as_us());
         self.update_transaction_statuses(sanitized_txs, executed);
-        let fee_collection_results =
-            self.filter_program_errors_and_collect_fee(sanitized_txs, executed);
+        let fee_collection_results = self.filter_program_errors_and_collect_fee(
+            sanitized_txs,
+            executed,
+            &mut execution_details,
+        );
 
         TransactionResults {
             fee_collection_results,
             execution_results: executed.to_vec(),
+            execution_details,
             rent_debits,
         }
     }
@@ -4846,6 +5410,7 @@ impl Bank {
         TransactionBalancesSet,
         Vec<Option<InnerInstructionsList>>,
         Vec<Option<TransactionLogMessages>>,
+        Vec<Option<ComputeBudgetExceeded>>,
     ) {
         let pre_balances = if collect_balances {
             self.collect_balances(batch)
@@ -4858,6 +5423,8 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_logs,
+            compute_budget_exceeded,
+            execution_details,
             _,
             tx_count,
             signature_count,
@@ -4873,6 +5440,7 @@ impl Bank {
             batch.sanitized_transactions(),
             &mut loaded_txs,
             &executed,
+            execution_details,
             tx_count,
             signature_count,
             timings,
@@ -4887,6 +5455,7 @@ impl Bank {
             TransactionBalancesSet::new(pre_balances, post_balances),
             inner_instructions,
             transaction_logs,
+            compute_budget_exceeded,
         )
     }
 
@@ -5139,9 +5708,16 @@ impl Bank {
             builtins
                 .feature_builtins
                 .extend_from_slice(&additional_builtins.feature_builtins);
+            builtins
+                .builtin_migrations
+                .extend_from_slice(&additional_builtins.builtin_migrations);
         }
+        self.builtin_migrations = Arc::new(builtins.builtin_migrations);
         if !debug_do_not_add_builtins {
             for builtin in builtins.genesis_builtins {
+                if self.is_builtin_migrated(&builtin.id) {
+                    continue;
+                }
                 self.add_builtin(
                     &builtin.name,
                     &builtin.id,
@@ -5167,6 +5743,20 @@ impl Bank {
         self.compute_budget = compute_budget;
     }
 
+    /// Compute budget overriding the default one of the transactions, if any
+    pub fn compute_budget(&self) -> Option<ComputeBudget> {
+        self.compute_budget
+    }
+
+    pub fn set_queue_conflicting_transactions(&mut self, queue_conflicting_transactions: bool) {
+        self.queue_conflicting_transactions = queue_conflicting_transactions;
+    }
+
+    /// Whether `prepare_queued_sanitized_batch` queues transactions behind conflicting ones
+    pub fn queue_conflicting_transactions(&self) -> bool {
+        self.queue_conflicting_transactions
+    }
+
     pub fn hard_forks(&self) -> Arc<RwLock<HardForks>> {
         self.hard_forks.clone()
     }
@@ -5234,22 +5824,25 @@ impl Bank {
     pub fn get_program_accounts(
         &self,
         program_id: &Pubkey,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.rc
             .accounts
-            .load_by_program(&self.ancestors, self.bank_id, program_id)
+            .load_by_program(&self.ancestors, self.bank_id, program_id, config)
     }
 
     pub fn get_filtered_program_accounts<F: Fn(&AccountSharedData) -> bool>(
         &self,
         program_id: &Pubkey,
         filter: F,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.rc.accounts.load_by_program_with_filter(
             &self.ancestors,
             self.bank_id,
             program_id,
             filter,
+            config,
         )
     }
 
@@ -5257,12 +5850,14 @@ impl Bank {
         &self,
         index_key: &IndexKey,
         filter: F,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
         self.rc.accounts.load_by_index_key_with_filter(
             &self.ancestors,
             self.bank_id,
             index_key,
             filter,
+            config,
         )
     }
 
@@ -5328,6 +5923,7 @@ impl Bank {
         num: usize,
         filter_by_address: &HashSet<Pubkey>,
         filter: AccountAddressFilter,
+        config: &ScanConfig,
     ) -> ScanResult<Vec<(Pubkey, u64)>> {
         self.rc.accounts.load_largest_accounts(
             &self.ancestors,
@@ -5335,6 +5931,32 @@ impl Bank {
             num,
             filter_by_address,
             filter,
+            config,
+        )
+    }
+
+    /// Returns the `num` largest accounts of the bank without scanning all the accounts, from
+    /// the largest accounts tracked as of the latest root. Returns `None` if the tracked accounts
+    /// need to be rebuilt first, or don't tell the largest accounts passing the filter.
+    pub fn get_tracked_largest_accounts(
+        &self,
+        num: usize,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: AccountAddressFilter,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        self.rc
+            .accounts
+            .tracked_largest_accounts(&self.ancestors, num, filter_by_address, filter)
+    }
+
+    /// Rebuilds the tracked largest accounts with a scan of all the accounts of this bank, which
+    /// must be rooted
+    pub fn rebuild_tracked_largest_accounts(&self, config: &ScanConfig) -> ScanResult<()> {
+        self.rc.accounts.rebuild_tracked_largest_accounts(
+            &self.ancestors,
+            self.bank_id,
+            self.slot(),
+            config,
         )
     }
 
@@ -5354,6 +5976,10 @@ impl Bank {
         self.transactions_per_entry_max.load(Relaxed)
     }
 
+    pub fn account_in_use_count(&self) -> u64 {
+        self.account_in_use_count.load(Relaxed)
+    }
+
     fn increment_transaction_count(&self, tx_count: u64) {
         self.transaction_count.fetch_add(tx_count, Relaxed);
     }
@@ -5458,6 +6084,16 @@ impl Bank {
         )
     }
 
+    /// Recalculates the accounts hash of this rooted bank and verifies it, see
+    /// `AccountsDb::verify_accounts_hash`
+    pub fn verify_accounts_hash(&self) -> std::result::Result<Hash, BankHashVerificationError> {
+        self.rc.accounts.accounts_db.verify_accounts_hash(
+            self.slot(),
+            &self.ancestors,
+            self.capitalization(),
+        )
+    }
+
     pub fn get_snapshot_storages(&self, base_slot: Option<Slot>) -> SnapshotStorages {
         self.rc
             .accounts
@@ -5556,7 +6192,7 @@ impl Bank {
         self.rc.accounts.accounts_db.get_accounts_hash(self.slot)
     }
 
-    pub fn get_thread_pool(&self) -> &ThreadPool {
+    pub fn get_thread_pool(&self) -> &ManagedThreadPool {
         &self.rc.accounts.accounts_db.thread_pool_clean
     }
 
@@ -5686,6 +6322,10 @@ impl Bank {
         self.ticks_per_slot
     }
 
+    pub fn ns_per_slot(&self) -> u128 {
+        self.ns_per_slot
+    }
+
     /// Return the number of slots per year
     pub fn slots_per_year(&self) -> f64 {
         self.slots_per_year
@@ -6187,7 +6827,7 @@ impl Bank {
         for (builtin, feature, activation_type) in feature_builtins.iter() {
             let should_populate = init_or_warp && self.feature_set.is_active(feature)
                 || !init_or_warp && new_feature_activations.contains(feature);
-            if should_populate {
+            if should_populate && !self.is_builtin_migrated(&builtin.id) {
                 match activation_type {
                     ActivationType::NewProgram => self.add_builtin(
                         &builtin.name,
@@ -6205,6 +6845,19 @@ impl Bank {
                 }
             }
         }
+        let builtin_migrations = self.builtin_migrations.clone();
+        for migration in builtin_migrations.iter() {
+            if new_feature_activations.contains(&migration.feature_id) {
+                self.migrate_builtin_to_bpf(migration);
+            } else if init_or_warp
+                && self.feature_set.is_active(&migration.feature_id)
+                && self.is_builtin_migrated(&migration.program_id)
+            {
+                // The accounts were migrated at the feature activation, only the builtin entry
+                // restored with the genesis builtins has to go
+                self.remove_builtin(&migration.name, &migration.program_id);
+            }
+        }
         for precompile in get_precompiles() {
             #[allow(clippy::blocks_in_if_conditions)]
             if precompile.feature.map_or(false, |ref feature_id| {
@@ -6215,24 +6868,135 @@ impl Bank {
         }
     }
 
-    fn apply_spl_token_v2_set_authority_fix(&mut self) {
-        if let Some(old_account) = self.get_account_with_fixed_root(&inline_spl_token_v2_0::id()) {
-            if let Some(new_account) =
-                self.get_account_with_fixed_root(&inline_spl_token_v2_0::new_token_program::id())
-            {
-                datapoint_info!(
-                    "bank-apply_spl_token_v2_set_authority_fix",
-                    ("slot", self.slot, i64),
+    /// Whether the builtin program was replaced by its BPF implementation
+    fn is_builtin_migrated(&self, program_id: &Pubkey) -> bool {
+        self.builtin_migrations
+            .iter()
+            .any(|migration| migration.program_id == *program_id)
+            && self
+                .get_account_with_fixed_root(program_id)
+                .map(|account| bpf_loader_upgradeable::check_id(account.owner()))
+                .unwrap_or(false)
+    }
+
+    /// Replaces a builtin program with the immutable upgradeable loader program deployed from the
+    /// migration's source buffer, at the builtin's address. Only done at the activation of the
+    /// migration feature, a bank activating it without a valid source buffer keeps the builtin.
+    fn migrate_builtin_to_bpf(&mut self, migration: &BuiltinMigration) {
+        if !self.is_builtin_migrated(&migration.program_id) {
+            let buffer_data_offset = UpgradeableLoaderState::buffer_data_offset().unwrap();
+            let buffer = match self.get_account_with_fixed_root(&migration.source_buffer) {
+                Some(buffer)
+                    if bpf_loader_upgradeable::check_id(buffer.owner())
+                        && buffer.data().len() > buffer_data_offset
+                        && buffer.state()
+                            == Ok(UpgradeableLoaderState::Buffer {
+                                authority_address: Some(migration.buffer_authority),
+                            }) =>
+                {
+                    buffer
+                }
+                _ => {
+                    warn!(
+                        "Unable to migrate builtin program {} ({}): invalid source buffer {}",
+                        migration.name, migration.program_id, migration.source_buffer
+                    );
+                    return;
+                }
+            };
+            let elf = &buffer.data()[buffer_data_offset..];
+            if hash(elf) != migration.program_hash {
+                warn!(
+                    "Unable to migrate builtin program {} ({}): unexpected program in source buffer {}",
+                    migration.name,
+                    migration.program_id,
+                    migration.source_buffer
                 );
+                return;
+            }
+            if let Err(err) = (migration.verify_program)(elf, self.feature_set.clone()) {
+                warn!(
+                    "Unable to migrate builtin program {} ({}): program verification failed: {}",
+                    migration.name, migration.program_id, err
+                );
+                return;
+            }
 
-                // Burn lamports in the old token account
-                self.capitalization
-                    .fetch_sub(old_account.lamports(), Relaxed);
-
-                // Transfer new token account to old token account
-                self.store_account(&inline_spl_token_v2_0::id(), &new_account);
-
-                // Clear new token account
+            let (programdata_address, _) = Pubkey::find_program_address(
+                &[migration.program_id.as_ref()],
+                &bpf_loader_upgradeable::id(),
+            );
+            let programdata_len = UpgradeableLoaderState::programdata_len(elf.len()).unwrap();
+            let mut programdata = AccountSharedData::new(
+                self.get_minimum_balance_for_rent_exemption(programdata_len),
+                programdata_len,
+                &bpf_loader_upgradeable::id(),
+            );
+            programdata
+                .set_state(&UpgradeableLoaderState::ProgramData {
+                    slot: self.slot(),
+                    upgrade_authority_address: None,
+                })
+                .unwrap();
+            let programdata_data_offset =
+                UpgradeableLoaderState::programdata_data_offset().unwrap();
+            programdata.data_as_mut_slice()[programdata_data_offset..].copy_from_slice(elf);
+
+            let program_len = UpgradeableLoaderState::program_len().unwrap();
+            let mut program = AccountSharedData::new(
+                self.get_minimum_balance_for_rent_exemption(program_len),
+                program_len,
+                &bpf_loader_upgradeable::id(),
+            );
+            program
+                .set_state(&UpgradeableLoaderState::Program {
+                    programdata_address,
+                })
+                .unwrap();
+            program.set_executable(true);
+
+            // The lamports of the buffer and of the builtin account are burned, the ones of the
+            // program accounts are issued
+            self.store_account_and_update_capitalization(
+                &migration.source_buffer,
+                &AccountSharedData::default(),
+            );
+            self.store_account_and_update_capitalization(&programdata_address, &programdata);
+            self.store_account_and_update_capitalization(&migration.program_id, &program);
+
+            datapoint_info!(
+                "bank-migrate_builtin_to_bpf",
+                ("slot", self.slot, i64),
+                ("program_id", migration.program_id.to_string(), String),
+            );
+            info!(
+                "Migrated builtin program {} ({}) to BPF at slot {}",
+                migration.name,
+                migration.program_id,
+                self.slot()
+            );
+        }
+        self.remove_builtin(&migration.name, &migration.program_id);
+    }
+
+    fn apply_spl_token_v2_set_authority_fix(&mut self) {
+        if let Some(old_account) = self.get_account_with_fixed_root(&inline_spl_token_v2_0::id()) {
+            if let Some(new_account) =
+                self.get_account_with_fixed_root(&inline_spl_token_v2_0::new_token_program::id())
+            {
+                datapoint_info!(
+                    "bank-apply_spl_token_v2_set_authority_fix",
+                    ("slot", self.slot, i64),
+                );
+
+                // Burn lamports in the old token account
+                self.capitalization
+                    .fetch_sub(old_account.lamports(), Relaxed);
+
+                // Transfer new token account to old token account
+                self.store_account(&inline_spl_token_v2_0::id(), &new_account);
+
+                // Clear new token account
                 self.store_account(
                     &inline_spl_token_v2_0::new_token_program::id(),
                     &AccountSharedData::default(),
@@ -6346,6 +7110,7 @@ pub fn get_total_accounts_stats(bank: &Bank) -> ScanResult<TotalAccountsStats> {
                 }
             }
         },
+        &ScanConfig::default(),
     )
 }
 
@@ -6458,9 +7223,10 @@ pub(crate) mod tests {
         timing::duration_as_s,
     };
     use solana_vote_program::{
-        vote_instruction,
+        vote_instruction::{self, VoteError},
         vote_state::{
-            self, BlockTimestamp, Vote, VoteInit, VoteState, VoteStateVersions, MAX_LOCKOUT_HISTORY,
+            self, BlockTimestamp, Vote, VoteInit, VoteState, VoteStateVersions,
+            MAX_COMMISSION_INCREASE, MAX_LOCKOUT_HISTORY,
         },
     };
     use std::{result, thread::Builder, time::Duration};
@@ -6677,6 +7443,58 @@ pub(crate) mod tests {
         assert_eq!(bank1.block_height(), 1);
     }
 
+    #[test]
+    fn test_update_commission_bounded_by_epoch_start_commission() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            voting_keypair,
+        } = create_genesis_config_with_leader(1_000_000_000, &solana_sdk::pubkey::new_rand(), 42);
+        activate_all_features(&mut genesis_config);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let vote_pubkey = voting_keypair.pubkey();
+        let update_commission = |bank: &Bank, commission| {
+            let tx = Transaction::new_signed_with_payer(
+                &[vote_instruction::update_commission(
+                    &vote_pubkey,
+                    &vote_pubkey,
+                    commission,
+                )],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &voting_keypair],
+                bank.last_blockhash(),
+            );
+            bank.process_transaction(&tx)
+        };
+        let too_large = Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VoteError::CommissionIncreaseTooLarge as u32),
+        ));
+
+        // The increases of an epoch add up, relative to the commission it started with
+        assert_eq!(
+            update_commission(&bank, MAX_COMMISSION_INCREASE - 1),
+            Ok(())
+        );
+        assert_eq!(update_commission(&bank, MAX_COMMISSION_INCREASE), Ok(()));
+        assert_eq!(
+            update_commission(&bank, MAX_COMMISSION_INCREASE + 1),
+            too_large
+        );
+
+        // The next epoch starts with the commission the previous one ended with
+        let slot = genesis_config.epoch_schedule.get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        assert_eq!(
+            update_commission(&bank, 2 * MAX_COMMISSION_INCREASE),
+            Ok(())
+        );
+        assert_eq!(
+            update_commission(&bank, 2 * MAX_COMMISSION_INCREASE + 1),
+            too_large
+        );
+    }
+
     #[test]
     fn test_bank_update_epoch_stakes() {
         impl Bank {
@@ -8811,6 +9629,7 @@ pub(crate) mod tests {
         assert_eq!(res.len(), 2);
         assert_eq!(res[0], Ok(()));
         assert_eq!(res[1], Err(TransactionError::AccountInUse));
+        assert_eq!(bank.account_in_use_count(), 1);
         assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 0);
         assert_eq!(bank.get_balance(&key1), 1);
         assert_eq!(bank.get_balance(&key2), 0);
@@ -8930,6 +9749,123 @@ pub(crate) mod tests {
         assert_eq!(bank.get_balance(&pubkey), 500);
     }
 
+    #[test]
+    fn test_simulate_bundle() {
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        bank.freeze();
+        let blockhash = bank.last_blockhash();
+        let keypair = Keypair::new();
+        let pubkey = solana_sdk::pubkey::new_rand();
+
+        let fund =
+            system_transaction::transfer(&mint_keypair, &keypair.pubkey(), 1_000_000, blockhash);
+        let spend = system_transaction::transfer(&keypair, &pubkey, 500_000, blockhash);
+        let overspend = system_transaction::transfer(&keypair, &pubkey, 1_000_000, blockhash);
+        let spend_again = system_transaction::transfer(&keypair, &pubkey, 1_000, blockhash);
+        let sanitize =
+            |tx: &Transaction| SanitizedTransaction::from_transaction_for_tests(tx.clone());
+
+        // The spending transaction fails on its own, the account is only funded by the bundle
+        assert_eq!(
+            bank.simulate_transaction(sanitize(&spend)).result,
+            Err(TransactionError::AccountNotFound)
+        );
+
+        let results = bank.simulate_bundle(
+            [&fund, &spend, &overspend, &spend_again]
+                .iter()
+                .map(|tx| sanitize(tx))
+                .collect(),
+        );
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().result, Ok(()));
+        let spent = results[1].as_ref().unwrap();
+        assert_eq!(spent.result, Ok(()));
+        let (_, account) = spent
+            .post_simulation_accounts
+            .iter()
+            .find(|(key, _)| key == &pubkey)
+            .unwrap();
+        assert_eq!(account.lamports(), 500_000);
+
+        // The bundle stops at the first failed transaction
+        assert!(results[2].as_ref().unwrap().result.is_err());
+        assert!(results[3].is_none());
+
+        // Nothing was committed
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 0);
+        assert_eq!(bank.get_balance(&pubkey), 0);
+
+        // A transaction can't be replayed within the bundle
+        let results = bank.simulate_bundle(vec![sanitize(&fund), sanitize(&fund)]);
+        assert_eq!(results[0].as_ref().unwrap().result, Ok(()));
+        assert_eq!(
+            results[1].as_ref().unwrap().result,
+            Err(TransactionError::AlreadyProcessed)
+        );
+    }
+
+    #[test]
+    fn test_simulate_bundle_fees_and_nonce() {
+        let (mut bank, _mint_keypair, custodian_keypair, nonce_keypair) =
+            setup_nonce_with_bank(10_000_000, |_| {}, 5_000_000, 250_000, None).unwrap();
+        let custodian_pubkey = custodian_keypair.pubkey();
+        let nonce_pubkey = nonce_keypair.pubkey();
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let nonce_hash = get_nonce_account(&bank, &nonce_pubkey).unwrap();
+        for _ in 0..MAX_RECENT_BLOCKHASHES + 1 {
+            goto_end_of_slot(Arc::get_mut(&mut bank).unwrap());
+            bank = Arc::new(new_from_parent(&bank));
+        }
+        bank.freeze();
+
+        let durable_transfer = |lamports| {
+            SanitizedTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::advance_nonce_account(&nonce_pubkey, &nonce_pubkey),
+                    system_instruction::transfer(&custodian_pubkey, &pubkey, lamports),
+                ],
+                Some(&custodian_pubkey),
+                &[&custodian_keypair, &nonce_keypair],
+                nonce_hash,
+            ))
+        };
+        let first = durable_transfer(100_000);
+        let second = durable_transfer(200_000);
+        let fee = Bank::calculate_fee_with_feature_set(
+            first.message(),
+            bank.get_lamports_per_signature(),
+            &bank.feature_set,
+        );
+        assert!(fee > 0);
+        assert_eq!(bank.simulate_transaction(second.clone()).result, Ok(()));
+
+        let results = bank.simulate_bundle(vec![first, second]);
+        let first_result = results[0].as_ref().unwrap();
+        assert_eq!(first_result.result, Ok(()));
+        let post_balance = |pubkey| {
+            first_result
+                .post_simulation_accounts
+                .iter()
+                .find(|(key, _)| key == pubkey)
+                .unwrap()
+                .1
+                .lamports()
+        };
+        assert_eq!(
+            post_balance(&custodian_pubkey),
+            bank.get_balance(&custodian_pubkey) - 100_000 - fee
+        );
+        assert_eq!(post_balance(&pubkey), 100_000);
+
+        // The nonce was advanced by the first transaction
+        assert_eq!(
+            results[1].as_ref().unwrap().result,
+            Err(TransactionError::BlockhashNotFound)
+        );
+    }
+
     #[test]
     fn test_transfer_to_sysvar() {
         solana_logger::setup();
@@ -9227,7 +10163,12 @@ pub(crate) mod tests {
         ];
         let initial_balance = bank.get_balance(&leader);
 
-        let results = bank.filter_program_errors_and_collect_fee(&[tx1, tx2], &results);
+        let mut execution_details = vec![TransactionExecutionDetails::default(); 2];
+        let results = bank.filter_program_errors_and_collect_fee(
+            &[tx1, tx2],
+            &results,
+            &mut execution_details,
+        );
         bank.freeze();
         assert_eq!(
             bank.get_balance(&leader),
@@ -9239,6 +10180,15 @@ pub(crate) mod tests {
         );
         assert_eq!(results[0], Ok(()));
         assert_eq!(results[1], Ok(()));
+
+        // The fee is charged even though the second transaction failed
+        let fee_details = FeeDetails {
+            signature_fee: bank.fee_rate_governor.lamports_per_signature,
+            precompile_signature_fee: 0,
+            prioritization_fee: 0,
+        };
+        assert_eq!(execution_details[0].fee_details, Some(fee_details));
+        assert_eq!(execution_details[1].fee_details, Some(fee_details));
     }
 
     #[test]
@@ -9384,6 +10334,59 @@ pub(crate) mod tests {
         assert!(bank.transfer(2, &mint_keypair, &bob.pubkey()).is_ok());
     }
 
+    #[test]
+    fn test_prepare_queued_sanitized_batch() {
+        let (genesis_config, mint_keypair) = create_genesis_config(10);
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let txs: Vec<_> = [
+            (&mint_keypair, &alice),
+            (&bob, &alice),
+            (&mint_keypair, &bob),
+        ]
+        .iter()
+        .map(|(from, to)| {
+            SanitizedTransaction::from_transaction_for_tests(system_transaction::transfer(
+                from,
+                &to.pubkey(),
+                1,
+                genesis_config.hash(),
+            ))
+        })
+        .collect();
+
+        // Without queuing, the transactions conflicting with the first one fail to lock
+        let batch = bank.prepare_queued_sanitized_batch(&txs);
+        assert_eq!(
+            batch.lock_results(),
+            &vec![
+                Ok(()),
+                Err(TransactionError::AccountInUse),
+                Err(TransactionError::AccountInUse)
+            ]
+        );
+        drop(batch);
+
+        // With queuing, they are left to the next batch, which waits for alice's account
+        bank.set_queue_conflicting_transactions(true);
+        let batch = bank.prepare_queued_sanitized_batch(&txs);
+        assert_eq!(batch.lock_results(), &vec![Ok(())]);
+        let next_batch = bank.prepare_queued_sanitized_batch(&txs[1..]);
+        assert_eq!(
+            next_batch.lock_results(),
+            &vec![Err(TransactionError::AccountInUse)]
+        );
+        drop(next_batch);
+        drop(batch);
+        let batch = bank.prepare_queued_sanitized_batch(&txs[1..]);
+        assert_eq!(batch.lock_results(), &vec![Ok(())]);
+        assert_eq!(
+            batch.sanitized_transactions()[0].signature(),
+            txs[1].signature()
+        );
+    }
+
     #[test]
     fn test_readonly_relaxed_locks() {
         let (genesis_config, _) = create_genesis_config(3);
@@ -10456,11 +11459,15 @@ pub(crate) mod tests {
         let bank1 = Arc::new(new_from_parent(&bank0));
         bank1.squash();
         assert_eq!(
-            bank0.get_program_accounts(&program_id).unwrap(),
+            bank0
+                .get_program_accounts(&program_id, &ScanConfig::default())
+                .unwrap(),
             vec![(pubkey0, account0.clone())]
         );
         assert_eq!(
-            bank1.get_program_accounts(&program_id).unwrap(),
+            bank1
+                .get_program_accounts(&program_id, &ScanConfig::default())
+                .unwrap(),
             vec![(pubkey0, account0)]
         );
         assert_eq!(
@@ -10479,8 +11486,20 @@ pub(crate) mod tests {
 
         let bank3 = Arc::new(new_from_parent(&bank2));
         bank3.squash();
-        assert_eq!(bank1.get_program_accounts(&program_id).unwrap().len(), 2);
-        assert_eq!(bank3.get_program_accounts(&program_id).unwrap().len(), 2);
+        assert_eq!(
+            bank1
+                .get_program_accounts(&program_id, &ScanConfig::default())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            bank3
+                .get_program_accounts(&program_id, &ScanConfig::default())
+                .unwrap()
+                .len(),
+            2
+        );
     }
 
     #[test]
@@ -10501,7 +11520,11 @@ pub(crate) mod tests {
         bank.store_account(&address, &account);
 
         let indexed_accounts = bank
-            .get_filtered_indexed_accounts(&IndexKey::ProgramId(program_id), |_| true)
+            .get_filtered_indexed_accounts(
+                &IndexKey::ProgramId(program_id),
+                |_| true,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(indexed_accounts.len(), 1);
         assert_eq!(indexed_accounts[0], (address, account));
@@ -10514,27 +11537,39 @@ pub(crate) mod tests {
         let bank = Arc::new(new_from_parent(&bank));
         bank.store_account(&address, &new_account);
         let indexed_accounts = bank
-            .get_filtered_indexed_accounts(&IndexKey::ProgramId(program_id), |_| true)
+            .get_filtered_indexed_accounts(
+                &IndexKey::ProgramId(program_id),
+                |_| true,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(indexed_accounts.len(), 1);
         assert_eq!(indexed_accounts[0], (address, new_account.clone()));
         let indexed_accounts = bank
-            .get_filtered_indexed_accounts(&IndexKey::ProgramId(another_program_id), |_| true)
+            .get_filtered_indexed_accounts(
+                &IndexKey::ProgramId(another_program_id),
+                |_| true,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(indexed_accounts.len(), 1);
         assert_eq!(indexed_accounts[0], (address, new_account.clone()));
 
         // Post-processing filter
         let indexed_accounts = bank
-            .get_filtered_indexed_accounts(&IndexKey::ProgramId(program_id), |account| {
-                account.owner() == &program_id
-            })
+            .get_filtered_indexed_accounts(
+                &IndexKey::ProgramId(program_id),
+                |account| account.owner() == &program_id,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert!(indexed_accounts.is_empty());
         let indexed_accounts = bank
-            .get_filtered_indexed_accounts(&IndexKey::ProgramId(another_program_id), |account| {
-                account.owner() == &another_program_id
-            })
+            .get_filtered_indexed_accounts(
+                &IndexKey::ProgramId(another_program_id),
+                |account| account.owner() == &another_program_id,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(indexed_accounts.len(), 1);
         assert_eq!(indexed_accounts[0], (address, new_account));
@@ -11472,21 +12507,28 @@ pub(crate) mod tests {
         let txs = vec![tx0, tx1, tx2];
 
         let lock_result = bank0.prepare_batch_for_tests(txs);
-        let (transaction_results, transaction_balances_set, inner_instructions, transaction_logs) =
-            bank0.load_execute_and_commit_transactions(
-                &lock_result,
-                MAX_PROCESSING_AGE,
-                true,
-                false,
-                false,
-                &mut ExecuteTimings::default(),
-            );
+        let (
+            transaction_results,
+            transaction_balances_set,
+            inner_instructions,
+            transaction_logs,
+            compute_budget_exceeded,
+        ) = bank0.load_execute_and_commit_transactions(
+            &lock_result,
+            MAX_PROCESSING_AGE,
+            true,
+            false,
+            false,
+            &mut ExecuteTimings::default(),
+        );
 
         assert!(inner_instructions.iter().all(Option::is_none));
         assert!(transaction_logs.iter().all(Option::is_none));
+        assert!(compute_budget_exceeded.iter().all(Option::is_none));
 
         assert_eq!(inner_instructions.len(), 3);
         assert_eq!(transaction_logs.len(), 3);
+        assert_eq!(compute_budget_exceeded.len(), 3);
         assert_eq!(transaction_balances_set.pre_balances.len(), 3);
         assert_eq!(transaction_balances_set.post_balances.len(), 3);
 
@@ -12796,6 +13838,67 @@ pub(crate) mod tests {
         assert!(cache.get(&key3).is_some());
     }
 
+    #[test]
+    fn test_cached_executors_aging() {
+        let hot = solana_sdk::pubkey::new_rand();
+        let executor: Arc<dyn Executor> = Arc::new(TestExecutor {});
+        let mut cache = CachedExecutors::new(2, 0);
+
+        cache.put(&hot, executor.clone());
+        for _ in 0..10 {
+            assert!(cache.get(&hot).is_some());
+        }
+        // a flood of new programs only displaces the other new programs
+        let mut last = Pubkey::default();
+        for _ in 0..5 {
+            last = solana_sdk::pubkey::new_rand();
+            cache.put(&last, executor.clone());
+            assert!(cache.get(&last).is_some());
+        }
+        assert!(cache.get(&hot).is_some());
+        assert!(cache.get(&last).is_some());
+
+        // once the new programs are used more than the hot one, it is evicted
+        for _ in 0..10 {
+            assert!(cache.get(&last).is_some());
+        }
+        assert_eq!(cache.put(&solana_sdk::pubkey::new_rand(), executor), 1);
+        assert!(cache.get(&hot).is_none());
+
+        assert_eq!(cache.set_max(1), 1);
+        assert_eq!(cache.executors.len(), 1);
+    }
+
+    #[test]
+    fn test_cached_executors_prewarm() {
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let key3 = solana_sdk::pubkey::new_rand();
+        let key4 = solana_sdk::pubkey::new_rand();
+        let executor: Arc<dyn Executor> = Arc::new(TestExecutor {});
+        let mut parent = CachedExecutors::new(2, 0);
+        parent.put(&key1, executor.clone());
+        parent.put(&key2, executor.clone());
+        assert!(parent.get(&key2).is_some());
+
+        let mut child = parent.clone();
+        child.remove(&key1);
+        child.put(&key3, executor.clone());
+        for _ in 0..3 {
+            assert!(child.get(&key3).is_some());
+        }
+        // key2 is evicted to make room for key4, which is then removed
+        assert_eq!(child.put(&key4, executor.clone()), 1);
+        child.remove(&key4);
+
+        // key1 was invalidated by the child, key2 fits again
+        assert_eq!(child.prewarm(&parent), 1);
+        assert!(child.get(&key1).is_none());
+        assert!(child.get(&key2).is_some());
+        assert!(child.get(&key3).is_some());
+        assert_eq!(child.prewarm(&parent), 0);
+    }
+
     #[test]
     fn test_bank_executor_cache() {
         solana_logger::setup();
@@ -12985,6 +14088,338 @@ pub(crate) mod tests {
         assert!(bank.feature_set.is_active(&test_feature));
     }
 
+    #[test]
+    fn test_migrate_builtin_to_bpf() {
+        // Stands in for the upgradeable loader running a BPF implementation of the config program
+        fn mock_bpf_config_program(
+            first_instruction_account: usize,
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> std::result::Result<(), InstructionError> {
+            // The program data and program accounts precede the instruction accounts
+            solana_config_program::config_processor::process_instruction(
+                first_instruction_account + 2,
+                data,
+                invoke_context,
+            )
+        }
+
+        #[derive(Serialize, Deserialize, Default)]
+        struct TestConfig {
+            value: u64,
+        }
+        impl solana_config_program::ConfigState for TestConfig {
+            fn max_space() -> u64 {
+                bincode::serialized_size(&Self::default()).unwrap()
+            }
+        }
+
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000_000);
+        let test_feature = "TestFeature11111111111111111111111111111111"
+            .parse::<Pubkey>()
+            .unwrap();
+        let source_buffer = solana_sdk::pubkey::new_rand();
+        let mut builtins = Builtins {
+            genesis_builtins: vec![Builtin::new(
+                "mock bpf loader upgradeable",
+                bpf_loader_upgradeable::id(),
+                mock_bpf_config_program,
+            )],
+            feature_builtins: vec![],
+            builtin_migrations: vec![],
+        };
+        let new_bank = |builtins: &Builtins| {
+            let bank0 = Arc::new(Bank::new_with_paths_for_tests(
+                &genesis_config,
+                Vec::new(),
+                &[],
+                None,
+                Some(builtins),
+                AccountSecondaryIndexes::default(),
+                false,
+                AccountShrinkThreshold::default(),
+                false,
+            ));
+            Bank::new_from_parent(&bank0, &Pubkey::default(), 1)
+        };
+        let native_bank = new_bank(&builtins);
+        let buffer_authority = solana_sdk::pubkey::new_rand();
+        let mut elf = b"\x7fELF".to_vec();
+        elf.resize(64, 7);
+        let migration = BuiltinMigration {
+            name: "config_program".to_string(),
+            program_id: solana_config_program::id(),
+            feature_id: test_feature,
+            source_buffer,
+            buffer_authority,
+            program_hash: hash(&elf),
+            // Stands in for the loader verification
+            verify_program: |elf, _feature_set| {
+                if elf.starts_with(b"\x7fELF") {
+                    Ok(())
+                } else {
+                    Err(InstructionError::InvalidAccountData)
+                }
+            },
+        };
+        builtins.builtin_migrations.push(migration.clone());
+        let mut migrated_bank = new_bank(&builtins);
+
+        let mut feature_set = FeatureSet::clone(&migrated_bank.feature_set);
+        feature_set.inactive.insert(test_feature);
+        let activate_feature = |bank: &mut Bank| {
+            bank.store_account_and_update_capitalization(
+                &test_feature,
+                &feature::create_account(&Feature::default(), 42),
+            );
+            bank.feature_set = Arc::new(feature_set.clone());
+            bank.apply_feature_activations(false, false);
+            assert!(bank.feature_set.is_active(&test_feature));
+        };
+        let store_buffer = |bank: &Bank, authority_address: Pubkey, elf: &[u8]| {
+            let buffer_data_offset = UpgradeableLoaderState::buffer_data_offset().unwrap();
+            let buffer_len = UpgradeableLoaderState::buffer_len(elf.len()).unwrap();
+            let mut buffer = AccountSharedData::new(
+                bank.get_minimum_balance_for_rent_exemption(buffer_len),
+                buffer_len,
+                &bpf_loader_upgradeable::id(),
+            );
+            buffer
+                .set_state(&UpgradeableLoaderState::Buffer {
+                    authority_address: Some(authority_address),
+                })
+                .unwrap();
+            buffer.data_as_mut_slice()[buffer_data_offset..].copy_from_slice(elf);
+            bank.store_account_and_update_capitalization(&source_buffer, &buffer);
+        };
+
+        // Activating the feature without a source buffer leaves the builtin in place
+        activate_feature(&mut migrated_bank);
+        assert!(!migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+
+        // So does a source buffer held by another authority
+        store_buffer(&migrated_bank, solana_sdk::pubkey::new_rand(), &elf);
+        activate_feature(&mut migrated_bank);
+        assert!(!migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+
+        // Or holding another program
+        let mut other_elf = elf.clone();
+        other_elf[63] = 8;
+        store_buffer(&migrated_bank, buffer_authority, &other_elf);
+        activate_feature(&mut migrated_bank);
+        assert!(!migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+
+        // Or holding the expected program, which fails the verification
+        let invalid_elf = vec![7; 64];
+        migrated_bank.builtin_migrations = Arc::new(vec![BuiltinMigration {
+            program_hash: hash(&invalid_elf),
+            ..migration.clone()
+        }]);
+        store_buffer(&migrated_bank, buffer_authority, &invalid_elf);
+        activate_feature(&mut migrated_bank);
+        assert!(!migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+        assert!(migrated_bank.get_account(&source_buffer).is_some());
+
+        // Activate it again with the expected source buffer
+        migrated_bank.builtin_migrations = Arc::new(vec![migration]);
+        store_buffer(&migrated_bank, buffer_authority, &elf);
+        activate_feature(&mut migrated_bank);
+        assert!(migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+
+        let program = migrated_bank
+            .get_account(&solana_config_program::id())
+            .unwrap();
+        assert!(program.executable());
+        let (programdata_address, _) = Pubkey::find_program_address(
+            &[solana_config_program::id().as_ref()],
+            &bpf_loader_upgradeable::id(),
+        );
+        assert_eq!(
+            program.state(),
+            Ok(UpgradeableLoaderState::Program {
+                programdata_address
+            })
+        );
+        let programdata = migrated_bank.get_account(&programdata_address).unwrap();
+        assert_eq!(
+            programdata.state(),
+            Ok(UpgradeableLoaderState::ProgramData {
+                slot: 1,
+                upgrade_authority_address: None,
+            })
+        );
+        let programdata_data_offset = UpgradeableLoaderState::programdata_data_offset().unwrap();
+        assert_eq!(&programdata.data()[programdata_data_offset..], &elf[..]);
+        assert!(migrated_bank.get_account(&source_buffer).is_none());
+        assert_eq!(
+            migrated_bank.capitalization(),
+            migrated_bank.calculate_capitalization(true)
+        );
+
+        // The migrated program behaves as the builtin it replaced
+        let config_keypair = Keypair::new();
+        let transactions = vec![
+            Transaction::new_signed_with_payer(
+                &solana_config_program::config_instruction::create_account::<TestConfig>(
+                    &mint_keypair.pubkey(),
+                    &config_keypair.pubkey(),
+                    1_000_000,
+                    vec![],
+                ),
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &config_keypair],
+                native_bank.last_blockhash(),
+            ),
+            Transaction::new_signed_with_payer(
+                &[solana_config_program::config_instruction::store(
+                    &config_keypair.pubkey(),
+                    true,
+                    vec![],
+                    &TestConfig { value: 42 },
+                )],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &config_keypair],
+                native_bank.last_blockhash(),
+            ),
+            Transaction::new_signed_with_payer(
+                &[solana_config_program::config_instruction::store(
+                    &config_keypair.pubkey(),
+                    false,
+                    vec![],
+                    &TestConfig { value: 43 },
+                )],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair],
+                native_bank.last_blockhash(),
+            ),
+        ];
+        let results: Vec<_> = transactions
+            .iter()
+            .map(|transaction| {
+                let result = native_bank.process_transaction(transaction);
+                assert_eq!(result, migrated_bank.process_transaction(transaction));
+                assert_eq!(
+                    native_bank.get_account(&config_keypair.pubkey()),
+                    migrated_bank.get_account(&config_keypair.pubkey())
+                );
+                result
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(()),
+                Ok(()),
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::MissingRequiredSignature
+                ))
+            ]
+        );
+
+        // Reloading a migrated bank only drops the builtin restored with the genesis builtins
+        let is_builtin = |bank: &Bank| {
+            bank.instruction_processor
+                .programs()
+                .iter()
+                .any(|(program_id, _)| *program_id == solana_config_program::id())
+        };
+        assert!(!is_builtin(&migrated_bank));
+        migrated_bank.instruction_processor.add_program(
+            &solana_config_program::id(),
+            solana_config_program::config_processor::process_instruction,
+        );
+        let capitalization = migrated_bank.capitalization();
+        migrated_bank.apply_feature_activations(true, false);
+        assert!(!is_builtin(&migrated_bank));
+        assert!(migrated_bank.is_builtin_migrated(&solana_config_program::id()));
+        assert_eq!(migrated_bank.capitalization(), capitalization);
+    }
+
+    #[test]
+    fn test_migrate_builtin_to_bpf_only_at_activation() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(1_000_000_000);
+        let test_feature = "TestFeature11111111111111111111111111111111"
+            .parse::<Pubkey>()
+            .unwrap();
+        let source_buffer = solana_sdk::pubkey::new_rand();
+        let buffer_authority = solana_sdk::pubkey::new_rand();
+        let mut elf = b"\x7fELF".to_vec();
+        elf.resize(64, 7);
+        let builtins = Builtins {
+            genesis_builtins: vec![Builtin::new(
+                "config_program",
+                solana_config_program::id(),
+                solana_config_program::config_processor::process_instruction,
+            )],
+            feature_builtins: vec![],
+            builtin_migrations: vec![BuiltinMigration {
+                name: "config_program".to_string(),
+                program_id: solana_config_program::id(),
+                feature_id: test_feature,
+                source_buffer,
+                buffer_authority,
+                program_hash: hash(&elf),
+                verify_program: |_elf, _feature_set| Ok(()),
+            }],
+        };
+        let bank0 = Arc::new(Bank::new_with_paths_for_tests(
+            &genesis_config,
+            Vec::new(),
+            &[],
+            None,
+            Some(&builtins),
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+            false,
+        ));
+        let mut bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+
+        // The feature activated without a source buffer, so the builtin was kept
+        let mut feature_set = FeatureSet::clone(&bank.feature_set);
+        feature_set.inactive.remove(&test_feature);
+        feature_set.active.insert(test_feature, 0);
+        bank.feature_set = Arc::new(feature_set);
+        bank.store_account_and_update_capitalization(
+            &test_feature,
+            &feature::create_account(
+                &Feature {
+                    activated_at: Some(0),
+                },
+                42,
+            ),
+        );
+
+        // A source buffer showing up later doesn't migrate the builtin when the bank is reloaded
+        let buffer_data_offset = UpgradeableLoaderState::buffer_data_offset().unwrap();
+        let buffer_len = UpgradeableLoaderState::buffer_len(elf.len()).unwrap();
+        let mut buffer = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(buffer_len),
+            buffer_len,
+            &bpf_loader_upgradeable::id(),
+        );
+        buffer
+            .set_state(&UpgradeableLoaderState::Buffer {
+                authority_address: Some(buffer_authority),
+            })
+            .unwrap();
+        buffer.data_as_mut_slice()[buffer_data_offset..].copy_from_slice(&elf);
+        bank.store_account_and_update_capitalization(&source_buffer, &buffer);
+        let program = bank.get_account(&solana_config_program::id());
+
+        bank.apply_feature_activations(true, false);
+        assert!(bank.feature_set.is_active(&test_feature));
+        assert!(!bank.is_builtin_migrated(&solana_config_program::id()));
+        assert_eq!(bank.get_account(&solana_config_program::id()), program);
+        assert_eq!(bank.get_account(&source_buffer), Some(buffer));
+        assert!(bank
+            .instruction_processor
+            .programs()
+            .iter()
+            .any(|(program_id, _)| *program_id == solana_config_program::id()));
+    }
+
     #[test]
     fn test_spl_token_v2_replacement() {
         let (genesis_config, _mint_keypair) = create_genesis_config(0);
@@ -13115,13 +14550,25 @@ pub(crate) mod tests {
 
         let bank2 =
             Bank::new_from_parent(&bank1, &Pubkey::default(), bank1.first_slot_in_next_epoch());
-        assert_eq!(bank2.get_program_accounts(&sysvar::id()).unwrap().len(), 8);
+        assert_eq!(
+            bank2
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap()
+                .len(),
+            8
+        );
 
         // force rent collection for sysvars
         bank2.collect_rent_in_partition((0, 0, 1)); // all range
 
         // no sysvar should be deleted due to rent
-        assert_eq!(bank2.get_program_accounts(&sysvar::id()).unwrap().len(), 8);
+        assert_eq!(
+            bank2
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap()
+                .len(),
+            8
+        );
     }
 
     // this test can be removed after rent_for_sysvars activation on mainnet-beta
@@ -13154,7 +14601,9 @@ pub(crate) mod tests {
         );
 
         {
-            let sysvars = bank1.get_program_accounts(&sysvar::id()).unwrap();
+            let sysvars = bank1
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap();
             assert_eq!(sysvars.len(), 8);
             assert!(sysvars
                 .iter()
@@ -13189,7 +14638,9 @@ pub(crate) mod tests {
         );
 
         {
-            let sysvars = bank2.get_program_accounts(&sysvar::id()).unwrap();
+            let sysvars = bank2
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap();
             assert_eq!(sysvars.len(), 8);
             assert!(sysvars
                 .iter()
@@ -13240,6 +14691,7 @@ pub(crate) mod tests {
                 ),
             ],
             feature_builtins: (vec![]),
+            builtin_migrations: vec![],
         };
 
         let bank0 = Arc::new(Bank::new_with_paths_for_tests(
@@ -13266,7 +14718,9 @@ pub(crate) mod tests {
             &feature::create_account(&Feature { activated_at: None }, feature_balance),
         );
         {
-            let sysvars = bank1.get_program_accounts(&sysvar::id()).unwrap();
+            let sysvars = bank1
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap();
             assert_eq!(sysvars.len(), 9);
             assert!(sysvars
                 .iter()
@@ -13301,7 +14755,9 @@ pub(crate) mod tests {
             },
         );
         {
-            let sysvars = bank2.get_program_accounts(&sysvar::id()).unwrap();
+            let sysvars = bank2
+                .get_program_accounts(&sysvar::id(), &ScanConfig::default())
+                .unwrap();
             assert_eq!(sysvars.len(), 9);
             assert!(sysvars
                 .iter()
@@ -13670,7 +15126,8 @@ pub(crate) mod tests {
                             bank_to_scan_receiver.recv_timeout(Duration::from_millis(10))
                         {
                             info!("scanning program accounts for slot {}", bank_to_scan.slot());
-                            let accounts_result = bank_to_scan.get_program_accounts(&program_id);
+                            let accounts_result = bank_to_scan
+                                .get_program_accounts(&program_id, &ScanConfig::default());
                             let _ = scan_finished_sender.send(bank_to_scan.bank_id());
                             num_banks_scanned.fetch_add(1, Relaxed);
                             match (&acceptable_scan_results, accounts_result.is_err()) {
@@ -13764,6 +15221,7 @@ pub(crate) mod tests {
             let abs_request_handler = AbsRequestHandler {
                 snapshot_request_handler: None,
                 pruned_banks_receiver,
+                accounts_hash_audit: None,
             };
             test_store_scan_consistency(
                 *accounts_db_caching_enabled,
@@ -14631,24 +16089,44 @@ pub(crate) mod tests {
 
         // Return only one largest account
         assert_eq!(
-            bank.get_largest_accounts(1, &pubkeys_hashset, AccountAddressFilter::Include)
-                .unwrap(),
+            bank.get_largest_accounts(
+                1,
+                &pubkeys_hashset,
+                AccountAddressFilter::Include,
+                &ScanConfig::default()
+            )
+            .unwrap(),
             vec![(pubkeys[4], sol_to_lamports(5.0))]
         );
         assert_eq!(
-            bank.get_largest_accounts(1, &HashSet::new(), AccountAddressFilter::Exclude)
-                .unwrap(),
+            bank.get_largest_accounts(
+                1,
+                &HashSet::new(),
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default()
+            )
+            .unwrap(),
             vec![(pubkeys[4], sol_to_lamports(5.0))]
         );
         assert_eq!(
-            bank.get_largest_accounts(1, &exclude4, AccountAddressFilter::Exclude)
-                .unwrap(),
+            bank.get_largest_accounts(
+                1,
+                &exclude4,
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default()
+            )
+            .unwrap(),
             vec![(pubkeys[3], sol_to_lamports(4.0))]
         );
 
         // Return all added accounts
         let results = bank
-            .get_largest_accounts(10, &pubkeys_hashset, AccountAddressFilter::Include)
+            .get_largest_accounts(
+                10,
+                &pubkeys_hashset,
+                AccountAddressFilter::Include,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(results.len(), sorted_accounts.len());
         for pubkey_balance in sorted_accounts.iter() {
@@ -14660,7 +16138,12 @@ pub(crate) mod tests {
 
         let expected_accounts = sorted_accounts[1..].to_vec();
         let results = bank
-            .get_largest_accounts(10, &exclude4, AccountAddressFilter::Exclude)
+            .get_largest_accounts(
+                10,
+                &exclude4,
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default(),
+            )
             .unwrap();
         // results include 5 Bank builtins
         assert_eq!(results.len(), 10);
@@ -14674,7 +16157,12 @@ pub(crate) mod tests {
         // Return 3 added accounts
         let expected_accounts = sorted_accounts[0..4].to_vec();
         let results = bank
-            .get_largest_accounts(4, &pubkeys_hashset, AccountAddressFilter::Include)
+            .get_largest_accounts(
+                4,
+                &pubkeys_hashset,
+                AccountAddressFilter::Include,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(results.len(), expected_accounts.len());
         for pubkey_balance in expected_accounts.iter() {
@@ -14683,7 +16171,12 @@ pub(crate) mod tests {
 
         let expected_accounts = expected_accounts[1..4].to_vec();
         let results = bank
-            .get_largest_accounts(3, &exclude4, AccountAddressFilter::Exclude)
+            .get_largest_accounts(
+                3,
+                &exclude4,
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default(),
+            )
             .unwrap();
         assert_eq!(results.len(), expected_accounts.len());
         for pubkey_balance in expected_accounts.iter() {
@@ -14696,12 +16189,75 @@ pub(crate) mod tests {
             .cloned()
             .collect();
         assert_eq!(
-            bank.get_largest_accounts(2, &exclude, AccountAddressFilter::Exclude)
-                .unwrap(),
+            bank.get_largest_accounts(
+                2,
+                &exclude,
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default()
+            )
+            .unwrap(),
             vec![pubkeys_balances[3], pubkeys_balances[1]]
         );
     }
 
+    #[test]
+    fn test_get_tracked_largest_accounts() {
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(42, &solana_sdk::pubkey::new_rand(), 42);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let pubkeys: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        for (pubkey, sol) in pubkeys.iter().zip(vec![2.0, 3.0, 4.0]) {
+            let account = AccountSharedData::new(sol_to_lamports(sol), 0, &Pubkey::default());
+            bank0.store_account(pubkey, &account);
+        }
+        bank0.squash();
+        let scanned_largest_accounts = |bank: &Bank| {
+            bank.get_largest_accounts(
+                3,
+                &HashSet::new(),
+                AccountAddressFilter::Exclude,
+                &ScanConfig::default(),
+            )
+            .unwrap()
+        };
+        let tracked_largest_accounts = |bank: &Bank| {
+            bank.get_tracked_largest_accounts(3, &HashSet::new(), AccountAddressFilter::Exclude)
+        };
+
+        // The largest accounts aren't tracked until rebuilt
+        assert_eq!(tracked_largest_accounts(&bank0), None);
+        bank0
+            .rebuild_tracked_largest_accounts(&ScanConfig::default())
+            .unwrap();
+        assert_eq!(
+            tracked_largest_accounts(&bank0),
+            Some(scanned_largest_accounts(&bank0))
+        );
+
+        // The accounts stored since the root are accounted for, before and once rooted
+        let bank1 = Arc::new(new_from_parent(&bank0));
+        let account = AccountSharedData::new(sol_to_lamports(10.0), 0, &Pubkey::default());
+        bank1.store_account(&pubkeys[3], &account);
+        bank1.store_account(&pubkeys[2], &AccountSharedData::default());
+        assert_eq!(
+            tracked_largest_accounts(&bank1),
+            Some(scanned_largest_accounts(&bank1))
+        );
+        bank1.squash();
+        // Rooting the slot only queues it, its accounts are accounted for once asked for
+        let largest_accounts = &bank1.rc.accounts.accounts_db.largest_accounts;
+        assert_eq!(largest_accounts.read().unwrap().root(), Some(0));
+        assert_eq!(
+            tracked_largest_accounts(&bank1),
+            Some(vec![
+                (pubkeys[3], sol_to_lamports(10.0)),
+                (pubkeys[1], sol_to_lamports(3.0)),
+                (pubkeys[0], sol_to_lamports(2.0)),
+            ])
+        );
+        assert_eq!(largest_accounts.read().unwrap().root(), Some(1));
+    }
+
     #[test]
     fn test_transfer_sysvar() {
         solana_logger::setup();
@@ -15193,10 +16749,16 @@ pub(crate) mod tests {
         // Default: no fee.
         let message =
             SanitizedMessage::try_from(Message::new(&[], Some(&Pubkey::new_unique()))).unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 0), 0);
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 0, &FeatureSet::default()),
+            0
+        );
 
         // One signature, a fee.
-        assert_eq!(Bank::calculate_fee(&message, 1), 1);
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 1, &FeatureSet::default()),
+            1
+        );
 
         // Two signatures, double the fee.
         let key0 = Pubkey::new_unique();
@@ -15204,7 +16766,10 @@ pub(crate) mod tests {
         let ix0 = system_instruction::transfer(&key0, &key1, 1);
         let ix1 = system_instruction::transfer(&key1, &key0, 1);
         let message = SanitizedMessage::try_from(Message::new(&[ix0, ix1], Some(&key0))).unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 2), 4);
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 2, &FeatureSet::default()),
+            4
+        );
     }
 
     #[test]
@@ -15233,7 +16798,10 @@ pub(crate) mod tests {
             Some(&key0),
         ))
         .unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 1), 2);
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 1, &FeatureSet::default()),
+            2
+        );
 
         secp_instruction1.data = vec![0];
         secp_instruction2.data = vec![10];
@@ -15242,6 +16810,56 @@ pub(crate) mod tests {
             Some(&key0),
         ))
         .unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 1), 11);
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 1, &FeatureSet::default()),
+            11
+        );
+        assert_eq!(
+            Bank::calculate_fee_details_with_feature_set(&message, 1, &FeatureSet::default()),
+            FeeDetails {
+                signature_fee: 1,
+                precompile_signature_fee: 10,
+                prioritization_fee: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_fee_compute_unit_price() {
+        let key0 = Pubkey::new_unique();
+        let key1 = Pubkey::new_unique();
+        let message = SanitizedMessage::try_from(Message::new(
+            &[
+                ComputeBudgetInstruction::request_units(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(25),
+                system_instruction::transfer(&key0, &key1, 1),
+            ],
+            Some(&key0),
+        ))
+        .unwrap();
+
+        // The requested units are charged at the offered price once the charge is enabled
+        assert_eq!(
+            Bank::calculate_fee_details_with_feature_set(&message, 5, &FeatureSet::all_enabled()),
+            FeeDetails {
+                signature_fee: 5,
+                precompile_signature_fee: 0,
+                prioritization_fee: 3,
+            }
+        );
+
+        // Enabling the instruction alone does not charge for it
+        let mut feature_set = FeatureSet::all_enabled();
+        feature_set
+            .active
+            .remove(&feature_set::charge_compute_unit_price::id());
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 5, &feature_set),
+            5
+        );
+        assert_eq!(
+            Bank::calculate_fee_with_feature_set(&message, 5, &FeatureSet::default()),
+            5
+        );
     }
 }
@@ -1,12 +1,13 @@
 use crate::system_instruction_processor;
 use solana_sdk::{
-    feature_set,
+    feature_set::{self, FeatureSet},
+    hash::Hash,
     instruction::InstructionError,
     process_instruction::{stable_log, InvokeContext, ProcessInstructionWithContext},
     pubkey::Pubkey,
     stake, system_program,
 };
-use std::fmt;
+use std::{fmt, sync::Arc};
 
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
 use solana_frozen_abi::abi_example::AbiExample;
@@ -93,6 +94,43 @@ impl AbiExample for Builtin {
     }
 }
 
+/// Loads and verifies a BPF program as the loader would deploy it under the feature set
+pub type VerifyProgram = fn(&[u8], Arc<FeatureSet>) -> Result<(), InstructionError>;
+
+/// A builtin program replaced by a BPF implementation once a feature is activated
+#[derive(Debug, Clone)]
+pub struct BuiltinMigration {
+    /// Name of the builtin program
+    pub name: String,
+    /// Address of the builtin program, which the BPF implementation takes over
+    pub program_id: Pubkey,
+    /// Feature migrating the builtin program at its activation slot
+    pub feature_id: Pubkey,
+    /// Upgradeable loader buffer holding the BPF implementation
+    pub source_buffer: Pubkey,
+    /// Authority the source buffer must be held by
+    pub buffer_authority: Pubkey,
+    /// Hash of the BPF implementation the source buffer must hold
+    pub program_hash: Hash,
+    /// Verification of the BPF implementation before it is migrated to
+    pub verify_program: VerifyProgram,
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl AbiExample for BuiltinMigration {
+    fn example() -> Self {
+        Self {
+            name: String::default(),
+            program_id: Pubkey::default(),
+            feature_id: Pubkey::default(),
+            source_buffer: Pubkey::default(),
+            buffer_authority: Pubkey::default(),
+            program_hash: Hash::default(),
+            verify_program: |_, _| Ok(()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Builtins {
     /// Builtin programs that are always available
@@ -100,6 +138,9 @@ pub struct Builtins {
 
     /// Builtin programs activated or deactivated dynamically by feature
     pub feature_builtins: Vec<(Builtin, Pubkey, ActivationType)>,
+
+    /// Builtin programs replaced by a BPF implementation by feature
+    pub builtin_migrations: Vec<BuiltinMigration>,
 }
 
 /// Builtin programs that are always available
@@ -176,9 +217,19 @@ fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
     ]
 }
 
+/// Builtin programs replaced by a BPF implementation by feature
+///
+/// Note: The BPF implementation is deployed to the source buffer ahead of the feature activation,
+/// the migration leaves the builtin in place if the buffer is missing or doesn't hold the expected
+/// program.
+fn builtin_migrations() -> Vec<BuiltinMigration> {
+    vec![]
+}
+
 pub(crate) fn get() -> Builtins {
     Builtins {
         genesis_builtins: genesis_builtins(),
         feature_builtins: feature_builtins(),
+        builtin_migrations: builtin_migrations(),
     }
 }
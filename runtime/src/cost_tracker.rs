@@ -2,6 +2,8 @@
 //! The main functions are:
 //! - would_transaction_fit(&tx_cost), immutable function to test if tx with tx_cost would fit into current block
 //! - add_transaction_cost(&tx_cost), mutable function to accumulate tx_cost to tracker.
+//! - try_reserve_transaction_cost(&tx_cost), mutable function to reserve room for a tx about to be
+//!   executed, committed once the tx is recorded or released otherwise.
 //!
 use crate::{block_cost_limits::*, cost_model::TransactionCost};
 use solana_sdk::{clock::Slot, pubkey::Pubkey, transaction::SanitizedTransaction};
@@ -25,6 +27,22 @@ pub struct CostTracker {
     cost_by_writable_accounts: HashMap<Pubkey, u64>,
     block_cost: u64,
     transaction_count: u64,
+    /// cost reserved by transactions being executed, included in block_cost
+    in_flight_cost: u64,
+}
+
+/// Costs accumulated by a block, including the ones of the transactions being executed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockCostReport {
+    pub slot: Slot,
+    pub block_cost: u64,
+    pub in_flight_cost: u64,
+    pub block_cost_limit: u64,
+    pub account_cost_limit: u64,
+    pub transaction_count: u64,
+    pub costliest_account: String,
+    pub costliest_account_cost: u64,
 }
 
 impl Default for CostTracker {
@@ -42,6 +60,7 @@ impl CostTracker {
             cost_by_writable_accounts: HashMap::with_capacity(WRITABLE_ACCOUNTS_PER_BLOCK),
             block_cost: 0,
             transaction_count: 0,
+            in_flight_cost: 0,
         }
     }
 
@@ -78,6 +97,70 @@ impl CostTracker {
         Ok(self.block_cost)
     }
 
+    /// Reserves the cost of a transaction about to be executed if it fits, so that concurrent
+    /// banking threads don't execute transactions the block has no room left for. The
+    /// reservation is either committed once the transaction is recorded, or released.
+    pub fn try_reserve_transaction_cost(
+        &mut self,
+        transaction: &SanitizedTransaction,
+        tx_cost: &TransactionCost,
+    ) -> Result<u64, CostTrackerError> {
+        let block_cost = self.try_add(transaction, tx_cost)?;
+        self.in_flight_cost += tx_cost.sum();
+        Ok(block_cost)
+    }
+
+    /// Reserves the cost of a transaction about to be executed regardless of the limits
+    pub fn reserve_transaction_cost(
+        &mut self,
+        transaction: &SanitizedTransaction,
+        tx_cost: &TransactionCost,
+    ) {
+        self.add_transaction_cost(transaction, tx_cost);
+        self.in_flight_cost += tx_cost.sum();
+    }
+
+    pub fn commit_reserved_transaction_cost(
+        &mut self,
+        _transaction: &SanitizedTransaction,
+        tx_cost: &TransactionCost,
+    ) {
+        self.in_flight_cost = self.in_flight_cost.saturating_sub(tx_cost.sum());
+    }
+
+    /// Gives back the room reserved by a transaction which didn't make it into the block
+    pub fn release_reserved_transaction_cost(
+        &mut self,
+        _transaction: &SanitizedTransaction,
+        tx_cost: &TransactionCost,
+    ) {
+        let cost = tx_cost.sum();
+        self.remove_transaction(&tx_cost.writable_accounts, &cost);
+        self.in_flight_cost = self.in_flight_cost.saturating_sub(cost);
+    }
+
+    pub fn block_cost(&self) -> u64 {
+        self.block_cost
+    }
+
+    pub fn in_flight_cost(&self) -> u64 {
+        self.in_flight_cost
+    }
+
+    pub fn block_cost_report(&self, bank_slot: Slot) -> BlockCostReport {
+        let (costliest_account, costliest_account_cost) = self.find_costliest_account();
+        BlockCostReport {
+            slot: bank_slot,
+            block_cost: self.block_cost,
+            in_flight_cost: self.in_flight_cost,
+            block_cost_limit: self.block_cost_limit,
+            account_cost_limit: self.account_cost_limit,
+            transaction_count: self.transaction_count,
+            costliest_account: costliest_account.to_string(),
+            costliest_account_cost,
+        }
+    }
+
     pub fn report_stats(&self, bank_slot: Slot) {
         // skip reporting if block is empty
         if self.transaction_count == 0 {
@@ -90,6 +173,7 @@ impl CostTracker {
             "cost_tracker_stats",
             ("bank_slot", bank_slot as i64, i64),
             ("block_cost", self.block_cost as i64, i64),
+            ("in_flight_cost", self.in_flight_cost as i64, i64),
             ("transaction_count", self.transaction_count as i64, i64),
             (
                 "number_of_accounts",
@@ -152,6 +236,19 @@ impl CostTracker {
         self.block_cost += cost;
         self.transaction_count += 1;
     }
+
+    fn remove_transaction(&mut self, keys: &[Pubkey], cost: &u64) {
+        for account_key in keys.iter() {
+            if let Some(chained_cost) = self.cost_by_writable_accounts.get_mut(account_key) {
+                *chained_cost = chained_cost.saturating_sub(*cost);
+                if *chained_cost == 0 {
+                    self.cost_by_writable_accounts.remove(account_key);
+                }
+            }
+        }
+        self.block_cost = self.block_cost.saturating_sub(*cost);
+        self.transaction_count = self.transaction_count.saturating_sub(1);
+    }
 }
 
 #[cfg(test)]
@@ -369,4 +466,58 @@ mod tests {
             assert_eq!(acct2, costliest_account);
         }
     }
+
+    #[test]
+    fn test_cost_tracker_reserve_commit_release() {
+        let (mint_keypair, start_hash) = test_setup();
+        let (tx, _keys, _cost) = build_simple_transaction(&mint_keypair, &start_hash);
+        let tx = SanitizedTransaction::from_transaction_for_tests(tx);
+
+        let acct1 = Pubkey::new_unique();
+        let acct2 = Pubkey::new_unique();
+        let cost = 100;
+        let mut testee = CostTracker::new(cost * 2, cost * 3);
+        let tx_cost1 = TransactionCost {
+            writable_accounts: vec![acct1],
+            execution_cost: cost,
+            ..TransactionCost::default()
+        };
+        let tx_cost2 = TransactionCost {
+            writable_accounts: vec![acct1, acct2],
+            execution_cost: cost,
+            ..TransactionCost::default()
+        };
+
+        // reserved costs count against the limits right away
+        assert_eq!(
+            testee.try_reserve_transaction_cost(&tx, &tx_cost1).unwrap(),
+            cost
+        );
+        assert_eq!(
+            testee.try_reserve_transaction_cost(&tx, &tx_cost2).unwrap(),
+            cost * 2
+        );
+        assert_eq!(cost * 2, testee.in_flight_cost());
+        assert!(testee.try_reserve_transaction_cost(&tx, &tx_cost1).is_err());
+
+        // committing keeps the cost in the block
+        testee.commit_reserved_transaction_cost(&tx, &tx_cost1);
+        assert_eq!(cost, testee.in_flight_cost());
+        assert_eq!(cost * 2, testee.block_cost());
+
+        // releasing gives the room back
+        testee.release_reserved_transaction_cost(&tx, &tx_cost2);
+        assert_eq!(0, testee.in_flight_cost());
+        assert_eq!(cost, testee.block_cost());
+        assert_eq!(1, testee.transaction_count);
+        assert_eq!(1, testee.cost_by_writable_accounts.len());
+        assert!(testee.try_reserve_transaction_cost(&tx, &tx_cost2).is_ok());
+
+        let report = testee.block_cost_report(7);
+        assert_eq!(7, report.slot);
+        assert_eq!(cost * 2, report.block_cost);
+        assert_eq!(cost, report.in_flight_cost);
+        assert_eq!(acct1.to_string(), report.costliest_account);
+        assert_eq!(cost * 2, report.costliest_account_cost);
+    }
 }
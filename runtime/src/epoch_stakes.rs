@@ -1,6 +1,7 @@
 use {
     crate::{stakes::Stakes, vote_account::VoteAccount},
     serde::{Deserialize, Serialize},
+    solana_program_runtime::invoke_context::EpochStakeCallback,
     solana_sdk::{clock::Epoch, pubkey::Pubkey},
     std::{collections::HashMap, sync::Arc},
 };
@@ -59,6 +60,19 @@ impl EpochStakes {
             .unwrap_or(0)
     }
 
+    pub fn vote_account_commission(&self, vote_account: &Pubkey) -> Option<u8> {
+        self.stakes
+            .vote_accounts()
+            .get(vote_account)
+            .and_then(|(_stake, account)| {
+                account
+                    .vote_state()
+                    .as_ref()
+                    .ok()
+                    .map(|vote_state| vote_state.commission)
+            })
+    }
+
     fn parse_epoch_vote_accounts(
         epoch_vote_accounts: &HashMap<Pubkey, (u64, VoteAccount)>,
         leader_schedule_epoch: Epoch,
@@ -116,6 +130,32 @@ impl EpochStakes {
     }
 }
 
+/// The stakes a bank's programs see: those of its epoch, and the ones snapshotted for the leader
+/// schedule epoch when its epoch started, which hold the vote accounts as of the epoch boundary
+pub(crate) struct BankEpochStakes<'a> {
+    pub(crate) epoch_stakes: Option<&'a EpochStakes>,
+    pub(crate) epoch_start_stakes: Option<&'a EpochStakes>,
+}
+
+impl<'a> EpochStakeCallback for BankEpochStakes<'a> {
+    fn get_epoch_total_stake(&self) -> u64 {
+        self.epoch_stakes
+            .map(|epoch_stakes| epoch_stakes.total_stake())
+            .unwrap_or(0)
+    }
+
+    fn get_epoch_vote_account_stake(&self, vote_address: &Pubkey) -> u64 {
+        self.epoch_stakes
+            .map(|epoch_stakes| epoch_stakes.vote_account_stake(vote_address))
+            .unwrap_or(0)
+    }
+
+    fn get_epoch_start_commission(&self, vote_address: &Pubkey) -> Option<u8> {
+        self.epoch_start_stakes
+            .and_then(|epoch_stakes| epoch_stakes.vote_account_commission(vote_address))
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
@@ -0,0 +1,360 @@
+//! The `largest_accounts` module keeps track of the accounts holding the most lamports as of the
+//! latest root. The tracker is seeded once with a scan of all the accounts. The slots rooted since
+//! are only queued, and the accounts stored in them are accounted for when the largest accounts
+//! are next asked for, so that they are listed without scanning all the accounts again and
+//! without adding to the cost of rooting a slot.
+
+use {
+    crate::accounts::AccountAddressFilter,
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::collections::{BTreeSet, HashMap, HashSet},
+};
+
+/// Number of accounts tracked by default, enough for the largest accounts to be listed after
+/// some of the tracked ones are filtered out, or lose their balance
+pub const DEFAULT_NUM_TRACKED_LARGEST_ACCOUNTS: usize = 1_000;
+
+/// Number of rooted slots queued before the tracker gives up on them and is rebuilt once asked
+/// for the largest accounts again
+pub const MAX_PENDING_ROOTS: usize = 100_000;
+
+#[derive(Debug)]
+pub struct LargestAccounts {
+    capacity: usize,
+    balances: HashMap<Pubkey, u64>,
+    by_balance: BTreeSet<(u64, Pubkey)>,
+    /// Every account which isn't tracked holds at most this many lamports, unknown until the
+    /// tracker is first seeded
+    untracked_max: Option<u64>,
+    /// Latest root the tracked balances are up to date with
+    root: Option<Slot>,
+    /// Slots rooted since the tracked balances were last updated, queued once the tracker
+    /// starts being rebuilt
+    pending_roots: Option<Vec<Slot>>,
+}
+
+impl Default for LargestAccounts {
+    fn default() -> Self {
+        Self::new(DEFAULT_NUM_TRACKED_LARGEST_ACCOUNTS)
+    }
+}
+
+impl LargestAccounts {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            balances: HashMap::new(),
+            by_balance: BTreeSet::new(),
+            untracked_max: None,
+            root: None,
+            pending_roots: None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Latest root the tracked balances are up to date with, once the tracker was seeded
+    pub fn root(&self) -> Option<Slot> {
+        self.untracked_max.and(self.root)
+    }
+
+    /// Whether the rooted slots are to be queued, which is only the case once the tracker was
+    /// asked for the largest accounts
+    pub fn is_tracking(&self) -> bool {
+        self.pending_roots.is_some()
+    }
+
+    /// Queues `root` for its accounts to be accounted for later. Stops tracking if too many
+    /// roots are queued, the largest accounts no longer being asked for.
+    pub fn add_root(&mut self, root: Slot) {
+        let pending_roots = match self.pending_roots.as_mut() {
+            Some(pending_roots) => pending_roots,
+            None => return,
+        };
+        pending_roots.push(root);
+        if pending_roots.len() > MAX_PENDING_ROOTS {
+            *self = Self::new(self.capacity);
+        }
+    }
+
+    /// Takes the queued roots, once the tracker was seeded
+    pub fn take_pending_roots(&mut self) -> Vec<Slot> {
+        match self.pending_roots.as_mut() {
+            Some(pending_roots) if self.untracked_max.is_some() => std::mem::take(pending_roots),
+            _ => vec![],
+        }
+    }
+
+    /// Accounts currently tracked
+    pub fn tracked(&self) -> Vec<Pubkey> {
+        self.balances.keys().copied().collect()
+    }
+
+    /// Updates the tracked accounts with `balances`, loaded as of `root` or later, which include
+    /// those of the tracked accounts and of all the accounts stored in the roots taken since the
+    /// last update
+    pub fn update(&mut self, root: Slot, balances: impl IntoIterator<Item = (Pubkey, u64)>) {
+        if self.untracked_max.is_none() {
+            return;
+        }
+        for (pubkey, lamports) in balances {
+            self.update_balance(pubkey, lamports);
+        }
+        self.trim();
+        self.root = Some(
+            self.root
+                .map_or(root, |tracked_root| tracked_root.max(root)),
+        );
+    }
+
+    fn update_balance(&mut self, pubkey: Pubkey, lamports: u64) {
+        let was_tracked = match self.balances.remove(&pubkey) {
+            Some(old_lamports) => self.by_balance.remove(&(old_lamports, pubkey)),
+            None => false,
+        };
+        // The tracked accounts are kept whatever their new balance, which is known
+        let is_tracked = was_tracked
+            || self
+                .untracked_max
+                .map(|untracked_max| lamports > untracked_max)
+                .unwrap_or(false);
+        if lamports > 0 && is_tracked {
+            self.balances.insert(pubkey, lamports);
+            self.by_balance.insert((lamports, pubkey));
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.by_balance.len() > self.capacity {
+            let smallest = *self.by_balance.iter().next().unwrap();
+            self.by_balance.remove(&smallest);
+            self.balances.remove(&smallest.1);
+            self.untracked_max = self.untracked_max.map(|max| max.max(smallest.0));
+        }
+    }
+
+    /// Starts rebuilding the tracked accounts, queuing the roots from now on
+    pub fn begin_rebuild(&mut self) {
+        if self.pending_roots.is_none() {
+            self.pending_roots = Some(vec![]);
+        }
+    }
+
+    /// Replaces the tracked accounts with `largest`, the accounts with the most lamports as of
+    /// `root`, which were scanned since the rebuild started. Ignored if the tracked accounts
+    /// were updated past `root` in the meantime, or stopped being tracked.
+    pub fn finish_rebuild(&mut self, root: Slot, largest: Vec<(Pubkey, u64)>) {
+        if self
+            .root()
+            .map_or(false, |tracked_root| tracked_root > root)
+        {
+            return;
+        }
+        let pending_roots = match self.pending_roots.as_mut() {
+            Some(pending_roots) => pending_roots,
+            None => return,
+        };
+        // The roots up to the scanned one are accounted for
+        pending_roots.retain(|pending_root| *pending_root > root);
+        // The scan holds all the accounts with lamports if it didn't fill up
+        self.untracked_max = Some(if largest.len() < self.capacity {
+            0
+        } else {
+            largest
+                .iter()
+                .map(|(_, lamports)| *lamports)
+                .min()
+                .unwrap_or_default()
+        });
+        self.balances.clear();
+        self.by_balance.clear();
+        for (pubkey, lamports) in largest.into_iter().filter(|(_, lamports)| *lamports > 0) {
+            self.balances.insert(pubkey, lamports);
+            self.by_balance.insert((lamports, pubkey));
+        }
+        self.root = Some(root);
+    }
+
+    /// Keeps the tracked accounts as they are, the rebuild being aborted. Stops queuing the
+    /// roots if the tracker was never seeded.
+    pub fn abort_rebuild(&mut self) {
+        if self.untracked_max.is_none() {
+            self.pending_roots = None;
+        }
+    }
+
+    /// Returns the `num` accounts with the most lamports, out of the tracked ones and `unrooted`,
+    /// the latest balances of the accounts stored since the root. Returns `None` if some of the
+    /// accounts which aren't tracked could be among them.
+    pub fn largest(
+        &self,
+        num: usize,
+        unrooted: &HashMap<Pubkey, u64>,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: &AccountAddressFilter,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        let untracked_max = self.untracked_max?;
+        if num == 0 {
+            return Some(vec![]);
+        }
+        let mut largest: Vec<_> = self
+            .by_balance
+            .iter()
+            .filter(|(_, pubkey)| !unrooted.contains_key(pubkey))
+            .copied()
+            .chain(
+                unrooted
+                    .iter()
+                    .filter(|(_, lamports)| **lamports > 0)
+                    .map(|(pubkey, lamports)| (*lamports, *pubkey)),
+            )
+            .filter(|(_, pubkey)| {
+                let contains_address = filter_by_address.contains(pubkey);
+                match filter {
+                    AccountAddressFilter::Exclude => !contains_address,
+                    AccountAddressFilter::Include => contains_address,
+                }
+            })
+            .collect();
+        largest.sort_unstable_by(|a, b| b.cmp(a));
+        largest.truncate(num);
+        // The untracked accounts holding as many lamports as the last one are left out
+        let is_complete = match largest.last() {
+            Some((lamports, _)) if largest.len() == num => *lamports >= untracked_max,
+            _ => untracked_max == 0,
+        };
+        is_complete.then(|| {
+            largest
+                .into_iter()
+                .map(|(lamports, pubkey)| (pubkey, lamports))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn largest(
+        largest_accounts: &LargestAccounts,
+        num: usize,
+        unrooted: &HashMap<Pubkey, u64>,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        largest_accounts.largest(
+            num,
+            unrooted,
+            &HashSet::new(),
+            &AccountAddressFilter::Exclude,
+        )
+    }
+
+    #[test]
+    fn test_largest_accounts() {
+        let pubkeys: Vec<_> = (0..5).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        let mut largest_accounts = LargestAccounts::new(3);
+        let no_unrooted = HashMap::new();
+
+        // The roots aren't queued until the first rebuild
+        largest_accounts.add_root(1);
+        largest_accounts.update(1, vec![(pubkeys[0], 10)]);
+        assert!(!largest_accounts.is_tracking());
+        assert_eq!(largest(&largest_accounts, 1, &no_unrooted), None);
+
+        // The roots queued during the rebuild are kept past the scanned one
+        largest_accounts.begin_rebuild();
+        largest_accounts.add_root(1);
+        largest_accounts.add_root(2);
+        assert_eq!(largest_accounts.take_pending_roots(), Vec::<Slot>::new());
+        largest_accounts.finish_rebuild(
+            1,
+            vec![(pubkeys[1], 40), (pubkeys[2], 30), (pubkeys[3], 20)],
+        );
+        assert_eq!(largest_accounts.root(), Some(1));
+        assert_eq!(largest_accounts.take_pending_roots(), vec![2]);
+        assert_eq!(largest_accounts.take_pending_roots(), Vec::<Slot>::new());
+        largest_accounts.update(2, vec![(pubkeys[1], 50)]);
+        assert_eq!(largest_accounts.root(), Some(2));
+        assert_eq!(
+            largest(&largest_accounts, 2, &no_unrooted),
+            Some(vec![(pubkeys[1], 50), (pubkeys[2], 30)])
+        );
+
+        // Only the accounts with more lamports than the untracked ones are tracked
+        largest_accounts.update(3, vec![(pubkeys[0], 15), (pubkeys[4], 25)]);
+        assert_eq!(
+            largest(&largest_accounts, 3, &no_unrooted),
+            Some(vec![(pubkeys[1], 50), (pubkeys[2], 30), (pubkeys[4], 25)])
+        );
+        assert_eq!(largest_accounts.balances.get(&pubkeys[0]), None);
+
+        // Emptied accounts are forgotten, the untracked ones may then be among the largest
+        largest_accounts.update(4, vec![(pubkeys[1], 0)]);
+        assert_eq!(
+            largest(&largest_accounts, 2, &no_unrooted),
+            Some(vec![(pubkeys[2], 30), (pubkeys[4], 25)])
+        );
+        assert_eq!(largest(&largest_accounts, 3, &no_unrooted), None);
+
+        // The unrooted balances override the tracked ones
+        let unrooted = vec![(pubkeys[2], 1), (pubkeys[3], 60)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            largest(&largest_accounts, 2, &unrooted),
+            Some(vec![(pubkeys[3], 60), (pubkeys[4], 25)])
+        );
+        assert_eq!(
+            largest_accounts.largest(
+                2,
+                &unrooted,
+                &vec![pubkeys[3]].into_iter().collect(),
+                &AccountAddressFilter::Include,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_largest_accounts_all_tracked() {
+        let pubkeys: Vec<_> = (0..2).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        let mut largest_accounts = LargestAccounts::new(3);
+        largest_accounts.begin_rebuild();
+        largest_accounts.abort_rebuild();
+        assert!(!largest_accounts.is_tracking());
+
+        // All the accounts with lamports are tracked if fewer than the capacity were scanned
+        largest_accounts.begin_rebuild();
+        largest_accounts.finish_rebuild(5, vec![(pubkeys[0], 7)]);
+        largest_accounts.update(6, vec![(pubkeys[1], 1)]);
+        assert_eq!(
+            largest(&largest_accounts, 3, &HashMap::new()),
+            Some(vec![(pubkeys[0], 7), (pubkeys[1], 1)])
+        );
+
+        // A rebuild scanned before the latest update is ignored
+        largest_accounts.begin_rebuild();
+        largest_accounts.finish_rebuild(5, vec![]);
+        assert_eq!(largest_accounts.root(), Some(6));
+        assert_eq!(largest_accounts.tracked().len(), 2);
+    }
+
+    #[test]
+    fn test_largest_accounts_too_many_pending_roots() {
+        let mut largest_accounts = LargestAccounts::new(3);
+        largest_accounts.begin_rebuild();
+        largest_accounts.finish_rebuild(0, vec![(solana_sdk::pubkey::new_rand(), 7)]);
+        for root in 1..=MAX_PENDING_ROOTS as Slot {
+            largest_accounts.add_root(root);
+        }
+        assert!(largest_accounts.is_tracking());
+
+        // The tracker is to be rebuilt once too many roots are queued
+        largest_accounts.add_root(MAX_PENDING_ROOTS as Slot + 1);
+        assert!(!largest_accounts.is_tracking());
+        assert_eq!(largest_accounts.root(), None);
+        assert_eq!(largest(&largest_accounts, 1, &HashMap::new()), None);
+    }
+}
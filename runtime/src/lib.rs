@@ -5,6 +5,7 @@ pub mod accounts_background_service;
 pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_hash;
+pub mod accounts_hash_audit;
 pub mod accounts_index;
 pub mod accounts_index_storage;
 pub mod accounts_update_notifier_interface;
@@ -32,6 +33,7 @@ pub mod genesis_utils;
 pub mod hardened_unpack;
 pub mod in_mem_accounts_index;
 pub mod inline_spl_token_v2_0;
+pub mod largest_accounts;
 pub mod loader_utils;
 pub mod message_processor;
 pub mod neon_evm_program;
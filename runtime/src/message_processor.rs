@@ -3,17 +3,18 @@ use solana_measure::measure::Measure;
 use solana_program_runtime::{
     instruction_processor::{ExecuteDetailsTimings, Executors, InstructionProcessor},
     instruction_recorder::InstructionRecorder,
-    invoke_context::ThisInvokeContext,
+    invoke_context::{EpochStakeCallback, ThisInvokeContext},
     log_collector::LogCollector,
 };
 use solana_sdk::{
     account::{AccountSharedData, WritableAccount},
-    compute_budget::ComputeBudget,
+    compute_budget::{ComputeBudget, ComputeBudgetExceeded},
     feature_set::{
         neon_evm_compute_budget, prevent_calling_precompiles_as_programs, requestable_heap_size,
         tx_wide_compute_cap, FeatureSet,
     },
     hash::Hash,
+    ic_logger_msg,
     message::Message,
     precompiles::is_precompile,
     process_instruction::{ComputeMeter, InvokeContext},
@@ -42,6 +43,8 @@ impl MessageProcessor {
     /// For each instruction it calls the program entrypoint method and verifies that the result of
     /// the call does not violate the bank's accounting rules.
     /// The accounts are committed back to the bank only if every instruction succeeds.
+    /// If an instruction runs out of compute units, the details of where the budget was
+    /// exhausted are logged and stored in `compute_budget_exceeded`.
     #[allow(clippy::too_many_arguments)]
     pub fn process_message(
         instruction_processor: &InstructionProcessor,
@@ -59,6 +62,8 @@ impl MessageProcessor {
         sysvars: &[(Pubkey, Vec<u8>)],
         blockhash: Hash,
         lamports_per_signature: u64,
+        epoch_stakes: Option<&dyn EpochStakeCallback>,
+        compute_budget_exceeded: &mut Option<ComputeBudgetExceeded>,
     ) -> Result<(), TransactionError> {
         let mut invoke_context = ThisInvokeContext::new(
             rent,
@@ -73,8 +78,10 @@ impl MessageProcessor {
             feature_set,
             blockhash,
             lamports_per_signature,
+            epoch_stakes,
         );
         let compute_meter = invoke_context.get_compute_meter();
+        let mut units_consumed = Vec::with_capacity(message.instructions.len());
 
         debug_assert_eq!(program_indices.len(), message.instructions.len());
         for (instruction_index, (instruction, program_indices)) in message
@@ -88,6 +95,7 @@ impl MessageProcessor {
                 && is_precompile(program_id, |id| invoke_context.is_feature_active(id))
             {
                 // Precompiled programs don't have an instruction processor
+                units_consumed.push(0);
                 continue;
             }
 
@@ -138,13 +146,36 @@ impl MessageProcessor {
 
             time.stop();
             let post_remaining_units = compute_meter.borrow().get_remaining();
-            timings.accumulate_program(
-                instruction.program_id(&message.account_keys),
-                time.as_us(),
-                pre_remaining_units - post_remaining_units,
-            );
-
-            result?;
+            let instruction_units = pre_remaining_units.saturating_sub(post_remaining_units);
+            units_consumed.push(instruction_units);
+            timings.accumulate_program(program_id, time.as_us(), instruction_units);
+
+            if let Err(err) = result {
+                // Programs report running out of compute units differently, BPF programs fail
+                // to complete for instance, so the exhausted meter is what identifies it
+                if post_remaining_units == 0 {
+                    let logger = invoke_context.get_logger();
+                    ic_logger_msg!(
+                        logger,
+                        "Program {} exceeded the compute budget of {} units in instruction {}",
+                        program_id,
+                        compute_budget.max_units,
+                        instruction_index
+                    );
+                    ic_logger_msg!(
+                        logger,
+                        "Compute units consumed per instruction: {:?}",
+                        units_consumed
+                    );
+                    *compute_budget_exceeded = Some(ComputeBudgetExceeded {
+                        instruction_index: instruction_index as u8,
+                        program_id: *program_id,
+                        units_consumed,
+                        units_limit: compute_budget.max_units,
+                    });
+                }
+                return Err(err);
+            }
         }
         Ok(())
     }
@@ -269,6 +300,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].1.borrow().lamports(), 100);
@@ -299,6 +332,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -333,6 +368,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -476,6 +513,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -510,6 +549,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
 
@@ -541,6 +582,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].1.borrow().lamports(), 80);
@@ -597,6 +640,8 @@ mod tests {
             &[],
             Hash::default(),
             0,
+            None,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -606,4 +651,81 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_compute_budget_exceeded() {
+        let mock_program_id = Pubkey::new_unique();
+        fn mock_process_instruction(
+            _first_instruction_account: usize,
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            let units = bincode::deserialize(data).unwrap();
+            invoke_context
+                .get_compute_meter()
+                .borrow_mut()
+                .consume(units)
+        }
+        let mut instruction_processor = InstructionProcessor::default();
+        instruction_processor.add_program(&mock_program_id, mock_process_instruction);
+
+        let mock_program_account = AccountSharedData::new_ref(1, 0, &native_loader::id());
+        mock_program_account.borrow_mut().set_executable(true);
+        let accounts = vec![(mock_program_id, mock_program_account)];
+
+        let message = Message::new(
+            &[
+                Instruction::new_with_bincode(mock_program_id, &100u64, vec![]),
+                Instruction::new_with_bincode(mock_program_id, &1_000u64, vec![]),
+            ],
+            None,
+        );
+        let compute_budget = ComputeBudget {
+            max_units: 500,
+            ..ComputeBudget::new()
+        };
+        let log_collector = Rc::new(LogCollector::default());
+        let mut compute_budget_exceeded = None;
+
+        let result = MessageProcessor::process_message(
+            &instruction_processor,
+            &message,
+            &[vec![0], vec![0]],
+            &accounts,
+            RentCollector::default().rent,
+            Some(log_collector.clone()),
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+            compute_budget,
+            ThisComputeMeter::new_ref(compute_budget.max_units),
+            &mut ExecuteDetailsTimings::default(),
+            &[],
+            Hash::default(),
+            0,
+            None,
+            &mut compute_budget_exceeded,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::ComputationalBudgetExceeded
+            ))
+        );
+        assert_eq!(
+            compute_budget_exceeded,
+            Some(ComputeBudgetExceeded {
+                instruction_index: 1,
+                program_id: mock_program_id,
+                units_consumed: vec![100, 400],
+                units_limit: 500,
+            })
+        );
+        let logs: Vec<String> = Rc::try_unwrap(log_collector).ok().unwrap().into();
+        assert!(logs.contains(&format!(
+            "Program {} exceeded the compute budget of 500 units in instruction 1",
+            mock_program_id
+        )));
+    }
 }
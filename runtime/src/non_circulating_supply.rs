@@ -1,6 +1,6 @@
 use {
     crate::{
-        accounts_index::{AccountIndex, IndexKey, ScanResult},
+        accounts_index::{AccountIndex, IndexKey, ScanConfig, ScanResult},
         bank::Bank,
     },
     log::*,
@@ -42,9 +42,10 @@ pub fn calculate_non_circulating_supply(bank: &Arc<Bank>) -> ScanResult<NonCircu
             // zero-lamport Account::Default() after being wiped and reinitialized in later
             // updates. We include the redundant filter here to avoid returning these accounts.
             |account| account.owner() == &stake::program::id(),
+            &ScanConfig::default(),
         )?
     } else {
-        bank.get_program_accounts(&stake::program::id())?
+        bank.get_program_accounts(&stake::program::id(), &ScanConfig::default())?
     };
 
     for (pubkey, account) in stake_accounts.iter() {
@@ -3,6 +3,7 @@ use {
         accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
         accounts_index::AccountSecondaryIndexes,
         accounts_update_notifier_interface::AccountsUpdateNotifier,
+        append_vec::{AppendVec, AppendVecFormat},
         bank::{Bank, BankSlotDelta},
         builtins::Builtins,
         hardened_unpack::{unpack_snapshot, ParallelSelector, UnpackError, UnpackedAppendVecMap},
@@ -49,6 +50,7 @@ pub const DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 100_000;
 pub const DEFAULT_INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 100;
 const MAX_SNAPSHOT_DATA_FILE_SIZE: u64 = 32 * 1024 * 1024 * 1024; // 32 GiB
 const VERSION_STRING_V1_2_0: &str = "1.2.0";
+const VERSION_STRING_V1_3_0: &str = "1.3.0";
 const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion::V1_2_0;
 pub(crate) const TMP_BANK_SNAPSHOT_PREFIX: &str = "tmp-bank-snapshot-";
 pub const TMP_SNAPSHOT_ARCHIVE_PREFIX: &str = "tmp-snapshot-archive-";
@@ -61,6 +63,9 @@ pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-sna
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
     V1_2_0,
+    /// Same serialization as V1_2_0, but the archived storages may use the v2 append vec format,
+    /// which nodes that only read V1_2_0 archives can't load
+    V1_3_0,
 }
 
 impl Default for SnapshotVersion {
@@ -79,6 +84,7 @@ impl From<SnapshotVersion> for &'static str {
     fn from(snapshot_version: SnapshotVersion) -> &'static str {
         match snapshot_version {
             SnapshotVersion::V1_2_0 => VERSION_STRING_V1_2_0,
+            SnapshotVersion::V1_3_0 => VERSION_STRING_V1_3_0,
         }
     }
 }
@@ -98,6 +104,7 @@ impl FromStr for SnapshotVersion {
         };
         match version_string {
             VERSION_STRING_V1_2_0 => Ok(SnapshotVersion::V1_2_0),
+            VERSION_STRING_V1_3_0 => Ok(SnapshotVersion::V1_3_0),
             _ => Err("unsupported snapshot version"),
         }
     }
@@ -242,6 +249,22 @@ pub fn remove_tmp_snapshot_archives(snapshot_archives_dir: impl AsRef<Path>) {
     }
 }
 
+/// Return the version to record in the archive of `snapshot_package`. Storages in the v2 append
+/// vec format are archived as is, so archives holding any are marked with a version that nodes
+/// without v2 support refuse, rather than failing to load the storages.
+fn archived_snapshot_version(snapshot_package: &SnapshotPackage) -> SnapshotVersion {
+    let has_v2_storages = snapshot_package
+        .snapshot_storages
+        .iter()
+        .flatten()
+        .any(|storage| storage.accounts.format() != AppendVecFormat::V1);
+    if has_v2_storages {
+        SnapshotVersion::V1_3_0
+    } else {
+        snapshot_package.snapshot_version
+    }
+}
+
 /// Make a snapshot archive out of the snapshot package
 pub fn archive_snapshot_package(
     snapshot_package: &SnapshotPackage,
@@ -299,11 +322,20 @@ pub fn archive_snapshot_package(
     for storage in snapshot_package.snapshot_storages.iter().flatten() {
         storage.flush()?;
         let storage_path = storage.get_path();
-        let output_path = staging_accounts_dir.join(crate::append_vec::AppendVec::file_name(
+        let output_path = staging_accounts_dir.join(AppendVec::file_name(
             storage.slot(),
             storage.append_vec_id(),
         ));
 
+        if storage.is_in_memory() {
+            // There's no file to link to, so the contents are written out instead
+            storage
+                .accounts
+                .write_to_file(&output_path)
+                .map_err(|e| SnapshotError::IoWithSource(e, "write in-memory storage"))?;
+            continue;
+        }
+
         // `storage_path` - The file path where the AppendVec itself is located
         // `output_path` - The file path where the AppendVec will be placed in the staging directory.
         let storage_path =
@@ -319,7 +351,8 @@ pub fn archive_snapshot_package(
     {
         let mut f = fs::File::create(staging_version_file)
             .map_err(|e| SnapshotError::IoWithSource(e, "create version file"))?;
-        f.write_all(snapshot_package.snapshot_version.as_str().as_bytes())
+        let snapshot_version = archived_snapshot_version(snapshot_package);
+        f.write_all(snapshot_version.as_str().as_bytes())
             .map_err(|e| SnapshotError::IoWithSource(e, "write version file"))?;
     }
 
@@ -640,7 +673,7 @@ pub fn add_bank_snapshot<P: AsRef<Path>>(
     let mut bank_serialize = Measure::start("bank-serialize-ms");
     let bank_snapshot_serializer = move |stream: &mut BufWriter<File>| -> Result<()> {
         let serde_style = match snapshot_version {
-            SnapshotVersion::V1_2_0 => SerdeStyle::Newer,
+            SnapshotVersion::V1_2_0 | SnapshotVersion::V1_3_0 => SerdeStyle::Newer,
         };
         bank_to_stream(serde_style, stream.by_ref(), bank, snapshot_storages)?;
         Ok(())
@@ -1464,7 +1497,7 @@ fn rebuild_bank_from_snapshots(
     let bank = deserialize_snapshot_data_files(&snapshot_root_paths, |snapshot_streams| {
         Ok(
             match incremental_snapshot_version.unwrap_or(full_snapshot_version) {
-                SnapshotVersion::V1_2_0 => bank_from_streams(
+                SnapshotVersion::V1_2_0 | SnapshotVersion::V1_3_0 => bank_from_streams(
                     SerdeStyle::Newer,
                     snapshot_streams,
                     account_paths,
@@ -2002,6 +2035,15 @@ mod tests {
         assert_matches!(result, Err(SnapshotError::Io(ref message)) if message.to_string().starts_with("invalid snapshot data file"));
     }
 
+    #[test]
+    fn test_snapshot_version_from_str() {
+        for version in [SnapshotVersion::V1_2_0, SnapshotVersion::V1_3_0] {
+            assert_eq!(version.as_str().parse(), Ok(version));
+        }
+        assert_eq!("v1.3.0".parse(), Ok(SnapshotVersion::V1_3_0));
+        assert!("1.4.0".parse::<SnapshotVersion>().is_err());
+    }
+
     #[test]
     fn test_parse_full_snapshot_archive_filename() {
         assert_eq!(
@@ -33,7 +33,7 @@ fn main() {
         store.capacity(),
         num_accounts,
     );
-    for account in store.accounts(0) {
+    for account in store.accounts(store.first_account_offset()) {
         info!(
             "  account: {:?} version: {} data: {} hash: {:?}",
             account.meta.pubkey, account.meta.write_version, account.meta.data_len, account.hash
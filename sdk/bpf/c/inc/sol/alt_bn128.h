@@ -0,0 +1,54 @@
+#pragma once
+/**
+ * @brief Solana alt_bn128 system call
+ */
+
+#include <sol/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/** Add two G1 points */
+#define ALT_BN128_ADD 0
+/** Multiply a G1 point by a scalar */
+#define ALT_BN128_MUL 2
+/** Check a product of pairings */
+#define ALT_BN128_PAIRING 3
+
+/** Length of an addition result */
+#define ALT_BN128_ADDITION_OUTPUT_LEN 64
+/** Length of a multiplication result */
+#define ALT_BN128_MULTIPLICATION_OUTPUT_LEN 64
+/** Length of a pairing result */
+#define ALT_BN128_PAIRING_OUTPUT_LEN 32
+
+/** The input data is invalid */
+#define ALT_BN128_ERROR_INVALID_INPUT_DATA 1
+/** A point is not on the curve or not in the correct subgroup */
+#define ALT_BN128_ERROR_GROUP_ERROR 2
+/** Unexpected error */
+#define ALT_BN128_ERROR_UNEXPECTED_ERROR 3
+
+/**
+ * Perform an operation on the alt_bn128 curve, using the big-endian encoding
+ * of EIP-196 and EIP-197.
+ *
+ * @param group_op One of ALT_BN128_ADD, ALT_BN128_MUL or ALT_BN128_PAIRING
+ * @param input Encoded points and scalars
+ * @param input_size Length of input in bytes
+ * @param result Array to hold the result, of the operation's output length
+ * @return 0 if executed successfully
+ */
+uint64_t sol_alt_bn128_group_op(
+    uint64_t group_op,
+    const uint8_t *input,
+    uint64_t input_size,
+    uint8_t *result
+);
+
+#ifdef __cplusplus
+}
+#endif
+
+/**@}*/
@@ -88,6 +88,13 @@ static uint64_t sol_invoke(
   );
 }
 
+/**
+ * Get the height of the invocation stack of the current instruction, 1 for an
+ * instruction of the transaction and one more for each level of cross-program
+ * invocation
+ */
+uint64_t sol_get_stack_height();
+
 #ifdef __cplusplus
 }
 #endif
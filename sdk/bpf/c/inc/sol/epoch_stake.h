@@ -0,0 +1,26 @@
+#pragma once
+/**
+ * @brief Solana epoch stake system call
+ */
+
+#include <sol/pubkey.h>
+#include <sol/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * Get the stake of the current epoch
+ *
+ * @param vote_address Vote account to get the delegated stake of, or NULL to
+ *   get the total stake of the epoch
+ * @return The stake in lamports, 0 if the vote account has no stake
+ */
+uint64_t sol_get_epoch_stake(const SolPubkey *vote_address);
+
+#ifdef __cplusplus
+}
+#endif
+
+/**@}*/
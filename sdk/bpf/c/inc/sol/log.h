@@ -29,6 +29,11 @@ void sol_log_64_(uint64_t, uint64_t, uint64_t, uint64_t, uint64_t);
 void sol_log_compute_units_();
 #define sol_log_compute_units() sol_log_compute_units_()
 
+/**
+ * Returns the number of compute units remaining
+ */
+uint64_t sol_remaining_compute_units();
+
 /**
  * Prints the hexadecimal representation of an array
  *
@@ -0,0 +1,45 @@
+#pragma once
+/**
+ * @brief Solana Poseidon system call
+ */
+
+#include <sol/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/** Length of a Poseidon hash result */
+#define POSEIDON_RESULT_LENGTH 32
+
+/** BN254 scalar field with x^5 S-boxes, compatible with circomlib */
+#define POSEIDON_PARAMETERS_BN254_X5 0
+
+/** Big-endian inputs and result */
+#define POSEIDON_ENDIANNESS_BIG_ENDIAN 0
+/** Little-endian inputs and result */
+#define POSEIDON_ENDIANNESS_LITTLE_ENDIAN 1
+
+/**
+ * Poseidon
+ *
+ * @param parameters Parameters of the permutation, POSEIDON_PARAMETERS_BN254_X5
+ * @param endianness Byte order of the inputs and the result
+ * @param bytes Array of field elements of at most 32 bytes each
+ * @param bytes_len Number of field elements, at most 12
+ * @param result 32 byte array to hold the result
+ * @return 0 if executed successfully
+ */
+uint64_t sol_poseidon(
+    uint64_t parameters,
+    uint64_t endianness,
+    const SolBytes *bytes,
+    uint64_t bytes_len,
+    uint8_t *result
+);
+
+#ifdef __cplusplus
+}
+#endif
+
+/**@}*/
@@ -3,14 +3,17 @@
  * @brief Solana C-based BPF program types and utility functions
  */
 
+#include <sol/alt_bn128.h>
 #include <sol/assert.h>
 #include <sol/blake3.h>
 #include <sol/cpi.h>
 #include <sol/deserialize.h>
 #include <sol/deserialize_deprecated.h>
 #include <sol/entrypoint.h>
+#include <sol/epoch_stake.h>
 #include <sol/keccak.h>
 #include <sol/log.h>
+#include <sol/poseidon.h>
 #include <sol/pubkey.h>
 #include <sol/return_data.h>
 #include <sol/secp256k1.h>
@@ -4,8 +4,12 @@ use {
         crate_description, crate_name, crate_version, value_t, value_t_or_exit, values_t, App, Arg,
     },
     regex::Regex,
+    serde_json::{json, Value},
     solana_download_utils::download_file,
-    solana_sdk::signature::{write_keypair_file, Keypair},
+    solana_sdk::{
+        hash::{hash, Hash, Hasher},
+        signature::{write_keypair_file, Keypair},
+    },
     std::{
         collections::{HashMap, HashSet},
         env,
@@ -29,10 +33,15 @@ struct Config<'a> {
     generate_child_script_on_failure: bool,
     no_default_features: bool,
     offline: bool,
+    skip_unchanged: bool,
     verbose: bool,
     workspace: bool,
 }
 
+/// Lists the programs produced in the output directory, with the hash of each program and of
+/// the sources it was built from
+const BUILD_MANIFEST_FILE_NAME: &str = "build-manifest.json";
+
 impl Default for Config<'_> {
     fn default() -> Self {
         Self {
@@ -50,6 +59,7 @@ impl Default for Config<'_> {
             generate_child_script_on_failure: false,
             no_default_features: false,
             offline: false,
+            skip_unchanged: false,
             verbose: false,
             workspace: false,
         }
@@ -396,7 +406,20 @@ fn link_bpf_toolchain(config: &Config) {
     }
 }
 
-fn build_bpf_package(config: &Config, target_directory: &Path, package: &cargo_metadata::Package) {
+fn bpf_out_dir(config: &Config, target_directory: &Path) -> PathBuf {
+    config
+        .bpf_out_dir
+        .as_ref()
+        .cloned()
+        .unwrap_or_else(|| target_directory.join("deploy"))
+}
+
+/// Returns the path of the deployable program, if the package has a cdylib target
+fn build_bpf_package(
+    config: &Config,
+    target_directory: &Path,
+    package: &cargo_metadata::Package,
+) -> Option<PathBuf> {
     let program_name = {
         let cdylib_targets = package
             .targets
@@ -435,11 +458,7 @@ fn build_bpf_package(config: &Config, target_directory: &Path, package: &cargo_m
         exit(1);
     });
 
-    let bpf_out_dir = config
-        .bpf_out_dir
-        .as_ref()
-        .cloned()
-        .unwrap_or_else(|| target_directory.join("deploy"));
+    let bpf_out_dir = bpf_out_dir(config, target_directory);
 
     let target_build_directory = target_directory
         .join("bpfel-unknown-unknown")
@@ -633,11 +652,125 @@ fn build_bpf_package(config: &Config, target_directory: &Path, package: &cargo_m
         println!("  $ solana program deploy {}", program_so.display());
         println!("The program address will default to this keypair (override with --program-id):");
         println!("  {}", program_keypair.display());
-    } else if config.dump {
-        println!("Note: --dump is only available for crates with a cdylib target");
+        Some(program_so)
+    } else {
+        if config.dump {
+            println!("Note: --dump is only available for crates with a cdylib target");
+        }
+        None
+    }
+}
+
+/// Hash every source file of `package` and of the workspace packages it depends on by path,
+/// together with the build settings, so that a program only needs rebuilding when this changes.
+/// The build outputs in `target_directory` and `bpf_out_dir` are left out, wherever they are.
+fn source_fingerprint(
+    config: &Config,
+    metadata: &cargo_metadata::Metadata,
+    package: &cargo_metadata::Package,
+    target_directory: &Path,
+    bpf_out_dir: &Path,
+) -> Hash {
+    let mut package_dirs = vec![];
+    let mut pending = vec![package];
+    let mut visited = HashSet::new();
+    while let Some(package) = pending.pop() {
+        if !visited.insert(&package.id) {
+            continue;
+        }
+        if let Some(dir) = package.manifest_path.parent() {
+            package_dirs.push(PathBuf::from(dir.as_std_path()));
+        }
+        for dependency in &package.dependencies {
+            if let Some(path) = &dependency.path {
+                if let Some(dependency) = metadata
+                    .packages
+                    .iter()
+                    .find(|package| package.manifest_path.parent() == Some(path.as_path()))
+                {
+                    pending.push(dependency);
+                }
+            }
+        }
+    }
+
+    let mut files = vec![metadata
+        .workspace_root
+        .join("Cargo.lock")
+        .into_std_path_buf()];
+    let output_dirs: Vec<_> = [target_directory, bpf_out_dir]
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect();
+    for dir in package_dirs {
+        collect_source_files(&dir, &output_dirs, &mut files);
+    }
+    files.sort();
+    files.dedup();
+
+    let mut hasher = Hasher::default();
+    for file in files {
+        if let Ok(contents) = fs::read(&file) {
+            hasher.hash(file.to_string_lossy().as_bytes());
+            hasher.hash(&contents);
+        }
+    }
+    hasher.hash(config.bpf_sdk.to_string_lossy().as_bytes());
+    hasher.hash(&[config.no_default_features as u8]);
+    for feature in &config.features {
+        hasher.hash(feature.as_bytes());
+    }
+    for arg in config.cargo_args.iter().flatten() {
+        hasher.hash(arg.as_bytes());
+    }
+    hasher.hash(env::var("RUSTFLAGS").unwrap_or_default().as_bytes());
+    hasher.result()
+}
+
+fn collect_source_files(dir: &Path, output_dirs: &[PathBuf], files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if path.is_dir() {
+            let is_output_dir = entry.file_name() == "target"
+                || path
+                    .canonicalize()
+                    .map_or(false, |path| output_dirs.contains(&path));
+            if !hidden && !is_output_dir {
+                collect_source_files(&path, output_dirs, files);
+            }
+        } else if !hidden {
+            files.push(path);
+        }
     }
 }
 
+fn read_build_manifest(bpf_out_dir: &Path) -> Vec<Value> {
+    fs::read(bpf_out_dir.join(BUILD_MANIFEST_FILE_NAME))
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<Value>(&contents).ok())
+        .and_then(|manifest| manifest["programs"].as_array().cloned())
+        .unwrap_or_default()
+}
+
+fn write_build_manifest(bpf_out_dir: &Path, programs: Vec<Value>) {
+    let manifest_path = bpf_out_dir.join(BUILD_MANIFEST_FILE_NAME);
+    let contents = serde_json::to_string_pretty(&json!({ "programs": programs }))
+        .expect("Manifest serialization");
+    fs::write(&manifest_path, contents).unwrap_or_else(|err| {
+        eprintln!("Unable to write {}: {}", manifest_path.display(), err);
+        exit(1);
+    });
+}
+
+fn file_hash(path: &Path) -> Option<Hash> {
+    fs::read(path).ok().map(|contents| hash(&contents))
+}
+
 fn build_bpf(config: Config, manifest_path: Option<PathBuf>) {
     let mut metadata_command = cargo_metadata::MetadataCommand::new();
     if let Some(manifest_path) = manifest_path {
@@ -652,30 +785,73 @@ fn build_bpf(config: Config, manifest_path: Option<PathBuf>) {
         exit(1);
     });
 
-    if let Some(root_package) = metadata.root_package() {
-        if !config.workspace {
-            build_bpf_package(&config, metadata.target_directory.as_ref(), root_package);
-            return;
-        }
-    }
-
-    let all_bpf_packages = metadata
-        .packages
-        .iter()
-        .filter(|package| {
-            if metadata.workspace_members.contains(&package.id) {
-                for target in package.targets.iter() {
-                    if target.kind.contains(&"cdylib".to_string()) {
-                        return true;
+    let root_package = metadata.root_package().filter(|_| !config.workspace);
+    let all_bpf_packages = if let Some(root_package) = root_package {
+        vec![root_package]
+    } else {
+        metadata
+            .packages
+            .iter()
+            .filter(|package| {
+                if metadata.workspace_members.contains(&package.id) {
+                    for target in package.targets.iter() {
+                        if target.kind.contains(&"cdylib".to_string()) {
+                            return true;
+                        }
                     }
                 }
-            }
-            false
-        })
-        .collect::<Vec<_>>();
+                false
+            })
+            .collect::<Vec<_>>()
+    };
 
+    let target_directory: &Path = metadata.target_directory.as_ref();
+    let bpf_out_dir = bpf_out_dir(&config, target_directory);
+    let mut manifest = read_build_manifest(&bpf_out_dir);
     for package in all_bpf_packages {
-        build_bpf_package(&config, metadata.target_directory.as_ref(), package);
+        let fingerprint =
+            source_fingerprint(&config, &metadata, package, target_directory, &bpf_out_dir)
+                .to_string();
+        let previous = manifest
+            .iter()
+            .position(|entry| entry["package"] == package.name.as_str());
+        if config.skip_unchanged {
+            let up_to_date = previous.map_or(false, |index| {
+                let entry = &manifest[index];
+                entry["sourceFingerprint"] == fingerprint.as_str()
+                    && entry["path"]
+                        .as_str()
+                        .and_then(|path| file_hash(Path::new(path)))
+                        .map(|hash| entry["hash"] == hash.to_string().as_str())
+                        .unwrap_or(false)
+            });
+            if up_to_date {
+                println!("Skipping {}: up to date", package.name);
+                continue;
+            }
+        }
+
+        if let Some(program_so) = build_bpf_package(&config, target_directory, package) {
+            let program_hash = file_hash(&program_so).unwrap_or_else(|| {
+                eprintln!("Unable to read {}", program_so.display());
+                exit(1);
+            });
+            let entry = json!({
+                "package": package.name,
+                "version": package.version.to_string(),
+                "program": program_so.file_stem().map(|stem| stem.to_string_lossy()),
+                "path": program_so.display().to_string(),
+                "hash": program_hash.to_string(),
+                "sourceFingerprint": fingerprint,
+            });
+            match previous {
+                Some(index) => manifest[index] = entry,
+                None => manifest.push(entry),
+            }
+        }
+    }
+    if !manifest.is_empty() {
+        write_build_manifest(&bpf_out_dir, manifest);
     }
 }
 
@@ -761,6 +937,15 @@ fn main() {
                 .takes_value(false)
                 .help("Run without accessing the network"),
         )
+        .arg(
+            Arg::with_name("skip_unchanged")
+                .long("skip-unchanged")
+                .takes_value(false)
+                .help(
+                    "Skip packages whose sources and build settings are unchanged since \
+                       the program recorded in the build manifest was built",
+                ),
+        )
         .arg(
             Arg::with_name("verbose")
                 .short("v")
@@ -808,6 +993,7 @@ fn main() {
         generate_child_script_on_failure: matches.is_present("generate_child_script_on_failure"),
         no_default_features: matches.is_present("no_default_features"),
         offline: matches.is_present("offline"),
+        skip_unchanged: matches.is_present("skip_unchanged"),
         verbose: matches.is_present("verbose"),
         workspace: matches.is_present("workspace"),
     };
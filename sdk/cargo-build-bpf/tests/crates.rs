@@ -79,6 +79,40 @@ fn test_out_dir() {
     fs::remove_dir_all("tmp_out").expect("Failed to remove tmp_out dir");
 }
 
+#[test]
+#[serial]
+fn test_skip_unchanged() {
+    let output = run_cargo_build("noop", &["--skip-unchanged"]);
+    assert!(output.status.success());
+    let cwd = env::current_dir().expect("Unable to get current working directory");
+    let manifest = cwd
+        .join("tests")
+        .join("crates")
+        .join("noop")
+        .join("target")
+        .join("deploy")
+        .join("build-manifest.json");
+    let manifest = fs::read_to_string(manifest).expect("Unable to read build manifest");
+    assert!(manifest.contains("\"package\": \"noop\""));
+
+    let output = run_cargo_build("noop", &["--skip-unchanged"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Skipping noop: up to date"));
+}
+
+#[test]
+#[serial]
+fn test_skip_unchanged_out_dir_in_package() {
+    // The program written into the package's own directory isn't one of its sources
+    let out_dir = "tests/crates/noop/program_out";
+    let output = run_cargo_build("noop", &["--skip-unchanged", "--bpf-out-dir", out_dir]);
+    assert!(output.status.success());
+    let output = run_cargo_build("noop", &["--skip-unchanged", "--bpf-out-dir", out_dir]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Skipping noop: up to date"));
+    fs::remove_dir_all(out_dir).expect("Failed to remove program_out dir");
+}
+
 #[test]
 #[serial]
 fn test_generate_child_script_on_failre() {
@@ -16,6 +16,7 @@ struct Config {
     bpf_out_dir: Option<String>,
     cargo: PathBuf,
     cargo_build_bpf: PathBuf,
+    compute_budget_config: Option<PathBuf>,
     extra_cargo_test_args: Vec<String>,
     features: Vec<String>,
     generate_child_script_on_failure: bool,
@@ -34,6 +35,7 @@ impl Default for Config {
             bpf_out_dir: None,
             cargo: PathBuf::from("cargo"),
             cargo_build_bpf: PathBuf::from("cargo-build-bpf"),
+            compute_budget_config: None,
             extra_cargo_test_args: vec![],
             features: vec![],
             generate_child_script_on_failure: false,
@@ -135,6 +137,11 @@ fn test_bpf_package(config: &Config, target_directory: &Path, package: &cargo_me
     // Pass --bpf-out-dir along to the solana-program-test crate
     env::set_var("BPF_OUT_DIR", bpf_out_dir);
 
+    // Pass --compute-budget-config along to the solana-program-test crate
+    if let Some(compute_budget_config) = &config.compute_budget_config {
+        env::set_var("COMPUTE_BUDGET_CONFIG", compute_budget_config);
+    }
+
     cargo_args.insert(0, "test");
 
     if let Some(test_name) = &config.test_name {
@@ -262,6 +269,13 @@ fn main() {
                 .takes_value(true)
                 .help("Place final BPF build artifacts in this directory"),
         )
+        .arg(
+            Arg::with_name("compute_budget_config")
+                .long("compute-budget-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Execute test transactions with the compute budget in this TOML file"),
+        )
         .arg(
             Arg::with_name("no_run")
                 .long("no-run")
@@ -306,6 +320,15 @@ fn main() {
     let mut config = Config {
         bpf_sdk: value_t!(matches, "bpf_sdk", String).ok(),
         bpf_out_dir: value_t!(matches, "bpf_out_dir", String).ok(),
+        compute_budget_config: value_t!(matches, "compute_budget_config", PathBuf)
+            .ok()
+            .map(|path| {
+                // Tests run from their package directory
+                path.canonicalize().unwrap_or_else(|err| {
+                    eprintln!("Failed to find {}: {}", path.display(), err);
+                    exit(1);
+                })
+            }),
         extra_cargo_test_args: values_t!(matches, "extra_cargo_test_args", String)
             .ok()
             .unwrap_or_else(Vec::new),
@@ -0,0 +1,355 @@
+//! Operations on the alt_bn128 elliptic curve, as specified by EIP-196 and EIP-197.
+//!
+//! Points and scalars use the big-endian encoding of the Ethereum precompiles so that proofs
+//! and verifying keys produced by existing zk-SNARK tooling can be passed through unchanged.
+use thiserror::Error;
+
+/// Size of an encoded field element
+pub const ALT_BN128_FIELD_SIZE: usize = 32;
+/// Size of an encoded G1 point
+pub const ALT_BN128_POINT_SIZE: usize = 64;
+/// Maximum input length of the addition operation, two G1 points
+pub const ALT_BN128_ADDITION_INPUT_LEN: usize = 128;
+/// Maximum input length of the multiplication operation, a G1 point and a scalar
+pub const ALT_BN128_MULTIPLICATION_INPUT_LEN: usize = 96;
+/// Length of each element of a pairing input, a G1 point and a G2 point
+pub const ALT_BN128_PAIRING_ELEMENT_LEN: usize = 192;
+pub const ALT_BN128_ADDITION_OUTPUT_LEN: usize = 64;
+pub const ALT_BN128_MULTIPLICATION_OUTPUT_LEN: usize = 64;
+pub const ALT_BN128_PAIRING_OUTPUT_LEN: usize = 32;
+
+/// Group operations accepted by the `sol_alt_bn128_group_op` syscall
+pub const ALT_BN128_ADD: u64 = 0;
+pub const ALT_BN128_MUL: u64 = 2;
+pub const ALT_BN128_PAIRING: u64 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AltBn128Error {
+    #[error("The input data is invalid")]
+    InvalidInputData,
+    #[error("A point is not on the curve or not in the correct subgroup")]
+    GroupError,
+    #[error("Unexpected error")]
+    UnexpectedError,
+}
+
+impl From<u64> for AltBn128Error {
+    fn from(v: u64) -> AltBn128Error {
+        match v {
+            1 => AltBn128Error::InvalidInputData,
+            2 => AltBn128Error::GroupError,
+            3 => AltBn128Error::UnexpectedError,
+            _ => panic!("Unsupported AltBn128Error"),
+        }
+    }
+}
+
+impl From<AltBn128Error> for u64 {
+    fn from(v: AltBn128Error) -> u64 {
+        match v {
+            AltBn128Error::InvalidInputData => 1,
+            AltBn128Error::GroupError => 2,
+            AltBn128Error::UnexpectedError => 3,
+        }
+    }
+}
+
+/// Add two G1 points, returning their encoded sum
+///
+/// Inputs shorter than `ALT_BN128_ADDITION_INPUT_LEN` are padded with zeros.
+pub fn alt_bn128_addition(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+    if input.len() > ALT_BN128_ADDITION_INPUT_LEN {
+        return Err(AltBn128Error::InvalidInputData);
+    }
+    #[cfg(target_arch = "bpf")]
+    {
+        group_op(ALT_BN128_ADD, input, ALT_BN128_ADDITION_OUTPUT_LEN)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        host::addition(input)
+    }
+}
+
+/// Multiply a G1 point by a scalar, returning the encoded product
+///
+/// Inputs shorter than `ALT_BN128_MULTIPLICATION_INPUT_LEN` are padded with zeros.
+pub fn alt_bn128_multiplication(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+    if input.len() > ALT_BN128_MULTIPLICATION_INPUT_LEN {
+        return Err(AltBn128Error::InvalidInputData);
+    }
+    #[cfg(target_arch = "bpf")]
+    {
+        group_op(ALT_BN128_MUL, input, ALT_BN128_MULTIPLICATION_OUTPUT_LEN)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        host::multiplication(input)
+    }
+}
+
+/// Check whether the product of the pairings of a list of (G1, G2) pairs is one
+///
+/// Returns a 32 byte big-endian integer, 1 if the check passes and 0 otherwise.
+pub fn alt_bn128_pairing(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+    if input.len() % ALT_BN128_PAIRING_ELEMENT_LEN != 0 {
+        return Err(AltBn128Error::InvalidInputData);
+    }
+    #[cfg(target_arch = "bpf")]
+    {
+        group_op(ALT_BN128_PAIRING, input, ALT_BN128_PAIRING_OUTPUT_LEN)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        host::pairing(input)
+    }
+}
+
+#[cfg(target_arch = "bpf")]
+fn group_op(op: u64, input: &[u8], output_len: usize) -> Result<Vec<u8>, AltBn128Error> {
+    extern "C" {
+        fn sol_alt_bn128_group_op(
+            op: u64,
+            input: *const u8,
+            input_size: u64,
+            result: *mut u8,
+        ) -> u64;
+    }
+
+    let mut result_buffer = vec![0u8; output_len];
+    let result = unsafe {
+        sol_alt_bn128_group_op(
+            op,
+            input.as_ptr(),
+            input.len() as u64,
+            result_buffer.as_mut_ptr(),
+        )
+    };
+
+    match result {
+        0 => Ok(result_buffer),
+        error => Err(AltBn128Error::from(error)),
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+mod host {
+    use {
+        super::*,
+        ark_bn254::{Bn254, Fq, Fq12, Fq2, G1Affine, G2Affine},
+        ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve},
+        ark_ff::{BigInteger, BigInteger256, One, PrimeField, Zero},
+    };
+
+    pub(super) fn addition(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+        let mut input = input.to_vec();
+        input.resize(ALT_BN128_ADDITION_INPUT_LEN, 0);
+        let p = g1_from_be_bytes(&input[..ALT_BN128_POINT_SIZE])?;
+        let q = g1_from_be_bytes(&input[ALT_BN128_POINT_SIZE..])?;
+        Ok(g1_to_be_bytes(
+            (p.into_projective() + q.into_projective()).into_affine(),
+        ))
+    }
+
+    pub(super) fn multiplication(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+        let mut input = input.to_vec();
+        input.resize(ALT_BN128_MULTIPLICATION_INPUT_LEN, 0);
+        let p = g1_from_be_bytes(&input[..ALT_BN128_POINT_SIZE])?;
+        let scalar = bigint_from_be_bytes(&input[ALT_BN128_POINT_SIZE..]);
+        Ok(g1_to_be_bytes(p.mul(scalar).into_affine()))
+    }
+
+    pub(super) fn pairing(input: &[u8]) -> Result<Vec<u8>, AltBn128Error> {
+        let mut pairs = Vec::with_capacity(input.len() / ALT_BN128_PAIRING_ELEMENT_LEN);
+        for element in input.chunks(ALT_BN128_PAIRING_ELEMENT_LEN) {
+            let p = g1_from_be_bytes(&element[..ALT_BN128_POINT_SIZE])?;
+            let q = g2_from_be_bytes(&element[ALT_BN128_POINT_SIZE..])?;
+            pairs.push((p.into(), q.into()));
+        }
+        let result = if Bn254::product_of_pairings(&pairs) == Fq12::one() {
+            BigInteger256::from(1u64)
+        } else {
+            BigInteger256::from(0u64)
+        };
+        Ok(result.to_bytes_be())
+    }
+
+    fn bigint_from_be_bytes(bytes: &[u8]) -> BigInteger256 {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+            let mut limb_bytes = [0u8; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_be_bytes(limb_bytes);
+        }
+        BigInteger256::new(limbs)
+    }
+
+    fn fq_from_be_bytes(bytes: &[u8]) -> Result<Fq, AltBn128Error> {
+        Fq::from_repr(bigint_from_be_bytes(bytes)).ok_or(AltBn128Error::GroupError)
+    }
+
+    // G2 coordinates are encoded imaginary part first
+    fn fq2_from_be_bytes(bytes: &[u8]) -> Result<Fq2, AltBn128Error> {
+        Ok(Fq2::new(
+            fq_from_be_bytes(&bytes[ALT_BN128_FIELD_SIZE..])?,
+            fq_from_be_bytes(&bytes[..ALT_BN128_FIELD_SIZE])?,
+        ))
+    }
+
+    fn g1_from_be_bytes(bytes: &[u8]) -> Result<G1Affine, AltBn128Error> {
+        // The point at infinity is encoded as all zeros, which is not a point on the curve
+        if bytes.iter().all(|byte| *byte == 0) {
+            return Ok(G1Affine::zero());
+        }
+        let point = G1Affine::new(
+            fq_from_be_bytes(&bytes[..ALT_BN128_FIELD_SIZE])?,
+            fq_from_be_bytes(&bytes[ALT_BN128_FIELD_SIZE..])?,
+            false,
+        );
+        if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+            Ok(point)
+        } else {
+            Err(AltBn128Error::GroupError)
+        }
+    }
+
+    fn g2_from_be_bytes(bytes: &[u8]) -> Result<G2Affine, AltBn128Error> {
+        if bytes.iter().all(|byte| *byte == 0) {
+            return Ok(G2Affine::zero());
+        }
+        let point = G2Affine::new(
+            fq2_from_be_bytes(&bytes[..ALT_BN128_POINT_SIZE])?,
+            fq2_from_be_bytes(&bytes[ALT_BN128_POINT_SIZE..])?,
+            false,
+        );
+        if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+            Ok(point)
+        } else {
+            Err(AltBn128Error::GroupError)
+        }
+    }
+
+    fn g1_to_be_bytes(point: G1Affine) -> Vec<u8> {
+        if point.is_zero() {
+            return vec![0u8; ALT_BN128_POINT_SIZE];
+        }
+        let mut bytes = point.x.into_repr().to_bytes_be();
+        bytes.extend_from_slice(&point.y.into_repr().to_bytes_be());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generator of G1
+    const G1: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                      0000000000000000000000000000000000000000000000000000000000000002";
+    // Negation of the generator of G1
+    const G1_NEG: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                          30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    // Generator of G2
+    const G2: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                      1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                      090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                      12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    fn decode(hex_str: &str) -> Vec<u8> {
+        hex::decode(hex_str).unwrap()
+    }
+
+    #[test]
+    fn test_alt_bn128_addition() {
+        // EIP-196 test vector
+        let input = decode(
+            "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+             063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+             07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+             06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+        );
+        assert_eq!(
+            alt_bn128_addition(&input).unwrap(),
+            decode(
+                "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+                 301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915"
+            )
+        );
+
+        // A point plus its negation is the point at infinity
+        let input = [decode(G1), decode(G1_NEG)].concat();
+        assert_eq!(alt_bn128_addition(&input).unwrap(), vec![0u8; 64]);
+
+        // Empty input adds two points at infinity
+        assert_eq!(alt_bn128_addition(&[]).unwrap(), vec![0u8; 64]);
+
+        // (1, 3) is not on the curve
+        let mut input = [decode(G1), decode(G1)].concat();
+        input[63] = 3;
+        assert_eq!(alt_bn128_addition(&input), Err(AltBn128Error::GroupError));
+
+        assert_eq!(
+            alt_bn128_addition(&[0u8; ALT_BN128_ADDITION_INPUT_LEN + 1]),
+            Err(AltBn128Error::InvalidInputData)
+        );
+    }
+
+    #[test]
+    fn test_alt_bn128_multiplication() {
+        // EIP-196 test vector
+        let input = decode(
+            "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+             21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204\
+             00000000000000000000000000000000000000000000000011138ce750fa15c2",
+        );
+        assert_eq!(
+            alt_bn128_multiplication(&input).unwrap(),
+            decode(
+                "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c\
+                 031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc"
+            )
+        );
+
+        // Multiplying by two matches adding a point to itself
+        let mut input = decode(G1);
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(2);
+        assert_eq!(
+            alt_bn128_multiplication(&input).unwrap(),
+            alt_bn128_addition(&[decode(G1), decode(G1)].concat()).unwrap()
+        );
+
+        assert_eq!(
+            alt_bn128_multiplication(&[0u8; ALT_BN128_MULTIPLICATION_INPUT_LEN + 1]),
+            Err(AltBn128Error::InvalidInputData)
+        );
+    }
+
+    #[test]
+    fn test_alt_bn128_pairing() {
+        let one = {
+            let mut one = vec![0u8; 32];
+            one[31] = 1;
+            one
+        };
+
+        // The empty product is one
+        assert_eq!(alt_bn128_pairing(&[]).unwrap(), one);
+
+        // e(P, Q) * e(-P, Q) == 1
+        let input = [decode(G1), decode(G2), decode(G1_NEG), decode(G2)].concat();
+        assert_eq!(alt_bn128_pairing(&input).unwrap(), one);
+
+        // e(P, Q) * e(P, Q) != 1
+        let input = [decode(G1), decode(G2), decode(G1), decode(G2)].concat();
+        assert_eq!(alt_bn128_pairing(&input).unwrap(), vec![0u8; 32]);
+
+        assert_eq!(
+            alt_bn128_pairing(&input[1..]),
+            Err(AltBn128Error::InvalidInputData)
+        );
+    }
+}
@@ -200,6 +200,10 @@ pub fn is_close_instruction(instruction_data: &[u8]) -> bool {
     !instruction_data.is_empty() && 5 == instruction_data[0]
 }
 
+pub fn is_set_authority_checked_instruction(instruction_data: &[u8]) -> bool {
+    !instruction_data.is_empty() && 6 == instruction_data[0]
+}
+
 /// Returns the instructions required to set a buffers's authority.
 pub fn set_buffer_authority(
     buffer_address: &Pubkey,
@@ -235,6 +239,43 @@ pub fn set_upgrade_authority(
     Instruction::new_with_bincode(id(), &UpgradeableLoaderInstruction::SetAuthority, metas)
 }
 
+/// Returns the instructions required to set a buffers's authority, which the new authority
+/// must sign.
+pub fn set_buffer_authority_checked(
+    buffer_address: &Pubkey,
+    current_authority_address: &Pubkey,
+    new_authority_address: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &UpgradeableLoaderInstruction::SetAuthorityChecked,
+        vec![
+            AccountMeta::new(*buffer_address, false),
+            AccountMeta::new_readonly(*current_authority_address, true),
+            AccountMeta::new_readonly(*new_authority_address, true),
+        ],
+    )
+}
+
+/// Returns the instructions required to set a program's authority, which the new authority
+/// must sign.
+pub fn set_upgrade_authority_checked(
+    program_address: &Pubkey,
+    current_authority_address: &Pubkey,
+    new_authority_address: &Pubkey,
+) -> Instruction {
+    let (programdata_address, _) = Pubkey::find_program_address(&[program_address.as_ref()], &id());
+    Instruction::new_with_bincode(
+        id(),
+        &UpgradeableLoaderInstruction::SetAuthorityChecked,
+        vec![
+            AccountMeta::new(programdata_address, false),
+            AccountMeta::new_readonly(*current_authority_address, true),
+            AccountMeta::new_readonly(*new_authority_address, true),
+        ],
+    )
+}
+
 /// Returns the instructions required to close a buffer account
 pub fn close(
     close_address: &Pubkey,
@@ -351,6 +392,15 @@ mod tests {
             is_instruction_fn(&bincode::serialize(&UpgradeableLoaderInstruction::Close).unwrap());
         let expected_result = matches!(expected_instruction, UpgradeableLoaderInstruction::Close);
         assert_eq!(expected_result, result);
+
+        let result = is_instruction_fn(
+            &bincode::serialize(&UpgradeableLoaderInstruction::SetAuthorityChecked).unwrap(),
+        );
+        let expected_result = matches!(
+            expected_instruction,
+            UpgradeableLoaderInstruction::SetAuthorityChecked
+        );
+        assert_eq!(expected_result, result);
     }
 
     #[test]
@@ -362,6 +412,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_set_authority_checked_instruction() {
+        assert!(!is_set_authority_checked_instruction(&[]));
+        assert_is_instruction(
+            is_set_authority_checked_instruction,
+            UpgradeableLoaderInstruction::SetAuthorityChecked {},
+        );
+    }
+
     #[test]
     fn test_is_upgrade_instruction() {
         assert!(!is_upgrade_instruction(&[]));
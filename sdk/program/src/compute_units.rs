@@ -0,0 +1,19 @@
+//! Compute budget introspection
+
+/// Return the number of compute units the program may still consume
+///
+/// The call itself is charged a small, fixed number of units before the remaining units are
+/// read, so programs can poll it inside a loop to stop working before their budget runs out.
+pub fn sol_remaining_compute_units() -> u64 {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_remaining_compute_units() -> u64;
+        }
+
+        unsafe { sol_remaining_compute_units() }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_remaining_compute_units()
+}
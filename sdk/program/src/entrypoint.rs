@@ -32,6 +32,19 @@ pub const HEAP_START_ADDRESS: u64 = 0x300000000;
 /// Length of the heap memory region used for program heap.
 pub const HEAP_LENGTH: usize = 32 * 1024;
 
+/// Name of the ELF section in which a program declares its stack requirements
+pub const STACK_NOTE_SECTION: &str = ".note.solana.stack";
+/// Owner name of the stack requirements note
+pub const STACK_NOTE_NAME: &str = "Solana";
+/// Type of the stack requirements note
+pub const STACK_NOTE_TYPE: u32 = 1;
+/// Maximum stack frame size a program may declare
+pub const MAX_STACK_FRAME_SIZE: usize = 64 * 1024;
+/// Maximum call depth a program may declare
+pub const MAX_CALL_DEPTH: usize = 256;
+/// Maximum total stack size, frame size times call depth, a program may declare
+pub const MAX_STACK_SIZE: usize = 1024 * 1024;
+
 /// Declare the program entry point and set up global handlers.
 ///
 /// This macro emits the common boilerplate necessary to begin program
@@ -216,6 +229,54 @@ macro_rules! custom_panic_default {
     };
 }
 
+/// Declare the stack frame size and maximum call depth the program requires.
+///
+/// By default programs get stack frames of 4KB, the size the BPF backend of
+/// LLVM assumes, and a call depth of 64. Programs compiled with larger stack
+/// frames, or that recurse deeply, can declare what they need in an ELF note
+/// that the loader reads when the program is deployed. The stack frame size
+/// must be a power of two multiple of the default size. The additional stack
+/// memory is charged for in compute units each time the program is invoked.
+///
+/// ```ignore
+/// // 8KB stack frames, up to 128 frames deep
+/// solana_program::custom_stack!(8 * 1024, 128);
+/// ```
+#[macro_export]
+macro_rules! custom_stack {
+    ($stack_frame_size:expr, $max_call_depth:expr) => {
+        #[cfg(target_arch = "bpf")]
+        #[link_section = ".note.solana.stack"]
+        #[used]
+        static SOLANA_STACK_NOTE: $crate::entrypoint::StackNote =
+            $crate::entrypoint::StackNote::new($stack_frame_size, $max_call_depth);
+    };
+}
+
+/// The ELF note emitted by [`custom_stack`]
+#[repr(C)]
+pub struct StackNote {
+    name_size: u32,
+    desc_size: u32,
+    note_type: u32,
+    name: [u8; 8],
+    stack_frame_size: u32,
+    max_call_depth: u32,
+}
+
+impl StackNote {
+    pub const fn new(stack_frame_size: usize, max_call_depth: usize) -> Self {
+        Self {
+            name_size: 7,
+            desc_size: 8,
+            note_type: STACK_NOTE_TYPE,
+            name: *b"Solana\0\0",
+            stack_frame_size: stack_frame_size as u32,
+            max_call_depth: max_call_depth as u32,
+        }
+    }
+}
+
 /// The bump allocator used as the default rust heap when running programs.
 pub struct BumpAllocator {
     pub start: usize,
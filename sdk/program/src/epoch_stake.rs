@@ -0,0 +1,32 @@
+//! Stake of the current epoch
+//!
+//! The stakes are those used for the current epoch's leader schedule and vote weighting, so
+//! programs can weight votes by stake without relying on an oracle.
+
+use crate::pubkey::Pubkey;
+
+fn get_epoch_stake(vote_address: *const u8) -> u64 {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_epoch_stake(vote_address: *const u8) -> u64;
+        }
+
+        unsafe { sol_get_epoch_stake(vote_address) }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_epoch_stake(vote_address)
+}
+
+/// Return the total active stake of the current epoch
+pub fn get_epoch_total_stake() -> u64 {
+    get_epoch_stake(std::ptr::null())
+}
+
+/// Return the active stake delegated to a vote account in the current epoch
+///
+/// Returns zero if the account is not a vote account or has no stake delegated to it.
+pub fn get_epoch_stake_for_vote_account(vote_address: &Pubkey) -> u64 {
+    get_epoch_stake(vote_address as *const _ as *const u8)
+}
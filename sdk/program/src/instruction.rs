@@ -236,7 +236,128 @@ pub enum InstructionError {
     #[error("Provided owner is not allowed")]
     IllegalOwner,
     // Note: For any new error added here an equivalent ProgramError and its
-    // conversions must also be added
+    // conversions must also be added, as well as its code in
+    // INSTRUCTION_ERROR_CODES
+}
+
+/// Stable numeric code and name of every [`InstructionError`] variant, indexed by code.
+///
+/// Codes are never changed or reused, so clients in any language can interpret an error from
+/// its code alone. A code is the index the variant is serialized with.
+pub const INSTRUCTION_ERROR_CODES: &[(u32, &str)] = &[
+    (0, "GenericError"),
+    (1, "InvalidArgument"),
+    (2, "InvalidInstructionData"),
+    (3, "InvalidAccountData"),
+    (4, "AccountDataTooSmall"),
+    (5, "InsufficientFunds"),
+    (6, "IncorrectProgramId"),
+    (7, "MissingRequiredSignature"),
+    (8, "AccountAlreadyInitialized"),
+    (9, "UninitializedAccount"),
+    (10, "UnbalancedInstruction"),
+    (11, "ModifiedProgramId"),
+    (12, "ExternalAccountLamportSpend"),
+    (13, "ExternalAccountDataModified"),
+    (14, "ReadonlyLamportChange"),
+    (15, "ReadonlyDataModified"),
+    (16, "DuplicateAccountIndex"),
+    (17, "ExecutableModified"),
+    (18, "RentEpochModified"),
+    (19, "NotEnoughAccountKeys"),
+    (20, "AccountDataSizeChanged"),
+    (21, "AccountNotExecutable"),
+    (22, "AccountBorrowFailed"),
+    (23, "AccountBorrowOutstanding"),
+    (24, "DuplicateAccountOutOfSync"),
+    (25, "Custom"),
+    (26, "InvalidError"),
+    (27, "ExecutableDataModified"),
+    (28, "ExecutableLamportChange"),
+    (29, "ExecutableAccountNotRentExempt"),
+    (30, "UnsupportedProgramId"),
+    (31, "CallDepth"),
+    (32, "MissingAccount"),
+    (33, "ReentrancyNotAllowed"),
+    (34, "MaxSeedLengthExceeded"),
+    (35, "InvalidSeeds"),
+    (36, "InvalidRealloc"),
+    (37, "ComputationalBudgetExceeded"),
+    (38, "PrivilegeEscalation"),
+    (39, "ProgramEnvironmentSetupFailure"),
+    (40, "ProgramFailedToComplete"),
+    (41, "ProgramFailedToCompile"),
+    (42, "Immutable"),
+    (43, "IncorrectAuthority"),
+    (44, "BorshIoError"),
+    (45, "AccountNotRentExempt"),
+    (46, "InvalidAccountOwner"),
+    (47, "ArithmeticOverflow"),
+    (48, "UnsupportedSysvar"),
+    (49, "IllegalOwner"),
+];
+
+impl InstructionError {
+    /// Stable numeric code of the error, see [`INSTRUCTION_ERROR_CODES`]
+    pub fn code(&self) -> u32 {
+        match self {
+            InstructionError::GenericError => 0,
+            InstructionError::InvalidArgument => 1,
+            InstructionError::InvalidInstructionData => 2,
+            InstructionError::InvalidAccountData => 3,
+            InstructionError::AccountDataTooSmall => 4,
+            InstructionError::InsufficientFunds => 5,
+            InstructionError::IncorrectProgramId => 6,
+            InstructionError::MissingRequiredSignature => 7,
+            InstructionError::AccountAlreadyInitialized => 8,
+            InstructionError::UninitializedAccount => 9,
+            InstructionError::UnbalancedInstruction => 10,
+            InstructionError::ModifiedProgramId => 11,
+            InstructionError::ExternalAccountLamportSpend => 12,
+            InstructionError::ExternalAccountDataModified => 13,
+            InstructionError::ReadonlyLamportChange => 14,
+            InstructionError::ReadonlyDataModified => 15,
+            InstructionError::DuplicateAccountIndex => 16,
+            InstructionError::ExecutableModified => 17,
+            InstructionError::RentEpochModified => 18,
+            InstructionError::NotEnoughAccountKeys => 19,
+            InstructionError::AccountDataSizeChanged => 20,
+            InstructionError::AccountNotExecutable => 21,
+            InstructionError::AccountBorrowFailed => 22,
+            InstructionError::AccountBorrowOutstanding => 23,
+            InstructionError::DuplicateAccountOutOfSync => 24,
+            InstructionError::Custom(..) => 25,
+            InstructionError::InvalidError => 26,
+            InstructionError::ExecutableDataModified => 27,
+            InstructionError::ExecutableLamportChange => 28,
+            InstructionError::ExecutableAccountNotRentExempt => 29,
+            InstructionError::UnsupportedProgramId => 30,
+            InstructionError::CallDepth => 31,
+            InstructionError::MissingAccount => 32,
+            InstructionError::ReentrancyNotAllowed => 33,
+            InstructionError::MaxSeedLengthExceeded => 34,
+            InstructionError::InvalidSeeds => 35,
+            InstructionError::InvalidRealloc => 36,
+            InstructionError::ComputationalBudgetExceeded => 37,
+            InstructionError::PrivilegeEscalation => 38,
+            InstructionError::ProgramEnvironmentSetupFailure => 39,
+            InstructionError::ProgramFailedToComplete => 40,
+            InstructionError::ProgramFailedToCompile => 41,
+            InstructionError::Immutable => 42,
+            InstructionError::IncorrectAuthority => 43,
+            InstructionError::BorshIoError(..) => 44,
+            InstructionError::AccountNotRentExempt => 45,
+            InstructionError::InvalidAccountOwner => 46,
+            InstructionError::ArithmeticOverflow => 47,
+            InstructionError::UnsupportedSysvar => 48,
+            InstructionError::IllegalOwner => 49,
+        }
+    }
+
+    /// Name of the error variant, see [`INSTRUCTION_ERROR_CODES`]
+    pub fn name(&self) -> &'static str {
+        INSTRUCTION_ERROR_CODES[self.code() as usize].1
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -377,10 +498,53 @@ impl CompiledInstruction {
     }
 }
 
+/// The stack height of the instructions of a transaction, invoked by the runtime
+pub const TRANSACTION_LEVEL_STACK_HEIGHT: usize = 1;
+
+/// Get the height of the invocation stack of the current instruction
+///
+/// The height is `TRANSACTION_LEVEL_STACK_HEIGHT` for an instruction of the transaction, and one
+/// more for each level of cross-program invocation, so that a program can tell whether it was
+/// invoked by another program without that program passing it in the instruction data.
+pub fn get_stack_height() -> usize {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_stack_height() -> u64;
+        }
+
+        unsafe { sol_get_stack_height() as usize }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        crate::program_stubs::sol_get_stack_height() as usize
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_instruction_error_codes() {
+        for (index, (code, name)) in INSTRUCTION_ERROR_CODES.iter().enumerate() {
+            assert_eq!(index as u32, *code);
+            // The code deserializes to the variant it names, with a zeroed
+            // payload if the variant has one.
+            let mut data = code.to_le_bytes().to_vec();
+            data.extend_from_slice(&[0; 8]);
+            let err: InstructionError = bincode::deserialize(&data).unwrap();
+            assert_eq!(err.code(), *code);
+            assert_eq!(err.name(), *name);
+            assert!(format!("{:?}", err).starts_with(name));
+            assert_eq!(serialize(&err).unwrap()[..4], code.to_le_bytes());
+        }
+        // Every variant is in the table.
+        let data = (INSTRUCTION_ERROR_CODES.len() as u32).to_le_bytes();
+        assert!(bincode::deserialize::<InstructionError>(&data).is_err());
+    }
+
     #[test]
     fn test_visit_each_account() {
         let do_work = |accounts: &[u8]| -> (usize, usize) {
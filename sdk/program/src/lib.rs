@@ -6,17 +6,20 @@
 extern crate self as solana_program;
 
 pub mod account_info;
+pub mod alt_bn128;
 pub mod blake3;
 pub mod borsh;
 pub mod bpf_loader;
 pub mod bpf_loader_deprecated;
 pub mod bpf_loader_upgradeable;
 pub mod clock;
+pub mod compute_units;
 pub mod decode_error;
 pub mod ed25519_program;
 pub mod entrypoint;
 pub mod entrypoint_deprecated;
 pub mod epoch_schedule;
+pub mod epoch_stake;
 pub mod feature;
 pub mod fee_calculator;
 pub mod hash;
@@ -30,6 +33,7 @@ pub mod log;
 pub mod message;
 pub mod native_token;
 pub mod nonce;
+pub mod poseidon;
 pub mod program;
 pub mod program_error;
 pub mod program_memory;
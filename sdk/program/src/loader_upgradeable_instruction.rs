@@ -125,4 +125,16 @@ pub enum UpgradeableLoaderInstruction {
     ///   3. `[writable]` The associated Program account if the account to close
     ///      is a ProgramData account.
     Close,
+
+    /// Set a new authority that is allowed to write the buffer or upgrade the
+    /// program.  Unlike `SetAuthority`, the new authority must sign, so that
+    /// the authority can't be handed to an address nobody controls.  The
+    /// authority can't be removed with this instruction.
+    ///
+    /// # Account references
+    ///   0. `[writable]` The Buffer or ProgramData account to change the
+    ///      authority of.
+    ///   1. `[signer]` The current authority.
+    ///   2. `[signer]` The new authority.
+    SetAuthorityChecked,
 }
@@ -0,0 +1,199 @@
+//! Builders of instructions and messages, to assemble them a piece at a time
+
+use crate::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+/// Builds an [`Instruction`] out of its program, accounts and data
+///
+/// ```
+/// # use solana_program::{message::InstructionBuilder, pubkey::Pubkey};
+/// let (program_id, from, to) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+/// let instruction = InstructionBuilder::new(program_id)
+///     .writable_signer(from)
+///     .writable(to)
+///     .data(vec![1, 2, 3])
+///     .build();
+/// assert!(instruction.accounts[0].is_signer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct InstructionBuilder {
+    program_id: Pubkey,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+}
+
+impl InstructionBuilder {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self {
+            program_id,
+            accounts: vec![],
+            data: vec![],
+        }
+    }
+
+    pub fn account(mut self, account: AccountMeta) -> Self {
+        self.accounts.push(account);
+        self
+    }
+
+    pub fn writable(self, pubkey: Pubkey) -> Self {
+        self.account(AccountMeta::new(pubkey, false))
+    }
+
+    pub fn writable_signer(self, pubkey: Pubkey) -> Self {
+        self.account(AccountMeta::new(pubkey, true))
+    }
+
+    pub fn readonly(self, pubkey: Pubkey) -> Self {
+        self.account(AccountMeta::new_readonly(pubkey, false))
+    }
+
+    pub fn readonly_signer(self, pubkey: Pubkey) -> Self {
+        self.account(AccountMeta::new_readonly(pubkey, true))
+    }
+
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn build(self) -> Instruction {
+        Instruction::new_with_bytes(self.program_id, &self.data, self.accounts)
+    }
+}
+
+/// Builds a legacy [`Message`] out of its instructions, fee payer and blockhash
+///
+/// ```
+/// # use solana_program::{hash::Hash, message::MessageBuilder, pubkey::Pubkey, system_instruction};
+/// let (payer, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let message = MessageBuilder::new()
+///     .payer(payer)
+///     .instruction(system_instruction::transfer(&payer, &to, 42))
+///     .recent_blockhash(Hash::new_unique())
+///     .build();
+/// assert_eq!(message.account_keys[0], payer);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MessageBuilder {
+    instructions: Vec<Instruction>,
+    payer: Option<Pubkey>,
+    recent_blockhash: Hash,
+    /// Nonce account and its authority, when the message uses a durable nonce
+    nonce: Option<(Pubkey, Pubkey)>,
+}
+
+impl MessageBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the account paying the fees, the first signer of the message
+    pub fn payer(mut self, payer: Pubkey) -> Self {
+        self.payer = Some(payer);
+        self
+    }
+
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Sets the blockhash of the message, or the nonce when it uses a durable nonce
+    pub fn recent_blockhash(mut self, recent_blockhash: Hash) -> Self {
+        self.recent_blockhash = recent_blockhash;
+        self
+    }
+
+    /// Makes the message use the durable nonce of `nonce_account_pubkey`, by advancing it in its
+    /// first instruction
+    pub fn nonce(mut self, nonce_account_pubkey: Pubkey, nonce_authority_pubkey: Pubkey) -> Self {
+        self.nonce = Some((nonce_account_pubkey, nonce_authority_pubkey));
+        self
+    }
+
+    pub fn build(self) -> Message {
+        let Self {
+            mut instructions,
+            payer,
+            recent_blockhash,
+            nonce,
+        } = self;
+        if let Some((nonce_account_pubkey, nonce_authority_pubkey)) = nonce {
+            instructions.insert(
+                0,
+                system_instruction::advance_nonce_account(
+                    &nonce_account_pubkey,
+                    &nonce_authority_pubkey,
+                ),
+            );
+        }
+        Message::new_with_blockhash(&instructions, payer.as_ref(), &recent_blockhash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_builder() {
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let transfer = system_instruction::transfer(&payer, &to, 42);
+        assert_eq!(
+            MessageBuilder::new()
+                .payer(payer)
+                .instruction(transfer.clone())
+                .recent_blockhash(blockhash)
+                .build(),
+            Message::new_with_blockhash(&[transfer.clone()], Some(&payer), &blockhash)
+        );
+
+        let nonce_account = Pubkey::new_unique();
+        assert_eq!(
+            MessageBuilder::new()
+                .payer(payer)
+                .instructions(vec![transfer.clone()])
+                .nonce(nonce_account, payer)
+                .recent_blockhash(blockhash)
+                .build(),
+            Message {
+                recent_blockhash: blockhash,
+                ..Message::new_with_nonce(vec![transfer], Some(&payer), &nonce_account, &payer)
+            }
+        );
+    }
+
+    #[test]
+    fn test_instruction_builder() {
+        let program_id = Pubkey::new_unique();
+        let accounts = vec![
+            AccountMeta::new(Pubkey::new_unique(), true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ];
+        assert_eq!(
+            InstructionBuilder::new(program_id)
+                .writable_signer(accounts[0].pubkey)
+                .writable(accounts[1].pubkey)
+                .readonly_signer(accounts[2].pubkey)
+                .readonly(accounts[3].pubkey)
+                .data(vec![7])
+                .build(),
+            Instruction::new_with_bytes(program_id, &[7], accounts)
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Rendering of messages for humans, in logs and test failures: the accounts with their roles,
+//! then the instructions with the names of the programs they invoke and their data
+
+use {
+    crate::{
+        bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, ed25519_program,
+        message::Message, pubkey::Pubkey, secp256k1_program, stake,
+        system_instruction::SystemInstruction, system_program, sysvar,
+    },
+    bincode::Options,
+    lazy_static::lazy_static,
+    std::{collections::HashMap, fmt, str::FromStr},
+};
+
+lazy_static! {
+    static ref BUILTIN_PROGRAM_NAMES: HashMap<Pubkey, &'static str> = {
+        // The ids of the programs outside of this crate are parsed
+        let parse = |s| Pubkey::from_str(s).unwrap();
+        [
+            (system_program::id(), "System Program"),
+            (parse("Vote111111111111111111111111111111111111111"), "Vote Program"),
+            (stake::program::id(), "Stake Program"),
+            (stake::config::id(), "Stake Config"),
+            (parse("Config1111111111111111111111111111111111111"), "Config Program"),
+            (parse("ComputeBudget111111111111111111111111111111"), "Compute Budget Program"),
+            (parse("Feature111111111111111111111111111111111111"), "Feature Program"),
+            (parse("NativeLoader1111111111111111111111111111111"), "Native Loader"),
+            (bpf_loader_deprecated::id(), "BPF Loader"),
+            (bpf_loader::id(), "BPF Loader 2"),
+            (bpf_loader_upgradeable::id(), "BPF Upgradeable Loader"),
+            (secp256k1_program::id(), "Secp256k1 Program"),
+            (ed25519_program::id(), "Ed25519 Program"),
+            (sysvar::clock::id(), "Clock Sysvar"),
+            (sysvar::epoch_schedule::id(), "Epoch Schedule Sysvar"),
+            (sysvar::instructions::id(), "Instructions Sysvar"),
+            (sysvar::rent::id(), "Rent Sysvar"),
+            (sysvar::rewards::id(), "Rewards Sysvar"),
+            (sysvar::slot_hashes::id(), "Slot Hashes Sysvar"),
+            (sysvar::slot_history::id(), "Slot History Sysvar"),
+            (sysvar::stake_history::id(), "Stake History Sysvar"),
+        ]
+        .iter()
+        .cloned()
+        .collect()
+    };
+}
+
+/// Name of a program or sysvar built in the runtime
+pub fn builtin_program_name(pubkey: &Pubkey) -> Option<&'static str> {
+    BUILTIN_PROGRAM_NAMES.get(pubkey).copied()
+}
+
+/// Renders a message with the names of the builtin programs, and of the programs of a registry
+pub struct MessageDisplay<'a> {
+    message: &'a Message,
+    program_names: Option<&'a HashMap<Pubkey, String>>,
+}
+
+impl Message {
+    /// Renders the message with the names of `program_names` on top of the builtin ones
+    pub fn display_with_program_names<'a>(
+        &'a self,
+        program_names: &'a HashMap<Pubkey, String>,
+    ) -> MessageDisplay<'a> {
+        MessageDisplay {
+            message: self,
+            program_names: Some(program_names),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        MessageDisplay {
+            message: self,
+            program_names: None,
+        }
+        .fmt(f)
+    }
+}
+
+impl MessageDisplay<'_> {
+    fn name(&self, pubkey: &Pubkey) -> Option<&str> {
+        self.program_names
+            .and_then(|program_names| program_names.get(pubkey))
+            .map(String::as_str)
+            .or_else(|| builtin_program_name(pubkey))
+    }
+
+    /// Decodes the data of the instructions of the builtin programs that use bincode
+    fn parse_data(program_id: &Pubkey, data: &[u8]) -> Option<String> {
+        let options = bincode::options()
+            .with_limit(data.len() as u64)
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+        if system_program::check_id(program_id) {
+            options
+                .deserialize::<SystemInstruction>(data)
+                .ok()
+                .map(|instruction| format!("{:?}", instruction))
+        } else if stake::program::check_id(program_id) {
+            options
+                .deserialize::<stake::instruction::StakeInstruction>(data)
+                .ok()
+                .map(|instruction| format!("{:?}", instruction))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for MessageDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = self.message;
+        writeln!(f, "Recent blockhash: {}", message.recent_blockhash)?;
+        writeln!(f, "Accounts:")?;
+        for (i, pubkey) in message.account_keys.iter().enumerate() {
+            let mut roles = vec![];
+            if message.is_signer(i) {
+                roles.push("signer");
+            }
+            if message.is_writable(i, /*demote_program_write_locks=*/ true) {
+                roles.push("writable");
+            } else {
+                roles.push("readonly");
+            }
+            if i == 0 && message.header.num_required_signatures > 0 {
+                roles.push("fee payer");
+            }
+            if message.is_key_called_as_program(i) {
+                roles.push("program");
+            }
+            write!(f, "  {} {} {}", i, pubkey, roles.join(", "))?;
+            match self.name(pubkey) {
+                Some(name) => writeln!(f, " ({})", name)?,
+                None => writeln!(f)?,
+            }
+        }
+        writeln!(f, "Instructions:")?;
+        for (i, instruction) in message.instructions.iter().enumerate() {
+            let program_id = message
+                .account_keys
+                .get(instruction.program_id_index as usize);
+            match program_id {
+                Some(program_id) => match self.name(program_id) {
+                    Some(name) => writeln!(f, "  {} {}", i, name)?,
+                    None => writeln!(f, "  {} {}", i, program_id)?,
+                },
+                None => writeln!(
+                    f,
+                    "  {} invalid program index {}",
+                    i, instruction.program_id_index
+                )?,
+            }
+            writeln!(
+                f,
+                "    accounts: [{}]",
+                instruction
+                    .accounts
+                    .iter()
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+            writeln!(f, "    data: {}", hex::encode(&instruction.data))?;
+            if let Some(parsed) =
+                program_id.and_then(|program_id| Self::parse_data(program_id, &instruction.data))
+            {
+                writeln!(f, "    parsed: {}", parsed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::system_instruction};
+
+    #[test]
+    fn test_message_display() {
+        let payer = Pubkey::new(&[1; 32]);
+        let to = Pubkey::new(&[2; 32]);
+        let message = Message::new(
+            &[system_instruction::transfer(&payer, &to, 42)],
+            Some(&payer),
+        );
+        assert_eq!(
+            message.to_string(),
+            format!(
+                "Recent blockhash: 11111111111111111111111111111111
+Accounts:
+  0 {} signer, writable, fee payer
+  1 {} writable
+  2 11111111111111111111111111111111 readonly, program (System Program)
+Instructions:
+  0 System Program
+    accounts: [0, 1]
+    data: 020000002a00000000000000
+    parsed: Transfer {{ lamports: 42 }}
+",
+                payer, to
+            )
+        );
+
+        let mut program_names = HashMap::new();
+        program_names.insert(to, "Recipient".to_string());
+        assert!(message
+            .display_with_program_names(&program_names)
+            .to_string()
+            .contains(&format!("  1 {} writable (Recipient)\n", to)));
+    }
+}
@@ -5,11 +5,15 @@ pub mod legacy;
 #[cfg(not(target_arch = "bpf"))]
 #[path = ""]
 mod non_bpf_modules {
+    mod builder;
+    mod display;
     mod mapped;
     mod sanitized;
     pub mod v0;
     mod versions;
 
+    pub use builder::*;
+    pub use display::*;
     pub use mapped::*;
     pub use sanitized::*;
     pub use versions::*;
@@ -0,0 +1,443 @@
+//! Hashing with the [Poseidon] hash function.
+//!
+//! [Poseidon]: https://www.poseidon-hash.info/
+use {std::convert::TryFrom, thiserror::Error};
+
+/// Length of a Poseidon hash result
+pub const HASH_BYTES: usize = 32;
+/// Maximum number of inputs accepted by a single hash
+pub const MAX_INPUTS: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PoseidonSyscallError {
+    #[error("Invalid parameters")]
+    InvalidParameters,
+    #[error("Invalid endianness")]
+    InvalidEndianness,
+    #[error("Invalid number of inputs, the maximum is 12")]
+    InvalidNumberOfInputs,
+    #[error("Input is empty or longer than 32 bytes")]
+    InvalidInputLength,
+    #[error("Input is larger than the modulus of the prime field")]
+    InputLargerThanModulus,
+    #[error("Unexpected error")]
+    Unexpected,
+}
+
+impl From<u64> for PoseidonSyscallError {
+    fn from(v: u64) -> PoseidonSyscallError {
+        match v {
+            1 => PoseidonSyscallError::InvalidParameters,
+            2 => PoseidonSyscallError::InvalidEndianness,
+            3 => PoseidonSyscallError::InvalidNumberOfInputs,
+            4 => PoseidonSyscallError::InvalidInputLength,
+            5 => PoseidonSyscallError::InputLargerThanModulus,
+            6 => PoseidonSyscallError::Unexpected,
+            _ => panic!("Unsupported PoseidonSyscallError"),
+        }
+    }
+}
+
+impl From<PoseidonSyscallError> for u64 {
+    fn from(v: PoseidonSyscallError) -> u64 {
+        match v {
+            PoseidonSyscallError::InvalidParameters => 1,
+            PoseidonSyscallError::InvalidEndianness => 2,
+            PoseidonSyscallError::InvalidNumberOfInputs => 3,
+            PoseidonSyscallError::InvalidInputLength => 4,
+            PoseidonSyscallError::InputLargerThanModulus => 5,
+            PoseidonSyscallError::Unexpected => 6,
+        }
+    }
+}
+
+/// Parameters of the Poseidon permutation
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameters {
+    /// The BN254 scalar field with x^5 S-boxes, compatible with [circomlib].
+    ///
+    /// [circomlib]: https://github.com/iden3/circomlib
+    Bn254X5 = 0,
+}
+
+impl TryFrom<u64> for Parameters {
+    type Error = PoseidonSyscallError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            x if x == Parameters::Bn254X5 as u64 => Ok(Parameters::Bn254X5),
+            _ => Err(PoseidonSyscallError::InvalidParameters),
+        }
+    }
+}
+
+/// Byte order of the inputs and of the resulting hash
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    BigEndian = 0,
+    LittleEndian = 1,
+}
+
+impl TryFrom<u64> for Endianness {
+    type Error = PoseidonSyscallError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            x if x == Endianness::BigEndian as u64 => Ok(Endianness::BigEndian),
+            x if x == Endianness::LittleEndian as u64 => Ok(Endianness::LittleEndian),
+            _ => Err(PoseidonSyscallError::InvalidEndianness),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoseidonHash(pub [u8; HASH_BYTES]);
+
+impl PoseidonHash {
+    pub fn new(hash_array: [u8; HASH_BYTES]) -> Self {
+        Self(hash_array)
+    }
+
+    pub fn to_bytes(self) -> [u8; HASH_BYTES] {
+        self.0
+    }
+}
+
+/// Return a Poseidon hash for the given data, each element of which is a field element of at
+/// most 32 bytes
+pub fn hashv(
+    parameters: Parameters,
+    endianness: Endianness,
+    vals: &[&[u8]],
+) -> Result<PoseidonHash, PoseidonSyscallError> {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_poseidon(
+                parameters: u64,
+                endianness: u64,
+                vals: *const u8,
+                val_len: u64,
+                hash_result: *mut u8,
+            ) -> u64;
+        }
+
+        let mut hash_result = [0; HASH_BYTES];
+        let result = unsafe {
+            sol_poseidon(
+                parameters as u64,
+                endianness as u64,
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                &mut hash_result as *mut _ as *mut u8,
+            )
+        };
+
+        match result {
+            0 => Ok(PoseidonHash::new(hash_result)),
+            error => Err(PoseidonSyscallError::from(error)),
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    {
+        let Parameters::Bn254X5 = parameters;
+        if vals.is_empty() || vals.len() > MAX_INPUTS {
+            return Err(PoseidonSyscallError::InvalidNumberOfInputs);
+        }
+        if vals
+            .iter()
+            .any(|val| val.is_empty() || val.len() > HASH_BYTES)
+        {
+            return Err(PoseidonSyscallError::InvalidInputLength);
+        }
+        host::hash_bn254_x5(endianness, vals).map(PoseidonHash::new)
+    }
+}
+
+/// The circomlib compatible Poseidon permutation over the BN254 scalar field.
+///
+/// Its round constants and MDS matrices are derived with the Grain LFSR of the reference
+/// parameter generation script (`generate_parameters_grain.sage` from the Poseidon paper), which
+/// is how circomlib obtained them, rather than embedded as tables.
+#[cfg(not(target_arch = "bpf"))]
+mod host {
+    use {
+        super::*,
+        ark_bn254::Fr,
+        ark_ff::{BigInteger, BigInteger256, Field, PrimeField, Zero},
+        lazy_static::lazy_static,
+    };
+
+    const FULL_ROUNDS: usize = 8;
+    /// Partial rounds for each width, starting at a width of 2 (a single input)
+    const PARTIAL_ROUNDS: [usize; MAX_INPUTS] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65];
+    const FIELD_BITS: usize = 254;
+
+    struct PermutationParameters {
+        width: usize,
+        partial_rounds: usize,
+        round_constants: Vec<Fr>,
+        mds: Vec<Vec<Fr>>,
+    }
+
+    lazy_static! {
+        static ref BN254_X5: Vec<PermutationParameters> = (1..=MAX_INPUTS)
+            .map(|inputs| PermutationParameters::new(inputs + 1))
+            .collect();
+    }
+
+    /// The self-shrinking Grain LFSR that generates the parameters, with bit `i` of the state
+    /// holding the `i`-th oldest bit
+    struct Grain(u128);
+
+    impl Grain {
+        fn new(width: usize, partial_rounds: usize) -> Self {
+            let mut state = 0u128;
+            let mut len = 0;
+            // field type (prime field), S-box type (x^alpha), field size, width and rounds,
+            // followed by 30 set bits
+            for (value, bits) in [
+                (1, 2),
+                (0, 4),
+                (FIELD_BITS, 12),
+                (width, 12),
+                (FULL_ROUNDS, 10),
+                (partial_rounds, 10),
+                ((1 << 30) - 1, 30),
+            ] {
+                for i in (0..bits).rev() {
+                    state |= (((value >> i) & 1) as u128) << len;
+                    len += 1;
+                }
+            }
+            let mut grain = Self(state);
+            for _ in 0..160 {
+                grain.step();
+            }
+            grain
+        }
+
+        fn step(&mut self) -> u64 {
+            let state = self.0;
+            let bit = (state >> 62 ^ state >> 51 ^ state >> 38 ^ state >> 23 ^ state >> 13 ^ state)
+                & 1;
+            self.0 = state >> 1 | bit << 79;
+            bit as u64
+        }
+
+        fn next_bit(&mut self) -> u64 {
+            loop {
+                if self.step() == 1 {
+                    return self.step();
+                }
+                self.step();
+            }
+        }
+
+        fn next_bigint(&mut self) -> BigInteger256 {
+            let mut limbs = [0u64; 4];
+            for i in (0..FIELD_BITS).rev() {
+                limbs[i / 64] |= self.next_bit() << (i % 64);
+            }
+            BigInteger256::new(limbs)
+        }
+    }
+
+    impl PermutationParameters {
+        fn new(width: usize) -> Self {
+            let partial_rounds = PARTIAL_ROUNDS[width - 2];
+            let mut grain = Grain::new(width, partial_rounds);
+
+            let num_constants = (FULL_ROUNDS + partial_rounds) * width;
+            let mut round_constants = Vec::with_capacity(num_constants);
+            while round_constants.len() < num_constants {
+                // values outside of the field are discarded
+                if let Some(constant) = Fr::from_repr(grain.next_bigint()) {
+                    round_constants.push(constant);
+                }
+            }
+
+            // Cauchy matrix of 2 * width distinct elements, reduced into the field
+            let elements = loop {
+                let elements: Vec<Fr> = (0..2 * width)
+                    .map(|_| Fr::from_le_bytes_mod_order(&grain.next_bigint().to_bytes_le()))
+                    .collect();
+                if (1..elements.len()).all(|i| !elements[..i].contains(&elements[i])) {
+                    break elements;
+                }
+            };
+            let (xs, ys) = elements.split_at(width);
+            let mds = xs
+                .iter()
+                .map(|x| {
+                    ys.iter()
+                        .map(|y| (*x + y).inverse().expect("elements are distinct"))
+                        .collect()
+                })
+                .collect();
+
+            Self {
+                width,
+                partial_rounds,
+                round_constants,
+                mds,
+            }
+        }
+
+        fn permute(&self, state: &mut Vec<Fr>) {
+            let half_full_rounds = FULL_ROUNDS / 2;
+            for round in 0..FULL_ROUNDS + self.partial_rounds {
+                for (i, element) in state.iter_mut().enumerate() {
+                    *element += &self.round_constants[round * self.width + i];
+                }
+                if round < half_full_rounds || round >= half_full_rounds + self.partial_rounds {
+                    state.iter_mut().for_each(|element| *element = sbox(*element));
+                } else {
+                    state[0] = sbox(state[0]);
+                }
+                *state = self
+                    .mds
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .zip(state.iter())
+                            .fold(Fr::zero(), |acc, (m, element)| acc + *m * element)
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    fn sbox(element: Fr) -> Fr {
+        element.square().square() * element
+    }
+
+    fn fr_from_bytes(endianness: Endianness, bytes: &[u8]) -> Result<Fr, PoseidonSyscallError> {
+        let mut le_bytes = [0u8; HASH_BYTES];
+        le_bytes[..bytes.len()].copy_from_slice(bytes);
+        if endianness == Endianness::BigEndian {
+            le_bytes[..bytes.len()].reverse();
+        }
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(le_bytes.chunks(8)) {
+            let mut limb_bytes = [0u8; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+        Fr::from_repr(BigInteger256::new(limbs)).ok_or(PoseidonSyscallError::InputLargerThanModulus)
+    }
+
+    pub(super) fn hash_bn254_x5(
+        endianness: Endianness,
+        vals: &[&[u8]],
+    ) -> Result<[u8; HASH_BYTES], PoseidonSyscallError> {
+        let parameters = &BN254_X5[vals.len() - 1];
+        let mut state = Vec::with_capacity(parameters.width);
+        state.push(Fr::zero());
+        for val in vals {
+            state.push(fr_from_bytes(endianness, val)?);
+        }
+        parameters.permute(&mut state);
+
+        let bytes = match endianness {
+            Endianness::BigEndian => state[0].into_repr().to_bytes_be(),
+            Endianness::LittleEndian => state[0].into_repr().to_bytes_le(),
+        };
+        let mut hash = [0u8; HASH_BYTES];
+        hash.copy_from_slice(&bytes);
+        Ok(hash)
+    }
+}
+
+/// Return a Poseidon hash for the given field element
+pub fn hash(
+    parameters: Parameters,
+    endianness: Endianness,
+    val: &[u8],
+) -> Result<PoseidonHash, PoseidonSyscallError> {
+    hashv(parameters, endianness, &[val])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be_bytes(value: u8) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[31] = value;
+        bytes
+    }
+
+    #[test]
+    fn test_poseidon_circom_vectors() {
+        // Reference values from circomlibjs
+        let hash_1 = "29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133";
+        let hash_1_2 = "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a";
+
+        let result = hash(Parameters::Bn254X5, Endianness::BigEndian, &be_bytes(1)).unwrap();
+        assert_eq!(hex::encode(result.to_bytes()), hash_1);
+
+        let result = hashv(
+            Parameters::Bn254X5,
+            Endianness::BigEndian,
+            &[&be_bytes(1)[..], &be_bytes(2)[..]],
+        )
+        .unwrap();
+        assert_eq!(hex::encode(result.to_bytes()), hash_1_2);
+
+        // The widest parameters, with the maximum number of inputs
+        let hash_1_to_12 = "058814945232937db248a01e7cc55b3d681cc08702c8168494e856c1ef7693b5";
+        let inputs: Vec<[u8; 32]> = (1..=MAX_INPUTS as u8).map(be_bytes).collect();
+        let inputs: Vec<&[u8]> = inputs.iter().map(|input| &input[..]).collect();
+        let result = hashv(Parameters::Bn254X5, Endianness::BigEndian, &inputs).unwrap();
+        assert_eq!(hex::encode(result.to_bytes()), hash_1_to_12);
+
+        // Little-endian inputs produce the same hash, in little-endian
+        let mut le_1 = be_bytes(1);
+        le_1.reverse();
+        let mut le_2 = be_bytes(2);
+        le_2.reverse();
+        let mut result = hashv(
+            Parameters::Bn254X5,
+            Endianness::LittleEndian,
+            &[&le_1[..], &le_2[..]],
+        )
+        .unwrap()
+        .to_bytes();
+        result.reverse();
+        assert_eq!(hex::encode(result), hash_1_2);
+    }
+
+    #[test]
+    fn test_poseidon_invalid_inputs() {
+        assert_eq!(
+            hashv(Parameters::Bn254X5, Endianness::BigEndian, &[]),
+            Err(PoseidonSyscallError::InvalidNumberOfInputs)
+        );
+        let one = be_bytes(1);
+        let inputs = vec![&one[..]; MAX_INPUTS + 1];
+        assert_eq!(
+            hashv(Parameters::Bn254X5, Endianness::BigEndian, &inputs),
+            Err(PoseidonSyscallError::InvalidNumberOfInputs)
+        );
+        assert_eq!(
+            hash(Parameters::Bn254X5, Endianness::BigEndian, &[1u8; 33]),
+            Err(PoseidonSyscallError::InvalidInputLength)
+        );
+        assert_eq!(
+            hash(Parameters::Bn254X5, Endianness::BigEndian, &[0xff; 32]),
+            Err(PoseidonSyscallError::InputLargerThanModulus)
+        );
+        assert_eq!(
+            Parameters::try_from(1),
+            Err(PoseidonSyscallError::InvalidParameters)
+        );
+        assert_eq!(
+            Endianness::try_from(2),
+            Err(PoseidonSyscallError::InvalidEndianness)
+        );
+    }
+}
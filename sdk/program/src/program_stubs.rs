@@ -27,6 +27,18 @@ pub trait SyscallStubs: Sync + Send {
     fn sol_log_compute_units(&self) {
         sol_log("SyscallStubs: sol_log_compute_units() not available");
     }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        sol_log("SyscallStubs: sol_remaining_compute_units() defaulting to 0");
+        0
+    }
+    fn sol_get_epoch_stake(&self, _vote_address: *const u8) -> u64 {
+        sol_log("SyscallStubs: sol_get_epoch_stake() defaulting to 0");
+        0
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        sol_log("SyscallStubs: sol_get_stack_height() defaulting to 0");
+        0
+    }
     fn sol_invoke_signed(
         &self,
         _instruction: &Instruction,
@@ -109,6 +121,21 @@ pub(crate) fn sol_log_compute_units() {
     SYSCALL_STUBS.read().unwrap().sol_log_compute_units();
 }
 
+pub(crate) fn sol_remaining_compute_units() -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_remaining_compute_units()
+}
+
+pub(crate) fn sol_get_epoch_stake(vote_address: *const u8) -> u64 {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_epoch_stake(vote_address)
+}
+
+pub(crate) fn sol_get_stack_height() -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_stack_height()
+}
+
 pub(crate) fn sol_invoke_signed(
     instruction: &Instruction,
     account_infos: &[AccountInfo],
@@ -46,6 +46,12 @@ pub enum StakeError {
 
     #[error("custodian signature not present")]
     CustodianSignatureMissing,
+
+    #[error("only fully activated stake can be redelegated")]
+    RedelegateTransientOrInactiveStake,
+
+    #[error("stake cannot be redelegated to the vote account it is delegated to")]
+    RedelegateToSameVoteAccount,
 }
 
 impl<E> DecodeError<E> for StakeError {
@@ -222,6 +228,24 @@ pub enum StakeInstruction {
     ///   1. `[SIGNER]` Lockup authority or withdraw authority
     ///   2. Optional: `[SIGNER]` New lockup authority
     SetLockupChecked(LockupCheckedArgs),
+
+    /// Redelegate activated stake to another vote account without waiting for it to cool down
+    ///
+    /// Upon success:
+    ///   * the delegated stake account is scheduled for deactivation, and its effective stake
+    ///     is moved out, leaving its rent exempt reserve and any undelegated lamports
+    ///   * the uninitialized stake account receives the effective stake, takes over the
+    ///     authorities and lockup of the delegated stake account, and is scheduled for
+    ///     activation to the new vote account. Lamports already in the uninitialized stake
+    ///     account, less its rent exempt reserve, are delegated as well
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Delegated stake account to be redelegated; its stake must be fully active
+    ///   1. `[WRITE]` Uninitialized stake account that will hold the redelegated stake
+    ///   2. `[]` Vote account to which the stake will be redelegated
+    ///   3. `[]` Address of config account that carries stake config
+    ///   4. `[SIGNER]` Stake authority
+    Redelegate,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -416,6 +440,68 @@ pub fn split_with_seed(
     ]
 }
 
+fn _redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*uninitialized_stake_pubkey, false),
+        AccountMeta::new_readonly(*vote_pubkey, false),
+        AccountMeta::new_readonly(config::id(), false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+    Instruction::new_with_bincode(id(), &StakeInstruction::Redelegate, account_metas)
+}
+
+pub fn redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate(
+            uninitialized_stake_pubkey,
+            std::mem::size_of::<StakeState>() as u64,
+        ),
+        system_instruction::assign(uninitialized_stake_pubkey, &id()),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
+pub fn redelegate_with_seed(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey, // derived using create_with_seed()
+    base: &Pubkey,                       // base
+    seed: &str,                          // seed
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate_with_seed(
+            uninitialized_stake_pubkey,
+            base,
+            seed,
+            std::mem::size_of::<StakeState>() as u64,
+            &id(),
+        ),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
 pub fn merge(
     destination_stake_pubkey: &Pubkey,
     source_stake_pubkey: &Pubkey,
@@ -4,18 +4,32 @@ use {
     crate::{
         borsh::try_from_slice_unchecked,
         entrypoint::HEAP_LENGTH as MIN_HEAP_FRAME_BYTES,
-        feature_set::{requestable_heap_size, FeatureSet},
-        instruction::{Instruction, InstructionError},
+        feature_set::{
+            cap_transaction_accounts_data_size, compute_unit_price_instruction,
+            requestable_heap_size, FeatureSet,
+        },
+        instruction::{CompiledInstruction, Instruction, InstructionError},
+        message::SanitizedMessage,
+        pubkey::Pubkey,
         transaction::{SanitizedTransaction, TransactionError},
     },
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    std::sync::Arc,
+    std::{convert::TryFrom, sync::Arc},
 };
 
 crate::declare_id!("ComputeBudget111111111111111111111111111111");
 
 const MAX_UNITS: u32 = 1_000_000;
+/// Compute budget instructions must be in the first instructions of a transaction (avoid nonce
+/// marker), otherwise they're ignored
+pub const MAX_COMPUTE_BUDGET_INSTRUCTIONS: usize = 3;
 const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+/// Default limit on the total data size of the accounts loaded by a transaction
+pub const DEFAULT_LOADED_ACCOUNTS_DATA_SIZE_LIMIT: u32 = 32 * 1024 * 1024;
+/// Largest loaded accounts data size limit a transaction may request
+pub const MAX_LOADED_ACCOUNTS_DATA_SIZE_LIMIT: u32 = 64 * 1024 * 1024;
+/// Compute unit prices are expressed in micro-lamports
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
 
 /// Compute Budget Instructions
 #[derive(
@@ -38,6 +52,14 @@ pub enum ComputeBudgetInstruction {
     /// The value requested must be a multiple of 1024. This new heap frame size
     /// applies to each program executed, including all calls to CPIs.
     RequestHeapFrame(u32),
+    /// Request a specific limit, in bytes, on the total data size of all the
+    /// accounts the transaction loads, including programs.
+    RequestLoadedAccountsDataSizeLimit(u32),
+    /// Set the price, in micro-lamports, the transaction offers per compute
+    /// unit. The price of the requested compute units is charged on top of
+    /// the signature fees, and leaders schedule the transactions offering
+    /// higher prices first.
+    SetComputeUnitPrice(u64),
 }
 impl ComputeBudgetInstruction {
     /// Create a `ComputeBudgetInstruction::RequestUnits` `Instruction`
@@ -52,9 +74,59 @@ impl ComputeBudgetInstruction {
             vec![],
         )
     }
+    /// Create a `ComputeBudgetInstruction::RequestLoadedAccountsDataSizeLimit` `Instruction`
+    pub fn request_loaded_accounts_data_size_limit(bytes: u32) -> Instruction {
+        Instruction::new_with_borsh(
+            id(),
+            &ComputeBudgetInstruction::RequestLoadedAccountsDataSizeLimit(bytes),
+            vec![],
+        )
+    }
+    /// Create a `ComputeBudgetInstruction::SetComputeUnitPrice` `Instruction`
+    pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        Instruction::new_with_borsh(
+            id(),
+            &ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports),
+            vec![],
+        )
+    }
+}
+
+/// Compute budget instructions of a transaction, given its program instructions, in order.
+/// Those that fail to deserialize are returned as errors.
+pub fn compute_budget_instructions<'a, I>(
+    program_instructions: I,
+) -> impl Iterator<Item = Result<ComputeBudgetInstruction, std::io::Error>> + 'a
+where
+    I: Iterator<Item = (&'a Pubkey, &'a CompiledInstruction)> + 'a,
+{
+    program_instructions
+        .take(MAX_COMPUTE_BUDGET_INSTRUCTIONS)
+        .filter(|(program_id, _)| check_id(program_id))
+        .map(|(_, instruction)| try_from_slice_unchecked(&instruction.data))
 }
 
-#[derive(Clone, Copy, Debug, AbiExample, PartialEq)]
+/// Price per compute unit, in micro-lamports, offered by a transaction given its program
+/// instructions, zero if it doesn't set one
+pub fn get_compute_unit_price<'a, I>(program_instructions: I) -> u64
+where
+    I: Iterator<Item = (&'a Pubkey, &'a CompiledInstruction)> + 'a,
+{
+    compute_budget_instructions(program_instructions)
+        .filter_map(|instruction| match instruction {
+            Ok(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports)) => {
+                Some(micro_lamports)
+            }
+            _ => None,
+        })
+        .last()
+        .unwrap_or_default()
+}
+
+/// The compute budget deserializes from a configuration, such as a TOML file, overriding only
+/// the fields it sets, to benchmark programs under another cost model than the cluster's
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, AbiExample, PartialEq)]
+#[serde(default, deny_unknown_fields)]
 pub struct ComputeBudget {
     /// Number of compute units that an instruction is allowed.  Compute units
     /// are consumed by program execution, resources they use, etc...
@@ -93,6 +165,25 @@ pub struct ComputeBudget {
     /// Number of compute units per additional 32k heap above the default (~.5
     /// us per 32k at 15 units/us rounded up)
     pub heap_cost: u64,
+    /// Number of compute units per additional 32k of stack above the default,
+    /// charged to programs that declare larger stack frames or call depth
+    pub stack_cost: u64,
+    /// Maximum total data size, in bytes, of the accounts a transaction may load
+    pub loaded_accounts_data_size_limit: usize,
+    /// Number of compute units consumed to add two alt_bn128 G1 points
+    pub alt_bn128_addition_cost: u64,
+    /// Number of compute units consumed to multiply an alt_bn128 G1 point by a scalar
+    pub alt_bn128_multiplication_cost: u64,
+    /// Number of compute units consumed by the first pair of an alt_bn128 pairing check
+    pub alt_bn128_pairing_one_pair_cost_first: u64,
+    /// Number of compute units consumed by each additional pair of an alt_bn128 pairing check
+    pub alt_bn128_pairing_one_pair_cost_other: u64,
+    /// Coefficient `a` of the quadratic cost `a * n^2 + c` of a Poseidon hash of `n` inputs
+    pub poseidon_cost_coefficient_a: u64,
+    /// Coefficient `c` of the quadratic cost `a * n^2 + c` of a Poseidon hash of `n` inputs
+    pub poseidon_cost_coefficient_c: u64,
+    /// Price, in micro-lamports per compute unit, offered by the transaction
+    pub compute_unit_price: u64,
 }
 impl Default for ComputeBudget {
     fn default() -> Self {
@@ -119,49 +210,119 @@ impl ComputeBudget {
             syscall_base_cost: 100,
             heap_size: None,
             heap_cost: 8,
+            stack_cost: 8,
+            loaded_accounts_data_size_limit: DEFAULT_LOADED_ACCOUNTS_DATA_SIZE_LIMIT as usize,
+            alt_bn128_addition_cost: 334,
+            alt_bn128_multiplication_cost: 3_840,
+            alt_bn128_pairing_one_pair_cost_first: 36_364,
+            alt_bn128_pairing_one_pair_cost_other: 12_121,
+            poseidon_cost_coefficient_a: 61,
+            poseidon_cost_coefficient_c: 542,
+            compute_unit_price: 0,
         }
     }
+
+    /// Reads a compute budget from a TOML configuration, e.g. `invoke_units = 2000`, the fields
+    /// it doesn't set keeping their default value
+    #[cfg(feature = "full")]
+    pub fn from_toml_str(config: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(config)?)
+    }
+
+    /// Reads a compute budget from a TOML configuration file, see `from_toml_str`
+    #[cfg(feature = "full")]
+    pub fn from_toml_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+        Self::from_toml_str(&config).map_err(|err| {
+            format!("Invalid compute budget config {}: {}", path.display(), err).into()
+        })
+    }
+
     pub fn process_transaction(
         &mut self,
         tx: &SanitizedTransaction,
         feature_set: Arc<FeatureSet>,
+    ) -> Result<(), TransactionError> {
+        self.process_message(tx.message(), &feature_set)
+    }
+
+    pub fn process_message(
+        &mut self,
+        message: &SanitizedMessage,
+        feature_set: &FeatureSet,
     ) -> Result<(), TransactionError> {
         let error = TransactionError::InstructionError(0, InstructionError::InvalidInstructionData);
-        // Compute budget instruction must be in the 1st 3 instructions (avoid
-        // nonce marker), otherwise ignored
-        for (program_id, instruction) in tx.message().program_instructions_iter().take(3) {
-            if check_id(program_id) {
-                match try_from_slice_unchecked(&instruction.data) {
-                    Ok(ComputeBudgetInstruction::RequestUnits(units)) => {
-                        if units > MAX_UNITS {
-                            return Err(error);
-                        }
-                        self.max_units = units as u64;
+        for instruction in compute_budget_instructions(message.program_instructions_iter()) {
+            match instruction {
+                Ok(ComputeBudgetInstruction::RequestUnits(units)) => {
+                    if units > MAX_UNITS {
+                        return Err(error);
                     }
-                    Ok(ComputeBudgetInstruction::RequestHeapFrame(bytes)) => {
-                        if !feature_set.is_active(&requestable_heap_size::id())
-                            || bytes > MAX_HEAP_FRAME_BYTES
-                            || bytes < MIN_HEAP_FRAME_BYTES as u32
-                            || bytes % 1024 != 0
-                        {
-                            return Err(error);
-                        }
-                        self.heap_size = Some(bytes as usize);
+                    self.max_units = units as u64;
+                }
+                Ok(ComputeBudgetInstruction::RequestHeapFrame(bytes)) => {
+                    if !feature_set.is_active(&requestable_heap_size::id())
+                        || bytes > MAX_HEAP_FRAME_BYTES
+                        || bytes < MIN_HEAP_FRAME_BYTES as u32
+                        || bytes % 1024 != 0
+                    {
+                        return Err(error);
+                    }
+                    self.heap_size = Some(bytes as usize);
+                }
+                Ok(ComputeBudgetInstruction::RequestLoadedAccountsDataSizeLimit(bytes)) => {
+                    if !feature_set.is_active(&cap_transaction_accounts_data_size::id())
+                        || bytes == 0
+                        || bytes > MAX_LOADED_ACCOUNTS_DATA_SIZE_LIMIT
+                    {
+                        return Err(error);
                     }
-                    _ => return Err(error),
+                    self.loaded_accounts_data_size_limit = bytes as usize;
                 }
+                Ok(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports)) => {
+                    if !feature_set.is_active(&compute_unit_price_instruction::id()) {
+                        return Err(error);
+                    }
+                    self.compute_unit_price = micro_lamports;
+                }
+                _ => return Err(error),
             }
         }
         Ok(())
     }
+
+    /// Fee, in lamports rounded up, for the requested compute units at the offered price
+    pub fn prioritization_fee(&self) -> u64 {
+        let micro_lamports =
+            u128::from(self.compute_unit_price).saturating_mul(u128::from(self.max_units));
+        let lamports = micro_lamports.saturating_add(u128::from(MICRO_LAMPORTS_PER_LAMPORT - 1))
+            / u128::from(MICRO_LAMPORTS_PER_LAMPORT);
+        u64::try_from(lamports).unwrap_or(u64::MAX)
+    }
+}
+
+/// Diagnostics recorded when a transaction fails because it ran out of compute units
+#[derive(Serialize, Deserialize, Clone, Debug, Default, AbiExample, PartialEq, Eq)]
+pub struct ComputeBudgetExceeded {
+    /// Index of the top-level instruction that was executing
+    pub instruction_index: u8,
+    /// Program invoked by that instruction
+    pub program_id: Pubkey,
+    /// Units consumed by each top-level instruction, up to and including the failing one
+    pub units_consumed: Vec<u64>,
+    /// Compute unit limit that was exceeded
+    pub units_limit: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        hash::Hash, message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer,
-        transaction::Transaction,
+        hash::Hash, message::Message, signature::Keypair, signer::Signer, transaction::Transaction,
     };
 
     macro_rules! test {
@@ -296,6 +457,58 @@ mod tests {
             ComputeBudget::default()
         );
 
+        // LoadedAccountsDataSizeLimit
+        test!(
+            &[
+                ComputeBudgetInstruction::request_loaded_accounts_data_size_limit(
+                    MAX_LOADED_ACCOUNTS_DATA_SIZE_LIMIT
+                ),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Ok(()),
+            ComputeBudget {
+                loaded_accounts_data_size_limit: MAX_LOADED_ACCOUNTS_DATA_SIZE_LIMIT as usize,
+                ..ComputeBudget::default()
+            }
+        );
+        test!(
+            &[
+                ComputeBudgetInstruction::request_loaded_accounts_data_size_limit(
+                    MAX_LOADED_ACCOUNTS_DATA_SIZE_LIMIT + 1
+                ),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData,
+            )),
+            ComputeBudget::default()
+        );
+        test!(
+            &[
+                ComputeBudgetInstruction::request_loaded_accounts_data_size_limit(0),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData,
+            )),
+            ComputeBudget::default()
+        );
+
+        // ComputeUnitPrice
+        test!(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(42),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Ok(()),
+            ComputeBudget {
+                compute_unit_price: 42,
+                ..ComputeBudget::default()
+            }
+        );
+
         // Combined
         test!(
             &[
@@ -311,4 +524,83 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_prioritization_fee() {
+        let compute_budget = |max_units, compute_unit_price| ComputeBudget {
+            max_units,
+            compute_unit_price,
+            ..ComputeBudget::default()
+        };
+        assert_eq!(compute_budget(200_000, 0).prioritization_fee(), 0);
+        assert_eq!(compute_budget(200_000, 5).prioritization_fee(), 1);
+        assert_eq!(compute_budget(200_000, 10).prioritization_fee(), 2);
+        assert_eq!(compute_budget(200_000, 11).prioritization_fee(), 3);
+        assert_eq!(
+            compute_budget(u64::MAX, u64::MAX).prioritization_fee(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_get_compute_unit_price() {
+        let payer = Pubkey::new_unique();
+        let compute_unit_price = |instructions: &[Instruction]| {
+            let message = Message::new(instructions, Some(&payer));
+            get_compute_unit_price(message.instructions.iter().map(|instruction| {
+                (
+                    &message.account_keys[usize::from(instruction.program_id_index)],
+                    instruction,
+                )
+            }))
+        };
+        let other_instruction = Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]);
+
+        assert_eq!(compute_unit_price(&[other_instruction.clone()]), 0);
+        assert_eq!(
+            compute_unit_price(&[
+                ComputeBudgetInstruction::request_units(1),
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+                other_instruction.clone(),
+            ]),
+            7
+        );
+        // The last price set wins
+        assert_eq!(
+            compute_unit_price(&[
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+                ComputeBudgetInstruction::set_compute_unit_price(9),
+            ]),
+            9
+        );
+        // Only the first instructions are considered
+        assert_eq!(
+            compute_unit_price(&[
+                other_instruction.clone(),
+                other_instruction.clone(),
+                other_instruction,
+                ComputeBudgetInstruction::set_compute_unit_price(7),
+            ]),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "full")]
+    fn test_from_toml_str() {
+        assert_eq!(
+            ComputeBudget::from_toml_str("").unwrap(),
+            ComputeBudget::default()
+        );
+        assert_eq!(
+            ComputeBudget::from_toml_str("invoke_units = 2000\nheap_size = 65536").unwrap(),
+            ComputeBudget {
+                invoke_units: 2000,
+                heap_size: Some(65536),
+                ..ComputeBudget::default()
+            }
+        );
+        assert!(ComputeBudget::from_toml_str("invoke_unit = 2000").is_err());
+        assert!(ComputeBudget::from_toml_str("invoke_units = -1").is_err());
+    }
 }
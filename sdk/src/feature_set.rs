@@ -233,6 +233,62 @@ pub mod add_compute_budget_program {
     solana_sdk::declare_id!("4d5AKtxoh93Dwm1vHXUU3iRATuMndx1c431KgT2td52r");
 }
 
+pub mod cap_transaction_accounts_data_size {
+    solana_sdk::declare_id!("HPRKcCSY1tCsmYHvFuis65oH9Z4dqcUJSoJvAjTERiUj");
+}
+
+pub mod remaining_compute_units_syscall_enabled {
+    solana_sdk::declare_id!("Cba2H4xrNjctTz2mAV6xjmFux81WZZLdQvnDV5R5QnQx");
+}
+
+pub mod enable_alt_bn128_syscall {
+    solana_sdk::declare_id!("7gYTB1s9JVsPgQGjYfVJaGds9Q1XdfAAM84PUYwYRjrB");
+}
+
+pub mod enable_poseidon_syscall {
+    solana_sdk::declare_id!("2yHgxpp3n9vgFmfF9UxPPfrq8x1ZuSCqXCVDAUApRZb7");
+}
+
+pub mod get_epoch_stake_syscall_enabled {
+    solana_sdk::declare_id!("ENZJe2FfYmZP4VYrzDjaPpiocfpwf3BDpZYu5mBEA3Jq");
+}
+
+pub mod program_stack_metadata_enabled {
+    solana_sdk::declare_id!("23VsMGFFgPVDPLeQjnnQduPmHoM1wydaWt58yZh2wM56");
+}
+
+pub mod vote_commission_increase_cap {
+    solana_sdk::declare_id!("2ssbFzGcQGhuWYwPvgRCq9QjcYmZ6oY7YHEALG9UYDAJ");
+}
+
+pub mod stake_redelegate_instruction {
+    solana_sdk::declare_id!("5R2UurrmF3gmfQK9fa3abTYNRqSwbZ9qnEPQLRW7GgUS");
+}
+
+pub mod enable_bpf_loader_set_authority_checked_ix {
+    solana_sdk::declare_id!("Ew4RAPGS6YjxWmybVrssy5vAkdkni5g4cuaebdFRRwDW");
+}
+
+pub mod sign_repair_requests {
+    solana_sdk::declare_id!("DQv56ggDiQnQTwzNoXRwLCXHnqeCFMXBsghDL9PQArJp");
+}
+
+pub mod stack_height_syscall_enabled {
+    solana_sdk::declare_id!("9hCzZJf3VVUa7zT9GNLnZrTGXCAdh24vUhdzWgVRi8FH");
+}
+
+pub mod compute_unit_price_instruction {
+    solana_sdk::declare_id!("GoiAufgHq68A7JoHkHsRPDzj13rQAKcwk1j3qmqUuvi6");
+}
+
+pub mod gossip_extended_contact_info {
+    solana_sdk::declare_id!("HJNatazEJGyzvpXsXW6z35zjwTzN5jgeZPN993fVSZXG");
+}
+
+pub mod charge_compute_unit_price {
+    solana_sdk::declare_id!("HJb38MvyjNRnDQHxFxeAExB6sPtvrr2VuZteoJ9qiYtP");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -286,6 +342,20 @@ lazy_static! {
         (requestable_heap_size::id(), "Requestable heap frame size"),
         (disable_fee_calculator::id(), "deprecate fee calculator"),
         (add_compute_budget_program::id(), "Add compute_budget_program"),
+        (cap_transaction_accounts_data_size::id(), "cap the accounts data size loaded by a transaction"),
+        (remaining_compute_units_syscall_enabled::id(), "enable the sol_remaining_compute_units syscall"),
+        (enable_alt_bn128_syscall::id(), "add alt_bn128 syscalls"),
+        (enable_poseidon_syscall::id(), "add poseidon syscall"),
+        (get_epoch_stake_syscall_enabled::id(), "enable the sol_get_epoch_stake syscall"),
+        (program_stack_metadata_enabled::id(), "read program stack frame size and call depth from an ELF note"),
+        (vote_commission_increase_cap::id(), "cap vote commission increases and disallow them in the second half of an epoch"),
+        (stake_redelegate_instruction::id(), "enable the redelegate stake instruction"),
+        (enable_bpf_loader_set_authority_checked_ix::id(), "enable bpf upgradeable loader SetAuthorityChecked instruction"),
+        (sign_repair_requests::id(), "sign repair requests"),
+        (stack_height_syscall_enabled::id(), "enable the sol_get_stack_height syscall"),
+        (compute_unit_price_instruction::id(), "enable the compute budget SetComputeUnitPrice instruction"),
+        (gossip_extended_contact_info::id(), "push ExtendedContactInfo gossip values"),
+        (charge_compute_unit_price::id(), "charge the prioritization fee offered with SetComputeUnitPrice"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
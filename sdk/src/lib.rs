@@ -44,6 +44,7 @@ pub mod secp256k1_instruction;
 pub mod shred_version;
 pub mod signature;
 pub mod signer;
+pub mod slot_time;
 pub mod system_transaction;
 pub mod timing;
 pub mod transaction;
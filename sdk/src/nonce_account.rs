@@ -38,6 +38,17 @@ pub fn lamports_per_signature_of(account: &AccountSharedData) -> Option<u64> {
     }
 }
 
+/// Blockhash stored by an initialized nonce account, the one its durable nonce transactions use
+pub fn blockhash_of(account: &AccountSharedData) -> Option<Hash> {
+    let state = StateMut::<Versions>::state(account)
+        .ok()?
+        .convert_to_current();
+    match state {
+        State::Initialized(data) => Some(data.blockhash),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
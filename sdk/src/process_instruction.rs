@@ -10,7 +10,13 @@ use solana_sdk::{
     message::Message,
     pubkey::Pubkey,
 };
-use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    rc::Rc,
+    sync::Arc,
+};
 
 pub type ProcessInstructionWithContext =
     fn(usize, &[u8], &mut dyn InvokeContext) -> Result<(), InstructionError>;
@@ -96,6 +102,15 @@ pub trait InvokeContext {
     fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError>;
     /// Get the return data
     fn get_return_data(&self) -> (Pubkey, &[u8]);
+    /// Get the total stake of the current epoch
+    fn get_epoch_total_stake(&self) -> u64;
+    /// Get the stake delegated to a vote account in the current epoch
+    fn get_epoch_vote_account_stake(&self, vote_address: &Pubkey) -> u64;
+    /// Get the commission a vote account had when the current epoch started
+    fn get_epoch_start_commission(&self, vote_address: &Pubkey) -> Option<u8>;
+    /// Get the profile the compute units consumed by the functions of the programs are recorded
+    /// into, if profiling is enabled
+    fn get_compute_unit_profile(&self) -> Option<Rc<RefCell<ComputeUnitProfile>>>;
 }
 
 /// Convenience macro to log a message with an `Rc<RefCell<dyn Logger>>`
@@ -136,6 +151,36 @@ pub trait ComputeMeter {
     fn get_remaining(&self) -> u64;
 }
 
+/// Compute units consumed by each function of the programs executed while profiling. The units
+/// of a function include those of the syscalls it made, cross-program invocations included.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ComputeUnitProfile {
+    programs: HashMap<Pubkey, BTreeMap<String, u64>>,
+}
+
+impl ComputeUnitProfile {
+    /// Record that the function of a program consumed `units` more compute units
+    pub fn record(&mut self, program_id: &Pubkey, function: &str, units: u64) {
+        let functions = self.programs.entry(*program_id).or_default();
+        match functions.get_mut(function) {
+            Some(function_units) => *function_units = function_units.saturating_add(units),
+            None => {
+                functions.insert(function.to_string(), units);
+            }
+        }
+    }
+
+    /// Get the compute units consumed by each function of a program
+    pub fn get_program_profile(&self, program_id: &Pubkey) -> Option<&BTreeMap<String, u64>> {
+        self.programs.get(program_id)
+    }
+
+    /// Get the ids of the programs profiled
+    pub fn program_ids(&self) -> impl Iterator<Item = &Pubkey> {
+        self.programs.keys()
+    }
+}
+
 /// Log messages
 pub trait Logger {
     fn log_enabled(&self) -> bool;
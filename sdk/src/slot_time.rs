@@ -0,0 +1,120 @@
+//! The `slot_time` module estimates the wall-clock time of slots, and of the epoch boundaries,
+//! from an exponential moving average of the recent slot times.
+
+use crate::{
+    clock::{Epoch, Slot, UnixTimestamp, DEFAULT_MS_PER_SLOT},
+    epoch_schedule::EpochSchedule,
+};
+
+/// Weight of a new sample in the moving average of the slot time, by default
+pub const DEFAULT_SLOT_TIME_EMA_ALPHA: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotTimeEstimator {
+    alpha: f64,
+    ms_per_slot: f64,
+}
+
+impl Default for SlotTimeEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MS_PER_SLOT as f64)
+    }
+}
+
+impl SlotTimeEstimator {
+    /// Starts the moving average at `ms_per_slot`, typically the target slot time of the cluster
+    pub fn new(ms_per_slot: f64) -> Self {
+        Self {
+            alpha: DEFAULT_SLOT_TIME_EMA_ALPHA,
+            ms_per_slot,
+        }
+    }
+
+    /// Sets the weight of a new sample in the moving average, between 0 and 1
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha.max(0.0).min(1.0);
+        self
+    }
+
+    /// Feeds the samples of `num_slots` slots produced in `period_ms` milliseconds, oldest first
+    pub fn from_samples(ms_per_slot: f64, samples: impl IntoIterator<Item = (u64, u64)>) -> Self {
+        let mut estimator = Self::new(ms_per_slot);
+        for (num_slots, period_ms) in samples {
+            estimator.update(num_slots, period_ms);
+        }
+        estimator
+    }
+
+    /// Feeds a sample of `num_slots` slots produced in `period_ms` milliseconds, samples without
+    /// any slot are ignored
+    pub fn update(&mut self, num_slots: u64, period_ms: u64) {
+        if num_slots == 0 {
+            return;
+        }
+        let sample = period_ms as f64 / num_slots as f64;
+        self.ms_per_slot += self.alpha * (sample - self.ms_per_slot);
+    }
+
+    /// Moving average of the slot time, in milliseconds
+    pub fn ms_per_slot(&self) -> f64 {
+        self.ms_per_slot
+    }
+
+    /// Estimates the time of `slot`, knowing that `current_slot` was at `current_time`
+    pub fn estimate_slot_time(
+        &self,
+        current_slot: Slot,
+        current_time: UnixTimestamp,
+        slot: Slot,
+    ) -> UnixTimestamp {
+        let slots = slot as f64 - current_slot as f64;
+        let offset_secs = (slots * self.ms_per_slot / 1000.0).round();
+        current_time.saturating_add(offset_secs as UnixTimestamp)
+    }
+
+    /// Estimates the time of the first slot of `epoch`, knowing that `current_slot` was at
+    /// `current_time`
+    pub fn estimate_epoch_start_time(
+        &self,
+        epoch_schedule: &EpochSchedule,
+        current_slot: Slot,
+        current_time: UnixTimestamp,
+        epoch: Epoch,
+    ) -> UnixTimestamp {
+        self.estimate_slot_time(
+            current_slot,
+            current_time,
+            epoch_schedule.get_first_slot_in_epoch(epoch),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_time_estimator() {
+        let mut estimator = SlotTimeEstimator::new(400.0).with_alpha(0.5);
+        assert_eq!(estimator.ms_per_slot(), 400.0);
+        estimator.update(0, 60_000);
+        assert_eq!(estimator.ms_per_slot(), 400.0);
+        estimator.update(100, 60_000);
+        assert_eq!(estimator.ms_per_slot(), 500.0);
+        assert_eq!(
+            SlotTimeEstimator::from_samples(400.0, vec![(100, 60_000)]).ms_per_slot(),
+            440.0
+        );
+
+        assert_eq!(estimator.estimate_slot_time(100, 1_000, 100), 1_000);
+        assert_eq!(estimator.estimate_slot_time(100, 1_000, 110), 1_005);
+        assert_eq!(estimator.estimate_slot_time(100, 1_000, 90), 995);
+
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(1);
+        assert_eq!(
+            estimator.estimate_epoch_start_time(&epoch_schedule, first_slot - 20, 1_000, 1),
+            1_010
+        );
+    }
+}
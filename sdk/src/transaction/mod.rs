@@ -122,6 +122,74 @@ pub enum TransactionError {
     /// Transaction loads a writable account that cannot be written
     #[error("Transaction loads a writable account that cannot be written")]
     InvalidWritableAccount,
+
+    /// Transaction loads accounts whose total data size exceeds its limit
+    #[error("Transaction loads accounts whose total data size exceeds its limit")]
+    MaxLoadedAccountsDataSizeExceeded,
+}
+
+/// Stable numeric code and name of every [`TransactionError`] variant, indexed by code.
+///
+/// Codes are never changed or reused, so clients in any language can interpret an error from
+/// its code alone. A code is the index the variant is serialized with. The code of the
+/// instruction error wrapped by [`TransactionError::InstructionError`] is listed in
+/// [`INSTRUCTION_ERROR_CODES`](crate::instruction::INSTRUCTION_ERROR_CODES).
+pub const TRANSACTION_ERROR_CODES: &[(u32, &str)] = &[
+    (0, "AccountInUse"),
+    (1, "AccountLoadedTwice"),
+    (2, "AccountNotFound"),
+    (3, "ProgramAccountNotFound"),
+    (4, "InsufficientFundsForFee"),
+    (5, "InvalidAccountForFee"),
+    (6, "AlreadyProcessed"),
+    (7, "BlockhashNotFound"),
+    (8, "InstructionError"),
+    (9, "CallChainTooDeep"),
+    (10, "MissingSignatureForFee"),
+    (11, "InvalidAccountIndex"),
+    (12, "SignatureFailure"),
+    (13, "InvalidProgramForExecution"),
+    (14, "SanitizeFailure"),
+    (15, "ClusterMaintenance"),
+    (16, "AccountBorrowOutstanding"),
+    (17, "WouldExceedMaxBlockCostLimit"),
+    (18, "UnsupportedVersion"),
+    (19, "InvalidWritableAccount"),
+    (20, "MaxLoadedAccountsDataSizeExceeded"),
+];
+
+impl TransactionError {
+    /// Stable numeric code of the error, see [`TRANSACTION_ERROR_CODES`]
+    pub fn code(&self) -> u32 {
+        match self {
+            TransactionError::AccountInUse => 0,
+            TransactionError::AccountLoadedTwice => 1,
+            TransactionError::AccountNotFound => 2,
+            TransactionError::ProgramAccountNotFound => 3,
+            TransactionError::InsufficientFundsForFee => 4,
+            TransactionError::InvalidAccountForFee => 5,
+            TransactionError::AlreadyProcessed => 6,
+            TransactionError::BlockhashNotFound => 7,
+            TransactionError::InstructionError(..) => 8,
+            TransactionError::CallChainTooDeep => 9,
+            TransactionError::MissingSignatureForFee => 10,
+            TransactionError::InvalidAccountIndex => 11,
+            TransactionError::SignatureFailure => 12,
+            TransactionError::InvalidProgramForExecution => 13,
+            TransactionError::SanitizeFailure => 14,
+            TransactionError::ClusterMaintenance => 15,
+            TransactionError::AccountBorrowOutstanding => 16,
+            TransactionError::WouldExceedMaxBlockCostLimit => 17,
+            TransactionError::UnsupportedVersion => 18,
+            TransactionError::InvalidWritableAccount => 19,
+            TransactionError::MaxLoadedAccountsDataSizeExceeded => 20,
+        }
+    }
+
+    /// Name of the error variant, see [`TRANSACTION_ERROR_CODES`]
+    pub fn name(&self) -> &'static str {
+        TRANSACTION_ERROR_CODES[self.code() as usize].1
+    }
 }
 
 pub type Result<T> = result::Result<T, TransactionError>;
@@ -534,6 +602,25 @@ mod tests {
     use bincode::{deserialize, serialize, serialized_size};
     use std::mem::size_of;
 
+    #[test]
+    fn test_transaction_error_codes() {
+        for (index, (code, name)) in TRANSACTION_ERROR_CODES.iter().enumerate() {
+            assert_eq!(index as u32, *code);
+            // The code deserializes to the variant it names, with a zeroed
+            // payload if the variant has one.
+            let mut data = code.to_le_bytes().to_vec();
+            data.extend_from_slice(&[0; 8]);
+            let err: TransactionError = deserialize(&data).unwrap();
+            assert_eq!(err.code(), *code);
+            assert_eq!(err.name(), *name);
+            assert!(format!("{:?}", err).starts_with(name));
+            assert_eq!(serialize(&err).unwrap()[..4], code.to_le_bytes());
+        }
+        // Every variant is in the table.
+        let data = (TRANSACTION_ERROR_CODES.len() as u32).to_le_bytes();
+        assert!(deserialize::<TransactionError>(&data).is_err());
+    }
+
     fn get_program_id(tx: &Transaction, instruction_index: usize) -> &Pubkey {
         let message = tx.message();
         let instruction = &message.instructions[instruction_index];
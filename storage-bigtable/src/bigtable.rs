@@ -8,6 +8,7 @@ use {
     },
     backoff::{future::retry, ExponentialBackoff},
     log::*,
+    solana_storage_proto::versioned::{self, StoredFormat},
     std::time::{Duration, Instant},
     thiserror::Error,
     tonic::{
@@ -110,6 +111,7 @@ pub struct BigTableConnection {
     channel: tonic::transport::Channel,
     table_prefix: String,
     timeout: Option<Duration>,
+    write_versioned_protobuf: bool,
 }
 
 impl BigTableConnection {
@@ -137,6 +139,7 @@ impl BigTableConnection {
                         .connect_lazy(),
                     table_prefix: format!("projects/emulator/instances/{}/tables/", instance_name),
                     timeout,
+                    write_versioned_protobuf: false,
                 })
             }
 
@@ -178,11 +181,16 @@ impl BigTableConnection {
                     channel: endpoint.connect_lazy(),
                     table_prefix,
                     timeout,
+                    write_versioned_protobuf: false,
                 })
             }
         }
     }
 
+    pub fn set_write_versioned_protobuf(&mut self, write_versioned_protobuf: bool) {
+        self.write_versioned_protobuf = write_versioned_protobuf;
+    }
+
     /// Create a new BigTable client.
     ///
     /// Clients require `&mut self`, due to `Tonic::transport::Channel` limitations, however
@@ -211,6 +219,7 @@ impl BigTableConnection {
             client,
             table_prefix: self.table_prefix.clone(),
             timeout: self.timeout,
+            write_versioned_protobuf: self.write_versioned_protobuf,
         }
     }
 
@@ -273,6 +282,7 @@ pub struct BigTable<F: FnMut(Request<()>) -> InterceptedRequestResult> {
     client: bigtable_client::BigtableClient<InterceptedService<tonic::transport::Channel, F>>,
     table_prefix: String,
     timeout: Option<Duration>,
+    write_versioned_protobuf: bool,
 }
 
 impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
@@ -707,8 +717,14 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         let mut bytes_written = 0;
         let mut new_row_data = vec![];
         for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
+            // Readers deployed before the versioned framing can only decode plain protobuf
+            let buf = if self.write_versioned_protobuf {
+                versioned::encode(data)
+            } else {
+                let mut buf = Vec::with_capacity(data.encoded_len());
+                data.encode(&mut buf).unwrap();
+                buf
+            };
             let data = compress_best(&buf)?;
             bytes_written += data.len();
             new_row_data.push((row_key, vec![("proto".to_string(), data)]));
@@ -753,7 +769,14 @@ where
         .1;
 
     let data = decompress(value)?;
-    T::decode(&data[..]).map_err(|err| {
+    let result = match versioned::stored_format(&data) {
+        StoredFormat::Versioned(_) => versioned::decode(&data),
+        StoredFormat::Legacy => {
+            inc_new_counter_info!("storage-bigtable-legacy_protobuf_reads", 1);
+            T::decode(&data[..]).map_err(|err| err.into())
+        }
+    };
+    result.map_err(|err| {
         warn!("Failed to deserialize {}/{}: {}", table, key, err);
         Error::ObjectCorrupt(format!("{}/{}", table, key))
     })
@@ -785,7 +808,9 @@ mod tests {
     use super::*;
     use crate::StoredConfirmedBlock;
     use prost::Message;
-    use solana_sdk::{hash::Hash, signature::Keypair, system_transaction};
+    use solana_sdk::{
+        hash::Hash, message::MappedAddresses, signature::Keypair, system_transaction,
+    };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{
         ConfirmedBlock, TransactionStatusMeta, TransactionWithStatusMeta,
@@ -809,6 +834,11 @@ mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                loaded_addresses: MappedAddresses::default(),
+                compute_budget_exceeded: None,
+                compute_units_consumed: None,
+                fee_details: None,
+                loaded_accounts_data_size: None,
             }),
         };
         let block = ConfirmedBlock {
@@ -845,6 +875,25 @@ mod tests {
             panic!("deserialization should produce CellData::Protobuf");
         }
 
+        let versioned_block = compress_best(&versioned::encode(&generated::ConfirmedBlock::from(
+            block.clone(),
+        )))
+        .unwrap();
+        let deserialized = deserialize_protobuf_or_bincode_cell_data::<
+            StoredConfirmedBlock,
+            generated::ConfirmedBlock,
+        >(
+            &[("proto".to_string(), versioned_block)],
+            "",
+            "".to_string(),
+        )
+        .unwrap();
+        if let CellData::Protobuf(protobuf_block) = deserialized {
+            assert_eq!(block, protobuf_block.try_into().unwrap());
+        } else {
+            panic!("deserialization should produce CellData::Protobuf");
+        }
+
         let deserialized = deserialize_protobuf_or_bincode_cell_data::<
             StoredConfirmedBlock,
             generated::ConfirmedBlock,
@@ -5,6 +5,7 @@ use {
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         deserialize_utils::default_on_eof,
+        message::MappedAddresses,
         pubkey::Pubkey,
         signature::Signature,
         sysvar::is_sysvar_id,
@@ -27,6 +28,9 @@ use {
 #[macro_use]
 extern crate serde_derive;
 
+#[macro_use]
+extern crate solana_metrics;
+
 mod access_token;
 mod bigtable;
 mod compression;
@@ -212,6 +216,11 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         }
     }
 }
@@ -347,6 +356,13 @@ impl LedgerStorage {
         Ok(Self { connection })
     }
 
+    /// Upload protobuf cells with the versioned framing. Leave it unset while the instance may
+    /// still be read by a release that predates the framing.
+    pub fn set_write_versioned_protobuf(&mut self, write_versioned_protobuf: bool) {
+        self.connection
+            .set_write_versioned_protobuf(write_versioned_protobuf);
+    }
+
     /// Return the available slot that contains a block
     pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
         let mut bigtable = self.connection.client();
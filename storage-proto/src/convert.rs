@@ -2,17 +2,18 @@ use {
     crate::{StoredExtendedRewards, StoredTransactionStatusMeta},
     solana_account_decoder::parse_token::{real_number_string_trimmed, UiTokenAmount},
     solana_sdk::{
+        compute_budget::ComputeBudgetExceeded,
         hash::Hash,
         instruction::CompiledInstruction,
         instruction::InstructionError,
-        message::{Message, MessageHeader},
+        message::{MappedAddresses, Message, MessageHeader},
         pubkey::Pubkey,
         signature::Signature,
         transaction::Transaction,
         transaction::TransactionError,
     },
     solana_transaction_status::{
-        ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionByAddrInfo,
+        ConfirmedBlock, FeeDetails, InnerInstructions, Reward, RewardType, TransactionByAddrInfo,
         TransactionStatusMeta, TransactionTokenBalance, TransactionWithStatusMeta,
     },
     std::{
@@ -276,6 +277,11 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -304,6 +310,28 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|reward| reward.into())
             .collect();
+        let loaded_writable_addresses = loaded_addresses
+            .writable
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let loaded_readonly_addresses = loaded_addresses
+            .readonly
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let compute_budget_exceeded = compute_budget_exceeded.map(|details| details.into());
+        let compute_units_consumed =
+            compute_units_consumed.map(|compute_units_consumed| generated::ComputeUnitsConsumed {
+                compute_units_consumed,
+            });
+        let fee_details = fee_details.map(|fee_details| fee_details.into());
+        let loaded_accounts_data_size =
+            loaded_accounts_data_size.map(|loaded_accounts_data_size| {
+                generated::LoadedAccountsDataSize {
+                    loaded_accounts_data_size,
+                }
+            });
 
         Self {
             err,
@@ -315,6 +343,12 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         }
     }
 }
@@ -340,6 +374,12 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -365,6 +405,28 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .collect(),
         );
         let rewards = Some(rewards.into_iter().map(|reward| reward.into()).collect());
+        let loaded_addresses = MappedAddresses {
+            writable: loaded_writable_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+            readonly: loaded_readonly_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+        };
+        let compute_budget_exceeded = compute_budget_exceeded.map(|details| details.into());
+        let compute_units_consumed = compute_units_consumed.map(
+            |generated::ComputeUnitsConsumed {
+                 compute_units_consumed,
+             }| compute_units_consumed,
+        );
+        let fee_details = fee_details.map(|fee_details| fee_details.into());
+        let loaded_accounts_data_size = loaded_accounts_data_size.map(
+            |generated::LoadedAccountsDataSize {
+                 loaded_accounts_data_size,
+             }| loaded_accounts_data_size,
+        );
         Ok(Self {
             status,
             fee,
@@ -375,10 +437,57 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         })
     }
 }
 
+impl From<FeeDetails> for generated::FeeDetails {
+    fn from(value: FeeDetails) -> Self {
+        Self {
+            signature_fee: value.signature_fee,
+            precompile_signature_fee: value.precompile_signature_fee,
+            prioritization_fee: value.prioritization_fee,
+        }
+    }
+}
+
+impl From<generated::FeeDetails> for FeeDetails {
+    fn from(value: generated::FeeDetails) -> Self {
+        Self {
+            signature_fee: value.signature_fee,
+            precompile_signature_fee: value.precompile_signature_fee,
+            prioritization_fee: value.prioritization_fee,
+        }
+    }
+}
+
+impl From<ComputeBudgetExceeded> for generated::ComputeBudgetExceeded {
+    fn from(value: ComputeBudgetExceeded) -> Self {
+        Self {
+            instruction_index: value.instruction_index as u32,
+            program_id: <Pubkey as AsRef<[u8]>>::as_ref(&value.program_id).into(),
+            units_consumed: value.units_consumed,
+            units_limit: value.units_limit,
+        }
+    }
+}
+
+impl From<generated::ComputeBudgetExceeded> for ComputeBudgetExceeded {
+    fn from(value: generated::ComputeBudgetExceeded) -> Self {
+        Self {
+            instruction_index: value.instruction_index as u8,
+            program_id: Pubkey::new(&value.program_id),
+            units_consumed: value.units_consumed,
+            units_limit: value.units_limit,
+        }
+    }
+}
+
 impl From<InnerInstructions> for generated::InnerInstructions {
     fn from(value: InnerInstructions) -> Self {
         Self {
@@ -553,6 +662,7 @@ impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
             17 => TransactionError::WouldExceedMaxBlockCostLimit,
             18 => TransactionError::UnsupportedVersion,
             19 => TransactionError::InvalidWritableAccount,
+            20 => TransactionError::MaxLoadedAccountsDataSizeExceeded,
             _ => return Err("Invalid TransactionError"),
         })
     }
@@ -620,6 +730,9 @@ impl From<TransactionError> for tx_by_addr::TransactionError {
                 TransactionError::InvalidWritableAccount => {
                     tx_by_addr::TransactionErrorType::InvalidWritableAccount
                 }
+                TransactionError::MaxLoadedAccountsDataSizeExceeded => {
+                    tx_by_addr::TransactionErrorType::MaxLoadedAccountsDataSizeExceeded
+                }
             } as i32,
             instruction_error: match transaction_error {
                 TransactionError::InstructionError(index, ref instruction_error) => {
@@ -895,6 +1008,14 @@ mod test {
 
     #[test]
     fn test_transaction_error_encode() {
+        let transaction_error = TransactionError::MaxLoadedAccountsDataSizeExceeded;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
         let transaction_error = TransactionError::AccountBorrowOutstanding;
         let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
             transaction_error.clone().into();
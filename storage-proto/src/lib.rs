@@ -4,14 +4,19 @@ use {
         parse_token::{real_number_string_trimmed, UiTokenAmount},
         StringAmount,
     },
-    solana_sdk::{deserialize_utils::default_on_eof, transaction::Result},
+    solana_sdk::{
+        compute_budget::ComputeBudgetExceeded, deserialize_utils::default_on_eof,
+        message::MappedAddresses, transaction::Result,
+    },
     solana_transaction_status::{
-        InnerInstructions, Reward, RewardType, TransactionStatusMeta, TransactionTokenBalance,
+        FeeDetails, InnerInstructions, Reward, RewardType, TransactionStatusMeta,
+        TransactionTokenBalance,
     },
     std::str::FromStr,
 };
 
 pub mod convert;
+pub mod versioned;
 
 pub type StoredExtendedRewards = Vec<StoredExtendedReward>;
 
@@ -165,6 +170,16 @@ pub struct StoredTransactionStatusMeta {
     pub post_token_balances: Option<Vec<StoredTransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Vec<StoredExtendedReward>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: MappedAddresses,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_budget_exceeded: Option<ComputeBudgetExceeded>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_units_consumed: Option<u64>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub fee_details: Option<FeeDetails>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_accounts_data_size: Option<u64>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -179,6 +194,11 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = value;
         Self {
             status,
@@ -193,6 +213,11 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         }
     }
 }
@@ -209,6 +234,11 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         } = value;
         Self {
             status,
@@ -223,6 +253,11 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            loaded_addresses,
+            compute_budget_exceeded,
+            compute_units_consumed,
+            fee_details,
+            loaded_accounts_data_size,
         }
     }
 }
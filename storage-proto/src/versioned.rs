@@ -0,0 +1,138 @@
+//! Explicitly versioned framing for protobuf values persisted in the blockstore and bigtable.
+//!
+//! Status columns used to hold either bincode or unversioned protobuf bytes, and readers guessed
+//! the format by trying one decoder and falling back to the other. Values written by `encode` are
+//! prefixed with a magic and a version byte so that they always decode unambiguously.
+//!
+//! Readers accept both framings, decoding unversioned values through a counted legacy path.
+//! Writers store unversioned protobuf unless configured to write the versioned framing, which
+//! should only be enabled once every deployed reader understands it.
+use {prost::Message, std::fmt};
+
+/// The magic can't start a valid protobuf message, since it encodes field number 0, nor a bincode
+/// status meta or rewards list, since it would encode an enum variant or sequence length far
+/// beyond any real entry
+const MAGIC: [u8; 7] = [0x00, 0xff, b'S', b'O', b'L', b'P', b'B'];
+
+/// Version written by `encode`. Bump it whenever the stored protobuf schema changes in a way old
+/// readers can't handle.
+pub const CURRENT_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredFormat {
+    /// Written before explicit versioning; either bincode or unversioned protobuf
+    Legacy,
+    /// Protobuf framed with the given version
+    Versioned(u8),
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The value predates explicit versioning and must be decoded as a legacy value
+    Unversioned,
+    /// The value was written by a newer, incompatible release
+    UnsupportedVersion(u8),
+    Protobuf(prost::DecodeError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Unversioned => write!(f, "value is not versioned"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported stored protobuf version {}", version)
+            }
+            DecodeError::Protobuf(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<prost::DecodeError> for DecodeError {
+    fn from(err: prost::DecodeError) -> Self {
+        DecodeError::Protobuf(err)
+    }
+}
+
+pub fn stored_format(data: &[u8]) -> StoredFormat {
+    if data.len() > MAGIC.len() && data[..MAGIC.len()] == MAGIC {
+        StoredFormat::Versioned(data[MAGIC.len()])
+    } else {
+        StoredFormat::Legacy
+    }
+}
+
+pub fn encode<T: Message>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(MAGIC.len() + 1 + value.encoded_len());
+    buf.extend_from_slice(&MAGIC);
+    buf.push(CURRENT_VERSION);
+    value
+        .encode(&mut buf)
+        .expect("Vec<u8> grows to fit the encoded message");
+    buf
+}
+
+pub fn decode<T: Message + Default>(data: &[u8]) -> Result<T, DecodeError> {
+    match stored_format(data) {
+        StoredFormat::Legacy => Err(DecodeError::Unversioned),
+        StoredFormat::Versioned(CURRENT_VERSION) => Ok(T::decode(&data[MAGIC.len() + 1..])?),
+        StoredFormat::Versioned(version) => Err(DecodeError::UnsupportedVersion(version)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{convert::generated, StoredTransactionStatusMeta},
+        solana_transaction_status::TransactionStatusMeta,
+    };
+
+    #[test]
+    fn test_versioned_round_trip() {
+        let meta = generated::TransactionStatusMeta {
+            fee: 42,
+            pre_balances: vec![1, 2],
+            post_balances: vec![2, 1],
+            ..generated::TransactionStatusMeta::default()
+        };
+        let data = encode(&meta);
+        assert_eq!(
+            stored_format(&data),
+            StoredFormat::Versioned(CURRENT_VERSION)
+        );
+        assert_eq!(
+            decode::<generated::TransactionStatusMeta>(&data).unwrap(),
+            meta
+        );
+
+        let mut newer = data;
+        newer[MAGIC.len()] = CURRENT_VERSION + 1;
+        assert!(matches!(
+            decode::<generated::TransactionStatusMeta>(&newer),
+            Err(DecodeError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_values_are_not_versioned() {
+        let meta = generated::TransactionStatusMeta::from(TransactionStatusMeta {
+            fee: 42,
+            ..TransactionStatusMeta::default()
+        });
+        let mut protobuf = Vec::with_capacity(meta.encoded_len());
+        meta.encode(&mut protobuf).unwrap();
+        assert_eq!(stored_format(&protobuf), StoredFormat::Legacy);
+
+        let bincode = bincode::serialize(&StoredTransactionStatusMeta::from(
+            TransactionStatusMeta::default(),
+        ))
+        .unwrap();
+        assert_eq!(stored_format(&bincode), StoredFormat::Legacy);
+        assert!(matches!(
+            decode::<generated::TransactionStatusMeta>(&bincode),
+            Err(DecodeError::Unversioned)
+        ));
+    }
+}
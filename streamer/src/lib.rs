@@ -1,5 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod packet;
+pub mod recv_backend;
 pub mod recvmmsg;
 pub mod sendmmsg;
 pub mod socket;
@@ -1,6 +1,7 @@
 //! The `packet` module defines data structures and methods to pull data from the network.
 use crate::{
-    recvmmsg::{recv_mmsg, NUM_RCVMMSGS},
+    recv_backend::{BatchSizeTuner, RecvBackend, RecvMmsgBackend},
+    recvmmsg::NUM_RCVMMSGS,
     socket::SocketAddrSpace,
 };
 pub use solana_perf::packet::{
@@ -13,6 +14,37 @@ pub use solana_sdk::packet::{Meta, Packet, PACKET_DATA_SIZE};
 use std::{io::Result, net::UdpSocket, time::Instant};
 
 pub fn recv_from(obj: &mut Packets, socket: &UdpSocket, max_wait_ms: u64) -> Result<usize> {
+    recv_from_with_limit(obj, socket, max_wait_ms, PACKETS_PER_BATCH)
+}
+
+/// Like `recv_from`, but stops coalescing once `max_packets` packets have been received.
+/// `max_packets` is clamped to `1..=PACKETS_PER_BATCH`.
+pub fn recv_from_with_limit(
+    obj: &mut Packets,
+    socket: &UdpSocket,
+    max_wait_ms: u64,
+    max_packets: usize,
+) -> Result<usize> {
+    recv_from_with_backend(
+        obj,
+        socket,
+        max_wait_ms,
+        max_packets,
+        &mut RecvMmsgBackend,
+        &mut BatchSizeTuner::fixed(NUM_RCVMMSGS),
+    )
+}
+
+/// Like `recv_from_with_limit`, but reads with `backend`, in batches of the size `tuner` sets
+pub fn recv_from_with_backend(
+    obj: &mut Packets,
+    socket: &UdpSocket,
+    max_wait_ms: u64,
+    max_packets: usize,
+    backend: &mut dyn RecvBackend,
+    tuner: &mut BatchSizeTuner,
+) -> Result<usize> {
+    let max_packets = max_packets.max(1).min(PACKETS_PER_BATCH);
     let mut i = 0;
     //DOCUMENTED SIDE-EFFECT
     //Performance out of the IO without poll
@@ -24,11 +56,9 @@ pub fn recv_from(obj: &mut Packets, socket: &UdpSocket, max_wait_ms: u64) -> Res
     trace!("receiving on {}", socket.local_addr().unwrap());
     let start = Instant::now();
     loop {
-        obj.packets.resize(
-            std::cmp::min(i + NUM_RCVMMSGS, PACKETS_PER_BATCH),
-            Packet::default(),
-        );
-        match recv_mmsg(socket, &mut obj.packets[i..]) {
+        let requested = std::cmp::min(tuner.batch_size(), max_packets - i);
+        obj.packets.resize(i + requested, Packet::default());
+        match backend.recv(socket, &mut obj.packets[i..]) {
             Err(_) if i > 0 => {
                 if start.elapsed().as_millis() as u64 > max_wait_ms {
                     break;
@@ -39,6 +69,7 @@ pub fn recv_from(obj: &mut Packets, socket: &UdpSocket, max_wait_ms: u64) -> Res
                 return Err(e);
             }
             Ok((_, npkts)) => {
+                tuner.record(requested, npkts);
                 if i == 0 {
                     socket.set_nonblocking(true)?;
                 }
@@ -46,7 +77,7 @@ pub fn recv_from(obj: &mut Packets, socket: &UdpSocket, max_wait_ms: u64) -> Res
                 i += npkts;
                 // Try to batch into big enough buffers
                 // will cause less re-shuffling later on.
-                if start.elapsed().as_millis() as u64 > max_wait_ms || i >= PACKETS_PER_BATCH {
+                if start.elapsed().as_millis() as u64 > max_wait_ms || i >= max_packets {
                     break;
                 }
             }
@@ -165,4 +196,24 @@ mod tests {
         assert_eq!(recvd, PACKETS_PER_BATCH);
         assert_eq!(p.packets.capacity(), PACKETS_PER_BATCH);
     }
+
+    #[test]
+    fn test_recv_from_with_limit() {
+        solana_logger::setup();
+        let recv_socket = UdpSocket::bind("127.0.0.1:0").expect("bind");
+        let addr = recv_socket.local_addr().unwrap();
+        let send_socket = UdpSocket::bind("127.0.0.1:0").expect("bind");
+        let mut p = Packets::default();
+        p.packets.resize(16, Packet::default());
+        for m in p.packets.iter_mut() {
+            m.meta.set_addr(&addr);
+            m.meta.size = 1;
+        }
+        send_to(&p, &send_socket, &SocketAddrSpace::Unspecified).unwrap();
+
+        let mut p = Packets::default();
+        let recvd = recv_from_with_limit(&mut p, &recv_socket, 100, 4).unwrap();
+        assert_eq!(recvd, 4);
+        assert_eq!(p.packets.len(), 4);
+    }
 }
@@ -0,0 +1,371 @@
+//! The `recv_backend` module abstracts how batches of datagrams are read from a socket: with
+//! recvmmsg(), or with io_uring on Linux when the `io-uring` feature is enabled. It also tunes
+//! the number of datagrams asked for in each batch to the rate they arrive at.
+
+use {
+    crate::{
+        packet::Packet,
+        recvmmsg::{recv_mmsg, NUM_RCVMMSGS},
+    },
+    std::{io, net::UdpSocket, str::FromStr},
+};
+
+/// Reads batches of datagrams from a socket
+pub trait RecvBackend: Send {
+    /// Reads datagrams into `packets`, waiting for the first one unless the socket is non
+    /// blocking, and returns their total size and their number
+    fn recv(&mut self, socket: &UdpSocket, packets: &mut [Packet]) -> io::Result<(usize, usize)>;
+}
+
+/// Reads with one recvmmsg() call per batch
+pub struct RecvMmsgBackend;
+
+impl RecvBackend for RecvMmsgBackend {
+    fn recv(&mut self, socket: &UdpSocket, packets: &mut [Packet]) -> io::Result<(usize, usize)> {
+        recv_mmsg(socket, packets)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvBackendKind {
+    RecvMmsg,
+    IoUring,
+}
+
+impl Default for RecvBackendKind {
+    fn default() -> Self {
+        Self::RecvMmsg
+    }
+}
+
+impl FromStr for RecvBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recvmmsg" => Ok(Self::RecvMmsg),
+            "io_uring" => Ok(Self::IoUring),
+            _ => Err(format!(
+                "unknown receive backend {}, expected recvmmsg or io_uring",
+                s
+            )),
+        }
+    }
+}
+
+impl RecvBackendKind {
+    /// Creates a backend of this kind, recvmmsg() being used when io_uring isn't available
+    pub fn new_backend(self) -> Box<dyn RecvBackend> {
+        match self {
+            Self::RecvMmsg => Box::new(RecvMmsgBackend),
+            Self::IoUring => new_io_uring_backend(),
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+fn new_io_uring_backend() -> Box<dyn RecvBackend> {
+    match io_uring_backend::IoUringBackend::new() {
+        Ok(backend) => Box::new(backend),
+        Err(err) => {
+            warn!("io_uring is unavailable, using recvmmsg: {}", err);
+            Box::new(RecvMmsgBackend)
+        }
+    }
+}
+
+#[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+fn new_io_uring_backend() -> Box<dyn RecvBackend> {
+    warn!("solana-streamer was built without the io-uring feature, using recvmmsg");
+    Box::new(RecvMmsgBackend)
+}
+
+/// Smallest number of datagrams the tuner asks for in a batch
+pub const MIN_RECV_BATCH_SIZE: usize = 8;
+
+/// Number of mostly empty batches in a row after which the batch size is halved
+const SPARSE_BATCHES_TO_SHRINK: usize = 16;
+
+/// Tunes the number of datagrams asked for in each batch, between `MIN_RECV_BATCH_SIZE` and
+/// `NUM_RCVMMSGS`. The size starts small and is doubled as soon as a batch comes back full, and
+/// halved once batches keep coming back mostly empty, which saves preparing the buffers of
+/// datagrams that aren't there.
+#[derive(Clone, Debug)]
+pub struct BatchSizeTuner {
+    batch_size: usize,
+    autotune: bool,
+    sparse_batches: usize,
+}
+
+impl BatchSizeTuner {
+    /// Always asks for `batch_size` datagrams, at most `NUM_RCVMMSGS`
+    pub fn fixed(batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1).min(NUM_RCVMMSGS),
+            autotune: false,
+            sparse_batches: 0,
+        }
+    }
+
+    pub fn autotuned() -> Self {
+        Self {
+            batch_size: MIN_RECV_BATCH_SIZE,
+            autotune: true,
+            sparse_batches: 0,
+        }
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Records that `received` datagrams came back from a batch of `requested`
+    pub fn record(&mut self, requested: usize, received: usize) {
+        // Batches cut short by the room left in the packets tell nothing of the rate
+        if !self.autotune || requested < self.batch_size {
+            return;
+        }
+        if received >= requested {
+            self.batch_size = (self.batch_size * 2).min(NUM_RCVMMSGS);
+            self.sparse_batches = 0;
+        } else if received * 4 < requested {
+            self.sparse_batches += 1;
+            if self.sparse_batches >= SPARSE_BATCHES_TO_SHRINK {
+                self.batch_size = (self.batch_size / 2).max(MIN_RECV_BATCH_SIZE);
+                self.sparse_batches = 0;
+            }
+        } else {
+            self.sparse_batches = 0;
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod io_uring_backend {
+    use {
+        super::RecvBackend,
+        crate::{
+            packet::Packet,
+            recvmmsg::{cast_socket_addr, NUM_RCVMMSGS},
+        },
+        io_uring::{opcode, types, IoUring},
+        libc::{iovec, msghdr, sockaddr_storage, socklen_t},
+        std::{io, mem, net::UdpSocket, os::unix::io::AsRawFd},
+    };
+
+    const SOCKADDR_STORAGE_SIZE: usize = mem::size_of::<sockaddr_storage>();
+
+    /// Reads a batch with a single submission of non blocking recvmsg() operations, one per
+    /// datagram. The operations all complete before `recv` returns, so that the kernel never
+    /// writes to the packets afterwards.
+    pub struct IoUringBackend {
+        ring: IoUring,
+        // Boxed so that the addresses the kernel writes to don't move
+        hdrs: Box<[msghdr; NUM_RCVMMSGS]>,
+        iovs: Box<[iovec; NUM_RCVMMSGS]>,
+        addrs: Box<[sockaddr_storage; NUM_RCVMMSGS]>,
+    }
+
+    // The raw pointers of the headers only point to the buffers of the backend, and to the packets
+    // during a call to `recv`
+    unsafe impl Send for IoUringBackend {}
+
+    impl IoUringBackend {
+        pub fn new() -> io::Result<Self> {
+            Ok(Self {
+                ring: IoUring::new(NUM_RCVMMSGS as u32)?,
+                hdrs: Box::new(unsafe { mem::zeroed() }),
+                iovs: Box::new(unsafe { mem::zeroed() }),
+                addrs: Box::new(unsafe { mem::zeroed() }),
+            })
+        }
+
+        /// Waits for the socket to be readable, unless it is non blocking. Returns false if it
+        /// isn't readable before the read timeout of the socket.
+        fn wait_readable(socket: &UdpSocket) -> io::Result<bool> {
+            let fd = socket.as_raw_fd();
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            if flags < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if flags & libc::O_NONBLOCK != 0 {
+                return Ok(false);
+            }
+            let timeout_ms = socket
+                .read_timeout()?
+                .map(|timeout| timeout.as_millis().min(i32::MAX as u128) as i32)
+                .unwrap_or(-1);
+            let mut pollfd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+                0 => Ok(false),
+                n if n > 0 => Ok(true),
+                _ => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() == io::ErrorKind::Interrupted {
+                        Ok(true)
+                    } else {
+                        Err(err)
+                    }
+                }
+            }
+        }
+    }
+
+    impl RecvBackend for IoUringBackend {
+        fn recv(
+            &mut self,
+            socket: &UdpSocket,
+            packets: &mut [Packet],
+        ) -> io::Result<(usize, usize)> {
+            let count = packets.len().min(NUM_RCVMMSGS);
+            if count == 0 {
+                return Ok((0, 0));
+            }
+            let fd = types::Fd(socket.as_raw_fd());
+            loop {
+                for (i, packet) in packets.iter_mut().take(count).enumerate() {
+                    self.iovs[i] = iovec {
+                        iov_base: packet.data.as_mut_ptr() as *mut libc::c_void,
+                        iov_len: packet.data.len(),
+                    };
+                    self.hdrs[i] = unsafe { mem::zeroed() };
+                    self.hdrs[i].msg_name = &mut self.addrs[i] as *mut _ as *mut _;
+                    self.hdrs[i].msg_namelen = SOCKADDR_STORAGE_SIZE as socklen_t;
+                    self.hdrs[i].msg_iov = &mut self.iovs[i];
+                    self.hdrs[i].msg_iovlen = 1;
+                    let entry = opcode::RecvMsg::new(fd, &mut self.hdrs[i])
+                        .flags(libc::MSG_DONTWAIT as u32)
+                        .build()
+                        .user_data(i as u64);
+                    // The header and the buffers it points to outlive the operation, which
+                    // completes before this call returns
+                    unsafe { self.ring.submission().push(&entry) }.map_err(|_| {
+                        io::Error::new(io::ErrorKind::Other, "io_uring submission queue is full")
+                    })?;
+                }
+                let submitted = self.ring.submit_and_wait(count);
+
+                let mut sizes = [None; NUM_RCVMMSGS];
+                let mut last_err = None;
+                for entry in self.ring.completion() {
+                    let i = entry.user_data() as usize;
+                    if entry.result() >= 0 {
+                        sizes[i] = Some(entry.result() as usize);
+                    } else {
+                        last_err = Some(io::Error::from_raw_os_error(-entry.result()));
+                    }
+                }
+                submitted?;
+
+                // The datagrams are moved to the front of the packets, over the operations
+                // which found none
+                let mut npkts = 0;
+                let mut total_size = 0;
+                for (i, size) in sizes.iter().enumerate().take(count) {
+                    let size = match size {
+                        Some(size) => *size,
+                        None => continue,
+                    };
+                    let addr = match cast_socket_addr(&self.addrs[i], self.hdrs[i].msg_namelen) {
+                        Some(addr) => addr.to_std(),
+                        None => continue,
+                    };
+                    packets.swap(npkts, i);
+                    packets[npkts].meta.size = size;
+                    packets[npkts].meta.set_addr(&addr);
+                    npkts += 1;
+                    total_size += size;
+                }
+                if npkts > 0 {
+                    return Ok((total_size, npkts));
+                }
+                let err = last_err.unwrap_or_else(|| io::ErrorKind::WouldBlock.into());
+                if err.kind() != io::ErrorKind::WouldBlock || !Self::wait_readable(socket)? {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_size_tuner() {
+        let mut tuner = BatchSizeTuner::fixed(NUM_RCVMMSGS);
+        tuner.record(NUM_RCVMMSGS, 0);
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS);
+
+        // The batch size grows while batches come back full
+        let mut tuner = BatchSizeTuner::autotuned();
+        assert_eq!(tuner.batch_size(), MIN_RECV_BATCH_SIZE);
+        while tuner.batch_size() < NUM_RCVMMSGS {
+            let batch_size = tuner.batch_size();
+            tuner.record(batch_size, batch_size);
+            assert_eq!(tuner.batch_size(), (batch_size * 2).min(NUM_RCVMMSGS));
+        }
+        tuner.record(NUM_RCVMMSGS, NUM_RCVMMSGS);
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS);
+
+        for _ in 0..SPARSE_BATCHES_TO_SHRINK - 1 {
+            tuner.record(NUM_RCVMMSGS, 1);
+        }
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS);
+        tuner.record(NUM_RCVMMSGS, 1);
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS / 2);
+
+        // Batches cut short are ignored
+        for _ in 0..SPARSE_BATCHES_TO_SHRINK {
+            tuner.record(4, 0);
+        }
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS / 2);
+
+        // A batch half full resets the sparse streak, a full one grows the batch size
+        for _ in 0..SPARSE_BATCHES_TO_SHRINK - 1 {
+            tuner.record(NUM_RCVMMSGS / 2, 0);
+        }
+        tuner.record(NUM_RCVMMSGS / 2, NUM_RCVMMSGS / 4);
+        tuner.record(NUM_RCVMMSGS / 2, 0);
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS / 2);
+        tuner.record(NUM_RCVMMSGS / 2, NUM_RCVMMSGS / 2);
+        assert_eq!(tuner.batch_size(), NUM_RCVMMSGS);
+
+        for _ in 0..100 * SPARSE_BATCHES_TO_SHRINK {
+            let batch_size = tuner.batch_size();
+            tuner.record(batch_size, 0);
+        }
+        assert_eq!(tuner.batch_size(), MIN_RECV_BATCH_SIZE);
+    }
+
+    #[test]
+    fn test_recv_backends() {
+        assert_eq!("recvmmsg".parse(), Ok(RecvBackendKind::RecvMmsg));
+        assert_eq!("io_uring".parse(), Ok(RecvBackendKind::IoUring));
+        assert!("epoll".parse::<RecvBackendKind>().is_err());
+
+        for kind in &[RecvBackendKind::RecvMmsg, RecvBackendKind::IoUring] {
+            let reader = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let addr = reader.local_addr().unwrap();
+            for i in 0..10 {
+                sender.send_to(&[i; 10], &addr).unwrap();
+            }
+            let mut backend = kind.new_backend();
+            let mut packets = vec![Packet::default(); 32];
+            let (total_size, npkts) = backend.recv(&reader, &mut packets).unwrap();
+            assert_eq!(npkts, 10);
+            assert_eq!(total_size, 100);
+            for (i, packet) in packets.iter().take(npkts).enumerate() {
+                assert_eq!(packet.meta.size, 10);
+                assert_eq!(packet.meta.addr(), sender.local_addr().unwrap());
+                assert_eq!(packet.data[..10], [i as u8; 10]);
+            }
+        }
+    }
+}
@@ -42,25 +42,21 @@ pub fn recv_mmsg(socket: &UdpSocket, packets: &mut [Packet]) -> io::Result<(usiz
 }
 
 #[cfg(target_os = "linux")]
-fn cast_socket_addr(addr: &sockaddr_storage, hdr: &mmsghdr) -> Option<InetAddr> {
+pub(crate) fn cast_socket_addr(addr: &sockaddr_storage, namelen: socklen_t) -> Option<InetAddr> {
     use libc::{sa_family_t, sockaddr_in, sockaddr_in6};
     const SOCKADDR_IN_SIZE: usize = std::mem::size_of::<sockaddr_in>();
     const SOCKADDR_IN6_SIZE: usize = std::mem::size_of::<sockaddr_in6>();
-    if addr.ss_family == AF_INET as sa_family_t
-        && hdr.msg_hdr.msg_namelen == SOCKADDR_IN_SIZE as socklen_t
-    {
+    if addr.ss_family == AF_INET as sa_family_t && namelen == SOCKADDR_IN_SIZE as socklen_t {
         let addr = addr as *const _ as *const sockaddr_in;
         return Some(unsafe { InetAddr::V4(*addr) });
     }
-    if addr.ss_family == AF_INET6 as sa_family_t
-        && hdr.msg_hdr.msg_namelen == SOCKADDR_IN6_SIZE as socklen_t
-    {
+    if addr.ss_family == AF_INET6 as sa_family_t && namelen == SOCKADDR_IN6_SIZE as socklen_t {
         let addr = addr as *const _ as *const sockaddr_in6;
         return Some(unsafe { InetAddr::V6(*addr) });
     }
     error!(
         "recvmmsg unexpected ss_family:{} msg_namelen:{}",
-        addr.ss_family, hdr.msg_hdr.msg_namelen
+        addr.ss_family, namelen
     );
     None
 }
@@ -104,7 +100,7 @@ pub fn recv_mmsg(sock: &UdpSocket, packets: &mut [Packet]) -> io::Result<(usize,
         .zip(hdrs)
         .take(nrecv as usize)
         .filter_map(|(addr, hdr)| {
-            let addr = cast_socket_addr(addr, &hdr)?.to_std();
+            let addr = cast_socket_addr(addr, hdr.msg_hdr.msg_namelen)?.to_std();
             Some((addr, hdr))
         })
         .zip(packets.iter_mut())
@@ -3,6 +3,7 @@
 
 use crate::{
     packet::{self, send_to, Packets, PacketsRecycler, PACKETS_PER_BATCH},
+    recv_backend::{BatchSizeTuner, RecvBackendKind},
     recvmmsg::NUM_RCVMMSGS,
     socket::SocketAddrSpace,
 };
@@ -32,19 +33,57 @@ pub enum StreamerError {
 
 pub type Result<T> = std::result::Result<T, StreamerError>;
 
+/// How often each receiver reports the kernel's drop count for its socket
+const SOCKET_STATS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReceiverConfig {
+    /// Milliseconds to keep reading into a batch once its first packet arrives
+    pub coalesce_ms: u64,
+    /// Stop coalescing once a batch holds this many packets, at most `PACKETS_PER_BATCH`
+    pub max_coalesce_packets: usize,
+    /// Kernel receive buffer size to request for the socket; None keeps the system default
+    pub recv_buffer_size: Option<usize>,
+    /// How the datagrams are read from the socket
+    pub recv_backend: RecvBackendKind,
+    /// Tune the number of datagrams read per call to the packet rate, instead of always asking
+    /// for `NUM_RCVMMSGS`
+    pub autotune_batch_size: bool,
+}
+
+impl ReceiverConfig {
+    pub fn new(coalesce_ms: u64) -> Self {
+        Self {
+            coalesce_ms,
+            max_coalesce_packets: PACKETS_PER_BATCH,
+            recv_buffer_size: None,
+            recv_backend: RecvBackendKind::default(),
+            autotune_batch_size: false,
+        }
+    }
+}
+
 fn recv_loop(
     sock: &UdpSocket,
     exit: Arc<AtomicBool>,
     channel: &PacketSender,
     recycler: &PacketsRecycler,
     name: &'static str,
-    coalesce_ms: u64,
+    config: &ReceiverConfig,
     use_pinned_memory: bool,
 ) -> Result<()> {
     let mut recv_count = 0;
     let mut call_count = 0;
     let mut now = Instant::now();
     let mut num_max_received = 0; // Number of times maximum packets were received
+    let mut last_drops = socket_drops(sock);
+    let mut last_socket_report = Instant::now();
+    let mut backend = config.recv_backend.new_backend();
+    let mut tuner = if config.autotune_batch_size {
+        BatchSizeTuner::autotuned()
+    } else {
+        BatchSizeTuner::fixed(NUM_RCVMMSGS)
+    };
     loop {
         let mut msgs = if use_pinned_memory {
             Packets::new_with_recycler(recycler.clone(), PACKETS_PER_BATCH, name)
@@ -57,7 +96,14 @@ fn recv_loop(
             if exit.load(Ordering::Relaxed) {
                 return Ok(());
             }
-            if let Ok(len) = packet::recv_from(&mut msgs, sock, coalesce_ms) {
+            if let Ok(len) = packet::recv_from_with_backend(
+                &mut msgs,
+                sock,
+                config.coalesce_ms,
+                config.max_coalesce_packets,
+                backend.as_mut(),
+                &mut tuner,
+            ) {
                 if len == NUM_RCVMMSGS {
                     num_max_received += 1;
                 }
@@ -76,11 +122,31 @@ fn recv_loop(
                 ("call_count", i64::from(call_count), i64),
                 ("elapsed", now.elapsed().as_millis() as i64, i64),
                 ("max_received", i64::from(num_max_received), i64),
+                ("batch_size", tuner.batch_size() as i64, i64),
             );
             recv_count = 0;
             call_count = 0;
             num_max_received = 0;
         }
+        if last_socket_report.elapsed() > SOCKET_STATS_REPORT_INTERVAL {
+            let drops = socket_drops(sock);
+            if let (Some(last), Some(current)) = (last_drops, drops) {
+                datapoint_info!(
+                    "streamer-receiver-socket",
+                    ("name", name, String),
+                    (
+                        "port",
+                        sock.local_addr()
+                            .map(|addr| addr.port())
+                            .unwrap_or_default(),
+                        i64
+                    ),
+                    ("drops", current.saturating_sub(last), i64),
+                );
+            }
+            last_drops = drops;
+            last_socket_report = Instant::now();
+        }
         now = Instant::now();
     }
 }
@@ -93,9 +159,40 @@ pub fn receiver(
     name: &'static str,
     coalesce_ms: u64,
     use_pinned_memory: bool,
+) -> JoinHandle<()> {
+    receiver_with_config(
+        sock,
+        exit,
+        packet_sender,
+        recycler,
+        name,
+        ReceiverConfig::new(coalesce_ms),
+        use_pinned_memory,
+    )
+}
+
+pub fn receiver_with_config(
+    sock: Arc<UdpSocket>,
+    exit: &Arc<AtomicBool>,
+    packet_sender: PacketSender,
+    recycler: PacketsRecycler,
+    name: &'static str,
+    config: ReceiverConfig,
+    use_pinned_memory: bool,
 ) -> JoinHandle<()> {
     let res = sock.set_read_timeout(Some(Duration::new(1, 0)));
     assert!(!res.is_err(), "streamer::receiver set_read_timeout error");
+    if let Some(size) = config.recv_buffer_size {
+        match set_recv_buffer_size(&sock, size) {
+            Ok(actual) if actual < size => warn!(
+                "{}: requested a {} byte receive buffer but the kernel granted {}; \
+                 consider raising net.core.rmem_max",
+                name, size, actual
+            ),
+            Ok(_) => (),
+            Err(err) => warn!("{}: failed to set receive buffer size: {}", name, err),
+        }
+    }
     let exit = exit.clone();
     Builder::new()
         .name("solana-receiver".to_string())
@@ -106,13 +203,64 @@ pub fn receiver(
                 &packet_sender,
                 &recycler.clone(),
                 name,
-                coalesce_ms,
+                &config,
                 use_pinned_memory,
             );
         })
         .unwrap()
 }
 
+/// Request a kernel receive buffer of `size` bytes and return the size actually granted, which
+/// is capped by `net.core.rmem_max`
+#[cfg(target_os = "linux")]
+fn set_recv_buffer_size(sock: &UdpSocket, size: usize) -> std::io::Result<usize> {
+    use {
+        nix::sys::socket::{getsockopt, setsockopt, sockopt::RcvBuf},
+        std::os::unix::io::AsRawFd,
+    };
+    let fd = sock.as_raw_fd();
+    setsockopt(fd, RcvBuf, &size)?;
+    // Linux doubles the requested size to leave room for bookkeeping overhead
+    Ok(getsockopt(fd, RcvBuf)? / 2)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_recv_buffer_size(_sock: &UdpSocket, _size: usize) -> std::io::Result<usize> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "setting the receive buffer size is only supported on linux",
+    ))
+}
+
+/// Number of datagrams the kernel dropped on this socket, e.g. because its receive buffer was full
+#[cfg(target_os = "linux")]
+fn socket_drops(sock: &UdpSocket) -> Option<u64> {
+    use std::os::unix::io::AsRawFd;
+    let inode = nix::sys::stat::fstat(sock.as_raw_fd()).ok()?.st_ino as u64;
+    ["/proc/net/udp", "/proc/net/udp6"].iter().find_map(|path| {
+        let table = std::fs::read_to_string(path).ok()?;
+        parse_udp_drops(&table, inode)
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_drops(_sock: &UdpSocket) -> Option<u64> {
+    None
+}
+
+/// Find the drops column for the socket with the given inode in a `/proc/net/udp` table
+#[cfg(any(target_os = "linux", test))]
+fn parse_udp_drops(table: &str, inode: u64) -> Option<u64> {
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(9)?.parse::<u64>().ok()? == inode {
+            fields.get(12)?.parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
 fn recv_send(
     sock: &UdpSocket,
     r: &PacketReceiver,
@@ -257,4 +405,16 @@ mod test {
         t_receiver.join().expect("join");
         t_responder.join().expect("join");
     }
+
+    #[test]
+    fn test_parse_udp_drops() {
+        let table = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  100: 00000000:1F90 00000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 41001 2 0000000000000000 0
+  101: 00000000:1F90 00000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 41002 2 0000000000000000 17
+";
+        assert_eq!(parse_udp_drops(table, 41001), Some(0));
+        assert_eq!(parse_udp_drops(table, 41002), Some(17));
+        assert_eq!(parse_udp_drops(table, 41003), None);
+    }
 }
@@ -3,6 +3,7 @@ use {
     log::*,
     solana_client::rpc_client::RpcClient,
     solana_core::{
+        replay_stage::{request_warp_slot, WarpSlotSender},
         tower_storage::TowerStorage,
         validator::{Validator, ValidatorConfig, ValidatorStartProgress},
     },
@@ -13,15 +14,20 @@ use {
     },
     solana_ledger::{blockstore::create_new_ledger, create_new_tmp_ledger},
     solana_net_utils::PortRange,
+    solana_poh::poh_service::ManualTicks,
     solana_rpc::rpc::JsonRpcConfig,
     solana_runtime::{
+        accounts_db::AccountsDbConfig, bank_forks::BankForks,
         genesis_utils::create_genesis_config_with_leader_ex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE, snapshot_config::SnapshotConfig,
     },
     solana_sdk::{
         account::{Account, AccountSharedData},
+        account_utils::StateMut,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
         clock::{Slot, DEFAULT_MS_PER_SLOT},
         commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudget,
         epoch_schedule::EpochSchedule,
         exit::Exit,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
@@ -85,6 +91,7 @@ pub struct TestValidatorGenesis {
     rpc_ports: Option<(u16, u16)>, // (JsonRpc, JsonRpcPubSub), None == random ports
     warp_slot: Option<Slot>,
     no_bpf_jit: bool,
+    accounts_in_memory: bool,
     accounts: HashMap<Pubkey, AccountSharedData>,
     programs: Vec<ProgramInfo>,
     epoch_schedule: Option<EpochSchedule>,
@@ -93,6 +100,8 @@ pub struct TestValidatorGenesis {
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub max_ledger_shreds: Option<u64>,
+    manual_ticks: Option<Arc<ManualTicks>>,
+    compute_budget: Option<ComputeBudget>,
 }
 
 impl TestValidatorGenesis {
@@ -151,6 +160,26 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Only advance PoH when requested through `manual_ticks`, for example with
+    /// `TestValidator::advance_slots`
+    pub fn manual_ticks(&mut self, manual_ticks: Arc<ManualTicks>) -> &mut Self {
+        self.manual_ticks = Some(manual_ticks);
+        self
+    }
+
+    /// Execute transactions with `compute_budget` instead of the default one, for example to
+    /// benchmark programs under a proposed cost model
+    pub fn compute_budget(&mut self, compute_budget: ComputeBudget) -> &mut Self {
+        self.compute_budget = Some(compute_budget);
+        self
+    }
+
+    /// Keep account storages in memory instead of under the ledger directory
+    pub fn accounts_in_memory(&mut self, accounts_in_memory: bool) -> &mut Self {
+        self.accounts_in_memory = accounts_in_memory;
+        self
+    }
+
     pub fn gossip_host(&mut self, gossip_host: IpAddr) -> &mut Self {
         self.node_config.gossip_addr.set_ip(gossip_host);
         self
@@ -202,6 +231,60 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Clone programs, along with the program data accounts of the upgradeable ones and all the
+    /// accounts they own, from the cluster of `rpc_client`
+    pub fn clone_programs_with_accounts<T>(
+        &mut self,
+        program_ids: T,
+        rpc_client: &RpcClient,
+    ) -> &mut Self
+    where
+        T: IntoIterator<Item = Pubkey>,
+    {
+        for program_id in program_ids {
+            info!("Fetching program {} over RPC...", program_id);
+            let program_account = rpc_client.get_account(&program_id).unwrap_or_else(|err| {
+                error!("Failed to fetch {}: {}", program_id, err);
+                solana_core::validator::abort();
+            });
+            if !program_account.executable {
+                error!("{} is not a program", program_id);
+                solana_core::validator::abort();
+            }
+            if bpf_loader_upgradeable::check_id(&program_account.owner) {
+                if let Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) = program_account.state()
+                {
+                    self.clone_accounts(Some(programdata_address), rpc_client);
+                }
+            }
+            self.add_account(program_id, AccountSharedData::from(program_account));
+
+            info!("Fetching the accounts owned by {} over RPC...", program_id);
+            let accounts = rpc_client
+                .get_program_accounts(&program_id)
+                .unwrap_or_else(|err| {
+                    error!(
+                        "Failed to fetch the accounts owned by {}: {}",
+                        program_id, err
+                    );
+                    solana_core::validator::abort();
+                });
+            info!(
+                "Cloning {} accounts owned by {}",
+                accounts.len(),
+                program_id
+            );
+            self.add_accounts(
+                accounts
+                    .into_iter()
+                    .map(|(address, account)| (address, AccountSharedData::from(account))),
+            );
+        }
+        self
+    }
+
     /// Add an account to the test environment with the account data in the provided `filename`
     pub fn add_account_with_file_data(
         &mut self,
@@ -320,6 +403,7 @@ pub struct TestValidator {
     gossip: SocketAddr,
     validator: Option<Validator>,
     vote_account_address: Pubkey,
+    manual_ticks: Option<Arc<ManualTicks>>,
 }
 
 impl TestValidator {
@@ -532,6 +616,12 @@ impl TestValidator {
             rocksdb_compaction_interval: Some(100), // Compact every 100 slots
             max_ledger_shreds: config.max_ledger_shreds,
             no_wait_for_vote_to_start_leader: true,
+            poh_manual_ticks: config.manual_ticks.clone(),
+            compute_budget: config.compute_budget,
+            accounts_db_config: Some(AccountsDbConfig {
+                storages_in_memory: config.accounts_in_memory,
+                ..AccountsDbConfig::default()
+            }),
             ..ValidatorConfig::default()
         };
         if let Some(ref tower_storage) = config.tower_storage {
@@ -578,6 +668,9 @@ impl TestValidator {
                     break;
                 }
                 println!("Waiting for fees to stabilize {:?}...", num_tries);
+                if let Some(manual_ticks) = &config.manual_ticks {
+                    manual_ticks.advance(1);
+                }
                 match rpc_client.get_latest_blockhash() {
                     Ok(blockhash) => {
                         message.recent_blockhash = blockhash;
@@ -611,9 +704,24 @@ impl TestValidator {
             gossip,
             validator,
             vote_account_address,
+            manual_ticks: config.manual_ticks.clone(),
         })
     }
 
+    /// Advance PoH by `slots` slots and wait for it to get there. Returns the slot PoH advanced
+    /// to, or None if the validator wasn't started with `TestValidatorGenesis::manual_ticks`.
+    pub fn advance_slots(&self, slots: u64) -> Option<Slot> {
+        let manual_ticks = self.manual_ticks.as_ref()?;
+        let timeout = Duration::from_millis(DEFAULT_MS_PER_SLOT * (slots + 10));
+        manual_ticks.advance_and_wait(slots, timeout)
+    }
+
+    /// Warp the running validator ahead to `slot`, as if every slot in between had been skipped.
+    /// Returns the slot warped to.
+    pub fn warp_to_slot(&self, slot: Slot) -> Result<Slot, String> {
+        request_warp_slot(&self.warp_slot_sender(), slot, Duration::from_secs(60))
+    }
+
     /// Return the validator's TPU address
     pub fn tpu(&self) -> &SocketAddr {
         &self.tpu
@@ -667,6 +775,14 @@ impl TestValidator {
     pub fn cluster_info(&self) -> Arc<ClusterInfo> {
         self.validator.as_ref().unwrap().cluster_info.clone()
     }
+
+    pub fn warp_slot_sender(&self) -> WarpSlotSender {
+        self.validator.as_ref().unwrap().warp_slot_sender.clone()
+    }
+
+    pub fn bank_forks(&self) -> Arc<RwLock<BankForks>> {
+        self.validator.as_ref().unwrap().bank_forks.clone()
+    }
 }
 
 impl Drop for TestValidator {
@@ -15,19 +15,23 @@ pub mod parse_token;
 pub mod parse_vote;
 pub mod token_balances;
 
-pub use {crate::extract_memos::extract_and_fmt_memos, solana_runtime::bank::RewardType};
+pub use {
+    crate::extract_memos::extract_and_fmt_memos,
+    solana_runtime::bank::{FeeDetails, RewardType},
+};
 use {
     crate::{
-        parse_accounts::{parse_accounts, ParsedAccount},
+        parse_accounts::{parse_accounts, parse_loaded_accounts, ParsedAccount},
         parse_instruction::{parse, ParsedInstruction},
     },
     solana_account_decoder::parse_token::UiTokenAmount,
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudgetExceeded,
         deserialize_utils::default_on_eof,
         instruction::CompiledInstruction,
-        message::{Message, MessageHeader},
+        message::{MappedAddresses, Message, MessageHeader},
         pubkey::Pubkey,
         sanitize::Sanitize,
         signature::Signature,
@@ -51,18 +55,30 @@ pub enum UiParsedInstruction {
 }
 
 impl UiInstruction {
-    fn parse(instruction: &CompiledInstruction, message: &Message) -> Self {
-        let program_id = instruction.program_id(&message.account_keys);
-        if let Ok(parsed_instruction) = parse(program_id, instruction, &message.account_keys) {
+    fn parse(instruction: &CompiledInstruction, account_keys: &[Pubkey]) -> Self {
+        let program_id = instruction.program_id(account_keys);
+        if let Ok(parsed_instruction) = parse(program_id, instruction, account_keys) {
             UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed_instruction))
         } else {
             UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
-                UiPartiallyDecodedInstruction::from(instruction, &message.account_keys),
+                UiPartiallyDecodedInstruction::from(instruction, account_keys),
             ))
         }
     }
 }
 
+/// Returns the message account keys followed by the addresses loaded from address maps, which
+/// is the list that instruction account indexes resolve against
+fn expand_account_keys(message: &Message, loaded_addresses: &MappedAddresses) -> Vec<Pubkey> {
+    message
+        .account_keys
+        .iter()
+        .chain(&loaded_addresses.writable)
+        .chain(&loaded_addresses.readonly)
+        .cloned()
+        .collect()
+}
+
 /// A duplicate representation of a CompiledInstruction for pretty JSON serialization
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -82,6 +98,16 @@ impl From<&CompiledInstruction> for UiCompiledInstruction {
     }
 }
 
+impl UiCompiledInstruction {
+    pub fn decode(&self) -> Option<CompiledInstruction> {
+        Some(CompiledInstruction {
+            program_id_index: self.program_id_index,
+            accounts: self.accounts.clone(),
+            data: bs58::decode(&self.data).into_vec().ok()?,
+        })
+    }
+}
+
 /// A partially decoded CompiledInstruction that includes explicit account addresses
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -155,17 +181,44 @@ impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
     }
 }
 
+impl From<UiTransactionTokenBalance> for TransactionTokenBalance {
+    fn from(token_balance: UiTransactionTokenBalance) -> Self {
+        Self {
+            account_index: token_balance.account_index,
+            mint: token_balance.mint,
+            ui_token_amount: token_balance.ui_token_amount,
+            owner: token_balance.owner.unwrap_or_default(),
+        }
+    }
+}
+
 impl UiInnerInstructions {
-    fn parse(inner_instructions: InnerInstructions, message: &Message) -> Self {
+    fn parse(inner_instructions: InnerInstructions, account_keys: &[Pubkey]) -> Self {
         Self {
             index: inner_instructions.index,
             instructions: inner_instructions
                 .instructions
                 .iter()
-                .map(|ix| UiInstruction::parse(ix, message))
+                .map(|ix| UiInstruction::parse(ix, account_keys))
                 .collect(),
         }
     }
+
+    /// Returns `None` if any instruction was parsed, as the encoding of parsed instructions
+    /// can't be reversed
+    pub fn decode(self) -> Option<InnerInstructions> {
+        Some(InnerInstructions {
+            index: self.index,
+            instructions: self
+                .instructions
+                .iter()
+                .map(|ix| match ix {
+                    UiInstruction::Compiled(ix) => ix.decode(),
+                    UiInstruction::Parsed(_) => None,
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
 }
 
 impl From<InnerInstructions> for UiInnerInstructions {
@@ -198,6 +251,16 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Rewards>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: MappedAddresses,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_budget_exceeded: Option<ComputeBudgetExceeded>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_units_consumed: Option<u64>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub fee_details: Option<FeeDetails>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_accounts_data_size: Option<u64>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -212,6 +275,11 @@ impl Default for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            loaded_addresses: MappedAddresses::default(),
+            compute_budget_exceeded: None,
+            compute_units_consumed: None,
+            fee_details: None,
+            loaded_accounts_data_size: None,
         }
     }
 }
@@ -230,10 +298,122 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_budget_exceeded: Option<UiComputeBudgetExceeded>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_units_consumed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_details: Option<UiFeeDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_accounts_data_size: Option<u64>,
+}
+
+/// A duplicate representation of MappedAddresses, listing the accounts a transaction loaded from
+/// address maps
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl UiLoadedAddresses {
+    /// Legacy transactions don't load any addresses, so they omit the field entirely
+    fn from_mapped_addresses(loaded_addresses: &MappedAddresses) -> Option<Self> {
+        if loaded_addresses.writable.is_empty() && loaded_addresses.readonly.is_empty() {
+            return None;
+        }
+        Some(Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+        })
+    }
+
+    fn decode(&self) -> Option<MappedAddresses> {
+        let decode_pubkeys = |pubkeys: &[String]| -> Option<Vec<Pubkey>> {
+            pubkeys.iter().map(|pubkey| pubkey.parse().ok()).collect()
+        };
+        Some(MappedAddresses {
+            writable: decode_pubkeys(&self.writable)?,
+            readonly: decode_pubkeys(&self.readonly)?,
+        })
+    }
+}
+
+/// A duplicate representation of ComputeBudgetExceeded for pretty JSON serialization
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiComputeBudgetExceeded {
+    pub instruction_index: u8,
+    pub program_id: String,
+    pub units_consumed: Vec<u64>,
+    pub units_limit: u64,
+}
+
+impl From<ComputeBudgetExceeded> for UiComputeBudgetExceeded {
+    fn from(compute_budget_exceeded: ComputeBudgetExceeded) -> Self {
+        Self {
+            instruction_index: compute_budget_exceeded.instruction_index,
+            program_id: compute_budget_exceeded.program_id.to_string(),
+            units_consumed: compute_budget_exceeded.units_consumed,
+            units_limit: compute_budget_exceeded.units_limit,
+        }
+    }
+}
+
+impl UiComputeBudgetExceeded {
+    fn decode(self) -> Option<ComputeBudgetExceeded> {
+        Some(ComputeBudgetExceeded {
+            instruction_index: self.instruction_index,
+            program_id: self.program_id.parse().ok()?,
+            units_consumed: self.units_consumed,
+            units_limit: self.units_limit,
+        })
+    }
+}
+
+/// A duplicate representation of FeeDetails for pretty JSON serialization
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiFeeDetails {
+    pub signature_fee: u64,
+    pub precompile_signature_fee: u64,
+    pub prioritization_fee: u64,
+}
+
+impl From<FeeDetails> for UiFeeDetails {
+    fn from(fee_details: FeeDetails) -> Self {
+        Self {
+            signature_fee: fee_details.signature_fee,
+            precompile_signature_fee: fee_details.precompile_signature_fee,
+            prioritization_fee: fee_details.prioritization_fee,
+        }
+    }
+}
+
+impl From<UiFeeDetails> for FeeDetails {
+    fn from(fee_details: UiFeeDetails) -> Self {
+        Self {
+            signature_fee: fee_details.signature_fee,
+            precompile_signature_fee: fee_details.precompile_signature_fee,
+            prioritization_fee: fee_details.prioritization_fee,
+        }
+    }
 }
 
 impl UiTransactionStatusMeta {
     fn parse(meta: TransactionStatusMeta, message: &Message) -> Self {
+        let account_keys = expand_account_keys(message, &meta.loaded_addresses);
         Self {
             err: meta.status.clone().err(),
             status: meta.status,
@@ -242,7 +422,7 @@ impl UiTransactionStatusMeta {
             post_balances: meta.post_balances,
             inner_instructions: meta.inner_instructions.map(|ixs| {
                 ixs.into_iter()
-                    .map(|ix| UiInnerInstructions::parse(ix, message))
+                    .map(|ix| UiInnerInstructions::parse(ix, &account_keys))
                     .collect()
             }),
             log_messages: meta.log_messages,
@@ -253,8 +433,50 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            loaded_addresses: UiLoadedAddresses::from_mapped_addresses(&meta.loaded_addresses),
+            compute_budget_exceeded: meta.compute_budget_exceeded.map(Into::into),
+            compute_units_consumed: meta.compute_units_consumed,
+            fee_details: meta.fee_details.map(Into::into),
+            loaded_accounts_data_size: meta.loaded_accounts_data_size,
         }
     }
+
+    /// Reverses the encoding of a status meta, which is only possible when its inner
+    /// instructions weren't parsed
+    pub fn decode(self) -> Option<TransactionStatusMeta> {
+        let decode_token_balances = |balances: Option<Vec<UiTransactionTokenBalance>>| {
+            balances.map(|balances| balances.into_iter().map(Into::into).collect())
+        };
+        Some(TransactionStatusMeta {
+            status: self.status,
+            fee: self.fee,
+            pre_balances: self.pre_balances,
+            post_balances: self.post_balances,
+            inner_instructions: match self.inner_instructions {
+                Some(ixs) => Some(
+                    ixs.into_iter()
+                        .map(UiInnerInstructions::decode)
+                        .collect::<Option<_>>()?,
+                ),
+                None => None,
+            },
+            log_messages: self.log_messages,
+            pre_token_balances: decode_token_balances(self.pre_token_balances),
+            post_token_balances: decode_token_balances(self.post_token_balances),
+            rewards: self.rewards,
+            loaded_addresses: match self.loaded_addresses {
+                Some(loaded_addresses) => loaded_addresses.decode()?,
+                None => MappedAddresses::default(),
+            },
+            compute_budget_exceeded: match self.compute_budget_exceeded {
+                Some(compute_budget_exceeded) => Some(compute_budget_exceeded.decode()?),
+                None => None,
+            },
+            compute_units_consumed: self.compute_units_consumed,
+            fee_details: self.fee_details.map(Into::into),
+            loaded_accounts_data_size: self.loaded_accounts_data_size,
+        })
+    }
 }
 
 impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
@@ -276,6 +498,11 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            loaded_addresses: UiLoadedAddresses::from_mapped_addresses(&meta.loaded_addresses),
+            compute_budget_exceeded: meta.compute_budget_exceeded.map(Into::into),
+            compute_units_consumed: meta.compute_units_consumed,
+            fee_details: meta.fee_details.map(Into::into),
+            loaded_accounts_data_size: meta.loaded_accounts_data_size,
         }
     }
 }
@@ -440,6 +667,25 @@ pub struct EncodedConfirmedBlock {
     pub block_height: Option<u64>,
 }
 
+impl EncodedConfirmedBlock {
+    /// Reverses `ConfirmedBlock::encode()`, which is only possible for binary encodings
+    pub fn decode(self) -> Option<ConfirmedBlock> {
+        Some(ConfirmedBlock {
+            previous_blockhash: self.previous_blockhash,
+            blockhash: self.blockhash,
+            parent_slot: self.parent_slot,
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(EncodedTransactionWithStatusMeta::decode)
+                .collect::<Option<_>>()?,
+            rewards: self.rewards,
+            block_time: self.block_time,
+            block_height: self.block_height,
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UiConfirmedBlock {
@@ -570,10 +816,19 @@ pub struct TransactionWithStatusMeta {
 
 impl TransactionWithStatusMeta {
     fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
+        let loaded_addresses = self
+            .meta
+            .as_ref()
+            .map(|meta| meta.loaded_addresses.clone())
+            .unwrap_or_default();
         let message = self.transaction.message();
         let meta = self.meta.map(|meta| meta.encode(encoding, message));
         EncodedTransactionWithStatusMeta {
-            transaction: EncodedTransaction::encode(self.transaction, encoding),
+            transaction: EncodedTransaction::encode_with_loaded_addresses(
+                self.transaction,
+                encoding,
+                &loaded_addresses,
+            ),
             meta,
         }
     }
@@ -586,6 +841,19 @@ pub struct EncodedTransactionWithStatusMeta {
     pub meta: Option<UiTransactionStatusMeta>,
 }
 
+impl EncodedTransactionWithStatusMeta {
+    /// Returns `None` unless the transaction was encoded in a binary format
+    pub fn decode(self) -> Option<TransactionWithStatusMeta> {
+        Some(TransactionWithStatusMeta {
+            transaction: self.transaction.decode()?,
+            meta: match self.meta {
+                Some(meta) => Some(meta.decode()?),
+                None => None,
+            },
+        })
+    }
+}
+
 impl TransactionStatusMeta {
     fn encode(self, encoding: UiTransactionEncoding, message: &Message) -> UiTransactionStatusMeta {
         match encoding {
@@ -623,6 +891,17 @@ pub enum EncodedTransaction {
 
 impl EncodedTransaction {
     pub fn encode(transaction: Transaction, encoding: UiTransactionEncoding) -> Self {
+        Self::encode_with_loaded_addresses(transaction, encoding, &MappedAddresses::default())
+    }
+
+    /// Encode a transaction that loaded `loaded_addresses` from address maps. When parsing, the
+    /// loaded addresses are appended to the account keys so that every instruction account
+    /// index can be resolved.
+    pub fn encode_with_loaded_addresses(
+        transaction: Transaction,
+        encoding: UiTransactionEncoding,
+        loaded_addresses: &MappedAddresses,
+    ) -> Self {
         match encoding {
             UiTransactionEncoding::Binary => EncodedTransaction::LegacyBinary(
                 bs58::encode(bincode::serialize(&transaction).unwrap()).into_string(),
@@ -654,16 +933,17 @@ impl EncodedTransaction {
                             .collect(),
                     })
                 } else {
+                    let account_keys = expand_account_keys(&transaction.message, loaded_addresses);
+                    let mut parsed_accounts = parse_accounts(&transaction.message);
+                    parsed_accounts.extend(parse_loaded_accounts(loaded_addresses));
                     UiMessage::Parsed(UiParsedMessage {
-                        account_keys: parse_accounts(&transaction.message),
+                        account_keys: parsed_accounts,
                         recent_blockhash: transaction.message.recent_blockhash.to_string(),
                         instructions: transaction
                             .message
                             .instructions
                             .iter()
-                            .map(|instruction| {
-                                UiInstruction::parse(instruction, &transaction.message)
-                            })
+                            .map(|instruction| UiInstruction::parse(instruction, &account_keys))
                             .collect(),
                     })
                 };
@@ -715,7 +995,7 @@ pub struct TransactionByAddrInfo {
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {super::*, solana_sdk::hash::Hash};
 
     #[test]
     fn test_decode_invalid_transaction() {
@@ -731,6 +1011,172 @@ mod test {
         assert!(unsanitary_transaction.decode().is_none());
     }
 
+    #[test]
+    fn test_encode_with_loaded_addresses() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let loaded_addresses = MappedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, program_id],
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0, 2, 3],
+                data: vec![],
+            }],
+            ..Message::default()
+        };
+        let transaction_with_meta = TransactionWithStatusMeta {
+            transaction: Transaction::new_unsigned(message),
+            meta: Some(TransactionStatusMeta {
+                loaded_addresses: loaded_addresses.clone(),
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        let encoded = transaction_with_meta.encode(UiTransactionEncoding::JsonParsed);
+        assert_eq!(
+            encoded.meta.unwrap().loaded_addresses,
+            Some(UiLoadedAddresses {
+                writable: vec![loaded_addresses.writable[0].to_string()],
+                readonly: vec![loaded_addresses.readonly[0].to_string()],
+            })
+        );
+        let parsed_message = match encoded.transaction {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Parsed(parsed_message),
+                ..
+            }) => parsed_message,
+            _ => panic!("expected a parsed message"),
+        };
+        assert_eq!(parsed_message.account_keys.len(), 4);
+        assert!(parsed_message.account_keys[2].writable);
+        assert!(!parsed_message.account_keys[3].writable);
+        assert_eq!(
+            parsed_message.instructions[0],
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+                UiPartiallyDecodedInstruction {
+                    program_id: program_id.to_string(),
+                    accounts: vec![
+                        payer.to_string(),
+                        loaded_addresses.writable[0].to_string(),
+                        loaded_addresses.readonly[0].to_string(),
+                    ],
+                    data: String::new(),
+                }
+            ))
+        );
+
+        // Legacy transactions don't report any loaded addresses
+        let encoded = TransactionWithStatusMeta {
+            transaction: Transaction::default(),
+            meta: Some(TransactionStatusMeta::default()),
+        }
+        .encode(UiTransactionEncoding::Json);
+        assert_eq!(encoded.meta.unwrap().loaded_addresses, None);
+    }
+
+    #[test]
+    fn test_decode_encoded_block() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, program_id],
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0],
+                data: vec![1],
+            }],
+            ..Message::default()
+        };
+        let token_balance = TransactionTokenBalance {
+            account_index: 0,
+            mint: Pubkey::new_unique().to_string(),
+            ui_token_amount: solana_account_decoder::parse_token::token_amount_to_ui_amount(1, 2),
+            owner: payer.to_string(),
+        };
+        let block = ConfirmedBlock {
+            previous_blockhash: Hash::new_unique().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: 1,
+            transactions: vec![TransactionWithStatusMeta {
+                transaction: Transaction::new_unsigned(message),
+                meta: Some(TransactionStatusMeta {
+                    fee: 5_000,
+                    pre_balances: vec![10_000, 1],
+                    post_balances: vec![5_000, 1],
+                    inner_instructions: Some(vec![InnerInstructions {
+                        index: 0,
+                        instructions: vec![CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![3],
+                            data: vec![2],
+                        }],
+                    }]),
+                    log_messages: Some(vec!["Program log: 1".to_string()]),
+                    pre_token_balances: Some(vec![token_balance.clone()]),
+                    post_token_balances: Some(vec![token_balance]),
+                    loaded_addresses: MappedAddresses {
+                        writable: vec![Pubkey::new_unique()],
+                        readonly: vec![Pubkey::new_unique()],
+                    },
+                    compute_budget_exceeded: Some(ComputeBudgetExceeded {
+                        instruction_index: 0,
+                        program_id,
+                        units_consumed: vec![200_000],
+                        units_limit: 200_000,
+                    }),
+                    compute_units_consumed: Some(200_000),
+                    fee_details: Some(FeeDetails {
+                        signature_fee: 5_000,
+                        precompile_signature_fee: 0,
+                        prioritization_fee: 1_000,
+                    }),
+                    loaded_accounts_data_size: Some(1_000),
+                    ..TransactionStatusMeta::default()
+                }),
+            }],
+            rewards: vec![],
+            block_time: Some(1),
+            block_height: Some(1),
+        };
+
+        for encoding in [UiTransactionEncoding::Base64, UiTransactionEncoding::Base58] {
+            assert_eq!(block.clone().encode(encoding).decode(), Some(block.clone()));
+        }
+
+        // Parsed transactions and instructions can't be decoded
+        assert_eq!(
+            block
+                .clone()
+                .encode(UiTransactionEncoding::JsonParsed)
+                .decode(),
+            None
+        );
+        let mut encoded = block.encode(UiTransactionEncoding::Base64);
+        let meta = encoded.transactions[0].meta.as_mut().unwrap();
+        meta.inner_instructions.as_mut().unwrap()[0].instructions[0] = UiInstruction::Parsed(
+            UiParsedInstruction::PartiallyDecoded(UiPartiallyDecodedInstruction {
+                program_id: program_id.to_string(),
+                accounts: vec![],
+                data: String::new(),
+            }),
+        );
+        assert_eq!(encoded.decode(), None);
+    }
+
     #[test]
     fn test_satisfies_commitment() {
         let status = TransactionStatus {
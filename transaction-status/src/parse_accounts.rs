@@ -1,4 +1,4 @@
-use solana_sdk::message::Message;
+use solana_sdk::message::{MappedAddresses, Message};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +20,27 @@ pub fn parse_accounts(message: &Message) -> Vec<ParsedAccount> {
     accounts
 }
 
+/// Addresses loaded from address maps are never signers
+pub fn parse_loaded_accounts(loaded_addresses: &MappedAddresses) -> Vec<ParsedAccount> {
+    let writable = loaded_addresses
+        .writable
+        .iter()
+        .map(|pubkey| ParsedAccount {
+            pubkey: pubkey.to_string(),
+            writable: true,
+            signer: false,
+        });
+    let readonly = loaded_addresses
+        .readonly
+        .iter()
+        .map(|pubkey| ParsedAccount {
+            pubkey: pubkey.to_string(),
+            writable: false,
+            signer: false,
+        });
+    writable.chain(readonly).collect()
+}
+
 #[cfg(test)]
 mod test {
     use {super::*, solana_sdk::message::MessageHeader};
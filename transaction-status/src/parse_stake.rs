@@ -268,6 +268,19 @@ pub fn parse_stake(
                 }),
             })
         }
+        StakeInstruction::Redelegate => {
+            check_num_stake_accounts(&instruction.accounts, 5)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "newStakeAccount": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "voteAccount": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "stakeConfigAccount": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "stakeAuthority": account_keys[instruction.accounts[4] as usize].to_string(),
+                }),
+            })
+        }
     }
 }
 
@@ -412,6 +425,25 @@ mod test {
         );
         assert!(parse_stake(&message.instructions[2], &keys[0..2]).is_err());
 
+        // Compiled order: new stake account (signer, allocate + assign first), stake authority
+        // (signer), stake account, vote account, stake config
+        let instructions = instruction::redelegate(&keys[2], &keys[1], &keys[3], &keys[0]);
+        let message = Message::new(&instructions, None);
+        assert_eq!(
+            parse_stake(&message.instructions[2], &keys[0..5]).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": keys[2].to_string(),
+                    "newStakeAccount": keys[0].to_string(),
+                    "voteAccount": keys[3].to_string(),
+                    "stakeConfigAccount": keys[4].to_string(),
+                    "stakeAuthority": keys[1].to_string(),
+                }),
+            }
+        );
+        assert!(parse_stake(&message.instructions[2], &keys[0..4]).is_err());
+
         let instruction = instruction::withdraw(&keys[1], &keys[0], &keys[2], lamports, None);
         let message = Message::new(&[instruction], None);
         assert_eq!(
@@ -0,0 +1,84 @@
+//! Converts the results of criterion benches into the libtest JSON bench format read by
+//! `solana-upload-perf`, so they can be reported alongside the nightly benches.
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Recursively collects the `new` result directories of each benchmark under `dir`.
+fn find_results(dir: &Path, results: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Err(why) => panic!("couldn't read {}: {:?}", dir.display(), why),
+        Ok(entries) => entries,
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().unwrap() == "new" {
+            if path.join("benchmark.json").exists() && path.join("estimates.json").exists() {
+                results.push(path);
+            }
+        } else if path.file_name().unwrap() != "report" {
+            find_results(&path, results);
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Value {
+    let contents = match fs::read_to_string(path) {
+        Err(why) => panic!("couldn't read {}: {:?}", path.display(), why),
+        Ok(contents) => contents,
+    };
+    serde_json::from_str(&contents).unwrap()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let dirs = if args.is_empty() {
+        vec!["target/criterion".to_string()]
+    } else {
+        args
+    };
+
+    let mut results = Vec::new();
+    for dir in dirs.iter() {
+        find_results(Path::new(dir), &mut results);
+    }
+    results.sort();
+
+    for result in results {
+        let benchmark = read_json(&result.join("benchmark.json"));
+        let estimates = read_json(&result.join("estimates.json"));
+
+        let name = benchmark["full_id"].as_str().unwrap().replace('/', "_");
+        let median = estimates["median"]["point_estimate"].as_f64().unwrap();
+        let deviation = estimates["std_dev"]["point_estimate"].as_f64().unwrap();
+        println!(
+            "{}",
+            json!({
+                "type": "bench",
+                "name": name,
+                "median": median as i64,
+                "deviation": deviation as i64,
+            })
+        );
+
+        // Benches reporting their throughput in instructions are also reported in MIPS
+        if let Some(elements) = benchmark["throughput"]["Elements"].as_u64() {
+            if median > 0.0 {
+                let mips = elements as f64 * 1_000.0 / median;
+                println!(
+                    "{}",
+                    json!({
+                        "type": "bench",
+                        "name": format!("{}_mips", name),
+                        "median": mips as i64,
+                        "deviation": 0,
+                    })
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Account parsers loaded from dynamic libraries, parsing the accounts of the programs the
+//! account decoder doesn't know about in `jsonParsed` RPC responses
+
+use {
+    libloading::{Library, Symbol},
+    solana_account_decoder::parse_account_data::{register_account_parser, AccountParser},
+    solana_sdk::pubkey::Pubkey,
+    std::{error::Error, sync::Arc},
+};
+
+/// Loads the account parser of the dynamic library at `libpath` and registers it, returning the
+/// program whose accounts it parses. The library shall export a `C` function
+/// `_create_account_parser` returning a pointer to an `AccountParser` trait object. It stays
+/// loaded until the validator exits, as the RPC threads may parse accounts with it at any time.
+///
+/// # Safety
+///
+/// The library must be built with the same compiler and account decoder as the validator, and
+/// the pointer it returns must come from `Box::into_raw`.
+pub unsafe fn load_account_parser_plugin(libpath: &str) -> Result<Pubkey, Box<dyn Error>> {
+    type AccountParserConstructor = unsafe fn() -> *mut dyn AccountParser;
+    let lib = Library::new(libpath)?;
+    let constructor: Symbol<AccountParserConstructor> = lib.get(b"_create_account_parser")?;
+    let parser: Arc<dyn AccountParser> = Arc::from(Box::from_raw(constructor()));
+    let program_id = parser.program_id();
+    register_account_parser(parser)?;
+    std::mem::forget(lib);
+    Ok(program_id)
+}
@@ -5,23 +5,50 @@ use {
     jsonrpc_ipc_server::{RequestContext, ServerBuilder},
     jsonrpc_server_utils::tokio,
     log::*,
+    serde_derive::{Deserialize, Serialize},
     solana_core::{
-        consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
+        consensus::Tower,
+        dumped_slots::{DumpedSlot, DumpedSlots},
+        leader_slot_reports::{LeaderSlotReport, LeaderSlotReports},
+        replay_stage::{request_warp_slot, WarpSlotSender},
+        tower_storage::TowerStorage,
+        validator::ValidatorStartProgress,
     },
     solana_gossip::cluster_info::ClusterInfo,
+    solana_perf::thread_affinity::{self, PinnedThread},
+    solana_poh::poh_service::ManualTicks,
+    solana_rayon_threadlimit::thread_pools,
+    solana_rpc::slow_query_log::{SlowQuery, SlowQueryLog},
+    solana_runtime::{
+        accounts_hash_audit::{AccountsHashAudit, AccountsHashAuditStatus},
+        bank_forks::BankForks,
+        cost_tracker::BlockCostReport,
+    },
     solana_sdk::{
+        clock::{Slot, DEFAULT_MS_PER_SLOT},
         exit::Exit,
         signature::{read_keypair_file, Keypair, Signer},
     },
     std::{
+        fs,
         net::SocketAddr,
         path::{Path, PathBuf},
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
         thread::{self, Builder},
         time::{Duration, SystemTime},
     },
 };
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadPoolInfo {
+    pub name: String,
+    pub num_pools: usize,
+    pub num_threads: usize,
+    pub installs: u64,
+    pub busy_us: u64,
+}
+
 #[derive(Clone)]
 pub struct AdminRpcRequestMetadata {
     pub rpc_addr: Option<SocketAddr>,
@@ -30,7 +57,16 @@ pub struct AdminRpcRequestMetadata {
     pub validator_exit: Arc<RwLock<Exit>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub cluster_info: Arc<RwLock<Option<Arc<ClusterInfo>>>>,
+    pub bank_forks: Arc<RwLock<Option<Arc<RwLock<BankForks>>>>>,
     pub tower_storage: Arc<dyn TowerStorage>,
+    pub manual_ticks: Option<Arc<ManualTicks>>,
+    pub warp_slot_sender: Arc<Mutex<Option<WarpSlotSender>>>,
+    pub rpc_slow_query_log: Option<Arc<SlowQueryLog>>,
+    pub leader_slot_reports: Option<Arc<LeaderSlotReports>>,
+    pub dumped_slots: Option<Arc<DumpedSlots>>,
+    pub accounts_hash_audit: Option<Arc<AccountsHashAudit>>,
+    /// File the log filter set at runtime is saved to, so that it's restored on restart
+    pub log_filter_path: Option<PathBuf>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
@@ -44,8 +80,41 @@ pub trait AdminRpc {
     #[rpc(meta, name = "rpcAddress")]
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>>;
 
-    #[rpc(name = "setLogFilter")]
-    fn set_log_filter(&self, filter: String) -> Result<()>;
+    #[rpc(meta, name = "setLogFilter")]
+    fn set_log_filter(&self, meta: Self::Metadata, filter: String) -> Result<()>;
+
+    #[rpc(name = "setTransactionTracing")]
+    fn set_transaction_tracing(&self, enabled: bool) -> Result<()>;
+
+    #[rpc(meta, name = "slowQueries")]
+    fn slow_queries(&self, meta: Self::Metadata) -> Result<Vec<SlowQuery>>;
+
+    #[rpc(meta, name = "leaderSlotReports")]
+    fn leader_slot_reports(&self, meta: Self::Metadata) -> Result<Vec<LeaderSlotReport>>;
+
+    #[rpc(meta, name = "dumpedSlots")]
+    fn dumped_slots(&self, meta: Self::Metadata) -> Result<Vec<DumpedSlot>>;
+
+    #[rpc(meta, name = "blockCost")]
+    fn block_cost(&self, meta: Self::Metadata) -> Result<BlockCostReport>;
+
+    #[rpc(meta, name = "verifyAccountsHash")]
+    fn verify_accounts_hash(&self, meta: Self::Metadata) -> Result<AccountsHashAuditStatus>;
+
+    #[rpc(meta, name = "accountsHashAuditStatus")]
+    fn accounts_hash_audit_status(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<AccountsHashAuditStatus>>;
+
+    #[rpc(name = "threadAffinity")]
+    fn thread_affinity(&self) -> Result<Vec<PinnedThread>>;
+
+    #[rpc(name = "threadPools")]
+    fn thread_pools(&self) -> Result<Vec<ThreadPoolInfo>>;
+
+    #[rpc(name = "setThreadPoolSize")]
+    fn set_thread_pool_size(&self, name: String, num_threads: usize) -> Result<()>;
 
     #[rpc(meta, name = "startTime")]
     fn start_time(&self, meta: Self::Metadata) -> Result<SystemTime>;
@@ -61,6 +130,12 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "setIdentity")]
     fn set_identity(&self, meta: Self::Metadata, keypair_file: String) -> Result<()>;
+
+    #[rpc(meta, name = "advanceSlot")]
+    fn advance_slot(&self, meta: Self::Metadata, slots: Option<u64>) -> Result<Slot>;
+
+    #[rpc(meta, name = "warpSlot")]
+    fn warp_slot(&self, meta: Self::Metadata, slot: Slot) -> Result<Slot>;
 }
 
 pub struct AdminRpcImpl;
@@ -94,12 +169,111 @@ impl AdminRpc for AdminRpcImpl {
         Ok(meta.rpc_addr)
     }
 
-    fn set_log_filter(&self, filter: String) -> Result<()> {
+    fn set_log_filter(&self, meta: Self::Metadata, filter: String) -> Result<()> {
         debug!("set_log_filter admin rpc request received");
         solana_logger::setup_with(&filter);
+        if let Some(log_filter_path) = meta.log_filter_path {
+            fs::write(&log_filter_path, &filter).map_err(|err| {
+                jsonrpc_core::error::Error::invalid_params(format!(
+                    "Log filter set, but not saved to {:?}: {}",
+                    log_filter_path, err
+                ))
+            })?;
+        }
         Ok(())
     }
 
+    fn set_transaction_tracing(&self, enabled: bool) -> Result<()> {
+        debug!("set_transaction_tracing admin rpc request received");
+        solana_metrics::transaction_tracing::set_enabled(enabled);
+        Ok(())
+    }
+
+    fn slow_queries(&self, meta: Self::Metadata) -> Result<Vec<SlowQuery>> {
+        debug!("slow_queries admin rpc request received");
+        let slow_query_log = meta.rpc_slow_query_log.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "Validator was not started with --rpc-slow-query-threshold-ms",
+            )
+        })?;
+        Ok(slow_query_log.recent())
+    }
+
+    fn leader_slot_reports(&self, meta: Self::Metadata) -> Result<Vec<LeaderSlotReport>> {
+        debug!("leader_slot_reports admin rpc request received");
+        let leader_slot_reports = meta.leader_slot_reports.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "Validator is not recording leader slot reports",
+            )
+        })?;
+        Ok(leader_slot_reports.recent())
+    }
+
+    fn dumped_slots(&self, meta: Self::Metadata) -> Result<Vec<DumpedSlot>> {
+        debug!("dumped_slots admin rpc request received");
+        let dumped_slots = meta.dumped_slots.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Validator is not recording dumped slots")
+        })?;
+        Ok(dumped_slots.recent())
+    }
+
+    fn block_cost(&self, meta: Self::Metadata) -> Result<BlockCostReport> {
+        debug!("block_cost admin rpc request received");
+        let bank_forks = meta.bank_forks.read().unwrap().clone().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Retry once validator start up is complete")
+        })?;
+        let working_bank = bank_forks.read().unwrap().working_bank();
+        let block_cost_report = working_bank
+            .read_cost_tracker()
+            .unwrap()
+            .block_cost_report(working_bank.slot());
+        Ok(block_cost_report)
+    }
+
+    fn verify_accounts_hash(&self, meta: Self::Metadata) -> Result<AccountsHashAuditStatus> {
+        debug!("verify_accounts_hash admin rpc request received");
+        let accounts_hash_audit = meta.accounts_hash_audit.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Validator doesn't audit the accounts hash")
+        })?;
+        Ok(accounts_hash_audit.request())
+    }
+
+    fn accounts_hash_audit_status(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<AccountsHashAuditStatus>> {
+        debug!("accounts_hash_audit_status admin rpc request received");
+        let accounts_hash_audit = meta.accounts_hash_audit.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Validator doesn't audit the accounts hash")
+        })?;
+        Ok(accounts_hash_audit.status())
+    }
+
+    fn thread_affinity(&self) -> Result<Vec<PinnedThread>> {
+        debug!("thread_affinity admin rpc request received");
+        Ok(thread_affinity::pinned_threads())
+    }
+
+    fn thread_pools(&self) -> Result<Vec<ThreadPoolInfo>> {
+        debug!("thread_pools admin rpc request received");
+        Ok(thread_pools::thread_pool_stats()
+            .into_iter()
+            .map(|stats| ThreadPoolInfo {
+                name: stats.name.to_string(),
+                num_pools: stats.num_pools,
+                num_threads: stats.num_threads,
+                installs: stats.installs,
+                busy_us: stats.busy_us,
+            })
+            .collect())
+    }
+
+    fn set_thread_pool_size(&self, name: String, num_threads: usize) -> Result<()> {
+        debug!("set_thread_pool_size admin rpc request received");
+        thread_pools::set_thread_pool_size(&name, num_threads)
+            .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
+
     fn start_time(&self, meta: Self::Metadata) -> Result<SystemTime> {
         debug!("start_time admin rpc request received");
         Ok(meta.start_time)
@@ -167,6 +341,42 @@ impl AdminRpc for AdminRpcImpl {
             ))
         }
     }
+
+    fn advance_slot(&self, meta: Self::Metadata, slots: Option<u64>) -> Result<Slot> {
+        debug!("advance_slot request received");
+
+        let manual_ticks = meta.manual_ticks.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "Validator was not started with manual slot advancement",
+            )
+        })?;
+        let slots = slots.unwrap_or(1);
+        let timeout = Duration::from_millis(DEFAULT_MS_PER_SLOT.saturating_mul(slots + 10));
+        manual_ticks
+            .advance_and_wait(slots, timeout)
+            .ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params(
+                    "Retry once validator start up is complete",
+                )
+            })
+    }
+
+    fn warp_slot(&self, meta: Self::Metadata, slot: Slot) -> Result<Slot> {
+        debug!("warp_slot request received");
+
+        let warp_slot_sender = meta
+            .warp_slot_sender
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params(
+                    "Warping is only supported by solana-test-validator once start up is complete",
+                )
+            })?;
+        request_warp_slot(&warp_slot_sender, slot, Duration::from_secs(60))
+            .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
 }
 
 // Start the Admin RPC interface
@@ -212,6 +422,18 @@ pub fn run(ledger_path: &Path, metadata: AdminRpcRequestMetadata) {
         .unwrap();
 }
 
+/// File of the log filter set through the admin RPC service
+pub fn log_filter_path(ledger_path: &Path) -> PathBuf {
+    ledger_path.join("log-filter")
+}
+
+/// Log filter last set through the admin RPC service, if any
+pub fn load_log_filter(ledger_path: &Path) -> Option<String> {
+    let filter = fs::read_to_string(log_filter_path(ledger_path)).ok()?;
+    let filter = filter.trim();
+    (!filter.is_empty()).then(|| filter.to_string())
+}
+
 fn admin_rpc_path(ledger_path: &Path) -> PathBuf {
     #[cfg(target_family = "windows")]
     {
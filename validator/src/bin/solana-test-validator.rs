@@ -11,10 +11,12 @@ use {
     solana_client::rpc_client::RpcClient,
     solana_core::tower_storage::FileTowerStorage,
     solana_faucet::faucet::{run_local_faucet_with_port, FAUCET_PORT},
+    solana_poh::poh_service::ManualTicks,
     solana_rpc::rpc::JsonRpcConfig,
     solana_sdk::{
         account::AccountSharedData,
         clock::Slot,
+        compute_budget::ComputeBudget,
         epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH},
         native_token::sol_to_lamports,
         pubkey::Pubkey,
@@ -34,7 +36,7 @@ use {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         process::exit,
-        sync::{mpsc::channel, Arc, RwLock},
+        sync::{mpsc::channel, Arc, Mutex, RwLock},
         time::{Duration, SystemTime, UNIX_EPOCH},
     },
 };
@@ -172,6 +174,15 @@ fn main() {
                 .takes_value(false)
                 .help("Disable the just-in-time compiler and instead use the interpreter for BPF. Windows always disables JIT."),
         )
+        .arg(
+            Arg::with_name("accounts_in_memory")
+                .long("accounts-in-memory")
+                .takes_value(false)
+                .help(
+                    "Keep account storages in memory instead of files under the ledger directory. \
+                     Accounts written since the last snapshot are lost when the validator exits",
+                ),
+        )
         .arg(
             Arg::with_name("slots_per_epoch")
                 .long("slots-per-epoch")
@@ -247,6 +258,31 @@ fn main() {
                      If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("clone_program_with_accounts")
+                .long("clone-program-with-accounts")
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .validator(is_pubkey)
+                .multiple(true)
+                .requires("json_rpc_url")
+                .help(
+                    "Copy a program, its program data account if it's upgradeable, and all \
+                     the accounts it owns from the cluster referenced by the --url argument \
+                     into the genesis configuration. The RPC node must serve \
+                     getProgramAccounts for the program. \
+                     If the ledger already exists then this parameter is silently ignored",
+                ),
+        )
+        .arg(
+            Arg::with_name("manual_slot_advance")
+                .long("manual-slot-advance")
+                .takes_value(false)
+                .help(
+                    "Only advance slots when requested with \
+                     `solana-validator --ledger LEDGER advance-slot [SLOTS]`",
+                ),
+        )
         .arg(
             Arg::with_name("warp_slot")
                 .required(false)
@@ -282,6 +318,16 @@ fn main() {
                      If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("compute_budget_config")
+                .long("compute-budget-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .help(
+                    "Execute transactions with the compute budget in this TOML file. \
+                     Unset fields keep their default value",
+                ),
+        )
         .get_matches();
 
     let output = if matches.is_present("quiet") {
@@ -436,6 +482,10 @@ fn main() {
     let clone_accounts: HashSet<_> = pubkeys_of(&matches, "clone_account")
         .map(|v| v.into_iter().collect())
         .unwrap_or_default();
+    let clone_programs_with_accounts: HashSet<_> =
+        pubkeys_of(&matches, "clone_program_with_accounts")
+            .map(|v| v.into_iter().collect())
+            .unwrap_or_default();
 
     let warp_slot = if matches.is_present("warp_slot") {
         Some(match matches.value_of("warp_slot") {
@@ -495,6 +545,10 @@ fn main() {
         for (name, long) in &[
             ("bpf_program", "--bpf-program"),
             ("clone_account", "--clone"),
+            (
+                "clone_program_with_accounts",
+                "--clone-program-with-accounts",
+            ),
             ("mint_address", "--mint"),
             ("slots_per_epoch", "--slots-per-epoch"),
             ("faucet_sol", "--faucet-sol"),
@@ -514,8 +568,24 @@ fn main() {
     genesis.max_ledger_shreds = value_of(&matches, "limit_ledger_size");
 
     let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
+    let manual_ticks = matches
+        .is_present("manual_slot_advance")
+        .then(|| Arc::new(ManualTicks::default()));
+    if let Some(manual_ticks) = &manual_ticks {
+        genesis.manual_ticks(manual_ticks.clone());
+    }
+    if let Some(compute_budget_config) = matches.value_of("compute_budget_config") {
+        let compute_budget =
+            ComputeBudget::from_toml_file(compute_budget_config).unwrap_or_else(|err| {
+                println!("Error: {}", err);
+                exit(1);
+            });
+        genesis.compute_budget(compute_budget);
+    }
 
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_bank_forks = Arc::new(RwLock::new(None));
+    let admin_service_warp_slot_sender = Arc::new(Mutex::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -528,7 +598,15 @@ fn main() {
             validator_exit: genesis.validator_exit.clone(),
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
+            bank_forks: admin_service_bank_forks.clone(),
             tower_storage: tower_storage.clone(),
+            manual_ticks: manual_ticks.clone(),
+            warp_slot_sender: admin_service_warp_slot_sender.clone(),
+            rpc_slow_query_log: None,
+            leader_slot_reports: None,
+            dumped_slots: None,
+            accounts_hash_audit: None,
+            log_filter_path: None,
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -558,6 +636,7 @@ fn main() {
             ..JsonRpcConfig::default()
         })
         .bpf_jit(bpf_jit)
+        .accounts_in_memory(matches.is_present("accounts_in_memory"))
         .rpc_port(rpc_port)
         .add_programs_with_path(&programs);
 
@@ -570,6 +649,15 @@ fn main() {
         );
     }
 
+    if !clone_programs_with_accounts.is_empty() {
+        genesis.clone_programs_with_accounts(
+            clone_programs_with_accounts,
+            cluster_rpc_client
+                .as_ref()
+                .expect("bug: --url argument missing?"),
+        );
+    }
+
     if let Some(warp_slot) = warp_slot {
         genesis.warp_slot(warp_slot);
     }
@@ -603,6 +691,9 @@ fn main() {
     match genesis.start_with_mint_address(mint_address, socket_addr_space) {
         Ok(test_validator) => {
             *admin_service_cluster_info.write().unwrap() = Some(test_validator.cluster_info());
+            *admin_service_bank_forks.write().unwrap() = Some(test_validator.bank_forks());
+            *admin_service_warp_slot_sender.lock().unwrap() =
+                Some(test_validator.warp_slot_sender());
             if let Some(dashboard) = dashboard {
                 dashboard.run(Duration::from_millis(250));
             }
@@ -3,7 +3,10 @@ use {
     rand::{seq::SliceRandom, thread_rng, Rng},
     solana_client::rpc_client::RpcClient,
     solana_core::validator::{ValidatorConfig, ValidatorStartProgress},
-    solana_download_utils::{download_snapshot_archive, DownloadProgressRecord},
+    solana_download_utils::{
+        download_snapshot_archive, download_snapshot_archive_from_peers,
+        DownloadProgressCallbackOption, DownloadProgressRecord, DEFAULT_DOWNLOAD_CHUNK_SIZE,
+    },
     solana_genesis_utils::download_then_check_genesis_hash,
     solana_gossip::{
         cluster_info::{ClusterInfo, Node},
@@ -11,6 +14,7 @@ use {
         crds_value,
         gossip_service::GossipService,
     },
+    solana_net_utils::nat_pmp::{self, NatProtocol},
     solana_runtime::{
         snapshot_archive_info::SnapshotArchiveInfoGetter,
         snapshot_package::SnapshotType,
@@ -29,7 +33,7 @@ use {
     solana_streamer::socket::SocketAddrSpace,
     std::{
         collections::{HashMap, HashSet},
-        net::{SocketAddr, TcpListener, UdpSocket},
+        net::{Ipv4Addr, SocketAddr, TcpListener, UdpSocket},
         path::Path,
         process::exit,
         sync::{
@@ -49,6 +53,9 @@ pub struct RpcBootstrapConfig {
     pub max_genesis_archive_unpacked_size: u64,
     pub no_check_vote_account: bool,
     pub incremental_snapshot_fetch: bool,
+    /// Number of peers to download the snapshots from in parallel, when they are fetched
+    /// incrementally
+    pub snapshot_download_peers: usize,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -133,12 +140,11 @@ pub fn rpc_bootstrap(
     }
 }
 
-fn verify_reachable_ports(
-    node: &Node,
-    cluster_entrypoint: &ContactInfo,
-    validator_config: &ValidatorConfig,
+/// The UDP sockets of the node which must be reachable from the other nodes
+fn public_udp_sockets<'a>(
+    node: &'a Node,
     socket_addr_space: &SocketAddrSpace,
-) -> bool {
+) -> Vec<&'a UdpSocket> {
     let mut udp_sockets = vec![&node.sockets.gossip, &node.sockets.repair];
 
     if ContactInfo::is_valid_address(&node.info.serve_repair, socket_addr_space) {
@@ -161,40 +167,174 @@ fn verify_reachable_ports(
     if ContactInfo::is_valid_address(&node.info.tvu_forwards, socket_addr_space) {
         udp_sockets.extend(node.sockets.tvu_forwards.iter());
     }
+    udp_sockets
+}
 
-    let mut tcp_listeners = vec![];
+/// The addresses the RPC services will bind to, if they must be reachable from the other nodes
+fn public_rpc_bind_addrs(
+    node: &Node,
+    validator_config: &ValidatorConfig,
+    socket_addr_space: &SocketAddrSpace,
+) -> Vec<(&'static str, SocketAddr)> {
+    let mut rpc_bind_addrs = vec![];
     if let Some((rpc_addr, rpc_pubsub_addr)) = validator_config.rpc_addrs {
-        for (purpose, bind_addr, public_addr) in &[
+        for (purpose, bind_addr, public_addr) in [
             ("RPC", rpc_addr, &node.info.rpc),
             ("RPC pubsub", rpc_pubsub_addr, &node.info.rpc_pubsub),
         ] {
             if ContactInfo::is_valid_address(public_addr, socket_addr_space) {
-                tcp_listeners.push((
-                    bind_addr.port(),
-                    TcpListener::bind(bind_addr).unwrap_or_else(|err| {
-                        error!(
-                            "Unable to bind to tcp {:?} for {}: {}",
-                            bind_addr, purpose, err
-                        );
-                        exit(1);
-                    }),
-                ));
+                rpc_bind_addrs.push((purpose, bind_addr));
             }
         }
     }
+    rpc_bind_addrs
+}
+
+/// Binds the TCP ports of the node which must be reachable from the other nodes
+fn public_tcp_listeners(
+    node: &Node,
+    validator_config: &ValidatorConfig,
+    socket_addr_space: &SocketAddrSpace,
+) -> Vec<(u16, TcpListener)> {
+    let mut tcp_listeners = vec![];
+    for (purpose, bind_addr) in public_rpc_bind_addrs(node, validator_config, socket_addr_space) {
+        tcp_listeners.push((
+            bind_addr.port(),
+            TcpListener::bind(bind_addr).unwrap_or_else(|err| {
+                error!(
+                    "Unable to bind to tcp {:?} for {}: {}",
+                    bind_addr, purpose, err
+                );
+                exit(1);
+            }),
+        ));
+    }
 
     if let Some(ip_echo) = &node.sockets.ip_echo {
         let ip_echo = ip_echo.try_clone().expect("unable to clone tcp_listener");
         tcp_listeners.push((ip_echo.local_addr().unwrap().port(), ip_echo));
     }
+    tcp_listeners
+}
 
+fn verify_reachable_ports(
+    node: &Node,
+    cluster_entrypoint: &ContactInfo,
+    validator_config: &ValidatorConfig,
+    socket_addr_space: &SocketAddrSpace,
+) -> bool {
     solana_net_utils::verify_reachable_ports(
         &cluster_entrypoint.gossip,
-        tcp_listeners,
-        &udp_sockets,
+        public_tcp_listeners(node, validator_config, socket_addr_space),
+        &public_udp_sockets(node, socket_addr_space),
     )
 }
 
+/// Checks that all the public ports of the node are reachable from one of the entrypoints, and
+/// prints a report of the reachable and unreachable ports. Returns whether they all are.
+pub fn check_ports(
+    node: &Node,
+    cluster_entrypoints: &[ContactInfo],
+    validator_config: &ValidatorConfig,
+    socket_addr_space: &SocketAddrSpace,
+) -> bool {
+    if cluster_entrypoints.is_empty() {
+        eprintln!("Checking the ports requires an --entrypoint to connect back to them");
+        return false;
+    }
+    let mut order: Vec<_> = (0..cluster_entrypoints.len()).collect();
+    order.shuffle(&mut thread_rng());
+    for i in order {
+        let entrypoint = &cluster_entrypoints[i].gossip;
+        println!(
+            "Checking the ports of {} from {}",
+            node.info.gossip.ip(),
+            entrypoint
+        );
+        let report = solana_net_utils::check_reachable_ports(
+            entrypoint,
+            public_tcp_listeners(node, validator_config, socket_addr_space),
+            &public_udp_sockets(node, socket_addr_space),
+        );
+        print!("{}", report);
+        if report.all_reachable() {
+            println!("All the ports are reachable");
+            return true;
+        }
+    }
+    println!(
+        "Some ports are unreachable, check the firewall and the port forwarding of the NAT \
+         router, or use --request-nat-mappings if it supports NAT-PMP"
+    );
+    false
+}
+
+/// The public ports of the node, to map on the NAT gateway
+pub fn public_nat_ports(
+    node: &Node,
+    validator_config: &ValidatorConfig,
+    socket_addr_space: &SocketAddrSpace,
+) -> Vec<(NatProtocol, u16)> {
+    let mut ports: Vec<_> = public_udp_sockets(node, socket_addr_space)
+        .into_iter()
+        .map(|udp_socket| (NatProtocol::Udp, udp_socket.local_addr().unwrap().port()))
+        .chain(
+            public_rpc_bind_addrs(node, validator_config, socket_addr_space)
+                .into_iter()
+                .map(|(_purpose, bind_addr)| (NatProtocol::Tcp, bind_addr.port())),
+        )
+        .chain(
+            node.sockets
+                .ip_echo
+                .iter()
+                .map(|ip_echo| (NatProtocol::Tcp, ip_echo.local_addr().unwrap().port())),
+        )
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Requests NAT-PMP mappings from `gateway` for `ports`, and logs the mappings which were refused
+/// or don't keep the port number.  Returns the lifetime of the shortest mapping granted, after
+/// which they must be requested again, at least `MIN_NAT_MAPPING_LIFETIME_SECS`.
+pub fn request_nat_mappings(gateway: Ipv4Addr, ports: &[(NatProtocol, u16)]) -> Option<Duration> {
+    let mut lifetime_secs: Option<u32> = None;
+    for &(protocol, port) in ports {
+        match nat_pmp::request_nat_pmp_mapping(
+            gateway,
+            protocol,
+            port,
+            nat_pmp::DEFAULT_NAT_MAPPING_LIFETIME_SECS,
+        ) {
+            Ok(mapping) => {
+                if mapping.external_port != port {
+                    warn!(
+                        "NAT gateway {} mapped {}/{} to external port {}, the node advertises \
+                         {} and will be unreachable there",
+                        gateway, protocol, port, mapping.external_port, port
+                    );
+                } else {
+                    info!(
+                        "NAT gateway {} mapped {}/{} for {}s",
+                        gateway, protocol, port, mapping.lifetime_secs
+                    );
+                }
+                lifetime_secs = Some(
+                    lifetime_secs.map_or(mapping.lifetime_secs, |lifetime_secs| {
+                        lifetime_secs.min(mapping.lifetime_secs)
+                    }),
+                );
+            }
+            Err(err) => warn!("Unable to map {}/{}: {}", protocol, port, err),
+        }
+    }
+    lifetime_secs.map(|lifetime_secs| {
+        let lifetime_secs = lifetime_secs.max(nat_pmp::MIN_NAT_MAPPING_LIFETIME_SECS);
+        Duration::from_secs(u64::from(lifetime_secs))
+    })
+}
+
 fn is_trusted_validator(id: &Pubkey, trusted_validators: &Option<HashSet<Pubkey>>) -> bool {
     if let Some(trusted_validators) = trusted_validators {
         trusted_validators.contains(id)
@@ -795,11 +935,13 @@ mod with_incremental_snapshots {
 
     /// Struct to wrap the return value from get_rpc_node().  The `rpc_contact_info` is the peer to
     /// download from, and `snapshot_hash` is the (optional) full and (optional) incremental
-    /// snapshots to download.
+    /// snapshots to download.  The `snapshot_peers` are the other peers offering the same
+    /// snapshots.
     #[derive(Debug)]
     struct GetRpcNodeResult {
         rpc_contact_info: ContactInfo,
         snapshot_hash: Option<SnapshotHash>,
+        snapshot_peers: Vec<ContactInfo>,
     }
 
     /// Struct to wrap the peers & snapshot hashes together.
@@ -862,6 +1004,8 @@ mod with_incremental_snapshots {
                 validator_config,
                 &mut blacklisted_rpc_nodes,
                 &bootstrap_config,
+                snapshot_archives_dir,
+                maximum_local_snapshot_age,
             );
             if rpc_node_details.is_none() {
                 return;
@@ -869,6 +1013,7 @@ mod with_incremental_snapshots {
             let GetRpcNodeResult {
                 rpc_contact_info,
                 snapshot_hash,
+                snapshot_peers,
             } = rpc_node_details.unwrap();
 
             info!(
@@ -939,6 +1084,7 @@ mod with_incremental_snapshots {
                     &mut download_abort_count,
                     snapshot_hash,
                     &rpc_contact_info,
+                    &snapshot_peers,
                 )
             })
             .map(|_| {
@@ -999,6 +1145,10 @@ mod with_incremental_snapshots {
     /// 2. have the highest full snapshot slot
     /// 3. have the highest incremental snapshot slot
     ///
+    /// Peers with an incremental snapshot based on a full snapshot we already have are preferred,
+    /// as long as their incremental snapshot is recent enough, so that only the incremental
+    /// snapshot needs to be downloaded.
+    ///
     /// NOTE: If the node has configured a full snapshot interval that is non-standard, it is
     /// possible that there are no compatible snapshot hashes available.  At that time, a node may
     /// (1) try again, (2) change its full snapshot interval back to a standard/default value, or
@@ -1010,6 +1160,8 @@ mod with_incremental_snapshots {
         validator_config: &ValidatorConfig,
         blacklisted_rpc_nodes: &mut HashSet<Pubkey>,
         bootstrap_config: &RpcBootstrapConfig,
+        snapshot_archives_dir: &Path,
+        maximum_local_snapshot_age: Slot,
     ) -> Option<GetRpcNodeResult> {
         let mut blacklist_timeout = Instant::now();
         let mut newer_cluster_snapshot_timeout = None;
@@ -1041,15 +1193,23 @@ mod with_incremental_snapshots {
                     return Some(GetRpcNodeResult {
                         rpc_contact_info: random_peer.clone(),
                         snapshot_hash: None,
+                        snapshot_peers: vec![],
                     });
                 }
             }
 
+            let local_full_snapshot_hashes: HashSet<_> =
+                snapshot_utils::get_full_snapshot_archives(snapshot_archives_dir)
+                    .into_iter()
+                    .map(|snapshot_archive| (snapshot_archive.slot(), *snapshot_archive.hash()))
+                    .collect();
             let peer_snapshot_hashes = get_peer_snapshot_hashes(
                 cluster_info,
                 validator_config,
                 bootstrap_config,
                 &rpc_peers,
+                &local_full_snapshot_hashes,
+                maximum_local_snapshot_age,
             );
 
             if peer_snapshot_hashes.is_empty() {
@@ -1091,9 +1251,20 @@ mod with_incremental_snapshots {
                     rpc_peers,
                 );
 
+                let mut snapshot_peers: Vec<_> = peer_snapshot_hashes
+                    .into_iter()
+                    .filter(|peer_snapshot_hash| {
+                        peer_snapshot_hash.snapshot_hash == final_snapshot_hash
+                            && peer_snapshot_hash.rpc_contact_info.id != final_rpc_contact_info.id
+                    })
+                    .map(|peer_snapshot_hash| peer_snapshot_hash.rpc_contact_info)
+                    .collect();
+                snapshot_peers.shuffle(&mut thread_rng());
+
                 return Some(GetRpcNodeResult {
                     rpc_contact_info: final_rpc_contact_info,
                     snapshot_hash: Some(final_snapshot_hash),
+                    snapshot_peers,
                 });
             }
         }
@@ -1105,11 +1276,16 @@ mod with_incremental_snapshots {
     /// 1. have a full snapshot slot that is a multiple of our full snapshot interval
     /// 2. have the highest full snapshot slot
     /// 3. have the highest incremental snapshot slot
+    ///
+    /// unless some peers have a recent enough incremental snapshot based on one of the local full
+    /// snapshots, in which case only those peers are considered.
     fn get_peer_snapshot_hashes(
         cluster_info: &ClusterInfo,
         validator_config: &ValidatorConfig,
         bootstrap_config: &RpcBootstrapConfig,
         rpc_peers: &[ContactInfo],
+        local_full_snapshot_hashes: &HashSet<(Slot, Hash)>,
+        maximum_local_snapshot_age: Slot,
     ) -> Vec<PeerSnapshotHash> {
         // Which strategy to use for getting the peer snapshot hashes?  The standard way is what's
         // described in the function's documentation.  However, if there are no trusted peers that
@@ -1166,6 +1342,11 @@ mod with_incremental_snapshots {
                 }
             }
 
+            retain_peer_snapshot_hashes_based_on_local_full_snapshots(
+                local_full_snapshot_hashes,
+                maximum_local_snapshot_age,
+                &mut peer_snapshot_hashes,
+            );
             retain_peer_snapshot_hashes_with_highest_full_snapshot_slot(&mut peer_snapshot_hashes);
             retain_peer_snapshot_hashes_with_highest_incremental_snapshot_slot(
                 &mut peer_snapshot_hashes,
@@ -1254,9 +1435,10 @@ mod with_incremental_snapshots {
         trusted_snapshot_hashes
     }
 
-    /// Get trusted snapshot hashes from all the eligible peers.  This fn will get only one
-    /// snapshot hash per peer (the one with the highest slot).  This may be just a full snapshot
-    /// hash, or a combo full (i.e. base) snapshot hash and incremental snapshot hash.
+    /// Get trusted snapshot hashes from all the eligible peers.  This fn will get the snapshot
+    /// hash with the highest slot of each peer.  This may be just a full snapshot hash, or a combo
+    /// full (i.e. base) snapshot hash and incremental snapshot hash.  If the incremental snapshots
+    /// of a peer are based on an older full snapshot than its highest one, both are returned.
     fn get_trusted_peer_snapshot_hashes(
         cluster_info: &ClusterInfo,
         validator_config: &ValidatorConfig,
@@ -1272,24 +1454,39 @@ mod with_incremental_snapshots {
                 continue;
             }
 
-            let mut highest_snapshot_hash =
+            let incremental_snapshot_hash =
                 get_highest_incremental_snapshot_hash_for_peer(cluster_info, &rpc_peer.id);
 
             // Get this peer's highest (full) snapshot hash.  We need to get these snapshot hashes
             // (instead of just the IncrementalSnapshotHashes) in case the peer is either (1) not
             // taking incremental snapshots, or (2) if the last snapshot taken was a full snapshot,
             // which would get pushed to CRDS here (i.e. `crds_value::SnapshotHashes`) first.
-            let snapshot_hash = get_highest_full_snapshot_hash_for_peer(cluster_info, &rpc_peer.id);
-            if snapshot_hash > highest_snapshot_hash {
-                highest_snapshot_hash = snapshot_hash;
-            }
+            // In the latter case, the incremental snapshots on the previous full snapshot are
+            // kept too, in case that full snapshot is one we already have.
+            let full_snapshot_hash =
+                get_highest_full_snapshot_hash_for_peer(cluster_info, &rpc_peer.id);
+            let snapshot_hashes = match (incremental_snapshot_hash, full_snapshot_hash) {
+                (Some(incremental_snapshot_hash), Some(full_snapshot_hash))
+                    if full_snapshot_hash > incremental_snapshot_hash =>
+                {
+                    let mut snapshot_hashes = vec![full_snapshot_hash];
+                    if incremental_snapshot_hash.incr.is_some() {
+                        snapshot_hashes.push(incremental_snapshot_hash);
+                    }
+                    snapshot_hashes
+                }
+                (incremental_snapshot_hash, full_snapshot_hash) => incremental_snapshot_hash
+                    .or(full_snapshot_hash)
+                    .into_iter()
+                    .collect(),
+            };
 
-            if let Some(snapshot_hash) = highest_snapshot_hash {
-                peer_snapshot_hashes.push(PeerSnapshotHash {
+            peer_snapshot_hashes.extend(snapshot_hashes.into_iter().map(|snapshot_hash| {
+                PeerSnapshotHash {
                     rpc_contact_info: rpc_peer.clone(),
                     snapshot_hash,
-                });
-            };
+                }
+            }));
         }
 
         trace!("peer snapshot hashes: {:?}", &peer_snapshot_hashes);
@@ -1339,6 +1536,41 @@ mod with_incremental_snapshots {
         );
     }
 
+    /// Retain the peer snapshot hashes with an incremental snapshot based on one of the local full
+    /// snapshots, if any of them is no more than `maximum_local_snapshot_age` slots older than
+    /// the highest peer snapshot.  Downloading only the incremental snapshot is then enough.
+    fn retain_peer_snapshot_hashes_based_on_local_full_snapshots(
+        local_full_snapshot_hashes: &HashSet<(Slot, Hash)>,
+        maximum_local_snapshot_age: Slot,
+        peer_snapshot_hashes: &mut Vec<PeerSnapshotHash>,
+    ) {
+        let highest_snapshot_slot = peer_snapshot_hashes
+            .iter()
+            .map(|peer_snapshot_hash| {
+                let SnapshotHash { full, incr } = peer_snapshot_hash.snapshot_hash;
+                incr.unwrap_or(full).0
+            })
+            .max()
+            .unwrap_or_default();
+        let is_based_on_local_full_snapshot = |peer_snapshot_hash: &PeerSnapshotHash| {
+            let SnapshotHash { full, incr } = peer_snapshot_hash.snapshot_hash;
+            local_full_snapshot_hashes.contains(&full)
+                && incr.unwrap_or(full).0
+                    >= highest_snapshot_slot.saturating_sub(maximum_local_snapshot_age)
+        };
+        if peer_snapshot_hashes
+            .iter()
+            .any(is_based_on_local_full_snapshot)
+        {
+            peer_snapshot_hashes.retain(is_based_on_local_full_snapshot);
+        }
+
+        trace!(
+            "retain peer snapshot hashes based on local full snapshots: {:?}",
+            &peer_snapshot_hashes
+        );
+    }
+
     /// Retain the peer snapshot hashes with the highest full snapshot slot
     fn retain_peer_snapshot_hashes_with_highest_full_snapshot_slot(
         peer_snapshot_hashes: &mut Vec<PeerSnapshotHash>,
@@ -1427,6 +1659,7 @@ mod with_incremental_snapshots {
         download_abort_count: &mut u64,
         snapshot_hash: Option<SnapshotHash>,
         rpc_contact_info: &ContactInfo,
+        snapshot_peers: &[ContactInfo],
     ) -> Result<(), String> {
         if snapshot_hash.is_none() {
             return Ok(());
@@ -1469,6 +1702,7 @@ mod with_incremental_snapshots {
                 maximum_snapshot_download_abort,
                 download_abort_count,
                 rpc_contact_info,
+                snapshot_peers,
                 full_snapshot_hash,
                 SnapshotType::FullSnapshot,
             )?;
@@ -1499,6 +1733,7 @@ mod with_incremental_snapshots {
                     maximum_snapshot_download_abort,
                     download_abort_count,
                     rpc_contact_info,
+                    snapshot_peers,
                     incremental_snapshot_hash,
                     SnapshotType::IncrementalSnapshot(full_snapshot_hash.0),
                 )?;
@@ -1508,7 +1743,8 @@ mod with_incremental_snapshots {
         Ok(())
     }
 
-    /// Download a snapshot
+    /// Download a snapshot, from `rpc_contact_info` and up to `snapshot_download_peers - 1` of the
+    /// `snapshot_peers` in parallel
     #[allow(clippy::too_many_arguments)]
     fn download_snapshot(
         snapshot_archives_dir: &Path,
@@ -1520,6 +1756,7 @@ mod with_incremental_snapshots {
         maximum_snapshot_download_abort: u64,
         download_abort_count: &mut u64,
         rpc_contact_info: &ContactInfo,
+        snapshot_peers: &[ContactInfo],
         desired_snapshot_hash: (Slot, Hash),
         snapshot_type: SnapshotType,
     ) -> Result<(), String> {
@@ -1541,15 +1778,8 @@ mod with_incremental_snapshots {
             slot: desired_snapshot_hash.0,
             rpc_addr: rpc_contact_info.rpc,
         };
-        download_snapshot_archive(
-            &rpc_contact_info.rpc,
-            snapshot_archives_dir,
-            desired_snapshot_hash,
-            snapshot_type,
-            maximum_full_snapshot_archives_to_retain,
-            maximum_incremental_snapshot_archives_to_retain,
-            use_progress_bar,
-            &mut Some(Box::new(|download_progress: &DownloadProgressRecord| {
+        let mut progress_notify_callback: DownloadProgressCallbackOption = Some(Box::new(
+            |download_progress: &DownloadProgressRecord| {
                 debug!("Download progress: {:?}", download_progress);
                 if download_progress.last_throughput < minimal_snapshot_download_speed
                     && download_progress.notification_count <= 1
@@ -1580,7 +1810,37 @@ mod with_incremental_snapshots {
                 } else {
                     true
                 }
-            })),
+            },
+        ));
+        if bootstrap_config.snapshot_download_peers > 1 && !snapshot_peers.is_empty() {
+            // A slow peer just downloads fewer chunks, the download is aborted if all the peers
+            // together are too slow
+            let rpc_addrs: Vec<_> = std::iter::once(rpc_contact_info)
+                .chain(snapshot_peers.iter())
+                .take(bootstrap_config.snapshot_download_peers)
+                .map(|contact_info| contact_info.rpc)
+                .collect();
+            return download_snapshot_archive_from_peers(
+                &rpc_addrs,
+                snapshot_archives_dir,
+                desired_snapshot_hash,
+                snapshot_type,
+                maximum_full_snapshot_archives_to_retain,
+                maximum_incremental_snapshot_archives_to_retain,
+                DEFAULT_DOWNLOAD_CHUNK_SIZE,
+                use_progress_bar,
+                &mut progress_notify_callback,
+            );
+        }
+        download_snapshot_archive(
+            &rpc_contact_info.rpc,
+            snapshot_archives_dir,
+            desired_snapshot_hash,
+            snapshot_type,
+            maximum_full_snapshot_archives_to_retain,
+            maximum_incremental_snapshot_archives_to_retain,
+            use_progress_bar,
+            &mut progress_notify_callback,
         )
     }
 
@@ -1916,5 +2176,59 @@ mod with_incremental_snapshots {
             retain_peer_snapshot_hashes_with_highest_incremental_snapshot_slot(&mut actual);
             assert_eq!(expected, actual);
         }
+
+        #[test]
+        fn test_retain_peer_snapshot_hashes_based_on_local_full_snapshots() {
+            let contact_info = default_contact_info_for_tests();
+            let local_full_snapshot_hashes: HashSet<_> =
+                vec![(100_000, Hash::default())].into_iter().collect();
+            let peer_snapshot_hashes = vec![
+                PeerSnapshotHash::new(contact_info.clone(), (200_000, Hash::default()), None),
+                PeerSnapshotHash::new(
+                    contact_info.clone(),
+                    (200_000, Hash::default()),
+                    Some((200_100, Hash::default())),
+                ),
+                PeerSnapshotHash::new(
+                    contact_info.clone(),
+                    (100_000, Hash::default()),
+                    Some((199_900, Hash::default())),
+                ),
+                PeerSnapshotHash::new(
+                    contact_info.clone(),
+                    (100_000, Hash::new_unique()),
+                    Some((200_000, Hash::default())),
+                ),
+                PeerSnapshotHash::new(
+                    contact_info.clone(),
+                    (100_000, Hash::default()),
+                    Some((150_000, Hash::default())),
+                ),
+            ];
+
+            // Only the incremental snapshot based on the local full snapshot which is recent
+            // enough is kept
+            let expected = vec![PeerSnapshotHash::new(
+                contact_info,
+                (100_000, Hash::default()),
+                Some((199_900, Hash::default())),
+            )];
+            let mut actual = peer_snapshot_hashes.clone();
+            retain_peer_snapshot_hashes_based_on_local_full_snapshots(
+                &local_full_snapshot_hashes,
+                500,
+                &mut actual,
+            );
+            assert_eq!(expected, actual);
+
+            // None of them is recent enough, so all the peer snapshot hashes are kept
+            let mut actual = peer_snapshot_hashes.clone();
+            retain_peer_snapshot_hashes_based_on_local_full_snapshots(
+                &local_full_snapshot_hashes,
+                100,
+                &mut actual,
+            );
+            assert_eq!(peer_snapshot_hashes, actual);
+        }
     }
 }
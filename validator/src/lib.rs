@@ -16,6 +16,7 @@ use {
     },
 };
 
+pub mod account_parser_plugin;
 pub mod admin_rpc_service;
 pub mod bootstrap;
 pub mod dashboard;
@@ -76,7 +77,11 @@ pub fn redirect_stderr_to_file(logfile: Option<String>) -> Option<JoinHandle<()>
             }
             #[cfg(not(unix))]
             {
-                println!("logrotate is not supported on this platform");
+                eprintln!(
+                    "Warning: logrotate is not supported on this platform, \
+                     {} will not be reopened on SIGUSR1",
+                    logfile
+                );
                 solana_logger::setup_file_with_default(&logfile, filter);
                 None
             }
@@ -7,11 +7,16 @@ use {
     console::style,
     log::*,
     rand::{seq::SliceRandom, thread_rng},
+    solana_bpf_loader_program::executable_cache::{
+        self, ExecutableCacheConfig, DEFAULT_EXECUTABLE_CACHE_CAPACITY,
+        DEFAULT_JIT_COMPILER_THREADS,
+    },
     solana_clap_utils::{
         input_parsers::{keypair_of, keypairs_of, pubkey_of, value_of},
         input_validators::{
             is_keypair, is_keypair_or_ask_keyword, is_niceness_adjustment_valid, is_parsable,
             is_pow2, is_pubkey, is_pubkey_or_keypair, is_slot, is_valid_percentage,
+            is_within_range,
         },
         keypair::SKIP_SEED_PHRASE_VALIDATION_ARG,
     },
@@ -20,30 +25,54 @@ use {
         rpc_request::MAX_MULTIPLE_ACCOUNTS,
     },
     solana_core::{
+        dumped_slots::{DumpedSlots, DEFAULT_DUMPED_SLOTS_CAPACITY},
+        external_block_producer::ExternalBlockProducerConfig,
+        leader_slot_reports::{LeaderSlotReports, DEFAULT_LEADER_SLOT_REPORTS_CAPACITY},
         ledger_cleanup_service::{DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS},
+        replay_stage::ProducedBlockVerification,
         tower_storage,
         tpu::DEFAULT_TPU_COALESCE_MS,
         validator::{is_snapshot_config_valid, Validator, ValidatorConfig, ValidatorStartProgress},
     },
     solana_gossip::{
-        cluster_info::{Node, VALIDATOR_PORT_RANGE},
+        cluster_info::{Node, DEFAULT_NUM_TPU_SOCKETS, VALIDATOR_PORT_RANGE},
         contact_info::ContactInfo,
     },
     solana_ledger::blockstore_db::BlockstoreRecoveryMode,
-    solana_metrics::datapoint_info,
-    solana_perf::recycler::enable_recycler_warming,
+    solana_logger::LogFormat,
+    solana_metrics::{datapoint_info, transaction_tracing},
+    solana_perf::{
+        recycler::enable_recycler_warming,
+        thread_affinity::{self, ThreadAffinityConfig},
+    },
     solana_poh::poh_service,
-    solana_replica_lib::accountsdb_repl_server::AccountsDbReplServiceConfig,
-    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
+    solana_rayon_threadlimit::thread_pools,
+    solana_replica_lib::{
+        accountsdb_repl_server::AccountsDbReplServiceConfig,
+        rpc_reads_server::RpcReadsServiceConfig,
+    },
+    solana_rpc::{
+        accounts_scan_throttle::{
+            DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT, DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS,
+        },
+        rpc::JsonRpcConfig,
+        rpc_compression::DEFAULT_RPC_COMPRESSION_MIN_BYTES,
+        rpc_pubsub_service::{PubSubConfig, SlowConsumerPolicy},
+        slow_query_log::{SlowQueryLog, DEFAULT_SLOW_QUERY_LOG_CAPACITY},
+        transaction_history_search::DEFAULT_TRANSACTION_HISTORY_SEARCH_THREADS,
+    },
     solana_runtime::{
         accounts_db::{
             AccountShrinkThreshold, AccountsDbConfig, DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE,
             DEFAULT_ACCOUNTS_SHRINK_RATIO,
         },
+        accounts_hash_audit::{AccountsHashAudit, AccountsHashAuditState, AccountsHashAuditStatus},
         accounts_index::{
             AccountIndex, AccountSecondaryIndexes, AccountSecondaryIndexesIncludeExclude,
             AccountsIndexConfig,
         },
+        append_vec::{AppendVecFormat, MAXIMUM_APPEND_VEC_FILE_SIZE},
+        bank::MAX_CACHED_EXECUTORS,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
         snapshot_config::SnapshotConfig,
         snapshot_utils::{
@@ -58,13 +87,16 @@ use {
         commitment_config::CommitmentConfig,
         hash::Hash,
         pubkey::Pubkey,
+        rpc_port::DEFAULT_RPC_PORT,
         signature::{Keypair, Signer},
     },
     solana_send_transaction_service::send_transaction_service,
-    solana_streamer::socket::SocketAddrSpace,
+    solana_streamer::{
+        packet::PACKETS_PER_BATCH, recv_backend::RecvBackendKind, socket::SocketAddrSpace,
+    },
     solana_validator::{
-        admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile, lock_ledger,
-        new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
+        account_parser_plugin, admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile,
+        lock_ledger, new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
     },
     std::{
         collections::{HashSet, VecDeque},
@@ -74,7 +106,7 @@ use {
         path::{Path, PathBuf},
         process::exit,
         str::FromStr,
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
         time::{Duration, SystemTime},
     },
 };
@@ -98,6 +130,8 @@ const INCLUDE_KEY: &str = "account-index-include-key";
 const DEFAULT_MIN_SNAPSHOT_DOWNLOAD_SPEED: u64 = 10485760;
 // The maximum times of snapshot download abort and retry
 const MAX_SNAPSHOT_DOWNLOAD_ABORT: u32 = 5;
+// The RPC threads of a --snapshot-only-mode validator, which only serves snapshots
+const DEFAULT_SNAPSHOT_ONLY_MODE_RPC_THREADS: usize = 2;
 
 fn monitor_validator(ledger_path: &Path) {
     let dashboard = Dashboard::new(ledger_path, None, None).unwrap_or_else(|err| {
@@ -352,6 +386,36 @@ fn wait_for_restart_window(
     Ok(())
 }
 
+fn accounts_hash_audit_status_to_string(status: &AccountsHashAuditStatus) -> String {
+    let slot = status
+        .slot
+        .map(|slot| format!(" of slot {}", slot))
+        .unwrap_or_default();
+    let kind = if status.scheduled {
+        "scheduled"
+    } else {
+        "requested"
+    };
+    match status.state {
+        AccountsHashAuditState::Requested => format!("Accounts hash audit {}", kind),
+        AccountsHashAuditState::Running => {
+            format!("Running the {} accounts hash audit{}", kind, slot)
+        }
+        AccountsHashAuditState::Passed => format!(
+            "Accounts hash audit{} passed in {}ms: {}",
+            slot,
+            status.elapsed_ms.unwrap_or_default(),
+            status.accounts_hash.as_deref().unwrap_or_default(),
+        ),
+        AccountsHashAuditState::Failed => format!(
+            "Accounts hash audit{} FAILED in {}ms: {}",
+            slot,
+            status.elapsed_ms.unwrap_or_default(),
+            status.error.as_deref().unwrap_or_default(),
+        ),
+    }
+}
+
 fn hash_validator(hash: String) -> Result<(), String> {
     Hash::from_str(&hash)
         .map(|_| ())
@@ -505,6 +569,21 @@ pub fn main() {
         PubSubConfig::default().queue_capacity_items.to_string();
     let default_rpc_pubsub_queue_capacity_bytes =
         PubSubConfig::default().queue_capacity_bytes.to_string();
+    let default_rpc_pubsub_connection_queue_capacity_items = PubSubConfig::default()
+        .connection_queue_capacity_items
+        .to_string();
+    let default_rpc_pubsub_connection_queue_capacity_bytes = PubSubConfig::default()
+        .connection_queue_capacity_bytes
+        .to_string();
+    let default_rpc_pubsub_max_logs_subscription_addresses = PubSubConfig::default()
+        .max_logs_subscription_addresses
+        .to_string();
+    let default_rpc_pubsub_max_logs_notification_messages = PubSubConfig::default()
+        .max_logs_notification_messages
+        .to_string();
+    let default_rpc_pubsub_max_logs_notifications_per_second = PubSubConfig::default()
+        .max_logs_notifications_per_second
+        .to_string();
     let default_send_transaction_service_config = send_transaction_service::Config::default();
     let default_rpc_send_transaction_retry_ms = default_send_transaction_service_config
         .retry_rate_ms
@@ -515,8 +594,18 @@ pub fn main() {
     let default_rpc_send_transaction_service_max_retries = default_send_transaction_service_config
         .service_max_retries
         .to_string();
+    let default_rpc_compression_min_bytes = DEFAULT_RPC_COMPRESSION_MIN_BYTES.to_string();
     let default_rpc_threads = num_cpus::get().to_string();
+    let default_rpc_transaction_history_search_threads =
+        DEFAULT_TRANSACTION_HISTORY_SEARCH_THREADS.to_string();
+    let default_rpc_max_concurrent_accounts_scans =
+        DEFAULT_MAX_CONCURRENT_ACCOUNTS_SCANS.to_string();
+    let default_rpc_accounts_scan_queue_timeout =
+        DEFAULT_ACCOUNTS_SCAN_QUEUE_TIMEOUT.as_secs().to_string();
     let default_accountsdb_repl_threads = num_cpus::get().to_string();
+    let default_rpc_grpc_threads = num_cpus::get().to_string();
+    let default_bpf_executable_cache_capacity = DEFAULT_EXECUTABLE_CACHE_CAPACITY.to_string();
+    let default_bpf_jit_compiler_threads = DEFAULT_JIT_COMPILER_THREADS.to_string();
     let default_maximum_full_snapshot_archives_to_retain =
         &DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN.to_string();
     let default_maximum_incremental_snapshot_archives_to_retain =
@@ -530,6 +619,9 @@ pub fn main() {
     let default_accounts_shrink_optimize_total_space =
         &DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE.to_string();
     let default_accounts_shrink_ratio = &DEFAULT_ACCOUNTS_SHRINK_RATIO.to_string();
+    let default_tpu_sockets = &DEFAULT_NUM_TPU_SOCKETS.to_string();
+    let default_tpu_coalesce_max_packets = &PACKETS_PER_BATCH.to_string();
+    let default_executor_cache_capacity = &MAX_CACHED_EXECUTORS.to_string();
 
     let matches = App::new(crate_name!()).about(crate_description!())
         .version(solana_version::version!())
@@ -639,6 +731,29 @@ pub fn main() {
                        The --no-voting flag is implicit when this flag is enabled \
                       "),
         )
+        .arg(
+            Arg::with_name("snapshot_only_mode")
+                .long("snapshot-only-mode")
+                .takes_value(false)
+                .conflicts_with_all(&[
+                    "restricted_repair_only_mode",
+                    "private_rpc",
+                    "enable_rpc_transaction_history",
+                    "obsolete_v1_7_rpc_api",
+                    "enable_accountsdb_repl",
+                    "rpc_grpc_port",
+                ])
+                .help("Only follow the cluster closely enough to produce fresh snapshots, \
+                       serving them and repair to other nodes. The TPU ports are closed \
+                       and not published, no transaction is sent nor PubSub served, only \
+                       the RPC methods required to serve snapshots are exposed, on the \
+                       default RPC port unless --rpc-port is set, and, unless set \
+                       otherwise, the ledger is kept to its minimum size and fewer RPC \
+                       threads are used. \
+                       \
+                       The --no-voting flag is implicit when this flag is enabled \
+                      "),
+        )
         .arg(
             Arg::with_name("dev_halt_at_slot")
                 .long("dev-halt-at-slot")
@@ -679,6 +794,38 @@ pub fn main() {
                 .takes_value(false)
                 .help("Do not perform TCP/UDP reachable port checks at start-up")
         )
+        .arg(
+            Arg::with_name("check_ports")
+                .long("check-ports")
+                .takes_value(false)
+                .conflicts_with("no_port_check")
+                .help("Check that the gossip, repair, TPU, TVU and RPC ports are reachable \
+                       from the entrypoint, print a report of the reachable and unreachable \
+                       ports, then exit, with an error if any port is unreachable")
+        )
+        .arg(
+            Arg::with_name("request_nat_mappings")
+                .long("request-nat-mappings")
+                .takes_value(false)
+                .help("Request mappings for the public ports of the validator from the NAT \
+                       gateway with NAT-PMP at start-up, and renew them while the validator runs. \
+                       Gateways which only support UPnP or PCP are not supported")
+        )
+        .arg(
+            Arg::with_name("nat_gateway")
+                .long("nat-gateway")
+                .value_name("IP_ADDRESS")
+                .takes_value(true)
+                .requires("request_nat_mappings")
+                .validator(|value| {
+                    value
+                        .parse::<std::net::Ipv4Addr>()
+                        .map(|_| ())
+                        .map_err(|err| format!("invalid IPv4 address {}: {}", value, err))
+                })
+                .help("IPv4 address of the NAT gateway to request the port mappings from \
+                       [default: the default gateway of the machine]")
+        )
         .arg(
             Arg::with_name("enable_rpc_transaction_history")
                 .long("enable-rpc-transaction-history")
@@ -710,6 +857,17 @@ pub fn main() {
                 .help("Include CPI inner instructions and logs in the \
                         historical transaction info stored"),
         )
+        .arg(
+            Arg::with_name("max_stored_log_messages_bytes")
+                .long("max-stored-log-messages-bytes")
+                .value_name("BYTES")
+                .requires("enable_cpi_and_log_storage")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Truncate the logs stored in the historical info of a transaction past \
+                       this many bytes, 0 to store no logs.  Truncated logs end with a \
+                       \"Log truncated\" message [default: unlimited]"),
+        )
         .arg(
             Arg::with_name("rpc_max_multiple_accounts")
                 .long("rpc-max-multiple-accounts")
@@ -939,6 +1097,17 @@ pub fn main() {
                 .help("The maximum number of times to abort and retry when encountering a \
                       slow snapshot download."),
         )
+        .arg(
+            Arg::with_name("snapshot_download_peers")
+                .long("snapshot-download-peers")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value("1")
+                .help("Download the snapshots from up to this many peers offering them in \
+                      parallel, over their RPC ports, in chunks that are resumed if the \
+                      download is interrupted. Only applies with --incremental-snapshots."),
+        )
         .arg(
             Arg::with_name("contact_debug_interval")
                 .long("contact-debug-interval")
@@ -1045,6 +1214,27 @@ pub fn main() {
                        Sending the SIGUSR1 signal to the validator process will cause it \
                        to re-open the log file"),
         )
+        .arg(
+            Arg::with_name("log_format")
+                .long("log-format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Format of the log records. With json, each record is a JSON object \
+                       on its own line, with the timestamp, level, target and fields \
+                       of the record"),
+        )
+        .arg(
+            Arg::with_name("transaction_tracing_endpoint")
+                .long("transaction-tracing-endpoint")
+                .value_name("URL")
+                .takes_value(true)
+                .help("Export a trace of the stages each transaction goes through to the \
+                       OpenTelemetry collector at this OTLP gRPC endpoint. Tracing can be \
+                       toggled on the running validator with the set-transaction-tracing \
+                       command. Requires a validator built with the `tracing` feature"),
+        )
         .arg(
             Arg::with_name("wait_for_supermajority")
                 .long("wait-for-supermajority")
@@ -1125,6 +1315,14 @@ pub fn main() {
                 .takes_value(false)
                 .help("Disable manual compaction of the ledger database (this is ignored).")
         )
+        .arg(
+            Arg::with_name("versioned_protobuf_storage")
+                .long("versioned-protobuf-storage")
+                .takes_value(false)
+                .help("Write transaction statuses and rewards to the ledger, and upload them to \
+                       BigTable, with the versioned protobuf framing. Releases predating the \
+                       framing can't read them back")
+        )
         .arg(
             Arg::with_name("rocksdb_compaction_interval")
                 .long("rocksdb-compaction-interval-slots")
@@ -1140,6 +1338,62 @@ pub fn main() {
                 .validator(is_parsable::<u64>)
                 .help("Milliseconds to wait in the TPU receiver for packet coalescing."),
         )
+        .arg(
+            Arg::with_name("tpu_coalesce_max_packets")
+                .long("tpu-coalesce-max-packets")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(default_tpu_coalesce_max_packets)
+                .validator(|s| is_within_range(s, 1, PACKETS_PER_BATCH))
+                .help("Stop coalescing TPU packets once a batch holds this many packets"),
+        )
+        .arg(
+            Arg::with_name("tpu_sockets")
+                .long("tpu-sockets")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(default_tpu_sockets)
+                .validator(|s| is_within_range(s, 1, 1024))
+                .help("Number of sockets bound to the TPU port with SO_REUSEPORT. \
+                       Each socket is serviced by its own receive thread."),
+        )
+        .arg(
+            Arg::with_name("tpu_recv_buffer_size")
+                .long("tpu-recv-buffer-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Kernel receive buffer size to request for each TPU socket. \
+                       Capped by the net.core.rmem_max sysctl. [default: system default]"),
+        )
+        .arg(
+            Arg::with_name("tpu_recv_backend")
+                .long("tpu-recv-backend")
+                .value_name("BACKEND")
+                .takes_value(true)
+                .possible_values(&["recvmmsg", "io_uring"])
+                .default_value("recvmmsg")
+                .help("How the TPU receivers read packets from their sockets. \
+                       io_uring requires a validator built with its io-uring feature, \
+                       recvmmsg is used otherwise"),
+        )
+        .arg(
+            Arg::with_name("tpu_autotune_recv_batch_size")
+                .long("tpu-autotune-recv-batch-size")
+                .takes_value(false)
+                .help("Tune the number of packets the TPU receivers read per system call \
+                       to the packet rate"),
+        )
+        .arg(
+            Arg::with_name("external_block_producer_socket")
+                .long("external-block-producer-socket")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Listen on this Unix domain socket for transaction bundles from a local \
+                       relayer. Bundles are only used during this node's leader slots, and each \
+                       is committed in order in a single entry only if all of its transactions \
+                       succeed."),
+        )
         .arg(
             Arg::with_name("rocksdb_max_compaction_jitter")
                 .long("rocksdb-max-compaction-jitter-slots")
@@ -1192,6 +1446,64 @@ pub fn main() {
                 .default_value("30")
                 .help("Number of seconds before timing out RPC requests backed by BigTable"),
         )
+        .arg(
+            Arg::with_name("rpc_transaction_history_search_threads")
+                .long("rpc-transaction-history-search-threads")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .default_value(&default_rpc_transaction_history_search_threads)
+                .help("Number of threads searching the transaction history for the \
+                       signature statuses of getSignatureStatuses"),
+        )
+        .arg(
+            Arg::with_name("rpc_max_transaction_history_searches_per_second")
+                .long("rpc-max-transaction-history-searches-per-second")
+                .value_name("NUMBER")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Maximum number of signature statuses searched in the transaction \
+                       history per second, beyond which getSignatureStatuses requests \
+                       searching it are rejected [default: unlimited]"),
+        )
+        .arg(
+            Arg::with_name("rpc_max_concurrent_accounts_scans")
+                .long("rpc-max-concurrent-accounts-scans")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .default_value(&default_rpc_max_concurrent_accounts_scans)
+                .help("Maximum number of accounts scans of getProgramAccounts and \
+                       getLargestAccounts running at once, the other scans wait in a queue"),
+        )
+        .arg(
+            Arg::with_name("rpc_accounts_scan_queue_timeout")
+                .long("rpc-accounts-scan-queue-timeout")
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .default_value(&default_rpc_accounts_scan_queue_timeout)
+                .help("Number of seconds an accounts scan waits in the queue before \
+                       its request is rejected"),
+        )
+        .arg(
+            Arg::with_name("rpc_max_accounts_scan_duration")
+                .long("rpc-max-accounts-scan-duration")
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Number of seconds after which an accounts scan is aborted and \
+                       its request fails [default: unlimited]"),
+        )
+        .arg(
+            Arg::with_name("rpc_max_accounts_scan_bytes")
+                .long("rpc-max-accounts-scan-bytes")
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Number of bytes read from the account storages after which an \
+                       accounts scan is aborted and its request fails [default: unlimited]"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_worker_threads")
                 .long("rpc-pubsub-worker-threads")
@@ -1278,6 +1590,37 @@ pub fn main() {
                 .help("The maximum total size of notifications that RPC PubSub will store \
                        across all connections."),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_connection_queue_capacity_items")
+                .long("rpc-pubsub-connection-queue-capacity-items")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_connection_queue_capacity_items)
+                .help("The maximum number of notifications that RPC PubSub will queue \
+                       for a single connection."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_connection_queue_capacity_bytes")
+                .long("rpc-pubsub-connection-queue-capacity-bytes")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_connection_queue_capacity_bytes)
+                .help("The maximum total size of notifications that RPC PubSub will queue \
+                       for a single connection."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_slow_consumer_policy")
+                .long("rpc-pubsub-slow-consumer-policy")
+                .takes_value(true)
+                .value_name("POLICY")
+                .possible_values(&["disconnect", "drop-oldest", "drop-newest"])
+                .default_value("disconnect")
+                .help("What RPC PubSub does once the queue of a connection is full: \
+                       disconnect the client, or drop either its oldest queued \
+                       notifications or the new ones"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_notification_threads")
                 .long("rpc-pubsub-notification-threads")
@@ -1287,6 +1630,38 @@ pub fn main() {
                 .help("The maximum number of threads that RPC PubSub will use \
                        for generating notifications."),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_logs_subscription_addresses")
+                .long("rpc-pubsub-max-logs-subscription-addresses")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_logs_subscription_addresses)
+                .help("The maximum number of addresses a logs subscription may \
+                       filter the transactions by"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_logs_notification_messages")
+                .long("rpc-pubsub-max-logs-notification-messages")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_logs_notification_messages)
+                .help("The maximum number of log messages of a transaction that RPC \
+                       PubSub notifies, the following ones are replaced by a marker. \
+                       Unlimited if 0"),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_logs_notifications_per_second")
+                .long("rpc-pubsub-max-logs-notifications-per-second")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_logs_notifications_per_second)
+                .help("The maximum number of notifications RPC PubSub sends to a logs \
+                       subscription per second, the dropped ones are counted in the \
+                       next notification sent. Unlimited if 0"),
+        )
         .arg(
             Arg::with_name("rpc_send_transaction_retry_ms")
                 .long("rpc-send-retry-ms")
@@ -1322,6 +1697,32 @@ pub fn main() {
                 .default_value(&default_rpc_send_transaction_service_max_retries)
                 .help("The maximum number of transaction broadcast retries, regardless of requested value."),
         )
+        .arg(
+            Arg::with_name("rpc_compression_min_bytes")
+                .long("rpc-compression-min-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_compression_min_bytes)
+                .help("Compress the JSON RPC responses of at least this many bytes for the \
+                       clients accepting a gzip or zstd Content-Encoding"),
+        )
+        .arg(
+            Arg::with_name("no_rpc_compression")
+                .long("no-rpc-compression")
+                .takes_value(false)
+                .help("Never compress JSON RPC responses"),
+        )
+        .arg(
+            Arg::with_name("rpc_slow_query_threshold_ms")
+                .long("rpc-slow-query-threshold-ms")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Log the JSON RPC calls taking at least this long, including their time \
+                       queued and the size of their response. The most recent ones can be \
+                       fetched with the slow-rpc-queries command"),
+        )
         .arg(
             Arg::with_name("rpc_scan_and_fix_roots")
                 .long("rpc-scan-and-fix-roots")
@@ -1364,6 +1765,25 @@ pub fn main() {
                 .hidden(true)
                 .help("Number of threads to use for servicing AccountsDb Replication requests"),
         )
+        .arg(
+            Arg::with_name("rpc_grpc_port")
+                .long("rpc-grpc-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .validator(solana_validator::port_validator)
+                .requires("rpc_port")
+                .help("Serve the account, block, transaction status and slot reads over gRPC \
+                       on this port, bound to the --rpc-bind-address"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_threads")
+                .long("rpc-grpc-threads")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .default_value(&default_rpc_grpc_threads)
+                .help("Number of threads to use for servicing gRPC requests"),
+        )
         .arg(
             Arg::with_name("accountsdb_plugin_config")
                 .long("accountsdb-plugin-config")
@@ -1373,6 +1793,15 @@ pub fn main() {
                 .hidden(true)
                 .help("Specify the configuration file for the AccountsDb plugin."),
         )
+        .arg(
+            Arg::with_name("account_parser_plugin")
+                .long("account-parser-plugin")
+                .value_name("LIBRARY")
+                .takes_value(true)
+                .multiple(true)
+                .help("Load an account parser from a dynamic library, parsing the accounts of \
+                       its program in jsonParsed RPC responses"),
+        )
         .arg(
             Arg::with_name("halt_on_trusted_validators_accounts_hash_mismatch")
                 .alias("halt-on-trusted-validators-accounts-hash-mismatch")
@@ -1440,6 +1869,27 @@ pub fn main() {
                 .takes_value(false)
                 .conflicts_with("no_bpf_jit")
         )
+        .arg(
+            Arg::with_name("bpf_executable_cache_capacity")
+                .long("bpf-executable-cache-capacity")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(&default_bpf_executable_cache_capacity)
+                .help("Number of verified BPF program executables shared by all the banks, \
+                       0 to disable the cache"),
+        )
+        .arg(
+            Arg::with_name("bpf_jit_compiler_threads")
+                .long("bpf-jit-compiler-threads")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(&default_bpf_jit_compiler_threads)
+                .help("Number of threads JIT compiling BPF programs in the background, \
+                       the programs run in the interpreter until they are compiled. \
+                       0 to compile the programs before their first run"),
+        )
         .arg(
             Arg::with_name("poh_pinned_cpu_core")
                 .hidden(true)
@@ -1456,6 +1906,44 @@ pub fn main() {
                 })
                 .help("EXPERIMENTAL: Specify which CPU core PoH is pinned to"),
         )
+        .arg(
+            Arg::with_name("thread_affinity")
+                .hidden(true)
+                .long("experimental-thread-affinity")
+                .takes_value(true)
+                .value_name("SPEC")
+                .validator(|s| {
+                    let config = ThreadAffinityConfig::from_str(&s)?;
+                    let max_index = core_affinity::get_core_ids().map(|cids| cids.len() - 1).unwrap_or(0);
+                    match config.max_core() {
+                        Some(core) if core > max_index => Err(format!(
+                            "core {} is out of the range [0, {}]",
+                            core, max_index
+                        )),
+                        _ => Ok(()),
+                    }
+                })
+                .help("EXPERIMENTAL: Pin the critical threads to CPU cores, by role. \
+                       SPEC is a comma separated list of ROLE=CORE or ROLE=FIRST-LAST entries, \
+                       such as poh=2,sigverify=4-7,banking=8-11. The threads of a role are \
+                       pinned to its cores in turn. Roles: poh, sigverify, banking. \
+                       The poh entry overrides --experimental-poh-pinned-cpu-core"),
+        )
+        .arg(
+            Arg::with_name("thread_pool_size")
+                .long("thread-pool-size")
+                .takes_value(true)
+                .multiple(true)
+                .value_name("NAME=THREADS")
+                .validator(|s| thread_pools::parse_thread_pool_size(&s).map(|_| ()))
+                .help("Number of threads of a thread pool, instead of its default size. \
+                       May be specified multiple times. Pools: replay, sigverify, \
+                       accounts-hash, accounts-db, accounts-clean, entry, shred, \
+                       sigverify-shreds, blockstore, blockstore-purge, window, retransmit, \
+                       gossip, gossip-consume, gossip-listen, epoch-boundary. \
+                       The pools can be resized at runtime with the set-thread-pool-size \
+                       subcommand"),
+        )
         .arg(
             Arg::with_name("poh_hashes_per_batch")
                 .hidden(true)
@@ -1497,6 +1985,16 @@ pub fn main() {
                 .long("no-accounts-db-caching")
                 .help("Disables accounts caching"),
         )
+        .arg(
+            Arg::with_name("executor_cache_capacity")
+                .long("executor-cache-capacity")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value(default_executor_cache_capacity)
+                .validator(|s| is_within_range(s, 1, 10_000))
+                .help("Number of compiled programs each bank keeps cached. \
+                       Larger caches avoid recompiling programs after bursts of deployments."),
+        )
         .arg(
             Arg::with_name("accounts_db_skip_shrink")
                 .long("accounts-db-skip-shrink")
@@ -1527,6 +2025,48 @@ pub fn main() {
                 .takes_value(true)
                 .help("Number of passes to calculate the hash of all accounts"),
         )
+        .arg(
+            Arg::with_name("accounts_db_append_vec_format")
+                .long("accounts-db-append-vec-format")
+                .value_name("FORMAT")
+                .possible_values(&["v1", "v2"])
+                .default_value("v1")
+                .takes_value(true)
+                .help("On-disk layout of newly created account storages. \
+                       v2 adds per-entry checksums verified at load and optional \
+                       compression of account data. Existing storages are read \
+                       in either format. Snapshot archives holding v2 storages \
+                       are marked with snapshot version 1.3.0 and can't be loaded \
+                       by validators that predate the v2 format."),
+        )
+        .arg(
+            Arg::with_name("accounts_db_compression_threshold")
+                .long("accounts-db-compression-threshold")
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Compress account data of at least this many bytes when \
+                       storing accounts in the v2 append vec format"),
+        )
+        .arg(
+            Arg::with_name("accounts_db_file_size")
+                .long("accounts-db-file-size")
+                .value_name("BYTES")
+                .validator(|s| is_within_range(s, 1, MAXIMUM_APPEND_VEC_FILE_SIZE))
+                .takes_value(true)
+                .help("Size preallocated for each new account storage file. \
+                       Larger files reduce file churn and fragmentation at the \
+                       cost of disk space [default: 4MiB]"),
+        )
+        .arg(
+            Arg::with_name("accounts_db_max_recycle_stores")
+                .long("accounts-db-max-recycle-stores")
+                .value_name("COUNT")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Number of dropped account storage files kept to be \
+                       reused instead of deleted [default: 1000]"),
+        )
         .arg(
             Arg::with_name("accounts_index_path")
                 .long("accounts-index-path")
@@ -1606,6 +2146,35 @@ pub fn main() {
                 .help("Allow contacting private ip addresses")
                 .hidden(true),
         )
+        .arg(
+            Arg::with_name("verify_produced_blocks")
+                .long("verify-produced-blocks")
+                .value_name("POLICY")
+                .takes_value(true)
+                .possible_values(&["warn", "abort"])
+                .help("Replay each block this validator produces before voting on it, and \
+                       compare the bank hash with the one banking stage produced. \
+                       On mismatch, either report it and vote on the replayed bank (warn), \
+                       or abort the validator (abort)"),
+        )
+        .arg(
+            Arg::with_name("accounts_hash_audit_interval")
+                .long("accounts-hash-audit-interval")
+                .value_name("HOURS")
+                .takes_value(true)
+                .validator(|s| is_within_range(s, 1, 24 * 365))
+                .help("Recalculate and verify the accounts hash of the root bank in the \
+                       background at this interval, for example 168 to audit it weekly. \
+                       An audit can also be requested with `solana-validator verify-accounts-hash` \
+                       [default: only audit on request]"),
+        )
+        .arg(
+            Arg::with_name("queue_conflicting_transactions")
+                .long("queue-conflicting-transactions")
+                .takes_value(false)
+                .help("When leader, execute transactions that conflict with an earlier \
+                       transaction of their batch after it, instead of retrying them later"),
+        )
         .arg(
             Arg::with_name("disable_epoch_boundary_optimization")
                 .long("disable-epoch-boundary-optimization")
@@ -1614,6 +2183,14 @@ pub fn main() {
                 optimize_epoch_boundary_updates feature switch if enabled.")
                 .hidden(true),
         )
+        .arg(
+            Arg::with_name("disable_duplicate_slot_dumping")
+                .long("disable-duplicate-slot-dumping")
+                .takes_value(false)
+                .help("Keep the local version of the slots the cluster confirmed another \
+                       version of, instead of purging them and repairing that version. \
+                       The slots are listed by the dumped-slots subcommand for recovery by hand"),
+        )
         .after_help("The default subcommand is run")
         .subcommand(
             SubCommand::with_name("exit")
@@ -1712,7 +2289,113 @@ pub fn main() {
                     .index(1)
                     .help("New filter using the same format as the RUST_LOG environment variable")
             )
-            .after_help("Note: the new filter only applies to the currently running validator instance")
+            .after_help("Note: the new filter is saved to the log-filter file of the ledger \
+                         directory and restored when the validator restarts unless RUST_LOG is \
+                         set, delete that file to go back to the default filter")
+        )
+        .subcommand(
+            SubCommand::with_name("set-transaction-tracing")
+            .about("Start or stop recording the validator's transaction traces")
+            .arg(
+                Arg::with_name("state")
+                    .takes_value(true)
+                    .index(1)
+                    .required(true)
+                    .possible_values(&["on", "off"])
+                    .help("Whether transaction traces are recorded")
+            )
+            .after_help("Note: traces are only exported by a validator started with \
+                         --transaction-tracing-endpoint")
+        )
+        .subcommand(
+            SubCommand::with_name("slow-rpc-queries")
+            .about("Display the most recent slow JSON RPC calls")
+            .after_help("Note: slow calls are only logged by a validator started with \
+                         --rpc-slow-query-threshold-ms")
+        )
+        .subcommand(
+            SubCommand::with_name("leader-slot-reports")
+            .about("Display how the most recent leader slots of the validator went")
+        )
+        .subcommand(
+            SubCommand::with_name("dumped-slots")
+            .about("Display the most recent slots the cluster confirmed another version of")
+        )
+        .subcommand(
+            SubCommand::with_name("block-cost")
+            .about("Display the cost of the working block, including the one in flight")
+        )
+        .subcommand(
+            SubCommand::with_name("verify-accounts-hash")
+            .about("Recalculate and verify the accounts hash of the latest root in the background")
+            .arg(
+                Arg::with_name("status")
+                    .long("status")
+                    .takes_value(false)
+                    .help("Only display the status of the last audit")
+            )
+            .arg(
+                Arg::with_name("no_wait")
+                    .long("no-wait")
+                    .takes_value(false)
+                    .help("Don't wait for the audit to finish")
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("thread-affinity")
+            .about("Display the CPU cores the critical threads of the validator are pinned to")
+        )
+        .subcommand(
+            SubCommand::with_name("thread-pools")
+            .about("Display the size and usage of the thread pools of the validator")
+        )
+        .subcommand(
+            SubCommand::with_name("set-thread-pool-size")
+            .about("Resize a thread pool of the validator")
+            .arg(
+                Arg::with_name("name")
+                    .takes_value(true)
+                    .index(1)
+                    .required(true)
+                    .possible_values(&thread_pools::THREAD_POOL_NAMES)
+                    .value_name("NAME")
+                    .help("Thread pool to resize")
+            )
+            .arg(
+                Arg::with_name("num_threads")
+                    .takes_value(true)
+                    .index(2)
+                    .required(true)
+                    .validator(is_parsable::<usize>)
+                    .value_name("THREADS")
+                    .help("Number of threads of the pool")
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("advance-slot")
+            .about("Advance a validator running with manual slot advancement")
+            .arg(
+                Arg::with_name("slots")
+                    .takes_value(true)
+                    .index(1)
+                    .validator(is_parsable::<u64>)
+                    .value_name("SLOTS")
+                    .default_value("1")
+                    .help("Number of slots to advance")
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("warp-slot")
+            .about("Warp a running solana-test-validator ahead to a future slot")
+            .arg(
+                Arg::with_name("slot")
+                    .takes_value(true)
+                    .index(1)
+                    .required(true)
+                    .validator(is_parsable::<Slot>)
+                    .value_name("SLOT")
+                    .help("Slot to warp to")
+            )
         )
         .subcommand(
             SubCommand::with_name("wait-for-restart-window")
@@ -1872,6 +2555,242 @@ pub fn main() {
                 });
             return;
         }
+        ("set-transaction-tracing", Some(subcommand_matches)) => {
+            let enabled = subcommand_matches.value_of("state") == Some("on");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.set_transaction_tracing(enabled).await })
+                .unwrap_or_else(|err| {
+                    println!("setTransactionTracing request failed: {}", err);
+                    exit(1);
+                });
+            return;
+        }
+        ("slow-rpc-queries", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let slow_queries = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.slow_queries().await })
+                .unwrap_or_else(|err| {
+                    println!("slowQueries request failed: {}", err);
+                    exit(1);
+                });
+            for query in slow_queries {
+                println!(
+                    "{} {}: queue {}us, processing {}us, {} bytes, params {}",
+                    query.timestamp,
+                    query.method,
+                    query.queue_us,
+                    query.processing_us,
+                    query.response_bytes,
+                    query.params,
+                );
+            }
+            return;
+        }
+        ("leader-slot-reports", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let leader_slot_reports = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.leader_slot_reports().await })
+                .unwrap_or_else(|err| {
+                    println!("leaderSlotReports request failed: {}", err);
+                    exit(1);
+                });
+            for report in leader_slot_reports {
+                println!(
+                    "slot {}: {} packets received, {} buffered, {} transactions executed, \
+                     dropped {} invalid, {} expired or processed, {} on full buffer, \
+                     retried {} on cost limit, {} retryable, {} compute units, \
+                     {} lamports in fees, first shred after {}",
+                    report.slot,
+                    report.packets_received,
+                    report.packets_buffered,
+                    report.transactions_executed,
+                    report.packets_dropped.invalid,
+                    report.packets_dropped.expired_or_processed,
+                    report.packets_dropped.buffer_full,
+                    report.packets_retried.cost_limit,
+                    report.packets_retried.retryable,
+                    report.compute_units,
+                    report.fees_earned,
+                    report
+                        .time_to_first_shred_us
+                        .map(|us| format!("{}us", us))
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+            }
+            return;
+        }
+        ("dumped-slots", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let dumped_slots = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.dumped_slots().await })
+                .unwrap_or_else(|err| {
+                    println!("dumpedSlots request failed: {}", err);
+                    exit(1);
+                });
+            for dumped_slot in dumped_slots {
+                println!(
+                    "{} slot {}: {:?}, local version {}, cluster version {}, {}",
+                    dumped_slot.timestamp,
+                    dumped_slot.slot,
+                    dumped_slot.reason,
+                    dumped_slot.dumped_hash.as_deref().unwrap_or("-"),
+                    dumped_slot.correct_hash,
+                    if dumped_slot.purged {
+                        "purged for repair"
+                    } else {
+                        "kept"
+                    },
+                );
+            }
+            return;
+        }
+        ("block-cost", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let report = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.block_cost().await })
+                .unwrap_or_else(|err| {
+                    println!("blockCost request failed: {}", err);
+                    exit(1);
+                });
+            println!(
+                "slot {}: {} of {} compute units, {} in flight, {} transactions, \
+                 costliest account {} with {} of {} compute units",
+                report.slot,
+                report.block_cost,
+                report.block_cost_limit,
+                report.in_flight_cost,
+                report.transaction_count,
+                report.costliest_account,
+                report.costliest_account_cost,
+                report.account_cost_limit,
+            );
+            return;
+        }
+        ("verify-accounts-hash", Some(subcommand_matches)) => {
+            let status_only = subcommand_matches.is_present("status");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let status = admin_rpc_service::runtime()
+                .block_on(async move {
+                    let admin_client = admin_client.await?;
+                    if status_only {
+                        admin_client.accounts_hash_audit_status().await
+                    } else {
+                        admin_client.verify_accounts_hash().await.map(Some)
+                    }
+                })
+                .unwrap_or_else(|err| {
+                    println!("verifyAccountsHash request failed: {}", err);
+                    exit(1);
+                });
+            let mut status = match status {
+                Some(status) => status,
+                None => {
+                    println!("The accounts hash wasn't audited yet");
+                    return;
+                }
+            };
+            println!("{}", accounts_hash_audit_status_to_string(&status));
+            if status_only || subcommand_matches.is_present("no_wait") {
+                return;
+            }
+            while !status.is_finished() {
+                std::thread::sleep(Duration::from_secs(10));
+                let admin_client = admin_rpc_service::connect(&ledger_path);
+                let next_status = admin_rpc_service::runtime()
+                    .block_on(async move { admin_client.await?.accounts_hash_audit_status().await })
+                    .unwrap_or_else(|err| {
+                        println!("accountsHashAuditStatus request failed: {}", err);
+                        exit(1);
+                    })
+                    .unwrap_or_else(|| status.clone());
+                if next_status != status {
+                    println!("{}", accounts_hash_audit_status_to_string(&next_status));
+                }
+                status = next_status;
+            }
+            if status.state == AccountsHashAuditState::Failed {
+                exit(1);
+            }
+            return;
+        }
+        ("thread-affinity", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let pinned_threads = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.thread_affinity().await })
+                .unwrap_or_else(|err| {
+                    println!("threadAffinity request failed: {}", err);
+                    exit(1);
+                });
+            for thread in pinned_threads {
+                println!(
+                    "{} {} {}: core {}",
+                    thread.role, thread.index, thread.thread_name, thread.core
+                );
+            }
+            return;
+        }
+        ("thread-pools", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let thread_pools = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.thread_pools().await })
+                .unwrap_or_else(|err| {
+                    println!("threadPools request failed: {}", err);
+                    exit(1);
+                });
+            for thread_pool in thread_pools {
+                println!(
+                    "{}: {} pools, {} threads, {} installs, busy {}us",
+                    thread_pool.name,
+                    thread_pool.num_pools,
+                    thread_pool.num_threads,
+                    thread_pool.installs,
+                    thread_pool.busy_us
+                );
+            }
+            return;
+        }
+        ("set-thread-pool-size", Some(subcommand_matches)) => {
+            let name = value_t_or_exit!(subcommand_matches, "name", String);
+            let num_threads = value_t_or_exit!(subcommand_matches, "num_threads", usize);
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .set_thread_pool_size(name, num_threads)
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    println!("setThreadPoolSize request failed: {}", err);
+                    exit(1);
+                });
+            return;
+        }
+        ("advance-slot", Some(subcommand_matches)) => {
+            let slots = value_t_or_exit!(subcommand_matches, "slots", u64);
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let slot = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.advance_slot(Some(slots)).await })
+                .unwrap_or_else(|err| {
+                    println!("advanceSlot request failed: {}", err);
+                    exit(1);
+                });
+            println!("{}", slot);
+            return;
+        }
+        ("warp-slot", Some(subcommand_matches)) => {
+            let slot = value_t_or_exit!(subcommand_matches, "slot", Slot);
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let slot = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.warp_slot(slot).await })
+                .unwrap_or_else(|err| {
+                    println!("warpSlot request failed: {}", err);
+                    exit(1);
+                });
+            println!("{}", slot);
+            return;
+        }
         ("wait-for-restart-window", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
             let identity = pubkey_of(subcommand_matches, "identity");
@@ -1910,7 +2829,19 @@ pub fn main() {
         }
     };
     let use_progress_bar = logfile.is_none();
+    solana_logger::set_log_format(value_t_or_exit!(matches, "log_format", LogFormat));
     let _logger_thread = redirect_stderr_to_file(logfile);
+    if let Some(log_filter) = admin_rpc_service::load_log_filter(&ledger_path) {
+        if std::env::var_os("RUST_LOG").is_some() {
+            info!(
+                "RUST_LOG is set, ignoring the log filter set at runtime: {}",
+                log_filter
+            );
+        } else {
+            info!("Restoring the log filter set at runtime: {}", log_filter);
+            solana_logger::setup_with(&log_filter);
+        }
+    }
 
     info!("{} {}", crate_name!(), solana_version::version!());
     info!("Starting validator with: {:#?}", std::env::args_os());
@@ -1921,6 +2852,18 @@ pub fn main() {
         enable_recycler_warming();
     }
 
+    // The config is set before any of the critical threads starts, they pin themselves to their
+    // cores when they do
+    if let Some(spec) = matches.value_of("thread_affinity") {
+        thread_affinity::set_thread_affinity_config(ThreadAffinityConfig::from_str(spec).unwrap());
+    }
+
+    // The sizes are set before any of the pools is built
+    for pool_size in matches.values_of("thread_pool_size").into_iter().flatten() {
+        let (name, num_threads) = thread_pools::parse_thread_pool_size(pool_size).unwrap();
+        thread_pools::set_thread_pool_size(name, num_threads).unwrap();
+    }
+
     solana_core::validator::report_target_features();
 
     let authorized_voter_keypairs = keypairs_of(&matches, "authorized_voter_keypairs")
@@ -1945,6 +2888,7 @@ pub fn main() {
             u64
         ),
         incremental_snapshot_fetch: matches.is_present("incremental_snapshots"),
+        snapshot_download_peers: value_t_or_exit!(matches, "snapshot_download_peers", usize),
     };
 
     let private_rpc = matches.is_present("private_rpc");
@@ -2011,6 +2955,7 @@ pub fn main() {
     let account_indexes = process_account_indexes(&matches);
 
     let restricted_repair_only_mode = matches.is_present("restricted_repair_only_mode");
+    let snapshot_only_mode = matches.is_present("snapshot_only_mode");
     let accounts_shrink_optimize_total_space =
         value_t_or_exit!(matches, "accounts_shrink_optimize_total_space", bool);
     let shrink_ratio = value_t_or_exit!(matches, "accounts_shrink_ratio", f64);
@@ -2117,12 +3062,21 @@ pub fn main() {
         index: Some(accounts_index_config),
         accounts_hash_cache_path: Some(ledger_path.clone()),
         filler_account_count,
+        file_size: value_t!(matches, "accounts_db_file_size", u64).ok(),
+        max_recycle_stores: value_t!(matches, "accounts_db_max_recycle_stores", usize).ok(),
         ..AccountsDbConfig::default()
     };
 
     if let Some(passes) = value_t!(matches, "accounts_hash_num_passes", usize).ok() {
         accounts_db_config.hash_calc_num_passes = Some(passes);
     }
+    accounts_db_config.append_vec_format = match matches.value_of("accounts_db_append_vec_format") {
+        Some("v2") => Some(AppendVecFormat::V2 {
+            compression_threshold: value_t!(matches, "accounts_db_compression_threshold", usize)
+                .ok(),
+        }),
+        _ => None,
+    };
     let accounts_db_config = Some(accounts_db_config);
 
     let accountsdb_repl_service_config = if matches.is_present("enable_accountsdb_repl") {
@@ -2145,6 +3099,29 @@ pub fn main() {
         None
     };
 
+    let rpc_reads_service_config =
+        value_t!(matches, "rpc_grpc_port", u16)
+            .ok()
+            .map(|rpc_grpc_port| RpcReadsServiceConfig {
+                worker_threads: value_t_or_exit!(matches, "rpc_grpc_threads", usize),
+                server_addr: SocketAddr::new(rpc_bind_address, rpc_grpc_port),
+            });
+
+    if let Some(libpaths) = matches.values_of("account_parser_plugin") {
+        for libpath in libpaths {
+            match unsafe { account_parser_plugin::load_account_parser_plugin(libpath) } {
+                Ok(program_id) => info!(
+                    "Loaded the account parser of program {} from {}",
+                    program_id, libpath
+                ),
+                Err(err) => {
+                    eprintln!("Failed to load account parser plugin {}: {}", libpath, err);
+                    exit(1);
+                }
+            }
+        }
+    }
+
     let accountsdb_plugin_config_files = if matches.is_present("accountsdb_plugin_config") {
         Some(
             values_t_or_exit!(matches, "accountsdb_plugin_config", String)
@@ -2171,13 +3148,21 @@ pub fn main() {
         rpc_config: JsonRpcConfig {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_cpi_and_log_storage: matches.is_present("enable_cpi_and_log_storage"),
+            max_stored_log_messages_bytes: value_t!(
+                matches,
+                "max_stored_log_messages_bytes",
+                usize
+            )
+            .ok(),
             enable_bigtable_ledger_storage: matches
                 .is_present("enable_rpc_bigtable_ledger_storage"),
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
+            bigtable_versioned_protobuf: matches.is_present("versioned_protobuf_storage"),
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
             }),
             minimal_api: matches.is_present("minimal_rpc_api"),
+            snapshot_only: snapshot_only_mode,
             obsolete_v1_7_api: matches.is_present("obsolete_v1_7_rpc_api"),
             max_multiple_accounts: Some(value_t_or_exit!(
                 matches,
@@ -2189,25 +3174,75 @@ pub fn main() {
                 "health_check_slot_distance",
                 u64
             ),
-            rpc_threads: value_t_or_exit!(matches, "rpc_threads", usize),
+            rpc_threads: if snapshot_only_mode && matches.occurrences_of("rpc_threads") == 0 {
+                DEFAULT_SNAPSHOT_ONLY_MODE_RPC_THREADS
+            } else {
+                value_t_or_exit!(matches, "rpc_threads", usize)
+            },
             rpc_niceness_adj: value_t_or_exit!(matches, "rpc_niceness_adj", i8),
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
+            slow_query_log: value_t!(matches, "rpc_slow_query_threshold_ms", u64)
+                .ok()
+                .map(|threshold_ms| {
+                    Arc::new(SlowQueryLog::new(
+                        Duration::from_millis(threshold_ms),
+                        DEFAULT_SLOW_QUERY_LOG_CAPACITY,
+                    ))
+                }),
+            response_compression_min_bytes: if matches.is_present("no_rpc_compression") {
+                None
+            } else {
+                Some(value_t_or_exit!(
+                    matches,
+                    "rpc_compression_min_bytes",
+                    usize
+                ))
+            },
+            transaction_history_search_threads: Some(value_t_or_exit!(
+                matches,
+                "rpc_transaction_history_search_threads",
+                usize
+            )),
+            max_transaction_history_searches_per_second: value_t!(
+                matches,
+                "rpc_max_transaction_history_searches_per_second",
+                u64
+            )
+            .ok(),
+            max_concurrent_accounts_scans: Some(value_t_or_exit!(
+                matches,
+                "rpc_max_concurrent_accounts_scans",
+                usize
+            )),
+            accounts_scan_queue_timeout: Some(Duration::from_secs(value_t_or_exit!(
+                matches,
+                "rpc_accounts_scan_queue_timeout",
+                u64
+            ))),
+            max_accounts_scan_duration: value_t!(matches, "rpc_max_accounts_scan_duration", u64)
+                .ok()
+                .map(Duration::from_secs),
+            max_accounts_scan_bytes: value_t!(matches, "rpc_max_accounts_scan_bytes", usize).ok(),
         },
         accountsdb_repl_service_config,
+        rpc_reads_service_config,
         accountsdb_plugin_config_files,
-        rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
-            (
-                SocketAddr::new(rpc_bind_address, rpc_port),
-                SocketAddr::new(rpc_bind_address, rpc_port + 1),
-                // If additional ports are added, +2 needs to be skipped to avoid a conflict with
-                // the websocket port (which is +2) in web3.js This odd port shifting is tracked at
-                // https://github.com/solana-labs/solana/issues/12250
-            )
-        }),
+        rpc_addrs: value_t!(matches, "rpc_port", u16)
+            .ok()
+            .or_else(|| snapshot_only_mode.then(|| DEFAULT_RPC_PORT))
+            .map(|rpc_port| {
+                (
+                    SocketAddr::new(rpc_bind_address, rpc_port),
+                    SocketAddr::new(rpc_bind_address, rpc_port + 1),
+                    // If additional ports are added, +2 needs to be skipped to avoid a conflict with
+                    // the websocket port (which is +2) in web3.js This odd port shifting is tracked at
+                    // https://github.com/solana-labs/solana/issues/12250
+                )
+            }),
         pubsub_config: PubSubConfig {
             enable_vote_subscription: matches.is_present("rpc_pubsub_enable_vote_subscription"),
             max_active_subscriptions: value_t_or_exit!(
@@ -2227,14 +3262,46 @@ pub fn main() {
             ),
             worker_threads: value_t_or_exit!(matches, "rpc_pubsub_worker_threads", usize),
             notification_threads: value_of(&matches, "rpc_pubsub_notification_threads"),
+            connection_queue_capacity_items: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_connection_queue_capacity_items",
+                usize
+            ),
+            connection_queue_capacity_bytes: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_connection_queue_capacity_bytes",
+                usize
+            ),
+            slow_consumer_policy: matches
+                .value_of("rpc_pubsub_slow_consumer_policy")
+                .map(SlowConsumerPolicy::from)
+                .unwrap(),
+            max_logs_subscription_addresses: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_logs_subscription_addresses",
+                usize
+            ),
+            max_logs_notification_messages: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_logs_notification_messages",
+                usize
+            ),
+            max_logs_notifications_per_second: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_logs_notifications_per_second",
+                usize
+            ),
         },
-        voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
+        voting_disabled: matches.is_present("no_voting")
+            || restricted_repair_only_mode
+            || snapshot_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
         trusted_validators,
         repair_validators,
         gossip_validators,
         frozen_accounts: values_t!(matches, "frozen_accounts", Pubkey).unwrap_or_default(),
         no_rocksdb_compaction,
+        blockstore_versioned_protobuf: matches.is_present("versioned_protobuf_storage"),
         rocksdb_compaction_interval,
         rocksdb_max_compaction_jitter,
         wal_recovery_mode,
@@ -2273,10 +3340,35 @@ pub fn main() {
         accounts_db_skip_shrink: matches.is_present("accounts_db_skip_shrink"),
         accounts_db_use_index_hash_calculation: matches.is_present("accounts_db_index_hashing"),
         tpu_coalesce_ms,
+        tpu_coalesce_max_packets: value_t_or_exit!(matches, "tpu_coalesce_max_packets", usize),
+        tpu_recv_buffer_size: value_t!(matches, "tpu_recv_buffer_size", usize).ok(),
+        tpu_recv_backend: value_t_or_exit!(matches, "tpu_recv_backend", RecvBackendKind),
+        tpu_autotune_recv_batch_size: matches.is_present("tpu_autotune_recv_batch_size"),
+        executor_cache_capacity: value_t_or_exit!(matches, "executor_cache_capacity", usize),
+        external_block_producer_config: matches
+            .value_of("external_block_producer_socket")
+            .map(|path| ExternalBlockProducerConfig::new(PathBuf::from(path))),
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         accounts_shrink_ratio,
         disable_epoch_boundary_optimization: matches
             .is_present("disable_epoch_boundary_optimization"),
+        verify_produced_blocks: value_t!(
+            matches,
+            "verify_produced_blocks",
+            ProducedBlockVerification
+        )
+        .ok(),
+        leader_slot_reports: Some(Arc::new(LeaderSlotReports::new(
+            DEFAULT_LEADER_SLOT_REPORTS_CAPACITY,
+        ))),
+        disable_duplicate_slot_dumping: matches.is_present("disable_duplicate_slot_dumping"),
+        dumped_slots: Some(Arc::new(DumpedSlots::new(DEFAULT_DUMPED_SLOTS_CAPACITY))),
+        accounts_hash_audit: Some(Arc::new(AccountsHashAudit::new(
+            value_t!(matches, "accounts_hash_audit_interval", u64)
+                .ok()
+                .map(|hours| Duration::from_secs(hours * 60 * 60)),
+        ))),
+        queue_conflicting_transactions: matches.is_present("queue_conflicting_transactions"),
         ..ValidatorConfig::default()
     };
 
@@ -2407,6 +3499,11 @@ pub fn main() {
             (Slot::MAX, Slot::MAX)
         };
 
+    if snapshot_only_mode && full_snapshot_archive_interval_slots == Slot::MAX {
+        eprintln!("--snapshot-only-mode requires snapshots to be enabled");
+        exit(1);
+    }
+
     validator_config.snapshot_config = Some(SnapshotConfig {
         full_snapshot_archive_interval_slots,
         incremental_snapshot_archive_interval_slots,
@@ -2456,6 +3553,8 @@ pub fn main() {
             exit(1);
         }
         validator_config.max_ledger_shreds = Some(limit_ledger_size);
+    } else if snapshot_only_mode {
+        validator_config.max_ledger_shreds = Some(DEFAULT_MIN_MAX_LEDGER_SHREDS);
     }
 
     if matches.is_present("halt_on_trusted_validators_accounts_hash_mismatch") {
@@ -2478,6 +3577,7 @@ pub fn main() {
 
     let start_progress = Arc::new(RwLock::new(ValidatorStartProgress::default()));
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_bank_forks = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -2487,7 +3587,15 @@ pub fn main() {
             start_progress: start_progress.clone(),
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
+            bank_forks: admin_service_bank_forks.clone(),
             tower_storage: validator_config.tower_storage.clone(),
+            manual_ticks: validator_config.poh_manual_ticks.clone(),
+            warp_slot_sender: Arc::new(Mutex::new(None)),
+            rpc_slow_query_log: validator_config.rpc_config.slow_query_log.clone(),
+            leader_slot_reports: validator_config.leader_slot_reports.clone(),
+            dumped_slots: validator_config.dumped_slots.clone(),
+            accounts_hash_audit: validator_config.accounts_hash_audit.clone(),
+            log_filter_path: Some(admin_rpc_service::log_filter_path(&ledger_path)),
         },
     );
 
@@ -2548,11 +3656,12 @@ pub fn main() {
         .map(ContactInfo::new_gossip_entry_point)
         .collect::<Vec<_>>();
 
-    let mut node = Node::new_with_external_ip(
+    let mut node = Node::new_with_external_ip_and_tpu_sockets(
         &identity_keypair.pubkey(),
         &gossip_addr,
         dynamic_port_range,
         bind_address,
+        value_t_or_exit!(matches, "tpu_sockets", usize),
     );
 
     if restricted_repair_only_mode {
@@ -2570,6 +3679,19 @@ pub fn main() {
         node.sockets.ip_echo = None;
     }
 
+    if snapshot_only_mode {
+        let any = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0)), 0);
+        // A snapshot-only node doesn't process transactions, only the TVU, repair, gossip and
+        // RPC ports are needed to follow the cluster and serve snapshots. Dropping the TPU
+        // sockets leaves the fetch stage without any receiver, so the TPU never gets a packet.
+        node.info.tpu = any;
+        node.info.tpu_forwards = any;
+        node.info.tpu_vote = any;
+        node.sockets.tpu.clear();
+        node.sockets.tpu_forwards.clear();
+        node.sockets.tpu_vote.clear();
+    }
+
     if !private_rpc {
         if let Some(public_rpc_addr) = public_rpc_addr {
             node.info.rpc = public_rpc_addr;
@@ -2580,10 +3702,59 @@ pub fn main() {
         }
     }
 
+    let check_ports = matches.is_present("check_ports");
+    if matches.is_present("request_nat_mappings") {
+        let gateway = value_t!(matches, "nat_gateway", std::net::Ipv4Addr)
+            .ok()
+            .or_else(solana_net_utils::nat_pmp::default_gateway)
+            .unwrap_or_else(|| {
+                eprintln!("Unable to determine the NAT gateway, set it with --nat-gateway");
+                exit(1);
+            });
+        let ports = bootstrap::public_nat_ports(&node, &validator_config, &socket_addr_space);
+        match bootstrap::request_nat_mappings(gateway, &ports) {
+            Some(lifetime) if !check_ports => {
+                // The mappings expire, renew them halfway through their lifetime
+                std::thread::Builder::new()
+                    .name("sol-nat-mappings".to_string())
+                    .spawn(move || {
+                        let mut lifetime = lifetime;
+                        loop {
+                            std::thread::sleep(lifetime / 2);
+                            lifetime = bootstrap::request_nat_mappings(gateway, &ports)
+                                .unwrap_or(lifetime);
+                        }
+                    })
+                    .unwrap();
+            }
+            Some(_) => (),
+            None => warn!("The NAT gateway {} did not map any port", gateway),
+        }
+    }
+    if check_ports {
+        let all_reachable = bootstrap::check_ports(
+            &node,
+            &cluster_entrypoints,
+            &validator_config,
+            &socket_addr_space,
+        );
+        exit(if all_reachable { 0 } else { 1 });
+    }
+
     solana_metrics::set_host_id(identity_keypair.pubkey().to_string());
     solana_metrics::set_panic_hook("validator");
+    if let Some(endpoint) = matches.value_of("transaction_tracing_endpoint") {
+        transaction_tracing::init(endpoint, "solana-validator").unwrap_or_else(|err| {
+            eprintln!("Failed to initialize transaction tracing: {}", err);
+            exit(1);
+        });
+    }
 
     solana_entry::entry::init_poh();
+    executable_cache::configure(ExecutableCacheConfig {
+        capacity: value_t_or_exit!(matches, "bpf_executable_cache_capacity", usize),
+        jit_compiler_threads: value_t_or_exit!(matches, "bpf_jit_compiler_threads", usize),
+    });
     snapshot_utils::remove_tmp_snapshot_archives(&snapshot_archives_dir);
 
     let identity_keypair = Arc::new(identity_keypair);
@@ -2630,6 +3801,7 @@ pub fn main() {
         socket_addr_space,
     );
     *admin_service_cluster_info.write().unwrap() = Some(validator.cluster_info.clone());
+    *admin_service_bank_forks.write().unwrap() = Some(validator.bank_forks.clone());
 
     if let Some(filename) = init_complete_file {
         File::create(filename).unwrap_or_else(|_| {
@@ -2639,6 +3811,7 @@ pub fn main() {
     }
     info!("Validator initialized");
     validator.join();
+    transaction_tracing::shutdown();
     info!("Validator exiting..");
 }
 
@@ -9,7 +9,11 @@ use {
         input_validators::{is_parsable, is_pubkey_or_keypair, is_url},
     },
     solana_cli_output::display::format_labeled_address,
-    solana_client::{client_error, rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
+    solana_client::{
+        client_error,
+        rpc_client::RpcClient,
+        rpc_response::{RpcDuplicateInstance, RpcVoteAccountStatus},
+    },
     solana_metrics::{datapoint_error, datapoint_info},
     solana_notifier::Notifier,
     solana_sdk::{
@@ -102,7 +106,7 @@ fn get_config() -> Config {
                 .takes_value(true)
                 .validator(is_pubkey_or_keypair)
                 .multiple(true)
-                .help("Validator identities to monitor for delinquency")
+                .help("Validator identities to monitor for delinquency and duplicate running instances")
         )
         .arg(
             Arg::with_name("minimum_validator_identity_balance")
@@ -178,13 +182,23 @@ fn get_config() -> Config {
     config
 }
 
-fn get_cluster_info(
-    config: &Config,
-    rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+type ClusterInfo = (
+    u64,
+    Hash,
+    RpcVoteAccountStatus,
+    HashMap<Pubkey, u64>,
+    Vec<RpcDuplicateInstance>,
+);
+
+fn get_cluster_info(config: &Config, rpc_client: &RpcClient) -> client_error::Result<ClusterInfo> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_latest_blockhash()?;
     let vote_accounts = rpc_client.get_vote_accounts()?;
+    // Nodes running older releases don't support getHealthDetail
+    let duplicate_instances = rpc_client
+        .get_health_detail()
+        .map(|health_detail| health_detail.duplicate_instances)
+        .unwrap_or_default();
 
     let mut validator_balances = HashMap::new();
     for validator_identity in &config.validator_identity_pubkeys {
@@ -199,6 +213,7 @@ fn get_cluster_info(
         recent_blockhash,
         vote_accounts,
         validator_balances,
+        duplicate_instances,
     ))
 }
 
@@ -218,7 +233,13 @@ fn main() -> Result<(), Box<dyn error::Error>> {
 
     loop {
         let failure = match get_cluster_info(&config, &rpc_client) {
-            Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+            Ok((
+                transaction_count,
+                recent_blockhash,
+                vote_accounts,
+                validator_balances,
+                duplicate_instances,
+            )) => {
                 info!("Current transaction count: {}", transaction_count);
                 info!("Recent blockhash: {}", recent_blockhash);
                 info!("Current validator count: {}", vote_accounts.current.len());
@@ -299,6 +320,19 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         validator_errors.push(format!("{} missing", formatted_validator_identity));
                     }
 
+                    if let Some(duplicate_instance) = duplicate_instances
+                        .iter()
+                        .find(|duplicate| duplicate.identity == validator_identity.to_string())
+                    {
+                        failures.push((
+                            "duplicate-instance",
+                            format!(
+                                "{} is running {} instances",
+                                formatted_validator_identity, duplicate_instance.num_instances
+                            ),
+                        ));
+                    }
+
                     if let Some(balance) = validator_balances.get(validator_identity) {
                         if *balance < config.minimum_validator_identity_balance {
                             failures.push((
@@ -293,10 +293,22 @@ export type ContactInfo = {
   gossip: string | null;
   /** TPU network address for the node (null if not available) */
   tpu: string | null;
+  /** TPU forwards network address for the node (null if not available) */
+  tpuForwards?: string | null;
+  /** TPU vote network address for the node (null if not available) */
+  tpuVote?: string | null;
+  /** Repair request network address for the node (null if not available) */
+  serveRepair?: string | null;
   /** JSON RPC network address for the node (null if not available) */
   rpc: string | null;
+  /** JSON RPC PubSub network address for the node (null if not available) */
+  pubsub?: string | null;
   /** Software version of the node (null if not available) */
   version: string | null;
+  /** Unique identifier of the node's feature set (null if not available) */
+  featureSet?: number | null;
+  /** Shred version the node has been configured to use (null if not available) */
+  shredVersion?: number | null;
 };
 
 /**
@@ -1299,8 +1311,14 @@ const ContactInfoResult = pick({
   pubkey: string(),
   gossip: nullable(string()),
   tpu: nullable(string()),
+  tpuForwards: optional(nullable(string())),
+  tpuVote: optional(nullable(string())),
+  serveRepair: optional(nullable(string())),
   rpc: nullable(string()),
+  pubsub: optional(nullable(string())),
   version: nullable(string()),
+  featureSet: optional(nullable(number())),
+  shredVersion: optional(nullable(number())),
 });
 
 const VoteAccountInfoResult = pick({